@@ -0,0 +1,69 @@
+// Engine Benchmarks
+//
+// Times the standard workloads on the interpreter and VM, and code generation
+// on the native backend, which does not run them; its times are printed apart.
+//
+// Usage:
+//   cargo bench --bench engine_bench
+//   cargo bench --bench engine_bench -- --save-baseline target/engine-baseline.txt
+//   cargo bench --bench engine_bench -- --baseline target/engine-baseline.txt
+
+use std::process::ExitCode;
+
+use glimmer_weave::bench::{BenchReport, BenchSuite};
+
+/// Allowed slowdown against a baseline before a pair counts as regressed
+const TOLERANCE: f64 = 0.10;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    let option = |flag: &str| {
+        args.iter()
+            .position(|arg| arg == flag)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    };
+
+    let report = BenchSuite::new().warmup(2).samples(20).run();
+    println!("{}", report);
+
+    if let Some(path) = option("--save-baseline") {
+        if let Err(e) = std::fs::write(&path, report.to_baseline()) {
+            eprintln!("Failed to write baseline '{}': {}", path, e);
+            return ExitCode::FAILURE;
+        }
+        println!("Saved baseline to {}", path);
+    }
+
+    if let Some(path) = option("--baseline") {
+        let baseline = match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| BenchReport::from_baseline(&text))
+        {
+            Ok(baseline) => baseline,
+            Err(e) => {
+                eprintln!("Failed to read baseline '{}': {}", path, e);
+                return ExitCode::FAILURE;
+            }
+        };
+
+        let regressions = report.compare(&baseline, TOLERANCE);
+        if regressions.is_empty() {
+            println!("No regressions against {}", path);
+        } else {
+            for regression in &regressions {
+                println!(
+                    "REGRESSION {} on {}: {:?} -> {:?} ({:.2}x)",
+                    regression.workload,
+                    regression.engine.name(),
+                    regression.baseline,
+                    regression.current,
+                    regression.ratio()
+                );
+            }
+            return ExitCode::FAILURE;
+        }
+    }
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,44 @@
+//! Running arbitrary source in the interpreter and the VM must never panic
+//!
+//! Both engines run under a deadline on a clock that ticks at every
+//! safepoint, so non-terminating programs end with a timeout.
+
+#![no_main]
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use glimmer_weave::bytecode_compiler::compile;
+use glimmer_weave::vm::VM;
+use glimmer_weave::{Evaluator, Lexer, Parser};
+use libfuzzer_sys::fuzz_target;
+
+const STEPS: u64 = 2_000;
+
+fn ticking_clock() -> impl Fn() -> u64 {
+    let ticks = Rc::new(Cell::new(0u64));
+    move || {
+        ticks.set(ticks.get() + 1);
+        ticks.get()
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let Ok(ast) = Parser::new(tokens).parse() else {
+        return;
+    };
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_clock(Box::new(ticking_clock()));
+    let _ = evaluator.eval_with_deadline(&ast, STEPS);
+
+    if let Ok(chunk) = compile(&ast) {
+        let mut vm = VM::new();
+        vm.set_clock(Box::new(ticking_clock()));
+        let _ = vm.execute_with_deadline(chunk, STEPS);
+    }
+});
@@ -0,0 +1,15 @@
+//! Lexing, parsing and analyzing arbitrary source must never panic
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+    let tokens = glimmer_weave::Lexer::new(source).tokenize_positioned();
+    if let Ok(ast) = glimmer_weave::Parser::new(tokens).parse() {
+        let _ = glimmer_weave::analyze(&ast);
+    }
+});
@@ -0,0 +1,21 @@
+#!/bin/bash
+# No_std size check: builds the size probe against glimmer_weave without std
+# and fails if its code and data grow past the limit in size-limit.txt
+
+set -e
+
+cd "$(dirname "$0")"
+
+cargo build --release --quiet
+library=target/release/libglimmer_weave_size_check.so
+
+# text + data; bss is the probe's own allocator arena
+read -r text data _ < <(size "$library" | tail -n 1)
+bytes=$((text + data))
+limit=$(cat size-limit.txt)
+
+echo "no_std diagnostic path: $bytes bytes (limit $limit)"
+if [ "$bytes" -gt "$limit" ]; then
+    echo "error: the no_std build grew past $limit bytes; shrink it, or raise size-limit.txt if the growth is intended"
+    exit 1
+fi
@@ -0,0 +1,96 @@
+//! Size probe for the no_std build: reports a diagnostic into a caller's
+//! buffer the way a kernel console would, so the linked library holds what
+//! error reporting costs and nothing else
+
+#![no_std]
+
+extern crate alloc;
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::cell::UnsafeCell;
+use core::fmt::{self, Write};
+use core::panic::PanicInfo;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use glimmer_weave::error_formatter::{ColorChoice, Diagnostic};
+use glimmer_weave::source_location::{SourceLocation, SourceSpan};
+
+/// Hands out a static arena front to back and never reuses it, as an
+/// early-boot kernel heap would
+struct BumpAllocator {
+    next: AtomicUsize,
+}
+
+const ARENA_SIZE: usize = 64 * 1024;
+
+struct Arena(UnsafeCell<[u8; ARENA_SIZE]>);
+
+// Disjoint ranges are handed out through `BumpAllocator::next`
+unsafe impl Sync for Arena {}
+
+static ARENA: Arena = Arena(UnsafeCell::new([0; ARENA_SIZE]));
+
+unsafe impl GlobalAlloc for BumpAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let base = ARENA.0.get() as usize;
+        let mut next = self.next.load(Ordering::Relaxed);
+        loop {
+            let start = (base + next).next_multiple_of(layout.align()) - base;
+            let end = start + layout.size();
+            if end > ARENA_SIZE {
+                return core::ptr::null_mut();
+            }
+            match self.next.compare_exchange_weak(next, end, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return (base + start) as *mut u8,
+                Err(current) => next = current,
+            }
+        }
+    }
+
+    unsafe fn dealloc(&self, _ptr: *mut u8, _layout: Layout) {}
+}
+
+#[global_allocator]
+static ALLOCATOR: BumpAllocator = BumpAllocator { next: AtomicUsize::new(0) };
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {}
+}
+
+/// Caller-owned bytes, filled from the front
+struct Buffer<'a> {
+    bytes: &'a mut [u8],
+    len: usize,
+}
+
+impl Write for Buffer<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.bytes.len() {
+            return Err(fmt::Error);
+        }
+        self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Write a diagnostic for `line`:`column` into `buffer` and return the number
+/// of bytes written, or 0 if it did not fit
+///
+/// # Safety
+///
+/// `buffer` must be valid for writes of `capacity` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn glimmer_report(buffer: *mut u8, capacity: usize, line: usize, column: usize, ansi: bool) -> usize {
+    let bytes = core::slice::from_raw_parts_mut(buffer, capacity);
+    let mut out = Buffer { bytes, len: 0 };
+    let color = if ansi { ColorChoice::Ansi } else { ColorChoice::Plain };
+    let diagnostic = Diagnostic::error("Use of moved value")
+        .with_primary_label(SourceSpan::point(SourceLocation::new(line, column)), "value used here");
+    match diagnostic.write_to(&mut out, color) {
+        Ok(()) => out.len,
+        Err(_) => 0,
+    }
+}
@@ -6,8 +6,10 @@
 //! capturing statements, expressions, and their relationships.
 
 use alloc::boxed::Box;
-use alloc::string::String;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use crate::bigint::BigInt;
+use crate::language_version::LanguageVersion;
 use crate::source_location::SourceSpan;
 
 /// Borrow mode for parameters and types
@@ -52,6 +54,17 @@ impl Lifetime {
     }
 }
 
+/// Resolved storage location of a variable reference
+///
+/// Filled in by [`crate::semantic::resolve_scopes`]. `depth` counts scopes
+/// outward from the innermost one at the point of use, and `slot` is the
+/// binding's position within that scope in definition order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScopeSlot {
+    pub depth: usize,
+    pub slot: usize,
+}
+
 /// Type annotation in the AST (syntactic representation)
 ///
 /// This is the syntactic form of types as they appear in source code.
@@ -73,6 +86,13 @@ pub enum TypeAnnotation {
     List(Box<TypeAnnotation>),
     /// Map type: `Map`
     Map,
+    /// Fixed-size array type: `array of Number length 16`
+    /// A list whose length is known statically, which native codegen keeps
+    /// inline on the stack
+    Array {
+        element: Box<TypeAnnotation>,
+        length: usize,
+    },
     /// Function type: `Function<(Number, Text) -> Truth>`
     Function {
         param_types: Vec<TypeAnnotation>,
@@ -104,6 +124,18 @@ pub struct Parameter {
     pub lifetime: Option<Lifetime>,
 }
 
+/// Aspects a form may list in its `deriving` clause
+pub const DERIVABLE_ASPECTS: [&str; 3] = ["Display", "Ordered", "Hashable"];
+
+/// Why a chant, form or grove is deprecated and what replaces it:
+/// `deprecated "draws past the edge" use draw_clipped`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deprecation {
+    pub message: String,
+    /// Name to use instead, e.g. `draw_clipped` or `Screen.print`
+    pub replacement: Option<String>,
+}
+
 /// Struct field definition
 #[derive(Debug, Clone, PartialEq)]
 pub struct StructField {
@@ -119,10 +151,96 @@ pub struct StructField {
 /// Examples:
 /// - `Red` - unit variant (fields is empty)
 /// - `Move(x: Number, y: Number)` - variant with data
+/// - `Error is 1` - unit variant with an explicit discriminant
 #[derive(Debug, Clone, PartialEq)]
 pub struct VariantCase {
     pub name: String,
     pub fields: Vec<Parameter>,  // Fields if this variant carries data
+    pub discriminant: Option<i64>,  // Explicit `is N` discriminant, if any
+}
+
+/// Discriminant of each case of a variant, in declaration order
+///
+/// Cases without an explicit `is N` take the previous case's discriminant
+/// plus one, starting from 0, so `variant Status with Ok, Error end` numbers
+/// its cases 0 and 1.
+pub fn variant_discriminants(cases: &[VariantCase]) -> Vec<i64> {
+    let mut next = 0;
+    cases
+        .iter()
+        .map(|case| {
+            let discriminant = case.discriminant.unwrap_or(next);
+            next = discriminant.wrapping_add(1);
+            discriminant
+        })
+        .collect()
+}
+
+/// Discriminant of the case named `name`, if the variant has one
+pub fn case_discriminant(cases: &[VariantCase], name: &str) -> Option<i64> {
+    let position = cases.iter().position(|case| case.name == name)?;
+    variant_discriminants(cases).get(position).copied()
+}
+
+/// Why a call's named arguments do not fit the parameters of its chant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgumentError {
+    /// No parameter has the name
+    Unknown(String),
+    /// The parameter was already given, positionally or by name
+    Duplicate(String),
+    /// The parameter was given no argument
+    Missing(String),
+    /// The parameter is variadic, and takes positional arguments only
+    Variadic(String),
+}
+
+impl core::fmt::Display for ArgumentError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ArgumentError::Unknown(name) => write!(f, "No parameter named '{}'", name),
+            ArgumentError::Duplicate(name) => write!(f, "Parameter '{}' is given more than once", name),
+            ArgumentError::Missing(name) => write!(f, "Missing argument for parameter '{}'", name),
+            ArgumentError::Variadic(name) => write!(f, "Variadic parameter '{}' cannot be passed by name", name),
+        }
+    }
+}
+
+/// Order a call's arguments to match `params`
+///
+/// `args` holds the positional arguments followed by the named ones, whose
+/// names are `arg_names`. Positional arguments fill parameters from the
+/// left, named ones fill the parameter of their name, and anything past the
+/// fixed parameters is left at the end for a variadic parameter (or for the
+/// arity check to refuse). Without named arguments, `args` is returned as
+/// is.
+pub fn order_arguments<T>(params: &[Parameter], mut args: Vec<T>, arg_names: &[String]) -> Result<Vec<T>, ArgumentError> {
+    if arg_names.is_empty() {
+        return Ok(args);
+    }
+    let named = args.split_off(args.len().saturating_sub(arg_names.len()));
+    let fixed = params.iter().take_while(|param| !param.is_variadic).count();
+    let extra = args.split_off(args.len().min(fixed));
+
+    let mut slots: Vec<Option<T>> = args.into_iter().map(Some).collect();
+    slots.resize_with(fixed, || None);
+    for (name, value) in arg_names.iter().zip(named) {
+        let index = params.iter().position(|param| param.name == *name).ok_or_else(|| ArgumentError::Unknown(name.clone()))?;
+        if params[index].is_variadic {
+            return Err(ArgumentError::Variadic(name.clone()));
+        }
+        match &mut slots[index] {
+            Some(_) => return Err(ArgumentError::Duplicate(name.clone())),
+            slot => *slot = Some(value),
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(slots.len() + extra.len());
+    for (param, slot) in params.iter().zip(slots) {
+        ordered.push(slot.ok_or_else(|| ArgumentError::Missing(param.name.clone()))?);
+    }
+    ordered.extend(extra);
+    Ok(ordered)
 }
 
 /// Trait method signature
@@ -252,6 +370,8 @@ pub enum AstNode {
     /// or with types: `chant factorial(n: Number) -> Number then ... end`
     /// or with generics: `chant identity<T>(x: T) -> T then ... end`
     /// or with lifetimes: `chant process<'a>(borrow 'a data as List<T>) -> borrow 'a T then ... end`
+    /// or hinted for inlining: `swift chant get_x(p) then p.x end`
+    /// or deprecated: `deprecated "too slow" use fast_sum` on the line before
     ChantDef {
         name: String,
         type_params: Vec<String>,  // Generic type parameters like ["T", "U"]
@@ -259,21 +379,27 @@ pub enum AstNode {
         params: Vec<Parameter>,
         return_type: Option<TypeAnnotation>,
         body: Vec<AstNode>,
+        swift: bool,  // `swift` hint: inline regardless of size
+        deprecated: Option<Deprecation>,
         span: SourceSpan,
     },
 
     /// Struct definition: `form Person with name as Text age as Number end`
     /// or with generics: `form Box<T> with value as T end`
+    /// or with derived aspects: `form Point with ... deriving Display, Ordered end`
     FormDef {
         name: String,
         type_params: Vec<String>,  // Generic type parameters like ["T", "U"]
         fields: Vec<StructField>,
+        deriving: Vec<String>,  // Aspects from a `deriving` clause, like ["Display"]
+        deprecated: Option<Deprecation>,
         span: SourceSpan,
     },
 
     /// Enum definition: `variant Color then Red, Green, Blue end`
     /// or with data: `variant Message then Quit, Move(x: Number, y: Number) end`
     /// or with generics: `variant Option<T> then Some(value: T), None end`
+    /// or with discriminants: `variant Status with Ok is 0, Error is 1 end`
     VariantDef {
         name: String,
         type_params: Vec<String>,  // Generic type parameters like ["T"]
@@ -306,7 +432,8 @@ pub enum AstNode {
         span: SourceSpan,
     },
 
-    /// Pattern matching: `match x with when 1 then ... end`
+    /// Pattern matching: `match x with when 1 then ... end`; the span is
+    /// that of the closing `end`, where a missing arm would go
     MatchStmt {
         value: Box<AstNode>,
         arms: Vec<MatchArm>,
@@ -320,6 +447,38 @@ pub enum AstNode {
         span: SourceSpan,
     },
 
+    /// Cleanup block: `defer ... end`
+    ///
+    /// Runs when the enclosing chant (or the program) finishes, including
+    /// when it unwinds with an error or is cancelled.
+    DeferStmt {
+        body: Vec<AstNode>,
+        span: SourceSpan,
+    },
+
+    /// Structured concurrency scope: `together ... end`
+    ///
+    /// Tasks spawned in the body belong to the scope. It ends once they
+    /// have all finished, or cancels those still pending when the body
+    /// exits early (see `scheduler`).
+    TogetherBlock {
+        body: Vec<AstNode>,
+        span: SourceSpan,
+    },
+
+    /// Store migration: `migrate store "config" from 1 to 2 with chant(old) then ... end`
+    ///
+    /// Registers a chant taking the namespace's value at version `from` to
+    /// its shape at `to` (see `storage`).
+    MigrateStore {
+        namespace: String,
+        from: u32,
+        to: u32,
+        param: String,
+        body: Vec<AstNode>,
+        span: SourceSpan,
+    },
+
     /// Capability request: `request VGA.write with justification "message"`
     RequestStmt {
         capability: Box<AstNode>,
@@ -334,6 +493,7 @@ pub enum AstNode {
         name: String,
         body: Vec<AstNode>,
         exports: Vec<String>,  // Items listed in 'offer'
+        deprecated: Option<Deprecation>,
         span: SourceSpan,
     },
 
@@ -353,6 +513,24 @@ pub enum AstNode {
         span: SourceSpan,
     },
 
+    /// Embedded test: `verify "adds up" then body end`
+    ///
+    /// Skipped when the script runs; the test runner runs each block on its
+    /// own (see `test_runner`).
+    VerifyBlock {
+        name: String,
+        body: Vec<AstNode>,
+        span: SourceSpan,
+    },
+
+    /// Language version pragma: `speaks "1.2"` (see `language_version`)
+    ///
+    /// Only the first statement of a script may be one.
+    Speaks {
+        version: LanguageVersion,
+        span: SourceSpan,
+    },
+
     // === Expressions ===
 
     /// Numeric literal: `42`, `3.14`
@@ -378,10 +556,31 @@ pub enum AstNode {
         span: SourceSpan,
     },
 
+    /// Big integer literal: `123n`
+    BigInt {
+        value: BigInt,
+        span: SourceSpan,
+    },
+
+    /// 64-bit integer literal: `42i`
+    Integer {
+        value: i64,
+        span: SourceSpan,
+    },
+
+    /// Number with a unit of measure: `10 ms`, `4 KiB`
+    Measure {
+        value: f64,
+        unit: String,
+        span: SourceSpan,
+    },
+
     /// Variable reference: `x`, `counter`
     Ident {
         name: String,
         span: SourceSpan,
+        /// Scope slot assigned by the resolution pass (None = unresolved)
+        slot: Option<ScopeSlot>,
     },
 
     /// Triumph value: `Triumph(42)` (successful Outcome)
@@ -413,6 +612,12 @@ pub enum AstNode {
         span: SourceSpan,
     },
 
+    /// Tuple literal: `(1, "a", true)`, or `(x,)` with one element
+    Tuple {
+        elements: Vec<AstNode>,
+        span: SourceSpan,
+    },
+
     /// Map literal: `{name: "Elara", age: 42}`
     Map {
         entries: Vec<(String, AstNode)>,
@@ -443,6 +648,13 @@ pub enum AstNode {
         span: SourceSpan,
     },
 
+    /// Measure read out as a plain Number in a unit: `delay in s`
+    InUnit {
+        value: Box<AstNode>,
+        unit: String,
+        span: SourceSpan,
+    },
+
     /// Borrow expression: `borrow x`, `borrow mut y`
     /// Creates a reference to a value
     BorrowExpr {
@@ -453,10 +665,14 @@ pub enum AstNode {
 
     /// Function call: `greet("Elara")`, `VGA.write("Hello")`
     /// or with type args: `identity<Number>(42)`
+    /// or with named arguments: `draw(x: 10, y: 20)`
     Call {
         callee: Box<AstNode>,
         type_args: Vec<TypeAnnotation>,  // Type arguments for generic function calls
         args: Vec<AstNode>,
+        /// Names of the trailing named arguments, in call order: for
+        /// `draw(1, y: 2)` the args are `[1, 2]` and the names `["y"]`
+        arg_names: Vec<String>,
         span: SourceSpan,
     },
 
@@ -559,10 +775,13 @@ pub enum UnaryOperator {
     Negate,  // -
 }
 
-/// Match arm: `when pattern then body`
+/// Match arm: `when pattern then body`, or `when pattern where guard then body`
 #[derive(Debug, Clone, PartialEq)]
 pub struct MatchArm {
     pub pattern: Pattern,
+    /// Condition checked after the pattern binds; a false guard moves on
+    /// to the next arm
+    pub guard: Option<AstNode>,
     pub body: Vec<AstNode>,
 }
 
@@ -581,6 +800,38 @@ pub enum Pattern {
         variant: String,  // "Triumph", "Mishap", "Present", "Absent"
         inner: Option<Box<Pattern>>,  // The inner pattern (if any)
     },
+    /// Tuple pattern: `when (x, 0) then ...`, matching tuples of exactly
+    /// as many elements
+    Tuple(Vec<Pattern>),
+    /// Or-pattern: `when 1 or 2 or 3 then ...`, matching when any
+    /// alternative does; every alternative binds the same names
+    Or(Vec<Pattern>),
+    /// Text affix pattern: `when starts "gw:" bound rest then ...`
+    /// or `when ends ".gw" then ...`
+    Affix {
+        side: AffixSide,
+        affix: String,
+        rest: Option<String>,  // Binds the text without the affix
+    },
+}
+
+/// End of the text an affix pattern matches at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AffixSide {
+    /// `starts "..."`
+    Prefix,
+    /// `ends "..."`
+    Suffix,
+}
+
+impl AffixSide {
+    /// `text` without `affix` at this end, or `None` if it isn't there
+    pub fn strip<'a>(self, text: &'a str, affix: &str) -> Option<&'a str> {
+        match self {
+            AffixSide::Prefix => text.strip_prefix(affix),
+            AffixSide::Suffix => text.strip_suffix(affix),
+        }
+    }
 }
 
 /// Error handler: `harmonize on ErrorType then ...`
@@ -630,7 +881,12 @@ impl AstNode {
                 | AstNode::YieldStmt { .. }
                 | AstNode::MatchStmt { .. }
                 | AstNode::AttemptStmt { .. }
+                | AstNode::DeferStmt { .. }
+                | AstNode::TogetherBlock { .. }
+                | AstNode::MigrateStore { .. }
                 | AstNode::RequestStmt { .. }
+                | AstNode::VerifyBlock { .. }
+                | AstNode::Speaks { .. }
                 | AstNode::ExprStmt { .. }
         )
     }
@@ -639,6 +895,310 @@ impl AstNode {
     pub fn is_expression(&self) -> bool {
         !self.is_statement()
     }
+
+    /// Name of this node's variant, e.g. `"Call"` or `"ChantDef"`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AstNode::BindStmt { .. } => "BindStmt",
+            AstNode::WeaveStmt { .. } => "WeaveStmt",
+            AstNode::SetStmt { .. } => "SetStmt",
+            AstNode::IfStmt { .. } => "IfStmt",
+            AstNode::ForStmt { .. } => "ForStmt",
+            AstNode::WhileStmt { .. } => "WhileStmt",
+            AstNode::ChantDef { .. } => "ChantDef",
+            AstNode::FormDef { .. } => "FormDef",
+            AstNode::VariantDef { .. } => "VariantDef",
+            AstNode::AspectDef { .. } => "AspectDef",
+            AstNode::EmbodyStmt { .. } => "EmbodyStmt",
+            AstNode::YieldStmt { .. } => "YieldStmt",
+            AstNode::MatchStmt { .. } => "MatchStmt",
+            AstNode::AttemptStmt { .. } => "AttemptStmt",
+            AstNode::DeferStmt { .. } => "DeferStmt",
+            AstNode::TogetherBlock { .. } => "TogetherBlock",
+            AstNode::MigrateStore { .. } => "MigrateStore",
+            AstNode::RequestStmt { .. } => "RequestStmt",
+            AstNode::ModuleDecl { .. } => "ModuleDecl",
+            AstNode::Import { .. } => "Import",
+            AstNode::Export { .. } => "Export",
+            AstNode::VerifyBlock { .. } => "VerifyBlock",
+            AstNode::Speaks { .. } => "Speaks",
+            AstNode::Number { .. } => "Number",
+            AstNode::Text { .. } => "Text",
+            AstNode::Truth { .. } => "Truth",
+            AstNode::Nothing { .. } => "Nothing",
+            AstNode::BigInt { .. } => "BigInt",
+            AstNode::Integer { .. } => "Integer",
+            AstNode::Measure { .. } => "Measure",
+            AstNode::Ident { .. } => "Ident",
+            AstNode::Triumph { .. } => "Triumph",
+            AstNode::Mishap { .. } => "Mishap",
+            AstNode::Present { .. } => "Present",
+            AstNode::Absent { .. } => "Absent",
+            AstNode::List { .. } => "List",
+            AstNode::Tuple { .. } => "Tuple",
+            AstNode::Map { .. } => "Map",
+            AstNode::StructLiteral { .. } => "StructLiteral",
+            AstNode::BinaryOp { .. } => "BinaryOp",
+            AstNode::UnaryOp { .. } => "UnaryOp",
+            AstNode::InUnit { .. } => "InUnit",
+            AstNode::BorrowExpr { .. } => "BorrowExpr",
+            AstNode::Call { .. } => "Call",
+            AstNode::FieldAccess { .. } => "FieldAccess",
+            AstNode::ModuleAccess { .. } => "ModuleAccess",
+            AstNode::IndexAccess { .. } => "IndexAccess",
+            AstNode::Range { .. } => "Range",
+            AstNode::Pipeline { .. } => "Pipeline",
+            AstNode::SeekExpr { .. } => "SeekExpr",
+            AstNode::ExprStmt { .. } => "ExprStmt",
+            AstNode::Block { .. } => "Block",
+            AstNode::Break { .. } => "Break",
+            AstNode::Continue { .. } => "Continue",
+            AstNode::Try { .. } => "Try",
+        }
+    }
+
+    /// Source span of this node
+    pub fn span(&self) -> &SourceSpan {
+        match self {
+            AstNode::BindStmt { span, .. }
+            | AstNode::WeaveStmt { span, .. }
+            | AstNode::SetStmt { span, .. }
+            | AstNode::IfStmt { span, .. }
+            | AstNode::ForStmt { span, .. }
+            | AstNode::WhileStmt { span, .. }
+            | AstNode::ChantDef { span, .. }
+            | AstNode::FormDef { span, .. }
+            | AstNode::VariantDef { span, .. }
+            | AstNode::AspectDef { span, .. }
+            | AstNode::EmbodyStmt { span, .. }
+            | AstNode::YieldStmt { span, .. }
+            | AstNode::MatchStmt { span, .. }
+            | AstNode::AttemptStmt { span, .. }
+            | AstNode::DeferStmt { span, .. }
+            | AstNode::TogetherBlock { span, .. }
+            | AstNode::MigrateStore { span, .. }
+            | AstNode::RequestStmt { span, .. }
+            | AstNode::ModuleDecl { span, .. }
+            | AstNode::Import { span, .. }
+            | AstNode::Export { span, .. }
+            | AstNode::VerifyBlock { span, .. }
+            | AstNode::Speaks { span, .. }
+            | AstNode::Number { span, .. }
+            | AstNode::Text { span, .. }
+            | AstNode::Truth { span, .. }
+            | AstNode::Nothing { span, .. }
+            | AstNode::BigInt { span, .. }
+            | AstNode::Integer { span, .. }
+            | AstNode::Measure { span, .. }
+            | AstNode::Ident { span, .. }
+            | AstNode::Triumph { span, .. }
+            | AstNode::Mishap { span, .. }
+            | AstNode::Present { span, .. }
+            | AstNode::Absent { span, .. }
+            | AstNode::List { span, .. }
+            | AstNode::Tuple { span, .. }
+            | AstNode::Map { span, .. }
+            | AstNode::StructLiteral { span, .. }
+            | AstNode::BinaryOp { span, .. }
+            | AstNode::UnaryOp { span, .. }
+            | AstNode::InUnit { span, .. }
+            | AstNode::BorrowExpr { span, .. }
+            | AstNode::Call { span, .. }
+            | AstNode::FieldAccess { span, .. }
+            | AstNode::ModuleAccess { span, .. }
+            | AstNode::IndexAccess { span, .. }
+            | AstNode::Range { span, .. }
+            | AstNode::Pipeline { span, .. }
+            | AstNode::SeekExpr { span, .. }
+            | AstNode::ExprStmt { span, .. }
+            | AstNode::Block { span, .. }
+            | AstNode::Break { span, .. }
+            | AstNode::Continue { span, .. }
+            | AstNode::Try { span, .. } => span,
+        }
+    }
+
+    /// References to every direct child node, in source order
+    pub fn children(&self) -> Vec<&AstNode> {
+        let mut children: Vec<&AstNode> = Vec::new();
+        match self {
+            AstNode::BindStmt { value, .. }
+            | AstNode::WeaveStmt { value, .. }
+            | AstNode::YieldStmt { value, .. }
+            | AstNode::Triumph { value, .. }
+            | AstNode::Mishap { value, .. }
+            | AstNode::Present { value, .. }
+            | AstNode::BorrowExpr { value, .. } => children.push(value),
+            AstNode::RequestStmt { capability: expr, .. }
+            | AstNode::ExprStmt { expr, .. }
+            | AstNode::Try { expr, .. }
+            | AstNode::UnaryOp { operand: expr, .. }
+            | AstNode::InUnit { value: expr, .. }
+            | AstNode::FieldAccess { object: expr, .. } => children.push(expr),
+            AstNode::SetStmt { target: left, value: right, .. }
+            | AstNode::BinaryOp { left, right, .. }
+            | AstNode::IndexAccess { object: left, index: right, .. }
+            | AstNode::Range { start: left, end: right, .. } => {
+                children.push(left);
+                children.push(right);
+            }
+            AstNode::IfStmt { condition, then_branch, else_branch, .. } => {
+                children.push(condition);
+                children.extend(then_branch.iter());
+                if let Some(else_branch) = else_branch {
+                    children.extend(else_branch.iter());
+                }
+            }
+            AstNode::ForStmt { iterable: head, body, .. } | AstNode::WhileStmt { condition: head, body, .. } => {
+                children.push(head);
+                children.extend(body.iter());
+            }
+            AstNode::Call { callee, args, .. } => {
+                children.push(callee);
+                children.extend(args.iter());
+            }
+            AstNode::MatchStmt { value, arms, .. } => {
+                children.push(value);
+                for arm in arms.iter() {
+                    children.extend(arm.guard.iter());
+                    children.extend(arm.body.iter());
+                }
+            }
+            AstNode::AttemptStmt { body, handlers, .. } => {
+                children.extend(body.iter());
+                for handler in handlers.iter() {
+                    children.extend(handler.body.iter());
+                }
+            }
+            AstNode::ChantDef { body: nodes, .. }
+            | AstNode::DeferStmt { body: nodes, .. }
+            | AstNode::TogetherBlock { body: nodes, .. }
+            | AstNode::MigrateStore { body: nodes, .. }
+            | AstNode::VerifyBlock { body: nodes, .. }
+            | AstNode::EmbodyStmt { methods: nodes, .. }
+            | AstNode::ModuleDecl { body: nodes, .. }
+            | AstNode::List { elements: nodes, .. }
+            | AstNode::Tuple { elements: nodes, .. }
+            | AstNode::Pipeline { stages: nodes, .. }
+            | AstNode::Block { statements: nodes, .. } => children.extend(nodes.iter()),
+            AstNode::Map { entries, .. } | AstNode::StructLiteral { fields: entries, .. } => {
+                children.extend(entries.iter().map(|(_, value)| value));
+            }
+            AstNode::SeekExpr { conditions, .. } => {
+                children.extend(conditions.iter().map(|condition| condition.value.as_ref()));
+            }
+            AstNode::FormDef { .. }
+            | AstNode::VariantDef { .. }
+            | AstNode::AspectDef { .. }
+            | AstNode::Import { .. }
+            | AstNode::Export { .. }
+            | AstNode::Speaks { .. }
+            | AstNode::Number { .. }
+            | AstNode::BigInt { .. }
+            | AstNode::Integer { .. }
+            | AstNode::Measure { .. }
+            | AstNode::Text { .. }
+            | AstNode::Truth { .. }
+            | AstNode::Nothing { .. }
+            | AstNode::Ident { .. }
+            | AstNode::Absent { .. }
+            | AstNode::ModuleAccess { .. }
+            | AstNode::Break { .. }
+            | AstNode::Continue { .. } => {}
+        }
+        children
+    }
+
+    /// Mutable references to every direct child node, in source order
+    pub fn children_mut(&mut self) -> Vec<&mut AstNode> {
+        let mut children: Vec<&mut AstNode> = Vec::new();
+        match self {
+            AstNode::BindStmt { value, .. }
+            | AstNode::WeaveStmt { value, .. }
+            | AstNode::YieldStmt { value, .. }
+            | AstNode::Triumph { value, .. }
+            | AstNode::Mishap { value, .. }
+            | AstNode::Present { value, .. }
+            | AstNode::BorrowExpr { value, .. } => children.push(value),
+            AstNode::RequestStmt { capability: expr, .. }
+            | AstNode::ExprStmt { expr, .. }
+            | AstNode::Try { expr, .. }
+            | AstNode::UnaryOp { operand: expr, .. }
+            | AstNode::InUnit { value: expr, .. }
+            | AstNode::FieldAccess { object: expr, .. } => children.push(expr),
+            AstNode::SetStmt { target: left, value: right, .. }
+            | AstNode::BinaryOp { left, right, .. }
+            | AstNode::IndexAccess { object: left, index: right, .. }
+            | AstNode::Range { start: left, end: right, .. } => {
+                children.push(left);
+                children.push(right);
+            }
+            AstNode::IfStmt { condition, then_branch, else_branch, .. } => {
+                children.push(condition);
+                children.extend(then_branch.iter_mut());
+                if let Some(else_branch) = else_branch {
+                    children.extend(else_branch.iter_mut());
+                }
+            }
+            AstNode::ForStmt { iterable: head, body, .. } | AstNode::WhileStmt { condition: head, body, .. } => {
+                children.push(head);
+                children.extend(body.iter_mut());
+            }
+            AstNode::Call { callee, args, .. } => {
+                children.push(callee);
+                children.extend(args.iter_mut());
+            }
+            AstNode::MatchStmt { value, arms, .. } => {
+                children.push(value);
+                for arm in arms.iter_mut() {
+                    children.extend(arm.guard.iter_mut());
+                    children.extend(arm.body.iter_mut());
+                }
+            }
+            AstNode::AttemptStmt { body, handlers, .. } => {
+                children.extend(body.iter_mut());
+                for handler in handlers.iter_mut() {
+                    children.extend(handler.body.iter_mut());
+                }
+            }
+            AstNode::ChantDef { body: nodes, .. }
+            | AstNode::DeferStmt { body: nodes, .. }
+            | AstNode::TogetherBlock { body: nodes, .. }
+            | AstNode::MigrateStore { body: nodes, .. }
+            | AstNode::VerifyBlock { body: nodes, .. }
+            | AstNode::EmbodyStmt { methods: nodes, .. }
+            | AstNode::ModuleDecl { body: nodes, .. }
+            | AstNode::List { elements: nodes, .. }
+            | AstNode::Tuple { elements: nodes, .. }
+            | AstNode::Pipeline { stages: nodes, .. }
+            | AstNode::Block { statements: nodes, .. } => children.extend(nodes.iter_mut()),
+            AstNode::Map { entries, .. } | AstNode::StructLiteral { fields: entries, .. } => {
+                children.extend(entries.iter_mut().map(|(_, value)| value));
+            }
+            AstNode::SeekExpr { conditions, .. } => {
+                children.extend(conditions.iter_mut().map(|condition| condition.value.as_mut()));
+            }
+            AstNode::FormDef { .. }
+            | AstNode::VariantDef { .. }
+            | AstNode::AspectDef { .. }
+            | AstNode::Import { .. }
+            | AstNode::Export { .. }
+            | AstNode::Speaks { .. }
+            | AstNode::Number { .. }
+            | AstNode::BigInt { .. }
+            | AstNode::Integer { .. }
+            | AstNode::Measure { .. }
+            | AstNode::Text { .. }
+            | AstNode::Truth { .. }
+            | AstNode::Nothing { .. }
+            | AstNode::Ident { .. }
+            | AstNode::Absent { .. }
+            | AstNode::ModuleAccess { .. }
+            | AstNode::Break { .. }
+            | AstNode::Continue { .. } => {}
+        }
+        children
+    }
 }
 
 impl BinaryOperator {
@@ -0,0 +1,270 @@
+//! # AST Queries
+//!
+//! Structural queries over parsed programs, so tools and lints can say what
+//! they look for instead of walking the tree by hand. A [`Query`] matches
+//! nodes by kind, by name and by where they sit: inside, directly under or
+//! around other nodes that match queries of their own.
+//!
+//! ```
+//! use glimmer_weave::ast_query::{find_calls, Query};
+//! use glimmer_weave::{Lexer, Parser};
+//!
+//! let source = "chant paint() then\n    VGA.write(\"hi\")\nend\nVGA.write(\"top\")\n";
+//! let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap();
+//!
+//! assert_eq!(find_calls(&ast, "VGA.write").len(), 2);
+//!
+//! let in_chants = Query::call_to("VGA.write").inside(Query::kind("ChantDef"));
+//! let found = in_chants.find(&ast);
+//! assert_eq!(found.len(), 1);
+//! assert_eq!(found[0].enclosing_name("ChantDef").as_deref(), Some("paint"));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::ast::AstNode;
+use crate::capability::capability_name;
+use crate::source_location::SourceSpan;
+
+/// A structural predicate over AST nodes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Query {
+    /// Every node
+    Any,
+    /// Nodes of one kind, as named by [`AstNode::kind`]
+    Kind(String),
+    /// Nodes whose [`node_name`] is this name
+    Named(String),
+    /// Calls to a dotted callee path, e.g. `VGA.write`
+    CallTo(String),
+    /// Nodes with an ancestor that matches
+    Inside(Box<Query>),
+    /// Nodes whose parent matches
+    ChildOf(Box<Query>),
+    /// Nodes with a descendant that matches
+    Containing(Box<Query>),
+    /// Nodes that match both
+    And(Box<Query>, Box<Query>),
+    /// Nodes that match either
+    Or(Box<Query>, Box<Query>),
+    /// Nodes that do not match
+    Not(Box<Query>),
+}
+
+impl Query {
+    /// Match every node
+    pub fn any() -> Self {
+        Query::Any
+    }
+
+    /// Match nodes of `kind` (`"Call"`, `"ChantDef"`, ...)
+    pub fn kind(kind: &str) -> Self {
+        Query::Kind(kind.to_string())
+    }
+
+    /// Match nodes named `name`: definitions, bindings, identifiers and
+    /// paths
+    pub fn named(name: &str) -> Self {
+        Query::Named(name.to_string())
+    }
+
+    /// Match calls whose callee is the dotted `path`
+    pub fn call_to(path: &str) -> Self {
+        Query::CallTo(path.to_string())
+    }
+
+    /// Narrow to nodes nested, at any depth, in a node matching `outer`
+    pub fn inside(self, outer: Query) -> Self {
+        self.and(Query::Inside(Box::new(outer)))
+    }
+
+    /// Narrow to nodes directly under a node matching `parent`
+    pub fn child_of(self, parent: Query) -> Self {
+        self.and(Query::ChildOf(Box::new(parent)))
+    }
+
+    /// Narrow to nodes with a node matching `inner` nested in them
+    pub fn containing(self, inner: Query) -> Self {
+        self.and(Query::Containing(Box::new(inner)))
+    }
+
+    /// Match nodes that match both queries
+    pub fn and(self, other: Query) -> Self {
+        Query::And(Box::new(self), Box::new(other))
+    }
+
+    /// Match nodes that match either query
+    pub fn or(self, other: Query) -> Self {
+        Query::Or(Box::new(self), Box::new(other))
+    }
+
+    /// Every node in `nodes` that matches, in source order
+    pub fn find<'a>(&self, nodes: &'a [AstNode]) -> Vec<QueryMatch<'a>> {
+        let mut found = Vec::new();
+        let mut ancestors = Vec::new();
+        for node in nodes {
+            self.collect(node, &mut ancestors, &mut found, false);
+        }
+        found
+    }
+
+    /// Whether any node in `nodes` matches
+    pub fn exists(&self, nodes: &[AstNode]) -> bool {
+        let mut ancestors = Vec::new();
+        let mut found = Vec::new();
+        nodes.iter().any(|node| {
+            self.collect(node, &mut ancestors, &mut found, true);
+            !found.is_empty()
+        })
+    }
+
+    /// Whether `node`, under `ancestors` (outermost first), matches
+    pub fn matches(&self, node: &AstNode, ancestors: &[&AstNode]) -> bool {
+        match self {
+            Query::Any => true,
+            Query::Kind(kind) => node.kind() == kind,
+            Query::Named(name) => node_name(node).as_deref() == Some(name.as_str()),
+            Query::CallTo(path) => match node {
+                AstNode::Call { callee, .. } => callee_path(callee).as_deref() == Some(path.as_str()),
+                _ => false,
+            },
+            Query::Inside(outer) => (0..ancestors.len()).any(|i| outer.matches(ancestors[i], &ancestors[..i])),
+            Query::ChildOf(parent) => match ancestors.split_last() {
+                Some((parent_node, above)) => parent.matches(parent_node, above),
+                None => false,
+            },
+            Query::Containing(inner) => {
+                let mut path = ancestors.to_vec();
+                path.push(node);
+                let mut found = Vec::new();
+                for child in node.children() {
+                    inner.collect(child, &mut path, &mut found, true);
+                    if !found.is_empty() {
+                        return true;
+                    }
+                }
+                false
+            }
+            Query::And(a, b) => a.matches(node, ancestors) && b.matches(node, ancestors),
+            Query::Or(a, b) => a.matches(node, ancestors) || b.matches(node, ancestors),
+            Query::Not(query) => !query.matches(node, ancestors),
+        }
+    }
+
+    /// Push matches in `node` and below; with `first`, stop at the first
+    fn collect<'a>(&self, node: &'a AstNode, ancestors: &mut Vec<&'a AstNode>, found: &mut Vec<QueryMatch<'a>>, first: bool) {
+        if self.matches(node, ancestors) {
+            found.push(QueryMatch { node, ancestors: ancestors.clone() });
+            if first {
+                return;
+            }
+        }
+        ancestors.push(node);
+        for child in node.children() {
+            self.collect(child, ancestors, found, first);
+            if first && !found.is_empty() {
+                break;
+            }
+        }
+        ancestors.pop();
+    }
+}
+
+impl core::ops::Not for Query {
+    type Output = Query;
+
+    fn not(self) -> Query {
+        Query::Not(Box::new(self))
+    }
+}
+
+/// A node a query matched, with the nodes around it
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryMatch<'a> {
+    pub node: &'a AstNode,
+    /// Nodes the match is nested in, outermost first
+    pub ancestors: Vec<&'a AstNode>,
+}
+
+impl<'a> QueryMatch<'a> {
+    /// Where the matched node is
+    pub fn span(&self) -> &'a SourceSpan {
+        self.node.span()
+    }
+
+    /// Nearest ancestor of `kind`
+    pub fn enclosing(&self, kind: &str) -> Option<&'a AstNode> {
+        self.ancestors.iter().rev().copied().find(|ancestor| ancestor.kind() == kind)
+    }
+
+    /// Name of the nearest ancestor of `kind`, e.g. the chant a call is in
+    pub fn enclosing_name(&self, kind: &str) -> Option<String> {
+        self.enclosing(kind).and_then(node_name)
+    }
+}
+
+/// Name a node goes by: what a definition or binding names, an
+/// identifier, a dotted path, the callee of a call or the capability of a
+/// request
+pub fn node_name(node: &AstNode) -> Option<String> {
+    match node {
+        AstNode::BindStmt { name, .. }
+        | AstNode::WeaveStmt { name, .. }
+        | AstNode::ChantDef { name, .. }
+        | AstNode::FormDef { name, .. }
+        | AstNode::VariantDef { name, .. }
+        | AstNode::AspectDef { name, .. }
+        | AstNode::ModuleDecl { name, .. }
+        | AstNode::VerifyBlock { name, .. }
+        | AstNode::ForStmt { variable: name, .. }
+        | AstNode::EmbodyStmt { aspect_name: name, .. }
+        | AstNode::Import { module_name: name, .. }
+        | AstNode::StructLiteral { struct_name: name, .. } => Some(name.clone()),
+        AstNode::Ident { .. } | AstNode::FieldAccess { .. } | AstNode::ModuleAccess { .. } => callee_path(node),
+        AstNode::Call { callee, .. } => callee_path(callee),
+        AstNode::RequestStmt { capability, .. } => Some(capability_name(capability)),
+        _ => None,
+    }
+}
+
+/// Dotted path of an identifier or field chain (`VGA.write`)
+fn callee_path(node: &AstNode) -> Option<String> {
+    match node {
+        AstNode::Ident { name, .. } => Some(name.clone()),
+        AstNode::FieldAccess { object, field, .. } => Some(format!("{}.{}", callee_path(object)?, field)),
+        AstNode::ModuleAccess { module, member, .. } => Some(format!("{}.{}", module, member)),
+        _ => None,
+    }
+}
+
+/// Spans of every call to the dotted `path` in `nodes`, in source order
+pub fn find_calls(nodes: &[AstNode], path: &str) -> Vec<SourceSpan> {
+    Query::call_to(path).find(nodes).iter().map(|found| found.span().clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Vec<AstNode> {
+        Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_nesting_predicates() {
+        let ast = parse("chant outer() then\n    should x then\n        log(1)\n    end\n    log(2)\nend\nlog(3)\n");
+        let under_if = Query::call_to("log").inside(Query::kind("IfStmt"));
+        assert_eq!(under_if.find(&ast).len(), 1);
+        let direct = Query::kind("ExprStmt").child_of(Query::named("outer"));
+        assert_eq!(direct.find(&ast).len(), 1);
+        let chants_with_ifs = Query::kind("ChantDef").containing(Query::kind("IfStmt"));
+        assert!(chants_with_ifs.exists(&ast));
+        let outside = Query::call_to("log").and(!Query::any().inside(Query::kind("ChantDef")));
+        assert_eq!(outside.find(&ast).len(), 1);
+    }
+}
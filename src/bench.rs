@@ -0,0 +1,558 @@
+//! # Engine Benchmarks
+//!
+//! Times representative Glimmer-Weave workloads against each execution
+//! engine so performance regressions in any of them show up side by side.
+//!
+//! Each workload is parsed once. Per engine, the timed section is:
+//! - **Interpreter**: evaluating the AST with a fresh [`Evaluator`]
+//! - **VM**: executing pre-compiled bytecode on a fresh [`VM`]
+//! - **Native codegen**: generating x86-64 assembly (the output is not
+//!   assembled or run, since that needs an external toolchain)
+//!
+//! Only the interpreter and VM run the workloads, so reports list the native
+//! code generation times apart from them rather than side by side.
+//!
+//! The standard workloads stick to loops, arithmetic and local bindings, the
+//! programs every engine handles: the VM does not run chants, text or forms
+//! yet, and no engine runs `seek` queries. Custom workloads an engine cannot
+//! handle are reported as unsupported rather than failing the whole run.
+//!
+//! ```no_run
+//! use glimmer_weave::bench::BenchSuite;
+//!
+//! let report = BenchSuite::new().samples(5).run();
+//! println!("{}", report);
+//! ```
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::ast::AstNode;
+use crate::bytecode_compiler;
+use crate::codegen::compile_to_asm;
+use crate::eval::Evaluator;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::vm::VM;
+
+/// A named Glimmer-Weave program used as a benchmark
+#[derive(Debug, Clone, PartialEq)]
+pub struct Workload {
+    pub name: String,
+    pub source: String,
+}
+
+impl Workload {
+    /// Create a workload from a name and program source
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Self {
+        Workload {
+            name: name.into(),
+            source: source.into(),
+        }
+    }
+}
+
+/// The standard workload set, which every engine can handle
+pub fn standard_workloads() -> Vec<Workload> {
+    vec![
+        Workload::new("fib", FIB),
+        Workload::new("collatz", COLLATZ),
+        Workload::new("primes", PRIMES),
+    ]
+}
+
+const FIB: &str = r#"
+weave a as 0
+weave round as 0
+whilst round is not 200 then
+    weave i as 0
+    weave b as 1
+    set a to 0
+    whilst i is not 60 then
+        bind next to a + b
+        set a to b
+        set b to next
+        set i to i + 1
+    end
+    set round to round + 1
+end
+a
+"#;
+
+const COLLATZ: &str = r#"
+weave total as 0
+weave start as 1
+whilst start is not 100 then
+    weave n as start
+    whilst n is not 1 then
+        should n % 2 is 0 then
+            set n to n / 2
+        otherwise
+            set n to n * 3 + 1
+        end
+        set total to total + 1
+    end
+    set start to start + 1
+end
+total
+"#;
+
+const PRIMES: &str = r#"
+weave count as 0
+weave n as 2
+whilst n is not 2000 then
+    weave d as 2
+    weave prime as 1
+    whilst prime is 1 and d * d <= n then
+        should n % d is 0 then
+            set prime to 0
+        end
+        set d to d + 1
+    end
+    should prime is 1 then
+        set count to count + 1
+    end
+    set n to n + 1
+end
+count
+"#;
+
+/// An execution engine that workloads can be timed against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Engine {
+    /// Tree-walking interpreter
+    Interpreter,
+    /// Bytecode compiler and register VM
+    Vm,
+    /// x86-64 code generator; only generation is timed, nothing is run
+    NativeCodegen,
+}
+
+impl Engine {
+    /// All engines, in report order
+    pub const ALL: [Engine; 3] = [Engine::Interpreter, Engine::Vm, Engine::NativeCodegen];
+
+    /// Short name used in reports and baselines
+    pub fn name(&self) -> &'static str {
+        match self {
+            Engine::Interpreter => "interpreter",
+            Engine::Vm => "vm",
+            Engine::NativeCodegen => "native_codegen",
+        }
+    }
+
+    /// Whether the timed section runs the workload, so its times compare
+    /// with the other engines that do
+    pub fn runs_workload(&self) -> bool {
+        !matches!(self, Engine::NativeCodegen)
+    }
+
+    fn from_name(name: &str) -> Option<Engine> {
+        Engine::ALL.into_iter().find(|engine| engine.name() == name)
+    }
+}
+
+/// Summary statistics over the timed samples of one workload on one engine
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timing {
+    pub samples: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+impl Timing {
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let total: Duration = samples.iter().sum();
+        Timing {
+            samples: samples.len(),
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            mean: total / samples.len() as u32,
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Result of timing one workload on one engine
+#[derive(Debug, Clone, PartialEq)]
+pub enum Measurement {
+    /// The workload ran; timings are available
+    Timed(Timing),
+    /// The engine could not parse, compile or run the workload
+    Unsupported(String),
+}
+
+/// One row of a [`BenchReport`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub workload: String,
+    pub engine: Engine,
+    pub measurement: Measurement,
+}
+
+impl BenchResult {
+    /// Median time, if the workload ran
+    pub fn median(&self) -> Option<Duration> {
+        match &self.measurement {
+            Measurement::Timed(timing) => Some(timing.median),
+            Measurement::Unsupported(_) => None,
+        }
+    }
+}
+
+/// A workload/engine pair whose median time grew beyond the allowed tolerance
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    pub workload: String,
+    pub engine: Engine,
+    pub baseline: Duration,
+    pub current: Duration,
+}
+
+impl Regression {
+    /// How many times slower the current run is than the baseline
+    pub fn ratio(&self) -> f64 {
+        self.current.as_secs_f64() / self.baseline.as_secs_f64()
+    }
+}
+
+/// Timings for every workload/engine pair of a suite run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BenchReport {
+    pub results: Vec<BenchResult>,
+}
+
+impl BenchReport {
+    /// Look up the result for a workload on an engine
+    pub fn get(&self, workload: &str, engine: Engine) -> Option<&BenchResult> {
+        self.results
+            .iter()
+            .find(|result| result.workload == workload && result.engine == engine)
+    }
+
+    /// Compare median times against a baseline report
+    ///
+    /// A pair regresses when its median exceeds the baseline median by more
+    /// than `tolerance` (0.10 allows a 10% slowdown). Pairs missing from
+    /// either report, or unsupported in either, are skipped.
+    pub fn compare(&self, baseline: &BenchReport, tolerance: f64) -> Vec<Regression> {
+        self.results
+            .iter()
+            .filter_map(|result| {
+                let current = result.median()?;
+                let base = baseline.get(&result.workload, result.engine)?.median()?;
+                if current.as_secs_f64() > base.as_secs_f64() * (1.0 + tolerance) {
+                    Some(Regression {
+                        workload: result.workload.clone(),
+                        engine: result.engine,
+                        baseline: base,
+                        current,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Serialize median times as `workload engine nanoseconds` lines
+    ///
+    /// Unsupported pairs are omitted. Read back with [`BenchReport::from_baseline`].
+    pub fn to_baseline(&self) -> String {
+        let mut out = String::new();
+        for result in &self.results {
+            if let Some(median) = result.median() {
+                out.push_str(&format!("{} {} {}\n", result.workload, result.engine.name(), median.as_nanos()));
+            }
+        }
+        out
+    }
+
+    /// Parse a baseline written by [`BenchReport::to_baseline`]
+    ///
+    /// Each pair is restored as a single-sample timing of its median.
+    pub fn from_baseline(text: &str) -> Result<BenchReport, String> {
+        let mut results = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let (workload, engine, nanos) = match parts.as_slice() {
+                [workload, engine, nanos] => (workload, engine, nanos),
+                _ => return Err(format!("line {}: expected `workload engine nanoseconds`", number + 1)),
+            };
+            let engine = Engine::from_name(engine)
+                .ok_or_else(|| format!("line {}: unknown engine '{}'", number + 1, engine))?;
+            let nanos: u64 = nanos
+                .parse()
+                .map_err(|_| format!("line {}: invalid duration '{}'", number + 1, nanos))?;
+            results.push(BenchResult {
+                workload: workload.to_string(),
+                engine,
+                measurement: Measurement::Timed(Timing::from_samples(vec![Duration::from_nanos(nanos)])),
+            });
+        }
+        Ok(BenchReport { results })
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (runs, codegen): (Vec<&BenchResult>, Vec<&BenchResult>) =
+            self.results.iter().partition(|result| result.engine.runs_workload());
+        write_rows(f, &runs)?;
+        if !codegen.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "code generation only; the generated assembly is not run, so these times")?;
+            writeln!(f, "do not compare with the runs above:")?;
+            write_rows(f, &codegen)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_rows(f: &mut fmt::Formatter<'_>, results: &[&BenchResult]) -> fmt::Result {
+    writeln!(f, "{:<18} {:<14} {:>12} {:>12} {:>12}", "workload", "engine", "min", "median", "max")?;
+    for result in results {
+        match &result.measurement {
+            Measurement::Timed(timing) => writeln!(
+                f,
+                "{:<18} {:<14} {:>12} {:>12} {:>12}",
+                result.workload,
+                result.engine.name(),
+                format!("{:.1?}", timing.min),
+                format!("{:.1?}", timing.median),
+                format!("{:.1?}", timing.max),
+            )?,
+            Measurement::Unsupported(reason) => {
+                let reason = reason.lines().next().unwrap_or_default();
+                let reason: String = reason.chars().take(60).collect();
+                writeln!(f, "{:<18} {:<14} unsupported: {}", result.workload, result.engine.name(), reason)?
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Builder for a benchmark run over a set of workloads and engines
+#[derive(Debug, Clone)]
+pub struct BenchSuite {
+    workloads: Vec<Workload>,
+    engines: Vec<Engine>,
+    warmup: usize,
+    samples: usize,
+}
+
+impl Default for BenchSuite {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BenchSuite {
+    /// Standard workloads on all engines, one warmup run and ten samples
+    pub fn new() -> Self {
+        BenchSuite {
+            workloads: standard_workloads(),
+            engines: Engine::ALL.to_vec(),
+            warmup: 1,
+            samples: 10,
+        }
+    }
+
+    /// Replace the workloads to run
+    pub fn workloads(mut self, workloads: Vec<Workload>) -> Self {
+        self.workloads = workloads;
+        self
+    }
+
+    /// Restrict the engines to time
+    pub fn engines(mut self, engines: &[Engine]) -> Self {
+        self.engines = engines.to_vec();
+        self
+    }
+
+    /// Untimed runs before sampling starts
+    pub fn warmup(mut self, warmup: usize) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    /// Timed runs per workload/engine pair (at least one)
+    pub fn samples(mut self, samples: usize) -> Self {
+        self.samples = samples.max(1);
+        self
+    }
+
+    /// Time every workload on every engine
+    pub fn run(&self) -> BenchReport {
+        let mut results = Vec::new();
+        for workload in &self.workloads {
+            let ast = Parser::new(Lexer::new(&workload.source).tokenize_positioned()).parse();
+            for &engine in &self.engines {
+                let measurement = match &ast {
+                    Ok(ast) => self.measure(engine, ast),
+                    Err(e) => Measurement::Unsupported(format!("parse error: {}", e.message)),
+                };
+                results.push(BenchResult {
+                    workload: workload.name.clone(),
+                    engine,
+                    measurement,
+                });
+            }
+        }
+        BenchReport { results }
+    }
+
+    fn measure(&self, engine: Engine, ast: &[AstNode]) -> Measurement {
+        let mut run: Box<dyn FnMut() -> Result<(), String>> = match engine {
+            Engine::Interpreter => Box::new(|| {
+                Evaluator::new().eval(ast).map(drop).map_err(|e| format!("{:?}", e))
+            }),
+            Engine::Vm => {
+                let chunk = match bytecode_compiler::compile(ast) {
+                    Ok(chunk) => chunk,
+                    Err(e) => return Measurement::Unsupported(format!("{:?}", e)),
+                };
+                Box::new(move || VM::new().execute(chunk.clone()).map(drop).map_err(|e| format!("{:?}", e)))
+            }
+            Engine::NativeCodegen => Box::new(|| compile_to_asm(ast).map(drop)),
+        };
+
+        for _ in 0..self.warmup {
+            if let Err(e) = run() {
+                return Measurement::Unsupported(e);
+            }
+        }
+
+        let mut samples = Vec::with_capacity(self.samples);
+        for _ in 0..self.samples {
+            let start = Instant::now();
+            if let Err(e) = run() {
+                return Measurement::Unsupported(e);
+            }
+            samples.push(start.elapsed());
+        }
+        Measurement::Timed(Timing::from_samples(samples))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timed(workload: &str, engine: Engine, millis: u64) -> BenchResult {
+        BenchResult {
+            workload: workload.to_string(),
+            engine,
+            measurement: Measurement::Timed(Timing::from_samples(vec![Duration::from_millis(millis)])),
+        }
+    }
+
+    #[test]
+    fn test_standard_workloads_run_on_every_engine() {
+        let report = BenchSuite::new().warmup(0).samples(1).run();
+
+        assert_eq!(report.results.len(), 9);
+        for result in &report.results {
+            assert!(
+                result.median().is_some(),
+                "{} failed on {}: {:?}",
+                result.workload,
+                result.engine.name(),
+                result.measurement
+            );
+        }
+    }
+
+    #[test]
+    fn test_unsupported_workloads_are_reported() {
+        let report = BenchSuite::new()
+            .workloads(vec![
+                Workload::new("seek_query", r#"seek where essence is "Scroll""#),
+                Workload::new("broken", "bind to"),
+            ])
+            .warmup(0)
+            .samples(1)
+            .run();
+
+        assert_eq!(report.results.len(), 6);
+        let seek = report.get("seek_query", Engine::Interpreter).unwrap();
+        assert!(matches!(seek.measurement, Measurement::Unsupported(_)));
+        let broken = report.get("broken", Engine::Vm).unwrap();
+        assert!(matches!(&broken.measurement, Measurement::Unsupported(reason) if reason.starts_with("parse error")));
+    }
+
+    #[test]
+    fn test_timing_statistics() {
+        let timing = Timing::from_samples(vec![
+            Duration::from_millis(30),
+            Duration::from_millis(10),
+            Duration::from_millis(20),
+        ]);
+        assert_eq!(timing.samples, 3);
+        assert_eq!(timing.min, Duration::from_millis(10));
+        assert_eq!(timing.median, Duration::from_millis(20));
+        assert_eq!(timing.mean, Duration::from_millis(20));
+        assert_eq!(timing.max, Duration::from_millis(30));
+    }
+
+    #[test]
+    fn test_compare_detects_regressions() {
+        let baseline = BenchReport {
+            results: vec![timed("fib", Engine::Interpreter, 100), timed("fib", Engine::Vm, 100)],
+        };
+        let current = BenchReport {
+            results: vec![
+                timed("fib", Engine::Interpreter, 105),
+                timed("fib", Engine::Vm, 150),
+                timed("collatz", Engine::Vm, 500),
+            ],
+        };
+
+        let regressions = current.compare(&baseline, 0.10);
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].engine, Engine::Vm);
+        assert!((regressions[0].ratio() - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_baseline_round_trip() {
+        let report = BenchReport {
+            results: vec![
+                timed("fib", Engine::Interpreter, 12),
+                BenchResult {
+                    workload: "seek_query".to_string(),
+                    engine: Engine::NativeCodegen,
+                    measurement: Measurement::Unsupported("not implemented".to_string()),
+                },
+            ],
+        };
+
+        let restored = BenchReport::from_baseline(&report.to_baseline()).unwrap();
+        assert_eq!(restored.results, vec![timed("fib", Engine::Interpreter, 12)]);
+        assert!(BenchReport::from_baseline("fib turbo 10").is_err());
+    }
+
+    #[test]
+    fn test_codegen_is_listed_apart_from_runs() {
+        let report = BenchReport {
+            results: vec![
+                timed("fib", Engine::Interpreter, 12),
+                timed("fib", Engine::NativeCodegen, 1),
+                timed("fib", Engine::Vm, 4),
+            ],
+        };
+
+        let text = report.to_string();
+        let (runs, codegen) = text.split_once("code generation only").unwrap();
+        assert!(runs.contains("interpreter") && runs.contains("vm"), "{}", text);
+        assert!(!runs.contains("native_codegen"), "{}", text);
+        assert!(codegen.contains("native_codegen"), "{}", text);
+    }
+}
@@ -0,0 +1,438 @@
+//! # Big Integers
+//!
+//! Arbitrary-precision integers for scripts that count, hash or sign past
+//! 2^53, where a Number (an `f64`) starts dropping low bits. Scripts write
+//! them with an `n` suffix (`123n`) or convert with `bigint(x)`; the usual
+//! operators work on two big integers, and mixing one with a Number is a
+//! type error rather than a silent rounding.
+//!
+//! The implementation is plain Rust over `alloc`, so it works in `no_std`
+//! builds: a sign and a little-endian vector of 32-bit limbs. Division
+//! truncates toward zero and the remainder takes the dividend's sign, as
+//! with Rust's integer `/` and `%`.
+//!
+//! ```
+//! use glimmer_weave::bigint::BigInt;
+//!
+//! let big = BigInt::parse("9007199254740993").unwrap();
+//! let sum = &big + &BigInt::from_i64(1);
+//! assert_eq!(sum.to_string(), "9007199254740994");
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+
+/// An integer of any size
+#[derive(Debug, Clone, PartialEq, Eq, Default, Hash)]
+pub struct BigInt {
+    /// Never set for zero
+    negative: bool,
+    /// Little-endian 32-bit limbs without trailing zeros; empty for zero
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    /// Zero
+    pub fn zero() -> Self {
+        BigInt::default()
+    }
+
+    /// The integer `value`
+    pub fn from_i64(value: i64) -> Self {
+        let magnitude = value.unsigned_abs();
+        BigInt::from_parts(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+
+    /// The integer a whole, finite Number holds, exactly
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() || value % 1.0 != 0.0 {
+            return None;
+        }
+        if value.abs() < 9.2e18 {
+            return Some(BigInt::from_i64(value as i64));
+        }
+        // Beyond 2^63 a whole f64 is its 53-bit mantissa times a power of two
+        let bits = value.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as u32 - 1075;
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let magnitude = &BigInt::from_i64(mantissa as i64) * &BigInt::from_i64(2).pow(exponent);
+        Some(if value < 0.0 { -&magnitude } else { magnitude })
+    }
+
+    /// Parse decimal digits with an optional leading `-`
+    pub fn parse(text: &str) -> Option<Self> {
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, text),
+        };
+        if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        let mut magnitude = Vec::new();
+        for chunk in digits.as_bytes().chunks(9) {
+            let mut scale = 1u32;
+            let mut value = 0u32;
+            for byte in chunk {
+                scale *= 10;
+                value = value * 10 + u32::from(byte - b'0');
+            }
+            mul_small(&mut magnitude, scale);
+            add_small(&mut magnitude, value);
+        }
+        Some(BigInt::from_parts(negative, magnitude))
+    }
+
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        trim(&mut magnitude);
+        BigInt { negative: negative && !magnitude.is_empty(), magnitude }
+    }
+
+    /// Whether this is zero
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    /// Whether this is below zero
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Bits in the magnitude, without leading zeros; 0 for zero
+    pub fn bit_len(&self) -> u64 {
+        match self.magnitude.last() {
+            Some(top) => (self.magnitude.len() as u64 - 1) * 32 + u64::from(32 - top.leading_zeros()),
+            None => 0,
+        }
+    }
+
+    /// The nearest Number (rounding may lose low bits past 2^53)
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self.magnitude.iter().rev().fold(0.0, |acc, &limb| acc * 4_294_967_296.0 + f64::from(limb));
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    /// The value as an `i64`, if it fits
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+        let magnitude = self.magnitude.iter().rev().fold(0u64, |acc, &limb| (acc << 32) | u64::from(limb));
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    /// Quotient and remainder, or `None` when dividing by zero
+    pub fn div_rem(&self, divisor: &BigInt) -> Option<(BigInt, BigInt)> {
+        if divisor.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &divisor.magnitude);
+        Some((
+            BigInt::from_parts(self.negative != divisor.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+
+    /// This raised to `exponent`
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut result = BigInt::from_i64(1);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+
+    /// `self^exponent mod modulus`, in `0..modulus`; `None` for a negative
+    /// exponent or a modulus below one
+    pub fn mod_pow(&self, exponent: &BigInt, modulus: &BigInt) -> Option<BigInt> {
+        if exponent.negative || modulus.negative || modulus.is_zero() {
+            return None;
+        }
+        let reduce = |value: &BigInt| -> Option<BigInt> {
+            let (_, remainder) = value.div_rem(modulus)?;
+            Some(if remainder.negative { &remainder + modulus } else { remainder })
+        };
+        let mut result = reduce(&BigInt::from_i64(1))?;
+        let mut base = reduce(self)?;
+        for limb in &exponent.magnitude {
+            for bit in 0..32 {
+                if limb >> bit & 1 == 1 {
+                    result = reduce(&(&result * &base))?;
+                }
+                base = reduce(&(&base * &base))?;
+            }
+        }
+        Some(result)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => cmp_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl core::ops::Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl core::ops::Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitude(&self.magnitude, &other.magnitude));
+        }
+        // Opposite signs: the larger magnitude wins
+        match cmp_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::from_parts(other.negative, sub_magnitude(&other.magnitude, &self.magnitude)),
+            _ => BigInt::from_parts(self.negative, sub_magnitude(&self.magnitude, &other.magnitude)),
+        }
+    }
+}
+
+impl core::ops::Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl core::ops::Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut product = vec![0u32; self.magnitude.len() + other.magnitude.len()];
+        for (i, &a) in self.magnitude.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.magnitude.iter().enumerate() {
+                let sum = u64::from(product[i + j]) + u64::from(a) * u64::from(b) + carry;
+                product[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            product[i + other.magnitude.len()] = carry as u32;
+        }
+        BigInt::from_parts(self.negative != other.negative, product)
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return f.write_str("0");
+        }
+        // Peel off nine decimal digits at a time
+        let mut rest = self.magnitude.clone();
+        let mut chunks = Vec::new();
+        while !rest.is_empty() {
+            chunks.push(div_small(&mut rest, 1_000_000_000));
+        }
+        let mut text = String::new();
+        if self.negative {
+            text.push('-');
+        }
+        for (index, chunk) in chunks.iter().rev().enumerate() {
+            if index == 0 {
+                text.push_str(&format!("{}", chunk));
+            } else {
+                text.push_str(&format!("{:09}", chunk));
+            }
+        }
+        f.write_str(&text)
+    }
+}
+
+fn trim(magnitude: &mut Vec<u32>) {
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+}
+
+fn cmp_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (index, &limb) in long.iter().enumerate() {
+        let total = u64::from(limb) + u64::from(short.get(index).copied().unwrap_or(0)) + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    sum.push(carry as u32);
+    sum
+}
+
+/// `a - b` for `a >= b`
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (index, &limb) in a.iter().enumerate() {
+        let mut total = i64::from(limb) - i64::from(b.get(index).copied().unwrap_or(0)) - borrow;
+        borrow = 0;
+        if total < 0 {
+            total += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(total as u32);
+    }
+    difference
+}
+
+fn mul_small(magnitude: &mut Vec<u32>, factor: u32) {
+    let mut carry = 0u64;
+    for limb in magnitude.iter_mut() {
+        let product = u64::from(*limb) * u64::from(factor) + carry;
+        *limb = product as u32;
+        carry = product >> 32;
+    }
+    if carry > 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+fn add_small(magnitude: &mut Vec<u32>, mut value: u32) {
+    for limb in magnitude.iter_mut() {
+        let (sum, overflow) = limb.overflowing_add(value);
+        *limb = sum;
+        if !overflow {
+            return;
+        }
+        value = 1;
+    }
+    if value > 0 {
+        magnitude.push(value);
+    }
+}
+
+/// Divide in place by a small divisor, returning the remainder
+fn div_small(magnitude: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder = 0u64;
+    for limb in magnitude.iter_mut().rev() {
+        let current = (remainder << 32) | u64::from(*limb);
+        *limb = (current / u64::from(divisor)) as u32;
+        remainder = current % u64::from(divisor);
+    }
+    trim(magnitude);
+    remainder as u32
+}
+
+/// Schoolbook binary long division of magnitudes; `b` is not zero
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [divisor] = b {
+        let mut quotient = a.to_vec();
+        let remainder = div_small(&mut quotient, *divisor);
+        return (quotient, vec![remainder]);
+    }
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for index in (0..a.len() * 32).rev() {
+        // remainder = remainder * 2 + next bit of a
+        let mut carry = a[index / 32] >> (index % 32) & 1;
+        for limb in remainder.iter_mut() {
+            let shifted = (*limb << 1) | carry;
+            carry = *limb >> 31;
+            *limb = shifted;
+        }
+        if carry > 0 {
+            remainder.push(carry);
+        }
+        if cmp_magnitude(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, b);
+            trim(&mut remainder);
+            quotient[index / 32] |= 1 << (index % 32);
+        }
+    }
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(text: &str) -> BigInt {
+        BigInt::parse(text).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        for text in ["0", "-1", "4294967296", "-123456789012345678901234567890", "1000000000"] {
+            assert_eq!(big(text).to_string(), text);
+        }
+        assert_eq!(big("-0"), BigInt::zero());
+        assert_eq!(big("007").to_string(), "7");
+        assert!(BigInt::parse("12a").is_none());
+        assert!(BigInt::parse("-").is_none());
+    }
+
+    #[test]
+    fn test_arithmetic_matches_i128() {
+        let samples: [i128; 7] = [0, 1, -1, 4_294_967_295, -4_294_967_297, 9_007_199_254_740_993, -(1 << 90) + 12345];
+        for &a in &samples {
+            for &b in &samples {
+                let (x, y) = (big(&a.to_string()), big(&b.to_string()));
+                assert_eq!((&x + &y).to_string(), (a + b).to_string());
+                assert_eq!((&x - &y).to_string(), (a - b).to_string());
+                if a.abs() < 1 << 62 && b.abs() < 1 << 62 {
+                    assert_eq!((&x * &y).to_string(), (a * b).to_string());
+                }
+                if b != 0 {
+                    let (quotient, remainder) = x.div_rem(&y).unwrap();
+                    assert_eq!((quotient.to_string(), remainder.to_string()), ((a / b).to_string(), (a % b).to_string()));
+                }
+                assert_eq!(x.cmp(&y), a.cmp(&b));
+            }
+        }
+        assert!(big("5").div_rem(&BigInt::zero()).is_none());
+    }
+
+    #[test]
+    fn test_pow_mod_pow_and_conversions() {
+        assert_eq!(big("2").pow(100).to_string(), "1267650600228229401496703205376");
+        assert_eq!(big("2").pow(100).bit_len(), 101);
+        assert_eq!(big("-4294967295").bit_len(), 32);
+        assert_eq!(BigInt::zero().bit_len(), 0);
+        assert_eq!(big("4").mod_pow(&big("13"), &big("497")), Some(big("445")));
+        assert_eq!(big("-4").mod_pow(&big("3"), &big("5")), Some(big("1")));
+        assert_eq!(big("4").mod_pow(&big("-1"), &big("5")), None);
+
+        assert_eq!(BigInt::from_i64(i64::MIN).to_string(), "-9223372036854775808");
+        assert_eq!(BigInt::from_i64(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(BigInt::from_f64(-42.0), Some(big("-42")));
+        assert_eq!(BigInt::from_f64(1e40).map(|n| n.to_f64()), Some(1e40));
+        assert_eq!(BigInt::from_f64(0.5), None);
+        assert_eq!(BigInt::from_f64(f64::NAN), None);
+        assert_eq!(big("-9007199254740993").to_f64(), -9007199254740992.0);
+    }
+}
@@ -0,0 +1,208 @@
+//! Glimmer-Weave command-line tool
+//!
+//! # Usage
+//!
+//! ```bash
+//! gwc fix [--check] <file>...
+//! gwc doc <file>
+//! gwc doc --builtins
+//! gwc heap [--json] <file>
+//! gwc crash <file>
+//! gwc bundle [--archive] <entry> <output>
+//! ```
+//!
+//! `fix` applies every machine-applicable fix-it suggestion (see
+//! `glimmer_weave::fixit`) to each file in place and lists what it changed.
+//! With `--check` the files are left alone, and the exit status is 1 if any
+//! of them has fixes to apply.
+//!
+//! `doc` prints a Markdown reference of the file's definitions (see
+//! `glimmer_weave::docgen`); `doc --builtins` prints one of the runtime's
+//! builtins instead.
+//!
+//! `heap` runs the file and prints the graph of values its bindings keep
+//! alive (see `glimmer_weave::heap_graph`) as Graphviz source, or as JSON
+//! with `--json`.
+//!
+//! `crash` runs the file and, if it dies with an uncaught error, prints the
+//! error to stderr and its crash dump (see `glimmer_weave::crash_dump`) as
+//! JSON to stdout.
+//!
+//! `bundle` packs the entry script and every module it imports into one
+//! file (see `glimmer_weave::bundle`): concatenated sources by default, or
+//! with `--archive` a binary archive that also holds the linked program as
+//! bytecode when it compiles.
+
+use std::process::ExitCode;
+
+use glimmer_weave::bundle::{Bundle, BundleFormat};
+use glimmer_weave::{docgen, fixit, CompilerPipeline, Evaluator, Lexer, ModuleResolver, Parser};
+
+const USAGE: &str = "usage: gwc fix [--check] <file>...\n       gwc doc <file>\n       gwc doc --builtins\n       gwc heap [--json] <file>\n       gwc crash <file>\n       gwc bundle [--archive] <entry> <output>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.split_first() {
+        Some((command, rest)) if command == "fix" => fix(rest),
+        Some((command, [flag])) if command == "doc" && flag == "--builtins" => {
+            print!("{}", docgen::render_builtins("Builtins", &glimmer_weave::runtime::BuiltinRegistry::new()));
+            ExitCode::SUCCESS
+        }
+        Some((command, [path])) if command == "doc" => doc(path),
+        Some((command, [path])) if command == "heap" => heap(path, false),
+        Some((command, [flag, path])) if command == "heap" && flag == "--json" => heap(path, true),
+        Some((command, [path])) if command == "crash" => crash(path),
+        Some((command, [entry, output])) if command == "bundle" => bundle(entry, output, BundleFormat::Source),
+        Some((command, [flag, entry, output])) if command == "bundle" && flag == "--archive" => {
+            bundle(entry, output, BundleFormat::Archive)
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn fix(args: &[String]) -> ExitCode {
+    let check = args.iter().any(|arg| arg == "--check");
+    let paths: Vec<&String> = args.iter().filter(|arg| *arg != "--check").collect();
+    if paths.is_empty() {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    }
+
+    let mut status = ExitCode::SUCCESS;
+    for path in paths {
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                status = ExitCode::from(2);
+                continue;
+            }
+        };
+        let fixed = fixit::fix(&source);
+        for suggestion in &fixed.applied {
+            println!("{}:{}:{}: {}", path, suggestion.start.line, suggestion.start.column, suggestion.message);
+        }
+        if fixed.applied.is_empty() {
+            continue;
+        }
+        if check {
+            status = ExitCode::FAILURE;
+        } else if let Err(error) = std::fs::write(path, &fixed.source) {
+            eprintln!("{}: {}", path, error);
+            status = ExitCode::from(2);
+        }
+    }
+    status
+}
+
+fn doc(path: &str) -> ExitCode {
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("{}: {}", path, error);
+            return ExitCode::from(2);
+        }
+    };
+    match Parser::new(Lexer::new(&source).tokenize_positioned()).parse() {
+        Ok(ast) => {
+            let title = std::path::Path::new(path).file_stem().map_or(path.into(), |stem| stem.to_string_lossy());
+            print!("{}", docgen::render(&title, &ast));
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{}: parse error: {}", path, error.message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn heap(path: &str, json: bool) -> ExitCode {
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("{}: {}", path, error);
+            return ExitCode::from(2);
+        }
+    };
+    let ast = match Parser::new(Lexer::new(&source).tokenize_positioned()).parse() {
+        Ok(ast) => ast,
+        Err(error) => {
+            eprintln!("{}: parse error: {}", path, error.message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut evaluator = Evaluator::new();
+    let status = match evaluator.eval(&ast) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}: runtime error: {:?}", path, error);
+            ExitCode::FAILURE
+        }
+    };
+    let graph = evaluator.heap_graph();
+    if json {
+        println!("{}", graph.to_json());
+    } else {
+        print!("{}", graph.to_dot());
+    }
+    status
+}
+
+fn crash(path: &str) -> ExitCode {
+    let source = match std::fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("{}: {}", path, error);
+            return ExitCode::from(2);
+        }
+    };
+    let ast = match Parser::new(Lexer::new(&source).tokenize_positioned()).parse() {
+        Ok(ast) => ast,
+        Err(error) => {
+            eprintln!("{}: parse error: {}", path, error.message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut evaluator = Evaluator::new();
+    evaluator.set_crash_dumps(true);
+    if evaluator.eval(&ast).is_ok() {
+        return ExitCode::SUCCESS;
+    }
+    if let Some(dump) = evaluator.crash_dump() {
+        eprint!("{}", dump.diagnostic().format_with_source(&source));
+        println!("{}", dump.to_json());
+    }
+    ExitCode::FAILURE
+}
+
+fn bundle(entry: &str, output: &str, format: BundleFormat) -> ExitCode {
+    // Imports resolve against the entry's directory
+    let root = match std::path::Path::new(entry).parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_string_lossy().into_owned(),
+        _ => ".".to_string(),
+    };
+    let mut resolver = ModuleResolver::new(root, "std".to_string());
+    let mut bundle = match Bundle::build(entry, &mut resolver) {
+        Ok(bundle) => bundle,
+        Err(error) => {
+            eprintln!("{}: {:?}", entry, error);
+            return ExitCode::FAILURE;
+        }
+    };
+    if format == BundleFormat::Archive {
+        if let Err(error) = bundle.compile(&mut CompilerPipeline::new()) {
+            eprintln!("{}: bundling sources only, the program did not compile: {:?}", entry, error);
+        }
+    }
+    if let Err(error) = std::fs::write(output, bundle.encode(format)) {
+        eprintln!("{}: {}", output, error);
+        return ExitCode::from(2);
+    }
+    for module in &bundle.manifest.modules {
+        println!("{} ({})", module.path, module.name);
+    }
+    ExitCode::SUCCESS
+}
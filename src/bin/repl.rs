@@ -1,5 +1,6 @@
 /// Glimmer-Weave REPL (Read-Eval-Print Loop)
 /// Interactive shell for rapid prototyping and testing code snippets
+use glimmer_weave::runtime::BuiltinRegistry;
 use glimmer_weave::{Evaluator, Lexer, Parser};
 use rustyline::error::ReadlineError;
 use rustyline::{DefaultEditor, Result};
@@ -18,10 +19,13 @@ const HELP_MESSAGE: &str = r#"
 Glimmer-Weave REPL Commands:
 
   :help         Show this help message
+  :help <name>  Describe a builtin, such as `upper` or `Text.upper`
+  :builtins [prefix]  List builtins, or those whose name starts with prefix
   :quit, :exit  Exit the REPL
   :clear        Clear the screen
   :env          Show all defined variables
   :reset        Reset the environment (clear all variables)
+  :test <path>  Run the verify blocks of a script or a directory of scripts
 
 Examples:
 
@@ -50,6 +54,7 @@ fn format_value(value: &glimmer_weave::eval::Value) -> String {
 
     match value {
         Value::Number(n) => format!("{}", n),
+        Value::Integer(n) => format!("{}i", n),
         Value::Text(s) => format!("\"{}\"", s),
         Value::Truth(b) => format!("{}", b),
         Value::Nothing => "nothing".to_string(),
@@ -57,6 +62,18 @@ fn format_value(value: &glimmer_weave::eval::Value) -> String {
             let formatted: Vec<String> = items.iter().map(format_value).collect();
             format!("[{}]", formatted.join(", "))
         }
+        Value::Tuple(elements) => {
+            let formatted: Vec<String> = elements.iter().map(format_value).collect();
+            if formatted.len() == 1 {
+                format!("({},)", formatted[0])
+            } else {
+                format!("({})", formatted.join(", "))
+            }
+        }
+        Value::Set(elements) => {
+            let formatted: Vec<String> = elements.iter().map(|element| format_value(&element.to_value())).collect();
+            format!("Set({})", formatted.join(", "))
+        }
         Value::Map(map) => {
             let formatted: Vec<String> = map
                 .iter()
@@ -107,6 +124,7 @@ fn format_value(value: &glimmer_weave::eval::Value) -> String {
         Value::Range { start, end } => format!("range({}, {})", format_value(start), format_value(end)),
         Value::StructDef { name, .. } => format!("<struct definition: {}>", name),
         Value::VariantDef { name, .. } => format!("<enum definition: {}>", name),
+        other => glimmer_weave::coercion::to_text(other).unwrap_or_else(|_| format!("<{}>", other.type_name())),
     }
 }
 
@@ -186,6 +204,25 @@ fn main() -> Result<()> {
                             println!("Environment reset.");
                             continue;
                         }
+                        cmd if cmd.starts_with(":help ") => {
+                            let name = cmd[":help ".len()..].trim();
+                            match BuiltinRegistry::new().describe(name) {
+                                Some(builtin) => println!("{}", builtin.help()),
+                                None => println!("No builtin named `{}`", name),
+                            }
+                            continue;
+                        }
+                        cmd if cmd == ":builtins" || cmd.starts_with(":builtins ") => {
+                            let registry = BuiltinRegistry::new();
+                            for builtin in registry.completions(cmd[":builtins".len()..].trim()) {
+                                println!("  {:<48} {}", builtin.usage(), builtin.description);
+                            }
+                            continue;
+                        }
+                        cmd if cmd.starts_with(":test ") => {
+                            run_tests(cmd[":test ".len()..].trim());
+                            continue;
+                        }
                         cmd => {
                             println!("Unknown command: {}", cmd);
                             println!("Type :help for available commands.");
@@ -254,6 +291,53 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Run the verify blocks of the script at `path`, or of every script under
+/// it when it is a directory, and print a report
+fn run_tests(path: &str) {
+    use glimmer_weave::test_runner::{discover, TestRunner};
+    use glimmer_weave::ModuleResolver;
+
+    let target = std::path::Path::new(path);
+    let (root, entries) = if target.is_dir() {
+        match discover(path) {
+            Ok(entries) => (target.to_path_buf(), entries),
+            Err(err) => {
+                println!("Cannot read {}: {}", path, err);
+                return;
+            }
+        }
+    } else {
+        let root = target.parent().map(|dir| dir.to_path_buf()).unwrap_or_default();
+        (root, vec![path.to_string()])
+    };
+
+    let root_text = root.to_string_lossy().into_owned();
+    let stdlib = root.join("std").to_string_lossy().into_owned();
+    let mut runner = TestRunner::new(ModuleResolver::new(root_text, stdlib));
+    let entries: Vec<&str> = entries.iter().map(String::as_str).collect();
+    let report = match runner.run(&entries) {
+        Ok(report) => report,
+        Err(err) => {
+            println!("Cannot load modules: {:?}", err);
+            return;
+        }
+    };
+
+    for module in &report.modules {
+        println!("{} ({} ms)", module.path, module.elapsed);
+        for result in &module.results {
+            match &result.failure {
+                None => println!("  ok    {}", result.name),
+                Some(failure) => println!("  FAIL  {}: {}", result.name, failure),
+            }
+            for line in result.output.lines() {
+                println!("        | {}", line);
+            }
+        }
+    }
+    println!("{} passed, {} failed", report.passed(), report.failed());
+}
+
 enum EvalError {
     Incomplete,
     Parse(String),
@@ -6,11 +6,14 @@
 //! - Values cannot be used after being moved
 
 use alloc::collections::BTreeMap;
-use alloc::string::String;
+use alloc::format;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use core::fmt;
 
 use crate::ast::{AstNode, BorrowMode};
+use crate::error_formatter::Diagnostic;
+use crate::fixit::{Applicability, Suggestion};
 use crate::source_location::SourceSpan;
 
 /// Errors that can occur during borrow checking
@@ -89,6 +92,48 @@ impl fmt::Display for BorrowError {
     }
 }
 
+impl BorrowError {
+    /// Diagnostic marking where the conflict happens and where the earlier
+    /// move or borrow it conflicts with happened
+    pub fn diagnostic(&self) -> Diagnostic {
+        let diagnostic = match self {
+            BorrowError::UseAfterMove { variable, moved_at, used_at } => {
+                Diagnostic::error(format!("Use of moved value '{}'", variable))
+                    .with_primary_label(used_at.clone(), "value used here after move")
+                    .with_secondary_label(moved_at.clone(), "value moved here")
+            }
+            BorrowError::MutableBorrowConflict { variable, immutable_borrow_at, mutable_borrow_at } => {
+                Diagnostic::error(format!("Cannot borrow '{}' as mutable because it is also borrowed as immutable", variable))
+                    .with_primary_label(mutable_borrow_at.clone(), "mutable borrow occurs here")
+                    .with_secondary_label(immutable_borrow_at.clone(), "immutable borrow occurs here")
+            }
+            BorrowError::MultipleMutableBorrows { variable, first_borrow_at, second_borrow_at } => {
+                Diagnostic::error(format!("Cannot borrow '{}' as mutable more than once", variable))
+                    .with_primary_label(second_borrow_at.clone(), "second mutable borrow occurs here")
+                    .with_secondary_label(first_borrow_at.clone(), "first mutable borrow occurs here")
+            }
+            BorrowError::BorrowOfMovedValue { variable, moved_at, borrowed_at } => {
+                Diagnostic::error(format!("Cannot borrow '{}' because it was moved", variable))
+                    .with_primary_label(borrowed_at.clone(), "value borrowed here after move")
+                    .with_secondary_label(moved_at.clone(), "value moved here")
+            }
+        };
+        self.suggestions().into_iter().fold(diagnostic, Diagnostic::with_suggestion)
+    }
+
+    /// Edits that would fix this error: borrowing a value that is used
+    /// after it moves, rather than moving it
+    pub fn suggestions(&self) -> Vec<Suggestion> {
+        match self {
+            BorrowError::UseAfterMove { variable, moved_at, .. } if moved_at.is_known() => {
+                let message = format!("borrow '{}' instead of moving it", variable);
+                vec![Suggestion::insert(message, &moved_at.start, "borrow ", Applicability::MaybeIncorrect)]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
 /// Tracks the state of a variable in the borrow checker
 #[derive(Debug, Clone, PartialEq)]
 enum VarState {
@@ -124,7 +169,7 @@ impl BorrowChecker {
     fn check_move(&self, node: &AstNode) -> Option<(String, SourceSpan)> {
         match node {
             // Direct variable reference - might be a move depending on type
-            AstNode::Ident { name, span } => {
+            AstNode::Ident { name, span, .. } => {
                 // For now, assume all variables are move types except simple values
                 // In a real implementation, we'd check the type system
                 // Copy types: Number, Truth, Nothing
@@ -189,20 +234,20 @@ impl BorrowChecker {
 
     fn check_node(&mut self, node: &AstNode) {
         match node {
-            AstNode::BindStmt { name, typ: _, value, span } => {
+            AstNode::BindStmt { name, typ: _, value, .. } => {
                 // Check if the value is being moved
-                if let Some((moved_var, _)) = self.check_move(value) {
-                    self.mark_moved(&moved_var, span.clone());
+                if let Some((moved_var, moved_at)) = self.check_move(value) {
+                    self.mark_moved(&moved_var, moved_at);
                 } else {
                     self.check_node(value);
                 }
                 // New variable takes ownership
                 self.variables.insert(name.clone(), VarState::Owned);
             }
-            AstNode::WeaveStmt { name, typ: _, value, span } => {
+            AstNode::WeaveStmt { name, typ: _, value, .. } => {
                 // Check if the value is being moved
-                if let Some((moved_var, _)) = self.check_move(value) {
-                    self.mark_moved(&moved_var, span.clone());
+                if let Some((moved_var, moved_at)) = self.check_move(value) {
+                    self.mark_moved(&moved_var, moved_at);
                 } else {
                     self.check_node(value);
                 }
@@ -246,7 +291,7 @@ impl BorrowChecker {
                 self.check_node(target);
                 self.check_node(value);
             }
-            AstNode::Ident { name, span } => {
+            AstNode::Ident { name, span, .. } => {
                 // Check if variable is moved
                 if let Some(VarState::Moved(moved_at)) = self.variables.get(name) {
                     self.errors.push(BorrowError::UseAfterMove {
@@ -298,10 +343,10 @@ impl BorrowChecker {
                 self.check_node(left);
                 self.check_node(right);
             }
-            AstNode::UnaryOp { operand, .. } => {
+            AstNode::UnaryOp { operand, .. } | AstNode::InUnit { value: operand, .. } => {
                 self.check_node(operand);
             }
-            AstNode::Call { callee, type_args: _, args, span: _ } => {
+            AstNode::Call { callee, args, .. } => {
                 self.check_node(callee);
                 // For each argument, check if it's being moved
                 for arg in args {
@@ -361,7 +406,7 @@ impl BorrowChecker {
                 // The borrow itself doesn't move
                 self.check_node(value);
             }
-            AstNode::List { elements, .. } => {
+            AstNode::List { elements, .. } | AstNode::Tuple { elements, .. } => {
                 for elem in elements {
                     self.check_node(elem);
                 }
@@ -375,6 +420,9 @@ impl BorrowChecker {
             }
             // Literals don't need checking
             AstNode::Number { .. }
+            | AstNode::BigInt { .. }
+            | AstNode::Integer { .. }
+            | AstNode::Measure { .. }
             | AstNode::Text { .. }
             | AstNode::Truth { .. }
             | AstNode::Nothing { .. } => {}
@@ -436,6 +484,7 @@ mod tests {
                 value: Box::new(AstNode::Ident {
                     name: "data".to_string(),
                     span: SourceSpan::unknown(),
+                    slot: None,
                 }),
                 span: SourceSpan::unknown(),
             },
@@ -443,6 +492,7 @@ mod tests {
             AstNode::Ident {
                 name: "data".to_string(),
                 span: SourceSpan::unknown(),
+                slot: None,
             },
         ];
 
@@ -481,12 +531,15 @@ mod tests {
                 callee: Box::new(AstNode::Ident {
                     name: "process".to_string(),
                     span: SourceSpan::unknown(),
+                    slot: None,
                 }),
                 type_args: vec![],
+                arg_names: vec![],
                 args: vec![AstNode::BorrowExpr {
                     value: Box::new(AstNode::Ident {
                         name: "data".to_string(),
                         span: SourceSpan::unknown(),
+                        slot: None,
                     }),
                     mutable: false,
                     span: SourceSpan::unknown(),
@@ -497,6 +550,7 @@ mod tests {
             AstNode::Ident {
                 name: "data".to_string(),
                 span: SourceSpan::unknown(),
+                slot: None,
             },
         ];
 
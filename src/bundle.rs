@@ -0,0 +1,383 @@
+//! # Script Bundles
+//!
+//! Packs a script and every module it imports into one self-contained
+//! file, so AethelOS can distribute and install scripts as single files.
+//!
+//! [`Bundle::build`] walks the module graph through a
+//! [`ModuleResolver`] and records each module's source under a
+//! [`Manifest`]: the entry path, and every module's path, grove name and
+//! content hash, in dependency order. Paths inside the project root are
+//! kept relative to it, so a bundle installs under any root. [`Bundle::compile`] adds the linked
+//! program as bytecode, and [`Bundle::sign`] a detached signature from the
+//! host's key.
+//!
+//! A bundle is written in one of two forms, which [`Bundle::decode`] tells
+//! apart by their first bytes:
+//!
+//! - [`BundleFormat::Source`]: the module sources concatenated, each after
+//!   a `#@ module` comment line that gives its boundary, with the manifest
+//!   and signature in `#@` lines too. Readable and diffable.
+//! - [`BundleFormat::Archive`]: a binary `GWB\0` archive of the manifest,
+//!   the sources and the program as a [`.gwc` image](crate::bytecode_image).
+//!
+//! Both forms sign the same bytes, the archive without its signature, so a
+//! bundle can change form and keep its signature. Decoding checks every
+//! source against its hash in the manifest; [`Bundle::verify`] checks the
+//! signature, and [`Bundle::install`] registers the sources with a resolver
+//! to run the entry from.
+//!
+//! ```
+//! use glimmer_weave::bundle::{Bundle, BundleFormat};
+//! use glimmer_weave::ModuleResolver;
+//!
+//! let mut resolver = ModuleResolver::new("/app".to_string(), "/std".to_string());
+//! resolver.add_source("/app/math.gw", "grove Math with\n    bind base to 40\n    offer base\nend");
+//! resolver.add_source("/app/main.gw", "summon Math from \"math.gw\"\nMath.base + 2");
+//!
+//! let bundle = Bundle::build("/app/main.gw", &mut resolver).unwrap();
+//! let text = bundle.encode(BundleFormat::Source);
+//! assert!(text.starts_with(b"#@ glimmer-bundle 2\n#@ entry main.gw\n"));
+//!
+//! let mut target = ModuleResolver::new("/elsewhere".to_string(), "/std".to_string());
+//! let entry = Bundle::decode(&text).unwrap().install(&mut target);
+//! assert_eq!(target.load_graph(&entry).unwrap(), vec!["/elsewhere/math.gw", "/elsewhere/main.gw"]);
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::bytecode::BytecodeChunk;
+use crate::bytecode_image::{self, ImageError, Reader, Writer};
+use crate::module_cache::{content_hash, ContentHash};
+use crate::module_resolver::{ModuleResolver, ResolverError};
+use crate::pipeline::{CompilerPipeline, Output, Target};
+use crate::verify::{verify_image, Verifier, VerifyError};
+
+/// Magic bytes every archive starts with
+pub const MAGIC: &[u8; 4] = b"GWB\0";
+
+/// First line of every source bundle
+pub const SOURCE_HEADER: &str = "#@ glimmer-bundle";
+
+/// Version of both bundle forms
+///
+/// Version 2 records SHA-256 content hashes; version 1 bundles, with
+/// 64-bit hashes, are no longer read.
+pub const BUNDLE_VERSION: u16 = 2;
+
+/// File extension of bundles, in either form
+pub const EXTENSION: &str = "gwb";
+
+/// Form a bundle is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFormat {
+    /// Concatenated sources with `#@` boundary lines
+    Source,
+    /// Binary archive with the program as bytecode
+    Archive,
+}
+
+/// What a bundle holds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    /// Path of the script the bundle runs, like the module paths
+    pub entry: String,
+    /// Every module, dependencies ahead of their importers and the entry
+    /// last
+    pub modules: Vec<ManifestEntry>,
+}
+
+/// A module in the manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// Path the module was resolved to: relative to the project root for
+    /// modules inside it, as resolved otherwise
+    pub path: String,
+    /// Grove name, or the file name for scripts without a grove
+    pub name: String,
+    /// [`content_hash`] of the source
+    pub hash: ContentHash,
+}
+
+/// Why a bundle could not be built or read
+#[derive(Debug, Clone, PartialEq)]
+pub enum BundleError {
+    /// A module could not be loaded
+    Resolver(ResolverError),
+    /// The linked program did not compile
+    Compile(String),
+    /// The data is not a bundle in either form
+    NotABundle,
+    /// The bundle was written by another version of the format
+    UnsupportedVersion(u16),
+    /// An archive field or the program image could not be decoded
+    Image(ImageError),
+    /// A `#@` line of a source bundle is not understood
+    Malformed { line: usize, reason: String },
+    /// A module's source doesn't match its hash in the manifest
+    HashMismatch { path: String },
+}
+
+impl From<ResolverError> for BundleError {
+    fn from(error: ResolverError) -> Self {
+        BundleError::Resolver(error)
+    }
+}
+
+impl From<ImageError> for BundleError {
+    fn from(error: ImageError) -> Self {
+        BundleError::Image(error)
+    }
+}
+
+/// A script with everything it imports
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub manifest: Manifest,
+    /// Source of each module, in manifest order
+    pub sources: Vec<String>,
+    /// The linked program, once [`compile`](Bundle::compile)d
+    pub program: Option<BytecodeChunk>,
+    /// Detached signature over [`signed_bytes`](Bundle::signed_bytes)
+    pub signature: Option<Vec<u8>>,
+}
+
+impl Bundle {
+    /// Bundle `entry` and every module it imports, transitively
+    pub fn build(entry: &str, resolver: &mut ModuleResolver) -> Result<Self, BundleError> {
+        let order = resolver.load_graph(entry)?;
+        let root = format!("{}/", resolver.project_root());
+        let relative = |path: &str| path.strip_prefix(root.as_str()).unwrap_or(path).to_string();
+        let mut modules = Vec::new();
+        let mut sources = Vec::new();
+        for path in order {
+            let source = resolver.module_source(&path)?;
+            let name = resolver.get_module(&path).map_or_else(|| path.clone(), |module| module.name.clone());
+            modules.push(ManifestEntry { hash: content_hash(&source), path: relative(&path), name });
+            sources.push(source);
+        }
+        let manifest = Manifest { entry: relative(entry), modules };
+        Ok(Bundle { manifest, sources, program: None, signature: None })
+    }
+
+    /// Link the modules into one bytecode program with `pipeline`
+    ///
+    /// The signature no longer covers the bundle afterwards and is dropped;
+    /// sign after compiling.
+    pub fn compile(&mut self, pipeline: &mut CompilerPipeline) -> Result<(), BundleError> {
+        let mut resolver = ModuleResolver::new("/bundle".to_string(), String::new());
+        let entry = self.install(&mut resolver);
+        match pipeline.compile_project(&entry, &mut resolver, Target::Bytecode) {
+            Ok(Output::Bytecode(chunk)) => {
+                self.program = Some(chunk);
+                self.signature = None;
+                Ok(())
+            }
+            Ok(_) => Err(BundleError::Compile("the pipeline did not produce bytecode".to_string())),
+            Err(diagnostics) => Err(BundleError::Compile(diagnostics.to_string())),
+        }
+    }
+
+    /// Source of the module at `path`
+    pub fn source(&self, path: &str) -> Option<&str> {
+        let index = self.manifest.modules.iter().position(|module| module.path == path)?;
+        self.sources.get(index).map(String::as_str)
+    }
+
+    /// Register every module's source with `resolver`, under its project
+    /// root, returning the entry path to load
+    ///
+    /// A resolver with a verifier installed still wants each module's own
+    /// signature; check the bundle's with [`verify`](Bundle::verify).
+    pub fn install(&self, resolver: &mut ModuleResolver) -> String {
+        let root = resolver.project_root().to_string();
+        let installed = |path: &str| if path.starts_with('/') { path.to_string() } else { format!("{}/{}", root, path) };
+        for (module, source) in self.manifest.modules.iter().zip(&self.sources) {
+            resolver.add_source(installed(&module.path), source.clone());
+        }
+        installed(&self.manifest.entry)
+    }
+
+    /// Bytes the signature is made over: the archive without its signature
+    pub fn signed_bytes(&self) -> Vec<u8> {
+        let mut out = Writer(Vec::new());
+        out.0.extend_from_slice(MAGIC);
+        out.u16(BUNDLE_VERSION);
+        out.text(&self.manifest.entry);
+        out.len(self.manifest.modules.len());
+        for (module, source) in self.manifest.modules.iter().zip(&self.sources) {
+            out.text(&module.path);
+            out.text(&module.name);
+            out.0.extend_from_slice(&module.hash);
+            out.text(source);
+        }
+        out.truth(self.program.is_some());
+        if let Some(program) = &self.program {
+            out.bytes(&bytecode_image::encode(program));
+        }
+        out.0
+    }
+
+    /// Sign the bundle with the host's key
+    pub fn sign(&mut self, signer: impl FnOnce(&[u8]) -> Vec<u8>) {
+        self.signature = Some(signer(&self.signed_bytes()));
+    }
+
+    /// Check the signature, failing when there is none
+    pub fn verify(&self, verifier: &dyn Verifier) -> Result<(), VerifyError> {
+        verify_image(verifier, &self.signed_bytes(), self.signature.as_deref())
+    }
+
+    /// Write the bundle in `format`
+    pub fn encode(&self, format: BundleFormat) -> Vec<u8> {
+        match format {
+            BundleFormat::Archive => {
+                let mut out = Writer(self.signed_bytes());
+                out.truth(self.signature.is_some());
+                if let Some(signature) = &self.signature {
+                    out.bytes(signature);
+                }
+                out.0
+            }
+            BundleFormat::Source => self.encode_source().into_bytes(),
+        }
+    }
+
+    fn encode_source(&self) -> String {
+        let mut out = format!("{} {}\n#@ entry {}\n", SOURCE_HEADER, BUNDLE_VERSION, self.manifest.entry);
+        for (module, source) in self.manifest.modules.iter().zip(&self.sources) {
+            // The byte length marks where the source ends, whatever it holds
+            out.push_str(&format!("#@ module {} {} {} {}\n", hex(&module.hash), source.len(), module.name, module.path));
+            out.push_str(source);
+            out.push('\n');
+        }
+        if let Some(program) = &self.program {
+            out.push_str(&format!("#@ program {}\n", hex(&bytecode_image::encode(program))));
+        }
+        if let Some(signature) = &self.signature {
+            out.push_str(&format!("#@ signature {}\n", hex(signature)));
+        }
+        out
+    }
+
+    /// Read a bundle in either form, checking each source against the
+    /// manifest
+    pub fn decode(data: &[u8]) -> Result<Self, BundleError> {
+        let bundle = if data.starts_with(MAGIC) {
+            decode_archive(data)?
+        } else if data.starts_with(SOURCE_HEADER.as_bytes()) {
+            let text = core::str::from_utf8(data).map_err(|_| BundleError::Image(ImageError::InvalidText))?;
+            decode_source(text)?
+        } else {
+            return Err(BundleError::NotABundle);
+        };
+        for (module, source) in bundle.manifest.modules.iter().zip(&bundle.sources) {
+            if content_hash(source) != module.hash {
+                return Err(BundleError::HashMismatch { path: module.path.clone() });
+            }
+        }
+        Ok(bundle)
+    }
+}
+
+fn decode_archive(data: &[u8]) -> Result<Bundle, BundleError> {
+    let mut input = Reader(&data[MAGIC.len()..]);
+    let version = input.u16()?;
+    if version != BUNDLE_VERSION {
+        return Err(BundleError::UnsupportedVersion(version));
+    }
+    let entry = input.text()?;
+    let mut modules = Vec::new();
+    let mut sources = Vec::new();
+    for _ in 0..input.len()? {
+        let path = input.text()?;
+        let name = input.text()?;
+        let mut hash: ContentHash = [0; 32];
+        hash.copy_from_slice(input.take(32)?);
+        modules.push(ManifestEntry { path, name, hash });
+        sources.push(input.text()?);
+    }
+    let program = if input.truth()? { Some(bytecode_image::decode(input.bytes()?)?) } else { None };
+    let signature = if input.truth()? { Some(input.bytes()?.to_vec()) } else { None };
+    Ok(Bundle { manifest: Manifest { entry, modules }, sources, program, signature })
+}
+
+fn decode_source(text: &str) -> Result<Bundle, BundleError> {
+    let mut bundle = Bundle {
+        manifest: Manifest { entry: String::new(), modules: Vec::new() },
+        sources: Vec::new(),
+        program: None,
+        signature: None,
+    };
+    let mut rest = text;
+    let mut line = 0;
+    while !rest.is_empty() {
+        line += 1;
+        let (current, after) = rest.split_once('\n').unwrap_or((rest, ""));
+        rest = after;
+        let malformed = |reason: &str| BundleError::Malformed { line, reason: reason.to_string() };
+        let Some(directive) = current.strip_prefix("#@ ") else {
+            return Err(malformed("expected a `#@` line"));
+        };
+        let (keyword, value) = directive.split_once(' ').unwrap_or((directive, ""));
+        match keyword {
+            "glimmer-bundle" if line == 1 => {
+                let version = value.parse().map_err(|_| malformed("version is not a number"))?;
+                if version != BUNDLE_VERSION {
+                    return Err(BundleError::UnsupportedVersion(version));
+                }
+            }
+            "entry" => bundle.manifest.entry = value.to_string(),
+            "module" => {
+                let mut fields = value.splitn(4, ' ');
+                let (Some(hash), Some(len), Some(name), Some(path)) = (fields.next(), fields.next(), fields.next(), fields.next()) else {
+                    return Err(malformed("expected a hash, length, name and path"));
+                };
+                let hash: ContentHash = unhex(hash)
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| malformed("hash is not 64 hexadecimal digits"))?;
+                let len: usize = len.parse().map_err(|_| malformed("length is not a number"))?;
+                let source = rest.get(..len).ok_or_else(|| malformed("source is cut short"))?;
+                rest = rest[len..].strip_prefix('\n').ok_or_else(|| malformed("source runs past its length"))?;
+                line += source.matches('\n').count() + 1;
+                bundle.manifest.modules.push(ManifestEntry { path: path.to_string(), name: name.to_string(), hash });
+                bundle.sources.push(source.to_string());
+            }
+            "program" => {
+                let image = unhex(value).ok_or_else(|| malformed("program is not hexadecimal"))?;
+                bundle.program = Some(bytecode_image::decode(&image)?);
+            }
+            "signature" => bundle.signature = Some(unhex(value).ok_or_else(|| malformed("signature is not hexadecimal"))?),
+            _ => return Err(malformed("unknown directive")),
+        }
+    }
+    if bundle.manifest.entry.is_empty() {
+        return Err(BundleError::Malformed { line, reason: "no entry".to_string() });
+    }
+    Ok(bundle)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_roundtrip() {
+        assert_eq!(hex(&[0, 0xab, 0x7f]), "00ab7f");
+        assert_eq!(unhex("00ab7f"), Some(vec![0, 0xab, 0x7f]));
+        assert_eq!(unhex("0g"), None);
+        assert_eq!(unhex("abc"), None);
+    }
+}
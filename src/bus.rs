@@ -0,0 +1,293 @@
+//! # Message Bus
+//!
+//! Named-topic publish/subscribe between cooperating scripts. Scripts call
+//! `subscribe(topic, handler)` and `publish(topic, value)`; the
+//! [`Session`](crate::session::Session) connects every evaluator it spawns
+//! to its bus.
+//!
+//! Publishing copies the value into the inbox of every subscribed
+//! evaluator. The handler runs later, during that evaluator's event pump:
+//! `run_until_idle`, `yield_now` and `block_on_event` deliver waiting
+//! messages as well as running ready tasks. Values cross as copies in the
+//! [`value_codec`](crate::value_codec) encoding, so only plain data can be
+//! published and scripts never share state through the bus.
+//!
+//! ## Priorities and budgets
+//!
+//! `subscribe(topic, handler, priority)` gives a handler a priority (0 when
+//! left out). Each delivery takes the waiting message whose handlers have
+//! the highest priority, oldest first among equals, and runs its handlers
+//! highest first. A [`PumpPolicy`] caps how many messages one pump delivers
+//! in all and per topic, so a chatty device topic leaves the rest for the
+//! next pump instead of holding the script; and a message passed over for
+//! newer ones more than its starvation limit is delivered next whatever its
+//! priority, and counted in [`PumpStats::starved`].
+//!
+//! A topic's namespace is the part before its first `.`; publishing or
+//! subscribing needs the capability [`topic_capability`] names for it, so
+//! `sensors.temperature` needs `request Bus.sensors`.
+//!
+//! ```
+//! use glimmer_weave::bus::{topic_capability, Inbox, MessageBus};
+//! use glimmer_weave::sync::{lock, shared};
+//! use glimmer_weave::Value;
+//!
+//! let mut bus = MessageBus::new();
+//! let inbox = shared(Inbox::new());
+//! bus.subscribe("sensors.temperature", &inbox);
+//! assert_eq!(bus.publish("sensors.temperature", &Value::Number(21.0)), 1);
+//! assert_eq!(lock(&inbox).len(), 1);
+//! assert_eq!(topic_capability("sensors.temperature"), "Bus.sensors");
+//! ```
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::eval::Value;
+use crate::sync::{lock, Shared, WeakShared};
+
+/// A published value on its way to a subscriber
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub topic: String,
+    pub value: Value,
+    /// Deliveries of newer messages it was passed over for
+    pub waited: usize,
+}
+
+/// Messages waiting for one evaluator's event pump
+pub type Inbox = VecDeque<Message>;
+
+/// Capability a script must hold to publish or subscribe to `topic`
+pub fn topic_capability(topic: &str) -> String {
+    let namespace = topic.split('.').next().unwrap_or(topic);
+    format!("Bus.{}", namespace)
+}
+
+/// Topic subscriptions of the evaluators sharing a bus
+///
+/// Inboxes are held weakly: an evaluator that is dropped stops receiving
+/// messages without unsubscribing.
+#[derive(Debug, Default)]
+pub struct MessageBus {
+    subscriptions: Vec<(String, WeakShared<Inbox>)>,
+}
+
+impl MessageBus {
+    /// Create a bus without subscribers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Deliver messages published on `topic` to `inbox`
+    ///
+    /// Subscribing an inbox to a topic twice delivers each message once.
+    pub fn subscribe(&mut self, topic: &str, inbox: &Shared<Inbox>) {
+        let inbox = Shared::downgrade(inbox);
+        let subscribed = self
+            .subscriptions
+            .iter()
+            .any(|(existing, subscriber)| existing == topic && subscriber.ptr_eq(&inbox));
+        if !subscribed {
+            self.subscriptions.push((topic.to_string(), inbox));
+        }
+    }
+
+    /// Copy `value` into the inbox of every subscriber to `topic`,
+    /// returning how many received it
+    pub fn publish(&mut self, topic: &str, value: &Value) -> usize {
+        self.subscriptions.retain(|(_, inbox)| inbox.strong_count() > 0);
+        let mut delivered = 0;
+        for (_, inbox) in self.subscriptions.iter().filter(|(subscribed, _)| subscribed == topic) {
+            if let Some(inbox) = inbox.upgrade() {
+                lock(&inbox).push_back(Message { topic: topic.to_string(), value: value.clone(), waited: 0 });
+                delivered += 1;
+            }
+        }
+        delivered
+    }
+
+    /// Number of live subscribers to `topic`
+    pub fn subscriber_count(&self, topic: &str) -> usize {
+        self.subscriptions
+            .iter()
+            .filter(|(subscribed, inbox)| subscribed == topic && inbox.strong_count() > 0)
+            .count()
+    }
+}
+
+/// Passes over a message may take before it is delivered regardless of
+/// priority
+pub const DEFAULT_STARVATION_LIMIT: usize = 64;
+
+/// Limits on what one event pump delivers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PumpPolicy {
+    /// Messages delivered per pump at most; `None` empties the inbox
+    pub max_events: Option<usize>,
+    /// Messages of one topic delivered per pump at most
+    pub topic_budget: Option<usize>,
+    /// Passes over after which a message is delivered next
+    pub starvation_limit: usize,
+}
+
+impl Default for PumpPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PumpPolicy {
+    /// Deliver everything, by priority, with the default starvation limit
+    pub fn new() -> Self {
+        PumpPolicy { max_events: None, topic_budget: None, starvation_limit: DEFAULT_STARVATION_LIMIT }
+    }
+
+    /// Deliver at most `max` messages per pump
+    pub fn max_events(mut self, max: usize) -> Self {
+        self.max_events = Some(max);
+        self
+    }
+
+    /// Deliver at most `budget` messages of each topic per pump
+    pub fn topic_budget(mut self, budget: usize) -> Self {
+        self.topic_budget = Some(budget);
+        self
+    }
+
+    /// Deliver a message next once it has been passed over `limit` times
+    pub fn starvation_limit(mut self, limit: usize) -> Self {
+        self.starvation_limit = limit;
+        self
+    }
+
+    /// Take the message to deliver next from `inbox`, given the priority of
+    /// each topic, and count it against `pump`
+    pub fn next(&self, inbox: &mut Inbox, pump: &mut Pump, priority: impl Fn(&str) -> i64) -> Next {
+        if inbox.is_empty() {
+            return Next::Empty;
+        }
+        if self.max_events.is_some_and(|max| pump.delivered >= max) {
+            return Next::OverBudget;
+        }
+        let eligible = |message: &Message| {
+            self.topic_budget.is_none_or(|budget| pump.by_topic.get(&message.topic).copied().unwrap_or(0) < budget)
+        };
+
+        let starving = inbox.iter().position(|message| eligible(message) && message.waited >= self.starvation_limit);
+        let chosen = starving.or_else(|| {
+            let mut best: Option<(usize, i64)> = None;
+            for (index, message) in inbox.iter().enumerate().filter(|(_, message)| eligible(message)) {
+                let rank = priority(&message.topic);
+                if best.is_none_or(|(_, best_rank)| rank > best_rank) {
+                    best = Some((index, rank));
+                }
+            }
+            best.map(|(index, _)| index)
+        });
+        let Some(index) = chosen else {
+            return Next::OverBudget;
+        };
+
+        for message in inbox.iter_mut().take(index) {
+            if eligible(message) {
+                message.waited += 1;
+            }
+        }
+        let Some(message) = inbox.remove(index) else {
+            return Next::Empty;
+        };
+        pump.delivered += 1;
+        *pump.by_topic.entry(message.topic.clone()).or_insert(0) += 1;
+        Next::Deliver { message, starved: starving.is_some() }
+    }
+}
+
+/// Messages one event pump has delivered so far
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Pump {
+    delivered: usize,
+    by_topic: BTreeMap<String, usize>,
+}
+
+/// What [`PumpPolicy::next`] chose
+#[derive(Debug, Clone, PartialEq)]
+pub enum Next {
+    /// Deliver this message; `starved` if it jumped ahead for waiting too long
+    Deliver { message: Message, starved: bool },
+    /// Nothing is waiting
+    Empty,
+    /// Messages are waiting, but this pump's budgets are spent
+    OverBudget,
+}
+
+/// Counts kept across an evaluator's event pumps
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PumpStats {
+    /// Messages delivered, by topic
+    pub delivered: BTreeMap<String, u64>,
+    /// Messages delivered only after hitting the starvation limit, by topic
+    pub starved: BTreeMap<String, u64>,
+    /// Pumps that left messages waiting because their budgets were spent
+    pub over_budget: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dropped_inboxes_stop_receiving() {
+        let mut bus = MessageBus::new();
+        let kept = crate::sync::shared(Inbox::new());
+        let dropped = crate::sync::shared(Inbox::new());
+        bus.subscribe("jobs.done", &kept);
+        bus.subscribe("jobs.done", &kept);
+        bus.subscribe("jobs.done", &dropped);
+        assert_eq!(bus.subscriber_count("jobs.done"), 2);
+
+        drop(dropped);
+        assert_eq!(bus.publish("jobs.done", &Value::Nothing), 1);
+        assert_eq!(bus.publish("jobs.other", &Value::Nothing), 0);
+        assert_eq!(lock(&kept).len(), 1);
+        assert_eq!(bus.subscriber_count("jobs.done"), 1);
+    }
+
+    fn inbox(topics: &[&str]) -> Inbox {
+        topics.iter().map(|topic| Message { topic: topic.to_string(), value: Value::Nothing, waited: 0 }).collect()
+    }
+
+    fn order(policy: &PumpPolicy, inbox: &mut Inbox, pump: &mut Pump) -> Vec<(String, bool)> {
+        let priority = |topic: &str| if topic.starts_with("hi") { 1 } else { 0 };
+        let mut delivered = Vec::new();
+        while let Next::Deliver { message, starved } = policy.next(inbox, pump, priority) {
+            delivered.push((message.topic, starved));
+        }
+        delivered
+    }
+
+    #[test]
+    fn test_priority_budget_and_starvation() {
+        let mut queued = inbox(&["lo.a", "hi.a", "hi.b", "hi.c"]);
+        let starving = PumpPolicy::new().starvation_limit(2);
+        let delivered = order(&starving, &mut queued, &mut Pump::default());
+        let topics: Vec<&str> = delivered.iter().map(|(topic, _)| topic.as_str()).collect();
+        assert_eq!(topics, ["hi.a", "hi.b", "lo.a", "hi.c"]);
+        assert!(delivered[2].1);
+
+        let mut queued = inbox(&["hi.a", "hi.a", "hi.a", "lo.a"]);
+        let budgeted = PumpPolicy::new().topic_budget(1).max_events(3);
+        let mut pump = Pump::default();
+        assert_eq!(order(&budgeted, &mut queued, &mut pump).len(), 2);
+        assert_eq!(budgeted.next(&mut queued, &mut pump, |_| 0), Next::OverBudget);
+        assert_eq!(queued.len(), 2);
+    }
+
+    #[test]
+    fn test_topic_capability_uses_the_namespace() {
+        assert_eq!(topic_capability("power.shutdown.requested"), "Bus.power");
+        assert_eq!(topic_capability("alerts"), "Bus.alerts");
+    }
+}
@@ -17,6 +17,14 @@
 //! ```
 //!
 //! Most instructions use register operands (r0-r255).
+//!
+//! ## Versioning
+//!
+//! Every chunk carries the [`BYTECODE_VERSION`] it was compiled for, and
+//! [`OPCODES`] records the version each opcode appeared in. The VM runs
+//! chunks from [`MIN_BYTECODE_VERSION`] up to the current version and
+//! rejects anything else before executing a single instruction.
+//! [`instruction_set_docs`] renders the table as a Markdown reference.
 
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
@@ -222,6 +230,270 @@ pub enum Instruction {
 
     /// Print for debugging: `print(r[src])`
     Print { src: Register },
+
+    // ===== Variant Instructions =====
+
+    /// Create a variant case: `r[dest] = Enum.Case(r[field_start]..)`, with the
+    /// enum and case names in `constants[enum_id]` and `constants[case_id]`
+    CreateVariant {
+        dest: Register,
+        enum_id: ConstantId,
+        case_id: ConstantId,
+        field_start: Register,
+        field_count: u8,
+    },
+
+    /// Check a variant's case: `r[dest] = r[value] is the case named constants[case_id]`
+    /// (Triumph, Mishap, Present and Absent included)
+    IsVariant { dest: Register, value: Register, case_id: ConstantId },
+
+    /// Extract a field of any variant: `r[dest] = r[value].fields[index]`,
+    /// indexed like the native enum layout's field slots
+    ExtractField { dest: Register, value: Register, index: u8 },
+
+    // ===== Text Builder Instructions =====
+
+    /// Create an empty text builder: `r[dest] = text_builder()`
+    CreateTextBuilder { dest: Register },
+
+    /// Append the text form of a value in place: `r[dest] = text_push(r[builder], r[value])`
+    TextPush { dest: Register, builder: Register, value: Register },
+
+    /// Append a Text in place: `r[dest] = text_push_text(r[builder], r[value])`
+    TextPushText { dest: Register, builder: Register, value: Register },
+
+    /// Copy out the text built so far: `r[dest] = text_build(r[builder])`
+    TextBuild { dest: Register, builder: Register },
+
+    // ===== Text Pattern Instructions =====
+
+    /// Strip an affix: `r[dest] = Present(rest)` if `r[value]` is Text
+    /// starting (or, with `suffix`, ending) with `constants[affix_id]`,
+    /// else `Absent`
+    StripAffix { dest: Register, value: Register, affix_id: ConstantId, suffix: bool },
+
+    // ===== Tuple Instructions =====
+
+    /// Create tuple: `r[dest] = (r[start]..r[start+count-1])`
+    CreateTuple { dest: Register, start: Register, count: u8 },
+
+    /// Check tuple shape: `r[dest] = r[value] is a tuple of count elements`
+    IsTuple { dest: Register, value: Register, count: u8 },
+
+    /// Project a tuple element: `r[dest] = r[tuple].index`
+    TupleGet { dest: Register, tuple: Register, index: u8 },
+}
+
+/// Bytecode format version written into every compiled chunk
+///
+/// Bump this when an opcode is added or the meaning of an existing one
+/// changes; opcodes record the version that introduced them in [`OPCODES`].
+pub const BYTECODE_VERSION: u16 = 7;
+
+/// Oldest chunk version the VM still runs
+pub const MIN_BYTECODE_VERSION: u16 = 1;
+
+/// Reference entry for one opcode of the instruction set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpcodeInfo {
+    /// Opcode byte (the instruction's position in [`Instruction`])
+    pub opcode: u8,
+    /// `Instruction` variant name
+    pub name: &'static str,
+    /// Mnemonic used by the disassembler
+    pub mnemonic: &'static str,
+    /// Operand names, in encoding order
+    pub operands: &'static [&'static str],
+    /// Effect on registers, globals and the handler stack
+    pub stack_effect: &'static str,
+    /// Bytecode version that introduced the opcode
+    pub since: u16,
+}
+
+const fn opcode(
+    opcode: u8,
+    name: &'static str,
+    mnemonic: &'static str,
+    operands: &'static [&'static str],
+    stack_effect: &'static str,
+    since: u16,
+) -> OpcodeInfo {
+    OpcodeInfo { opcode, name, mnemonic, operands, stack_effect, since }
+}
+
+/// The instruction set, indexed by opcode
+pub const OPCODES: &[OpcodeInfo] = &[
+    opcode(0, "LoadConst", "LOAD_CONST", &["dest", "constant_id"], "r[dest] = constants[id]", 1),
+    opcode(1, "Move", "MOVE", &["dest", "src"], "r[dest] = r[src]", 1),
+    opcode(2, "LoadNothing", "LOAD_NOTHING", &["dest"], "r[dest] = nothing", 1),
+    opcode(3, "LoadTruth", "LOAD_TRUTH", &["dest", "value"], "r[dest] = true/false", 1),
+    opcode(4, "AddNum", "ADD_NUM", &["dest", "left", "right"], "r[dest] = r[left] + r[right]", 1),
+    opcode(5, "SubNum", "SUB_NUM", &["dest", "left", "right"], "r[dest] = r[left] - r[right]", 1),
+    opcode(6, "MulNum", "MUL_NUM", &["dest", "left", "right"], "r[dest] = r[left] * r[right]", 1),
+    opcode(7, "DivNum", "DIV_NUM", &["dest", "left", "right"], "r[dest] = r[left] / r[right]", 1),
+    opcode(8, "ModNum", "MOD_NUM", &["dest", "left", "right"], "r[dest] = r[left] % r[right]", 1),
+    opcode(9, "NegNum", "NEG_NUM", &["dest", "src"], "r[dest] = -r[src]", 1),
+    opcode(10, "ConcatText", "CONCAT_TEXT", &["dest", "left", "right"], "r[dest] = r[left] + r[right]", 1),
+    opcode(11, "Eq", "EQ", &["dest", "left", "right"], "r[dest] = r[left] == r[right]", 1),
+    opcode(12, "Ne", "NE", &["dest", "left", "right"], "r[dest] = r[left] != r[right]", 1),
+    opcode(13, "Lt", "LT", &["dest", "left", "right"], "r[dest] = r[left] < r[right]", 1),
+    opcode(14, "Le", "LE", &["dest", "left", "right"], "r[dest] = r[left] <= r[right]", 1),
+    opcode(15, "Gt", "GT", &["dest", "left", "right"], "r[dest] = r[left] > r[right]", 1),
+    opcode(16, "Ge", "GE", &["dest", "left", "right"], "r[dest] = r[left] >= r[right]", 1),
+    opcode(17, "Not", "NOT", &["dest", "src"], "r[dest] = not r[src]", 1),
+    opcode(18, "And", "AND", &["dest", "left", "right"], "r[dest] = r[left] and r[right]", 1),
+    opcode(19, "Or", "OR", &["dest", "left", "right"], "r[dest] = r[left] or r[right]", 1),
+    opcode(20, "Jump", "JUMP", &["offset"], "pc += offset", 1),
+    opcode(21, "JumpIfTrue", "JUMP_IF_TRUE", &["cond", "offset"], "if r[cond] then pc += offset", 1),
+    opcode(22, "JumpIfFalse", "JUMP_IF_FALSE", &["cond", "offset"], "if not r[cond] then pc += offset", 1),
+    opcode(23, "DefineGlobal", "DEF_GLOBAL", &["name_id", "src"], "globals[name] = r[src]", 1),
+    opcode(24, "LoadGlobal", "LOAD_GLOBAL", &["dest", "name_id"], "r[dest] = globals[name]", 1),
+    opcode(25, "StoreGlobal", "STORE_GLOBAL", &["name_id", "src"], "globals[name] = r[src]", 1),
+    opcode(26, "LoadLocal", "LOAD_LOCAL", &["dest", "local_index"], "r[dest] = locals[index]", 1),
+    opcode(27, "StoreLocal", "STORE_LOCAL", &["local_index", "src"], "locals[index] = r[src]", 1),
+    opcode(28, "CreateList", "CREATE_LIST", &["dest", "start", "count"], "r[dest] = [r[start]..r[start+count-1]]", 1),
+    opcode(29, "CreateMap", "CREATE_MAP", &["dest"], "r[dest] = {}", 1),
+    opcode(30, "GetIndex", "GET_INDEX", &["dest", "list", "index"], "r[dest] = r[list][r[index]]", 1),
+    opcode(31, "SetIndex", "SET_INDEX", &["list", "index", "value"], "r[list][r[index]] = r[value]", 1),
+    opcode(32, "GetField", "GET_FIELD", &["dest", "map", "field_id"], "r[dest] = r[map].field", 1),
+    opcode(33, "SetField", "SET_FIELD", &["map", "field_id", "value"], "r[map].field = r[value]", 1),
+    opcode(34, "Call", "CALL", &["dest", "func", "arg_start", "arg_count"], "r[dest] = r[func](r[arg_start]..r[arg_start+arg_count-1])", 1),
+    opcode(35, "Return", "RETURN", &["value"], "return r[value]", 1),
+    opcode(36, "CreateClosure", "CREATE_CLOSURE", &["dest", "function_id", "capture_count"], "r[dest] = closure(function_id, captured)", 1),
+    opcode(37, "CreateTriumph", "CREATE_TRIUMPH", &["dest", "value"], "r[dest] = Triumph(r[value])", 1),
+    opcode(38, "CreateMishap", "CREATE_MISHAP", &["dest", "value"], "r[dest] = Mishap(r[value])", 1),
+    opcode(39, "CreatePresent", "CREATE_PRESENT", &["dest", "value"], "r[dest] = Present(r[value])", 1),
+    opcode(40, "CreateAbsent", "CREATE_ABSENT", &["dest"], "r[dest] = Absent", 1),
+    opcode(41, "IsTriumph", "IS_TRIUMPH", &["dest", "value"], "r[dest] = is_triumph(r[value])", 1),
+    opcode(42, "IsMishap", "IS_MISHAP", &["dest", "value"], "r[dest] = is_mishap(r[value])", 1),
+    opcode(43, "IsPresent", "IS_PRESENT", &["dest", "value"], "r[dest] = is_present(r[value])", 1),
+    opcode(44, "IsAbsent", "IS_ABSENT", &["dest", "value"], "r[dest] = is_absent(r[value])", 1),
+    opcode(45, "ExtractInner", "EXTRACT_INNER", &["dest", "value"], "r[dest] = r[value].inner", 1),
+    opcode(46, "CreateStruct", "CREATE_STRUCT", &["dest", "struct_def_id", "field_start", "field_count"], "r[dest] = struct(struct_def_id, r[field_start]..r[field_start+field_count-1])", 1),
+    opcode(47, "SetupTry", "SETUP_TRY", &["handler_offset"], "handlers.push(handler_offset)", 1),
+    opcode(48, "PopTry", "POP_TRY", &[], "handlers.pop()", 1),
+    opcode(49, "Throw", "THROW", &["error_reg"], "raise r[error_reg]", 1),
+    opcode(50, "Halt", "HALT", &[], "stop with r[0]", 1),
+    opcode(51, "Print", "PRINT", &["src"], "print(r[src])", 1),
+    opcode(52, "CreateVariant", "CREATE_VARIANT", &["dest", "enum_id", "case_id", "field_start", "field_count"], "r[dest] = case(case_id, r[field_start]..r[field_start+field_count-1])", 2),
+    opcode(53, "IsVariant", "IS_VARIANT", &["dest", "value", "case_id"], "r[dest] = r[value] is case(case_id)", 2),
+    opcode(54, "ExtractField", "EXTRACT_FIELD", &["dest", "value", "index"], "r[dest] = r[value].fields[index]", 2),
+    opcode(55, "CreateTextBuilder", "CREATE_BUILDER", &["dest"], "r[dest] = text_builder()", 3),
+    opcode(56, "TextPush", "TEXT_PUSH", &["dest", "builder", "value"], "r[builder] += to_text(r[value]); r[dest] = r[builder]", 3),
+    opcode(57, "TextPushText", "TEXT_PUSH_TEXT", &["dest", "builder", "value"], "r[builder] += r[value] (Text); r[dest] = r[builder]", 3),
+    opcode(58, "TextBuild", "TEXT_BUILD", &["dest", "builder"], "r[dest] = text of r[builder]", 3),
+    opcode(59, "StripAffix", "STRIP_AFFIX", &["dest", "value", "affix_id", "suffix"], "r[dest] = Present(r[value] without affix) or Absent", 4),
+    opcode(60, "CreateTuple", "CREATE_TUPLE", &["dest", "start", "count"], "r[dest] = (r[start]..r[start+count-1])", 6),
+    opcode(61, "IsTuple", "IS_TUPLE", &["dest", "value", "count"], "r[dest] = r[value] is a tuple of count elements", 6),
+    opcode(62, "TupleGet", "TUPLE_GET", &["dest", "tuple", "index"], "r[dest] = r[tuple].index", 6),
+];
+
+impl Instruction {
+    /// Opcode byte of this instruction
+    pub fn opcode(&self) -> u8 {
+        match self {
+            Instruction::LoadConst { .. } => 0,
+            Instruction::Move { .. } => 1,
+            Instruction::LoadNothing { .. } => 2,
+            Instruction::LoadTruth { .. } => 3,
+            Instruction::AddNum { .. } => 4,
+            Instruction::SubNum { .. } => 5,
+            Instruction::MulNum { .. } => 6,
+            Instruction::DivNum { .. } => 7,
+            Instruction::ModNum { .. } => 8,
+            Instruction::NegNum { .. } => 9,
+            Instruction::ConcatText { .. } => 10,
+            Instruction::Eq { .. } => 11,
+            Instruction::Ne { .. } => 12,
+            Instruction::Lt { .. } => 13,
+            Instruction::Le { .. } => 14,
+            Instruction::Gt { .. } => 15,
+            Instruction::Ge { .. } => 16,
+            Instruction::Not { .. } => 17,
+            Instruction::And { .. } => 18,
+            Instruction::Or { .. } => 19,
+            Instruction::Jump { .. } => 20,
+            Instruction::JumpIfTrue { .. } => 21,
+            Instruction::JumpIfFalse { .. } => 22,
+            Instruction::DefineGlobal { .. } => 23,
+            Instruction::LoadGlobal { .. } => 24,
+            Instruction::StoreGlobal { .. } => 25,
+            Instruction::LoadLocal { .. } => 26,
+            Instruction::StoreLocal { .. } => 27,
+            Instruction::CreateList { .. } => 28,
+            Instruction::CreateMap { .. } => 29,
+            Instruction::GetIndex { .. } => 30,
+            Instruction::SetIndex { .. } => 31,
+            Instruction::GetField { .. } => 32,
+            Instruction::SetField { .. } => 33,
+            Instruction::Call { .. } => 34,
+            Instruction::Return { .. } => 35,
+            Instruction::CreateClosure { .. } => 36,
+            Instruction::CreateTriumph { .. } => 37,
+            Instruction::CreateMishap { .. } => 38,
+            Instruction::CreatePresent { .. } => 39,
+            Instruction::CreateAbsent { .. } => 40,
+            Instruction::IsTriumph { .. } => 41,
+            Instruction::IsMishap { .. } => 42,
+            Instruction::IsPresent { .. } => 43,
+            Instruction::IsAbsent { .. } => 44,
+            Instruction::ExtractInner { .. } => 45,
+            Instruction::CreateStruct { .. } => 46,
+            Instruction::SetupTry { .. } => 47,
+            Instruction::PopTry => 48,
+            Instruction::Throw { .. } => 49,
+            Instruction::Halt => 50,
+            Instruction::Print { .. } => 51,
+            Instruction::CreateVariant { .. } => 52,
+            Instruction::IsVariant { .. } => 53,
+            Instruction::ExtractField { .. } => 54,
+            Instruction::CreateTextBuilder { .. } => 55,
+            Instruction::TextPush { .. } => 56,
+            Instruction::TextPushText { .. } => 57,
+            Instruction::TextBuild { .. } => 58,
+            Instruction::StripAffix { .. } => 59,
+            Instruction::CreateTuple { .. } => 60,
+            Instruction::IsTuple { .. } => 61,
+            Instruction::TupleGet { .. } => 62,
+        }
+    }
+
+    /// Reference entry for this instruction's opcode
+    pub fn info(&self) -> &'static OpcodeInfo {
+        &OPCODES[self.opcode() as usize]
+    }
+
+    /// Whether the VM checks deadlines before this instruction: backward
+    /// jumps (loop back-edges) and calls
+    pub fn is_safepoint(&self) -> bool {
+        match self {
+            Instruction::Jump { offset }
+            | Instruction::JumpIfTrue { offset, .. }
+            | Instruction::JumpIfFalse { offset, .. } => *offset < 0,
+            Instruction::Call { .. } => true,
+            _ => false,
+        }
+    }
+}
+
+/// Render the instruction set reference as a Markdown table
+pub fn instruction_set_docs() -> String {
+    use alloc::format;
+
+    let mut output = format!("# Quicksilver instruction set (bytecode version {})\n\n", BYTECODE_VERSION);
+    output.push_str("| Opcode | Mnemonic | Operands | Effect | Since |\n");
+    output.push_str("|-------:|----------|----------|--------|------:|\n");
+    for info in OPCODES {
+        output.push_str(&format!(
+            "| {} | `{}` | {} | `{}` | {} |\n",
+            info.opcode,
+            info.mnemonic,
+            info.operands.join(", "),
+            info.stack_effect,
+            info.since
+        ));
+    }
+    output
 }
 
 /// Constant value in the constant pool
@@ -229,6 +501,10 @@ pub enum Instruction {
 pub enum Constant {
     /// Number constant
     Number(f64),
+    /// Big integer constant
+    BigInt(crate::bigint::BigInt),
+    /// 64-bit integer constant
+    Integer(i64),
     /// Text constant
     Text(String),
     /// Truth constant
@@ -267,6 +543,9 @@ pub struct BytecodeChunk {
 
     /// Number of local variables
     pub local_count: u8,
+
+    /// Bytecode format version the chunk was compiled for
+    pub version: u16,
 }
 
 impl BytecodeChunk {
@@ -279,6 +558,7 @@ impl BytecodeChunk {
             name,
             param_count: 0,
             local_count: 0,
+            version: BYTECODE_VERSION,
         }
     }
 
@@ -308,15 +588,21 @@ impl BytecodeChunk {
     }
 
     /// Patch a jump instruction at the given offset
-    pub fn patch_jump(&mut self, jump_offset: usize, target_offset: usize) {
+    ///
+    /// Jumps are relative; a `SetupTry` handler offset is absolute.
+    pub fn patch_jump(&mut self, jump_offset: usize, target_offset: usize) -> Result<(), String> {
+        use alloc::format;
+
         let relative_offset = (target_offset as isize - jump_offset as isize - 1) as i16;
 
-        match &mut self.instructions[jump_offset] {
-            Instruction::Jump { offset } => *offset = relative_offset,
-            Instruction::JumpIfTrue { offset, .. } => *offset = relative_offset,
-            Instruction::JumpIfFalse { offset, .. } => *offset = relative_offset,
-            _ => panic!("Attempted to patch non-jump instruction"),
+        match self.instructions.get_mut(jump_offset) {
+            Some(Instruction::Jump { offset }) => *offset = relative_offset,
+            Some(Instruction::JumpIfTrue { offset, .. }) => *offset = relative_offset,
+            Some(Instruction::JumpIfFalse { offset, .. }) => *offset = relative_offset,
+            Some(Instruction::SetupTry { handler_offset }) => *handler_offset = target_offset,
+            _ => return Err(format!("Instruction {} is not a jump", jump_offset)),
         }
+        Ok(())
     }
 }
 
@@ -325,6 +611,8 @@ impl Constant {
     pub fn type_name(&self) -> &str {
         match self {
             Constant::Number(_) => "Number",
+            Constant::BigInt(_) => "BigInt",
+            Constant::Integer(_) => "Integer",
             Constant::Text(_) => "Text",
             Constant::Truth(_) => "Truth",
             Constant::Nothing => "Nothing",
@@ -349,6 +637,7 @@ impl<'a> Disassembler<'a> {
         use alloc::format;
 
         let mut output = format!("==== {} ====\n", self.chunk.name);
+        output.push_str(&format!("Version: {}\n", self.chunk.version));
         output.push_str(&format!("Parameters: {}\n", self.chunk.param_count));
         output.push_str(&format!("Locals: {}\n", self.chunk.local_count));
         output.push_str(&format!("Constants: {}\n", self.chunk.constants.len()));
@@ -521,6 +810,42 @@ impl<'a> Disassembler<'a> {
             Instruction::ExtractInner { dest, value } => {
                 format!("EXTRACT_INNER  r{} <- r{}.inner", dest, value)
             }
+            Instruction::CreateVariant { dest, enum_id, case_id, field_start, field_count } => {
+                format!("CREATE_VARIANT r{} <- #{}.#{}(r{}, {} fields)", dest, enum_id, case_id, field_start, field_count)
+            }
+            Instruction::IsVariant { dest, value, case_id } => {
+                format!("IS_VARIANT     r{} <- r{} is #{}", dest, value, case_id)
+            }
+            Instruction::ExtractField { dest, value, index } => {
+                format!("EXTRACT_FIELD  r{} <- r{}.fields[{}]", dest, value, index)
+            }
+            // Text builder instructions
+            Instruction::CreateTextBuilder { dest } => {
+                format!("CREATE_BUILDER r{}", dest)
+            }
+            Instruction::TextPush { dest, builder, value } => {
+                format!("TEXT_PUSH      r{} <- r{} += to_text(r{})", dest, builder, value)
+            }
+            Instruction::TextPushText { dest, builder, value } => {
+                format!("TEXT_PUSH_TEXT r{} <- r{} += r{}", dest, builder, value)
+            }
+            Instruction::TextBuild { dest, builder } => {
+                format!("TEXT_BUILD     r{} <- text(r{})", dest, builder)
+            }
+            Instruction::StripAffix { dest, value, affix_id, suffix } => {
+                let side = if *suffix { "suffix" } else { "prefix" };
+                format!("STRIP_AFFIX    r{} <- r{} without {} #{}", dest, value, side, affix_id)
+            }
+            // Tuple instructions
+            Instruction::CreateTuple { dest, start, count } => {
+                format!("CREATE_TUPLE   r{} <- (r{}..r{})", dest, start, start + count - 1)
+            }
+            Instruction::IsTuple { dest, value, count } => {
+                format!("IS_TUPLE       r{} <- r{} is tuple of {}", dest, value, count)
+            }
+            Instruction::TupleGet { dest, tuple, index } => {
+                format!("TUPLE_GET      r{} <- r{}.{}", dest, tuple, index)
+            }
             // Struct instructions
             Instruction::CreateStruct { dest, struct_def_id, field_start, field_count } => {
                 format!("CREATE_STRUCT  r{} <- struct(#{}, r{}..r{} ({} fields))",
@@ -587,7 +912,8 @@ mod tests {
         chunk.emit(Instruction::Return { value: 0 }, 4);  // offset 3
 
         // Patch jump from 0 to 3 (skip two instructions)
-        chunk.patch_jump(0, 3);
+        chunk.patch_jump(0, 3).unwrap();
+        assert!(chunk.patch_jump(1, 3).is_err());
 
         if let Instruction::Jump { offset } = chunk.instructions[0] {
             assert_eq!(offset, 2); // Jump forward by 2 instructions
@@ -611,4 +937,103 @@ mod tests {
         assert!(output.contains("LOAD_CONST"));
         assert!(output.contains("RETURN"));
     }
+
+    /// One instance of every instruction, in opcode order
+    fn every_instruction() -> Vec<Instruction> {
+        vec![
+            Instruction::LoadConst { dest: 0, constant_id: 0 },
+            Instruction::Move { dest: 0, src: 1 },
+            Instruction::LoadNothing { dest: 0 },
+            Instruction::LoadTruth { dest: 0, value: true },
+            Instruction::AddNum { dest: 0, left: 1, right: 2 },
+            Instruction::SubNum { dest: 0, left: 1, right: 2 },
+            Instruction::MulNum { dest: 0, left: 1, right: 2 },
+            Instruction::DivNum { dest: 0, left: 1, right: 2 },
+            Instruction::ModNum { dest: 0, left: 1, right: 2 },
+            Instruction::NegNum { dest: 0, src: 1 },
+            Instruction::ConcatText { dest: 0, left: 1, right: 2 },
+            Instruction::Eq { dest: 0, left: 1, right: 2 },
+            Instruction::Ne { dest: 0, left: 1, right: 2 },
+            Instruction::Lt { dest: 0, left: 1, right: 2 },
+            Instruction::Le { dest: 0, left: 1, right: 2 },
+            Instruction::Gt { dest: 0, left: 1, right: 2 },
+            Instruction::Ge { dest: 0, left: 1, right: 2 },
+            Instruction::Not { dest: 0, src: 1 },
+            Instruction::And { dest: 0, left: 1, right: 2 },
+            Instruction::Or { dest: 0, left: 1, right: 2 },
+            Instruction::Jump { offset: 1 },
+            Instruction::JumpIfTrue { cond: 0, offset: 1 },
+            Instruction::JumpIfFalse { cond: 0, offset: 1 },
+            Instruction::DefineGlobal { name_id: 0, src: 1 },
+            Instruction::LoadGlobal { dest: 0, name_id: 0 },
+            Instruction::StoreGlobal { name_id: 0, src: 1 },
+            Instruction::LoadLocal { dest: 0, local_index: 0 },
+            Instruction::StoreLocal { local_index: 0, src: 1 },
+            Instruction::CreateList { dest: 0, start: 1, count: 2 },
+            Instruction::CreateMap { dest: 0 },
+            Instruction::GetIndex { dest: 0, list: 1, index: 2 },
+            Instruction::SetIndex { list: 0, index: 1, value: 2 },
+            Instruction::GetField { dest: 0, map: 1, field_id: 0 },
+            Instruction::SetField { map: 0, field_id: 0, value: 1 },
+            Instruction::Call { dest: 0, func: 1, arg_start: 2, arg_count: 1 },
+            Instruction::Return { value: 0 },
+            Instruction::CreateClosure { dest: 0, function_id: 0, capture_count: 0 },
+            Instruction::CreateTriumph { dest: 0, value: 1 },
+            Instruction::CreateMishap { dest: 0, value: 1 },
+            Instruction::CreatePresent { dest: 0, value: 1 },
+            Instruction::CreateAbsent { dest: 0 },
+            Instruction::IsTriumph { dest: 0, value: 1 },
+            Instruction::IsMishap { dest: 0, value: 1 },
+            Instruction::IsPresent { dest: 0, value: 1 },
+            Instruction::IsAbsent { dest: 0, value: 1 },
+            Instruction::ExtractInner { dest: 0, value: 1 },
+            Instruction::CreateStruct { dest: 0, struct_def_id: 0, field_start: 1, field_count: 1 },
+            Instruction::SetupTry { handler_offset: 0 },
+            Instruction::PopTry,
+            Instruction::Throw { error_reg: 0 },
+            Instruction::Halt,
+            Instruction::Print { src: 0 },
+            Instruction::CreateVariant { dest: 0, enum_id: 0, case_id: 1, field_start: 1, field_count: 2 },
+            Instruction::IsVariant { dest: 0, value: 1, case_id: 0 },
+            Instruction::ExtractField { dest: 0, value: 1, index: 0 },
+            Instruction::CreateTextBuilder { dest: 0 },
+            Instruction::TextPush { dest: 0, builder: 0, value: 1 },
+            Instruction::TextPushText { dest: 0, builder: 0, value: 1 },
+            Instruction::TextBuild { dest: 0, builder: 1 },
+            Instruction::StripAffix { dest: 0, value: 1, affix_id: 0, suffix: true },
+            Instruction::CreateTuple { dest: 0, start: 1, count: 2 },
+            Instruction::IsTuple { dest: 0, value: 1, count: 2 },
+            Instruction::TupleGet { dest: 0, tuple: 1, index: 0 },
+        ]
+    }
+
+    #[test]
+    fn test_opcode_table_matches_instructions() {
+        let instructions = every_instruction();
+        assert_eq!(instructions.len(), OPCODES.len());
+
+        let chunk = BytecodeChunk::new("table".to_string());
+        let disasm = Disassembler::new(&chunk);
+        for (i, instruction) in instructions.iter().enumerate() {
+            let info = instruction.info();
+            assert_eq!(instruction.opcode() as usize, i);
+            assert_eq!(info.opcode as usize, i);
+            assert!(format!("{:?}", instruction).starts_with(info.name), "{} vs {:?}", info.name, instruction);
+            assert!(disasm.disassemble_instruction(instruction).starts_with(info.mnemonic));
+            assert!(info.since <= BYTECODE_VERSION);
+        }
+    }
+
+    #[test]
+    fn test_instruction_set_docs() {
+        let docs = instruction_set_docs();
+        assert!(docs.contains("bytecode version 7"));
+        assert!(docs.contains("| 4 | `ADD_NUM` | dest, left, right | `r[dest] = r[left] + r[right]` | 1 |"));
+        assert_eq!(docs.lines().filter(|line| line.starts_with("| ") && !line.starts_with("| Opcode")).count(), OPCODES.len());
+    }
+
+    #[test]
+    fn test_new_chunk_has_current_version() {
+        assert_eq!(BytecodeChunk::new("test".to_string()).version, BYTECODE_VERSION);
+    }
 }
@@ -15,8 +15,9 @@
 //! 3. Generate type-aware instructions
 //! 4. Optimize simple patterns (constant folding, etc.)
 
-use crate::ast::{AstNode, BinaryOperator, UnaryOperator};
+use crate::ast::{order_arguments, AstNode, BinaryOperator, Parameter, UnaryOperator};
 use crate::bytecode::{BytecodeChunk, Constant, Instruction, Register, ConstantId};
+use crate::module_cache::{content_hash, CacheStore};
 use crate::source_location::SourceSpan;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
@@ -33,6 +34,11 @@ pub enum CompileError {
     UndefinedVariable(String),
     /// Unsupported feature
     UnsupportedFeature(String),
+    /// Source failed to parse (`compile_cached` only)
+    ParseError(String),
+    /// The compiler broke one of its own invariants; a compiler bug,
+    /// reported rather than panicking
+    Internal(String),
 }
 
 pub type CompileResult<T> = Result<T, CompileError>;
@@ -101,6 +107,15 @@ pub struct BytecodeCompiler {
     /// Map of function names to their entry points
     /// This allows calling functions by name
     function_table: BTreeMap<String, usize>,
+
+    /// Cases of user-defined variants (case name -> enum name, field count)
+    variant_cases: BTreeMap<String, (String, usize)>,
+
+    /// Parameters of the chants compiled so far, to place named arguments
+    chant_params: BTreeMap<String, Vec<Parameter>>,
+
+    /// Set while compiling a `match` that ends a chant body
+    tail_match: bool,
 }
 
 impl BytecodeCompiler {
@@ -115,6 +130,9 @@ impl BytecodeCompiler {
             current_function: None,
             function_entry: None,
             function_table: BTreeMap::new(),
+            variant_cases: BTreeMap::new(),
+            chant_params: BTreeMap::new(),
+            tail_match: false,
         }
     }
 
@@ -153,14 +171,14 @@ impl BytecodeCompiler {
                     // Global scope
                     let name_id = self.add_string_constant(name.clone());
                     self.emit(Instruction::DefineGlobal { name_id, src: value_reg }, 0);
-                    self.current_scope_mut().variables.insert(name.clone(), VarLocation::Global(name.clone()));
+                    self.current_scope_mut()?.variables.insert(name.clone(), VarLocation::Global(name.clone()));
                 } else {
                     // Local scope
                     let local_index = self.local_count;
                     self.local_count += 1;
                     self.chunk.local_count = self.local_count;
                     self.emit(Instruction::StoreLocal { local_index, src: value_reg }, 0);
-                    self.current_scope_mut().variables.insert(name.clone(), VarLocation::Local(local_index));
+                    self.current_scope_mut()?.variables.insert(name.clone(), VarLocation::Local(local_index));
                 }
 
                 self.free_register(value_reg);
@@ -249,7 +267,7 @@ impl BytecodeCompiler {
 
                 // Patch jump to else
                 let else_offset = self.chunk.offset();
-                self.chunk.patch_jump(jump_to_else, else_offset);
+                self.patch_jump(jump_to_else, else_offset)?;
 
                 // Compile else branch if present
                 if let Some(else_stmts) = else_branch {
@@ -260,7 +278,7 @@ impl BytecodeCompiler {
 
                 // Patch jump over else
                 let end_offset = self.chunk.offset();
-                self.chunk.patch_jump(jump_over_else, end_offset);
+                self.patch_jump(jump_over_else, end_offset)?;
 
                 Ok(None)
             }
@@ -288,7 +306,7 @@ impl BytecodeCompiler {
 
                 // Patch jump to end
                 let end_offset = self.chunk.offset();
-                self.chunk.patch_jump(jump_to_end, end_offset);
+                self.patch_jump(jump_to_end, end_offset)?;
 
                 Ok(None)
             }
@@ -296,6 +314,9 @@ impl BytecodeCompiler {
             AstNode::MatchStmt { value, arms, .. } => {
                 use crate::ast::Pattern;
 
+                // Arms of a match that ends a chant return from it
+                let tail = core::mem::take(&mut self.tail_match);
+
                 // Compile the value to match against
                 let match_value_reg = self.compile_expr(value)?;
 
@@ -308,8 +329,21 @@ impl BytecodeCompiler {
                     self.scopes.push(Scope::new(self.scopes.len()));
                     let scope_local_start = self.local_count;
 
+                    // Where a false guard jumps from, to be patched to the next arm
+                    let mut guard_jump = None;
+
+                    // A bare unit case name (`when Idle`) matches that case rather than binding
+                    let unit_case;
+                    let pattern = match &arm.pattern {
+                        Pattern::Ident(name) if matches!(self.variant_cases.get(name), Some((_, 0))) => {
+                            unit_case = Pattern::Enum { variant: name.clone(), inner: None };
+                            &unit_case
+                        }
+                        pattern => pattern,
+                    };
+
                     // Compile pattern matching logic
-                    match &arm.pattern {
+                    match pattern {
                         Pattern::Literal(lit_node) => {
                             // Compile the literal value
                             let lit_reg = self.compile_expr(lit_node)?;
@@ -329,10 +363,7 @@ impl BytecodeCompiler {
                             self.free_register(cmp_reg);
 
                             // Pattern matched! Execute arm body
-                            let mut result_reg = None;
-                            for stmt in &arm.body {
-                                result_reg = self.compile_stmt(stmt)?;
-                            }
+                            let result_reg = self.compile_arm_body(arm, tail, &mut guard_jump)?;
 
                             // If arm produced a result, move it to a temp register
                             if let Some(reg) = result_reg {
@@ -346,7 +377,7 @@ impl BytecodeCompiler {
 
                             // Patch jump to next arm
                             let next_arm_offset = self.chunk.offset();
-                            self.chunk.patch_jump(jump_to_next_arm, next_arm_offset);
+                            self.patch_jump(jump_to_next_arm, next_arm_offset)?;
                         }
 
                         Pattern::Ident(var_name) => {
@@ -359,16 +390,77 @@ impl BytecodeCompiler {
                                 local_index,
                                 src: match_value_reg
                             }, 0);
-                            self.current_scope_mut().variables.insert(
+                            self.current_scope_mut()?.variables.insert(
                                 var_name.clone(),
                                 VarLocation::Local(local_index)
                             );
 
                             // Execute arm body
-                            let mut result_reg = None;
-                            for stmt in &arm.body {
-                                result_reg = self.compile_stmt(stmt)?;
+                            let result_reg = self.compile_arm_body(arm, tail, &mut guard_jump)?;
+
+                            // Jump to end
+                            if let Some(reg) = result_reg {
+                                jumps_to_end.push((self.chunk.offset(), reg));
+                            } else {
+                                jumps_to_end.push((self.chunk.offset(), match_value_reg));
+                            }
+                            self.emit(Instruction::Jump { offset: 0 }, 0);
+                        }
+
+                        Pattern::Affix { side, affix, rest } => {
+                            // Strip the affix into a Maybe and test it
+                            let affix_id = self.add_string_constant(affix.clone());
+                            let stripped_reg = self.alloc_register()?;
+                            self.emit(Instruction::StripAffix {
+                                dest: stripped_reg,
+                                value: match_value_reg,
+                                affix_id,
+                                suffix: *side == crate::ast::AffixSide::Suffix,
+                            }, 0);
+                            let check_reg = self.alloc_register()?;
+                            self.emit(Instruction::IsPresent { dest: check_reg, value: stripped_reg }, 0);
+
+                            // Jump to next arm if the affix isn't there
+                            self.emit(Instruction::JumpIfFalse { cond: check_reg, offset: 0 }, 0);
+                            let jump_to_next_arm = self.chunk.offset() - 1;
+                            self.free_register(check_reg);
+
+                            // Bind the rest of the text
+                            if let Some(var_name) = rest {
+                                self.emit(Instruction::ExtractInner { dest: stripped_reg, value: stripped_reg }, 0);
+                                let local_index = self.local_count;
+                                self.local_count += 1;
+                                self.chunk.local_count = self.local_count;
+                                self.emit(Instruction::StoreLocal { local_index, src: stripped_reg }, 0);
+                                self.current_scope_mut()?.variables.insert(
+                                    var_name.clone(),
+                                    VarLocation::Local(local_index)
+                                );
+                            }
+                            self.free_register(stripped_reg);
+
+                            // Pattern matched! Execute arm body
+                            let result_reg = self.compile_arm_body(arm, tail, &mut guard_jump)?;
+
+                            // Jump to end
+                            if let Some(reg) = result_reg {
+                                jumps_to_end.push((self.chunk.offset(), reg));
+                            } else {
+                                jumps_to_end.push((self.chunk.offset(), match_value_reg));
                             }
+                            self.emit(Instruction::Jump { offset: 0 }, 0);
+
+                            // Patch jump to next arm
+                            let next_arm_offset = self.chunk.offset();
+                            self.patch_jump(jump_to_next_arm, next_arm_offset)?;
+                        }
+
+                        Pattern::Or(alternatives) => {
+                            // Any equal alternative goes on to the body
+                            let jump_to_next_arm = self.compile_or_alternatives(alternatives, match_value_reg)?;
+
+                            // Pattern matched! Execute arm body
+                            let result_reg = self.compile_arm_body(arm, tail, &mut guard_jump)?;
 
                             // Jump to end
                             if let Some(reg) = result_reg {
@@ -377,15 +469,38 @@ impl BytecodeCompiler {
                                 jumps_to_end.push((self.chunk.offset(), match_value_reg));
                             }
                             self.emit(Instruction::Jump { offset: 0 }, 0);
+
+                            // Patch jump to next arm
+                            let next_arm_offset = self.chunk.offset();
+                            self.patch_jump(jump_to_next_arm, next_arm_offset)?;
+                        }
+
+                        Pattern::Tuple(elements) => {
+                            // Check the shape and every element, binding as we go
+                            let jumps_to_next_arm = self.compile_tuple_pattern(elements, match_value_reg)?;
+
+                            // Pattern matched! Execute arm body
+                            let result_reg = self.compile_arm_body(arm, tail, &mut guard_jump)?;
+
+                            // Jump to end
+                            if let Some(reg) = result_reg {
+                                jumps_to_end.push((self.chunk.offset(), reg));
+                            } else {
+                                jumps_to_end.push((self.chunk.offset(), match_value_reg));
+                            }
+                            self.emit(Instruction::Jump { offset: 0 }, 0);
+
+                            // Patch jumps to next arm
+                            let next_arm_offset = self.chunk.offset();
+                            for jump in jumps_to_next_arm {
+                                self.patch_jump(jump, next_arm_offset)?;
+                            }
                         }
 
                         Pattern::Wildcard => {
                             // Wildcard - always matches, no binding
                             // Execute arm body
-                            let mut result_reg = None;
-                            for stmt in &arm.body {
-                                result_reg = self.compile_stmt(stmt)?;
-                            }
+                            let result_reg = self.compile_arm_body(arm, tail, &mut guard_jump)?;
 
                             // Jump to end
                             if let Some(reg) = result_reg {
@@ -399,15 +514,8 @@ impl BytecodeCompiler {
                         Pattern::Enum { variant, inner } => {
                             // Check variant tag
                             let check_reg = self.alloc_register()?;
-                            let instruction = match variant.as_str() {
-                                "Triumph" => Instruction::IsTriumph { dest: check_reg, value: match_value_reg },
-                                "Mishap" => Instruction::IsMishap { dest: check_reg, value: match_value_reg },
-                                "Present" => Instruction::IsPresent { dest: check_reg, value: match_value_reg },
-                                "Absent" => Instruction::IsAbsent { dest: check_reg, value: match_value_reg },
-                                _ => return Err(CompileError::UnsupportedFeature(
-                                    format!("Unknown enum variant: {}", variant)
-                                )),
-                            };
+                            let user_case = self.variant_cases.get(variant).cloned();
+                            let instruction = self.variant_check(variant, check_reg, match_value_reg)?;
                             self.emit(instruction, 0);
 
                             // Jump to next arm if variant doesn't match
@@ -416,7 +524,9 @@ impl BytecodeCompiler {
                             self.free_register(check_reg);
 
                             // If we have an inner pattern, extract and match it
-                            if let Some(inner_pattern) = inner {
+                            if let (Some(inner_pattern), Some((_, field_count))) = (inner, user_case) {
+                                self.bind_variant_fields(variant, inner_pattern, field_count, match_value_reg)?;
+                            } else if let Some(inner_pattern) = inner {
                                 // Extract inner value (if variant has one)
                                 if variant != "Absent" {
                                     let inner_reg = self.alloc_register()?;
@@ -436,7 +546,7 @@ impl BytecodeCompiler {
                                                 local_index,
                                                 src: inner_reg,
                                             }, 0);
-                                            self.current_scope_mut().variables.insert(
+                                            self.current_scope_mut()?.variables.insert(
                                                 var_name.clone(),
                                                 VarLocation::Local(local_index)
                                             );
@@ -457,10 +567,7 @@ impl BytecodeCompiler {
                             }
 
                             // Pattern matched! Execute arm body
-                            let mut result_reg = None;
-                            for stmt in &arm.body {
-                                result_reg = self.compile_stmt(stmt)?;
-                            }
+                            let result_reg = self.compile_arm_body(arm, tail, &mut guard_jump)?;
 
                             // Jump to end
                             if let Some(reg) = result_reg {
@@ -472,10 +579,15 @@ impl BytecodeCompiler {
 
                             // Patch jump to next arm
                             let next_arm_offset = self.chunk.offset();
-                            self.chunk.patch_jump(jump_to_next_arm, next_arm_offset);
+                            self.patch_jump(jump_to_next_arm, next_arm_offset)?;
                         }
                     }
 
+                    if let Some(jump) = guard_jump {
+                        let next_arm_offset = self.chunk.offset();
+                        self.patch_jump(jump, next_arm_offset)?;
+                    }
+
                     // Pop scope and restore local count
                     self.scopes.pop();
                     self.local_count = scope_local_start;
@@ -484,7 +596,7 @@ impl BytecodeCompiler {
                 // Patch all jumps to end
                 let end_offset = self.chunk.offset();
                 for (jump_offset, _result_reg) in &jumps_to_end {
-                    self.chunk.patch_jump(*jump_offset, end_offset);
+                    self.patch_jump(*jump_offset, end_offset)?;
                 }
 
                 self.free_register(match_value_reg);
@@ -505,6 +617,7 @@ impl BytecodeCompiler {
 
                 // Register function in function table for later calls
                 self.function_table.insert(name.clone(), entry_point);
+                self.chant_params.insert(name.clone(), params.clone());
 
                 // Push new scope for function
                 self.scopes.push(Scope::new(self.scopes.len()));
@@ -514,25 +627,14 @@ impl BytecodeCompiler {
                     let local_index = self.local_count;
                     self.local_count += 1;
                     self.chunk.local_count = self.local_count;
-                    self.current_scope_mut().variables.insert(
+                    self.current_scope_mut()?.variables.insert(
                         param.name.clone(),
                         VarLocation::Local(local_index)
                     );
                 }
 
-                // Compile function body
-                let mut last_reg = None;
-                for stmt in body {
-                    last_reg = self.compile_stmt(stmt)?;
-                }
-
-                // If no explicit yield, return nothing
-                if last_reg.is_none() {
-                    let reg = self.alloc_register()?;
-                    self.emit(Instruction::LoadNothing { dest: reg }, 0);
-                    self.emit(Instruction::Return { value: reg }, 0);
-                    self.free_register(reg);
-                }
+                // Compile function body; without a `yield` it returns its last value
+                self.compile_chant_body(body)?;
 
                 // Restore previous function context
                 self.scopes.pop();
@@ -542,6 +644,14 @@ impl BytecodeCompiler {
                 Ok(None)
             }
 
+            AstNode::VariantDef { name, variants, .. } => {
+                // Cases are built and matched by name; nothing runs at definition
+                for case in variants {
+                    self.variant_cases.insert(case.name.clone(), (name.clone(), case.fields.len()));
+                }
+                Ok(None)
+            }
+
             AstNode::FormDef { name, fields, type_params: _, .. } => {
                 // Create struct definition as a constant
                 let struct_def_id = self.chunk.add_constant(Constant::StructDef {
@@ -570,9 +680,9 @@ impl BytecodeCompiler {
 
             AstNode::YieldStmt { value, .. } => {
                 // Check for tail call (yield f(args) where f is current function)
-                if let AstNode::Call { callee, args, .. } = value.as_ref() {
+                if let AstNode::Call { callee, args, arg_names, .. } = value.as_ref() {
                     if let AstNode::Ident { name: func_name, .. } = callee.as_ref() {
-                        if Some(func_name) == self.current_function.as_ref() {
+                        if Some(func_name) == self.current_function.as_ref() && arg_names.is_empty() {
                             // This is a tail call! Use TCO.
                             // Evaluate arguments
                             let mut arg_regs = Vec::new();
@@ -630,7 +740,7 @@ impl BytecodeCompiler {
 
                 // Patch SetupTry to point to handler code
                 let handler_start = self.chunk.offset();
-                self.chunk.patch_jump(setup_try_index, handler_start);
+                self.patch_jump(setup_try_index, handler_start)?;
 
                 // The error type and value will be in registers set by the VM
                 // r254: error type (as Text)
@@ -692,7 +802,7 @@ impl BytecodeCompiler {
 
                     // Patch jump to next handler
                     let next_handler_offset = self.chunk.offset();
-                    self.chunk.patch_jump(jump_to_next_handler, next_handler_offset);
+                    self.patch_jump(jump_to_next_handler, next_handler_offset)?;
 
                     // Patch jump to end to point past all handlers
                     // We'll patch this at the very end
@@ -705,12 +815,12 @@ impl BytecodeCompiler {
 
                     // Patch the jump to end
                     let end_offset = self.chunk.offset();
-                    self.chunk.patch_jump(jump_to_end, end_offset);
+                    self.patch_jump(jump_to_end, end_offset)?;
                 }
 
                 // Patch jump over handlers
                 let final_offset = self.chunk.offset();
-                self.chunk.patch_jump(jump_over_handlers, final_offset);
+                self.patch_jump(jump_over_handlers, final_offset)?;
 
                 Ok(None)
             }
@@ -744,6 +854,11 @@ impl BytecodeCompiler {
                 Ok(Some(reg))
             }
 
+            // Verify blocks only run under the test runner
+            AstNode::VerifyBlock { .. } => Ok(None),
+            // Applied when parsing and analyzing
+            AstNode::Speaks { .. } => Ok(None),
+
             // === Module System (Phase 5: Bytecode VM Support) ===
             AstNode::ModuleDecl { name, body: _, exports: _, .. } => {
                 // Module declarations in bytecode compilation require multi-file compilation
@@ -784,6 +899,98 @@ impl BytecodeCompiler {
         }
     }
 
+    /// Compile a chant body so that it returns its implicit value
+    ///
+    /// Follows the evaluator's rule: the last statement's value when it is
+    /// an expression, the taken branch's when it is a `should` or `match`,
+    /// and Nothing otherwise.
+    fn compile_chant_body(&mut self, body: &[AstNode]) -> CompileResult<()> {
+        let Some((last, init)) = body.split_last() else {
+            return self.emit_return_nothing();
+        };
+        for stmt in init {
+            self.compile_stmt(stmt)?;
+        }
+
+        match last {
+            AstNode::IfStmt { condition, then_branch, else_branch, .. } => {
+                let cond_reg = self.compile_expr(condition)?;
+                self.emit(Instruction::JumpIfFalse { cond: cond_reg, offset: 0 }, 0);
+                let jump_to_else = self.chunk.offset() - 1;
+                self.free_register(cond_reg);
+
+                self.compile_chant_body(then_branch)?;
+
+                let else_offset = self.chunk.offset();
+                self.patch_jump(jump_to_else, else_offset)?;
+                match else_branch {
+                    Some(else_stmts) => self.compile_chant_body(else_stmts),
+                    None => self.emit_return_nothing(),
+                }
+            }
+            AstNode::MatchStmt { .. } => {
+                self.tail_match = true;
+                self.compile_stmt(last)?;
+                // Reached only when no arm matched
+                self.emit_return_nothing()
+            }
+            AstNode::YieldStmt { .. } => self.compile_stmt(last).map(|_| ()),
+            AstNode::ExprStmt { expr: value, .. } => self.emit_return(value),
+            node if node.is_expression() => self.emit_return(node),
+            node => {
+                self.compile_stmt(node)?;
+                self.emit_return_nothing()
+            }
+        }
+    }
+
+    /// Compile the statements of a match arm; in a tail match they return
+    /// from the chant and leave no result
+    ///
+    /// With the pattern's bindings in place, a guard is tested first; a false
+    /// one jumps from `guard_jump` to the next arm once the caller patches it.
+    fn compile_arm_body(
+        &mut self,
+        arm: &crate::ast::MatchArm,
+        tail: bool,
+        guard_jump: &mut Option<usize>,
+    ) -> CompileResult<Option<Register>> {
+        if let Some(guard) = &arm.guard {
+            let cond = self.compile_expr(guard)?;
+            self.emit(Instruction::JumpIfFalse { cond, offset: 0 }, 0);
+            *guard_jump = Some(self.chunk.offset() - 1);
+            self.free_register(cond);
+        }
+
+        if tail {
+            self.compile_chant_body(&arm.body)?;
+            return Ok(None);
+        }
+
+        let mut result_reg = None;
+        for stmt in &arm.body {
+            result_reg = self.compile_stmt(stmt)?;
+        }
+        Ok(result_reg)
+    }
+
+    /// Return the value of an expression from the current chant
+    fn emit_return(&mut self, value: &AstNode) -> CompileResult<()> {
+        let reg = self.compile_expr(value)?;
+        self.emit(Instruction::Return { value: reg }, 0);
+        self.free_register(reg);
+        Ok(())
+    }
+
+    /// Return Nothing from the current chant
+    fn emit_return_nothing(&mut self) -> CompileResult<()> {
+        let reg = self.alloc_register()?;
+        self.emit(Instruction::LoadNothing { dest: reg }, 0);
+        self.emit(Instruction::Return { value: reg }, 0);
+        self.free_register(reg);
+        Ok(())
+    }
+
     /// Compile an expression (returns register containing result)
     fn compile_expr(&mut self, node: &AstNode) -> CompileResult<Register> {
         match node {
@@ -794,6 +1001,20 @@ impl BytecodeCompiler {
                 Ok(reg)
             }
 
+            AstNode::BigInt { value, .. } => {
+                let reg = self.alloc_register()?;
+                let const_id = self.chunk.add_constant(Constant::BigInt(value.clone()));
+                self.emit(Instruction::LoadConst { dest: reg, constant_id: const_id }, 0);
+                Ok(reg)
+            }
+
+            AstNode::Integer { value, .. } => {
+                let reg = self.alloc_register()?;
+                let const_id = self.chunk.add_constant(Constant::Integer(*value));
+                self.emit(Instruction::LoadConst { dest: reg, constant_id: const_id }, 0);
+                Ok(reg)
+            }
+
             AstNode::Text { value, .. } => {
                 let reg = self.alloc_register()?;
                 let const_id = self.chunk.add_constant(Constant::Text(value.clone()));
@@ -814,6 +1035,13 @@ impl BytecodeCompiler {
             }
 
             AstNode::Ident { name, .. } => {
+                // A unit case of a user-defined variant, unless a variable shadows it
+                if self.resolve_variable(name).is_err() {
+                    if let Some((enum_name, 0)) = self.variant_cases.get(name).cloned() {
+                        return self.compile_variant(&enum_name, name, &[]);
+                    }
+                }
+
                 let reg = self.alloc_register()?;
                 let location = self.resolve_variable(name)?;
 
@@ -848,6 +1076,17 @@ impl BytecodeCompiler {
                 self.compile_unary_op(*op, operand)
             }
 
+            // Measures are Numbers in base units; `in` divides back out
+            AstNode::Measure { value, unit, span } => {
+                let unit = known_unit(unit)?;
+                self.compile_expr(&AstNode::Number { value: unit.to_base(*value), span: span.clone() })
+            }
+
+            AstNode::InUnit { value, unit, span } => {
+                let factor = AstNode::Number { value: known_unit(unit)?.factor, span: span.clone() };
+                self.compile_binary_op(value, BinaryOperator::Div, &factor)
+            }
+
             AstNode::List { elements, .. } => {
                 // Compile all elements into consecutive registers
                 let start_reg = self.next_register;
@@ -874,6 +1113,28 @@ impl BytecodeCompiler {
                 Ok(dest_reg)
             }
 
+            AstNode::Tuple { elements, .. } => {
+                // Compile all elements into consecutive registers
+                let start_reg = self.next_register;
+                let mut regs = Vec::new();
+                for elem in elements {
+                    regs.push(self.compile_expr(elem)?);
+                }
+
+                let dest_reg = self.alloc_register()?;
+                self.emit(Instruction::CreateTuple {
+                    dest: dest_reg,
+                    start: start_reg,
+                    count: regs.len() as u8,
+                }, 0);
+
+                for reg in regs {
+                    self.free_register(reg);
+                }
+
+                Ok(dest_reg)
+            }
+
             AstNode::Map { entries, .. } => {
                 let dest_reg = self.alloc_register()?;
                 self.emit(Instruction::CreateMap { dest: dest_reg }, 0);
@@ -926,7 +1187,31 @@ impl BytecodeCompiler {
                 Ok(dest_reg)
             }
 
-            AstNode::Call { callee, args, .. } => {
+            AstNode::Call { callee, args, arg_names, .. } => {
+                if !arg_names.is_empty() {
+                    return self.compile_named_call(callee, args, arg_names);
+                }
+
+                // Constructing a variant case that carries data
+                if let AstNode::Ident { name, .. } = callee.as_ref() {
+                    if let Some((enum_name, field_count)) = self.variant_cases.get(name).cloned() {
+                        if field_count > 0 && self.resolve_variable(name).is_err() {
+                            if args.len() != field_count {
+                                return Err(CompileError::UnsupportedFeature(format!(
+                                    "{} takes {} fields, got {}", name, field_count, args.len()
+                                )));
+                            }
+                            return self.compile_variant(&enum_name, name, args);
+                        }
+                    }
+                    // Text builder builtins have their own instructions
+                    if self.resolve_variable(name).is_err() {
+                        if let Some(dest) = self.compile_text_builder_call(name, args)? {
+                            return Ok(dest);
+                        }
+                    }
+                }
+
                 // Compile callee (should be a function value)
                 let func_reg = self.compile_expr(callee)?;
 
@@ -1135,13 +1420,279 @@ impl BytecodeCompiler {
     /// FUTURE: Useful for debugging, error reporting, and implementing
     /// scope-aware introspection features (e.g., listing local variables).
     #[allow(dead_code)]
-    fn current_scope(&self) -> &Scope {
-        self.scopes.last().expect("No scope available")
+    fn current_scope(&self) -> CompileResult<&Scope> {
+        self.scopes.last().ok_or_else(|| CompileError::Internal("No scope available".to_string()))
     }
 
     /// Get current scope mutably
-    fn current_scope_mut(&mut self) -> &mut Scope {
-        self.scopes.last_mut().expect("No scope available")
+    fn current_scope_mut(&mut self) -> CompileResult<&mut Scope> {
+        self.scopes.last_mut().ok_or_else(|| CompileError::Internal("No scope available".to_string()))
+    }
+
+    /// Point the jump emitted at `jump_offset` to `target_offset`
+    fn patch_jump(&mut self, jump_offset: usize, target_offset: usize) -> CompileResult<()> {
+        self.chunk.patch_jump(jump_offset, target_offset).map_err(CompileError::Internal)
+    }
+
+    /// Build a variant case from its field expressions
+    fn compile_variant(&mut self, enum_name: &str, case: &str, fields: &[AstNode]) -> CompileResult<Register> {
+        // Compile fields into consecutive registers
+        let field_start = self.next_register;
+        let mut field_regs = Vec::new();
+        for field in fields {
+            field_regs.push(self.compile_expr(field)?);
+        }
+
+        let enum_id = self.add_string_constant(enum_name.to_string());
+        let case_id = self.add_string_constant(case.to_string());
+        let dest = self.alloc_register()?;
+        self.emit(Instruction::CreateVariant {
+            dest,
+            enum_id,
+            case_id,
+            field_start,
+            field_count: fields.len() as u8,
+        }, 0);
+
+        for reg in field_regs {
+            self.free_register(reg);
+        }
+        Ok(dest)
+    }
+
+    /// Compile a call with named arguments to a chant defined earlier
+    ///
+    /// Arguments are evaluated in the order they are written, then moved
+    /// into parameter order for the call.
+    fn compile_named_call(&mut self, callee: &AstNode, args: &[AstNode], arg_names: &[String]) -> CompileResult<Register> {
+        let params = match callee {
+            AstNode::Ident { name, .. } => self.chant_params.get(name).cloned(),
+            _ => None,
+        }
+        .ok_or_else(|| CompileError::UnsupportedFeature("Named arguments need a chant defined earlier in the program".to_string()))?;
+        let order = order_arguments(&params, (0..args.len()).collect(), arg_names)
+            .map_err(|e| CompileError::UnsupportedFeature(e.to_string()))?;
+
+        let func_reg = self.compile_expr(callee)?;
+        let mut value_regs = Vec::new();
+        for arg in args {
+            value_regs.push(self.compile_expr(arg)?);
+        }
+
+        let arg_start = self.next_register;
+        let mut arg_regs = Vec::new();
+        for index in order {
+            let reg = self.alloc_register()?;
+            self.emit(Instruction::Move { dest: reg, src: value_regs[index] }, 0);
+            arg_regs.push(reg);
+        }
+
+        let dest_reg = self.alloc_register()?;
+        self.emit(Instruction::Call {
+            dest: dest_reg,
+            func: func_reg,
+            arg_start,
+            arg_count: arg_regs.len() as u8,
+        }, 0);
+
+        for reg in arg_regs.into_iter().chain(value_regs) {
+            self.free_register(reg);
+        }
+        self.free_register(func_reg);
+
+        Ok(dest_reg)
+    }
+
+    /// Compile a call to `text_builder`, `text_push`, `text_push_text` or
+    /// `text_build`; `None` for any other name
+    fn compile_text_builder_call(&mut self, name: &str, args: &[AstNode]) -> CompileResult<Option<Register>> {
+        let arity = match name {
+            "text_builder" => 0,
+            "text_build" => 1,
+            "text_push" | "text_push_text" => 2,
+            _ => return Ok(None),
+        };
+        if args.len() != arity {
+            return Err(CompileError::UnsupportedFeature(format!(
+                "{} takes {} arguments, got {}", name, arity, args.len()
+            )));
+        }
+
+        let operands = args.iter().map(|arg| self.compile_expr(arg)).collect::<CompileResult<Vec<_>>>()?;
+        let dest = self.alloc_register()?;
+        let instruction = match (name, operands.as_slice()) {
+            ("text_push", &[builder, value]) => Instruction::TextPush { dest, builder, value },
+            ("text_push_text", &[builder, value]) => Instruction::TextPushText { dest, builder, value },
+            ("text_build", &[builder]) => Instruction::TextBuild { dest, builder },
+            _ => Instruction::CreateTextBuilder { dest },
+        };
+        self.emit(instruction, 0);
+
+        for reg in operands {
+            self.free_register(reg);
+        }
+        Ok(Some(dest))
+    }
+
+    /// Test `value` against a tuple pattern, binding the names in it;
+    /// returns the jumps to patch to the next arm, taken on a mismatch
+    fn compile_tuple_pattern(&mut self, patterns: &[crate::ast::Pattern], value: Register) -> CompileResult<Vec<usize>> {
+        use crate::ast::Pattern;
+
+        let mut jumps = Vec::new();
+        let check_reg = self.alloc_register()?;
+        self.emit(Instruction::IsTuple { dest: check_reg, value, count: patterns.len() as u8 }, 0);
+        self.emit(Instruction::JumpIfFalse { cond: check_reg, offset: 0 }, 0);
+        jumps.push(self.chunk.offset() - 1);
+        self.free_register(check_reg);
+
+        for (index, pattern) in patterns.iter().enumerate() {
+            if matches!(pattern, Pattern::Wildcard) {
+                continue;
+            }
+            let element_reg = self.alloc_register()?;
+            self.emit(Instruction::TupleGet { dest: element_reg, tuple: value, index: index as u8 }, 0);
+            match pattern {
+                Pattern::Ident(name) => {
+                    let local_index = self.local_count;
+                    self.local_count += 1;
+                    self.chunk.local_count = self.local_count;
+                    self.emit(Instruction::StoreLocal { local_index, src: element_reg }, 0);
+                    self.current_scope_mut()?.variables.insert(name.clone(), VarLocation::Local(local_index));
+                }
+                Pattern::Literal(literal) => {
+                    let literal_reg = self.compile_expr(literal)?;
+                    self.emit(Instruction::Eq { dest: literal_reg, left: element_reg, right: literal_reg }, 0);
+                    self.emit(Instruction::JumpIfFalse { cond: literal_reg, offset: 0 }, 0);
+                    jumps.push(self.chunk.offset() - 1);
+                    self.free_register(literal_reg);
+                }
+                Pattern::Tuple(inner) => jumps.extend(self.compile_tuple_pattern(inner, element_reg)?),
+                Pattern::Or(alternatives) => jumps.push(self.compile_or_alternatives(alternatives, element_reg)?),
+                _ => {
+                    return Err(CompileError::UnsupportedFeature(
+                        "Only names, literals, `_`, tuples and or-patterns are supported inside tuple patterns".to_string(),
+                    ))
+                }
+            }
+            self.free_register(element_reg);
+        }
+        Ok(jumps)
+    }
+
+    /// Instruction writing to `dest` whether `value` is the case `variant`,
+    /// either a case of a declared variant or one of the builtin wrappers
+    fn variant_check(&mut self, variant: &str, dest: Register, value: Register) -> CompileResult<Instruction> {
+        if self.variant_cases.contains_key(variant) {
+            let case_id = self.add_string_constant(variant.to_string());
+            return Ok(Instruction::IsVariant { dest, value, case_id });
+        }
+        match variant {
+            "Triumph" => Ok(Instruction::IsTriumph { dest, value }),
+            "Mishap" => Ok(Instruction::IsMishap { dest, value }),
+            "Present" => Ok(Instruction::IsPresent { dest, value }),
+            "Absent" => Ok(Instruction::IsAbsent { dest, value }),
+            _ => Err(CompileError::UnsupportedFeature(format!("Unknown enum variant: {}", variant))),
+        }
+    }
+
+    /// Test whether `value` is the case `variant`, into a fresh register
+    fn compile_case_check(&mut self, variant: &str, value: Register) -> CompileResult<Register> {
+        let check_reg = self.alloc_register()?;
+        let instruction = self.variant_check(variant, check_reg, value)?;
+        self.emit(instruction, 0);
+        Ok(check_reg)
+    }
+
+    /// Test `value` against the alternatives of an or-pattern, each a literal
+    /// or a case without fields; returns the jump to patch to the next arm,
+    /// taken when none matches
+    fn compile_or_alternatives(&mut self, alternatives: &[crate::ast::Pattern], value: Register) -> CompileResult<usize> {
+        use crate::ast::Pattern;
+
+        let mut jumps_to_body = Vec::new();
+        for alternative in alternatives {
+            let check_reg = match alternative {
+                Pattern::Literal(literal) => {
+                    let literal_reg = self.compile_expr(literal)?;
+                    self.emit(Instruction::Eq { dest: literal_reg, left: value, right: literal_reg }, 0);
+                    literal_reg
+                }
+                Pattern::Ident(name) if matches!(self.variant_cases.get(name), Some((_, 0))) => {
+                    self.compile_case_check(name, value)?
+                }
+                Pattern::Enum { variant, inner: None } => self.compile_case_check(variant, value)?,
+                _ => {
+                    return Err(CompileError::UnsupportedFeature(
+                        "Only literals and cases without fields are supported as or-pattern alternatives".to_string(),
+                    ))
+                }
+            };
+            self.emit(Instruction::JumpIfTrue { cond: check_reg, offset: 0 }, 0);
+            jumps_to_body.push(self.chunk.offset() - 1);
+            self.free_register(check_reg);
+        }
+        self.emit(Instruction::Jump { offset: 0 }, 0);
+        let jump_to_next_arm = self.chunk.offset() - 1;
+
+        let body_offset = self.chunk.offset();
+        for jump in jumps_to_body {
+            self.patch_jump(jump, body_offset)?;
+        }
+        Ok(jump_to_next_arm)
+    }
+
+    /// Bind the fields of a matched variant case to the names in its pattern
+    fn bind_variant_fields(
+        &mut self,
+        case: &str,
+        pattern: &crate::ast::Pattern,
+        field_count: usize,
+        value: Register,
+    ) -> CompileResult<()> {
+        use crate::ast::Pattern;
+
+        // Several fields arrive as a list of names, with placeholders for
+        // nested patterns; `_` binds nothing
+        let binding = |name: &String| (name != "_").then(|| name.clone());
+        let bindings: Vec<Option<String>> = match pattern {
+            Pattern::Ident(name) => vec![binding(name)],
+            Pattern::Wildcard => vec![None],
+            Pattern::Literal(list) => match list.as_ref() {
+                AstNode::List { elements, .. } => elements
+                    .iter()
+                    .map(|element| match element {
+                        AstNode::Ident { name, .. } => binding(name),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => return Err(CompileError::UnsupportedFeature(
+                    "Complex nested enum patterns not yet supported".to_string()
+                )),
+            },
+            _ => return Err(CompileError::UnsupportedFeature(
+                "Complex nested enum patterns not yet supported".to_string()
+            )),
+        };
+        if bindings.len() > field_count {
+            return Err(CompileError::UnsupportedFeature(format!(
+                "Pattern binds {} fields but {} has {}", bindings.len(), case, field_count
+            )));
+        }
+
+        for (index, binding) in bindings.into_iter().enumerate() {
+            let Some(name) = binding else { continue };
+            let field_reg = self.alloc_register()?;
+            self.emit(Instruction::ExtractField { dest: field_reg, value, index: index as u8 }, 0);
+
+            let local_index = self.local_count;
+            self.local_count += 1;
+            self.chunk.local_count = self.local_count;
+            self.emit(Instruction::StoreLocal { local_index, src: field_reg }, 0);
+            self.current_scope_mut()?.variables.insert(name, VarLocation::Local(local_index));
+            self.free_register(field_reg);
+        }
+        Ok(())
     }
 
     /// Resolve a variable to its location
@@ -1178,12 +1729,45 @@ impl BytecodeCompiler {
     }
 }
 
+/// The unit a `Measure` or `InUnit` node names
+fn known_unit(name: &str) -> CompileResult<crate::units::Unit> {
+    crate::units::unit(name).ok_or_else(|| CompileError::UnsupportedFeature(format!("unknown unit '{}'", name)))
+}
+
 /// Compile Glimmer-Weave AST to bytecode
 pub fn compile(nodes: &[AstNode]) -> CompileResult<BytecodeChunk> {
     let mut compiler = BytecodeCompiler::new("main".to_string());
     compiler.compile(nodes)
 }
 
+/// Compile Glimmer-Weave source to bytecode, reusing cached artifacts
+///
+/// A chunk cached for the same source is returned as is; otherwise the
+/// cached parse is reused when there is one, and the results are stored
+/// for next time.
+pub fn compile_cached(source: &str, cache: &mut dyn CacheStore) -> CompileResult<BytecodeChunk> {
+    let hash = content_hash(source);
+    if let Some(chunk) = cache.chunk(hash) {
+        return Ok(chunk);
+    }
+
+    let nodes = match cache.module(hash) {
+        Some(nodes) => nodes,
+        None => {
+            let tokens = crate::lexer::Lexer::new(source).tokenize_positioned();
+            let nodes = crate::parser::Parser::new(tokens)
+                .parse()
+                .map_err(|e| CompileError::ParseError(format!("{:?}", e)))?;
+            cache.store_module(hash, &nodes);
+            nodes
+        }
+    };
+
+    let chunk = compile(&nodes)?;
+    cache.store_chunk(hash, &chunk);
+    Ok(chunk)
+}
+
 /// Compile Glimmer-Weave AST to bytecode with monomorphization
 /// This applies monomorphization to generic functions before compilation
 pub fn compile_with_monomorphization(nodes: &[AstNode]) -> CompileResult<BytecodeChunk> {
@@ -1212,7 +1796,7 @@ mod tests {
     #[test]
     fn test_compile_simple_number() {
         let chunk = compile_source("42").expect("Compile failed");
-        assert!(chunk.instructions.len() > 0);
+        assert!(!chunk.instructions.is_empty());
         assert_eq!(chunk.constants.len(), 1);
         assert_eq!(chunk.constants[0], Constant::Number(42.0));
     }
@@ -1382,16 +1966,14 @@ offer add, mul
     fn test_module_qualified_access_compiles() {
         // Module-qualified access should compile to LoadGlobal with qualified name
         // This allows the interpreter to set up qualified names as globals
-        let ast = vec![
-            AstNode::ModuleAccess {
-                module: "Math".to_string(),
-                member: "add".to_string(),
-                span: SourceSpan::default(),
-            }
-        ];
+        let ast = AstNode::ModuleAccess {
+            module: "Math".to_string(),
+            member: "add".to_string(),
+            span: SourceSpan::default(),
+        };
 
         let mut compiler = BytecodeCompiler::new("test".to_string());
-        let result = compiler.compile_expr(&ast[0]);
+        let result = compiler.compile_expr(&ast);
 
         // Should compile successfully
         assert!(result.is_ok(), "Module-qualified access should compile");
@@ -0,0 +1,604 @@
+//! # Bytecode Images
+//!
+//! Binary `.gwc` encoding of compiled [`BytecodeChunk`]s, so compiled
+//! scripts can be stored (for example in the module cache) and loaded
+//! without re-parsing.
+//!
+//! ## Layout
+//!
+//! All integers are little-endian; text is a `u32` byte length followed by
+//! UTF-8.
+//!
+//! | Field        | Encoding                                          |
+//! |--------------|---------------------------------------------------|
+//! | magic        | `GWC\0`                                           |
+//! | version      | `u16`, the chunk's bytecode version               |
+//! | name         | text                                              |
+//! | param_count  | `u8`                                              |
+//! | local_count  | `u8`                                              |
+//! | constants    | `u32` count, then a tag byte and payload each     |
+//! | instructions | `u32` count, then the opcode byte and operands    |
+//! | lines        | `u32` count, then a `u64` each                    |
+//!
+//! Operands are written in [`OpcodeInfo::operands`](crate::bytecode::OpcodeInfo)
+//! order: registers, counts and truths as one byte, constant ids as `u16`,
+//! jump offsets as `i16` and handler offsets as `u64`.
+//!
+//! Hosts that only run signed code load images with [`decode_verified`],
+//! which hands the image and its detached signature to a
+//! [`Verifier`](crate::verify::Verifier) before decoding anything.
+//!
+//! ```
+//! use glimmer_weave::bytecode::{BytecodeChunk, Constant, Instruction};
+//! use glimmer_weave::bytecode_image::{decode, encode};
+//!
+//! let mut chunk = BytecodeChunk::new("main".to_string());
+//! let id = chunk.add_constant(Constant::Number(42.0));
+//! chunk.emit(Instruction::LoadConst { dest: 0, constant_id: id }, 1);
+//! chunk.emit(Instruction::Halt, 1);
+//!
+//! let decoded = decode(&encode(&chunk)).unwrap();
+//! assert_eq!(decoded.instructions, chunk.instructions);
+//! ```
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::ast::{Lifetime, StructField, TypeAnnotation};
+use crate::bigint::BigInt;
+use crate::bytecode::{BytecodeChunk, Constant, Instruction};
+use crate::verify::{verify_image, Verifier, VerifyError};
+
+/// Magic bytes every image starts with
+pub const MAGIC: &[u8; 4] = b"GWC\0";
+
+/// File extension of bytecode images
+pub const EXTENSION: &str = "gwc";
+
+/// Why an image could not be decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageError {
+    /// The data does not start with [`MAGIC`]
+    BadMagic,
+    /// The data ends in the middle of a field
+    Truncated,
+    /// An instruction uses an opcode this build does not know
+    UnknownOpcode(u8),
+    /// A constant or type uses a tag this build does not know
+    UnknownTag(u8),
+    /// A text field is not valid UTF-8
+    InvalidText,
+    /// A big integer constant is not decimal digits
+    InvalidBigInt,
+    /// The image's signature is missing or rejected by the verifier
+    Verification(VerifyError),
+}
+
+/// Encode a chunk as a `.gwc` image
+pub fn encode(chunk: &BytecodeChunk) -> Vec<u8> {
+    let mut out = Writer(Vec::new());
+    out.0.extend_from_slice(MAGIC);
+    out.u16(chunk.version);
+    out.text(&chunk.name);
+    out.u8(chunk.param_count);
+    out.u8(chunk.local_count);
+
+    out.len(chunk.constants.len());
+    for constant in &chunk.constants {
+        out.constant(constant);
+    }
+    out.len(chunk.instructions.len());
+    for instruction in &chunk.instructions {
+        encode_instruction(&mut out, instruction);
+    }
+    out.len(chunk.lines.len());
+    for line in &chunk.lines {
+        out.usize(*line);
+    }
+    out.0
+}
+
+/// Decode a `.gwc` image
+///
+/// The version is taken from the image as is; the VM's loader decides
+/// whether it can run it.
+pub fn decode(image: &[u8]) -> Result<BytecodeChunk, ImageError> {
+    let mut input = Reader(image);
+    if input.take(MAGIC.len())? != MAGIC {
+        return Err(ImageError::BadMagic);
+    }
+    let version = input.u16()?;
+    let mut chunk = BytecodeChunk::new(input.text()?);
+    chunk.version = version;
+    chunk.param_count = input.u8()?;
+    chunk.local_count = input.u8()?;
+
+    for _ in 0..input.len()? {
+        let constant = input.constant()?;
+        chunk.constants.push(constant);
+    }
+    for _ in 0..input.len()? {
+        let instruction = decode_instruction(&mut input)?;
+        chunk.instructions.push(instruction);
+    }
+    for _ in 0..input.len()? {
+        let line = input.usize()?;
+        chunk.lines.push(line);
+    }
+    Ok(chunk)
+}
+
+/// Decode a `.gwc` image once `verifier` accepts its detached signature
+///
+/// Unsigned and badly signed images fail with [`ImageError::Verification`]
+/// before any of their bytes are decoded.
+pub fn decode_verified(verifier: &dyn Verifier, image: &[u8], signature: Option<&[u8]>) -> Result<BytecodeChunk, ImageError> {
+    verify_image(verifier, image, signature).map_err(ImageError::Verification)?;
+    decode(image)
+}
+
+/// Defines `encode_instruction` and `decode_instruction` from one table of
+/// opcodes and operand encodings, so the two cannot drift apart
+macro_rules! instruction_codec {
+    ($($opcode:literal => $variant:ident { $($field:ident: $kind:ident),* }),* $(,)?) => {
+        fn encode_instruction(out: &mut Writer, instruction: &Instruction) {
+            match instruction {
+                $(Instruction::$variant { $($field),* } => {
+                    out.u8($opcode);
+                    $(out.$kind(*$field);)*
+                })*
+            }
+        }
+
+        fn decode_instruction(input: &mut Reader) -> Result<Instruction, ImageError> {
+            match input.u8()? {
+                $($opcode => Ok(Instruction::$variant { $($field: input.$kind()?),* }),)*
+                other => Err(ImageError::UnknownOpcode(other)),
+            }
+        }
+    };
+}
+
+instruction_codec! {
+    0 => LoadConst { dest: u8, constant_id: u16 },
+    1 => Move { dest: u8, src: u8 },
+    2 => LoadNothing { dest: u8 },
+    3 => LoadTruth { dest: u8, value: truth },
+    4 => AddNum { dest: u8, left: u8, right: u8 },
+    5 => SubNum { dest: u8, left: u8, right: u8 },
+    6 => MulNum { dest: u8, left: u8, right: u8 },
+    7 => DivNum { dest: u8, left: u8, right: u8 },
+    8 => ModNum { dest: u8, left: u8, right: u8 },
+    9 => NegNum { dest: u8, src: u8 },
+    10 => ConcatText { dest: u8, left: u8, right: u8 },
+    11 => Eq { dest: u8, left: u8, right: u8 },
+    12 => Ne { dest: u8, left: u8, right: u8 },
+    13 => Lt { dest: u8, left: u8, right: u8 },
+    14 => Le { dest: u8, left: u8, right: u8 },
+    15 => Gt { dest: u8, left: u8, right: u8 },
+    16 => Ge { dest: u8, left: u8, right: u8 },
+    17 => Not { dest: u8, src: u8 },
+    18 => And { dest: u8, left: u8, right: u8 },
+    19 => Or { dest: u8, left: u8, right: u8 },
+    20 => Jump { offset: i16 },
+    21 => JumpIfTrue { cond: u8, offset: i16 },
+    22 => JumpIfFalse { cond: u8, offset: i16 },
+    23 => DefineGlobal { name_id: u16, src: u8 },
+    24 => LoadGlobal { dest: u8, name_id: u16 },
+    25 => StoreGlobal { name_id: u16, src: u8 },
+    26 => LoadLocal { dest: u8, local_index: u8 },
+    27 => StoreLocal { local_index: u8, src: u8 },
+    28 => CreateList { dest: u8, start: u8, count: u8 },
+    29 => CreateMap { dest: u8 },
+    30 => GetIndex { dest: u8, list: u8, index: u8 },
+    31 => SetIndex { list: u8, index: u8, value: u8 },
+    32 => GetField { dest: u8, map: u8, field_id: u16 },
+    33 => SetField { map: u8, field_id: u16, value: u8 },
+    34 => Call { dest: u8, func: u8, arg_start: u8, arg_count: u8 },
+    35 => Return { value: u8 },
+    36 => CreateClosure { dest: u8, function_id: u16, capture_count: u8 },
+    37 => CreateTriumph { dest: u8, value: u8 },
+    38 => CreateMishap { dest: u8, value: u8 },
+    39 => CreatePresent { dest: u8, value: u8 },
+    40 => CreateAbsent { dest: u8 },
+    41 => IsTriumph { dest: u8, value: u8 },
+    42 => IsMishap { dest: u8, value: u8 },
+    43 => IsPresent { dest: u8, value: u8 },
+    44 => IsAbsent { dest: u8, value: u8 },
+    45 => ExtractInner { dest: u8, value: u8 },
+    46 => CreateStruct { dest: u8, struct_def_id: u16, field_start: u8, field_count: u8 },
+    47 => SetupTry { handler_offset: usize },
+    48 => PopTry {},
+    49 => Throw { error_reg: u8 },
+    50 => Halt {},
+    51 => Print { src: u8 },
+    52 => CreateVariant { dest: u8, enum_id: u16, case_id: u16, field_start: u8, field_count: u8 },
+    53 => IsVariant { dest: u8, value: u8, case_id: u16 },
+    54 => ExtractField { dest: u8, value: u8, index: u8 },
+    55 => CreateTextBuilder { dest: u8 },
+    56 => TextPush { dest: u8, builder: u8, value: u8 },
+    57 => TextPushText { dest: u8, builder: u8, value: u8 },
+    58 => TextBuild { dest: u8, builder: u8 },
+    59 => StripAffix { dest: u8, value: u8, affix_id: u16, suffix: truth },
+    60 => CreateTuple { dest: u8, start: u8, count: u8 },
+    61 => IsTuple { dest: u8, value: u8, count: u8 },
+    62 => TupleGet { dest: u8, tuple: u8, index: u8 },
+}
+
+/// Little-endian output buffer, shared with [`bundle`](crate::bundle)
+pub(crate) struct Writer(pub(crate) Vec<u8>);
+
+impl Writer {
+    pub(crate) fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    pub(crate) fn truth(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    pub(crate) fn u16(&mut self, value: u16) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i16(&mut self, value: i16) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn len(&mut self, value: usize) {
+        self.0.extend_from_slice(&(value as u32).to_le_bytes());
+    }
+
+    pub(crate) fn usize(&mut self, value: usize) {
+        self.0.extend_from_slice(&(value as u64).to_le_bytes());
+    }
+
+    pub(crate) fn text(&mut self, value: &str) {
+        self.bytes(value.as_bytes());
+    }
+
+    /// Length-prefixed bytes
+    pub(crate) fn bytes(&mut self, value: &[u8]) {
+        self.len(value.len());
+        self.0.extend_from_slice(value);
+    }
+
+    fn constant(&mut self, constant: &Constant) {
+        match constant {
+            Constant::Number(n) => {
+                self.u8(0);
+                self.0.extend_from_slice(&n.to_le_bytes());
+            }
+            Constant::Text(s) => {
+                self.u8(1);
+                self.text(s);
+            }
+            Constant::Truth(b) => {
+                self.u8(2);
+                self.truth(*b);
+            }
+            Constant::Nothing => self.u8(3),
+            Constant::StructDef { name, fields } => {
+                self.u8(4);
+                self.text(name);
+                self.len(fields.len());
+                for field in fields {
+                    self.text(&field.name);
+                    self.type_annotation(&field.typ);
+                }
+            }
+            Constant::Capability { resource, permissions } => {
+                self.u8(5);
+                self.text(resource);
+                self.len(permissions.len());
+                for permission in permissions {
+                    self.text(permission);
+                }
+            }
+            // Decimal digits, so the image stays independent of limb layout
+            Constant::BigInt(n) => {
+                self.u8(6);
+                self.text(&n.to_string());
+            }
+            Constant::Integer(n) => {
+                self.u8(7);
+                self.0.extend_from_slice(&n.to_le_bytes());
+            }
+        }
+    }
+
+    fn type_annotation(&mut self, typ: &TypeAnnotation) {
+        match typ {
+            TypeAnnotation::Named(name) => {
+                self.u8(0);
+                self.text(name);
+            }
+            TypeAnnotation::Generic(name) => {
+                self.u8(1);
+                self.text(name);
+            }
+            TypeAnnotation::Parametrized { name, type_args } => {
+                self.u8(2);
+                self.text(name);
+                self.type_list(type_args);
+            }
+            TypeAnnotation::List(inner) => {
+                self.u8(3);
+                self.type_annotation(inner);
+            }
+            TypeAnnotation::Map => self.u8(4),
+            TypeAnnotation::Function { param_types, return_type } => {
+                self.u8(5);
+                self.type_list(param_types);
+                self.type_annotation(return_type);
+            }
+            TypeAnnotation::Optional(inner) => {
+                self.u8(6);
+                self.type_annotation(inner);
+            }
+            TypeAnnotation::Borrowed { lifetime, inner, mutable } => {
+                self.u8(7);
+                match lifetime {
+                    Some(lifetime) => {
+                        self.truth(true);
+                        self.text(&lifetime.name);
+                    }
+                    None => self.truth(false),
+                }
+                self.type_annotation(inner);
+                self.truth(*mutable);
+            }
+            TypeAnnotation::Array { element, length } => {
+                self.u8(8);
+                self.type_annotation(element);
+                self.usize(*length);
+            }
+        }
+    }
+
+    fn type_list(&mut self, types: &[TypeAnnotation]) {
+        self.len(types.len());
+        for typ in types {
+            self.type_annotation(typ);
+        }
+    }
+}
+
+/// Little-endian input cursor, shared with [`bundle`](crate::bundle)
+pub(crate) struct Reader<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, count: usize) -> Result<&'a [u8], ImageError> {
+        if self.0.len() < count {
+            return Err(ImageError::Truncated);
+        }
+        let (head, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ImageError> {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(self.take(N)?);
+        Ok(bytes)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, ImageError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn truth(&mut self) -> Result<bool, ImageError> {
+        Ok(self.u8()? != 0)
+    }
+
+    pub(crate) fn u16(&mut self) -> Result<u16, ImageError> {
+        self.array().map(u16::from_le_bytes)
+    }
+
+    fn i16(&mut self) -> Result<i16, ImageError> {
+        self.array().map(i16::from_le_bytes)
+    }
+
+    pub(crate) fn len(&mut self) -> Result<usize, ImageError> {
+        self.array().map(|bytes| u32::from_le_bytes(bytes) as usize)
+    }
+
+    pub(crate) fn usize(&mut self) -> Result<usize, ImageError> {
+        self.array().map(|bytes| u64::from_le_bytes(bytes) as usize)
+    }
+
+    pub(crate) fn text(&mut self) -> Result<String, ImageError> {
+        let bytes = self.bytes()?;
+        core::str::from_utf8(bytes).map(String::from).map_err(|_| ImageError::InvalidText)
+    }
+
+    /// Length-prefixed bytes
+    pub(crate) fn bytes(&mut self) -> Result<&'a [u8], ImageError> {
+        let len = self.len()?;
+        self.take(len)
+    }
+
+    fn constant(&mut self) -> Result<Constant, ImageError> {
+        match self.u8()? {
+            0 => self.array().map(|bytes| Constant::Number(f64::from_le_bytes(bytes))),
+            1 => self.text().map(Constant::Text),
+            2 => self.truth().map(Constant::Truth),
+            3 => Ok(Constant::Nothing),
+            4 => {
+                let name = self.text()?;
+                let mut fields = Vec::new();
+                for _ in 0..self.len()? {
+                    let name = self.text()?;
+                    let typ = self.type_annotation()?;
+                    fields.push(StructField { name, typ });
+                }
+                Ok(Constant::StructDef { name, fields })
+            }
+            5 => {
+                let resource = self.text()?;
+                let mut permissions = Vec::new();
+                for _ in 0..self.len()? {
+                    permissions.push(self.text()?);
+                }
+                Ok(Constant::Capability { resource, permissions })
+            }
+            6 => BigInt::parse(&self.text()?).map(Constant::BigInt).ok_or(ImageError::InvalidBigInt),
+            7 => self.array().map(|bytes| Constant::Integer(i64::from_le_bytes(bytes))),
+            tag => Err(ImageError::UnknownTag(tag)),
+        }
+    }
+
+    fn type_annotation(&mut self) -> Result<TypeAnnotation, ImageError> {
+        match self.u8()? {
+            0 => self.text().map(TypeAnnotation::Named),
+            1 => self.text().map(TypeAnnotation::Generic),
+            2 => {
+                let name = self.text()?;
+                let type_args = self.type_list()?;
+                Ok(TypeAnnotation::Parametrized { name, type_args })
+            }
+            3 => Ok(TypeAnnotation::List(Box::new(self.type_annotation()?))),
+            4 => Ok(TypeAnnotation::Map),
+            5 => {
+                let param_types = self.type_list()?;
+                let return_type = Box::new(self.type_annotation()?);
+                Ok(TypeAnnotation::Function { param_types, return_type })
+            }
+            6 => Ok(TypeAnnotation::Optional(Box::new(self.type_annotation()?))),
+            7 => {
+                let lifetime = if self.truth()? { Some(Lifetime { name: self.text()? }) } else { None };
+                let inner = Box::new(self.type_annotation()?);
+                let mutable = self.truth()?;
+                Ok(TypeAnnotation::Borrowed { lifetime, inner, mutable })
+            }
+            8 => {
+                let element = Box::new(self.type_annotation()?);
+                let length = self.usize()?;
+                Ok(TypeAnnotation::Array { element, length })
+            }
+            tag => Err(ImageError::UnknownTag(tag)),
+        }
+    }
+
+    fn type_list(&mut self) -> Result<Vec<TypeAnnotation>, ImageError> {
+        let mut types = Vec::new();
+        for _ in 0..self.len()? {
+            types.push(self.type_annotation()?);
+        }
+        Ok(types)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytecode::OPCODES;
+
+    #[test]
+    fn test_roundtrip_constants_and_metadata() {
+        let mut chunk = BytecodeChunk::new("shapes".to_string());
+        chunk.param_count = 2;
+        chunk.local_count = 3;
+        chunk.add_constant(Constant::Number(-1.5));
+        chunk.add_constant(Constant::Text("héllo".to_string()));
+        chunk.add_constant(Constant::Truth(true));
+        chunk.add_constant(Constant::Nothing);
+        chunk.add_constant(Constant::StructDef {
+            name: "Point".to_string(),
+            fields: vec![
+                StructField { name: "x".to_string(), typ: TypeAnnotation::Named("Number".to_string()) },
+                StructField {
+                    name: "tags".to_string(),
+                    typ: TypeAnnotation::Parametrized {
+                        name: "List".to_string(),
+                        type_args: vec![TypeAnnotation::Borrowed {
+                            lifetime: Some(Lifetime { name: "a".to_string() }),
+                            inner: Box::new(TypeAnnotation::Generic("T".to_string())),
+                            mutable: true,
+                        }],
+                    },
+                },
+            ],
+        });
+        chunk.add_constant(Constant::Capability {
+            resource: "VGA.write".to_string(),
+            permissions: vec!["access".to_string()],
+        });
+        chunk.add_constant(Constant::BigInt(BigInt::parse("-123456789012345678901234567890").unwrap()));
+        chunk.add_constant(Constant::Integer(i64::MIN));
+        chunk.emit(Instruction::Jump { offset: -3 }, 7);
+        chunk.emit(Instruction::SetupTry { handler_offset: 70_000 }, 8);
+        chunk.emit(Instruction::PopTry, 9);
+
+        let decoded = decode(&encode(&chunk)).unwrap();
+        assert_eq!(decoded.name, "shapes");
+        assert_eq!((decoded.param_count, decoded.local_count, decoded.version), (2, 3, chunk.version));
+        assert_eq!(decoded.constants, chunk.constants);
+        assert_eq!(decoded.instructions, chunk.instructions);
+        assert_eq!(decoded.lines, vec![7, 8, 9]);
+    }
+
+    #[test]
+    fn test_codec_opcodes_match_the_reference_table() {
+        // One instruction per opcode, with distinct operand values
+        let mut chunk = BytecodeChunk::new("all".to_string());
+        for info in OPCODES {
+            let mut image = vec![info.opcode];
+            image.resize(1 + 16, 1);
+            let instruction = decode_instruction(&mut Reader(&image)).unwrap();
+            assert_eq!(instruction.opcode(), info.opcode, "{}", info.name);
+            chunk.emit(instruction, 1);
+        }
+        assert_eq!(decode(&encode(&chunk)).unwrap().instructions, chunk.instructions);
+    }
+
+    #[test]
+    fn test_malformed_images() {
+        let image = encode(&BytecodeChunk::new("main".to_string()));
+        assert_eq!(decode(b"ELF\0").unwrap_err(), ImageError::BadMagic);
+        assert_eq!(decode(&image[..image.len() - 1]).unwrap_err(), ImageError::Truncated);
+
+        let mut chunk = BytecodeChunk::new("main".to_string());
+        chunk.emit(Instruction::Halt, 1);
+        let mut image = encode(&chunk);
+        let opcode_at = image.len() - 4 - 8 - 1;
+        image[opcode_at] = 200;
+        assert_eq!(decode(&image).unwrap_err(), ImageError::UnknownOpcode(200));
+    }
+
+    /// Toy verifier: a signature is the byte sum of the image
+    fn byte_sum_verifier(image: &[u8], signature: &[u8]) -> Result<(), VerifyError> {
+        let sum: u64 = image.iter().map(|&b| b as u64).sum();
+        if signature != sum.to_string().as_bytes() {
+            return Err(VerifyError::InvalidSignature { reason: "digest mismatch".to_string() });
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_verified() {
+        let mut chunk = BytecodeChunk::new("main".to_string());
+        let id = chunk.add_constant(Constant::Number(42.0));
+        chunk.emit(Instruction::LoadConst { dest: 0, constant_id: id }, 1);
+        let image = encode(&chunk);
+        let sum: u64 = image.iter().map(|&b| b as u64).sum();
+        let signature = sum.to_string().into_bytes();
+
+        let decoded = decode_verified(&byte_sum_verifier, &image, Some(&signature)).unwrap();
+        assert_eq!(decoded.instructions, chunk.instructions);
+
+        assert_eq!(
+            decode_verified(&byte_sum_verifier, &image, None).unwrap_err(),
+            ImageError::Verification(VerifyError::MissingSignature)
+        );
+
+        // A patched image no longer matches the signature
+        let mut tampered = image.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            decode_verified(&byte_sum_verifier, &tampered, Some(&signature)),
+            Err(ImageError::Verification(VerifyError::InvalidSignature { .. }))
+        ));
+    }
+}
@@ -0,0 +1,65 @@
+//! # Cancellation
+//!
+//! Ctrl-C for scripts. The host keeps a clone of a [`CancellationToken`],
+//! installs it on the evaluator or VM, and trips it from another context
+//! (an interrupt handler, another thread). The running script notices at
+//! its next safepoint and unwinds with `RuntimeError::Cancelled`, running
+//! its `defer` blocks on the way out; `attempt` cannot catch it.
+//!
+//! ```
+//! use glimmer_weave::cancellation::CancellationToken;
+//!
+//! let token = CancellationToken::new();
+//! let host = token.clone();
+//! host.cancel();
+//! assert!(token.is_cancelled());
+//! ```
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Shared flag the host trips to cancel a running script
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// Request cancellation; every clone of the token sees it
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Clear the flag so the token can be reused for the next run
+    pub fn reset(&self) {
+        self.cancelled.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_the_flag() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+
+        token.cancel();
+        assert!(clone.is_cancelled());
+
+        clone.reset();
+        assert!(!token.is_cancelled());
+    }
+}
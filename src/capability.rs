@@ -0,0 +1,380 @@
+//! # Capabilities
+//!
+//! Every capability a script requests, is granted or denied, and uses is
+//! recorded in a [`CapabilityAudit`], together with the source span and the
+//! chant it happened in, so AethelOS can show users exactly what a script
+//! touched.
+//!
+//! Grants are decided by a host [`CapabilityPolicy`]; without one, every
+//! request is granted (permissions are then enforced by the kernel when the
+//! capability is used). A "use" is recorded whenever a capability token is
+//! passed to a native chant.
+//!
+//! The host reads the log through
+//! [`Evaluator::capability_audit`](crate::eval::Evaluator::capability_audit).
+//! Scripts can read it with the `capabilities()` builtin once they have been
+//! granted [`AUDIT_CAPABILITY`] themselves.
+//!
+//! A deterministic evaluator denies the capabilities
+//! [`is_nondeterministic`] names before asking the policy.
+//!
+//! Before a script runs, [`CapabilityInference`] reports every capability it
+//! may request, so the host can prompt for permission up front. Library
+//! modules declare their requirements with
+//! [`CapabilityInference::declare`], and importing them adds those
+//! requirements to the importer's.
+//!
+//! ```
+//! use glimmer_weave::capability::{AuditEvent, CapabilityAudit};
+//! use glimmer_weave::source_location::SourceSpan;
+//!
+//! let mut audit = CapabilityAudit::new();
+//! audit.record("VGA.write", AuditEvent::Granted, None, SourceSpan::unknown());
+//! assert!(audit.is_granted("VGA.write"));
+//! assert_eq!(audit.touched(), vec!["VGA.write"]);
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::ast::AstNode;
+use crate::ast_query::Query;
+use crate::eval::Value;
+use crate::module_resolver::ModuleResolver;
+use crate::source_location::SourceSpan;
+
+/// Capability a script must hold to read the audit log with `capabilities()`
+pub const AUDIT_CAPABILITY: &str = "Audit.read";
+
+/// Capability namespaces a deterministic evaluator denies: time,
+/// randomness, and I/O whose results differ from run to run
+pub const NONDETERMINISTIC_NAMESPACES: &[&str] = &["Time", "Clock", "Random", "FS", "Net", "Console", "Store"];
+
+/// Whether `capability` lies in one of the [`NONDETERMINISTIC_NAMESPACES`]
+pub fn is_nondeterministic(capability: &str) -> bool {
+    let namespace = capability.split('.').next().unwrap_or(capability);
+    NONDETERMINISTIC_NAMESPACES.contains(&namespace)
+}
+
+/// Host decision on capability requests
+pub trait CapabilityPolicy: crate::sync::MaybeSend {
+    /// Grant `capability`, or deny it with a reason
+    fn decide(&mut self, capability: &str, justification: &str) -> Result<(), String>;
+}
+
+impl<F: FnMut(&str, &str) -> Result<(), String> + crate::sync::MaybeSend> CapabilityPolicy for F {
+    fn decide(&mut self, capability: &str, justification: &str) -> Result<(), String> {
+        self(capability, justification)
+    }
+}
+
+/// What happened to a capability
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuditEvent {
+    /// The script asked for it, with its justification
+    Requested { justification: String },
+    /// The policy granted it
+    Granted,
+    /// The policy denied it
+    Denied { reason: String },
+    /// The token was passed to a native chant
+    Used { by: String },
+}
+
+impl AuditEvent {
+    /// Short name of the event kind
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AuditEvent::Requested { .. } => "request",
+            AuditEvent::Granted => "grant",
+            AuditEvent::Denied { .. } => "deny",
+            AuditEvent::Used { .. } => "use",
+        }
+    }
+}
+
+/// One audit log record
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub capability: String,
+    pub event: AuditEvent,
+    /// Chant the event happened in (`None` at the top level)
+    pub chant: Option<String>,
+    pub span: SourceSpan,
+}
+
+impl AuditEntry {
+    /// Script-facing form: a map with `capability`, `event`, `detail`,
+    /// `chant` and `line`
+    pub fn to_value(&self) -> Value {
+        let detail = match &self.event {
+            AuditEvent::Requested { justification } => Value::Text(justification.clone()),
+            AuditEvent::Denied { reason } => Value::Text(reason.clone()),
+            AuditEvent::Used { by } => Value::Text(by.clone()),
+            AuditEvent::Granted => Value::Nothing,
+        };
+
+        let mut fields = BTreeMap::new();
+        fields.insert("capability".to_string(), Value::Text(self.capability.clone()));
+        fields.insert("event".to_string(), Value::Text(self.event.kind().to_string()));
+        fields.insert("detail".to_string(), detail);
+        fields.insert(
+            "chant".to_string(),
+            self.chant.clone().map_or(Value::Nothing, Value::Text),
+        );
+        fields.insert("line".to_string(), Value::Number(self.span.start.line as f64));
+        Value::Map(fields)
+    }
+}
+
+/// Ordered log of capability events
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CapabilityAudit {
+    entries: Vec<AuditEntry>,
+}
+
+impl CapabilityAudit {
+    /// Create an empty log
+    pub fn new() -> Self {
+        CapabilityAudit::default()
+    }
+
+    /// Append an event
+    pub fn record(&mut self, capability: &str, event: AuditEvent, chant: Option<String>, span: SourceSpan) {
+        self.entries.push(AuditEntry {
+            capability: capability.to_string(),
+            event,
+            chant,
+            span,
+        });
+    }
+
+    /// Every event, oldest first
+    pub fn entries(&self) -> &[AuditEntry] {
+        &self.entries
+    }
+
+    /// Events concerning one capability
+    pub fn for_capability<'a>(&'a self, capability: &'a str) -> impl Iterator<Item = &'a AuditEntry> + 'a {
+        self.entries.iter().filter(move |entry| entry.capability == capability)
+    }
+
+    /// Whether `capability` has been granted
+    pub fn is_granted(&self, capability: &str) -> bool {
+        self.for_capability(capability).any(|entry| entry.event == AuditEvent::Granted)
+    }
+
+    /// Capabilities that were granted or used, in first-touched order
+    pub fn touched(&self) -> Vec<&str> {
+        let mut touched: Vec<&str> = Vec::new();
+        for entry in &self.entries {
+            let touches = matches!(entry.event, AuditEvent::Granted | AuditEvent::Used { .. });
+            if touches && !touched.contains(&entry.capability.as_str()) {
+                touched.push(&entry.capability);
+            }
+        }
+        touched
+    }
+
+    /// Forget every event
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Name a capability request refers to (`VGA.write` for `request VGA.write`)
+///
+/// The capability expression is never evaluated; the request is named after
+/// its source form.
+pub fn capability_name(node: &AstNode) -> String {
+    match node {
+        AstNode::Ident { name, .. } => name.clone(),
+        AstNode::FieldAccess { object, field, .. } => {
+            format!("{}.{}", capability_name(object), field)
+        }
+        // Parameterized requests, e.g. `Net.connect(host, port)`
+        AstNode::Call { callee, .. } => capability_name(callee),
+        AstNode::Number { value, .. } => value.to_string(),
+        AstNode::Text { value, .. } => value.clone(),
+        AstNode::Truth { value, .. } => value.to_string(),
+        AstNode::Nothing { .. } => "nothing".to_string(),
+        _ => "<expression>".to_string(),
+    }
+}
+
+/// A capability a script may request, and where
+#[derive(Debug, Clone, PartialEq)]
+pub struct Requirement {
+    pub capability: String,
+    /// Justification given in the request, or the module that requires it
+    pub justification: String,
+    /// Chant containing the request (`None` at the top level)
+    pub chant: Option<String>,
+    /// Module the requirement comes from: the enclosing grove, or the
+    /// imported library that declared it
+    pub module: Option<String>,
+    pub span: SourceSpan,
+}
+
+/// Capabilities a script may request at runtime
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CapabilityRequirements {
+    requirements: Vec<Requirement>,
+}
+
+impl CapabilityRequirements {
+    /// Every requirement, in source order
+    pub fn requirements(&self) -> &[Requirement] {
+        &self.requirements
+    }
+
+    /// Distinct capability names, sorted
+    pub fn capabilities(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.requirements.iter().map(|r| r.capability.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        names
+    }
+
+    /// Whether the script may request `capability`
+    pub fn contains(&self, capability: &str) -> bool {
+        self.requirements.iter().any(|r| r.capability == capability)
+    }
+
+    /// Whether the script requests no capability at all
+    pub fn is_empty(&self) -> bool {
+        self.requirements.is_empty()
+    }
+}
+
+/// Static pass that collects the capabilities a script may request
+///
+/// Every `request` is counted whether or not the code around it runs, so the
+/// result over-approximates what a single run touches.
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityInference {
+    /// Module name -> capabilities it requires
+    declared: BTreeMap<String, Vec<String>>,
+}
+
+impl CapabilityInference {
+    /// Create a pass with no declared modules
+    pub fn new() -> Self {
+        CapabilityInference::default()
+    }
+
+    /// Declare the capabilities a library module requires; scripts that
+    /// import it inherit them
+    pub fn declare(&mut self, module: &str, capabilities: &[&str]) {
+        let declared = self.declared.entry(module.to_string()).or_default();
+        for capability in capabilities {
+            if !declared.iter().any(|known| known == capability) {
+                declared.push(capability.to_string());
+            }
+        }
+    }
+
+    /// Declare every module loaded by `resolver` with the capabilities its
+    /// source requests
+    pub fn declare_loaded(&mut self, resolver: &ModuleResolver) {
+        for (_, module) in resolver.loaded_modules() {
+            let required = self.infer(&module.ast);
+            self.declare(&module.name, &required.capabilities());
+        }
+    }
+
+    /// Capabilities declared for `module`
+    pub fn declared(&self, module: &str) -> &[String] {
+        self.declared.get(module).map_or(&[], Vec::as_slice)
+    }
+
+    /// Collect the capabilities `nodes` may request
+    pub fn infer(&self, nodes: &[AstNode]) -> CapabilityRequirements {
+        let mut found = CapabilityRequirements::default();
+        for site in Query::kind("RequestStmt").or(Query::kind("Import")).find(nodes) {
+            let chant = site.enclosing_name("ChantDef");
+            match site.node {
+                AstNode::RequestStmt { capability, justification, span } => {
+                    found.requirements.push(Requirement {
+                        capability: capability_name(capability),
+                        justification: justification.clone(),
+                        chant,
+                        module: site.enclosing_name("ModuleDecl"),
+                        span: span.clone(),
+                    });
+                }
+                AstNode::Import { module_name, span, .. } => {
+                    for capability in self.declared(module_name) {
+                        found.requirements.push(Requirement {
+                            capability: capability.clone(),
+                            justification: format!("required by {}", module_name),
+                            chant: chant.clone(),
+                            module: Some(module_name.clone()),
+                            span: span.clone(),
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        found
+    }
+}
+
+/// Collect the capabilities a script may request, without declared modules
+pub fn infer_capabilities(nodes: &[AstNode]) -> CapabilityRequirements {
+    CapabilityInference::new().infer(nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_touched_skips_denied_capabilities() {
+        let mut audit = CapabilityAudit::new();
+        let justification = || AuditEvent::Requested { justification: "why".to_string() };
+        audit.record("FS.read", justification(), None, SourceSpan::unknown());
+        audit.record("FS.read", AuditEvent::Denied { reason: "no".to_string() }, None, SourceSpan::unknown());
+        audit.record("VGA.write", justification(), None, SourceSpan::unknown());
+        audit.record("VGA.write", AuditEvent::Granted, None, SourceSpan::unknown());
+        audit.record("VGA.write", AuditEvent::Used { by: "draw".to_string() }, None, SourceSpan::unknown());
+
+        assert_eq!(audit.touched(), vec!["VGA.write"]);
+        assert!(!audit.is_granted("FS.read"));
+        assert_eq!(audit.for_capability("FS.read").count(), 2);
+    }
+
+    #[test]
+    fn test_entry_to_value() {
+        let entry = AuditEntry {
+            capability: "VGA.write".to_string(),
+            event: AuditEvent::Used { by: "draw".to_string() },
+            chant: Some("paint".to_string()),
+            span: SourceSpan::unknown(),
+        };
+        let Value::Map(fields) = entry.to_value() else { panic!("Expected a map") };
+        assert_eq!(fields["event"], Value::Text("use".to_string()));
+        assert_eq!(fields["detail"], Value::Text("draw".to_string()));
+        assert_eq!(fields["chant"], Value::Text("paint".to_string()));
+    }
+
+    #[test]
+    fn test_nondeterministic_namespaces() {
+        assert!(is_nondeterministic("Time.now"));
+        assert!(is_nondeterministic("FS"));
+        assert!(!is_nondeterministic("VGA.write"));
+        assert!(!is_nondeterministic("Filesystem.read"));
+    }
+
+    #[test]
+    fn test_declare_merges_requirements() {
+        let mut inference = CapabilityInference::new();
+        inference.declare("Net", &["Net.send"]);
+        inference.declare("Net", &["Net.send", "Net.connect"]);
+        assert_eq!(inference.declared("Net"), ["Net.send".to_string(), "Net.connect".to_string()]);
+        assert!(inference.declared("Math").is_empty());
+    }
+}
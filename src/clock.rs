@@ -0,0 +1,101 @@
+//! # Clock
+//!
+//! Host-provided time source for execution deadlines.
+//!
+//! The evaluator and the VM read the clock at safepoints (loop back-edges
+//! and calls) while running under a deadline, and abort with a timeout once
+//! the clock reaches it. Units are up to the host: AethelOS can hand in its
+//! tick counter, while std builds default to [`SystemClock`] milliseconds.
+//! Any `Fn() -> u64` closure is a clock too. A [`StepClock`] counts its own
+//! readings instead of time, so a deadline becomes a budget of safepoints
+//! that runs out at the same point on every run.
+//!
+//! ```
+//! use glimmer_weave::clock::Clock;
+//!
+//! let ticks = || 42;
+//! assert_eq!(ticks.now(), 42);
+//! ```
+
+/// Monotonic time source read at safepoints
+pub trait Clock: crate::sync::MaybeSend {
+    /// Current time; must never go backwards
+    fn now(&self) -> u64;
+}
+
+impl<F: Fn() -> u64 + crate::sync::MaybeSend> Clock for F {
+    fn now(&self) -> u64 {
+        self()
+    }
+}
+
+/// Milliseconds since the first `SystemClock` reading in this process
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        use std::sync::OnceLock;
+        use std::time::Instant;
+
+        static ORIGIN: OnceLock<Instant> = OnceLock::new();
+        ORIGIN.get_or_init(Instant::now).elapsed().as_millis() as u64
+    }
+}
+
+/// Clock that advances by one each time it is read
+///
+/// Deterministic evaluators measure deadlines against it (see
+/// [`Evaluator::set_deterministic`](crate::eval::Evaluator::set_deterministic)).
+#[derive(Debug, Default)]
+pub struct StepClock {
+    steps: core::cell::Cell<u64>,
+}
+
+impl StepClock {
+    /// Create a clock that has not been read yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Clock for StepClock {
+    fn now(&self) -> u64 {
+        let step = self.steps.get() + 1;
+        self.steps.set(step);
+        step
+    }
+}
+
+/// Default clock for new evaluators and VMs: [`SystemClock`] under std,
+/// none otherwise (the host must install one before using deadlines)
+pub(crate) fn default_clock() -> Option<alloc::boxed::Box<dyn Clock>> {
+    #[cfg(feature = "std")]
+    {
+        Some(alloc::boxed::Box::new(SystemClock))
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        None
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_system_clock_is_monotonic() {
+        let first = SystemClock.now();
+        let second = SystemClock.now();
+        assert!(second >= first);
+    }
+
+    #[test]
+    fn test_step_clock_counts_readings() {
+        let clock = StepClock::new();
+        assert_eq!([clock.now(), clock.now(), clock.now()], [1, 2, 3]);
+    }
+}
@@ -9,21 +9,79 @@
 //!
 //! - **Calling Convention**: System V AMD64 ABI
 //! - **Registers**:
-//!   - Function args: rdi, rsi, rdx, rcx, r8, r9
+//!   - Function args: rdi, rsi, rdx, rcx, r8, r9, then the stack
 //!   - Return value: rax
 //!   - Callee-saved: rbx, r12-r15, rbp, rsp
 //!   - Caller-saved: r10, r11
-//! - **Stack**: 16-byte aligned before `call` instructions
+//! - **Temporaries**: rax holds the value being computed; rbx, rdx, r10 and
+//!   r11 are scratch within a single operation. A value that must survive
+//!   the evaluation of another expression is pushed (binary operands) or
+//!   kept in a frame slot (struct pointers), since a nested call may clobber
+//!   any register. Functions save rbx, which the ABI makes callee-saved.
+//! - **Stack**: 16-byte aligned before `call` instructions. Each function
+//!   reserves its whole frame in the prologue, once the body has been
+//!   generated and the frame's size is known.
+//! - **Function values**: a chant used as a value is a pointer to a record
+//!   whose first word is its code address, followed by captured variables.
+//!   Chants without captures get a static record in `.data`; nested chants
+//!   that capture are allocated where they are defined, copying captures by
+//!   value. Indirect calls pass the record in r10 and `call *0(%r10)`.
+//! - **Fixed-size arrays**: a variable typed `array of T length N` and bound
+//!   to a list literal keeps its N elements inline in the frame, and the
+//!   variable holds their address. Indexing it by name reads the frame
+//!   directly: constant indices are checked at compile time, any other index
+//!   at run time, trapping with `ud2` when out of bounds.
+//! - **Branch layout**: given a [`Profile`], a `should` whose `otherwise`
+//!   branch ran more often than its `then` branch is emitted with the
+//!   `otherwise` branch on the fall-through path.
 //!
 //! ## Output Format
 //!
 //! Generates AT&T syntax assembly that can be assembled with GNU as or NASM.
 
 use alloc::string::{String, ToString};
+use alloc::boxed::Box;
 use alloc::vec::Vec;
 use alloc::format;
 use crate::ast::*;
-use crate::native_runtime::NativeRuntime;
+use crate::native_runtime::{enum_field_offset, enum_size, NativeRuntime, ENUM_TAG_OFFSET};
+use crate::profile::Profile;
+use crate::target::{Arch, TargetSpec};
+
+/// Round a stack area up to the 16-byte alignment calls need
+fn align16(bytes: i32) -> i32 {
+    (bytes + 15) & !15
+}
+
+/// Offset of the code address in a closure record
+///
+/// A function value is a pointer to a closure record: the code address,
+/// then one slot per captured value. Chants that capture nothing share a
+/// static record; the callee finds its record in r10.
+const CLOSURE_CODE_OFFSET: i32 = 0;
+
+/// Identifiers a chant body refers to, in order of first use
+fn referenced_names(body: &[AstNode]) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut pending: Vec<&AstNode> = body.iter().rev().collect();
+    while let Some(node) = pending.pop() {
+        if let AstNode::Ident { name, .. } = node {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        pending.extend(node.children().into_iter().rev());
+    }
+    names
+}
+
+/// Global symbol of the C-callable wrapper for a top-level chant
+///
+/// Embedders look compiled chants up by this name (see
+/// `native_module::CompiledModule::get_fn`).
+pub fn export_symbol(chant: &str) -> String {
+    format!("glimmer_{}", chant)
+}
 
 /// x86-64 register
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -67,6 +125,12 @@ pub enum Instruction {
     /// Move: mov src, dst
     Mov(String, String),
 
+    /// Move a single byte: movb src, dst
+    MovByte(String, String),
+
+    /// Move a 32-bit value, sign-extended: movslq src, dst
+    MovSignExtend(String, String),
+
     /// Add: add src, dst (dst += src)
     Add(String, String),
 
@@ -106,6 +170,9 @@ pub enum Instruction {
     /// Call function: call label
     Call(String),
 
+    /// Call through a code address in memory: call *operand
+    CallIndirect(String),
+
     /// Return: ret
     Ret,
 
@@ -157,6 +224,9 @@ pub enum Instruction {
     /// Set if less or equal: setle dst (signed)
     Setle(String),
 
+    /// Trap on an invalid opcode: ud2
+    Trap,
+
     /// Comment (for debugging generated code)
     Comment(String),
 }
@@ -167,6 +237,8 @@ impl Instruction {
         match self {
             Instruction::Label(label) => format!("{}:", label),
             Instruction::Mov(src, dst) => format!("    movq {}, {}", src, dst),
+            Instruction::MovByte(src, dst) => format!("    movb {}, {}", src, dst),
+            Instruction::MovSignExtend(src, dst) => format!("    movslq {}, {}", src, dst),
             Instruction::Add(src, dst) => format!("    addq {}, {}", src, dst),
             Instruction::Sub(src, dst) => format!("    subq {}, {}", src, dst),
             Instruction::IMul(src, dst) => format!("    imulq {}, {}", src, dst),
@@ -180,6 +252,7 @@ impl Instruction {
             Instruction::Jge(label) => format!("    jge {}", label),
             Instruction::Jle(label) => format!("    jle {}", label),
             Instruction::Call(label) => format!("    call {}", label),
+            Instruction::CallIndirect(target) => format!("    call *{}", target),
             Instruction::Ret => "    ret".to_string(),
             Instruction::Push(src) => format!("    pushq {}", src),
             Instruction::Pop(dst) => format!("    popq {}", dst),
@@ -197,6 +270,7 @@ impl Instruction {
             Instruction::Setl(dst) => format!("    setl {}", dst),
             Instruction::Setge(dst) => format!("    setge {}", dst),
             Instruction::Setle(dst) => format!("    setle {}", dst),
+            Instruction::Trap => "    ud2".to_string(),
             Instruction::Comment(text) => format!("    # {}", text),
         }
     }
@@ -213,9 +287,19 @@ pub struct CodeGen {
     /// Current stack offset (for local variables)
     stack_offset: i32,
 
+    /// Lowest stack offset used by the current frame, which sizes it
+    frame_low: i32,
+
+    /// Bytes pushed below the current frame by expression temporaries
+    pushed: i32,
+
     /// Variable locations on stack (name -> offset from rbp)
     variables: Vec<(String, i32)>,
 
+    /// Fixed-size arrays in the current frame: the variable's slot, the
+    /// offset of their first element, and their length
+    arrays: Vec<(i32, i32, usize)>,
+
     /// Current function name (for TCO detection)
     current_function: Option<String>,
 
@@ -227,6 +311,42 @@ pub struct CodeGen {
 
     /// String literals (label, data)
     string_literals: Vec<(String, String)>,
+
+    /// Variant cases (case name, enum tag, field slots)
+    variant_cases: Vec<(String, i64, usize)>,
+
+    /// Set while generating a `match` that ends a chant body
+    tail_match: bool,
+
+    /// Names of the chants defined so far (and the top-level ones)
+    chants: Vec<String>,
+
+    /// Parameters of the chants defined so far (and the top-level ones),
+    /// to place named arguments
+    chant_params: Vec<(String, Vec<Parameter>)>,
+
+    /// Chants that capture variables, and so exist only as closure records
+    closures: Vec<String>,
+
+    /// Chants used as values, which get a static closure record
+    static_closures: Vec<String>,
+
+    /// Frame slot holding the running closure's own record
+    closure_self: Option<i32>,
+
+    /// Top-level chants, which get an exported wrapper symbol
+    exports: Vec<String>,
+
+    /// Branch outcomes from an earlier run, which decide branch layout
+    profile: Option<Profile>,
+
+    /// Whether the program calls the `exit` builtin, which returns from
+    /// `main` through the frame saved at `.L_main_frame`
+    program_exit: bool,
+
+    /// Machine the code is generated for: its argument registers and word
+    /// size
+    target: TargetSpec,
 }
 
 impl Default for CodeGen {
@@ -242,12 +362,209 @@ impl CodeGen {
             instructions: Vec::new(),
             label_counter: 0,
             stack_offset: 0,
+            frame_low: 0,
+            pushed: 0,
             variables: Vec::new(),
+            arrays: Vec::new(),
             current_function: None,
             function_entry_label: None,
             struct_defs: Vec::new(),
             string_literals: Vec::new(),
+            variant_cases: [("Triumph", 1, 1), ("Present", 1, 1), ("Mishap", 0, 1), ("Absent", 0, 0)]
+                .iter()
+                .map(|(name, tag, slots)| (name.to_string(), *tag, *slots))
+                .collect(),
+            tail_match: false,
+            chants: Vec::new(),
+            chant_params: Vec::new(),
+            closures: Vec::new(),
+            static_closures: Vec::new(),
+            closure_self: None,
+            exports: Vec::new(),
+            profile: None,
+            program_exit: false,
+            target: TargetSpec::X86_64_SYSV,
+        }
+    }
+
+    /// Create a code generator for `target`
+    ///
+    /// Fails for architectures without a back end; only x86-64 has one.
+    pub fn for_target(target: &TargetSpec) -> Result<Self, String> {
+        if target.arch != Arch::X86_64 || target.pointer_width != 64 {
+            return Err(format!(
+                "No code generator for target '{}' ({}, {}-bit)",
+                target.name,
+                target.arch.name(),
+                target.pointer_width
+            ));
+        }
+        Ok(CodeGen { target: *target, ..Self::new() })
+    }
+
+    /// The target code is generated for
+    pub fn target(&self) -> &TargetSpec {
+        &self.target
+    }
+
+    /// Registers carrying the first call arguments
+    fn arg_registers(&self) -> &'static [&'static str] {
+        self.target.calls.arg_registers
+    }
+
+    /// Size of a value slot in bytes
+    fn word(&self) -> i32 {
+        self.target.pointer_bytes()
+    }
+
+    /// Offset of a captured value in a closure record
+    fn closure_capture_offset(&self, index: usize) -> i32 {
+        self.word() * (index as i32 + 1)
+    }
+
+    /// Offset from rbp of a parameter passed on the stack (past the argument
+    /// registers), above the saved rbx, the saved rbp and the return address
+    fn stack_param_offset(&self, index: usize) -> i32 {
+        3 * self.word() + self.word() * (index - self.arg_registers().len()) as i32
+    }
+
+    /// Create a code generator that lays out branches by their outcomes in
+    /// an earlier run
+    pub fn with_profile(profile: Profile) -> Self {
+        CodeGen { profile: Some(profile), ..Self::new() }
+    }
+
+    /// Whether the profile saw a `should` take its `otherwise` branch more
+    /// often than its `then` branch, which then goes on the fall-through path
+    fn else_is_hot(&self, span: &crate::source_location::SourceSpan) -> bool {
+        self.profile
+            .as_ref()
+            .and_then(|profile| profile.branch(span))
+            .is_some_and(|counts| counts.not_taken > counts.taken)
+    }
+
+    /// Generate a chant body, leaving its implicit value in rax
+    ///
+    /// Follows the evaluator's rule: the last statement's value when it is
+    /// an expression, the taken branch's when it is a `should` or `match`,
+    /// and Nothing (0) otherwise.
+    fn gen_chant_body(&mut self, body: &[AstNode]) -> Result<(), String> {
+        let Some((last, init)) = body.split_last() else {
+            self.emit(Instruction::Mov("$0".to_string(), Register::Rax.name().to_string()));
+            return Ok(());
+        };
+        for stmt in init {
+            self.gen_statement(stmt)?;
+        }
+
+        match last {
+            AstNode::IfStmt { condition, then_branch, else_branch, span } => {
+                let else_label = format!(".L_else_{}", self.label_counter);
+                let end_label = format!(".L_if_end_{}", self.label_counter);
+                self.label_counter += 1;
+                let else_body = else_branch.as_deref().unwrap_or_default();
+
+                self.gen_expr(condition)?;
+                self.emit(Instruction::Cmp("$0".to_string(), Register::Rax.name().to_string()));
+                if self.else_is_hot(span) {
+                    // The otherwise branch falls through; `then` is jumped to
+                    let then_label = format!(".L_then_{}", self.label_counter - 1);
+                    self.emit(Instruction::Jne(then_label.clone()));
+                    self.gen_chant_body(else_body)?;
+                    self.emit(Instruction::Jmp(end_label.clone()));
+                    self.emit(Instruction::Label(then_label));
+                    self.gen_chant_body(then_branch)?;
+                } else {
+                    self.emit(Instruction::Je(else_label.clone()));
+                    self.gen_chant_body(then_branch)?;
+                    self.emit(Instruction::Jmp(end_label.clone()));
+                    self.emit(Instruction::Label(else_label));
+                    self.gen_chant_body(else_body)?;
+                }
+                self.emit(Instruction::Label(end_label));
+                Ok(())
+            }
+            AstNode::MatchStmt { .. } => {
+                self.tail_match = true;
+                self.gen_statement(last)
+            }
+            AstNode::YieldStmt { .. } => self.gen_statement(last),
+            AstNode::ExprStmt { expr, .. } => self.gen_expr(expr),
+            node if node.is_expression() => self.gen_expr(node),
+            node => {
+                self.gen_statement(node)?;
+                self.emit(Instruction::Mov("$0".to_string(), Register::Rax.name().to_string()));
+                Ok(())
+            }
+        }
+    }
+
+    /// Generate a matched arm: its guard, which jumps to `guard_exit` when
+    /// false, then its body
+    fn gen_arm(&mut self, arm: &crate::ast::MatchArm, guard_exit: &str, tail: bool) -> Result<(), String> {
+        if let Some(guard) = &arm.guard {
+            self.gen_expr(guard)?;
+            self.emit(Instruction::Cmp("$0".to_string(), Register::Rax.name().to_string()));
+            self.emit(Instruction::Je(guard_exit.to_string()));
+        }
+        self.gen_arm_body(&arm.body, tail)
+    }
+
+    /// Generate the statements of a match arm; in a tail match they leave
+    /// the chant's value in rax
+    fn gen_arm_body(&mut self, body: &[AstNode], tail: bool) -> Result<(), String> {
+        if tail {
+            return self.gen_chant_body(body);
+        }
+        for stmt in body {
+            self.gen_statement(stmt)?;
+        }
+        Ok(())
+    }
+
+    /// Enum tag and field count of a variant case, if it is known
+    fn variant_case(&self, case: &str) -> Option<(i64, usize)> {
+        self.variant_cases
+            .iter()
+            .find(|(name, _, _)| name == case)
+            .map(|(_, tag, slots)| (*tag, *slots))
+    }
+
+    /// Build an enum case on the stack, leaving its address in rax
+    ///
+    /// Uses the enum layout documented in `native_runtime`: the tag, then
+    /// one slot per field.
+    fn gen_variant_alloc(&mut self, case: &str, tag: i64, fields: &[AstNode]) -> Result<(), String> {
+        self.emit(Instruction::Comment(format!("Create {} variant", case)));
+
+        let base = self.reserve_slot(enum_size(fields.len()));
+
+        // Store tag
+        self.emit(Instruction::Mov(
+            format!("${}", tag),
+            format!("{}(%rbp)", base + ENUM_TAG_OFFSET)
+        ));
+
+        // Evaluate each field into its slot
+        for (index, value) in fields.iter().enumerate() {
+            self.gen_expr(value)?;
+            self.emit(Instruction::Mov(
+                Register::Rax.name().to_string(),
+                format!("{}(%rbp)", base + enum_field_offset(index))
+            ));
         }
+
+        // Load address of enum into rax
+        self.emit(Instruction::Mov(
+            Register::Rbp.name().to_string(),
+            Register::Rax.name().to_string()
+        ));
+        self.emit(Instruction::Add(
+            format!("${}", base),
+            Register::Rax.name().to_string()
+        ));
+
+        Ok(())
     }
 
     /// Generate a unique label
@@ -268,12 +585,250 @@ impl CodeGen {
 
     /// Allocate space for a local variable
     fn alloc_var(&mut self, name: String) -> i32 {
-        self.stack_offset -= 8;  // 8 bytes for i64/f64
-        let offset = self.stack_offset;
+        let offset = self.reserve_slot(8);  // 8 bytes for i64/f64
         self.variables.push((name, offset));
         offset
     }
 
+    /// Reserve `bytes` of the current frame, returning their offset from rbp
+    fn reserve_slot(&mut self, bytes: i32) -> i32 {
+        self.stack_offset -= bytes;
+        self.frame_low = self.frame_low.min(self.stack_offset);
+        self.stack_offset
+    }
+
+    /// Emit a function prologue, returning where its frame reservation goes
+    ///
+    /// rbx is scratch in generated code but callee-saved in the ABI, so it
+    /// is saved next to rbp. The frame's size is only known once the body is
+    /// generated, so `end_frame` fills the reservation in afterwards.
+    fn begin_frame(&mut self, label: String) -> usize {
+        self.emit(Instruction::Label(label));
+        self.emit(Instruction::Push(Register::Rbp.name().to_string()));
+        self.emit(Instruction::Push(Register::Rbx.name().to_string()));
+        self.emit(Instruction::Mov(Register::Rsp.name().to_string(), Register::Rbp.name().to_string()));
+        self.emit(Instruction::Comment("frame reservation".to_string()));
+        self.stack_offset = 0;
+        self.frame_low = 0;
+        self.pushed = 0;
+        self.instructions.len() - 1
+    }
+
+    /// Reserve the frame begun at `reservation`, sized so that rsp is
+    /// 16-byte aligned at calls
+    ///
+    /// The return address, rbp and rbx take 24 bytes, so the frame is 8 bytes
+    /// off a multiple of 16.
+    fn end_frame(&mut self, reservation: usize) {
+        let size = align16(8 - self.frame_low) - 8;
+        self.instructions[reservation] = Instruction::Sub(format!("${}", size), Register::Rsp.name().to_string());
+    }
+
+    /// Tear down the current frame, restoring rbx and rbp, before a `ret`
+    /// or a tail call's jump
+    fn emit_leave(&mut self) {
+        self.emit(Instruction::Mov(Register::Rbp.name().to_string(), Register::Rsp.name().to_string()));
+        self.emit(Instruction::Pop(Register::Rbx.name().to_string()));
+        self.emit(Instruction::Pop(Register::Rbp.name().to_string()));
+    }
+
+    /// Allocate a closure record for `chant` and bind it to the chant's name
+    ///
+    /// The record holds the code address, then the captured values as they
+    /// are now.
+    fn gen_closure_record(&mut self, chant: &str, captures: &[(String, i32)]) {
+        self.emit(Instruction::Comment(format!("Closure record for {}", chant)));
+        self.emit_runtime_call(NativeRuntime::gen_struct_alloc(captures.len() + 1));
+        self.emit(Instruction::Lea(format!(".L_func_{}(%rip)", chant), "%rbx".to_string()));
+        self.emit(Instruction::Mov("%rbx".to_string(), format!("{}(%rax)", CLOSURE_CODE_OFFSET)));
+        for (index, (_, offset)) in captures.iter().enumerate() {
+            self.emit(Instruction::Mov(format!("{}(%rbp)", offset), "%rbx".to_string()));
+            self.emit(Instruction::Mov("%rbx".to_string(), format!("{}(%rax)", self.closure_capture_offset(index))));
+        }
+
+        let record_offset = self.alloc_var(chant.to_string());
+        self.emit(Instruction::Mov(Register::Rax.name().to_string(), format!("{}(%rbp)", record_offset)));
+    }
+
+    /// Fail for a capturing chant used where its closure record is out of scope
+    fn check_not_closure(&self, chant: &str) -> Result<(), String> {
+        if self.closures.iter().any(|closure| closure == chant) {
+            return Err(format!(
+                "Chant {} captures variables, so native code can only use it where it is defined",
+                chant
+            ));
+        }
+        Ok(())
+    }
+
+    /// Emit a runtime snippet that calls into the native runtime, padding
+    /// rsp to the 16-byte alignment the call needs
+    ///
+    /// Values live across the snippet must be in the frame or pushed: the
+    /// call clobbers every caller-saved register.
+    fn emit_runtime_call(&mut self, code: Vec<Instruction>) {
+        let padding = self.pushed % 16;
+        if padding > 0 {
+            self.emit(Instruction::Sub(format!("${}", padding), Register::Rsp.name().to_string()));
+        }
+        for inst in code {
+            self.emit(inst);
+        }
+        if padding > 0 {
+            self.emit(Instruction::Add(format!("${}", padding), Register::Rsp.name().to_string()));
+        }
+    }
+
+    /// Evaluate call arguments and load the first six into their registers
+    ///
+    /// Arguments are evaluated left to right into a scratch area below rsp,
+    /// so evaluating one cannot clobber another already in its register.
+    /// The seventh argument onwards stay on the stack, from 0(%rsp) up, as
+    /// the System V ABI passes them. Returns the bytes still reserved, which
+    /// the caller releases after the call; rsp is 16-byte aligned until then.
+    fn gen_call_args(&mut self, args: &[AstNode]) -> Result<i32, String> {
+        self.gen_placed_call_args(args, &(0..args.len()).collect::<Vec<_>>())
+    }
+
+    /// Evaluate a call's arguments with named ones, in the order they are
+    /// written, into the places of the parameters of `func_name`
+    fn gen_named_call_args(&mut self, func_name: &str, args: &[AstNode], arg_names: &[String]) -> Result<i32, String> {
+        let params = self
+            .chant_params
+            .iter()
+            .rev()
+            .find(|(name, _)| name == func_name)
+            .map(|(_, params)| params.clone())
+            .ok_or_else(|| format!("Named arguments need a chant, and '{}' is not one", func_name))?;
+        let order = order_arguments(&params, (0..args.len()).collect(), arg_names).map_err(|e| e.to_string())?;
+        let mut places = vec![0; args.len()];
+        for (place, index) in order.into_iter().enumerate() {
+            places[index] = place;
+        }
+        self.gen_placed_call_args(args, &places)
+    }
+
+    /// Evaluate arguments in order, argument `i` into the register or stack
+    /// slot of position `places[i]`
+    fn gen_placed_call_args(&mut self, args: &[AstNode], places: &[usize]) -> Result<i32, String> {
+        let registers = self.arg_registers();
+        let word = self.word();
+        let in_registers = args.len().min(registers.len()) as i32;
+        let register_area = align16(word * in_registers);
+        let stack_area = align16(word * (args.len() as i32 - in_registers));
+        let padding = self.pushed % 16;
+        let reserved = register_area + stack_area + padding;
+
+        if reserved > 0 {
+            self.emit(Instruction::Sub(format!("${}", reserved), Register::Rsp.name().to_string()));
+        }
+        self.pushed += reserved;
+
+        for (arg, &index) in args.iter().zip(places) {
+            self.gen_expr(arg)?;
+            let slot = if index < registers.len() {
+                word * index as i32
+            } else {
+                register_area + word * (index - registers.len()) as i32
+            };
+            self.emit(Instruction::Mov(Register::Rax.name().to_string(), format!("{}(%rsp)", slot)));
+        }
+
+        for (index, register) in registers.iter().take(args.len()).enumerate() {
+            self.emit(Instruction::Mov(format!("{}(%rsp)", word * index as i32), format!("%{}", register)));
+        }
+        if register_area > 0 {
+            self.emit(Instruction::Add(format!("${}", register_area), Register::Rsp.name().to_string()));
+        }
+
+        self.pushed -= reserved;
+        Ok(stack_area + padding)
+    }
+
+    /// Bind a fixed-size array, laying its elements out in the frame and
+    /// binding the name to their address
+    fn gen_array_bind(&mut self, name: &str, length: usize, value: &AstNode) -> Result<(), String> {
+        let AstNode::List { elements, .. } = value else {
+            return Err(format!("Fixed-size array '{}' must be bound to a list literal in native codegen", name));
+        };
+        if elements.len() != length {
+            return Err(format!(
+                "Fixed-size array '{}' has length {} but is bound to {} elements",
+                name, length, elements.len()
+            ));
+        }
+        let bytes = i32::try_from(length * 8)
+            .map_err(|_| format!("Fixed-size array '{}' is too large for the stack", name))?;
+
+        self.emit(Instruction::Comment(format!("Fixed-size array {} of length {}", name, length)));
+        let base = self.reserve_slot(bytes);
+        for (index, element) in elements.iter().enumerate() {
+            self.gen_expr(element)?;
+            self.emit(Instruction::Mov(
+                Register::Rax.name().to_string(),
+                format!("{}(%rbp)", base + 8 * index as i32)
+            ));
+        }
+
+        let offset = self.alloc_var(name.to_string());
+        self.emit(Instruction::Lea(format!("{}(%rbp)", base), Register::Rax.name().to_string()));
+        self.emit(Instruction::Mov(Register::Rax.name().to_string(), format!("{}(%rbp)", offset)));
+        self.arrays.push((offset, base, length));
+        Ok(())
+    }
+
+    /// The first element's offset and the length of the fixed-size array
+    /// `object` names, if it names one in this frame
+    fn array_layout(&self, object: &AstNode) -> Option<(i32, usize)> {
+        let AstNode::Ident { name, .. } = object else {
+            return None;
+        };
+        let slot = self.get_var(name)?;
+        self.arrays
+            .iter()
+            .find(|(array_slot, _, _)| *array_slot == slot)
+            .map(|(_, base, length)| (*base, *length))
+    }
+
+    /// Whether `object` names a fixed-size array in this frame
+    fn is_array(&self, object: &AstNode) -> bool {
+        self.array_layout(object).is_some()
+    }
+
+    /// Generate the address of an element of a fixed-size array, for a load
+    /// or a store
+    ///
+    /// A constant index is checked here and folds into a frame offset. Any
+    /// other index is computed into rcx and checked at run time, trapping
+    /// when it is out of bounds.
+    fn gen_array_element(&mut self, object: &AstNode, index: &AstNode) -> Result<String, String> {
+        let (base, length) = self
+            .array_layout(object)
+            .ok_or_else(|| "Indexing in native codegen is only supported on fixed-size arrays".to_string())?;
+
+        if let AstNode::Number { value, .. } = index {
+            if value % 1.0 != 0.0 || *value < 0.0 || *value >= length as f64 {
+                return Err(format!("Index {} is out of bounds for an array of length {}", value, length));
+            }
+            return Ok(format!("{}(%rbp)", base + 8 * (*value as i32)));
+        }
+
+        let fail_label = format!(".L_bounds_fail_{}", self.label_counter);
+        let ok_label = format!(".L_bounds_ok_{}", self.label_counter);
+        self.label_counter += 1;
+
+        self.gen_expr(index)?;
+        self.emit(Instruction::Mov(Register::Rax.name().to_string(), Register::Rcx.name().to_string()));
+        self.emit(Instruction::Cmp("$0".to_string(), Register::Rcx.name().to_string()));
+        self.emit(Instruction::Jl(fail_label.clone()));
+        self.emit(Instruction::Cmp(format!("${}", length), Register::Rcx.name().to_string()));
+        self.emit(Instruction::Jl(ok_label.clone()));
+        self.emit(Instruction::Label(fail_label));
+        self.emit(Instruction::Trap);
+        self.emit(Instruction::Label(ok_label));
+        Ok(format!("{}(%rbp,%rcx,8)", base))
+    }
+
     /// Get variable stack offset
     fn get_var(&self, name: &str) -> Option<i32> {
         self.variables.iter()
@@ -284,28 +839,140 @@ impl CodeGen {
 
     /// Generate code for a program (list of statements)
     pub fn compile(&mut self, nodes: &[AstNode]) -> Result<Vec<Instruction>, String> {
+        // Top-level chants can be used as values before their definition
+        for node in nodes {
+            if let AstNode::ChantDef { name, params, .. } = node {
+                self.chants.push(name.clone());
+                self.chant_params.push((name.clone(), params.clone()));
+                self.exports.push(name.clone());
+            }
+        }
+
+        let used = referenced_names(nodes);
+        let builtin = |name: &str| used.iter().any(|used| used == name) && !self.chants.iter().any(|chant| chant == name);
+        self.program_exit = builtin("exit");
+        let wants_arguments = builtin("arguments");
+
         // Function prologue
-        self.emit(Instruction::Label("main".to_string()));
-        self.emit(Instruction::Push(Register::Rbp.name().to_string()));
-        self.emit(Instruction::Mov(Register::Rsp.name().to_string(), Register::Rbp.name().to_string()));
+        let reservation = self.begin_frame("main".to_string());
+        if self.program_exit {
+            self.emit(Instruction::Mov(Register::Rbp.name().to_string(), ".L_main_frame(%rip)".to_string()));
+        }
+        if wants_arguments {
+            self.gen_program_arguments();
+        }
 
         // Generate code for each statement
         for node in nodes {
             self.gen_statement(node)?;
         }
 
+        // Running to the end is success; `exit` arrives with its status
+        self.emit(Instruction::Mov("$0".to_string(), Register::Rax.name().to_string()));
+        if self.program_exit {
+            self.emit(Instruction::Label(".L_main_exit".to_string()));
+            self.emit(Instruction::Mov("$0".to_string(), ".L_main_frame(%rip)".to_string()));
+        }
+        self.end_frame(reservation);
+
         // Function epilogue
-        self.emit(Instruction::Mov(Register::Rbp.name().to_string(), Register::Rsp.name().to_string()));
-        self.emit(Instruction::Pop(Register::Rbp.name().to_string()));
+        self.emit_leave();
         self.emit(Instruction::Ret);
 
         Ok(self.instructions.clone())
     }
 
+    /// Bind `arguments` to the program's arguments, from the argc and argv
+    /// `main` is entered with
+    ///
+    /// The list uses the layout documented in `native_runtime` and holds
+    /// `argv[1..argc]` as heap texts, so it leaves out the program name.
+    fn gen_program_arguments(&mut self) {
+        let id = self.label_counter;
+        self.label_counter += 1;
+        let count = self.reserve_slot(8);
+        let argv = self.reserve_slot(8);
+        let index = self.reserve_slot(8);
+        let list = self.alloc_var("arguments".to_string());
+        let slot = |offset: i32| format!("{}(%rbp)", offset);
+
+        self.emit(Instruction::Comment("Program arguments from argc and argv".to_string()));
+        self.emit(Instruction::MovSignExtend("%edi".to_string(), "%rax".to_string()));
+        self.emit(Instruction::Mov("%rsi".to_string(), slot(argv)));
+        self.emit(Instruction::Dec("%rax".to_string()));
+        self.emit(Instruction::Cmp("$0".to_string(), "%rax".to_string()));
+        self.emit(Instruction::Jge(format!(".L_args_counted_{}", id)));
+        self.emit(Instruction::Mov("$0".to_string(), "%rax".to_string()));
+        self.emit(Instruction::Label(format!(".L_args_counted_{}", id)));
+        self.emit(Instruction::Mov("%rax".to_string(), slot(count)));
+
+        // capacity, length, then one text pointer per argument
+        self.emit(Instruction::Lea("16(,%rax,8)".to_string(), "%rdi".to_string()));
+        self.emit_runtime_call(NativeRuntime::gen_malloc_call());
+        self.emit(Instruction::Mov("%rax".to_string(), slot(list)));
+        self.emit(Instruction::Mov(slot(count), "%rbx".to_string()));
+        self.emit(Instruction::Mov("%rbx".to_string(), "0(%rax)".to_string()));
+        self.emit(Instruction::Mov("%rbx".to_string(), "8(%rax)".to_string()));
+
+        self.emit(Instruction::Mov("$0".to_string(), slot(index)));
+        self.emit(Instruction::Label(format!(".L_args_loop_{}", id)));
+        self.emit(Instruction::Mov(slot(index), "%rcx".to_string()));
+        self.emit(Instruction::Cmp(slot(count), "%rcx".to_string()));
+        self.emit(Instruction::Jge(format!(".L_args_done_{}", id)));
+        self.emit(Instruction::Mov(slot(argv), "%r11".to_string()));
+        self.emit(Instruction::Mov("8(%r11,%rcx,8)".to_string(), "%r11".to_string()));
+
+        // strlen into r10, as gen_string_alloc expects
+        self.emit(Instruction::Xor("%r10".to_string(), "%r10".to_string()));
+        self.emit(Instruction::Label(format!(".L_args_strlen_{}", id)));
+        self.emit(Instruction::Xor("%r8".to_string(), "%r8".to_string()));
+        self.emit(Instruction::MovByte("(%r11,%r10,1)".to_string(), "%r8b".to_string()));
+        self.emit(Instruction::Cmp("$0".to_string(), "%r8".to_string()));
+        self.emit(Instruction::Je(format!(".L_args_copy_{}", id)));
+        self.emit(Instruction::Inc("%r10".to_string()));
+        self.emit(Instruction::Jmp(format!(".L_args_strlen_{}", id)));
+        self.emit(Instruction::Label(format!(".L_args_copy_{}", id)));
+
+        let copy_id = self.label_counter;
+        self.label_counter += 1;
+        self.emit_runtime_call(NativeRuntime::gen_string_alloc(copy_id));
+        self.emit(Instruction::Mov(slot(list), "%rbx".to_string()));
+        self.emit(Instruction::Mov(slot(index), "%rcx".to_string()));
+        self.emit(Instruction::Mov("%rax".to_string(), "16(%rbx,%rcx,8)".to_string()));
+        self.emit(Instruction::Inc(slot(index)));
+        self.emit(Instruction::Jmp(format!(".L_args_loop_{}", id)));
+        self.emit(Instruction::Label(format!(".L_args_done_{}", id)));
+    }
+
+    /// Generate `exit(status)`: return the status from `main`, unwinding
+    /// every chant frame in between
+    ///
+    /// In a chant called from outside the program (through a
+    /// `native_module` export), where `main` is not running, `exit` returns
+    /// the status from that chant instead.
+    fn gen_exit(&mut self, status: &AstNode) -> Result<(), String> {
+        let local = format!(".L_exit_local_{}", self.label_counter);
+        self.label_counter += 1;
+
+        self.gen_expr(status)?;
+        self.emit(Instruction::Cmp("$0".to_string(), ".L_main_frame(%rip)".to_string()));
+        self.emit(Instruction::Je(local.clone()));
+        self.emit(Instruction::Mov(".L_main_frame(%rip)".to_string(), Register::Rbp.name().to_string()));
+        self.emit(Instruction::Jmp(".L_main_exit".to_string()));
+        self.emit(Instruction::Label(local));
+        self.emit_leave();
+        self.emit(Instruction::Ret);
+        Ok(())
+    }
+
     /// Generate code for a statement
     fn gen_statement(&mut self, node: &AstNode) -> Result<(), String> {
         match node {
-            AstNode::BindStmt { name, typ: _, value, ..  } | AstNode::WeaveStmt { name, typ: _, value, .. } => {
+            AstNode::BindStmt { name, typ, value, ..  } | AstNode::WeaveStmt { name, typ, value, .. } => {
+                if let Some(TypeAnnotation::Array { length, .. }) = typ {
+                    return self.gen_array_bind(name, *length, value);
+                }
+
                 // Evaluate expression into rax
                 self.gen_expr(value)?;
 
@@ -320,10 +987,21 @@ impl CodeGen {
             }
 
             AstNode::SetStmt { target, value, ..  } => {
-                // Only support simple variable assignment in codegen
-                // Index/field assignment requires heap allocation runtime
+                // Only support simple variable assignment in codegen, and
+                // stores into fixed-size arrays, which live in the frame
+                // Other index/field assignment requires heap allocation runtime
                 let name = match target.as_ref() {
                     AstNode::Ident { name, .. } => name,
+                    AstNode::IndexAccess { object, index, .. } if self.is_array(object) => {
+                        self.gen_expr(value)?;
+                        self.emit(Instruction::Push(Register::Rax.name().to_string()));
+                        self.pushed += 8;
+                        let element = self.gen_array_element(object, index)?;
+                        self.emit(Instruction::Pop(Register::Rax.name().to_string()));
+                        self.pushed -= 8;
+                        self.emit(Instruction::Mov(Register::Rax.name().to_string(), element));
+                        return Ok(());
+                    }
                     _ => {
                         return Err("Index and field assignment not supported in native codegen (requires heap allocation runtime). Use interpreter or bytecode VM instead.".to_string());
                     }
@@ -343,7 +1021,7 @@ impl CodeGen {
                 Ok(())
             }
 
-            AstNode::IfStmt { condition, then_branch, else_branch, .. } => {
+            AstNode::IfStmt { condition, then_branch, else_branch, span } => {
                 // Generate unique labels
                 let else_label = format!(".L_else_{}", self.label_counter);
                 let end_label = format!(".L_if_end_{}", self.label_counter);
@@ -358,6 +1036,22 @@ impl CodeGen {
                     Register::Rax.name().to_string()
                 ));
 
+                // A profiled-hot otherwise branch goes on the fall-through path
+                if let (Some(else_stmts), true) = (else_branch, self.else_is_hot(span)) {
+                    let then_label = format!(".L_then_{}", self.label_counter - 1);
+                    self.emit(Instruction::Jne(then_label.clone()));
+                    for stmt in else_stmts {
+                        self.gen_statement(stmt)?;
+                    }
+                    self.emit(Instruction::Jmp(end_label.clone()));
+                    self.emit(Instruction::Label(then_label));
+                    for stmt in then_branch {
+                        self.gen_statement(stmt)?;
+                    }
+                    self.emit(Instruction::Label(end_label));
+                    return Ok(());
+                }
+
                 // Jump to else branch if condition is false
                 if else_branch.is_some() {
                     self.emit(Instruction::Je(else_label.clone()));
@@ -432,6 +1126,9 @@ impl CodeGen {
                 self.label_counter += 1;
                 let end_label = format!(".L_match_end_{}", match_id);
 
+                // Arms of a match that ends a chant leave its value in rax
+                let tail = core::mem::take(&mut self.tail_match);
+
                 // Evaluate the match value into rax
                 self.gen_expr(value)?;
 
@@ -445,8 +1142,20 @@ impl CodeGen {
                 // Generate code for each arm
                 for (arm_idx, arm) in arms.iter().enumerate() {
                     let next_arm_label = format!(".L_match_arm_{}_{}", match_id, arm_idx + 1);
+                    // Where a false guard goes: the last arm has no next label
+                    let guard_exit = if arm_idx < arms.len() - 1 { next_arm_label.clone() } else { end_label.clone() };
+
+                    // A bare unit case name (`when Idle`) matches that case rather than binding
+                    let unit_case;
+                    let pattern = match &arm.pattern {
+                        Pattern::Ident(name) if self.variant_case(name).is_some_and(|(_, slots)| slots == 0) => {
+                            unit_case = Pattern::Enum { variant: name.clone(), inner: None };
+                            &unit_case
+                        }
+                        pattern => pattern,
+                    };
 
-                    match &arm.pattern {
+                    match pattern {
                         Pattern::Literal(lit_node) => {
                             // Evaluate the literal into rbx
                             self.gen_expr(lit_node)?;
@@ -473,9 +1182,7 @@ impl CodeGen {
                             }
 
                             // Pattern matched! Execute arm body
-                            for stmt in &arm.body {
-                                self.gen_statement(stmt)?;
-                            }
+                            self.gen_arm(arm, &guard_exit, tail)?;
 
                             // Jump to end
                             self.emit(Instruction::Jmp(end_label.clone()));
@@ -501,9 +1208,7 @@ impl CodeGen {
                             ));
 
                             // Execute arm body
-                            for stmt in &arm.body {
-                                self.gen_statement(stmt)?;
-                            }
+                            self.gen_arm(arm, &guard_exit, tail)?;
 
                             // Jump to end
                             self.emit(Instruction::Jmp(end_label.clone()));
@@ -514,12 +1219,71 @@ impl CodeGen {
                             }
                         }
 
+                        Pattern::Affix { .. } => {
+                            return Err(
+                                "Text affix patterns are not supported in native codegen. Use interpreter or bytecode VM instead."
+                                    .to_string(),
+                            );
+                        }
+
+                        Pattern::Or(alternatives) => {
+                            let body_label = format!(".L_match_arm_{}_{}_body", match_id, arm_idx);
+                            for alternative in alternatives {
+                                let Pattern::Literal(lit_node) = alternative else {
+                                    return Err(
+                                        "Only literal or-pattern alternatives are supported in native codegen. Use interpreter or bytecode VM instead."
+                                            .to_string(),
+                                    );
+                                };
+
+                                // Evaluate the literal into rbx
+                                self.gen_expr(lit_node)?;
+                                self.emit(Instruction::Mov(
+                                    Register::Rax.name().to_string(),
+                                    Register::Rbx.name().to_string()
+                                ));
+
+                                // Load match value back into rax
+                                self.emit(Instruction::Mov(
+                                    format!("{}(%rbp)", match_value_offset),
+                                    Register::Rax.name().to_string()
+                                ));
+
+                                // Any equal alternative goes on to the body
+                                self.emit(Instruction::Cmp(
+                                    Register::Rbx.name().to_string(),
+                                    Register::Rax.name().to_string()
+                                ));
+                                self.emit(Instruction::Je(body_label.clone()));
+                            }
+
+                            // No alternative matched
+                            self.emit(Instruction::Jmp(guard_exit.clone()));
+
+                            // Pattern matched! Execute arm body
+                            self.emit(Instruction::Label(body_label));
+                            self.gen_arm(arm, &guard_exit, tail)?;
+
+                            // Jump to end
+                            self.emit(Instruction::Jmp(end_label.clone()));
+
+                            // Emit next arm label
+                            if arm_idx < arms.len() - 1 {
+                                self.emit(Instruction::Label(next_arm_label));
+                            }
+                        }
+
+                        Pattern::Tuple(_) => {
+                            return Err(
+                                "Tuple patterns are not supported in native codegen. Use interpreter or bytecode VM instead."
+                                    .to_string(),
+                            );
+                        }
+
                         Pattern::Wildcard => {
                             // Wildcard - always matches, no binding
                             // Execute arm body
-                            for stmt in &arm.body {
-                                self.gen_statement(stmt)?;
-                            }
+                            self.gen_arm(arm, &guard_exit, tail)?;
 
                             // Jump to end
                             self.emit(Instruction::Jmp(end_label.clone()));
@@ -541,19 +1305,15 @@ impl CodeGen {
                                 Register::Rax.name().to_string()
                             ));
 
-                            // Load tag from offset +8 relative to enum pointer
-                            // Enum layout: [value at +0, tag at +8]
+                            // Load tag (see the enum layout in native_runtime)
                             self.emit(Instruction::Mov(
-                                "8(%rax)".to_string(),
+                                format!("{}(%rax)", ENUM_TAG_OFFSET),
                                 Register::Rbx.name().to_string()
                             ));
 
                             // Determine expected tag value for this variant
-                            let expected_tag = match variant.as_str() {
-                                "Triumph" | "Present" => 1,
-                                "Mishap" | "Absent" => 0,
-                                _ => return Err(format!("Unknown enum variant: {}", variant)),
-                            };
+                            let (expected_tag, slots) = self.variant_case(variant)
+                                .ok_or_else(|| format!("Unknown enum variant: {}", variant))?;
 
                             // Compare tag with expected value
                             self.emit(Instruction::Cmp(
@@ -566,47 +1326,59 @@ impl CodeGen {
                                 self.emit(Instruction::Jne(next_arm_label.clone()));
                             }
 
-                            // Tag matched! Now handle inner pattern binding
+                            // Tag matched! Now bind field slots to the inner patterns
                             if let Some(inner_pattern) = inner {
-                                match inner_pattern.as_ref() {
-                                    Pattern::Ident(var_name) => {
-                                        // Extract inner value and bind to variable
-                                        // Load match value pointer again (still in rax)
-                                        self.emit(Instruction::Mov(
-                                            format!("{}(%rbp)", match_value_offset),
-                                            Register::Rax.name().to_string()
-                                        ));
-
-                                        // Load inner value from offset +0
-                                        self.emit(Instruction::Mov(
-                                            "0(%rax)".to_string(),
-                                            Register::Rbx.name().to_string()
-                                        ));
-
-                                        // Store inner value to variable
-                                        let var_offset = self.alloc_var(var_name.clone());
-                                        self.emit(Instruction::Mov(
-                                            Register::Rbx.name().to_string(),
-                                            format!("{}(%rbp)", var_offset)
-                                        ));
-                                    }
-
-                                    Pattern::Wildcard => {
-                                        // No binding needed for wildcard
-                                    }
-
-                                    _ => {
-                                        return Err(
-                                            "Complex nested enum patterns not yet supported in native codegen".to_string()
-                                        );
-                                    }
+                                let binding = |name: &String| (name != "_").then(|| name.clone());
+                                let bindings: Vec<Option<String>> = match inner_pattern.as_ref() {
+                                    Pattern::Ident(var_name) => Some(vec![binding(var_name)]),
+                                    Pattern::Wildcard => Some(vec![None]),
+                                    // Several fields arrive as a list of names, with
+                                    // placeholders for nested patterns; `_` binds nothing
+                                    Pattern::Literal(list) => match list.as_ref() {
+                                        AstNode::List { elements, .. } => Some(elements.iter().map(|element| match element {
+                                            AstNode::Ident { name, .. } => binding(name),
+                                            _ => None,
+                                        }).collect()),
+                                        _ => None,
+                                    },
+                                    _ => None,
+                                }.ok_or_else(|| {
+                                    "Complex nested enum patterns not yet supported in native codegen".to_string()
+                                })?;
+
+                                if bindings.len() > slots {
+                                    return Err(format!(
+                                        "Pattern binds {} fields but {} has {}",
+                                        bindings.len(), variant, slots
+                                    ));
+                                }
+
+                                for (slot, binding) in bindings.into_iter().enumerate() {
+                                    let Some(var_name) = binding else { continue };
+
+                                    // Load match value pointer
+                                    self.emit(Instruction::Mov(
+                                        format!("{}(%rbp)", match_value_offset),
+                                        Register::Rax.name().to_string()
+                                    ));
+
+                                    // Load field from its slot
+                                    self.emit(Instruction::Mov(
+                                        format!("{}(%rax)", enum_field_offset(slot)),
+                                        Register::Rbx.name().to_string()
+                                    ));
+
+                                    // Store field to variable
+                                    let var_offset = self.alloc_var(var_name);
+                                    self.emit(Instruction::Mov(
+                                        Register::Rbx.name().to_string(),
+                                        format!("{}(%rbp)", var_offset)
+                                    ));
                                 }
                             }
 
                             // Execute arm body
-                            for stmt in &arm.body {
-                                self.gen_statement(stmt)?;
-                            }
+                            self.gen_arm(arm, &guard_exit, tail)?;
 
                             // Jump to end
                             self.emit(Instruction::Jmp(end_label.clone()));
@@ -629,48 +1401,94 @@ impl CodeGen {
                 // Generate function with TCO support
                 let old_function = self.current_function.clone();
                 let old_label = self.function_entry_label.clone();
-                let old_vars = self.variables.clone();
-                let old_stack = self.stack_offset;
+                let old_vars = core::mem::take(&mut self.variables);
+                let old_arrays = core::mem::take(&mut self.arrays);
+                let old_frame = (self.stack_offset, self.frame_low, self.pushed);
+                let old_closure = self.closure_self.take();
+
+                // Variables of the enclosing frame the body uses are captured
+                // by value into a closure record
+                let captures: Vec<(String, i32)> = referenced_names(body)
+                    .into_iter()
+                    .filter(|used| params.iter().all(|param| &param.name != used))
+                    .filter_map(|used| {
+                        let offset = old_vars.iter().rev().find(|(var, _)| *var == used)?.1;
+                        Some((used, offset))
+                    })
+                    .collect();
+                if !self.chants.contains(name) {
+                    self.chants.push(name.clone());
+                    self.chant_params.push((name.clone(), params.clone()));
+                }
+                if !captures.is_empty() {
+                    self.closures.push(name.clone());
+                }
 
                 // Create function label
                 let func_label = format!(".L_func_{}", name);
+                let end_label = format!(".L_func_end_{}", name);
                 self.current_function = Some(name.clone());
                 self.function_entry_label = Some(func_label.clone());
 
-                // Function prologue
-                self.emit(Instruction::Label(func_label.clone()));
-                self.emit(Instruction::Push(Register::Rbp.name().to_string()));
-                self.emit(Instruction::Mov(Register::Rsp.name().to_string(), Register::Rbp.name().to_string()));
+                // The enclosing code runs past the definition, not into it
+                self.emit(Instruction::Jmp(end_label.clone()));
+
+                // Function prologue; the function gets its own frame
+                let reservation = self.begin_frame(func_label);
 
                 // Allocate parameters on stack
-                // Args come in rdi, rsi, rdx, rcx, r8, r9 (System V ABI)
-                let arg_regs = ["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
+                // The first six come in rdi, rsi, rdx, rcx, r8, r9 (System V
+                // ABI); the rest are already on the stack above the return address
                 for (i, param) in params.iter().enumerate() {
-                    if i < arg_regs.len() {
+                    if let Some(register) = self.arg_registers().get(i) {
                         let offset = self.alloc_var(param.name.clone());
                         self.emit(Instruction::Mov(
-                            format!("%{}", arg_regs[i]),
+                            format!("%{}", register),
                             format!("{}(%rbp)", offset)
                         ));
+                    } else {
+                        self.variables.push((param.name.clone(), self.stack_param_offset(i)));
                     }
                 }
 
-                // Compile function body
-                for stmt in body {
-                    self.gen_statement(stmt)?;
+                // A closure is called with its record in r10 (the ABI's static
+                // chain register): keep the record for calls to itself, and
+                // copy the captured values into the frame
+                if !captures.is_empty() {
+                    let self_offset = self.alloc_var(name.clone());
+                    self.emit(Instruction::Mov("%r10".to_string(), format!("{}(%rbp)", self_offset)));
+                    self.closure_self = Some(self_offset);
+                    for (index, (captured, _)) in captures.iter().enumerate() {
+                        self.emit(Instruction::Mov(
+                            format!("{}(%r10)", self.closure_capture_offset(index)),
+                            Register::Rax.name().to_string()
+                        ));
+                        let offset = self.alloc_var(captured.clone());
+                        self.emit(Instruction::Mov(Register::Rax.name().to_string(), format!("{}(%rbp)", offset)));
+                    }
                 }
 
-                // Default return (if no explicit yield)
-                self.emit(Instruction::Mov("$0".to_string(), Register::Rax.name().to_string()));
-                self.emit(Instruction::Mov(Register::Rbp.name().to_string(), Register::Rsp.name().to_string()));
-                self.emit(Instruction::Pop(Register::Rbp.name().to_string()));
+                // Compile function body; without a `yield` it returns its last value
+                self.gen_chant_body(body)?;
+
+                // Return
+                self.emit_leave();
                 self.emit(Instruction::Ret);
+                self.end_frame(reservation);
+                self.emit(Instruction::Label(end_label));
 
                 // Restore context
                 self.current_function = old_function;
                 self.function_entry_label = old_label;
                 self.variables = old_vars;
-                self.stack_offset = old_stack;
+                self.arrays = old_arrays;
+                (self.stack_offset, self.frame_low, self.pushed) = old_frame;
+                self.closure_self = old_closure;
+
+                // Build the closure record and bind it to the chant's name
+                if !captures.is_empty() {
+                    self.gen_closure_record(name, &captures);
+                }
 
                 Ok(())
             }
@@ -682,31 +1500,48 @@ impl CodeGen {
                 Ok(())
             }
 
+            AstNode::VariantDef { name, variants, .. } => {
+                // Record each case's discriminant as its enum tag
+                self.emit(Instruction::Comment(format!("Variant definition: {}", name)));
+                let tags = variant_discriminants(variants);
+                for (case, tag) in variants.iter().zip(tags) {
+                    self.variant_cases.retain(|(existing, _, _)| existing != &case.name);
+                    self.variant_cases.push((case.name.clone(), tag, case.fields.len()));
+                }
+                Ok(())
+            }
+
             AstNode::YieldStmt { value, ..  } => {
                 // Check for tail call (yield f(args) where f is current function)
-                if let AstNode::Call { callee, args, .. } = value.as_ref() {
+                if let AstNode::Call { callee, args, arg_names, .. } = value.as_ref() {
                     if let AstNode::Ident { name: func_name, .. } = callee.as_ref() {
-                        if Some(func_name) == self.current_function.as_ref() {
+                        if Some(func_name) == self.current_function.as_ref() && arg_names.is_empty() {
                             // This is a tail call! Use TCO.
                             // Evaluate arguments
-                            let arg_regs = ["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
-                            for (i, arg) in args.iter().enumerate() {
-                                if i < arg_regs.len() {
-                                    // Evaluate arg into rax
-                                    self.gen_expr(arg)?;
-                                    // Move to appropriate argument register
-                                    self.emit(Instruction::Mov(
-                                        Register::Rax.name().to_string(),
-                                        format!("%{}", arg_regs[i])
-                                    ));
-                                }
+                            self.gen_call_args(args)?;
+
+                            // Stack arguments replace this call's own
+                            let registers = self.arg_registers().len();
+                            for i in registers..args.len() {
+                                self.emit(Instruction::Mov(
+                                    format!("{}(%rsp)", self.word() * (i - registers) as i32),
+                                    Register::Rax.name().to_string()
+                                ));
+                                self.emit(Instruction::Mov(
+                                    Register::Rax.name().to_string(),
+                                    format!("{}(%rbp)", self.stack_param_offset(i))
+                                ));
                             }
 
                             // Jump back to function start (TCO!)
                             if let Some(entry_label) = self.function_entry_label.clone() {
+                                // A closure re-enters with its record
+                                if let Some(self_offset) = self.closure_self {
+                                    self.emit(Instruction::Mov(format!("{}(%rbp)", self_offset), "%r10".to_string()));
+                                }
+
                                 // Restore stack frame
-                                self.emit(Instruction::Mov(Register::Rbp.name().to_string(), Register::Rsp.name().to_string()));
-                                self.emit(Instruction::Pop(Register::Rbp.name().to_string()));
+                                self.emit_leave();
                                 self.emit(Instruction::Jmp(entry_label));
                             }
 
@@ -717,8 +1552,7 @@ impl CodeGen {
 
                 // Not a tail call, emit normal return
                 self.gen_expr(value)?;
-                self.emit(Instruction::Mov(Register::Rbp.name().to_string(), Register::Rsp.name().to_string()));
-                self.emit(Instruction::Pop(Register::Rbp.name().to_string()));
+                self.emit_leave();
                 self.emit(Instruction::Ret);
                 Ok(())
             }
@@ -851,6 +1685,11 @@ impl CodeGen {
                 ))
             }
 
+            // Verify blocks only run under the test runner
+            AstNode::VerifyBlock { .. } => Ok(()),
+            // Applied when parsing and analyzing
+            AstNode::Speaks { .. } => Ok(()),
+
             AstNode::RequestStmt { .. } => {
                 // Capability requests are not supported in native codegen
                 //
@@ -885,15 +1724,64 @@ impl CodeGen {
     fn gen_expr(&mut self, node: &AstNode) -> Result<(), String> {
         match node {
             AstNode::Number { value: n, .. } => {
-                // Load immediate value into rax
+                // Load immediate value into rax, truncated toward zero like
+                // the `truncate` builtin
+                let int = crate::convert::truncate(*n)
+                    .map_err(|error| format!("Number {} cannot be compiled to a native integer: {}", n, error))?;
                 self.emit(Instruction::Mov(
-                    format!("${}", *n as i64),
+                    format!("${}", int),
                     Register::Rax.name().to_string()
                 ));
                 Ok(())
             }
 
+            AstNode::Integer { value, .. } => {
+                self.emit(Instruction::Mov(format!("${}", value), Register::Rax.name().to_string()));
+                Ok(())
+            }
+
+            // Native integers wrap at 64 bits, which is what big integers avoid
+            AstNode::BigInt { value, .. } => {
+                Err(format!("Big integer {}n cannot be compiled to native code", value))
+            }
+
+            // Measures are integers in base units; `in` divides back out
+            AstNode::Measure { value, unit, span } => {
+                let unit = crate::units::unit(unit).ok_or_else(|| format!("Unknown unit '{}'", unit))?;
+                self.gen_expr(&AstNode::Number { value: unit.to_base(*value), span: span.clone() })
+            }
+
+            AstNode::InUnit { value, unit, span } => {
+                let unit = crate::units::unit(unit).ok_or_else(|| format!("Unknown unit '{}'", unit))?;
+                self.gen_expr(&AstNode::BinaryOp {
+                    left: value.clone(),
+                    op: BinaryOperator::Div,
+                    right: Box::new(AstNode::Number { value: unit.factor, span: span.clone() }),
+                    span: span.clone(),
+                })
+            }
+
             AstNode::Ident { name, .. } => {
+                // A unit case of a user-defined variant, unless a variable shadows it
+                if self.get_var(name).is_none() {
+                    if let Some((tag, 0)) = self.variant_case(name) {
+                        return self.gen_variant_alloc(name, tag, &[]);
+                    }
+                }
+
+                // A chant used as a value is its static closure record
+                if self.get_var(name).is_none() && self.chants.contains(name) {
+                    self.check_not_closure(name)?;
+                    if !self.static_closures.contains(name) {
+                        self.static_closures.push(name.clone());
+                    }
+                    self.emit(Instruction::Lea(
+                        format!(".L_closure_{}(%rip)", name),
+                        Register::Rax.name().to_string()
+                    ));
+                    return Ok(());
+                }
+
                 // Load variable from stack into rax
                 let offset = self.get_var(name)
                     .ok_or_else(|| format!("Undefined variable: {}", name))?;
@@ -910,6 +1798,7 @@ impl CodeGen {
 
                 // Save left operand on stack
                 self.emit(Instruction::Push(Register::Rax.name().to_string()));
+                self.pushed += 8;
 
                 // Evaluate right operand into rax
                 self.gen_expr(right)?;
@@ -922,6 +1811,7 @@ impl CodeGen {
 
                 // Pop left operand from stack into rax
                 self.emit(Instruction::Pop(Register::Rax.name().to_string()));
+                self.pushed -= 8;
 
                 // Perform operation
                 match op {
@@ -1142,168 +2032,81 @@ impl CodeGen {
                 Ok(())
             }
 
-            AstNode::Call { callee, args, .. } => {
-                // Function call with System V ABI
-                // Arguments in: rdi, rsi, rdx, rcx, r8, r9
-                let arg_regs = ["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
+            AstNode::Call { callee, args, arg_names, .. } => {
+                if let (AstNode::Ident { name, .. }, [status]) = (callee.as_ref(), args.as_slice()) {
+                    if name == "exit" && self.program_exit && self.get_var(name).is_none() {
+                        return self.gen_exit(status);
+                    }
+                }
+
+                // Constructing a variant case that carries data
+                if let AstNode::Ident { name, .. } = callee.as_ref() {
+                    if let Some((tag, slots)) = self.variant_case(name).filter(|(_, slots)| *slots > 0) {
+                        if args.len() != slots {
+                            return Err(format!("{} takes {} fields, got {}", name, slots, args.len()));
+                        }
+                        return self.gen_variant_alloc(name, tag, args);
+                    }
+                }
 
-                // Evaluate arguments and move to argument registers
-                for (i, arg) in args.iter().enumerate() {
-                    if i < arg_regs.len() {
-                        self.gen_expr(arg)?;  // Result in rax
+                // Function call with System V ABI
+                let reserved = match callee.as_ref() {
+                    // A chant called by name
+                    AstNode::Ident { name: func_name, .. } if self.get_var(func_name).is_none() => {
+                        self.check_not_closure(func_name)?;
+
+                        // Evaluate arguments into registers and the stack
+                        let reserved = if arg_names.is_empty() {
+                            self.gen_call_args(args)?
+                        } else {
+                            self.gen_named_call_args(func_name, args, arg_names)?
+                        };
+                        self.emit(Instruction::Call(format!(".L_func_{}", func_name)));
+                        reserved
+                    }
+                    // A function value: a closure record, whose first slot is
+                    // the code address, passed to the callee in r10
+                    _ if !arg_names.is_empty() => {
+                        return Err("Named arguments need a chant called by name in native codegen".to_string());
+                    }
+                    _ => {
+                        self.gen_expr(callee)?;
+                        let callee_offset = self.reserve_slot(8);
                         self.emit(Instruction::Mov(
                             Register::Rax.name().to_string(),
-                            format!("%{}", arg_regs[i])
+                            format!("{}(%rbp)", callee_offset)
                         ));
+
+                        let reserved = self.gen_call_args(args)?;
+                        self.emit(Instruction::Mov(format!("{}(%rbp)", callee_offset), "%r10".to_string()));
+                        self.emit(Instruction::CallIndirect(format!("{}(%r10)", CLOSURE_CODE_OFFSET)));
+                        reserved
                     }
-                }
+                };
 
-                // Call the function
-                if let AstNode::Ident { name: func_name, .. } = callee.as_ref() {
-                    let func_label = format!(".L_func_{}", func_name);
-                    self.emit(Instruction::Call(func_label));
-                } else {
-                    return Err("Indirect calls not supported yet".to_string());
+                // Drop the stack arguments
+                if reserved > 0 {
+                    self.emit(Instruction::Add(format!("${}", reserved), Register::Rsp.name().to_string()));
                 }
 
                 // Result is in rax
                 Ok(())
             }
 
-            // Enum constructors - Outcome type
+            // Enum constructors - Outcome and Maybe use the general enum layout
             AstNode::Triumph { value, .. } => {
-                self.emit(Instruction::Comment("Create Triumph variant".to_string()));
-
-                // Evaluate inner value
-                self.gen_expr(value)?;
-
-                // Allocate 16 bytes on stack for enum (tag + value)
-                self.stack_offset -= 16;
-
-                // Store tag (1 for Triumph) at -8(%rbp)
-                self.emit(Instruction::Mov(
-                    "$1".to_string(),
-                    format!("{}(%rbp)", self.stack_offset + 8)
-                ));
-
-                // Store value (in rax) at stack_offset(%rbp)
-                self.emit(Instruction::Mov(
-                    Register::Rax.name().to_string(),
-                    format!("{}(%rbp)", self.stack_offset)
-                ));
-
-                // Load address of enum into rax
-                self.emit(Instruction::Mov(
-                    Register::Rbp.name().to_string(),
-                    Register::Rax.name().to_string()
-                ));
-                self.emit(Instruction::Add(
-                    format!("${}", self.stack_offset),
-                    Register::Rax.name().to_string()
-                ));
-
-                Ok(())
+                self.gen_variant_alloc("Triumph", 1, core::slice::from_ref(value.as_ref()))
             }
 
             AstNode::Mishap { value, .. } => {
-                self.emit(Instruction::Comment("Create Mishap variant".to_string()));
-
-                // Evaluate inner value
-                self.gen_expr(value)?;
-
-                // Allocate 16 bytes on stack for enum (tag + value)
-                self.stack_offset -= 16;
-
-                // Store tag (0 for Mishap) at -8(%rbp)
-                self.emit(Instruction::Mov(
-                    "$0".to_string(),
-                    format!("{}(%rbp)", self.stack_offset + 8)
-                ));
-
-                // Store value (in rax) at stack_offset(%rbp)
-                self.emit(Instruction::Mov(
-                    Register::Rax.name().to_string(),
-                    format!("{}(%rbp)", self.stack_offset)
-                ));
-
-                // Load address of enum into rax
-                self.emit(Instruction::Mov(
-                    Register::Rbp.name().to_string(),
-                    Register::Rax.name().to_string()
-                ));
-                self.emit(Instruction::Add(
-                    format!("${}", self.stack_offset),
-                    Register::Rax.name().to_string()
-                ));
-
-                Ok(())
+                self.gen_variant_alloc("Mishap", 0, core::slice::from_ref(value.as_ref()))
             }
 
-            // Enum constructors - Maybe type
             AstNode::Present { value, .. } => {
-                self.emit(Instruction::Comment("Create Present variant".to_string()));
-
-                // Evaluate inner value
-                self.gen_expr(value)?;
-
-                // Allocate 16 bytes on stack for enum (tag + value)
-                self.stack_offset -= 16;
-
-                // Store tag (1 for Present) at -8(%rbp)
-                self.emit(Instruction::Mov(
-                    "$1".to_string(),
-                    format!("{}(%rbp)", self.stack_offset + 8)
-                ));
-
-                // Store value (in rax) at stack_offset(%rbp)
-                self.emit(Instruction::Mov(
-                    Register::Rax.name().to_string(),
-                    format!("{}(%rbp)", self.stack_offset)
-                ));
-
-                // Load address of enum into rax
-                self.emit(Instruction::Mov(
-                    Register::Rbp.name().to_string(),
-                    Register::Rax.name().to_string()
-                ));
-                self.emit(Instruction::Add(
-                    format!("${}", self.stack_offset),
-                    Register::Rax.name().to_string()
-                ));
-
-                Ok(())
+                self.gen_variant_alloc("Present", 1, core::slice::from_ref(value.as_ref()))
             }
 
-            AstNode::Absent { .. } => {
-                self.emit(Instruction::Comment("Create Absent { span: SourceSpan::default() } variant".to_string()));
-
-                // Allocate 16 bytes on stack for enum (tag + value)
-                self.stack_offset -= 16;
-
-                // Store tag (0 for Absent { span: SourceSpan::default() }) at -8(%rbp)
-                self.emit(Instruction::Mov(
-                    "$0".to_string(),
-                    format!("{}(%rbp)", self.stack_offset + 8)
-                ));
-
-                // Store dummy value (0) at stack_offset(%rbp) - not used for Absent { span: SourceSpan::default() }
-                self.emit(Instruction::Mov(
-                    "$0".to_string(),
-                    format!("{}(%rbp)", self.stack_offset)
-                ));
-
-                // Load address of enum into rax
-                self.emit(Instruction::Mov(
-                    Register::Rbp.name().to_string(),
-                    Register::Rax.name().to_string()
-                ));
-                self.emit(Instruction::Add(
-                    format!("${}", self.stack_offset),
-                    Register::Rax.name().to_string()
-                ));
-
-                Ok(())
-            }
+            AstNode::Absent { .. } => self.gen_variant_alloc("Absent", 0, &[]),
 
             AstNode::StructLiteral { struct_name, fields, .. } => {
                 // Allocate struct on heap and initialize fields
@@ -1318,15 +2121,14 @@ impl CodeGen {
                 let field_count = struct_fields.len();
 
                 // Allocate heap memory for struct
-                let alloc_code = NativeRuntime::gen_struct_alloc(field_count);
-                for inst in alloc_code {
-                    self.emit(inst);
-                }
+                self.emit_runtime_call(NativeRuntime::gen_struct_alloc(field_count));
 
-                // Save struct pointer to rbx (we'll use it for field stores)
+                // Keep the struct pointer in the frame: evaluating a field
+                // may clobber any register
+                let pointer_offset = self.reserve_slot(8);
                 self.emit(Instruction::Mov(
                     "%rax".to_string(),
-                    "%rbx".to_string()
+                    format!("{}(%rbp)", pointer_offset)
                 ));
 
                 // Initialize each field
@@ -1339,6 +2141,11 @@ impl CodeGen {
                         .position(|f| f.name == *field_name)
                         .ok_or_else(|| format!("Field {} not found in struct {}", field_name, struct_name))?;
 
+                    // Reload the struct pointer into rbx for the store
+                    self.emit(Instruction::Mov(
+                        format!("{}(%rbp)", pointer_offset),
+                        "%rbx".to_string()
+                    ));
                     let store_code = NativeRuntime::gen_struct_field_store(field_index);
                     for inst in store_code {
                         self.emit(inst);
@@ -1347,13 +2154,19 @@ impl CodeGen {
 
                 // Move struct pointer back to rax (return value)
                 self.emit(Instruction::Mov(
-                    "%rbx".to_string(),
+                    format!("{}(%rbp)", pointer_offset),
                     "%rax".to_string()
                 ));
 
                 Ok(())
             }
 
+            AstNode::IndexAccess { object, index, .. } => {
+                let element = self.gen_array_element(object, index)?;
+                self.emit(Instruction::Mov(element, Register::Rax.name().to_string()));
+                Ok(())
+            }
+
             AstNode::FieldAccess { object, field, .. } => {
                 // Field access on heap-allocated structs
                 self.emit(Instruction::Comment(format!("Field access: .{}", field)));
@@ -1422,10 +2235,9 @@ impl CodeGen {
                 ));
 
                 // Allocate string on heap (length + data)
-                let alloc_code = NativeRuntime::gen_string_alloc();
-                for inst in alloc_code {
-                    self.emit(inst);
-                }
+                let copy_id = self.label_counter;
+                self.label_counter += 1;
+                self.emit_runtime_call(NativeRuntime::gen_string_alloc(copy_id));
 
                 // Result (heap pointer) is in %rax
                 Ok(())
@@ -1462,14 +2274,22 @@ impl CodeGen {
     pub fn to_assembly(&self) -> String {
         let mut asm = String::new();
 
-        // .data section for string literals
-        if !self.string_literals.is_empty() {
+        // .data section for string literals, static closure records and the
+        // frame `exit` returns through
+        if !self.string_literals.is_empty() || !self.static_closures.is_empty() || self.program_exit {
             asm.push_str(".data\n");
+            if self.program_exit {
+                asm.push_str(&format!(".p2align 3\n.L_main_frame:\n    {} 0\n", self.target.word_directive()));
+            }
             for (label, data) in &self.string_literals {
                 asm.push_str(&format!("{}:\n", label));
                 // Emit string as .ascii directive (not null-terminated)
                 asm.push_str(&format!("    .ascii \"{}\"\n", data));
             }
+            for chant in &self.static_closures {
+                asm.push_str(&format!(".L_closure_{}:\n", chant));
+                asm.push_str(&format!("    {} .L_func_{}\n", self.target.word_directive(), chant));
+            }
             asm.push('\n');
         }
 
@@ -1485,6 +2305,17 @@ impl CodeGen {
             asm.push('\n');
         }
 
+        // C-callable entry points for the top-level chants. Chants already
+        // follow the System V ABI, so a wrapper is a jump to the body;
+        // closures need their record in r10 and get none.
+        for chant in self.exports.iter().filter(|chant| !self.closures.contains(chant)) {
+            let symbol = export_symbol(chant);
+            asm.push_str(&format!("\n.globl {}\n", symbol));
+            asm.push_str(&format!(".type {}, @function\n", symbol));
+            asm.push_str(&format!("{}:\n", symbol));
+            asm.push_str(&format!("    jmp .L_func_{}\n", chant));
+        }
+
         asm
     }
 }
@@ -1496,6 +2327,23 @@ pub fn compile_to_asm(nodes: &[AstNode]) -> Result<String, String> {
     Ok(codegen.to_assembly())
 }
 
+/// Compile Glimmer-Weave AST to x86-64 assembly, laying out branches by
+/// their outcomes in `profile`
+pub fn compile_to_asm_with_profile(nodes: &[AstNode], profile: &Profile) -> Result<String, String> {
+    let mut codegen = CodeGen::with_profile(profile.clone());
+    codegen.compile(nodes)?;
+    Ok(codegen.to_assembly())
+}
+
+/// Compile Glimmer-Weave AST to assembly for `target`, laying out branches
+/// by `profile` when there is one
+pub fn compile_to_asm_for(nodes: &[AstNode], target: &TargetSpec, profile: Option<&Profile>) -> Result<String, String> {
+    let mut codegen = CodeGen::for_target(target)?;
+    codegen.profile = profile.cloned();
+    codegen.compile(nodes)?;
+    Ok(codegen.to_assembly())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1506,6 +2354,12 @@ mod tests {
         SourceSpan::unknown()
     }
 
+    fn compile_source(source: &str) -> Result<String, String> {
+        let tokens = crate::Lexer::new(source).tokenize_positioned();
+        let ast = crate::Parser::new(tokens).parse().expect("parse failed");
+        compile_to_asm(&ast)
+    }
+
     #[test]
     fn test_compile_number() {
         let ast = vec![AstNode::Number { value: 42.0, span: span() }];
@@ -1518,6 +2372,15 @@ mod tests {
         assert!(asm.contains("movq $42"));
     }
 
+    #[test]
+    fn test_number_literals_truncate_like_the_builtin() {
+        let asm = compile_to_asm(&[AstNode::Number { value: -2.7, span: span() }]).unwrap();
+        assert!(asm.contains("movq $-2,"));
+
+        let huge = compile_to_asm(&[AstNode::Number { value: 1e300, span: span() }]);
+        assert!(huge.unwrap_err().contains("outside the 64-bit integer range"));
+    }
+
     #[test]
     fn test_compile_arithmetic() {
         use AstNode::*;
@@ -1704,15 +2567,15 @@ mod tests {
             },
             WhileStmt {
                 condition: Box::new(BinaryOp {
-                    left: Box::new(Ident { name: "x".to_string(), span: SourceSpan::default() }),
+                    left: Box::new(Ident { name: "x".to_string(), span: SourceSpan::default(), slot: None }),
                     op: Greater,
                     right: Box::new(Number { value: 0.0, span: span() }),
                     span: span(),
                 }),
                 body: vec![SetStmt {
-                    target: Box::new(Ident { name: "x".to_string(), span: SourceSpan::default() }),
+                    target: Box::new(Ident { name: "x".to_string(), span: SourceSpan::default(), slot: None }),
                     value: Box::new(BinaryOp {
-                        left: Box::new(Ident { name: "x".to_string(), span: SourceSpan::default() }),
+                        left: Box::new(Ident { name: "x".to_string(), span: SourceSpan::default(), slot: None }),
                         op: Sub,
                         right: Box::new(Number { value: 1.0, span: span() }),
                         span: span(),
@@ -1757,30 +2620,31 @@ mod tests {
             return_type: None,
             body: vec![IfStmt {
                 condition: Box::new(BinaryOp {
-                    left: Box::new(Ident { name: "n".to_string(), span: SourceSpan::default() }),
+                    left: Box::new(Ident { name: "n".to_string(), span: SourceSpan::default(), slot: None }),
                     op: LessEq,
                     right: Box::new(Number { value: 0.0, span: span() }),
                     span: span(),
                 }),
                 then_branch: vec![YieldStmt {
-                    value: Box::new(Ident { name: "acc".to_string(), span: SourceSpan::default() }),
+                    value: Box::new(Ident { name: "acc".to_string(), span: SourceSpan::default(), slot: None }),
                     span: span(),
                 }],
                 else_branch: Some(vec![YieldStmt {
                     value: Box::new(Call {
-                        callee: Box::new(Ident { name: "sum_to".to_string(), span: SourceSpan::default() }),
+                        callee: Box::new(Ident { name: "sum_to".to_string(), span: SourceSpan::default(), slot: None }),
                         type_args: vec![],
+                        arg_names: vec![],
                         args: vec![
                             BinaryOp {
-                                left: Box::new(Ident { name: "n".to_string(), span: SourceSpan::default() }),
+                                left: Box::new(Ident { name: "n".to_string(), span: SourceSpan::default(), slot: None }),
                                 op: Sub,
                                 right: Box::new(Number { value: 1.0, span: span() }),
                                 span: span(),
                             },
                             BinaryOp {
-                                left: Box::new(Ident { name: "acc".to_string(), span: SourceSpan::default() }),
+                                left: Box::new(Ident { name: "acc".to_string(), span: SourceSpan::default(), slot: None }),
                                 op: Add,
-                                right: Box::new(Ident { name: "n".to_string(), span: SourceSpan::default() }),
+                                right: Box::new(Ident { name: "n".to_string(), span: SourceSpan::default(), slot: None }),
                                 span: span(),
                             },
                         ],
@@ -1790,6 +2654,8 @@ mod tests {
                 }]),
                 span: span(),
             }],
+            swift: false,
+            deprecated: None,
             span: span(),
         }];
 
@@ -1819,14 +2685,17 @@ mod tests {
             arms: vec![
                 crate::ast::MatchArm {
                     pattern: crate::ast::Pattern::Literal(Box::new(Number { value: 1.0, span: span() })),
+                    guard: None,
                     body: vec![Number { value: 100.0, span: span() }],
                 },
                 crate::ast::MatchArm {
                     pattern: crate::ast::Pattern::Literal(Box::new(Number { value: 2.0, span: span() })),
+                    guard: None,
                     body: vec![Number { value: 200.0, span: span() }],
                 },
                 crate::ast::MatchArm {
                     pattern: crate::ast::Pattern::Wildcard,
+                    guard: None,
                     body: vec![Number { value: 999.0, span: span() }],
                 },
             ],
@@ -1862,8 +2731,9 @@ mod tests {
             arms: vec![
                 crate::ast::MatchArm {
                     pattern: crate::ast::Pattern::Ident("n".to_string()),
+                    guard: None,
                     body: vec![BinaryOp {
-                        left: Box::new(Ident { name: "n".to_string(), span: SourceSpan::default() }),
+                        left: Box::new(Ident { name: "n".to_string(), span: SourceSpan::default(), slot: None }),
                         op: BinaryOperator::Mul,
                         right: Box::new(Number { value: 2.0, span: span() }),
                         span: span(),
@@ -1942,7 +2812,7 @@ mod tests {
     fn test_compile_absent_constructor() {
         use AstNode::*;
 
-        // Absent { span: SourceSpan::default() }
+        // Absent
         let ast = vec![Absent { span: SourceSpan::default() }];
 
         let result = compile_to_asm(&ast);
@@ -1950,7 +2820,7 @@ mod tests {
         let asm = result.unwrap();
 
         // Should contain comment
-        assert!(asm.contains("Create Absent { span: SourceSpan::default() } variant"));
+        assert!(asm.contains("Create Absent variant"));
 
         // Should store tag=0
         assert!(asm.contains("movq $0"));
@@ -1974,20 +2844,22 @@ mod tests {
                 span: span(),
             },
             MatchStmt {
-                value: Box::new(Ident { name: "result".to_string(), span: SourceSpan::default() }),
+                value: Box::new(Ident { name: "result".to_string(), span: SourceSpan::default(), slot: None }),
                 arms: vec![
                     crate::ast::MatchArm {
                         pattern: Pattern::Enum {
                             variant: "Triumph".to_string(),
                             inner: Some(Box::new(Pattern::Ident("x".to_string()))),
                         },
-                        body: vec![Ident { name: "x".to_string(), span: SourceSpan::default() }],
+                        guard: None,
+                        body: vec![Ident { name: "x".to_string(), span: SourceSpan::default(), slot: None }],
                     },
                     crate::ast::MatchArm {
                         pattern: Pattern::Enum {
                             variant: "Mishap".to_string(),
                             inner: Some(Box::new(Pattern::Ident("e".to_string()))),
                         },
+                        guard: None,
                         body: vec![Number { value: 0.0, span: span() }],
                     },
                 ],
@@ -2002,7 +2874,8 @@ mod tests {
         // Should contain comments for variants
         assert!(asm.contains("Match Triumph variant") || asm.contains("Match"));
 
-        // Should load tag from offset +8
+        // Should load the tag from offset 0 and the bound field from offset +8
+        assert!(asm.contains("movq 0(%rax), %rbx"));
         assert!(asm.contains("8(%rax)"));
 
         // Should compare tag with expected values
@@ -2030,15 +2903,16 @@ mod tests {
                 span: span(),
             },
             MatchStmt {
-                value: Box::new(Ident { name: "option".to_string(), span: SourceSpan::default() }),
+                value: Box::new(Ident { name: "option".to_string(), span: SourceSpan::default(), slot: None }),
                 arms: vec![
                     crate::ast::MatchArm {
                         pattern: Pattern::Enum {
                             variant: "Present".to_string(),
                             inner: Some(Box::new(Pattern::Ident("n".to_string()))),
                         },
+                        guard: None,
                         body: vec![BinaryOp {
-                            left: Box::new(Ident { name: "n".to_string(), span: SourceSpan::default() }),
+                            left: Box::new(Ident { name: "n".to_string(), span: SourceSpan::default(), slot: None }),
                             op: BinaryOperator::Mul,
                             right: Box::new(Number { value: 2.0, span: span() }),
                             span: span(),
@@ -2049,6 +2923,7 @@ mod tests {
                             variant: "Absent".to_string(),
                             inner: None,
                         },
+                        guard: None,
                         body: vec![Number { value: 0.0, span: span() }],
                     },
                 ],
@@ -2068,6 +2943,436 @@ mod tests {
         assert!(asm.contains("(%rax)"));
     }
 
+    #[test]
+    fn test_compile_variant_discriminants_as_tags() {
+        use AstNode::*;
+        use crate::ast::{Pattern, VariantCase};
+
+        let case = |name: &str, discriminant| VariantCase {
+            name: name.to_string(),
+            fields: vec![],
+            discriminant,
+        };
+
+        // variant Status with Ok is 3, Error end
+        // bind status = 0
+        // match status with when Error() then 1 end
+        let ast = vec![
+            VariantDef {
+                name: "Status".to_string(),
+                type_params: vec![],
+                variants: vec![case("Ok", Some(3)), case("Error", None)],
+                span: span(),
+            },
+            BindStmt {
+                name: "status".to_string(),
+                typ: None,
+                value: Box::new(Number { value: 0.0, span: span() }),
+                span: span(),
+            },
+            MatchStmt {
+                value: Box::new(Ident { name: "status".to_string(), span: SourceSpan::default(), slot: None }),
+                arms: vec![crate::ast::MatchArm {
+                    pattern: Pattern::Enum { variant: "Error".to_string(), inner: None },
+                    guard: None,
+                    body: vec![Number { value: 1.0, span: span() }],
+                }],
+                span: span(),
+            },
+        ];
+
+        let asm = compile_to_asm(&ast).unwrap();
+        assert!(asm.contains("cmpq $4, %rbx"));
+
+        // Cases of undefined variants have no tag
+        let unknown = vec![MatchStmt {
+            value: Box::new(Number { value: 0.0, span: span() }),
+            arms: vec![crate::ast::MatchArm {
+                pattern: Pattern::Enum { variant: "Missing".to_string(), inner: None },
+                guard: None,
+                body: vec![],
+            }],
+            span: span(),
+        }];
+        assert!(compile_to_asm(&unknown).is_err());
+    }
+
+    /// The instruction before a chant's epilogue, which leaves its value in rax
+    fn return_value<'a>(asm: &'a str, chant: &str) -> &'a str {
+        let body = &asm[asm.find(&format!(".L_func_{}:", chant)).unwrap()..];
+        let lines: Vec<&str> = body.lines().collect();
+        let epilogue = lines.iter().position(|line| *line == "    movq %rbp, %rsp").unwrap();
+        lines[epilogue - 1].trim()
+    }
+
+    #[test]
+    fn test_chant_returns_last_expression() {
+        let asm = compile_source(r#"
+            chant answer() then
+                bind unused to 1
+                42
+            end
+        "#).unwrap();
+        assert_eq!(return_value(&asm, "answer"), "movq $42, %rax");
+
+        let asm = compile_source(r#"
+            chant quiet() then
+                bind unused to 42
+            end
+        "#).unwrap();
+        assert_eq!(return_value(&asm, "quiet"), "movq $0, %rax");
+    }
+
+    #[test]
+    fn test_chant_returns_taken_branch() {
+        let asm = compile_source(r#"
+            chant pick(flag) then
+                should flag then
+                    1
+                otherwise
+                    2
+                end
+            end
+        "#).unwrap();
+
+        // Each branch leaves its value in rax for the shared epilogue
+        let body = &asm[asm.find(".L_func_pick:").unwrap()..];
+        let then_value = body.find("movq $1, %rax").unwrap();
+        let else_value = body.find("movq $2, %rax").unwrap();
+        assert!(then_value < else_value);
+        assert_eq!(return_value(&asm, "pick"), ".L_if_end_0:");
+    }
+
+    /// The instructions of a chant, from its label to its first `ret`
+    fn chant_asm<'a>(asm: &'a str, chant: &str) -> Vec<&'a str> {
+        let body = &asm[asm.find(&format!(".L_func_{}:", chant)).unwrap()..];
+        let mut lines: Vec<&str> = body.lines().map(str::trim).collect();
+        lines.truncate(lines.iter().position(|line| *line == "ret").unwrap() + 1);
+        lines
+    }
+
+    #[test]
+    fn test_prologue_reserves_frame() {
+        let asm = compile_source(r#"
+            chant sum3(a, b) then
+                bind c to a + b
+                c
+            end
+            sum3(1, 2)
+        "#).unwrap();
+
+        // Two parameters and a local; with rbp, rbx and the return address
+        // that keeps rsp 16-byte aligned
+        assert_eq!(
+            &chant_asm(&asm, "sum3")[1..5],
+            ["pushq %rbp", "pushq %rbx", "movq %rsp, %rbp", "subq $24, %rsp"]
+        );
+
+        // Code around a definition jumps over it; main has no locals
+        assert!(asm.contains("jmp .L_func_end_sum3\n.L_func_sum3:"));
+        assert!(asm.contains("main:\n    pushq %rbp\n    pushq %rbx\n    movq %rsp, %rbp\n    subq $8, %rsp\n    jmp"));
+    }
+
+    #[test]
+    fn test_calls_keep_stack_aligned() {
+        let asm = compile_source(r#"
+            chant twice(x) then
+                x * 2
+            end
+            chant outer(y) then
+                y + twice(y)
+            end
+        "#).unwrap();
+        let outer = chant_asm(&asm, "outer");
+
+        // The left operand is pushed, so the argument area is padded by 8
+        let call = outer.iter().position(|line| *line == "call .L_func_twice").unwrap();
+        assert_eq!(outer[call - 1], "addq $16, %rsp");
+        assert_eq!(outer[call + 1], "addq $8, %rsp");
+        assert!(outer.contains(&"subq $24, %rsp"));
+    }
+
+    #[test]
+    fn test_stack_parameters() {
+        let asm = compile_source(r#"
+            chant last_of_eight(a, b, c, d, e, f, g, h) then
+                g + h
+            end
+            last_of_eight(1, 2, 3, 4, 5, 6, 7, 8)
+        "#).unwrap();
+
+        // The callee reads the seventh and eighth arguments above its return address
+        let callee = chant_asm(&asm, "last_of_eight");
+        assert!(callee.contains(&"movq 24(%rbp), %rax"));
+        assert!(callee.contains(&"movq 32(%rbp), %rax"));
+        assert!(callee.contains(&"subq $56, %rsp"));
+
+        // The caller leaves them at 0(%rsp) and 8(%rsp) for the call, then drops them
+        let main = &asm[asm.find(".L_func_end_last_of_eight:").unwrap()..];
+        assert!(main.contains("subq $64, %rsp"));
+        assert!(main.contains("movq %rax, 48(%rsp)"));
+        assert!(main.contains("movq %rax, 56(%rsp)"));
+        assert!(main.contains("addq $48, %rsp\n    call .L_func_last_of_eight\n    addq $16, %rsp"));
+    }
+
+    #[test]
+    fn test_tail_call_with_stack_parameters() {
+        let asm = compile_source(r#"
+            chant spin(n, a, b, c, d, e, f, acc) then
+                should n is 0 then
+                    yield acc
+                end
+                yield spin(n - 1, a, b, c, d, e, f, acc + 1)
+            end
+        "#).unwrap();
+
+        // The new stack arguments overwrite the current ones before the jump
+        let spin = &asm[asm.find(".L_func_spin:").unwrap()..];
+        let jump = spin.find("jmp .L_func_spin").unwrap();
+        let rewrite = spin.find("movq 8(%rsp), %rax\n    movq %rax, 32(%rbp)").unwrap();
+        assert!(rewrite < jump);
+    }
+
+    #[test]
+    fn test_struct_pointer_survives_field_calls() {
+        let asm = compile_source(r#"
+            form Pair with
+                a as Number
+                b as Number
+            end
+            chant twice(x) then
+                x * 2
+            end
+            chant make(n) then
+                Pair { a: twice(n), b: n + twice(n) }
+            end
+        "#).unwrap();
+        let make = chant_asm(&asm, "make");
+
+        // The pointer is spilled after allocation and reloaded for each store,
+        // since the field calls (and the addition's use of rbx) clobber registers
+        let spill = make.iter().position(|line| *line == "movq %rax, -16(%rbp)").unwrap();
+        let reloads: Vec<usize> = make.iter()
+            .enumerate()
+            .filter(|(_, line)| **line == "movq -16(%rbp), %rbx")
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(reloads.len(), 2);
+        assert!(spill < reloads[0]);
+        assert_eq!(make[reloads[1] + 2], "movq %rax, 8(%rbx)");
+        let last_call = make.iter().rposition(|line| *line == "call .L_func_twice").unwrap();
+        assert!(last_call < reloads[1]);
+    }
+
+    #[test]
+    fn test_runtime_calls_are_aligned() {
+        let asm = compile_source(r#"
+            chant tagged(n) then
+                n + "tag"
+            end
+        "#).unwrap();
+
+        // With the left operand pushed, the allocation is padded by 8 bytes
+        let push = asm.find("pushq %rax").unwrap();
+        let call = asm.find("call gl_malloc").unwrap();
+        assert!(asm[push..call].contains("subq $8, %rsp"));
+        assert!(asm[call..].contains("addq $8, %rsp\n    movq %rax, %rbx\n    popq %rax"));
+    }
+
+    #[test]
+    fn test_rbx_is_preserved() {
+        let asm = compile_source(r#"
+            chant sum(a, b) then
+                a + b
+            end
+        "#).unwrap();
+        let sum = chant_asm(&asm, "sum");
+        assert_eq!(sum[2], "pushq %rbx");
+        assert_eq!(&sum[sum.len() - 4..], ["movq %rbp, %rsp", "popq %rbx", "popq %rbp", "ret"]);
+    }
+
+    #[test]
+    fn test_chant_as_value_and_indirect_call() {
+        let asm = compile_source(r#"
+            chant double(x) then
+                x * 2
+            end
+            chant apply(f, x) then
+                f(x)
+            end
+            apply(double, 1)
+        "#).unwrap();
+
+        // A chant without captures is a static record holding its address
+        assert!(asm.contains(".L_closure_double:\n    .quad .L_func_double"));
+        assert!(asm.contains("leaq .L_closure_double(%rip), %rax"));
+
+        // Calling a parameter loads the record into r10 and jumps through it
+        let apply = chant_asm(&asm, "apply");
+        let call = apply.iter().position(|line| *line == "call *0(%r10)").unwrap();
+        assert_eq!(apply[call - 1], "movq -24(%rbp), %r10");
+        assert!(asm.contains("call .L_func_apply"));
+    }
+
+    #[test]
+    fn test_capturing_chant_builds_closure_record() {
+        let asm = compile_source(r#"
+            chant make_adder(n) then
+                chant add_n(x) then
+                    x + n
+                end
+                add_n
+            end
+        "#).unwrap();
+
+        // The closure keeps its record and copies captures into its frame
+        let add_n = chant_asm(&asm, "add_n");
+        assert_eq!(&add_n[5..9], [
+            "movq %rdi, -8(%rbp)",
+            "movq %r10, -16(%rbp)",
+            "movq 8(%r10), %rax",
+            "movq %rax, -24(%rbp)",
+        ]);
+
+        // The enclosing chant allocates the record: code address, then n
+        let record = &asm[asm.find("# Closure record for add_n").unwrap()..];
+        let record = &record[..record.find("ret").unwrap()];
+        assert!(record.contains("call gl_malloc"));
+        assert!(record.contains("leaq .L_func_add_n(%rip), %rbx\n    movq %rbx, 0(%rax)"));
+        assert!(record.contains("movq -8(%rbp), %rbx\n    movq %rbx, 8(%rax)"));
+        assert!(!asm.contains(".L_closure_add_n"));
+    }
+
+    #[test]
+    fn test_top_level_chants_are_exported() {
+        let asm = compile_source(r#"
+            bind base to 10
+            chant add(a, b) then
+                a + b
+            end
+            chant offset(x) then
+                x + base
+            end
+            chant outer() then
+                chant inner() then
+                    1
+                end
+                inner()
+            end
+        "#).unwrap();
+
+        assert!(asm.contains(".globl glimmer_add\n.type glimmer_add, @function\nglimmer_add:\n    jmp .L_func_add\n"));
+        assert!(asm.contains("glimmer_outer:"));
+        // Closures need their record and nested chants are not exported
+        assert!(!asm.contains("glimmer_offset"));
+        assert!(!asm.contains("glimmer_inner"));
+    }
+
+    #[test]
+    fn test_string_copies_use_unique_labels() {
+        let asm = compile_source(r#"
+            bind a to "one"
+            bind b to "two"
+        "#).unwrap();
+        assert!(asm.contains("movb (%r11,%rcx,1), %r8b"));
+        let mut loops: Vec<&str> = asm.lines().filter(|line| line.starts_with(".L_string_copy_loop")).collect();
+        loops.dedup();
+        assert_eq!(loops.len(), 2);
+    }
+
+    #[test]
+    fn test_closure_out_of_scope_is_rejected() {
+        let error = compile_source(r#"
+            chant outer(n) then
+                chant inner() then
+                    n
+                end
+                inner()
+            end
+            chant elsewhere() then
+                inner()
+            end
+        "#).unwrap_err();
+        assert!(error.contains("inner captures variables"));
+    }
+
+    #[test]
+    fn test_compile_user_variant_construction() {
+        let asm = compile_source(r#"
+            variant Shape with
+                Dot is 5,
+                Rect(w: Number, h: Number)
+            end
+            chant build() then
+                yield Rect(6, 7)
+            end
+            chant single() then
+                yield Dot
+            end
+        "#).unwrap();
+
+        // Rect follows Dot's discriminant, its tag followed by both fields
+        assert!(asm.contains("Create Rect variant"));
+        assert!(asm.contains("movq $6, -24(%rbp)"));
+        assert!(asm.contains("movq %rax, -16(%rbp)"));
+        assert!(asm.contains("movq %rax, -8(%rbp)"));
+
+        // Unit cases are built from their bare name
+        assert!(asm.contains("Create Dot variant"));
+        assert!(asm.contains("movq $5, -8(%rbp)"));
+
+        let wrong_arity = compile_source(r#"
+            variant Shape then
+                Rect(w: Number, h: Number)
+            end
+            chant build() then
+                yield Rect(1)
+            end
+        "#);
+        assert!(wrong_arity.unwrap_err().contains("Rect takes 2 fields"));
+    }
+
+    #[test]
+    fn test_compile_user_variant_match() {
+        let asm = compile_source(r#"
+            variant Shape then
+                Dot,
+                Rect(w: Number, h: Number)
+            end
+            chant area(shape) then
+                match shape with
+                    when Dot then
+                        yield 1
+                    when Rect(w, h) then
+                        yield w * h
+                end
+            end
+        "#).unwrap();
+
+        // A bare unit case compares the tag instead of binding a variable
+        assert!(asm.contains("Match Dot variant"));
+        assert!(asm.contains("cmpq $0, %rbx"));
+
+        // Both field slots are bound
+        assert!(asm.contains("Match Rect variant"));
+        assert!(asm.contains("cmpq $1, %rbx"));
+        assert!(asm.contains("movq 8(%rax), %rbx"));
+        assert!(asm.contains("movq 16(%rax), %rbx"));
+
+        let too_many = compile_source(r#"
+            variant Shape then
+                Dot(size: Number)
+            end
+            chant area(shape) then
+                match shape with
+                    when Dot(a, b) then
+                        yield a
+                end
+            end
+        "#);
+        assert!(too_many.unwrap_err().contains("binds 2 fields"));
+    }
+
     #[test]
     fn test_compile_struct_codegen_produces_malloc_calls() {
         // This test verifies that struct allocation infrastructure generates
@@ -2110,6 +3415,26 @@ mod tests {
                 "Should store to offset 8 (field 1)");
     }
 
+    #[test]
+    fn test_profile_puts_hot_otherwise_branch_first() {
+        let source = "weave x as 0\nshould x is 1 then\n    set x to 10\notherwise\n    set x to 20\nend\nx";
+        let tokens = crate::Lexer::new(source).tokenize_positioned();
+        let ast = crate::Parser::new(tokens).parse().expect("parse failed");
+        let profile = Profile::parse("glimmer-profile 1\nbranch 2:1 1 9").unwrap();
+
+        let plain = compile_to_asm(&ast).unwrap();
+        let laid_out = compile_to_asm_with_profile(&ast, &profile).unwrap();
+        let position = |asm: &str, needle: &str| asm.find(needle).unwrap();
+        assert!(position(&plain, "$10") < position(&plain, "$20"));
+        assert!(position(&laid_out, "$20") < position(&laid_out, "$10"));
+        assert!(laid_out.contains("jne .L_then_0"));
+        assert!(!laid_out.contains("je .L_else_0"));
+
+        // A branch that mostly takes `then` keeps the usual layout
+        let usual = Profile::parse("glimmer-profile 1\nbranch 2:1 9 1").unwrap();
+        assert_eq!(compile_to_asm_with_profile(&ast, &usual).unwrap(), plain);
+    }
+
     #[test]
     fn test_compile_string_literal_codegen() {
         use crate::ast::AstNode::Text;
@@ -2144,7 +3469,7 @@ mod tests {
         // Test that gen_string_alloc generates complete memcpy code
         use crate::native_runtime::NativeRuntime;
 
-        let alloc_code = NativeRuntime::gen_string_alloc();
+        let alloc_code = NativeRuntime::gen_string_alloc(0);
         let asm_str = alloc_code.iter()
             .map(|inst| inst.to_asm())
             .collect::<Vec<_>>()
@@ -2178,6 +3503,7 @@ mod tests {
             name: "Math".to_string(),
             body: vec![],
             exports: vec!["add".to_string()],
+            deprecated: None,
             span: span(),
         }];
 
@@ -0,0 +1,159 @@
+//! # Coercions
+//!
+//! Every conversion the language applies on a script's behalf, so the
+//! interpreter, the VM and the builtins agree on them. Arithmetic and
+//! comparison never coerce: `"1" + 1` is a type error in every engine.
+//! What does convert:
+//!
+//! | From | [`truthy`] | [`to_number`] | [`to_text`] |
+//! |------|------------|---------------|-------------|
+//! | Number | not `0` (NaN is truthy) | itself | shortest form, `3` not `3.0` |
+//! | BigInt | not `0n` | nearest Number | digits, no `n` |
+//! | Integer | not `0i` | nearest Number | digits, no `i` |
+//! | Text | not empty | parsed as a decimal, else [`CoercionError::Invalid`] | itself |
+//! | Truth | itself | `1` or `0` | `true` or `false` |
+//! | Nothing | false | unsupported | `nothing` |
+//! | List | not empty | unsupported | `[List]` |
+//! | Set | not empty | unsupported | `[Set]` |
+//! | Frozen, Tainted | as what they wrap | as what they wrap | as what they wrap |
+//! | anything else | true | unsupported | its printed form |
+//!
+//! Maps, Outcomes and Maybes are truthy even when empty, a Mishap or
+//! Absent: test them with `map_size`, `is_triumph` and `is_present`.
+//! Conversions see through [`taint`](crate::taint) marks; the evaluator
+//! taints what a builtin returns from tainted arguments.
+//!
+//! A Maybe is only unwrapped explicitly, by [`present`]: Present gives its
+//! value, or Nothing when it has none, and Absent is an
+//! [`CoercionError::Absent`].
+//!
+//! [`coerce`] applies any [`Target`] to a [`Value`], which is how the
+//! matrix in `tests/test_coercion.rs` checks every pair.
+//!
+//! ```
+//! use glimmer_weave::coercion::{to_number, to_text, truthy, CoercionError};
+//! use glimmer_weave::Value;
+//!
+//! assert!(!truthy(&Value::List(vec![])));
+//! assert_eq!(to_number(&Value::Text("2.5".to_string())), Ok(2.5));
+//! assert_eq!(to_text(&Value::Number(3.0)), Ok("3".to_string()));
+//! assert!(matches!(to_number(&Value::Nothing), Err(CoercionError::Unsupported { .. })));
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use crate::eval::{RuntimeError, Value};
+
+/// What a value is converted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Truth,
+    Number,
+    Text,
+    /// The value inside a Present
+    Present,
+}
+
+impl Target {
+    pub const ALL: [Target; 4] = [Target::Truth, Target::Number, Target::Text, Target::Present];
+
+    /// Kinds of value that convert to this target, as type errors list them
+    pub fn accepts(self) -> &'static str {
+        match self {
+            Target::Truth => "any value",
+            Target::Number => "Number, Text, or Truth",
+            Target::Text => "any value",
+            Target::Present => "Maybe",
+        }
+    }
+}
+
+/// Why a value doesn't convert
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoercionError {
+    /// Values of this kind never convert to the target
+    Unsupported { from: String, target: Target },
+    /// This value of a convertible kind doesn't (`"twelve"` to a Number)
+    Invalid(String),
+    /// An Absent was unwrapped
+    Absent,
+}
+
+impl From<CoercionError> for RuntimeError {
+    fn from(error: CoercionError) -> Self {
+        match error {
+            CoercionError::Unsupported { from, target } => {
+                RuntimeError::TypeError { expected: target.accepts().to_string(), got: from }
+            }
+            CoercionError::Invalid(message) => RuntimeError::Custom(message),
+            CoercionError::Absent => RuntimeError::Custom("Absent has no value".to_string()),
+        }
+    }
+}
+
+fn unsupported(value: &Value, target: Target) -> CoercionError {
+    CoercionError::Unsupported { from: value.type_name().to_string(), target }
+}
+
+/// Whether a condition holding `value` passes
+pub fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Truth(b) => *b,
+        Value::Nothing => false,
+        Value::Number(n) => *n != 0.0,
+        Value::BigInt(n) => !n.is_zero(),
+        Value::Integer(n) => *n != 0,
+        Value::Text(s) => !s.is_empty(),
+        Value::List(l) => !l.is_empty(),
+        Value::Set(s) => !s.is_empty(),
+        Value::Frozen { value } | Value::Tainted { value, .. } => truthy(value),
+        _ => true,
+    }
+}
+
+/// The Number `value` stands for
+///
+/// Text is parsed as Rust parses an `f64`, so `"1e3"`, `"inf"` and `"NaN"`
+/// convert; surrounding whitespace does not.
+pub fn to_number(value: &Value) -> Result<f64, CoercionError> {
+    match value {
+        Value::Number(n) => Ok(*n),
+        // The nearest Number; past 2^53 the low digits round away
+        Value::BigInt(n) => Ok(n.to_f64()),
+        Value::Integer(n) => Ok(*n as f64),
+        Value::Text(s) => s.parse::<f64>().map_err(|_| CoercionError::Invalid(format!("Cannot convert '{}' to number", s))),
+        Value::Truth(b) => Ok(if *b { 1.0 } else { 0.0 }),
+        Value::Frozen { value } | Value::Tainted { value, .. } => to_number(value),
+        other => Err(unsupported(other, Target::Number)),
+    }
+}
+
+/// `value` as `to_text` prints it, leaving out `Display` aspects, which
+/// only the evaluator can call
+pub fn to_text(value: &Value) -> Result<String, CoercionError> {
+    // Without a describe hook nothing can fail
+    crate::runtime::render_text(value, &mut |_| None).map_err(|_| unsupported(value, Target::Text))
+}
+
+/// The value inside a Maybe: Nothing for a Present without one, and an
+/// error for Absent
+pub fn present(value: &Value) -> Result<Value, CoercionError> {
+    match value {
+        Value::Maybe { present: true, value } => Ok(value.as_deref().cloned().unwrap_or(Value::Nothing)),
+        Value::Maybe { present: false, .. } => Err(CoercionError::Absent),
+        Value::Frozen { value } => present(value).map(Value::freeze),
+        Value::Tainted { value, sources } => present(value).map(|inner| inner.taint(sources)),
+        other => Err(unsupported(other, Target::Present)),
+    }
+}
+
+/// Convert `value` to `target`, as a script value
+pub fn coerce(value: &Value, target: Target) -> Result<Value, CoercionError> {
+    match target {
+        Target::Truth => Ok(Value::Truth(truthy(value))),
+        Target::Number => to_number(value).map(Value::Number),
+        Target::Text => to_text(value).map(Value::Text),
+        Target::Present => present(value),
+    }
+}
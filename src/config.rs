@@ -0,0 +1,699 @@
+//! # Configuration Files
+//!
+//! TOML and INI documents as nested Maps, for scripts that read and write
+//! system configuration.
+//!
+//! `config_parse_toml(text)` and `config_parse_ini(text)` return
+//! `Triumph(map)`, or a Mishap naming the line that failed to parse.
+//! Scalars are typed: numbers become Numbers, `true`/`false` Truths and
+//! everything else Text. TOML tables and dotted keys become nested Maps and
+//! arrays of tables Lists of Maps; TOML dates and times have no type of
+//! their own and stay Text. INI sections become Maps under the root, and an
+//! INI value is also a Truth when it is `yes`/`no` or `on`/`off`.
+//!
+//! `config_emit_toml(map)` and `config_emit_ini(map)` write a Map back out;
+//! TOML writes nested Maps as tables and Lists of Maps as arrays of tables.
+//! INI has one level of sections and no lists, and TOML has no `nothing`;
+//! values a format cannot hold are an error rather than being dropped.
+//!
+//! ```
+//! use glimmer_weave::config::{emit_toml, parse_toml};
+//! use glimmer_weave::Value;
+//!
+//! let config = parse_toml("[server]\nport = 8080\nhosts = [\"a\", \"b\"]\n").unwrap();
+//! let Value::Map(root) = &config else { unreachable!() };
+//! let Value::Map(server) = &root["server"] else { unreachable!() };
+//! assert_eq!(server["port"], Value::Number(8080.0));
+//! assert_eq!(emit_toml(&config).unwrap(), "[server]\nhosts = [\"a\", \"b\"]\nport = 8080\n");
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::eval::Value;
+
+/// Why a document did not parse
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    /// Line the problem is on, from 1
+    pub line: usize,
+    pub message: String,
+}
+
+impl core::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+type Table = BTreeMap<String, Value>;
+
+/// Parse a TOML document into a Map
+pub fn parse_toml(text: &str) -> Result<Value, ConfigError> {
+    let mut parser = Toml { chars: text.chars().collect(), pos: 0, line: 1 };
+    let mut root = Table::new();
+    let mut current: Vec<String> = Vec::new();
+    let mut headers: Vec<Vec<String>> = Vec::new();
+
+    loop {
+        parser.skip_trivia();
+        match parser.peek() {
+            None => return Ok(Value::Map(root)),
+            Some('[') => {
+                parser.bump();
+                let array = parser.eat('[');
+                parser.skip_blank();
+                let path = parser.key_path()?;
+                parser.skip_blank();
+                parser.expect(']')?;
+                if array {
+                    parser.expect(']')?;
+                }
+                parser.end_of_line()?;
+
+                if array {
+                    let Some((last, parent)) = path.split_last() else {
+                        return Err(parser.error("expected a key"));
+                    };
+                    let table = table_at(&mut root, parent).map_err(|message| parser.error(message))?;
+                    match table.entry(last.clone()).or_insert_with(|| Value::List(Vec::new())) {
+                        Value::List(items) if items.iter().all(|item| matches!(item, Value::Map(_))) => {
+                            items.push(Value::Map(Table::new()))
+                        }
+                        _ => return Err(parser.error(format!("'{}' is not an array of tables", last))),
+                    }
+                } else {
+                    if headers.contains(&path) {
+                        return Err(parser.error(format!("table [{}] is defined twice", path.join("."))));
+                    }
+                    table_at(&mut root, &path).map_err(|message| parser.error(message))?;
+                    headers.push(path.clone());
+                }
+                current = path;
+            }
+            Some(_) => {
+                let (path, value) = parser.key_value()?;
+                parser.end_of_line()?;
+                let table = table_at(&mut root, &current).map_err(|message| parser.error(message))?;
+                insert(table, &path, value).map_err(|message| parser.error(message))?;
+            }
+        }
+    }
+}
+
+/// Parse an INI document into a Map of sections
+pub fn parse_ini(text: &str) -> Result<Value, ConfigError> {
+    let mut root = Table::new();
+    let mut section: Option<String> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let error = |message: String| ConfigError { line: index + 1, message };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('[') {
+            let name = header.strip_suffix(']').ok_or_else(|| error("section header has no closing ']'".to_string()))?.trim();
+            if root.contains_key(name) {
+                return Err(error(format!("[{}] is defined twice", name)));
+            }
+            root.insert(name.to_string(), Value::Map(Table::new()));
+            section = Some(name.to_string());
+            continue;
+        }
+
+        let split = line.find(['=', ':']).ok_or_else(|| error("expected 'key = value'".to_string()))?;
+        let key = line[..split].trim();
+        if key.is_empty() {
+            return Err(error("expected a key before the '='".to_string()));
+        }
+        let value = ini_scalar(line[split + 1..].trim()).map_err(error)?;
+        let table = match &section {
+            Some(name) => match root.get_mut(name) {
+                Some(Value::Map(table)) => table,
+                _ => unreachable!("sections are Maps"),
+            },
+            None => &mut root,
+        };
+        if table.insert(key.to_string(), value).is_some() {
+            return Err(error(format!("'{}' is defined twice", key)));
+        }
+    }
+    Ok(Value::Map(root))
+}
+
+/// Write a Map as a TOML document
+pub fn emit_toml(value: &Value) -> Result<String, String> {
+    let Value::Map(root) = value else {
+        return Err(format!("a TOML document is a Map, not a {}", value.type_name()));
+    };
+    let mut out = String::new();
+    emit_table(&mut out, &[], root)?;
+    Ok(out)
+}
+
+/// Write a Map of sections (and keys before the first section) as an INI
+/// document
+pub fn emit_ini(value: &Value) -> Result<String, String> {
+    let Value::Map(root) = value else {
+        return Err(format!("an INI document is a Map, not a {}", value.type_name()));
+    };
+    let mut out = String::new();
+    for (key, value) in root.iter().filter(|(_, value)| !matches!(value, Value::Map(_))) {
+        out.push_str(&format!("{} = {}\n", ini_key(key)?, ini_value(key, value)?));
+    }
+    for (name, value) in root {
+        let Value::Map(section) = value else { continue };
+        if name.contains([']', '\n']) {
+            return Err(format!("'{}' cannot be an INI section name", name));
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&format!("[{}]\n", name));
+        for (key, value) in section {
+            out.push_str(&format!("{} = {}\n", ini_key(key)?, ini_value(key, value)?));
+        }
+    }
+    Ok(out)
+}
+
+/// Cursor over a TOML document
+struct Toml {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Toml {
+    fn error(&self, message: impl Into<String>) -> ConfigError {
+        ConfigError { line: self.line, message: message.into() }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn starts_with(&self, text: &str) -> bool {
+        text.chars().enumerate().all(|(offset, c)| self.chars.get(self.pos + offset) == Some(&c))
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let matched = self.peek() == Some(c);
+        if matched {
+            self.bump();
+        }
+        matched
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ConfigError> {
+        match self.eat(c) {
+            true => Ok(()),
+            false => Err(self.error(format!("expected '{}'", c))),
+        }
+    }
+
+    /// Spaces and tabs
+    fn skip_blank(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+    }
+
+    /// Whitespace, newlines and comments
+    fn skip_trivia(&mut self) {
+        loop {
+            match self.peek() {
+                Some(' ' | '\t' | '\r' | '\n') => {
+                    self.bump();
+                }
+                Some('#') => {
+                    while !matches!(self.peek(), None | Some('\n')) {
+                        self.bump();
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// Nothing but a comment before the next line
+    fn end_of_line(&mut self) -> Result<(), ConfigError> {
+        self.skip_blank();
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.bump();
+            }
+        }
+        self.eat('\r');
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.bump();
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("unexpected '{}' after the value", c))),
+        }
+    }
+
+    /// `a.b."c"`
+    fn key_path(&mut self) -> Result<Vec<String>, ConfigError> {
+        let mut path = Vec::new();
+        loop {
+            self.skip_blank();
+            let key = match self.peek() {
+                Some('"') => self.basic_string()?,
+                Some('\'') => self.literal_string()?,
+                _ => {
+                    let mut key = String::new();
+                    while let Some(c) = self.peek().filter(|c| is_bare_key_char(*c)) {
+                        key.push(c);
+                        self.bump();
+                    }
+                    if key.is_empty() {
+                        return Err(self.error("expected a key"));
+                    }
+                    key
+                }
+            };
+            path.push(key);
+            self.skip_blank();
+            if !self.eat('.') {
+                return Ok(path);
+            }
+        }
+    }
+
+    /// `key = value`
+    fn key_value(&mut self) -> Result<(Vec<String>, Value), ConfigError> {
+        let path = self.key_path()?;
+        self.skip_blank();
+        self.expect('=')?;
+        self.skip_blank();
+        Ok((path, self.value()?))
+    }
+
+    fn value(&mut self) -> Result<Value, ConfigError> {
+        match self.peek() {
+            Some('"') if self.starts_with("\"\"\"") => self.multiline_string('"').map(Value::Text),
+            Some('\'') if self.starts_with("'''") => self.multiline_string('\'').map(Value::Text),
+            Some('"') => self.basic_string().map(Value::Text),
+            Some('\'') => self.literal_string().map(Value::Text),
+            Some('[') => self.array(),
+            Some('{') => self.inline_table(),
+            _ => {
+                let mut token = String::new();
+                while let Some(c) = self.peek().filter(|c| !matches!(c, ',' | ']' | '}' | '#' | '\r' | '\n')) {
+                    token.push(c);
+                    self.bump();
+                }
+                let token = token.trim_end();
+                match token {
+                    "true" => Ok(Value::Truth(true)),
+                    "false" => Ok(Value::Truth(false)),
+                    "" => Err(self.error("expected a value")),
+                    _ => match parse_number(token) {
+                        Some(n) => Ok(Value::Number(n)),
+                        // Dates and times
+                        None if token.starts_with(|c: char| c.is_ascii_digit()) && token.contains(['-', ':']) => {
+                            Ok(Value::Text(token.to_string()))
+                        }
+                        None => Err(self.error(format!("'{}' is not a TOML value", token))),
+                    },
+                }
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, ConfigError> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia();
+            if self.eat(']') {
+                return Ok(Value::List(items));
+            }
+            items.push(self.value()?);
+            self.skip_trivia();
+            if !self.eat(',') {
+                self.expect(']')?;
+                return Ok(Value::List(items));
+            }
+        }
+    }
+
+    fn inline_table(&mut self) -> Result<Value, ConfigError> {
+        self.expect('{')?;
+        let mut table = Table::new();
+        self.skip_blank();
+        if self.eat('}') {
+            return Ok(Value::Map(table));
+        }
+        loop {
+            let (path, value) = self.key_value()?;
+            insert(&mut table, &path, value).map_err(|message| self.error(message))?;
+            self.skip_blank();
+            if !self.eat(',') {
+                self.expect('}')?;
+                return Ok(Value::Map(table));
+            }
+        }
+    }
+
+    /// `"..."` with escapes
+    fn basic_string(&mut self) -> Result<String, ConfigError> {
+        self.expect('"')?;
+        let mut text = String::new();
+        loop {
+            match self.bump() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('"') => return Ok(text),
+                Some('\\') => text.push(self.escape()?),
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    /// `'...'`, taken as written
+    fn literal_string(&mut self) -> Result<String, ConfigError> {
+        self.expect('\'')?;
+        let mut text = String::new();
+        loop {
+            match self.bump() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some('\'') => return Ok(text),
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    /// `"""..."""` with escapes, or `'''...'''` taken as written
+    fn multiline_string(&mut self, quote: char) -> Result<String, ConfigError> {
+        let delimiter: String = [quote; 3].iter().collect();
+        self.pos += 3;
+        // A newline straight after the opening quotes is not part of the text
+        self.eat('\r');
+        self.eat('\n');
+        let mut text = String::new();
+        loop {
+            if self.starts_with(&delimiter) {
+                self.pos += 3;
+                return Ok(text);
+            }
+            match self.bump() {
+                None => return Err(self.error("unterminated string")),
+                // A backslash ending a line joins it to the next one
+                Some('\\') if quote == '"' && matches!(self.peek(), Some(' ' | '\t' | '\r' | '\n')) => {
+                    while matches!(self.peek(), Some(' ' | '\t' | '\r' | '\n')) {
+                        self.bump();
+                    }
+                }
+                Some('\\') if quote == '"' => text.push(self.escape()?),
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    /// The character an escape after a backslash stands for
+    fn escape(&mut self) -> Result<char, ConfigError> {
+        let digits = match self.bump() {
+            Some('b') => return Ok('\u{8}'),
+            Some('t') => return Ok('\t'),
+            Some('n') => return Ok('\n'),
+            Some('f') => return Ok('\u{c}'),
+            Some('r') => return Ok('\r'),
+            Some('"') => return Ok('"'),
+            Some('\\') => return Ok('\\'),
+            Some('u') => 4,
+            Some('U') => 8,
+            other => return Err(self.error(format!("unknown escape '\\{}'", other.unwrap_or(' ')))),
+        };
+        let hex: String = (0..digits).filter_map(|_| self.bump()).collect();
+        u32::from_str_radix(&hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or_else(|| self.error(format!("'\\u{}' is not a character", hex)))
+    }
+}
+
+fn is_bare_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// The table at `path` under `root`, creating missing ones; an array of
+/// tables stands for its last table
+fn table_at<'a>(root: &'a mut Table, path: &[String]) -> Result<&'a mut Table, String> {
+    let mut table = root;
+    for key in path {
+        table = match table.entry(key.clone()).or_insert_with(|| Value::Map(Table::new())) {
+            Value::Map(inner) => inner,
+            Value::List(items) => match items.last_mut() {
+                Some(Value::Map(inner)) => inner,
+                _ => return Err(format!("'{}' is not a table", key)),
+            },
+            _ => return Err(format!("'{}' already holds a value, not a table", key)),
+        };
+    }
+    Ok(table)
+}
+
+/// Set a dotted key, refusing to redefine one
+fn insert(table: &mut Table, path: &[String], value: Value) -> Result<(), String> {
+    let Some((last, parent)) = path.split_last() else {
+        return Err("expected a key".to_string());
+    };
+    let table = table_at(table, parent)?;
+    if table.contains_key(last) {
+        return Err(format!("'{}' is defined twice", path.join(".")));
+    }
+    table.insert(last.clone(), value);
+    Ok(())
+}
+
+/// The Number a TOML or INI number stands for: decimal with optional `_`
+/// separators, `0x`/`0o`/`0b` integers, `inf` and `nan`
+fn parse_number(token: &str) -> Option<f64> {
+    if token.starts_with('_') || token.ends_with('_') || token.contains("__") {
+        return None;
+    }
+    let digits: String = token.chars().filter(|c| *c != '_').collect();
+    let (negative, unsigned) = match digits.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, digits.strip_prefix('+').unwrap_or(&digits)),
+    };
+    let magnitude = match unsigned {
+        "inf" => f64::INFINITY,
+        "nan" => f64::NAN,
+        _ => {
+            let radix = [("0x", 16), ("0o", 8), ("0b", 2)].into_iter().find(|(prefix, _)| unsigned.starts_with(prefix));
+            match radix {
+                Some((_, radix)) => i64::from_str_radix(&unsigned[2..], radix).ok()? as f64,
+                None if unsigned.starts_with(|c: char| c.is_ascii_digit())
+                    && unsigned.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-')) =>
+                {
+                    unsigned.parse().ok()?
+                }
+                None => return None,
+            }
+        }
+    };
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// Typed value of an INI value: a quoted string, a Truth, a Number or Text
+fn ini_scalar(raw: &str) -> Result<Value, String> {
+    if raw.starts_with('"') {
+        let mut parser = Toml { chars: raw.chars().collect(), pos: 0, line: 1 };
+        let text = parser.basic_string().map_err(|error| error.message)?;
+        parser.skip_blank();
+        return match parser.peek() {
+            None | Some(';' | '#') => Ok(Value::Text(text)),
+            Some(_) => Err("unexpected text after the closing quote".to_string()),
+        };
+    }
+    // An inline comment starts at whitespace followed by ';' or '#'
+    let end = raw
+        .char_indices()
+        .find(|&(index, c)| matches!(c, ';' | '#') && raw[..index].ends_with([' ', '\t']))
+        .map_or(raw.len(), |(index, _)| index);
+    let text = raw[..end].trim_end();
+    Ok(match text.to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" => Value::Truth(true),
+        "false" | "no" | "off" => Value::Truth(false),
+        _ => match parse_number(text) {
+            Some(n) => Value::Number(n),
+            None => Value::Text(text.to_string()),
+        },
+    })
+}
+
+fn emit_table(out: &mut String, path: &[String], table: &Table) -> Result<(), String> {
+    let nested = |value: &Value| match value {
+        Value::Map(_) => true,
+        Value::List(items) => !items.is_empty() && items.iter().all(|item| matches!(item, Value::Map(_))),
+        _ => false,
+    };
+    for (key, value) in table.iter().filter(|(_, value)| !nested(value)) {
+        out.push_str(&format!("{} = {}\n", toml_key(key), toml_value(value)?));
+    }
+    for (key, value) in table.iter().filter(|(_, value)| nested(value)) {
+        let mut child = path.to_vec();
+        child.push(key.clone());
+        let header: Vec<String> = child.iter().map(|key| toml_key(key)).collect();
+        let header = header.join(".");
+        match value {
+            Value::Map(inner) => {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                out.push_str(&format!("[{}]\n", header));
+                emit_table(out, &child, inner)?;
+            }
+            Value::List(items) => {
+                for item in items {
+                    let Value::Map(inner) = item else { continue };
+                    if !out.is_empty() {
+                        out.push('\n');
+                    }
+                    out.push_str(&format!("[[{}]]\n", header));
+                    emit_table(out, &child, inner)?;
+                }
+            }
+            _ => unreachable!("only tables are nested"),
+        }
+    }
+    Ok(())
+}
+
+fn toml_key(key: &str) -> String {
+    match !key.is_empty() && key.chars().all(is_bare_key_char) {
+        true => key.to_string(),
+        false => quote(key),
+    }
+}
+
+fn toml_value(value: &Value) -> Result<String, String> {
+    match value {
+        Value::Text(text) => Ok(quote(text)),
+        Value::Number(n) => Ok(number_text(*n)),
+        Value::Truth(truth) => Ok(truth.to_string()),
+        Value::List(items) => {
+            let items = items.iter().map(toml_value).collect::<Result<Vec<_>, _>>()?;
+            Ok(format!("[{}]", items.join(", ")))
+        }
+        Value::Map(entries) if entries.is_empty() => Ok("{}".to_string()),
+        Value::Map(entries) => {
+            let entries = entries
+                .iter()
+                .map(|(key, value)| Ok(format!("{} = {}", toml_key(key), toml_value(value)?)))
+                .collect::<Result<Vec<_>, String>>()?;
+            Ok(format!("{{ {} }}", entries.join(", ")))
+        }
+        other => Err(format!("{} values have no TOML form", other.type_name())),
+    }
+}
+
+fn ini_key(key: &str) -> Result<&str, String> {
+    match key.is_empty() || key.trim() != key || key.contains(['=', ':', '\n']) || key.starts_with([';', '#', '[']) {
+        true => Err(format!("'{}' cannot be an INI key", key)),
+        false => Ok(key),
+    }
+}
+
+fn ini_value(key: &str, value: &Value) -> Result<String, String> {
+    match value {
+        // Quoted when it would otherwise read back as something else
+        Value::Text(text) if text.contains(['\n', '\r']) || ini_scalar(text) != Ok(Value::Text(text.clone())) => {
+            Ok(quote(text))
+        }
+        Value::Text(text) => Ok(text.clone()),
+        Value::Number(n) => Ok(number_text(*n)),
+        Value::Truth(truth) => Ok(truth.to_string()),
+        other => Err(format!("INI values are Text, Numbers and Truths; '{}' holds a {}", key, other.type_name())),
+    }
+}
+
+/// Integral Numbers without a fraction, the rest as TOML floats
+fn number_text(n: f64) -> String {
+    if n.is_nan() {
+        "nan".to_string()
+    } else if n.is_infinite() {
+        if n > 0.0 { "inf" } else { "-inf" }.to_string()
+    } else if n % 1.0 == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        format!("{:?}", n)
+    }
+}
+
+/// A double-quoted string both formats read back
+fn quote(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get<'a>(value: &'a Value, path: &[&str]) -> &'a Value {
+        path.iter().fold(value, |value, key| match value {
+            Value::Map(entries) => &entries[*key],
+            other => panic!("expected a Map at '{}', got {:?}", key, other),
+        })
+    }
+
+    #[test]
+    fn test_toml_numbers_strings_and_errors() {
+        let config = parse_toml(concat!(
+            "mask = 0xff_ff\n",
+            "ratio = -1.5e3\n",
+            "path = 'C:\\dir'\n",
+            "motd = \"\"\"\nWelcome\\u0021 \\\n    Enjoy\"\"\"\n",
+        ))
+        .unwrap();
+        assert_eq!(get(&config, &["mask"]), &Value::Number(65535.0));
+        assert_eq!(get(&config, &["ratio"]), &Value::Number(-1500.0));
+        assert_eq!(get(&config, &["path"]), &Value::Text("C:\\dir".to_string()));
+        assert_eq!(get(&config, &["motd"]), &Value::Text("Welcome! Enjoy".to_string()));
+
+        assert_eq!(parse_toml("a = 1\na = 2").unwrap_err().to_string(), "line 2: 'a' is defined twice");
+        assert_eq!(parse_toml("a = [1,\n2,\n").unwrap_err().line, 3);
+        assert!(parse_toml("a = 1 b = 2").is_err());
+        assert!(parse_toml("a = yes").is_err());
+    }
+
+    #[test]
+    fn test_ini_quoting_round_trips() {
+        let config = parse_ini("name = \"on\"\nmotto = keep calm ; and carry on\n").unwrap();
+        assert_eq!(get(&config, &["name"]), &Value::Text("on".to_string()));
+        assert_eq!(get(&config, &["motto"]), &Value::Text("keep calm".to_string()));
+        let text = emit_ini(&config).unwrap();
+        assert_eq!(text, "motto = keep calm\nname = \"on\"\n");
+        assert_eq!(parse_ini(&text).unwrap(), config);
+    }
+}
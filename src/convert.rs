@@ -0,0 +1,115 @@
+//! # Numeric Conversions
+//!
+//! How a Number (an `f64`) becomes a 64-bit integer. The checked conversion
+//! builtins (`to_int_checked`, `truncate`, `saturate`) and native codegen,
+//! which lowers number literals to `i64` immediates, share these rules:
+//!
+//! - conversion truncates toward zero, so `2.7` becomes `2` and `-2.7`
+//!   becomes `-2`;
+//! - [`truncate`] fails on NaN, infinities and values outside the `i64`
+//!   range instead of producing a garbage integer;
+//! - [`saturate`] clamps infinities and out-of-range values to `i64::MIN`
+//!   or `i64::MAX` and only fails on NaN;
+//! - [`exact`] also fails when there is a fractional part to drop.
+//!
+//! ```
+//! use glimmer_weave::convert::{exact, saturate, truncate, ConversionError};
+//!
+//! assert_eq!(truncate(-2.7), Ok(-2));
+//! assert_eq!(truncate(1e300), Err(ConversionError::OutOfRange));
+//! assert_eq!(saturate(1e300), Ok(i64::MAX));
+//! assert_eq!(exact(2.5), Err(ConversionError::Fractional));
+//! ```
+
+/// 2^63, the first Number above the `i64` range
+const I64_LIMIT: f64 = 9_223_372_036_854_775_808.0;
+
+/// Why a Number has no integer counterpart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The Number is NaN
+    NotANumber,
+    /// The Number is infinite
+    Infinite,
+    /// The Number, truncated, is outside the `i64` range
+    OutOfRange,
+    /// The Number has a fractional part (only [`exact`] reports this)
+    Fractional,
+}
+
+impl core::fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let reason = match self {
+            ConversionError::NotANumber => "not a number",
+            ConversionError::Infinite => "infinite",
+            ConversionError::OutOfRange => "outside the 64-bit integer range",
+            ConversionError::Fractional => "has a fractional part",
+        };
+        f.write_str(reason)
+    }
+}
+
+/// Truncate toward zero, failing on NaN, infinities and out-of-range values
+pub fn truncate(n: f64) -> Result<i64, ConversionError> {
+    if n.is_nan() {
+        Err(ConversionError::NotANumber)
+    } else if n.is_infinite() {
+        Err(ConversionError::Infinite)
+    } else if !(-I64_LIMIT..I64_LIMIT).contains(&n) {
+        // No Number lies strictly between -2^63 - 1 and -2^63, so this is
+        // exactly the set that truncates outside the range
+        Err(ConversionError::OutOfRange)
+    } else {
+        // In range, `as` truncates toward zero exactly
+        Ok(n as i64)
+    }
+}
+
+/// Truncate toward zero, clamping to the `i64` range; fails only on NaN
+pub fn saturate(n: f64) -> Result<i64, ConversionError> {
+    if n.is_nan() {
+        Err(ConversionError::NotANumber)
+    } else {
+        // `as` truncates and saturates
+        Ok(n as i64)
+    }
+}
+
+/// Convert a Number that is already a whole number in the `i64` range
+pub fn exact(n: f64) -> Result<i64, ConversionError> {
+    let int = truncate(n)?;
+    if int as f64 == n {
+        Ok(int)
+    } else {
+        Err(ConversionError::Fractional)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_rounds_toward_zero_within_range() {
+        assert_eq!(truncate(2.7), Ok(2));
+        assert_eq!(truncate(-2.7), Ok(-2));
+        assert_eq!(truncate(-0.5), Ok(0));
+        assert_eq!(truncate(-I64_LIMIT), Ok(i64::MIN));
+        assert_eq!(truncate(I64_LIMIT), Err(ConversionError::OutOfRange));
+        assert_eq!(truncate(-I64_LIMIT * 2.0), Err(ConversionError::OutOfRange));
+        assert_eq!(truncate(f64::NAN), Err(ConversionError::NotANumber));
+        assert_eq!(truncate(f64::NEG_INFINITY), Err(ConversionError::Infinite));
+    }
+
+    #[test]
+    fn test_saturate_clamps_and_exact_rejects_fractions() {
+        assert_eq!(saturate(f64::INFINITY), Ok(i64::MAX));
+        assert_eq!(saturate(-1e300), Ok(i64::MIN));
+        assert_eq!(saturate(-3.9), Ok(-3));
+        assert_eq!(saturate(f64::NAN), Err(ConversionError::NotANumber));
+
+        assert_eq!(exact(-42.0), Ok(-42));
+        assert_eq!(exact(0.1), Err(ConversionError::Fractional));
+        assert_eq!(exact(1e19), Err(ConversionError::OutOfRange));
+    }
+}
@@ -0,0 +1,206 @@
+//! # Crash Dumps
+//!
+//! Structured reports of scripts that die with an uncaught error, for hosts
+//! that file bug reports on the script author's behalf.
+//!
+//! With [`Evaluator::set_crash_dumps`](crate::eval::Evaluator::set_crash_dumps)
+//! on, an `eval` that fails leaves a [`CrashDump`] behind: the error, the
+//! chants it unwound through with the line each was at (innermost first),
+//! the bindings visible where it was raised, the tail of the capability
+//! audit log and where it happened. Errors a script handles with
+//! `attempt ... harmonize` leave nothing. The VM builds the same report
+//! from a [`VmError`](crate::vm::VmError) with
+//! [`VM::crash_dump`](crate::vm::VM::crash_dump), located by instruction.
+//!
+//! Bindings are recorded the way [`heap_graph`](crate::heap_graph) labels
+//! them: scalars by value, containers by size, tainted data only by the
+//! capabilities it came from. [`CrashDump::to_json`] serializes the report
+//! for the host, and [`CrashDump::diagnostic`] renders it for people.
+//!
+//! ```
+//! use glimmer_weave::{Evaluator, Lexer, Parser};
+//!
+//! let source = "chant ratio(a, b) then\n    yield a / b\nend\nbind total to 0\nratio(1, total)";
+//! let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap();
+//! let mut evaluator = Evaluator::new();
+//! evaluator.set_crash_dumps(true);
+//! assert!(evaluator.eval(&ast).is_err());
+//!
+//! let dump = evaluator.crash_dump().unwrap();
+//! assert_eq!(dump.error_type, "DivisionByZero");
+//! assert_eq!(dump.backtrace[0].chant.as_deref(), Some("ratio"));
+//! assert_eq!(dump.backtrace[1].span.start.line, 5);
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::capability::AuditEntry;
+use crate::error_formatter::Diagnostic;
+use crate::eval::Value;
+use crate::heap_graph::json_string;
+use crate::source_location::SourceSpan;
+
+/// Audit entries a crash dump keeps, most recent last
+pub const DEFAULT_AUDIT_TAIL: usize = 16;
+
+/// Report of an uncaught error
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrashDump {
+    /// Kind of error, as `harmonize on` names it (`"DivisionByZero"`)
+    pub error_type: String,
+    pub message: String,
+    /// Chants the error unwound through, innermost first; the outermost
+    /// frame is the top level
+    pub backtrace: Vec<CrashFrame>,
+    /// Bindings visible where the error was raised, by name
+    pub environment: Vec<CrashBinding>,
+    /// Last capability audit entries, oldest first
+    pub audit_tail: Vec<AuditEntry>,
+    pub location: CrashLocation,
+}
+
+/// A chant on the way out, and the statement it was running
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashFrame {
+    /// `None` at the top level
+    pub chant: Option<String>,
+    pub span: SourceSpan,
+}
+
+/// A binding in the failing scope
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashBinding {
+    pub name: String,
+    /// Type name of the value
+    pub kind: String,
+    /// The value, or its size for containers
+    pub summary: String,
+}
+
+impl CrashBinding {
+    pub fn new(name: &str, value: &Value) -> Self {
+        CrashBinding {
+            name: name.to_string(),
+            kind: value.type_name().to_string(),
+            summary: crate::heap_graph::summary(value),
+        }
+    }
+}
+
+/// Where the error was raised
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CrashLocation {
+    /// A statement of the interpreted source
+    Source(SourceSpan),
+    /// An instruction of a bytecode chunk
+    Bytecode {
+        chunk: String,
+        instruction: usize,
+        /// Source line the instruction was compiled from, if recorded
+        line: Option<usize>,
+    },
+}
+
+impl CrashDump {
+    /// Dump with only the error and its location; the rest is filled in by
+    /// whoever saw it fail
+    pub fn new(error_type: &str, message: &str, location: CrashLocation) -> Self {
+        CrashDump {
+            error_type: error_type.to_string(),
+            message: message.to_string(),
+            backtrace: Vec::new(),
+            environment: Vec::new(),
+            audit_tail: Vec::new(),
+            location,
+        }
+    }
+
+    /// The error as a diagnostic pointing at where it was raised, with the
+    /// backtrace as notes; quote the source with
+    /// [`Diagnostic::format_with_source`]
+    pub fn diagnostic(&self) -> Diagnostic {
+        let headline = format!("{}: {}", self.error_type, self.message);
+        let mut diagnostic = match &self.location {
+            CrashLocation::Source(span) => Diagnostic::error(headline).with_primary_label(span.clone(), "raised here"),
+            CrashLocation::Bytecode { chunk, instruction, line } => {
+                let line = line.map_or(String::new(), |line| format!(" (line {})", line));
+                Diagnostic::error(headline).with_note(format!("at instruction {} of `{}`{}", instruction, chunk, line))
+            }
+        };
+        for frame in &self.backtrace {
+            let chant = frame.chant.as_deref().map_or("the top level".to_string(), |chant| format!("`{}`", chant));
+            diagnostic = diagnostic.with_note(format!("in {} at {}", chant, frame.span));
+        }
+        diagnostic
+    }
+
+    /// JSON object with the fields of the report; spans are
+    /// `{"line":..,"column":..}` of their start
+    pub fn to_json(&self) -> String {
+        let optional = |text: Option<&str>| text.map_or("null".to_string(), json_string);
+        let span = |span: &SourceSpan| format!("{{\"line\":{},\"column\":{}}}", span.start.line, span.start.column);
+        let backtrace: Vec<String> = self
+            .backtrace
+            .iter()
+            .map(|frame| format!("{{\"chant\":{},\"at\":{}}}", optional(frame.chant.as_deref()), span(&frame.span)))
+            .collect();
+        let environment: Vec<String> = self
+            .environment
+            .iter()
+            .map(|binding| {
+                format!(
+                    "{{\"name\":{},\"kind\":{},\"summary\":{}}}",
+                    json_string(&binding.name),
+                    json_string(&binding.kind),
+                    json_string(&binding.summary)
+                )
+            })
+            .collect();
+        let audit: Vec<String> = self
+            .audit_tail
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{{\"capability\":{},\"event\":{},\"chant\":{},\"at\":{}}}",
+                    json_string(&entry.capability),
+                    json_string(entry.event.kind()),
+                    optional(entry.chant.as_deref()),
+                    span(&entry.span)
+                )
+            })
+            .collect();
+        let location = match &self.location {
+            CrashLocation::Source(at) => format!("{{\"source\":{}}}", span(at)),
+            CrashLocation::Bytecode { chunk, instruction, line } => format!(
+                "{{\"chunk\":{},\"instruction\":{},\"line\":{}}}",
+                json_string(chunk),
+                instruction,
+                line.map_or("null".to_string(), |line| line.to_string())
+            ),
+        };
+        format!(
+            "{{\"error_type\":{},\"message\":{},\"location\":{},\"backtrace\":[{}],\"environment\":[{}],\"audit_tail\":[{}]}}",
+            json_string(&self.error_type),
+            json_string(&self.message),
+            location,
+            backtrace.join(","),
+            environment.join(","),
+            audit.join(",")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytecode_locations() {
+        let location = CrashLocation::Bytecode { chunk: "main".to_string(), instruction: 7, line: Some(3) };
+        let dump = CrashDump::new("DivisionByZero", "Division by zero", location);
+        assert!(dump.to_json().contains("\"location\":{\"chunk\":\"main\",\"instruction\":7,\"line\":3}"));
+        assert!(dump.diagnostic().format().contains("at instruction 7 of `main` (line 3)"));
+    }
+}
@@ -0,0 +1,372 @@
+//! # Common Subexpression Elimination
+//!
+//! Computes a repeated pure expression once, into a binding placed before
+//! the first statement that uses it, before the compiled back ends see the
+//! program:
+//!
+//! ```glimmer
+//! bind d to p.x * p.x + p.y * p.y          bind common.0 to p.x * p.x + p.y * p.y
+//! should d greater than 4 then         →    bind d to common.0
+//!     VGA.write(p.x * p.x + p.y * p.y)      should d greater than 4 then
+//! end                                           VGA.write(common.0)
+//!                                           end
+//! ```
+//!
+//! Only expressions [`purity`](crate::purity) finds pure are shared, and
+//! only occurrences every run evaluates: statement values, conditions of
+//! `should` and `match`, and the left side of `and`/`or`. Loop conditions,
+//! pipeline stages and nested bodies are left alone, though each nested
+//! statement list is optimized on its own. Sharing stops at the first
+//! statement that may change one of the expression's variables: one that
+//! binds or `set`s it (including through an index or field), or, when the
+//! variable is `set` by some chant, one that calls anything.
+//!
+//! The shared expression is evaluated before the rest of its first
+//! statement, so if it fails, it fails before that statement's calls run.
+//! Introduced names contain a `.`, so they never collide with script
+//! variables.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use crate::ast::*;
+use crate::purity::{assigned_in_chants, contains_call, is_pure, node_count, root_name, same_expr, statement_lists, variables};
+use crate::source_location::SourceSpan;
+
+/// Smallest expression worth a binding of its own, in AST nodes
+pub const CSE_SIZE_MINIMUM: usize = 3;
+
+/// Shares repeated pure expressions between the statements of a block
+pub struct SubexpressionEliminator {
+    /// Variables some chant `set`s, which any call may change
+    assigned_in_chants: BTreeSet<String>,
+    counter: usize,
+}
+
+/// An expression worth sharing, and the statements it is shared between
+struct Shared {
+    expr: AstNode,
+    first: usize,
+    last: usize,
+}
+
+impl Default for SubexpressionEliminator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SubexpressionEliminator {
+    /// Create a new eliminator
+    pub fn new() -> Self {
+        SubexpressionEliminator {
+            assigned_in_chants: BTreeSet::new(),
+            counter: 0,
+        }
+    }
+
+    /// Share repeated pure expressions throughout a program
+    pub fn eliminate(&mut self, program: &[AstNode]) -> Vec<AstNode> {
+        self.assigned_in_chants = assigned_in_chants(program);
+        let mut program = program.to_vec();
+        self.eliminate_block(&mut program);
+        program
+    }
+
+    /// Share expressions between the statements of a list, nested lists
+    /// first, largest expression first
+    fn eliminate_block(&mut self, statements: &mut Vec<AstNode>) {
+        for statement in statements.iter_mut() {
+            for block in statement_lists(statement) {
+                self.eliminate_block(block);
+            }
+        }
+        while let Some(Shared { expr, first, last }) = self.best_shared(statements) {
+            let name = format!("common.{}", self.counter);
+            self.counter += 1;
+            for statement in &mut statements[first..=last] {
+                for root in evaluated_roots(statement) {
+                    replace(root, &expr, &name);
+                }
+            }
+            statements.insert(
+                first,
+                AstNode::BindStmt { name, typ: None, value: Box::new(expr), span: SourceSpan::default() },
+            );
+        }
+    }
+
+    /// The largest expression that occurs at least twice in a run of
+    /// statements that leaves its variables alone
+    fn best_shared(&self, statements: &mut [AstNode]) -> Option<Shared> {
+        let mut occurrences: Vec<(AstNode, usize)> = Vec::new();
+        for (index, statement) in statements.iter_mut().enumerate() {
+            for root in evaluated_roots(statement) {
+                collect(root, index, &mut occurrences);
+            }
+        }
+        let calls: Vec<bool> = statements.iter().map(contains_call).collect();
+        let changes: Vec<BTreeSet<String>> = statements.iter().map(|statement| self.changes(statement)).collect();
+
+        let mut best: Option<(usize, Shared)> = None;
+        let mut seen: Vec<&AstNode> = Vec::new();
+        for (expr, _) in &occurrences {
+            if seen.iter().any(|known| same_expr(known, expr)) {
+                continue;
+            }
+            seen.push(expr);
+            let size = node_count(expr);
+            if best.as_ref().is_some_and(|(best_size, _)| *best_size >= size) {
+                continue;
+            }
+            let reads = variables(expr);
+            let called_away = reads.iter().any(|name| self.assigned_in_chants.contains(name));
+            // Occurrences per statement, in order
+            let mut counts = vec![0usize; statements.len()];
+            for (other, index) in &occurrences {
+                if same_expr(other, expr) {
+                    counts[*index] += 1;
+                }
+            }
+            for first in 0..statements.len() {
+                if counts[first] == 0 || (called_away && calls[first]) {
+                    continue;
+                }
+                let (mut count, mut last) = (counts[first], first);
+                for index in first + 1..statements.len() {
+                    if changes[index - 1].iter().any(|name| reads.contains(name)) {
+                        break;
+                    }
+                    if counts[index] > 0 {
+                        if called_away && calls[index] {
+                            break;
+                        }
+                        count += counts[index];
+                        last = index;
+                    }
+                }
+                if count >= 2 {
+                    best = Some((size, Shared { expr: expr.clone(), first, last }));
+                    break;
+                }
+            }
+        }
+        best.map(|(_, shared)| shared)
+    }
+
+    /// Names a statement may change once it has run
+    fn changes(&self, statement: &AstNode) -> BTreeSet<String> {
+        let mut changed = BTreeSet::new();
+        let mut pending = vec![statement];
+        while let Some(node) = pending.pop() {
+            match node {
+                AstNode::SetStmt { target, .. } => changed.extend(root_name(target)),
+                AstNode::BindStmt { name, .. }
+                | AstNode::WeaveStmt { name, .. }
+                | AstNode::ChantDef { name, .. }
+                | AstNode::ForStmt { variable: name, .. } => {
+                    changed.insert(name.clone());
+                }
+                AstNode::MatchStmt { arms, .. } => {
+                    for arm in arms {
+                        pattern_names(&arm.pattern, &mut changed);
+                    }
+                }
+                AstNode::Call { .. } | AstNode::Pipeline { .. } => changed.extend(self.assigned_in_chants.iter().cloned()),
+                _ => {}
+            }
+            pending.extend(node.children());
+        }
+        changed
+    }
+}
+
+/// Share repeated pure expressions with a fresh [`SubexpressionEliminator`]
+pub fn eliminate_common_subexpressions(program: &[AstNode]) -> Vec<AstNode> {
+    SubexpressionEliminator::new().eliminate(program)
+}
+
+/// The expressions a statement evaluates every time it runs
+fn evaluated_roots(statement: &mut AstNode) -> Vec<&mut AstNode> {
+    match statement {
+        AstNode::BindStmt { value, .. }
+        | AstNode::WeaveStmt { value, .. }
+        | AstNode::YieldStmt { value, .. }
+        | AstNode::ExprStmt { expr: value, .. }
+        | AstNode::IfStmt { condition: value, .. }
+        | AstNode::MatchStmt { value, .. }
+        | AstNode::ForStmt { iterable: value, .. } => vec![value.as_mut()],
+        // The target names what is assigned; only an index inside it is read
+        AstNode::SetStmt { target, value, .. } => match target.as_mut() {
+            AstNode::IndexAccess { index, .. } => vec![index.as_mut(), value.as_mut()],
+            _ => vec![value.as_mut()],
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Subexpressions evaluated whenever their root is, skipping the right of
+/// `and`/`or` and anything run per element or in a scope of its own
+fn evaluated_children(node: &mut AstNode) -> Vec<&mut AstNode> {
+    match node {
+        AstNode::BinaryOp { left, op: BinaryOperator::And | BinaryOperator::Or, .. } => vec![left.as_mut()],
+        AstNode::Pipeline { .. } | AstNode::SeekExpr { .. } => Vec::new(),
+        node if node.is_statement() => Vec::new(),
+        node => node.children_mut(),
+    }
+}
+
+fn is_shareable(node: &AstNode) -> bool {
+    matches!(
+        node,
+        AstNode::BinaryOp { .. } | AstNode::UnaryOp { .. } | AstNode::FieldAccess { .. } | AstNode::IndexAccess { .. }
+    ) && node_count(node) >= CSE_SIZE_MINIMUM
+        && is_pure(node)
+        && !variables(node).is_empty()
+}
+
+fn collect(node: &mut AstNode, index: usize, occurrences: &mut Vec<(AstNode, usize)>) {
+    if is_shareable(node) {
+        occurrences.push((node.clone(), index));
+    }
+    for child in evaluated_children(node) {
+        collect(child, index, occurrences);
+    }
+}
+
+fn replace(node: &mut AstNode, expr: &AstNode, name: &str) {
+    if same_expr(node, expr) {
+        *node = AstNode::Ident { name: name.into(), span: SourceSpan::default(), slot: None };
+        return;
+    }
+    for child in evaluated_children(node) {
+        replace(child, expr, name);
+    }
+}
+
+fn pattern_names(pattern: &Pattern, names: &mut BTreeSet<String>) {
+    match pattern {
+        Pattern::Ident(name) | Pattern::Affix { rest: Some(name), .. } => {
+            names.insert(name.clone());
+        }
+        Pattern::Enum { inner: Some(inner), .. } => pattern_names(inner, names),
+        Pattern::Tuple(elements) | Pattern::Or(elements) => elements.iter().for_each(|element| pattern_names(element, names)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::Evaluator;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Vec<AstNode> {
+        Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap()
+    }
+
+    /// Eliminate, check the result still computes the same, and return it
+    fn eliminate_checked(source: &str) -> Vec<AstNode> {
+        let program = parse(source);
+        let optimized = eliminate_common_subexpressions(&program);
+        assert_eq!(
+            Evaluator::new().eval(&optimized).unwrap(),
+            Evaluator::new().eval(&program).unwrap(),
+            "{}",
+            source
+        );
+        optimized
+    }
+
+    fn bindings(program: &[AstNode]) -> Vec<String> {
+        program
+            .iter()
+            .filter_map(|node| match node {
+                AstNode::BindStmt { name, .. } if name.starts_with("common.") => Some(name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn count(program: &[AstNode], source: &str) -> usize {
+        let expr = match parse(source).remove(0) {
+            AstNode::ExprStmt { expr, .. } => *expr,
+            other => other,
+        };
+        fn walk(node: &AstNode, expr: &AstNode) -> usize {
+            usize::from(same_expr(node, expr)) + node.children().into_iter().map(|child| walk(child, expr)).sum::<usize>()
+        }
+        program.iter().map(|node| walk(node, &expr)).sum()
+    }
+
+    const POINT: &str = "form Point with x as Number y as Number end\nbind p to Point { x: 3, y: 4 }\n";
+
+    #[test]
+    fn test_shares_across_statements() {
+        let source = format!(
+            "{}bind d to p.x * p.x + p.y * p.y\nbind e to p.x * p.x + p.y * p.y + 1\nd + e",
+            POINT
+        );
+        let optimized = eliminate_checked(&source);
+        assert_eq!(bindings(&optimized), ["common.0"]);
+        assert_eq!(count(&optimized, "p.x * p.x + p.y * p.y"), 1);
+    }
+
+    #[test]
+    fn test_shares_within_a_statement() {
+        let optimized = eliminate_checked("bind a to 3\nbind b to 4\n(a + b) * (a + b)");
+        assert_eq!(count(&optimized, "a + b"), 1);
+    }
+
+    #[test]
+    fn test_assignment_ends_sharing() {
+        let source = "weave a as 3\nbind b to a * 2 + 1\nset a to 10\nbind c to a * 2 + 1\nb + c";
+        let optimized = eliminate_checked(source);
+        assert!(bindings(&optimized).is_empty());
+
+        let field = format!("{}bind d to p.x * p.x\nset p.x to 5\nd + p.x * p.x", POINT.replace("bind p", "weave p").replace(" to Point", " as Point"));
+        assert!(bindings(&eliminate_checked(&field)).is_empty());
+    }
+
+    #[test]
+    fn test_calls_that_assign_end_sharing() {
+        let source = "weave a as 3\nchant bump() then\nset a to a + 1\na\nend\nbind b to a * a + bump()\nbind c to a * a\nb + c";
+        assert!(bindings(&eliminate_checked(source)).is_empty());
+
+        // A call that cannot touch the variables is no obstacle
+        let source = "bind a to 3\nchant double(n) then\nn * 2\nend\nbind b to a * a + double(1)\nbind c to a * a\nb + c";
+        assert_eq!(bindings(&eliminate_checked(source)), ["common.0"]);
+    }
+
+    #[test]
+    fn test_conditional_occurrences_are_not_shared() {
+        // The right of `and` may never run, so it cannot justify a binding
+        let source = "bind a to 3\nbind ok to a greater than 1 and a * a greater than 4\nbind b to a * a\nb";
+        let optimized = eliminate_checked(source);
+        assert!(bindings(&optimized).is_empty());
+        assert_eq!(count(&optimized, "a * a"), 2);
+    }
+
+    #[test]
+    fn test_nested_blocks_are_optimized_on_their_own() {
+        let source = r#"
+            weave total as 0
+            weave i as 0
+            whilst i less than 4 then
+                bind sq to i * i + 1
+                set total to total + i * i + 1 + sq
+                set i to i + 1
+            end
+            total
+        "#;
+        let optimized = eliminate_checked(source);
+        assert!(bindings(&optimized).is_empty());
+        let AstNode::WhileStmt { body, .. } = &optimized[2] else {
+            panic!("expected the loop, got {:?}", optimized[2]);
+        };
+        assert_eq!(bindings(body), ["common.0"]);
+    }
+}
@@ -0,0 +1,247 @@
+//! # CSV
+//!
+//! Delimited tabular text, read into and written from script values.
+//!
+//! `csv_parse(text, options)` returns `Triumph(rows)` or a Mishap naming the
+//! line that failed. With a header row (the default) each row is a Map from
+//! column name to field; with `header: false` each row is a List of fields.
+//! Fields are Text; quoted fields may hold delimiters, newlines and doubled
+//! quotes, as in RFC 4180.
+//!
+//! `csv_emit(rows, options)` writes a List of Maps (under a header row) or
+//! of Lists back out, quoting only the fields that need it. Map rows are
+//! written in `columns` order when the option is given, otherwise in the
+//! order their keys first appear; a missing field is written empty.
+//!
+//! Both take an optional options Map: `delimiter` (a one-character Text,
+//! `","` by default) and `header` (a Truth); `csv_emit` also takes
+//! `columns` (a List of Text).
+//!
+//! ```
+//! use glimmer_weave::csv::{emit, parse, CsvOptions};
+//!
+//! let rows = parse("name,notes\nada,\"likes \"\"tea\"\", cake\"\n", &CsvOptions::default()).unwrap();
+//! assert_eq!(emit(&rows, &CsvOptions::default()).unwrap(), "name,notes\nada,\"likes \"\"tea\"\", cake\"\n");
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::eval::Value;
+
+/// How fields are separated and laid out
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    /// Whether the first row names the columns (rows are then Maps)
+    pub header: bool,
+    /// Column order for writing Map rows
+    pub columns: Option<Vec<String>>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        CsvOptions { delimiter: ',', header: true, columns: None }
+    }
+}
+
+/// Why a document did not parse
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvError {
+    /// Line the problem is on, from 1
+    pub line: usize,
+    pub message: String,
+}
+
+impl core::fmt::Display for CsvError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Parse delimited text into a List of Maps, or of Lists without a header
+pub fn parse(text: &str, options: &CsvOptions) -> Result<Value, CsvError> {
+    let records = records(text, options.delimiter)?;
+    if !options.header {
+        let rows = records.into_iter().map(|(_, fields)| Value::List(fields.into_iter().map(Value::Text).collect()));
+        return Ok(Value::List(rows.collect()));
+    }
+
+    let mut records = records.into_iter();
+    let Some((_, header)) = records.next() else {
+        return Ok(Value::List(Vec::new()));
+    };
+    for (index, name) in header.iter().enumerate() {
+        if header[..index].contains(name) {
+            return Err(CsvError { line: 1, message: format!("column '{}' appears twice in the header", name) });
+        }
+    }
+    let mut rows = Vec::new();
+    for (line, fields) in records {
+        if fields.len() != header.len() {
+            let message = format!("{} fields where the header has {}", fields.len(), header.len());
+            return Err(CsvError { line, message });
+        }
+        let row: BTreeMap<String, Value> = header.iter().cloned().zip(fields.into_iter().map(Value::Text)).collect();
+        rows.push(Value::Map(row));
+    }
+    Ok(Value::List(rows))
+}
+
+/// Write a List of Maps or Lists as delimited text
+pub fn emit(rows: &Value, options: &CsvOptions) -> Result<String, String> {
+    let Value::List(rows) = rows else {
+        return Err(format!("rows are a List, not a {}", rows.type_name()));
+    };
+    let mut out = String::new();
+    let mut write_row = |fields: Vec<String>| {
+        let quoted: Vec<String> = fields.iter().map(|field| quote(field, options.delimiter)).collect();
+        out.push_str(&quoted.join(&options.delimiter.to_string()));
+        out.push('\n');
+    };
+
+    if rows.iter().all(|row| matches!(row, Value::Map(_))) && !rows.is_empty() {
+        let columns = match &options.columns {
+            Some(columns) => columns.clone(),
+            None => {
+                let mut columns: Vec<String> = Vec::new();
+                for row in rows {
+                    let Value::Map(entries) = row else { continue };
+                    for key in entries.keys() {
+                        if !columns.contains(key) {
+                            columns.push(key.clone());
+                        }
+                    }
+                }
+                columns
+            }
+        };
+        if options.header {
+            write_row(columns.clone());
+        }
+        for row in rows {
+            let Value::Map(entries) = row else { continue };
+            let fields = columns.iter().map(|column| entries.get(column).map_or(Ok(String::new()), field_text));
+            write_row(fields.collect::<Result<_, _>>()?);
+        }
+    } else {
+        for row in rows {
+            let Value::List(items) = row else {
+                return Err(format!("rows are all Maps or all Lists; found a {}", row.type_name()));
+            };
+            write_row(items.iter().map(field_text).collect::<Result<_, _>>()?);
+        }
+    }
+    Ok(out)
+}
+
+/// Records with the line each starts on
+fn records(text: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>, CsvError> {
+    let mut records = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+
+    while chars.peek().is_some() {
+        let start = line;
+        let mut fields = Vec::new();
+        loop {
+            let mut field = String::new();
+            if chars.peek() == Some(&'"') {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        None => return Err(CsvError { line: start, message: "quoted field is never closed".to_string() }),
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => {
+                            if c == '\n' {
+                                line += 1;
+                            }
+                            field.push(c);
+                        }
+                    }
+                }
+                if !matches!(chars.peek(), None | Some('\r' | '\n')) && chars.peek() != Some(&delimiter) {
+                    return Err(CsvError { line, message: "text after a closing quote".to_string() });
+                }
+            } else {
+                while let Some(&c) = chars.peek() {
+                    if c == delimiter || c == '\n' || c == '\r' {
+                        break;
+                    }
+                    field.push(c);
+                    chars.next();
+                }
+            }
+            fields.push(field);
+
+            match chars.next() {
+                Some(c) if c == delimiter => continue,
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                _ => {}
+            }
+            line += 1;
+            break;
+        }
+        // Blank lines hold no record
+        if fields != [""] {
+            records.push((start, fields));
+        }
+    }
+    Ok(records)
+}
+
+fn field_text(value: &Value) -> Result<String, String> {
+    match value {
+        Value::Text(text) => Ok(text.clone()),
+        Value::Number(n) => Ok(format!("{}", n)),
+        Value::Truth(truth) => Ok(truth.to_string()),
+        Value::Nothing => Ok(String::new()),
+        other => Err(format!("{} values cannot be CSV fields", other.type_name())),
+    }
+}
+
+/// `field`, quoted when it holds the delimiter, a quote or a line break
+fn quote(field: &str, delimiter: char) -> String {
+    match field.contains([delimiter, '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text_rows(rows: &[&[&str]]) -> Value {
+        let row = |fields: &&[&str]| Value::List(fields.iter().map(|field| Value::Text(field.to_string())).collect());
+        Value::List(rows.iter().map(row).collect())
+    }
+
+    #[test]
+    fn test_records_span_quoted_newlines() {
+        let options = CsvOptions { header: false, ..CsvOptions::default() };
+        let parsed = parse("a,\"two\r\nlines\",c\r\n\r\n,,\n", &options).unwrap();
+        assert_eq!(parsed, text_rows(&[&["a", "two\r\nlines", "c"], &["", "", ""]]));
+
+        let error = parse("a,b\n\"open,c\nd,e", &options).unwrap_err();
+        assert_eq!(error, CsvError { line: 2, message: "quoted field is never closed".to_string() });
+        assert_eq!(parse("\"a\"b,c", &options).unwrap_err().message, "text after a closing quote");
+    }
+
+    #[test]
+    fn test_custom_delimiter_round_trip() {
+        let options = CsvOptions { delimiter: ';', header: false, ..CsvOptions::default() };
+        let rows = text_rows(&[&["1,5", "a;b", "say \"hi\""]]);
+        let text = emit(&rows, &options).unwrap();
+        assert_eq!(text, "1,5;\"a;b\";\"say \"\"hi\"\"\"\n");
+        assert_eq!(parse(&text, &options).unwrap(), rows);
+    }
+}
@@ -0,0 +1,113 @@
+//! # Deprecations
+//!
+//! A chant, form or grove can be marked deprecated on the line before its
+//! definition, with a message and optionally what to use instead:
+//!
+//! ```glimmer-weave
+//! deprecated "draws past the screen edge" use draw_clipped
+//! chant draw(x, y, text) then
+//!     VGA.write_at(text, x, y)
+//! end
+//! ```
+//!
+//! It still works, but the semantic analyzer warns wherever it is used: at
+//! calls to a chant (plain or through its grove), literals of a form, and
+//! imports of and calls into a grove. Uses inside the deprecated definition
+//! itself are not reported. The doc generator shows a banner on it.
+//!
+//! ```
+//! use glimmer_weave::deprecation::check;
+//! use glimmer_weave::{Lexer, Parser, SemanticWarning};
+//!
+//! let source = "deprecated \"too slow\" use fast_sum\nchant sum(a, b) then\n    yield a + b\nend\nsum(1, 2)\n";
+//! let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap();
+//! let warnings = check(&ast);
+//! assert!(matches!(&warnings[..], [SemanticWarning::Deprecated { name, .. }] if name == "sum"));
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::ast::{AstNode, Deprecation};
+use crate::ast_query::{node_name, Query, QueryMatch};
+use crate::semantic::SemanticWarning;
+
+/// Warnings for every use of a deprecated definition in `nodes`
+pub fn check(nodes: &[AstNode]) -> Vec<SemanticWarning> {
+    let definitions = Query::kind("ChantDef").or(Query::kind("FormDef")).or(Query::kind("ModuleDecl"));
+    let mut warnings = Vec::new();
+    for definition in definitions.find(nodes) {
+        let Some(deprecation) = deprecation(definition.node) else {
+            continue;
+        };
+        let name = node_name(definition.node).unwrap_or_default();
+        let uses = match definition.node {
+            AstNode::ChantDef { .. } => {
+                let mut calls = Query::call_to(&name);
+                if let Some(grove) = definition.enclosing_name("ModuleDecl") {
+                    calls = calls.or(Query::call_to(&format!("{}.{}", grove, name)));
+                }
+                calls.find(nodes)
+            }
+            AstNode::FormDef { .. } => Query::kind("StructLiteral").and(Query::named(&name)).find(nodes),
+            _ => {
+                let prefix = format!("{}.", name);
+                let mut uses = Query::kind("Import").or(Query::kind("Call")).find(nodes);
+                uses.retain(|found| match found.node {
+                    AstNode::Call { .. } => node_name(found.node).is_some_and(|path| path.starts_with(&prefix)),
+                    _ => node_name(found.node).as_deref() == Some(name.as_str()),
+                });
+                uses
+            }
+        };
+        for found in uses.iter().filter(|found| !is_within(found, definition.node)) {
+            warnings.push(SemanticWarning::Deprecated {
+                name: name.clone(),
+                message: deprecation.message.clone(),
+                replacement: deprecation.replacement.clone(),
+                span: found.span().clone(),
+            });
+        }
+    }
+    warnings.sort_by_key(|warning| warning.span().map(|span| (span.start.line, span.start.column)));
+    warnings
+}
+
+/// The deprecation a definition carries
+pub fn deprecation(node: &AstNode) -> Option<&Deprecation> {
+    match node {
+        AstNode::ChantDef { deprecated, .. } | AstNode::FormDef { deprecated, .. } | AstNode::ModuleDecl { deprecated, .. } => {
+            deprecated.as_ref()
+        }
+        _ => None,
+    }
+}
+
+/// Banner text for a deprecation: its message, then what to use instead
+pub fn describe(deprecation: &Deprecation) -> String {
+    match &deprecation.replacement {
+        Some(replacement) => format!("{}; use `{}` instead", deprecation.message, replacement),
+        None => deprecation.message.clone(),
+    }
+}
+
+/// Whether a match is the definition itself or nested in it
+fn is_within(found: &QueryMatch<'_>, definition: &AstNode) -> bool {
+    core::ptr::eq(found.node, definition) || found.ancestors.iter().any(|ancestor| core::ptr::eq(*ancestor, definition))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_recursive_uses_are_not_reported() {
+        let source = "deprecated \"old\"\nchant count(n) then\n    should n is 0 then\n        yield 0\n    end\n    yield count(n - 1)\nend\n";
+        let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap_or_default();
+        assert!(matches!(&ast[0], AstNode::ChantDef { deprecated: Some(_), .. }));
+        assert!(check(&ast).is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+//! # Doc Generator
+//!
+//! Renders a Markdown reference of the definitions in a script or module:
+//! its groves with what they offer, chants with their signatures, forms
+//! with their fields, variants with their cases and aspects with their
+//! methods. A definition marked `deprecated` gets a banner with its
+//! message and replacement. `gwc doc` prints it for a file.
+//!
+//! [`render_builtins`] renders the same kind of reference for the runtime's
+//! builtins from their registry metadata, which `gwc doc --builtins` prints.
+//!
+//! ```
+//! use glimmer_weave::docgen::render;
+//! use glimmer_weave::{Lexer, Parser};
+//!
+//! let source = "deprecated \"too slow\" use fast_sum\nchant sum(a: Number, b: Number) -> Number then\n    yield a + b\nend\n";
+//! let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap();
+//! let docs = render("math", &ast);
+//! assert!(docs.contains("## `chant sum(a: Number, b: Number) -> Number`"));
+//! assert!(docs.contains("> **Deprecated:** too slow; use `fast_sum` instead"));
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::ast::{AstNode, BorrowMode, Parameter, TypeAnnotation};
+use crate::deprecation::{deprecation, describe};
+use crate::runtime::{BuiltinRegistry, BUILTIN_MODULES};
+
+/// Markdown reference for the definitions in `nodes`, under the heading
+/// `title`
+pub fn render(title: &str, nodes: &[AstNode]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}", title);
+    render_items(&mut out, nodes, 2);
+    out
+}
+
+/// Markdown reference for the builtins in `registry`, in registration
+/// order, with the module names they are also reachable by
+pub fn render_builtins(title: &str, registry: &BuiltinRegistry) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {}", title);
+    for builtin in registry.functions() {
+        let _ = writeln!(out, "\n## `{}`", builtin.usage());
+        if !builtin.description.is_empty() {
+            let _ = writeln!(out, "\n{}", builtin.description);
+        }
+        let aliases: Vec<String> = BUILTIN_MODULES
+            .iter()
+            .flat_map(|(module, members)| {
+                members.iter().filter(|(_, name)| *name == builtin.name).map(move |(member, _)| format!("`{}.{}`", module, member))
+            })
+            .collect();
+        if !aliases.is_empty() {
+            let _ = writeln!(out, "\nAlso {}", aliases.join(", "));
+        }
+        if !builtin.capabilities.is_empty() {
+            let capabilities: Vec<String> = builtin.capabilities.iter().map(|capability| format!("`{}`", capability)).collect();
+            let _ = writeln!(out, "\nRequires {}", capabilities.join(", "));
+        }
+    }
+    out
+}
+
+fn render_items(out: &mut String, nodes: &[AstNode], level: usize) {
+    let heading = "#".repeat(level);
+    for node in nodes {
+        let title = match node {
+            AstNode::ModuleDecl { name, .. } => format!("grove {}", name),
+            AstNode::ChantDef { name, type_params, params, return_type, swift, .. } => {
+                let mut signature = format!("{}chant {}{}({})", if *swift { "swift " } else { "" }, name, generics(type_params), parameters(params));
+                if let Some(return_type) = return_type {
+                    let _ = write!(signature, " -> {}", annotation(return_type));
+                }
+                signature
+            }
+            AstNode::FormDef { name, type_params, .. } => format!("form {}{}", name, generics(type_params)),
+            AstNode::VariantDef { name, type_params, .. } => format!("variant {}{}", name, generics(type_params)),
+            AstNode::AspectDef { name, type_params, .. } => format!("aspect {}{}", name, generics(type_params)),
+            _ => continue,
+        };
+        let _ = writeln!(out, "\n{} `{}`", heading, title);
+        if let Some(deprecation) = deprecation(node) {
+            let _ = writeln!(out, "\n> **Deprecated:** {}", describe(deprecation));
+        }
+
+        match node {
+            AstNode::ModuleDecl { body, exports, .. } => {
+                if !exports.is_empty() {
+                    let _ = writeln!(out, "\nOffers: {}", exports.iter().map(|name| format!("`{}`", name)).collect::<Vec<_>>().join(", "));
+                }
+                render_items(out, body, level + 1);
+            }
+            AstNode::FormDef { fields, deriving, .. } => {
+                let _ = writeln!(out);
+                for field in fields {
+                    let _ = writeln!(out, "- `{}`: `{}`", field.name, annotation(&field.typ));
+                }
+                if !deriving.is_empty() {
+                    let _ = writeln!(out, "\nDerives {}", deriving.join(", "));
+                }
+            }
+            AstNode::VariantDef { variants, .. } => {
+                let _ = writeln!(out);
+                for case in variants {
+                    if case.fields.is_empty() {
+                        let _ = writeln!(out, "- `{}`", case.name);
+                    } else {
+                        let _ = writeln!(out, "- `{}({})`", case.name, parameters(&case.fields));
+                    }
+                }
+            }
+            AstNode::AspectDef { methods, .. } => {
+                let _ = writeln!(out);
+                for method in methods {
+                    let returns = method.return_type.as_ref().map(|typ| format!(" -> {}", annotation(typ))).unwrap_or_default();
+                    let _ = writeln!(out, "- `chant {}({}){}`", method.name, parameters(&method.params), returns);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn generics(type_params: &[String]) -> String {
+    if type_params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", type_params.join(", "))
+    }
+}
+
+fn parameters(params: &[Parameter]) -> String {
+    let rendered: Vec<String> = params
+        .iter()
+        .map(|param| {
+            let typ = param.typ.as_ref().map(annotation);
+            match (&param.borrow_mode, typ) {
+                (BorrowMode::Borrowed, Some(typ)) => format!("borrow {} as {}", param.name, typ),
+                (BorrowMode::BorrowedMut, Some(typ)) => format!("borrow mut {} as {}", param.name, typ),
+                (_, typ) => {
+                    let variadic = if param.is_variadic { "..." } else { "" };
+                    match typ {
+                        Some(typ) => format!("{}{}: {}", variadic, param.name, typ),
+                        None => format!("{}{}", variadic, param.name),
+                    }
+                }
+            }
+        })
+        .collect();
+    rendered.join(", ")
+}
+
+/// Source form of a type annotation
+fn annotation(typ: &TypeAnnotation) -> String {
+    match typ {
+        TypeAnnotation::Named(name) | TypeAnnotation::Generic(name) => name.clone(),
+        TypeAnnotation::Parametrized { name, type_args } => {
+            format!("{}<{}>", name, type_args.iter().map(annotation).collect::<Vec<_>>().join(", "))
+        }
+        TypeAnnotation::List(inner) => format!("List<{}>", annotation(inner)),
+        TypeAnnotation::Map => "Map".to_string(),
+        TypeAnnotation::Array { element, length } => format!("array of {} length {}", annotation(element), length),
+        TypeAnnotation::Function { param_types, return_type } => format!(
+            "Function<({}) -> {}>",
+            param_types.iter().map(annotation).collect::<Vec<_>>().join(", "),
+            annotation(return_type)
+        ),
+        TypeAnnotation::Optional(inner) => format!("{}?", annotation(inner)),
+        TypeAnnotation::Borrowed { lifetime, inner, mutable } => {
+            let lifetime = lifetime.as_ref().map(|lifetime| format!("'{} ", lifetime.name)).unwrap_or_default();
+            format!("borrow {}{}{}", lifetime, if *mutable { "mut " } else { "" }, annotation(inner))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_groves_nest_and_show_banners() {
+        let source = "deprecated \"use Screen\"\ngrove Console with\n    chant write(text) then\n        text\n    end\n    offer write\nend\n";
+        let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap_or_default();
+        assert_eq!(
+            render("io", &ast),
+            "# io\n\n## `grove Console`\n\n> **Deprecated:** use Screen\n\nOffers: `write`\n\n### `chant write(text)`\n"
+        );
+    }
+
+    #[test]
+    fn test_builtins_list_aliases_and_capabilities() {
+        let docs = render_builtins("Builtins", &BuiltinRegistry::new());
+        assert!(docs.contains("\n## `upper(text: Text) -> Text`\n\nThe text in uppercase\n\nAlso `Text.upper`\n"));
+        assert!(docs.contains("\n## `store_get(key: Text) -> Maybe`\n\nThe value stored under the key\n\nAlso `Store.get`\n\nRequires `Store.readwrite`\n"));
+    }
+}
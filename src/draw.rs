@@ -0,0 +1,310 @@
+//! # Framebuffer Drawing
+//!
+//! Pixel graphics for UI scripts, through the evaluator's
+//! [`FramebufferProvider`]. Every `Draw` builtin needs a
+//! [`DRAW_CAPABILITY`] grant, the way `Term` needs `Term.control`.
+//!
+//! Drawing is double-buffered: `Draw.pixel(x, y, color)`,
+//! `Draw.rect(x, y, width, height, color)`,
+//! `Draw.blit(x, y, width, pixels)`, `Draw.text(x, y, text, color)` and
+//! `Draw.clear(color)` change a back buffer held by the evaluator, and
+//! `Draw.present()` hands the finished frame to the provider in one go, so
+//! the screen never shows a half-drawn frame. `Draw.size()` is
+//! `{ width, height }`. Colors are `0xRRGGBB` Numbers or `"#rrggbb"`
+//! Text; coordinates count from the top left and drawing is clipped to
+//! the screen. Text uses the provider's glyphs, by default the small
+//! built-in font from [`builtin_glyph`].
+//!
+//! ```
+//! use glimmer_weave::draw::Canvas;
+//!
+//! let mut canvas = Canvas::new(4, 3);
+//! canvas.rect(-1, 1, 3, 5, 0xFF0000);
+//! assert_eq!(canvas.get(1, 2), Some(0xFF0000));
+//! assert_eq!(canvas.get(2, 2), Some(0));
+//! assert_eq!(canvas.get(4, 0), None);
+//! ```
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Capability a script must hold to use the `Draw` builtins
+pub const DRAW_CAPABILITY: &str = "Framebuffer.draw";
+
+/// Width of the built-in font's glyphs, in pixels
+pub const BUILTIN_GLYPH_WIDTH: u32 = 3;
+
+/// Height of the built-in font's glyphs, in pixels
+pub const BUILTIN_GLYPH_HEIGHT: u32 = 5;
+
+/// Host side of pixel graphics
+///
+/// Errors become runtime errors of the builtin that hit them.
+pub trait FramebufferProvider: crate::sync::MaybeSend {
+    /// Width and height of the screen, in pixels
+    fn size(&self) -> (u32, u32);
+    /// Show a frame of `width * height` `0xRRGGBB` pixels, row by row
+    fn present(&mut self, pixels: &[u32]) -> Result<(), String>;
+    /// Glyph `Draw.text` draws for `c`, or `None` to draw a box instead
+    fn glyph(&self, c: char) -> Option<Glyph> {
+        builtin_glyph(c)
+    }
+}
+
+/// One character's pixels
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glyph {
+    /// Width in pixels, at most 32
+    pub width: u32,
+    /// Rows from the top; bit `width - 1` is the leftmost pixel
+    pub rows: Vec<u32>,
+}
+
+/// Glyph of the built-in 3x5 font: digits, letters (lowercase drawn as
+/// uppercase), space and common punctuation
+pub fn builtin_glyph(c: char) -> Option<Glyph> {
+    let rows: [u32; 5] = match c.to_ascii_uppercase() {
+        ' ' => [0b000, 0b000, 0b000, 0b000, 0b000],
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        ',' => [0b000, 0b000, 0b000, 0b010, 0b100],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        '=' => [0b000, 0b111, 0b000, 0b111, 0b000],
+        '!' => [0b010, 0b010, 0b010, 0b000, 0b010],
+        '?' => [0b111, 0b001, 0b010, 0b000, 0b010],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        _ => return None,
+    };
+    Some(Glyph { width: BUILTIN_GLYPH_WIDTH, rows: rows.to_vec() })
+}
+
+/// A grid of `0xRRGGBB` pixels that drawing is clipped to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u32>,
+}
+
+impl Canvas {
+    /// A black canvas
+    pub fn new(width: u32, height: u32) -> Self {
+        Canvas { width, height, pixels: vec![0; width as usize * height as usize] }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Every pixel, row by row
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+
+    /// Pixel at `x`, `y`, if that is on the canvas
+    pub fn get(&self, x: i64, y: i64) -> Option<u32> {
+        self.index(x, y).map(|index| self.pixels[index])
+    }
+
+    fn index(&self, x: i64, y: i64) -> Option<usize> {
+        let on = (0..i64::from(self.width)).contains(&x) && (0..i64::from(self.height)).contains(&y);
+        on.then(|| y as usize * self.width as usize + x as usize)
+    }
+
+    /// Fill the whole canvas
+    pub fn clear(&mut self, color: u32) {
+        self.pixels.fill(color);
+    }
+
+    pub fn pixel(&mut self, x: i64, y: i64, color: u32) {
+        if let Some(index) = self.index(x, y) {
+            self.pixels[index] = color;
+        }
+    }
+
+    /// Fill a `width` by `height` rectangle whose top left is `x`, `y`
+    pub fn rect(&mut self, x: i64, y: i64, width: u32, height: u32, color: u32) {
+        let (left, right) = Self::clip(x, width, self.width);
+        let (top, bottom) = Self::clip(y, height, self.height);
+        for row in top..bottom {
+            let start = row * self.width as usize;
+            self.pixels[start + left..start + right].fill(color);
+        }
+    }
+
+    /// Copy `pixels`, rows of `width`, with their top left at `x`, `y`; a
+    /// short last row is drawn as far as it goes
+    pub fn blit(&mut self, x: i64, y: i64, width: u32, pixels: &[u32]) {
+        if width == 0 {
+            return;
+        }
+        for (row, line) in pixels.chunks(width as usize).enumerate() {
+            for (column, &color) in line.iter().enumerate() {
+                self.pixel(x + column as i64, y + row as i64, color);
+            }
+        }
+    }
+
+    /// Draw the set bits of `glyph` with its top left at `x`, `y`
+    pub fn glyph(&mut self, x: i64, y: i64, glyph: &Glyph, color: u32) {
+        let width = glyph.width.min(32);
+        for (row, bits) in glyph.rows.iter().enumerate() {
+            for column in 0..width {
+                if bits >> (width - 1 - column) & 1 == 1 {
+                    self.pixel(x + i64::from(column), y + row as i64, color);
+                }
+            }
+        }
+    }
+
+    /// Draw `text` on one line from `x`, `y`, one pixel between glyphs,
+    /// and return the width it took; characters `glyph` has no glyph for
+    /// are drawn as boxes the size of the built-in font's
+    pub fn text(&mut self, x: i64, y: i64, text: &str, color: u32, glyph: impl Fn(char) -> Option<Glyph>) -> i64 {
+        let mut cursor = x;
+        for c in text.chars() {
+            let width = match glyph(c) {
+                Some(glyph) => {
+                    self.glyph(cursor, y, &glyph, color);
+                    glyph.width
+                }
+                None => {
+                    self.outline(cursor, y, BUILTIN_GLYPH_WIDTH, BUILTIN_GLYPH_HEIGHT, color);
+                    BUILTIN_GLYPH_WIDTH
+                }
+            };
+            cursor += i64::from(width) + 1;
+        }
+        (cursor - x - 1).max(0)
+    }
+
+    fn outline(&mut self, x: i64, y: i64, width: u32, height: u32, color: u32) {
+        self.rect(x, y, width, 1, color);
+        self.rect(x, y + i64::from(height) - 1, width, 1, color);
+        self.rect(x, y, 1, height, color);
+        self.rect(x + i64::from(width) - 1, y, 1, height, color);
+    }
+
+    /// Visible part of the span `start..start + length` on an axis of `limit`
+    fn clip(start: i64, length: u32, limit: u32) -> (usize, usize) {
+        let from = start.clamp(0, i64::from(limit));
+        let to = start.saturating_add(i64::from(length)).clamp(0, i64::from(limit));
+        (from as usize, to.max(from) as usize)
+    }
+}
+
+/// A provider with the back buffer scripts draw into
+pub struct DoubleBuffer {
+    provider: Box<dyn FramebufferProvider>,
+    back: Canvas,
+    frames: u64,
+}
+
+impl DoubleBuffer {
+    /// Draw for `provider`, on a black back buffer of its size
+    pub fn new(provider: Box<dyn FramebufferProvider>) -> Self {
+        let (width, height) = provider.size();
+        DoubleBuffer { provider, back: Canvas::new(width, height), frames: 0 }
+    }
+
+    /// The frame being drawn
+    pub fn back(&self) -> &Canvas {
+        &self.back
+    }
+
+    pub fn back_mut(&mut self) -> &mut Canvas {
+        &mut self.back
+    }
+
+    /// Draw `text` with the provider's glyphs (see [`Canvas::text`])
+    pub fn text(&mut self, x: i64, y: i64, text: &str, color: u32) -> i64 {
+        let provider = &self.provider;
+        self.back.text(x, y, text, color, |c| provider.glyph(c))
+    }
+
+    /// Show the back buffer; it keeps its contents for the next frame
+    ///
+    /// If the screen has changed size since, the frame is not shown and
+    /// the back buffer starts over, black, at the new size.
+    pub fn present(&mut self) -> Result<(), String> {
+        let (width, height) = self.provider.size();
+        if (width, height) != (self.back.width(), self.back.height()) {
+            self.back = Canvas::new(width, height);
+            return Err(alloc::format!("The screen is now {}x{}; redraw the frame", width, height));
+        }
+        self.provider.present(self.back.pixels())?;
+        self.frames += 1;
+        Ok(())
+    }
+
+    /// Frames presented so far
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blit_and_text_are_clipped() {
+        let mut canvas = Canvas::new(5, 5);
+        canvas.blit(3, 3, 2, &[1, 2, 3, 4, 5]);
+        assert_eq!([canvas.get(3, 3), canvas.get(4, 4)], [Some(1), Some(4)]);
+
+        let mut canvas = Canvas::new(8, 5);
+        assert_eq!(canvas.text(-2, 0, "17", 9, builtin_glyph), 7);
+        // The 1's base, then the 7's top row
+        assert_eq!(canvas.get(0, 4), Some(9));
+        assert_eq!(canvas.get(2, 0), Some(9));
+        assert_eq!(canvas.get(4, 0), Some(9));
+        assert_eq!(canvas.get(2, 4), Some(0));
+    }
+}
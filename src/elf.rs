@@ -32,8 +32,12 @@
 //! └─────────────────┘
 //! ```
 
+use alloc::format;
+use alloc::string::String;
 use alloc::vec::Vec;
 
+use crate::target::{Endian, TargetSpec};
+
 /// ELF file class
 #[repr(u8)]
 #[derive(Debug, Clone, Copy)]
@@ -65,11 +69,13 @@ pub enum ElfType {
 
 /// ELF machine type
 #[repr(u16)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ElfMachine {
     None = 0,
     X86 = 3,
+    Arm = 40,
     X86_64 = 62,
+    AArch64 = 183,
 }
 
 /// Section type
@@ -165,20 +171,62 @@ pub struct Elf64Symbol {
     pub st_size: u64,            // Symbol size
 }
 
+/// Appends fields in a target's byte order
+struct FieldWriter {
+    bytes: Vec<u8>,
+    endian: Endian,
+}
+
+impl FieldWriter {
+    fn new(endian: Endian) -> Self {
+        FieldWriter { bytes: Vec::new(), endian }
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        match self.endian {
+            Endian::Little => self.bytes.extend_from_slice(&value.to_le_bytes()),
+            Endian::Big => self.bytes.extend_from_slice(&value.to_be_bytes()),
+        }
+    }
+
+    fn u32(&mut self, value: u32) {
+        match self.endian {
+            Endian::Little => self.bytes.extend_from_slice(&value.to_le_bytes()),
+            Endian::Big => self.bytes.extend_from_slice(&value.to_be_bytes()),
+        }
+    }
+
+    fn u64(&mut self, value: u64) {
+        match self.endian {
+            Endian::Little => self.bytes.extend_from_slice(&value.to_le_bytes()),
+            Endian::Big => self.bytes.extend_from_slice(&value.to_be_bytes()),
+        }
+    }
+}
+
 impl Elf64Header {
-    /// Create a new ELF64 header for a relocatable object file
+    /// Create a new ELF64 header for an x86-64 System V relocatable object
     pub fn new_relocatable() -> Self {
+        Self::relocatable_for(&TargetSpec::X86_64_SYSV)
+    }
+
+    /// Create a new ELF64 header for a relocatable object for `target`
+    pub fn relocatable_for(target: &TargetSpec) -> Self {
         let mut e_ident = [0u8; 16];
         e_ident[0..4].copy_from_slice(&[0x7f, b'E', b'L', b'F']);  // Magic number
         e_ident[4] = ElfClass::Elf64 as u8;                         // 64-bit
-        e_ident[5] = ElfData::LittleEndian as u8;                   // Little endian
+        e_ident[5] = target.endian.elf_data() as u8;                // Byte order
         e_ident[6] = 1;                                             // ELF version
-        e_ident[7] = 0;                                             // System V ABI
+        e_ident[7] = target.os_abi.elf_value();                     // OS ABI
 
         Elf64Header {
             e_ident,
             e_type: ElfType::Relocatable as u16,
-            e_machine: ElfMachine::X86_64 as u16,
+            e_machine: target.elf_machine as u16,
             e_version: 1,
             e_entry: 0,
             e_phoff: 0,
@@ -193,24 +241,29 @@ impl Elf64Header {
         }
     }
 
-    /// Convert to bytes
+    /// Convert to little-endian bytes
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.e_ident);
-        bytes.extend_from_slice(&self.e_type.to_le_bytes());
-        bytes.extend_from_slice(&self.e_machine.to_le_bytes());
-        bytes.extend_from_slice(&self.e_version.to_le_bytes());
-        bytes.extend_from_slice(&self.e_entry.to_le_bytes());
-        bytes.extend_from_slice(&self.e_phoff.to_le_bytes());
-        bytes.extend_from_slice(&self.e_shoff.to_le_bytes());
-        bytes.extend_from_slice(&self.e_flags.to_le_bytes());
-        bytes.extend_from_slice(&self.e_ehsize.to_le_bytes());
-        bytes.extend_from_slice(&self.e_phentsize.to_le_bytes());
-        bytes.extend_from_slice(&self.e_phnum.to_le_bytes());
-        bytes.extend_from_slice(&self.e_shentsize.to_le_bytes());
-        bytes.extend_from_slice(&self.e_shnum.to_le_bytes());
-        bytes.extend_from_slice(&self.e_shstrndx.to_le_bytes());
-        bytes
+        self.encode(Endian::Little)
+    }
+
+    /// Convert to bytes in the given byte order
+    pub fn encode(&self, endian: Endian) -> Vec<u8> {
+        let mut out = FieldWriter::new(endian);
+        out.bytes.extend_from_slice(&self.e_ident);
+        out.u16(self.e_type);
+        out.u16(self.e_machine);
+        out.u32(self.e_version);
+        out.u64(self.e_entry);
+        out.u64(self.e_phoff);
+        out.u64(self.e_shoff);
+        out.u32(self.e_flags);
+        out.u16(self.e_ehsize);
+        out.u16(self.e_phentsize);
+        out.u16(self.e_phnum);
+        out.u16(self.e_shentsize);
+        out.u16(self.e_shnum);
+        out.u16(self.e_shstrndx);
+        out.bytes
     }
 }
 
@@ -231,20 +284,25 @@ impl Elf64SectionHeader {
         }
     }
 
-    /// Convert to bytes
+    /// Convert to little-endian bytes
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.sh_name.to_le_bytes());
-        bytes.extend_from_slice(&self.sh_type.to_le_bytes());
-        bytes.extend_from_slice(&self.sh_flags.to_le_bytes());
-        bytes.extend_from_slice(&self.sh_addr.to_le_bytes());
-        bytes.extend_from_slice(&self.sh_offset.to_le_bytes());
-        bytes.extend_from_slice(&self.sh_size.to_le_bytes());
-        bytes.extend_from_slice(&self.sh_link.to_le_bytes());
-        bytes.extend_from_slice(&self.sh_info.to_le_bytes());
-        bytes.extend_from_slice(&self.sh_addralign.to_le_bytes());
-        bytes.extend_from_slice(&self.sh_entsize.to_le_bytes());
-        bytes
+        self.encode(Endian::Little)
+    }
+
+    /// Convert to bytes in the given byte order
+    pub fn encode(&self, endian: Endian) -> Vec<u8> {
+        let mut out = FieldWriter::new(endian);
+        out.u32(self.sh_name);
+        out.u32(self.sh_type);
+        out.u64(self.sh_flags);
+        out.u64(self.sh_addr);
+        out.u64(self.sh_offset);
+        out.u64(self.sh_size);
+        out.u32(self.sh_link);
+        out.u32(self.sh_info);
+        out.u64(self.sh_addralign);
+        out.u64(self.sh_entsize);
+        out.bytes
     }
 }
 
@@ -273,16 +331,21 @@ impl Elf64Symbol {
         }
     }
 
-    /// Convert to bytes
+    /// Convert to little-endian bytes
     pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.st_name.to_le_bytes());
-        bytes.push(self.st_info);
-        bytes.push(self.st_other);
-        bytes.extend_from_slice(&self.st_shndx.to_le_bytes());
-        bytes.extend_from_slice(&self.st_value.to_le_bytes());
-        bytes.extend_from_slice(&self.st_size.to_le_bytes());
-        bytes
+        self.encode(Endian::Little)
+    }
+
+    /// Convert to bytes in the given byte order
+    pub fn encode(&self, endian: Endian) -> Vec<u8> {
+        let mut out = FieldWriter::new(endian);
+        out.u32(self.st_name);
+        out.u8(self.st_info);
+        out.u8(self.st_other);
+        out.u16(self.st_shndx);
+        out.u64(self.st_value);
+        out.u64(self.st_size);
+        out.bytes
     }
 }
 
@@ -333,6 +396,7 @@ impl StringTable {
 
 /// ELF object file builder
 pub struct ElfBuilder {
+    target: TargetSpec,
     text_section: Vec<u8>,
     data_section: Vec<u8>,
     symbols: Vec<Elf64Symbol>,
@@ -347,12 +411,30 @@ impl Default for ElfBuilder {
 }
 
 impl ElfBuilder {
-    /// Create a new ELF builder
+    /// Create a new ELF builder for x86-64 System V objects
     pub fn new() -> Self {
+        Self::with_target(TargetSpec::X86_64_SYSV)
+    }
+
+    /// Create a new ELF builder for `target`
+    ///
+    /// Only 64-bit targets can be written; ELF32 objects are not supported.
+    pub fn for_target(target: &TargetSpec) -> Result<Self, String> {
+        if target.pointer_width != 64 {
+            return Err(format!(
+                "Target '{}' is {}-bit; only ELF64 objects can be written",
+                target.name, target.pointer_width
+            ));
+        }
+        Ok(Self::with_target(*target))
+    }
+
+    fn with_target(target: TargetSpec) -> Self {
         // First symbol is always null
         let symbols = vec![Elf64Symbol::null()];
 
         ElfBuilder {
+            target,
             text_section: Vec::new(),
             data_section: Vec::new(),
             symbols,
@@ -383,7 +465,8 @@ impl ElfBuilder {
         let mut output = Vec::new();
 
         // Create header
-        let mut header = Elf64Header::new_relocatable();
+        let endian = self.target.endian;
+        let mut header = Elf64Header::relocatable_for(&self.target);
 
         // Build section name string table
         let _null_name = self.shstring_table.add("");
@@ -400,7 +483,7 @@ impl ElfBuilder {
         let symtab_offset = data_offset + self.data_section.len() as u64;
 
         let symtab_bytes: Vec<u8> = self.symbols.iter()
-            .flat_map(|sym| sym.to_bytes())
+            .flat_map(|sym| sym.encode(endian))
             .collect();
 
         let strtab_offset = symtab_offset + symtab_bytes.len() as u64;
@@ -417,7 +500,7 @@ impl ElfBuilder {
         header.e_shstrndx = 6;  // .shstrtab is section 6
 
         // Write header
-        output.extend_from_slice(&header.to_bytes());
+        output.extend_from_slice(&header.encode(endian));
 
         // Write sections
         output.extend_from_slice(&self.text_section);
@@ -428,7 +511,7 @@ impl ElfBuilder {
 
         // Write section headers
         // 0: Null section
-        output.extend_from_slice(&Elf64SectionHeader::null().to_bytes());
+        output.extend_from_slice(&Elf64SectionHeader::null().encode(endian));
 
         // 1: .text
         let text_header = Elf64SectionHeader {
@@ -443,7 +526,7 @@ impl ElfBuilder {
             sh_addralign: 16,
             sh_entsize: 0,
         };
-        output.extend_from_slice(&text_header.to_bytes());
+        output.extend_from_slice(&text_header.encode(endian));
 
         // 2: .data
         let data_header = Elf64SectionHeader {
@@ -458,7 +541,7 @@ impl ElfBuilder {
             sh_addralign: 8,
             sh_entsize: 0,
         };
-        output.extend_from_slice(&data_header.to_bytes());
+        output.extend_from_slice(&data_header.encode(endian));
 
         // 3: .bss (empty for now)
         let bss_header = Elf64SectionHeader {
@@ -473,7 +556,7 @@ impl ElfBuilder {
             sh_addralign: 8,
             sh_entsize: 0,
         };
-        output.extend_from_slice(&bss_header.to_bytes());
+        output.extend_from_slice(&bss_header.encode(endian));
 
         // 4: .symtab
         let symtab_header = Elf64SectionHeader {
@@ -488,7 +571,7 @@ impl ElfBuilder {
             sh_addralign: 8,
             sh_entsize: core::mem::size_of::<Elf64Symbol>() as u64,
         };
-        output.extend_from_slice(&symtab_header.to_bytes());
+        output.extend_from_slice(&symtab_header.encode(endian));
 
         // 5: .strtab
         let strtab_header = Elf64SectionHeader {
@@ -503,7 +586,7 @@ impl ElfBuilder {
             sh_addralign: 1,
             sh_entsize: 0,
         };
-        output.extend_from_slice(&strtab_header.to_bytes());
+        output.extend_from_slice(&strtab_header.encode(endian));
 
         // 6: .shstrtab
         let shstrtab_header = Elf64SectionHeader {
@@ -518,16 +601,24 @@ impl ElfBuilder {
             sh_addralign: 1,
             sh_entsize: 0,
         };
-        output.extend_from_slice(&shstrtab_header.to_bytes());
+        output.extend_from_slice(&shstrtab_header.encode(endian));
 
         output
     }
 }
 
-/// Create an ELF object file from machine code
+/// Create an x86-64 System V ELF object file from machine code
 pub fn create_elf_object(code: &[u8], function_name: &str) -> Vec<u8> {
     let mut builder = ElfBuilder::new();
     builder.add_text(code);
     builder.add_function(function_name, 0, code.len() as u64);
     builder.build()
 }
+
+/// Create an ELF object file for `target` from machine code
+pub fn create_elf_object_for(target: &TargetSpec, code: &[u8], function_name: &str) -> Result<Vec<u8>, String> {
+    let mut builder = ElfBuilder::for_target(target)?;
+    builder.add_text(code);
+    builder.add_function(function_name, 0, code.len() as u64);
+    Ok(builder.build())
+}
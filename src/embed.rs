@@ -0,0 +1,173 @@
+//! # Embedding
+//!
+//! One call to run a script: [`run`] takes the source and [`EvalOptions`]
+//! and drives the lexer, parser, semantic analysis and the chosen back end,
+//! so an embedder doesn't have to wire the [`CompilerPipeline`], prelude,
+//! capability policy and I/O hosts together by hand.
+//!
+//! ```
+//! use glimmer_weave::{run, EvalOptions, SandboxProfile, Value};
+//!
+//! let options = EvalOptions::new().sandbox(SandboxProfile::Locked);
+//! assert_eq!(run("bind x to 20\nx + 22", options).unwrap(), Value::Number(42.0));
+//!
+//! let denied = run("request FS.write with justification \"save\"", EvalOptions::new().sandbox(SandboxProfile::Locked));
+//! assert!(denied.unwrap_err().has_errors());
+//! ```
+//!
+//! Failures of any stage, the script's own runtime errors included, come
+//! back as [`Diagnostics`]. Hosts that keep evaluators around, share modules
+//! between scripts or need intermediate results use the
+//! [`Session`](crate::session::Session) and [`CompilerPipeline`] directly.
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error_formatter::{Diagnostic, Diagnostics};
+use crate::eval::Value;
+use crate::output::OutputSink;
+use crate::pipeline::{CompilerPipeline, Output, Target};
+use crate::script_prelude::Prelude;
+use crate::stream::StreamHost;
+use crate::vm::VM;
+
+/// Engine that runs the script
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// The tree-walking interpreter, with the whole runtime library
+    #[default]
+    Interpreter,
+    /// Compile to bytecode and run it in the VM
+    ///
+    /// The VM has no host builtins, so the sandbox, standard library and
+    /// I/O options do not apply to it.
+    Bytecode,
+}
+
+/// Which capability requests the script is granted
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SandboxProfile {
+    /// Grant every request
+    #[default]
+    Trusted,
+    /// Grant only the named capabilities, e.g. `"FS.read"`
+    Allow(Vec<String>),
+    /// Deny every request
+    Locked,
+}
+
+/// How [`run`] executes a script
+#[derive(Default)]
+pub struct EvalOptions {
+    backend: Backend,
+    sandbox: SandboxProfile,
+    no_stdlib: bool,
+    deterministic: bool,
+    output: Option<Box<dyn OutputSink>>,
+    input: Option<Box<dyn StreamHost>>,
+}
+
+impl EvalOptions {
+    /// The interpreter with the standard library, trusted and without an
+    /// output sink
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run the script with `backend`
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Decide capability requests by `profile`
+    pub fn sandbox(mut self, profile: SandboxProfile) -> Self {
+        self.sandbox = profile;
+        self
+    }
+
+    /// Enable or disable the standard library; without it no builtin is in
+    /// scope
+    pub fn stdlib(mut self, enabled: bool) -> Self {
+        self.no_stdlib = !enabled;
+        self
+    }
+
+    /// Deny the capabilities that would make runs of the script differ
+    /// (see [`Evaluator::set_deterministic`](crate::eval::Evaluator::set_deterministic))
+    ///
+    /// The bytecode VM has no host builtins and is always deterministic.
+    pub fn deterministic(mut self, enabled: bool) -> Self {
+        self.deterministic = enabled;
+        self
+    }
+
+    /// Send what `print` and `println` write to `sink`
+    pub fn output(mut self, sink: impl OutputSink + 'static) -> Self {
+        self.output = Some(Box::new(sink));
+        self
+    }
+
+    /// Read the files and console input scripts stream from `host`
+    pub fn input(mut self, host: impl StreamHost + 'static) -> Self {
+        self.input = Some(Box::new(host));
+        self
+    }
+}
+
+/// Run `source` as [`EvalOptions`] say, returning the value of its last
+/// expression
+///
+/// A script that calls `exit(status)` returns `Nothing`.
+pub fn run(source: &str, options: EvalOptions) -> Result<Value, Diagnostics> {
+    let prelude = if options.no_stdlib { Prelude::none() } else { Prelude::default() };
+    let mut pipeline = CompilerPipeline::new().prelude(prelude);
+    match options.backend {
+        Backend::Interpreter => {
+            let evaluator = pipeline.evaluator_mut();
+            match options.sandbox {
+                SandboxProfile::Trusted => {}
+                SandboxProfile::Allow(allowed) => {
+                    evaluator.set_capability_policy(Box::new(move |capability: &str, _: &str| {
+                        if allowed.iter().any(|name| name == capability) {
+                            Ok(())
+                        } else {
+                            Err(format!("{} is not allowed by the sandbox", capability))
+                        }
+                    }));
+                }
+                SandboxProfile::Locked => {
+                    evaluator.set_capability_policy(Box::new(|capability: &str, _: &str| {
+                        Err(format!("{} is not allowed by the sandbox", capability))
+                    }));
+                }
+            }
+            if options.deterministic {
+                evaluator.set_deterministic(true);
+            }
+            if let Some(sink) = options.output {
+                evaluator.set_output_sink(sink);
+            }
+            if let Some(host) = options.input {
+                evaluator.set_stream_host(host);
+            }
+            match pipeline.run(source, Target::Eval)? {
+                Output::Value(value) => Ok(value),
+                _ => unreachable!("the Eval target yields a value"),
+            }
+        }
+        Backend::Bytecode => {
+            let chunk = match pipeline.run(source, Target::Bytecode)? {
+                Output::Bytecode(chunk) => chunk,
+                _ => unreachable!("the Bytecode target yields a chunk"),
+            };
+            VM::new().execute(chunk).map_err(|error| {
+                let mut diagnostics = Diagnostics::new();
+                diagnostics.push(Diagnostic::error(format!("Runtime error: {:?}", error)));
+                diagnostics
+            })
+        }
+    }
+}
@@ -1,12 +1,49 @@
 //! Error Formatter
 //!
 //! Pretty-prints diagnostics with source location information.
+//!
+//! [`Diagnostic::write_to`] writes straight into any [`fmt::Write`] sink,
+//! e.g. a kernel console or a fixed buffer, without building intermediate
+//! strings; the fixed parts of the layout come from static tables. With
+//! [`ColorChoice::Ansi`] severities are colored, and with
+//! [`ColorChoice::Plain`] any escape codes, including ones embedded in
+//! messages, are stripped on the way out.
+//!
+//! [`Diagnostic::write_with`] takes [`FormatOptions`] instead: a
+//! [`ColorDepth`] for what the device can show (none, 16, 256 or 24-bit
+//! colors), a [`Theme`] (the standard colors, a palette that stays distinct
+//! under color blindness, or bright bold high contrast), and whether the
+//! device can underline. Severities are always spelled out, so no theme
+//! relies on color alone. Dumb terminals get no escape codes at all, and
+//! [`FormatOptions::vga`] keeps to what the VGA text console shows: 16
+//! colors and no underline. Under `std`, [`FormatOptions::from_env`] picks
+//! options from `NO_COLOR`, `TERM`, `COLORTERM` and `GLIMMER_THEME`.
+//!
+//! Given the source, [`Diagnostic::write_with_source`] quotes the lines the
+//! labels point at and marks each span under its text, `^` for the primary
+//! label and `-` for secondary ones, with each label's message beside or
+//! below its marker:
+//!
+//! ```text
+//! error: Use of moved value 'x'
+//!   ---> line 2:11
+//!    |
+//!  2 | bind z to x + y
+//!    |           ^   - moved here
+//!    |           |
+//!    |           used here
+//! ```
+//!
+//! Tabs print as four spaces and wide characters take two columns, so the
+//! markers line up on a terminal; markers are plain characters, so they
+//! survive consoles without color or underline.
 
 use alloc::string::String;
 use alloc::vec::Vec;
-use alloc::format;
 use core::fmt;
+use core::fmt::Write;
 
+use crate::fixit::Suggestion;
 use crate::source_location::SourceSpan;
 
 /// Severity level of a diagnostic message
@@ -22,15 +59,272 @@ pub enum Severity {
     Help,
 }
 
+/// Name of each severity, in declaration order
+const SEVERITY_NAMES: [&str; 4] = ["error", "warning", "info", "help"];
+
+const RESET: &str = "\x1b[0m";
+const PRIMARY_MARKER: &str = "  ---> ";
+const SECONDARY_MARKER: &str = "  ---- ";
+const NOTE_PREFIX: &str = "  = note: ";
+const HELP_PREFIX: &str = "  = help: ";
+const TAB: &str = "    ";
+
+impl Severity {
+    /// Lowercase name, as diagnostics print it
+    pub fn as_str(self) -> &'static str {
+        SEVERITY_NAMES[self as usize]
+    }
+}
+
 impl fmt::Display for Severity {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Severity::Error => write!(f, "error"),
-            Severity::Warning => write!(f, "warning"),
-            Severity::Info => write!(f, "info"),
-            Severity::Help => write!(f, "help"),
+        f.write_str(self.as_str())
+    }
+}
+
+/// Whether formatted diagnostics carry ANSI color codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// No escape codes; any in the messages themselves are stripped
+    #[default]
+    Plain,
+    /// Color the severity for terminals
+    Ansi,
+}
+
+/// How many colors the output device can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorDepth {
+    /// No escape codes; any in the messages themselves are stripped
+    #[default]
+    NoColor,
+    /// The 16 standard ANSI colors
+    Ansi16,
+    /// The xterm 256-color palette
+    Ansi256,
+    /// 24-bit RGB
+    TrueColor,
+}
+
+/// Colors diagnostics are drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    /// Red errors, yellow warnings, cyan info, green help
+    #[default]
+    Standard,
+    /// Okabe-Ito colors, which stay apart under red-green color blindness;
+    /// help is blue rather than green
+    ColorBlind,
+    /// Bright, bold colors, with severities underlined where the device can
+    HighContrast,
+}
+
+impl Theme {
+    /// Theme called `name` (`standard`, `color-blind` or `high-contrast`)
+    pub fn from_name(name: &str) -> Option<Theme> {
+        match name {
+            "standard" => Some(Theme::Standard),
+            "color-blind" | "colorblind" => Some(Theme::ColorBlind),
+            "high-contrast" => Some(Theme::HighContrast),
+            _ => None,
         }
     }
+
+    fn styles(self) -> &'static [Style; 5] {
+        &THEME_STYLES[self as usize]
+    }
+}
+
+/// How [`Diagnostic::write_with`] renders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FormatOptions {
+    /// Colors the device can show
+    pub depth: ColorDepth,
+    /// Colors to draw in
+    pub theme: Theme,
+    /// Whether the device can underline; without it underlined parts are
+    /// only bold
+    pub underline: bool,
+}
+
+impl From<ColorChoice> for FormatOptions {
+    fn from(color: ColorChoice) -> Self {
+        match color {
+            ColorChoice::Plain => FormatOptions::plain(),
+            ColorChoice::Ansi => FormatOptions::ansi(ColorDepth::Ansi16, Theme::Standard),
+        }
+    }
+}
+
+impl FormatOptions {
+    /// No escape codes, for dumb terminals, files and pipes
+    pub const fn plain() -> Self {
+        FormatOptions { depth: ColorDepth::NoColor, theme: Theme::Standard, underline: false }
+    }
+
+    /// A terminal showing `depth` colors, drawn in `theme`
+    pub const fn ansi(depth: ColorDepth, theme: Theme) -> Self {
+        FormatOptions { depth, theme, underline: true }
+    }
+
+    /// The VGA text console: 16 colors, no underline
+    pub const fn vga() -> Self {
+        FormatOptions { depth: ColorDepth::Ansi16, theme: Theme::Standard, underline: false }
+    }
+
+    /// The same options in `theme`
+    pub const fn with_theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Options for a terminal described by the environment `var` reads
+    ///
+    /// `NO_COLOR` or a missing or `dumb` `TERM` turn colors off;
+    /// `COLORTERM=truecolor` (or `24bit`) and a `TERM` ending in
+    /// `256color` raise the depth; the Linux console cannot underline.
+    /// `GLIMMER_THEME` names the theme.
+    pub fn detect(var: impl Fn(&str) -> Option<String>) -> Self {
+        let theme = var("GLIMMER_THEME").and_then(|name| Theme::from_name(&name)).unwrap_or_default();
+        let term = var("TERM").unwrap_or_default();
+        if var("NO_COLOR").is_some_and(|value| !value.is_empty()) || term.is_empty() || term == "dumb" {
+            return FormatOptions::plain().with_theme(theme);
+        }
+        let depth = match var("COLORTERM").as_deref() {
+            Some("truecolor" | "24bit") => ColorDepth::TrueColor,
+            _ if term.ends_with("256color") => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16,
+        };
+        FormatOptions { depth, theme, underline: term != "linux" }
+    }
+
+    /// Options for standard error, from this process's environment; plain
+    /// when standard error is not a terminal
+    #[cfg(feature = "std")]
+    pub fn from_env() -> Self {
+        use std::io::IsTerminal;
+        let options = Self::detect(|name| std::env::var(name).ok());
+        if std::io::stderr().is_terminal() {
+            options
+        } else {
+            FormatOptions::plain().with_theme(options.theme)
+        }
+    }
+
+    /// Start drawing in `style`
+    fn open<W: Write>(&self, out: &mut W, style: &Style) -> fmt::Result {
+        if self.depth == ColorDepth::NoColor {
+            return Ok(());
+        }
+        out.write_str("\x1b[")?;
+        if style.bold {
+            out.write_str("1;")?;
+        }
+        if style.underline && self.underline {
+            out.write_str("4;")?;
+        }
+        match self.depth {
+            ColorDepth::TrueColor => write!(out, "38;2;{};{};{}m", style.rgb.0, style.rgb.1, style.rgb.2),
+            ColorDepth::Ansi256 => write!(out, "38;5;{}m", style.ansi256),
+            _ => write!(out, "{}m", style.ansi16),
+        }
+    }
+
+    /// Stop drawing in a style
+    fn close<W: Write>(&self, out: &mut W) -> fmt::Result {
+        if self.depth == ColorDepth::NoColor {
+            return Ok(());
+        }
+        out.write_str(RESET)
+    }
+}
+
+/// Look of one part of a diagnostic at every color depth
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Style {
+    ansi16: u8,
+    ansi256: u8,
+    rgb: (u8, u8, u8),
+    bold: bool,
+    underline: bool,
+}
+
+const fn style(ansi16: u8, ansi256: u8, rgb: (u8, u8, u8), bold: bool, underline: bool) -> Style {
+    Style { ansi16, ansi256, rgb, bold, underline }
+}
+
+/// Styles of each theme, in declaration order: the four severities, then
+/// secondary labels
+const THEME_STYLES: [[Style; 5]; 3] = [
+    [
+        style(31, 160, (205, 49, 49), true, false),
+        style(33, 178, (229, 165, 10), true, false),
+        style(36, 37, (17, 168, 205), true, false),
+        style(32, 34, (13, 188, 121), true, false),
+        style(34, 33, (36, 114, 200), false, false),
+    ],
+    [
+        style(31, 166, (213, 94, 0), true, false),
+        style(33, 214, (230, 159, 0), true, false),
+        style(36, 117, (86, 180, 233), true, false),
+        style(34, 32, (0, 114, 178), true, false),
+        style(35, 175, (204, 121, 167), false, false),
+    ],
+    [
+        style(91, 196, (255, 0, 0), true, true),
+        style(93, 226, (255, 255, 0), true, true),
+        style(96, 51, (0, 255, 255), true, true),
+        style(92, 46, (0, 255, 0), true, true),
+        style(97, 231, (255, 255, 255), true, false),
+    ],
+];
+
+/// Writer that drops ANSI escape sequences before passing text on
+///
+/// Sequences split across writes are dropped too.
+pub struct StripAnsi<W> {
+    inner: W,
+    in_escape: bool,
+}
+
+impl<W: Write> StripAnsi<W> {
+    /// Strip escape codes from everything written to `inner`
+    pub fn new(inner: W) -> Self {
+        StripAnsi { inner, in_escape: false }
+    }
+
+    /// The wrapped writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for StripAnsi<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let mut rest = s;
+        while !rest.is_empty() {
+            if self.in_escape {
+                // CSI sequences end with a byte in '@'..='~'; '[' opens them
+                match rest.char_indices().find(|&(i, c)| !(i == 0 && c == '[') && ('@'..='~').contains(&c)) {
+                    Some((end, _)) => {
+                        self.in_escape = false;
+                        rest = &rest[end + 1..];
+                    }
+                    None => return Ok(()),
+                }
+            } else {
+                match rest.find('\x1b') {
+                    Some(start) => {
+                        self.inner.write_str(&rest[..start])?;
+                        self.in_escape = true;
+                        rest = &rest[start + 1..];
+                    }
+                    None => return self.inner.write_str(rest),
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 /// A label pointing to a specific location in source code
@@ -75,6 +369,8 @@ pub struct Diagnostic {
     pub labels: Vec<Label>,
     /// Additional notes or suggestions
     pub notes: Vec<String>,
+    /// Edits that would fix the problem, printed as help lines
+    pub suggestions: Vec<Suggestion>,
 }
 
 impl Diagnostic {
@@ -85,6 +381,7 @@ impl Diagnostic {
             message: message.into(),
             labels: Vec::new(),
             notes: Vec::new(),
+            suggestions: Vec::new(),
         }
     }
 
@@ -95,6 +392,7 @@ impl Diagnostic {
             message: message.into(),
             labels: Vec::new(),
             notes: Vec::new(),
+            suggestions: Vec::new(),
         }
     }
 
@@ -116,32 +414,388 @@ impl Diagnostic {
         self
     }
 
+    /// Attach an edit that would fix the problem
+    pub fn with_suggestion(mut self, suggestion: Suggestion) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
+
     /// Format this diagnostic for display
     pub fn format(&self) -> String {
-        let mut output = format!("{}: {}\n", self.severity, self.message);
+        let mut output = String::new();
+        // Writing to a String cannot fail
+        let _ = self.write_to(&mut output, ColorChoice::Plain);
+        output
+    }
+
+    /// Write this diagnostic to `out` without allocating
+    pub fn write_to<W: Write>(&self, out: &mut W, color: ColorChoice) -> fmt::Result {
+        self.write_with(out, &color.into())
+    }
+
+    /// Write this diagnostic to `out` as `options` say, without allocating
+    pub fn write_with<W: Write>(&self, out: &mut W, options: &FormatOptions) -> fmt::Result {
+        match options.depth {
+            ColorDepth::NoColor => self.write_layout(&mut StripAnsi::new(out), options),
+            _ => self.write_layout(out, options),
+        }
+    }
+
+    fn write_layout<W: Write>(&self, out: &mut W, options: &FormatOptions) -> fmt::Result {
+        let styles = options.theme.styles();
+        let severity = &styles[self.severity as usize];
+        options.open(out, severity)?;
+        out.write_str(self.severity.as_str())?;
+        options.close(out)?;
+        out.write_str(": ")?;
+        out.write_str(&self.message)?;
+        out.write_char('\n')?;
 
-        // Add labels
         for label in &self.labels {
-            let marker = if label.primary { "--->" } else { "----" };
-            output.push_str(&format!("  {} {}", marker, label.span));
+            if label.primary {
+                options.open(out, &Style { underline: false, ..*severity })?;
+                out.write_str(PRIMARY_MARKER)?;
+            } else {
+                options.open(out, &styles[4])?;
+                out.write_str(SECONDARY_MARKER)?;
+            }
+            options.close(out)?;
+            write!(out, "{}", label.span)?;
             if let Some(ref msg) = label.message {
-                output.push_str(&format!(": {}", msg));
+                out.write_str(": ")?;
+                out.write_str(msg)?;
             }
-            output.push('\n');
+            out.write_char('\n')?;
         }
 
-        // Add notes
-        for note in &self.notes {
-            output.push_str(&format!("  = note: {}\n", note));
+        self.write_footer(out)
+    }
+    /// Write this diagnostic to `out` with the lines of `source` its labels
+    /// point at, without allocating
+    ///
+    /// Labels whose spans are unknown or lie outside `source` are listed
+    /// after the snippet instead.
+    pub fn write_with_source<W: Write>(&self, out: &mut W, source: &str, options: &FormatOptions) -> fmt::Result {
+        match options.depth {
+            ColorDepth::NoColor => self.write_snippet(&mut StripAnsi::new(out), source, options),
+            _ => self.write_snippet(out, source, options),
         }
+    }
 
+    /// Plain text of this diagnostic quoting `source`
+    pub fn format_with_source(&self, source: &str) -> String {
+        let mut output = String::new();
+        // Writing to a String cannot fail
+        let _ = self.write_with_source(&mut output, source, &FormatOptions::plain());
         output
     }
+
+    fn write_snippet<W: Write>(&self, out: &mut W, source: &str, options: &FormatOptions) -> fmt::Result {
+        let line_count = source.lines().count();
+        let quoted = |label: &Label| label.span.is_known() && label.span.start.line <= line_count;
+        let Some(first) = self.labels.iter().filter(|label| quoted(label)).min_by_key(|label| !label.primary) else {
+            return self.write_layout(out, options);
+        };
+
+        options.open(out, self.severity_style(options))?;
+        out.write_str(self.severity.as_str())?;
+        options.close(out)?;
+        out.write_str(": ")?;
+        out.write_str(&self.message)?;
+        out.write_char('\n')?;
+        writeln!(out, "{}{}", PRIMARY_MARKER, first.span.start)?;
+
+        let quoted_lines = || self.labels.iter().filter(|label| quoted(label)).map(|label| label.span.start.line);
+        let gutter = digits(quoted_lines().max().unwrap_or(0));
+        write_gutter(out, gutter, None)?;
+        out.write_char('\n')?;
+        let mut previous: Option<usize> = None;
+        while let Some(line) = quoted_lines().filter(|&line| previous.is_none_or(|previous| line > previous)).min() {
+            match previous {
+                Some(previous) if line == previous + 2 => self.write_source_line(out, source, gutter, previous + 1)?,
+                Some(previous) if line > previous + 2 => out.write_str("...\n")?,
+                _ => {}
+            }
+            self.write_source_line(out, source, gutter, line)?;
+            self.write_markers(out, source, gutter, line, options)?;
+            previous = Some(line);
+        }
+
+        for label in self.labels.iter().filter(|label| !quoted(label)) {
+            out.write_str(if label.primary { PRIMARY_MARKER } else { SECONDARY_MARKER })?;
+            write!(out, "{}", label.span)?;
+            if let Some(ref msg) = label.message {
+                out.write_str(": ")?;
+                out.write_str(msg)?;
+            }
+            out.write_char('\n')?;
+        }
+        self.write_footer(out)
+    }
+
+    /// Notes, then a help line per suggestion
+    fn write_footer<W: Write>(&self, out: &mut W) -> fmt::Result {
+        for note in &self.notes {
+            out.write_str(NOTE_PREFIX)?;
+            out.write_str(note)?;
+            out.write_char('\n')?;
+        }
+        for suggestion in &self.suggestions {
+            out.write_str(HELP_PREFIX)?;
+            out.write_str(&suggestion.message)?;
+            writeln!(out, " ({})", suggestion.start)?;
+        }
+        Ok(())
+    }
+
+    fn severity_style<'a>(&self, options: &'a FormatOptions) -> &'a Style {
+        &options.theme.styles()[self.severity as usize]
+    }
+
+    fn label_style(&self, label: &Label, options: &FormatOptions) -> Style {
+        if label.primary {
+            Style { underline: false, ..*self.severity_style(options) }
+        } else {
+            options.theme.styles()[4]
+        }
+    }
+
+    fn write_source_line<W: Write>(&self, out: &mut W, source: &str, gutter: usize, line: usize) -> fmt::Result {
+        write_gutter(out, gutter, Some(line))?;
+        let text = source.lines().nth(line - 1).unwrap_or_default();
+        for (i, part) in text.split('\t').enumerate() {
+            if i > 0 {
+                out.write_str(TAB)?;
+            }
+            out.write_str(part)?;
+        }
+        out.write_char('\n')
+    }
+
+    /// Marker row for the labels on `line`, then rows hanging the messages
+    /// of all but the rightmost label below their markers
+    fn write_markers<W: Write>(&self, out: &mut W, source: &str, gutter: usize, line: usize, options: &FormatOptions) -> fmt::Result {
+        let text = source.lines().nth(line - 1).unwrap_or_default();
+        // Labels on this line as (display column, index), which orders them
+        // left to right
+        let on_line = || {
+            self.labels.iter().enumerate().filter(move |(_, label)| label.span.is_known() && label.span.start.line == line)
+        };
+        let position = |(index, label): (usize, &Label)| (marker_extent(text, &label.span).0, index);
+        let Some(rightmost) = on_line().map(position).max() else {
+            return Ok(());
+        };
+        let end = on_line().map(|(_, label)| marker_extent(text, &label.span)).map(|(column, width)| column + width).max().unwrap_or(0);
+
+        write_gutter(out, gutter, None)?;
+        let mut current: Option<usize> = None;
+        for column in 0..end {
+            // The primary label's marker wins where markers overlap
+            let covering = on_line()
+                .filter(|(_, label)| {
+                    let (start, width) = marker_extent(text, &label.span);
+                    (start..start + width).contains(&column)
+                })
+                .max_by_key(|(_, label)| label.primary)
+                .map(|(index, _)| index);
+            if covering != current {
+                if current.is_some() {
+                    options.close(out)?;
+                }
+                if let Some(index) = covering {
+                    options.open(out, &self.label_style(&self.labels[index], options))?;
+                }
+                current = covering;
+            }
+            out.write_char(match covering {
+                Some(index) if self.labels[index].primary => '^',
+                Some(_) => '-',
+                None => ' ',
+            })?;
+        }
+        if current.is_some() {
+            options.close(out)?;
+        }
+        if let Some(message) = &self.labels[rightmost.1].message {
+            out.write_char(' ')?;
+            self.write_styled(out, rightmost.1, message, options)?;
+        }
+        out.write_char('\n')?;
+
+        // Labels left of `bound` with a message still to hang
+        let hanging = |bound: (usize, usize)| {
+            on_line().filter(|(_, label)| label.message.is_some()).map(position).filter(move |&at| at < bound)
+        };
+        if hanging(rightmost).next().is_none() {
+            return Ok(());
+        }
+        self.write_bars(out, gutter, hanging(rightmost), options)?;
+        out.write_char('\n')?;
+        let mut bound = rightmost;
+        while let Some((column, index)) = hanging(bound).max() {
+            self.write_bars(out, gutter, hanging((column, index)), options)?;
+            let mut written = hanging((column, index)).map(|(column, _)| column + 1).max().unwrap_or(0);
+            while written < column {
+                out.write_char(' ')?;
+                written += 1;
+            }
+            if let Some(message) = &self.labels[index].message {
+                self.write_styled(out, index, message, options)?;
+            }
+            out.write_char('\n')?;
+            bound = (column, index);
+        }
+        Ok(())
+    }
+
+    /// Gutter, then a `|` under each marker in `at`
+    fn write_bars<W: Write>(&self, out: &mut W, gutter: usize, at: impl Iterator<Item = (usize, usize)> + Clone, options: &FormatOptions) -> fmt::Result {
+        write_gutter(out, gutter, None)?;
+        let end = at.clone().map(|(column, _)| column + 1).max().unwrap_or(0);
+        for column in 0..end {
+            match at.clone().filter(|&(start, _)| start == column).max() {
+                Some((_, index)) => self.write_styled(out, index, "|", options)?,
+                None => out.write_char(' ')?,
+            }
+        }
+        Ok(())
+    }
+
+    fn write_styled<W: Write>(&self, out: &mut W, index: usize, text: &str, options: &FormatOptions) -> fmt::Result {
+        options.open(out, &self.label_style(&self.labels[index], options))?;
+        out.write_str(text)?;
+        options.close(out)
+    }
+}
+
+/// Number of decimal digits in `n`
+fn digits(mut n: usize) -> usize {
+    let mut count = 1;
+    while n >= 10 {
+        n /= 10;
+        count += 1;
+    }
+    count
+}
+
+/// ` 12 | `, or the same width of blank gutter without a line number
+fn write_gutter<W: Write>(out: &mut W, width: usize, line: Option<usize>) -> fmt::Result {
+    match line {
+        Some(line) => write!(out, " {:>width$} | ", line, width = width),
+        None => write!(out, " {:width$} | ", "", width = width),
+    }
+}
+
+/// First display column and width of the marker for `span` on `line`
+///
+/// Columns count characters from 1 and `span` ends on the column of its
+/// last character; a span running onto later lines is marked to the end of
+/// its first.
+fn marker_extent(line: &str, span: &SourceSpan) -> (usize, usize) {
+    let first = span.start.column.saturating_sub(1);
+    let last = if span.end.line == span.start.line && span.end.column > span.start.column {
+        span.end.column - 1
+    } else if span.end.line > span.start.line {
+        usize::MAX
+    } else {
+        first
+    };
+    let (mut column, mut width) = (0, 0);
+    for (i, c) in line.chars().enumerate() {
+        let columns = if c == '\t' { TAB.len() } else { char_width(c) };
+        if i < first {
+            column += columns;
+        } else if i <= last {
+            width += columns;
+        }
+    }
+    (column, width.max(1))
+}
+
+/// Terminal columns `c` takes: none for combining marks and other
+/// zero-width characters, two for East Asian wide characters and emoji
+fn char_width(c: char) -> usize {
+    match c as u32 {
+        0x00..=0x1F | 0x7F..=0x9F => 0,
+        0x0300..=0x036F | 0x1AB0..=0x1AFF | 0x1DC0..=0x1DFF | 0x200B..=0x200F | 0x20D0..=0x20FF => 0,
+        0xFE00..=0xFE0F | 0xFE20..=0xFE2F => 0,
+        0x1100..=0x115F | 0x2E80..=0x303E | 0x3041..=0x33FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF => 2,
+        0xA000..=0xA4CF | 0xAC00..=0xD7A3 | 0xF900..=0xFAFF | 0xFE30..=0xFE4F | 0xFF00..=0xFF60 | 0xFFE0..=0xFFE6 => 2,
+        0x1F300..=0x1F64F | 0x1F900..=0x1F9FF | 0x20000..=0x3FFFD => 2,
+        _ => 1,
+    }
 }
 
 impl fmt::Display for Diagnostic {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.format())
+        self.write_to(f, ColorChoice::Plain)
+    }
+}
+
+/// Accumulates diagnostics from every stage of a compilation
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    items: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    /// Create an empty accumulator
+    pub fn new() -> Self {
+        Diagnostics { items: Vec::new() }
+    }
+
+    /// Record a diagnostic
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.items.push(diagnostic);
+    }
+
+    /// Whether any error-severity diagnostic has been recorded
+    pub fn has_errors(&self) -> bool {
+        self.items.iter().any(|d| d.severity == Severity::Error)
+    }
+
+    /// Number of error-severity diagnostics
+    pub fn error_count(&self) -> usize {
+        self.items.iter().filter(|d| d.severity == Severity::Error).count()
+    }
+
+    /// Number of recorded diagnostics
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Whether nothing has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Iterate over recorded diagnostics in the order they were reported
+    pub fn iter(&self) -> core::slice::Iter<'_, Diagnostic> {
+        self.items.iter()
+    }
+
+    /// Take the recorded diagnostics
+    pub fn into_vec(self) -> Vec<Diagnostic> {
+        self.items
+    }
+
+    /// Write every diagnostic to `out` without allocating
+    pub fn write_to<W: Write>(&self, out: &mut W, color: ColorChoice) -> fmt::Result {
+        self.write_with(out, &color.into())
+    }
+
+    /// Write every diagnostic to `out` as `options` say, without allocating
+    pub fn write_with<W: Write>(&self, out: &mut W, options: &FormatOptions) -> fmt::Result {
+        for diagnostic in &self.items {
+            diagnostic.write_with(out, options)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f, ColorChoice::Plain)
     }
 }
 
@@ -163,4 +817,75 @@ mod tests {
         assert!(formatted.contains("value used here"));
         assert!(formatted.contains("note: 'x' was moved on line 8"));
     }
+
+    #[test]
+    fn test_diagnostics_accumulator() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.push(Diagnostic::warning("unused binding 'y'"));
+        assert!(!diagnostics.has_errors());
+
+        diagnostics.push(Diagnostic::error("undefined variable 'x'"));
+        assert!(diagnostics.has_errors());
+        assert_eq!(diagnostics.error_count(), 1);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics.to_string().starts_with("warning: unused binding 'y'"));
+    }
+
+    #[test]
+    fn test_color_choice() {
+        let diag = Diagnostic::error("bad \x1b[4mthing\x1b[0m").with_note("see \x1b[1mdocs");
+
+        let mut colored = String::new();
+        diag.write_to(&mut colored, ColorChoice::Ansi).unwrap();
+        assert!(colored.starts_with("\x1b[1;31merror\x1b[0m: bad \x1b[4mthing"));
+
+        let mut plain = String::new();
+        diag.write_to(&mut plain, ColorChoice::Plain).unwrap();
+        assert_eq!(plain, "error: bad thing\n  = note: see docs\n");
+        assert_eq!(plain, diag.format());
+    }
+
+    #[test]
+    fn test_themes_and_depths() {
+        let diag = Diagnostic::warning("slow loop")
+            .with_secondary_label(SourceSpan::point(SourceLocation::new(1, 2)), "here");
+        let render = |options: FormatOptions| {
+            let mut out = String::new();
+            diag.write_with(&mut out, &options).unwrap();
+            out
+        };
+        let high_contrast = FormatOptions::ansi(ColorDepth::Ansi256, Theme::HighContrast);
+        assert!(render(high_contrast).starts_with("\x1b[1;4;38;5;226mwarning\x1b[0m"));
+        let vga = FormatOptions::vga().with_theme(Theme::HighContrast);
+        assert!(render(vga).starts_with("\x1b[1;93mwarning\x1b[0m"));
+        let color_blind = FormatOptions::ansi(ColorDepth::TrueColor, Theme::ColorBlind);
+        assert!(render(color_blind).contains("\x1b[38;2;204;121;167m  ---- \x1b[0mline 1:2"));
+        assert_eq!(render(FormatOptions::plain().with_theme(Theme::HighContrast)), diag.format());
+    }
+
+    #[test]
+    fn test_detect_options() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.to_string())
+        };
+        assert_eq!(FormatOptions::detect(env(&[])), FormatOptions::plain());
+        assert_eq!(FormatOptions::detect(env(&[("TERM", "dumb")])).depth, ColorDepth::NoColor);
+        assert_eq!(FormatOptions::detect(env(&[("TERM", "xterm-256color"), ("NO_COLOR", "1")])).depth, ColorDepth::NoColor);
+        assert_eq!(
+            FormatOptions::detect(env(&[("TERM", "xterm-256color"), ("GLIMMER_THEME", "color-blind")])),
+            FormatOptions::ansi(ColorDepth::Ansi256, Theme::ColorBlind)
+        );
+        assert_eq!(FormatOptions::detect(env(&[("TERM", "xterm"), ("COLORTERM", "truecolor")])).depth, ColorDepth::TrueColor);
+        assert_eq!(FormatOptions::detect(env(&[("TERM", "linux")])), FormatOptions::vga());
+    }
+
+    #[test]
+    fn test_strip_ansi_across_writes() {
+        let mut out = StripAnsi::new(String::new());
+        out.write_str("a\x1b").unwrap();
+        out.write_str("[1;3").unwrap();
+        out.write_str("1mb\x1b[").unwrap();
+        out.write_str("mc").unwrap();
+        assert_eq!(out.into_inner(), "abc");
+    }
 }
@@ -10,16 +10,22 @@
 //! - Capability requests (via kernel syscalls)
 
 use alloc::boxed::Box;
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use crate::ast::*;
+use crate::bigint::BigInt;
+use crate::source_location::SourceSpan;
 
 /// Runtime value types in Glimmer-Weave
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     /// Numeric value (f64)
     Number(f64),
+    /// Arbitrary-precision integer (`123n`)
+    BigInt(BigInt),
+    /// 64-bit integer (`42i`)
+    Integer(i64),
     /// String value
     Text(String),
     /// Boolean value
@@ -28,13 +34,21 @@ pub enum Value {
     Nothing,
     /// List of values
     List(Vec<Value>),
+    /// Fixed-size group of values: `(1, "a", true)`
+    Tuple(Vec<Value>),
+    /// Unordered collection of distinct elements, kept sorted
+    Set(BTreeSet<SetElement>),
     /// Map from string keys to values
+    ///
+    /// Entries iterate in key order, whatever order they were inserted in.
     Map(BTreeMap<String, Value>),
     /// Function (stored as AST for now - could be bytecode later)
     Chant {
         params: Vec<Parameter>,
         body: Vec<AstNode>,
         closure: Environment,
+        /// Declared return type, used to convert errors propagated by `?`
+        return_type: Option<TypeAnnotation>,
     },
     /// Native function (builtin runtime library function)
     NativeChant(crate::runtime::NativeFunction),
@@ -113,8 +127,107 @@ pub enum Value {
         borrowed: bool,  // true if currently borrowed mutably
         borrow_count: usize,  // Number of immutable borrows
     },
+    /// Host handle (file, socket, device) released exactly once, either by
+    /// `release(handle)` or when the acquiring chant finishes
+    Resource {
+        id: crate::resource::ResourceId,  // 0 until the evaluator adopts it
+        kind: String,
+        handle: u64,
+    },
+    /// Growable text buffer for building large text piece by piece
+    /// Copies share the buffer, so `text_push` appends in place instead of
+    /// copying the text built so far
+    TextBuilder {
+        buffer: TextBuffer,
+    },
+    /// Deeply immutable view of a list, map or form instance (see `freeze`)
+    /// Reads see through it and hand out frozen views of what they read
+    Frozen {
+        value: Box<Value>,
+    },
+    /// Value derived from data a source capability produced, with the
+    /// names of those capabilities (see [`crate::taint`])
+    Tainted {
+        value: Box<Value>,
+        sources: Vec<String>,
+    },
+}
+
+/// Element of a [`Value::Set`]
+///
+/// Only values with a total order can be set elements: nothing, truths,
+/// numbers and text, ordered in that sequence. Numbers compare by
+/// `f64::total_cmp` with `-0` stored as `0`, so every NaN is one element.
+#[derive(Debug, Clone)]
+pub enum SetElement {
+    Nothing,
+    Truth(bool),
+    Number(f64),
+    Text(String),
+}
+
+impl SetElement {
+    /// The element standing for `value`; an error for values without an order
+    pub fn from_value(value: &Value) -> Result<SetElement, RuntimeError> {
+        match value {
+            Value::Nothing => Ok(SetElement::Nothing),
+            Value::Truth(b) => Ok(SetElement::Truth(*b)),
+            Value::Number(n) if *n == 0.0 => Ok(SetElement::Number(0.0)),
+            Value::Number(n) => Ok(SetElement::Number(*n)),
+            Value::Text(s) => Ok(SetElement::Text(s.clone())),
+            Value::Frozen { value } | Value::Tainted { value, .. } => SetElement::from_value(value),
+            other => Err(RuntimeError::TypeError {
+                expected: "Number, Text, Truth or Nothing as a set element".to_string(),
+                got: other.type_name().to_string(),
+            }),
+        }
+    }
+
+    /// The element as a script value
+    pub fn to_value(&self) -> Value {
+        match self {
+            SetElement::Nothing => Value::Nothing,
+            SetElement::Truth(b) => Value::Truth(*b),
+            SetElement::Number(n) => Value::Number(*n),
+            SetElement::Text(s) => Value::Text(s.clone()),
+        }
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            SetElement::Nothing => 0,
+            SetElement::Truth(_) => 1,
+            SetElement::Number(_) => 2,
+            SetElement::Text(_) => 3,
+        }
+    }
+}
+
+impl Ord for SetElement {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        match (self, other) {
+            (SetElement::Truth(a), SetElement::Truth(b)) => a.cmp(b),
+            (SetElement::Number(a), SetElement::Number(b)) => a.total_cmp(b),
+            (SetElement::Text(a), SetElement::Text(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl PartialOrd for SetElement {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
+impl PartialEq for SetElement {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for SetElement {}
+
 /// Iterator state - tracks position and remaining elements
 #[derive(Debug, Clone, PartialEq)]
 pub enum IteratorState {
@@ -146,29 +259,36 @@ pub enum IteratorState {
     },
     /// Empty iterator - always returns Absent
     Empty,
+    /// Form or variant embodying `Iterable`, advanced by its `next` method
+    Aspect {
+        value: Box<Value>,
+    },
+    /// Lines or bytes pulled from an open stream resource on demand
+    Stream {
+        source: Box<Value>,
+        unit: crate::stream::StreamUnit,
+    },
 }
 
 impl Value {
-    /// Check if value is truthy (for conditionals)
+    /// Check if value is truthy (for conditionals); see
+    /// [`coercion::truthy`](crate::coercion::truthy)
     pub fn is_truthy(&self) -> bool {
-        match self {
-            Value::Truth(b) => *b,
-            Value::Nothing => false,
-            Value::Number(n) => *n != 0.0,
-            Value::Text(s) => !s.is_empty(),
-            Value::List(l) => !l.is_empty(),
-            _ => true,
-        }
+        crate::coercion::truthy(self)
     }
 
     /// Convert to human-readable string (for debugging)
     pub fn type_name(&self) -> &str {
         match self {
             Value::Number(_) => "Number",
+            Value::BigInt(_) => "BigInt",
+            Value::Integer(_) => "Integer",
             Value::Text(_) => "Text",
             Value::Truth(_) => "Truth",
             Value::Nothing => "Nothing",
             Value::List(_) => "List",
+            Value::Tuple(_) => "Tuple",
+            Value::Set(_) => "Set",
             Value::Map(_) => "Map",
             Value::Chant { .. } => "Chant",
             Value::NativeChant(_) => "NativeChant",
@@ -184,8 +304,182 @@ impl Value {
             Value::Iterator { iterator_type, .. } => iterator_type.as_str(),
             Value::Shared { .. } => "Shared",
             Value::Cell { .. } => "Cell",
+            Value::Resource { .. } => "Resource",
+            Value::TextBuilder { .. } => "TextBuilder",
+            Value::Frozen { value } | Value::Tainted { value, .. } => value.type_name(),
+        }
+    }
+
+    /// Wrap a host handle for a native to return; the evaluator adopts it
+    /// and releases it when the calling chant finishes
+    pub fn resource(kind: &str, handle: u64) -> Value {
+        Value::Resource { id: 0, kind: kind.to_string(), handle }
+    }
+
+    /// Deeply immutable view of the value, for handing shared state to
+    /// untrusted scripts
+    ///
+    /// `set` on a frozen value, or on anything read out of it, fails with
+    /// [`RuntimeError::FrozenValue`]; copies stay frozen. Only lists, maps
+    /// and form instances are wrapped, since nothing else can be changed in
+    /// place; a tuple freezes its elements.
+    pub fn freeze(self) -> Value {
+        match self {
+            Value::List(_) | Value::Map(_) | Value::StructInstance { .. } => Value::Frozen { value: Box::new(self) },
+            Value::Tuple(items) => Value::Tuple(items.into_iter().map(Value::freeze).collect()),
+            other => other,
+        }
+    }
+
+    /// Whether the value is a frozen view
+    pub fn is_frozen(&self) -> bool {
+        matches!(self, Value::Frozen { .. })
+    }
+
+    /// Mutable copy of the value, with every frozen view inside it thawed too
+    pub fn thaw(self) -> Value {
+        match self {
+            Value::Frozen { value } => value.thaw(),
+            Value::List(items) => Value::List(items.into_iter().map(Value::thaw).collect()),
+            Value::Tuple(items) => Value::Tuple(items.into_iter().map(Value::thaw).collect()),
+            Value::Map(entries) => Value::Map(entries.into_iter().map(|(key, value)| (key, value.thaw())).collect()),
+            Value::StructInstance { struct_name, fields } => Value::StructInstance {
+                struct_name,
+                fields: fields.into_iter().map(|(key, value)| (key, value.thaw())).collect(),
+            },
+            other => other,
+        }
+    }
+
+    /// The value marked as derived from `sources`; unchanged when there
+    /// are none
+    pub fn taint(self, sources: &[String]) -> Value {
+        if sources.is_empty() {
+            return self;
+        }
+        match self {
+            Value::Tainted { value, sources: mut existing } => {
+                crate::taint::merge(&mut existing, sources.iter().cloned());
+                Value::Tainted { value, sources: existing }
+            }
+            value => Value::Tainted { value: Box::new(value), sources: sources.to_vec() },
+        }
+    }
+
+    /// Source capabilities of every tainted value in or under this one
+    pub fn taint_sources(&self) -> Vec<String> {
+        let mut sources = Vec::new();
+        self.collect_taint(&mut sources);
+        sources
+    }
+
+    fn collect_taint(&self, sources: &mut Vec<String>) {
+        match self {
+            Value::Tainted { value, sources: own } => {
+                crate::taint::merge(sources, own.iter().cloned());
+                value.collect_taint(sources);
+            }
+            Value::Frozen { value } | Value::Outcome { value, .. } => value.collect_taint(sources),
+            Value::Maybe { value: Some(value), .. } => value.collect_taint(sources),
+            Value::List(items) | Value::Tuple(items) | Value::VariantValue { fields: items, .. } => {
+                items.iter().for_each(|item| item.collect_taint(sources))
+            }
+            Value::Map(entries) | Value::StructInstance { fields: entries, .. } => {
+                entries.values().for_each(|value| value.collect_taint(sources))
+            }
+            _ => {}
+        }
+    }
+
+    /// The value with every taint mark in or under it removed
+    pub fn untainted(self) -> Value {
+        match self {
+            Value::Tainted { value, .. } => value.untainted(),
+            Value::Frozen { value } => Value::Frozen { value: Box::new(value.untainted()) },
+            Value::Outcome { success, value } => Value::Outcome { success, value: Box::new(value.untainted()) },
+            Value::Maybe { present, value } => Value::Maybe { present, value: value.map(|value| Box::new(value.untainted())) },
+            Value::List(items) => Value::List(items.into_iter().map(Value::untainted).collect()),
+            Value::Tuple(items) => Value::Tuple(items.into_iter().map(Value::untainted).collect()),
+            Value::VariantValue { enum_name, variant_name, fields, type_args } => Value::VariantValue {
+                enum_name,
+                variant_name,
+                fields: fields.into_iter().map(Value::untainted).collect(),
+                type_args,
+            },
+            Value::Map(entries) => Value::Map(entries.into_iter().map(|(key, value)| (key, value.untainted())).collect()),
+            Value::StructInstance { struct_name, fields } => Value::StructInstance {
+                struct_name,
+                fields: fields.into_iter().map(|(key, value)| (key, value.untainted())).collect(),
+            },
+            other => other,
         }
     }
+
+    /// The value under an outer taint mark, with the mark's sources
+    pub(crate) fn split_taint(self) -> (Value, Vec<String>) {
+        match self {
+            Value::Tainted { value, sources } => (*value, sources),
+            other => (other, Vec::new()),
+        }
+    }
+
+    /// What a frozen view shows; any other value as is
+    pub(crate) fn unfrozen(&self) -> &Value {
+        match self {
+            Value::Frozen { value } => value,
+            other => other,
+        }
+    }
+
+    /// Empty text builder
+    pub fn text_builder() -> Value {
+        Value::TextBuilder { buffer: TextBuffer::default() }
+    }
+}
+
+/// Text behind a builder, shared by the builder's copies
+///
+/// Builders compare equal when their text is.
+#[derive(Debug, Clone, Default)]
+pub struct TextBuffer(crate::sync::Shared<String>);
+
+impl TextBuffer {
+    /// Append `text`
+    pub fn push_str(&self, text: &str) {
+        crate::sync::lock(&self.0).push_str(text);
+    }
+
+    /// The text so far
+    pub fn text(&self) -> String {
+        crate::sync::lock(&self.0).clone()
+    }
+
+    /// Length of the text so far, in bytes
+    pub fn len(&self) -> usize {
+        crate::sync::lock(&self.0).len()
+    }
+
+    /// Whether nothing has been appended
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Bytes reserved for the text
+    pub(crate) fn capacity(&self) -> usize {
+        crate::sync::lock(&self.0).capacity()
+    }
+
+    /// Address of the buffer, the same for every copy of a builder
+    pub(crate) fn id(&self) -> usize {
+        crate::sync::Shared::as_ptr(&self.0) as *const () as usize
+    }
+}
+
+impl PartialEq for TextBuffer {
+    fn eq(&self, other: &Self) -> bool {
+        // A buffer is locked once, even when compared with itself
+        crate::sync::Shared::ptr_eq(&self.0, &other.0) || self.text() == other.text()
+    }
 }
 
 /// Runtime errors that can occur during evaluation
@@ -247,6 +541,35 @@ pub enum RuntimeError {
     CompileError {
         message: String,
     },
+    /// Evaluation nested deeper than the evaluator's depth limit
+    DepthLimitExceeded {
+        limit: usize,
+    },
+    /// Chant calls nested deeper than the evaluator's call depth limit
+    StackOverflow {
+        limit: usize,
+    },
+    /// Execution ran past its deadline (see `Evaluator::eval_with_deadline`)
+    Timeout,
+    /// The host tripped the evaluator's cancellation token
+    Cancelled,
+    /// Resource used after it was released
+    ResourceReleased {
+        kind: String,
+        handle: u64,
+    },
+    /// Resource released a second time
+    DoubleRelease {
+        kind: String,
+        handle: u64,
+    },
+    /// `set` on a frozen value (names the variable holding it)
+    FrozenValue(String),
+    /// Integer arithmetic left the 64-bit range (names the operation)
+    IntegerOverflow(String),
+    /// `exit(status)` unwinding to the outermost `eval`, which records the
+    /// status (see `Evaluator::exit_status`)
+    Exit(i32),
 }
 
 impl RuntimeError {
@@ -271,6 +594,15 @@ impl RuntimeError {
             RuntimeError::ContinueOutsideLoop => "ContinueOutsideLoop",
             RuntimeError::Custom(_) => "CustomError",
             RuntimeError::CompileError { .. } => "CompileError",
+            RuntimeError::DepthLimitExceeded { .. } => "DepthLimitExceeded",
+            RuntimeError::StackOverflow { .. } => "StackOverflow",
+            RuntimeError::Timeout => "Timeout",
+            RuntimeError::Cancelled => "Cancelled",
+            RuntimeError::ResourceReleased { .. } => "ResourceReleased",
+            RuntimeError::DoubleRelease { .. } => "DoubleRelease",
+            RuntimeError::FrozenValue(_) => "FrozenValue",
+            RuntimeError::IntegerOverflow(_) => "IntegerOverflow",
+            RuntimeError::Exit(_) => "Exit",
         }
     }
 
@@ -301,6 +633,23 @@ impl RuntimeError {
             RuntimeError::UnexpectedYield => Value::Text("Unexpected yield outside function".to_string()),
             RuntimeError::MatchFailed => Value::Text("No pattern matched".to_string()),
             RuntimeError::CompileError { message } => Value::Text(message.clone()),
+            RuntimeError::DepthLimitExceeded { limit } => {
+                Value::Text(format!("Evaluation depth limit of {} exceeded", limit))
+            }
+            RuntimeError::StackOverflow { limit } => {
+                Value::Text(format!("Call depth limit of {} exceeded", limit))
+            }
+            RuntimeError::Timeout => Value::Text("Execution deadline exceeded".to_string()),
+            RuntimeError::Cancelled => Value::Text("Execution cancelled".to_string()),
+            RuntimeError::ResourceReleased { kind, handle } => {
+                Value::Text(format!("Resource {} #{} used after release", kind, handle))
+            }
+            RuntimeError::DoubleRelease { kind, handle } => {
+                Value::Text(format!("Resource {} #{} released twice", kind, handle))
+            }
+            RuntimeError::FrozenValue(name) => Value::Text(format!("Cannot modify frozen value '{}'", name)),
+            RuntimeError::IntegerOverflow(operation) => Value::Text(format!("Integer overflow in {}", operation)),
+            RuntimeError::Exit(status) => Value::Number(*status as f64),
             RuntimeError::Return(val) => val.clone(),
             RuntimeError::TailCall { function_name, .. } => Value::Text(format!("Tail call to {}", function_name)),
             RuntimeError::BreakOutsideLoop => Value::Text("Cannot use 'break' outside of a loop".to_string()),
@@ -312,19 +661,61 @@ impl RuntimeError {
 /// Variable binding with mutability tracking
 #[derive(Debug, Clone, PartialEq)]
 struct Binding {
+    name: String,
     value: Value,
     mutable: bool,
 }
 
+/// A single scope of bindings
+///
+/// Bindings are stored in definition order so a resolved [`ScopeSlot`] can
+/// address them directly; the name index serves unresolved lookups.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Frame {
+    slots: Vec<Binding>,
+    index: BTreeMap<String, usize>,
+}
+
+impl Frame {
+    fn get(&self, name: &str) -> Option<&Binding> {
+        self.index.get(name).map(|&slot| &self.slots[slot])
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut Binding> {
+        match self.index.get(name) {
+            Some(&slot) => Some(&mut self.slots[slot]),
+            None => None,
+        }
+    }
+
+    /// Binding at `slot`, if it holds `name`
+    fn slot(&self, name: &str, slot: usize) -> Option<&Binding> {
+        self.slots.get(slot).filter(|binding| binding.name == name)
+    }
+
+    /// Insert a binding, replacing any existing one of the same name in place
+    fn insert(&mut self, name: String, value: Value, mutable: bool) {
+        if let Some(&slot) = self.index.get(&name) {
+            self.slots[slot] = Binding { name, value, mutable };
+        } else {
+            self.index.insert(name.clone(), self.slots.len());
+            self.slots.push(Binding { name, value, mutable });
+        }
+    }
+}
+
 /// Environment manages variable scopes
 ///
 /// Scopes are nested: inner scopes can shadow outer scopes.
 /// When a function is called, we push a new scope.
 /// When it returns, we pop the scope.
+///
+/// Each scope is an indexed frame, so identifiers carrying a [`ScopeSlot`]
+/// from [`crate::semantic::resolve_scopes`] skip the name search.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Environment {
     /// Stack of scopes (innermost scope is last)
-    scopes: Vec<BTreeMap<String, Binding>>,
+    scopes: Vec<Frame>,
 }
 
 impl Default for Environment {
@@ -337,13 +728,13 @@ impl Environment {
     /// Create a new environment with one empty scope
     pub fn new() -> Self {
         Environment {
-            scopes: alloc::vec![BTreeMap::new()],
+            scopes: alloc::vec![Frame::default()],
         }
     }
 
     /// Push a new scope (for function calls, blocks)
     pub fn push_scope(&mut self) {
-        self.scopes.push(BTreeMap::new());
+        self.scopes.push(Frame::default());
     }
 
     /// Pop the innermost scope
@@ -356,17 +747,29 @@ impl Environment {
     /// Define a new immutable binding
     pub fn define(&mut self, name: String, value: Value) {
         if let Some(scope) = self.scopes.last_mut() {
-            scope.insert(name, Binding { value, mutable: false });
+            scope.insert(name, value, false);
         }
     }
 
     /// Define a new mutable binding
     pub fn define_mut(&mut self, name: String, value: Value) {
         if let Some(scope) = self.scopes.last_mut() {
-            scope.insert(name, Binding { value, mutable: true });
+            scope.insert(name, value, true);
         }
     }
 
+    /// Check whether a name is bound in any scope
+    pub fn contains(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.get(name).is_some())
+    }
+
+    /// Every binding in scope, outermost scope first
+    pub fn bindings(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.scopes
+            .iter()
+            .flat_map(|scope| scope.slots.iter().map(|binding| (binding.name.as_str(), &binding.value)))
+    }
+
     /// Get a variable's value (searches from innermost to outermost scope)
     pub fn get(&self, name: &str) -> Result<Value, RuntimeError> {
         for scope in self.scopes.iter().rev() {
@@ -377,6 +780,19 @@ impl Environment {
         Err(RuntimeError::UndefinedVariable(name.to_string()))
     }
 
+    /// Get a variable's value through its resolved slot
+    ///
+    /// [`crate::semantic::resolve_scopes`] resolves a name to the innermost
+    /// scope that can bind it, so no scope inside the slot's can shadow it.
+    /// Falls back to [`Environment::get`] when the slot does not (yet) hold
+    /// `name`.
+    pub fn get_resolved(&self, name: &str, slot: ScopeSlot) -> Result<Value, RuntimeError> {
+        match self.resolved_binding(name, slot) {
+            Some(binding) => Ok(binding.value.clone()),
+            None => self.get(name),
+        }
+    }
+
     /// Set a variable's value (must be mutable)
     pub fn set(&mut self, name: &str, value: Value) -> Result<(), RuntimeError> {
         for scope in self.scopes.iter_mut().rev() {
@@ -390,6 +806,26 @@ impl Environment {
         }
         Err(RuntimeError::UndefinedVariable(name.to_string()))
     }
+
+    /// Set a variable's value through its resolved slot (must be mutable)
+    pub fn set_resolved(&mut self, name: &str, slot: ScopeSlot, value: Value) -> Result<(), RuntimeError> {
+        let frame = match self.scopes.len().checked_sub(slot.depth + 1) {
+            Some(frame) if self.scopes[frame].slot(name, slot.slot).is_some() => frame,
+            _ => return self.set(name, value),
+        };
+        let binding = &mut self.scopes[frame].slots[slot.slot];
+        if !binding.mutable {
+            return Err(RuntimeError::ImmutableBinding(name.to_string()));
+        }
+        binding.value = value;
+        Ok(())
+    }
+
+    /// Binding a resolved slot refers to, if it holds `name`
+    fn resolved_binding(&self, name: &str, slot: ScopeSlot) -> Option<&Binding> {
+        let frame = self.scopes.len().checked_sub(slot.depth + 1)?;
+        self.scopes[frame].slot(name, slot.slot)
+    }
 }
 
 /// Trait definition information (runtime copy)
@@ -410,6 +846,9 @@ struct TraitDefinition {
 struct TraitImplKey {
     aspect_name: String,
     target_type: String,  // Normalized string representation
+    /// Normalized aspect type arguments, so `ConvertError<A>` and
+    /// `ConvertError<B>` for one type are separate implementations
+    type_args: Vec<String>,
 }
 
 /// Trait implementation information (runtime)
@@ -425,8 +864,29 @@ struct TraitImplementation {
     target_type: TypeAnnotation,
     methods: BTreeMap<String, Vec<AstNode>>,  // method_name -> function body
     method_params: BTreeMap<String, Vec<Parameter>>,  // method_name -> parameters
+    method_return_types: BTreeMap<String, Option<TypeAnnotation>>,  // method_name -> return type
 }
 
+/// Default limit on how deeply node evaluation may nest.
+///
+/// Expressions are evaluated on a heap-allocated work stack, so this bounds
+/// statement nesting and call depth, which recurse on the host stack. A level
+/// takes at most about 6 KiB of host stack in a debug build (nested loops and
+/// recursion through loop bodies cost the most) and 2.5 KiB in a release
+/// build, so the default stays within 1.5 MiB and fits the 2 MiB stack of a
+/// spawned thread. Raise it with [`Evaluator::set_max_depth`] on larger
+/// stacks.
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+
+/// Default limit on how deeply chant calls may nest.
+///
+/// A recursive call nests up to three levels of evaluation, so recursion
+/// this deep stays within [`DEFAULT_MAX_DEPTH`] and fails with
+/// `RuntimeError::StackOverflow` first. A call takes about 8 KiB of host
+/// stack in a debug build, or 11 KiB when it recurses through a loop body,
+/// and 3 to 5 KiB in a release build, so the default stays within 1 MiB.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 80;
+
 /// Evaluator executes Glimmer-Weave programs
 pub struct Evaluator {
     environment: Environment,
@@ -434,15 +894,194 @@ pub struct Evaluator {
     trait_definitions: BTreeMap<String, TraitDefinition>,
     /// Trait implementations (embody statements)
     trait_implementations: BTreeMap<TraitImplKey, TraitImplementation>,
+    /// Declared return types of the chants being executed (innermost last)
+    return_types: Vec<Option<TypeAnnotation>>,
+    /// Current nesting depth of recursive node evaluation
+    depth: usize,
+    /// Maximum nesting depth before evaluation fails with `DepthLimitExceeded`
+    max_depth: usize,
+    /// Number of chant calls currently running (tail calls reuse theirs)
+    call_depth: usize,
+    /// Maximum call depth before a call fails with `StackOverflow`
+    max_call_depth: usize,
 
     // === Module System (Phase 4) ===
     /// Module resolver for loading external modules
-    module_resolver: Option<crate::module_resolver::ModuleResolver>,
+    module_resolver: Option<crate::sync::Shared<crate::module_resolver::ModuleResolver>>,
     /// Module-level environments (module_name -> environment)
     module_environments: BTreeMap<String, Environment>,
     /// Imported modules tracking (effective_name -> items)
     /// None = import all, Some(list) = import specific items
     imported_modules: BTreeMap<String, Option<Vec<String>>>,
+    /// Namespaced builtin modules from the prelude (module -> member -> function)
+    builtin_modules: BTreeMap<String, BTreeMap<String, Value>>,
+    /// Scheduler for tasks started with `spawn`
+    scheduler: Box<dyn crate::scheduler::Scheduler>,
+    /// Unfinished tasks of each open `together` scope, innermost last
+    together: Vec<BTreeSet<crate::scheduler::TaskId>>,
+    /// Time source checked against `deadline` at safepoints
+    clock: Option<Box<dyn crate::clock::Clock>>,
+    /// Clock reading at which evaluation aborts with `RuntimeError::Timeout`
+    deadline: Option<u64>,
+    /// Heartbeats the script promised with `watchdog(interval)`
+    watchdog: crate::watchdog::Watchdog,
+    /// Told when a heartbeat is overdue; without one the script is cancelled
+    watchdog_host: Option<Box<dyn crate::watchdog::WatchdogHost>>,
+    /// Token the host trips to stop evaluation with `RuntimeError::Cancelled`
+    cancellation: Option<crate::cancellation::CancellationToken>,
+    /// Deferred cleanups of the running chants and the program (innermost last)
+    defer_frames: Vec<Vec<Deferred>>,
+    /// Number of `defer` bodies currently running; cancellation waits for them
+    cleanup_depth: usize,
+    /// Host handles adopted from native results
+    resources: crate::resource::ResourceTable,
+    /// Closes host handles on release
+    resource_host: Option<Box<dyn crate::resource::ResourceHost>>,
+    /// Opens, reads and closes the sources behind stream iterators
+    stream_host: Option<Box<dyn crate::stream::StreamHost>>,
+    /// Opens the connections behind `Net.tcp_connect` and keeps their allowlist
+    net: Option<Box<dyn crate::net::NetProvider>>,
+    /// Endpoints granted by `request Net.connect(host, port)`, which
+    /// `http_get` and `http_post` may reach
+    net_grants: Vec<(String, u16)>,
+    /// Receives what `print` and `println` write
+    output: Option<Box<dyn crate::output::OutputSink>>,
+    /// Moves the cursor, colors and clears for the `Term` module
+    terminal: Option<Box<dyn crate::term::Terminal>>,
+    /// Back buffer and screen of the `Draw` module
+    framebuffer: Option<crate::draw::DoubleBuffer>,
+    /// Plays what the `Sound` module asks for
+    speaker: Option<Box<dyn crate::sound::Speaker>>,
+    /// Shows what `with_progress` tasks report
+    progress_sink: Option<Box<dyn crate::progress::ProgressSink>>,
+    /// Totals of the running `with_progress` tasks, by task number
+    progress_tasks: BTreeMap<u64, f64>,
+    /// Number of the last `with_progress` task started
+    last_progress_task: u64,
+    /// Localized templates `tr` looks keys up in
+    messages: crate::i18n::MessageCatalogs,
+    /// Backs `store_get`, `store_set` and `store_delete`
+    storage: Box<dyn crate::storage::StorageProvider>,
+    /// Chants registered by `migrate store`
+    store_migrations: crate::storage::Migrations<Value>,
+    /// Namespaces already brought up to date in `storage`
+    store_migrated: BTreeSet<String>,
+    /// Bus `publish` and `subscribe` go through, when spawned from a session
+    bus: Option<crate::sync::Shared<crate::bus::MessageBus>>,
+    /// Messages published to this evaluator's subscriptions, awaiting the pump
+    inbox: crate::sync::Shared<crate::bus::Inbox>,
+    /// Handlers registered with `subscribe`: topic, handler and priority
+    bus_handlers: Vec<(String, Value, i64)>,
+    /// Budgets and starvation limit of the event pump
+    pump_policy: crate::bus::PumpPolicy,
+    /// What the event pump has delivered so far
+    pump_stats: crate::bus::PumpStats,
+    /// Every capability request, grant, denial and use so far
+    capability_audit: crate::capability::CapabilityAudit,
+    /// Decides capability requests; `None` grants everything
+    capability_policy: Option<Box<dyn crate::capability::CapabilityPolicy>>,
+    /// Names of the chants being called (innermost last), for the audit log
+    chant_names: Vec<String>,
+    /// Sources, sinks and sanitizers of tainted data; `None` tracks nothing
+    taint_policy: Option<crate::taint::TaintPolicy>,
+    /// Tainted data that reached a sink, in flagging mode
+    taint_violations: Vec<crate::taint::TaintViolation>,
+    /// Sources of the tainted arguments of the native chants being called,
+    /// so sinks their callbacks reach count as tainted
+    taint_context: Vec<String>,
+    /// Whether top-level evaluations record a leak report
+    leak_detection: bool,
+    /// Heap usage around the last top-level evaluation, with leak detection on
+    leak_report: Option<crate::leak_check::LeakReport>,
+    /// Call counts and branch outcomes, while profiling is on
+    profile: Option<crate::profile::Profile>,
+    /// Finished statements and the bindings they changed, while recording
+    recording: Option<crate::time_travel::Recording>,
+    /// Status passed to `exit` by the last top-level evaluation
+    exit_status: Option<i32>,
+    /// Whether nondeterministic capabilities are denied (see `set_deterministic`)
+    deterministic: bool,
+    /// Whether failing top-level evaluations leave a crash dump
+    crash_dumps: bool,
+    /// Report of the last failed top-level evaluation, or of the error
+    /// unwinding now
+    crash_dump: Option<crate::crash_dump::CrashDump>,
+    /// Error the crash dump is tracing, and the chant depth of its last frame
+    unwinding: Option<(RuntimeError, usize)>,
+}
+
+/// Bindings a script can see, the innermost of shadowed names, leaving out
+/// builtins and internal names
+pub(crate) fn visible_bindings(environment: &Environment) -> BTreeMap<&str, &Value> {
+    environment
+        .bindings()
+        .filter(|(name, value)| !name.starts_with("__") && !matches!(value, Value::NativeChant(_)))
+        .collect()
+}
+
+/// Source position of a called chant's name, for the audit log
+fn callee_span(callee_node: &AstNode) -> SourceSpan {
+    match callee_node {
+        AstNode::Ident { span, .. } | AstNode::FieldAccess { span, .. } => span.clone(),
+        _ => SourceSpan::unknown(),
+    }
+}
+
+/// Options map of `http_get` and `http_post`: `headers`, `timeout` and
+/// `redirects`
+fn http_options(value: &Value) -> Result<crate::http::HttpOptions, RuntimeError> {
+    let mistyped = |expected: &str, got: &Value| RuntimeError::TypeError {
+        expected: expected.to_string(),
+        got: got.type_name().to_string(),
+    };
+    let Value::Map(entries) = value else {
+        return Err(mistyped("options Map", value));
+    };
+    let mut options = crate::http::HttpOptions::default();
+    for (key, value) in entries {
+        match (key.as_str(), value) {
+            ("headers", Value::Map(headers)) => {
+                for (name, value) in headers {
+                    let Value::Text(value) = value else {
+                        return Err(mistyped("Text header value", value));
+                    };
+                    crate::http::check_header(name, value).map_err(RuntimeError::Custom)?;
+                    options.headers.push((name.clone(), value.clone()));
+                }
+            }
+            ("timeout", Value::Number(millis)) if *millis >= 0.0 => options.timeout = Some(*millis as u64),
+            ("redirects", Value::Number(count)) if *count >= 0.0 => options.redirects = *count as usize,
+            ("headers", other) => return Err(mistyped("headers Map", other)),
+            ("timeout" | "redirects", other) => return Err(mistyped("non-negative Number", other)),
+            (unknown, _) => return Err(RuntimeError::Custom(format!("Unknown HTTP option '{}'", unknown))),
+        }
+    }
+    Ok(options)
+}
+
+/// Id of the resource a value carries out of a chant: the resource itself,
+/// or the stream an iterator reads from
+fn carried_resource(value: &Value) -> Option<crate::resource::ResourceId> {
+    match value {
+        Value::Resource { id, .. } => Some(*id),
+        Value::Iterator { state, .. } => match state.as_ref() {
+            IteratorState::Stream { source, .. } => carried_resource(source),
+            IteratorState::Map { inner, .. } | IteratorState::Filter { inner, .. } | IteratorState::Take { inner, .. } => {
+                carried_resource(inner)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Cleanup registered with a defer frame
+#[derive(Debug)]
+enum Deferred {
+    /// A `defer` block
+    Block(Vec<AstNode>),
+    /// Automatic release of a resource acquired in the frame
+    Release(Value),
 }
 
 impl Default for Evaluator {
@@ -452,25 +1091,93 @@ impl Default for Evaluator {
 }
 
 impl Evaluator {
-    /// Create a new evaluator with empty environment
+    /// Create a new evaluator with the default prelude in scope
     pub fn new() -> Self {
+        Self::with_prelude(&crate::script_prelude::Prelude::default())
+    }
+
+    /// Create an evaluator whose global scope holds the builtins of `prelude`
+    pub fn with_prelude(prelude: &crate::script_prelude::Prelude) -> Self {
         let mut evaluator = Evaluator {
             environment: Environment::new(),
             trait_definitions: BTreeMap::new(),
             trait_implementations: BTreeMap::new(),
+            return_types: Vec::new(),
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            call_depth: 0,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
             module_resolver: None,
             module_environments: BTreeMap::new(),
             imported_modules: BTreeMap::new(),
+            builtin_modules: BTreeMap::new(),
+            scheduler: Box::new(crate::scheduler::CooperativeScheduler::new()),
+            together: Vec::new(),
+            clock: crate::clock::default_clock(),
+            deadline: None,
+            watchdog: crate::watchdog::Watchdog::new(),
+            watchdog_host: None,
+            cancellation: None,
+            defer_frames: Vec::new(),
+            cleanup_depth: 0,
+            resources: crate::resource::ResourceTable::new(),
+            resource_host: None,
+            stream_host: crate::stream::default_streams(),
+            net: crate::net::default_net(),
+            net_grants: Vec::new(),
+            output: None,
+            terminal: crate::term::default_terminal(),
+            framebuffer: None,
+            speaker: crate::sound::default_speaker(),
+            progress_sink: None,
+            progress_tasks: BTreeMap::new(),
+            last_progress_task: 0,
+            messages: crate::i18n::MessageCatalogs::default(),
+            storage: Box::new(crate::storage::MemoryStorage::new()),
+            store_migrations: crate::storage::Migrations::new(),
+            store_migrated: BTreeSet::new(),
+            bus: None,
+            inbox: crate::sync::shared(crate::bus::Inbox::new()),
+            bus_handlers: Vec::new(),
+            pump_policy: crate::bus::PumpPolicy::new(),
+            pump_stats: crate::bus::PumpStats::default(),
+            capability_audit: crate::capability::CapabilityAudit::new(),
+            capability_policy: None,
+            chant_names: Vec::new(),
+            taint_policy: None,
+            taint_violations: Vec::new(),
+            taint_context: Vec::new(),
+            leak_detection: false,
+            leak_report: None,
+            exit_status: None,
+            profile: None,
+            recording: None,
+            deterministic: false,
+            crash_dumps: false,
+            crash_dump: None,
+            unwinding: None,
         };
 
-        // Register builtin runtime library functions
-        for builtin in crate::runtime::get_builtins() {
+        // Register the prelude's builtin runtime library functions
+        for builtin in prelude.natives() {
             evaluator.environment.define(
                 builtin.name.clone(),
                 Value::NativeChant(builtin),
             );
         }
 
+        for (module, members) in prelude.modules() {
+            let members = members
+                .into_iter()
+                .map(|(member, builtin)| (member.to_string(), Value::NativeChant(builtin)))
+                .collect();
+            evaluator.builtin_modules.insert(module.to_string(), members);
+        }
+
+        // Builtins stay in the outermost frame; top-level bindings start a
+        // fresh one so their resolved slots do not depend on the builtin set
+        evaluator.environment.push_scope();
+
         evaluator
     }
 
@@ -479,6 +1186,30 @@ impl Evaluator {
         &self.environment
     }
 
+    /// Bind a host value, such as a native chant or a resource, as a global
+    pub fn define_global(&mut self, name: &str, value: Value) {
+        self.environment.define(name.to_string(), value);
+    }
+
+    /// Evaluate a program with its command-line arguments bound to the list
+    /// `arguments`
+    ///
+    /// The arguments follow the program name, as `argv[1..]` does for a
+    /// compiled program.
+    pub fn eval_with_args(&mut self, nodes: &[AstNode], args: Vec<Value>) -> Result<Value, RuntimeError> {
+        self.define_global("arguments", Value::List(args));
+        self.eval(nodes)
+    }
+
+    /// Status the last top-level evaluation passed to `exit`, or `None` if
+    /// it ran to the end
+    ///
+    /// A program that calls `exit` evaluates to `nothing` once its deferred
+    /// cleanups have run.
+    pub fn exit_status(&self) -> Option<i32> {
+        self.exit_status
+    }
+
     /// Set the module resolver for loading external modules
     ///
     /// This must be called before evaluating code that uses imports.
@@ -486,1226 +1217,3818 @@ impl Evaluator {
     /// # Arguments
     /// * `resolver` - The module resolver to use
     pub fn set_module_resolver(&mut self, resolver: crate::module_resolver::ModuleResolver) {
-        self.module_resolver = Some(resolver);
+        self.module_resolver = Some(crate::sync::shared(resolver));
     }
 
-    /// Evaluate a list of statements (program or block)
-    pub fn eval(&mut self, nodes: &[AstNode]) -> Result<Value, RuntimeError> {
-        let mut result = Value::Nothing;
-        for node in nodes {
-            result = self.eval_node(node)?;
-        }
-        Ok(result)
+    /// Load modules through a resolver shared with other evaluators (see
+    /// [`Session`](crate::session::Session))
+    pub(crate) fn share_module_resolver(&mut self, resolver: crate::sync::Shared<crate::module_resolver::ModuleResolver>) {
+        self.module_resolver = Some(resolver);
     }
 
-    /// Evaluate using the bytecode VM (Quicksilver fast path)
+    /// Set the maximum evaluation nesting depth
     ///
-    /// This provides 5-10x performance improvement for pure expressions
-    /// that don't require stateful environment access.
+    /// Evaluation that nests deeper (for example, deep non-tail recursion)
+    /// fails with `RuntimeError::DepthLimitExceeded` instead of overflowing
+    /// the host stack. The default fits a 2 MiB stack; raise the limit on a
+    /// larger one, or lower it on a smaller one.
     ///
-    /// **Limitations:**
-    /// - Does not support local variables (only globals)
-    /// - Does not support function calls (chants)
-    /// - Does not support capability requests
+    /// # Arguments
+    /// * `max_depth` - The maximum nesting depth
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    /// Get the maximum evaluation nesting depth
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Set how deeply chant calls may nest
     ///
-    /// **Use cases:**
-    /// - Arithmetic expressions
-    /// - Comparisons
-    /// - Simple data structures (lists, maps)
-    /// - Global variable access
+    /// A call beyond the limit fails with `RuntimeError::StackOverflow`,
+    /// which scripts can catch with `harmonize on StackOverflow`. Tail calls
+    /// do not add to the depth. Calls also nest evaluation, so raising this
+    /// past about a third of [`max_depth`](Self::max_depth) needs a higher
+    /// evaluation limit too.
     ///
-    /// # Returns
-    /// * `Ok(Value)` - Result of execution
-    /// * `Err(RuntimeError)` - If compilation or execution fails
-    pub fn eval_with_vm(&mut self, nodes: &[AstNode]) -> Result<Value, RuntimeError> {
-        use crate::bytecode_compiler::compile;
-        use crate::vm::{VM, VmError};
-
-        // Compile AST to bytecode
-        let chunk = compile(nodes).map_err(|e| RuntimeError::CompileError {
-            message: alloc::format!("{:?}", e),
-        })?;
+    /// # Arguments
+    /// * `max_call_depth` - The maximum number of nested chant calls
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
 
-        // Execute in VM
-        let mut vm = VM::new();
-        let result = vm.execute(chunk).map_err(|e| match e {
-            VmError::TypeError(msg) => RuntimeError::TypeError {
-                expected: "compatible type".to_string(),
-                got: msg,
-            },
-            VmError::UndefinedVariable(name) => RuntimeError::UndefinedVariable(name),
-            VmError::DivisionByZero => RuntimeError::DivisionByZero,
-            VmError::OutOfBounds => RuntimeError::IndexOutOfBounds {
-                index: 0,
-                length: 0,
-            },
-            _ => RuntimeError::CompileError {
-                message: alloc::format!("{:?}", e),
-            },
-        })?;
+    /// Get the maximum chant call depth
+    pub fn max_call_depth(&self) -> usize {
+        self.max_call_depth
+    }
 
-        Ok(result)
+    /// Install the clock that deadlines are measured against
+    pub fn set_clock(&mut self, clock: Box<dyn crate::clock::Clock>) {
+        self.clock = Some(clock);
     }
 
-    /// Call a function value with the given arguments.
-    ///
-    /// Handles three types of callable values:
-    /// - `Value::Chant`: User-defined functions with tail-call optimization
-    /// - `Value::NativeChant`: Built-in native functions
-    /// - `Value::VariantConstructor`: Enum variant constructors
+    /// Make every run of the same script on the same inputs produce the
+    /// same result, e.g. for build scripts
     ///
-    /// # Arguments
-    /// * `func` - The function value to call
-    /// * `args` - The arguments to pass (already evaluated)
-    /// * `callee_node` - The AST node of the callee (for tail-call detection)
-    /// * `type_args` - Type arguments for generic functions
+    /// A deterministic evaluator denies requests for the capabilities
+    /// [`is_nondeterministic`](crate::capability::is_nondeterministic) names
+    /// (time, randomness, files, console, network and storage) before the
+    /// policy is asked, and measures deadlines against a
+    /// [`StepClock`](crate::clock::StepClock), so a timeout hits at the same
+    /// safepoint every run. Maps already iterate in key order. Turning the
+    /// mode off goes back to the default clock.
+    pub fn set_deterministic(&mut self, enabled: bool) {
+        self.deterministic = enabled;
+        self.clock = if enabled {
+            Some(Box::new(crate::clock::StepClock::new()))
+        } else {
+            crate::clock::default_clock()
+        };
+    }
+
+    /// Whether the evaluator runs in deterministic mode
+    pub fn is_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// Evaluate statements, aborting with `RuntimeError::Timeout` once the
+    /// clock reaches `deadline`
     ///
-    /// # Returns
-    /// * `Ok(Value)` - The result of the function call
-    /// * `Err(RuntimeError)` - If the call fails (arity mismatch, not callable, etc.)
+    /// The clock is read at safepoints (loop iterations and calls), so a
+    /// script stops at the first safepoint past the deadline. The deadline is
+    /// in the clock's units; under std the default clock counts milliseconds.
+    pub fn eval_with_deadline(&mut self, nodes: &[AstNode], deadline: u64) -> Result<Value, RuntimeError> {
+        if self.clock.is_none() {
+            return Err(RuntimeError::Custom("eval_with_deadline: No clock installed".to_string()));
+        }
+
+        let outer = self.deadline.replace(deadline);
+        let result = self.eval(nodes);
+        self.deadline = outer;
+        result
+    }
+
+    /// Install the token the host trips to cancel evaluation
+    pub fn set_cancellation_token(&mut self, token: crate::cancellation::CancellationToken) {
+        self.cancellation = Some(token);
+    }
+
+    /// Decide what happens when a heartbeat promised with `watchdog` is
+    /// overdue (by default the script is cancelled)
+    pub fn set_watchdog_host(&mut self, host: Box<dyn crate::watchdog::WatchdogHost>) {
+        self.watchdog_host = Some(host);
+    }
+
+    /// Heartbeat state of the script
+    pub fn watchdog(&self) -> &crate::watchdog::Watchdog {
+        &self.watchdog
+    }
+
+    /// Check the deadline, the watchdog and the cancellation token; called
+    /// at loop back-edges and calls
+    fn safepoint(&mut self) -> Result<(), RuntimeError> {
+        if let (Some(deadline), Some(clock)) = (self.deadline, &self.clock) {
+            if clock.now() >= deadline {
+                return Err(RuntimeError::Timeout);
+            }
+        }
+        if let (Some(_), Some(clock)) = (self.watchdog.interval(), &self.clock) {
+            if let Some(missed) = self.watchdog.check(clock.now()) {
+                let action = match self.watchdog_host.as_mut() {
+                    Some(host) => host.missed(&missed),
+                    None => crate::watchdog::WatchdogAction::Cancel,
+                };
+                if action == crate::watchdog::WatchdogAction::Cancel && self.cleanup_depth == 0 {
+                    self.watchdog.disarm();
+                    return Err(RuntimeError::Cancelled);
+                }
+            }
+        }
+        // `defer` bodies run to completion while a cancellation unwinds
+        if self.cleanup_depth == 0 && self.cancellation.as_ref().is_some_and(|token| token.is_cancelled()) {
+            return Err(RuntimeError::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Run `body` with its own `defer` frame, then the deferred bodies in
+    /// reverse order, whether `body` succeeded or not
+    ///
+    /// Resources acquired in the frame are released along with the `defer`
+    /// bodies, except one that `body` returns, which moves to the caller.
+    ///
+    /// An error from a deferred body replaces a successful result but never
+    /// hides the error that started the unwinding.
+    fn with_defer_frame<F>(&mut self, body: F) -> Result<Value, RuntimeError>
+    where
+        F: FnOnce(&mut Self) -> Result<Value, RuntimeError>,
+    {
+        self.defer_frames.push(Vec::new());
+        let result = body(self);
+        self.close_defer_frame(result)
+    }
+
+    /// Pop the innermost defer frame and run its cleanups
+    ///
+    /// Kept out of `with_defer_frame`, whose frame stays live across
+    /// nested chant calls.
+    #[inline(never)]
+    fn close_defer_frame(&mut self, mut result: Result<Value, RuntimeError>) -> Result<Value, RuntimeError> {
+        let mut deferred = self.defer_frames.pop().unwrap_or_default();
+
+        // A resource yielded from a chant, alone or under a stream
+        // iterator, now belongs to the caller
+        let returned = match &result {
+            Ok(value) | Err(RuntimeError::Return(value)) => carried_resource(value),
+            _ => None,
+        };
+        if let (Some(id), Some(caller)) = (returned, self.defer_frames.last_mut()) {
+            if let Some(index) = deferred
+                .iter()
+                .position(|cleanup| matches!(cleanup, Deferred::Release(Value::Resource { id: owned, .. }) if *owned == id))
+            {
+                caller.push(deferred.remove(index));
+            }
+        }
+
+        self.cleanup_depth += 1;
+        for cleanup in deferred.iter().rev() {
+            let outcome = match cleanup {
+                Deferred::Block(body) => {
+                    self.environment.push_scope();
+                    let outcome = self.eval_statements(body);
+                    self.environment.pop_scope();
+                    outcome
+                }
+                Deferred::Release(Value::Resource { id, .. }) if !self.resources.is_live(*id) => Ok(Value::Nothing),
+                Deferred::Release(resource) => self.release_resource(resource),
+            };
+            if let Err(error) = outcome {
+                if result.is_ok() {
+                    result = Err(error);
+                }
+            }
+        }
+        self.cleanup_depth -= 1;
+
+        result
+    }
+
+    /// Register a `defer` body with the innermost frame
+    fn eval_defer(&mut self, body: &[AstNode]) -> Result<Value, RuntimeError> {
+        match self.defer_frames.last_mut() {
+            Some(frame) => frame.push(Deferred::Block(body.to_vec())),
+            // Only reachable for statements run outside `eval`
+            None => return self.with_defer_frame(|this| this.eval_defer(body)),
+        }
+        Ok(Value::Nothing)
+    }
+
+    /// Install the host hook that closes handles on release
+    pub fn set_resource_host(&mut self, host: Box<dyn crate::resource::ResourceHost>) {
+        self.resource_host = Some(host);
+    }
+
+    /// Install the host that backs `fs_lines`, `fs_bytes` and
+    /// `console_read_lines`
+    pub fn set_stream_host(&mut self, host: Box<dyn crate::stream::StreamHost>) {
+        self.stream_host = Some(host);
+    }
+
+    /// Install the provider that backs the `Net` module and decides which
+    /// endpoints `request Net.connect(host, port)` may name
+    pub fn set_net_provider(&mut self, provider: Box<dyn crate::net::NetProvider>) {
+        self.net = Some(provider);
+    }
+
+    /// Control the screen and read keys for the `Term` module through
+    /// `terminal`
+    pub fn set_terminal(&mut self, terminal: Box<dyn crate::term::Terminal>) {
+        self.terminal = Some(terminal);
+    }
+
+    /// Draw for the `Draw` module through `provider`, on a fresh back buffer
+    pub fn set_framebuffer(&mut self, provider: Box<dyn crate::draw::FramebufferProvider>) {
+        self.framebuffer = Some(crate::draw::DoubleBuffer::new(provider));
+    }
+
+    /// The installed framebuffer, with the frame being drawn
+    pub fn framebuffer(&self) -> Option<&crate::draw::DoubleBuffer> {
+        self.framebuffer.as_ref()
+    }
+
+    /// Play the `Sound` module's tones through `speaker`
+    pub fn set_speaker(&mut self, speaker: Box<dyn crate::sound::Speaker>) {
+        self.speaker = Some(speaker);
+    }
+
+    /// Report the progress of `with_progress` tasks to `sink`
+    pub fn set_progress_sink(&mut self, sink: Box<dyn crate::progress::ProgressSink>) {
+        self.progress_sink = Some(sink);
+    }
+
+    /// Translate `tr` keys with `messages`, replacing any catalogs loaded
+    /// so far
+    pub fn set_messages(&mut self, messages: crate::i18n::MessageCatalogs) {
+        self.messages = messages;
+    }
+
+    /// Catalogs `tr` translates with, including those scripts loaded
+    pub fn messages(&self) -> &crate::i18n::MessageCatalogs {
+        &self.messages
+    }
+
+    /// Send what `print` and `println` write to `sink`
+    pub fn set_output_sink(&mut self, sink: Box<dyn crate::output::OutputSink>) {
+        self.output = Some(sink);
+    }
+
+    /// Install the provider that backs `store_get`, `store_set` and
+    /// `store_delete`, replacing the evaluator's in-memory storage
+    pub fn set_storage_provider(&mut self, provider: Box<dyn crate::storage::StorageProvider>) {
+        self.storage = provider;
+        self.store_migrated.clear();
+    }
+
+    /// Publish and subscribe through a bus shared with other evaluators
+    /// (see [`Session`](crate::session::Session))
+    pub(crate) fn join_bus(&mut self, bus: crate::sync::Shared<crate::bus::MessageBus>) {
+        self.bus = Some(bus);
+    }
+
+    /// Hand a host handle to scripts, e.g. to bind it as a global
+    ///
+    /// Resources acquired this way are not tied to any chant; scripts (or
+    /// the host, through `release_resource`) must release them.
+    pub fn acquire_resource(&mut self, kind: &str, handle: u64) -> Value {
+        let id = self.resources.adopt(kind, handle);
+        Value::Resource { id, kind: kind.to_string(), handle }
+    }
+
+    /// Release a resource and close its host handle
+    pub fn release_resource(&mut self, resource: &Value) -> Result<Value, RuntimeError> {
+        let Value::Resource { id, kind, handle } = resource else {
+            return Err(RuntimeError::TypeError {
+                expected: "Resource".to_string(),
+                got: resource.type_name().to_string(),
+            });
+        };
+        let (kind, handle) = self.resources.release(*id).map_err(|_| RuntimeError::DoubleRelease {
+            kind: kind.clone(),
+            handle: *handle,
+        })?;
+        let closed = if kind == crate::stream::STREAM_RESOURCE {
+            self.stream_host.as_mut().map(|host| host.close(handle))
+        } else if kind == crate::net::SOCKET_RESOURCE {
+            self.net.as_mut().map(|net| net.close(handle))
+        } else if kind == crate::progress::PROGRESS_RESOURCE {
+            // Nothing to close; `with_progress` finishes the task
+            None
+        } else {
+            self.resource_host.as_mut().map(|host| host.release(&kind, handle))
+        };
+        if let Some(Err(message)) = closed {
+            return Err(RuntimeError::Custom(format!("release: {}", message)));
+        }
+        Ok(Value::Nothing)
+    }
+
+    /// Number of adopted resources that have not been released
+    pub fn live_resources(&self) -> usize {
+        self.resources.live_count()
+    }
+
+    /// Record a [`LeakReport`](crate::leak_check::LeakReport) around every
+    /// top-level evaluation
+    pub fn set_leak_detection(&mut self, enabled: bool) {
+        self.leak_detection = enabled;
+        if !enabled {
+            self.leak_report = None;
+        }
+    }
+
+    /// Heap usage around the last top-level evaluation, if leak detection is on
+    pub fn leak_report(&self) -> Option<&crate::leak_check::LeakReport> {
+        self.leak_report.as_ref()
+    }
+
+    /// Count chant calls and `should` outcomes into a
+    /// [`Profile`](crate::profile::Profile) from now on, keeping any counts
+    /// gathered so far
+    pub fn enable_profiling(&mut self) {
+        if self.profile.is_none() {
+            self.profile = Some(crate::profile::Profile::new());
+        }
+    }
+
+    /// Counts gathered since profiling was enabled
+    pub fn profile(&self) -> Option<&crate::profile::Profile> {
+        self.profile.as_ref()
+    }
+
+    /// Stop profiling and return the counts gathered
+    pub fn take_profile(&mut self) -> Option<crate::profile::Profile> {
+        self.profile.take()
+    }
+
+    /// Record every statement finished from now on into `recording`, for
+    /// stepping back through it with a
+    /// [`Debugger`](crate::time_travel::Debugger)
+    pub fn start_recording(&mut self, mut recording: crate::time_travel::Recording) {
+        recording.begin(visible_bindings(&self.environment));
+        self.recording = Some(recording);
+    }
+
+    /// Steps recorded so far
+    pub fn recording(&self) -> Option<&crate::time_travel::Recording> {
+        self.recording.as_ref()
+    }
+
+    /// Stop recording and return the steps recorded
+    pub fn take_recording(&mut self) -> Option<crate::time_travel::Recording> {
+        self.recording.take()
+    }
+
+    /// Leave a [`CrashDump`](crate::crash_dump::CrashDump) behind when a
+    /// top-level evaluation fails with an uncaught error
+    pub fn set_crash_dumps(&mut self, enabled: bool) {
+        self.crash_dumps = enabled;
+    }
+
+    /// Report of the last top-level evaluation, if it failed with crash
+    /// dumps on
+    pub fn crash_dump(&self) -> Option<&crate::crash_dump::CrashDump> {
+        self.crash_dump.as_ref().filter(|_| self.unwinding.is_none())
+    }
+
+    /// Add the statement `node` to the crash dump of `error`, which it
+    /// failed with: the first statement to fail starts a dump, and the
+    /// statement each enclosing chant was running adds a frame
+    fn trace_crash(&mut self, node: &AstNode, error: &RuntimeError) {
+        use crate::crash_dump::{CrashBinding, CrashDump, CrashFrame, CrashLocation};
+
+        if matches!(
+            error,
+            RuntimeError::Return(_)
+                | RuntimeError::TailCall { .. }
+                | RuntimeError::Exit(_)
+                | RuntimeError::BreakOutsideLoop
+                | RuntimeError::ContinueOutsideLoop
+        ) {
+            return;
+        }
+        let depth = self.chant_names.len();
+        let frame = CrashFrame { chant: self.chant_names.last().cloned(), span: node.span().clone() };
+        match &mut self.unwinding {
+            // Errors only unwind outward: a deeper one, or another error,
+            // was raised after the last was handled
+            Some((traced, last)) if traced == error && depth <= *last => {
+                if depth < *last {
+                    *last = depth;
+                    if let Some(dump) = self.crash_dump.as_mut() {
+                        dump.backtrace.push(frame);
+                    }
+                }
+            }
+            _ => {
+                let message = match error.error_value() {
+                    Value::Text(message) => message,
+                    other => crate::heap_graph::summary(&other),
+                };
+                let mut dump = CrashDump::new(error.error_type(), &message, CrashLocation::Source(frame.span.clone()));
+                dump.backtrace.push(frame);
+                dump.environment = visible_bindings(&self.environment)
+                    .into_iter()
+                    .map(|(name, value)| CrashBinding::new(name, value))
+                    .collect();
+                self.crash_dump = Some(dump);
+                self.unwinding = Some((error.clone(), depth));
+            }
+        }
+    }
+
+    /// Finish or discard the crash dump once a top-level evaluation is over
+    fn finish_crash_dump(&mut self, result: &Result<Value, RuntimeError>) {
+        let unwinding = self.unwinding.take();
+        match (result, self.crash_dump.as_mut()) {
+            (Err(error), Some(dump)) if unwinding.is_some_and(|(traced, _)| traced == *error) => {
+                let entries = self.capability_audit.entries();
+                let tail = entries.len().saturating_sub(crate::crash_dump::DEFAULT_AUDIT_TAIL);
+                dump.audit_tail = entries[tail..].to_vec();
+            }
+            _ => self.crash_dump = None,
+        }
+    }
+
+    /// Add a step for a finished statement to the recording
+    fn record_step(&mut self, node: &AstNode, result: &Result<Value, RuntimeError>) {
+        let error = match result {
+            Err(RuntimeError::Return(_) | RuntimeError::TailCall { .. } | RuntimeError::Exit(_))
+            | Err(RuntimeError::BreakOutsideLoop | RuntimeError::ContinueOutsideLoop) => None,
+            Err(error) => Some(error.clone()),
+            Ok(_) => None,
+        };
+        let chant = self.chant_names.last().cloned();
+        if let Some(recording) = self.recording.as_mut() {
+            recording.record(node.kind(), node.span().clone(), chant, &visible_bindings(&self.environment), error);
+        }
+    }
+
+    /// Current heap usage of this evaluator
+    pub fn heap_snapshot(&self) -> crate::leak_check::HeapSnapshot {
+        let mut snapshot = crate::leak_check::HeapSnapshot {
+            native_bytes: crate::leak_check::native_heap_used(),
+            live_resources: self.resources.live_count(),
+            ..Default::default()
+        };
+        for (name, value) in self.environment.bindings() {
+            snapshot.retained_bytes += crate::leak_check::retained_bytes(value);
+            snapshot.globals.push(name.to_string());
+        }
+        snapshot
+    }
+
+    /// Graph of the values the script's bindings keep alive, for export
+    /// to Graphviz or JSON
+    pub fn heap_graph(&self) -> crate::heap_graph::HeapGraph {
+        crate::heap_graph::HeapGraph::capture(visible_bindings(&self.environment))
+    }
+
+    /// Adopt a resource fresh from a native and release it when the
+    /// current chant finishes
+    fn adopt_resource(&mut self, result: Value) -> Value {
+        match result {
+            Value::Resource { id: 0, kind, handle } => {
+                let resource = self.acquire_resource(&kind, handle);
+                if let Some(frame) = self.defer_frames.last_mut() {
+                    frame.push(Deferred::Release(resource.clone()));
+                }
+                resource
+            }
+            other => other,
+        }
+    }
+
+    /// Fail if any argument is a released resource
+    fn check_resources(&self, args: &[Value]) -> Result<(), RuntimeError> {
+        for arg in args {
+            if let Value::Resource { id, kind, handle } = arg {
+                if self.resources.check(*id).is_err() {
+                    return Err(RuntimeError::ResourceReleased { kind: kind.clone(), handle: *handle });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Install the policy that grants or denies capability requests
+    pub fn set_capability_policy(&mut self, policy: Box<dyn crate::capability::CapabilityPolicy>) {
+        self.capability_policy = Some(policy);
+    }
+
+    /// Capability events recorded so far
+    pub fn capability_audit(&self) -> &crate::capability::CapabilityAudit {
+        &self.capability_audit
+    }
+
+    /// Forget the recorded capability events
+    pub fn clear_capability_audit(&mut self) {
+        self.capability_audit.clear();
+    }
+
+    /// Track data from the policy's source capabilities and keep it from
+    /// its sinks (see [`crate::taint`])
+    ///
+    /// Values the script hands back may then carry taint marks;
+    /// [`Value::untainted`] removes them.
+    pub fn set_taint_policy(&mut self, policy: crate::taint::TaintPolicy) {
+        self.taint_policy = Some(policy);
+    }
+
+    /// Tainted data that reached a sink while the policy was flagging
+    pub fn taint_violations(&self) -> &[crate::taint::TaintViolation] {
+        &self.taint_violations
+    }
+
+    /// Append a capability event, attributed to the running chant
+    fn audit(&mut self, capability: &str, event: crate::capability::AuditEvent, span: SourceSpan) {
+        let chant = self.chant_names.last().cloned();
+        self.capability_audit.record(capability, event, chant, span);
+    }
+
+    /// Record a use of every capability token passed to the native `by`
+    fn audit_uses(&mut self, args: &[Value], by: &str, callee_node: &AstNode) {
+        for arg in args {
+            if let Value::Capability { resource, .. } = arg {
+                let event = crate::capability::AuditEvent::Used { by: by.to_string() };
+                self.audit(resource, event, callee_span(callee_node));
+            }
+        }
+    }
+
+    /// `capabilities()`: the audit log as a list of maps, for scripts
+    /// holding the audit capability
+    fn script_capabilities(&self) -> Result<Value, RuntimeError> {
+        use crate::capability::AUDIT_CAPABILITY;
+
+        if !self.capability_audit.is_granted(AUDIT_CAPABILITY) {
+            return Err(RuntimeError::CapabilityDenied {
+                capability: AUDIT_CAPABILITY.to_string(),
+                reason: format!("capabilities() requires `request {}`", AUDIT_CAPABILITY),
+            });
+        }
+        let entries = self.capability_audit.entries().iter().map(|entry| entry.to_value()).collect();
+        Ok(Value::List(entries))
+    }
+
+    /// Replace the scheduler that runs spawned tasks
+    ///
+    /// Hosts such as AethelOS install their own scheduler so script yields
+    /// and blocking waits go through the OS scheduler.
+    pub fn set_scheduler(&mut self, scheduler: Box<dyn crate::scheduler::Scheduler>) {
+        self.scheduler = scheduler;
+    }
+
+    /// Run spawned tasks and deliver bus messages until none is left
+    ///
+    /// This is the evaluator's event pump; hosts call it to let a script
+    /// that subscribed to topics handle what was published since.
+    pub fn run_until_idle(&mut self) -> Result<(), RuntimeError> {
+        let mut pump = crate::bus::Pump::default();
+        loop {
+            if let Some(task) = self.scheduler.next_ready() {
+                self.run_task(task)?;
+            } else if !self.deliver_message(&mut pump)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Limit what each run of the event pump delivers, and when a message
+    /// passed over for higher priority ones counts as starved
+    pub fn set_pump_policy(&mut self, policy: crate::bus::PumpPolicy) {
+        self.pump_policy = policy;
+    }
+
+    /// Messages the event pump has delivered, starved and left waiting
+    pub fn pump_stats(&self) -> &crate::bus::PumpStats {
+        &self.pump_stats
+    }
+
+    /// Call the handlers of the next message in the inbox, returning
+    /// whether there was one within the budgets of `pump`
+    fn deliver_message(&mut self, pump: &mut crate::bus::Pump) -> Result<bool, RuntimeError> {
+        let handlers = &self.bus_handlers;
+        let priority = |topic: &str| {
+            handlers.iter().filter(|(subscribed, _, _)| subscribed == topic).map(|(_, _, priority)| *priority).max().unwrap_or(0)
+        };
+        // Not borrowed across the handlers, which may publish to this inbox
+        let next = self.pump_policy.next(&mut crate::sync::lock(&self.inbox), pump, priority);
+        let message = match next {
+            crate::bus::Next::Deliver { message, starved } => {
+                *self.pump_stats.delivered.entry(message.topic.clone()).or_insert(0) += 1;
+                if starved {
+                    *self.pump_stats.starved.entry(message.topic.clone()).or_insert(0) += 1;
+                }
+                message
+            }
+            crate::bus::Next::Empty => return Ok(false),
+            crate::bus::Next::OverBudget => {
+                self.pump_stats.over_budget += 1;
+                return Ok(false);
+            }
+        };
+        let mut handlers: Vec<(Value, i64)> = self
+            .bus_handlers
+            .iter()
+            .filter(|(topic, _, _)| *topic == message.topic)
+            .map(|(_, handler, priority)| (handler.clone(), *priority))
+            .collect();
+        handlers.sort_by_key(|(_, priority)| core::cmp::Reverse(*priority));
+        let callee = AstNode::Nothing { span: SourceSpan::unknown() };
+        for (handler, _) in handlers {
+            self.call_value(handler, vec![message.value.clone()], &callee, &[])?;
+        }
+        Ok(true)
+    }
+
+    /// Run one spawned task to completion
+    fn run_task(&mut self, task: crate::scheduler::Task) -> Result<Value, RuntimeError> {
+        let callee = AstNode::Nothing { span: SourceSpan::unknown() };
+        let result = self.call_value(task.chant, task.args, &callee, &[]);
+        for scope in &mut self.together {
+            scope.remove(&task.id);
+        }
+        result
+    }
+
+    /// Number of `together` scopes open
+    pub fn together_depth(&self) -> usize {
+        self.together.len()
+    }
+
+    /// Run a `together` scope: the body, then its tasks until they have all
+    /// finished. If the body or one of the tasks fails (or the body exits
+    /// with `yield`, `break` or `continue`), tasks that have not started are
+    /// cancelled instead.
+    fn eval_together(&mut self, body: &[AstNode]) -> Result<Value, RuntimeError> {
+        self.together.push(BTreeSet::new());
+        self.environment.push_scope();
+        let mut result = self.eval(body).map(|_| Value::Nothing);
+        self.environment.pop_scope();
+        if result.is_ok() {
+            result = self.join_together().map(|_| Value::Nothing);
+        }
+        for task in self.together.pop().unwrap_or_default() {
+            self.scheduler.cancel(task);
+        }
+        result
+    }
+
+    /// Run ready tasks until the innermost `together` scope's have finished
+    fn join_together(&mut self) -> Result<(), RuntimeError> {
+        loop {
+            let pending = self.together.last().map_or(0, BTreeSet::len);
+            if pending == 0 {
+                return Ok(());
+            }
+            if let Some(task) = self.scheduler.next_ready() {
+                self.run_task(task)?;
+                continue;
+            }
+            if self.deliver_message(&mut crate::bus::Pump::default())? {
+                continue;
+            }
+
+            // Nothing left to run here; let the host scheduler catch up
+            self.scheduler.yield_now();
+            match self.scheduler.next_ready() {
+                Some(task) => {
+                    self.run_task(task)?;
+                }
+                None => {
+                    return Err(RuntimeError::Custom(format!(
+                        "Deadlock: {} task(s) of a together scope never became ready",
+                        pending
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Handle `watchdog` and `heartbeat`, which read the clock
+    fn call_watchdog_builtin(&mut self, name: &str, args: &[Value]) -> Option<Result<Value, RuntimeError>> {
+        if !matches!(name, "watchdog" | "heartbeat") {
+            return None;
+        }
+        let Some(now) = self.clock.as_ref().map(|clock| clock.now()) else {
+            return Some(Err(RuntimeError::Custom(format!("{}: No clock installed", name))));
+        };
+        if name == "heartbeat" {
+            self.watchdog.heartbeat(now);
+            return Some(Ok(Value::Nothing));
+        }
+        let result = match args {
+            [Value::Number(interval)] | [Value::Number(interval), Value::Text(_)] if *interval > 0.0 => {
+                self.watchdog.set_name(match args.get(1) {
+                    Some(Value::Text(name)) => Some(name.clone()),
+                    _ => None,
+                });
+                self.watchdog.arm(*interval as u64, now);
+                Ok(Value::Nothing)
+            }
+            [_] | [_, _] => Err(RuntimeError::TypeError {
+                expected: "positive interval (Number) and optional name (Text)".to_string(),
+                got: args.iter().map(Value::type_name).collect::<Vec<_>>().join(", "),
+            }),
+            _ => Err(RuntimeError::ArityMismatch { expected: 1, got: args.len() }),
+        };
+        Some(result)
+    }
+
+    /// Handle calls to the task builtins, which need the scheduler
+    fn call_task_builtin(&mut self, name: &str, args: &[Value]) -> Option<Result<Value, RuntimeError>> {
+        let result = match name {
+            "spawn" => match args.split_first() {
+                Some((chant, rest)) => {
+                    let id = self.scheduler.spawn(chant.clone(), rest.to_vec());
+                    if let Some(scope) = self.together.last_mut() {
+                        scope.insert(id);
+                    }
+                    Ok(Value::Number(id as f64))
+                }
+                None => Err(RuntimeError::ArityMismatch { expected: 1, got: 0 }),
+            },
+            "yield_now" => {
+                self.scheduler.yield_now();
+                self.run_until_idle().map(|_| Value::Nothing)
+            }
+            "block_on_event" => Self::event_id(&args[0]).and_then(|event| self.block_on_event(event)),
+            "signal_event" => Self::event_id(&args[0]).map(|event| {
+                self.scheduler.signal(event);
+                Value::Nothing
+            }),
+            _ => return None,
+        };
+        Some(result)
+    }
+
+    /// Run ready tasks until `event` is signalled
+    fn block_on_event(&mut self, event: crate::scheduler::EventId) -> Result<Value, RuntimeError> {
+        loop {
+            if self.scheduler.is_signalled(event) {
+                return Ok(Value::Nothing);
+            }
+            if let Some(task) = self.scheduler.next_ready() {
+                self.run_task(task)?;
+                continue;
+            }
+            if self.deliver_message(&mut crate::bus::Pump::default())? {
+                continue;
+            }
+
+            // Nothing left to run here; let the host scheduler wait for it
+            self.scheduler.block_on_event(event);
+            if self.scheduler.is_signalled(event) {
+                return Ok(Value::Nothing);
+            }
+            match self.scheduler.next_ready() {
+                Some(task) => {
+                    self.run_task(task)?;
+                }
+                None => {
+                    return Err(RuntimeError::Custom(format!(
+                        "Deadlock: event {} is never signalled",
+                        event
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Convert a script value to an event id
+    fn event_id(value: &Value) -> Result<crate::scheduler::EventId, RuntimeError> {
+        match value {
+            Value::Number(n) if *n >= 0.0 && n % 1.0 == 0.0 => Ok(*n as crate::scheduler::EventId),
+            other => Err(RuntimeError::TypeError {
+                expected: "event id (non-negative whole Number)".to_string(),
+                got: other.type_name().to_string(),
+            }),
+        }
+    }
+
+    /// Evaluate a list of statements (program or block)
+    ///
+    /// The outermost call owns the program's `defer` frame, so deferred
+    /// bodies at the top level run once the program finishes.
+    pub fn eval(&mut self, nodes: &[AstNode]) -> Result<Value, RuntimeError> {
+        if !self.defer_frames.is_empty() {
+            return self.eval_statements(nodes);
+        }
+        self.eval_program(nodes)
+    }
+
+    /// Evaluate a program's statements in its own `defer` frame
+    ///
+    /// Kept out of `eval`, whose frame stays live across nested blocks.
+    #[inline(never)]
+    fn eval_program(&mut self, nodes: &[AstNode]) -> Result<Value, RuntimeError> {
+        self.exit_status = None;
+        self.crash_dump = None;
+        let result = if self.leak_detection {
+            let before = self.heap_snapshot();
+            let result = self.with_defer_frame(|this| this.eval_statements(nodes));
+            self.leak_report = Some(crate::leak_check::LeakReport::new(before, self.heap_snapshot()));
+            result
+        } else {
+            self.with_defer_frame(|this| this.eval_statements(nodes))
+        };
+        if self.crash_dumps {
+            self.finish_crash_dump(&result);
+        }
+        match result {
+            Err(RuntimeError::Exit(status)) => {
+                self.exit_status = Some(status);
+                Ok(Value::Nothing)
+            }
+            other => other,
+        }
+    }
+
+    /// Evaluate statements in order, returning the last value
+    fn eval_statements(&mut self, nodes: &[AstNode]) -> Result<Value, RuntimeError> {
+        let mut result = Ok(Value::Nothing);
+        for node in nodes {
+            result = self.eval_node(node);
+            if result.is_err() {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Evaluate a chant body, returning its implicit value
+    ///
+    /// A chant that finishes without `yield` returns the value of its last
+    /// statement when that is an expression, or a `should`/`match` whose
+    /// taken branch ends in one (recursively). Any other final statement,
+    /// such as a binding, assignment or loop, makes the value Nothing. The
+    /// VM and native codegen follow the same rule.
+    fn eval_chant_body(&mut self, body: &[AstNode]) -> Result<Value, RuntimeError> {
+        // Branches count toward the depth limit as they would through
+        // eval_node; they are followed in a loop rather than by recursing,
+        // as this frame stays live across nested chant calls
+        let depth = self.depth;
+        let mut body = body;
+        let result = loop {
+            // The statements before the last run from this frame, so calls
+            // in them do not hold the tail's frame too
+            let Some((last, init)) = body.split_last() else { break Ok(Value::Nothing) };
+            let init = self.eval_statements(init);
+            if init.is_err() {
+                break init;
+            }
+            match self.chant_body_tail(last) {
+                Ok(ChantTail::Branch(branch)) => body = branch,
+                Ok(ChantTail::Match(value, arms)) => break self.eval_match(value, arms, true),
+                Ok(ChantTail::Value(node)) => break self.eval_node(node),
+                Ok(ChantTail::Statement(node)) => match self.eval_node(node) {
+                    Ok(_) => break Ok(Value::Nothing),
+                    Err(error) => break Err(error),
+                },
+                Ok(ChantTail::Nothing) => break Ok(Value::Nothing),
+                Err(error) => break Err(error),
+            }
+        };
+        self.depth = depth;
+        result
+    }
+
+    /// Find how the last statement of a chant body gives its value
+    ///
+    /// Takes a final `should` branch by evaluating its condition.
+    #[inline(never)]
+    fn chant_body_tail<'a>(&mut self, last: &'a AstNode) -> Result<ChantTail<'a>, RuntimeError> {
+        Ok(match last {
+            AstNode::IfStmt { condition, then_branch, else_branch, span } => {
+                self.descend()?;
+                if self.eval_condition(condition, span)? {
+                    ChantTail::Branch(then_branch)
+                } else if let Some(else_body) = else_branch {
+                    ChantTail::Branch(else_body)
+                } else {
+                    ChantTail::Nothing
+                }
+            }
+            AstNode::MatchStmt { value, arms, .. } => {
+                self.descend()?;
+                ChantTail::Match(value, arms)
+            }
+            node if node.is_expression() || matches!(node, AstNode::ExprStmt { .. }) => ChantTail::Value(node),
+            node => ChantTail::Statement(node),
+        })
+    }
+
+    /// Evaluate a `should` condition, counting its outcome when profiling
+    fn eval_condition(&mut self, condition: &AstNode, span: &SourceSpan) -> Result<bool, RuntimeError> {
+        let taken = self.eval_node(condition)?.is_truthy();
+        if let Some(profile) = self.profile.as_mut() {
+            profile.record_branch(span, taken);
+        }
+        Ok(taken)
+    }
+
+    /// Evaluate using the bytecode VM (Quicksilver fast path)
+    ///
+    /// This provides 5-10x performance improvement for pure expressions
+    /// that don't require stateful environment access.
+    ///
+    /// **Limitations:**
+    /// - Does not support local variables (only globals)
+    /// - Does not support function calls (chants)
+    /// - Does not support capability requests
+    ///
+    /// **Use cases:**
+    /// - Arithmetic expressions
+    /// - Comparisons
+    /// - Simple data structures (lists, maps)
+    /// - Global variable access
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - Result of execution
+    /// * `Err(RuntimeError)` - If compilation or execution fails
+    pub fn eval_with_vm(&mut self, nodes: &[AstNode]) -> Result<Value, RuntimeError> {
+        use crate::bytecode_compiler::compile;
+        use crate::vm::{VM, VmError};
+
+        // Compile AST to bytecode
+        let chunk = compile(nodes).map_err(|e| RuntimeError::CompileError {
+            message: alloc::format!("{:?}", e),
+        })?;
+
+        // Execute in VM
+        let mut vm = VM::new();
+        let result = vm.execute(chunk).map_err(|e| match e {
+            VmError::TypeError(msg) => RuntimeError::TypeError {
+                expected: "compatible type".to_string(),
+                got: msg,
+            },
+            VmError::UndefinedVariable(name) => RuntimeError::UndefinedVariable(name),
+            VmError::DivisionByZero => RuntimeError::DivisionByZero,
+            VmError::IntegerOverflow(operation) => RuntimeError::IntegerOverflow(operation),
+            VmError::Timeout => RuntimeError::Timeout,
+            VmError::Cancelled => RuntimeError::Cancelled,
+            VmError::OutOfBounds => RuntimeError::IndexOutOfBounds {
+                index: 0,
+                length: 0,
+            },
+            _ => RuntimeError::CompileError {
+                message: alloc::format!("{:?}", e),
+            },
+        })?;
+
+        Ok(result)
+    }
+
+    /// Call a function value with the given arguments.
+    ///
+    /// Handles three types of callable values:
+    /// - `Value::Chant`: User-defined functions with tail-call optimization
+    /// - `Value::NativeChant`: Built-in native functions
+    /// - `Value::VariantConstructor`: Enum variant constructors
+    ///
+    /// # Arguments
+    /// * `func` - The function value to call
+    /// * `args` - The arguments to pass (already evaluated)
+    /// * `callee_node` - The AST node of the callee (for tail-call detection)
+    /// * `type_args` - Type arguments for generic functions
+    ///
+    /// # Returns
+    /// * `Ok(Value)` - The result of the function call
+    /// * `Err(RuntimeError)` - If the call fails (arity mismatch, not callable, etc.)
+    #[allow(clippy::question_mark)]
     fn call_value(
         &mut self,
-        func: Value,
-        args: Vec<Value>,
+        func: Value,
+        args: Vec<Value>,
+        callee_node: &AstNode,
+        type_args: &[TypeAnnotation]
+    ) -> Result<Value, RuntimeError> {
+        // This frame stays live across nested chant calls, so it avoids the
+        // temporaries of `?` and borrows the chant rather than taking it apart
+        if let Err(error) = self.safepoint() {
+            return Err(error);
+        }
+
+        match &func {
+            Value::Chant { params, body, return_type, .. } => self.call_chant(params, body, return_type, args, callee_node),
+            Value::NativeChant(native_fn) => self.call_native(native_fn, args, callee_node),
+            _ => call_constructor(func, args, type_args),
+        }
+    }
+
+    /// Call a user-defined chant, looping on its recursive tail calls
+    ///
+    /// This frame stays live across nested chant calls, so the work around
+    /// each call and round is kept in helpers, and `?` is not used.
+    #[allow(clippy::question_mark)]
+    fn call_chant(
+        &mut self,
+        params: &[Parameter],
+        body: &[AstNode],
+        return_type: &Option<TypeAnnotation>,
+        args: Vec<Value>,
+        callee_node: &AstNode,
+    ) -> Result<Value, RuntimeError> {
+        let func_name = match self.enter_chant(params, args.len(), return_type, callee_node) {
+            Ok(func_name) => func_name,
+            Err(error) => return Err(error),
+        };
+
+        // Trampoline loop for TCO: a recursive tail call loops with new
+        // args instead of recursing
+        let mut args = args;
+        let outcome = loop {
+            if let Err(error) = self.begin_chant_round(params, args, func_name.as_deref()) {
+                break Err(error);
+            }
+
+            // Execute function body; its `defer` blocks run on the way out
+            self.defer_frames.push(Vec::new());
+            let result = self.eval_chant_body(body);
+            match self.end_chant_round(result, func_name.as_deref()) {
+                ChantRound::TailCall(next) => args = next,
+                ChantRound::Done(outcome) => break outcome,
+            }
+        };
+        self.leave_chant(outcome, func_name)
+    }
+
+    /// Check a chant call's arguments and depth, then enter it
+    ///
+    /// Returns the chant's name when it is called by name, for detecting
+    /// its tail calls.
+    #[inline(never)]
+    fn enter_chant(
+        &mut self,
+        params: &[Parameter],
+        argc: usize,
+        return_type: &Option<TypeAnnotation>,
+        callee_node: &AstNode,
+    ) -> Result<Option<String>, RuntimeError> {
+        // Check if function has variadic parameters
+        let has_variadic = params.last().is_some_and(|p| p.is_variadic);
+
+        // Arity check
+        if has_variadic {
+            // Variadic function: must have at least the regular arguments
+            if argc < params.len() - 1 {
+                return Err(RuntimeError::ArityMismatch {
+                    expected: params.len() - 1,
+                    got: argc,
+                });
+            }
+        } else if params.len() != argc {
+            // Regular function: must have exact number of arguments
+            return Err(RuntimeError::ArityMismatch {
+                expected: params.len(),
+                got: argc,
+            });
+        }
+
+        if self.call_depth >= self.max_call_depth {
+            return Err(RuntimeError::StackOverflow { limit: self.max_call_depth });
+        }
+
+        self.call_depth += 1;
+        self.return_types.push(return_type.clone());
+        self.chant_names.push(crate::capability::capability_name(callee_node));
+
+        // Get function name if callee is an Ident (for TCO detection)
+        Ok(match callee_node {
+            AstNode::Ident { name, .. } => Some(name.clone()),
+            _ => None,
+        })
+    }
+
+    /// Start a round of a chant call: a new scope with its arguments bound
+    #[inline(never)]
+    fn begin_chant_round(&mut self, params: &[Parameter], mut args: Vec<Value>, func_name: Option<&str>) -> Result<(), RuntimeError> {
+        // Tail calls loop through here, so this is a back-edge too
+        self.safepoint()?;
+        if let (Some(profile), Some(name)) = (self.profile.as_mut(), self.chant_names.last()) {
+            profile.record_call(name);
+        }
+
+        // Push new scope for function call
+        self.environment.push_scope();
+
+        // Bind parameters, collecting the arguments past the regular ones
+        // into a list for a variadic parameter
+        if let Some(variadic_param) = params.last().filter(|p| p.is_variadic) {
+            let variadic_args = args.split_off(params.len() - 1);
+            for (param, arg) in params.iter().zip(args) {
+                self.environment.define(param.name.clone(), arg);
+            }
+            self.environment.define(variadic_param.name.clone(), Value::List(variadic_args));
+        } else {
+            for (param, arg) in params.iter().zip(args) {
+                self.environment.define(param.name.clone(), arg);
+            }
+        }
+
+        // Store function name for tail call detection
+        if let Some(name) = func_name {
+            self.environment.define("__current_function__".to_string(), Value::Text(name.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Finish a round of a chant call with the result of its body
+    ///
+    /// Runs the round's `defer` blocks and restores the environment. A
+    /// `yield` ends the call with its value, and a tail call back to the
+    /// chant (named `func_name`) starts another round.
+    #[inline(never)]
+    fn end_chant_round(&mut self, result: Result<Value, RuntimeError>, func_name: Option<&str>) -> ChantRound {
+        let result = self.close_defer_frame(result);
+        self.environment.pop_scope();
+        match result {
+            Err(RuntimeError::Return(val)) => ChantRound::Done(Ok(val)),
+            Err(RuntimeError::TailCall { function_name, args }) if func_name == Some(function_name.as_str()) => {
+                ChantRound::TailCall(args)
+            }
+            other => ChantRound::Done(other),
+        }
+    }
+
+    /// Leave a chant call entered by `enter_chant`
+    #[inline(never)]
+    fn leave_chant(&mut self, outcome: Result<Value, RuntimeError>, func_name: Option<String>) -> Result<Value, RuntimeError> {
+        self.return_types.pop();
+        self.chant_names.pop();
+        self.call_depth -= 1;
+        match (&func_name, &self.taint_policy) {
+            (Some(name), Some(policy)) if policy.is_sanitizer(name) => outcome.map(Value::untainted),
+            _ => outcome,
+        }
+    }
+
+    /// Call a native chant, under the taint policy when there is one
+    fn call_native(
+        &mut self,
+        native_fn: &crate::runtime::NativeFunction,
+        args: Vec<Value>,
+        callee_node: &AstNode,
+    ) -> Result<Value, RuntimeError> {
+        use crate::taint::{builtin_capabilities, merge, TaintMode, TaintViolation};
+
+        let Some(policy) = self.taint_policy.as_ref() else {
+            return self.call_native_plain(native_fn, args, callee_node);
+        };
+
+        let mut sources = self.taint_context.clone();
+        for arg in &args {
+            merge(&mut sources, arg.taint_sources());
+        }
+        if !sources.is_empty() {
+            let mut sinks: Vec<String> = args
+                .iter()
+                .filter_map(|arg| match arg {
+                    Value::Capability { resource, .. } if policy.is_sink(resource) => Some(resource.clone()),
+                    _ => None,
+                })
+                .collect();
+            let builtin_sinks = builtin_capabilities(&native_fn.name).iter().filter(|capability| policy.is_sink(capability));
+            sinks.extend(builtin_sinks.map(|capability| capability.to_string()));
+            for sink in sinks {
+                if policy.mode() == TaintMode::Block {
+                    return Err(RuntimeError::CapabilityDenied {
+                        capability: sink,
+                        reason: format!("{}() was passed data from {} that no sanitizer cleaned", native_fn.name, sources.join(", ")),
+                    });
+                }
+                self.taint_violations.push(TaintViolation {
+                    sources: sources.clone(),
+                    sink,
+                    by: native_fn.name.clone(),
+                    span: callee_span(callee_node),
+                });
+            }
+        }
+        let sanitizes = policy.is_sanitizer(&native_fn.name);
+
+        let args = args.into_iter().map(Value::untainted).collect();
+        let audited = self.capability_audit.entries().len();
+        let outer_context = core::mem::replace(&mut self.taint_context, sources.clone());
+        let result = self.call_native_plain(native_fn, args, callee_node);
+        self.taint_context = outer_context;
+        let result = result?;
+        if sanitizes {
+            return Ok(result);
+        }
+
+        // Data read through a source capability during the call
+        if let Some(policy) = self.taint_policy.as_ref() {
+            let builtin_sources = builtin_capabilities(&native_fn.name).iter().filter(|capability| policy.is_source(capability));
+            merge(&mut sources, builtin_sources.map(|capability| capability.to_string()));
+            for entry in self.capability_audit.entries().iter().skip(audited) {
+                if matches!(entry.event, crate::capability::AuditEvent::Used { .. }) && policy.is_source(&entry.capability) {
+                    merge(&mut sources, [entry.capability.clone()]);
+                }
+            }
+        }
+        Ok(result.taint(&sources))
+    }
+
+    /// Call a native chant with clean arguments
+    ///
+    /// Natives that need the evaluator's state are handled here by name.
+    /// Kept out of `call_value`, whose frame stays live across nested chant
+    /// calls.
+    fn call_native_plain(
+        &mut self,
+        native_fn: &crate::runtime::NativeFunction,
+        mut args: Vec<Value>,
+        callee_node: &AstNode,
+    ) -> Result<Value, RuntimeError> {
+        // Check arity (None = variadic)
+        if let Some(expected) = native_fn.arity {
+            if args.len() != expected {
+                return Err(RuntimeError::ArityMismatch {
+                    expected,
+                    got: args.len(),
+                });
+            }
+        }
+
+        // Builtins see through frozen views; only is_frozen asks about the
+        // view itself
+        if native_fn.name == "is_frozen" {
+            return Ok(Value::Truth(args[0].is_frozen()));
+        }
+        for arg in args.iter_mut() {
+            if let Value::Frozen { value } = arg {
+                *arg = core::mem::replace(value.as_mut(), Value::Nothing);
+            }
+        }
+
+        if native_fn.name == "release" {
+            return self.release_resource(&args[0]);
+        }
+        if native_fn.name == "capabilities" {
+            return self.script_capabilities();
+        }
+        if native_fn.name == "to_text" {
+            return self.render_text(&args[0]);
+        }
+        if matches!(native_fn.name.as_str(), "print" | "println") && self.output.is_some() {
+            return self.print(&args, native_fn.name == "println");
+        }
+        if native_fn.name == "text_push" {
+            // Pushed values print as `to_text` prints them, Display included
+            if let Value::Text(text) = self.render_text(&args[1])? {
+                return crate::runtime::builder_push(&args[0], &text);
+            }
+        }
+        if native_fn.name == "list_sort" {
+            return self.sort_list(&args[0]);
+        }
+        if native_fn.name == "hash" {
+            return self.hash_value(&args[0]);
+        }
+        if native_fn.name == "validate" {
+            return self.validate(&args[0], &args[1]);
+        }
+        if native_fn.name == "variant_index" {
+            if let Some(result) = self.variant_index(&args[0]) {
+                return result;
+            }
+        }
+        self.check_resources(&args)?;
+        self.audit_uses(&args, &native_fn.name, callee_node);
+
+        if let Some(result) = self.call_task_builtin(&native_fn.name, &args) {
+            return result;
+        }
+        if let Some(result) = self.call_watchdog_builtin(&native_fn.name, &args) {
+            return result;
+        }
+        if let Some(result) = self.call_iter_builtin(&native_fn.name, &mut args) {
+            return result;
+        }
+        if let Some(result) = self.call_stream_builtin(&native_fn.name, &args, callee_node) {
+            return result;
+        }
+        if let Some(result) = self.call_store_builtin(&native_fn.name, &args, callee_node) {
+            return result;
+        }
+        if let Some(result) = self.call_bus_builtin(&native_fn.name, &args, callee_node) {
+            return result;
+        }
+        if let Some(result) = self.call_net_builtin(&native_fn.name, &args) {
+            return result;
+        }
+        if let Some(result) = self.call_http_builtin(&native_fn.name, &args, callee_node) {
+            return result;
+        }
+        if let Some(result) = self.call_term_builtin(&native_fn.name, &args, callee_node) {
+            return result;
+        }
+        if let Some(result) = self.call_draw_builtin(&native_fn.name, &args, callee_node) {
+            return result;
+        }
+        if let Some(result) = self.call_sound_builtin(&native_fn.name, &args, callee_node) {
+            return result;
+        }
+        if let Some(result) = self.call_progress_builtin(&native_fn.name, &args) {
+            return result;
+        }
+        if let Some(result) = self.call_i18n_builtin(&native_fn.name, &args) {
+            return result;
+        }
+        if let Some(result) = self.call_compiler_builtin(&native_fn.name, &args, callee_node) {
+            return result;
+        }
+
+        // Call native function, adopting any resource it hands out
+        let result = (native_fn.func)(&args)?;
+        Ok(self.adopt_resource(result))
+    }
+
+    /// Handle the iterator builtins that drive iterators, which need the
+    /// evaluator to call chants and `Iterable` embodiments
+    ///
+    /// Forms and variants embodying `Iterable` are accepted wherever an
+    /// iterator is. Returns `None` to fall back to the native builtin.
+    fn call_iter_builtin(&mut self, name: &str, args: &mut [Value]) -> Option<Result<Value, RuntimeError>> {
+        if !matches!(name, "iter" | "iter_next" | "iter_map" | "iter_filter" | "iter_take" | "iter_fold" | "iter_collect") {
+            return None;
+        }
+        if let Some(iterator) = self.aspect_iterator(&args[0]) {
+            args[0] = iterator;
+            if name == "iter" {
+                return Some(Ok(args[0].clone()));
+            }
+        }
+        let result = match name {
+            "iter_next" => self.advance_iterator(args[0].clone()).map(|(iterator, item)| {
+                let maybe = Value::Maybe { present: item.is_some(), value: item.map(Box::new) };
+                Value::List(vec![iterator, maybe])
+            }),
+            "iter_collect" => self.collect_iterator(args[0].clone()).map(Value::List),
+            "iter_fold" => self.fold_iterator(args[0].clone(), args[1].clone(), &args[2]),
+            _ => return None,
+        };
+        Some(result)
+    }
+
+    /// Handle the builtins that open stream iterators, which need the
+    /// evaluator's stream host
+    fn call_stream_builtin(&mut self, name: &str, args: &[Value], callee_node: &AstNode) -> Option<Result<Value, RuntimeError>> {
+        use crate::stream::{StreamUnit, CONSOLE_READ_CAPABILITY, FS_READ_CAPABILITY};
+
+        let (capability, unit) = match name {
+            "fs_lines" => (FS_READ_CAPABILITY, StreamUnit::Lines),
+            "fs_bytes" => (FS_READ_CAPABILITY, StreamUnit::Bytes),
+            "console_read_lines" => (CONSOLE_READ_CAPABILITY, StreamUnit::Lines),
+            _ => return None,
+        };
+        Some(self.open_stream(name, capability, unit, args.first(), callee_node))
+    }
+
+    /// Open a file (or the console, without a path) as a lazy iterator
+    ///
+    /// The script must hold `capability`. The stream is adopted as a
+    /// resource, so it is closed when the current chant finishes.
+    fn open_stream(
+        &mut self,
+        name: &str,
+        capability: &str,
+        unit: crate::stream::StreamUnit,
+        path: Option<&Value>,
         callee_node: &AstNode,
-        type_args: &[TypeAnnotation]
     ) -> Result<Value, RuntimeError> {
-        // Convert type annotations to strings for Phase 3
-        let type_arg_names: Vec<String> = type_args.iter()
-            .map(|ta| match ta {
-                crate::ast::TypeAnnotation::Named(n) => n.clone(),
-                _ => "Unknown".to_string(),
-            })
-            .collect();
+        if !self.capability_audit.is_granted(capability) {
+            return Err(RuntimeError::CapabilityDenied {
+                capability: capability.to_string(),
+                reason: format!("{}() requires `request {}`", name, capability),
+            });
+        }
+        let Some(host) = self.stream_host.as_mut() else {
+            return Err(RuntimeError::Custom(format!("{}: No stream host installed", name)));
+        };
+        let opened = match path {
+            Some(Value::Text(path)) => host.open_file(path),
+            Some(other) => {
+                return Err(RuntimeError::TypeError {
+                    expected: "Text".to_string(),
+                    got: other.type_name().to_string(),
+                })
+            }
+            None => host.open_console(),
+        };
+        let handle = opened.map_err(|message| RuntimeError::Custom(format!("{}: {}", name, message)))?;
+
+        let used = crate::capability::AuditEvent::Used { by: name.to_string() };
+        self.audit(capability, used, callee_span(callee_node));
+        let source = self.adopt_resource(Value::resource(crate::stream::STREAM_RESOURCE, handle));
+        let iterator_type = match unit {
+            crate::stream::StreamUnit::Lines => "Lines",
+            crate::stream::StreamUnit::Bytes => "Bytes",
+        };
+        Ok(Value::Iterator {
+            iterator_type: iterator_type.to_string(),
+            state: Box::new(IteratorState::Stream { source: Box::new(source), unit }),
+        })
+    }
+
+    /// Handle the key-value builtins, which need the evaluator's storage
+    /// provider and the script to hold `Store.readwrite`
+    fn call_store_builtin(&mut self, name: &str, args: &[Value], callee_node: &AstNode) -> Option<Result<Value, RuntimeError>> {
+        use crate::storage::STORE_CAPABILITY;
+
+        if !matches!(name, "store_get" | "store_set" | "store_delete") {
+            return None;
+        }
+        if !self.capability_audit.is_granted(STORE_CAPABILITY) {
+            return Some(Err(RuntimeError::CapabilityDenied {
+                capability: STORE_CAPABILITY.to_string(),
+                reason: format!("{}() requires `request {}`", name, STORE_CAPABILITY),
+            }));
+        }
+        let key = match &args[0] {
+            Value::Text(key) => key,
+            other => {
+                return Some(Err(RuntimeError::TypeError {
+                    expected: "Text".to_string(),
+                    got: other.type_name().to_string(),
+                }))
+            }
+        };
+        let used = crate::capability::AuditEvent::Used { by: name.to_string() };
+        self.audit(STORE_CAPABILITY, used, callee_span(callee_node));
+        if let Err(error) = self.migrate_store(key) {
+            return Some(Err(error));
+        }
+
+        let failed = |message: String| RuntimeError::Custom(format!("{}: {}", name, message));
+        let version = self.store_migrations.target(key);
+        let result = match name {
+            "store_get" => self.storage.get(key).map_err(failed).and_then(|bytes| match bytes {
+                Some(bytes) => crate::value_codec::decode(&bytes)
+                    .map(|value| Value::Maybe { present: true, value: Some(Box::new(value)) })
+                    .map_err(|error| failed(error.to_string())),
+                None => Ok(Value::Maybe { present: false, value: None }),
+            }),
+            "store_set" => crate::value_codec::encode(&args[1])
+                .map_err(|error| failed(error.to_string()))
+                .and_then(|bytes| self.storage.set(key, &bytes).map_err(failed))
+                .and_then(|()| match version {
+                    Some(version) => crate::storage::set_stored_version(self.storage.as_mut(), key, version).map_err(failed),
+                    None => Ok(()),
+                })
+                .map(|()| Value::Nothing),
+            _ => self
+                .storage
+                .delete(&crate::storage::version_key(key))
+                .and_then(|_| self.storage.delete(key))
+                .map(Value::Truth)
+                .map_err(failed),
+        };
+        Some(result)
+    }
+
+    /// Register a `migrate store` chant for `namespace`
+    fn eval_migrate_store(
+        &mut self,
+        namespace: &str,
+        from: u32,
+        to: u32,
+        param: &str,
+        body: &[AstNode],
+    ) -> Result<Value, RuntimeError> {
+        let chant = Value::Chant {
+            params: vec![Parameter {
+                name: param.to_string(),
+                typ: None,
+                is_variadic: false,
+                borrow_mode: BorrowMode::Owned,
+                lifetime: None,
+            }],
+            body: body.to_vec(),
+            closure: self.environment.clone(),
+            return_type: None,
+        };
+        self.store_migrations.register(namespace, from, to, chant);
+        // A new step may leave the stored value behind again
+        self.store_migrated.remove(namespace);
+        Ok(Value::Nothing)
+    }
+
+    /// Bring `namespace` up to the version its migrations reach, once per
+    /// evaluator and provider
+    ///
+    /// Each step's result is saved before the next runs, so a failing step
+    /// leaves the value at the last version reached and is retried on the
+    /// next access.
+    fn migrate_store(&mut self, namespace: &str) -> Result<(), RuntimeError> {
+        if self.store_migrations.target(namespace).is_none() || self.store_migrated.contains(namespace) {
+            return Ok(());
+        }
+        // Marked first, so a migration chant can read its own namespace
+        self.store_migrated.insert(namespace.to_string());
+        let result = self.run_store_migrations(namespace);
+        if result.is_err() {
+            self.store_migrated.remove(namespace);
+        }
+        result
+    }
+
+    fn run_store_migrations(&mut self, namespace: &str) -> Result<(), RuntimeError> {
+        use crate::storage::{set_stored_version, stored_version};
+
+        let failed = |message: String| RuntimeError::Custom(format!("migrate store \"{}\": {}", namespace, message));
+        // Nothing stored yet: the first write records the current version
+        let Some(bytes) = self.storage.get(namespace).map_err(failed)? else {
+            return Ok(());
+        };
+        let stored = stored_version(self.storage.as_mut(), namespace).map_err(failed)?;
+        let steps = self.store_migrations.plan(namespace, stored).map_err(failed)?;
+        if steps.is_empty() {
+            return Ok(());
+        }
+
+        let mut value = crate::value_codec::decode(&bytes).map_err(|error| failed(error.to_string()))?;
+        let callee = AstNode::Nothing { span: SourceSpan::unknown() };
+        for (version, chant) in steps {
+            value = self.call_value(chant, vec![value], &callee, &[])?;
+            let bytes = crate::value_codec::encode(&value).map_err(|error| failed(error.to_string()))?;
+            self.storage.set(namespace, &bytes).map_err(failed)?;
+            set_stored_version(self.storage.as_mut(), namespace, version).map_err(failed)?;
+        }
+        Ok(())
+    }
+
+    /// Handle the `Net` module's builtins, which need the evaluator's
+    /// network provider
+    ///
+    /// Network failures come back as Mishaps; misuse is a runtime error.
+    fn call_net_builtin(&mut self, name: &str, args: &[Value]) -> Option<Result<Value, RuntimeError>> {
+        use crate::net::{parse_endpoint, NET_CONNECT_CAPABILITY, SOCKET_RESOURCE};
+
+        if !matches!(name, "tcp_connect" | "net_send" | "net_recv" | "net_close") {
+            return None;
+        }
+        if name == "net_close" {
+            return Some(self.release_resource(&args[0]));
+        }
+        let Some(net) = self.net.as_mut() else {
+            return Some(Err(RuntimeError::Custom(format!("{}: No network provider installed", name))));
+        };
+        let outcome = |result: Result<Value, String>| {
+            let (success, value) = match result {
+                Ok(value) => (true, value),
+                Err(message) => (false, Value::Text(message)),
+            };
+            Value::Outcome { success, value: Box::new(value) }
+        };
+
+        if name == "tcp_connect" {
+            let endpoint = match &args[0] {
+                Value::Capability { resource, permissions } if resource == NET_CONNECT_CAPABILITY => {
+                    permissions.iter().rev().find_map(|permission| parse_endpoint(permission))
+                }
+                _ => None,
+            };
+            let Some((host, port)) = endpoint else {
+                return Some(Err(RuntimeError::TypeError {
+                    expected: format!("{} capability", NET_CONNECT_CAPABILITY),
+                    got: args[0].type_name().to_string(),
+                }));
+            };
+            let connected = net.connect(host, port);
+            let socket = connected.map(|handle| self.adopt_resource(Value::resource(SOCKET_RESOURCE, handle)));
+            return Some(Ok(outcome(socket)));
+        }
+
+        let handle = match &args[0] {
+            Value::Resource { kind, handle, .. } if kind == SOCKET_RESOURCE => *handle,
+            other => {
+                return Some(Err(RuntimeError::TypeError {
+                    expected: "socket".to_string(),
+                    got: other.type_name().to_string(),
+                }))
+            }
+        };
+        let result = match (name, &args[1]) {
+            ("net_send", Value::Text(text)) => net.send(handle, text.as_bytes()).map(|sent| Value::Number(sent as f64)),
+            ("net_recv", Value::Number(max)) if *max >= 1.0 => net
+                .recv(handle, *max as usize)
+                .map(|bytes| Value::Text(String::from_utf8_lossy(&bytes).into_owned())),
+            (_, other) => {
+                return Some(Err(RuntimeError::TypeError {
+                    expected: if name == "net_send" { "Text" } else { "positive Number" }.to_string(),
+                    got: other.type_name().to_string(),
+                }))
+            }
+        };
+        Some(Ok(outcome(result)))
+    }
+
+    /// Handle `http_get` and `http_post`, which make requests through the
+    /// network provider to endpoints the script was granted
+    fn call_http_builtin(&mut self, name: &str, args: &[Value], callee_node: &AstNode) -> Option<Result<Value, RuntimeError>> {
+        use crate::http::{fetch, HttpOptions};
+
+        let (method, takes) = match name {
+            "http_get" => ("GET", 1),
+            "http_post" => ("POST", 2),
+            _ => return None,
+        };
+        if args.len() != takes && args.len() != takes + 1 {
+            return Some(Err(RuntimeError::ArityMismatch { expected: takes, got: args.len() }));
+        }
+        let texts: Vec<&str> = match args[..takes].iter().map(|arg| match arg {
+            Value::Text(text) => Ok(text.as_str()),
+            other => Err(other),
+        }).collect() {
+            Ok(texts) => texts,
+            Err(other) => {
+                return Some(Err(RuntimeError::TypeError { expected: "Text".to_string(), got: other.type_name().to_string() }))
+            }
+        };
+        let options = match args.get(takes).map(http_options).unwrap_or_else(|| Ok(HttpOptions::default())) {
+            Ok(options) => options,
+            Err(error) => return Some(Err(error)),
+        };
+
+        let used = crate::capability::AuditEvent::Used { by: name.to_string() };
+        self.audit(crate::net::NET_CONNECT_CAPABILITY, used, callee_span(callee_node));
+        let Some(net) = self.net.as_mut() else {
+            return Some(Err(RuntimeError::Custom(format!("{}: No network provider installed", name))));
+        };
+        let grants = &self.net_grants;
+        let granted = |host: &str, port: u16| grants.iter().any(|(granted, granted_port)| granted == host && *granted_port == port);
+
+        let (success, value) = match fetch(net.as_mut(), &granted, method, texts[0], texts.get(1).copied(), &options) {
+            Ok(response) => {
+                let headers = response.headers.into_iter().map(|(name, value)| (name, Value::Text(value))).collect();
+                let mut map = BTreeMap::new();
+                map.insert("status".to_string(), Value::Number(response.status as f64));
+                map.insert("headers".to_string(), Value::Map(headers));
+                map.insert("body".to_string(), Value::Text(response.body));
+                (true, Value::Map(map))
+            }
+            Err(message) => (false, Value::Text(message)),
+        };
+        Some(Ok(Value::Outcome { success, value: Box::new(value) }))
+    }
+
+    /// Handle the `Term` module's builtins, which need the evaluator's
+    /// terminal and output sink
+    fn call_term_builtin(&mut self, name: &str, args: &[Value], callee_node: &AstNode) -> Option<Result<Value, RuntimeError>> {
+        use crate::term::{Color, CONSOLE_READ_CAPABILITY, TERM_CAPABILITY};
+
+        let capability = match name {
+            "term_move_to" | "term_set_color" | "term_clear" => TERM_CAPABILITY,
+            "term_read_key" => CONSOLE_READ_CAPABILITY,
+            _ => return None,
+        };
+        if !self.capability_audit.is_granted(capability) {
+            return Some(Err(RuntimeError::CapabilityDenied {
+                capability: capability.to_string(),
+                reason: format!("{}() requires `request {}`", name, capability),
+            }));
+        }
+        let used = crate::capability::AuditEvent::Used { by: name.to_string() };
+        self.audit(capability, used, callee_span(callee_node));
+        let Some(terminal) = self.terminal.as_mut() else {
+            return Some(Err(RuntimeError::Custom(format!("{}: No terminal installed", name))));
+        };
+        let mut discard = |_: &str| {};
+        let out: &mut dyn crate::output::OutputSink = match self.output.as_mut() {
+            Some(sink) => sink.as_mut(),
+            None => &mut discard,
+        };
+
+        let color = |value: &Value| match value {
+            Value::Text(name) => Color::from_name(name).ok_or_else(|| RuntimeError::Custom(format!("Unknown color '{}'", name))),
+            other => Err(RuntimeError::TypeError { expected: "color name".to_string(), got: other.type_name().to_string() }),
+        };
+        let result = match (name, args) {
+            ("term_move_to", [Value::Number(row), Value::Number(column)])
+                if [row, column].iter().all(|n| *n % 1.0 == 0.0 && (0.0..=u32::MAX as f64).contains(*n)) =>
+            {
+                terminal.move_to(out, *row as u32, *column as u32).map(|()| Value::Nothing)
+            }
+            ("term_move_to", [row, column]) => {
+                return Some(Err(RuntimeError::TypeError {
+                    expected: "row and column Numbers from 0".to_string(),
+                    got: format!("{} and {}", row.type_name(), column.type_name()),
+                }))
+            }
+            ("term_set_color", [foreground]) | ("term_set_color", [foreground, Value::Nothing]) => match color(foreground) {
+                Ok(foreground) => terminal.set_color(out, foreground, None).map(|()| Value::Nothing),
+                Err(error) => return Some(Err(error)),
+            },
+            ("term_set_color", [foreground, background]) => match (color(foreground), color(background)) {
+                (Ok(foreground), Ok(background)) => terminal.set_color(out, foreground, Some(background)).map(|()| Value::Nothing),
+                (Err(error), _) | (_, Err(error)) => return Some(Err(error)),
+            },
+            ("term_set_color", _) => return Some(Err(RuntimeError::ArityMismatch { expected: 2, got: args.len() })),
+            ("term_clear", _) => terminal.clear(out).map(|()| Value::Nothing),
+            _ => terminal.read_key().map(|key| Value::Maybe { present: key.is_some(), value: key.map(|key| Box::new(Value::Text(key))) }),
+        };
+        Some(result.map_err(|error| RuntimeError::Custom(format!("{}: {}", name, error))))
+    }
+
+    /// Handle the `Compiler` module's builtins, which run compiler phases on
+    /// source text for scripts holding the capability
+    fn call_compiler_builtin(&mut self, name: &str, args: &[Value], callee_node: &AstNode) -> Option<Result<Value, RuntimeError>> {
+        use crate::self_host::{analyze, compile, lex, parse, COMPILER_CAPABILITY};
+
+        if !name.starts_with("compiler_") {
+            return None;
+        }
+        if !self.capability_audit.is_granted(COMPILER_CAPABILITY) {
+            return Some(Err(RuntimeError::CapabilityDenied {
+                capability: COMPILER_CAPABILITY.to_string(),
+                reason: format!("{}() requires `request {}`", name, COMPILER_CAPABILITY),
+            }));
+        }
+        let used = crate::capability::AuditEvent::Used { by: name.to_string() };
+        self.audit(COMPILER_CAPABILITY, used, callee_span(callee_node));
+
+        let result = match (name, args) {
+            ("compiler_lex", [Value::Text(source)]) => Ok(lex(source)),
+            ("compiler_parse", [Value::Text(source)]) => Ok(parse(source)),
+            ("compiler_analyze", [Value::Text(source)]) => Ok(analyze(source)),
+            ("compiler_compile", [Value::Text(source), Value::Text(target)]) => {
+                compile(source, target).map_err(RuntimeError::Custom)
+            }
+            ("compiler_compile", [source, target]) => Err(RuntimeError::TypeError {
+                expected: "source and target Text".to_string(),
+                got: format!("{} and {}", source.type_name(), target.type_name()),
+            }),
+            (_, [source]) => Err(RuntimeError::TypeError { expected: "Text".to_string(), got: source.type_name().to_string() }),
+            (_, _) => Err(RuntimeError::ArityMismatch { expected: if name == "compiler_compile" { 2 } else { 1 }, got: args.len() }),
+        };
+        Some(result)
+    }
+
+    /// Handle the `Draw` module's builtins, which draw into the evaluator's
+    /// framebuffer
+    fn call_draw_builtin(&mut self, name: &str, args: &[Value], callee_node: &AstNode) -> Option<Result<Value, RuntimeError>> {
+        use crate::draw::DRAW_CAPABILITY;
+
+        if !name.starts_with("draw_") {
+            return None;
+        }
+        if !self.capability_audit.is_granted(DRAW_CAPABILITY) {
+            return Some(Err(RuntimeError::CapabilityDenied {
+                capability: DRAW_CAPABILITY.to_string(),
+                reason: format!("{}() requires `request {}`", name, DRAW_CAPABILITY),
+            }));
+        }
+        let used = crate::capability::AuditEvent::Used { by: name.to_string() };
+        self.audit(DRAW_CAPABILITY, used, callee_span(callee_node));
+        let Some(framebuffer) = self.framebuffer.as_mut() else {
+            return Some(Err(RuntimeError::Custom(format!("{}: No framebuffer installed", name))));
+        };
+        Some(Self::draw(framebuffer, name, args))
+    }
+
+    fn draw(framebuffer: &mut crate::draw::DoubleBuffer, name: &str, args: &[Value]) -> Result<Value, RuntimeError> {
+        let mistyped = |expected: &str, got: &Value| RuntimeError::TypeError {
+            expected: expected.to_string(),
+            got: got.type_name().to_string(),
+        };
+        // Far enough off screen for any canvas, near enough not to overflow
+        let coordinate = |value: &Value| match value {
+            Value::Number(n) if n % 1.0 == 0.0 && n.abs() <= f64::from(i32::MAX) => Ok(*n as i64),
+            other => Err(mistyped("whole Number (coordinate)", other)),
+        };
+        let length = |value: &Value| match value {
+            Value::Number(n) if n % 1.0 == 0.0 && (0.0..=f64::from(u32::MAX)).contains(n) => Ok(*n as u32),
+            other => Err(mistyped("whole Number from 0 (size)", other)),
+        };
+        let color = |value: &Value| match value {
+            Value::Number(n) if n % 1.0 == 0.0 && (0.0..=f64::from(0xFF_FFFF)).contains(n) => Ok(*n as u32),
+            Value::Text(hex) if hex.len() == 7 && hex.starts_with('#') && hex[1..].chars().all(|c| c.is_ascii_hexdigit()) => {
+                u32::from_str_radix(&hex[1..], 16).map_err(|_| mistyped("color (0xRRGGBB or \"#rrggbb\")", value))
+            }
+            other => Err(mistyped("color (0xRRGGBB or \"#rrggbb\")", other)),
+        };
+
+        let canvas = framebuffer.back_mut();
+        match name {
+            "draw_pixel" => canvas.pixel(coordinate(&args[0])?, coordinate(&args[1])?, color(&args[2])?),
+            "draw_rect" => canvas.rect(
+                coordinate(&args[0])?,
+                coordinate(&args[1])?,
+                length(&args[2])?,
+                length(&args[3])?,
+                color(&args[4])?,
+            ),
+            "draw_blit" => {
+                let Value::List(items) = &args[3] else {
+                    return Err(mistyped("List of colors", &args[3]));
+                };
+                let pixels = items.iter().map(color).collect::<Result<Vec<_>, _>>()?;
+                canvas.blit(coordinate(&args[0])?, coordinate(&args[1])?, length(&args[2])?, &pixels);
+            }
+            "draw_text" => {
+                let Value::Text(text) = &args[2] else {
+                    return Err(mistyped("Text", &args[2]));
+                };
+                let (x, y, color) = (coordinate(&args[0])?, coordinate(&args[1])?, color(&args[3])?);
+                return Ok(Value::Number(framebuffer.text(x, y, text, color) as f64));
+            }
+            "draw_clear" => canvas.clear(color(&args[0])?),
+            "draw_present" => {
+                framebuffer.present().map_err(|error| RuntimeError::Custom(format!("{}: {}", name, error)))?;
+            }
+            _ => {
+                let mut size = BTreeMap::new();
+                size.insert("width".to_string(), Value::Number(f64::from(canvas.width())));
+                size.insert("height".to_string(), Value::Number(f64::from(canvas.height())));
+                return Ok(Value::Map(size));
+            }
+        }
+        Ok(Value::Nothing)
+    }
+
+    /// Handle `Sound.tone` and `Sound.stop`, which play through the
+    /// evaluator's speaker
+    fn call_sound_builtin(&mut self, name: &str, args: &[Value], callee_node: &AstNode) -> Option<Result<Value, RuntimeError>> {
+        use crate::sound::{SPEAKER_CAPABILITY, TONE_RANGE};
+
+        if !matches!(name, "sound_tone" | "sound_stop") {
+            return None;
+        }
+        if !self.capability_audit.is_granted(SPEAKER_CAPABILITY) {
+            return Some(Err(RuntimeError::CapabilityDenied {
+                capability: SPEAKER_CAPABILITY.to_string(),
+                reason: format!("{}() requires `request {}`", name, SPEAKER_CAPABILITY),
+            }));
+        }
+        let used = crate::capability::AuditEvent::Used { by: name.to_string() };
+        self.audit(SPEAKER_CAPABILITY, used, callee_span(callee_node));
+        let Some(speaker) = self.speaker.as_mut() else {
+            return Some(Err(RuntimeError::Custom(format!("{}: No speaker installed", name))));
+        };
+
+        let result = match (name, args) {
+            ("sound_tone", [Value::Number(frequency), Value::Number(millis)])
+                if (TONE_RANGE.0..=TONE_RANGE.1).contains(frequency) && millis % 1.0 == 0.0 && *millis >= 0.0 =>
+            {
+                speaker.tone(*frequency, *millis as u64)
+            }
+            ("sound_tone", [frequency, millis]) => {
+                return Some(Err(RuntimeError::TypeError {
+                    expected: format!("frequency from {} to {} Hz and whole milliseconds from 0", TONE_RANGE.0, TONE_RANGE.1),
+                    got: format!("{} and {}", frequency.type_name(), millis.type_name()),
+                }))
+            }
+            _ => speaker.stop(),
+        };
+        Some(result.map(|()| Value::Nothing).map_err(|error| RuntimeError::Custom(format!("{}: {}", name, error))))
+    }
+
+    /// Handle `with_progress` and `progress_update`, which report to the
+    /// evaluator's progress sink
+    fn call_progress_builtin(&mut self, name: &str, args: &[Value]) -> Option<Result<Value, RuntimeError>> {
+        use crate::progress::{ProgressEvent, TaskEnd, PROGRESS_RESOURCE};
+
+        let mistyped = |expected: &str, got: &Value| {
+            Some(Err(RuntimeError::TypeError { expected: expected.to_string(), got: got.type_name().to_string() }))
+        };
+        match name {
+            "with_progress" => {
+                let total = match &args[0] {
+                    Value::Number(total) if *total >= 0.0 => *total,
+                    other => return mistyped("Number from 0", other),
+                };
+                self.last_progress_task += 1;
+                let task = self.last_progress_task;
+                let id = self.resources.adopt(PROGRESS_RESOURCE, task);
+                let handle = Value::Resource { id, kind: PROGRESS_RESOURCE.to_string(), handle: task };
+                self.progress_tasks.insert(task, total);
+                self.report_progress(ProgressEvent::Started { task, total });
+
+                let result = self.apply(&args[1], vec![handle]);
+
+                // The script may have released the handle itself
+                let _ = self.resources.release(id);
+                self.progress_tasks.remove(&task);
+                let end = match &result {
+                    Ok(_) => TaskEnd::Completed,
+                    Err(RuntimeError::Cancelled) => TaskEnd::Cancelled,
+                    Err(_) => TaskEnd::Failed,
+                };
+                self.report_progress(ProgressEvent::Finished { task, end });
+                Some(result)
+            }
+            "progress_update" => {
+                let (handle, done, message) = match args {
+                    [handle, done] => (handle, done, None),
+                    [handle, done, message] => (handle, done, Some(message)),
+                    _ => return Some(Err(RuntimeError::ArityMismatch { expected: 3, got: args.len() })),
+                };
+                let task = match handle {
+                    Value::Resource { kind, handle, .. } if kind == PROGRESS_RESOURCE => *handle,
+                    other => return mistyped("progress handle", other),
+                };
+                let done = match done {
+                    Value::Number(done) => *done,
+                    other => return mistyped("Number", other),
+                };
+                let message = match message {
+                    None => None,
+                    Some(Value::Text(message)) => Some(message.clone()),
+                    Some(other) => return mistyped("Text", other),
+                };
+                // An update is where a long task notices it was cancelled
+                if let Err(error) = self.safepoint() {
+                    return Some(Err(error));
+                }
+                let Some(&total) = self.progress_tasks.get(&task) else {
+                    return Some(Err(RuntimeError::ResourceReleased { kind: PROGRESS_RESOURCE.to_string(), handle: task }));
+                };
+                let done = done.clamp(0.0, total);
+                self.report_progress(ProgressEvent::Updated { task, done, total, message });
+                Some(Ok(Value::Nothing))
+            }
+            _ => None,
+        }
+    }
+
+    /// Handle `tr`, `catalog_load` and `set_locale`, which use the
+    /// evaluator's message catalogs
+    fn call_i18n_builtin(&mut self, name: &str, args: &[Value]) -> Option<Result<Value, RuntimeError>> {
+        let mistyped = |expected: &str, got: &Value| {
+            Some(Err(RuntimeError::TypeError { expected: expected.to_string(), got: got.type_name().to_string() }))
+        };
+        match name {
+            "tr" => {
+                let (key, values) = match args {
+                    [key] => (key, None),
+                    [key, Value::Map(values)] => (key, Some(values)),
+                    [_, other] => return mistyped("Map", other),
+                    _ => return Some(Err(RuntimeError::ArityMismatch { expected: 2, got: args.len() })),
+                };
+                let Value::Text(key) = key else {
+                    return mistyped("Text", key);
+                };
+                // An untranslated key shows as itself rather than failing
+                let template = self.messages.lookup(key).unwrap_or(key).to_string();
+                let mut failure = None;
+                let text = crate::i18n::interpolate(&template, |placeholder| {
+                    let value = values?.get(placeholder)?;
+                    match self.render_text(value) {
+                        Ok(Value::Text(text)) => Some(text),
+                        Ok(_) => None,
+                        Err(error) => {
+                            failure = Some(error);
+                            Some(String::new())
+                        }
+                    }
+                });
+                if let Some(error) = failure {
+                    return Some(Err(error));
+                }
+                Some(text.map(Value::Text).map_err(|error| RuntimeError::Custom(format!("tr(\"{}\"): {}", key, error))))
+            }
+            "catalog_load" => {
+                let Value::Text(locale) = &args[0] else {
+                    return mistyped("Text", &args[0]);
+                };
+                let Value::Map(entries) = &args[1] else {
+                    return mistyped("Map", &args[1]);
+                };
+                let mut catalog = crate::i18n::Catalog::new();
+                for (key, template) in entries {
+                    let Value::Text(template) = template else {
+                        return mistyped("Text", template);
+                    };
+                    catalog.insert(key.clone(), template.clone());
+                }
+                self.messages.load(locale, catalog);
+                Some(Ok(Value::Nothing))
+            }
+            "set_locale" => {
+                let Value::Text(locale) = &args[0] else {
+                    return mistyped("Text", &args[0]);
+                };
+                self.messages.set_locale(locale);
+                Some(Ok(Value::Nothing))
+            }
+            _ => None,
+        }
+    }
+
+    fn report_progress(&mut self, event: crate::progress::ProgressEvent) {
+        if let Some(sink) = self.progress_sink.as_mut() {
+            sink.report(&event);
+        }
+    }
+
+    /// Handle `publish` and `subscribe`, which need the session's bus and
+    /// the capability of the topic's namespace
+    fn call_bus_builtin(&mut self, name: &str, args: &[Value], callee_node: &AstNode) -> Option<Result<Value, RuntimeError>> {
+        if !matches!(name, "publish" | "subscribe") {
+            return None;
+        }
+        let arity = if name == "subscribe" { 2..=3 } else { 2..=2 };
+        if !arity.contains(&args.len()) {
+            return Some(Err(RuntimeError::ArityMismatch { expected: 2, got: args.len() }));
+        }
+        let topic = match &args[0] {
+            Value::Text(topic) => topic,
+            other => {
+                return Some(Err(RuntimeError::TypeError {
+                    expected: "Text".to_string(),
+                    got: other.type_name().to_string(),
+                }))
+            }
+        };
+        let capability = crate::bus::topic_capability(topic);
+        if !self.capability_audit.is_granted(&capability) {
+            return Some(Err(RuntimeError::CapabilityDenied {
+                reason: format!("{}() on '{}' requires `request {}`", name, topic, capability),
+                capability,
+            }));
+        }
+        let Some(bus) = self.bus.clone() else {
+            return Some(Err(RuntimeError::Custom(format!(
+                "{}: No message bus; spawn the evaluator from a Session",
+                name
+            ))));
+        };
+        let used = crate::capability::AuditEvent::Used { by: name.to_string() };
+        self.audit(&capability, used, callee_span(callee_node));
+
+        let result = if name == "publish" {
+            // Subscribers get their own copy of plain data
+            crate::value_codec::encode(&args[1])
+                .and_then(|bytes| crate::value_codec::decode(&bytes))
+                .map(|value| Value::Number(crate::sync::lock(&bus).publish(topic, &value) as f64))
+                .map_err(|error| RuntimeError::Custom(format!("publish: {}", error)))
+        } else {
+            match (&args[1], args.get(2)) {
+                (Value::Chant { .. } | Value::NativeChant(_), None | Some(Value::Number(_))) => {
+                    let priority = match args.get(2) {
+                        Some(Value::Number(n)) => *n as i64,
+                        _ => 0,
+                    };
+                    crate::sync::lock(&bus).subscribe(topic, &self.inbox);
+                    self.bus_handlers.push((topic.clone(), args[1].clone(), priority));
+                    Ok(Value::Nothing)
+                }
+                (Value::Chant { .. } | Value::NativeChant(_), Some(other)) => Err(RuntimeError::TypeError {
+                    expected: "Number (priority)".to_string(),
+                    got: other.type_name().to_string(),
+                }),
+                (other, _) => Err(RuntimeError::TypeError {
+                    expected: "Chant".to_string(),
+                    got: other.type_name().to_string(),
+                }),
+            }
+        };
+        Some(result)
+    }
+
+    /// Pull the next line or byte from an open stream
+    fn read_stream(&mut self, source: &Value, unit: crate::stream::StreamUnit) -> Result<Option<Value>, RuntimeError> {
+        self.check_resources(core::slice::from_ref(source))?;
+        let (Value::Resource { handle, .. }, Some(host)) = (source, self.stream_host.as_mut()) else {
+            return Err(RuntimeError::Custom("stream: No stream host installed".to_string()));
+        };
+        let read = match unit {
+            crate::stream::StreamUnit::Lines => host.read_line(*handle).map(|line| line.map(Value::Text)),
+            crate::stream::StreamUnit::Bytes => host.read_byte(*handle).map(|byte| byte.map(|b| Value::Number(b as f64))),
+        };
+        read.map_err(|message| RuntimeError::Custom(format!("stream: {}", message)))
+    }
+
+    /// Evaluate a single AST node
+    ///
+    /// Expressions are evaluated on an explicit work stack (see `eval_expr`),
+    /// so deeply nested expressions do not consume host stack. Statements and
+    /// calls recurse, and that nesting is bounded by the evaluator's depth
+    /// limit: exceeding it fails with `RuntimeError::DepthLimitExceeded`
+    /// instead of overflowing the host stack.
+    pub fn eval_node(&mut self, node: &AstNode) -> Result<Value, RuntimeError> {
+        // This frame stays live across nested chant calls, so the depth
+        // check is made inline, without a `Result` of its own
+        if self.depth >= self.max_depth {
+            return Err(RuntimeError::DepthLimitExceeded { limit: self.max_depth });
+        }
+        self.depth += 1;
+        let result = match node {
+            AstNode::Triumph { .. }
+            | AstNode::Mishap { .. }
+            | AstNode::Present { .. }
+            | AstNode::BorrowExpr { .. }
+            | AstNode::List { .. }
+            | AstNode::Tuple { .. }
+            | AstNode::Map { .. }
+            | AstNode::Try { .. }
+            | AstNode::BinaryOp { .. }
+            | AstNode::UnaryOp { .. }
+            | AstNode::InUnit { .. }
+            | AstNode::Call { .. }
+            | AstNode::FieldAccess { .. }
+            | AstNode::IndexAccess { .. }
+            | AstNode::Range { .. }
+            | AstNode::ExprStmt { .. } => self.eval_expr(node),
+            _ => self.eval_statement(node),
+        };
+        self.depth -= 1;
+        if self.recording.is_some() || self.crash_dumps {
+            self.observe_statement(node, &result);
+        }
+        result
+    }
+
+    /// Record a statement's step and trace a crash it caused, when enabled
+    #[inline(never)]
+    fn observe_statement(&mut self, node: &AstNode, result: &Result<Value, RuntimeError>) {
+        if !node.is_statement() {
+            return;
+        }
+        if self.recording.is_some() {
+            self.record_step(node, result);
+        }
+        if let (true, Err(error)) = (self.crash_dumps, result) {
+            self.trace_crash(node, error);
+        }
+    }
+
+    /// Go one nesting level deeper, failing once the depth limit is reached;
+    /// the caller restores the depth
+    fn descend(&mut self) -> Result<(), RuntimeError> {
+        if self.depth >= self.max_depth {
+            return Err(RuntimeError::DepthLimitExceeded { limit: self.max_depth });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Evaluate an expression tree on an explicit work stack.
+    ///
+    /// Each node is split into tasks: its operands are evaluated first and
+    /// their values collected on a value stack, then a follow-up task combines
+    /// them. Nodes the stack machine does not decompose (statements, leaves,
+    /// method calls) are evaluated directly and their result pushed.
+    ///
+    /// This frame stays live across nested chant calls, so each task is
+    /// carried out by `expr_step` and only the calls that can recurse are
+    /// made from here, without `?` and its temporaries.
+    fn eval_expr(&mut self, root: &AstNode) -> Result<Value, RuntimeError> {
+        let mut tasks = vec![ExprTask::Eval(root)];
+        let mut values: Vec<Value> = Vec::new();
+
+        while let Some(task) = tasks.pop() {
+            let result = match self.expr_step(task, &mut tasks, &mut values) {
+                Ok(ExprStep::Done) => continue,
+                Ok(ExprStep::Call { callee, type_args, arg_names, argc }) => {
+                    self.call_from_stack(&mut values, argc, arg_names, callee, type_args)
+                }
+                Ok(ExprStep::Direct(node)) => self.eval_direct(node),
+                Err(error) => return Err(error),
+            };
+            match result {
+                Ok(value) => values.push(value),
+                Err(error) => return Err(error),
+            }
+        }
+
+        pop_value(&mut values)
+    }
+
+    /// Call the chant and arguments on top of the expression value stack
+    fn call_from_stack(
+        &mut self,
+        values: &mut Vec<Value>,
+        argc: usize,
+        arg_names: &[String],
+        callee: &AstNode,
+        type_args: &[TypeAnnotation],
+    ) -> Result<Value, RuntimeError> {
+        match pop_call(values, argc, arg_names) {
+            Ok((func, args)) => self.call_value(func, args, callee, type_args),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Evaluate a node the work stack does not decompose
+    fn eval_direct(&mut self, node: &AstNode) -> Result<Value, RuntimeError> {
+        match node {
+            AstNode::Call { callee, args, type_args, arg_names, .. } => self.eval_call(callee, args, type_args, arg_names),
+            _ => self.eval_statement(node),
+        }
+    }
+
+    /// Push the tasks that evaluate an expression node.
+    ///
+    /// Returns the node back if the work stack does not decompose it and it
+    /// must be evaluated directly.
+    fn expand_expr<'a>(node: &'a AstNode, tasks: &mut Vec<ExprTask<'a>>) -> Option<&'a AstNode> {
+        match node {
+            AstNode::Triumph { value, .. } => {
+                tasks.push(ExprTask::Outcome(true));
+                tasks.push(ExprTask::Eval(value));
+            }
+            AstNode::Mishap { value, .. } => {
+                tasks.push(ExprTask::Outcome(false));
+                tasks.push(ExprTask::Eval(value));
+            }
+            AstNode::Present { value, .. } => {
+                tasks.push(ExprTask::Present);
+                tasks.push(ExprTask::Eval(value));
+            }
+            // NOTE: Borrow checking is not yet implemented, so a borrow
+            // just evaluates the inner value (pass-through)
+            AstNode::BorrowExpr { value: inner, .. } | AstNode::ExprStmt { expr: inner, .. } => {
+                tasks.push(ExprTask::Eval(inner));
+            }
+            AstNode::List { elements, .. } => {
+                tasks.push(ExprTask::List(elements.len()));
+                tasks.extend(elements.iter().rev().map(ExprTask::Eval));
+            }
+            AstNode::Tuple { elements, .. } => {
+                tasks.push(ExprTask::Tuple(elements.len()));
+                tasks.extend(elements.iter().rev().map(ExprTask::Eval));
+            }
+            AstNode::Map { entries, .. } => {
+                tasks.push(ExprTask::Map(entries));
+                tasks.extend(entries.iter().rev().map(|(_, value)| ExprTask::Eval(value)));
+            }
+            AstNode::Try { expr, .. } => {
+                tasks.push(ExprTask::Try);
+                tasks.push(ExprTask::Eval(expr));
+            }
+            AstNode::BinaryOp { left, op, right, .. } => {
+                tasks.push(ExprTask::Binary(*op));
+                tasks.push(ExprTask::Eval(right));
+                tasks.push(ExprTask::Eval(left));
+            }
+            AstNode::UnaryOp { op, operand, .. } => {
+                tasks.push(ExprTask::Unary(*op));
+                tasks.push(ExprTask::Eval(operand));
+            }
+            AstNode::InUnit { value, unit, .. } => {
+                tasks.push(ExprTask::InUnit(unit));
+                tasks.push(ExprTask::Eval(value));
+            }
+            // object.method(...) may dispatch to a trait implementation
+            AstNode::Call { callee, .. } if matches!(callee.as_ref(), AstNode::FieldAccess { .. }) => {
+                return Some(node);
+            }
+            AstNode::Call { callee, args, type_args, arg_names, .. } => {
+                tasks.push(ExprTask::Call { callee, type_args, arg_names, argc: args.len() });
+                tasks.extend(args.iter().rev().map(ExprTask::Eval));
+                tasks.push(ExprTask::Eval(callee));
+            }
+            AstNode::FieldAccess { object, field, .. } => {
+                tasks.push(ExprTask::Field(field));
+                tasks.push(ExprTask::Eval(object));
+            }
+            AstNode::IndexAccess { object, index, .. } => {
+                tasks.push(ExprTask::Index);
+                tasks.push(ExprTask::Eval(index));
+                tasks.push(ExprTask::Eval(object));
+            }
+            AstNode::Range { start, end, .. } => {
+                tasks.push(ExprTask::Range);
+                tasks.push(ExprTask::Eval(end));
+                tasks.push(ExprTask::Eval(start));
+            }
+            other => return Some(other),
+        }
+        None
+    }
 
-        match func {
-            Value::Chant { params, body, closure: _ } => {
-                // Check if function has variadic parameters
-                let has_variadic = params.last().is_some_and(|p| p.is_variadic);
-                let required_params = if has_variadic { params.len() - 1 } else { params.len() };
-
-                // Arity check
-                if has_variadic {
-                    // Variadic function: must have at least required_params arguments
-                    if args.len() < required_params {
-                        return Err(RuntimeError::ArityMismatch {
-                            expected: required_params,
-                            got: args.len(),
-                        });
-                    }
-                } else {
-                    // Regular function: must have exact number of arguments
-                    if params.len() != args.len() {
-                        return Err(RuntimeError::ArityMismatch {
-                            expected: params.len(),
-                            got: args.len(),
-                        });
+    /// Carry out a task of the work stack, short of evaluating anything
+    /// that can recurse, which is handed back to `eval_expr`.
+    ///
+    /// Kept out of `eval_expr` so its temporaries do not enlarge the frame that
+    /// stays live across nested calls.
+    #[inline(never)]
+    fn expr_step<'a>(
+        &mut self,
+        task: ExprTask<'a>,
+        tasks: &mut Vec<ExprTask<'a>>,
+        values: &mut Vec<Value>,
+    ) -> Result<ExprStep<'a>, RuntimeError> {
+        let value = match task {
+            ExprTask::Eval(node) => match self.qualified_member(node) {
+                Some(member) => member?,
+                None => match Self::expand_expr(node, tasks) {
+                    Some(direct) => return Ok(ExprStep::Direct(direct)),
+                    None => return Ok(ExprStep::Done),
+                },
+            },
+            ExprTask::Call { callee, type_args, arg_names, argc } => {
+                return Ok(ExprStep::Call { callee, type_args, arg_names, argc })
+            }
+            ExprTask::Outcome(success) => Value::Outcome {
+                success,
+                value: Box::new(pop_value(values)?),
+            },
+            ExprTask::Present => Value::Maybe {
+                present: true,
+                value: Some(Box::new(pop_value(values)?)),
+            },
+            ExprTask::List(count) => Value::List(pop_values(values, count)?),
+            ExprTask::Tuple(count) => Value::Tuple(pop_values(values, count)?),
+            ExprTask::Map(entries) => {
+                let items = pop_values(values, entries.len())?;
+                Value::Map(entries.iter().map(|(key, _)| key.clone()).zip(items).collect())
+            }
+            ExprTask::Try => {
+                let value = pop_value(values)?;
+                self.try_value(value)?
+            }
+            ExprTask::Binary(op) => {
+                let right = pop_value(values)?;
+                let left = pop_value(values)?;
+                let (left, right) = (left.unfrozen(), right.unfrozen());
+                match op {
+                    BinaryOperator::Less | BinaryOperator::Greater | BinaryOperator::LessEq | BinaryOperator::GreaterEq
+                        if matches!(left, Value::StructInstance { .. } | Value::VariantValue { .. }) =>
+                    {
+                        let ordering = self.compare_values(left, right)?;
+                        Value::Truth(match op {
+                            BinaryOperator::Less => ordering.is_lt(),
+                            BinaryOperator::Greater => ordering.is_gt(),
+                            BinaryOperator::LessEq => ordering.is_le(),
+                            _ => ordering.is_ge(),
+                        })
                     }
+                    _ => self.eval_binary_op(left, op, right)?,
                 }
+            }
+            ExprTask::Unary(op) => {
+                let operand = pop_value(values)?;
+                self.eval_unary_op(op, &operand)?
+            }
+            ExprTask::InUnit(unit) => match pop_value(values)? {
+                Value::Number(n) => Value::Number(unit_named(unit)?.from_base(n)),
+                other => {
+                    return Err(RuntimeError::TypeError {
+                        expected: "Number".to_string(),
+                        got: other.type_name().to_string(),
+                    })
+                }
+            },
+            ExprTask::Field(field) => field_value(pop_value(values)?, field)?,
+            ExprTask::Index => {
+                let index = pop_value(values)?;
+                let object = pop_value(values)?;
+                index_value(object, index)?
+            }
+            ExprTask::Range => {
+                let end = pop_value(values)?;
+                let start = pop_value(values)?;
+                range_value(start, end)?
+            }
+        };
+        values.push(value);
+        Ok(ExprStep::Done)
+    }
 
-                // Get function name if callee is an Ident (for TCO detection)
-                let func_name = match callee_node {
-                    AstNode::Ident { name, .. } => Some(name.clone()),
-                    _ => None,
-                };
-
-                // Trampoline loop for TCO
-                let mut current_args = args;
-                loop {
-                    // Push new scope for function call
-                    self.environment.push_scope();
+    /// Evaluate a node other than a work-stack expression
+    ///
+    /// This frame stays live across nested chant calls, so it only
+    /// dispatches the statements that commonly nest; the rest are evaluated
+    /// by `eval_other_statement`.
+    fn eval_statement(&mut self, node: &AstNode) -> Result<Value, RuntimeError> {
+        match node {
+            // bind x to 42
+            AstNode::BindStmt { name, value, .. } => self.eval_bind(name, value, false),
 
-                    // Bind parameters
-                    if has_variadic {
-                        // Bind regular parameters
-                        for (i, param) in params.iter().take(required_params).enumerate() {
-                            self.environment.define(param.name.clone(), current_args[i].clone());
-                        }
+            // weave counter as 0
+            AstNode::WeaveStmt { name, value, .. } => self.eval_bind(name, value, true),
 
-                        // Collect remaining arguments into a list for the variadic parameter
-                        let variadic_param = &params[required_params];
-                        let variadic_args: Vec<Value> = current_args[required_params..].to_vec();
-                        self.environment.define(variadic_param.name.clone(), Value::List(variadic_args));
-                    } else {
-                        // Regular parameter binding
-                        for (param, arg) in params.iter().zip(current_args.iter()) {
-                            self.environment.define(param.name.clone(), arg.clone());
-                        }
-                    }
+            // set counter to 10, set list[i] to 5, set obj.field to "value"
+            AstNode::SetStmt { target, value, .. } => self.eval_set(target, value),
 
-                    // Store function name for tail call detection
-                    if let Some(ref name) = func_name {
-                        self.environment.define("__current_function__".to_string(), Value::Text(name.clone()));
-                    }
+            // should condition then ... otherwise ... end
+            AstNode::IfStmt { condition, then_branch, else_branch, span } => {
+                self.eval_if(condition, then_branch, else_branch.as_deref(), span)
+            }
 
-                    // Execute function body
-                    let result = self.eval(&body);
+            // for each x in list then ... end
+            AstNode::ForStmt { variable, iterable, body, .. } => self.eval_for(variable, iterable, body),
 
-                    // Restore environment
-                    self.environment.pop_scope();
+            // whilst condition then ... end
+            AstNode::WhileStmt { condition, body, .. } => self.eval_while(condition, body),
 
-                    // Handle result
-                    match result {
-                        Err(RuntimeError::Return(val)) => return Ok(val),
-                        Err(RuntimeError::TailCall { function_name, args }) => {
-                            // Check if it's a recursive tail call
-                            if Some(&function_name) == func_name.as_ref() {
-                                // TCO: Loop with new args instead of recursing!
-                                current_args = args;
-                                continue;
-                            } else {
-                                // Not a recursive call, re-throw to propagate up
-                                return Err(RuntimeError::TailCall { function_name, args });
-                            }
-                        }
-                        other => return other,
-                    }
-                }
-            }
-            Value::NativeChant(native_fn) => {
-                // Check arity (None = variadic)
-                if let Some(expected) = native_fn.arity {
-                    if args.len() != expected {
-                        return Err(RuntimeError::ArityMismatch {
-                            expected,
-                            got: args.len(),
-                        });
-                    }
-                }
+            // yield result
+            AstNode::YieldStmt { value, .. } => self.eval_yield(value),
 
-                // Call native function
-                (native_fn.func)(&args)
-            }
-            Value::VariantConstructor { enum_name, variant_name, field_params, type_params } => {
-                // Phase 2/3: Create a variant value with the provided arguments
-                if field_params.len() != args.len() {
-                    return Err(RuntimeError::ArityMismatch {
-                        expected: field_params.len(),
-                        got: args.len(),
-                    });
-                }
+            // === Block ===
+            AstNode::Block { statements, .. } => self.eval_block(statements),
 
-                // Phase 3: Check type argument count for generic enums
-                if !type_params.is_empty() && !type_arg_names.is_empty()
-                    && type_params.len() != type_arg_names.len() {
-                        return Err(RuntimeError::Custom(format!(
-                            "Type argument mismatch: expected {} type arguments, got {}",
-                            type_params.len(),
-                            type_arg_names.len()
-                        )));
-                    }
+            // === Pattern Matching ===
+            AstNode::MatchStmt { value, arms, .. } => self.eval_match(value, arms, false),
+            AstNode::AttemptStmt { body, handlers, .. } => self.eval_attempt(body, handlers),
 
-                // Create the variant value with the arguments as fields
-                Ok(Value::VariantValue {
-                    enum_name,
-                    variant_name,
-                    fields: args,
-                    type_args: type_arg_names,  // Phase 3: Store type arguments
-                })
-            }
-            _ => Err(RuntimeError::NotCallable(func.type_name().to_string())),
+            _ => self.eval_other_statement(node),
         }
     }
 
-    /// Evaluate a single AST node
-    pub fn eval_node(&mut self, node: &AstNode) -> Result<Value, RuntimeError> {
+    /// Evaluate a node `eval_statement` does not dispatch itself
+    #[inline(never)]
+    fn eval_other_statement(&mut self, node: &AstNode) -> Result<Value, RuntimeError> {
         match node {
             // === Literals ===
             AstNode::Number { value: n, .. } => Ok(Value::Number(*n)),
+            AstNode::BigInt { value, .. } => Ok(Value::BigInt(value.clone())),
+            AstNode::Integer { value, .. } => Ok(Value::Integer(*value)),
+            AstNode::Measure { value, unit, .. } => Ok(Value::Number(unit_named(unit)?.to_base(*value))),
             AstNode::Text { value: s, .. } => Ok(Value::Text(s.clone())),
             AstNode::Truth { value: b, .. } => Ok(Value::Truth(*b)),
             AstNode::Nothing { .. } => Ok(Value::Nothing),
+            AstNode::Absent { .. } => Ok(Value::Maybe {
+                present: false,
+                value: None,
+            }),
 
-            // === Outcome constructors ===
-            AstNode::Triumph { value, .. } => {
-                let inner = self.eval_node(value)?;
-                Ok(Value::Outcome {
-                    success: true,
-                    value: Box::new(inner),
-                })
+            // === Variables ===
+            AstNode::Ident { name, slot: Some(slot), .. } => self.environment.get_resolved(name, *slot),
+            AstNode::Ident { name, .. } => self.environment.get(name),
+
+            // === Statements ===
+
+            // chant greet(name) then ... end
+            AstNode::ChantDef { name, params, return_type, body, .. } => {
+                self.eval_chant_def(name, params, return_type, body)
             }
 
-            AstNode::Mishap { value, .. } => {
-                let inner = self.eval_node(value)?;
-                Ok(Value::Outcome {
-                    success: false,
-                    value: Box::new(inner),
-                })
+            AstNode::FormDef { name, fields, deriving, .. } => self.eval_form_def(name, fields, deriving),
+
+            AstNode::VariantDef { name, type_params, variants, .. } => {
+                self.eval_variant_def(name, type_params, variants)
             }
 
-            // === Maybe constructors ===
-            AstNode::Present { value, .. } => {
-                let inner = self.eval_node(value)?;
-                Ok(Value::Maybe {
-                    present: true,
-                    value: Some(Box::new(inner)),
-                })
+            AstNode::AspectDef { name, type_params, methods, .. } => self.eval_aspect_def(name, type_params, methods),
+
+            AstNode::EmbodyStmt { aspect_name, type_args, target_type, methods, .. } => {
+                self.eval_embody(aspect_name, type_args, target_type, methods)
             }
 
-            AstNode::Absent { .. } => Ok(Value::Maybe {
-                present: false,
-                value: None,
-            }),
+            AstNode::StructLiteral { struct_name, fields, .. } => self.eval_struct_literal(struct_name, fields),
 
-            // === Borrow expression ===
-            // NOTE: Borrow checking is not yet implemented
-            // For now, just evaluate the inner value (pass-through)
-            AstNode::BorrowExpr { value, .. } => self.eval_node(value),
+            // === Loop Control Flow ===
+            AstNode::Break { .. } => {
+                Err(RuntimeError::BreakOutsideLoop)
+            }
 
-            // === Variables ===
-            AstNode::Ident { name, .. } => self.environment.get(name),
+            AstNode::Continue { .. } => {
+                Err(RuntimeError::ContinueOutsideLoop)
+            }
 
-            // === Lists ===
-            AstNode::List { elements, .. } => {
-                let mut values = Vec::new();
-                for elem in elements {
-                    values.push(self.eval_node(elem)?);
-                }
-                Ok(Value::List(values))
+            // === Expressions (dispatched to the work stack by eval_node) ===
+            AstNode::Triumph { .. }
+            | AstNode::Mishap { .. }
+            | AstNode::Present { .. }
+            | AstNode::BorrowExpr { .. }
+            | AstNode::List { .. }
+            | AstNode::Tuple { .. }
+            | AstNode::Map { .. }
+            | AstNode::Try { .. }
+            | AstNode::BinaryOp { .. }
+            | AstNode::UnaryOp { .. }
+            | AstNode::InUnit { .. }
+            | AstNode::Call { .. }
+            | AstNode::FieldAccess { .. }
+            | AstNode::IndexAccess { .. }
+            | AstNode::Range { .. }
+            | AstNode::ExprStmt { .. } => self.eval_expr(node),
+
+            // Dispatched by eval_statement
+            AstNode::BindStmt { .. }
+            | AstNode::WeaveStmt { .. }
+            | AstNode::SetStmt { .. }
+            | AstNode::IfStmt { .. }
+            | AstNode::ForStmt { .. }
+            | AstNode::WhileStmt { .. }
+            | AstNode::YieldStmt { .. }
+            | AstNode::Block { .. }
+            | AstNode::MatchStmt { .. }
+            | AstNode::AttemptStmt { .. } => self.eval_statement(node),
+
+            AstNode::DeferStmt { body, .. } => self.eval_defer(body),
+            AstNode::TogetherBlock { body, .. } => self.eval_together(body),
+
+            AstNode::MigrateStore { namespace, from, to, param, body, .. } => {
+                self.eval_migrate_store(namespace, *from, *to, param, body)
+            }
+            AstNode::RequestStmt { capability, justification, span } => self.eval_request(capability, justification, span),
+            // Verify blocks only run under the test runner
+            AstNode::VerifyBlock { .. } => Ok(Value::Nothing),
+            // Applied when parsing and analyzing
+            AstNode::Speaks { .. } => Ok(Value::Nothing),
+            AstNode::Pipeline { stages, .. } => self.eval_pipeline(stages),
+            AstNode::SeekExpr { .. } => {
+                Err(RuntimeError::Custom("World-Tree queries not yet implemented".to_string()))
             }
 
-            // === Maps ===
-            AstNode::Map { entries, .. } => {
-                let mut map = BTreeMap::new();
-                for (key, value_node) in entries {
-                    let value = self.eval_node(value_node)?;
-                    map.insert(key.clone(), value);
-                }
-                Ok(Value::Map(map))
+            // === Module System (Phase 4: Interpreter Support) ===
+            AstNode::ModuleDecl { name, body, exports, .. } => self.eval_module_decl(name, body, exports),
+
+            AstNode::Import { module_name, path, items, alias, .. } => {
+                self.eval_import(module_name, path, items.as_deref(), alias.as_deref())
             }
 
-            // === Statements ===
+            AstNode::Export { items: _, .. } => {
+                // Export statements are handled during ModuleDecl evaluation
+                // This is a no-op in the interpreter
+                // Exports are tracked when the module is declared
+                Ok(Value::Nothing)
+            }
 
-            // bind x to 42
-            AstNode::BindStmt { name, typ: _, value, .. } => {
-                // Type annotations are checked by semantic analyzer, ignored at runtime
-                let val = self.eval_node(value)?;
-                self.environment.define(name.clone(), val.clone());
-                Ok(val)
+            AstNode::ModuleAccess { module, member, .. } => self.eval_module_access(module, member),
+        }
+    }
+
+    /// Evaluate a `should` statement's taken branch
+    fn eval_if(
+        &mut self,
+        condition: &AstNode,
+        then_branch: &[AstNode],
+        else_branch: Option<&[AstNode]>,
+        span: &SourceSpan,
+    ) -> Result<Value, RuntimeError> {
+        match self.eval_condition(condition, span) {
+            Ok(true) => self.eval(then_branch),
+            Ok(false) => match else_branch {
+                Some(else_body) => self.eval(else_body),
+                None => Ok(Value::Nothing),
+            },
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Evaluate a block's statements in their own scope
+    fn eval_block(&mut self, statements: &[AstNode]) -> Result<Value, RuntimeError> {
+        self.environment.push_scope();
+        let result = self.eval(statements);
+        self.environment.pop_scope();
+        result
+    }
+
+    /// Evaluate `bind` (immutable) or `weave` (mutable) bindings
+    fn eval_bind(&mut self, name: &str, value: &AstNode, mutable: bool) -> Result<Value, RuntimeError> {
+        // Type annotations are checked by semantic analyzer, ignored at runtime
+        let val = self.eval_node(value)?;
+        if mutable {
+            self.environment.define_mut(name.to_string(), val.clone());
+        } else {
+            self.environment.define(name.to_string(), val.clone());
+        }
+        Ok(val)
+    }
+
+    /// Define a chant in the current environment
+    fn eval_chant_def(
+        &mut self,
+        name: &str,
+        params: &[Parameter],
+        return_type: &Option<TypeAnnotation>,
+        body: &[AstNode],
+    ) -> Result<Value, RuntimeError> {
+        // Calls run in the caller's environment (recursion resolves through
+        // the defining scope), so the closure only snapshots the current
+        // bindings. It must not embed the chant itself: nesting each chant
+        // inside its own closure made every definition copy all earlier
+        // ones, growing memory exponentially with the number of chants.
+        let chant = Value::Chant {
+            params: params.to_vec(),
+            body: body.to_vec(),
+            closure: self.environment.clone(),
+            return_type: return_type.clone(),
+        };
+
+        // Define in current environment
+        self.environment.define(name.to_string(), chant.clone());
+        Ok(chant)
+    }
+
+    /// Define a struct (`form`) in the current environment
+    fn eval_form_def(
+        &mut self,
+        name: &str,
+        fields: &[crate::ast::StructField],
+        deriving: &[String],
+    ) -> Result<Value, RuntimeError> {
+        // Create struct definition
+        let struct_def = Value::StructDef {
+            name: name.to_string(),
+            fields: fields.to_vec(),
+        };
+
+        // Define in current environment
+        self.environment.define(name.to_string(), struct_def.clone());
+
+        // A derived aspect is an embodiment without methods: Display,
+        // Ordered and Hashable fall back to their derived behavior
+        for aspect in deriving {
+            if !DERIVABLE_ASPECTS.contains(&aspect.as_str()) {
+                return Err(RuntimeError::Custom(format!("Cannot derive {} for {}", aspect, name)));
             }
+            self.eval_embody(aspect, &[], &TypeAnnotation::Named(name.to_string()), &[])?;
+        }
+        Ok(struct_def)
+    }
 
-            // weave counter as 0
-            AstNode::WeaveStmt { name, typ: _, value, .. } => {
-                // Type annotations are checked by semantic analyzer, ignored at runtime
-                let val = self.eval_node(value)?;
-                self.environment.define_mut(name.clone(), val.clone());
-                Ok(val)
+    /// Register a trait (`aspect`) definition
+    fn eval_aspect_def(
+        &mut self,
+        name: &str,
+        type_params: &[String],
+        methods: &[crate::ast::TraitMethod],
+    ) -> Result<Value, RuntimeError> {
+        // Phase 3: Store trait definition in the runtime registry
+        let trait_def = TraitDefinition {
+            name: name.to_string(),
+            type_params: type_params.to_vec(),
+            methods: methods.to_vec(),
+        };
+        self.trait_definitions.insert(name.to_string(), trait_def);
+        Ok(Value::Nothing)
+    }
+
+    /// Evaluate a capability request, producing a capability token
+    fn eval_request(&mut self, capability: &AstNode, justification: &str, span: &SourceSpan) -> Result<Value, RuntimeError> {
+        use crate::capability::AuditEvent;
+
+        // Capability-based security: Request permission to access a resource
+        //
+        // This creates an unforgeable capability token that represents permission
+        // to access the requested resource. The request, its justification and
+        // the decision all go into the capability audit log.
+        //
+        // Without a host policy every request is granted (permission checking
+        // will be enforced by AethelOS when the capability is actually used)
+
+        // Extract resource name from the capability expression
+        // Note: We DON'T evaluate the expression, just extract its name
+        let resource = crate::capability::capability_name(capability);
+
+        let requested = AuditEvent::Requested { justification: justification.to_string() };
+        self.audit(&resource, requested, span.clone());
+
+        // `Net.connect(host, port)` names an endpoint, which the network
+        // provider must allow whatever the policy says
+        let endpoint = match capability {
+            AstNode::Call { args, .. } if resource == crate::net::NET_CONNECT_CAPABILITY => Some(self.request_endpoint(args)?),
+            _ => None,
+        };
+        let allowed = |net: &Option<Box<dyn crate::net::NetProvider>>, (host, port): &(String, u16)| {
+            net.as_ref().is_some_and(|net| net.allows(host, *port))
+        };
+
+        let decision = if self.deterministic && crate::capability::is_nondeterministic(&resource) {
+            Err(format!("{} is nondeterministic and denied in deterministic mode", resource))
+        } else if let Some(endpoint) = endpoint.as_ref().filter(|endpoint| !allowed(&self.net, endpoint)) {
+            Err(format!("{}:{} is not on the host's network allowlist", endpoint.0, endpoint.1))
+        } else {
+            match self.capability_policy.as_mut() {
+                Some(policy) => policy.decide(&resource, justification),
+                None => Ok(()),
             }
+        };
+        if let Err(reason) = decision {
+            self.audit(&resource, AuditEvent::Denied { reason: reason.clone() }, span.clone());
+            return Err(RuntimeError::CapabilityDenied { capability: resource, reason });
+        }
+        self.audit(&resource, AuditEvent::Granted, span.clone());
+
+        // Create capability token
+        // In a real system, this would be cryptographically signed by the kernel
+        let mut permissions = vec!["access".to_string(), justification.to_string()];
+        if let Some((host, port)) = endpoint {
+            permissions.push(crate::net::endpoint_permission(&host, port));
+            self.net_grants.push((host, port));
+        }
+        Ok(Value::Capability { resource, permissions })
+    }
 
-            // set counter to 10, set list[i] to 5, set obj.field to "value"
-            AstNode::SetStmt { target, value, .. } => {
-                let val = self.eval_node(value)?;
+    /// Host and port named by `request Net.connect(host, port)`
+    fn request_endpoint(&mut self, args: &[AstNode]) -> Result<(String, u16), RuntimeError> {
+        let [host, port] = args else {
+            return Err(RuntimeError::ArityMismatch { expected: 2, got: args.len() });
+        };
+        match (self.eval_node(host)?.untainted(), self.eval_node(port)?.untainted()) {
+            (Value::Text(host), Value::Number(port)) if port % 1.0 == 0.0 && (0.0..=65535.0).contains(&port) => {
+                Ok((host, port as u16))
+            }
+            (host, port) => Err(RuntimeError::TypeError {
+                expected: "Text host and port from 0 to 65535".to_string(),
+                got: format!("{} and {}", host.type_name(), port.type_name()),
+            }),
+        }
+    }
 
-                match target.as_ref() {
-                    // Simple identifier: set x to 5
-                    AstNode::Ident { name, .. } => {
-                        self.environment.set(name, val.clone())?;
-                    }
-                    // Index access: set list[i] to 5
-                    AstNode::IndexAccess { object, index, .. } => {
-                        let obj_val = self.eval_node(object)?;
-                        let index_val = self.eval_node(index)?;
-
-                        match (obj_val, index_val) {
-                            (Value::List(mut items), Value::Number(idx)) => {
-                                let i = idx as usize;
-                                if i >= items.len() {
-                                    return Err(RuntimeError::Custom(format!(
-                                        "Index {} out of bounds for list of length {}",
-                                        i,
-                                        items.len()
-                                    )));
-                                }
-                                items[i] = val.clone();
+    /// Evaluate `set target to value` for variables, list/map elements and struct fields
+    #[inline(never)]
+    fn eval_set(&mut self, target: &AstNode, value: &AstNode) -> Result<Value, RuntimeError> {
+        let val = self.eval_node(value)?;
+        self.assign(target, val)
+    }
 
-                                // Update the original variable
-                                if let AstNode::Ident { name, .. } = object.as_ref() {
-                                    self.environment.set(name, Value::List(items))?;
-                                } else {
-                                    return Err(RuntimeError::Custom(
-                                        "Can only assign to list elements of variables".to_string(),
-                                    ));
-                                }
-                            }
-                            (Value::Map(mut map), Value::Text(key)) => {
-                                map.insert(key, val.clone());
+    /// Store an evaluated value into an assignment target
+    fn assign(&mut self, target: &AstNode, val: Value) -> Result<Value, RuntimeError> {
+        match target {
+            // Simple identifier: set x to 5
+            AstNode::Ident { name, slot, .. } => {
+                match slot {
+                    Some(slot) => self.environment.set_resolved(name, *slot, val.clone())?,
+                    None => self.environment.set(name, val.clone())?,
+                }
+            }
+            // Index access: set list[i] to 5
+            AstNode::IndexAccess { object, index, .. } => {
+                // A container keeps its taint when parts of it are replaced
+                let (obj_val, sources) = self.eval_node(object)?.split_taint();
+                let (index_val, _) = self.eval_node(index)?.split_taint();
+                check_not_frozen(object, &obj_val)?;
+
+                match (obj_val, index_val) {
+                    (Value::List(mut items), idx @ (Value::Number(_) | Value::Integer(_))) => {
+                        let i = list_position(&idx)?;
+                        if i >= items.len() {
+                            return Err(RuntimeError::Custom(format!(
+                                "Index {} out of bounds for list of length {}",
+                                i,
+                                items.len()
+                            )));
+                        }
+                        items[i] = val.clone();
 
-                                // Update the original variable
-                                if let AstNode::Ident { name, .. } = object.as_ref() {
-                                    self.environment.set(name, Value::Map(map))?;
-                                } else {
-                                    return Err(RuntimeError::Custom(
-                                        "Can only assign to map elements of variables".to_string(),
-                                    ));
-                                }
-                            }
-                            _ => {
-                                return Err(RuntimeError::Custom(
-                                    "Invalid index assignment".to_string(),
-                                ));
-                            }
+                        // Update the original variable
+                        if let AstNode::Ident { name, .. } = object.as_ref() {
+                            self.environment.set(name, Value::List(items).taint(&sources))?;
+                        } else {
+                            return Err(RuntimeError::Custom(
+                                "Can only assign to list elements of variables".to_string(),
+                            ));
                         }
                     }
-                    // Field access: set obj.field to "value"
-                    AstNode::FieldAccess { object, field, .. } => {
-                        let mut obj_val = self.eval_node(object)?;
+                    (Value::Map(mut map), Value::Text(key)) => {
+                        map.insert(key, val.clone());
 
-                        if let Value::StructInstance { ref mut fields, .. } = obj_val {
-                            fields.insert(field.clone(), val.clone());
-
-                            // Update the original variable
-                            if let AstNode::Ident { name, .. } = object.as_ref() {
-                                self.environment.set(name, obj_val)?;
-                            } else {
-                                return Err(RuntimeError::Custom(
-                                    "Can only assign to fields of variables".to_string(),
-                                ));
-                            }
+                        // Update the original variable
+                        if let AstNode::Ident { name, .. } = object.as_ref() {
+                            self.environment.set(name, Value::Map(map).taint(&sources))?;
                         } else {
-                            return Err(RuntimeError::Custom(format!(
-                                "Cannot access field on non-struct value: {:?}",
-                                obj_val
-                            )));
+                            return Err(RuntimeError::Custom(
+                                "Can only assign to map elements of variables".to_string(),
+                            ));
                         }
                     }
+                    (Value::Tuple(_), _) => {
+                        return Err(RuntimeError::Custom(
+                            "Tuples cannot be changed; build a new one instead".to_string(),
+                        ));
+                    }
                     _ => {
-                        return Err(RuntimeError::Custom(format!(
-                            "Invalid assignment target: {:?}",
-                            target
-                        )));
+                        return Err(RuntimeError::Custom(
+                            "Invalid index assignment".to_string(),
+                        ));
                     }
                 }
-
-                Ok(val)
             }
+            // Field access: set obj.field to "value"
+            AstNode::FieldAccess { object, field, .. } => {
+                let (mut obj_val, sources) = self.eval_node(object)?.split_taint();
+                check_not_frozen(object, &obj_val)?;
 
-            // should condition then ... otherwise ... end
-            AstNode::IfStmt { condition, then_branch, else_branch, .. } => {
-                let cond_val = self.eval_node(condition)?;
-                if cond_val.is_truthy() {
-                    self.eval(then_branch)
-                } else if let Some(else_body) = else_branch {
-                    self.eval(else_body)
+                if let Value::StructInstance { ref mut fields, .. } = obj_val {
+                    fields.insert(field.clone(), val.clone());
+
+                    // Update the original variable
+                    if let AstNode::Ident { name, .. } = object.as_ref() {
+                        self.environment.set(name, obj_val.taint(&sources))?;
+                    } else {
+                        return Err(RuntimeError::Custom(
+                            "Can only assign to fields of variables".to_string(),
+                        ));
+                    }
                 } else {
-                    Ok(Value::Nothing)
+                    return Err(RuntimeError::Custom(format!(
+                        "Cannot access field on non-struct value: {:?}",
+                        obj_val
+                    )));
                 }
             }
+            _ => {
+                return Err(RuntimeError::Custom(format!(
+                    "Invalid assignment target: {:?}",
+                    target
+                )));
+            }
+        }
 
-            // for each x in list then ... end
-            AstNode::ForStmt { variable, iterable, body, .. } => {
-                let iter_val = self.eval_node(iterable)?;
-
-                let items = match iter_val {
-                    Value::List(ref items) => items.clone(),
-                    Value::Range { start, end } => {
-                        // Generate range values
-                        let mut items = Vec::new();
-                        let start_num = match start.as_ref() {
-                            Value::Number(n) => *n as i64,
-                            _ => return Err(RuntimeError::TypeError {
-                                expected: "Number".to_string(),
-                                got: start.type_name().to_string(),
-                            }),
-                        };
-                        let end_num = match end.as_ref() {
-                            Value::Number(n) => *n as i64,
-                            _ => return Err(RuntimeError::TypeError {
-                                expected: "Number".to_string(),
-                                got: end.type_name().to_string(),
-                            }),
-                        };
-                        for i in start_num..end_num {
-                            items.push(Value::Number(i as f64));
-                        }
-                        items
-                    }
-                    _ => return Err(RuntimeError::NotIterable(iter_val.type_name().to_string())),
-                };
+        Ok(val)
+    }
 
-                let mut result = Value::Nothing;
-                for item in items {
-                    self.environment.push_scope();
-                    self.environment.define(variable.clone(), item);
+    /// Evaluate a `for each` loop over a list, set, range or iterator
+    ///
+    /// Forms and variants embodying `Iterable` are iterated through their
+    /// `next` method.
+    #[inline(never)]
+    fn eval_for(&mut self, variable: &str, iterable: &AstNode, body: &[AstNode]) -> Result<Value, RuntimeError> {
+        let (items, sources) = match self.for_items(iterable) {
+            Ok(ForItems::List(items, sources)) => (items, sources),
+            Ok(ForItems::Iterator(iterator, sources)) => return self.eval_for_iterator(variable, iterator, body, &sources),
+            Err(e) => return Err(e),
+        };
 
-                    // Handle break/continue control flow
-                    match self.eval(body) {
-                        Ok(val) => result = val,
-                        Err(RuntimeError::BreakOutsideLoop) => {
-                            // Break exits the loop immediately
-                            self.environment.pop_scope();
-                            break;
-                        }
-                        Err(RuntimeError::ContinueOutsideLoop) => {
-                            // Continue skips to next iteration
-                            self.environment.pop_scope();
-                            continue;
-                        }
-                        Err(e) => {
-                            // All other errors propagate up
-                            self.environment.pop_scope();
-                            return Err(e);
-                        }
-                    }
+        let mut result = Value::Nothing;
+        for item in items {
+            match self.eval_for_round(variable, item, &sources, body, &mut result) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(result)
+    }
 
-                    self.environment.pop_scope();
+    /// Evaluate the iterable of a `for each` loop into the items to loop over
+    ///
+    /// Kept apart from `eval_for` so the loop's frame, live across every
+    /// nested call in its body, stays small.
+    #[inline(never)]
+    fn for_items(&mut self, iterable: &AstNode) -> Result<ForItems, RuntimeError> {
+        // Each item of a tainted iterable is tainted
+        let (iter_val, sources) = self.eval_node(iterable)?.split_taint();
+
+        let items = match iter_val {
+            Value::List(ref items) => items.clone(),
+            Value::Set(ref elements) => elements.iter().map(SetElement::to_value).collect(),
+            Value::Frozen { value } => match *value {
+                Value::List(items) => items.into_iter().map(Value::freeze).collect(),
+                other => return Err(RuntimeError::NotIterable(other.type_name().to_string())),
+            },
+            Value::Range { start, end } => {
+                // Generate range values
+                let mut items = Vec::new();
+                let start_num = match start.as_ref() {
+                    Value::Number(n) => *n as i64,
+                    _ => return Err(RuntimeError::TypeError {
+                        expected: "Number".to_string(),
+                        got: start.type_name().to_string(),
+                    }),
+                };
+                let end_num = match end.as_ref() {
+                    Value::Number(n) => *n as i64,
+                    _ => return Err(RuntimeError::TypeError {
+                        expected: "Number".to_string(),
+                        got: end.type_name().to_string(),
+                    }),
+                };
+                for i in start_num..end_num {
+                    items.push(Value::Number(i as f64));
                 }
-                Ok(result)
+                items
             }
+            Value::Iterator { .. } => return Ok(ForItems::Iterator(iter_val, sources)),
+            _ => match self.aspect_iterator(&iter_val) {
+                Some(iterator) => return Ok(ForItems::Iterator(iterator, sources)),
+                None => return Err(RuntimeError::NotIterable(iter_val.type_name().to_string())),
+            },
+        };
+        Ok(ForItems::List(items, sources))
+    }
 
-            // whilst condition then ... end
-            AstNode::WhileStmt { condition, body, .. } => {
-                let mut result = Value::Nothing;
-                loop {
-                    let cond_val = self.eval_node(condition)?;
-                    if !cond_val.is_truthy() {
-                        break;
-                    }
+    /// Evaluate a `for each` loop that advances an iterator until it is
+    /// exhausted
+    fn eval_for_iterator(
+        &mut self,
+        variable: &str,
+        mut iterator: Value,
+        body: &[AstNode],
+        sources: &[String],
+    ) -> Result<Value, RuntimeError> {
+        let mut result = Value::Nothing;
+        loop {
+            let (next, item) = self.advance_iterator(iterator)?;
+            iterator = next;
+            let Some(item) = item else { break };
+            if !self.eval_for_round(variable, item, sources, body, &mut result)? {
+                break;
+            }
+        }
+        Ok(result)
+    }
 
-                    // Handle break/continue control flow
-                    match self.eval(body) {
-                        Ok(val) => result = val,
-                        Err(RuntimeError::BreakOutsideLoop) => {
-                            // Break exits the loop immediately
-                            break;
-                        }
-                        Err(RuntimeError::ContinueOutsideLoop) => {
-                            // Continue re-evaluates the condition (next iteration)
-                            continue;
-                        }
-                        Err(e) => {
-                            // All other errors propagate up
-                            return Err(e);
-                        }
+    /// Run one round of a `for each` body with the loop variable bound to
+    /// `item` (tainted by `sources`), returning false once the body breaks
+    /// out of the loop
+    fn eval_for_round(
+        &mut self,
+        variable: &str,
+        item: Value,
+        sources: &[String],
+        body: &[AstNode],
+        result: &mut Value,
+    ) -> Result<bool, RuntimeError> {
+        let begun = self.begin_loop_round(variable, item, sources);
+        if begun.is_err() {
+            return begun.map(|()| false);
+        }
+
+        // Handle break/continue control flow
+        let outcome = match self.eval(body) {
+            Ok(val) => {
+                *result = val;
+                Ok(true)
+            }
+            // Break exits the loop immediately
+            Err(RuntimeError::BreakOutsideLoop) => Ok(false),
+            // Continue skips to next iteration
+            Err(RuntimeError::ContinueOutsideLoop) => Ok(true),
+            // All other errors propagate up
+            Err(e) => Err(e),
+        };
+
+        self.environment.pop_scope();
+        outcome
+    }
+
+    /// Start a round of a `for each` loop: a new scope with the loop
+    /// variable bound to `item`, tainted by `sources`
+    #[inline(never)]
+    fn begin_loop_round(&mut self, variable: &str, item: Value, sources: &[String]) -> Result<(), RuntimeError> {
+        self.safepoint()?;
+        self.environment.push_scope();
+        self.environment.define(variable.to_string(), item.taint(sources));
+        Ok(())
+    }
+
+    /// Wrap a form or variant embodying `Iterable` in an iterator, or
+    /// `None` if its type does not embody one
+    fn aspect_iterator(&self, value: &Value) -> Option<Value> {
+        if !matches!(value, Value::StructInstance { .. } | Value::VariantValue { .. }) {
+            return None;
+        }
+        let type_name = self.error_type_string(value);
+        self.aspect_impl("Iterable", &type_name)?;
+        Some(Value::Iterator {
+            iterator_type: type_name,
+            state: Box::new(IteratorState::Aspect { value: Box::new(value.clone()) }),
+        })
+    }
+
+    /// Advance an iterator by one element
+    ///
+    /// Returns the iterator's next state and the element, or `None` once it
+    /// is exhausted. `Map` and `Filter` call their chants; `Aspect` states
+    /// call the `next` method of their `Iterable` embodiment.
+    fn advance_iterator(&mut self, iterator: Value) -> Result<(Value, Option<Value>), RuntimeError> {
+        let Value::Iterator { iterator_type, state } = iterator else {
+            return Err(RuntimeError::TypeError {
+                expected: "Iterator".to_string(),
+                got: iterator.type_name().to_string(),
+            });
+        };
+        let (state, item) = match *state {
+            IteratorState::List { elements, index } => {
+                let item = elements.get(index).cloned();
+                let index = if item.is_some() { index + 1 } else { index };
+                (IteratorState::List { elements, index }, item)
+            }
+            IteratorState::Range { current, end, step } if current < end => {
+                (IteratorState::Range { current: current + step, end, step }, Some(Value::Number(current)))
+            }
+            exhausted @ (IteratorState::Range { .. } | IteratorState::Empty) => (exhausted, None),
+            IteratorState::Map { inner, func } => {
+                let (inner, item) = self.advance_iterator(*inner)?;
+                let item = match item {
+                    Some(item) => Some(self.apply(&func, vec![item])?),
+                    None => None,
+                };
+                (IteratorState::Map { inner: Box::new(inner), func }, item)
+            }
+            IteratorState::Filter { inner, predicate } => {
+                let mut inner = *inner;
+                let item = loop {
+                    self.safepoint()?;
+                    let (next, item) = self.advance_iterator(inner)?;
+                    inner = next;
+                    match item {
+                        Some(item) if !self.apply(&predicate, vec![item.clone()])?.is_truthy() => continue,
+                        item => break item,
                     }
-                }
-                Ok(result)
+                };
+                (IteratorState::Filter { inner: Box::new(inner), predicate }, item)
+            }
+            IteratorState::Take { inner, remaining: 0 } => (IteratorState::Take { inner, remaining: 0 }, None),
+            IteratorState::Take { inner, remaining } => {
+                let (inner, item) = self.advance_iterator(*inner)?;
+                (IteratorState::Take { inner: Box::new(inner), remaining: remaining - 1 }, item)
+            }
+            IteratorState::Aspect { value } => {
+                let (value, item) = self.next_from_aspect(*value)?;
+                (IteratorState::Aspect { value: Box::new(value) }, item)
+            }
+            IteratorState::Stream { source, unit } => {
+                let item = self.read_stream(&source, unit)?;
+                (IteratorState::Stream { source, unit }, item)
             }
+        };
+        Ok((Value::Iterator { iterator_type, state: Box::new(state) }, item))
+    }
 
-            // chant greet(name) then ... end
-            AstNode::ChantDef { name, params, return_type: _, body, .. } => {
-                // Clone environment and add function to it for recursion support
-                let mut closure_env = self.environment.clone();
-
-                // Create the function value
-                let chant = Value::Chant {
-                    params: params.clone(),
-                    body: body.clone(),
-                    closure: closure_env.clone(),
-                };
+    /// Call `next(self)` of a value's `Iterable` embodiment, returning
+    /// `self` as `next` left it and the element it yielded
+    ///
+    /// `self` is mutable inside `next`, so an iterator records its position
+    /// by `set`ting its own fields.
+    fn next_from_aspect(&mut self, value: Value) -> Result<(Value, Option<Value>), RuntimeError> {
+        let type_name = self.error_type_string(&value);
+        let next = self.aspect_impl("Iterable", &type_name).and_then(|trait_impl| {
+            let body = trait_impl.methods.get("next")?.clone();
+            let params = trait_impl.method_params.get("next")?.clone();
+            let return_type = trait_impl.method_return_types.get("next").cloned().flatten();
+            Some((body, params, return_type))
+        });
+        let Some((body, params, return_type)) = next else {
+            return Err(RuntimeError::Custom(format!("{} does not embody Iterable", type_name)));
+        };
 
-                // Add function to its own closure so it can call itself recursively
-                closure_env.define(name.clone(), chant.clone());
+        match self.call_trait_method_mut(&body, &params, return_type, value, &[])? {
+            (Value::Maybe { present: true, value: Some(item) }, value) => Ok((value, Some(*item))),
+            (Value::Maybe { present: false, .. }, value) => Ok((value, None)),
+            (other, _) => Err(RuntimeError::TypeError {
+                expected: "Maybe".to_string(),
+                got: other.type_name().to_string(),
+            }),
+        }
+    }
 
-                // Update the closure to include the function itself
-                let chant = Value::Chant {
-                    params: params.clone(),
-                    body: body.clone(),
-                    closure: closure_env,
-                };
+    /// Advance an iterator until it is exhausted, gathering its elements
+    fn collect_iterator(&mut self, mut iterator: Value) -> Result<Vec<Value>, RuntimeError> {
+        let mut items = Vec::new();
+        loop {
+            self.safepoint()?;
+            let (next, item) = self.advance_iterator(iterator)?;
+            let Some(item) = item else { return Ok(items) };
+            iterator = next;
+            items.push(item);
+        }
+    }
 
-                // Define in current environment
-                self.environment.define(name.clone(), chant.clone());
-                Ok(chant)
-            }
+    /// Combine an iterator's elements into `acc` with `func(acc, element)`
+    fn fold_iterator(&mut self, mut iterator: Value, mut acc: Value, func: &Value) -> Result<Value, RuntimeError> {
+        loop {
+            self.safepoint()?;
+            let (next, item) = self.advance_iterator(iterator)?;
+            let Some(item) = item else { return Ok(acc) };
+            iterator = next;
+            acc = self.apply(func, vec![acc, item])?;
+        }
+    }
 
-            AstNode::FormDef { name, fields, .. } => {
-                // Create struct definition
-                let struct_def = Value::StructDef {
-                    name: name.clone(),
-                    fields: fields.clone(),
-                };
+    /// Call a chant value from the runtime rather than from a call expression
+    fn apply(&mut self, func: &Value, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        let callee = AstNode::Nothing { span: SourceSpan::unknown() };
+        self.call_value(func.clone(), args, &callee, &[])
+    }
 
-                // Define in current environment
-                self.environment.define(name.clone(), struct_def.clone());
-                Ok(struct_def)
+    /// Evaluate a `whilst` loop
+    #[inline(never)]
+    fn eval_while(&mut self, condition: &AstNode, body: &[AstNode]) -> Result<Value, RuntimeError> {
+        let mut result = Value::Nothing;
+        loop {
+            match self.while_condition(condition) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => return Err(e),
             }
 
-            AstNode::VariantDef { name, type_params, variants, .. } => {
-                // Phase 1b/3: Create enum definition and register variant constructors
-
-                // Create and store enum definition
-                let variant_def = Value::VariantDef {
-                    name: name.clone(),
-                    type_params: type_params.clone(),
-                    variants: variants.clone(),
-                };
-                self.environment.define(name.clone(), variant_def.clone());
-
-                // Register each variant as a constructor
-                for variant in variants {
-                    if variant.fields.is_empty() {
-                        // Unit variant (Phase 1): register as a direct value
-                        // For generic enums, unit variants don't carry type info directly
-                        let variant_value = Value::VariantValue {
-                            enum_name: name.clone(),
-                            variant_name: variant.name.clone(),
-                            fields: Vec::new(),
-                            type_args: Vec::new(),  // Phase 3: Empty for now, will be filled on use
-                        };
-                        self.environment.define(variant.name.clone(), variant_value);
-                    } else {
-                        // Variant with data (Phase 2/3): create a constructor function
-                        let constructor = Value::VariantConstructor {
-                            enum_name: name.clone(),
-                            variant_name: variant.name.clone(),
-                            field_params: variant.fields.clone(),
-                            type_params: type_params.clone(),  // Phase 3: Store type params
-                        };
-                        self.environment.define(variant.name.clone(), constructor);
-                    }
+            // Handle break/continue control flow
+            match self.eval(body) {
+                Ok(val) => result = val,
+                Err(RuntimeError::BreakOutsideLoop) => {
+                    // Break exits the loop immediately
+                    break;
+                }
+                Err(RuntimeError::ContinueOutsideLoop) => {
+                    // Continue re-evaluates the condition (next iteration)
+                    continue;
+                }
+                Err(e) => {
+                    // All other errors propagate up
+                    return Err(e);
                 }
-
-                Ok(variant_def)
             }
+        }
+        Ok(result)
+    }
 
-            AstNode::AspectDef { name, type_params, methods, .. } => {
-                // Phase 3: Store trait definition in the runtime registry
-                let trait_def = TraitDefinition {
-                    name: name.clone(),
-                    type_params: type_params.clone(),
-                    methods: methods.clone(),
-                };
-                self.trait_definitions.insert(name.clone(), trait_def);
-                Ok(Value::Nothing)
-            }
+    /// Check the condition of a `whilst` loop before another round
+    #[inline(never)]
+    fn while_condition(&mut self, condition: &AstNode) -> Result<bool, RuntimeError> {
+        self.safepoint()?;
+        Ok(self.eval_node(condition)?.is_truthy())
+    }
 
-            AstNode::EmbodyStmt { aspect_name, type_args, target_type, methods, .. } => {
-                // Phase 3: Store trait implementation in the runtime registry
+    /// Define an enum and register its variant constructors
+    fn eval_variant_def(
+        &mut self,
+        name: &str,
+        type_params: &[String],
+        variants: &[crate::ast::VariantCase],
+    ) -> Result<Value, RuntimeError> {
+        // Phase 1b/3: Create enum definition and register variant constructors
 
-                // Create implementation key
-                let target_type_str = self.type_annotation_to_string(target_type);
-                let impl_key = TraitImplKey {
-                    aspect_name: aspect_name.clone(),
-                    target_type: target_type_str,
+        // Create and store enum definition
+        let variant_def = Value::VariantDef {
+            name: name.to_string(),
+            type_params: type_params.to_vec(),
+            variants: variants.to_vec(),
+        };
+        self.environment.define(name.to_string(), variant_def.clone());
+
+        // Register each variant as a constructor
+        for variant in variants {
+            if variant.fields.is_empty() {
+                // Unit variant (Phase 1): register as a direct value
+                // For generic enums, unit variants don't carry type info directly
+                let variant_value = Value::VariantValue {
+                    enum_name: name.to_string(),
+                    variant_name: variant.name.clone(),
+                    fields: Vec::new(),
+                    type_args: Vec::new(),  // Phase 3: Empty for now, will be filled on use
                 };
+                self.environment.define(variant.name.clone(), variant_value);
+            } else {
+                // Variant with data (Phase 2/3): create a constructor function
+                let constructor = Value::VariantConstructor {
+                    enum_name: name.to_string(),
+                    variant_name: variant.name.clone(),
+                    field_params: variant.fields.clone(),
+                    type_params: type_params.to_vec(),  // Phase 3: Store type params
+                };
+                self.environment.define(variant.name.clone(), constructor);
+            }
+        }
 
-                // Extract method bodies and parameters
-                let mut method_bodies = BTreeMap::new();
-                let mut method_params = BTreeMap::new();
-
-                for method_node in methods {
-                    if let AstNode::ChantDef { name, params, body, .. } = method_node {
-                        // Extract parameter names (skip 'self')
-                        let param_list = params.clone();
-                        method_params.insert(name.clone(), param_list);
-                        method_bodies.insert(name.clone(), body.clone());
-                    }
-                }
+        Ok(variant_def)
+    }
 
-                // Store the implementation
-                let trait_impl = TraitImplementation {
-                    aspect_name: aspect_name.clone(),
-                    type_args: type_args.clone(),
-                    target_type: target_type.clone(),
-                    methods: method_bodies,
-                    method_params,
-                };
+    /// Register a trait implementation (`embody Aspect for Type`)
+    fn eval_embody(
+        &mut self,
+        aspect_name: &str,
+        type_args: &[TypeAnnotation],
+        target_type: &TypeAnnotation,
+        methods: &[AstNode],
+    ) -> Result<Value, RuntimeError> {
+        // Phase 3: Store trait implementation in the runtime registry
+
+        // Create implementation key
+        let target_type_str = self.type_annotation_to_string(target_type);
+        let impl_key = TraitImplKey {
+            aspect_name: aspect_name.to_string(),
+            target_type: target_type_str,
+            type_args: type_args.iter().map(|ta| self.type_annotation_to_string(ta)).collect(),
+        };
 
-                self.trait_implementations.insert(impl_key, trait_impl);
-                Ok(Value::Nothing)
+        // Extract method bodies and parameters
+        let mut method_bodies = BTreeMap::new();
+        let mut method_params = BTreeMap::new();
+        let mut method_return_types = BTreeMap::new();
+
+        for method_node in methods {
+            if let AstNode::ChantDef { name, params, return_type, body, .. } = method_node {
+                // Extract parameter names (skip 'self')
+                let param_list = params.clone();
+                method_params.insert(name.clone(), param_list);
+                method_return_types.insert(name.clone(), return_type.clone());
+                method_bodies.insert(name.clone(), body.clone());
             }
+        }
 
-            AstNode::StructLiteral { struct_name, fields: field_values, .. } => {
-                // Look up the struct definition
-                let struct_def = self.environment.get(struct_name)?;
-
-                match struct_def {
-                    Value::StructDef { name: _, fields } => {
-                        // Evaluate all field values
-                        let mut evaluated_fields = BTreeMap::new();
-                        for (field_name, field_expr) in field_values {
-                            let value = self.eval_node(field_expr)?;
-                            evaluated_fields.insert(field_name.clone(), value);
-                        }
+        // Store the implementation
+        let trait_impl = TraitImplementation {
+            aspect_name: aspect_name.to_string(),
+            type_args: type_args.to_vec(),
+            target_type: target_type.clone(),
+            methods: method_bodies,
+            method_params,
+            method_return_types,
+        };
 
-                        // Check that all required fields are provided and types match
-                        for field in &fields {
-                            if !evaluated_fields.contains_key(&field.name) {
-                                return Err(RuntimeError::Custom(
-                                    format!("Missing field '{}' in struct '{}'", field.name, struct_name)
-                                ));
-                            }
+        self.trait_implementations.insert(impl_key, trait_impl);
+        Ok(Value::Nothing)
+    }
 
-                            // Validate field type matches declaration
-                            let value = &evaluated_fields[&field.name];
-                            if !self.value_matches_type(value, &field.typ) {
-                                return Err(RuntimeError::TypeError {
-                                    expected: self.type_annotation_to_string(&field.typ),
-                                    got: value.type_name().to_string(),
-                                });
-                            }
-                        }
+    /// Evaluate a struct literal, checking fields against the struct definition
+    fn eval_struct_literal(
+        &mut self,
+        struct_name: &str,
+        field_values: &[(String, AstNode)],
+    ) -> Result<Value, RuntimeError> {
+        // Look up the struct definition
+        let struct_def = self.environment.get(struct_name)?;
+
+        match struct_def {
+            Value::StructDef { name: _, fields } => {
+                // Evaluate all field values
+                let mut evaluated_fields = BTreeMap::new();
+                for (field_name, field_expr) in field_values {
+                    let value = self.eval_node(field_expr)?;
+                    evaluated_fields.insert(field_name.clone(), value);
+                }
 
-                        // Create struct instance
-                        Ok(Value::StructInstance {
-                            struct_name: struct_name.clone(),
-                            fields: evaluated_fields,
-                        })
+                // Check that all required fields are provided and types match
+                for field in &fields {
+                    if !evaluated_fields.contains_key(&field.name) {
+                        return Err(RuntimeError::Custom(
+                            format!("Missing field '{}' in struct '{}'", field.name, struct_name)
+                        ));
                     }
-                    _ => Err(RuntimeError::TypeError {
-                        expected: "struct definition".to_string(),
-                        got: struct_def.type_name().to_string(),
-                    }),
-                }
-            }
 
-            // yield result
-            AstNode::YieldStmt { value, .. } => {
-                // Check if we're yielding a call (potential tail call)
-                if let AstNode::Call { callee, args, .. } = value.as_ref() {
-                    // Check if callee is an identifier
-                    if let AstNode::Ident { name: func_name, .. } = callee.as_ref() {
-                        // Check if it's a tail call to the current function
-                        if let Ok(Value::Text(current_func)) = self.environment.get("__current_function__") {
-                            if func_name == &current_func {
-                                // This is a tail-recursive call!
-                                // Evaluate args and throw TailCall instead of Return
-                                let arg_vals: Result<Vec<Value>, RuntimeError> =
-                                    args.iter().map(|arg| self.eval_node(arg)).collect();
-                                let arg_vals = arg_vals?;
-
-                                return Err(RuntimeError::TailCall {
-                                    function_name: func_name.clone(),
-                                    args: arg_vals,
-                                });
-                            }
-                        }
+                    // Validate field type matches declaration
+                    let value = &evaluated_fields[&field.name];
+                    if !self.value_matches_type(value, &field.typ) {
+                        return Err(RuntimeError::TypeError {
+                            expected: self.type_annotation_to_string(&field.typ),
+                            got: value.type_name().to_string(),
+                        });
                     }
                 }
 
-                // Not a tail call, evaluate normally
-                let val = self.eval_node(value)?;
-                Err(RuntimeError::Return(val))
-            }
-
-            // === Loop Control Flow ===
-            AstNode::Break { .. } => {
-                Err(RuntimeError::BreakOutsideLoop)
+                // Create struct instance
+                Ok(Value::StructInstance {
+                    struct_name: struct_name.to_string(),
+                    fields: evaluated_fields,
+                })
             }
+            _ => Err(RuntimeError::TypeError {
+                expected: "struct definition".to_string(),
+                got: struct_def.type_name().to_string(),
+            }),
+        }
+    }
 
-            AstNode::Continue { .. } => {
-                Err(RuntimeError::ContinueOutsideLoop)
-            }
+    /// `validate(value, Form)`: check a Map (a decoded message, say) against
+    /// a form's fields
+    ///
+    /// Returns `Triumph` of the form instance, with nested Maps turned into
+    /// instances of their field's form, or `Mishap` of text naming the path
+    /// of the first problem, like `Job.tags[2]: expected Text, got Number`.
+    /// Keys the form does not declare are rejected. A `Maybe<T>` field may
+    /// be missing or `nothing` (giving `Absent`) or hold a plain `T` (giving
+    /// `Present`).
+    fn validate(&self, value: &Value, form: &Value) -> Result<Value, RuntimeError> {
+        let Value::StructDef { name, .. } = form else {
+            return Err(RuntimeError::TypeError {
+                expected: "form definition".to_string(),
+                got: form.type_name().to_string(),
+            });
+        };
+        Ok(match self.conform(value, &TypeAnnotation::Named(name.clone()), name) {
+            Ok(instance) => Value::Outcome { success: true, value: Box::new(instance) },
+            Err(problem) => Value::Outcome { success: false, value: Box::new(Value::Text(problem)) },
+        })
+    }
 
-            // Try operator: expr?
-            AstNode::Try { expr, .. } => {
-                let value = self.eval_node(expr)?;
-
-                // Check if value is an Outcome
-                match value {
-                    Value::Outcome { success, value: boxed_val } => {
-                        if success {
-                            // Triumph: unwrap the value
-                            Ok(*boxed_val)
-                        } else {
-                            // Mishap: propagate the error by returning it
-                            Err(RuntimeError::Return(Value::Outcome {
-                                success: false,
-                                value: boxed_val,
-                            }))
+    /// Check `value` against `typ` for `validate`, with `path` naming where
+    /// it sits in the message
+    fn conform(&self, value: &Value, typ: &TypeAnnotation, path: &str) -> Result<Value, String> {
+        let value = value.unfrozen();
+        let mismatch = || {
+            alloc::format!("{}: expected {}, got {}", path, self.type_annotation_to_string(typ), value.type_name())
+        };
+        match typ {
+            TypeAnnotation::Named(form_name) => {
+                let Ok(Value::StructDef { fields, .. }) = self.environment.get(form_name) else {
+                    return if self.value_matches_type(value, typ) { Ok(value.clone()) } else { Err(mismatch()) };
+                };
+                let entries = match value {
+                    Value::Map(entries) => entries,
+                    Value::StructInstance { struct_name, fields: entries } if struct_name == form_name => entries,
+                    _ => return Err(mismatch()),
+                };
+                if let Some(key) = entries.keys().find(|key| !fields.iter().any(|field| &field.name == *key)) {
+                    return Err(alloc::format!("{}.{}: not a field of {}", path, key, form_name));
+                }
+                let mut conformed = BTreeMap::new();
+                for field in &fields {
+                    let field_path = alloc::format!("{}.{}", path, field.name);
+                    let field_value = match (entries.get(&field.name), &field.typ) {
+                        (Some(field_value), _) => self.conform(field_value, &field.typ, &field_path)?,
+                        (None, TypeAnnotation::Parametrized { name, .. }) if name == "Maybe" => {
+                            Value::Maybe { present: false, value: None }
                         }
-                    }
-                    _ => {
-                        // Type error: ? can only be used on Outcome
-                        Err(RuntimeError::TypeError {
-                            expected: "Outcome".to_string(),
-                            got: value.type_name().to_string(),
-                        })
+                        (None, _) => return Err(alloc::format!("{}: missing field", field_path)),
+                    };
+                    conformed.insert(field.name.clone(), field_value);
+                }
+                Ok(Value::StructInstance { struct_name: form_name.clone(), fields: conformed })
+            }
+            TypeAnnotation::List(element) | TypeAnnotation::Array { element, .. } => {
+                let Value::List(items) = value else {
+                    return Err(mismatch());
+                };
+                if let TypeAnnotation::Array { length, .. } = typ {
+                    if items.len() != *length {
+                        return Err(alloc::format!("{}: expected {} elements, got {}", path, length, items.len()));
                     }
                 }
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| self.conform(item, element, &alloc::format!("{}[{}]", path, index)))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Value::List)
             }
-
-            // === Binary Operations ===
-            AstNode::BinaryOp { left, op, right, .. } => {
-                let left_val = self.eval_node(left)?;
-                let right_val = self.eval_node(right)?;
-                self.eval_binary_op(&left_val, *op, &right_val)
+            TypeAnnotation::Parametrized { name, type_args } if name == "Maybe" && type_args.len() == 1 => {
+                let inner = match value {
+                    Value::Nothing | Value::Maybe { value: None, .. } => return Ok(Value::Maybe { present: false, value: None }),
+                    Value::Maybe { value: Some(inner), .. } => inner,
+                    plain => plain,
+                };
+                let inner = self.conform(inner, &type_args[0], path)?;
+                Ok(Value::Maybe { present: true, value: Some(Box::new(inner)) })
             }
+            _ if self.value_matches_type(value, typ) => Ok(value.clone()),
+            _ => Err(mismatch()),
+        }
+    }
 
-            // === Unary Operations ===
-            AstNode::UnaryOp { op, operand, .. } => {
-                let val = self.eval_node(operand)?;
-                self.eval_unary_op(*op, &val)
-            }
-
-            // === Function Calls ===
-            AstNode::Call { callee, args, type_args, .. } => {
-                // Phase 3: Check if this is a trait method call (object.method(...))
-                if let AstNode::FieldAccess { object, field, .. } = callee.as_ref() {
-                    // Evaluate the object (the 'self' value)
-                    let self_value = self.eval_node(object)?;
-                    let self_type = self.value_type_string(&self_value);
-
-                    // Try to find a trait implementation for this type and method
-                    // Clone the method implementation data to avoid borrow conflicts
-                    let trait_method_impl = {
-                        let mut found: Option<(Vec<AstNode>, Vec<Parameter>)> = None;
-                        for (impl_key, trait_impl) in &self.trait_implementations {
-                            if impl_key.target_type == self_type {
-                                if let Some(method_body) = trait_impl.methods.get(field) {
-                                    let method_params = trait_impl.method_params.get(field)
-                                        .ok_or_else(|| RuntimeError::Custom(
-                                            alloc::format!("Trait method '{}' missing parameters", field)
-                                        ))?;
-                                    found = Some((method_body.clone(), method_params.clone()));
-                                    break;
-                                }
-                            }
-                        }
-                        found
-                    };
-
-                    if let Some((method_body, method_params)) = trait_method_impl {
-                        // Found a trait method! Execute it with self bound
+    /// Evaluate `yield`, turning self-recursive calls into tail calls
+    fn eval_yield(&mut self, value: &AstNode) -> Result<Value, RuntimeError> {
+        if let Some((func_name, args)) = self.tail_call_target(value) {
+            return Err(self.eval_tail_call(func_name, args));
+        }
 
-                        // Evaluate arguments
-                        let arg_vals: Result<Vec<Value>, RuntimeError> =
-                            args.iter().map(|arg| self.eval_node(arg)).collect();
-                        let arg_vals = arg_vals?;
+        // Not a tail call, evaluate normally
+        match self.eval_node(value) {
+            Ok(val) => Err(RuntimeError::Return(val)),
+            Err(error) => Err(error),
+        }
+    }
 
-                        // Check arity (including self)
-                        if method_params.len() != arg_vals.len() + 1 {
-                            return Err(RuntimeError::ArityMismatch {
-                                expected: method_params.len() - 1,  // -1 for self
-                                got: arg_vals.len(),
-                            });
-                        }
+    /// The name and arguments of a call to the running chant, which `yield`
+    /// makes a tail call
+    ///
+    /// Calls with named arguments take the ordinary path, which puts them
+    /// in parameter order.
+    #[inline(never)]
+    fn tail_call_target<'a>(&self, value: &'a AstNode) -> Option<(&'a str, &'a [AstNode])> {
+        let AstNode::Call { callee, args, arg_names, .. } = value else { return None };
+        let AstNode::Ident { name: func_name, .. } = callee.as_ref() else { return None };
+        match self.environment.get("__current_function__") {
+            Ok(Value::Text(current_func)) if func_name == &current_func && arg_names.is_empty() => Some((func_name, args)),
+            _ => None,
+        }
+    }
 
-                        // Execute the trait method with self bound
-                        self.environment.push_scope();
+    /// Evaluate a tail call's arguments into the `TailCall` that unwinds to
+    /// the running chant's trampoline
+    #[inline(never)]
+    fn eval_tail_call(&mut self, func_name: &str, args: &[AstNode]) -> RuntimeError {
+        let arg_vals: Result<Vec<Value>, RuntimeError> = args.iter().map(|arg| self.eval_node(arg)).collect();
+        match arg_vals {
+            Ok(args) => RuntimeError::TailCall { function_name: func_name.to_string(), args },
+            Err(error) => error,
+        }
+    }
 
-                        // Bind 'self' parameter
-                        self.environment.define("self".to_string(), self_value.clone());
+    /// Evaluate a call, dispatching `object.method(...)` to trait implementations
+    #[inline(never)]
+    fn eval_call(
+        &mut self,
+        callee: &AstNode,
+        args: &[AstNode],
+        type_args: &[TypeAnnotation],
+        arg_names: &[String],
+    ) -> Result<Value, RuntimeError> {
+        // Module.member(...) calls into an imported or builtin module
+        if let Some(func) = self.qualified_member(callee) {
+            let func = func?;
+            let arg_vals: Result<Vec<Value>, RuntimeError> =
+                args.iter().map(|arg| self.eval_node(arg)).collect();
+            let arg_vals = place_named_args(&func, arg_vals?, arg_names)?;
+            return self.call_value(func, arg_vals, callee, type_args);
+        }
 
-                        // Bind remaining parameters
-                        for (param, arg) in method_params.iter().skip(1).zip(arg_vals.iter()) {
-                            self.environment.define(param.name.clone(), arg.clone());
+        // Phase 3: Check if this is a trait method call (object.method(...))
+        if let AstNode::FieldAccess { object, field, .. } = callee {
+            // Evaluate the object (the 'self' value)
+            let self_value = self.eval_node(object)?;
+            let self_type = self.value_type_string(&self_value);
+
+            // Try to find a trait implementation for this type and method
+            // Clone the method implementation data to avoid borrow conflicts
+            let trait_method_impl = {
+                let mut found: Option<(Vec<AstNode>, Vec<Parameter>, Option<TypeAnnotation>)> = None;
+                for (impl_key, trait_impl) in &self.trait_implementations {
+                    if impl_key.target_type == self_type {
+                        if let Some(method_body) = trait_impl.methods.get(field) {
+                            let method_params = trait_impl.method_params.get(field)
+                                .ok_or_else(|| RuntimeError::Custom(
+                                    alloc::format!("Trait method '{}' missing parameters", field)
+                                ))?;
+                            let return_type = trait_impl.method_return_types.get(field).cloned().flatten();
+                            found = Some((method_body.clone(), method_params.clone(), return_type));
+                            break;
                         }
-
-                        // Execute method body
-                        let result = self.eval(&method_body);
-
-                        // Restore environment
-                        self.environment.pop_scope();
-
-                        // Handle return
-                        return match result {
-                            Err(RuntimeError::Return(val)) => Ok(val),
-                            other => other,
-                        };
                     }
-
-                    // Not a trait method, fall through to normal method call handling
                 }
+                found
+            };
+
+            if let Some((method_body, method_params, return_type)) = trait_method_impl {
+                // Found a trait method! Execute it with self bound
 
-                // Normal function call (not a trait method)
-                let func = self.eval_node(callee)?;
+                // Evaluate arguments, named ones ordered against the
+                // parameters after self
                 let arg_vals: Result<Vec<Value>, RuntimeError> =
                     args.iter().map(|arg| self.eval_node(arg)).collect();
-                let arg_vals = arg_vals?;
-
-                // Call the function using the helper method
-                self.call_value(func, arg_vals, callee, type_args)
-            }
-
-            // === Field Access ===
-            AstNode::FieldAccess { object, field, .. } => {
-                let obj = self.eval_node(object)?;
-                match obj {
-                    Value::Map(ref map) => {
-                        map.get(field)
-                            .cloned()
-                            .ok_or_else(|| RuntimeError::FieldNotFound {
-                                field: field.clone(),
-                                object: "Map".to_string(),
-                            })
-                    }
-                    Value::StructInstance { struct_name, ref fields } => {
-                        fields.get(field)
-                            .cloned()
-                            .ok_or_else(|| RuntimeError::FieldNotFound {
-                                field: field.clone(),
-                                object: struct_name.clone(),
-                            })
-                    }
-                    _ => Err(RuntimeError::TypeError {
-                        expected: "Map or Struct".to_string(),
-                        got: obj.type_name().to_string(),
-                    }),
-                }
-            }
+                let arg_vals = crate::ast::order_arguments(method_params.get(1..).unwrap_or(&[]), arg_vals?, arg_names)
+                    .map_err(|e| RuntimeError::Custom(e.to_string()))?;
 
-            // === Index Access ===
-            AstNode::IndexAccess { object, index, .. } => {
-                let obj = self.eval_node(object)?;
-                let idx = self.eval_node(index)?;
-
-                match (obj, idx) {
-                    (Value::List(ref list), Value::Number(n)) => {
-                        let index = n as usize;
-                        if index < list.len() {
-                            Ok(list[index].clone())
-                        } else {
-                            Err(RuntimeError::IndexOutOfBounds {
-                                index,
-                                length: list.len(),
-                            })
-                        }
-                    }
-                    (Value::Map(ref map), Value::Text(key)) => {
-                        map.get(&key)
-                            .cloned()
-                            .ok_or_else(|| RuntimeError::FieldNotFound {
-                                field: key,
-                                object: "Map".to_string(),
-                            })
-                    }
-                    (obj, idx) => Err(RuntimeError::TypeError {
-                        expected: "List or Map".to_string(),
-                        got: alloc::format!("{} with {} index", obj.type_name(), idx.type_name()),
-                    }),
+                // Check arity (including self)
+                if method_params.len() != arg_vals.len() + 1 {
+                    return Err(RuntimeError::ArityMismatch {
+                        expected: method_params.len() - 1,  // -1 for self
+                        got: arg_vals.len(),
+                    });
                 }
-            }
 
-            // === Range ===
-            AstNode::Range { start, end, .. } => {
-                let start_val = self.eval_node(start)?;
-                let end_val = self.eval_node(end)?;
-
-                // Validate that both start and end are Numbers
-                match (&start_val, &end_val) {
-                    (Value::Number(_), Value::Number(_)) => {
-                        Ok(Value::Range {
-                            start: Box::new(start_val),
-                            end: Box::new(end_val),
-                        })
-                    }
-                    (Value::Number(_), _) => {
-                        Err(RuntimeError::TypeError {
-                            expected: "Number".to_string(),
-                            got: end_val.type_name().to_string(),
-                        })
-                    }
-                    (_, _) => {
-                        Err(RuntimeError::TypeError {
-                            expected: "Number".to_string(),
-                            got: start_val.type_name().to_string(),
-                        })
-                    }
-                }
+                // Execute the trait method with self bound
+                return self.call_trait_method(
+                    &method_body,
+                    &method_params,
+                    return_type,
+                    self_value,
+                    &arg_vals,
+                );
             }
 
-            // === Expression Statement ===
-            AstNode::ExprStmt { expr, .. } => self.eval_node(expr),
-
-            // === Block ===
-            AstNode::Block { statements, .. } => {
-                self.environment.push_scope();
-                let result = self.eval(statements);
-                self.environment.pop_scope();
-                result
-            }
+            // Not a trait method, fall through to normal method call handling
+        }
 
-            // === Pattern Matching ===
-            AstNode::MatchStmt { value, arms, .. } => {
-                // Evaluate the value to match against
-                let match_value = self.eval_node(value)?;
-
-                // Try each arm in order
-                for arm in arms {
-                    // Check if pattern matches
-                    if let Some(bindings) = self.pattern_matches(&arm.pattern, &match_value)? {
-                        // Pattern matched! Create new scope and bind variables
-                        self.environment.push_scope();
-
-                        // Bind pattern variables
-                        for (name, val) in bindings {
-                            self.environment.define(name, val);
-                        }
+        // Normal function call (not a trait method)
+        let func = self.eval_node(callee)?;
+        let arg_vals: Result<Vec<Value>, RuntimeError> =
+            args.iter().map(|arg| self.eval_node(arg)).collect();
+        let arg_vals = place_named_args(&func, arg_vals?, arg_names)?;
 
-                        // Execute the arm body
-                        let mut result = Value::Nothing;
-                        for stmt in &arm.body {
-                            result = self.eval_node(stmt)?;
-                        }
+        // Call the function using the helper method
+        self.call_value(func, arg_vals, callee, type_args)
+    }
 
-                        // Pop scope and return result
-                        self.environment.pop_scope();
-                        return Ok(result);
-                    }
-                }
+    /// Evaluate a `match` statement against each arm in order
+    ///
+    /// As the last statement of a chant (`tail`), the arm body gives the
+    /// chant's implicit value. The arm body can call chants, so `?` is not
+    /// used in this frame.
+    #[inline(never)]
+    #[allow(clippy::question_mark)]
+    fn eval_match(&mut self, value: &AstNode, arms: &[crate::ast::MatchArm], tail: bool) -> Result<Value, RuntimeError> {
+        let arm = match self.enter_match_arm(value, arms) {
+            Ok(arm) => arm,
+            Err(e) => return Err(e),
+        };
 
-                // No pattern matched
-                Err(RuntimeError::Custom("No pattern matched".to_string()))
-            }
-            AstNode::AttemptStmt { body, handlers, .. } => {
-                // Try to execute the body
-                let result = self.eval(body);
+        // Execute the arm body
+        let result = if tail {
+            self.eval_chant_body(&arm.body)
+        } else {
+            self.eval_statements(&arm.body)
+        };
 
-                // If successful, return the result
-                if result.is_ok() {
-                    return result;
-                }
+        // Pop scope and return result
+        self.environment.pop_scope();
+        result
+    }
 
-                // An error occurred - try to find a matching handler
-                let error = result.unwrap_err();
+    /// Evaluate a `match` value and find the first arm that takes it,
+    /// entering a new scope with the arm's pattern variables bound
+    #[inline(never)]
+    fn enter_match_arm<'a>(&mut self, value: &AstNode, arms: &'a [crate::ast::MatchArm]) -> Result<&'a crate::ast::MatchArm, RuntimeError> {
+        // Evaluate the value to match against
+        let (match_value, sources) = self.eval_node(value)?.split_taint();
+
+        // Try each arm in order
+        for arm in arms {
+            // Check if pattern matches
+            if let Some(bindings) = self.pattern_matches(&arm.pattern, &match_value)? {
+                // Pattern matched! Create new scope and bind variables
+                self.environment.push_scope();
 
-                // Don't catch Return or TailCall - these are control flow, not errors
-                if matches!(error, RuntimeError::Return(_) | RuntimeError::TailCall { .. }) {
-                    return Err(error);
+                // Bind pattern variables
+                for (name, val) in bindings {
+                    self.environment.define(name, val.taint(&sources));
                 }
 
-                // Get the error type for matching
-                let error_type = error.error_type();
-
-                // Try to find a matching handler
-                for handler in handlers {
-                    // Check if this handler matches the error type
-                    // Support wildcard "_" to catch all errors
-                    if handler.error_type == error_type || handler.error_type == "_" {
-                        // Execute the handler body
-                        return self.eval(&handler.body);
+                // A false guard turns the value away; later arms still get a chance
+                if let Some(guard) = &arm.guard {
+                    match self.eval_node(guard) {
+                        Ok(admitted) if admitted.is_truthy() => {}
+                        result => {
+                            self.environment.pop_scope();
+                            result?;
+                            continue;
+                        }
                     }
                 }
 
-                // No handler matched - propagate the error
-                Err(error)
-            }
-            AstNode::RequestStmt { capability, justification, .. } => {
-                // Capability-based security: Request permission to access a resource
-                //
-                // This creates an unforgeable capability token that represents permission
-                // to access the requested resource. The justification is attached for
-                // audit logging by the AethelOS kernel.
-                //
-                // In a production system, this would:
-                // 1. Ask the user/kernel for permission
-                // 2. Log the request with justification
-                // 3. Grant or deny based on security policy
-                //
-                // For now, we create the capability token (permission checking
-                // will be enforced by AethelOS when the capability is actually used)
-
-                // Extract resource name from the capability expression
-                // Note: We DON'T evaluate the expression, just extract its name
-                let resource = self.node_to_string(capability);
-
-                // Create capability token
-                // In a real system, this would be cryptographically signed by the kernel
-                Ok(Value::Capability {
-                    resource,
-                    permissions: vec![
-                        "access".to_string(),
-                        justification.clone(),
-                    ],
-                })
+                return Ok(arm);
             }
-            AstNode::Pipeline { stages, .. } => {
-                // Pipeline: value | func1 | func2
-                // Equivalent to: func2(func1(value))
+        }
 
-                if stages.is_empty() {
-                    return Err(RuntimeError::Custom("Empty pipeline".to_string()));
-                }
+        // No pattern matched
+        Err(RuntimeError::Custom("No pattern matched".to_string()))
+    }
 
-                // Evaluate the first stage to get the initial value
-                let mut current_value = self.eval_node(&stages[0])?;
-
-                // For each remaining stage, pass the current value as an argument
-                for stage in stages.iter().skip(1) {
-                    // Each stage should be a function call or identifier
-                    match stage {
-                        // If it's a function call, prepend the current value as first argument
-                        AstNode::Call { callee, args, type_args, .. } => {
-                            // Evaluate the function
-                            let func = self.eval_node(callee)?;
-
-                            // Evaluate all existing arguments
-                            let mut all_args: Vec<Value> = vec![current_value.clone()];
-                            for arg in args {
-                                all_args.push(self.eval_node(arg)?);
-                            }
+    /// Evaluate an `attempt` block, dispatching errors to matching handlers
+    #[inline(never)]
+    fn eval_attempt(
+        &mut self,
+        body: &[AstNode],
+        handlers: &[crate::ast::ErrorHandler],
+    ) -> Result<Value, RuntimeError> {
+        // Try to execute the body, returning its result if successful
+        let error = match self.eval(body) {
+            Ok(value) => return Ok(value),
+            // An error occurred - try to find a matching handler
+            Err(error) => error,
+        };
 
-                            // Call the function with the current value as first argument
-                            current_value = self.call_value(func, all_args, callee, type_args)?;
-                        }
-                        // If it's just an identifier, call it with the current value
-                        AstNode::Ident { name, .. } => {
-                            let func = self.environment.get(name)?;
-                            current_value = self.call_value(func, vec![current_value.clone()], stage, &[])?;
-                        }
-                        // Otherwise, treat it as a function expression
-                        _ => {
-                            let func = self.eval_node(stage)?;
-                            current_value = self.call_value(func, vec![current_value.clone()], stage, &[])?;
-                        }
-                    }
-                }
+        // Don't catch Return, TailCall or Exit - these are control flow, not
+        // errors - nor Timeout and Cancelled, which the host relies on to stop
+        // the script
+        if matches!(
+            error,
+            RuntimeError::Return(_)
+                | RuntimeError::TailCall { .. }
+                | RuntimeError::Exit(_)
+                | RuntimeError::Timeout
+                | RuntimeError::Cancelled
+        ) {
+            return Err(error);
+        }
 
-                Ok(current_value)
+        // Get the error type for matching
+        let error_type = error.error_type();
+
+        // Try to find a matching handler
+        for handler in handlers {
+            // Check if this handler matches the error type
+            // Support wildcard "_" to catch all errors
+            if handler.error_type == error_type || handler.error_type == "_" {
+                // Handled: not a crash
+                self.unwinding = None;
+                self.crash_dump = None;
+                // Execute the handler body
+                return self.eval(&handler.body);
             }
-            AstNode::SeekExpr { .. } => {
-                Err(RuntimeError::Custom("World-Tree queries not yet implemented".to_string()))
-            }
-
-            // === Module System (Phase 4: Interpreter Support) ===
-            AstNode::ModuleDecl { name, body, exports, .. } => {
-                // Create a new environment for the module
-                let mut module_env = Environment::new();
-
-                // Copy builtins from global environment (first scope)
-                if let Some(global_scope) = self.environment.scopes.first() {
-                    for (name, binding) in global_scope {
-                        module_env.define(name.clone(), binding.value.clone());
-                    }
-                }
-
-                // Save current environment and switch to module environment
-                let saved_env = core::mem::replace(&mut self.environment, module_env);
+        }
 
-                // Evaluate module body
-                let mut result = Value::Nothing;
-                for stmt in body {
-                    result = self.eval_node(stmt)?;
-                }
+        // No handler matched - propagate the error
+        Err(error)
+    }
 
-                // Extract exported symbols from module environment
-                let module_env = core::mem::replace(&mut self.environment, saved_env);
+    /// Evaluate a pipeline, passing each stage's result to the next
+    fn eval_pipeline(&mut self, stages: &[AstNode]) -> Result<Value, RuntimeError> {
+        // Pipeline: value | func1 | func2
+        // Equivalent to: func2(func1(value))
 
-                // Store module environment for later access
-                self.module_environments.insert(name.clone(), module_env);
+        if stages.is_empty() {
+            return Err(RuntimeError::Custom("Empty pipeline".to_string()));
+        }
 
-                // Verify all exports exist (similar to semantic analysis)
-                // This is optional runtime validation
-                for export_name in exports {
-                    if let Some(module_env) = self.module_environments.get(name) {
-                        if module_env.get(export_name).is_err() {
-                            return Err(RuntimeError::Custom(format!(
-                                "Export '{}' not found in module '{}'",
-                                export_name, name
-                            )));
-                        }
+        // Evaluate the first stage to get the initial value
+        let mut current_value = self.eval_node(&stages[0])?;
+
+        // For each remaining stage, pass the current value as an argument
+        for stage in stages.iter().skip(1) {
+            // Each stage should be a function call or identifier
+            match stage {
+                // If it's a function call, prepend the current value as first argument
+                AstNode::Call { callee, args, type_args, arg_names, .. } => {
+                    // Evaluate the function
+                    let func = self.eval_node(callee)?;
+
+                    // Evaluate all existing arguments
+                    let mut all_args: Vec<Value> = vec![current_value.clone()];
+                    for arg in args {
+                        all_args.push(self.eval_node(arg)?);
                     }
+                    let all_args = place_named_args(&func, all_args, arg_names)?;
+
+                    // Call the function with the current value as first argument
+                    current_value = self.call_value(func, all_args, callee, type_args)?;
                 }
+                // If it's just an identifier, call it with the current value
+                AstNode::Ident { name, .. } => {
+                    let func = self.environment.get(name)?;
+                    current_value = self.call_value(func, vec![current_value.clone()], stage, &[])?;
+                }
+                // Otherwise, treat it as a function expression
+                _ => {
+                    let func = self.eval_node(stage)?;
+                    current_value = self.call_value(func, vec![current_value.clone()], stage, &[])?;
+                }
+            }
+        }
 
-                Ok(result)
+        Ok(current_value)
+    }
+
+    /// Evaluate a module declaration in its own environment
+    fn eval_module_decl(&mut self, name: &str, body: &[AstNode], exports: &[String]) -> Result<Value, RuntimeError> {
+        // Create a new environment for the module
+        let mut module_env = Environment::new();
+
+        // Copy builtins from global environment (first scope)
+        if let Some(global_scope) = self.environment.scopes.first() {
+            for binding in &global_scope.slots {
+                module_env.define(binding.name.clone(), binding.value.clone());
             }
+        }
+        // Module bindings get their own frame so resolved slots start at zero
+        module_env.push_scope();
 
-            AstNode::Import { module_name, path, items, alias, .. } => {
-                // Determine effective module name (alias takes precedence)
-                let effective_name = alias.as_ref().unwrap_or(module_name);
-
-                // Load module info (must complete before we can eval)
-                let (module_name_resolved, module_ast, module_exports) = {
-                    // Check if module resolver is available
-                    let resolver = self.module_resolver.as_mut().ok_or_else(|| {
-                        RuntimeError::Custom(
-                            "Module resolver not configured. Call set_module_resolver() before importing modules.".to_string()
-                        )
-                    })?;
-
-                    // Resolve the module path
-                    let resolved_path = resolver.resolve_path(path, None).map_err(|e| {
-                        RuntimeError::Custom(format!("Failed to resolve module path '{}': {:?}", path, e))
-                    })?;
-
-                    // Load the module
-                    let module_info = resolver.load_module(&resolved_path).map_err(|e| {
-                        RuntimeError::Custom(format!("Failed to load module from '{}': {:?}", resolved_path, e))
-                    })?;
-
-                    // Clone the data we need (releases the borrow of module_resolver)
-                    (module_info.name.clone(), module_info.ast.clone(), module_info.exports.clone())
-                };
+        // Save current environment and switch to module environment
+        let saved_env = core::mem::replace(&mut self.environment, module_env);
 
-                // Check if module has already been evaluated
-                if !self.module_environments.contains_key(&module_name_resolved) {
-                    // Evaluate the module if not already done
-                    // This will populate module_environments
-                    for node in &module_ast {
-                        self.eval_node(node)?;
-                    }
-                }
+        // Evaluate module body
+        let mut result = Value::Nothing;
+        for stmt in body {
+            result = self.eval_node(stmt)?;
+        }
 
-                // Get the module environment
-                let module_env = self.module_environments.get(&module_name_resolved).ok_or_else(|| {
-                    RuntimeError::Custom(format!(
-                        "Module '{}' not found after evaluation. This is a bug.",
-                        module_name_resolved
-                    ))
-                })?;
-
-                // Import symbols based on items list
-                match items {
-                    None => {
-                        // Import all exports
-                        // In the interpreter, we don't have explicit export tracking per symbol,
-                        // so we'll import all symbols from the module environment
-                        for export_name in &module_exports {
-                            if let Ok(value) = module_env.get(export_name) {
-                                // For "summon Module from path", prefix with module name
-                                let qualified_name = format!("{}.{}", effective_name, export_name);
-                                self.environment.define(qualified_name, value);
-                            }
-                        }
+        // Extract exported symbols from module environment
+        let module_env = core::mem::replace(&mut self.environment, saved_env);
 
-                        // Also store a reference to the module for qualified access
-                        self.imported_modules.insert(effective_name.clone(), None);
-                    }
-                    Some(item_list) => {
-                        // Import specific items
-                        for item in item_list {
-                            if let Ok(value) = module_env.get(item) {
-                                // For "gather x, y from Module", import directly (no prefix)
-                                self.environment.define(item.clone(), value);
-                            } else {
-                                return Err(RuntimeError::Custom(format!(
-                                    "Symbol '{}' not found in module '{}'",
-                                    item, module_name_resolved
-                                )));
-                            }
-                        }
+        // Store module environment for later access
+        self.module_environments.insert(name.to_string(), module_env);
 
-                        self.imported_modules.insert(effective_name.clone(), Some(item_list.clone()));
-                    }
+        // Verify all exports exist (similar to semantic analysis)
+        // This is optional runtime validation
+        for export_name in exports {
+            if let Some(module_env) = self.module_environments.get(name) {
+                if module_env.get(export_name).is_err() {
+                    return Err(RuntimeError::Custom(format!(
+                        "Export '{}' not found in module '{}'",
+                        export_name, name
+                    )));
                 }
-
-                Ok(Value::Nothing)
             }
+        }
 
-            AstNode::Export { items: _, .. } => {
-                // Export statements are handled during ModuleDecl evaluation
-                // This is a no-op in the interpreter
-                // Exports are tracked when the module is declared
-                Ok(Value::Nothing)
+        Ok(result)
+    }
+
+    /// Load, evaluate and import a module
+    fn eval_import(
+        &mut self,
+        module_name: &str,
+        path: &str,
+        items: Option<&[String]>,
+        alias: Option<&str>,
+    ) -> Result<Value, RuntimeError> {
+        // Determine effective module name (alias takes precedence)
+        let effective_name = alias.unwrap_or(module_name);
+
+        // Load module info (must complete before we can eval)
+        let (module_name_resolved, module_ast, module_exports) = {
+            // Check if module resolver is available
+            let mut resolver = crate::sync::lock(self.module_resolver.as_ref().ok_or_else(|| {
+                RuntimeError::Custom(
+                    "Module resolver not configured. Call set_module_resolver() before importing modules.".to_string()
+                )
+            })?);
+
+            // Resolve the module path
+            let resolved_path = resolver.resolve_path(path, None).map_err(|e| {
+                RuntimeError::Custom(format!("Failed to resolve module path '{}': {:?}", path, e))
+            })?;
+
+            // Load the module
+            let module_info = resolver.load_module(&resolved_path).map_err(|e| {
+                RuntimeError::Custom(format!("Failed to load module from '{}': {:?}", resolved_path, e))
+            })?;
+
+            // Clone the data we need (releases the borrow of module_resolver)
+            (module_info.name.clone(), module_info.ast.clone(), module_info.exports.clone())
+        };
+
+        // Check if module has already been evaluated
+        if !self.module_environments.contains_key(&module_name_resolved) {
+            // Evaluate the module if not already done
+            // This will populate module_environments
+            for node in &module_ast {
+                self.eval_node(node)?;
             }
+        }
 
-            AstNode::ModuleAccess { module, member, .. } => {
-                // Check if module is imported
-                if !self.imported_modules.contains_key(module) {
-                    return Err(RuntimeError::Custom(format!(
-                        "Module '{}' not imported. Use 'summon {} from \"path\"' to import it.",
-                        module, module
-                    )));
+        // Get the module environment
+        let module_env = self.module_environments.get(&module_name_resolved).ok_or_else(|| {
+            RuntimeError::Custom(format!(
+                "Module '{}' not found after evaluation. This is a bug.",
+                module_name_resolved
+            ))
+        })?;
+
+        // Import symbols based on items list
+        match items {
+            None => {
+                // Import all exports
+                // In the interpreter, we don't have explicit export tracking per symbol,
+                // so we'll import all symbols from the module environment
+                for export_name in &module_exports {
+                    if let Ok(value) = module_env.get(export_name) {
+                        // For "summon Module from path", prefix with module name
+                        let qualified_name = format!("{}.{}", effective_name, export_name);
+                        self.environment.define(qualified_name, value);
+                    }
                 }
 
-                // For qualified access (Module.member), look up the qualified name
-                // that was defined during import
-                let qualified_name = format!("{}.{}", module, member);
-                self.environment.get(&qualified_name).or_else(|_| {
-                    // If not found with qualified name, try looking in the module environment directly
-                    // This handles the case where the module was evaluated but imports weren't set up correctly
-                    if let Some(module_env) = self.module_environments.get(module) {
-                        module_env.get(member)
+                // Also store a reference to the module for qualified access
+                self.imported_modules.insert(effective_name.to_string(), None);
+            }
+            Some(item_list) => {
+                // Import specific items
+                for item in item_list {
+                    if let Ok(value) = module_env.get(item) {
+                        // For "gather x, y from Module", import directly (no prefix)
+                        self.environment.define(item.clone(), value);
                     } else {
-                        Err(RuntimeError::Custom(format!(
+                        return Err(RuntimeError::Custom(format!(
                             "Symbol '{}' not found in module '{}'",
-                            member, module
-                        )))
+                            item, module_name_resolved
+                        )));
                     }
-                })
+                }
+
+                self.imported_modules.insert(effective_name.to_string(), Some(item_list.to_vec()));
+            }
+        }
+
+        Ok(Value::Nothing)
+    }
+
+    /// Evaluate module-qualified access (`Module.member`)
+    /// Resolve `Name.member` when `Name` is an imported or builtin module
+    /// rather than a bound value
+    fn qualified_member(&self, node: &AstNode) -> Option<Result<Value, RuntimeError>> {
+        let AstNode::FieldAccess { object, field, .. } = node else { return None };
+        let AstNode::Ident { name, .. } = object.as_ref() else { return None };
+
+        let is_module = self.imported_modules.contains_key(name) || self.builtin_modules.contains_key(name);
+        if !is_module || self.environment.contains(name) {
+            return None;
+        }
+        Some(self.eval_module_access(name, field))
+    }
+
+    fn eval_module_access(&self, module: &str, member: &str) -> Result<Value, RuntimeError> {
+        // Check if module is imported, falling back to the builtin modules
+        if !self.imported_modules.contains_key(module) {
+            if let Some(members) = self.builtin_modules.get(module) {
+                return members
+                    .get(member)
+                    .cloned()
+                    .ok_or_else(|| RuntimeError::UndefinedVariable(format!("{}.{}", module, member)));
             }
+            return Err(RuntimeError::Custom(format!(
+                "Module '{}' not imported. Use 'summon {} from \"path\"' to import it.",
+                module, module
+            )));
         }
+
+        // For qualified access (Module.member), look up the qualified name
+        // that was defined during import
+        let qualified_name = format!("{}.{}", module, member);
+        self.environment.get(&qualified_name).or_else(|_| {
+            // If not found with qualified name, try looking in the module environment directly
+            // This handles the case where the module was evaluated but imports weren't set up correctly
+            if let Some(module_env) = self.module_environments.get(module) {
+                module_env.get(member)
+            } else {
+                Err(RuntimeError::Custom(format!(
+                    "Symbol '{}' not found in module '{}'",
+                    member, module
+                )))
+            }
+        })
     }
 
     /// Check if a pattern matches a value, returning bindings if it matches
@@ -1751,6 +5074,41 @@ impl Evaluator {
                 Ok(Some(Vec::new()))
             }
 
+            // Affix pattern - matches text with the affix, binding the rest
+            Pattern::Affix { side, affix, rest } => {
+                let Value::Text(text) = value else { return Ok(None) };
+                Ok(side.strip(text, affix).map(|remainder| match rest {
+                    Some(name) => vec![(name.clone(), Value::Text(remainder.to_string()))],
+                    None => Vec::new(),
+                }))
+            }
+
+            // Or-pattern - the first alternative that matches gives the bindings
+            Pattern::Or(alternatives) => {
+                for alternative in alternatives {
+                    if let Some(bindings) = self.pattern_matches(alternative, value)? {
+                        return Ok(Some(bindings));
+                    }
+                }
+                Ok(None)
+            }
+
+            // Tuple pattern - matches a tuple of as many elements, element by element
+            Pattern::Tuple(patterns) => {
+                let Value::Tuple(items) = value else { return Ok(None) };
+                if items.len() != patterns.len() {
+                    return Ok(None);
+                }
+                let mut bindings = Vec::new();
+                for (pattern, item) in patterns.iter().zip(items) {
+                    match self.pattern_matches(pattern, item)? {
+                        Some(found) => bindings.extend(found),
+                        None => return Ok(None),
+                    }
+                }
+                Ok(Some(bindings))
+            }
+
             // Enum pattern - matches Outcome, Maybe, or user-defined variants
             Pattern::Enum { variant, inner } => {
                 // First check if it's a user-defined variant
@@ -1855,6 +5213,12 @@ impl Evaluator {
         op: BinaryOperator,
         right: &Value,
     ) -> Result<Value, RuntimeError> {
+        if let (Value::Tainted { .. }, _) | (_, Value::Tainted { .. }) = (left, right) {
+            let (left, mut sources) = left.clone().split_taint();
+            let (right, right_sources) = right.clone().split_taint();
+            crate::taint::merge(&mut sources, right_sources);
+            return self.eval_binary_op(&left, op, &right).map(|value| value.taint(&sources));
+        }
         match (left, op, right) {
             // Arithmetic
             (Value::Number(l), BinaryOperator::Add, Value::Number(r)) => Ok(Value::Number(l + r)),
@@ -1875,6 +5239,34 @@ impl Evaluator {
                 }
             }
 
+            (
+                Value::BigInt(l),
+                BinaryOperator::Add
+                | BinaryOperator::Sub
+                | BinaryOperator::Mul
+                | BinaryOperator::Div
+                | BinaryOperator::Mod
+                | BinaryOperator::Greater
+                | BinaryOperator::Less
+                | BinaryOperator::GreaterEq
+                | BinaryOperator::LessEq,
+                Value::BigInt(r),
+            ) => bigint_op(l, op, r),
+
+            (
+                Value::Integer(l),
+                BinaryOperator::Add
+                | BinaryOperator::Sub
+                | BinaryOperator::Mul
+                | BinaryOperator::Div
+                | BinaryOperator::Mod
+                | BinaryOperator::Greater
+                | BinaryOperator::Less
+                | BinaryOperator::GreaterEq
+                | BinaryOperator::LessEq,
+                Value::Integer(r),
+            ) => integer_op(*l, op, *r),
+
             // String concatenation
             (Value::Text(l), BinaryOperator::Add, Value::Text(r)) => {
                 let mut result = l.clone();
@@ -1907,8 +5299,13 @@ impl Evaluator {
     /// Evaluate unary operation
     fn eval_unary_op(&self, op: UnaryOperator, operand: &Value) -> Result<Value, RuntimeError> {
         match (op, operand) {
+            (op, Value::Tainted { value, sources }) => self.eval_unary_op(op, value).map(|value| value.taint(sources)),
             (UnaryOperator::Not, val) => Ok(Value::Truth(!val.is_truthy())),
             (UnaryOperator::Negate, Value::Number(n)) => Ok(Value::Number(-n)),
+            (UnaryOperator::Negate, Value::BigInt(n)) => Ok(Value::BigInt(-n)),
+            (UnaryOperator::Negate, Value::Integer(n)) => {
+                n.checked_neg().map(Value::Integer).ok_or_else(|| RuntimeError::IntegerOverflow("negation".to_string()))
+            }
             (UnaryOperator::Negate, val) => Err(RuntimeError::TypeError {
                 expected: "Number".to_string(),
                 got: val.type_name().to_string(),
@@ -1920,12 +5317,17 @@ impl Evaluator {
     /// Returns true if the value conforms to the type, false otherwise
     fn value_matches_type(&self, value: &Value, type_ann: &TypeAnnotation) -> bool {
         match (value, type_ann) {
+            (Value::Frozen { value } | Value::Tainted { value, .. }, _) => self.value_matches_type(value, type_ann),
             // Basic type matching
             (Value::Number(_), TypeAnnotation::Named(name)) if name == "Number" => true,
+            (Value::BigInt(_), TypeAnnotation::Named(name)) if name == "BigInt" => true,
+            (Value::Integer(_), TypeAnnotation::Named(name)) if name == "Integer" => true,
             (Value::Text(_), TypeAnnotation::Named(name)) if name == "Text" => true,
             (Value::Truth(_), TypeAnnotation::Named(name)) if name == "Truth" => true,
             (Value::Nothing, TypeAnnotation::Named(name)) if name == "Nothing" => true,
             (Value::List(_), TypeAnnotation::Named(name)) if name == "List" => true,
+            (Value::Tuple(_), TypeAnnotation::Named(name)) if name == "Tuple" => true,
+            (Value::Set(_), TypeAnnotation::Named(name)) if name == "Set" => true,
             (Value::Map(_), TypeAnnotation::Named(name)) if name == "Map" => true,
             (Value::Map(_), TypeAnnotation::Map) => true,
 
@@ -1933,6 +5335,10 @@ impl Evaluator {
             (Value::StructInstance { struct_name, .. }, TypeAnnotation::Named(name))
                 if struct_name == name => true,
 
+            // Variant values match their variant's name
+            (Value::VariantValue { enum_name, .. }, TypeAnnotation::Named(name))
+                if enum_name == name => true,
+
             // Generic type parameters match anything (they're type variables)
             (_, TypeAnnotation::Generic(_)) => true,
 
@@ -1941,6 +5347,11 @@ impl Evaluator {
             (Value::List(_), TypeAnnotation::List(_)) => true,
             (Value::List(_), TypeAnnotation::Parametrized { name, .. }) if name == "List" => true,
 
+            // Fixed-size arrays are lists of exactly their length
+            (Value::List(items), TypeAnnotation::Array { element, length }) => {
+                items.len() == *length && items.iter().all(|item| self.value_matches_type(item, element))
+            }
+
             // Function/Chant type matching
             (Value::Chant { .. }, TypeAnnotation::Function { .. }) => true,
             (Value::Chant { .. }, TypeAnnotation::Named(name)) if name == "Function" => true,
@@ -1959,6 +5370,9 @@ impl Evaluator {
             // Iterator type
             (Value::Iterator { .. }, TypeAnnotation::Named(name)) if name == "Iterator" => true,
 
+            // Resource type
+            (Value::Resource { .. }, TypeAnnotation::Named(name)) if name == "Resource" => true,
+
             // Default: no match
             _ => false,
         }
@@ -1968,22 +5382,6 @@ impl Evaluator {
     fn type_annotation_to_string(&self, ann: &TypeAnnotation) -> String {
         type_annotation_to_string_helper(ann)
     }
-
-    /// Convert AST node to string representation (for capability requests)
-    #[allow(clippy::only_used_in_recursion)]
-    fn node_to_string(&self, node: &AstNode) -> String {
-        match node {
-            AstNode::Ident { name, .. } => name.clone(),
-            AstNode::FieldAccess { object, field, .. } => {
-                format!("{}.{}", self.node_to_string(object), field)
-            }
-            AstNode::Number { value: n, .. } => n.to_string(),
-            AstNode::Text { value: s, .. } => s.clone(),
-            AstNode::Truth { value: b, .. } => b.to_string(),
-            AstNode::Nothing { .. } => "nothing".to_string(),
-            _ => "<expression>".to_string(),
-        }
-    }
 }
 
 /// Convert TypeAnnotation to normalized string for trait impl lookup (standalone helper)
@@ -2002,6 +5400,9 @@ fn type_annotation_to_string_helper(ann: &TypeAnnotation) -> String {
             alloc::format!("{}<{}>", name, args.join(", "))
         }
         TypeAnnotation::Map => "Map".to_string(),
+        TypeAnnotation::Array { element, length } => {
+            alloc::format!("array of {} length {}", type_annotation_to_string_helper(element), length)
+        }
         TypeAnnotation::Function { .. } => "Function".to_string(),
         TypeAnnotation::Optional(inner) => {
             alloc::format!("{}?", type_annotation_to_string_helper(inner))
@@ -2017,8 +5418,742 @@ fn type_annotation_to_string_helper(ann: &TypeAnnotation) -> String {
     }
 }
 
+/// A unit of pending work on the expression evaluation stack
+enum ExprTask<'a> {
+    /// Evaluate a node, pushing its value
+    Eval(&'a AstNode),
+    /// Wrap the top value in Triumph (true) or Mishap (false)
+    Outcome(bool),
+    /// Wrap the top value in Present
+    Present,
+    /// Collect the top values into a list of the given length
+    List(usize),
+    /// Collect the top values into a tuple of the given length
+    Tuple(usize),
+    /// Collect the top values into a map with these entries' keys
+    Map(&'a [(String, AstNode)]),
+    /// Apply the try operator to the top value
+    Try,
+    /// Apply a binary operator to the top two values
+    Binary(BinaryOperator),
+    /// Apply a unary operator to the top value
+    Unary(UnaryOperator),
+    /// Read the top value, a measure, in a unit
+    InUnit(&'a str),
+    /// Call the callee value with the arguments above it on the stack
+    Call {
+        callee: &'a AstNode,
+        type_args: &'a [TypeAnnotation],
+        arg_names: &'a [String],
+        argc: usize,
+    },
+    /// Read a field of the top value
+    Field(&'a str),
+    /// Index the object below the top value with the top value
+    Index,
+    /// Build a range from the top two values
+    Range,
+}
+
+/// What a `for each` loop iterates over: a list of items, or an iterator
+/// to advance, along with the taint sources of the iterable
+enum ForItems {
+    List(Vec<Value>, Vec<String>),
+    Iterator(Value, Vec<String>),
+}
+
+/// How a round of a chant call ended
+enum ChantRound {
+    /// A recursive tail call, to run as another round with these arguments
+    TailCall(Vec<Value>),
+    /// The call is over, with this outcome
+    Done(Result<Value, RuntimeError>),
+}
+
+/// The part of a chant body that gives the chant's implicit value
+enum ChantTail<'a> {
+    /// The taken branch of a final `should`, itself a chant body
+    Branch(&'a [AstNode]),
+    /// A final `match`, whose taken arm gives the value
+    Match(&'a AstNode, &'a [crate::ast::MatchArm]),
+    /// A final expression
+    Value(&'a AstNode),
+    /// A final statement other than an expression, which gives no value
+    Statement(&'a AstNode),
+    /// No value: a final `should` took no branch
+    Nothing,
+}
+
+/// What `eval_expr` does once `expr_step` has carried out a task
+enum ExprStep<'a> {
+    /// Nothing more; any value is on the value stack
+    Done,
+    /// Call the chant below the top `argc` values with them as arguments
+    Call {
+        callee: &'a AstNode,
+        type_args: &'a [TypeAnnotation],
+        arg_names: &'a [String],
+        argc: usize,
+    },
+    /// Evaluate a node outside the work stack and push its value
+    Direct(&'a AstNode),
+}
+
+/// Arithmetic and ordering between two big integers
+///
+/// Division truncates toward zero. Shared with the VM so both backends agree.
+pub(crate) fn bigint_op(left: &BigInt, op: BinaryOperator, right: &BigInt) -> Result<Value, RuntimeError> {
+    let quotient_and_remainder = || left.div_rem(right).ok_or(RuntimeError::DivisionByZero);
+    Ok(match op {
+        BinaryOperator::Add => Value::BigInt(left + right),
+        BinaryOperator::Sub => Value::BigInt(left - right),
+        BinaryOperator::Mul => Value::BigInt(left * right),
+        BinaryOperator::Div => Value::BigInt(quotient_and_remainder()?.0),
+        BinaryOperator::Mod => Value::BigInt(quotient_and_remainder()?.1),
+        BinaryOperator::Greater => Value::Truth(left > right),
+        BinaryOperator::Less => Value::Truth(left < right),
+        BinaryOperator::GreaterEq => Value::Truth(left >= right),
+        BinaryOperator::LessEq => Value::Truth(left <= right),
+        BinaryOperator::Equal => Value::Truth(left == right),
+        BinaryOperator::NotEqual => Value::Truth(left != right),
+        BinaryOperator::And | BinaryOperator::Or => {
+            return Err(RuntimeError::TypeError { expected: "Truth".to_string(), got: "BigInt".to_string() })
+        }
+    })
+}
+
+/// Arithmetic and ordering between two 64-bit integers
+///
+/// Division truncates toward zero; a result outside the 64-bit range is an
+/// [`RuntimeError::IntegerOverflow`] rather than wrapping. Shared with the VM.
+pub(crate) fn integer_op(left: i64, op: BinaryOperator, right: i64) -> Result<Value, RuntimeError> {
+    let checked = |result: Option<i64>, operation: &str| {
+        result.map(Value::Integer).ok_or_else(|| RuntimeError::IntegerOverflow(operation.to_string()))
+    };
+    let divisor = || if right == 0 { Err(RuntimeError::DivisionByZero) } else { Ok(right) };
+    match op {
+        BinaryOperator::Add => checked(left.checked_add(right), "addition"),
+        BinaryOperator::Sub => checked(left.checked_sub(right), "subtraction"),
+        BinaryOperator::Mul => checked(left.checked_mul(right), "multiplication"),
+        BinaryOperator::Div => checked(left.checked_div(divisor()?), "division"),
+        BinaryOperator::Mod => checked(left.checked_rem(divisor()?), "remainder"),
+        BinaryOperator::Greater => Ok(Value::Truth(left > right)),
+        BinaryOperator::Less => Ok(Value::Truth(left < right)),
+        BinaryOperator::GreaterEq => Ok(Value::Truth(left >= right)),
+        BinaryOperator::LessEq => Ok(Value::Truth(left <= right)),
+        BinaryOperator::Equal => Ok(Value::Truth(left == right)),
+        BinaryOperator::NotEqual => Ok(Value::Truth(left != right)),
+        BinaryOperator::And | BinaryOperator::Or => {
+            Err(RuntimeError::TypeError { expected: "Truth".to_string(), got: "Integer".to_string() })
+        }
+    }
+}
+
+/// The unit a `Measure` or `InUnit` node names
+fn unit_named(name: &str) -> Result<crate::units::Unit, RuntimeError> {
+    crate::units::unit(name).ok_or_else(|| RuntimeError::Custom(format!("Unknown unit '{}'", name)))
+}
+
+/// Pop the top value of the expression value stack
+fn pop_value(values: &mut Vec<Value>) -> Result<Value, RuntimeError> {
+    values.pop().ok_or_else(|| RuntimeError::Custom("Expression stack underflow".to_string()))
+}
+
+/// Pop a chant and the `argc` arguments above it, named ones placed where
+/// its parameters expect them
+///
+/// One call, so `call_from_stack` holds a single result across nested chant
+/// calls.
+#[inline(never)]
+fn pop_call(values: &mut Vec<Value>, argc: usize, arg_names: &[String]) -> Result<(Value, Vec<Value>), RuntimeError> {
+    let args = pop_values(values, argc)?;
+    let func = pop_value(values)?;
+    let args = place_named_args(&func, args, arg_names)?;
+    Ok((func, args))
+}
+
+/// Put named arguments where the parameters of the chant called expect them
+fn place_named_args(func: &Value, args: Vec<Value>, arg_names: &[String]) -> Result<Vec<Value>, RuntimeError> {
+    if arg_names.is_empty() {
+        return Ok(args);
+    }
+    match func {
+        Value::Chant { params, .. } => {
+            crate::ast::order_arguments(params, args, arg_names).map_err(|e| RuntimeError::Custom(e.to_string()))
+        }
+        other => Err(RuntimeError::Custom(format!("Named arguments need a chant to call, not {}", other.type_name()))),
+    }
+}
+
+/// Pop the top `count` values of the expression value stack, in push order
+fn pop_values(values: &mut Vec<Value>, count: usize) -> Result<Vec<Value>, RuntimeError> {
+    let split = values.len().checked_sub(count)
+        .ok_or_else(|| RuntimeError::Custom("Expression stack underflow".to_string()))?;
+    Ok(values.split_off(split))
+}
+
+/// Call a value other than a chant: a variant constructor builds its case
+#[inline(never)]
+fn call_constructor(func: Value, args: Vec<Value>, type_args: &[TypeAnnotation]) -> Result<Value, RuntimeError> {
+    match func {
+        Value::VariantConstructor { enum_name, variant_name, field_params, type_params } => {
+            construct_variant(enum_name, variant_name, &field_params, &type_params, args, type_args)
+        }
+        _ => Err(RuntimeError::NotCallable(func.type_name().to_string())),
+    }
+}
+
+/// Create a variant value from a constructor call
+fn construct_variant(
+    enum_name: String,
+    variant_name: String,
+    field_params: &[Parameter],
+    type_params: &[String],
+    args: Vec<Value>,
+    type_args: &[TypeAnnotation],
+) -> Result<Value, RuntimeError> {
+    // Convert type annotations to strings for Phase 3
+    let type_arg_names: Vec<String> = type_args.iter()
+        .map(|ta| match ta {
+            crate::ast::TypeAnnotation::Named(n) => n.clone(),
+            _ => "Unknown".to_string(),
+        })
+        .collect();
+
+    // Phase 2/3: Create a variant value with the provided arguments
+    if field_params.len() != args.len() {
+        return Err(RuntimeError::ArityMismatch {
+            expected: field_params.len(),
+            got: args.len(),
+        });
+    }
+
+    // Phase 3: Check type argument count for generic enums
+    if !type_params.is_empty() && !type_arg_names.is_empty()
+        && type_params.len() != type_arg_names.len() {
+            return Err(RuntimeError::Custom(format!(
+                "Type argument mismatch: expected {} type arguments, got {}",
+                type_params.len(),
+                type_arg_names.len()
+            )));
+        }
+
+    // Create the variant value with the arguments as fields
+    Ok(Value::VariantValue {
+        enum_name,
+        variant_name,
+        fields: args,
+        type_args: type_arg_names,  // Phase 3: Store type arguments
+    })
+}
+
+/// Read a field from a map or struct instance
+fn field_value(obj: Value, field: &str) -> Result<Value, RuntimeError> {
+    match obj {
+        Value::Frozen { value } => field_value(*value, field).map(Value::freeze),
+        Value::Tainted { value, sources } => field_value(*value, field).map(|value| value.taint(&sources)),
+        Value::Map(ref map) => {
+            map.get(field)
+                .cloned()
+                .ok_or_else(|| RuntimeError::FieldNotFound {
+                    field: field.to_string(),
+                    object: "Map".to_string(),
+                })
+        }
+        Value::StructInstance { struct_name, ref fields } => {
+            fields.get(field)
+                .cloned()
+                .ok_or_else(|| RuntimeError::FieldNotFound {
+                    field: field.to_string(),
+                    object: struct_name.clone(),
+                })
+        }
+        _ => Err(RuntimeError::TypeError {
+            expected: "Map or Struct".to_string(),
+            got: obj.type_name().to_string(),
+        }),
+    }
+}
+
+/// Index into a list or tuple (by number) or map (by text)
+fn index_value(obj: Value, idx: Value) -> Result<Value, RuntimeError> {
+    match (obj, idx) {
+        (Value::Frozen { value }, idx) => index_value(*value, idx).map(Value::freeze),
+        (Value::Tainted { value, sources }, idx) => index_value(*value, idx).map(|value| value.taint(&sources)),
+        (obj, Value::Tainted { value, sources }) => index_value(obj, *value).map(|value| value.taint(&sources)),
+        (Value::List(ref list) | Value::Tuple(ref list), ref idx @ (Value::Number(_) | Value::Integer(_))) => {
+            let index = list_position(idx)?;
+            if index < list.len() {
+                Ok(list[index].clone())
+            } else {
+                Err(RuntimeError::IndexOutOfBounds {
+                    index,
+                    length: list.len(),
+                })
+            }
+        }
+        (Value::Map(ref map), Value::Text(key)) => {
+            map.get(&key)
+                .cloned()
+                .ok_or_else(|| RuntimeError::FieldNotFound {
+                    field: key,
+                    object: "Map".to_string(),
+                })
+        }
+        (obj, idx) => Err(RuntimeError::TypeError {
+            expected: "List, Tuple or Map".to_string(),
+            got: alloc::format!("{} with {} index", obj.type_name(), idx.type_name()),
+        }),
+    }
+}
+
+/// The list position a Number or Integer index names
+///
+/// A Number is truncated as it always has been; a negative Integer is an
+/// error rather than wrapping around.
+fn list_position(index: &Value) -> Result<usize, RuntimeError> {
+    match index {
+        Value::Integer(n) => usize::try_from(*n).map_err(|_| RuntimeError::Custom(format!("Negative index {}", n))),
+        Value::Number(n) => Ok(*n as usize),
+        other => Err(RuntimeError::TypeError { expected: "Number or Integer".to_string(), got: other.type_name().to_string() }),
+    }
+}
+
+/// Refuse to `set` a part of a frozen value
+fn check_not_frozen(object: &AstNode, value: &Value) -> Result<(), RuntimeError> {
+    if !value.is_frozen() {
+        return Ok(());
+    }
+    let name = match object {
+        AstNode::Ident { name, .. } => name.clone(),
+        _ => "<expression>".to_string(),
+    };
+    Err(RuntimeError::FrozenValue(name))
+}
+
+/// Build a range value, validating that both bounds are Numbers
+///
+/// A tainted bound taints the range.
+fn range_value(start_val: Value, end_val: Value) -> Result<Value, RuntimeError> {
+    match (start_val, end_val) {
+        (Value::Tainted { value, sources }, end_val) => range_value(*value, end_val).map(|range| range.taint(&sources)),
+        (start_val, Value::Tainted { value, sources }) => range_value(start_val, *value).map(|range| range.taint(&sources)),
+        (start_val, end_val) => number_range(start_val, end_val),
+    }
+}
+
+/// Build a range from bounds with any taint already removed
+fn number_range(start_val: Value, end_val: Value) -> Result<Value, RuntimeError> {
+    match (&start_val, &end_val) {
+        (Value::Number(_), Value::Number(_)) => {
+            Ok(Value::Range {
+                start: Box::new(start_val),
+                end: Box::new(end_val),
+            })
+        }
+        (Value::Number(_), _) => {
+            Err(RuntimeError::TypeError {
+                expected: "Number".to_string(),
+                got: end_val.type_name().to_string(),
+            })
+        }
+        (_, _) => {
+            Err(RuntimeError::TypeError {
+                expected: "Number".to_string(),
+                got: start_val.type_name().to_string(),
+            })
+        }
+    }
+}
+
 impl Evaluator {
 
+    /// Invoke a trait method body with `self` and the remaining parameters bound.
+    fn call_trait_method(
+        &mut self,
+        method_body: &[AstNode],
+        method_params: &[Parameter],
+        return_type: Option<TypeAnnotation>,
+        self_value: Value,
+        args: &[Value],
+    ) -> Result<Value, RuntimeError> {
+        self.run_trait_method(method_body, method_params, return_type, self_value, args, false).0
+    }
+
+    /// Invoke a trait method that may `set` fields of `self`, returning its
+    /// result and `self` as the method left it
+    fn call_trait_method_mut(
+        &mut self,
+        method_body: &[AstNode],
+        method_params: &[Parameter],
+        return_type: Option<TypeAnnotation>,
+        self_value: Value,
+        args: &[Value],
+    ) -> Result<(Value, Value), RuntimeError> {
+        let (result, updated) = self.run_trait_method(method_body, method_params, return_type, self_value, args, true);
+        Ok((result?, updated.ok_or_else(|| RuntimeError::UndefinedVariable("self".to_string()))?))
+    }
+
+    fn run_trait_method(
+        &mut self,
+        method_body: &[AstNode],
+        method_params: &[Parameter],
+        return_type: Option<TypeAnnotation>,
+        self_value: Value,
+        args: &[Value],
+        mutable_self: bool,
+    ) -> (Result<Value, RuntimeError>, Option<Value>) {
+        self.environment.push_scope();
+        self.return_types.push(return_type);
+
+        // Bind 'self' parameter
+        if mutable_self {
+            self.environment.define_mut("self".to_string(), self_value);
+        } else {
+            self.environment.define("self".to_string(), self_value);
+        }
+
+        // Bind remaining parameters
+        for (param, arg) in method_params.iter().skip(1).zip(args.iter()) {
+            self.environment.define(param.name.clone(), arg.clone());
+        }
+
+        // Execute method body; its `defer` blocks run on the way out
+        let result = self.with_defer_frame(|this| this.eval_chant_body(method_body));
+        let updated = if mutable_self { self.environment.get("self").ok() } else { None };
+
+        // Restore environment
+        self.return_types.pop();
+        self.environment.pop_scope();
+
+        // Handle return
+        let result = match result {
+            Err(RuntimeError::Return(val)) => Ok(val),
+            other => other,
+        };
+        (result, updated)
+    }
+
+    /// Apply the try operator (`?`) to an evaluated value.
+    fn try_value(&mut self, value: Value) -> Result<Value, RuntimeError> {
+        // Check if value is an Outcome
+        match value {
+            Value::Outcome { success, value: boxed_val } => {
+                if success {
+                    // Triumph: unwrap the value
+                    Ok(*boxed_val)
+                } else {
+                    // Mishap: convert the error to the chant's declared
+                    // error type (if needed), then propagate it by returning
+                    let error = self.convert_error(*boxed_val)?;
+                    Err(RuntimeError::Return(Value::Outcome {
+                        success: false,
+                        value: Box::new(error),
+                    }))
+                }
+            }
+            _ => {
+                // Type error: ? can only be used on Outcome
+                Err(RuntimeError::TypeError {
+                    expected: "Outcome".to_string(),
+                    got: value.type_name().to_string(),
+                })
+            }
+        }
+    }
+
+    /// An implementation of `aspect` for `target_type`, whatever the
+    /// aspect's type arguments
+    fn aspect_impl(&self, aspect: &str, target_type: &str) -> Option<&TraitImplementation> {
+        let first = TraitImplKey {
+            aspect_name: aspect.to_string(),
+            target_type: target_type.to_string(),
+            type_args: Vec::new(),
+        };
+        self.trait_implementations
+            .range(first..)
+            .next()
+            .filter(|(key, _)| key.aspect_name == aspect && key.target_type == target_type)
+            .map(|(_, trait_impl)| trait_impl)
+    }
+
+    /// Convert an error propagated by `?` into the enclosing chant's error type.
+    ///
+    /// When the current chant declares a return type of `Outcome<T, E>` and the
+    /// error is of some other type `F`, the `convert` method of an
+    /// `embody ConvertError<E> for F` implementation is applied to it.
+    /// Errors without a matching conversion are propagated unchanged.
+    fn convert_error(&mut self, error: Value) -> Result<Value, RuntimeError> {
+        let target = match self.return_types.last() {
+            Some(Some(TypeAnnotation::Parametrized { name, type_args }))
+                if name == "Outcome" && type_args.len() == 2 =>
+            {
+                self.type_annotation_to_string(&type_args[1])
+            }
+            _ => return Ok(error),
+        };
+
+        let source = self.error_type_string(&error);
+        if source == target {
+            return Ok(error);
+        }
+
+        let key = TraitImplKey {
+            aspect_name: "ConvertError".to_string(),
+            target_type: source,
+            type_args: vec![target],
+        };
+        let conversion = self.trait_implementations.get(&key).and_then(|trait_impl| {
+            let body = trait_impl.methods.get("convert")?;
+            let params = trait_impl.method_params.get("convert")?;
+            let return_type = trait_impl.method_return_types.get("convert").cloned().flatten();
+            Some((body.clone(), params.clone(), return_type))
+        });
+
+        match conversion {
+            Some((body, params, return_type)) => {
+                self.call_trait_method(&body, &params, return_type, error, &[])
+            }
+            None => Ok(error),
+        }
+    }
+
+    /// `to_text`, with forms embodying `Display` rendered by their `describe`
+    fn render_text(&mut self, value: &Value) -> Result<Value, RuntimeError> {
+        crate::runtime::render_text(value, &mut |value| self.describe(value)).map(Value::Text)
+    }
+
+    /// Write the arguments of `print`/`println` to the output sink,
+    /// separated by spaces
+    fn print(&mut self, args: &[Value], newline: bool) -> Result<Value, RuntimeError> {
+        let mut line = String::new();
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                line.push(' ');
+            }
+            if let Value::Text(text) = self.render_text(arg)? {
+                line.push_str(&text);
+            }
+        }
+        if newline {
+            line.push('\n');
+        }
+        if let Some(sink) = self.output.as_mut() {
+            sink.write(&line);
+        }
+        Ok(Value::Nothing)
+    }
+
+    /// Text from the `describe(self)` method of a value's `Display` aspect,
+    /// or `None` if its type does not embody one
+    ///
+    /// A derived `Display` (no `describe`) prints a form's fields in
+    /// declaration order; derived variants keep the structural rendering.
+    fn describe(&mut self, value: &Value) -> Option<Result<String, RuntimeError>> {
+        if !matches!(value, Value::StructInstance { .. } | Value::VariantValue { .. }) {
+            return None;
+        }
+        let trait_impl = self.aspect_impl("Display", &self.error_type_string(value))?;
+        let Some(body) = trait_impl.methods.get("describe").cloned() else {
+            return self.describe_derived(value);
+        };
+        let params = trait_impl.method_params.get("describe")?.clone();
+        let return_type = trait_impl.method_return_types.get("describe").cloned().flatten();
+
+        Some(match self.call_trait_method(&body, &params, return_type, value.clone(), &[]) {
+            Ok(Value::Text(text)) => Ok(text),
+            Ok(other) => Err(RuntimeError::TypeError {
+                expected: "Text".to_string(),
+                got: other.type_name().to_string(),
+            }),
+            Err(error) => Err(error),
+        })
+    }
+
+    /// Derived `Display` of a form: `Name { field: value, ... }` with fields
+    /// in declaration order
+    fn describe_derived(&mut self, value: &Value) -> Option<Result<String, RuntimeError>> {
+        let Value::StructInstance { struct_name, fields } = value else {
+            return None;
+        };
+        let Ok(Value::StructDef { fields: declared, .. }) = self.environment.get(struct_name) else {
+            return None;
+        };
+        let mut field_strings = Vec::new();
+        for field in declared {
+            if let Some(field_value) = fields.get(&field.name) {
+                match crate::runtime::render_text(field_value, &mut |value| self.describe(value)) {
+                    Ok(text) => field_strings.push(format!("{}: {}", field.name, text)),
+                    Err(error) => return Some(Err(error)),
+                }
+            }
+        }
+        Some(Ok(format!("{} {{ {} }}", struct_name, field_strings.join(", "))))
+    }
+
+    /// `variant_index` of a user-defined variant, numbered by its enum's
+    /// definition; `None` leaves other values to the builtin
+    fn variant_index(&mut self, value: &Value) -> Option<Result<Value, RuntimeError>> {
+        let Value::VariantValue { enum_name, variant_name, .. } = value else {
+            return None;
+        };
+        let discriminant = match self.environment.get(enum_name) {
+            Ok(Value::VariantDef { variants, .. }) => case_discriminant(&variants, variant_name),
+            _ => None,
+        };
+        Some(discriminant.map(|d| Value::Number(d as f64)).ok_or_else(|| {
+            RuntimeError::Custom(format!("{} is not a case of a defined variant {}", variant_name, enum_name))
+        }))
+    }
+
+    /// `hash`, with forms and variants hashed through their `Hashable` aspect
+    fn hash_value(&mut self, value: &Value) -> Result<Value, RuntimeError> {
+        crate::runtime::hash_value(value, &mut |value| self.hash_with_aspect(value)).map(crate::runtime::hash_to_number)
+    }
+
+    /// Hash of a form or variant from its `Hashable` aspect: the `hash(self)`
+    /// method if it has one, the structural hash if derived
+    fn hash_with_aspect(&mut self, value: &Value) -> Option<Result<u64, RuntimeError>> {
+        if !matches!(value, Value::StructInstance { .. } | Value::VariantValue { .. }) {
+            return None;
+        }
+        let type_name = self.error_type_string(value);
+        let Some(trait_impl) = self.aspect_impl("Hashable", &type_name) else {
+            return Some(Err(RuntimeError::Custom(format!("{} does not embody Hashable", type_name))));
+        };
+        let body = trait_impl.methods.get("hash")?.clone();
+        let params = trait_impl.method_params.get("hash").cloned().unwrap_or_default();
+        let return_type = trait_impl.method_return_types.get("hash").cloned().flatten();
+
+        Some(match self.call_trait_method(&body, &params, return_type, value.clone(), &[]) {
+            Ok(Value::Number(n)) => Ok(n.to_bits()),
+            Ok(other) => Err(RuntimeError::TypeError {
+                expected: "Number".to_string(),
+                got: other.type_name().to_string(),
+            }),
+            Err(error) => Err(error),
+        })
+    }
+
+    /// `list_sort`, ordering elements with [`Evaluator::compare_values`]
+    fn sort_list(&mut self, list: &Value) -> Result<Value, RuntimeError> {
+        let Value::List(items) = list else {
+            return Err(RuntimeError::TypeError {
+                expected: "List".to_string(),
+                got: list.type_name().to_string(),
+            });
+        };
+        crate::runtime::sort_values(items.clone(), &mut |left, right| self.compare_values(left, right))
+            .map(Value::List)
+    }
+
+    /// Order two values for the comparison operators and `list_sort`
+    ///
+    /// Numbers, text and truths compare naturally, and lists and tuples
+    /// lexicographically.
+    /// Forms and variants must embody `Ordered`: its `compare(self, other)`
+    /// yields a negative, zero or positive number, and an embodiment without
+    /// `compare` derives a lexicographic order (fields in declaration order,
+    /// variants by declaration order first).
+    fn compare_values(&mut self, left: &Value, right: &Value) -> Result<core::cmp::Ordering, RuntimeError> {
+        match (left, right) {
+            (Value::Number(l), Value::Number(r)) => {
+                l.partial_cmp(r).ok_or_else(|| RuntimeError::Custom("Cannot order NaN".to_string()))
+            }
+            (Value::BigInt(l), Value::BigInt(r)) => Ok(l.cmp(r)),
+            (Value::Integer(l), Value::Integer(r)) => Ok(l.cmp(r)),
+            (Value::Text(l), Value::Text(r)) => Ok(l.cmp(r)),
+            (Value::Truth(l), Value::Truth(r)) => Ok(l.cmp(r)),
+            (Value::List(l), Value::List(r)) | (Value::Tuple(l), Value::Tuple(r)) => self.compare_sequences(l, r),
+            (Value::StructInstance { .. } | Value::VariantValue { .. }, _) => self.compare_ordered(left, right),
+            _ => Err(RuntimeError::TypeError {
+                expected: left.type_name().to_string(),
+                got: right.type_name().to_string(),
+            }),
+        }
+    }
+
+    /// Lexicographic order of two sequences of values
+    fn compare_sequences(&mut self, left: &[Value], right: &[Value]) -> Result<core::cmp::Ordering, RuntimeError> {
+        for (l, r) in left.iter().zip(right) {
+            let ordering = self.compare_values(l, r)?;
+            if ordering.is_ne() {
+                return Ok(ordering);
+            }
+        }
+        Ok(left.len().cmp(&right.len()))
+    }
+
+    /// Order two forms or variants through their `Ordered` aspect
+    fn compare_ordered(&mut self, left: &Value, right: &Value) -> Result<core::cmp::Ordering, RuntimeError> {
+        let type_name = self.error_type_string(left);
+        let other_type = self.error_type_string(right);
+        if other_type != type_name {
+            return Err(RuntimeError::TypeError { expected: type_name, got: other_type });
+        }
+        let Some(trait_impl) = self.aspect_impl("Ordered", &type_name) else {
+            return Err(RuntimeError::Custom(format!("{} does not embody Ordered", type_name)));
+        };
+        let Some(body) = trait_impl.methods.get("compare").cloned() else {
+            return self.compare_derived(left, right);
+        };
+        let params = trait_impl.method_params.get("compare").cloned().unwrap_or_default();
+        let return_type = trait_impl.method_return_types.get("compare").cloned().flatten();
+
+        match self.call_trait_method(&body, &params, return_type, left.clone(), core::slice::from_ref(right))? {
+            Value::Number(n) => Ok(n.partial_cmp(&0.0).unwrap_or(core::cmp::Ordering::Equal)),
+            other => Err(RuntimeError::TypeError {
+                expected: "Number".to_string(),
+                got: other.type_name().to_string(),
+            }),
+        }
+    }
+
+    /// Derived `Ordered`: fields in declaration order; variants by their
+    /// position in the variant definition, then their fields
+    fn compare_derived(&mut self, left: &Value, right: &Value) -> Result<core::cmp::Ordering, RuntimeError> {
+        match (left, right) {
+            (Value::StructInstance { struct_name, fields: l }, Value::StructInstance { fields: r, .. }) => {
+                let names: Vec<String> = match self.environment.get(struct_name) {
+                    Ok(Value::StructDef { fields, .. }) => fields.into_iter().map(|field| field.name).collect(),
+                    _ => l.keys().cloned().collect(),
+                };
+                let l: Vec<Value> = names.iter().filter_map(|name| l.get(name).cloned()).collect();
+                let r: Vec<Value> = names.iter().filter_map(|name| r.get(name).cloned()).collect();
+                self.compare_sequences(&l, &r)
+            }
+            (
+                Value::VariantValue { enum_name, variant_name: l_case, fields: l, .. },
+                Value::VariantValue { variant_name: r_case, fields: r, .. },
+            ) => {
+                let cases = match self.environment.get(enum_name) {
+                    Ok(Value::VariantDef { variants, .. }) => variants,
+                    _ => Vec::new(),
+                };
+                let discriminant = |case: &String| case_discriminant(&cases, case);
+                let ordering = discriminant(l_case).cmp(&discriminant(r_case)).then_with(|| l_case.cmp(r_case));
+                if ordering.is_ne() {
+                    return Ok(ordering);
+                }
+                self.compare_sequences(l, r)
+            }
+            _ => Err(RuntimeError::TypeError {
+                expected: left.type_name().to_string(),
+                got: right.type_name().to_string(),
+            }),
+        }
+    }
+
+    /// Get the type name of an error value for conversion lookup.
+    ///
+    /// Unlike `value_type_string`, enum variants are named by their enum.
+    fn error_type_string(&self, value: &Value) -> String {
+        match value {
+            Value::VariantValue { enum_name, .. } => enum_name.clone(),
+            _ => self.value_type_string(value),
+        }
+    }
+
     /// Get the type name of a runtime value for trait lookup
     fn value_type_string(&self, value: &Value) -> String {
         match value {
@@ -2877,4 +7012,40 @@ end
         let result = eval_program(source);
         assert!(result.is_ok(), "Builtins should be available in modules");
     }
+
+    #[test]
+    fn test_environment_resolved_slots() {
+        let mut env = Environment::new();
+        env.define("a".to_string(), Value::Number(1.0));
+        env.define_mut("b".to_string(), Value::Number(2.0));
+        env.push_scope();
+        env.define("c".to_string(), Value::Number(3.0));
+
+        assert_eq!(env.get_resolved("c", ScopeSlot { depth: 0, slot: 0 }), Ok(Value::Number(3.0)));
+        assert_eq!(env.get_resolved("b", ScopeSlot { depth: 1, slot: 1 }), Ok(Value::Number(2.0)));
+
+        env.set_resolved("b", ScopeSlot { depth: 1, slot: 1 }, Value::Number(5.0)).unwrap();
+        assert_eq!(env.get("b"), Ok(Value::Number(5.0)));
+        assert_eq!(
+            env.set_resolved("a", ScopeSlot { depth: 1, slot: 0 }, Value::Nothing),
+            Err(RuntimeError::ImmutableBinding("a".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_environment_stale_slots_fall_back() {
+        let mut env = Environment::new();
+        env.define("a".to_string(), Value::Number(1.0));
+        env.push_scope();
+        env.define("a".to_string(), Value::Number(2.0));
+
+        // Slot holds a different name, or the depth is out of range
+        env.define("b".to_string(), Value::Number(3.0));
+        assert_eq!(env.get_resolved("b", ScopeSlot { depth: 0, slot: 0 }), Ok(Value::Number(3.0)));
+        assert_eq!(env.get_resolved("b", ScopeSlot { depth: 7, slot: 0 }), Ok(Value::Number(3.0)));
+        assert_eq!(
+            env.get_resolved("missing", ScopeSlot { depth: 0, slot: 0 }),
+            Err(RuntimeError::UndefinedVariable("missing".to_string()))
+        );
+    }
 }
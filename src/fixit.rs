@@ -0,0 +1,280 @@
+//! # Fix-it Suggestions
+//!
+//! Edits that would fix an error, attached to its diagnostic so tools can
+//! apply them: the language server offers them as quick fixes, and
+//! `gwc fix` applies the safe ones to files.
+//!
+//! A [`Suggestion`] replaces the text from one location up to (not
+//! including) another with new text; an insertion replaces nothing. Its
+//! [`Applicability`] says whether a tool may apply it unattended. This
+//! module suggests:
+//!
+//! - `end` at the end of a file that stops inside a block
+//! - the keyword an identifier misspells (`shuold` for `should`), applied
+//!   unattended only when it is the one keyword that close and the file
+//!   then parses further
+//! - an `otherwise` arm for a `match` without a catch-all arm
+//! - borrowing a value instead of moving it, when it is used after the move
+//!
+//! ```
+//! use glimmer_weave::fixit::fix;
+//!
+//! let fixed = fix("chant greet(name) then\n    shuold name is \"\" then\n        yield \"hi\"\n    end\n");
+//! assert_eq!(fixed.source, "chant greet(name) then\n    should name is \"\" then\n        yield \"hi\"\n    end\nend\n");
+//! assert_eq!(fixed.applied.len(), 2);
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::borrow_checker::BorrowChecker;
+use crate::lexer::Lexer;
+use crate::parser::{ParseError, Parser};
+use crate::semantic::SemanticAnalyzer;
+use crate::source_location::{SourceLocation, SourceSpan};
+use crate::token::{PositionedToken, Token};
+
+/// How safely a tool may apply a suggestion without asking
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Applicability {
+    /// The fix is certainly what was meant; `gwc fix` applies it
+    MachineApplicable,
+    /// The fix may be what was meant, or may change the meaning
+    MaybeIncorrect,
+    /// The fix holds placeholder code for the author to fill in
+    HasPlaceholders,
+}
+
+/// An edit that would fix a diagnostic's problem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// What the edit does, e.g. "add `end` to close the block"
+    pub message: String,
+    /// First replaced character (1-indexed line and column)
+    pub start: SourceLocation,
+    /// Character just past the replaced text; equal to `start` for an
+    /// insertion
+    pub end: SourceLocation,
+    /// Text put in place of the replaced text
+    pub replacement: String,
+    /// Whether tools may apply the edit unattended
+    pub applicability: Applicability,
+}
+
+impl Suggestion {
+    /// Replace the characters of `span` (through its last column) with
+    /// `replacement`
+    pub fn replace(message: impl Into<String>, span: &SourceSpan, replacement: impl Into<String>, applicability: Applicability) -> Self {
+        let end = SourceLocation::new(span.end.line, span.end.column + 1);
+        Suggestion { message: message.into(), start: span.start.clone(), end, replacement: replacement.into(), applicability }
+    }
+
+    /// Insert `text` before the character at `at`
+    pub fn insert(message: impl Into<String>, at: &SourceLocation, text: impl Into<String>, applicability: Applicability) -> Self {
+        Suggestion { message: message.into(), start: at.clone(), end: at.clone(), replacement: text.into(), applicability }
+    }
+
+    /// Whether `gwc fix` applies it
+    pub fn is_machine_applicable(&self) -> bool {
+        self.applicability == Applicability::MachineApplicable
+    }
+}
+
+/// Keywords an identifier may misspell; short ones are left out, since
+/// too many names are one edit away from them
+const KEYWORDS: &[&str] = &[
+    "bind", "weave", "should", "then", "otherwise", "whilst", "chant", "yield", "match", "when", "with",
+    "attempt", "harmonize", "defer", "request", "justification", "for", "each", "break", "continue",
+    "form", "variant", "aspect", "embody", "grove", "offer", "summon", "gather", "borrow", "end",
+];
+
+/// Suggestions for a parse of `source` (lexed as `tokens`) failing with
+/// `error`
+pub fn for_parse_error(source: &str, tokens: &[PositionedToken], error: &ParseError) -> Vec<Suggestion> {
+    let mut suggestions = Vec::new();
+    let Some(at) = tokens.get(error.position).or(tokens.last()) else {
+        return suggestions;
+    };
+
+    if matches!(at.token, Token::Eof) && error.message.starts_with("Expected End") {
+        let location = at.span.to_source_location();
+        // Close the block on a line of its own
+        let text = if location.column > 1 { "\nend" } else { "end\n" };
+        suggestions.push(Suggestion::insert("add `end` to close the block", &location, text, Applicability::MachineApplicable));
+    }
+
+    // A misspelled keyword is usually where parsing failed or starts the
+    // line it failed on
+    let line_start = tokens[..error.position.min(tokens.len())]
+        .iter()
+        .rposition(|token| matches!(token.token, Token::Newline))
+        .map_or(0, |newline| newline + 1);
+    let mut candidates = Vec::new();
+    for index in [line_start, error.position] {
+        if candidates.contains(&index) {
+            continue;
+        }
+        candidates.push(index);
+        let Some(PositionedToken { token: Token::Ident(word), span }) = tokens.get(index) else {
+            continue;
+        };
+        let close: Vec<&str> = KEYWORDS.iter().copied().filter(|keyword| is_one_edit_apart(word, keyword)).collect();
+        let [keyword] = close[..] else {
+            continue;
+        };
+        let span = SourceSpan::new(span.to_source_location(), SourceLocation::new(span.line, span.column + word.chars().count() - 1));
+        let mut suggestion = Suggestion::replace(format!("did you mean `{}`?", keyword), &span, keyword, Applicability::MaybeIncorrect);
+        if parses_further(&apply(source, core::slice::from_ref(&suggestion)), error.position) {
+            suggestion.applicability = Applicability::MachineApplicable;
+        }
+        suggestions.push(suggestion);
+    }
+    suggestions
+}
+
+/// Whether `source` parses, or fails later than token `position`
+fn parses_further(source: &str, position: usize) -> bool {
+    match Parser::new(Lexer::new(source).tokenize_positioned()).parse() {
+        Ok(_) => true,
+        Err(error) => error.position > position,
+    }
+}
+
+/// Whether one insertion, deletion, substitution or swap of neighbours
+/// turns `word` into `keyword` (and they differ)
+fn is_one_edit_apart(word: &str, keyword: &str) -> bool {
+    let a: Vec<char> = word.chars().collect();
+    let b: Vec<char> = keyword.chars().collect();
+    if a == b || a.len() < 3 || a.len().abs_diff(b.len()) > 1 {
+        return false;
+    }
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let (rest_a, rest_b) = (&a[prefix..], &b[prefix..]);
+    match rest_a.len().cmp(&rest_b.len()) {
+        core::cmp::Ordering::Less => rest_a == &rest_b[1..],
+        core::cmp::Ordering::Greater => &rest_a[1..] == rest_b,
+        core::cmp::Ordering::Equal => {
+            rest_a[1..] == rest_b[1..]
+                || (rest_a.len() >= 2 && rest_a[0] == rest_b[1] && rest_a[1] == rest_b[0] && rest_a[2..] == rest_b[2..])
+        }
+    }
+}
+
+/// Every suggestion for the problems in `source`: its parse error if it
+/// does not parse, otherwise its semantic and borrow errors
+pub fn suggestions(source: &str) -> Vec<Suggestion> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = match Parser::new(tokens.clone()).parse() {
+        Ok(ast) => ast,
+        Err(error) => return for_parse_error(source, &tokens, &error),
+    };
+    let mut suggestions = Vec::new();
+    if let Err(errors) = SemanticAnalyzer::new().analyze(&ast) {
+        suggestions.extend(errors.iter().flat_map(|error| error.suggestions()));
+    }
+    if let Err(errors) = BorrowChecker::new().check(&ast) {
+        suggestions.extend(errors.iter().flat_map(|error| error.suggestions()));
+    }
+    suggestions
+}
+
+/// Source with every suggestion applied, in order of position; a
+/// suggestion overlapping one already applied is skipped
+pub fn apply(source: &str, suggestions: &[Suggestion]) -> String {
+    let mut edits: Vec<(usize, usize, &str)> = suggestions
+        .iter()
+        .filter_map(|suggestion| {
+            let start = offset(source, &suggestion.start)?;
+            let end = offset(source, &suggestion.end)?.max(start);
+            Some((start, end, suggestion.replacement.as_str()))
+        })
+        .collect();
+    edits.sort_by_key(|&(start, end, _)| (start, end));
+
+    let mut fixed = String::with_capacity(source.len());
+    let mut copied = 0;
+    for (start, end, replacement) in edits {
+        // Insertions at the same place both apply; overlaps do not
+        if start < copied {
+            continue;
+        }
+        fixed.push_str(&source[copied..start]);
+        fixed.push_str(replacement);
+        copied = end;
+    }
+    fixed.push_str(&source[copied..]);
+    fixed
+}
+
+/// Byte offset of `location` in `source`; a column past the end of its
+/// line means the line's end
+fn offset(source: &str, location: &SourceLocation) -> Option<usize> {
+    if !location.is_known() {
+        return None;
+    }
+    let mut line_start = 0;
+    for _ in 1..location.line {
+        line_start += source[line_start..].find('\n')? + 1;
+    }
+    let line = &source[line_start..];
+    let line_end = line.find('\n').unwrap_or(line.len());
+    let column = line[..line_end].char_indices().nth(location.column - 1).map_or(line_end, |(offset, _)| offset);
+    Some(line_start + column)
+}
+
+/// Upper bound on fixing rounds; each round can only fix the first parse
+/// error, so unclosed blocks take one round each
+const MAX_FIX_ROUNDS: usize = 32;
+
+/// Result of [`fix`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixed {
+    /// Source with the fixes applied
+    pub source: String,
+    /// Suggestions applied, in the order they were
+    pub applied: Vec<Suggestion>,
+}
+
+/// Apply every machine-applicable suggestion for `source`, again and again
+/// until none is left
+pub fn fix(source: &str) -> Fixed {
+    let mut fixed = Fixed { source: source.to_string(), applied: Vec::new() };
+    for _ in 0..MAX_FIX_ROUNDS {
+        let safe: Vec<Suggestion> = suggestions(&fixed.source).into_iter().filter(Suggestion::is_machine_applicable).collect();
+        if safe.is_empty() {
+            break;
+        }
+        fixed.source = apply(&fixed.source, &safe);
+        fixed.applied.extend(safe);
+    }
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_edit_apart() {
+        assert!(is_one_edit_apart("shuold", "should"));
+        assert!(is_one_edit_apart("chnt", "chant"));
+        assert!(is_one_edit_apart("thenn", "then"));
+        assert!(is_one_edit_apart("wheb", "when"));
+        assert!(!is_one_edit_apart("should", "should"));
+        assert!(!is_one_edit_apart("sholud", "shield"));
+        assert!(!is_one_edit_apart("en", "end"));
+    }
+
+    #[test]
+    fn test_apply_handles_unicode_and_overlaps() {
+        let source = "bind café to 1\nbind x to café";
+        let at = |line, column| SourceLocation::new(line, column);
+        let edits = [
+            Suggestion { message: String::new(), start: at(2, 11), end: at(2, 15), replacement: "thé".to_string(), applicability: Applicability::MaybeIncorrect },
+            Suggestion { message: String::new(), start: at(2, 12), end: at(2, 13), replacement: "!".to_string(), applicability: Applicability::MaybeIncorrect },
+            Suggestion::insert("", &at(1, 99), " # one", Applicability::MaybeIncorrect),
+        ];
+        assert_eq!(apply(source, &edits), "bind café to 1 # one\nbind x to thé");
+    }
+}
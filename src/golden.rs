@@ -0,0 +1,258 @@
+//! # Golden Files
+//!
+//! Checks compiler output against expected output checked in next to the
+//! fixture programs, so a change to a backend shows up as a reviewable diff
+//! of the code it generates rather than as a behavior change found later.
+//!
+//! Every `*.gw` file in a fixture directory is compiled by each
+//! [`Backend`], and the output is compared with the golden file beside it:
+//! `loop.gw` against `loop.s` for x86-64 assembly and `loop.bc` for the
+//! bytecode disassembly. Output is [`normalize`]d first, so renumbered
+//! labels alone don't fail the check.
+//!
+//! In update mode (see [`GoldenSuite::update`], or set [`UPDATE_ENV`]) the
+//! golden files are rewritten with the current output instead, and the
+//! report lists the ones that changed. Review them like any other diff.
+//!
+//! ```no_run
+//! use glimmer_weave::golden::GoldenSuite;
+//!
+//! let report = GoldenSuite::new("tests/golden").run().unwrap();
+//! assert!(report.passed(), "{}", report);
+//! ```
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::bytecode::Disassembler;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+
+/// Environment variable that turns on update mode when set to anything but
+/// `0`
+pub const UPDATE_ENV: &str = "GLIMMER_UPDATE_GOLDEN";
+
+/// Code generator whose output is checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// x86-64 assembly from [`codegen`](crate::codegen)
+    Assembly,
+    /// Disassembled bytecode from [`bytecode_compiler`](crate::bytecode_compiler)
+    Bytecode,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 2] = [Backend::Assembly, Backend::Bytecode];
+
+    /// Extension of this backend's golden files
+    pub fn extension(self) -> &'static str {
+        match self {
+            Backend::Assembly => "s",
+            Backend::Bytecode => "bc",
+        }
+    }
+
+    /// Compile `source`, normalized for comparison
+    pub fn render(self, source: &str) -> Result<String, String> {
+        let ast = Parser::new(Lexer::new(source).tokenize_positioned())
+            .parse()
+            .map_err(|error| format!("parse error: {}", error.message))?;
+        let output = match self {
+            Backend::Assembly => crate::codegen::compile_to_asm(&ast)?,
+            Backend::Bytecode => {
+                let chunk = crate::bytecode_compiler::compile(&ast).map_err(|error| format!("{:?}", error))?;
+                Disassembler::new(&chunk).disassemble()
+            }
+        };
+        Ok(normalize(&output))
+    }
+}
+
+/// Renumber local labels in order of first use and drop trailing
+/// whitespace
+///
+/// Labels are `.L` names ending in `_<number>`; labels that shared a number
+/// (`.L_else_7`, `.L_if_end_7`) still share one afterwards.
+///
+/// ```
+/// use glimmer_weave::golden::normalize;
+///
+/// let asm = "    jmp .L_else_7  \n.L_then_7:\n    je .L_loop_3\n";
+/// assert_eq!(normalize(asm), "    jmp .L_else_0\n.L_then_0:\n    je .L_loop_1\n");
+/// ```
+pub fn normalize(output: &str) -> String {
+    let mut numbers: Vec<String> = Vec::new();
+    let mut normalized = String::with_capacity(output.len());
+    for line in output.lines() {
+        let mut rest = line.trim_end();
+        while let Some(start) = rest.find(".L") {
+            normalized.push_str(&rest[..start]);
+            let name_len = rest[start..]
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len() - start);
+            let name = &rest[start..start + name_len];
+            match name.rsplit_once('_') {
+                Some((prefix, number)) if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) => {
+                    let index = numbers.iter().position(|seen| seen == number).unwrap_or_else(|| {
+                        numbers.push(number.to_string());
+                        numbers.len() - 1
+                    });
+                    normalized.push_str(&format!("{}_{}", prefix, index));
+                }
+                _ => normalized.push_str(name),
+            }
+            rest = &rest[start + name_len..];
+        }
+        normalized.push_str(rest);
+        normalized.push('\n');
+    }
+    normalized
+}
+
+/// A golden file that didn't match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenFailure {
+    pub golden: PathBuf,
+    /// What went wrong: the first differing line, a missing golden file or
+    /// a compile error
+    pub problem: String,
+}
+
+/// Outcome of a [`GoldenSuite`] run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GoldenReport {
+    /// Golden files compared or written
+    pub checked: usize,
+    /// Golden files update mode rewrote because the output changed
+    pub updated: Vec<PathBuf>,
+    pub failures: Vec<GoldenFailure>,
+}
+
+impl GoldenReport {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for GoldenReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} golden files, {} failed, {} updated", self.checked, self.failures.len(), self.updated.len())?;
+        for failure in &self.failures {
+            writeln!(f, "  {}: {}", failure.golden.display(), failure.problem)?;
+        }
+        for path in &self.updated {
+            writeln!(f, "  updated {}", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// Fixture directory checked against its golden files
+#[derive(Debug, Clone)]
+pub struct GoldenSuite {
+    dir: PathBuf,
+    backends: Vec<Backend>,
+    update: bool,
+}
+
+impl GoldenSuite {
+    /// Suite over every backend, in update mode if [`UPDATE_ENV`] is set
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let update = std::env::var(UPDATE_ENV).is_ok_and(|value| value != "0");
+        GoldenSuite { dir: dir.into(), backends: Backend::ALL.to_vec(), update }
+    }
+
+    /// Check only these backends
+    pub fn backends(mut self, backends: &[Backend]) -> Self {
+        self.backends = backends.to_vec();
+        self
+    }
+
+    /// Rewrite golden files instead of comparing with them
+    pub fn update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// Compile every fixture with every backend; only reading the fixtures
+    /// or writing golden files fails the run itself
+    pub fn run(&self) -> io::Result<GoldenReport> {
+        let mut fixtures = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "gw") {
+                fixtures.push(path);
+            }
+        }
+        fixtures.sort();
+
+        let mut report = GoldenReport::default();
+        for fixture in &fixtures {
+            let source = std::fs::read_to_string(fixture)?;
+            for &backend in &self.backends {
+                let golden = fixture.with_extension(backend.extension());
+                report.checked += 1;
+                let actual = match backend.render(&source) {
+                    Ok(actual) => actual,
+                    Err(error) => {
+                        report.failures.push(GoldenFailure { golden, problem: format!("compile error: {}", error) });
+                        continue;
+                    }
+                };
+                self.check(&golden, &actual, &mut report)?;
+            }
+        }
+        Ok(report)
+    }
+
+    fn check(&self, golden: &Path, actual: &str, report: &mut GoldenReport) -> io::Result<()> {
+        let expected = match std::fs::read_to_string(golden) {
+            Ok(expected) => Some(expected),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(error) => return Err(error),
+        };
+        if expected.as_deref() == Some(actual) {
+            return Ok(());
+        }
+        if self.update {
+            std::fs::write(golden, actual)?;
+            report.updated.push(golden.to_path_buf());
+            return Ok(());
+        }
+        let problem = match expected {
+            Some(expected) => first_difference(&expected, actual),
+            None => format!("missing; set {}=1 to create it", UPDATE_ENV),
+        };
+        report.failures.push(GoldenFailure { golden: golden.to_path_buf(), problem });
+        Ok(())
+    }
+}
+
+/// The first line `actual` differs from `expected` at
+fn first_difference(expected: &str, actual: &str) -> String {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(want), Some(got)) if want == got => line += 1,
+            (Some(want), Some(got)) => return format!("line {}: expected `{}`, got `{}`", line, want.trim(), got.trim()),
+            (Some(want), None) => return format!("line {}: expected `{}`, got the end of the output", line, want.trim()),
+            (None, Some(got)) => return format!("line {}: expected the end of the output, got `{}`", line, got.trim()),
+            // Same lines, different line endings
+            (None, None) => return format!("line {}: line endings differ", line),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_difference() {
+        assert_eq!(first_difference("a\nb\n", "a\nc\n"), "line 2: expected `b`, got `c`");
+        assert_eq!(first_difference("a\n", "a\nb\n"), "line 2: expected the end of the output, got `b`");
+    }
+}
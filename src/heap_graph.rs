@@ -0,0 +1,368 @@
+//! # Heap Graphs
+//!
+//! A dump of everything a running script keeps alive, for finding what a
+//! long-lived AethelOS service retains between runs. Starting from the
+//! global bindings, a [`HeapGraph`] has a node per value — lists, maps and
+//! forms with their contents, `Shared` values with their reference counts,
+//! `Cell`s with their borrow state, chants with the environments their
+//! closures captured — and an edge from each value to what it holds, labelled
+//! with the index, key, field or binding name.
+//!
+//! Values are copied when they are bound, so the graph is a tree except for
+//! text builders: copies of a builder share one buffer, which gets a single
+//! node with an edge from each copy ([`HeapGraph::shared`]). Retention to look
+//! for is a chant whose closure holds on to a large value, or a global that
+//! [`HeapGraph::retainers`] ranks far above the rest.
+//!
+//! The graph exports to Graphviz ([`HeapGraph::to_dot`]) and to JSON
+//! ([`HeapGraph::to_json`]); `gwc heap` prints either for a script.
+//!
+//! ```
+//! use glimmer_weave::{Evaluator, Lexer, Parser};
+//!
+//! let source = "bind cache to [1, 2, 3]\nchant lookup(i) then\n    cache[i]\nend\n";
+//! let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap();
+//! let mut evaluator = Evaluator::new();
+//! evaluator.eval(&ast).unwrap();
+//!
+//! let graph = evaluator.heap_graph();
+//! let lookup = graph.binding("lookup").unwrap();
+//! assert!(graph.edges_from(lookup).any(|edge| edge.label == "closure"));
+//! assert!(graph.to_dot().starts_with("digraph heap {"));
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::mem::size_of;
+
+use crate::eval::{IteratorState, Value};
+
+/// Depth below the globals past which values are dumped without contents
+pub const DEFAULT_MAX_DEPTH: usize = 32;
+
+/// A value, or an environment, in the graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeapNode {
+    pub id: usize,
+    /// Type of the value (`"List"`, `"Cell"`, ...), `"Environment"` for the
+    /// globals and closure environments, `"Buffer"` for a builder's text
+    pub kind: String,
+    /// Short description: the value of a scalar, the size of a collection
+    pub label: String,
+    /// Estimated bytes the node owns, not counting the nodes it points to
+    pub bytes: usize,
+    /// Reference count of a `Shared` value, or owners of a builder's buffer
+    pub ref_count: Option<usize>,
+    /// Whether the contents were left out past the depth limit
+    pub truncated: bool,
+}
+
+/// A reference from one node to another
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeapEdge {
+    pub from: usize,
+    pub to: usize,
+    /// Index, key, field or binding name the reference goes through
+    pub label: String,
+}
+
+/// Object graph reachable from a set of bindings
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeapGraph {
+    /// Nodes by id; node 0 is the environment the graph was captured from
+    pub nodes: Vec<HeapNode>,
+    pub edges: Vec<HeapEdge>,
+}
+
+impl HeapGraph {
+    /// Graph of the values reachable from `bindings`
+    pub fn capture<'a>(bindings: impl IntoIterator<Item = (&'a str, &'a Value)>) -> Self {
+        Self::capture_with_depth(bindings, DEFAULT_MAX_DEPTH)
+    }
+
+    /// Graph of the values reachable from `bindings`, leaving out the
+    /// contents of values more than `max_depth` references away
+    pub fn capture_with_depth<'a>(bindings: impl IntoIterator<Item = (&'a str, &'a Value)>, max_depth: usize) -> Self {
+        let mut builder = Builder { graph: HeapGraph::default(), buffers: BTreeMap::new(), max_depth };
+        builder.environment("globals", bindings, 0);
+        builder.graph
+    }
+
+    /// The root: the environment the graph was captured from
+    pub fn root(&self) -> usize {
+        0
+    }
+
+    /// Node with the given id
+    pub fn node(&self, id: usize) -> Option<&HeapNode> {
+        self.nodes.get(id)
+    }
+
+    /// References out of a node
+    pub fn edges_from(&self, id: usize) -> impl Iterator<Item = &HeapEdge> {
+        self.edges.iter().filter(move |edge| edge.from == id)
+    }
+
+    /// Node a global binding refers to
+    pub fn binding(&self, name: &str) -> Option<usize> {
+        self.edges_from(self.root()).find(|edge| edge.label == name).map(|edge| edge.to)
+    }
+
+    /// Bytes reachable from a node, itself included, each node counted once
+    pub fn retained_bytes(&self, id: usize) -> usize {
+        let mut seen = alloc::vec![false; self.nodes.len()];
+        let mut pending = alloc::vec![id];
+        let mut total = 0;
+        while let Some(id) = pending.pop() {
+            if id >= seen.len() || seen[id] {
+                continue;
+            }
+            seen[id] = true;
+            total += self.nodes[id].bytes;
+            pending.extend(self.edges_from(id).map(|edge| edge.to));
+        }
+        total
+    }
+
+    /// Global bindings with the bytes each retains, largest first
+    pub fn retainers(&self) -> Vec<(&str, usize)> {
+        let mut retainers: Vec<(&str, usize)> =
+            self.edges_from(self.root()).map(|edge| (edge.label.as_str(), self.retained_bytes(edge.to))).collect();
+        retainers.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        retainers
+    }
+
+    /// Nodes referred to from more than one place
+    pub fn shared(&self) -> Vec<&HeapNode> {
+        let mut incoming = alloc::vec![0usize; self.nodes.len()];
+        for edge in &self.edges {
+            if let Some(count) = incoming.get_mut(edge.to) {
+                *count += 1;
+            }
+        }
+        self.nodes.iter().filter(|node| incoming[node.id] > 1).collect()
+    }
+
+    /// Graphviz source, one box per node labelled with its kind, summary
+    /// and size
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph heap {\n    node [shape=box, fontname=monospace];\n");
+        for node in &self.nodes {
+            let mut label = format!("{}\\n{}\\n{} bytes", dot_escape(&node.kind), dot_escape(&node.label), node.bytes);
+            if let Some(count) = node.ref_count {
+                let _ = write!(label, "\\nrefs: {}", count);
+            }
+            if node.truncated {
+                label.push_str("\\n...");
+            }
+            let _ = writeln!(out, "    n{} [label=\"{}\"];", node.id, label);
+        }
+        for edge in &self.edges {
+            let _ = writeln!(out, "    n{} -> n{} [label=\"{}\"];", edge.from, edge.to, dot_escape(&edge.label));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// JSON object with a `nodes` and an `edges` array, fields named as in
+    /// [`HeapNode`] and [`HeapEdge`]
+    pub fn to_json(&self) -> String {
+        let nodes: Vec<String> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let ref_count = node.ref_count.map_or("null".to_string(), |count| count.to_string());
+                format!(
+                    "{{\"id\":{},\"kind\":{},\"label\":{},\"bytes\":{},\"ref_count\":{},\"truncated\":{}}}",
+                    node.id,
+                    json_string(&node.kind),
+                    json_string(&node.label),
+                    node.bytes,
+                    ref_count,
+                    node.truncated
+                )
+            })
+            .collect();
+        let edges: Vec<String> = self
+            .edges
+            .iter()
+            .map(|edge| format!("{{\"from\":{},\"to\":{},\"label\":{}}}", edge.from, edge.to, json_string(&edge.label)))
+            .collect();
+        format!("{{\"nodes\":[{}],\"edges\":[{}]}}", nodes.join(","), edges.join(","))
+    }
+}
+
+/// Walks values into a graph, giving each builder buffer one node
+struct Builder {
+    graph: HeapGraph,
+    /// Node of each buffer seen, by address
+    buffers: BTreeMap<usize, usize>,
+    max_depth: usize,
+}
+
+impl Builder {
+    fn add(&mut self, kind: String, label: String, bytes: usize) -> usize {
+        let id = self.graph.nodes.len();
+        self.graph.nodes.push(HeapNode { id, kind, label, bytes, ref_count: None, truncated: false });
+        id
+    }
+
+    fn edge(&mut self, from: usize, to: usize, label: impl Into<String>) {
+        self.graph.edges.push(HeapEdge { from, to, label: label.into() });
+    }
+
+    fn environment<'a>(&mut self, label: &str, bindings: impl IntoIterator<Item = (&'a str, &'a Value)>, depth: usize) -> usize {
+        let bindings: Vec<(&str, &Value)> = bindings.into_iter().collect();
+        let id = self.add("Environment".to_string(), format!("{} ({} bindings)", label, bindings.len()), 0);
+        for (name, value) in bindings {
+            let child = self.value(value, depth + 1);
+            self.edge(id, child, name);
+        }
+        id
+    }
+
+    fn value(&mut self, value: &Value, depth: usize) -> usize {
+        let id = self.add(value.type_name().to_string(), summary(value), size_of::<Value>() + own_bytes(value));
+        if depth >= self.max_depth {
+            self.graph.nodes[id].truncated = !children(value).is_empty() || matches!(value, Value::Chant { .. });
+            return id;
+        }
+        match value {
+            Value::Shared { ref_count, .. } => self.graph.nodes[id].ref_count = Some(*ref_count),
+            Value::Chant { closure, .. } => {
+                let environment = self.environment("closure", crate::eval::visible_bindings(closure), depth);
+                self.edge(id, environment, "closure");
+            }
+            Value::TextBuilder { buffer } => {
+                let buffer_id = match self.buffers.get(&buffer.id()) {
+                    Some(&existing) => existing,
+                    None => {
+                        let created = self.add("Buffer".to_string(), format!("{} bytes of text", buffer.len()), buffer.capacity());
+                        self.buffers.insert(buffer.id(), created);
+                        created
+                    }
+                };
+                let owners = self.graph.nodes[buffer_id].ref_count.unwrap_or(0) + 1;
+                self.graph.nodes[buffer_id].ref_count = Some(owners);
+                self.edge(id, buffer_id, "buffer");
+            }
+            _ => {}
+        }
+        for (label, child) in children(value) {
+            let child_id = self.value(child, depth + 1);
+            self.edge(id, child_id, label);
+        }
+        id
+    }
+}
+
+/// Values a value holds, with the label of the reference to each
+fn children(value: &Value) -> Vec<(String, &Value)> {
+    match value {
+        Value::List(items) | Value::Tuple(items) | Value::VariantValue { fields: items, .. } => {
+            items.iter().enumerate().map(|(index, item)| (format!("[{}]", index), item)).collect()
+        }
+        Value::Map(entries) | Value::StructInstance { fields: entries, .. } => {
+            entries.iter().map(|(key, item)| (key.clone(), item)).collect()
+        }
+        Value::Range { start, end } => alloc::vec![("start".to_string(), &**start), ("end".to_string(), &**end)],
+        Value::Maybe { value: Some(inner), .. } => alloc::vec![("value".to_string(), &**inner)],
+        Value::Outcome { value: inner, .. }
+        | Value::Shared { value: inner, .. }
+        | Value::Cell { value: inner, .. }
+        | Value::Frozen { value: inner }
+        | Value::Tainted { value: inner, .. } => alloc::vec![("value".to_string(), &**inner)],
+        Value::Iterator { state, .. } => match &**state {
+            IteratorState::List { elements, index } => {
+                elements.iter().enumerate().skip(*index).map(|(i, item)| (format!("[{}]", i), item)).collect()
+            }
+            IteratorState::Map { inner, func } => alloc::vec![("inner".to_string(), &**inner), ("func".to_string(), &**func)],
+            IteratorState::Filter { inner, predicate } => {
+                alloc::vec![("inner".to_string(), &**inner), ("predicate".to_string(), &**predicate)]
+            }
+            IteratorState::Take { inner, .. } => alloc::vec![("inner".to_string(), &**inner)],
+            IteratorState::Aspect { value: inner } => alloc::vec![("value".to_string(), &**inner)],
+            IteratorState::Stream { source, .. } => alloc::vec![("source".to_string(), &**source)],
+            IteratorState::Range { .. } | IteratorState::Empty => Vec::new(),
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Heap bytes a value owns directly: text, spare collection capacity, keys
+/// and chant code (the rest is counted on the nodes it points to)
+fn own_bytes(value: &Value) -> usize {
+    match value {
+        Value::Text(text) => text.capacity(),
+        Value::List(items) | Value::Tuple(items) => (items.capacity() - items.len()) * size_of::<Value>(),
+        Value::Map(entries) | Value::StructInstance { fields: entries, .. } => entries.keys().map(String::capacity).sum(),
+        // Set elements are not values, so they get no nodes of their own
+        Value::Set(_) => crate::leak_check::retained_bytes(value) - size_of::<Value>(),
+        Value::Chant { params, body, .. } => {
+            params.len() * size_of::<crate::ast::Parameter>() + body.len() * size_of::<crate::ast::AstNode>()
+        }
+        _ => 0,
+    }
+}
+
+/// Label of a node: scalars show their value, containers their size
+pub(crate) fn summary(value: &Value) -> String {
+    match value {
+        Value::Number(n) => format!("{}", n),
+        Value::BigInt(n) => format!("{}n", n),
+        Value::Integer(n) => format!("{}i", n),
+        Value::Text(text) if text.chars().count() > 32 => format!("{:?}...", text.chars().take(32).collect::<String>()),
+        Value::Text(text) => format!("{:?}", text),
+        Value::Truth(truth) => truth.to_string(),
+        Value::List(items) => format!("{} items", items.len()),
+        Value::Tuple(elements) => format!("{} elements", elements.len()),
+        Value::Set(elements) => format!("{} elements", elements.len()),
+        Value::Map(entries) => format!("{} entries", entries.len()),
+        Value::StructInstance { fields, .. } => format!("{} fields", fields.len()),
+        Value::Chant { params, .. } => format!("chant({})", params.iter().map(|param| param.name.as_str()).collect::<Vec<_>>().join(", ")),
+        Value::Cell { borrowed: true, .. } => "borrowed mutably".to_string(),
+        Value::Cell { borrow_count, .. } => format!("{} borrows", borrow_count),
+        Value::Resource { kind, handle, .. } => format!("{} #{}", kind, handle),
+        Value::Frozen { .. } => "frozen".to_string(),
+        Value::Tainted { sources, .. } => format!("tainted by {}", sources.join(", ")),
+        _ => String::new(),
+    }
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+pub(crate) fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_depth_limit_truncates() {
+        let nested = Value::List(alloc::vec![Value::List(alloc::vec![Value::Number(1.0)])]);
+        let graph = HeapGraph::capture_with_depth([("nested", &nested)], 2);
+        assert_eq!(graph.nodes.len(), 3);
+        assert!(graph.nodes[2].truncated);
+        assert_eq!(json_string("a\"b\n\u{1}"), "\"a\\\"b\\n\\u0001\"");
+    }
+}
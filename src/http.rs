@@ -0,0 +1,300 @@
+//! # HTTP Client
+//!
+//! `http_get(url, options)` and `http_post(url, body, options)` speak
+//! HTTP/1.1 over the evaluator's [`NetProvider`], so scripts can call REST
+//! APIs without driving sockets themselves.
+//!
+//! The script must have been granted `request Net.connect(host, port)`
+//! for every endpoint the request touches, redirects included. A response
+//! comes back as `Triumph({status, headers, body})`, whatever its status;
+//! a failure to get one (refused connection, timeout, malformed reply,
+//! too many redirects) is a Mishap. Only `http://` URLs are supported.
+//!
+//! The optional `options` map takes `headers` (a map of header values),
+//! `timeout` (milliseconds per read or write) and `redirects` (how many to
+//! follow, [`DEFAULT_REDIRECTS`] when absent; with 0 a redirect response is
+//! returned as it is).
+//!
+//! Header names must be HTTP tokens, and neither header values nor URLs
+//! may hold control characters (URLs no spaces either), so a script cannot
+//! smuggle extra headers or a second request onto a connection.
+//!
+//! ```
+//! use glimmer_weave::http::{parse_response, parse_url};
+//!
+//! let url = parse_url("http://example.com:8080/items?page=2").unwrap();
+//! assert_eq!((url.host.as_str(), url.port, url.path.as_str()), ("example.com", 8080, "/items?page=2"));
+//!
+//! let response = parse_response(b"HTTP/1.1 404 Not Found\r\nContent-Length: 4\r\n\r\nnope").unwrap();
+//! assert_eq!((response.status, response.body.as_str()), (404, "nope"));
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::net::NetProvider;
+
+/// Redirects followed when the options do not say
+pub const DEFAULT_REDIRECTS: usize = 5;
+
+/// Largest response read, so a misbehaving server cannot exhaust memory
+pub const MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+
+/// The parts of an `http://` URL a request needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Url {
+    pub host: String,
+    pub port: u16,
+    /// Path and query, starting with `/`
+    pub path: String,
+}
+
+/// A parsed response
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    /// Header names lowercased, in the order received
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    /// Value of the first header named `name` (lowercase)
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// How a request is made
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpOptions {
+    /// Extra request headers
+    pub headers: Vec<(String, String)>,
+    /// Milliseconds each read or write may take
+    pub timeout: Option<u64>,
+    /// Redirects followed before giving up; 0 returns redirect responses
+    pub redirects: usize,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        HttpOptions { headers: Vec::new(), timeout: None, redirects: DEFAULT_REDIRECTS }
+    }
+}
+
+/// Split an `http://` URL into host, port and path
+pub fn parse_url(url: &str) -> Result<Url, String> {
+    check_url_text(url)?;
+    let rest = match url.split_once("://") {
+        Some(("http", rest)) => rest,
+        Some((scheme, _)) => return Err(format!("{}:// URLs are not supported", scheme)),
+        None => return Err(format!("'{}' is not an http:// URL", url)),
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, port.parse().map_err(|_| format!("Bad port in '{}'", url))?),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        return Err(format!("No host in '{}'", url));
+    }
+    Ok(Url { host: host.to_string(), port, path: path.to_string() })
+}
+
+/// Refuse URL text with spaces or control characters, which would end the
+/// request line early
+fn check_url_text(url: &str) -> Result<(), String> {
+    match url.chars().find(|c| c.is_control() || c.is_whitespace()) {
+        Some(c) => Err(format!("Invalid character {:?} in URL {:?}", c, url)),
+        None => Ok(()),
+    }
+}
+
+/// Check that a request header is safe to send: the name an HTTP token and
+/// the value free of control characters other than tab
+pub fn check_header(name: &str, value: &str) -> Result<(), String> {
+    let token_char = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+    if name.is_empty() || !name.chars().all(token_char) {
+        return Err(format!("Invalid header name {:?}", name));
+    }
+    if value.chars().any(|c| c.is_control() && c != '\t') {
+        return Err(format!("Invalid value for header '{}'", name));
+    }
+    Ok(())
+}
+
+/// Parse a complete response, decoding a chunked body
+pub fn parse_response(bytes: &[u8]) -> Result<Response, String> {
+    let head_end = find(bytes, b"\r\n\r\n").ok_or("Response ended inside its headers")?;
+    let head = String::from_utf8_lossy(&bytes[..head_end]);
+    let mut lines = head.split("\r\n");
+
+    let status_line = lines.next().unwrap_or_default();
+    let status = status_line
+        .split(' ')
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| format!("Bad status line '{}'", status_line))?;
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+    let response = Response { status, headers, body: String::new() };
+
+    let raw = &bytes[head_end + 4..];
+    let body = if response.header("transfer-encoding").is_some_and(|encoding| encoding.eq_ignore_ascii_case("chunked")) {
+        dechunk(raw)?
+    } else {
+        match response.header("content-length").map(str::parse::<usize>) {
+            Some(Ok(length)) if length <= raw.len() => raw[..length].to_vec(),
+            Some(Ok(_)) => return Err("Response ended inside its body".to_string()),
+            _ => raw.to_vec(),
+        }
+    };
+    Ok(Response { body: String::from_utf8_lossy(&body).into_owned(), ..response })
+}
+
+/// Make a request through `net`, following redirects
+///
+/// `granted` says whether the script may connect to an endpoint; every
+/// endpoint is checked before it is contacted.
+pub fn fetch(
+    net: &mut dyn NetProvider,
+    granted: &dyn Fn(&str, u16) -> bool,
+    method: &str,
+    url: &str,
+    body: Option<&str>,
+    options: &HttpOptions,
+) -> Result<Response, String> {
+    let (mut method, mut url, mut body) = (method.to_string(), parse_url(url)?, body);
+    for _ in 0..=options.redirects {
+        if !granted(&url.host, url.port) {
+            return Err(format!("No Net.connect capability for {}:{}", url.host, url.port));
+        }
+        let response = exchange(net, &method, &url, body, options)?;
+        let location = match response.status {
+            301 | 302 | 303 | 307 | 308 if options.redirects > 0 => response.header("location"),
+            _ => None,
+        };
+        let Some(location) = location else {
+            return Ok(response);
+        };
+
+        url = match location.starts_with('/') {
+            true => {
+                check_url_text(location)?;
+                Url { path: location.to_string(), ..url }
+            }
+            false => parse_url(location)?,
+        };
+        // 303, and 301/302 after a POST, turn the request into a GET
+        if response.status == 303 || (method == "POST" && matches!(response.status, 301 | 302)) {
+            method = "GET".to_string();
+            body = None;
+        }
+    }
+    Err(format!("More than {} redirects", options.redirects))
+}
+
+/// One request and its response over a fresh connection
+fn exchange(net: &mut dyn NetProvider, method: &str, url: &Url, body: Option<&str>, options: &HttpOptions) -> Result<Response, String> {
+    let mut request = format!("{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", method, url.path, url.host);
+    for (name, value) in &options.headers {
+        check_header(name, value)?;
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if let Some(body) = body {
+        request.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    request.push_str("\r\n");
+    request.push_str(body.unwrap_or_default());
+
+    let handle = net.connect(&url.host, url.port)?;
+    let received = net.set_timeout(handle, options.timeout).and_then(|()| {
+        net.send(handle, request.as_bytes())?;
+        let mut received = Vec::new();
+        loop {
+            let chunk = net.recv(handle, 8192)?;
+            if chunk.is_empty() {
+                return Ok(received);
+            }
+            received.extend_from_slice(&chunk);
+            if received.len() > MAX_RESPONSE_BYTES {
+                return Err(format!("Response larger than {} bytes", MAX_RESPONSE_BYTES));
+            }
+        }
+    });
+    // The connection is done with either way; a failed close loses nothing
+    let _ = net.close(handle);
+    parse_response(&received?)
+}
+
+/// Body of a `Transfer-Encoding: chunked` response
+fn dechunk(mut raw: &[u8]) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = find(raw, b"\r\n").ok_or("Response ended inside a chunk size")?;
+        let size_text = String::from_utf8_lossy(&raw[..line_end]);
+        let size_text = size_text.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size_text, 16).map_err(|_| format!("Bad chunk size '{}'", size_text))?;
+        raw = &raw[line_end + 2..];
+        if size == 0 {
+            return Ok(body);
+        }
+        let chunk = raw.get(..size).ok_or("Response ended inside a chunk")?;
+        body.extend_from_slice(chunk);
+        raw = raw.get(size + 2..).unwrap_or_default();
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_url_rejects_other_schemes() {
+        assert_eq!(parse_url("http://example.com").unwrap().path, "/");
+        assert_eq!(parse_url("http://example.com").unwrap().port, 80);
+        assert!(parse_url("https://example.com").is_err());
+        assert!(parse_url("example.com/items").is_err());
+        assert!(parse_url("http://:80/").is_err());
+    }
+
+    #[test]
+    fn test_parse_url_rejects_spaces_and_control_characters() {
+        assert!(parse_url("http://example.com/a b").is_err());
+        assert!(parse_url("http://example.com/a HTTP/1.1\r\nX-Injected: 1").is_err());
+        assert!(parse_url("http://example.com/\nGET /admin").is_err());
+        assert!(parse_url("http://exa\rmple.com/").is_err());
+        assert!(parse_url("http://example.com/a%20b").is_ok());
+    }
+
+    #[test]
+    fn test_check_header() {
+        assert_eq!(check_header("X-Request-Id", "7\tof 9"), Ok(()));
+        assert!(check_header("", "value").is_err());
+        assert!(check_header("X Id", "7").is_err());
+        assert!(check_header("X-Id:", "7").is_err());
+        assert!(check_header("X-Id\r\nX-Injected", "1").is_err());
+        assert!(check_header("X-Id", "7\r\nX-Injected: 1").is_err());
+        assert!(check_header("X-Id", "7\n").is_err());
+        assert!(check_header("X-Id", "7\0").is_err());
+    }
+
+    #[test]
+    fn test_parse_chunked_response() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nX-Id: 7\r\n\r\n4\r\nWiki\r\n5;note\r\npedia\r\n0\r\n\r\n";
+        let response = parse_response(raw).unwrap();
+        assert_eq!(response.body, "Wikipedia");
+        assert_eq!(response.header("x-id"), Some("7"));
+        assert!(parse_response(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nshort").is_err());
+    }
+}
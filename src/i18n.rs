@@ -0,0 +1,169 @@
+//! # Message Catalogs
+//!
+//! Localized text for scripts that talk to people.
+//!
+//! A catalog maps message keys to templates for one locale. Scripts call
+//! `tr("greeting", {name: "Ada"})`, which looks the key up in the current
+//! locale's catalog and fills `{name}` placeholders from the arguments
+//! (`{{` and `}}` stand for literal braces). A key missing from the current
+//! locale falls back to its language (`fr` for `fr-CA`), then to the
+//! default locale, then to the key itself.
+//!
+//! The host loads catalogs through
+//! [`Evaluator::set_messages`](crate::eval::Evaluator::set_messages);
+//! scripts can add their own with `catalog_load(locale, map)` and switch
+//! with `set_locale(locale)`. Given the same catalogs,
+//! [`CompilerPipeline::messages`](crate::pipeline::CompilerPipeline::messages)
+//! also rejects `tr` calls whose literal key is missing from the default
+//! catalog, before the script runs.
+//!
+//! ```
+//! use glimmer_weave::i18n::{interpolate, MessageCatalogs};
+//!
+//! let messages = MessageCatalogs::new("en")
+//!     .with_catalog("en", [("greeting", "Hello, {name}!")])
+//!     .with_catalog("fr", [("greeting", "Bonjour, {name} !")])
+//!     .with_locale("fr-CA");
+//! let template = messages.lookup("greeting").unwrap();
+//! assert_eq!(interpolate(template, |_| Some("Ada".to_string())), Ok("Bonjour, Ada !".to_string()));
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::ast::AstNode;
+use crate::ast_query::Query;
+use crate::semantic::SemanticError;
+
+/// Message templates of one locale, by key
+pub type Catalog = BTreeMap<String, String>;
+
+/// Catalogs for every loaded locale, and which one is in use
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageCatalogs {
+    default_locale: String,
+    locale: String,
+    catalogs: BTreeMap<String, Catalog>,
+}
+
+impl Default for MessageCatalogs {
+    fn default() -> Self {
+        Self::new("en")
+    }
+}
+
+impl MessageCatalogs {
+    /// No catalogs yet, using `default_locale`
+    pub fn new(default_locale: &str) -> Self {
+        MessageCatalogs { default_locale: default_locale.to_string(), locale: default_locale.to_string(), catalogs: BTreeMap::new() }
+    }
+
+    /// Also load `entries` for `locale`
+    pub fn with_catalog<'a>(mut self, locale: &str, entries: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        self.load(locale, entries.into_iter().map(|(key, template)| (key.to_string(), template.to_string())).collect());
+        self
+    }
+
+    /// Switch to `locale`
+    pub fn with_locale(mut self, locale: &str) -> Self {
+        self.set_locale(locale);
+        self
+    }
+
+    /// Add `catalog`'s entries to `locale`'s, replacing templates for keys
+    /// it already has
+    pub fn load(&mut self, locale: &str, catalog: Catalog) {
+        self.catalogs.entry(locale.to_string()).or_default().extend(catalog);
+    }
+
+    /// Look messages up in `locale` from now on
+    pub fn set_locale(&mut self, locale: &str) {
+        self.locale = locale.to_string();
+    }
+
+    /// Locale in use
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Catalog of the default locale, which every key should be in
+    pub fn default_catalog(&self) -> Option<&Catalog> {
+        self.catalogs.get(&self.default_locale)
+    }
+
+    /// Template for `key`: from the current locale, its language, or the
+    /// default locale
+    pub fn lookup(&self, key: &str) -> Option<&str> {
+        let language = self.locale.split(['-', '_']).next().unwrap_or_default();
+        [self.locale.as_str(), language, self.default_locale.as_str()]
+            .into_iter()
+            .find_map(|locale| self.catalogs.get(locale)?.get(key))
+            .map(String::as_str)
+    }
+}
+
+/// Fill the `{name}` placeholders of `template` with what `argument` gives
+/// for each name; fails naming the first placeholder it has no value for
+pub fn interpolate(template: &str, mut argument: impl FnMut(&str) -> Option<String>) -> Result<String, String> {
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(brace) = rest.find(['{', '}']) {
+        text.push_str(&rest[..brace]);
+        let (open, after) = rest[brace..].split_at(1);
+        if after.starts_with(open) {
+            text.push_str(open);
+            rest = &after[1..];
+            continue;
+        }
+        let Some(close) = after.find('}').filter(|_| open == "{") else {
+            return Err(format!("unmatched '{}' in \"{}\"", open, template));
+        };
+        let name = &after[..close];
+        text.push_str(&argument(name).ok_or_else(|| format!("no value for {{{}}}", name))?);
+        rest = &after[close + 1..];
+    }
+    text.push_str(rest);
+    Ok(text)
+}
+
+/// `tr` calls in `nodes` whose literal key is not in `catalog`
+pub fn check(nodes: &[AstNode], catalog: &Catalog) -> Vec<SemanticError> {
+    let mut errors = Vec::new();
+    for call in Query::call_to("tr").or(Query::call_to("I18n.tr")).find(nodes) {
+        if let AstNode::Call { args, .. } = call.node {
+            if let Some(AstNode::Text { value, span }) = args.first() {
+                if !catalog.contains_key(value) {
+                    errors.push(SemanticError::UnknownMessageKey { key: value.clone(), span: span.clone() });
+                }
+            }
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolation_escapes_and_errors() {
+        let args = |name: &str| (name == "n").then(|| "3".to_string());
+        assert_eq!(interpolate("{n} files {{copied}}", args), Ok("3 files {copied}".to_string()));
+        assert_eq!(interpolate("{count} left", args), Err("no value for {count}".to_string()));
+        assert!(interpolate("oops }", args).is_err());
+        assert!(interpolate("{n", args).is_err());
+    }
+
+    #[test]
+    fn test_lookup_falls_back() {
+        let messages = MessageCatalogs::new("en")
+            .with_catalog("en", [("quit", "Quit"), ("save", "Save")])
+            .with_catalog("de", [("quit", "Beenden")])
+            .with_locale("de-AT");
+        assert_eq!(messages.lookup("quit"), Some("Beenden"));
+        assert_eq!(messages.lookup("save"), Some("Save"));
+        assert_eq!(messages.lookup("load"), None);
+    }
+}
@@ -0,0 +1,486 @@
+//! # Inlining Module
+//!
+//! Replaces calls to small chants with the chants' bodies, removing call
+//! overhead for tiny accessors and helpers in hot loops.
+//!
+//! ## Example
+//!
+//! Input:
+//! ```glimmer
+//! chant area(w, h) then
+//!     w * h
+//! end
+//!
+//! bind total to area(width, 2) + 1
+//! ```
+//!
+//! Output (conceptual):
+//! ```glimmer
+//! chant area(w, h) then
+//!     w * h
+//! end
+//!
+//! bind total to width * 2 + 1
+//! ```
+//!
+//! ## Heuristics
+//!
+//! A top-level chant is inlined when:
+//! - its body is a single expression (optionally yielded) without
+//!   statements, `?` or pipelines
+//! - the expression has at most [`INLINE_SIZE_LIMIT`] nodes, or the chant
+//!   is marked `swift` (`swift chant get_x(p) then p.x end`)
+//! - it is not recursive, directly or through other chants
+//! - none of the names it uses, nor its own name, are rebound anywhere in
+//!   the program, so substituting it cannot capture a different binding
+//!
+//! and a call site is expanded when its arguments are free of side effects
+//! (literals, variables and operators over them). An argument used more than
+//! once must be a literal or variable, so no work is duplicated, and when the
+//! body calls other chants, variables passed in must never be `set`, since
+//! the call could change them before the body reads them.
+//!
+//! With a [`Profile`] from an earlier run, chants called often enough
+//! count as `swift`, and chants the run never called are not inlined.
+//!
+//! The chant definitions stay in the program: they may still be called
+//! through function values or from the host.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use crate::ast::*;
+use crate::profile::Profile;
+use crate::purity::{contains_call, is_pure, is_trivial, node_count};
+
+/// Largest body expression (in AST nodes) inlined without a `swift` hint
+pub const INLINE_SIZE_LIMIT: usize = 8;
+
+/// Bound on nested expansions, a guard on top of the recursion check
+const MAX_INLINE_DEPTH: usize = 16;
+
+/// A chant whose calls can be replaced by its body
+struct Candidate {
+    params: Vec<String>,
+    body: AstNode,
+    calls: bool,
+}
+
+/// Inliner replaces calls to small, non-recursive chants with their bodies
+pub struct Inliner {
+    candidates: BTreeMap<String, Candidate>,
+    /// Variables assigned with `set` somewhere in the program
+    assigned: BTreeSet<String>,
+    /// Call counts from an earlier run, and the count that makes a chant hot
+    profile: Option<(Profile, u64)>,
+}
+
+impl Default for Inliner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inliner {
+    /// Create a new inliner
+    pub fn new() -> Self {
+        Inliner {
+            candidates: BTreeMap::new(),
+            assigned: BTreeSet::new(),
+            profile: None,
+        }
+    }
+
+    /// Let call counts from an earlier run guide inlining
+    ///
+    /// Chants called at least `hot_threshold` times are inlined whatever
+    /// their size, as if marked `swift`; chants the profile never saw called
+    /// stay out of line unless they are marked `swift`.
+    pub fn with_profile(mut self, profile: &Profile, hot_threshold: u64) -> Self {
+        self.profile = Some((profile.clone(), hot_threshold));
+        self
+    }
+
+    /// Inline calls throughout a program
+    pub fn inline(&mut self, program: &[AstNode]) -> Vec<AstNode> {
+        let mut bound = BTreeSet::new();
+        for node in program {
+            collect_bindings(node, true, &mut bound, &mut self.assigned);
+        }
+
+        let chants: BTreeMap<&str, &AstNode> = program
+            .iter()
+            .filter_map(|node| match node {
+                AstNode::ChantDef { name, .. } => Some((name.as_str(), node)),
+                _ => None,
+            })
+            .collect();
+        for (name, chant) in &chants {
+            let heat = match &self.profile {
+                Some((profile, threshold)) if profile.is_hot(name, *threshold) => Heat::Hot,
+                Some((profile, _)) if profile.calls(name) == 0 => Heat::Cold,
+                _ => Heat::Unknown,
+            };
+            if let Some(candidate) = candidate(name, chant, &bound, heat) {
+                self.candidates.insert(name.to_string(), candidate);
+            }
+        }
+        let recursive: Vec<String> = self
+            .candidates
+            .keys()
+            .filter(|name| self.reaches_itself(name, &chants))
+            .cloned()
+            .collect();
+        for name in recursive {
+            self.candidates.remove(&name);
+        }
+
+        // Expand candidates within each other first, so a helper built on
+        // inlined accessors no longer counts as calling anything
+        let names: Vec<String> = self.candidates.keys().cloned().collect();
+        for name in names {
+            let mut body = self.candidates[&name].body.clone();
+            self.rewrite(&mut body, 0);
+            if let Some(candidate) = self.candidates.get_mut(&name) {
+                candidate.calls = contains_call(&body);
+                candidate.body = body;
+            }
+        }
+
+        let mut program = program.to_vec();
+        for node in program.iter_mut() {
+            self.rewrite(node, 0);
+        }
+        program
+    }
+
+    /// Whether a chant can call back into itself
+    fn reaches_itself(&self, start: &str, chants: &BTreeMap<&str, &AstNode>) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut pending: Vec<&str> = called_names(chants[start]);
+        while let Some(name) = pending.pop() {
+            if name == start {
+                return true;
+            }
+            if visited.insert(name) {
+                if let Some(chant) = chants.get(name) {
+                    pending.extend(called_names(chant));
+                }
+            }
+        }
+        false
+    }
+
+    /// Expand inlinable calls in a node, innermost first
+    fn rewrite(&self, node: &mut AstNode, depth: usize) {
+        for child in node.children_mut() {
+            self.rewrite(child, depth);
+        }
+        if depth >= MAX_INLINE_DEPTH {
+            return;
+        }
+        if let Some(mut expanded) = self.expand(node) {
+            self.rewrite(&mut expanded, depth + 1);
+            *node = expanded;
+        }
+    }
+
+    /// The body a call expands to, if the call can be inlined
+    fn expand(&self, node: &AstNode) -> Option<AstNode> {
+        let AstNode::Call { callee, type_args, args, arg_names, .. } = node else {
+            return None;
+        };
+        let AstNode::Ident { name, .. } = callee.as_ref() else {
+            return None;
+        };
+        let candidate = self.candidates.get(name)?;
+        if !type_args.is_empty() || !arg_names.is_empty() || args.len() != candidate.params.len() {
+            return None;
+        }
+
+        let uses = identifier_uses(&candidate.body);
+        for (param, arg) in candidate.params.iter().zip(args) {
+            if !is_pure(arg) {
+                return None;
+            }
+            if uses.get(param.as_str()).copied().unwrap_or(0) > 1 && !is_trivial(arg) {
+                return None;
+            }
+            if candidate.calls && reads_any(arg, &self.assigned) {
+                return None;
+            }
+        }
+
+        let substitutions: BTreeMap<&str, &AstNode> =
+            candidate.params.iter().map(String::as_str).zip(args.iter()).collect();
+        let mut body = candidate.body.clone();
+        substitute(&mut body, &substitutions);
+        Some(body)
+    }
+}
+
+/// Inline calls throughout a program with a fresh [`Inliner`]
+pub fn inline(program: &[AstNode]) -> Vec<AstNode> {
+    Inliner::new().inline(program)
+}
+
+/// How often a profile saw a chant called
+#[derive(Clone, Copy, PartialEq)]
+enum Heat {
+    Hot,
+    Cold,
+    Unknown,
+}
+
+/// Check a top-level chant against the heuristics that don't depend on call sites
+fn candidate(name: &str, chant: &AstNode, bound: &BTreeSet<String>, heat: Heat) -> Option<Candidate> {
+    let AstNode::ChantDef { type_params, params, body, swift, .. } = chant else {
+        return None;
+    };
+    if !type_params.is_empty() || bound.contains(name) || (heat == Heat::Cold && !swift) {
+        return None;
+    }
+    let swift = *swift || heat == Heat::Hot;
+    if params.iter().any(|param| param.is_variadic || param.borrow_mode != BorrowMode::Owned) {
+        return None;
+    }
+    let expr = match body.as_slice() {
+        [AstNode::ExprStmt { expr, .. }] | [AstNode::YieldStmt { value: expr, .. }] => expr.as_ref(),
+        _ => return None,
+    };
+    if !is_inlinable(expr) || (!swift && node_count(expr) > INLINE_SIZE_LIMIT) {
+        return None;
+    }
+
+    let params: Vec<String> = params.iter().map(|param| param.name.clone()).collect();
+    let captures_binding = identifier_uses(expr)
+        .keys()
+        .any(|used| !params.iter().any(|param| param == used) && bound.contains(*used));
+    if captures_binding {
+        return None;
+    }
+    Some(Candidate { params, body: expr.clone(), calls: contains_call(expr) })
+}
+
+/// Collect every name bound other than by a top-level chant definition,
+/// along with the variables targeted by `set`
+fn collect_bindings(node: &AstNode, top_level: bool, bound: &mut BTreeSet<String>, assigned: &mut BTreeSet<String>) {
+    match node {
+        AstNode::BindStmt { name, .. } | AstNode::WeaveStmt { name, .. } => {
+            bound.insert(name.clone());
+        }
+        AstNode::ForStmt { variable, .. } => {
+            bound.insert(variable.clone());
+        }
+        AstNode::ChantDef { name, params, .. } => {
+            if !top_level {
+                bound.insert(name.clone());
+            }
+            bound.extend(params.iter().map(|param| param.name.clone()));
+        }
+        AstNode::SetStmt { target, .. } => {
+            if let AstNode::Ident { name, .. } = target.as_ref() {
+                assigned.insert(name.clone());
+            }
+        }
+        AstNode::MatchStmt { arms, .. } => {
+            for arm in arms {
+                pattern_bindings(&arm.pattern, bound);
+            }
+        }
+        _ => {}
+    }
+    for child in node.children() {
+        collect_bindings(child, false, bound, assigned);
+    }
+}
+
+fn pattern_bindings(pattern: &Pattern, bound: &mut BTreeSet<String>) {
+    match pattern {
+        Pattern::Ident(name) | Pattern::Affix { rest: Some(name), .. } => {
+            bound.insert(name.clone());
+        }
+        Pattern::Enum { inner: Some(inner), .. } => pattern_bindings(inner, bound),
+        Pattern::Tuple(elements) | Pattern::Or(elements) => {
+            elements.iter().for_each(|element| pattern_bindings(element, bound))
+        }
+        _ => {}
+    }
+}
+
+/// Names of the chants a chant's body calls directly
+fn called_names(chant: &AstNode) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut pending = chant.children();
+    while let Some(node) = pending.pop() {
+        if let AstNode::Call { callee, .. } = node {
+            if let AstNode::Ident { name, .. } = callee.as_ref() {
+                names.push(name.as_str());
+            }
+        }
+        pending.extend(node.children());
+    }
+    names
+}
+
+/// Expressions that can stand in for a call: no statements, no early
+/// return through `?`, nothing that binds names
+fn is_inlinable(node: &AstNode) -> bool {
+    let allowed = match node {
+        AstNode::Call { callee, .. } => matches!(callee.as_ref(), AstNode::Ident { .. } | AstNode::ModuleAccess { .. }),
+        AstNode::Number { .. }
+        | AstNode::BigInt { .. }
+        | AstNode::Integer { .. }
+        | AstNode::Measure { .. }
+        | AstNode::Text { .. }
+        | AstNode::Truth { .. }
+        | AstNode::Nothing { .. }
+        | AstNode::Ident { .. }
+        | AstNode::Triumph { .. }
+        | AstNode::Mishap { .. }
+        | AstNode::Present { .. }
+        | AstNode::Absent { .. }
+        | AstNode::List { .. }
+        | AstNode::Tuple { .. }
+        | AstNode::Map { .. }
+        | AstNode::StructLiteral { .. }
+        | AstNode::BinaryOp { .. }
+        | AstNode::UnaryOp { .. }
+        | AstNode::InUnit { .. }
+        | AstNode::FieldAccess { .. }
+        | AstNode::ModuleAccess { .. }
+        | AstNode::IndexAccess { .. }
+        | AstNode::Range { .. } => true,
+        _ => false,
+    };
+    allowed && node.children().into_iter().all(is_inlinable)
+}
+
+fn reads_any(node: &AstNode, names: &BTreeSet<String>) -> bool {
+    identifier_uses(node).keys().any(|name| names.contains(*name))
+}
+
+/// How many times each identifier occurs in an expression
+fn identifier_uses(node: &AstNode) -> BTreeMap<&str, usize> {
+    let mut uses = BTreeMap::new();
+    let mut pending = vec![node];
+    while let Some(node) = pending.pop() {
+        if let AstNode::Ident { name, .. } = node {
+            *uses.entry(name.as_str()).or_insert(0) += 1;
+        }
+        pending.extend(node.children());
+    }
+    uses
+}
+
+/// Replace parameter references with the call's arguments
+fn substitute(node: &mut AstNode, substitutions: &BTreeMap<&str, &AstNode>) {
+    if let AstNode::Ident { name, .. } = node {
+        if let Some(arg) = substitutions.get(name.as_str()) {
+            *node = (*arg).clone();
+        }
+        return;
+    }
+    for child in node.children_mut() {
+        substitute(child, substitutions);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn inline_source(source: &str) -> Vec<AstNode> {
+        let tokens = Lexer::new(source).tokenize_positioned();
+        inline(&Parser::new(tokens).parse().unwrap())
+    }
+
+    /// The expression of the program's last statement
+    fn last_expr(program: &[AstNode]) -> &AstNode {
+        match program.last().unwrap() {
+            AstNode::ExprStmt { expr, .. } => expr,
+            AstNode::BindStmt { value, .. } => value,
+            other => panic!("unexpected statement {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_small_chant_is_inlined() {
+        let program = inline_source("chant area(w, h) then\n w * h\n end\n bind width to 3\n area(width, 2)");
+        match last_expr(&program) {
+            AstNode::BinaryOp { left, op: BinaryOperator::Mul, right, .. } => {
+                assert!(matches!(left.as_ref(), AstNode::Ident { name, .. } if name == "width"));
+                assert!(matches!(right.as_ref(), AstNode::Number { value, .. } if *value == 2.0));
+            }
+            other => panic!("expected the inlined body, got {:?}", other),
+        }
+        // The definition stays for indirect calls
+        assert!(matches!(&program[0], AstNode::ChantDef { .. }));
+    }
+
+    #[test]
+    fn test_nested_calls_inline_transitively() {
+        let program = inline_source(
+            "chant get_x(p) then\n p.x\n end\n chant twice_x(p) then\n get_x(p) * 2\n end\n bind p to 1\n twice_x(p)",
+        );
+        assert!(!contains_call(last_expr(&program)));
+    }
+
+    #[test]
+    fn test_size_limit_and_swift_hint() {
+        let large = "chant big(a) then\n a + a * a - a / a + a * a - a\n end\n big(1)";
+        assert!(contains_call(last_expr(&inline_source(large))));
+        let hinted = large.replacen("chant big", "swift chant big", 1);
+        assert!(!contains_call(last_expr(&inline_source(&hinted))));
+    }
+
+    #[test]
+    fn test_profile_drives_inlining() {
+        let source = "chant big(a) then\n a + a * a - a / a + a * a - a\n end\n chant small(a) then\n a + 1\n end\n big(1) + small(1)";
+        let program = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap();
+        let profile = Profile::parse("glimmer-profile 1\ncall big 500").unwrap();
+        let inlined = Inliner::new().with_profile(&profile, 100).inline(&program);
+        // Hot `big` is expanded despite its size; `small` never ran, so stays a call
+        let AstNode::BinaryOp { left, right, .. } = last_expr(&inlined) else {
+            panic!("expected the sum, got {:?}", last_expr(&inlined));
+        };
+        assert!(!contains_call(left));
+        assert!(matches!(right.as_ref(), AstNode::Call { .. }));
+    }
+
+    #[test]
+    fn test_recursive_chants_are_kept() {
+        let direct = inline_source("chant down(n) then\n down(n - 1)\n end\n down(3)");
+        assert!(contains_call(last_expr(&direct)));
+        let mutual = inline_source("chant ping(n) then\n pong(n)\n end\n chant pong(n) then\n ping(n)\n end\n ping(3)");
+        assert!(contains_call(last_expr(&mutual)));
+    }
+
+    #[test]
+    fn test_statement_bodies_are_kept() {
+        let program = inline_source("chant f(a) then\n bind b to a\n b\n end\n f(1)");
+        assert!(contains_call(last_expr(&program)));
+    }
+
+    #[test]
+    fn test_impure_or_duplicated_arguments_block_inlining() {
+        let source = "chant sq(a) then\n a * a\n end\n chant one() then\n 1\n end\n";
+        // A call argument may have side effects
+        assert!(contains_call(last_expr(&inline_source(&format!("{}sq(one() + 0)", source)))));
+        // A computed argument would be evaluated twice
+        let program = inline_source(&format!("{}bind n to 2\nsq(n + 1)", source));
+        assert!(matches!(last_expr(&program), AstNode::Call { .. }));
+        assert!(!contains_call(last_expr(&inline_source(&format!("{}bind n to 2\nsq(n)", source)))));
+    }
+
+    #[test]
+    fn test_shadowed_names_block_inlining() {
+        // `scale` in the body must keep meaning the top-level binding
+        let program = inline_source(
+            "bind scale to 2\n chant grow(x) then\n x * scale\n end\n chant user(scale) then\n grow(scale)\n end\n grow(1)",
+        );
+        assert!(contains_call(last_expr(&program)));
+    }
+}
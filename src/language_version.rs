@@ -0,0 +1,213 @@
+//! # Language Versions
+//!
+//! A script can name the language version it was written for with a
+//! `speaks` pragma before its first statement:
+//!
+//! ```glimmer-weave
+//! speaks "1.0"
+//! ```
+//!
+//! Syntax that arrived after that version is then refused with an error
+//! naming the version it needs, so the language can grow without old
+//! scripts quietly changing meaning. The parser refuses new statement
+//! forms; the semantic analyzer refuses new expressions and keywords. A
+//! script without the pragma speaks [`CURRENT`]. Each [`Feature`] records
+//! the version it arrived in:
+//!
+//! | Version | Features |
+//! |---------|----------|
+//! | 1.0 | The core language |
+//! | 1.1 | `defer` blocks, the `?` operator, `verify` blocks, `deriving` clauses |
+//! | 1.2 | Units of measure, `123n` literals, `swift` chants, rituals |
+//! | 1.3 | `together` scopes, store migrations |
+//! | 1.4 | Named arguments, tuples, `42i` literals, match guards, or-patterns |
+//!
+//! ```
+//! use glimmer_weave::language_version::{Feature, LanguageVersion};
+//!
+//! let old = LanguageVersion::parse("1.0").unwrap();
+//! assert!(!old.supports(Feature::Defer));
+//! assert_eq!(Feature::Defer.since().to_string(), "1.1");
+//! ```
+
+use core::fmt;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::ast::AstNode;
+use crate::ast_query::Query;
+use crate::semantic::SemanticError;
+
+/// A language version, `major.minor`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LanguageVersion {
+    pub major: u32,
+    pub minor: u32,
+}
+
+/// Version this implementation speaks, and scripts without a pragma target
+pub const CURRENT: LanguageVersion = LanguageVersion::new(1, 4);
+
+impl LanguageVersion {
+    pub const fn new(major: u32, minor: u32) -> Self {
+        LanguageVersion { major, minor }
+    }
+
+    /// Parse `"1.2"`; a missing minor version (`"1"`) means `.0`, and a
+    /// patch version (`"1.2.3"`) is ignored, since patches add no syntax
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || format!("\"{}\" is not a language version (expected e.g. \"1.2\")", text);
+        let mut parts = text.split('.');
+        let mut number = |default: Option<u32>| match parts.next() {
+            Some(part) => part.parse::<u32>().map_err(|_| invalid()),
+            None => default.ok_or_else(invalid),
+        };
+        let version = LanguageVersion::new(number(None)?, number(Some(0))?);
+        number(Some(0))?;
+        match parts.next() {
+            Some(_) => Err(invalid()),
+            None => Ok(version),
+        }
+    }
+
+    /// Whether a script speaking this version may use `feature`
+    pub fn supports(self, feature: Feature) -> bool {
+        self >= feature.since()
+    }
+}
+
+impl Default for LanguageVersion {
+    fn default() -> Self {
+        CURRENT
+    }
+}
+
+impl fmt::Display for LanguageVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Syntax that arrived after version 1.0
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feature {
+    Defer,
+    TryOperator,
+    VerifyBlocks,
+    Deriving,
+    Units,
+    BigIntLiterals,
+    SwiftChants,
+    Rituals,
+    TogetherScopes,
+    StoreMigrations,
+    NamedArguments,
+    Tuples,
+    IntegerLiterals,
+    MatchGuards,
+    OrPatterns,
+}
+
+impl Feature {
+    /// Version the feature arrived in
+    pub fn since(self) -> LanguageVersion {
+        match self {
+            Feature::Defer | Feature::TryOperator | Feature::VerifyBlocks | Feature::Deriving => LanguageVersion::new(1, 1),
+            Feature::Units | Feature::BigIntLiterals | Feature::SwiftChants | Feature::Rituals => LanguageVersion::new(1, 2),
+            Feature::TogetherScopes | Feature::StoreMigrations => LanguageVersion::new(1, 3),
+            Feature::NamedArguments | Feature::Tuples | Feature::IntegerLiterals | Feature::MatchGuards | Feature::OrPatterns => {
+                LanguageVersion::new(1, 4)
+            }
+        }
+    }
+
+    /// How errors name the feature
+    pub fn describe(self) -> &'static str {
+        match self {
+            Feature::Defer => "`defer` blocks",
+            Feature::TryOperator => "the `?` operator",
+            Feature::VerifyBlocks => "`verify` blocks",
+            Feature::Deriving => "`deriving` clauses",
+            Feature::Units => "units of measure",
+            Feature::BigIntLiterals => "big integer literals",
+            Feature::SwiftChants => "`swift` chants",
+            Feature::Rituals => "rituals",
+            Feature::TogetherScopes => "`together` scopes",
+            Feature::StoreMigrations => "store migrations",
+            Feature::NamedArguments => "named arguments",
+            Feature::Tuples => "tuples",
+            Feature::IntegerLiterals => "`42i` integer literals",
+            Feature::MatchGuards => "`where` guards on match arms",
+            Feature::OrPatterns => "`or` patterns",
+        }
+    }
+
+    /// Error for a script speaking `speaks` that uses the feature
+    pub fn unsupported(self, speaks: LanguageVersion) -> String {
+        format!("language version {} is needed for {}, but this script speaks {}", self.since(), self.describe(), speaks)
+    }
+}
+
+/// Version `nodes` speaks: the leading `speaks` pragma's, or [`CURRENT`]
+pub fn spoken(nodes: &[AstNode]) -> LanguageVersion {
+    match nodes.first() {
+        Some(AstNode::Speaks { version, .. }) => *version,
+        _ => CURRENT,
+    }
+}
+
+/// Expressions and keyword statements in `nodes` newer than the version
+/// the script speaks (the parser refuses newer statement forms itself)
+pub fn check(nodes: &[AstNode]) -> Vec<SemanticError> {
+    let speaks = spoken(nodes);
+    if speaks >= CURRENT {
+        return Vec::new();
+    }
+    let newer = Query::kind("DeferStmt")
+        .or(Query::kind("Try"))
+        .or(Query::kind("Measure"))
+        .or(Query::kind("InUnit"))
+        .or(Query::kind("BigInt"))
+        .or(Query::kind("Integer"));
+    newer
+        .find(nodes)
+        .into_iter()
+        .filter_map(|found| {
+            let feature = match found.node {
+                AstNode::DeferStmt { .. } => Feature::Defer,
+                AstNode::Try { .. } => Feature::TryOperator,
+                AstNode::BigInt { .. } => Feature::BigIntLiterals,
+                AstNode::Integer { .. } => Feature::IntegerLiterals,
+                _ => Feature::Units,
+            };
+            (!speaks.supports(feature)).then(|| SemanticError::UnsupportedFeature {
+                feature,
+                message: feature.unsupported(speaks),
+                span: found.span().clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_order() {
+        assert_eq!(LanguageVersion::parse("1.2"), Ok(LanguageVersion::new(1, 2)));
+        assert_eq!(LanguageVersion::parse("2"), Ok(LanguageVersion::new(2, 0)));
+        assert_eq!(LanguageVersion::parse("1.1.7"), Ok(LanguageVersion::new(1, 1)));
+        assert!(LanguageVersion::parse("1.x").is_err());
+        assert!(LanguageVersion::parse("1.2.3.4").is_err());
+        assert!(LanguageVersion::parse("").is_err());
+        assert!(LanguageVersion::new(1, 10) > LanguageVersion::new(1, 2));
+        assert!(CURRENT.supports(Feature::Rituals));
+        assert_eq!(
+            Feature::SwiftChants.unsupported(LanguageVersion::new(1, 1)),
+            "language version 1.2 is needed for `swift` chants, but this script speaks 1.1"
+        );
+    }
+}
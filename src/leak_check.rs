@@ -0,0 +1,176 @@
+//! # Leak Detection
+//!
+//! Compares heap usage before and after an execution, so long-running
+//! AethelOS services notice scripts that leave memory or host handles behind
+//! on every run.
+//!
+//! With leak detection enabled (`Evaluator::set_leak_detection`,
+//! `VM::set_leak_detection`), every top-level execution takes a
+//! [`HeapSnapshot`] on entry and on completion and keeps both in a
+//! [`LeakReport`]. A snapshot records:
+//! - bytes allocated on the native `gl_malloc` heap, through the allocator's
+//!   stats FFI (when the host installs
+//!   [`GlAllocator`](crate::native_allocator::GlAllocator) this covers every
+//!   allocation, interpreter values included)
+//! - an estimate of the bytes retained by values bound in the global scope,
+//!   which is what outlives an execution when a service reuses one evaluator
+//! - the number of host resources still live
+//!
+//! ```
+//! use glimmer_weave::leak_check::{HeapSnapshot, LeakReport};
+//!
+//! let before = HeapSnapshot::default();
+//! let after = HeapSnapshot { retained_bytes: 64, globals: vec!["cache".to_string()], ..before.clone() };
+//! let report = LeakReport::new(before, after);
+//! assert!(!report.is_clean());
+//! assert_eq!(report.new_globals(), ["cache"]);
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use crate::eval::Value;
+
+/// Heap usage at one point of an execution
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeapSnapshot {
+    /// Bytes allocated on the native heap; `None` without the native allocator
+    pub native_bytes: Option<u64>,
+    /// Estimated bytes retained by global values
+    pub retained_bytes: usize,
+    /// Names bound in the global scope
+    pub globals: Vec<String>,
+    /// Host resources adopted and not yet released
+    pub live_resources: usize,
+}
+
+/// Heap usage on entry to and completion of one execution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeakReport {
+    /// Snapshot taken before the execution
+    pub before: HeapSnapshot,
+    /// Snapshot taken after the execution, deferred cleanup included
+    pub after: HeapSnapshot,
+}
+
+impl LeakReport {
+    /// Compare two snapshots
+    pub fn new(before: HeapSnapshot, after: HeapSnapshot) -> Self {
+        LeakReport { before, after }
+    }
+
+    /// Native heap bytes the execution left allocated (negative if it freed more)
+    pub fn outstanding_native_bytes(&self) -> i64 {
+        match (self.before.native_bytes, self.after.native_bytes) {
+            (Some(before), Some(after)) => after as i64 - before as i64,
+            _ => 0,
+        }
+    }
+
+    /// Growth of the estimated bytes retained by global values
+    pub fn outstanding_retained_bytes(&self) -> isize {
+        self.after.retained_bytes as isize - self.before.retained_bytes as isize
+    }
+
+    /// Globals the execution bound that did not exist before
+    pub fn new_globals(&self) -> Vec<&str> {
+        self.after
+            .globals
+            .iter()
+            .filter(|name| !self.before.globals.contains(name))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Host resources the execution left live
+    pub fn leaked_resources(&self) -> usize {
+        self.after.live_resources.saturating_sub(self.before.live_resources)
+    }
+
+    /// Whether the execution left nothing behind
+    pub fn is_clean(&self) -> bool {
+        self.outstanding_native_bytes() <= 0
+            && self.outstanding_retained_bytes() <= 0
+            && self.new_globals().is_empty()
+            && self.leaked_resources() == 0
+    }
+}
+
+/// Bytes allocated on the native heap, if this build links the native allocator
+pub fn native_heap_used() -> Option<u64> {
+    #[cfg(feature = "allocator_tests")]
+    {
+        Some(crate::native_allocator::heap_stats().used)
+    }
+    #[cfg(not(feature = "allocator_tests"))]
+    {
+        None
+    }
+}
+
+/// Estimated heap bytes owned by a value, including the value itself
+///
+/// Counts the buffers of text, collections and boxed values. Chant closures
+/// are counted by their code only: the environment they capture is shared
+/// with the scope that created them and would otherwise be counted twice.
+pub fn retained_bytes(value: &Value) -> usize {
+    size_of::<Value>() + owned_bytes(value)
+}
+
+/// Heap bytes owned by a value, excluding the value itself
+fn owned_bytes(value: &Value) -> usize {
+    match value {
+        Value::Text(text) => text.capacity(),
+        Value::TextBuilder { buffer } => buffer.capacity(),
+        Value::List(items) | Value::Tuple(items) => {
+            (items.capacity() - items.len()) * size_of::<Value>() + items.iter().map(retained_bytes).sum::<usize>()
+        }
+        Value::Map(entries) | Value::StructInstance { fields: entries, .. } => entries
+            .iter()
+            .map(|(key, value)| key.capacity() + retained_bytes(value))
+            .sum(),
+        Value::VariantValue { fields, .. } => fields.iter().map(retained_bytes).sum(),
+        Value::Chant { params, body, .. } => {
+            params.len() * size_of::<crate::ast::Parameter>() + body.len() * size_of::<crate::ast::AstNode>()
+        }
+        Value::Range { start, end } => retained_bytes(start) + retained_bytes(end),
+        Value::Outcome { value, .. } | Value::Shared { value, .. } | Value::Cell { value, .. } | Value::Frozen { value } | Value::Tainted { value, .. } => {
+            retained_bytes(value)
+        }
+        Value::Maybe { value: Some(value), .. } => retained_bytes(value),
+        Value::Set(elements) => elements
+            .iter()
+            .map(|element| match element {
+                crate::eval::SetElement::Text(text) => size_of::<crate::eval::SetElement>() + text.capacity(),
+                _ => size_of::<crate::eval::SetElement>(),
+            })
+            .sum(),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retained_bytes_follow_contents() {
+        let small = Value::List(Vec::new());
+        let big = Value::List(alloc::vec![Value::Text("x".repeat(100)); 10]);
+        assert_eq!(retained_bytes(&small), size_of::<Value>());
+        assert!(retained_bytes(&big) >= 1000 + 10 * size_of::<Value>());
+    }
+
+    #[test]
+    fn test_report_deltas() {
+        let before = HeapSnapshot { native_bytes: Some(100), live_resources: 1, ..HeapSnapshot::default() };
+        let after = HeapSnapshot { native_bytes: Some(164), live_resources: 3, ..HeapSnapshot::default() };
+        let report = LeakReport::new(before.clone(), after);
+        assert_eq!(report.outstanding_native_bytes(), 64);
+        assert_eq!(report.leaked_resources(), 2);
+        assert!(!report.is_clean());
+
+        assert!(LeakReport::new(before.clone(), before).is_clean());
+    }
+}
@@ -22,8 +22,10 @@
 //! }
 //! ```
 
-use alloc::string::String;
+use alloc::format;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
+use crate::bigint::BigInt;
 use crate::token::{Span, Token, PositionedToken};
 
 /// Lexer state for tokenizing Glimmer-Weave source code
@@ -141,18 +143,56 @@ impl Lexer {
         Token::Text(result)
     }
 
-    /// Read a numeric literal (integer or float)
+    /// Read a numeric literal (integer, float, `n`-suffixed big integer or
+    /// `i`-suffixed 64-bit integer)
+    ///
+    /// `0x`, `0o` and `0b` prefixes give hex, octal and binary digits, and
+    /// `_` may separate any two digits: `0xFF_FF`, `1_000_000`. A prefix
+    /// without digits, a digit outside the radix, or a `_` that does not
+    /// stand between two digits makes the literal `Token::Invalid`.
     fn read_number(&mut self) -> Token {
+        let start = self.position;
+        let radix = match (self.current_char, self.peek()) {
+            (Some('0'), Some('x')) => 16,
+            (Some('0'), Some('o')) => 8,
+            (Some('0'), Some('b')) => 2,
+            _ => 10,
+        };
+        if radix != 10 {
+            self.advance();
+            self.advance();
+        }
+
         let mut num_str = String::new();
+        if let Err(reason) = self.read_digits(&mut num_str, radix) {
+            return self.invalid_number(start, reason);
+        }
+        if num_str.is_empty() {
+            return self.invalid_number(start, format!("no {} digits after the prefix", radix_name(radix)));
+        }
 
-        // Read digits before decimal point
-        while let Some(c) = self.current_char {
-            if c.is_ascii_digit() {
-                num_str.push(c);
+        // Big integer suffix: `123n`
+        if self.current_char == Some('n') && !self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            if let Some(value) = parse_bigint(&num_str, radix) {
                 self.advance();
-            } else {
-                break;
+                return Token::BigInt(value);
+            }
+        }
+
+        // Integer suffix: `42i`, which must fit in 64 bits
+        if self.current_char == Some('i') && !self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            self.advance();
+            return match i64::from_str_radix(&num_str, radix) {
+                Ok(value) => Token::Integer(value),
+                Err(_) => Token::Invalid(format!("Integer literal {} does not fit in 64 bits", self.text_from(start))),
+            };
+        }
+
+        if radix != 10 {
+            if let Some(c) = self.current_char.filter(|c| c.is_alphanumeric()) {
+                return self.invalid_number(start, format!("'{}' is not a valid {} digit", c, radix_name(radix)));
             }
+            return Token::Number(radix_number(&num_str, radix));
         }
 
         // Check for decimal point
@@ -161,13 +201,8 @@ impl Lexer {
             self.advance();
 
             // Read digits after decimal point
-            while let Some(c) = self.current_char {
-                if c.is_ascii_digit() {
-                    num_str.push(c);
-                    self.advance();
-                } else {
-                    break;
-                }
+            if let Err(reason) = self.read_digits(&mut num_str, 10) {
+                return self.invalid_number(start, reason);
             }
         }
 
@@ -176,6 +211,39 @@ impl Lexer {
         Token::Number(value)
     }
 
+    /// Source text from `start` up to the current position
+    fn text_from(&self, start: usize) -> String {
+        self.input[start..self.position].iter().collect()
+    }
+
+    /// Skip the rest of a malformed number literal and report it
+    fn invalid_number(&mut self, start: usize, reason: String) -> Token {
+        while self.current_char.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            self.advance();
+        }
+        Token::Invalid(format!("Invalid number literal {}: {}", self.text_from(start), reason))
+    }
+
+    /// Append digits of `radix` to `digits`, skipping `_` separators
+    ///
+    /// A `_` that does not stand between two digits is an error.
+    fn read_digits(&mut self, digits: &mut String, radix: u32) -> Result<(), String> {
+        while let Some(c) = self.current_char {
+            if c.is_digit(radix) {
+                digits.push(c);
+                self.advance();
+            } else if c == '_' {
+                if digits.is_empty() || !self.peek().is_some_and(|next| next.is_digit(radix)) {
+                    return Err("'_' must stand between two digits".to_string());
+                }
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        Ok(())
+    }
+
     /// Read a lifetime annotation (starting with ')
     fn read_lifetime(&mut self) -> Token {
         // Skip the opening apostrophe
@@ -385,6 +453,7 @@ impl Lexer {
             "first" => Token::First,
             "last" => Token::Last,
             "attempt" => Token::Attempt,
+            "defer" => Token::Defer,
             "harmonize" => Token::Harmonize,
             "on" => Token::On,
             "match" => Token::Match,
@@ -598,6 +667,37 @@ impl Lexer {
     }
 }
 
+/// A big integer from digits of any radix
+fn parse_bigint(digits: &str, radix: u32) -> Option<BigInt> {
+    if radix == 10 {
+        return BigInt::parse(digits);
+    }
+    let base = BigInt::from_i64(i64::from(radix));
+    digits.chars().try_fold(BigInt::zero(), |value, c| {
+        let digit = BigInt::from_i64(i64::from(c.to_digit(radix)?));
+        Some(&(&value * &base) + &digit)
+    })
+}
+
+/// Name of a literal radix, for messages
+fn radix_name(radix: u32) -> &'static str {
+    match radix {
+        16 => "hex",
+        8 => "octal",
+        2 => "binary",
+        _ => "decimal",
+    }
+}
+
+/// The nearest Number to digits of a non-decimal radix
+fn radix_number(digits: &str, radix: u32) -> f64 {
+    match u128::from_str_radix(digits, radix) {
+        Ok(value) => value as f64,
+        // Past 128 bits; large enough that rounding at each digit is moot
+        Err(_) => digits.chars().filter_map(|c| c.to_digit(radix)).fold(0.0, |value, digit| value * radix as f64 + digit as f64),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -618,6 +718,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_numbers() {
         let source = "42 3.14 0 100.5";
         let mut lexer = Lexer::new(source);
@@ -629,6 +730,50 @@ mod tests {
         assert_eq!(tokens[3], Token::Number(100.5));
     }
 
+    #[test]
+    fn test_radix_literals_and_separators() {
+        let source = "0xFF 0b1010 0o755 1_000_000 0xFF_FFi 0b11n 2_5.0_5 10 ms";
+        let tokens = Lexer::new(source).tokenize();
+
+        assert_eq!(tokens[0], Token::Number(255.0));
+        assert_eq!(tokens[1], Token::Number(10.0));
+        assert_eq!(tokens[2], Token::Number(493.0));
+        assert_eq!(tokens[3], Token::Number(1_000_000.0));
+        assert_eq!(tokens[4], Token::Integer(0xFFFF));
+        assert_eq!(tokens[5], Token::BigInt(BigInt::from_i64(3)));
+        assert_eq!(tokens[6], Token::Number(25.05));
+        assert_eq!(tokens[7], Token::Number(10.0));
+        assert_eq!(tokens[8], Token::Ident("ms".to_string()));
+    }
+
+    #[test]
+    fn test_malformed_radix_literals() {
+        let invalid = |source: &str| match Lexer::new(source).tokenize().as_slice() {
+            [Token::Invalid(message), Token::Eof] => message.clone(),
+            other => panic!("expected one invalid token for {}, got {:?}", source, other),
+        };
+        assert_eq!(invalid("0x"), "Invalid number literal 0x: no hex digits after the prefix");
+        assert_eq!(invalid("0xG1"), "Invalid number literal 0xG1: no hex digits after the prefix");
+        assert_eq!(invalid("0o8"), "Invalid number literal 0o8: no octal digits after the prefix");
+        assert_eq!(invalid("0b102"), "Invalid number literal 0b102: '2' is not a valid binary digit");
+        assert_eq!(invalid("0o17z"), "Invalid number literal 0o17z: 'z' is not a valid octal digit");
+        assert_eq!(invalid("0x_FF"), "Invalid number literal 0x_FF: '_' must stand between two digits");
+        assert_eq!(invalid("0xFF_"), "Invalid number literal 0xFF_: '_' must stand between two digits");
+        assert_eq!(invalid("0x_FF_"), "Invalid number literal 0x_FF_: '_' must stand between two digits");
+        assert_eq!(invalid("1_"), "Invalid number literal 1_: '_' must stand between two digits");
+        assert_eq!(invalid("1__000"), "Invalid number literal 1__000: '_' must stand between two digits");
+        assert_eq!(invalid("2.5_"), "Invalid number literal 2.5_: '_' must stand between two digits");
+    }
+
+    #[test]
+    fn test_integer_literal_out_of_range() {
+        let tokens = Lexer::new("9223372036854775807i 9223372036854775808i 0x1_0000_0000_0000_0000i x").tokenize();
+        assert_eq!(tokens[0], Token::Integer(i64::MAX));
+        assert_eq!(tokens[1], Token::Invalid("Integer literal 9223372036854775808i does not fit in 64 bits".to_string()));
+        assert_eq!(tokens[2], Token::Invalid("Integer literal 0x1_0000_0000_0000_0000i does not fit in 64 bits".to_string()));
+        assert_eq!(tokens[3], Token::Ident("x".to_string()));
+    }
+
     #[test]
     fn test_strings() {
         let source = r#""hello" "world" "test\nstring""#;
@@ -34,12 +34,70 @@
 //! - [`token`]: Token definitions for the lexer
 //! - [`lexer`]: Tokenizer for Glimmer-Weave source code
 //! - [`ast`]: Abstract Syntax Tree node types
+//! - [`ast_query`]: Structural queries over ASTs for tools and lints (`find_calls("VGA.write")`)
 //! - [`parser`]: Parser for building AST from tokens
 //! - [`eval`]: Evaluator/interpreter for executing AST
 //! - [`codegen`]: Code generator for compiling to x86-64 assembly
+//! - [`target`]: Pointer width, byte order, ELF ABI and register conventions of a compilation target
+//! - [`convert`]: Number-to-integer conversions shared by builtins and codegen
+//! - [`coercion`]: Truthiness, number and text conversions every engine applies alike
+//! - [`units`]: Units of measure for number literals (`10 ms`, `4 KiB`)
+//! - [`bigint`]: Arbitrary-precision integers behind `123n` literals
+//! - [`language_version`]: `speaks "1.2"` pragmas and the version each newer feature needs
+//! - [`inline`]: Optimizer pass that inlines calls to small chants
+//! - [`loop_opt`]: Loop-invariant code motion and strength reduction
+//! - [`range_analysis`]: Interval analysis that flags out-of-bounds indexing and division by zero
+//! - [`purity`]: Purity analysis shared by the optimizer passes
+//! - [`cse`]: Common subexpression elimination across statements
+//! - [`profile`]: Call counts and branch outcomes that guide optimized builds
+//! - [`deprecation`]: `deprecated` chants, forms and groves, and warnings where they are used
+//! - [`docgen`]: Markdown reference of a script's groves, chants and forms, with deprecation banners
+//! - [`time_travel`]: Recording of evaluation steps, and a debugger that steps back through them
+//! - [`pipeline`]: Builder that runs source through every compilation stage
+//! - [`self_host`]: Lexing, parsing, analysis and compilation as builtins behind `Compiler.inspect`
+//! - [`fixit`]: Machine-applicable edits attached to diagnostics, and applying them
+//! - [`embed`]: Single-call [`run`] for embedders, configured by [`EvalOptions`]
+//! - [`script_prelude`]: Builtins injected into the scope of every compilation unit
+//! - [`scheduler`]: Scheduler hooks for spawned script tasks
+//! - [`clock`]: Host time source for execution deadlines
+//! - [`cancellation`]: Tokens the host trips to cancel a running script
+//! - [`resource`]: Host handles with deterministic release
+//! - [`stream`]: Lazy line and byte iterators over host files and the console
+//! - [`net`]: TCP sockets behind `Net.connect` capabilities and a host allowlist
+//! - [`term`]: Cursor, color, clearing and key input behind `Term.control`
+//! - [`draw`]: Double-buffered pixel drawing behind `Framebuffer.draw`
+//! - [`sound`]: Tones through a host speaker behind `Speaker.play`
+//! - [`progress`]: Progress of long-running tasks, reported to a host sink
+//! - [`i18n`]: Message catalogs per locale for `tr`, with key checks
+//! - [`http`]: `http_get` and `http_post` over the network provider
+//! - [`output`]: Host sink for what scripts print
+//! - [`storage`]: Key-value state that outlives a script, kept by a host provider
+//! - [`bus`]: Named-topic publish/subscribe between the scripts of a session
+//! - [`sync`]: Shared state that becomes thread-safe with the `sync` feature
+//! - [`capability`]: Capability grant policy, audit log and requirement inference
+//! - [`taint`]: Tracks data from sensitive capabilities and keeps it from output sinks
+//! - [`verify`]: Signature checks on loaded code through a host verifier
+//! - [`bytecode_image`]: Binary `.gwc` encoding of compiled bytecode
+//! - [`bundle`]: Single-file bundles of a script and its modules, with a manifest and signature
+//! - [`value_codec`]: Compact binary encoding of values exchanged with the host
+//! - [`config`]: TOML and INI configuration documents as nested Maps
+//! - [`csv`]: Delimited tabular text as Lists of Maps or Lists
+//! - [`value_diff`]: Change lists between values and patching with them
+//! - [`module_cache`]: Content-addressed cache of parsed modules and compiled bytecode
+//! - [`session`]: Shared resolver, caches and policy for many short-lived evaluators
+//! - [`test_runner`]: Runs the `verify` blocks across a module graph
+//! - [`mutation`]: Mutation testing that judges how well verify blocks check a script
+//! - [`leak_check`]: Heap usage reports that flag what an execution leaves behind
+//! - [`heap_graph`]: Object graph dumps, as Graphviz or JSON, for finding retained values
+//! - [`crash_dump`]: Structured reports of scripts that die with an uncaught error
+//! - [`watchdog`]: Heartbeats a supervised script promises, and what happens when it misses one
+//! - `native_module`: Loads natively compiled chants so Rust can call them (std, Linux)
+//! - `bench`: Workload timings across interpreter, VM and native backends (std only)
+//! - `golden`: Compiler output checked against golden files, with an update mode (std only)
 
 // Declare as no_std by default, but allow std feature to enable standard library
 #![cfg_attr(not(feature = "std"), no_std)]
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
 
 // When std feature is enabled, provide alloc via std
 // Import macros (format!, vec!) from alloc
@@ -59,24 +117,87 @@ mod prelude;
 pub mod token;
 pub mod lexer;
 pub mod ast;
+pub mod ast_query;
 pub mod parser;
+pub mod ritual;
 pub mod eval;
 pub mod codegen;
 pub mod elf;
+pub mod target;
 pub mod runtime;
+pub mod convert;
+pub mod coercion;
+pub mod units;
+pub mod language_version;
+pub mod bigint;
+pub mod script_prelude;
+pub mod scheduler;
+pub mod clock;
+pub mod cancellation;
+pub mod resource;
+pub mod stream;
+pub mod net;
+pub mod term;
+pub mod draw;
+pub mod sound;
+pub mod progress;
+pub mod i18n;
+pub mod http;
+pub mod output;
+pub mod storage;
+pub mod bus;
+pub mod sync;
+pub mod capability;
+pub mod taint;
+pub mod verify;
 pub mod semantic;
+pub mod deprecation;
+pub mod docgen;
+pub mod range_analysis;
 pub mod bytecode;
 pub mod bytecode_compiler;
+pub mod bytecode_image;
+pub mod bundle;
+pub mod value_codec;
+pub mod config;
+pub mod csv;
+pub mod value_diff;
 pub mod vm;
 pub mod monomorphize;
+pub mod inline;
+pub mod loop_opt;
+pub mod purity;
+pub mod cse;
+pub mod profile;
+pub mod time_travel;
 pub mod type_inference;
 pub mod borrow_checker;
 pub mod lifetime_checker;
 pub mod source_location;
 pub mod error_formatter;
+pub mod fixit;
 pub mod native_runtime;
 pub mod module_resolver;
+pub mod module_cache;
+pub mod session;
+pub mod test_runner;
+pub mod mutation;
+pub mod leak_check;
+pub mod heap_graph;
+pub mod crash_dump;
+pub mod watchdog;
 pub mod symbol_table;
+pub mod pipeline;
+pub mod self_host;
+pub mod embed;
+
+// Engine benchmarks (needs std for timing)
+#[cfg(feature = "std")]
+pub mod bench;
+
+// Golden-file checks of compiler output (needs std for files)
+#[cfg(feature = "std")]
+pub mod golden;
 
 // LSP server (only available with lsp feature)
 #[cfg(feature = "lsp")]
@@ -84,39 +205,11 @@ pub mod lsp;
 
 // Native allocator FFI (only available when compiled with GNU assembler)
 #[cfg(all(target_arch = "x86_64", not(target_env = "msvc")))]
-pub mod native_allocator {
-    //! FFI bindings to the native heap allocator (gl_malloc/gl_free)
-    //!
-    //! This module is only available on x86_64 platforms with GNU assembler support.
-    //! The allocator is implemented in `src/native_allocator.S` and linked via build.rs.
-
-    extern "C" {
-        /// Initialize the allocator
-        ///
-        /// This is called automatically on first gl_malloc, but can be called explicitly.
-        pub fn gl_init_allocator();
-
-        /// Allocate `size` bytes of memory on the heap
-        ///
-        /// Returns a pointer to the allocated memory, or NULL if allocation fails.
-        /// The returned pointer is guaranteed to be 8-byte aligned.
-        pub fn gl_malloc(size: usize) -> *mut u8;
-
-        /// Free memory previously allocated by gl_malloc
-        ///
-        /// If ptr is NULL, this is a no-op (safe).
-        pub fn gl_free(ptr: *mut u8);
-
-        /// Get the total number of bytes currently allocated
-        pub fn gl_get_allocated_bytes() -> u64;
-
-        /// Get the start address of the heap
-        pub fn gl_get_heap_start() -> *mut u8;
+pub mod native_allocator;
 
-        /// Get the end address of the heap
-        pub fn gl_get_heap_end() -> *mut u8;
-    }
-}
+// Loader for natively compiled chants (needs the allocator and mmap)
+#[cfg(all(feature = "std", feature = "allocator_tests", target_os = "linux"))]
+pub mod native_module;
 
 // Re-export commonly used types
 pub use token::{Token, Span};
@@ -126,7 +219,13 @@ pub use parser::{Parser, ParseError, ParseResult};
 pub use eval::{Value, RuntimeError, Environment, Evaluator};
 pub use codegen::{CodeGen, Instruction, Register, compile_to_asm};
 pub use elf::{ElfBuilder, create_elf_object};
-pub use semantic::{SemanticAnalyzer, SemanticError, Type, analyze};
+pub use target::TargetSpec;
+pub use semantic::{SemanticAnalyzer, SemanticError, SemanticWarning, Type, analyze, resolve_scopes};
 pub use borrow_checker::{BorrowChecker, BorrowError};
 pub use lifetime_checker::{LifetimeChecker, LifetimeError};
 pub use module_resolver::{ModuleResolver, ModuleInfo, ResolverError, ResolverResult};
+pub use pipeline::CompilerPipeline;
+pub use embed::{run, Backend, EvalOptions, SandboxProfile};
+pub use script_prelude::Prelude;
+pub use scheduler::{CooperativeScheduler, Scheduler};
+pub use error_formatter::{ColorChoice, Diagnostic, Diagnostics, FormatOptions};
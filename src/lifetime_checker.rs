@@ -4,11 +4,13 @@
 //! This prevents dangling pointers and use-after-free errors.
 
 use alloc::collections::BTreeMap;
+use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use core::fmt;
 
 use crate::ast::{AstNode, Lifetime, TypeAnnotation};
+use crate::error_formatter::Diagnostic;
 use crate::source_location::SourceSpan;
 
 /// Errors that can occur during lifetime checking
@@ -79,6 +81,30 @@ impl fmt::Display for LifetimeError {
     }
 }
 
+impl LifetimeError {
+    /// Diagnostic marking the offending code
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            LifetimeError::OutlivesReferent { reference, reference_lifetime, referent_lifetime, span } => {
+                Diagnostic::error(format!("Reference '{}' outlives the data it points to", reference))
+                    .with_primary_label(span.clone(), format!("'{}' must not outlive '{}'", reference_lifetime, referent_lifetime))
+            }
+            LifetimeError::ReturnsLocalReference { variable, span } => {
+                Diagnostic::error(format!("Cannot return reference to local variable '{}'", variable))
+                    .with_primary_label(span.clone(), "returns a reference to data owned by this chant")
+            }
+            LifetimeError::UndeclaredLifetime { lifetime, span } => {
+                Diagnostic::error(format!("Lifetime '{}' is not declared", lifetime))
+                    .with_primary_label(span.clone(), "undeclared lifetime")
+            }
+            LifetimeError::LifetimeConflict { first, second, span } => {
+                Diagnostic::error(format!("Conflicting lifetime requirements: '{}' and '{}'", first, second))
+                    .with_primary_label(span.clone(), "required here")
+            }
+        }
+    }
+}
+
 /// Tracks lifetime information for variables
 #[derive(Debug, Clone)]
 struct LifetimeInfo {
@@ -256,7 +282,7 @@ impl LifetimeChecker {
                 }
                 self.check_type_annotation(inner);
             }
-            TypeAnnotation::List(inner) => {
+            TypeAnnotation::List(inner) | TypeAnnotation::Array { element: inner, .. } => {
                 self.check_type_annotation(inner);
             }
             TypeAnnotation::Parametrized { type_args, .. } => {
@@ -0,0 +1,528 @@
+//! # Loop Optimization Module
+//!
+//! Moves work out of loop bodies before the compiled back ends see them.
+//!
+//! ## Loop-Invariant Code Motion
+//!
+//! An expression inside a `whilst` or `for each` loop (condition included)
+//! whose variables the loop never changes is computed once, into a binding
+//! placed before the loop:
+//!
+//! ```glimmer
+//! whilst i less than n * 2 then          bind invariant.0 to n * 2
+//!     set total to total + (a + b)   →    bind invariant.1 to a + b
+//!     set i to i + 1                      whilst i less than invariant.0 then
+//! end                                         set total to total + invariant.1
+//!                                             set i to i + 1
+//!                                         end
+//! ```
+//!
+//! Only operators over variables and literals are hoisted, and only those
+//! that cannot fail: the hoisted expression runs even when the loop body
+//! does not, so division and modulo move only by a nonzero literal.
+//! A variable counts as changed when the loop binds or `set`s it (including
+//! through an index or field), and, when the loop calls anything, when any
+//! chant `set`s it.
+//!
+//! ## Strength Reduction
+//!
+//! A `whilst` loop counter stepped by a single top-level
+//! `set i to i + c` (or `- c`), with `c` a whole number, turns products
+//! `i * k` with a loop-invariant `k` into a running value:
+//!
+//! ```glimmer
+//! whilst i less than n then              weave stride.0 as i * 4
+//!     set total to total + i * 4     →    whilst i less than n then
+//!     set i to i + 1                          set total to total + stride.0
+//! end                                         set i to i + 1
+//!                                             set stride.0 to stride.0 + 4
+//!                                         end
+//! ```
+//!
+//! The running value is exact while the products are whole numbers.
+//! Introduced names contain a `.`, so they never collide with script
+//! variables.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use crate::ast::*;
+use crate::purity::{assigned_in_chants, root_name, same_expr, statement_lists};
+use crate::source_location::SourceSpan;
+
+/// Hoists loop invariants and strength-reduces loop counter products
+pub struct LoopOptimizer {
+    /// Variables some chant `set`s, which calls inside a loop may change
+    assigned_in_chants: BTreeSet<String>,
+    counter: usize,
+}
+
+impl Default for LoopOptimizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LoopOptimizer {
+    /// Create a new loop optimizer
+    pub fn new() -> Self {
+        LoopOptimizer {
+            assigned_in_chants: BTreeSet::new(),
+            counter: 0,
+        }
+    }
+
+    /// Optimize every loop in a program
+    pub fn optimize(&mut self, program: &[AstNode]) -> Vec<AstNode> {
+        self.assigned_in_chants = assigned_in_chants(program);
+        let mut program = program.to_vec();
+        self.optimize_block(&mut program);
+        program
+    }
+
+    /// Optimize the loops of a statement list, inner loops first, placing
+    /// each loop's new bindings right before it
+    fn optimize_block(&mut self, statements: &mut Vec<AstNode>) {
+        let mut index = 0;
+        while index < statements.len() {
+            for block in statement_lists(&mut statements[index]) {
+                self.optimize_block(block);
+            }
+            let prelude = match &mut statements[index] {
+                AstNode::WhileStmt { .. } | AstNode::ForStmt { .. } => self.optimize_loop(&mut statements[index]),
+                _ => Vec::new(),
+            };
+            let inserted = prelude.len();
+            statements.splice(index..index, prelude);
+            index += inserted + 1;
+        }
+    }
+
+    fn optimize_loop(&mut self, node: &mut AstNode) -> Vec<AstNode> {
+        let changed = self.changed_in_loop(node);
+        let mut prelude = Vec::new();
+
+        // Loop-invariant code motion
+        let mut hoisted: Vec<(AstNode, String)> = Vec::new();
+        match node {
+            AstNode::WhileStmt { condition, body, .. } => {
+                self.hoist(condition, &changed, &mut hoisted);
+                for statement in body.iter_mut() {
+                    self.hoist(statement, &changed, &mut hoisted);
+                }
+            }
+            AstNode::ForStmt { iterable: _, body, .. } => {
+                // The iterable is evaluated once already
+                for statement in body.iter_mut() {
+                    self.hoist(statement, &changed, &mut hoisted);
+                }
+            }
+            _ => {}
+        }
+        for (expr, name) in hoisted {
+            prelude.push(AstNode::BindStmt { name, typ: None, value: Box::new(expr), span: SourceSpan::default() });
+        }
+
+        // Strength reduction
+        if let AstNode::WhileStmt { condition, body, .. } = node {
+            for (counter, step) in induction_variables(body) {
+                if changed.iter().filter(|name| **name == counter).count() > 1 {
+                    continue;
+                }
+                let mut strides: Vec<(AstNode, String)> = Vec::new();
+                self.reduce(condition, &counter, &changed, &mut strides);
+                for statement in body.iter_mut() {
+                    self.reduce(statement, &counter, &changed, &mut strides);
+                }
+                let Some(update) = body.iter().position(|statement| is_step_of(statement, &counter).is_some()) else {
+                    continue;
+                };
+                for (offset, (factor, name)) in strides.into_iter().enumerate() {
+                    let delta = match &factor {
+                        AstNode::Number { value, .. } => number(step * value),
+                        _ if step == 1.0 => factor.clone(),
+                        _ => {
+                            let delta = self.fresh("invariant");
+                            prelude.push(AstNode::BindStmt {
+                                name: delta.clone(),
+                                typ: None,
+                                value: Box::new(binary(number(step), BinaryOperator::Mul, factor.clone())),
+                                span: SourceSpan::default(),
+                            });
+                            ident(&delta)
+                        }
+                    };
+                    prelude.push(AstNode::WeaveStmt {
+                        name: name.clone(),
+                        typ: None,
+                        value: Box::new(binary(ident(&counter), BinaryOperator::Mul, factor)),
+                        span: SourceSpan::default(),
+                    });
+                    body.insert(
+                        update + 1 + offset,
+                        AstNode::SetStmt {
+                            target: Box::new(ident(&name)),
+                            value: Box::new(binary(ident(&name), BinaryOperator::Add, delta)),
+                            span: SourceSpan::default(),
+                        },
+                    );
+                }
+            }
+        }
+        prelude
+    }
+
+    /// Names a loop may change: bound or `set` inside it (one entry per
+    /// `set`), plus what chants `set` when the loop calls anything
+    fn changed_in_loop(&self, node: &AstNode) -> Vec<String> {
+        let mut changed = Vec::new();
+        if let AstNode::ForStmt { variable, .. } = node {
+            changed.push(variable.clone());
+        }
+        let mut calls = false;
+        let mut pending = match node {
+            AstNode::WhileStmt { condition, body, .. } => {
+                let mut nodes: Vec<&AstNode> = body.iter().collect();
+                nodes.push(condition);
+                nodes
+            }
+            AstNode::ForStmt { body, .. } => body.iter().collect(),
+            _ => Vec::new(),
+        };
+        while let Some(node) = pending.pop() {
+            match node {
+                AstNode::SetStmt { target, .. } => changed.extend(root_name(target)),
+                AstNode::BindStmt { name, .. } | AstNode::WeaveStmt { name, .. } | AstNode::ChantDef { name, .. } => {
+                    changed.push(name.clone())
+                }
+                AstNode::ForStmt { variable, .. } => changed.push(variable.clone()),
+                AstNode::MatchStmt { arms, .. } => {
+                    for arm in arms {
+                        pattern_names(&arm.pattern, &mut changed);
+                    }
+                }
+                AstNode::Call { .. } | AstNode::Pipeline { .. } => calls = true,
+                _ => {}
+            }
+            pending.extend(node.children());
+        }
+        if calls {
+            changed.extend(self.assigned_in_chants.iter().cloned());
+        }
+        changed
+    }
+
+    /// Replace maximal invariant expressions with the bindings they move to
+    fn hoist(&mut self, node: &mut AstNode, changed: &[String], hoisted: &mut Vec<(AstNode, String)>) {
+        if is_invariant(node, changed) && worth_hoisting(node) {
+            let name = match hoisted.iter().find(|(expr, _)| same_expr(expr, node)) {
+                Some((_, name)) => name.clone(),
+                None => {
+                    let name = self.fresh("invariant");
+                    hoisted.push((node.clone(), name.clone()));
+                    name
+                }
+            };
+            *node = ident(&name);
+            return;
+        }
+        match node {
+            // A nested chant runs in its own scope
+            AstNode::ChantDef { .. } => {}
+            // The target names what is assigned; only an index inside it is read
+            AstNode::SetStmt { target, value, .. } => {
+                if let AstNode::IndexAccess { index, .. } = target.as_mut() {
+                    self.hoist(index, changed, hoisted);
+                }
+                self.hoist(value, changed, hoisted);
+            }
+            _ => {
+                for child in node.children_mut() {
+                    self.hoist(child, changed, hoisted);
+                }
+            }
+        }
+    }
+
+    /// Replace `counter * factor` products with running strides
+    fn reduce(&mut self, node: &mut AstNode, counter: &str, changed: &[String], strides: &mut Vec<(AstNode, String)>) {
+        if let AstNode::BinaryOp { left, op: BinaryOperator::Mul, right, .. } = node {
+            let factor = match (left.as_ref(), right.as_ref()) {
+                (AstNode::Ident { name, .. }, factor) | (factor, AstNode::Ident { name, .. })
+                    if name == counter && is_stride_factor(factor, changed) =>
+                {
+                    Some(factor.clone())
+                }
+                _ => None,
+            };
+            if let Some(factor) = factor {
+                let name = match strides.iter().find(|(known, _)| same_expr(known, &factor)) {
+                    Some((_, name)) => name.clone(),
+                    None => {
+                        let name = self.fresh("stride");
+                        strides.push((factor, name.clone()));
+                        name
+                    }
+                };
+                *node = ident(&name);
+                return;
+            }
+        }
+        match node {
+            AstNode::ChantDef { .. } => {}
+            AstNode::SetStmt { target, value, .. } => {
+                if let AstNode::IndexAccess { index, .. } = target.as_mut() {
+                    self.reduce(index, counter, changed, strides);
+                }
+                self.reduce(value, counter, changed, strides);
+            }
+            _ => {
+                for child in node.children_mut() {
+                    self.reduce(child, counter, changed, strides);
+                }
+            }
+        }
+    }
+
+    fn fresh(&mut self, kind: &str) -> String {
+        let name = format!("{}.{}", kind, self.counter);
+        self.counter += 1;
+        name
+    }
+}
+
+/// Optimize every loop in a program with a fresh [`LoopOptimizer`]
+pub fn optimize_loops(program: &[AstNode]) -> Vec<AstNode> {
+    LoopOptimizer::new().optimize(program)
+}
+
+fn pattern_names(pattern: &Pattern, names: &mut Vec<String>) {
+    match pattern {
+        Pattern::Ident(name) | Pattern::Affix { rest: Some(name), .. } => names.push(name.clone()),
+        Pattern::Enum { inner: Some(inner), .. } => pattern_names(inner, names),
+        Pattern::Tuple(elements) | Pattern::Or(elements) => elements.iter().for_each(|element| pattern_names(element, names)),
+        _ => {}
+    }
+}
+
+/// Operators over unchanged variables and literals that cannot fail
+fn is_invariant(node: &AstNode, changed: &[String]) -> bool {
+    match node {
+        AstNode::Number { .. } | AstNode::BigInt { .. } | AstNode::Measure { .. } | AstNode::Truth { .. } => true,
+        AstNode::Ident { name, .. } => !changed.contains(name),
+        // `in` divides by the unit's factor, which is never zero
+        AstNode::UnaryOp { operand, .. } | AstNode::InUnit { value: operand, .. } => is_invariant(operand, changed),
+        AstNode::BinaryOp { left, op, right, .. } => {
+            let safe = match op {
+                BinaryOperator::Div | BinaryOperator::Mod => {
+                    matches!(right.as_ref(), AstNode::Number { value, .. } if *value != 0.0)
+                }
+                _ => true,
+            };
+            safe && is_invariant(left, changed) && is_invariant(right, changed)
+        }
+        _ => false,
+    }
+}
+
+/// An operator applied to at least one variable (constants are left alone)
+fn worth_hoisting(node: &AstNode) -> bool {
+    fn reads_variable(node: &AstNode) -> bool {
+        matches!(node, AstNode::Ident { .. }) || node.children().into_iter().any(reads_variable)
+    }
+    matches!(node, AstNode::BinaryOp { .. } | AstNode::UnaryOp { .. }) && reads_variable(node)
+}
+
+fn is_stride_factor(node: &AstNode, changed: &[String]) -> bool {
+    match node {
+        AstNode::Number { value, .. } => value % 1.0 == 0.0,
+        AstNode::Ident { name, .. } => !changed.contains(name),
+        _ => false,
+    }
+}
+
+/// Counters stepped by a whole number in one top-level statement of the body
+fn induction_variables(body: &[AstNode]) -> Vec<(String, f64)> {
+    let mut counters: Vec<(String, f64)> = Vec::new();
+    for statement in body {
+        if let (Some(step), AstNode::SetStmt { target, .. }) = (statement_step(statement), statement) {
+            if let AstNode::Ident { name, .. } = target.as_ref() {
+                if !counters.iter().any(|(counter, _)| counter == name) {
+                    counters.push((name.clone(), step));
+                }
+            }
+        }
+    }
+    counters
+}
+
+fn statement_step(statement: &AstNode) -> Option<f64> {
+    match statement {
+        AstNode::SetStmt { target, .. } => match target.as_ref() {
+            AstNode::Ident { name, .. } => is_step_of(statement, name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The step of `set counter to counter + c` (or `- c`)
+fn is_step_of(statement: &AstNode, counter: &str) -> Option<f64> {
+    let AstNode::SetStmt { target, value, .. } = statement else {
+        return None;
+    };
+    if !matches!(target.as_ref(), AstNode::Ident { name, .. } if name == counter) {
+        return None;
+    }
+    let AstNode::BinaryOp { left, op, right, .. } = value.as_ref() else {
+        return None;
+    };
+    let (AstNode::Ident { name, .. }, AstNode::Number { value: step, .. }) = (left.as_ref(), right.as_ref()) else {
+        return None;
+    };
+    if name != counter || step % 1.0 != 0.0 {
+        return None;
+    }
+    match op {
+        BinaryOperator::Add => Some(*step),
+        BinaryOperator::Sub => Some(-step),
+        _ => None,
+    }
+}
+
+fn ident(name: &str) -> AstNode {
+    AstNode::Ident { name: name.into(), span: SourceSpan::default(), slot: None }
+}
+
+fn number(value: f64) -> AstNode {
+    AstNode::Number { value, span: SourceSpan::default() }
+}
+
+fn binary(left: AstNode, op: BinaryOperator, right: AstNode) -> AstNode {
+    AstNode::BinaryOp { left: Box::new(left), op, right: Box::new(right), span: SourceSpan::default() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::{Evaluator, Value};
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Vec<AstNode> {
+        Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap()
+    }
+
+    /// Optimize a program, checking it still evaluates to the same value
+    fn optimize_source(source: &str) -> Vec<AstNode> {
+        let program = parse(source);
+        let optimized = optimize_loops(&program);
+        let before = Evaluator::new().eval(&program).unwrap();
+        let after = Evaluator::new().eval(&optimized).unwrap();
+        assert_eq!(before, after);
+        optimized
+    }
+
+    fn bindings(program: &[AstNode]) -> Vec<&str> {
+        program
+            .iter()
+            .filter_map(|node| match node {
+                AstNode::BindStmt { name, .. } | AstNode::WeaveStmt { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn loop_body(program: &[AstNode]) -> &[AstNode] {
+        program
+            .iter()
+            .find_map(|node| match node {
+                AstNode::WhileStmt { body, .. } | AstNode::ForStmt { body, .. } => Some(body.as_slice()),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_invariant_is_hoisted() {
+        let program = optimize_source(
+            "bind a to 2\nbind b to 3\nweave total as 0\nweave i as 0\nwhilst i less than a * 2 then\n set total to total + (a + b)\n set i to i + 1\nend\ntotal",
+        );
+        assert_eq!(bindings(&program), ["a", "b", "total", "i", "invariant.0", "invariant.1"]);
+        match &loop_body(&program)[0] {
+            AstNode::SetStmt { value, .. } => match value.as_ref() {
+                AstNode::BinaryOp { right, .. } => assert!(matches!(right.as_ref(), AstNode::Ident { name, .. } if name == "invariant.1")),
+                other => panic!("unexpected value {:?}", other),
+            },
+            other => panic!("unexpected statement {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_changed_variables_stay_in_the_loop() {
+        // `limit` is set in the loop, `scale` by a chant the loop calls
+        let program = optimize_source(
+            "weave scale as 1\nchant grow() then\n set scale to scale + 1\n scale\nend\nweave limit as 4\nweave i as 0\nweave total as 0\nwhilst i less than limit - 1 then\n set limit to limit - 1\n set total to total + grow() + scale * 2\n set i to i + 1\nend\ntotal",
+        );
+        assert!(!bindings(&program).iter().any(|name| name.starts_with("invariant")));
+    }
+
+    #[test]
+    fn test_only_safe_division_is_hoisted() {
+        let program = optimize_source(
+            "bind d to 0\nbind n to 8\nweave i as 0\nweave total as 0\nwhilst i less than 0 then\n set total to total + n / d + n / 2\n set i to i + 1\nend\ntotal",
+        );
+        assert_eq!(bindings(&program).iter().filter(|name| name.starts_with("invariant")).count(), 1);
+    }
+
+    #[test]
+    fn test_for_loop_body_is_hoisted() {
+        let program = optimize_source(
+            "bind k to 3\nweave total as 0\nfor each x in [1, 2, 3] then\n set total to total + x * (k + 1)\nend\ntotal",
+        );
+        assert!(bindings(&program).contains(&"invariant.0"));
+    }
+
+    #[test]
+    fn test_counter_products_become_strides() {
+        let program = optimize_source(
+            "bind k to 3\nweave total as 0\nweave i as 0\nwhilst i less than 5 then\n set total to total + i * 4 + k * i\n set i to i + 2\nend\ntotal",
+        );
+        // `k * i` steps by `2 * k`, computed once
+        assert_eq!(bindings(&program), ["k", "total", "i", "stride.0", "invariant.2", "stride.1"]);
+        let body = loop_body(&program);
+        assert_eq!(body.len(), 4);
+        match (&body[2], &body[3]) {
+            (AstNode::SetStmt { target: first, value, .. }, AstNode::SetStmt { target: second, .. }) => {
+                assert!(matches!(first.as_ref(), AstNode::Ident { name, .. } if name == "stride.0"));
+                assert!(matches!(second.as_ref(), AstNode::Ident { name, .. } if name == "stride.1"));
+                assert!(matches!(value.as_ref(), AstNode::BinaryOp { right, .. }
+                    if matches!(right.as_ref(), AstNode::Number { value, .. } if *value == 8.0)));
+            }
+            other => panic!("unexpected statements {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_irregular_counters_are_not_reduced() {
+        // A second update, or one inside a branch, breaks the stride
+        let twice = optimize_source(
+            "weave total as 0\nweave i as 0\nwhilst i less than 9 then\n set total to total + i * 4\n set i to i + 1\n set i to i + 2\nend\ntotal",
+        );
+        assert!(!bindings(&twice).iter().any(|name| name.starts_with("stride")));
+        let branch = optimize_source(
+            "weave total as 0\nweave i as 0\nwhilst i less than 9 then\n set total to total + i * 4\n should total less than 10 then\n set i to i + 1\n otherwise\n set i to i + 3\n end\nend\ntotal",
+        );
+        assert!(!bindings(&branch).iter().any(|name| name.starts_with("stride")));
+    }
+
+    #[test]
+    fn test_nested_loops_and_chants() {
+        let program = optimize_source(
+            "chant run(n, k) then\n weave total as 0\n weave i as 0\n whilst i less than n then\n  weave j as 0\n  whilst j less than n then\n   set total to total + j * k + (n + k)\n   set j to j + 1\n  end\n  set i to i + 1\n end\n total\nend\nrun(4, 5)",
+        );
+        assert_eq!(Evaluator::new().eval(&program).unwrap(), Value::Number(264.0));
+    }
+}
@@ -7,6 +7,7 @@
 //! - Go-to-definition
 //! - Autocomplete
 //! - Document symbols
+//! - Quick fixes from fix-it suggestions
 
 #[cfg(feature = "lsp")]
 use std::collections::HashMap;
@@ -22,11 +23,15 @@ use tower_lsp::lsp_types::*;
 #[cfg(feature = "lsp")]
 use tower_lsp::{Client, LanguageServer, LspService, Server};
 
+#[cfg(feature = "lsp")]
+use crate::fixit::{self, Suggestion};
 #[cfg(feature = "lsp")]
 use crate::lexer::Lexer;
 #[cfg(feature = "lsp")]
 use crate::parser::Parser;
 #[cfg(feature = "lsp")]
+use crate::runtime::BuiltinRegistry;
+#[cfg(feature = "lsp")]
 use crate::semantic::SemanticAnalyzer;
 #[cfg(feature = "lsp")]
 use crate::type_inference::TypeInference;
@@ -174,6 +179,28 @@ impl GlimmerWeaveBackend {
             .await;
     }
 
+    /// Quick fix applying `suggestion` to the document at `uri`
+    fn quick_fix(uri: &Url, suggestion: Suggestion) -> CodeActionOrCommand {
+        let position = |location: &crate::source_location::SourceLocation| Position {
+            line: location.line.saturating_sub(1) as u32,
+            character: location.column.saturating_sub(1) as u32,
+        };
+        let edit = TextEdit {
+            range: Range { start: position(&suggestion.start), end: position(&suggestion.end) },
+            new_text: suggestion.replacement.clone(),
+        };
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title: suggestion.message.clone(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            edit: Some(WorkspaceEdit {
+                changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                ..WorkspaceEdit::default()
+            }),
+            is_preferred: Some(suggestion.is_machine_applicable()),
+            ..CodeAction::default()
+        })
+    }
+
     /// Extract the word at a given position in the text
     fn get_word_at_position(&self, text: &str, line: usize, character: usize) -> Option<String> {
         let lines: Vec<&str> = text.lines().collect();
@@ -228,6 +255,7 @@ impl LanguageServer for GlimmerWeaveBackend {
                 }),
                 definition_provider: Some(OneOf::Left(true)),
                 document_symbol_provider: Some(OneOf::Left(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
                 workspace: Some(WorkspaceServerCapabilities {
                     workspace_folders: Some(WorkspaceFoldersServerCapabilities {
                         supported: Some(true),
@@ -353,10 +381,10 @@ impl LanguageServer for GlimmerWeaveBackend {
             "bind", "weave", "set", "chant", "yield", "should", "then", "otherwise",
             "end", "for", "each", "in", "whilst", "attempt", "harmonize", "match",
             "when", "form", "with", "as", "Triumph", "Mishap", "Present", "Absent",
-            "borrow", "mut", "request",
+            "borrow", "mut", "request", "swift", "ritual",
         ];
 
-        let items: Vec<CompletionItem> = keywords
+        let mut items: Vec<CompletionItem> = keywords
             .iter()
             .map(|keyword| CompletionItem {
                 label: keyword.to_string(),
@@ -366,6 +394,16 @@ impl LanguageServer for GlimmerWeaveBackend {
             })
             .collect();
 
+        // Builtins, described by their registry metadata
+        let registry = BuiltinRegistry::new();
+        items.extend(registry.completions("").into_iter().map(|builtin| CompletionItem {
+            label: builtin.name.clone(),
+            kind: Some(CompletionItemKind::FUNCTION),
+            detail: Some(builtin.usage()),
+            documentation: Some(Documentation::String(builtin.help())),
+            ..Default::default()
+        }));
+
         Ok(Some(CompletionResponse::Array(items)))
     }
 
@@ -422,6 +460,27 @@ impl LanguageServer for GlimmerWeaveBackend {
         }
     }
 
+    async fn code_action(&self, params: CodeActionParams) -> JsonRpcResult<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri;
+        let text = {
+            let documents = self.documents.read().await;
+            documents.get(&uri).map(|doc| doc.text.clone())
+        };
+        let text = match text {
+            Some(text) => text,
+            None => return Ok(None),
+        };
+
+        // Offer the fixes that start on the lines the editor asked about
+        let lines = params.range.start.line..=params.range.end.line;
+        let actions: Vec<CodeActionOrCommand> = fixit::suggestions(&text)
+            .into_iter()
+            .filter(|suggestion| lines.contains(&(suggestion.start.line.saturating_sub(1) as u32)))
+            .map(|suggestion| Self::quick_fix(&uri, suggestion))
+            .collect();
+        Ok(Some(actions))
+    }
+
     async fn document_symbol(
         &self,
         params: DocumentSymbolParams,
@@ -0,0 +1,295 @@
+//! # Module Cache
+//!
+//! Content-addressed cache of parsed modules and compiled bytecode, so
+//! system scripts that run at every boot skip re-parsing.
+//!
+//! Artifacts are keyed by the [`content_hash`] of their source, a SHA-256
+//! digest, so an edited script simply misses the cache and two sources never
+//! share an entry. The
+//! [`ModuleResolver`](crate::module_resolver::ModuleResolver) consults the
+//! store before parsing a module, and
+//! [`compile_cached`](crate::bytecode_compiler::compile_cached) before
+//! compiling a script.
+//!
+//! Stores are pluggable through [`CacheStore`]:
+//! - [`MemoryCacheStore`] keeps everything in memory and works without std.
+//! - [`DiskCacheStore`] (std only) also writes compiled chunks to a
+//!   directory as `.gwc` images, so they survive a restart. Parsed syntax
+//!   trees have no on-disk format and stay in memory. With a verifier
+//!   installed it only loads images whose detached signature the verifier
+//!   accepts.
+//!
+//! ```
+//! use glimmer_weave::module_cache::content_hash;
+//!
+//! assert_eq!(content_hash("weave x as 1"), content_hash("weave x as 1"));
+//! assert_ne!(content_hash("weave x as 1"), content_hash("weave x as 2"));
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use crate::ast::AstNode;
+use crate::bytecode::BytecodeChunk;
+
+/// Hash identifying a source text
+pub type ContentHash = [u8; 32];
+
+/// SHA-256 digest of a source text
+pub fn content_hash(source: &str) -> ContentHash {
+    sha256(source.as_bytes())
+}
+
+/// Lowercase hexadecimal form of a hash, as used in file names
+pub fn hash_hex(hash: &ContentHash) -> alloc::string::String {
+    hash.iter().map(|byte| alloc::format!("{:02x}", byte)).collect()
+}
+
+/// SHA-256 round constants
+const K: [u32; 64] = [
+    0x428a_2f98, 0x7137_4491, 0xb5c0_fbcf, 0xe9b5_dba5, 0x3956_c25b, 0x59f1_11f1, 0x923f_82a4, 0xab1c_5ed5,
+    0xd807_aa98, 0x1283_5b01, 0x2431_85be, 0x550c_7dc3, 0x72be_5d74, 0x80de_b1fe, 0x9bdc_06a7, 0xc19b_f174,
+    0xe49b_69c1, 0xefbe_4786, 0x0fc1_9dc6, 0x240c_a1cc, 0x2de9_2c6f, 0x4a74_84aa, 0x5cb0_a9dc, 0x76f9_88da,
+    0x983e_5152, 0xa831_c66d, 0xb003_27c8, 0xbf59_7fc7, 0xc6e0_0bf3, 0xd5a7_9147, 0x06ca_6351, 0x1429_2967,
+    0x27b7_0a85, 0x2e1b_2138, 0x4d2c_6dfc, 0x5338_0d13, 0x650a_7354, 0x766a_0abb, 0x81c2_c92e, 0x9272_2c85,
+    0xa2bf_e8a1, 0xa81a_664b, 0xc24b_8b70, 0xc76c_51a3, 0xd192_e819, 0xd699_0624, 0xf40e_3585, 0x106a_a070,
+    0x19a4_c116, 0x1e37_6c08, 0x2748_774c, 0x34b0_bcb5, 0x391c_0cb3, 0x4ed8_aa4a, 0x5b9c_ca4f, 0x682e_6ff3,
+    0x748f_82ee, 0x78a5_636f, 0x84c8_7814, 0x8cc7_0208, 0x90be_fffa, 0xa450_6ceb, 0xbef9_a3f7, 0xc671_78f2,
+];
+
+/// SHA-256 (FIPS 180-4) of `data`
+fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut state: [u32; 8] = [
+        0x6a09_e667, 0xbb67_ae85, 0x3c6e_f372, 0xa54f_f53a, 0x510e_527f, 0x9b05_688c, 0x1f83_d9ab, 0x5be0_cd19,
+    ];
+
+    // Pad with a 1 bit, zeros, and the bit length, to a multiple of 64 bytes
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (word, bytes) in w.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0; 32];
+    for (bytes, word) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+/// Storage for cached artifacts
+pub trait CacheStore: crate::sync::MaybeSend {
+    /// Parsed module for a source hash
+    fn module(&mut self, hash: ContentHash) -> Option<Vec<AstNode>>;
+
+    /// Remember the parsed module of a source
+    fn store_module(&mut self, hash: ContentHash, ast: &[AstNode]);
+
+    /// Compiled chunk for a source hash
+    fn chunk(&mut self, hash: ContentHash) -> Option<BytecodeChunk>;
+
+    /// Remember the compiled chunk of a source
+    fn store_chunk(&mut self, hash: ContentHash, chunk: &BytecodeChunk);
+}
+
+/// In-memory cache store
+#[derive(Debug, Clone, Default)]
+pub struct MemoryCacheStore {
+    modules: BTreeMap<ContentHash, Vec<AstNode>>,
+    chunks: BTreeMap<ContentHash, BytecodeChunk>,
+}
+
+impl MemoryCacheStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        MemoryCacheStore::default()
+    }
+
+    /// Number of cached modules and chunks
+    pub fn len(&self) -> usize {
+        self.modules.len() + self.chunks.len()
+    }
+
+    /// Whether nothing is cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every cached artifact
+    pub fn clear(&mut self) {
+        self.modules.clear();
+        self.chunks.clear();
+    }
+}
+
+impl CacheStore for MemoryCacheStore {
+    fn module(&mut self, hash: ContentHash) -> Option<Vec<AstNode>> {
+        self.modules.get(&hash).cloned()
+    }
+
+    fn store_module(&mut self, hash: ContentHash, ast: &[AstNode]) {
+        self.modules.insert(hash, ast.to_vec());
+    }
+
+    fn chunk(&mut self, hash: ContentHash) -> Option<BytecodeChunk> {
+        self.chunks.get(&hash).cloned()
+    }
+
+    fn store_chunk(&mut self, hash: ContentHash, chunk: &BytecodeChunk) {
+        self.chunks.insert(hash, chunk.clone());
+    }
+}
+
+/// Cache store that persists compiled chunks under a directory
+#[cfg(feature = "std")]
+pub struct DiskCacheStore {
+    dir: std::path::PathBuf,
+    memory: MemoryCacheStore,
+    /// Checks images read back from disk, when set
+    verifier: Option<Box<dyn crate::verify::Verifier>>,
+}
+
+#[cfg(feature = "std")]
+impl DiskCacheStore {
+    /// Use `dir` for cached images, creating it if needed
+    pub fn new(dir: impl Into<std::path::PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(DiskCacheStore { dir, memory: MemoryCacheStore::new(), verifier: None })
+    }
+
+    /// Only load images from disk that `verifier` accepts
+    ///
+    /// Each image must have a detached signature next to it
+    /// (`<hash>.gwc.sig`); unsigned or badly signed images are misses and
+    /// get recompiled. Chunks compiled by this process are still reused
+    /// from memory.
+    pub fn set_verifier(&mut self, verifier: Box<dyn crate::verify::Verifier>) {
+        self.verifier = Some(verifier);
+    }
+
+    /// Path of the image for a source hash
+    pub fn chunk_path(&self, hash: ContentHash) -> std::path::PathBuf {
+        self.dir.join(format!("{}.{}", hash_hex(&hash), crate::bytecode_image::EXTENSION))
+    }
+
+    /// Read and decode the image for a source hash, verifying it when a
+    /// verifier is installed
+    fn read_chunk(&self, hash: ContentHash) -> Option<BytecodeChunk> {
+        let path = self.chunk_path(hash);
+        let image = std::fs::read(&path).ok()?;
+        match self.verifier.as_deref() {
+            Some(verifier) => {
+                let mut signature_path = path.into_os_string();
+                signature_path.push(crate::verify::SIGNATURE_EXTENSION);
+                let signature = std::fs::read(signature_path).ok();
+                crate::bytecode_image::decode_verified(verifier, &image, signature.as_deref()).ok()
+            }
+            None => crate::bytecode_image::decode(&image).ok(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Debug for DiskCacheStore {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("DiskCacheStore")
+            .field("dir", &self.dir)
+            .field("memory", &self.memory)
+            .field("verified", &self.verifier.is_some())
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl CacheStore for DiskCacheStore {
+    fn module(&mut self, hash: ContentHash) -> Option<Vec<AstNode>> {
+        self.memory.module(hash)
+    }
+
+    fn store_module(&mut self, hash: ContentHash, ast: &[AstNode]) {
+        self.memory.store_module(hash, ast);
+    }
+
+    fn chunk(&mut self, hash: ContentHash) -> Option<BytecodeChunk> {
+        if let Some(chunk) = self.memory.chunk(hash) {
+            return Some(chunk);
+        }
+        // Unreadable, stale or unverified images are misses; the chunk is recompiled
+        let chunk = self.read_chunk(hash)?;
+        self.memory.store_chunk(hash, &chunk);
+        Some(chunk)
+    }
+
+    fn store_chunk(&mut self, hash: ContentHash, chunk: &BytecodeChunk) {
+        self.memory.store_chunk(hash, chunk);
+        // The cache is an optimisation: failing to persist only costs a recompile
+        let _ = std::fs::write(self.chunk_path(hash), crate::bytecode_image::encode(chunk));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash_is_sha256() {
+        assert_eq!(hash_hex(&content_hash("")), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(hash_hex(&content_hash("abc")), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        // Two blocks once padded
+        assert_eq!(
+            hash_hex(&content_hash("abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_memory_store() {
+        let mut store = MemoryCacheStore::new();
+        let hash = content_hash("main");
+        assert!(store.chunk(hash).is_none());
+
+        store.store_chunk(hash, &BytecodeChunk::new("main".to_string()));
+        store.store_module(hash, &[]);
+        assert_eq!(store.chunk(hash).map(|chunk| chunk.name), Some("main".to_string()));
+        assert_eq!(store.module(hash), Some(Vec::new()));
+        assert_eq!(store.len(), 2);
+
+        store.clear();
+        assert!(store.is_empty());
+    }
+}
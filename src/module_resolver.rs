@@ -11,16 +11,34 @@
 //! - Building a dependency graph
 //! - Detecting circular dependencies
 //! - Caching loaded modules
+//! - Providing the prelude every loaded module sees
 //!
 //! ## Path Resolution Order
 //!
 //! 1. **Relative paths**: `./math.gw` or `../lib/utils.gw` - resolved relative to importing file
 //! 2. **Absolute paths**: `std/math.gw` - resolved from project root
 //! 3. **Standard library**: `std/math.gw` - resolved from standard library directory
+//!
+//! ## Caching
+//!
+//! With a cache store installed (`set_cache`), parsed modules are looked up
+//! by the content hash of their source before parsing, so unchanged modules
+//! are parsed once per store.
+//!
+//! ## Signed Modules
+//!
+//! With a verifier installed (`set_verifier`), every module source must come
+//! with a detached signature, registered with `add_signature` or read from
+//! `<path>.sig` on disk, and the verifier must accept it before the module
+//! is parsed.
 
 use crate::ast::AstNode;
 use crate::lexer::Lexer;
 use crate::parser::Parser;
+use crate::script_prelude::Prelude;
+use crate::verify::{Verifier, VerifyError};
+use crate::module_cache::{content_hash, CacheStore};
+use alloc::boxed::Box;
 use alloc::collections::BTreeMap;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
@@ -55,6 +73,12 @@ pub enum ResolverError {
         message: String,
     },
 
+    /// Module signature missing or rejected by the verifier
+    Verification {
+        path: String,
+        error: VerifyError,
+    },
+
     /// Invalid module path
     InvalidPath {
         path: String,
@@ -97,6 +121,21 @@ pub struct ModuleResolver {
 
     /// Currently being loaded (for cycle detection)
     loading_stack: Vec<String>,
+
+    /// In-memory sources registered with `add_source` (path -> source)
+    sources: BTreeMap<String, String>,
+
+    /// Builtins in scope in every module loaded through this resolver
+    prelude: Prelude,
+
+    /// Checks module signatures; `None` loads unsigned modules
+    verifier: Option<Box<dyn Verifier>>,
+
+    /// Detached signatures registered with `add_signature` (path -> signature)
+    signatures: BTreeMap<String, Vec<u8>>,
+
+    /// Parsed modules by source hash; `None` parses every load
+    cache: Option<Box<dyn CacheStore>>,
 }
 
 impl ModuleResolver {
@@ -112,9 +151,66 @@ impl ModuleResolver {
             module_cache: BTreeMap::new(),
             dependency_graph: BTreeMap::new(),
             loading_stack: Vec::new(),
+            sources: BTreeMap::new(),
+            prelude: Prelude::default(),
+            verifier: None,
+            signatures: BTreeMap::new(),
+            cache: None,
         }
     }
 
+    /// Directory non-relative imports are resolved from
+    pub fn project_root(&self) -> &str {
+        &self.project_root
+    }
+
+    /// Set the prelude injected into every compilation unit
+    ///
+    /// Use `Prelude::none()` for minimal kernel scripts that should start
+    /// from an empty global scope.
+    pub fn set_prelude(&mut self, prelude: Prelude) {
+        self.prelude = prelude;
+    }
+
+    /// The prelude injected into every compilation unit
+    pub fn prelude(&self) -> &Prelude {
+        &self.prelude
+    }
+
+    /// Register in-memory source for a module path
+    ///
+    /// Registered sources take precedence over the filesystem, which makes it
+    /// possible to load modules without `std` or from generated code.
+    ///
+    /// # Arguments
+    /// * `path` - Canonical path the module resolves to
+    /// * `source` - Glimmer-Weave source of the module
+    pub fn add_source(&mut self, path: impl Into<String>, source: impl Into<String>) {
+        self.sources.insert(path.into(), source.into());
+    }
+
+    /// Require every module to be signed and accepted by `verifier`
+    pub fn set_verifier(&mut self, verifier: Box<dyn Verifier>) {
+        self.verifier = Some(verifier);
+    }
+
+    /// Register the detached signature of a module
+    ///
+    /// Registered signatures take precedence over `.sig` files on disk.
+    pub fn add_signature(&mut self, path: impl Into<String>, signature: impl Into<Vec<u8>>) {
+        self.signatures.insert(path.into(), signature.into());
+    }
+
+    /// Look up parsed modules in `cache` before parsing them
+    pub fn set_cache(&mut self, cache: Box<dyn CacheStore>) {
+        self.cache = Some(cache);
+    }
+
+    /// The installed cache store, e.g. to share it with `compile_cached`
+    pub fn cache_mut(&mut self) -> Option<&mut (dyn CacheStore + 'static)> {
+        self.cache.as_deref_mut()
+    }
+
     /// Resolve an import path to a canonical file path
     ///
     /// Resolution order:
@@ -194,33 +290,123 @@ impl ModuleResolver {
         }
 
         // Check for circular dependency
-        if self.loading_stack.contains(&path.to_string()) {
-            // Build cycle path
-            let mut cycle = Vec::new();
-            let mut found = false;
-            for p in &self.loading_stack {
-                if found || p == path {
-                    cycle.push(p.clone());
-                    found = true;
-                }
-            }
-            cycle.push(path.to_string());
+        if self.loading_stack.iter().any(|p| p == path) {
+            return Err(self.cycle_error(path));
+        }
 
-            return Err(ResolverError::CircularDependency { cycle });
+        let source = self.read_source(path)?;
+        self.verify_source(path, &source)?;
+
+        let ast = self.parse_source(path, &source)?;
+
+        // Extract module information
+        let info = Self::extract_module_info(path, &ast)?;
+
+        // Add dependencies to graph
+        self.dependency_graph.insert(path.to_string(), info.dependencies.clone());
+
+        // Cache the module
+        self.module_cache.insert(path.to_string(), info);
+
+        Ok(&self.module_cache[path])
+    }
+
+    /// Load a module and everything it imports, transitively
+    ///
+    /// Dependencies are resolved relative to the module importing them, and
+    /// the dependency graph records the resolved paths.
+    ///
+    /// # Returns
+    /// Paths of all loaded modules in dependency order: every module comes
+    /// after the modules it imports, and `entry_path` comes last.
+    pub fn load_graph(&mut self, entry_path: &str) -> ResolverResult<Vec<String>> {
+        let mut order = Vec::new();
+        self.loading_stack.clear();
+        self.load_graph_from(entry_path, &mut order)?;
+        Ok(order)
+    }
+
+    /// Depth-first visit for `load_graph`
+    fn load_graph_from(&mut self, path: &str, order: &mut Vec<String>) -> ResolverResult<()> {
+        if self.loading_stack.iter().any(|p| p == path) {
+            return Err(self.cycle_error(path));
+        }
+        if order.iter().any(|p| p == path) {
+            return Ok(());
         }
 
-        // Add to loading stack
+        let imports = self.load_module(path)?.dependencies.clone();
+        let mut dependencies = Vec::new();
+        for import in &imports {
+            let resolved = self.resolve_path(import, Some(path))?;
+            if !dependencies.contains(&resolved) {
+                dependencies.push(resolved);
+            }
+        }
+        self.dependency_graph.insert(path.to_string(), dependencies.clone());
+
         self.loading_stack.push(path.to_string());
+        for dependency in &dependencies {
+            self.load_graph_from(dependency, order)?;
+        }
+        self.loading_stack.pop();
+
+        order.push(path.to_string());
+        Ok(())
+    }
 
-        // In real implementation, would read file here
-        // For Phase 2, we'll use a placeholder
-        // let source = std::fs::read_to_string(path).map_err(|e| ...)?;
+    /// Build the cycle reported when `path` is reached again while loading
+    fn cycle_error(&self, path: &str) -> ResolverError {
+        let start = self.loading_stack.iter().position(|p| p == path).unwrap_or(0);
+        let mut cycle = self.loading_stack[start..].to_vec();
+        cycle.push(path.to_string());
+        ResolverError::CircularDependency { cycle }
+    }
 
-        // For now, create a placeholder for testing
-        let source = format!("grove {} with end", Self::module_name_from_path(path));
+    /// Source of a module, as `load_module` reads it
+    pub fn module_source(&self, path: &str) -> ResolverResult<String> {
+        self.read_source(path)
+    }
+
+    /// Read the source of a module
+    ///
+    /// Sources registered with `add_source` are used first; with the `std`
+    /// feature the file is read from disk otherwise.
+    fn read_source(&self, path: &str) -> ResolverResult<String> {
+        if let Some(source) = self.sources.get(path) {
+            return Ok(source.clone());
+        }
+
+        #[cfg(feature = "std")]
+        {
+            match std::fs::read_to_string(path) {
+                Ok(source) => Ok(source),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(ResolverError::ModuleNotFound {
+                    path: path.to_string(),
+                    searched_paths: vec![path.to_string()],
+                }),
+                Err(e) => Err(ResolverError::IoError {
+                    path: path.to_string(),
+                    message: e.to_string(),
+                }),
+            }
+        }
 
-        // Parse the source
-        let mut lexer = Lexer::new(&source);
+        #[cfg(not(feature = "std"))]
+        Err(ResolverError::ModuleNotFound {
+            path: path.to_string(),
+            searched_paths: vec![path.to_string()],
+        })
+    }
+
+    /// Parse a module's source, going through the cache store if there is one
+    fn parse_source(&mut self, path: &str, source: &str) -> ResolverResult<Vec<AstNode>> {
+        let hash = content_hash(source);
+        if let Some(ast) = self.cache.as_mut().and_then(|cache| cache.module(hash)) {
+            return Ok(ast);
+        }
+
+        let mut lexer = Lexer::new(source);
         let tokens = lexer.tokenize_positioned();
         let mut parser = Parser::new(tokens);
 
@@ -229,19 +415,35 @@ impl ModuleResolver {
             error: format!("{:?}", e),
         })?;
 
-        // Extract module information
-        let info = Self::extract_module_info(path, &ast)?;
+        if let Some(cache) = self.cache.as_mut() {
+            cache.store_module(hash, &ast);
+        }
+        Ok(ast)
+    }
 
-        // Remove from loading stack
-        self.loading_stack.pop();
+    /// Check the module's signature when a verifier is installed
+    fn verify_source(&self, path: &str, source: &str) -> ResolverResult<()> {
+        let Some(verifier) = self.verifier.as_deref() else {
+            return Ok(());
+        };
+        let signature = self.read_signature(path);
+        crate::verify::verify_image(verifier, source.as_bytes(), signature.as_deref())
+            .map_err(|error| ResolverError::Verification { path: path.to_string(), error })
+    }
 
-        // Add dependencies to graph
-        self.dependency_graph.insert(path.to_string(), info.dependencies.clone());
+    /// Read the detached signature of a module, if it has one
+    fn read_signature(&self, path: &str) -> Option<Vec<u8>> {
+        if let Some(signature) = self.signatures.get(path) {
+            return Some(signature.clone());
+        }
 
-        // Cache the module
-        self.module_cache.insert(path.to_string(), info);
+        #[cfg(feature = "std")]
+        {
+            std::fs::read(format!("{}{}", path, crate::verify::SIGNATURE_EXTENSION)).ok()
+        }
 
-        Ok(&self.module_cache[path])
+        #[cfg(not(feature = "std"))]
+        None
     }
 
     /// Extract module name from file path
@@ -276,9 +478,16 @@ impl ModuleResolver {
         for node in ast {
             match node {
                 // Extract module name from grove declaration
-                AstNode::ModuleDecl { name: module_name, body: _, exports: module_exports, .. } => {
+                AstNode::ModuleDecl { name: module_name, body, exports: module_exports, .. } => {
                     name = module_name.clone();
                     exports.extend(module_exports.clone());
+
+                    // Imports can also appear inside the grove body
+                    for stmt in body {
+                        if let AstNode::Import { path: import_path, .. } = stmt {
+                            dependencies.push(import_path.clone());
+                        }
+                    }
                 }
 
                 // Extract dependencies from imports
@@ -461,4 +670,120 @@ mod tests {
         let result = resolver.check_circular_dependencies();
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_load_registered_source() {
+        let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+        resolver.add_source("/project/math.gw", "grove Math with\n    bind pi to 3\n    offer pi\nend");
+
+        let info = resolver.load_module("/project/math.gw").unwrap();
+        assert_eq!(info.name, "Math");
+        assert_eq!(info.exports, vec!["pi".to_string()]);
+    }
+
+    #[test]
+    fn test_load_missing_module() {
+        let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+        let result = resolver.load_module("/project/does-not-exist.gw");
+        assert!(matches!(result, Err(ResolverError::ModuleNotFound { .. })));
+    }
+
+    #[test]
+    fn test_load_graph_orders_dependencies_first() {
+        let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+        resolver.add_source("/project/main.gw", "summon A from \"a.gw\"\nsummon B from \"b.gw\"");
+        resolver.add_source("/project/a.gw", "summon B from \"b.gw\"\ngrove A with end");
+        resolver.add_source("/project/b.gw", "grove B with end");
+
+        let order = resolver.load_graph("/project/main.gw").unwrap();
+        assert_eq!(order, vec!["/project/b.gw", "/project/a.gw", "/project/main.gw"]);
+        assert!(resolver.check_circular_dependencies().is_ok());
+    }
+
+    #[test]
+    fn test_load_graph_detects_cycle() {
+        let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+        resolver.add_source("/project/a.gw", "summon B from \"b.gw\"");
+        resolver.add_source("/project/b.gw", "summon A from \"a.gw\"");
+
+        match resolver.load_graph("/project/a.gw") {
+            Err(ResolverError::CircularDependency { cycle }) => {
+                assert_eq!(cycle, vec!["/project/a.gw", "/project/b.gw", "/project/a.gw"]);
+            }
+            other => panic!("Expected CircularDependency error, got {:?}", other),
+        }
+    }
+
+    /// Toy verifier: a signature is `<key>:<byte sum of the image>`, and
+    /// only the `kernel` key is trusted
+    fn toy_verifier(image: &[u8], signature: &[u8]) -> Result<(), VerifyError> {
+        let signature = core::str::from_utf8(signature).unwrap_or("");
+        let (key, digest) = signature.split_once(':').unwrap_or(("", signature));
+        if key != "kernel" {
+            return Err(VerifyError::UntrustedKey { key: key.to_string() });
+        }
+        let sum: u64 = image.iter().map(|&b| b as u64).sum();
+        if digest != sum.to_string() {
+            return Err(VerifyError::InvalidSignature { reason: "digest mismatch".to_string() });
+        }
+        Ok(())
+    }
+
+    fn toy_signature(key: &str, source: &str) -> Vec<u8> {
+        let sum: u64 = source.bytes().map(|b| b as u64).sum();
+        format!("{}:{}", key, sum).into_bytes()
+    }
+
+    #[test]
+    fn test_verified_module_loads() {
+        let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+        resolver.set_verifier(Box::new(toy_verifier));
+        resolver.add_source("/project/a.gw", "weave x as 1");
+        resolver.add_signature("/project/a.gw", toy_signature("kernel", "weave x as 1"));
+
+        assert!(resolver.load_module("/project/a.gw").is_ok());
+    }
+
+    #[test]
+    fn test_verification_failures() {
+        let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+        resolver.set_verifier(Box::new(toy_verifier));
+        resolver.add_source("/project/unsigned.gw", "weave x as 1");
+        resolver.add_source("/project/tampered.gw", "weave x as 2");
+        resolver.add_signature("/project/tampered.gw", toy_signature("kernel", "weave x as 1"));
+        resolver.add_source("/project/foreign.gw", "weave x as 1");
+        resolver.add_signature("/project/foreign.gw", toy_signature("stranger", "weave x as 1"));
+
+        let error = |resolver: &mut ModuleResolver, path: &str| match resolver.load_module(path) {
+            Err(ResolverError::Verification { error, .. }) => error,
+            other => panic!("Expected a verification error, got {:?}", other),
+        };
+        assert_eq!(error(&mut resolver, "/project/unsigned.gw"), VerifyError::MissingSignature);
+        assert!(matches!(error(&mut resolver, "/project/tampered.gw"), VerifyError::InvalidSignature { .. }));
+        assert_eq!(error(&mut resolver, "/project/foreign.gw"), VerifyError::UntrustedKey { key: "stranger".to_string() });
+        assert!(resolver.get_module("/project/tampered.gw").is_none());
+    }
+
+    #[test]
+    fn test_unsigned_modules_load_without_a_verifier() {
+        let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+        resolver.add_source("/project/a.gw", "weave x as 1");
+        assert!(resolver.load_module("/project/a.gw").is_ok());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_signature_file_on_disk() {
+        let dir = std::env::temp_dir().join(format!("glimmer-weave-verify-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("signed.gw").to_string_lossy().into_owned();
+        std::fs::write(&path, "weave y as 2").unwrap();
+        std::fs::write(format!("{}.sig", path), toy_signature("kernel", "weave y as 2")).unwrap();
+
+        let mut resolver = ModuleResolver::new(dir.to_string_lossy().into_owned(), "/std".to_string());
+        resolver.set_verifier(Box::new(toy_verifier));
+        let loaded = resolver.load_module(&path).map(|_| ());
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(loaded, Ok(()));
+    }
 }
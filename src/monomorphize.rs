@@ -161,7 +161,7 @@ impl Monomorphizer {
                 self.find_instantiations_in_node(right);
             }
 
-            AstNode::UnaryOp { operand, .. } => {
+            AstNode::UnaryOp { operand, .. } | AstNode::InUnit { value: operand, .. } => {
                 self.find_instantiations_in_node(operand);
             }
 
@@ -196,7 +196,7 @@ impl Monomorphizer {
                 self.find_instantiations_in_node(expr);
             }
 
-            AstNode::List { elements, .. } => {
+            AstNode::List { elements, .. } | AstNode::Tuple { elements, .. } => {
                 for elem in elements {
                     self.find_instantiations_in_node(elem);
                 }
@@ -245,6 +245,9 @@ fn monomorphize_type_annotation_to_string(ann: &TypeAnnotation) -> String {
             format!("{}_{}", name, args.join("_"))
         }
         TypeAnnotation::Map => "Map".to_string(),
+        TypeAnnotation::Array { element, length } => {
+            format!("Array_{}_{}", monomorphize_type_annotation_to_string(element), length)
+        }
         TypeAnnotation::Function { .. } => "Function".to_string(),
         TypeAnnotation::Optional(inner) => {
             format!("Optional_{}", monomorphize_type_annotation_to_string(inner))
@@ -278,20 +281,21 @@ impl Monomorphizer {
                     &instantiation.type_args,
                     specialized_name,
                 );
-                specialized.push(specialized_func);
+                specialized.extend(specialized_func);
             }
         }
 
         specialized
     }
 
-    /// Specialize a generic function for specific type arguments
+    /// Specialize a generic function for specific type arguments, or `None`
+    /// if `generic_def` is not a chant
     fn specialize_function(
         &self,
         generic_def: &AstNode,
         type_args: &[String],
         specialized_name: &str,
-    ) -> AstNode {
+    ) -> Option<AstNode> {
         if let AstNode::ChantDef {
             name: _,
             type_params,
@@ -299,6 +303,8 @@ impl Monomorphizer {
             params,
             return_type,
             body,
+            swift,
+            deprecated,
             span,
         } = generic_def
         {
@@ -326,17 +332,19 @@ impl Monomorphizer {
                 .map(|t| self.substitute_type_annotation(t, &substitutions));
 
             // Create specialized function (no type parameters)
-            AstNode::ChantDef {
+            Some(AstNode::ChantDef {
                 name: specialized_name.to_string(),
                 type_params: vec![], // No type parameters in specialized version
                 lifetime_params: lifetime_params.clone(),
                 params: specialized_params,
                 return_type: specialized_return,
                 body: body.clone(), // Body doesn't need type substitution
+                swift: *swift,
+                deprecated: deprecated.clone(),
                 span: span.clone(),
-            }
+            })
         } else {
-            panic!("Expected ChantDef");
+            None
         }
     }
 
@@ -367,6 +375,12 @@ fn substitute_type_annotation_helper(
         TypeAnnotation::List(inner) => {
             TypeAnnotation::List(Box::new(substitute_type_annotation_helper(inner, substitutions)))
         }
+        TypeAnnotation::Array { element, length } => {
+            TypeAnnotation::Array {
+                element: Box::new(substitute_type_annotation_helper(element, substitutions)),
+                length: *length,
+            }
+        }
         TypeAnnotation::Parametrized { name, type_args } => {
             TypeAnnotation::Parametrized {
                 name: name.clone(),
@@ -405,7 +419,7 @@ impl Monomorphizer {
     /// Transform a node, replacing generic calls with calls to specialized versions
     fn transform_node(&self, node: &AstNode) -> AstNode {
         match node {
-            AstNode::Call { callee, type_args, args, span } => {
+            AstNode::Call { callee, type_args, args, arg_names, span } => {
                 // Check if this is a call to a generic function
                 if !type_args.is_empty() {
                     if let AstNode::Ident { name: func_name, .. } = &**callee {
@@ -425,9 +439,11 @@ impl Monomorphizer {
                                 callee: Box::new(AstNode::Ident {
                                     name: specialized_name.clone(),
                                     span: span.clone(),
+                                    slot: None,
                                 }),
                                 type_args: vec![], // No type args in specialized call
                                 args: args.iter().map(|arg| self.transform_node(arg)).collect(),
+                                arg_names: arg_names.clone(),
                                 span: span.clone(),
                             };
                         }
@@ -439,6 +455,7 @@ impl Monomorphizer {
                     callee: Box::new(self.transform_node(callee)),
                     type_args: type_args.clone(),
                     args: args.iter().map(|arg| self.transform_node(arg)).collect(),
+                    arg_names: arg_names.clone(),
                     span: span.clone(),
                 }
             }
@@ -457,6 +474,12 @@ impl Monomorphizer {
                 span: span.clone(),
             },
 
+            AstNode::InUnit { value, unit, span } => AstNode::InUnit {
+                value: Box::new(self.transform_node(value)),
+                unit: unit.clone(),
+                span: span.clone(),
+            },
+
             AstNode::BindStmt { name, typ, value, span } => AstNode::BindStmt {
                 name: name.clone(),
                 typ: typ.clone(),
@@ -492,6 +515,11 @@ impl Monomorphizer {
                 span: span.clone(),
             },
 
+            AstNode::Tuple { elements, span } => AstNode::Tuple {
+                elements: elements.iter().map(|elem| self.transform_node(elem)).collect(),
+                span: span.clone(),
+            },
+
             AstNode::Block { statements, span } => AstNode::Block {
                 statements: statements.iter().map(|stmt| self.transform_node(stmt)).collect(),
                 span: span.clone(),
@@ -559,9 +587,12 @@ mod tests {
                     value: Box::new(AstNode::Ident {
                         name: "x".to_string(),
                         span: dummy_span.clone(),
+                        slot: None,
                     }),
                     span: dummy_span.clone(),
                 }],
+                swift: false,
+                deprecated: None,
                 span: dummy_span.clone(),
             },
             AstNode::ExprStmt {
@@ -569,8 +600,10 @@ mod tests {
                     callee: Box::new(AstNode::Ident {
                         name: "identity".to_string(),
                         span: dummy_span.clone(),
+                        slot: None,
                     }),
                     type_args: vec![TypeAnnotation::Named("Number".to_string())],
+                    arg_names: vec![],
                     args: vec![AstNode::Number {
                         value: 42.0,
                         span: dummy_span.clone(),
@@ -0,0 +1,257 @@
+//! # Mutation Testing
+//!
+//! Judges how well a script's `verify` blocks check it.
+//!
+//! The tester makes many small changes to the script, one at a time: it
+//! swaps a binary operator for a near neighbour (`+` for `-`, `<` for `<=`,
+//! `and` for `or`), negates the condition of a `should` or `whilst`, or
+//! drops an expression or `set` statement. Each changed script, a mutant,
+//! reruns the verify blocks. A mutant some block catches is killed; one
+//! every block still passes survives, pointing at code the tests do not
+//! really check. Verify blocks themselves are never mutated.
+//!
+//! Mutants run in fresh evaluators under a budget of safepoints, so one
+//! that loops forever is stopped and counts as killed.
+//!
+//! ```
+//! use glimmer_weave::mutation::MutationTester;
+//! use glimmer_weave::{Lexer, Parser};
+//!
+//! let source = "chant add(a, b) then\n    a + b\nend\n\nverify \"adds\" then\n    add(2, 0) is 2\nend";
+//! let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap();
+//!
+//! // `a - b` still turns (2, 0) into 2
+//! let report = MutationTester::new().run(&ast).unwrap();
+//! assert_eq!(report.survived.len(), 1);
+//! assert_eq!(report.survived[0].to_string(), "swap + for - at line 2:7");
+//! ```
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::ast::{AstNode, BinaryOperator, UnaryOperator};
+use crate::clock::StepClock;
+use crate::eval::Evaluator;
+use crate::source_location::SourceSpan;
+use crate::test_runner::{block_failure, verify_programs};
+
+/// Safepoints each verify block may pass before it counts as hung
+pub const DEFAULT_STEP_BUDGET: u64 = 100_000;
+
+/// A change the tester made to a script
+#[derive(Debug, Clone, PartialEq)]
+pub enum MutationKind {
+    /// A binary operator replaced by another
+    SwapOperator { from: BinaryOperator, to: BinaryOperator },
+    /// A `should` or `whilst` condition wrapped in `not`
+    NegateCondition,
+    /// An expression or `set` statement replaced by `nothing`
+    DropStatement,
+}
+
+/// One mutation and where in the script it was made
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mutant {
+    pub kind: MutationKind,
+    pub span: SourceSpan,
+}
+
+impl fmt::Display for Mutant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            MutationKind::SwapOperator { from, to } => {
+                write!(f, "swap {} for {}", operator_symbol(*from), operator_symbol(*to))?
+            }
+            MutationKind::NegateCondition => write!(f, "negate condition")?,
+            MutationKind::DropStatement => write!(f, "drop statement")?,
+        }
+        write!(f, " at {}", self.span.start)
+    }
+}
+
+/// Mutants sorted by whether the verify blocks caught them
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MutationReport {
+    /// Mutants some verify block failed on, or that ran out of budget
+    pub killed: Vec<Mutant>,
+    /// Mutants every verify block still passed on
+    pub survived: Vec<Mutant>,
+}
+
+impl MutationReport {
+    /// Fraction of mutants killed, from 0 to 1; 1 when there were none
+    pub fn score(&self) -> f64 {
+        let total = self.killed.len() + self.survived.len();
+        if total == 0 {
+            1.0
+        } else {
+            self.killed.len() as f64 / total as f64
+        }
+    }
+}
+
+/// Why a script could not be mutation tested
+#[derive(Debug, Clone, PartialEq)]
+pub enum MutationError {
+    /// The script has no verify blocks to judge mutants with
+    NoVerifyBlocks,
+    /// A verify block fails on the unmutated script
+    BaselineFails { block: String, failure: String },
+}
+
+/// Runs a script's verify blocks against its mutants
+#[derive(Debug, Clone)]
+pub struct MutationTester {
+    step_budget: u64,
+}
+
+impl Default for MutationTester {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MutationTester {
+    /// Create a tester with the default step budget
+    pub fn new() -> Self {
+        MutationTester { step_budget: DEFAULT_STEP_BUDGET }
+    }
+
+    /// Allow each verify block `budget` safepoints before it counts as hung
+    pub fn step_budget(mut self, budget: u64) -> Self {
+        self.step_budget = budget;
+        self
+    }
+
+    /// Mutate `ast` every way the tester knows and rerun its verify blocks
+    /// against each mutant
+    pub fn run(&self, ast: &[AstNode]) -> Result<MutationReport, MutationError> {
+        let programs = verify_programs(ast);
+        if programs.is_empty() {
+            return Err(MutationError::NoVerifyBlocks);
+        }
+        for (block, program) in &programs {
+            if let Some(failure) = self.failure(program) {
+                return Err(MutationError::BaselineFails { block: block.clone(), failure });
+            }
+        }
+
+        let mut report = MutationReport::default();
+        for (mutant, mutated) in mutants(ast) {
+            let killed = verify_programs(&mutated)
+                .iter()
+                .any(|(_, program)| self.failure(program).is_some());
+            if killed {
+                report.killed.push(mutant);
+            } else {
+                report.survived.push(mutant);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Why one verify program fails, in a fresh evaluator under the budget
+    fn failure(&self, program: &[AstNode]) -> Option<String> {
+        let mut evaluator = Evaluator::new();
+        evaluator.set_clock(Box::new(StepClock::new()));
+        evaluator.set_output_sink(Box::new(|_: &str| {}));
+        block_failure(evaluator.eval_with_deadline(program, self.step_budget))
+    }
+}
+
+/// Every mutant of `ast`, each with the mutated script, in source order
+pub fn mutants(ast: &[AstNode]) -> Vec<(Mutant, Vec<AstNode>)> {
+    let mut mutants = Vec::new();
+    loop {
+        let mut mutated = ast.to_vec();
+        let mut seen = 0;
+        let mutant = mutated.iter_mut().find_map(|node| mutate(node, mutants.len(), &mut seen));
+        match mutant {
+            Some(mutant) => mutants.push((mutant, mutated)),
+            None => return mutants,
+        }
+    }
+}
+
+/// Apply the `target`th mutation found under `node`, counting the
+/// mutation sites passed in `seen`
+fn mutate(node: &mut AstNode, target: usize, seen: &mut usize) -> Option<Mutant> {
+    let kind = match node {
+        AstNode::VerifyBlock { .. } => return None,
+        AstNode::BinaryOp { op, .. } => Some(MutationKind::SwapOperator { from: *op, to: swapped(*op) }),
+        AstNode::IfStmt { .. } | AstNode::WhileStmt { .. } => Some(MutationKind::NegateCondition),
+        AstNode::ExprStmt { .. } | AstNode::SetStmt { .. } => Some(MutationKind::DropStatement),
+        _ => None,
+    };
+
+    if let Some(kind) = kind {
+        if *seen == target {
+            return Some(apply(node, kind));
+        }
+        *seen += 1;
+    }
+    node.children_mut().into_iter().find_map(|child| mutate(child, target, seen))
+}
+
+fn apply(node: &mut AstNode, kind: MutationKind) -> Mutant {
+    let span = match node {
+        AstNode::BinaryOp { op, span, .. } => {
+            if let MutationKind::SwapOperator { to, .. } = &kind {
+                *op = *to;
+            }
+            span.clone()
+        }
+        AstNode::IfStmt { condition, span, .. } | AstNode::WhileStmt { condition, span, .. } => {
+            let original = core::mem::replace(condition.as_mut(), AstNode::Nothing { span: span.clone() });
+            **condition = AstNode::UnaryOp { op: UnaryOperator::Not, operand: Box::new(original), span: span.clone() };
+            span.clone()
+        }
+        AstNode::ExprStmt { span, .. } | AstNode::SetStmt { span, .. } => {
+            let span = span.clone();
+            *node = AstNode::Nothing { span: span.clone() };
+            span
+        }
+        _ => SourceSpan::unknown(),
+    };
+    Mutant { kind, span }
+}
+
+/// The operator a swap mutation replaces `op` with
+fn swapped(op: BinaryOperator) -> BinaryOperator {
+    use BinaryOperator::*;
+    match op {
+        Add => Sub,
+        Sub => Add,
+        Mul => Div,
+        Div => Mul,
+        Mod => Mul,
+        Equal => NotEqual,
+        NotEqual => Equal,
+        Greater => GreaterEq,
+        GreaterEq => Greater,
+        Less => LessEq,
+        LessEq => Less,
+        And => Or,
+        Or => And,
+    }
+}
+
+fn operator_symbol(op: BinaryOperator) -> &'static str {
+    match op {
+        BinaryOperator::Add => "+",
+        BinaryOperator::Sub => "-",
+        BinaryOperator::Mul => "*",
+        BinaryOperator::Div => "/",
+        BinaryOperator::Mod => "%",
+        BinaryOperator::Equal => "is",
+        BinaryOperator::NotEqual => "is not",
+        BinaryOperator::Greater => ">",
+        BinaryOperator::Less => "<",
+        BinaryOperator::GreaterEq => ">=",
+        BinaryOperator::LessEq => "<=",
+        BinaryOperator::And => "and",
+        BinaryOperator::Or => "or",
+    }
+}
@@ -0,0 +1,337 @@
+//! FFI bindings to the native heap allocator (gl_malloc/gl_free)
+//!
+//! This module is only available on x86_64 platforms with GNU assembler support.
+//! The allocator is implemented in `src/native_allocator.S` and linked via build.rs.
+//!
+//! ## Embedded configuration
+//!
+//! By default the allocator maps its heap with `mmap` and grows on demand.
+//! Hosts without an OS underneath (AethelOS early boot, firmware) instead hand
+//! it a fixed region with [`gl_init_allocator_with`] before the first
+//! allocation, and may add more with [`gl_add_region`]. A fixed heap never
+//! grows by itself: when it runs out, the handler installed with
+//! [`gl_set_oom_handler`] gets a chance to make room before `gl_malloc`
+//! returns NULL.
+//!
+//! ## Sharing the heap with Rust
+//!
+//! [`GlAllocator`] implements [`GlobalAlloc`] on top of `gl_malloc`/`gl_free`,
+//! so a no_std host can route its own Rust allocations through the same heap
+//! as compiled scripts:
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static HEAP: GlAllocator = GlAllocator::new();
+//! ```
+//!
+//! Memory the host wants kept apart from the shared heap (DMA buffers,
+//! per-phase scratch space) goes into named [`Arena`]s, collected in
+//! [`Arenas`].
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::alloc::{GlobalAlloc, Layout};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Out-of-memory callback: receives the requested size, returns `true` to
+/// retry the allocation after making room
+pub type OomHandler = extern "C" fn(size: usize) -> bool;
+
+extern "C" {
+    /// Initialize the allocator
+    ///
+    /// This is called automatically on first gl_malloc, but can be called explicitly.
+    pub fn gl_init_allocator();
+
+    /// Initialize the allocator over a host-supplied region instead of mmap
+    ///
+    /// `base` must be 8-byte aligned and the region at least 32 bytes. Fails
+    /// (returns false) if the allocator is already initialized. The heap then
+    /// only grows through [`gl_add_region`].
+    pub fn gl_init_allocator_with(base: *mut u8, size: usize) -> bool;
+
+    /// Add another host-supplied region to an initialized heap
+    ///
+    /// The region must not overlap memory the allocator already manages.
+    pub fn gl_add_region(base: *mut u8, size: usize) -> bool;
+
+    /// Install (or with `None`, remove) the out-of-memory handler
+    ///
+    /// The handler must not allocate from this heap.
+    pub fn gl_set_oom_handler(handler: Option<OomHandler>);
+
+    /// Allocate `size` bytes of memory on the heap
+    ///
+    /// Returns a pointer to the allocated memory, or NULL if allocation fails.
+    /// The returned pointer is guaranteed to be 8-byte aligned.
+    pub fn gl_malloc(size: usize) -> *mut u8;
+
+    /// Free memory previously allocated by gl_malloc
+    ///
+    /// If ptr is NULL, this is a no-op (safe).
+    pub fn gl_free(ptr: *mut u8);
+
+    /// Get the total number of bytes currently allocated
+    pub fn gl_get_allocated_bytes() -> u64;
+
+    /// Get the number of bytes handed to the allocator (0 before initialization)
+    pub fn gl_get_heap_size() -> u64;
+
+    /// Get the number of bytes on the free lists
+    pub fn gl_get_free_bytes() -> u64;
+
+    /// Get the start address of the heap
+    pub fn gl_get_heap_start() -> *mut u8;
+
+    /// Get the end address of the heap
+    pub fn gl_get_heap_end() -> *mut u8;
+}
+
+/// Snapshot of the native heap
+///
+/// `used` and `free` count block payloads, so together they fall short of
+/// `size` by the block headers and region sentinels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeapStats {
+    /// Bytes handed to the allocator
+    pub size: u64,
+    /// Bytes in allocated blocks
+    pub used: u64,
+    /// Bytes in free blocks
+    pub free: u64,
+}
+
+/// Current heap statistics
+///
+/// Like the allocator itself, this must not race with allocations on
+/// another thread.
+pub fn heap_stats() -> HeapStats {
+    unsafe {
+        HeapStats {
+            size: gl_get_heap_size(),
+            used: gl_get_allocated_bytes(),
+            free: gl_get_free_bytes(),
+        }
+    }
+}
+
+/// Alignment every `gl_malloc` block satisfies
+pub const GL_MALLOC_ALIGN: usize = 8;
+
+/// [`GlobalAlloc`] adapter over `gl_malloc`/`gl_free`
+///
+/// The allocator itself is not thread-safe, so the adapter serializes its
+/// calls with a spin lock. Compiled scripts calling `gl_malloc` directly do
+/// not take that lock: hosts that share the heap across threads must run
+/// them on the thread that owns it.
+///
+/// Block headers record the size of every block, so `gl_free` needs no
+/// layout. Layouts aligned past [`GL_MALLOC_ALIGN`] are over-allocated and
+/// the block's real address is stored in the word just below the aligned
+/// pointer, where `dealloc` finds it again.
+#[derive(Debug)]
+pub struct GlAllocator {
+    locked: AtomicBool,
+}
+
+impl GlAllocator {
+    /// Create the adapter (usable in a `static`)
+    pub const fn new() -> Self {
+        GlAllocator { locked: AtomicBool::new(false) }
+    }
+
+    fn with_lock<T>(&self, f: impl FnOnce() -> T) -> T {
+        while self
+            .locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            core::hint::spin_loop();
+        }
+        let result = f();
+        self.locked.store(false, Ordering::Release);
+        result
+    }
+}
+
+impl Default for GlAllocator {
+    fn default() -> Self {
+        GlAllocator::new()
+    }
+}
+
+unsafe impl GlobalAlloc for GlAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if layout.align() <= GL_MALLOC_ALIGN {
+            return self.with_lock(|| gl_malloc(layout.size()));
+        }
+        // Room to move the pointer up to the alignment with a word below it
+        let padded = match layout.size().checked_add(layout.align()) {
+            Some(padded) => padded,
+            None => return core::ptr::null_mut(),
+        };
+        let raw = self.with_lock(|| gl_malloc(padded));
+        if raw.is_null() {
+            return raw;
+        }
+        let offset = (raw as usize + 1).next_multiple_of(layout.align()) - raw as usize;
+        let aligned = raw.add(offset);
+        (aligned as *mut *mut u8).sub(1).write(raw);
+        aligned
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let raw = if layout.align() <= GL_MALLOC_ALIGN {
+            ptr
+        } else {
+            (ptr as *mut *mut u8).sub(1).read()
+        };
+        self.with_lock(|| gl_free(raw));
+    }
+}
+
+/// Bump arena over a host-supplied region
+///
+/// Allocation only moves a cursor; nothing is freed individually and
+/// [`Arena::reset`] reclaims the whole arena at once.
+#[derive(Debug)]
+pub struct Arena {
+    name: String,
+    base: *mut u8,
+    size: usize,
+    used: usize,
+}
+
+impl Arena {
+    /// Create an arena over `size` bytes at `base`
+    ///
+    /// # Safety
+    ///
+    /// The region must be valid for writes and not used by anything else for
+    /// as long as the arena or its allocations are alive.
+    pub unsafe fn new(name: &str, base: *mut u8, size: usize) -> Self {
+        Arena { name: name.into(), base, size, used: 0 }
+    }
+
+    /// Name the arena was registered under
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Allocate `size` bytes aligned to `align` (a power of two), or NULL
+    /// when the arena is full
+    pub fn alloc(&mut self, size: usize, align: usize) -> *mut u8 {
+        debug_assert!(align.is_power_of_two());
+        let cursor = self.base as usize + self.used;
+        let start = match cursor.checked_next_multiple_of(align) {
+            Some(start) => start,
+            None => return core::ptr::null_mut(),
+        };
+        let offset = start - self.base as usize;
+        match offset.checked_add(size) {
+            Some(end) if end <= self.size => {
+                self.used = end;
+                self.base.wrapping_add(offset)
+            }
+            _ => core::ptr::null_mut(),
+        }
+    }
+
+    /// Reclaim every allocation at once
+    pub fn reset(&mut self) {
+        self.used = 0;
+    }
+
+    /// Size of the region in bytes
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Bytes allocated since the last reset, alignment padding included
+    pub fn used(&self) -> usize {
+        self.used
+    }
+
+    /// Bytes still available (before alignment padding)
+    pub fn free(&self) -> usize {
+        self.size - self.used
+    }
+}
+
+/// Arenas registered by the host, looked up by name
+#[derive(Debug, Default)]
+pub struct Arenas {
+    arenas: Vec<Arena>,
+}
+
+impl Arenas {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Arenas::default()
+    }
+
+    /// Register an arena, returning the one it replaces under the same name
+    pub fn insert(&mut self, arena: Arena) -> Option<Arena> {
+        match self.arenas.iter_mut().find(|existing| existing.name == arena.name) {
+            Some(existing) => Some(core::mem::replace(existing, arena)),
+            None => {
+                self.arenas.push(arena);
+                None
+            }
+        }
+    }
+
+    /// Arena registered under `name`
+    pub fn get(&self, name: &str) -> Option<&Arena> {
+        self.arenas.iter().find(|arena| arena.name == name)
+    }
+
+    /// Arena registered under `name`, for allocating
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Arena> {
+        self.arenas.iter_mut().find(|arena| arena.name == name)
+    }
+
+    /// Registered arenas, in registration order
+    pub fn iter(&self) -> impl Iterator<Item = &Arena> {
+        self.arenas.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arena_bumps_and_resets() {
+        let mut region = [0u64; 8];
+        let mut arena = unsafe { Arena::new("scratch", region.as_mut_ptr() as *mut u8, 64) };
+
+        let a = arena.alloc(3, 1);
+        let b = arena.alloc(8, 8);
+        assert_eq!(b as usize - a as usize, 8, "second block is aligned past the first");
+        assert_eq!(arena.used(), 16);
+        assert!(arena.alloc(64, 8).is_null());
+
+        arena.reset();
+        assert_eq!(arena.free(), 64);
+        assert_eq!(arena.alloc(64, 8), a);
+    }
+
+    #[test]
+    fn test_arenas_by_name() {
+        let mut dma = [0u64; 4];
+        let mut scratch = [0u64; 4];
+        let mut arenas = Arenas::new();
+        unsafe {
+            assert!(arenas.insert(Arena::new("dma", dma.as_mut_ptr() as *mut u8, 32)).is_none());
+            assert!(arenas.insert(Arena::new("scratch", scratch.as_mut_ptr() as *mut u8, 32)).is_none());
+            let replaced = arenas.insert(Arena::new("dma", dma.as_mut_ptr() as *mut u8, 16));
+            assert_eq!(replaced.map(|arena| arena.size()), Some(32));
+        }
+
+        assert!(!arenas.get_mut("scratch").unwrap().alloc(8, 8).is_null());
+        assert_eq!(arenas.get("dma").map(Arena::size), Some(16));
+        assert_eq!(arenas.iter().map(Arena::name).collect::<Vec<_>>(), ["dma", "scratch"]);
+        assert!(arenas.get("missing").is_none());
+    }
+}
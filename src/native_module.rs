@@ -0,0 +1,451 @@
+//! # Compiled Modules - Calling AOT-Compiled Chants from Rust
+//!
+//! Loads natively compiled scripts into the host process so embedders can
+//! call their chants directly, without the interpreter or VM in between.
+//!
+//! ## Calling Convention
+//!
+//! Every top-level chant gets a global wrapper symbol,
+//! [`export_symbol`](crate::codegen::export_symbol) (`glimmer_<name>`), that
+//! follows the System V AMD64 ABI:
+//!
+//! - Arguments are 64-bit words in rdi, rsi, rdx, rcx, r8, r9, then the stack
+//! - The result is a single 64-bit word in rax
+//! - rbx, rbp and rsp are preserved; the stack must be 16-byte aligned at the call
+//!
+//! Values are passed as words:
+//!
+//! | Glimmer-Weave | Word |
+//! |---------------|------|
+//! | `Number` | the integer value (`i64`) |
+//! | `Text` | pointer to a length word followed by the UTF-8 bytes |
+//! | forms | pointer to one 8-byte slot per field, in declaration order |
+//!
+//! Text and form values live on the `gl_malloc` heap (see
+//! [`native_runtime`](crate::native_runtime)), which compiled code never
+//! frees: arguments marshalled by [`CompiledFn::call`] stay allocated, as do
+//! the values a chant builds.
+//!
+//! Chants that capture variables of the script's top level are closures and
+//! have no wrapper.
+//!
+//! ## Loading
+//!
+//! [`CompiledModule::load`] takes the relocatable ELF object an assembler
+//! produces from [`compile_to_asm`](crate::codegen::compile_to_asm) output
+//! (for instance `as -o module.o module.s`). Its sections are mapped with
+//! `mmap`, relocations applied, and calls to `gl_malloc`/`gl_free` bound to
+//! the allocator linked into this crate. Code pages are then made
+//! read-only and executable.
+//!
+//! ```ignore
+//! let module = CompiledModule::load(&object)?;
+//! let area = module.get_fn("area").ok_or("no chant named area")?;
+//! let result = unsafe { area.call(&[NativeArg::Number(6), NativeArg::Number(7)])? };
+//! assert_eq!(result.as_number(), 42);
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ffi::c_void;
+use core::ptr;
+use crate::codegen::export_symbol;
+use crate::native_allocator::{gl_free, gl_malloc};
+
+const PROT_READ: i32 = 0x1;
+const PROT_WRITE: i32 = 0x2;
+const PROT_EXEC: i32 = 0x4;
+const MAP_PRIVATE: i32 = 0x02;
+const MAP_ANONYMOUS: i32 = 0x20;
+const MAP_FAILED: *mut c_void = !0usize as *mut c_void;
+const PAGE_SIZE: usize = 4096;
+
+extern "C" {
+    fn mmap(addr: *mut c_void, len: usize, prot: i32, flags: i32, fd: i32, offset: i64) -> *mut c_void;
+    fn mprotect(addr: *mut c_void, len: usize, prot: i32) -> i32;
+    fn munmap(addr: *mut c_void, len: usize) -> i32;
+}
+
+// ELF64 constants used by the loader
+const SHT_SYMTAB: u32 = 2;
+const SHT_RELA: u32 = 4;
+const SHT_NOBITS: u32 = 8;
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+const SHN_UNDEF: u16 = 0;
+const R_X86_64_64: u32 = 1;
+const R_X86_64_PC32: u32 = 2;
+const R_X86_64_PLT32: u32 = 4;
+
+/// Size of an absolute jump stub: `jmp *0(%rip)` followed by the target
+const STUB_SIZE: usize = 16;
+
+/// Runtime functions generated code may call, bound to this process's allocator
+fn runtime_symbol(name: &str) -> Option<usize> {
+    match name {
+        "gl_malloc" => Some(gl_malloc as *const () as usize),
+        "gl_free" => Some(gl_free as *const () as usize),
+        _ => None,
+    }
+}
+
+fn align_up(value: usize, align: usize) -> usize {
+    if align <= 1 {
+        value
+    } else {
+        (value + align - 1) & !(align - 1)
+    }
+}
+
+/// A section header, reduced to the fields the loader uses
+struct Section {
+    kind: u32,
+    flags: u64,
+    offset: usize,
+    size: usize,
+    link: usize,
+    info: usize,
+    align: usize,
+}
+
+/// A symbol table entry
+struct Symbol {
+    name: String,
+    section: u16,
+    value: u64,
+}
+
+/// Little-endian reads from the object file, failing on truncation
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn slice(&self, offset: usize, len: usize) -> Result<&[u8], String> {
+        offset
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(offset..end))
+            .ok_or_else(|| String::from("Truncated ELF object"))
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16, String> {
+        let bytes = self.slice(offset, 2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32, String> {
+        let mut word = [0; 4];
+        word.copy_from_slice(self.slice(offset, 4)?);
+        Ok(u32::from_le_bytes(word))
+    }
+
+    fn u64(&self, offset: usize) -> Result<u64, String> {
+        let mut word = [0; 8];
+        word.copy_from_slice(self.slice(offset, 8)?);
+        Ok(u64::from_le_bytes(word))
+    }
+
+    fn c_str(&self, offset: usize) -> Result<String, String> {
+        let rest = self.bytes.get(offset..).ok_or_else(|| String::from("Truncated ELF object"))?;
+        let len = rest.iter().position(|b| *b == 0).ok_or_else(|| String::from("Unterminated ELF string"))?;
+        Ok(String::from_utf8_lossy(&rest[..len]).into_owned())
+    }
+
+    fn sections(&self) -> Result<Vec<Section>, String> {
+        if self.slice(0, 4)? != b"\x7fELF" || self.slice(4, 2)? != [2, 1] {
+            return Err(String::from("Not a little-endian ELF64 object"));
+        }
+        if self.u16(16)? != 1 || self.u16(18)? != 62 {
+            return Err(String::from("Not a relocatable x86-64 object"));
+        }
+        let table = self.u64(40)? as usize;
+        let entry_size = self.u16(58)? as usize;
+        let count = self.u16(60)? as usize;
+        (0..count)
+            .map(|index| {
+                let header = table + index * entry_size;
+                Ok(Section {
+                    kind: self.u32(header + 4)?,
+                    flags: self.u64(header + 8)?,
+                    offset: self.u64(header + 24)? as usize,
+                    size: self.u64(header + 32)? as usize,
+                    link: self.u32(header + 40)? as usize,
+                    info: self.u32(header + 44)? as usize,
+                    align: self.u64(header + 48)? as usize,
+                })
+            })
+            .collect()
+    }
+
+    fn symbols(&self, sections: &[Section]) -> Result<Vec<Symbol>, String> {
+        let Some(table) = sections.iter().find(|section| section.kind == SHT_SYMTAB) else {
+            return Ok(Vec::new());
+        };
+        let names = sections.get(table.link).ok_or_else(|| String::from("Missing symbol string table"))?;
+        (0..table.size / 24)
+            .map(|index| {
+                let entry = table.offset + index * 24;
+                Ok(Symbol {
+                    name: self.c_str(names.offset + self.u32(entry)? as usize)?,
+                    section: self.u16(entry + 6)?,
+                    value: self.u64(entry + 8)?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A natively compiled script mapped into this process
+pub struct CompiledModule {
+    base: *mut u8,
+    len: usize,
+    /// Exported symbols and their addresses
+    exports: Vec<(String, usize)>,
+}
+
+impl CompiledModule {
+    /// Map a relocatable ELF object produced from generated assembly
+    pub fn load(object: &[u8]) -> Result<Self, String> {
+        let reader = Reader { bytes: object };
+        let sections = reader.sections()?;
+        let symbols = reader.symbols(&sections)?;
+
+        // Code first, then one stub per external symbol, then data on its
+        // own pages so the code pages can be made executable
+        let mut addresses = vec![None; sections.len()];
+        let mut cursor = 0;
+        for (index, section) in sections.iter().enumerate() {
+            if section.flags & SHF_ALLOC != 0 && section.flags & SHF_EXECINSTR != 0 {
+                cursor = align_up(cursor, section.align);
+                addresses[index] = Some(cursor);
+                cursor += section.size;
+            }
+        }
+        let externals: Vec<&str> = symbols
+            .iter()
+            .skip(1)
+            .filter(|symbol| symbol.section == SHN_UNDEF && !symbol.name.is_empty())
+            .map(|symbol| symbol.name.as_str())
+            .collect();
+        let stubs = align_up(cursor, STUB_SIZE);
+        let code_len = align_up(stubs + externals.len() * STUB_SIZE, PAGE_SIZE);
+        cursor = code_len;
+        for (index, section) in sections.iter().enumerate() {
+            if section.flags & SHF_ALLOC != 0 && section.flags & SHF_EXECINSTR == 0 {
+                cursor = align_up(cursor, section.align);
+                addresses[index] = Some(cursor);
+                cursor += section.size;
+            }
+        }
+        let len = align_up(cursor.max(1), PAGE_SIZE);
+
+        // SAFETY: a fresh private anonymous mapping; nothing else refers to it
+        let base = unsafe { mmap(ptr::null_mut(), len, PROT_READ | PROT_WRITE, MAP_PRIVATE | MAP_ANONYMOUS, -1, 0) };
+        if base == MAP_FAILED {
+            return Err(String::from("mmap failed"));
+        }
+        let module = CompiledModule { base: base as *mut u8, len, exports: Vec::new() };
+        module.populate(&reader, &sections, &symbols, &addresses, &externals, stubs, code_len)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn populate(
+        mut self,
+        reader: &Reader,
+        sections: &[Section],
+        symbols: &[Symbol],
+        addresses: &[Option<usize>],
+        externals: &[&str],
+        stubs: usize,
+        code_len: usize,
+    ) -> Result<Self, String> {
+        let base = self.base as usize;
+        for (section, address) in sections.iter().zip(addresses) {
+            if let (Some(address), true) = (address, section.kind != SHT_NOBITS) {
+                let bytes = reader.slice(section.offset, section.size)?;
+                // SAFETY: the layout reserved `section.size` bytes at `address`
+                unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), self.base.add(*address), bytes.len()) };
+            }
+        }
+
+        for (index, name) in externals.iter().enumerate() {
+            let target = runtime_symbol(name).ok_or_else(|| format!("Undefined symbol {}", name))?;
+            let mut stub = [0u8; STUB_SIZE];
+            stub[..6].copy_from_slice(&[0xff, 0x25, 0, 0, 0, 0]);
+            stub[6..14].copy_from_slice(&(target as u64).to_le_bytes());
+            // SAFETY: the stubs fit between the code and `code_len`
+            unsafe { ptr::copy_nonoverlapping(stub.as_ptr(), self.base.add(stubs + index * STUB_SIZE), STUB_SIZE) };
+        }
+
+        let symbol_address = |symbol: &Symbol| -> Result<usize, String> {
+            if symbol.section == SHN_UNDEF {
+                let index = externals.iter().position(|name| *name == symbol.name).unwrap_or(0);
+                return Ok(base + stubs + index * STUB_SIZE);
+            }
+            addresses
+                .get(symbol.section as usize)
+                .copied()
+                .flatten()
+                .map(|address| base + address + symbol.value as usize)
+                .ok_or_else(|| format!("Symbol {} is not in a loaded section", symbol.name))
+        };
+
+        for section in sections.iter().filter(|section| section.kind == SHT_RELA) {
+            let Some(Some(target)) = addresses.get(section.info) else {
+                continue;
+            };
+            for entry in (0..section.size / 24).map(|index| section.offset + index * 24) {
+                let offset = reader.u64(entry)? as usize;
+                let info = reader.u64(entry + 8)?;
+                let addend = reader.u64(entry + 16)? as i64;
+                let symbol = symbols.get((info >> 32) as usize).ok_or_else(|| String::from("Bad relocation symbol"))?;
+                let value = (symbol_address(symbol)? as i64).wrapping_add(addend);
+                let place = base + target + offset;
+                match info as u32 {
+                    R_X86_64_64 => {
+                        // SAFETY: relocations patch bytes inside their own loaded section
+                        unsafe { ptr::write_unaligned(place as *mut u64, value as u64) };
+                    }
+                    R_X86_64_PC32 | R_X86_64_PLT32 => {
+                        let relative = i32::try_from(value - place as i64)
+                            .map_err(|_| format!("Relocation against {} out of range", symbol.name))?;
+                        // SAFETY: as above
+                        unsafe { ptr::write_unaligned(place as *mut i32, relative) };
+                    }
+                    other => return Err(format!("Unsupported relocation type {}", other)),
+                }
+            }
+        }
+
+        for symbol in symbols.iter().filter(|symbol| symbol.section != SHN_UNDEF && !symbol.name.is_empty()) {
+            if let Ok(address) = symbol_address(symbol) {
+                self.exports.push((symbol.name.clone(), address));
+            }
+        }
+
+        // SAFETY: the code pages are part of this mapping
+        if unsafe { mprotect(self.base as *mut c_void, code_len, PROT_READ | PROT_EXEC) } != 0 {
+            return Err(String::from("mprotect failed"));
+        }
+        Ok(self)
+    }
+
+    /// Look up a top-level chant by its Glimmer-Weave name
+    pub fn get_fn(&self, name: &str) -> Option<CompiledFn<'_>> {
+        let symbol = export_symbol(name);
+        self.exports
+            .iter()
+            .find(|(export, _)| *export == symbol)
+            .map(|(_, address)| CompiledFn { address: *address, _module: self })
+    }
+}
+
+impl Drop for CompiledModule {
+    fn drop(&mut self) {
+        // SAFETY: `base`/`len` is the mapping made in `load`, and the
+        // `CompiledFn` borrows keep it alive while chants can be called
+        unsafe { munmap(self.base as *mut c_void, self.len) };
+    }
+}
+
+/// A chant of a [`CompiledModule`]
+#[derive(Clone, Copy)]
+pub struct CompiledFn<'a> {
+    address: usize,
+    _module: &'a CompiledModule,
+}
+
+/// An argument marshalled to the native calling convention
+#[derive(Debug, Clone, Copy)]
+pub enum NativeArg<'a> {
+    Number(i64),
+    /// Copied onto the script heap as a length-prefixed string
+    Text(&'a str),
+    /// Field values in declaration order, copied onto the script heap
+    Struct(&'a [u64]),
+    /// A word passed through unchanged, such as a value a chant returned
+    Raw(u64),
+}
+
+impl NativeArg<'_> {
+    fn to_word(self) -> Result<u64, String> {
+        let heap_copy = |words: &[u64], bytes: &[u8]| -> Result<u64, String> {
+            let size = words.len() * 8 + bytes.len();
+            // SAFETY: gl_malloc returns `size` writable bytes or NULL
+            let block = unsafe { gl_malloc(size.max(8)) };
+            if block.is_null() {
+                return Err(String::from("Out of memory marshalling an argument"));
+            }
+            // SAFETY: the copies stay within the allocated block
+            unsafe {
+                ptr::copy_nonoverlapping(words.as_ptr() as *const u8, block, words.len() * 8);
+                ptr::copy_nonoverlapping(bytes.as_ptr(), block.add(words.len() * 8), bytes.len());
+            }
+            Ok(block as u64)
+        };
+        match self {
+            NativeArg::Number(n) => Ok(n as u64),
+            NativeArg::Text(text) => heap_copy(&[text.len() as u64], text.as_bytes()),
+            NativeArg::Struct(fields) => heap_copy(fields, &[]),
+            NativeArg::Raw(word) => Ok(word),
+        }
+    }
+}
+
+/// The word a chant returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NativeValue(pub u64);
+
+impl NativeValue {
+    /// Read the result as a `Number`
+    pub fn as_number(self) -> i64 {
+        self.0 as i64
+    }
+
+    /// Read the result as a `Text`
+    ///
+    /// # Safety
+    ///
+    /// The chant must have returned a `Text`.
+    pub unsafe fn as_text(self) -> String {
+        let block = self.0 as *const u8;
+        let len = ptr::read_unaligned(block as *const u64) as usize;
+        let bytes = core::slice::from_raw_parts(block.add(8), len);
+        String::from_utf8_lossy(bytes).into_owned()
+    }
+
+    /// Read a field of a form the chant returned
+    ///
+    /// # Safety
+    ///
+    /// The chant must have returned a form with more than `index` fields.
+    pub unsafe fn field(self, index: usize) -> NativeValue {
+        NativeValue(ptr::read_unaligned((self.0 as *const u64).add(index)))
+    }
+}
+
+impl CompiledFn<'_> {
+    /// Address of the chant's wrapper
+    pub fn address(&self) -> *const c_void {
+        self.address as *const c_void
+    }
+
+    /// Call the chant with up to six arguments
+    ///
+    /// # Safety
+    ///
+    /// The arguments must match the chant's parameters in number and kind:
+    /// compiled code does not check them.
+    pub unsafe fn call(&self, args: &[NativeArg]) -> Result<NativeValue, String> {
+        if args.len() > 6 {
+            return Err(format!("CompiledFn::call passes at most 6 arguments, got {}", args.len()));
+        }
+        let mut words = [0u64; 6];
+        for (word, arg) in words.iter_mut().zip(args) {
+            *word = arg.to_word()?;
+        }
+        // Unused registers are ignored by the callee
+        let chant: extern "C" fn(u64, u64, u64, u64, u64, u64) -> u64 = core::mem::transmute(self.address);
+        Ok(NativeValue(chant(words[0], words[1], words[2], words[3], words[4], words[5])))
+    }
+}
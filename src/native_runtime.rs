@@ -31,6 +31,25 @@
 //!
 //! All fields are 8-byte aligned (f64 or pointer-sized).
 //!
+//! ### Enums
+//! Enum values (`Triumph`/`Mishap`, `Present`/`Absent` and user-defined
+//! `variant` cases) are a tag word followed by one slot per field of the
+//! case:
+//! ```text
+//! +------------------+
+//! | tag (8 bytes)    |  discriminant of the case
+//! +------------------+
+//! | field_0 (8 bytes)|
+//! +------------------+
+//! | field_1 (8 bytes)|
+//! +------------------+
+//! | ...              |
+//! +------------------+
+//! ```
+//!
+//! A case with N fields takes `8 * (N + 1)` bytes, so `Absent` is just its
+//! tag. The VM's `ExtractField` instruction indexes fields the same way.
+//!
 //! ### Strings
 //! Strings are allocated with a length prefix:
 //! ```text
@@ -58,6 +77,19 @@ use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use alloc::format;
 
+/// Offset of the tag word in an enum value
+pub const ENUM_TAG_OFFSET: i32 = 0;
+
+/// Offset of an enum field slot
+pub fn enum_field_offset(field_index: usize) -> i32 {
+    8 * (field_index as i32 + 1)
+}
+
+/// Size in bytes of an enum case with `field_count` fields
+pub fn enum_size(field_count: usize) -> i32 {
+    8 * (field_count as i32 + 1)
+}
+
 /// Native runtime functions available to generated code
 pub struct NativeRuntime {
     /// Generated initialization code (data section, etc)
@@ -148,7 +180,9 @@ impl NativeRuntime {
     /// Input: r10 = string length (in bytes)
     ///        r11 = pointer to string data (source)
     /// Output: rax = pointer to allocated string (with length prefix)
-    pub fn gen_string_alloc() -> Vec<Instruction> {
+    ///
+    /// `id` keeps the copy loop's labels unique within the program.
+    pub fn gen_string_alloc(id: usize) -> Vec<Instruction> {
         let mut code = vec![
             Instruction::Comment("Allocate string on heap".to_string()),
             // Calculate total size: 8 bytes (length) + string data
@@ -189,20 +223,22 @@ impl NativeRuntime {
         code.push(Instruction::Mov("$8".to_string(), "%rdx".to_string()));   // rdx = 8 (skip length)
 
         // Loop start label
-        code.push(Instruction::Label(".L_string_copy_loop".to_string()));
+        let copy_loop = format!(".L_string_copy_loop_{}", id);
+        let copy_done = format!(".L_string_copy_done_{}", id);
+        code.push(Instruction::Label(copy_loop.clone()));
 
         // Check if rcx >= r10 (copied all bytes?)
         code.push(Instruction::Cmp("%r10".to_string(), "%rcx".to_string()));
-        code.push(Instruction::Jge(".L_string_copy_done".to_string()));
+        code.push(Instruction::Jge(copy_done.clone()));
 
         // Copy one byte: byte = *(r11 + rcx)
-        code.push(Instruction::Mov(
+        code.push(Instruction::MovByte(
             "(%r11,%rcx,1)".to_string(),
             "%r8b".to_string()  // r8b = 8-bit register for byte
         ));
 
         // Store byte: *(rax + rdx) = byte
-        code.push(Instruction::Mov(
+        code.push(Instruction::MovByte(
             "%r8b".to_string(),
             "(%rax,%rdx,1)".to_string()
         ));
@@ -212,10 +248,10 @@ impl NativeRuntime {
         code.push(Instruction::Inc("%rdx".to_string()));  // rdx++
 
         // Loop back
-        code.push(Instruction::Jmp(".L_string_copy_loop".to_string()));
+        code.push(Instruction::Jmp(copy_loop));
 
         // Loop done
-        code.push(Instruction::Label(".L_string_copy_done".to_string()));
+        code.push(Instruction::Label(copy_done));
         code.push(Instruction::Comment("String allocated at rax".to_string()));
 
         code
@@ -309,7 +345,7 @@ mod tests {
     #[test]
     fn test_gen_malloc_call() {
         let code = NativeRuntime::gen_malloc_call();
-        assert!(code.len() >= 1);
+        assert!(!code.is_empty());
 
         // Should contain call to gl_malloc
         assert!(code.iter().any(|inst| {
@@ -344,6 +380,16 @@ mod tests {
         }));
     }
 
+    #[test]
+    fn test_enum_layout() {
+        // Tag first, then one slot per field
+        assert_eq!(ENUM_TAG_OFFSET, 0);
+        assert_eq!(enum_field_offset(0), 8);
+        assert_eq!(enum_field_offset(2), 24);
+        assert_eq!(enum_size(0), 8);
+        assert_eq!(enum_size(3), 32);
+    }
+
     #[test]
     fn test_gen_struct_field_store() {
         let code = NativeRuntime::gen_struct_field_store(1);
@@ -0,0 +1,179 @@
+//! # Network Sockets
+//!
+//! TCP connections for scripts, through the evaluator's [`NetProvider`].
+//!
+//! A script asks for each endpoint it talks to with
+//! `request Net.connect(host, port)`; the arguments are evaluated and the
+//! granted token only opens a connection to that endpoint. The provider
+//! has the last word: it refuses the request when the endpoint is not on
+//! its allowlist, whatever the capability policy says.
+//!
+//! `Net.tcp_connect(token)` opens the connection as a resource of kind
+//! [`SOCKET_RESOURCE`], closed when the chant that opened it finishes or
+//! earlier with `Net.close`. `Net.send(socket, text)` and
+//! `Net.recv(socket, max_bytes)` move text over it. Each returns an
+//! Outcome: network failures are Mishaps the script can handle, not
+//! runtime errors.
+//!
+//! ```
+//! use glimmer_weave::net::{NetProvider, StdNet};
+//!
+//! let net = StdNet::new().allow("example.com", 80);
+//! assert!(net.allows("example.com", 80));
+//! assert!(!net.allows("example.com", 443));
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Capability a script requests, with an endpoint, to open a connection
+pub const NET_CONNECT_CAPABILITY: &str = "Net.connect";
+
+/// Resource kind of an open connection
+pub const SOCKET_RESOURCE: &str = "socket";
+
+/// Host side of TCP networking
+///
+/// Handles are chosen by the host; errors become Mishaps for the script.
+pub trait NetProvider: crate::sync::MaybeSend {
+    /// Whether scripts may connect to `host` on `port`
+    fn allows(&self, host: &str, port: u16) -> bool;
+    /// Open a connection
+    fn connect(&mut self, host: &str, port: u16) -> Result<u64, String>;
+    /// Write `bytes`, returning how many were written
+    fn send(&mut self, handle: u64, bytes: &[u8]) -> Result<usize, String>;
+    /// Read at most `max` bytes; empty once the peer has closed
+    fn recv(&mut self, handle: u64, max: usize) -> Result<Vec<u8>, String>;
+    /// Close a connection; it is never used again
+    fn close(&mut self, handle: u64) -> Result<(), String>;
+    /// Fail a send or recv that takes longer than `millis` (`None`: wait
+    /// forever); providers without timeouts ignore this
+    fn set_timeout(&mut self, _handle: u64, _millis: Option<u64>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// TCP through `std::net`, to the endpoints on its allowlist
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct StdNet {
+    allowlist: Vec<(String, u16)>,
+    streams: alloc::collections::BTreeMap<u64, std::net::TcpStream>,
+    next_handle: u64,
+}
+
+#[cfg(feature = "std")]
+impl StdNet {
+    /// Create a provider that allows no endpoints yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow connections to `host` on `port`
+    pub fn allow(mut self, host: &str, port: u16) -> Self {
+        self.allowlist.push((host.into(), port));
+        self
+    }
+
+    fn stream(&mut self, handle: u64) -> Result<&mut std::net::TcpStream, String> {
+        self.streams.get_mut(&handle).ok_or_else(|| alloc::format!("Unknown socket handle {}", handle))
+    }
+}
+
+#[cfg(feature = "std")]
+impl NetProvider for StdNet {
+    fn allows(&self, host: &str, port: u16) -> bool {
+        self.allowlist.iter().any(|(allowed, allowed_port)| allowed == host && *allowed_port == port)
+    }
+
+    fn connect(&mut self, host: &str, port: u16) -> Result<u64, String> {
+        let stream = std::net::TcpStream::connect((host, port)).map_err(|error| alloc::format!("{}:{}: {}", host, port, error))?;
+        self.next_handle += 1;
+        self.streams.insert(self.next_handle, stream);
+        Ok(self.next_handle)
+    }
+
+    fn send(&mut self, handle: u64, bytes: &[u8]) -> Result<usize, String> {
+        use std::io::Write;
+        let stream = self.stream(handle)?;
+        stream.write_all(bytes).map_err(|error| error.to_string())?;
+        Ok(bytes.len())
+    }
+
+    fn recv(&mut self, handle: u64, max: usize) -> Result<Vec<u8>, String> {
+        use std::io::Read;
+        let mut buffer = alloc::vec![0; max];
+        let read = self.stream(handle)?.read(&mut buffer).map_err(|error| error.to_string())?;
+        buffer.truncate(read);
+        Ok(buffer)
+    }
+
+    fn close(&mut self, handle: u64) -> Result<(), String> {
+        self.streams.remove(&handle).map(|_| ()).ok_or_else(|| alloc::format!("Unknown socket handle {}", handle))
+    }
+
+    fn set_timeout(&mut self, handle: u64, millis: Option<u64>) -> Result<(), String> {
+        let timeout = millis.map(|millis| std::time::Duration::from_millis(millis.max(1)));
+        let stream = self.stream(handle)?;
+        stream.set_read_timeout(timeout).map_err(|error| error.to_string())?;
+        stream.set_write_timeout(timeout).map_err(|error| error.to_string())
+    }
+}
+
+/// Endpoint a `Net.connect` token was granted for, as written into its
+/// permissions
+pub(crate) fn endpoint_permission(host: &str, port: u16) -> String {
+    alloc::format!("{}:{}", host, port)
+}
+
+/// Host and port of an endpoint permission
+pub(crate) fn parse_endpoint(permission: &str) -> Option<(&str, u16)> {
+    let (host, port) = permission.rsplit_once(':')?;
+    Some((host, port.parse().ok()?))
+}
+
+/// Default network provider for new evaluators: [`StdNet`] allowing no
+/// endpoints under std, none otherwise
+pub(crate) fn default_net() -> Option<alloc::boxed::Box<dyn NetProvider>> {
+    #[cfg(feature = "std")]
+    {
+        Some(alloc::boxed::Box::new(StdNet::new()))
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        None
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_std_net_round_trip() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let echo = std::thread::spawn(move || {
+            use std::io::{Read, Write};
+            let (mut peer, _) = listener.accept().unwrap();
+            let mut buffer = [0; 5];
+            peer.read_exact(&mut buffer).unwrap();
+            peer.write_all(&buffer).unwrap();
+        });
+
+        let mut net = StdNet::new().allow("127.0.0.1", port);
+        let socket = net.connect("127.0.0.1", port).unwrap();
+        assert_eq!(net.send(socket, b"hello"), Ok(5));
+        assert_eq!(net.recv(socket, 16), Ok(b"hello".to_vec()));
+        echo.join().unwrap();
+        net.close(socket).unwrap();
+        assert!(net.send(socket, b"x").is_err());
+    }
+
+    #[test]
+    fn test_endpoint_permission_round_trip() {
+        let permission = endpoint_permission("::1", 8080);
+        assert_eq!(parse_endpoint(&permission), Some(("::1", 8080)));
+        assert_eq!(parse_endpoint("nowhere"), None);
+    }
+}
@@ -0,0 +1,29 @@
+//! # Output
+//!
+//! Host destination for what scripts print.
+//!
+//! `print(...)` and `println(...)` render their arguments as `to_text`
+//! does, separated by spaces, and hand the text to the evaluator's
+//! [`OutputSink`]. Without a sink they fail, since a bare evaluator has no
+//! console to write to. Any `FnMut(&str)` closure is a sink too.
+//!
+//! ```
+//! use glimmer_weave::output::OutputSink;
+//!
+//! let mut written = String::new();
+//! let mut sink = |text: &str| written.push_str(text);
+//! sink.write("hello\n");
+//! assert_eq!(written, "hello\n");
+//! ```
+
+/// Receives the text scripts print
+pub trait OutputSink: crate::sync::MaybeSend {
+    /// Write `text`, which ends with a newline when it came from `println`
+    fn write(&mut self, text: &str);
+}
+
+impl<F: FnMut(&str) + crate::sync::MaybeSend> OutputSink for F {
+    fn write(&mut self, text: &str) {
+        self(text)
+    }
+}
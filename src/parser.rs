@@ -9,6 +9,7 @@ use alloc::boxed::Box;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use crate::ast::*;
+use crate::language_version::{self, Feature, LanguageVersion};
 use crate::token::{Token, PositionedToken};
 use crate::source_location::SourceSpan;
 
@@ -16,6 +17,8 @@ use crate::source_location::SourceSpan;
 pub struct Parser {
     tokens: Vec<PositionedToken>,
     position: usize,
+    /// Version from the script's `speaks` pragma
+    version: LanguageVersion,
 }
 
 /// Parser error
@@ -30,7 +33,7 @@ pub type ParseResult<T> = Result<T, ParseError>;
 impl Parser {
     /// Create a new parser from a vector of positioned tokens
     pub fn new(tokens: Vec<PositionedToken>) -> Self {
-        Parser { tokens, position: 0 }
+        Parser { tokens, position: 0, version: language_version::CURRENT }
     }
 
     /// Get current token
@@ -46,10 +49,6 @@ impl Parser {
     }
 
     /// Peek at next token
-    ///
-    /// FUTURE: Will be needed for lookahead parsing of complex expressions
-    /// and disambiguation of ambiguous syntax (e.g., generics vs comparison).
-    #[allow(dead_code)]
     fn peek(&self) -> &Token {
         self.tokens.get(self.position + 1).map(|pt| &pt.token).unwrap_or(&Token::Eof)
     }
@@ -100,14 +99,78 @@ impl Parser {
         }
     }
 
+    /// Language version the script speaks: its `speaks` pragma's, or the
+    /// current one
+    pub fn language_version(&self) -> LanguageVersion {
+        self.version
+    }
+
+    /// Refuse `feature` if the script speaks a version older than it
+    fn require(&self, feature: Feature) -> ParseResult<()> {
+        if self.version.supports(feature) {
+            return Ok(());
+        }
+        Err(ParseError { message: feature.unsupported(self.version), position: self.position })
+    }
+
+    /// Whether a `speaks "1.2"` pragma starts here (`speaks` is not reserved)
+    fn at_speaks(&self) -> bool {
+        matches!(self.current(), Token::Ident(word) if word == "speaks") && matches!(self.peek(), Token::Text(_))
+    }
+
+    /// Parse: speaks "1.2"
+    fn parse_speaks(&mut self) -> ParseResult<AstNode> {
+        let span = self.current_span();
+        self.advance(); // consume 'speaks'
+        let text = match self.current() {
+            Token::Text(text) => text.clone(),
+            _ => {
+                return Err(ParseError {
+                    message: "Expected the language version after 'speaks'".to_string(),
+                    position: self.position,
+                })
+            }
+        };
+        let version = LanguageVersion::parse(&text).map_err(|message| ParseError { message, position: self.position })?;
+        if version > language_version::CURRENT {
+            return Err(ParseError {
+                message: alloc::format!(
+                    "This script speaks language version {}, but only versions up to {} are known",
+                    version,
+                    language_version::CURRENT
+                ),
+                position: self.position,
+            });
+        }
+        self.advance();
+        self.version = version;
+        Ok(AstNode::Speaks { version, span })
+    }
+
     /// Parse a complete program
     pub fn parse(&mut self) -> ParseResult<Vec<AstNode>> {
+        // Text the lexer could not read fails the whole parse
+        if let Some((position, message)) = self.tokens.iter().enumerate().find_map(|(position, token)| match &token.token {
+            Token::Invalid(message) => Some((position, message.clone())),
+            _ => None,
+        }) {
+            return Err(ParseError { message, position });
+        }
+
         let mut statements = Vec::new();
 
         self.skip_newlines();
+        if self.at_speaks() {
+            statements.push(self.parse_speaks()?);
+            self.skip_newlines();
+        }
 
         while !matches!(self.current(), Token::Eof) {
-            statements.push(self.parse_statement()?);
+            if self.at_ritual() {
+                statements.extend(self.parse_ritual_def()?);
+            } else {
+                statements.push(self.parse_statement()?);
+            }
             self.skip_newlines();
         }
 
@@ -126,8 +189,24 @@ impl Parser {
             Token::For => self.parse_for(),
             Token::Whilst => self.parse_while(),
             Token::Chant => self.parse_chant_def(),
+            // `swift chant` hints the optimizer to inline (`swift` is not reserved)
+            Token::Ident(word) if word == "swift" && matches!(self.peek(), Token::Chant) => {
+                self.require(Feature::SwiftChants)?;
+                self.advance();
+                let mut chant = self.parse_chant_def()?;
+                if let AstNode::ChantDef { swift, .. } = &mut chant {
+                    *swift = true;
+                }
+                Ok(chant)
+            }
             Token::Form => self.parse_form_def(),
             Token::Variant => self.parse_variant_def(),
+            // `ritual` desugars to several definitions, which only the
+            // top level and grove bodies take (`ritual` is not reserved)
+            _ if self.at_ritual() => Err(ParseError {
+                message: "A ritual can only be defined at the top level or in a grove".to_string(),
+                position: self.position,
+            }),
             Token::Aspect => self.parse_aspect_def(),
             Token::Embody => self.parse_embody_stmt(),
             Token::Yield => self.parse_yield(),
@@ -135,7 +214,22 @@ impl Parser {
             Token::Continue => self.parse_continue(),
             Token::Match => self.parse_match(),
             Token::Attempt => self.parse_attempt(),
+            Token::Defer => self.parse_defer(),
+            // `together ... end` (`together` is not reserved)
+            Token::Ident(word) if word == "together" && matches!(self.peek(), Token::Newline) => self.parse_together(),
+            // `migrate store "name" ...` (neither word is reserved)
+            Token::Ident(word) if word == "migrate" && matches!(self.peek(), Token::Ident(next) if next == "store") => {
+                self.parse_migrate_store()
+            }
             Token::Request => self.parse_request(),
+            // `verify "name" then ... end` (`verify` is not reserved)
+            Token::Ident(word) if word == "verify" && matches!(self.peek(), Token::Text(_)) => self.parse_verify(),
+            // `deprecated "message"` before a definition (`deprecated` is not reserved)
+            Token::Ident(word) if word == "deprecated" && matches!(self.peek(), Token::Text(_)) => self.parse_deprecated(),
+            _ if self.at_speaks() => Err(ParseError {
+                message: "A `speaks` pragma must come before the script's first statement".to_string(),
+                position: self.position,
+            }),
             // === Module System ===
             Token::Grove => self.parse_module_decl(),
             Token::Summon => self.parse_import(),
@@ -435,8 +529,8 @@ impl Parser {
                 };
                 self.advance();
 
-                // Check for optional type annotation: 'as Type'
-                let param_type = if self.match_token(Token::As) {
+                // Check for optional type annotation: 'as Type' or ': Type'
+                let param_type = if self.match_token(Token::As) || self.match_token(Token::Colon) {
                     Some(self.parse_type_annotation()?)
                 } else {
                     None
@@ -496,6 +590,8 @@ impl Parser {
             params,
             return_type,
             body,
+            swift: false,
+            deprecated: None,
             span: self.current_span(),
         })
     }
@@ -552,7 +648,17 @@ impl Parser {
         self.skip_newlines();
 
         let mut fields = Vec::new();
+        let mut deriving = Vec::new();
         while !matches!(self.current(), Token::End | Token::Eof) {
+            // `deriving A, B` closes the field list (a field may still be named `deriving`)
+            if matches!(self.current(), Token::Ident(word) if word == "deriving") && !matches!(self.peek(), Token::As) {
+                self.require(Feature::Deriving)?;
+                self.advance();
+                deriving = self.parse_deriving_list()?;
+                self.skip_newlines();
+                break;
+            }
+
             // Parse field: name as Type
             let field_name = match self.current() {
                 Token::Ident(n) => n.clone(),
@@ -583,13 +689,165 @@ impl Parser {
             name,
             type_params,
             fields,
+            deriving,
+            deprecated: None,
             span: self.current_span(),
         })
     }
 
+    /// Whether a `ritual Name` definition starts here
+    fn at_ritual(&self) -> bool {
+        matches!(self.current(), Token::Ident(word) if word == "ritual") && matches!(self.peek(), Token::Ident(_))
+    }
+
+    /// Parse a state machine and desugar it (see [`crate::ritual`]):
+    /// ritual Door with
+    ///     state Closed
+    ///     state Open
+    ///     on open from Closed to Open when powered
+    ///     entering Open then ... end
+    ///     leaving Open then ... end
+    /// end
+    fn parse_ritual_def(&mut self) -> ParseResult<Vec<AstNode>> {
+        self.require(Feature::Rituals)?;
+        let span = self.current_span();
+        self.advance(); // consume 'ritual'
+        let name = self.expect_ident("Expected ritual name after 'ritual'")?;
+        self.expect(Token::With)?;
+        self.skip_newlines();
+
+        let mut ritual = crate::ritual::Ritual {
+            name,
+            states: Vec::new(),
+            transitions: Vec::new(),
+            entering: Vec::new(),
+            leaving: Vec::new(),
+            span,
+        };
+        let start = self.position;
+        while !matches!(self.current(), Token::End | Token::Eof) {
+            match self.current() {
+                Token::Ident(word) if word == "state" => {
+                    self.advance();
+                    let state = self.expect_ident("Expected state name after 'state'")?;
+                    ritual.states.push(state);
+                }
+                Token::On => {
+                    self.advance();
+                    let event = match self.current() {
+                        Token::Ident(event) | Token::Text(event) => event.clone(),
+                        _ => {
+                            return Err(ParseError {
+                                message: "Expected event name after 'on'".to_string(),
+                                position: self.position,
+                            })
+                        }
+                    };
+                    self.advance();
+                    self.expect(Token::From)?;
+                    let mut from = vec![self.expect_ident("Expected state name after 'from'")?];
+                    while self.match_token(Token::Comma) {
+                        from.push(self.expect_ident("Expected state name after ','")?);
+                    }
+                    self.expect(Token::To)?;
+                    let to = self.expect_ident("Expected state name after 'to'")?;
+                    let guard = if self.match_token(Token::When) { Some(self.parse_expression()?) } else { None };
+                    ritual.transitions.push(crate::ritual::Transition { event, from, to, guard });
+                }
+                Token::Ident(word) if word == "entering" || word == "leaving" => {
+                    let entering = word == "entering";
+                    self.advance();
+                    let state = self.expect_ident("Expected state name for the action")?;
+                    self.expect(Token::Then)?;
+                    self.skip_newlines();
+                    let mut body = Vec::new();
+                    while !matches!(self.current(), Token::End | Token::Eof) {
+                        body.push(self.parse_statement()?);
+                        self.skip_newlines();
+                    }
+                    self.expect(Token::End)?;
+                    if entering {
+                        ritual.entering.push((state, body));
+                    } else {
+                        ritual.leaving.push((state, body));
+                    }
+                }
+                _ => {
+                    return Err(ParseError {
+                        message: "Expected 'state', 'on', 'entering' or 'leaving' in ritual".to_string(),
+                        position: self.position,
+                    })
+                }
+            }
+            self.skip_newlines();
+        }
+        self.expect(Token::End)?;
+
+        ritual.validate().map_err(|message| ParseError { message, position: start })?;
+        Ok(ritual.desugar())
+    }
+
+    /// Consume an identifier, or fail with `message`
+    fn expect_ident(&mut self, message: &str) -> ParseResult<String> {
+        match self.current() {
+            Token::Ident(name) => {
+                let name = name.clone();
+                self.advance();
+                Ok(name)
+            }
+            _ => Err(ParseError { message: message.to_string(), position: self.position }),
+        }
+    }
+
+    /// Parse the aspect names of a deriving clause: `Display, Ordered`
+    fn parse_deriving_list(&mut self) -> ParseResult<Vec<String>> {
+        let mut aspects = Vec::new();
+        loop {
+            match self.current() {
+                Token::Ident(aspect) => {
+                    aspects.push(aspect.clone());
+                    self.advance();
+                }
+                _ => {
+                    return Err(ParseError {
+                        message: "Expected aspect name in deriving clause".to_string(),
+                        position: self.position,
+                    })
+                }
+            }
+
+            if matches!(self.current(), Token::Comma) {
+                self.advance(); // consume comma
+            } else {
+                return Ok(aspects);
+            }
+        }
+    }
+
+    /// Parse a variant case discriminant: an integer, optionally negative
+    fn parse_discriminant(&mut self) -> ParseResult<i64> {
+        let negative = matches!(self.current(), Token::Minus);
+        if negative {
+            self.advance();
+        }
+
+        match self.current() {
+            Token::Number(n) if n % 1.0 == 0.0 && n.abs() <= i64::MAX as f64 => {
+                let value = *n as i64;
+                self.advance();
+                Ok(if negative { -value } else { value })
+            }
+            _ => Err(ParseError {
+                message: "Expected an integer discriminant after 'is'".to_string(),
+                position: self.position,
+            }),
+        }
+    }
+
     /// Parse: variant Color then Red, Green, Blue end
     /// or with data: variant Message then Quit, Move(x: Number, y: Number) end
     /// or with generics: variant Option<T> then Some(value: T), None end
+    /// or with discriminants: variant Status with Ok is 0, Error is 1 end
     fn parse_variant_def(&mut self) -> ParseResult<AstNode> {
         self.expect(Token::Variant)?;
 
@@ -636,7 +894,12 @@ impl Parser {
             Vec::new() // No generic type parameters
         };
 
-        self.expect(Token::Then)?;
+        // Cases follow `then`, or `with` as in form definitions
+        if matches!(self.current(), Token::With) {
+            self.advance();
+        } else {
+            self.expect(Token::Then)?;
+        }
         self.skip_newlines();
 
         let mut variants = Vec::new();
@@ -700,9 +963,18 @@ impl Parser {
                 Vec::new() // Unit variant (no fields)
             };
 
+            // Optional explicit discriminant: Name is 3
+            let discriminant = if matches!(self.current(), Token::Is) {
+                self.advance();
+                Some(self.parse_discriminant()?)
+            } else {
+                None
+            };
+
             variants.push(VariantCase {
                 name: variant_name,
                 fields,
+                discriminant,
             });
 
             // Handle comma separator between variants (optional)
@@ -984,6 +1256,13 @@ impl Parser {
         while matches!(self.current(), Token::When | Token::Otherwise) {
             if self.match_token(Token::When) {
                 let pattern = self.parse_pattern()?;
+                let guard = if self.check(&Token::Where) {
+                    self.require(Feature::MatchGuards)?;
+                    self.advance();
+                    Some(self.parse_expression()?)
+                } else {
+                    None
+                };
                 self.expect(Token::Then)?;
                 self.skip_newlines();
 
@@ -996,7 +1275,7 @@ impl Parser {
                     self.skip_newlines();
                 }
 
-                arms.push(MatchArm { pattern, body });
+                arms.push(MatchArm { pattern, guard, body });
             } else if self.match_token(Token::Otherwise) {
                 self.expect(Token::Then)?;
                 self.skip_newlines();
@@ -1009,19 +1288,35 @@ impl Parser {
 
                 arms.push(MatchArm {
                     pattern: Pattern::Wildcard,
+                    guard: None,
                     body,
                 });
                 break;
             }
         }
 
+        let span = self.current_span();
         self.expect(Token::End)?;
 
-        Ok(AstNode::MatchStmt { value, arms, span: self.current_span() })
+        Ok(AstNode::MatchStmt { value, arms, span })
     }
 
-    /// Parse pattern for match
+    /// Parse pattern for match: one alternative, or several joined by `or`
     fn parse_pattern(&mut self) -> ParseResult<Pattern> {
+        let first = self.parse_single_pattern()?;
+        if !self.check(&Token::Or) {
+            return Ok(first);
+        }
+        self.require(Feature::OrPatterns)?;
+        let mut alternatives = vec![first];
+        while self.match_token(Token::Or) {
+            alternatives.push(self.parse_single_pattern()?);
+        }
+        Ok(Pattern::Or(alternatives))
+    }
+
+    /// Parse a pattern without `or` alternatives
+    fn parse_single_pattern(&mut self) -> ParseResult<Pattern> {
         match self.current() {
             Token::Number(n) => {
                 let val = *n;
@@ -1029,6 +1324,18 @@ impl Parser {
                 self.advance();
                 Ok(Pattern::Literal(Box::new(AstNode::Number { value: val, span })))
             }
+            Token::BigInt(value) => {
+                let value = value.clone();
+                let span = self.current_span();
+                self.advance();
+                Ok(Pattern::Literal(Box::new(AstNode::BigInt { value, span })))
+            }
+            Token::Integer(value) => {
+                let value = *value;
+                let span = self.current_span();
+                self.advance();
+                Ok(Pattern::Literal(Box::new(AstNode::Integer { value, span })))
+            }
             Token::Text(s) => {
                 let val = s.clone();
                 let span = self.current_span();
@@ -1041,6 +1348,23 @@ impl Parser {
                 self.advance();
                 Ok(Pattern::Literal(Box::new(AstNode::Truth { value: val, span })))
             }
+            // `starts "gw:" bound rest` / `ends ".gw"` (neither word is reserved)
+            Token::Ident(word) if (word == "starts" || word == "ends") && matches!(self.peek(), Token::Text(_)) => {
+                let side = if word == "starts" { AffixSide::Prefix } else { AffixSide::Suffix };
+                self.advance();
+                let affix = match self.current() {
+                    Token::Text(affix) => affix.clone(),
+                    _ => String::new(),
+                };
+                self.advance();
+                let rest = if matches!(self.current(), Token::Ident(word) if word == "bound") {
+                    self.advance();
+                    Some(self.expect_ident("Expected a name after 'bound'")?)
+                } else {
+                    None
+                };
+                Ok(Pattern::Affix { side, affix, rest })
+            }
             Token::Ident(name) => {
                 let n = name.clone();
                 self.advance();
@@ -1075,7 +1399,7 @@ impl Parser {
                         // Single field - simple case
                         Ok(Pattern::Enum {
                             variant: n,
-                            inner: Some(Box::new(inner_patterns.into_iter().next().unwrap())),
+                            inner: inner_patterns.pop().map(Box::new),
                         })
                     } else {
                         // Multiple fields - we need a way to represent this
@@ -1087,7 +1411,7 @@ impl Parser {
                                 AstNode::List {
                                     elements: inner_patterns.into_iter()
                                         .map(|p| match p {
-                                            Pattern::Ident(name) => AstNode::Ident { name, span: SourceSpan::unknown() },
+                                            Pattern::Ident(name) => AstNode::Ident { name, span: SourceSpan::unknown(), slot: None },
                                             _ => AstNode::Nothing { span: SourceSpan::unknown() }, // Placeholder
                                         })
                                         .collect(),
@@ -1141,6 +1465,26 @@ impl Parser {
                 })
             }
 
+            // `(x, 0)`, or `(x,)` for a tuple of one
+            Token::LeftParen => {
+                self.advance();
+                let first = self.parse_pattern()?;
+                if !matches!(self.current(), Token::Comma) {
+                    self.expect(Token::RightParen)?;
+                    return Ok(first);
+                }
+                self.require(Feature::Tuples)?;
+                let mut elements = vec![first];
+                while self.match_token(Token::Comma) {
+                    if matches!(self.current(), Token::RightParen) {
+                        break;
+                    }
+                    elements.push(self.parse_pattern()?);
+                }
+                self.expect(Token::RightParen)?;
+                Ok(Pattern::Tuple(elements))
+            }
+
             _ => Err(ParseError {
                 message: "Expected pattern".to_string(),
                 position: self.position,
@@ -1149,6 +1493,183 @@ impl Parser {
     }
 
     /// Parse: attempt ... harmonize on Error then ... end
+    fn parse_defer(&mut self) -> ParseResult<AstNode> {
+        self.expect(Token::Defer)?;
+        self.skip_newlines();
+
+        let mut body = Vec::new();
+        while !matches!(self.current(), Token::End | Token::Eof) {
+            body.push(self.parse_statement()?);
+            self.skip_newlines();
+        }
+
+        self.expect(Token::End)?;
+
+        Ok(AstNode::DeferStmt { body, span: self.current_span() })
+    }
+
+    /// Parse: together ... end
+    fn parse_together(&mut self) -> ParseResult<AstNode> {
+        self.require(Feature::TogetherScopes)?;
+        let span = self.current_span();
+        self.advance(); // consume 'together'
+        self.skip_newlines();
+
+        let mut body = Vec::new();
+        while !matches!(self.current(), Token::End | Token::Eof) {
+            body.push(self.parse_statement()?);
+            self.skip_newlines();
+        }
+
+        self.expect(Token::End)?;
+
+        Ok(AstNode::TogetherBlock { body, span })
+    }
+
+    /// Parse: migrate store "name" from 1 to 2 with chant(old) then ... end
+    fn parse_migrate_store(&mut self) -> ParseResult<AstNode> {
+        self.require(Feature::StoreMigrations)?;
+        let span = self.current_span();
+        self.advance(); // consume 'migrate'
+        self.advance(); // consume 'store'
+
+        let namespace = match self.current() {
+            Token::Text(namespace) => namespace.clone(),
+            _ => {
+                return Err(ParseError {
+                    message: "Expected the store namespace after 'migrate store'".to_string(),
+                    position: self.position,
+                })
+            }
+        };
+        self.advance();
+
+        self.expect(Token::From)?;
+        let from = self.parse_store_version()?;
+        self.expect(Token::To)?;
+        let to = self.parse_store_version()?;
+        if to <= from {
+            return Err(ParseError {
+                message: format!("A migration must go to a later version, not from {} to {}", from, to),
+                position: self.position,
+            });
+        }
+
+        self.expect(Token::With)?;
+        self.expect(Token::Chant)?;
+        self.expect(Token::LeftParen)?;
+        let param = match self.current() {
+            Token::Ident(param) => param.clone(),
+            _ => {
+                return Err(ParseError {
+                    message: "Expected the name of the stored value".to_string(),
+                    position: self.position,
+                })
+            }
+        };
+        self.advance();
+        self.expect(Token::RightParen)?;
+        self.expect(Token::Then)?;
+        self.skip_newlines();
+
+        let mut body = Vec::new();
+        while !matches!(self.current(), Token::End | Token::Eof) {
+            body.push(self.parse_statement()?);
+            self.skip_newlines();
+        }
+
+        self.expect(Token::End)?;
+
+        Ok(AstNode::MigrateStore { namespace, from, to, param, body, span })
+    }
+
+    /// Parse a store version: a whole number
+    fn parse_store_version(&mut self) -> ParseResult<u32> {
+        match self.current() {
+            Token::Number(n) if n % 1.0 == 0.0 && *n >= 0.0 && *n <= f64::from(u32::MAX) => {
+                let version = *n as u32;
+                self.advance();
+                Ok(version)
+            }
+            _ => Err(ParseError {
+                message: "Expected a whole version number".to_string(),
+                position: self.position,
+            }),
+        }
+    }
+
+    /// Parse: deprecated "message" [use replacement], then the chant, form
+    /// or grove it marks on the following line
+    fn parse_deprecated(&mut self) -> ParseResult<AstNode> {
+        let position = self.position;
+        self.advance(); // consume 'deprecated'
+
+        let message = match self.current() {
+            Token::Text(message) => message.clone(),
+            _ => {
+                return Err(ParseError {
+                    message: "Expected the deprecation message".to_string(),
+                    position: self.position,
+                })
+            }
+        };
+        self.advance();
+
+        let mut replacement = None;
+        if matches!(self.current(), Token::Ident(word) if word == "use") {
+            self.advance();
+            let mut path = self.expect_ident("Expected the replacement's name after 'use'")?;
+            while self.match_token(Token::Dot) {
+                path.push('.');
+                path.push_str(&self.expect_ident("Expected a name after '.'")?);
+            }
+            replacement = Some(path);
+        }
+        self.skip_newlines();
+
+        let mut item = self.parse_statement()?;
+        match &mut item {
+            AstNode::ChantDef { deprecated, .. } | AstNode::FormDef { deprecated, .. } | AstNode::ModuleDecl { deprecated, .. } => {
+                *deprecated = Some(Deprecation { message, replacement });
+                Ok(item)
+            }
+            _ => Err(ParseError {
+                message: "`deprecated` must come before a chant, form or grove".to_string(),
+                position,
+            }),
+        }
+    }
+
+    /// Parse: verify "name" then body end
+    fn parse_verify(&mut self) -> ParseResult<AstNode> {
+        self.require(Feature::VerifyBlocks)?;
+        let span = self.current_span();
+        self.advance(); // consume 'verify'
+
+        let name = match self.current() {
+            Token::Text(name) => name.clone(),
+            _ => {
+                return Err(ParseError {
+                    message: "Expected the verify block's name".to_string(),
+                    position: self.position,
+                })
+            }
+        };
+        self.advance();
+        self.expect(Token::Then)?;
+        self.skip_newlines();
+
+        let mut body = Vec::new();
+        while !matches!(self.current(), Token::End | Token::Eof) {
+            body.push(self.parse_statement()?);
+            self.skip_newlines();
+        }
+
+        self.expect(Token::End)?;
+
+        Ok(AstNode::VerifyBlock { name, body, span })
+    }
+
     fn parse_attempt(&mut self) -> ParseResult<AstNode> {
         self.expect(Token::Attempt)?;
         self.skip_newlines();
@@ -1250,6 +1771,11 @@ impl Parser {
 
         // Parse module body until 'end'
         while !matches!(self.current(), Token::End | Token::Eof) {
+            if self.at_ritual() {
+                body.extend(self.parse_ritual_def()?);
+                self.skip_newlines();
+                continue;
+            }
             let stmt = self.parse_statement()?;
 
             // If it's an Export statement, extract the items
@@ -1267,6 +1793,7 @@ impl Parser {
             name,
             body,
             exports,
+            deprecated: None,
             span: self.current_span(),
         })
     }
@@ -1599,6 +2126,45 @@ impl Parser {
         }
     }
 
+    /// Parse call arguments up to the closing parenthesis: positional ones,
+    /// then any named ones (`x: 10`), returning the values in call order and
+    /// the names of the trailing named ones
+    fn parse_call_args(&mut self) -> ParseResult<(Vec<AstNode>, Vec<String>)> {
+        let mut args = Vec::new();
+        let mut arg_names: Vec<String> = Vec::new();
+        if matches!(self.current(), Token::RightParen) {
+            return Ok((args, arg_names));
+        }
+        loop {
+            match (self.current().clone(), self.peek()) {
+                (Token::Ident(name), Token::Colon) => {
+                    self.require(Feature::NamedArguments)?;
+                    if arg_names.contains(&name) {
+                        return Err(ParseError {
+                            message: format!("Duplicate named argument '{}'", name),
+                            position: self.position,
+                        });
+                    }
+                    self.advance(); // consume name
+                    self.advance(); // consume ':'
+                    args.push(self.parse_expression()?);
+                    arg_names.push(name);
+                }
+                _ if !arg_names.is_empty() => {
+                    return Err(ParseError {
+                        message: "Positional arguments must come before named arguments".to_string(),
+                        position: self.position,
+                    });
+                }
+                _ => args.push(self.parse_expression()?),
+            }
+            if !self.match_token(Token::Comma) {
+                break;
+            }
+        }
+        Ok((args, arg_names))
+    }
+
     /// Parse postfix: call, field access, index
     fn parse_postfix(&mut self) -> ParseResult<AstNode> {
         let mut expr = self.parse_primary()?;
@@ -1610,6 +2176,8 @@ impl Parser {
                     self.advance();
                     let field = match self.current() {
                         Token::Ident(f) => f.clone(),
+                        // Members such as `List.first` or `Cell.set` share a keyword's spelling
+                        keyword if keyword.is_keyword() => keyword.description().to_string(),
                         _ => {
                             return Err(ParseError {
                                 message: "Expected field name after '.'".to_string(),
@@ -1646,22 +2214,13 @@ impl Parser {
                         Token::LeftParen => {
                             // Generic function call: identity<Number>(42)
                             self.advance();
-                            let mut args = Vec::new();
-
-                            if !matches!(self.current(), Token::RightParen) {
-                                loop {
-                                    args.push(self.parse_expression()?);
-                                    if !self.match_token(Token::Comma) {
-                                        break;
-                                    }
-                                }
-                            }
-
+                            let (args, arg_names) = self.parse_call_args()?;
                             self.expect(Token::RightParen)?;
                             expr = AstNode::Call {
                                 callee: Box::new(expr),
                                 type_args,
                                 args,
+                                arg_names,
                                 span: self.current_span(),
                             };
                         }
@@ -1723,22 +2282,13 @@ impl Parser {
                     // Non-generic function call
                     let span = self.current_span();
                     self.advance();
-                    let mut args = Vec::new();
-
-                    if !matches!(self.current(), Token::RightParen) {
-                        loop {
-                            args.push(self.parse_expression()?);
-                            if !self.match_token(Token::Comma) {
-                                break;
-                            }
-                        }
-                    }
-
+                    let (args, arg_names) = self.parse_call_args()?;
                     self.expect(Token::RightParen)?;
                     expr = AstNode::Call {
                         callee: Box::new(expr),
                         type_args: Vec::new(), // No type arguments
                         args,
+                        arg_names,
                         span,
                     };
                 }
@@ -1801,6 +2351,21 @@ impl Parser {
                         break;
                     }
                 }
+                Token::In => {
+                    // Unit conversion: delay in s
+                    let unit = match self.peek() {
+                        Token::Ident(name) if crate::units::unit(name).is_some() => name.clone(),
+                        _ => break,
+                    };
+                    let span = self.current_span();
+                    self.advance(); // consume 'in'
+                    self.advance(); // consume the unit
+                    expr = AstNode::InUnit {
+                        value: Box::new(expr),
+                        unit,
+                        span,
+                    };
+                }
                 Token::Question => {
                     // Try operator: expr?
                     let span = self.current_span();
@@ -1823,8 +2388,26 @@ impl Parser {
             Token::Number(n) => {
                 let span = self.current_span();
                 self.advance();
+                // A unit right after the number: `10 ms`
+                if let Token::Ident(name) = self.current() {
+                    if crate::units::unit(name).is_some() {
+                        let unit = name.clone();
+                        self.advance();
+                        return Ok(AstNode::Measure { value: n, unit, span });
+                    }
+                }
                 Ok(AstNode::Number { value: n, span })
             }
+            Token::BigInt(value) => {
+                let span = self.current_span();
+                self.advance();
+                Ok(AstNode::BigInt { value, span })
+            }
+            Token::Integer(value) => {
+                let span = self.current_span();
+                self.advance();
+                Ok(AstNode::Integer { value, span })
+            }
             Token::Text(s) => {
                 let span = self.current_span();
                 self.advance();
@@ -1843,13 +2426,28 @@ impl Parser {
             Token::Ident(name) => {
                 let span = self.current_span();
                 self.advance();
-                Ok(AstNode::Ident { name, span })
+                Ok(AstNode::Ident { name, span, slot: None })
             }
             Token::LeftParen => {
+                let span = self.current_span();
                 self.advance();
                 let expr = self.parse_expression()?;
+                if !matches!(self.current(), Token::Comma) {
+                    self.expect(Token::RightParen)?;
+                    return Ok(expr);
+                }
+
+                // `(a, b)`, or `(a,)` for a tuple of one
+                self.require(Feature::Tuples)?;
+                let mut elements = vec![expr];
+                while self.match_token(Token::Comma) {
+                    if matches!(self.current(), Token::RightParen) {
+                        break;
+                    }
+                    elements.push(self.parse_expression()?);
+                }
                 self.expect(Token::RightParen)?;
-                Ok(expr)
+                Ok(AstNode::Tuple { elements, span })
             }
             Token::LeftBracket => self.parse_list(),
             Token::LeftBrace => self.parse_map(),
@@ -2045,20 +2643,43 @@ impl Parser {
 
                     // Special case for List to maintain backward compatibility
                     if name == "List" && type_args.len() == 1 {
-                        Ok(TypeAnnotation::List(Box::new(type_args.into_iter().next().unwrap())))
-                    } else {
-                        Ok(TypeAnnotation::Parametrized {
-                            name,
-                            type_args,
-                        })
+                        if let Some(element) = type_args.pop() {
+                            return Ok(TypeAnnotation::List(Box::new(element)));
+                        }
                     }
+                    Ok(TypeAnnotation::Parametrized {
+                        name,
+                        type_args,
+                    })
+                } else if name == "array" && matches!(self.current(), Token::Ident(word) if word == "of") {
+                    // Fixed-size array: array of Number length 16
+                    self.advance(); // consume 'of'
+                    let element = Box::new(self.parse_type_annotation()?);
+                    if !matches!(self.current(), Token::Ident(word) if word == "length") {
+                        return Err(ParseError {
+                            message: "Expected 'length' after the array element type".to_string(),
+                            position: self.position,
+                        });
+                    }
+                    self.advance(); // consume 'length'
+                    let length = match self.current() {
+                        Token::Number(n) if *n >= 0.0 && n % 1.0 == 0.0 => *n as usize,
+                        _ => {
+                            return Err(ParseError {
+                                message: "Expected a whole number as the array length".to_string(),
+                                position: self.position,
+                            })
+                        }
+                    };
+                    self.advance();
+                    Ok(TypeAnnotation::Array { element, length })
                 } else if name == "Map" {
                     Ok(TypeAnnotation::Map)
                 } else {
                     // Simple type: could be Named (Number, Text) or Generic (T, U)
                     // For now, treat single uppercase letters as generic type parameters
                     // The semantic analyzer will determine the actual meaning based on scope
-                    if name.len() == 1 && name.chars().next().unwrap().is_uppercase() {
+                    if name.len() == 1 && name.chars().all(char::is_uppercase) {
                         Ok(TypeAnnotation::Generic(name))
                     } else {
                         Ok(TypeAnnotation::Named(name))
@@ -0,0 +1,581 @@
+//! # Compilation Pipeline
+//!
+//! A single builder for running Glimmer-Weave source through the front end
+//! and into one of the back ends:
+//!
+//! ```text
+//! source → tokens → ast → semantic → [optimize] → eval | bytecode | asm | elf
+//! ```
+//!
+//! Every stage reports into one [`Diagnostics`] accumulator, and hooks can
+//! inspect or rewrite the intermediate result between stages. The pipeline
+//! stops after the first stage that leaves an error behind. Before
+//! evaluating, it resolves variable references to scope slots (see
+//! [`resolve_scopes`]).
+//!
+//! [`CompilerPipeline::compile_project`] runs the same stages over a program
+//! split into module files: the module graph is loaded through a
+//! [`ModuleResolver`], imports are checked against exports across modules, and
+//! the modules are linked into one program before the back end runs.
+//!
+//! ```
+//! use glimmer_weave::pipeline::{CompilerPipeline, Output, Target};
+//! use glimmer_weave::Value;
+//!
+//! let mut pipeline = CompilerPipeline::new().optimize(true);
+//! let output = pipeline.run("bind x to 20\nx + 22", Target::Eval).unwrap();
+//! assert!(matches!(output, Output::Value(Value::Number(n)) if n == 42.0));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::format;
+use alloc::vec;
+
+use crate::ast::AstNode;
+use crate::bytecode::BytecodeChunk;
+use crate::cse::SubexpressionEliminator;
+use crate::error_formatter::{Diagnostic, Diagnostics};
+use crate::eval::{Evaluator, Value};
+use crate::i18n::MessageCatalogs;
+use crate::inline::Inliner;
+use crate::lexer::Lexer;
+use crate::loop_opt::LoopOptimizer;
+use crate::module_resolver::ModuleResolver;
+use crate::monomorphize::Monomorphizer;
+use crate::parser::Parser;
+use crate::profile::{Profile, DEFAULT_HOT_CALL_THRESHOLD};
+use crate::target::TargetSpec;
+use crate::script_prelude::Prelude;
+use crate::semantic::{resolve_scopes, SemanticAnalyzer};
+use crate::token::PositionedToken;
+
+/// Back end the pipeline finishes with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// Run the program in the tree-walking interpreter
+    Eval,
+    /// Compile to a bytecode chunk for the VM
+    Bytecode,
+    /// Generate x86-64 assembly text
+    Asm,
+    /// Generate an ELF64 object file (requires an assembler, see [`CompilerPipeline::assembler`])
+    Elf,
+}
+
+/// Result of the back-end stage
+#[derive(Debug, Clone)]
+pub enum Output {
+    Value(Value),
+    Bytecode(BytecodeChunk),
+    Asm(String),
+    Elf(Vec<u8>),
+}
+
+type TokenHook = Box<dyn FnMut(&mut Vec<PositionedToken>, &mut Diagnostics)>;
+type AstHook = Box<dyn FnMut(&mut Vec<AstNode>, &mut Diagnostics)>;
+type Assembler = Box<dyn Fn(&str) -> Result<Vec<u8>, String>>;
+
+/// Builder that wires the lexer, parser, analyzers and back ends together
+pub struct CompilerPipeline {
+    semantic: bool,
+    optimize: bool,
+    after_lex: Option<TokenHook>,
+    after_parse: Option<AstHook>,
+    after_semantic: Option<AstHook>,
+    after_optimize: Option<AstHook>,
+    assembler: Option<Assembler>,
+    prelude: Prelude,
+    evaluator: Evaluator,
+    profile: Option<Profile>,
+    hot_call_threshold: u64,
+    target_spec: TargetSpec,
+    messages: Option<MessageCatalogs>,
+    diagnostics: Diagnostics,
+}
+
+impl Default for CompilerPipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompilerPipeline {
+    /// Create a pipeline with semantic analysis on and optimization off
+    pub fn new() -> Self {
+        CompilerPipeline {
+            semantic: true,
+            optimize: false,
+            after_lex: None,
+            after_parse: None,
+            after_semantic: None,
+            after_optimize: None,
+            assembler: None,
+            prelude: Prelude::default(),
+            evaluator: Evaluator::new(),
+            profile: None,
+            hot_call_threshold: DEFAULT_HOT_CALL_THRESHOLD,
+            target_spec: TargetSpec::X86_64_SYSV,
+            messages: None,
+            diagnostics: Diagnostics::new(),
+        }
+    }
+
+    /// Enable or disable the semantic analysis stage
+    pub fn semantic(mut self, enabled: bool) -> Self {
+        self.semantic = enabled;
+        self
+    }
+
+    /// Enable or disable the optimization stage
+    ///
+    /// Only the compiled back ends have passes here: generic chants are
+    /// monomorphized, since they cannot dispatch on type arguments at
+    /// runtime, then calls to small chants inlined (see [`crate::inline`]),
+    /// loops optimized (see [`crate::loop_opt`]) and repeated pure
+    /// expressions computed once (see [`crate::cse`]). [`Target::Eval`]
+    /// resolves variable references to scope slots either way.
+    pub fn optimize(mut self, enabled: bool) -> Self {
+        self.optimize = enabled;
+        self
+    }
+
+    /// Guide optimization with a profile from an earlier run
+    ///
+    /// Hot chants are inlined regardless of size, chants the run never
+    /// called are not inlined, and native code places the more frequent
+    /// branch of each `should` on the fall-through path (see
+    /// [`crate::profile`]). Used only when optimization is enabled.
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    /// Calls after which a profiled chant counts as hot (default
+    /// [`DEFAULT_HOT_CALL_THRESHOLD`])
+    pub fn hot_call_threshold(mut self, calls: u64) -> Self {
+        self.hot_call_threshold = calls;
+        self
+    }
+
+    /// Machine [`Target::Asm`] and [`Target::Elf`] compile for (default
+    /// [`TargetSpec::X86_64_SYSV`])
+    ///
+    /// The assembler must accept the target's assembly; compiling fails for
+    /// targets without a code generator (see [`crate::target`]).
+    pub fn target_spec(mut self, spec: TargetSpec) -> Self {
+        self.target_spec = spec;
+        self
+    }
+
+    /// Run a hook on the token stream before parsing
+    pub fn after_lex(mut self, hook: impl FnMut(&mut Vec<PositionedToken>, &mut Diagnostics) + 'static) -> Self {
+        self.after_lex = Some(Box::new(hook));
+        self
+    }
+
+    /// Run a hook on the AST right after parsing
+    pub fn after_parse(mut self, hook: impl FnMut(&mut Vec<AstNode>, &mut Diagnostics) + 'static) -> Self {
+        self.after_parse = Some(Box::new(hook));
+        self
+    }
+
+    /// Run a hook on the AST after semantic analysis
+    pub fn after_semantic(mut self, hook: impl FnMut(&mut Vec<AstNode>, &mut Diagnostics) + 'static) -> Self {
+        self.after_semantic = Some(Box::new(hook));
+        self
+    }
+
+    /// Run a hook on the AST after optimization (only when optimization is enabled)
+    pub fn after_optimize(mut self, hook: impl FnMut(&mut Vec<AstNode>, &mut Diagnostics) + 'static) -> Self {
+        self.after_optimize = Some(Box::new(hook));
+        self
+    }
+
+    /// Set the assembler that turns generated assembly into machine code for [`Target::Elf`]
+    pub fn assembler(mut self, assembler: impl Fn(&str) -> Result<Vec<u8>, String> + 'static) -> Self {
+        self.assembler = Some(Box::new(assembler));
+        self
+    }
+
+    /// Set the builtins in scope for semantic analysis and evaluation
+    ///
+    /// This replaces the evaluator with a fresh one holding `prelude`.
+    pub fn prelude(mut self, prelude: Prelude) -> Self {
+        self.evaluator = Evaluator::with_prelude(&prelude);
+        if let Some(messages) = &self.messages {
+            self.evaluator.set_messages(messages.clone());
+        }
+        self.prelude = prelude;
+        self
+    }
+
+    /// Use a preconfigured evaluator for [`Target::Eval`]
+    pub fn evaluator(mut self, evaluator: Evaluator) -> Self {
+        self.evaluator = evaluator;
+        if let Some(messages) = &self.messages {
+            self.evaluator.set_messages(messages.clone());
+        }
+        self
+    }
+
+    /// Localize `tr` with `messages`, and reject `tr` calls whose literal
+    /// key is missing from their default catalog during semantic analysis
+    pub fn messages(mut self, messages: MessageCatalogs) -> Self {
+        self.evaluator.set_messages(messages.clone());
+        self.messages = Some(messages);
+        self
+    }
+
+    /// The evaluator used for [`Target::Eval`]; its state persists across runs
+    pub fn evaluator_mut(&mut self) -> &mut Evaluator {
+        &mut self.evaluator
+    }
+
+    /// Diagnostics reported by the most recent run, including warnings
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// Run `source` through every stage and finish with `target`
+    ///
+    /// On failure, returns all diagnostics collected up to and including the
+    /// stage that failed.
+    pub fn run(&mut self, source: &str, target: Target) -> Result<Output, Diagnostics> {
+        self.diagnostics = Diagnostics::new();
+        match self.run_stages(source, target) {
+            Some(output) if !self.diagnostics.has_errors() => Ok(output),
+            _ => Err(self.diagnostics.clone()),
+        }
+    }
+
+    /// Compile a program spread over several module files
+    ///
+    /// Loads `entry_path` and every module it imports through `resolver`,
+    /// checks each import against the exports of the module it names, then
+    /// links all modules and the entry program into one program for `target`
+    /// (a single bytecode image, assembly listing or ELF object).
+    ///
+    /// Every module sees the resolver's prelude rather than the pipeline's,
+    /// and [`Target::Eval`] runs in a fresh evaluator holding that prelude.
+    ///
+    /// The back ends have one global namespace: module bodies are hoisted to
+    /// the top level, so the same top-level name defined by two modules is
+    /// reported as a link error.
+    pub fn compile_project(
+        &mut self,
+        entry_path: &str,
+        resolver: &mut ModuleResolver,
+        target: Target,
+    ) -> Result<Output, Diagnostics> {
+        self.diagnostics = Diagnostics::new();
+        match self.project_stages(entry_path, resolver, target) {
+            Some(output) if !self.diagnostics.has_errors() => Ok(output),
+            _ => Err(self.diagnostics.clone()),
+        }
+    }
+
+    fn project_stages(&mut self, entry_path: &str, resolver: &mut ModuleResolver, target: Target) -> Option<Output> {
+        // Module graph, with every dependency ahead of its importers
+        let order = match resolver.load_graph(entry_path) {
+            Ok(order) => order,
+            Err(e) => {
+                self.diagnostics.push(Diagnostic::error(format!("Module error: {:?}", e)));
+                return None;
+            }
+        };
+
+        let mut ast = Vec::new();
+        for path in &order {
+            let Some(module) = resolver.get_module(path) else { continue };
+            let mut nodes = module.ast.clone();
+            let mut qualifiers = BTreeMap::new();
+            for node in nodes.iter_mut() {
+                bind_imports(node, path, resolver, &mut qualifiers);
+            }
+            for node in nodes.iter_mut() {
+                qualify_module_access(node, &qualifiers);
+            }
+            ast.extend(nodes);
+        }
+        if let Some(hook) = self.after_parse.as_mut() {
+            hook(&mut ast, &mut self.diagnostics);
+        }
+        self.checkpoint()?;
+
+        self.analyze(&mut ast, resolver.prelude())?;
+        let ast = self.link(ast)?;
+
+        let project_evaluator = Evaluator::with_prelude(resolver.prelude());
+        let evaluator = core::mem::replace(&mut self.evaluator, project_evaluator);
+        let output = self.optimize_and_emit(ast, target);
+        self.evaluator = evaluator;
+        output
+    }
+
+    /// Hoist module bodies into one flat program
+    fn link(&mut self, ast: Vec<AstNode>) -> Option<Vec<AstNode>> {
+        let mut owners: BTreeMap<String, String> = BTreeMap::new();
+        let mut linked = Vec::new();
+
+        for node in ast {
+            let (owner, nodes) = match node {
+                AstNode::ModuleDecl { name, body, .. } => (format!("module '{}'", name), body),
+                AstNode::Import { .. } | AstNode::Export { .. } => continue,
+                node => (String::from("the entry program"), vec![node]),
+            };
+            for mut node in nodes {
+                if matches!(node, AstNode::Import { .. } | AstNode::Export { .. }) {
+                    continue;
+                }
+                if let Some(name) = top_level_name(&node) {
+                    match owners.get(name) {
+                        Some(existing) if *existing != owner => {
+                            self.diagnostics.push(Diagnostic::error(format!(
+                                "Link error: '{}' is defined by both {} and {}",
+                                name, existing, owner
+                            )));
+                        }
+                        Some(_) => {}
+                        None => {
+                            owners.insert(name.clone(), owner.clone());
+                        }
+                    }
+                }
+                unqualify_module_access(&mut node);
+                linked.push(node);
+            }
+        }
+
+        self.checkpoint()?;
+        Some(linked)
+    }
+
+    /// Lex, parse and analyze `source` without compiling or running it
+    ///
+    /// Returns every diagnostic collected, warnings included; they are also
+    /// left in [`CompilerPipeline::diagnostics`].
+    pub fn check(&mut self, source: &str) -> Diagnostics {
+        self.diagnostics = Diagnostics::new();
+        let _ = self.front_end(source);
+        self.diagnostics.clone()
+    }
+
+    fn run_stages(&mut self, source: &str, target: Target) -> Option<Output> {
+        let ast = self.front_end(source)?;
+        self.optimize_and_emit(ast, target)
+    }
+
+    /// Lexing, parsing and semantic analysis
+    fn front_end(&mut self, source: &str) -> Option<Vec<AstNode>> {
+        // Lexing
+        let mut tokens = Lexer::new(source).tokenize_positioned();
+        if let Some(hook) = self.after_lex.as_mut() {
+            hook(&mut tokens, &mut self.diagnostics);
+        }
+        self.checkpoint()?;
+
+        // Parsing
+        let mut ast = match Parser::new(tokens.clone()).parse() {
+            Ok(ast) => ast,
+            Err(e) => {
+                let mut diagnostic = Diagnostic::error(format!("Parse error: {}", e.message));
+                if let Some(token) = tokens.get(e.position) {
+                    diagnostic = diagnostic.with_primary_label(token.source_span(), "here");
+                }
+                diagnostic.suggestions = crate::fixit::for_parse_error(source, &tokens, &e);
+                self.diagnostics.push(diagnostic);
+                return None;
+            }
+        };
+        if let Some(hook) = self.after_parse.as_mut() {
+            hook(&mut ast, &mut self.diagnostics);
+        }
+        self.checkpoint()?;
+
+        let prelude = self.prelude.clone();
+        self.analyze(&mut ast, &prelude)?;
+        Some(ast)
+    }
+
+    /// Semantic analysis stage
+    fn analyze(&mut self, ast: &mut Vec<AstNode>, prelude: &Prelude) -> Option<()> {
+        if self.semantic {
+            let mut analyzer = SemanticAnalyzer::with_prelude(prelude);
+            if let Some(messages) = &self.messages {
+                analyzer.check_message_keys(messages.default_catalog().cloned().unwrap_or_default());
+            }
+            if let Err(errors) = analyzer.analyze(ast) {
+                for error in errors {
+                    let mut diagnostic = Diagnostic::error(format!("Semantic error: {:?}", error));
+                    if let Some(span) = error.span() {
+                        diagnostic = diagnostic.with_primary_label(span.clone(), "here");
+                    }
+                    diagnostic.suggestions = error.suggestions();
+                    self.diagnostics.push(diagnostic);
+                }
+            }
+            for warning in analyzer.warnings() {
+                let mut diagnostic = Diagnostic::warning(format!("Semantic warning: {:?}", warning));
+                if let Some(span) = warning.span() {
+                    diagnostic = diagnostic.with_primary_label(span.clone(), "here");
+                }
+                self.diagnostics.push(diagnostic);
+            }
+            if let Some(hook) = self.after_semantic.as_mut() {
+                hook(ast, &mut self.diagnostics);
+            }
+            self.checkpoint()?;
+        }
+        Some(())
+    }
+
+    /// Optimization and back-end stages
+    fn optimize_and_emit(&mut self, mut ast: Vec<AstNode>, target: Target) -> Option<Output> {
+        // Optimization
+        if self.optimize {
+            if target != Target::Eval {
+                ast = Monomorphizer::new().monomorphize(&ast);
+                let mut inliner = match &self.profile {
+                    Some(profile) => Inliner::new().with_profile(profile, self.hot_call_threshold),
+                    None => Inliner::new(),
+                };
+                ast = inliner.inline(&ast);
+                ast = LoopOptimizer::new().optimize(&ast);
+                ast = SubexpressionEliminator::new().eliminate(&ast);
+            }
+            if let Some(hook) = self.after_optimize.as_mut() {
+                hook(&mut ast, &mut self.diagnostics);
+            }
+            self.checkpoint()?;
+        }
+
+        // Back end
+        let output = match target {
+            Target::Eval => {
+                // After every hook, so the slots match the AST that runs
+                resolve_scopes(&mut ast);
+                self.evaluator
+                    .eval(&ast)
+                    .map(Output::Value)
+                    .map_err(|e| format!("Runtime error: {:?}", e))
+            }
+            Target::Bytecode => crate::bytecode_compiler::compile(&ast)
+                .map(Output::Bytecode)
+                .map_err(|e| format!("Bytecode compilation error: {:?}", e)),
+            Target::Asm => self
+                .compile_asm(&ast)
+                .map(Output::Asm)
+                .map_err(|e| format!("Code generation error: {}", e)),
+            Target::Elf => self.compile_elf(&ast),
+        };
+        match output {
+            Ok(output) => Some(output),
+            Err(message) => {
+                self.diagnostics.push(Diagnostic::error(message));
+                None
+            }
+        }
+    }
+
+    fn compile_elf(&self, ast: &[AstNode]) -> Result<Output, String> {
+        let assembler = self
+            .assembler
+            .as_ref()
+            .ok_or_else(|| String::from("ELF output needs an assembler; configure one with CompilerPipeline::assembler"))?;
+        let asm = self.compile_asm(ast).map_err(|e| format!("Code generation error: {}", e))?;
+        let code = assembler(&asm).map_err(|e| format!("Assembler error: {}", e))?;
+        crate::elf::create_elf_object_for(&self.target_spec, &code, "main").map(Output::Elf)
+    }
+
+    fn compile_asm(&self, ast: &[AstNode]) -> Result<String, String> {
+        let profile = self.profile.as_ref().filter(|_| self.optimize);
+        crate::codegen::compile_to_asm_for(ast, &self.target_spec, profile)
+    }
+
+    /// Stop the pipeline if the last stage left an error behind
+    fn checkpoint(&self) -> Option<()> {
+        if self.diagnostics.has_errors() {
+            None
+        } else {
+            Some(())
+        }
+    }
+}
+
+/// Point a module's imports at the modules they load
+///
+/// Each import is renamed to the `grove` name of the module its path resolves
+/// to, and the name it was imported under (alias or written name) is recorded
+/// in `qualifiers` so qualified accesses can be rewritten to match.
+fn bind_imports(
+    node: &mut AstNode,
+    importer: &str,
+    resolver: &ModuleResolver,
+    qualifiers: &mut BTreeMap<String, String>,
+) {
+    if let AstNode::Import { module_name, path, alias, .. } = node {
+        let target = resolver
+            .resolve_path(path, Some(importer))
+            .ok()
+            .and_then(|resolved| resolver.get_module(&resolved))
+            .map(|module| module.name.clone());
+        if let Some(target) = target {
+            let written = alias.take().unwrap_or_else(|| module_name.clone());
+            qualifiers.insert(written, target.clone());
+            *module_name = target;
+        }
+        return;
+    }
+    for child in node.children_mut() {
+        bind_imports(child, importer, resolver, qualifiers);
+    }
+}
+
+/// Turn `Name.member` on an imported module into a module access
+fn qualify_module_access(node: &mut AstNode, qualifiers: &BTreeMap<String, String>) {
+    let access = match &*node {
+        AstNode::FieldAccess { object, field, span } => match object.as_ref() {
+            AstNode::Ident { name, .. } => qualifiers.get(name).map(|module| AstNode::ModuleAccess {
+                module: module.clone(),
+                member: field.clone(),
+                span: span.clone(),
+            }),
+            _ => None,
+        },
+        _ => None,
+    };
+    match access {
+        Some(access) => *node = access,
+        None => {
+            for child in node.children_mut() {
+                qualify_module_access(child, qualifiers);
+            }
+        }
+    }
+}
+
+/// Replace module accesses with references to the hoisted member
+fn unqualify_module_access(node: &mut AstNode) {
+    if let AstNode::ModuleAccess { member, span, .. } = &*node {
+        *node = AstNode::Ident { name: member.clone(), slot: None, span: span.clone() };
+        return;
+    }
+    for child in node.children_mut() {
+        unqualify_module_access(child);
+    }
+}
+
+/// Name a top-level statement defines in the global namespace
+fn top_level_name(node: &AstNode) -> Option<&String> {
+    match node {
+        AstNode::ChantDef { name, .. }
+        | AstNode::FormDef { name, .. }
+        | AstNode::VariantDef { name, .. }
+        | AstNode::BindStmt { name, .. }
+        | AstNode::WeaveStmt { name, .. } => Some(name),
+        _ => None,
+    }
+}
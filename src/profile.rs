@@ -0,0 +1,271 @@
+//! # Execution Profiles
+//!
+//! Counts gathered while the interpreter runs a script, saved as a
+//! `.profile` file and fed back into the next optimized build:
+//!
+//! - **Call counts** per chant. The inliner expands hot chants as if they
+//!   were marked `swift`, and leaves chants the profile never saw called
+//!   out of line. Hosts that promote chants to native code (see
+//!   `native_module`) pick them with [`Profile::hot_chants`].
+//! - **Branch outcomes** per `should`, keyed by its source position. Native
+//!   codegen places the more frequent branch on the fall-through path.
+//!
+//! ## Collecting and using a profile
+//!
+//! ```
+//! use glimmer_weave::pipeline::{CompilerPipeline, Output, Target};
+//! use glimmer_weave::profile::Profile;
+//!
+//! let source = "chant double(n) then\n    n * 2\nend\ndouble(21)";
+//!
+//! let mut training = CompilerPipeline::new();
+//! training.evaluator_mut().enable_profiling();
+//! training.run(source, Target::Eval).unwrap();
+//! let text = training.evaluator_mut().take_profile().unwrap().to_text();
+//!
+//! let profile = Profile::parse(&text).unwrap();
+//! assert_eq!(profile.calls("double"), 1);
+//! let build = CompilerPipeline::new().optimize(true).profile(profile).run(source, Target::Asm);
+//! assert!(matches!(build, Ok(Output::Asm(_))));
+//! ```
+//!
+//! ## Format
+//!
+//! Line-oriented text: a header, then one record per line.
+//!
+//! ```text
+//! glimmer-profile 1
+//! call fib 177
+//! branch 12:5 40 2
+//! ```
+//!
+//! `call` gives a chant's name and how many times it ran; `branch` gives a
+//! `should`'s line and column, then how often its condition held and how
+//! often it did not.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::source_location::SourceSpan;
+
+/// First word of every profile
+pub const HEADER: &str = "glimmer-profile";
+
+/// Format version written by [`Profile::to_text`]
+pub const VERSION: u32 = 1;
+
+/// File extension of saved profiles
+pub const EXTENSION: &str = "profile";
+
+/// Calls after which a chant counts as hot
+pub const DEFAULT_HOT_CALL_THRESHOLD: u64 = 100;
+
+/// Why a profile could not be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileError {
+    /// The text does not start with [`HEADER`]
+    BadHeader,
+    /// The profile was written by a newer format version
+    UnsupportedVersion(u32),
+    /// A record could not be parsed (1-based line number)
+    Malformed { line: usize },
+}
+
+/// How often a `should` condition held and how often it did not
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BranchCounts {
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
+/// Call counts and branch outcomes from one or more runs
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    calls: BTreeMap<String, u64>,
+    branches: BTreeMap<(usize, usize), BranchCounts>,
+}
+
+impl Profile {
+    /// Create an empty profile
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a call to a chant
+    pub fn record_call(&mut self, chant: &str) {
+        match self.calls.get_mut(chant) {
+            Some(count) => *count += 1,
+            None => {
+                self.calls.insert(chant.into(), 1);
+            }
+        }
+    }
+
+    /// Count the outcome of the `should` at `span`
+    ///
+    /// Branches without a known position are not recorded.
+    pub fn record_branch(&mut self, span: &SourceSpan, taken: bool) {
+        if !span.start.is_known() {
+            return;
+        }
+        let counts = self.branches.entry((span.start.line, span.start.column)).or_default();
+        if taken {
+            counts.taken += 1;
+        } else {
+            counts.not_taken += 1;
+        }
+    }
+
+    /// How many times a chant was called
+    pub fn calls(&self, chant: &str) -> u64 {
+        self.calls.get(chant).copied().unwrap_or(0)
+    }
+
+    /// Outcomes of the `should` at `span`, if it ever ran
+    pub fn branch(&self, span: &SourceSpan) -> Option<BranchCounts> {
+        self.branches.get(&(span.start.line, span.start.column)).copied()
+    }
+
+    /// Whether a chant was called at least `threshold` times
+    pub fn is_hot(&self, chant: &str, threshold: u64) -> bool {
+        self.calls(chant) >= threshold
+    }
+
+    /// Chants called at least `threshold` times, most called first
+    pub fn hot_chants(&self, threshold: u64) -> Vec<(&str, u64)> {
+        let mut hot: Vec<(&str, u64)> = self
+            .calls
+            .iter()
+            .filter(|(_, count)| **count >= threshold)
+            .map(|(name, count)| (name.as_str(), *count))
+            .collect();
+        hot.sort_by_key(|(_, count)| core::cmp::Reverse(*count));
+        hot
+    }
+
+    /// Whether the profile recorded nothing
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty() && self.branches.is_empty()
+    }
+
+    /// Add the counts of another run
+    pub fn merge(&mut self, other: &Profile) {
+        for (chant, count) in &other.calls {
+            *self.calls.entry(chant.clone()).or_insert(0) += count;
+        }
+        for (position, counts) in &other.branches {
+            let merged = self.branches.entry(*position).or_default();
+            merged.taken += counts.taken;
+            merged.not_taken += counts.not_taken;
+        }
+    }
+
+    /// Render the profile in the `.profile` text format
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{} {}\n", HEADER, VERSION);
+        for (chant, count) in &self.calls {
+            text.push_str(&format!("call {} {}\n", chant, count));
+        }
+        for ((line, column), counts) in &self.branches {
+            text.push_str(&format!("branch {}:{} {} {}\n", line, column, counts.taken, counts.not_taken));
+        }
+        text
+    }
+
+    /// Read a profile in the `.profile` text format
+    ///
+    /// Blank lines and lines starting with `#` are skipped. Repeated
+    /// records add up.
+    pub fn parse(text: &str) -> Result<Profile, ProfileError> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let (_, header) = lines.next().ok_or(ProfileError::BadHeader)?;
+        let version = match header.split_whitespace().collect::<Vec<_>>().as_slice() {
+            [HEADER, version] => version.parse::<u32>().map_err(|_| ProfileError::BadHeader)?,
+            _ => return Err(ProfileError::BadHeader),
+        };
+        if version > VERSION {
+            return Err(ProfileError::UnsupportedVersion(version));
+        }
+
+        let mut profile = Profile::new();
+        for (number, line) in lines {
+            let malformed = ProfileError::Malformed { line: number };
+            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["call", chant, count] => {
+                    let count: u64 = count.parse().map_err(|_| malformed)?;
+                    *profile.calls.entry(chant.to_string()).or_insert(0) += count;
+                }
+                ["branch", position, taken, not_taken] => {
+                    let (line, column) = position.split_once(':').ok_or(malformed.clone())?;
+                    let key = (
+                        line.parse().map_err(|_| malformed.clone())?,
+                        column.parse().map_err(|_| malformed.clone())?,
+                    );
+                    let counts = profile.branches.entry(key).or_default();
+                    counts.taken += taken.parse::<u64>().map_err(|_| malformed.clone())?;
+                    counts.not_taken += not_taken.parse::<u64>().map_err(|_| malformed)?;
+                }
+                _ => return Err(malformed),
+            }
+        }
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source_location::SourceLocation;
+
+    fn at(line: usize, column: usize) -> SourceSpan {
+        SourceSpan::point(SourceLocation::new(line, column))
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let mut profile = Profile::new();
+        for _ in 0..3 {
+            profile.record_call("fib");
+        }
+        profile.record_call("main_loop");
+        profile.record_branch(&at(12, 5), true);
+        profile.record_branch(&at(12, 5), false);
+        profile.record_branch(&at(12, 5), false);
+        profile.record_branch(&SourceSpan::unknown(), true);
+
+        let text = profile.to_text();
+        assert_eq!(text, "glimmer-profile 1\ncall fib 3\ncall main_loop 1\nbranch 12:5 1 2\n");
+        assert_eq!(Profile::parse(&text).unwrap(), profile);
+        assert_eq!(profile.branch(&at(12, 5)), Some(BranchCounts { taken: 1, not_taken: 2 }));
+        assert_eq!(profile.hot_chants(2), [("fib", 3)]);
+    }
+
+    #[test]
+    fn test_merge_adds_counts() {
+        let mut first = Profile::parse("glimmer-profile 1\ncall f 2\nbranch 1:1 1 0").unwrap();
+        let second = Profile::parse("# second run\nglimmer-profile 1\n\ncall f 3\ncall g 1\nbranch 1:1 0 4").unwrap();
+        first.merge(&second);
+        assert_eq!(first.calls("f"), 5);
+        assert_eq!(first.calls("g"), 1);
+        assert_eq!(first.branch(&at(1, 1)), Some(BranchCounts { taken: 1, not_taken: 4 }));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(Profile::parse(""), Err(ProfileError::BadHeader));
+        assert_eq!(Profile::parse("call f 1"), Err(ProfileError::BadHeader));
+        assert_eq!(Profile::parse("glimmer-profile 9"), Err(ProfileError::UnsupportedVersion(9)));
+        assert_eq!(
+            Profile::parse("glimmer-profile 1\ncall f 1\nbranch 3 1 1"),
+            Err(ProfileError::Malformed { line: 3 })
+        );
+        assert_eq!(Profile::parse("glimmer-profile 1\ncall f lots"), Err(ProfileError::Malformed { line: 2 }));
+    }
+}
@@ -0,0 +1,71 @@
+//! # Progress Reporting
+//!
+//! A standard way for long-running scripts to report how far they have got,
+//! so the AethelOS shell (or any host) can show one progress display for
+//! every script.
+//!
+//! `with_progress(total, chant)` starts a task, calls `chant` with a
+//! progress handle and returns what the chant returns.
+//! `Progress.update(handle, done)` and
+//! `Progress.update(handle, done, message)` report progress through it.
+//! Each step reaches the evaluator's [`ProgressSink`] as a
+//! [`ProgressEvent`]: `Started`, any number of `Updated`, then `Finished`
+//! with how the task ended.
+//!
+//! Every update is also a cancellation point. If the host has tripped the
+//! evaluator's cancellation token, the update unwinds the script with
+//! `Cancelled` and the task finishes as [`TaskEnd::Cancelled`]. The handle
+//! is a resource of kind [`PROGRESS_RESOURCE`]; using it once its task has
+//! finished is an error.
+//!
+//! ```
+//! use glimmer_weave::progress::{ProgressEvent, ProgressSink};
+//!
+//! let mut shown = Vec::new();
+//! let mut sink = |event: &ProgressEvent| {
+//!     if let ProgressEvent::Updated { done, total, .. } = event {
+//!         shown.push(format!("{}/{}", done, total));
+//!     }
+//! };
+//! sink.report(&ProgressEvent::Updated { task: 1, done: 3.0, total: 10.0, message: None });
+//! assert_eq!(shown, ["3/10"]);
+//! ```
+
+use alloc::string::String;
+
+/// Resource kind of a progress handle
+pub const PROGRESS_RESOURCE: &str = "progress";
+
+/// How a task ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskEnd {
+    /// The chant returned
+    Completed,
+    /// The chant failed with an error
+    Failed,
+    /// The host cancelled the script
+    Cancelled,
+}
+
+/// One step of a task's progress
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    /// `with_progress` started task `task`, out of `total` units of work
+    Started { task: u64, total: f64 },
+    /// The script reported `done` units of `total`, clamped to `total`
+    Updated { task: u64, done: f64, total: f64, message: Option<String> },
+    /// The task's chant is over
+    Finished { task: u64, end: TaskEnd },
+}
+
+/// Host display for script progress
+pub trait ProgressSink: crate::sync::MaybeSend {
+    /// Show `event`
+    fn report(&mut self, event: &ProgressEvent);
+}
+
+impl<F: FnMut(&ProgressEvent) + crate::sync::MaybeSend> ProgressSink for F {
+    fn report(&mut self, event: &ProgressEvent) {
+        self(event)
+    }
+}
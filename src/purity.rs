@@ -0,0 +1,200 @@
+//! # Purity Analysis
+//!
+//! Answers the questions the optimizer passes share: which expressions can
+//! be evaluated earlier, later, fewer times or not at all without changing
+//! what a program does, which variables a call may change behind a
+//! statement's back, and when two expressions compute the same thing.
+//!
+//! An expression is pure when it is built only from literals, variable
+//! reads, operators and field, index or module access. Calls, pipelines and
+//! queries may have effects and are never pure.
+
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+use crate::ast::*;
+
+/// Expressions whose evaluation has no effect beyond producing a value
+pub fn is_pure(node: &AstNode) -> bool {
+    let allowed = matches!(
+        node,
+        AstNode::Number { .. }
+            | AstNode::BigInt { .. }
+            | AstNode::Integer { .. }
+            | AstNode::Measure { .. }
+            | AstNode::Text { .. }
+            | AstNode::Truth { .. }
+            | AstNode::Nothing { .. }
+            | AstNode::Ident { .. }
+            | AstNode::BinaryOp { .. }
+            | AstNode::UnaryOp { .. }
+            | AstNode::InUnit { .. }
+            | AstNode::FieldAccess { .. }
+            | AstNode::ModuleAccess { .. }
+            | AstNode::IndexAccess { .. }
+    );
+    allowed && node.children().into_iter().all(is_pure)
+}
+
+/// Literals and variable reads, which cost nothing to repeat
+pub fn is_trivial(node: &AstNode) -> bool {
+    matches!(
+        node,
+        AstNode::Number { .. }
+            | AstNode::BigInt { .. }
+            | AstNode::Integer { .. }
+            | AstNode::Measure { .. }
+            | AstNode::Text { .. }
+            | AstNode::Truth { .. }
+            | AstNode::Nothing { .. }
+            | AstNode::Ident { .. }
+    )
+}
+
+/// Whether evaluating a node may run code that is not visible at the node
+pub fn contains_call(node: &AstNode) -> bool {
+    matches!(node, AstNode::Call { .. } | AstNode::Pipeline { .. })
+        || node.children().into_iter().any(contains_call)
+}
+
+/// Size of an expression, counted in AST nodes
+pub fn node_count(node: &AstNode) -> usize {
+    1 + node.children().into_iter().map(node_count).sum::<usize>()
+}
+
+/// Every variable an expression reads
+pub fn variables(node: &AstNode) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    let mut pending = vec![node];
+    while let Some(node) = pending.pop() {
+        if let AstNode::Ident { name, .. } = node {
+            names.insert(name.clone());
+        }
+        pending.extend(node.children());
+    }
+    names
+}
+
+/// Structural equality of pure expressions, ignoring spans and scope slots
+pub fn same_expr(a: &AstNode, b: &AstNode) -> bool {
+    match (a, b) {
+        (AstNode::Number { value: x, .. }, AstNode::Number { value: y, .. }) => x == y,
+        (AstNode::Measure { value: x, unit: u, .. }, AstNode::Measure { value: y, unit: v, .. }) => x == y && u == v,
+        (AstNode::BigInt { value: x, .. }, AstNode::BigInt { value: y, .. }) => x == y,
+        (AstNode::Integer { value: x, .. }, AstNode::Integer { value: y, .. }) => x == y,
+        (AstNode::Text { value: x, .. }, AstNode::Text { value: y, .. }) => x == y,
+        (AstNode::Truth { value: x, .. }, AstNode::Truth { value: y, .. }) => x == y,
+        (AstNode::Nothing { .. }, AstNode::Nothing { .. }) => true,
+        (AstNode::Ident { name: x, .. }, AstNode::Ident { name: y, .. }) => x == y,
+        (AstNode::UnaryOp { op: x, operand: a, .. }, AstNode::UnaryOp { op: y, operand: b, .. }) => {
+            x == y && same_expr(a, b)
+        }
+        (
+            AstNode::BinaryOp { left: a, op: x, right: c, .. },
+            AstNode::BinaryOp { left: b, op: y, right: d, .. },
+        ) => x == y && same_expr(a, b) && same_expr(c, d),
+        (AstNode::FieldAccess { object: a, field: x, .. }, AstNode::FieldAccess { object: b, field: y, .. })
+        | (AstNode::InUnit { value: a, unit: x, .. }, AstNode::InUnit { value: b, unit: y, .. }) => {
+            x == y && same_expr(a, b)
+        }
+        (
+            AstNode::IndexAccess { object: a, index: c, .. },
+            AstNode::IndexAccess { object: b, index: d, .. },
+        ) => same_expr(a, b) && same_expr(c, d),
+        (
+            AstNode::ModuleAccess { module: a, member: x, .. },
+            AstNode::ModuleAccess { module: b, member: y, .. },
+        ) => a == b && x == y,
+        _ => false,
+    }
+}
+
+/// The variable an assignment target writes through
+pub fn root_name(target: &AstNode) -> Option<String> {
+    match target {
+        AstNode::Ident { name, .. } => Some(name.clone()),
+        AstNode::IndexAccess { object, .. } | AstNode::FieldAccess { object, .. } => root_name(object),
+        _ => None,
+    }
+}
+
+/// Variables some chant in the program `set`s, which any call may change
+pub fn assigned_in_chants(program: &[AstNode]) -> BTreeSet<String> {
+    fn collect(node: &AstNode, in_chant: bool, assigned: &mut BTreeSet<String>) {
+        if let (true, AstNode::SetStmt { target, .. }) = (in_chant, node) {
+            assigned.extend(root_name(target));
+        }
+        let in_chant = in_chant || matches!(node, AstNode::ChantDef { .. });
+        for child in node.children() {
+            collect(child, in_chant, assigned);
+        }
+    }
+    let mut assigned = BTreeSet::new();
+    for node in program {
+        collect(node, false, &mut assigned);
+    }
+    assigned
+}
+
+/// The statement lists directly inside a statement
+pub fn statement_lists(node: &mut AstNode) -> Vec<&mut Vec<AstNode>> {
+    match node {
+        AstNode::IfStmt { then_branch, else_branch, .. } => {
+            let mut lists = vec![then_branch];
+            lists.extend(else_branch.as_mut());
+            lists
+        }
+        AstNode::WhileStmt { body, .. }
+        | AstNode::ForStmt { body, .. }
+        | AstNode::ChantDef { body, .. }
+        | AstNode::DeferStmt { body, .. }
+        | AstNode::TogetherBlock { body, .. }
+        | AstNode::MigrateStore { body, .. }
+        | AstNode::ModuleDecl { body, .. }
+        | AstNode::Block { statements: body, .. } => vec![body],
+        AstNode::MatchStmt { arms, .. } => arms.iter_mut().map(|arm| &mut arm.body).collect(),
+        AstNode::AttemptStmt { body, handlers, .. } => {
+            let mut lists = vec![body];
+            lists.extend(handlers.iter_mut().map(|handler| &mut handler.body));
+            lists
+        }
+        AstNode::EmbodyStmt { methods, .. } => methods
+            .iter_mut()
+            .filter_map(|method| match method {
+                AstNode::ChantDef { body, .. } => Some(body),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn expr(source: &str) -> AstNode {
+        let tokens = Lexer::new(source).tokenize_positioned();
+        match Parser::new(tokens).parse().unwrap().remove(0) {
+            AstNode::ExprStmt { expr, .. } => *expr,
+            other => other,
+        }
+    }
+
+    #[test]
+    fn test_pure_expressions() {
+        assert!(is_pure(&expr("p.x * p.x + items[i] - Math.pi")));
+        assert!(!is_pure(&expr("p.x + f(1)")));
+        assert!(!is_pure(&expr("[1, 2]")));
+    }
+
+    #[test]
+    fn test_same_expr_ignores_spans() {
+        assert!(same_expr(&expr("p.x * p.x"), &expr("  p.x  *  p.x")));
+        assert!(same_expr(&expr("items[i + 1]"), &expr("items[i+1]")));
+        assert!(!same_expr(&expr("p.x * p.x"), &expr("p.y * p.y")));
+        assert!(!same_expr(&expr("a - b"), &expr("b - a")));
+    }
+}
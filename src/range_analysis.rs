@@ -0,0 +1,474 @@
+//! # Range Analysis
+//!
+//! Abstract interpretation that tracks what numbers and list lengths a
+//! program can produce, to catch bad indexing and division by zero before
+//! the program runs.
+//!
+//! Each number is approximated by an interval `[low, high]` and each list
+//! by an interval of possible lengths. Literals give exact intervals,
+//! arithmetic combines them, `should` conditions comparing a variable
+//! narrow it in each branch, and the branches join again afterwards. A
+//! `for each` over `range(a, b)` gives its variable `[a, b - 1]`. Variables
+//! a loop assigns are forgotten, since the analysis does not iterate to a
+//! fixed point, and chant parameters start out unknown.
+//!
+//! Indexing a list of known length with a known index, and dividing by a
+//! known divisor, are checked. When every value in the interval is out of
+//! bounds (or the divisor is exactly zero) the warning is `certain`;
+//! when only some are, it is not. Anything unknown is assumed fine, so the
+//! pass only warns about what it can see.
+//!
+//! ```
+//! use glimmer_weave::range_analysis::check;
+//! use glimmer_weave::{Lexer, Parser, SemanticWarning};
+//!
+//! let source = "bind xs to [1, 2, 3]\nbind i to 3\nxs[i]";
+//! let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap();
+//!
+//! let warnings = check(&ast);
+//! assert!(matches!(warnings[..], [SemanticWarning::IndexOutOfBounds { certain: true, .. }]));
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::ast::{AstNode, BinaryOperator, TypeAnnotation, UnaryOperator};
+use crate::semantic::SemanticWarning;
+
+/// Closed interval of numbers; either bound may be infinite
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Interval {
+    /// Every number
+    pub const ANY: Interval = Interval { low: f64::NEG_INFINITY, high: f64::INFINITY };
+
+    /// The interval holding just `value`
+    pub fn exact(value: f64) -> Self {
+        Interval { low: value, high: value }
+    }
+
+    /// Smallest interval holding both
+    pub fn join(self, other: Interval) -> Self {
+        Interval { low: self.low.min(other.low), high: self.high.max(other.high) }
+    }
+
+    /// Whether zero is one of the values
+    pub fn contains_zero(self) -> bool {
+        self.low <= 0.0 && self.high >= 0.0
+    }
+
+    fn is_bounded(self) -> bool {
+        self.low.is_finite() && self.high.is_finite()
+    }
+
+    fn add(self, other: Interval) -> Self {
+        Interval::hull([self.low + other.low, self.high + other.high])
+    }
+
+    fn sub(self, other: Interval) -> Self {
+        Interval::hull([self.low - other.high, self.high - other.low])
+    }
+
+    fn mul(self, other: Interval) -> Self {
+        Interval::hull([
+            self.low * other.low,
+            self.low * other.high,
+            self.high * other.low,
+            self.high * other.high,
+        ])
+    }
+
+    fn div(self, other: Interval) -> Self {
+        if other.contains_zero() {
+            return Interval::ANY;
+        }
+        Interval::hull([
+            self.low / other.low,
+            self.low / other.high,
+            self.high / other.low,
+            self.high / other.high,
+        ])
+    }
+
+    fn rem(self, other: Interval) -> Self {
+        let bound = other.low.abs().max(other.high.abs());
+        match (self.low >= 0.0, self.high <= 0.0) {
+            (true, _) => Interval { low: 0.0, high: bound.min(self.high) },
+            (_, true) => Interval { low: (-bound).max(self.low), high: 0.0 },
+            _ => Interval { low: -bound, high: bound },
+        }
+    }
+
+    /// Interval spanning `bounds`; infinities that cancel into NaN make it
+    /// every number
+    fn hull<const N: usize>(bounds: [f64; N]) -> Self {
+        if bounds.iter().any(|bound| bound.is_nan()) {
+            return Interval::ANY;
+        }
+        let low = bounds.iter().copied().fold(f64::INFINITY, f64::min);
+        let high = bounds.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        Interval { low, high }
+    }
+}
+
+/// What the analysis knows about a value
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Abstract {
+    Number(Interval),
+    List { length: Interval },
+    Unknown,
+}
+
+impl Abstract {
+    fn join(self, other: Abstract) -> Abstract {
+        match (self, other) {
+            (Abstract::Number(a), Abstract::Number(b)) => Abstract::Number(a.join(b)),
+            (Abstract::List { length: a }, Abstract::List { length: b }) => Abstract::List { length: a.join(b) },
+            _ => Abstract::Unknown,
+        }
+    }
+
+    fn number(self) -> Option<Interval> {
+        match self {
+            Abstract::Number(interval) => Some(interval),
+            _ => None,
+        }
+    }
+}
+
+/// Warnings about indexing and division that range analysis finds in `nodes`
+pub fn check(nodes: &[AstNode]) -> Vec<SemanticWarning> {
+    let mut analysis = RangeAnalysis { scopes: vec![BTreeMap::new()], warnings: Vec::new() };
+    analysis.block(nodes);
+    analysis.warnings
+}
+
+struct RangeAnalysis {
+    scopes: Vec<BTreeMap<String, Abstract>>,
+    warnings: Vec<SemanticWarning>,
+}
+
+impl RangeAnalysis {
+    fn block(&mut self, nodes: &[AstNode]) -> Abstract {
+        self.scopes.push(BTreeMap::new());
+        let mut last = Abstract::Unknown;
+        for node in nodes {
+            last = self.node(node);
+        }
+        self.scopes.pop();
+        last
+    }
+
+    fn node(&mut self, node: &AstNode) -> Abstract {
+        match node {
+            AstNode::Number { value, .. } => Abstract::Number(Interval::exact(*value)),
+            AstNode::List { elements, .. } => {
+                for element in elements {
+                    self.node(element);
+                }
+                Abstract::List { length: Interval::exact(elements.len() as f64) }
+            }
+            AstNode::Tuple { elements, .. } => {
+                for element in elements {
+                    self.node(element);
+                }
+                Abstract::Unknown
+            }
+            AstNode::Ident { name, .. } => self.lookup(name),
+
+            AstNode::BindStmt { name, typ, value, .. } | AstNode::WeaveStmt { name, typ, value, .. } => {
+                let mut value = self.node(value);
+                if let Some(TypeAnnotation::Array { length, .. }) = typ {
+                    value = Abstract::List { length: Interval::exact(*length as f64) };
+                }
+                self.define(name, value);
+                Abstract::Unknown
+            }
+            AstNode::SetStmt { target, value, .. } => {
+                let value = self.node(value);
+                match target.as_ref() {
+                    AstNode::Ident { name, .. } => self.assign(name, value),
+                    target => {
+                        self.node(target);
+                    }
+                }
+                Abstract::Unknown
+            }
+
+            AstNode::BinaryOp { left, op, right, span } => {
+                let left = self.node(left);
+                let right = self.node(right);
+                let (Some(left), Some(right)) = (left.number(), right.number()) else {
+                    return Abstract::Unknown;
+                };
+                match op {
+                    BinaryOperator::Add => Abstract::Number(left.add(right)),
+                    BinaryOperator::Sub => Abstract::Number(left.sub(right)),
+                    BinaryOperator::Mul => Abstract::Number(left.mul(right)),
+                    BinaryOperator::Div | BinaryOperator::Mod => {
+                        if right.is_bounded() && right.contains_zero() {
+                            let certain = right.low == 0.0 && right.high == 0.0;
+                            self.warnings.push(SemanticWarning::DivisionByZero { certain, span: span.clone() });
+                        }
+                        if *op == BinaryOperator::Div {
+                            Abstract::Number(left.div(right))
+                        } else {
+                            Abstract::Number(left.rem(right))
+                        }
+                    }
+                    _ => Abstract::Unknown,
+                }
+            }
+            AstNode::UnaryOp { op: UnaryOperator::Negate, operand, .. } => match self.node(operand) {
+                Abstract::Number(interval) => Abstract::Number(Interval { low: -interval.high, high: -interval.low }),
+                _ => Abstract::Unknown,
+            },
+
+            AstNode::IndexAccess { object, index, span } => {
+                let object = self.node(object);
+                let index = self.node(index);
+                if let (Abstract::List { length }, Abstract::Number(index)) = (object, index) {
+                    self.check_index(index, length, span);
+                }
+                Abstract::Unknown
+            }
+            AstNode::Call { callee, args, .. } => {
+                let args: Vec<Abstract> = args.iter().map(|arg| self.node(arg)).collect();
+                let is_list_length = match callee.as_ref() {
+                    AstNode::Ident { name, .. } => name == "list_length",
+                    AstNode::FieldAccess { object, field, .. } => {
+                        field == "length" && matches!(object.as_ref(), AstNode::Ident { name, .. } if name == "List")
+                    }
+                    AstNode::ModuleAccess { module, member, .. } => module == "List" && member == "length",
+                    _ => false,
+                };
+                match args[..] {
+                    [Abstract::List { length }] if is_list_length => Abstract::Number(length),
+                    _ => Abstract::Unknown,
+                }
+            }
+
+            AstNode::IfStmt { condition, then_branch, else_branch, .. } => {
+                self.node(condition);
+                let before = self.scopes.clone();
+                let then_reachable = self.narrow(condition, true);
+                if then_reachable {
+                    self.block(then_branch);
+                }
+                let after_then = core::mem::replace(&mut self.scopes, before);
+                let else_reachable = self.narrow(condition, false);
+                if else_reachable {
+                    if let Some(else_branch) = else_branch {
+                        self.block(else_branch);
+                    }
+                }
+                match (then_reachable, else_reachable) {
+                    (true, true) => self.join_scopes(&after_then),
+                    (true, false) => self.scopes = after_then,
+                    _ => {}
+                }
+                Abstract::Unknown
+            }
+            AstNode::MatchStmt { value, arms, .. } => {
+                self.node(value);
+                let before = self.scopes.clone();
+                let mut joined: Option<Vec<BTreeMap<String, Abstract>>> = None;
+                for arm in arms {
+                    self.scopes = before.clone();
+                    if let Some(guard) = &arm.guard {
+                        self.node(guard);
+                    }
+                    self.block(&arm.body);
+                    if let Some(joined) = &joined {
+                        self.join_scopes(joined);
+                    }
+                    joined = Some(self.scopes.clone());
+                }
+                self.scopes = joined.unwrap_or(before);
+                Abstract::Unknown
+            }
+            AstNode::WhileStmt { condition, body, .. } => {
+                self.forget_assigned(body);
+                self.node(condition);
+                if self.narrow(condition, true) {
+                    self.block(body);
+                }
+                self.forget_assigned(body);
+                Abstract::Unknown
+            }
+            AstNode::ForStmt { variable, iterable, body, .. } => {
+                let element = match iterable.as_ref() {
+                    AstNode::Range { start, end, .. } => {
+                        match (self.node(start).number(), self.node(end).number()) {
+                            (Some(start), Some(end)) if start.low <= end.high - 1.0 => {
+                                Abstract::Number(Interval { low: start.low, high: end.high - 1.0 })
+                            }
+                            _ => Abstract::Unknown,
+                        }
+                    }
+                    iterable => {
+                        self.node(iterable);
+                        Abstract::Unknown
+                    }
+                };
+                self.forget_assigned(body);
+                self.scopes.push(BTreeMap::new());
+                self.define(variable, element);
+                self.block(body);
+                self.scopes.pop();
+                self.forget_assigned(body);
+                Abstract::Unknown
+            }
+            AstNode::ChantDef { params, body, .. } => {
+                // The body runs later, when the globals it reads may differ
+                let outer = core::mem::replace(&mut self.scopes, vec![BTreeMap::new()]);
+                for param in params {
+                    self.define(&param.name, Abstract::Unknown);
+                }
+                self.block(body);
+                self.scopes = outer;
+                Abstract::Unknown
+            }
+
+            node => {
+                self.scopes.push(BTreeMap::new());
+                for child in node.children() {
+                    self.node(child);
+                }
+                self.scopes.pop();
+                Abstract::Unknown
+            }
+        }
+    }
+
+    fn check_index(&mut self, index: Interval, length: Interval, span: &crate::source_location::SourceSpan) {
+        // Indices are truncated, so anything in (-1, length) reads an element
+        let certain = index.low >= length.high || index.high <= -1.0;
+        let possible = (index.high.is_finite() && index.high >= length.low)
+            || (index.low.is_finite() && index.low <= -1.0);
+        if certain || possible {
+            self.warnings.push(SemanticWarning::IndexOutOfBounds { certain, span: span.clone() });
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Abstract {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+            .unwrap_or(Abstract::Unknown)
+    }
+
+    fn define(&mut self, name: &str, value: Abstract) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.into(), value);
+        }
+    }
+
+    fn assign(&mut self, name: &str, value: Abstract) {
+        if let Some(slot) = self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(name)) {
+            *slot = value;
+        }
+    }
+
+    /// Join the variables of two states that reached the same point
+    fn join_scopes(&mut self, other: &[BTreeMap<String, Abstract>]) {
+        for (scope, other) in self.scopes.iter_mut().zip(other) {
+            for (name, value) in scope.iter_mut() {
+                *value = match other.get(name) {
+                    Some(other) => value.join(*other),
+                    None => Abstract::Unknown,
+                };
+            }
+        }
+    }
+
+    /// Narrow the variable `condition` compares to what holds when the
+    /// condition is `holds`; false when it never can
+    fn narrow(&mut self, condition: &AstNode, holds: bool) -> bool {
+        let AstNode::BinaryOp { left, op, right, .. } = condition else {
+            return true;
+        };
+        let (name, op, bound) = match (left.as_ref(), right.as_ref()) {
+            (AstNode::Ident { name, .. }, other) => (name, *op, other),
+            (other, AstNode::Ident { name, .. }) => (name, mirrored(*op), other),
+            _ => return true,
+        };
+        let op = if holds { op } else { negated(op) };
+        let (Some(current), Some(bound)) = (self.lookup(name).number(), self.pure_number(bound)) else {
+            return true;
+        };
+
+        // A strict bound on an integer steps past it; truncation makes this
+        // safe for indices and it never excludes zero
+        let below = |limit: f64| if limit % 1.0 == 0.0 { limit - 1.0 } else { limit };
+        let above = |limit: f64| if limit % 1.0 == 0.0 { limit + 1.0 } else { limit };
+        let narrowed = match op {
+            BinaryOperator::Less => Interval { low: current.low, high: current.high.min(below(bound.high)) },
+            BinaryOperator::LessEq => Interval { low: current.low, high: current.high.min(bound.high) },
+            BinaryOperator::Greater => Interval { low: current.low.max(above(bound.low)), high: current.high },
+            BinaryOperator::GreaterEq => Interval { low: current.low.max(bound.low), high: current.high },
+            BinaryOperator::Equal => Interval { low: current.low.max(bound.low), high: current.high.min(bound.high) },
+            _ => return true,
+        };
+        if narrowed.low > narrowed.high {
+            return false;
+        }
+        self.assign(name, Abstract::Number(narrowed));
+        true
+    }
+
+    /// The interval of an expression the analysis can evaluate without
+    /// recording warnings again
+    fn pure_number(&mut self, node: &AstNode) -> Option<Interval> {
+        let recorded = self.warnings.len();
+        let value = self.node(node).number();
+        self.warnings.truncate(recorded);
+        value
+    }
+
+    /// Forget what is known about the variables `body` assigns
+    fn forget_assigned(&mut self, body: &[AstNode]) {
+        let mut pending: Vec<&AstNode> = body.iter().collect();
+        while let Some(node) = pending.pop() {
+            if let AstNode::SetStmt { target, .. } = node {
+                if let AstNode::Ident { name, .. } = target.as_ref() {
+                    self.assign(name, Abstract::Unknown);
+                }
+            }
+            if !matches!(node, AstNode::ChantDef { .. }) {
+                pending.extend(node.children());
+            }
+        }
+    }
+}
+
+/// The comparison with its operands swapped: `a < b` is `b > a`
+fn mirrored(op: BinaryOperator) -> BinaryOperator {
+    match op {
+        BinaryOperator::Less => BinaryOperator::Greater,
+        BinaryOperator::LessEq => BinaryOperator::GreaterEq,
+        BinaryOperator::Greater => BinaryOperator::Less,
+        BinaryOperator::GreaterEq => BinaryOperator::LessEq,
+        op => op,
+    }
+}
+
+/// The comparison that holds when `op` does not
+fn negated(op: BinaryOperator) -> BinaryOperator {
+    match op {
+        BinaryOperator::Less => BinaryOperator::GreaterEq,
+        BinaryOperator::LessEq => BinaryOperator::Greater,
+        BinaryOperator::Greater => BinaryOperator::LessEq,
+        BinaryOperator::GreaterEq => BinaryOperator::Less,
+        BinaryOperator::Equal => BinaryOperator::NotEqual,
+        BinaryOperator::NotEqual => BinaryOperator::Equal,
+        op => op,
+    }
+}
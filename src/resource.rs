@@ -0,0 +1,128 @@
+//! # Resources
+//!
+//! Host handles (files, sockets, devices) exposed to scripts as
+//! `Value::Resource` with deterministic release.
+//!
+//! A host native hands a handle to the script by returning
+//! [`Value::resource`](crate::eval::Value::resource); the evaluator adopts it
+//! into its [`ResourceTable`] and defers its release to the end of the chant
+//! (or program) that acquired it. Scripts may release earlier with
+//! `release(handle)`. Either way the evaluator calls the installed
+//! [`ResourceHost`] exactly once per handle, and using or releasing a handle
+//! after that fails with a runtime error instead of touching a stale host
+//! handle.
+//!
+//! ```
+//! use glimmer_weave::resource::ResourceTable;
+//!
+//! let mut table = ResourceTable::new();
+//! let id = table.adopt("file", 3);
+//! assert_eq!(table.release(id), Ok(("file".to_string(), 3)));
+//! assert!(table.release(id).is_err());
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+/// Evaluator-assigned identifier of an adopted resource
+///
+/// Zero marks a resource a native has created but the evaluator has not
+/// adopted yet.
+pub type ResourceId = u64;
+
+/// Host side of resource release
+pub trait ResourceHost: crate::sync::MaybeSend {
+    /// Close the host handle; an error message fails the `release` call
+    fn release(&mut self, kind: &str, handle: u64) -> Result<(), String>;
+}
+
+impl<F: FnMut(&str, u64) -> Result<(), String> + crate::sync::MaybeSend> ResourceHost for F {
+    fn release(&mut self, kind: &str, handle: u64) -> Result<(), String> {
+        self(kind, handle)
+    }
+}
+
+/// Why a resource could not be used or released
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceError {
+    /// The resource was released already
+    Released,
+    /// The id was never handed out by this table
+    Unknown,
+}
+
+/// Live state of every resource an evaluator has adopted
+#[derive(Debug, Default)]
+pub struct ResourceTable {
+    /// Resource id -> (kind, host handle); `None` once released
+    entries: BTreeMap<ResourceId, Option<(String, u64)>>,
+    next_id: ResourceId,
+}
+
+impl ResourceTable {
+    /// Create an empty table
+    pub fn new() -> Self {
+        ResourceTable::default()
+    }
+
+    /// Start tracking a host handle and return its id
+    pub fn adopt(&mut self, kind: &str, handle: u64) -> ResourceId {
+        self.next_id += 1;
+        self.entries.insert(self.next_id, Some((kind.into(), handle)));
+        self.next_id
+    }
+
+    /// Check that `id` may still be used
+    pub fn check(&self, id: ResourceId) -> Result<(), ResourceError> {
+        match self.entries.get(&id) {
+            Some(Some(_)) => Ok(()),
+            Some(None) => Err(ResourceError::Released),
+            None => Err(ResourceError::Unknown),
+        }
+    }
+
+    /// Mark `id` released and return the handle to close
+    pub fn release(&mut self, id: ResourceId) -> Result<(String, u64), ResourceError> {
+        match self.entries.get_mut(&id) {
+            Some(entry) => entry.take().ok_or(ResourceError::Released),
+            None => Err(ResourceError::Unknown),
+        }
+    }
+
+    /// Whether `id` is adopted and not yet released
+    pub fn is_live(&self, id: ResourceId) -> bool {
+        self.check(id).is_ok()
+    }
+
+    /// Number of adopted resources not yet released
+    pub fn live_count(&self) -> usize {
+        self.entries.values().filter(|entry| entry.is_some()).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_once() {
+        let mut table = ResourceTable::new();
+        let file = table.adopt("file", 3);
+        let socket = table.adopt("socket", 3);
+        assert_ne!(file, socket);
+        assert_eq!(table.live_count(), 2);
+
+        assert_eq!(table.release(file), Ok(("file".into(), 3)));
+        assert_eq!(table.check(file), Err(ResourceError::Released));
+        assert_eq!(table.release(file), Err(ResourceError::Released));
+        assert!(table.is_live(socket));
+        assert_eq!(table.live_count(), 1);
+    }
+
+    #[test]
+    fn test_unknown_ids() {
+        let mut table = ResourceTable::new();
+        assert_eq!(table.check(0), Err(ResourceError::Unknown));
+        assert_eq!(table.release(42), Err(ResourceError::Unknown));
+    }
+}
@@ -0,0 +1,283 @@
+//! # Rituals
+//!
+//! State machines declared in one place. A `ritual` lists its states (the
+//! first is where the machine starts), the events that move it between
+//! them with optional `when` guards, and actions to run on entering or
+//! leaving a state:
+//!
+//! ```text
+//! ritual Door with
+//!     state Locked
+//!     state Closed
+//!     state Open
+//!     on unlock from Locked to Closed
+//!     on open from Closed to Open when battery greater than 10
+//!     on close from Open to Closed
+//!     on lock from Closed, Open to Locked
+//!     entering Open then
+//!         println("opened")
+//!     end
+//! end
+//! ```
+//!
+//! The parser desugars a ritual into plain definitions, so every back end
+//! runs it without knowing about rituals:
+//!
+//! - `form Door with state as Text end`, whose `state` holds the current
+//!   state's name;
+//! - `door_start()`, which runs the first state's entry actions and returns
+//!   a `Door` in that state;
+//! - `door_step(machine, event)`, which matches on `machine.state`, takes
+//!   the first transition whose event equals the `event` text and whose
+//!   guard holds, runs the exit actions of the old state and the entry
+//!   actions of the new one and returns a `Door` in the new state. An event
+//!   without a transition, or a machine in a state the ritual doesn't
+//!   declare, comes back unchanged.
+//!
+//! Guards and actions run inside `door_step` (or `door_start`), where
+//! `machine` and `event` are in scope. Chant names are the ritual name in
+//! snake case (`DoorLock` becomes `door_lock_start` and `door_lock_step`).
+//!
+//! ```
+//! use glimmer_weave::ritual::chant_prefix;
+//!
+//! assert_eq!(chant_prefix("DoorLock"), "door_lock");
+//! ```
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::ast::{AstNode, BinaryOperator, MatchArm, Parameter, Pattern, StructField, TypeAnnotation};
+use crate::source_location::SourceSpan;
+
+/// A transition: `on event from A, B to C when guard`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub event: String,
+    pub from: Vec<String>,
+    pub to: String,
+    pub guard: Option<AstNode>,
+}
+
+/// A parsed `ritual` definition, before desugaring
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ritual {
+    pub name: String,
+    /// State names in declaration order; the first is the initial state
+    pub states: Vec<String>,
+    pub transitions: Vec<Transition>,
+    /// Bodies of `entering State then ... end`, by state
+    pub entering: Vec<(String, Vec<AstNode>)>,
+    /// Bodies of `leaving State then ... end`, by state
+    pub leaving: Vec<(String, Vec<AstNode>)>,
+    pub span: SourceSpan,
+}
+
+/// Snake-case prefix of the chants generated for the ritual `name`
+pub fn chant_prefix(name: &str) -> String {
+    let mut prefix = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if c.is_uppercase() && previous_lower {
+            prefix.push('_');
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+        prefix.extend(c.to_lowercase());
+    }
+    prefix
+}
+
+impl Ritual {
+    /// Check that the ritual has states and only refers to declared ones
+    pub fn validate(&self) -> Result<(), String> {
+        if self.states.is_empty() {
+            return Err(format!("Ritual '{}' declares no states", self.name));
+        }
+        for (index, state) in self.states.iter().enumerate() {
+            if self.states[..index].contains(state) {
+                return Err(format!("Ritual '{}' declares state '{}' twice", self.name, state));
+            }
+        }
+
+        let referenced = self
+            .transitions
+            .iter()
+            .flat_map(|transition| transition.from.iter().chain(core::iter::once(&transition.to)))
+            .chain(self.entering.iter().chain(&self.leaving).map(|(state, _)| state));
+        for state in referenced {
+            if !self.states.contains(state) {
+                return Err(format!("Ritual '{}' has no state '{}'", self.name, state));
+            }
+        }
+        Ok(())
+    }
+
+    /// The form, start chant and step chant the ritual stands for
+    pub fn desugar(&self) -> Vec<AstNode> {
+        let span = &self.span;
+        let prefix = chant_prefix(&self.name);
+        let initial = self.states.first().cloned().unwrap_or_default();
+
+        let form = AstNode::FormDef {
+            name: self.name.clone(),
+            type_params: Vec::new(),
+            fields: vec![StructField { name: "state".to_string(), typ: TypeAnnotation::Named("Text".to_string()) }],
+            deriving: Vec::new(),
+            deprecated: None,
+            span: span.clone(),
+        };
+
+        let mut start_body = self.actions(&self.entering, &initial);
+        start_body.push(self.machine_in(&initial));
+        let start = chant(format!("{}_start", prefix), Vec::new(), start_body, span);
+
+        let mut arms: Vec<MatchArm> = self
+            .states
+            .iter()
+            .map(|state| MatchArm {
+                pattern: Pattern::Literal(Box::new(text(state, span))),
+                guard: None,
+                body: self.transitions_from(state),
+            })
+            .collect();
+        arms.push(MatchArm { pattern: Pattern::Wildcard, guard: None, body: Vec::new() });
+        let step_body = vec![
+            AstNode::MatchStmt {
+                value: Box::new(AstNode::FieldAccess {
+                    object: Box::new(ident("machine", span)),
+                    field: "state".to_string(),
+                    span: span.clone(),
+                }),
+                arms,
+                span: span.clone(),
+            },
+            ident("machine", span),
+        ];
+        let params = vec![Parameter::untyped("machine".to_string()), Parameter::untyped("event".to_string())];
+        let step = chant(format!("{}_step", prefix), params, step_body, span);
+
+        vec![form, start, step]
+    }
+
+    /// One `should event is "e" and guard then ... yield ... end` per
+    /// transition out of `state`, in declaration order
+    fn transitions_from(&self, state: &str) -> Vec<AstNode> {
+        let span = &self.span;
+        self.transitions
+            .iter()
+            .filter(|transition| transition.from.iter().any(|from| from == state))
+            .map(|transition| {
+                let event_matches = binary(ident("event", span), BinaryOperator::Equal, text(&transition.event, span), span);
+                let condition = match &transition.guard {
+                    Some(guard) => binary(event_matches, BinaryOperator::And, guard.clone(), span),
+                    None => event_matches,
+                };
+
+                let mut then_branch = self.actions(&self.leaving, state);
+                then_branch.extend(self.actions(&self.entering, &transition.to));
+                then_branch.push(AstNode::YieldStmt { value: Box::new(self.machine_in(&transition.to)), span: span.clone() });
+
+                AstNode::IfStmt { condition: Box::new(condition), then_branch, else_branch: None, span: span.clone() }
+            })
+            .collect()
+    }
+
+    /// Action bodies registered for `state`, in declaration order
+    fn actions(&self, actions: &[(String, Vec<AstNode>)], state: &str) -> Vec<AstNode> {
+        actions
+            .iter()
+            .filter(|(owner, _)| owner == state)
+            .flat_map(|(_, body)| body.iter().cloned())
+            .collect()
+    }
+
+    /// `Name { state: "state" }`
+    fn machine_in(&self, state: &str) -> AstNode {
+        AstNode::StructLiteral {
+            struct_name: self.name.clone(),
+            type_args: Vec::new(),
+            fields: vec![("state".to_string(), text(state, &self.span))],
+            span: self.span.clone(),
+        }
+    }
+}
+
+fn chant(name: String, params: Vec<Parameter>, body: Vec<AstNode>, span: &SourceSpan) -> AstNode {
+    AstNode::ChantDef {
+        name,
+        type_params: Vec::new(),
+        lifetime_params: Vec::new(),
+        params,
+        return_type: None,
+        body,
+        swift: false,
+        deprecated: None,
+        span: span.clone(),
+    }
+}
+
+fn ident(name: &str, span: &SourceSpan) -> AstNode {
+    AstNode::Ident { name: name.to_string(), span: span.clone(), slot: None }
+}
+
+fn text(value: &str, span: &SourceSpan) -> AstNode {
+    AstNode::Text { value: value.to_string(), span: span.clone() }
+}
+
+fn binary(left: AstNode, op: BinaryOperator, right: AstNode, span: &SourceSpan) -> AstNode {
+    AstNode::BinaryOp { left: Box::new(left), op, right: Box::new(right), span: span.clone() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn door() -> Ritual {
+        Ritual {
+            name: "DoorLock".to_string(),
+            states: vec!["Locked".to_string(), "Open".to_string()],
+            transitions: vec![Transition {
+                event: "open".to_string(),
+                from: vec!["Locked".to_string()],
+                to: "Open".to_string(),
+                guard: None,
+            }],
+            entering: Vec::new(),
+            leaving: Vec::new(),
+            span: SourceSpan::unknown(),
+        }
+    }
+
+    #[test]
+    fn test_desugars_to_form_and_chants() {
+        let names: Vec<String> = door()
+            .desugar()
+            .iter()
+            .map(|node| match node {
+                AstNode::FormDef { name, .. } | AstNode::ChantDef { name, .. } => name.clone(),
+                other => panic!("Unexpected node {:?}", other),
+            })
+            .collect();
+        assert_eq!(names, ["DoorLock", "door_lock_start", "door_lock_step"]);
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_and_duplicate_states() {
+        assert_eq!(door().validate(), Ok(()));
+
+        let mut unknown = door();
+        unknown.transitions[0].to = "Ajar".to_string();
+        assert_eq!(unknown.validate(), Err("Ritual 'DoorLock' has no state 'Ajar'".to_string()));
+
+        let mut duplicate = door();
+        duplicate.states.push("Open".to_string());
+        assert!(duplicate.validate().is_err());
+
+        let mut empty = door();
+        empty.states.clear();
+        empty.transitions.clear();
+        assert!(empty.validate().is_err());
+    }
+}
@@ -4,22 +4,54 @@
 //!
 //! This module provides builtin functions for:
 //! - String manipulation (length, slice, concat, upper, lower, split, join, trim, replace, repeat, pad, reverse)
+//! - Text builders (text_builder, text_push, text_push_text, text_build - append in place, linear in the text built)
 //! - Math operations (abs, sqrt, pow, min, max, floor, ceil, round, sign, clamp, sin, cos, tan, log, exp)
-//! - List operations (length, push, pop, reverse, concat, slice, flatten, sum, product, min, max, contains)
+//! - List operations (length, push, pop, reverse, concat, slice, flatten, sum, product, min, max, contains, sort)
 //! - Map operations (keys, values, has, size)
-//! - Type conversion (to_text, to_number, to_truth, type_of)
+//! - Set operations (set_new, set_insert, set_contains, set_union, set_intersect - logarithmic membership)
+//! - Type conversion and hashing (to_text, to_number, to_truth, type_of, hash)
+//! - Checked conversions (to_number_checked, to_int_checked, truncate, saturate - Outcomes, never NaN)
+//! - Big integers (bigint, bigint_pow, bigint_mod_pow - exact past 2^53)
+//! - 64-bit integers (to_integer - overflow is an error, never a wrap)
+//! - Host interchange (value_encode, value_decode, validate, value_diff, value_patch, freeze, is_frozen, thaw)
+//! - Configuration files (config_parse_toml, config_parse_ini, config_emit_toml, config_emit_ini)
+//! - CSV (csv_parse, csv_emit - with optional delimiter, header and columns options)
 //! - Outcome/Maybe helpers (is_triumph, expect_present, refine_triumph, etc.)
 //! - Iterator operations (iter, iter_next, iter_map, iter_filter, iter_fold, iter_collect, iter_take)
-//! - I/O operations (print, println - require kernel context)
+//! - Streaming input (fs_lines, fs_bytes, console_read_lines - opened through the evaluator's stream host)
+//! - I/O operations (print, println - written to the evaluator's output sink)
+//! - Program exit (exit - ends evaluation with a status the host reads back)
+//! - Tasks (spawn, yield_now, block_on_event, signal_event - run by the evaluator's scheduler)
+//! - Key-value store (store_get, store_set, store_delete - kept by the evaluator's storage provider)
+//! - Message bus (publish, subscribe - through the session's bus, delivered by the event pump)
+//! - TCP sockets (tcp_connect, net_send, net_recv, net_close - through the evaluator's network provider)
+//! - HTTP requests (http_get, http_post - through the evaluator's network provider)
+//! - Terminal UI (term_move_to, term_set_color, term_clear, term_read_key - through the evaluator's terminal)
+//! - Progress (with_progress, progress_update - reported to the evaluator's progress sink)
+//! - Localization (tr, catalog_load, set_locale - through the evaluator's message catalogs)
+//! - Compiler phases (compiler_lex, compiler_parse, compiler_analyze, compiler_compile - gated by the evaluator)
+//! - Heap statistics (heap_used, heap_free - from the native allocator)
+//!
+//! Outside the prelude, builtins are grouped into namespaced modules
+//! ([`BUILTIN_MODULES`]) such as `Text.upper`, `List.push` and `Math.sqrt`.
+//!
+//! Every builtin is registered with a signature, a one-line description and
+//! the capabilities it needs, which [`BuiltinRegistry::describe`] and
+//! [`BuiltinRegistry::completions`] hand to the REPL's `:help`, docgen and
+//! LSP completion.
 
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use alloc::vec;
 use alloc::format;
 use alloc::boxed::Box;
-use crate::eval::{Value, RuntimeError};
+use alloc::collections::BTreeSet;
+use core::cmp::Ordering;
+use crate::bigint::BigInt;
+use crate::eval::{Value, RuntimeError, SetElement};
 
 /// Math functions abstraction - use std when available (tests), libm when no_std
+#[cfg(feature = "runtime-math")]
 mod math {
     // Use std math functions when std is available (includes tests)
     #[cfg(feature = "std")]
@@ -66,6 +98,25 @@ pub struct NativeFunction {
     pub name: String,
     pub func: NativeFn,
     pub arity: Option<usize>,  // None = variadic
+    /// Parameters and result, as `(text: Text, times: Number) -> Text`;
+    /// `name?` marks an optional parameter and `name...` the rest of the
+    /// arguments. Empty when undocumented.
+    pub signature: &'static str,
+    /// One-line description for help and completion
+    pub description: &'static str,
+    /// Capabilities a script must be granted to call it
+    pub capabilities: &'static [&'static str],
+}
+
+/// A parameter of a builtin, parsed from its signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuiltinParam {
+    pub name: &'static str,
+    pub typ: &'static str,
+    /// May be left out (`name?: T`)
+    pub optional: bool,
+    /// Takes the rest of the arguments (`name...: T`)
+    pub rest: bool,
 }
 
 impl NativeFunction {
@@ -74,10 +125,88 @@ impl NativeFunction {
             name: name.to_string(),
             arity,
             func,
+            signature: "",
+            description: "",
+            capabilities: &[],
+        }
+    }
+
+    /// Attach the signature and description
+    pub fn doc(mut self, signature: &'static str, description: &'static str) -> Self {
+        self.signature = signature;
+        self.description = description;
+        self
+    }
+
+    /// Attach the capabilities the function needs
+    pub fn requires(mut self, capabilities: &'static [&'static str]) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Parameters in the signature
+    pub fn params(&self) -> Vec<BuiltinParam> {
+        let list = self.signature.split_once(" -> ").map_or(self.signature, |(params, _)| params);
+        let list = list.trim().trim_start_matches('(').trim_end_matches(')');
+        split_top_level(list)
+            .into_iter()
+            .filter(|param| !param.is_empty())
+            .map(|param| {
+                let (name, typ) = param.split_once(": ").unwrap_or((param, "Any"));
+                let rest = name.ends_with("...");
+                let optional = name.ends_with('?');
+                BuiltinParam { name: name.trim_end_matches("...").trim_end_matches('?'), typ, optional, rest }
+            })
+            .collect()
+    }
+
+    /// Result type in the signature
+    pub fn returns(&self) -> Option<&'static str> {
+        self.signature.split_once(" -> ").map(|(_, result)| result.trim())
+    }
+
+    /// How a call looks: `upper(text: Text) -> Text`
+    pub fn usage(&self) -> String {
+        if self.signature.is_empty() {
+            return format!("{}(...)", self.name);
         }
+        format!("{}{}", self.name, self.signature)
+    }
+
+    /// Usage, description and required capabilities, one per line
+    pub fn help(&self) -> String {
+        let mut help = self.usage();
+        if !self.description.is_empty() {
+            help.push('\n');
+            help.push_str(self.description);
+        }
+        if !self.capabilities.is_empty() {
+            let capabilities: Vec<String> = self.capabilities.iter().map(|capability| format!("`{}`", capability)).collect();
+            help.push_str(&format!("\nRequires {}", capabilities.join(", ")));
+        }
+        help
     }
 }
 
+/// Split a parameter list at the commas outside `<...>`
+fn split_top_level(list: &'static str) -> Vec<&'static str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in list.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(list[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(list[start..].trim());
+    parts
+}
+
 impl core::fmt::Debug for NativeFunction {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("NativeFunction")
@@ -93,168 +222,887 @@ impl PartialEq for NativeFunction {
     }
 }
 
-/// Get all builtin functions
+/// Groups of builtins that can be compiled in or out
+///
+/// Every group except [`BuiltinGroup::Core`] sits behind a cargo feature so
+/// kernel builds can leave out what they do not use (`runtime-math` also
+/// drops the libm dependency).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinGroup {
+    /// String functions (`runtime-text`)
+    Text,
+    /// Math functions, including trig and log (`runtime-math`)
+    Math,
+    /// Lists, maps, conversions, I/O and the Outcome/Maybe/variant helpers
+    Core,
+    /// Iterator functions (`runtime-iter`)
+    Iter,
+    /// Shared<T> and Cell<T> operations (`runtime-smartptr`)
+    SmartPtr,
+}
+
+impl BuiltinGroup {
+    /// Every group, in registration order
+    pub const ALL: [BuiltinGroup; 5] = [
+        BuiltinGroup::Text,
+        BuiltinGroup::Math,
+        BuiltinGroup::Core,
+        BuiltinGroup::Iter,
+        BuiltinGroup::SmartPtr,
+    ];
+
+    /// Whether the group was compiled into this build
+    pub fn is_enabled(self) -> bool {
+        match self {
+            BuiltinGroup::Text => cfg!(feature = "runtime-text"),
+            BuiltinGroup::Math => cfg!(feature = "runtime-math"),
+            BuiltinGroup::Core => true,
+            BuiltinGroup::Iter => cfg!(feature = "runtime-iter"),
+            BuiltinGroup::SmartPtr => cfg!(feature = "runtime-smartptr"),
+        }
+    }
+
+    /// Native functions of the group; empty when the group is compiled out
+    pub fn functions(self) -> Vec<NativeFunction> {
+        match self {
+            #[cfg(feature = "runtime-text")]
+            BuiltinGroup::Text => text_builtins(),
+            #[cfg(feature = "runtime-math")]
+            BuiltinGroup::Math => math_builtins(),
+            BuiltinGroup::Core => core_builtins(),
+            #[cfg(feature = "runtime-iter")]
+            BuiltinGroup::Iter => iter_builtins(),
+            #[cfg(feature = "runtime-smartptr")]
+            BuiltinGroup::SmartPtr => smartptr_builtins(),
+            #[allow(unreachable_patterns)]
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Registry of the builtin functions available in this build
+#[derive(Debug, Clone, Default)]
+pub struct BuiltinRegistry {
+    functions: Vec<NativeFunction>,
+}
+
+impl BuiltinRegistry {
+    /// Registry holding every enabled group
+    pub fn new() -> Self {
+        let mut registry = BuiltinRegistry::empty();
+        for group in BuiltinGroup::ALL {
+            registry.register_group(group);
+        }
+        registry
+    }
+
+    /// Registry without any builtins
+    pub fn empty() -> Self {
+        BuiltinRegistry { functions: Vec::new() }
+    }
+
+    /// Register the functions of `group`; a compiled-out group adds nothing
+    pub fn register_group(&mut self, group: BuiltinGroup) -> &mut Self {
+        for function in group.functions() {
+            self.register(function);
+        }
+        self
+    }
+
+    /// Register a single native function, replacing one of the same name
+    pub fn register(&mut self, function: NativeFunction) -> &mut Self {
+        match self.functions.iter_mut().find(|existing| existing.name == function.name) {
+            Some(existing) => *existing = function,
+            None => self.functions.push(function),
+        }
+        self
+    }
+
+    /// Look up a registered function by name
+    pub fn get(&self, name: &str) -> Option<&NativeFunction> {
+        self.functions.iter().find(|function| function.name == name)
+    }
+
+    /// Look up a function by its flat name or as a module member
+    /// (`Text.upper`)
+    pub fn describe(&self, name: &str) -> Option<&NativeFunction> {
+        if let Some(function) = self.get(name) {
+            return Some(function);
+        }
+        let (module, member) = name.split_once('.')?;
+        let (_, members) = BUILTIN_MODULES.iter().find(|(candidate, _)| *candidate == module)?;
+        let (_, builtin) = members.iter().find(|(candidate, _)| *candidate == member)?;
+        self.get(builtin)
+    }
+
+    /// Functions whose name starts with `prefix`, sorted by name
+    pub fn completions(&self, prefix: &str) -> Vec<&NativeFunction> {
+        let mut matches: Vec<&NativeFunction> = self.functions.iter().filter(|function| function.name.starts_with(prefix)).collect();
+        matches.sort_by(|a, b| a.name.cmp(&b.name));
+        matches
+    }
+
+    /// Functions that need `capability`
+    pub fn requiring<'a>(&'a self, capability: &'a str) -> impl Iterator<Item = &'a NativeFunction> + 'a {
+        self.functions.iter().filter(move |function| function.capabilities.contains(&capability))
+    }
+
+    /// Whether a function of that name is registered
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Registered functions, in registration order
+    pub fn functions(&self) -> &[NativeFunction] {
+        &self.functions
+    }
+
+    /// Consume the registry, returning its functions
+    pub fn into_functions(self) -> Vec<NativeFunction> {
+        self.functions
+    }
+}
+
+/// Get all builtin functions compiled into this build
 pub fn get_builtins() -> Vec<NativeFunction> {
+    BuiltinRegistry::new().into_functions()
+}
+
+/// String functions
+#[cfg(feature = "runtime-text")]
+fn text_builtins() -> Vec<NativeFunction> {
     vec![
-        // === String Functions ===
-        NativeFunction::new("length", Some(1), string_length),
-        NativeFunction::new("slice", Some(3), string_slice),
-        NativeFunction::new("concat", Some(2), string_concat),
-        NativeFunction::new("upper", Some(1), string_upper),
-        NativeFunction::new("lower", Some(1), string_lower),
-        NativeFunction::new("split", Some(2), string_split),
-        NativeFunction::new("join", Some(2), string_join),
-        NativeFunction::new("trim", Some(1), string_trim),
-        NativeFunction::new("starts_with", Some(2), string_starts_with),
-        NativeFunction::new("ends_with", Some(2), string_ends_with),
-        NativeFunction::new("contains", Some(2), string_contains),
-        NativeFunction::new("replace", Some(3), string_replace),
-        NativeFunction::new("char_at", Some(2), string_char_at),
-        NativeFunction::new("repeat", Some(2), string_repeat),
-        NativeFunction::new("pad_left", Some(3), string_pad_left),
-        NativeFunction::new("pad_right", Some(3), string_pad_right),
-        NativeFunction::new("reverse", Some(1), string_reverse),
-
-        // === Math Functions ===
-        NativeFunction::new("abs", Some(1), math_abs),
-        NativeFunction::new("sqrt", Some(1), math_sqrt),
-        NativeFunction::new("pow", Some(2), math_pow),
-        NativeFunction::new("min", Some(2), math_min),
-        NativeFunction::new("max", Some(2), math_max),
-        NativeFunction::new("floor", Some(1), math_floor),
-        NativeFunction::new("ceil", Some(1), math_ceil),
-        NativeFunction::new("round", Some(1), math_round),
-        NativeFunction::new("sign", Some(1), math_sign),
-        NativeFunction::new("clamp", Some(3), math_clamp),
-        NativeFunction::new("sin", Some(1), math_sin),
-        NativeFunction::new("cos", Some(1), math_cos),
-        NativeFunction::new("tan", Some(1), math_tan),
-        NativeFunction::new("log", Some(1), math_log),
-        NativeFunction::new("exp", Some(1), math_exp),
+        NativeFunction::new("length", Some(1), string_length)
+            .doc("(text: Text) -> Number", "Number of characters in the text"),
+        NativeFunction::new("slice", Some(3), string_slice)
+            .doc("(text: Text, start: Number, end: Number) -> Text", "Characters from start up to but not including end"),
+        NativeFunction::new("concat", Some(2), string_concat)
+            .doc("(a: Text, b: Text) -> Text", "The two texts joined"),
+        NativeFunction::new("upper", Some(1), string_upper)
+            .doc("(text: Text) -> Text", "The text in uppercase"),
+        NativeFunction::new("lower", Some(1), string_lower)
+            .doc("(text: Text) -> Text", "The text in lowercase"),
+        NativeFunction::new("split", Some(2), string_split)
+            .doc("(text: Text, separator: Text) -> List<Text>", "Pieces of the text between separators"),
+        NativeFunction::new("join", Some(2), string_join)
+            .doc("(items: List<Text>, separator: Text) -> Text", "The texts joined with the separator between them"),
+        NativeFunction::new("trim", Some(1), string_trim)
+            .doc("(text: Text) -> Text", "The text without leading and trailing whitespace"),
+        NativeFunction::new("starts_with", Some(2), string_starts_with)
+            .doc("(text: Text, prefix: Text) -> Truth", "Whether the text starts with the prefix"),
+        NativeFunction::new("ends_with", Some(2), string_ends_with)
+            .doc("(text: Text, suffix: Text) -> Truth", "Whether the text ends with the suffix"),
+        NativeFunction::new("contains", Some(2), string_contains)
+            .doc("(text: Text, needle: Text) -> Truth", "Whether the needle occurs in the text"),
+        NativeFunction::new("replace", Some(3), string_replace)
+            .doc("(text: Text, from: Text, to: Text) -> Text", "The text with every occurrence of from replaced by to"),
+        NativeFunction::new("char_at", Some(2), string_char_at)
+            .doc("(text: Text, index: Number) -> Text", "The character at the index"),
+        NativeFunction::new("repeat", Some(2), string_repeat)
+            .doc("(text: Text, times: Number) -> Text", "The text repeated the given number of times"),
+        NativeFunction::new("pad_left", Some(3), string_pad_left)
+            .doc("(text: Text, width: Number, pad: Text) -> Text", "The text padded on the left to the width"),
+        NativeFunction::new("pad_right", Some(3), string_pad_right)
+            .doc("(text: Text, width: Number, pad: Text) -> Text", "The text padded on the right to the width"),
+        NativeFunction::new("reverse", Some(1), string_reverse)
+            .doc("(text: Text) -> Text", "The characters of the text in reverse order"),
+        NativeFunction::new("text_builder", Some(0), text_builder)
+            .doc("() -> TextBuilder", "An empty text builder"),
+        NativeFunction::new("text_push", Some(2), text_push)
+            .doc("(builder: TextBuilder, value: Any) -> TextBuilder", "The builder with the value's text appended"),
+        NativeFunction::new("text_push_text", Some(2), text_push_text)
+            .doc("(builder: TextBuilder, text: Text) -> TextBuilder", "The builder with the text appended, rejecting anything else"),
+        NativeFunction::new("text_build", Some(1), text_build)
+            .doc("(builder: TextBuilder) -> Text", "The text built so far"),
+    ]
+}
 
+/// Math functions
+#[cfg(feature = "runtime-math")]
+#[cfg(feature = "runtime-math")]
+fn math_builtins() -> Vec<NativeFunction> {
+    vec![
+        NativeFunction::new("abs", Some(1), math_abs)
+            .doc("(n: Number) -> Number", "Absolute value"),
+        NativeFunction::new("sqrt", Some(1), math_sqrt)
+            .doc("(n: Number) -> Number", "Square root"),
+        NativeFunction::new("pow", Some(2), math_pow)
+            .doc("(base: Number, exponent: Number) -> Number", "The base raised to the exponent"),
+        NativeFunction::new("min", Some(2), math_min)
+            .doc("(a: Number, b: Number) -> Number", "The smaller of two numbers"),
+        NativeFunction::new("max", Some(2), math_max)
+            .doc("(a: Number, b: Number) -> Number", "The larger of two numbers"),
+        NativeFunction::new("floor", Some(1), math_floor)
+            .doc("(n: Number) -> Number", "Largest whole number not above n"),
+        NativeFunction::new("ceil", Some(1), math_ceil)
+            .doc("(n: Number) -> Number", "Smallest whole number not below n"),
+        NativeFunction::new("round", Some(1), math_round)
+            .doc("(n: Number) -> Number", "Nearest whole number, halves away from zero"),
+        NativeFunction::new("sign", Some(1), math_sign)
+            .doc("(n: Number) -> Number", "-1, 0 or 1 by the sign of n"),
+        NativeFunction::new("clamp", Some(3), math_clamp)
+            .doc("(n: Number, low: Number, high: Number) -> Number", "n limited to the range from low to high"),
+        NativeFunction::new("sin", Some(1), math_sin)
+            .doc("(radians: Number) -> Number", "Sine"),
+        NativeFunction::new("cos", Some(1), math_cos)
+            .doc("(radians: Number) -> Number", "Cosine"),
+        NativeFunction::new("tan", Some(1), math_tan)
+            .doc("(radians: Number) -> Number", "Tangent"),
+        NativeFunction::new("log", Some(1), math_log)
+            .doc("(n: Number) -> Number", "Natural logarithm"),
+        NativeFunction::new("exp", Some(1), math_exp)
+            .doc("(n: Number) -> Number", "e raised to n"),
+    ]
+}
+
+/// Builtins that are always compiled in
+fn core_builtins() -> Vec<NativeFunction> {
+    vec![
         // === List Functions ===
-        NativeFunction::new("list_length", Some(1), list_length),
-        NativeFunction::new("list_push", Some(2), list_push),
-        NativeFunction::new("list_pop", Some(1), list_pop),
-        NativeFunction::new("list_reverse", Some(1), list_reverse),
-        NativeFunction::new("list_first", Some(1), list_first),
-        NativeFunction::new("list_last", Some(1), list_last),
-        NativeFunction::new("list_concat", Some(2), list_concat),
-        NativeFunction::new("list_slice", Some(3), list_slice),
-        NativeFunction::new("list_flatten", Some(1), list_flatten),
-        NativeFunction::new("list_sum", Some(1), list_sum),
-        NativeFunction::new("list_product", Some(1), list_product),
-        NativeFunction::new("list_min", Some(1), list_min),
-        NativeFunction::new("list_max", Some(1), list_max),
-        NativeFunction::new("list_contains", Some(2), list_contains),
-        NativeFunction::new("list_index_of", Some(2), list_index_of),
+        NativeFunction::new("list_length", Some(1), list_length)
+            .doc("(list: List) -> Number", "Number of items in the list"),
+        NativeFunction::new("list_push", Some(2), list_push)
+            .doc("(list: List, item: Any) -> List", "The list with the item appended"),
+        NativeFunction::new("list_pop", Some(1), list_pop)
+            .doc("(list: List) -> List", "The list without its last item"),
+        NativeFunction::new("list_reverse", Some(1), list_reverse)
+            .doc("(list: List) -> List", "The items in reverse order"),
+        NativeFunction::new("list_first", Some(1), list_first)
+            .doc("(list: List) -> Any", "The first item; an error for an empty list"),
+        NativeFunction::new("list_last", Some(1), list_last)
+            .doc("(list: List) -> Any", "The last item; an error for an empty list"),
+        NativeFunction::new("list_concat", Some(2), list_concat)
+            .doc("(a: List, b: List) -> List", "The items of both lists"),
+        NativeFunction::new("list_slice", Some(3), list_slice)
+            .doc("(list: List, start: Number, end: Number) -> List", "Items from start up to but not including end"),
+        NativeFunction::new("list_flatten", Some(1), list_flatten)
+            .doc("(lists: List<List>) -> List", "The items of the inner lists in one list"),
+        NativeFunction::new("list_sum", Some(1), list_sum)
+            .doc("(numbers: List<Number>) -> Number", "Sum of the numbers"),
+        NativeFunction::new("list_product", Some(1), list_product)
+            .doc("(numbers: List<Number>) -> Number", "Product of the numbers"),
+        NativeFunction::new("list_min", Some(1), list_min)
+            .doc("(numbers: List<Number>) -> Number", "Smallest of the numbers"),
+        NativeFunction::new("list_max", Some(1), list_max)
+            .doc("(numbers: List<Number>) -> Number", "Largest of the numbers"),
+        NativeFunction::new("list_contains", Some(2), list_contains)
+            .doc("(list: List, item: Any) -> Truth", "Whether the item is in the list"),
+        NativeFunction::new("list_index_of", Some(2), list_index_of)
+            .doc("(list: List, item: Any) -> Number", "Index of the first equal item, or -1"),
+        NativeFunction::new("list_sort", Some(1), list_sort)
+            .doc("(list: List) -> List", "The items in ascending order"),
 
         // === Map Functions ===
-        NativeFunction::new("map_keys", Some(1), map_keys),
-        NativeFunction::new("map_values", Some(1), map_values),
-        NativeFunction::new("map_has", Some(2), map_has),
-        NativeFunction::new("map_size", Some(1), map_size),
+        NativeFunction::new("map_keys", Some(1), map_keys)
+            .doc("(map: Map) -> List<Text>", "Keys of the map, in order"),
+        NativeFunction::new("map_values", Some(1), map_values)
+            .doc("(map: Map) -> List", "Values of the map, in key order"),
+        NativeFunction::new("map_has", Some(2), map_has)
+            .doc("(map: Map, key: Text) -> Truth", "Whether the map has the key"),
+        NativeFunction::new("map_size", Some(1), map_size)
+            .doc("(map: Map) -> Number", "Number of entries in the map"),
+
+        // === Set Functions ===
+        NativeFunction::new("set_new", None, set_new)
+            .doc("(items?: List) -> Set", "A set of the list's distinct items, or an empty set"),
+        NativeFunction::new("set_insert", Some(2), set_insert)
+            .doc("(set: Set, item: Any) -> Set", "The set with the item added"),
+        NativeFunction::new("set_contains", Some(2), set_contains)
+            .doc("(set: Set, item: Any) -> Truth", "Whether the item is in the set"),
+        NativeFunction::new("set_union", Some(2), set_union)
+            .doc("(a: Set, b: Set) -> Set", "Items in either set"),
+        NativeFunction::new("set_intersect", Some(2), set_intersect)
+            .doc("(a: Set, b: Set) -> Set", "Items in both sets"),
 
         // === Type Conversion ===
-        NativeFunction::new("to_text", Some(1), to_text),
-        NativeFunction::new("to_number", Some(1), to_number),
-        NativeFunction::new("to_truth", Some(1), to_truth),
-        NativeFunction::new("type_of", Some(1), type_of),
+        NativeFunction::new("to_text", Some(1), to_text)
+            .doc("(value: Any) -> Text", "The value as text"),
+        NativeFunction::new("to_number", Some(1), to_number)
+            .doc("(value: Any) -> Number", "The Number a Number, Text or Truth stands for"),
+        NativeFunction::new("to_number_checked", Some(1), to_number_checked)
+            .doc("(value: Any) -> Outcome<Number, Text>", "Like to_number, but a Mishap instead of an error, NaN or infinity"),
+        NativeFunction::new("to_int_checked", Some(1), to_int_checked)
+            .doc("(value: Any) -> Outcome<Number, Text>", "The integer a Number or Text holds exactly, or a Mishap"),
+        NativeFunction::new("truncate", Some(1), truncate)
+            .doc("(n: Number) -> Outcome<Number, Text>", "n without its fraction; a Mishap beyond 64 bits"),
+        NativeFunction::new("saturate", Some(1), saturate)
+            .doc("(n: Number) -> Outcome<Number, Text>", "n without its fraction, clamped to 64 bits; a Mishap for NaN"),
+        NativeFunction::new("to_integer", Some(1), to_integer)
+            .doc("(value: Any) -> Integer", "The 64-bit integer a whole Number, decimal Text or big integer holds"),
+        NativeFunction::new("bigint", Some(1), bigint)
+            .doc("(value: Any) -> BigInt", "A big integer from a whole Number, decimal Text or big integer"),
+        NativeFunction::new("bigint_pow", Some(2), bigint_pow)
+            .doc("(base: BigInt, exponent: Number) -> BigInt", "The base raised to a whole, non-negative exponent"),
+        NativeFunction::new("bigint_mod_pow", Some(3), bigint_mod_pow)
+            .doc("(base: BigInt, exponent: BigInt, modulus: BigInt) -> BigInt", "The base raised to the exponent, modulo the modulus"),
+        NativeFunction::new("to_truth", Some(1), to_truth)
+            .doc("(value: Any) -> Truth", "Whether the value is truthy"),
+        NativeFunction::new("type_of", Some(1), type_of)
+            .doc("(value: Any) -> Text", "Name of the value's type"),
+        NativeFunction::new("hash", Some(1), hash)
+            .doc("(value: Any) -> Number", "Stable hash of the value"),
+        NativeFunction::new("value_encode", Some(1), value_encode)
+            .doc("(value: Any) -> List<Number>", "Bytes of the value's binary encoding"),
+        NativeFunction::new("value_decode", Some(1), value_decode)
+            .doc("(bytes: List<Number>) -> Any", "The value a binary encoding holds"),
+        NativeFunction::new("validate", Some(2), validate)
+            .doc("(value: Any, form: Form) -> Outcome<Any, Text>", "An instance of the form built from the value, or the first problem"),
+        NativeFunction::new("value_diff", Some(2), value_diff)
+            .doc("(old: Any, new: Any) -> List<Map>", "Changes turning the first value into the second"),
+        NativeFunction::new("value_patch", Some(2), value_patch)
+            .doc("(value: Any, changes: List<Map>) -> Any", "The value with the changes from value_diff applied"),
+        NativeFunction::new("freeze", Some(1), freeze)
+            .doc("(value: Any) -> Any", "A deeply immutable copy of the value"),
+        NativeFunction::new("is_frozen", Some(1), is_frozen)
+            .doc("(value: Any) -> Truth", "Whether the value is frozen"),
+        NativeFunction::new("thaw", Some(1), thaw)
+            .doc("(value: Any) -> Any", "A mutable copy of a frozen value"),
+        NativeFunction::new("config_parse_toml", Some(1), config_parse_toml)
+            .doc("(text: Text) -> Outcome<Map, Text>", "A Map from TOML text"),
+        NativeFunction::new("config_parse_ini", Some(1), config_parse_ini)
+            .doc("(text: Text) -> Outcome<Map, Text>", "A Map from INI text"),
+        NativeFunction::new("config_emit_toml", Some(1), config_emit_toml)
+            .doc("(config: Map) -> Text", "The Map as TOML text"),
+        NativeFunction::new("config_emit_ini", Some(1), config_emit_ini)
+            .doc("(config: Map) -> Text", "The Map as INI text"),
+        NativeFunction::new("csv_parse", None, csv_parse)
+            .doc("(text: Text, options?: Map) -> Outcome<List, Text>", "Rows of CSV text"),
+        NativeFunction::new("csv_emit", None, csv_emit)
+            .doc("(rows: List, options?: Map) -> Text", "Rows as CSV text"),
 
         // === I/O Functions ===
-        NativeFunction::new("print", None, io_print),
-        NativeFunction::new("println", None, io_println),
+        NativeFunction::new("print", None, io_print)
+            .doc("(values...: Any) -> Nothing", "Print the values"),
+        NativeFunction::new("println", None, io_println)
+            .doc("(values...: Any) -> Nothing", "Print the values and a newline"),
+
+        // === Program Functions ===
+        NativeFunction::new("exit", Some(1), program_exit)
+            .doc("(status: Number) -> Nothing", "End the program with the status"),
+
+        // === Task Functions ===
+        // Dispatched by the evaluator to its scheduler
+        NativeFunction::new("spawn", None, task_spawn)
+            .doc("(chant: Chant, args...: Any) -> Number", "Run the chant as a task; its task id"),
+        NativeFunction::new("yield_now", Some(0), task_yield_now)
+            .doc("() -> Nothing", "Let other tasks run"),
+        NativeFunction::new("block_on_event", Some(1), task_block_on_event)
+            .doc("(event: Number) -> Nothing", "Wait until the event is signalled"),
+        NativeFunction::new("signal_event", Some(1), task_signal_event)
+            .doc("(event: Number) -> Nothing", "Wake tasks waiting on the event"),
+
+        // === Watchdog Functions ===
+        // Dispatched by the evaluator to its watchdog
+        NativeFunction::new("watchdog", None, watchdog_arm)
+            .doc("(interval: Number, name?: Text) -> Nothing", "Arm a watchdog that fires without a heartbeat in the interval"),
+        NativeFunction::new("heartbeat", Some(0), watchdog_heartbeat)
+            .doc("() -> Nothing", "Reset the watchdog"),
+
+        // === Resource Functions ===
+        // Dispatched by the evaluator to its resource table
+        NativeFunction::new("release", Some(1), resource_release)
+            .doc("(resource: Resource) -> Nothing", "Release a resource before its scope ends"),
+
+        // === Store Functions ===
+        // Dispatched by the evaluator to its storage provider
+        NativeFunction::new("store_get", Some(1), store_get)
+            .doc("(key: Text) -> Maybe", "The value stored under the key")
+            .requires(&["Store.readwrite"]),
+        NativeFunction::new("store_set", Some(2), store_set)
+            .doc("(key: Text, value: Any) -> Nothing", "Store the value under the key")
+            .requires(&["Store.readwrite"]),
+        NativeFunction::new("store_delete", Some(1), store_delete)
+            .doc("(key: Text) -> Truth", "Delete the key; whether it was stored")
+            .requires(&["Store.readwrite"]),
+
+        // === Bus Functions ===
+        // Dispatched by the evaluator to its session's message bus
+        NativeFunction::new("publish", Some(2), bus_publish)
+            .doc("(topic: Text, message: Any) -> Nothing", "Send a message to the topic's subscribers; needs the topic namespace's capability"),
+        NativeFunction::new("subscribe", None, bus_subscribe)
+            .doc("(topic: Text, handler: Chant, priority?: Number) -> Nothing", "Call the handler with the topic's messages; needs the topic namespace's capability"),
+
+        // === Net Functions ===
+        // Dispatched by the evaluator to its network provider
+        NativeFunction::new("tcp_connect", Some(1), net_builtin)
+            .doc("(endpoint: Capability) -> Outcome<Resource, Text>", "A socket to the endpoint a Net.connect capability grants")
+            .requires(&["Net.connect"]),
+        NativeFunction::new("net_send", Some(2), net_builtin)
+            .doc("(socket: Resource, text: Text) -> Outcome<Number, Text>", "Send text on the socket; the bytes sent"),
+        NativeFunction::new("net_recv", Some(2), net_builtin)
+            .doc("(socket: Resource, max: Number) -> Outcome<Text, Text>", "Up to max bytes from the socket"),
+        NativeFunction::new("net_close", Some(1), net_builtin)
+            .doc("(socket: Resource) -> Nothing", "Close the socket"),
+        NativeFunction::new("http_get", None, net_builtin)
+            .doc("(url: Text, options?: Map) -> Outcome<Map, Text>", "GET the URL")
+            .requires(&["Net.connect"]),
+        NativeFunction::new("http_post", None, net_builtin)
+            .doc("(url: Text, body: Text, options?: Map) -> Outcome<Map, Text>", "POST the body to the URL")
+            .requires(&["Net.connect"]),
+
+        // === Term Functions ===
+        // Dispatched by the evaluator to its terminal
+        NativeFunction::new("term_move_to", Some(2), term_builtin)
+            .doc("(row: Number, column: Number) -> Nothing", "Move the terminal cursor")
+            .requires(&["Term.control"]),
+        NativeFunction::new("term_set_color", None, term_builtin)
+            .doc("(fg: Text, bg?: Text) -> Nothing", "Set the terminal colors")
+            .requires(&["Term.control"]),
+        NativeFunction::new("term_clear", Some(0), term_builtin)
+            .doc("() -> Nothing", "Clear the terminal")
+            .requires(&["Term.control"]),
+        NativeFunction::new("term_read_key", Some(0), term_builtin)
+            .doc("() -> Maybe<Text>", "The next key pressed, if any")
+            .requires(&["Console.read"]),
+
+        // === Draw Functions ===
+        // Dispatched by the evaluator to its framebuffer
+        NativeFunction::new("draw_pixel", Some(3), draw_builtin)
+            .doc("(x: Number, y: Number, color: Number) -> Nothing", "Set a pixel of the back buffer")
+            .requires(&["Framebuffer.draw"]),
+        NativeFunction::new("draw_rect", Some(5), draw_builtin)
+            .doc("(x: Number, y: Number, width: Number, height: Number, color: Number) -> Nothing", "Fill a rectangle of the back buffer")
+            .requires(&["Framebuffer.draw"]),
+        NativeFunction::new("draw_blit", Some(4), draw_builtin)
+            .doc("(x: Number, y: Number, width: Number, pixels: List<Number>) -> Nothing", "Copy rows of pixels to the back buffer")
+            .requires(&["Framebuffer.draw"]),
+        NativeFunction::new("draw_text", Some(4), draw_builtin)
+            .doc("(x: Number, y: Number, text: Text, color: Number) -> Number", "Draw text to the back buffer; its width in pixels")
+            .requires(&["Framebuffer.draw"]),
+        NativeFunction::new("draw_clear", Some(1), draw_builtin)
+            .doc("(color: Number) -> Nothing", "Fill the back buffer")
+            .requires(&["Framebuffer.draw"]),
+        NativeFunction::new("draw_present", Some(0), draw_builtin)
+            .doc("() -> Nothing", "Show the back buffer")
+            .requires(&["Framebuffer.draw"]),
+        NativeFunction::new("draw_size", Some(0), draw_builtin)
+            .doc("() -> Map", "Width and height of the framebuffer")
+            .requires(&["Framebuffer.draw"]),
+
+        // === Sound Functions ===
+        // Dispatched by the evaluator to its speaker
+        NativeFunction::new("sound_tone", Some(2), sound_builtin)
+            .doc("(frequency: Number, duration: Number) -> Nothing", "Play a tone, in hertz for milliseconds")
+            .requires(&["Speaker.play"]),
+        NativeFunction::new("sound_stop", Some(0), sound_builtin)
+            .doc("() -> Nothing", "Silence the speaker")
+            .requires(&["Speaker.play"]),
+
+        // === Progress Functions ===
+        // Dispatched by the evaluator to its progress sink
+        NativeFunction::new("with_progress", Some(2), progress_builtin)
+            .doc("(total: Number, chant: Chant) -> Any", "Run the chant with a progress handle for total steps"),
+        NativeFunction::new("progress_update", None, progress_builtin)
+            .doc("(progress: Resource, done: Number, message?: Text) -> Nothing", "Report steps done"),
+
+        // === Localization Functions ===
+        // Dispatched by the evaluator to its message catalogs
+        NativeFunction::new("tr", None, i18n_builtin)
+            .doc("(key: Text, args?: Map) -> Text", "The message for the key in the current locale"),
+        NativeFunction::new("catalog_load", Some(2), i18n_builtin)
+            .doc("(locale: Text, text: Text) -> Nothing", "Load a message catalog for the locale"),
+        NativeFunction::new("set_locale", Some(1), i18n_builtin)
+            .doc("(locale: Text) -> Nothing", "Switch the locale tr looks messages up in"),
+
+        // === Compiler Functions ===
+        // Dispatched by the evaluator, which holds the capability grants
+        NativeFunction::new("compiler_lex", Some(1), compiler_builtin)
+            .doc("(source: Text) -> List<Map>", "The tokens of the source")
+            .requires(&["Compiler.inspect"]),
+        NativeFunction::new("compiler_parse", Some(1), compiler_builtin)
+            .doc("(source: Text) -> Outcome<List<Map>, List<Map>>", "The syntax tree of the source, or its diagnostics")
+            .requires(&["Compiler.inspect"]),
+        NativeFunction::new("compiler_analyze", Some(1), compiler_builtin)
+            .doc("(source: Text) -> List<Map>", "Diagnostics of parsing and analyzing the source")
+            .requires(&["Compiler.inspect"]),
+        NativeFunction::new("compiler_compile", Some(2), compiler_builtin)
+            .doc("(source: Text, target: Text) -> Outcome<Text, List<Map>>", "Bytecode disassembly or assembly of the source, or its diagnostics")
+            .requires(&["Compiler.inspect"]),
+
+        // === Capability Functions ===
+        // Dispatched by the evaluator to its capability audit log
+        NativeFunction::new("capabilities", Some(0), capability_log)
+            .doc("() -> List<Map>", "The capability audit log")
+            .requires(&["Audit.read"]),
+
+        // === Heap Functions ===
+        NativeFunction::new("heap_used", Some(0), heap_used)
+            .doc("() -> Number", "Bytes allocated on the native heap"),
+        NativeFunction::new("heap_free", Some(0), heap_free)
+            .doc("() -> Number", "Bytes free on the native heap"),
 
         // === Outcome<T, E> Helper Functions ===
         // Inspection
-        NativeFunction::new("is_triumph", Some(1), is_triumph),
-        NativeFunction::new("is_mishap", Some(1), is_mishap),
+        NativeFunction::new("is_triumph", Some(1), is_triumph)
+            .doc("(outcome: Outcome) -> Truth", "Whether the outcome is a Triumph"),
+        NativeFunction::new("is_mishap", Some(1), is_mishap)
+            .doc("(outcome: Outcome) -> Truth", "Whether the outcome is a Mishap"),
 
         // Extraction
-        NativeFunction::new("expect_triumph", Some(2), expect_triumph),
-        NativeFunction::new("triumph_or", Some(2), triumph_or),
-        NativeFunction::new("triumph_or_else", Some(2), triumph_or_else),
-        NativeFunction::new("expect_mishap", Some(2), expect_mishap),
+        NativeFunction::new("expect_triumph", Some(2), expect_triumph)
+            .doc("(outcome: Outcome, message: Text) -> Any", "The Triumph's value, or an error with the message"),
+        NativeFunction::new("triumph_or", Some(2), triumph_or)
+            .doc("(outcome: Outcome, default: Any) -> Any", "The Triumph's value, or the default"),
+        NativeFunction::new("triumph_or_else", Some(2), triumph_or_else)
+            .doc("(outcome: Outcome, fallback: Chant) -> Any", "The Triumph's value, or the fallback's result"),
+        NativeFunction::new("expect_mishap", Some(2), expect_mishap)
+            .doc("(outcome: Outcome, message: Text) -> Any", "The Mishap's value, or an error with the message"),
 
         // Transformation
-        NativeFunction::new("refine_triumph", Some(2), refine_triumph),
-        NativeFunction::new("refine_mishap", Some(2), refine_mishap),
+        NativeFunction::new("refine_triumph", Some(2), refine_triumph)
+            .doc("(outcome: Outcome, transform: Chant) -> Outcome", "The outcome with its Triumph value transformed"),
+        NativeFunction::new("refine_mishap", Some(2), refine_mishap)
+            .doc("(outcome: Outcome, transform: Chant) -> Outcome", "The outcome with its Mishap value transformed"),
 
         // Chaining
-        NativeFunction::new("then_triumph", Some(2), then_triumph),
+        NativeFunction::new("then_triumph", Some(2), then_triumph)
+            .doc("(outcome: Outcome, next: Chant) -> Outcome", "The next chant's outcome for a Triumph's value"),
 
         // === Maybe<T> Helper Functions ===
         // Inspection
-        NativeFunction::new("is_present", Some(1), is_present),
-        NativeFunction::new("is_absent", Some(1), is_absent),
+        NativeFunction::new("is_present", Some(1), is_present)
+            .doc("(maybe: Maybe) -> Truth", "Whether the maybe is Present"),
+        NativeFunction::new("is_absent", Some(1), is_absent)
+            .doc("(maybe: Maybe) -> Truth", "Whether the maybe is Absent"),
 
         // Extraction
-        NativeFunction::new("expect_present", Some(2), expect_present),
-        NativeFunction::new("present_or", Some(2), present_or),
-        NativeFunction::new("present_or_else", Some(2), present_or_else),
+        NativeFunction::new("expect_present", Some(2), expect_present)
+            .doc("(maybe: Maybe, message: Text) -> Any", "The Present value, or an error with the message"),
+        NativeFunction::new("present_or", Some(2), present_or)
+            .doc("(maybe: Maybe, default: Any) -> Any", "The Present value, or the default"),
+        NativeFunction::new("present_or_else", Some(2), present_or_else)
+            .doc("(maybe: Maybe, fallback: Chant) -> Any", "The Present value, or the fallback's result"),
 
         // Transformation
-        NativeFunction::new("refine_present", Some(2), refine_present),
+        NativeFunction::new("refine_present", Some(2), refine_present)
+            .doc("(maybe: Maybe, transform: Chant) -> Maybe", "The maybe with its value transformed"),
 
         // Chaining
-        NativeFunction::new("then_present", Some(2), then_present),
+        NativeFunction::new("then_present", Some(2), then_present)
+            .doc("(maybe: Maybe, next: Chant) -> Maybe", "The next chant's maybe for a Present value"),
 
         // === Conversion Functions ===
-        NativeFunction::new("present_or_mishap", Some(2), present_or_mishap),
-        NativeFunction::new("triumph_or_absent", Some(1), triumph_or_absent),
+        NativeFunction::new("present_or_mishap", Some(2), present_or_mishap)
+            .doc("(maybe: Maybe, error: Any) -> Outcome", "Triumph of the Present value, or Mishap of the error"),
+        NativeFunction::new("triumph_or_absent", Some(1), triumph_or_absent)
+            .doc("(outcome: Outcome) -> Maybe", "Present of the Triumph value, or Absent"),
 
         // === Combination Functions ===
-        NativeFunction::new("both_triumph", Some(2), both_triumph),
-        NativeFunction::new("either_triumph", Some(2), either_triumph),
+        NativeFunction::new("both_triumph", Some(2), both_triumph)
+            .doc("(a: Outcome, b: Outcome) -> Outcome", "Triumph of both values, or the first Mishap"),
+        NativeFunction::new("either_triumph", Some(2), either_triumph)
+            .doc("(a: Outcome, b: Outcome) -> Outcome", "The first outcome if a Triumph, else the second"),
 
         // === Enum (Variant) Helper Functions - Phase 4 ===
         // Inspection
-        NativeFunction::new("is_variant", Some(2), is_variant),
+        NativeFunction::new("is_variant", Some(2), is_variant)
+            .doc("(value: Any, variant: Text) -> Truth", "Whether the value is the named variant"),
 
         // Extraction
-        NativeFunction::new("expect_variant", Some(3), expect_variant),
-        NativeFunction::new("variant_or", Some(3), variant_or),
+        NativeFunction::new("expect_variant", Some(3), expect_variant)
+            .doc("(value: Any, variant: Text, message: Text) -> Any", "The variant's fields, or an error with the message"),
+        NativeFunction::new("variant_or", Some(3), variant_or)
+            .doc("(value: Any, variant: Text, default: Any) -> Any", "The variant's fields, or the default"),
 
         // Transformation
-        NativeFunction::new("refine_variant", Some(3), refine_variant),
+        NativeFunction::new("refine_variant", Some(3), refine_variant)
+            .doc("(value: Any, variant: Text, transform: Chant) -> Maybe", "The transformed fields of a matching variant, or Absent"),
+
+        // Discriminants
+        NativeFunction::new("variant_index", Some(1), variant_index)
+            .doc("(value: Any) -> Number", "Discriminant of a variant value"),
+        NativeFunction::new("from_index", Some(2), from_index)
+            .doc("(enum: Any, index: Number) -> Maybe", "The unit case of the enum with the discriminant"),
+    ]
+}
 
-        // === Iterator Functions - Phase 1 ===
+/// Iterator functions
+#[cfg(feature = "runtime-iter")]
+#[cfg(feature = "runtime-iter")]
+fn iter_builtins() -> Vec<NativeFunction> {
+    vec![
         // Core iteration
-        NativeFunction::new("iter", Some(1), iter_create),
-        NativeFunction::new("iter_next", Some(1), iter_next),
+        NativeFunction::new("iter", Some(1), iter_create)
+            .doc("(source: Any) -> Iterator", "An iterator over a list or range"),
+        NativeFunction::new("iter_next", Some(1), iter_next)
+            .doc("(iterator: Iterator) -> List", "The advanced iterator and a Maybe of the next item"),
 
         // Transformation
-        NativeFunction::new("iter_map", Some(2), iter_map),
-        NativeFunction::new("iter_filter", Some(2), iter_filter),
+        NativeFunction::new("iter_map", Some(2), iter_map)
+            .doc("(iterator: Iterator, transform: Chant) -> Iterator", "An iterator of transformed items"),
+        NativeFunction::new("iter_filter", Some(2), iter_filter)
+            .doc("(iterator: Iterator, keep: Chant) -> Iterator", "An iterator of the items keep accepts"),
 
         // Reduction
-        NativeFunction::new("iter_fold", Some(3), iter_fold),
-        NativeFunction::new("iter_collect", Some(1), iter_collect),
+        NativeFunction::new("iter_fold", Some(3), iter_fold)
+            .doc("(iterator: Iterator, initial: Any, combine: Chant) -> Any", "The items combined into one value"),
+        NativeFunction::new("iter_collect", Some(1), iter_collect)
+            .doc("(iterator: Iterator) -> List", "The remaining items in a list"),
 
         // Limiting
-        NativeFunction::new("iter_take", Some(2), iter_take),
+        NativeFunction::new("iter_take", Some(2), iter_take)
+            .doc("(iterator: Iterator, n: Number) -> Iterator", "An iterator of at most n items"),
+
+        // Streaming input
+        NativeFunction::new("fs_lines", Some(1), stream_open)
+            .doc("(path: Text) -> Iterator", "Lines of a file, read lazily")
+            .requires(&["FS.read"]),
+        NativeFunction::new("fs_bytes", Some(1), stream_open)
+            .doc("(path: Text) -> Iterator", "Bytes of a file, read lazily")
+            .requires(&["FS.read"]),
+        NativeFunction::new("console_read_lines", Some(0), stream_open)
+            .doc("() -> Iterator", "Lines of console input, read lazily")
+            .requires(&["Console.read"]),
+    ]
+}
 
-        // === Smart Pointer Functions ===
+/// Smart pointer functions
+#[cfg(feature = "runtime-smartptr")]
+fn smartptr_builtins() -> Vec<NativeFunction> {
+    vec![
         // Shared<T> (Rc-like) operations
-        NativeFunction::new("Shared_new", Some(1), shared_new),
-        NativeFunction::new("Shared_get", Some(1), shared_get),
-        NativeFunction::new("Shared_clone", Some(1), shared_clone),
-        NativeFunction::new("Shared_count", Some(1), shared_count),
+        NativeFunction::new("Shared_new", Some(1), shared_new)
+            .doc("(value: Any) -> Shared", "A reference-counted pointer to the value"),
+        NativeFunction::new("Shared_get", Some(1), shared_get)
+            .doc("(shared: Shared) -> Any", "The value the pointer holds"),
+        NativeFunction::new("Shared_clone", Some(1), shared_clone)
+            .doc("(shared: Shared) -> Shared", "Another reference to the value"),
+        NativeFunction::new("Shared_count", Some(1), shared_count)
+            .doc("(shared: Shared) -> Number", "Number of references to the value"),
 
         // Cell<T> (RefCell-like) operations
-        NativeFunction::new("Cell_new", Some(1), cell_new),
-        NativeFunction::new("Cell_get", Some(1), cell_get),
-        NativeFunction::new("Cell_set", Some(2), cell_set),
-        NativeFunction::new("Cell_borrow", Some(1), cell_borrow),
-        NativeFunction::new("Cell_borrow_mut", Some(1), cell_borrow_mut),
-        NativeFunction::new("Cell_release", Some(1), cell_release),
+        NativeFunction::new("Cell_new", Some(1), cell_new)
+            .doc("(value: Any) -> Cell", "A cell holding the value"),
+        NativeFunction::new("Cell_get", Some(1), cell_get)
+            .doc("(cell: Cell) -> Any", "The value in the cell"),
+        NativeFunction::new("Cell_set", Some(2), cell_set)
+            .doc("(cell: Cell, value: Any) -> Nothing", "Replace the value in the cell"),
+        NativeFunction::new("Cell_borrow", Some(1), cell_borrow)
+            .doc("(cell: Cell) -> Any", "Borrow the value immutably"),
+        NativeFunction::new("Cell_borrow_mut", Some(1), cell_borrow_mut)
+            .doc("(cell: Cell) -> Any", "Borrow the value mutably"),
+        NativeFunction::new("Cell_release", Some(1), cell_release)
+            .doc("(cell: Cell) -> Nothing", "Release a borrow of the cell"),
     ]
 }
 
+/// Namespaced builtin modules: module name and `(member, builtin)` pairs
+///
+/// Members are reached as `Text.upper`, `List.push`, `Math.sqrt`, ...; the
+/// builtin is the flat global name the function is also registered under.
+pub const BUILTIN_MODULES: &[(&str, &[(&str, &str)])] = &[
+    ("Text", &[
+        ("length", "length"),
+        ("slice", "slice"),
+        ("concat", "concat"),
+        ("upper", "upper"),
+        ("lower", "lower"),
+        ("split", "split"),
+        ("join", "join"),
+        ("trim", "trim"),
+        ("starts_with", "starts_with"),
+        ("ends_with", "ends_with"),
+        ("contains", "contains"),
+        ("replace", "replace"),
+        ("char_at", "char_at"),
+        ("repeat", "repeat"),
+        ("pad_left", "pad_left"),
+        ("pad_right", "pad_right"),
+        ("reverse", "reverse"),
+        ("builder", "text_builder"),
+        ("push", "text_push"),
+        ("push_text", "text_push_text"),
+        ("build", "text_build"),
+    ]),
+    ("Math", &[
+        ("abs", "abs"),
+        ("sqrt", "sqrt"),
+        ("pow", "pow"),
+        ("min", "min"),
+        ("max", "max"),
+        ("floor", "floor"),
+        ("ceil", "ceil"),
+        ("round", "round"),
+        ("sign", "sign"),
+        ("clamp", "clamp"),
+        ("sin", "sin"),
+        ("cos", "cos"),
+        ("tan", "tan"),
+        ("log", "log"),
+        ("exp", "exp"),
+    ]),
+    ("List", &[
+        ("length", "list_length"),
+        ("push", "list_push"),
+        ("pop", "list_pop"),
+        ("reverse", "list_reverse"),
+        ("first", "list_first"),
+        ("last", "list_last"),
+        ("concat", "list_concat"),
+        ("slice", "list_slice"),
+        ("flatten", "list_flatten"),
+        ("sum", "list_sum"),
+        ("product", "list_product"),
+        ("min", "list_min"),
+        ("max", "list_max"),
+        ("contains", "list_contains"),
+        ("index_of", "list_index_of"),
+        ("sort", "list_sort"),
+    ]),
+    ("Map", &[
+        ("keys", "map_keys"),
+        ("values", "map_values"),
+        ("has", "map_has"),
+        ("size", "map_size"),
+    ]),
+    ("Set", &[
+        ("new", "set_new"),
+        ("insert", "set_insert"),
+        ("contains", "set_contains"),
+        ("union", "set_union"),
+        ("intersect", "set_intersect"),
+    ]),
+    ("Convert", &[
+        ("to_text", "to_text"),
+        ("to_number", "to_number"),
+        ("to_number_checked", "to_number_checked"),
+        ("to_int_checked", "to_int_checked"),
+        ("truncate", "truncate"),
+        ("saturate", "saturate"),
+        ("to_integer", "to_integer"),
+        ("to_truth", "to_truth"),
+        ("type_of", "type_of"),
+        ("hash", "hash"),
+        ("encode", "value_encode"),
+        ("decode", "value_decode"),
+    ]),
+    ("Config", &[
+        ("parse_toml", "config_parse_toml"),
+        ("parse_ini", "config_parse_ini"),
+        ("emit_toml", "config_emit_toml"),
+        ("emit_ini", "config_emit_ini"),
+    ]),
+    ("Csv", &[
+        ("parse", "csv_parse"),
+        ("emit", "csv_emit"),
+    ]),
+    ("BigInt", &[
+        ("from", "bigint"),
+        ("pow", "bigint_pow"),
+        ("mod_pow", "bigint_mod_pow"),
+    ]),
+    ("Variant", &[
+        ("matches", "is_variant"),
+        ("expect", "expect_variant"),
+        ("value_or", "variant_or"),
+        ("refine", "refine_variant"),
+        ("index", "variant_index"),
+        ("from_index", "from_index"),
+    ]),
+    ("Task", &[
+        ("spawn", "spawn"),
+        ("yield_now", "yield_now"),
+        ("wait", "block_on_event"),
+        ("signal", "signal_event"),
+    ]),
+    ("Resource", &[
+        ("release", "release"),
+    ]),
+    ("Bus", &[
+        ("publish", "publish"),
+        ("subscribe", "subscribe"),
+    ]),
+    ("Net", &[
+        ("tcp_connect", "tcp_connect"),
+        ("send", "net_send"),
+        ("recv", "net_recv"),
+        ("close", "net_close"),
+        ("http_get", "http_get"),
+        ("http_post", "http_post"),
+    ]),
+    ("Term", &[
+        ("move_to", "term_move_to"),
+        ("set_color", "term_set_color"),
+        ("clear", "term_clear"),
+        ("read_key", "term_read_key"),
+    ]),
+    ("Draw", &[
+        ("pixel", "draw_pixel"),
+        ("rect", "draw_rect"),
+        ("blit", "draw_blit"),
+        ("text", "draw_text"),
+        ("clear", "draw_clear"),
+        ("present", "draw_present"),
+        ("size", "draw_size"),
+    ]),
+    ("Sound", &[
+        ("tone", "sound_tone"),
+        ("stop", "sound_stop"),
+    ]),
+    ("Progress", &[
+        ("run", "with_progress"),
+        ("update", "progress_update"),
+    ]),
+    ("Compiler", &[
+        ("lex", "compiler_lex"),
+        ("parse", "compiler_parse"),
+        ("analyze", "compiler_analyze"),
+        ("compile", "compiler_compile"),
+    ]),
+    ("I18n", &[
+        ("tr", "tr"),
+        ("load", "catalog_load"),
+        ("set_locale", "set_locale"),
+    ]),
+    ("Store", &[
+        ("get", "store_get"),
+        ("set", "store_set"),
+        ("delete", "store_delete"),
+    ]),
+    ("Heap", &[
+        ("used", "heap_used"),
+        ("free", "heap_free"),
+    ]),
+    ("Shared", &[
+        ("new", "Shared_new"),
+        ("get", "Shared_get"),
+        ("clone", "Shared_clone"),
+        ("count", "Shared_count"),
+    ]),
+    ("Cell", &[
+        ("new", "Cell_new"),
+        ("get", "Cell_get"),
+        ("set", "Cell_set"),
+        ("borrow", "Cell_borrow"),
+        ("borrow_mut", "Cell_borrow_mut"),
+        ("release", "Cell_release"),
+    ]),
+];
+
+/// Builtin modules with their members bound to native functions
+pub fn builtin_modules() -> Vec<(&'static str, Vec<(&'static str, NativeFunction)>)> {
+    let builtins = get_builtins();
+    BUILTIN_MODULES
+        .iter()
+        .map(|(module, members)| {
+            let members: Vec<_> = members
+                .iter()
+                .filter_map(|(member, builtin)| {
+                    builtins.iter().find(|b| b.name == *builtin).map(|b| (*member, b.clone()))
+                })
+                .collect();
+            (*module, members)
+        })
+        // Modules whose group is compiled out disappear entirely
+        .filter(|(_, members)| !members.is_empty())
+        .collect()
+}
+
 // ============================================================================
 // STRING FUNCTIONS
 // ============================================================================
 
+#[cfg(feature = "runtime-text")]
 fn string_length(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Text(s) => Ok(Value::Number(s.len() as f64)),
@@ -265,6 +1113,7 @@ fn string_length(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_slice(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1], &args[2]) {
         (Value::Text(s), Value::Number(start), Value::Number(end)) => {
@@ -278,7 +1127,14 @@ fn string_slice(args: &[Value]) -> Result<Value, RuntimeError> {
                 });
             }
 
-            Ok(Value::Text(s[start..end].to_string()))
+            // Indices count bytes; one inside a character has no slice
+            match s.get(start..end) {
+                Some(slice) => Ok(Value::Text(slice.to_string())),
+                None => Err(RuntimeError::Custom(format!(
+                    "slice: Index {} is inside a multi-byte character",
+                    if s.is_char_boundary(start) { end } else { start }
+                ))),
+            }
         }
         _ => Err(RuntimeError::TypeError {
             expected: "Text, Number, Number".to_string(),
@@ -287,6 +1143,7 @@ fn string_slice(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_concat(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1]) {
         (Value::Text(s1), Value::Text(s2)) => {
@@ -301,6 +1158,7 @@ fn string_concat(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_upper(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Text(s) => {
@@ -317,6 +1175,7 @@ fn string_upper(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_lower(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Text(s) => {
@@ -333,6 +1192,7 @@ fn string_lower(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_split(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1]) {
         (Value::Text(s), Value::Text(delimiter)) => {
@@ -348,6 +1208,7 @@ fn string_split(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_join(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1]) {
         (Value::List(items), Value::Text(separator)) => {
@@ -378,6 +1239,7 @@ fn string_join(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_trim(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Text(s) => Ok(Value::Text(s.trim().to_string())),
@@ -388,6 +1250,7 @@ fn string_trim(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_starts_with(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1]) {
         (Value::Text(s), Value::Text(prefix)) => {
@@ -400,6 +1263,7 @@ fn string_starts_with(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_ends_with(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1]) {
         (Value::Text(s), Value::Text(suffix)) => {
@@ -412,6 +1276,7 @@ fn string_ends_with(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_contains(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1]) {
         (Value::Text(s), Value::Text(substring)) => {
@@ -424,6 +1289,7 @@ fn string_contains(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_replace(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1], &args[2]) {
         (Value::Text(s), Value::Text(from), Value::Text(to)) => {
@@ -436,6 +1302,7 @@ fn string_replace(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_char_at(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1]) {
         (Value::Text(s), Value::Number(index)) => {
@@ -459,6 +1326,7 @@ fn string_char_at(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_repeat(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1]) {
         (Value::Text(s), Value::Number(n)) => {
@@ -476,20 +1344,22 @@ fn string_repeat(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_pad_left(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1], &args[2]) {
         (Value::Text(s), Value::Number(width), Value::Text(pad_char)) => {
             let width = *width as usize;
-            if pad_char.len() != 1 {
+            let mut pad_chars = pad_char.chars();
+            let (Some(pad_ch), None) = (pad_chars.next(), pad_chars.next()) else {
                 return Err(RuntimeError::Custom("Pad character must be a single character".to_string()));
-            }
-            let pad_ch = pad_char.chars().next().unwrap();
+            };
+            let length = s.chars().count();
 
-            if s.len() >= width {
+            if length >= width {
                 Ok(Value::Text(s.clone()))
             } else {
                 let mut result = String::new();
-                for _ in 0..(width - s.len()) {
+                for _ in 0..(width - length) {
                     result.push(pad_ch);
                 }
                 result.push_str(s);
@@ -503,20 +1373,22 @@ fn string_pad_left(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_pad_right(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1], &args[2]) {
         (Value::Text(s), Value::Number(width), Value::Text(pad_char)) => {
             let width = *width as usize;
-            if pad_char.len() != 1 {
+            let mut pad_chars = pad_char.chars();
+            let (Some(pad_ch), None) = (pad_chars.next(), pad_chars.next()) else {
                 return Err(RuntimeError::Custom("Pad character must be a single character".to_string()));
-            }
-            let pad_ch = pad_char.chars().next().unwrap();
+            };
+            let length = s.chars().count();
 
-            if s.len() >= width {
+            if length >= width {
                 Ok(Value::Text(s.clone()))
             } else {
                 let mut result = s.clone();
-                for _ in 0..(width - s.len()) {
+                for _ in 0..(width - length) {
                     result.push(pad_ch);
                 }
                 Ok(Value::Text(result))
@@ -529,6 +1401,7 @@ fn string_pad_right(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
 fn string_reverse(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Text(s) => {
@@ -542,10 +1415,67 @@ fn string_reverse(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-text")]
+fn text_builder(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::text_builder())
+}
+
+/// Append the text form of any value
+#[cfg(feature = "runtime-text")]
+fn text_push(args: &[Value]) -> Result<Value, RuntimeError> {
+    let text = render_text(&args[1], &mut |_| None)?;
+    builder_push(&args[0], &text)
+}
+
+/// Append a Text, rejecting anything else
+#[cfg(feature = "runtime-text")]
+fn text_push_text(args: &[Value]) -> Result<Value, RuntimeError> {
+    match &args[1] {
+        Value::Text(text) => builder_push(&args[0], text),
+        v => Err(RuntimeError::TypeError {
+            expected: "Text".to_string(),
+            got: v.type_name().to_string(),
+        }),
+    }
+}
+
+#[cfg(feature = "runtime-text")]
+fn text_build(args: &[Value]) -> Result<Value, RuntimeError> {
+    builder_build(&args[0])
+}
+
+/// Append `text` to a text builder in place, returning the builder
+///
+/// Shared by the `text_push` natives and the VM's `TEXT_PUSH`.
+pub fn builder_push(builder: &Value, text: &str) -> Result<Value, RuntimeError> {
+    match builder {
+        Value::TextBuilder { buffer } => {
+            buffer.push_str(text);
+            Ok(builder.clone())
+        }
+        v => Err(RuntimeError::TypeError {
+            expected: "TextBuilder".to_string(),
+            got: v.type_name().to_string(),
+        }),
+    }
+}
+
+/// Text accumulated in a text builder; the builder keeps its contents
+pub fn builder_build(builder: &Value) -> Result<Value, RuntimeError> {
+    match builder {
+        Value::TextBuilder { buffer } => Ok(Value::Text(buffer.text())),
+        v => Err(RuntimeError::TypeError {
+            expected: "TextBuilder".to_string(),
+            got: v.type_name().to_string(),
+        }),
+    }
+}
+
 // ============================================================================
 // MATH FUNCTIONS
 // ============================================================================
 
+#[cfg(feature = "runtime-math")]
 fn math_abs(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Number(n) => Ok(Value::Number(n.abs())),
@@ -556,6 +1486,7 @@ fn math_abs(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_sqrt(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Number(n) => {
@@ -572,6 +1503,7 @@ fn math_sqrt(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_pow(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1]) {
         (Value::Number(base), Value::Number(exp)) => {
@@ -584,6 +1516,7 @@ fn math_pow(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_min(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1]) {
         (Value::Number(a), Value::Number(b)) => {
@@ -596,6 +1529,7 @@ fn math_min(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_max(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1]) {
         (Value::Number(a), Value::Number(b)) => {
@@ -608,6 +1542,7 @@ fn math_max(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_floor(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Number(n) => Ok(Value::Number(math::floor(*n))),
@@ -618,6 +1553,7 @@ fn math_floor(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_ceil(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Number(n) => Ok(Value::Number(math::ceil(*n))),
@@ -628,6 +1564,7 @@ fn math_ceil(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_round(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Number(n) => Ok(Value::Number(math::round(*n))),
@@ -638,6 +1575,7 @@ fn math_round(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_sign(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Number(n) => {
@@ -657,6 +1595,7 @@ fn math_sign(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_clamp(args: &[Value]) -> Result<Value, RuntimeError> {
     match (&args[0], &args[1], &args[2]) {
         (Value::Number(value), Value::Number(min_val), Value::Number(max_val)) => {
@@ -679,6 +1618,7 @@ fn math_clamp(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_sin(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Number(n) => Ok(Value::Number(math::sin(*n))),
@@ -689,6 +1629,7 @@ fn math_sin(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_cos(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Number(n) => Ok(Value::Number(math::cos(*n))),
@@ -699,6 +1640,7 @@ fn math_cos(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_tan(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Number(n) => Ok(Value::Number(math::tan(*n))),
@@ -709,6 +1651,7 @@ fn math_tan(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_log(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Number(n) => {
@@ -725,6 +1668,7 @@ fn math_log(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+#[cfg(feature = "runtime-math")]
 fn math_exp(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Number(n) => Ok(Value::Number(math::exp(*n))),
@@ -1054,6 +1998,58 @@ fn list_index_of(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+/// Sort numbers, text or truths in ascending order
+///
+/// The evaluator sorts through its own comparison instead, which also
+/// orders forms and variants embodying `Ordered`.
+fn list_sort(args: &[Value]) -> Result<Value, RuntimeError> {
+    match &args[0] {
+        Value::List(items) => sort_values(items.clone(), &mut natural_order).map(Value::List),
+        v => Err(RuntimeError::TypeError {
+            expected: "List".to_string(),
+            got: v.type_name().to_string(),
+        }),
+    }
+}
+
+/// Order of two numbers, big integers, texts or truths
+fn natural_order(left: &Value, right: &Value) -> Result<Ordering, RuntimeError> {
+    match (left, right) {
+        (Value::Number(l), Value::Number(r)) => {
+            l.partial_cmp(r).ok_or_else(|| RuntimeError::Custom("Cannot order NaN".to_string()))
+        }
+        (Value::BigInt(l), Value::BigInt(r)) => Ok(l.cmp(r)),
+        (Value::Integer(l), Value::Integer(r)) => Ok(l.cmp(r)),
+        (Value::Text(l), Value::Text(r)) => Ok(l.cmp(r)),
+        (Value::Truth(l), Value::Truth(r)) => Ok(l.cmp(r)),
+        (l, r) => Err(RuntimeError::TypeError {
+            expected: l.type_name().to_string(),
+            got: r.type_name().to_string(),
+        }),
+    }
+}
+
+/// Stable sort with a fallible comparison; the first error aborts the sort
+pub fn sort_values(
+    mut items: Vec<Value>,
+    compare: &mut dyn FnMut(&Value, &Value) -> Result<Ordering, RuntimeError>,
+) -> Result<Vec<Value>, RuntimeError> {
+    let mut error = None;
+    items.sort_by(|left, right| {
+        if error.is_some() {
+            return Ordering::Equal;
+        }
+        compare(left, right).unwrap_or_else(|e| {
+            error = Some(e);
+            Ordering::Equal
+        })
+    });
+    match error {
+        Some(e) => Err(e),
+        None => Ok(items),
+    }
+}
+
 // ============================================================================
 // MAP FUNCTIONS
 // ============================================================================
@@ -1110,44 +2106,113 @@ fn map_size(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+// ============================================================================
+// SET FUNCTIONS
+// ============================================================================
+
+fn set_elements(value: &Value) -> Result<&BTreeSet<SetElement>, RuntimeError> {
+    match value {
+        Value::Set(elements) => Ok(elements),
+        v => Err(RuntimeError::TypeError {
+            expected: "Set".to_string(),
+            got: v.type_name().to_string(),
+        }),
+    }
+}
+
+fn set_new(args: &[Value]) -> Result<Value, RuntimeError> {
+    match args {
+        [] => Ok(Value::Set(BTreeSet::new())),
+        [Value::List(items)] => Ok(Value::Set(items.iter().map(SetElement::from_value).collect::<Result<_, _>>()?)),
+        [v] => Err(RuntimeError::TypeError {
+            expected: "List".to_string(),
+            got: v.type_name().to_string(),
+        }),
+        _ => Err(RuntimeError::ArityMismatch { expected: 1, got: args.len() }),
+    }
+}
+
+fn set_insert(args: &[Value]) -> Result<Value, RuntimeError> {
+    let mut elements = set_elements(&args[0])?.clone();
+    elements.insert(SetElement::from_value(&args[1])?);
+    Ok(Value::Set(elements))
+}
+
+fn set_contains(args: &[Value]) -> Result<Value, RuntimeError> {
+    let elements = set_elements(&args[0])?;
+    Ok(Value::Truth(elements.contains(&SetElement::from_value(&args[1])?)))
+}
+
+fn set_union(args: &[Value]) -> Result<Value, RuntimeError> {
+    let (a, b) = (set_elements(&args[0])?, set_elements(&args[1])?);
+    Ok(Value::Set(a.union(b).cloned().collect()))
+}
+
+fn set_intersect(args: &[Value]) -> Result<Value, RuntimeError> {
+    let (a, b) = (set_elements(&args[0])?, set_elements(&args[1])?);
+    Ok(Value::Set(a.intersection(b).cloned().collect()))
+}
+
 // ============================================================================
 // TYPE CONVERSION FUNCTIONS
 // ============================================================================
 
 fn to_text(args: &[Value]) -> Result<Value, RuntimeError> {
-    let text = match &args[0] {
+    Ok(Value::Text(crate::coercion::to_text(&args[0])?))
+}
+
+/// Hook asked to describe each value [`render_text`] prints; `None` leaves
+/// the value to the structural printer
+pub type DescribeHook<'a> = dyn FnMut(&Value) -> Option<Result<String, RuntimeError>> + 'a;
+
+/// Render a value as `to_text` does, asking `describe` first for the value
+/// and every value nested in it
+///
+/// The evaluator passes a hook that calls `describe(self)` on forms
+/// embodying the `Display` aspect.
+pub fn render_text(value: &Value, describe: &mut DescribeHook) -> Result<String, RuntimeError> {
+    if let Some(text) = describe(value) {
+        return text;
+    }
+    let text = match value {
         Value::Number(n) => format!("{}", n),
+        Value::BigInt(n) => n.to_string(),
+        Value::Integer(n) => n.to_string(),
         Value::Text(s) => s.clone(),
         Value::Truth(b) => if *b { "true".to_string() } else { "false".to_string() },
         Value::Nothing => "nothing".to_string(),
         Value::List(_) => "[List]".to_string(),
+        Value::Tuple(items) => {
+            let mut item_strings = Vec::new();
+            for v in items.iter() {
+                item_strings.push(render_text(v, describe)?);
+            }
+            // A tuple of one keeps its comma, as it is written
+            if item_strings.len() == 1 {
+                format!("({},)", item_strings[0])
+            } else {
+                format!("({})", item_strings.join(", "))
+            }
+        }
         Value::Map(_) => "[Map]".to_string(),
+        Value::Set(_) => "[Set]".to_string(),
         Value::Chant { .. } => "[Chant]".to_string(),
         Value::NativeChant(native_fn) => format!("[NativeChant:{}]", native_fn.name),
         Value::Capability { .. } => "[Capability]".to_string(),
         Value::Range { .. } => "[Range]".to_string(),
         Value::Outcome { success, value } => {
             // Recursively convert inner value to text
-            let inner_text = to_text(&[*value.clone()])?;
-            if let Value::Text(inner) = inner_text {
-                if *success {
-                    format!("Triumph({})", inner)
-                } else {
-                    format!("Mishap({})", inner)
-                }
+            let inner = render_text(value, describe)?;
+            if *success {
+                format!("Triumph({})", inner)
             } else {
-                unreachable!("to_text always returns Text")
+                format!("Mishap({})", inner)
             }
         }
         Value::Maybe { present, value } => {
             if *present {
                 if let Some(v) = value {
-                    let inner_text = to_text(&[*v.clone()])?;
-                    if let Value::Text(inner) = inner_text {
-                        format!("Present({})", inner)
-                    } else {
-                        unreachable!("to_text always returns Text")
-                    }
+                    format!("Present({})", render_text(v, describe)?)
                 } else {
                     "Present(nothing)".to_string()
                 }
@@ -1162,12 +2227,7 @@ fn to_text(args: &[Value]) -> Result<Value, RuntimeError> {
             // Format as StructName { field1: value1, field2: value2 }
             let mut field_strings = Vec::new();
             for (k, v) in fields.iter() {
-                let v_text = to_text(core::slice::from_ref(v))?;
-                if let Value::Text(s) = v_text {
-                    field_strings.push(format!("{}: {}", k, s));
-                } else {
-                    unreachable!("to_text always returns Text")
-                }
+                field_strings.push(format!("{}: {}", k, render_text(v, describe)?));
             }
             format!("{} {{ {} }}", struct_name, field_strings.join(", "))
         }
@@ -1183,12 +2243,7 @@ fn to_text(args: &[Value]) -> Result<Value, RuntimeError> {
                 // Phase 2: Format fields
                 let mut field_strings = Vec::new();
                 for v in fields.iter() {
-                    let v_text = to_text(core::slice::from_ref(v))?;
-                    if let Value::Text(s) = v_text {
-                        field_strings.push(s);
-                    } else {
-                        unreachable!("to_text always returns Text")
-                    }
+                    field_strings.push(render_text(v, describe)?);
                 }
                 format!("{}({})", variant_name, field_strings.join(", "))
             }
@@ -1208,28 +2263,401 @@ fn to_text(args: &[Value]) -> Result<Value, RuntimeError> {
             // Show Cell with inner value type
             format!("[Cell<{}>]", value.type_name())
         }
+        Value::Resource { kind, handle, .. } => {
+            format!("[Resource:{} #{}]", kind, handle)
+        }
+        Value::TextBuilder { buffer } => {
+            format!("[TextBuilder ({} bytes)]", buffer.len())
+        }
+        Value::Frozen { value } | Value::Tainted { value, .. } => render_text(value, describe)?,
     };
-    Ok(Value::Text(text))
+    Ok(text)
+}
+
+fn hash(args: &[Value]) -> Result<Value, RuntimeError> {
+    hash_value(&args[0], &mut |_| None).map(hash_to_number)
+}
+
+/// Hook asked to hash each value [`hash_value`] visits; `None` leaves the
+/// value to the structural hash
+pub type HashHook<'a> = dyn FnMut(&Value) -> Option<Result<u64, RuntimeError>> + 'a;
+
+/// 64-bit FNV-1a step over `bytes`
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    const PRIME: u64 = 0x0100_0000_01b3;
+    bytes.iter().fold(hash, |hash, byte| (hash ^ *byte as u64).wrapping_mul(PRIME))
+}
+
+/// Structural hash of a value, asking `hook` first for the value and every
+/// value nested in it
+///
+/// Equal values hash equally. The evaluator passes a hook that applies the
+/// `Hashable` aspect of forms and variants.
+pub fn hash_value(value: &Value, hook: &mut HashHook) -> Result<u64, RuntimeError> {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+    if let Some(hash) = hook(value) {
+        return hash;
+    }
+    let tag = fnv1a(OFFSET_BASIS, value.type_name().as_bytes());
+    let mut nested = |hash: u64, value: &Value| -> Result<u64, RuntimeError> {
+        Ok(fnv1a(hash, &hash_value(value, hook)?.to_le_bytes()))
+    };
+    match value {
+        // 0 and -0 are equal, so they must hash alike
+        Value::Number(n) => Ok(fnv1a(tag, &(n + 0.0).to_bits().to_le_bytes())),
+        Value::BigInt(n) => Ok(fnv1a(tag, n.to_string().as_bytes())),
+        Value::Integer(n) => Ok(fnv1a(tag, &n.to_le_bytes())),
+        Value::Text(s) => Ok(fnv1a(tag, s.as_bytes())),
+        Value::Truth(b) => Ok(fnv1a(tag, &[*b as u8])),
+        Value::Nothing => Ok(tag),
+        Value::List(items) => items.iter().try_fold(tag, &mut nested),
+        Value::Map(entries) => entries
+            .iter()
+            .try_fold(tag, |hash, (key, item)| nested(fnv1a(hash, key.as_bytes()), item)),
+        Value::StructInstance { struct_name, fields } => fields
+            .iter()
+            .try_fold(fnv1a(tag, struct_name.as_bytes()), |hash, (key, item)| nested(fnv1a(hash, key.as_bytes()), item)),
+        Value::VariantValue { enum_name, variant_name, fields, .. } => {
+            let hash = fnv1a(fnv1a(tag, enum_name.as_bytes()), variant_name.as_bytes());
+            fields.iter().try_fold(hash, &mut nested)
+        }
+        Value::Outcome { success, value } => nested(fnv1a(tag, &[*success as u8]), value),
+        Value::Maybe { value: Some(value), .. } => nested(tag, value),
+        Value::Maybe { value: None, .. } => Ok(tag),
+        Value::Frozen { value } | Value::Tainted { value, .. } => hash_value(value, hook),
+        other => Err(RuntimeError::TypeError {
+            expected: "hashable value".to_string(),
+            got: other.type_name().to_string(),
+        }),
+    }
+}
+
+/// A hash as a script number, keeping the 53 bits an f64 holds exactly
+pub fn hash_to_number(hash: u64) -> Value {
+    Value::Number((hash & ((1 << 53) - 1)) as f64)
 }
 
 fn to_number(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::Number(crate::coercion::to_number(&args[0])?))
+}
+
+/// A conversion result: `Triumph(n)`, or `Mishap(reason)`
+fn converted(result: Result<f64, String>) -> Value {
+    match result {
+        Ok(n) => Value::Outcome { success: true, value: Box::new(Value::Number(n)) },
+        Err(reason) => Value::Outcome { success: false, value: Box::new(Value::Text(reason)) },
+    }
+}
+
+/// The Number a Text or Number stands for, or why there is none
+fn checked_number(value: &Value) -> Result<Result<f64, String>, RuntimeError> {
+    let n = match crate::coercion::to_number(value) {
+        Ok(n) => n,
+        Err(crate::coercion::CoercionError::Invalid(reason)) => return Ok(Err(reason)),
+        Err(error) => return Err(error.into()),
+    };
+    if n.is_finite() {
+        Ok(Ok(n))
+    } else {
+        Ok(Err(format!("{} is not a finite number", n)))
+    }
+}
+
+/// Like `to_number`, but a Mishap instead of an error or a NaN/infinity
+fn to_number_checked(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(converted(checked_number(&args[0])?))
+}
+
+/// Triumph with the integer a Number or Text holds exactly, or a Mishap
+fn to_int_checked(args: &[Value]) -> Result<Value, RuntimeError> {
+    let result = checked_number(&args[0])?.and_then(|n| {
+        crate::convert::exact(n)
+            .map(|int| int as f64)
+            .map_err(|error| format!("Cannot convert {} to an integer: {}", n, error))
+    });
+    Ok(converted(result))
+}
+
+/// Apply one of the [`crate::convert`] rules to a Number argument
+fn integer_conversion(
+    args: &[Value],
+    convert: fn(f64) -> Result<i64, crate::convert::ConversionError>,
+) -> Result<Value, RuntimeError> {
     match &args[0] {
-        Value::Number(n) => Ok(Value::Number(*n)),
-        Value::Text(s) => {
-            s.parse::<f64>()
-                .map(Value::Number)
-                .map_err(|_| RuntimeError::Custom(format!("Cannot convert '{}' to number", s)))
-        }
-        Value::Truth(b) => Ok(Value::Number(if *b { 1.0 } else { 0.0 })),
+        Value::Number(n) => Ok(converted(
+            convert(*n)
+                .map(|int| int as f64)
+                .map_err(|error| format!("Cannot convert {} to an integer: {}", n, error)),
+        )),
         v => Err(RuntimeError::TypeError {
-            expected: "Number, Text, or Truth".to_string(),
+            expected: "Number".to_string(),
             got: v.type_name().to_string(),
         }),
     }
 }
 
+/// Drop the fraction; a Mishap for NaN, infinities and values beyond 64 bits
+fn truncate(args: &[Value]) -> Result<Value, RuntimeError> {
+    integer_conversion(args, crate::convert::truncate)
+}
+
+/// Drop the fraction and clamp to the 64-bit range; a Mishap only for NaN
+fn saturate(args: &[Value]) -> Result<Value, RuntimeError> {
+    integer_conversion(args, crate::convert::saturate)
+}
+
+/// A 64-bit integer from a whole Number, decimal Text, a big integer that
+/// fits, or another integer
+fn to_integer(args: &[Value]) -> Result<Value, RuntimeError> {
+    let out_of_range = |shown: String| RuntimeError::Custom(format!("Cannot convert {} to an integer", shown));
+    match &args[0] {
+        Value::Integer(n) => Ok(Value::Integer(*n)),
+        Value::Number(n) => crate::convert::exact(*n).map(Value::Integer).map_err(|_| out_of_range(n.to_string())),
+        Value::BigInt(n) => n.to_i64().map(Value::Integer).ok_or_else(|| out_of_range(n.to_string())),
+        Value::Text(s) => s.trim().parse::<i64>().map(Value::Integer).map_err(|_| out_of_range(format!("'{}'", s))),
+        v => Err(RuntimeError::TypeError {
+            expected: "Integer, Number, BigInt, or Text".to_string(),
+            got: v.type_name().to_string(),
+        }),
+    }
+}
+
+/// A big integer from a whole Number, decimal Text or another big integer
+fn bigint(args: &[Value]) -> Result<Value, RuntimeError> {
+    match &args[0] {
+        Value::BigInt(n) => Ok(Value::BigInt(n.clone())),
+        Value::Integer(n) => Ok(Value::BigInt(BigInt::from_i64(*n))),
+        Value::Number(n) => BigInt::from_f64(*n)
+            .map(Value::BigInt)
+            .ok_or_else(|| RuntimeError::Custom(format!("Cannot convert {} to a big integer", n))),
+        Value::Text(s) => BigInt::parse(s.trim())
+            .map(Value::BigInt)
+            .ok_or_else(|| RuntimeError::Custom(format!("Cannot convert '{}' to a big integer", s))),
+        v => Err(RuntimeError::TypeError {
+            expected: "BigInt, Integer, Number, or Text".to_string(),
+            got: v.type_name().to_string(),
+        }),
+    }
+}
+
+/// The big integer argument at `index`
+fn bigint_arg(args: &[Value], index: usize) -> Result<&BigInt, RuntimeError> {
+    match &args[index] {
+        Value::BigInt(n) => Ok(n),
+        v => Err(RuntimeError::TypeError {
+            expected: "BigInt".to_string(),
+            got: v.type_name().to_string(),
+        }),
+    }
+}
+
+/// Largest result `bigint_pow` computes, in bits (about 79,000 decimal
+/// digits)
+///
+/// The result takes at most `bit_len(base) * exponent` bits, so a power is
+/// refused when that bound is larger, before any work is done.
+pub const BIGINT_POW_MAX_BITS: u64 = 1 << 18;
+
+/// `bigint_pow(base, exponent)` with a whole, non-negative Number exponent
+/// and a result of at most [`BIGINT_POW_MAX_BITS`]
+fn bigint_pow(args: &[Value]) -> Result<Value, RuntimeError> {
+    let base = bigint_arg(args, 0)?;
+    let out_of_range = |n: &dyn core::fmt::Display| {
+        RuntimeError::Custom(format!("bigint_pow: exponent {} is not a whole number from 0 to 2^32 - 1", n))
+    };
+    let exponent = match &args[1] {
+        Value::Number(n) if n % 1.0 == 0.0 && (0.0..=u32::MAX as f64).contains(n) => *n as u32,
+        Value::Number(n) => return Err(out_of_range(n)),
+        Value::BigInt(n) => n.to_i64().and_then(|n| u32::try_from(n).ok()).ok_or_else(|| out_of_range(n))?,
+        v => {
+            return Err(RuntimeError::TypeError {
+                expected: "Number or BigInt".to_string(),
+                got: v.type_name().to_string(),
+            })
+        }
+    };
+    // Powers of 0, 1 and -1 stay that small
+    let bits = base.bit_len() * u64::from(exponent);
+    if base.bit_len() > 1 && bits > BIGINT_POW_MAX_BITS {
+        return Err(RuntimeError::Custom(format!(
+            "bigint_pow: result of up to {} bits is over the limit of {} bits",
+            bits, BIGINT_POW_MAX_BITS
+        )));
+    }
+    Ok(Value::BigInt(base.pow(exponent)))
+}
+
+/// `bigint_mod_pow(base, exponent, modulus)`, the result in `0..modulus`
+fn bigint_mod_pow(args: &[Value]) -> Result<Value, RuntimeError> {
+    let (base, exponent, modulus) = (bigint_arg(args, 0)?, bigint_arg(args, 1)?, bigint_arg(args, 2)?);
+    base.mod_pow(exponent, modulus).map(Value::BigInt).ok_or_else(|| {
+        RuntimeError::Custom("bigint_mod_pow needs an exponent from 0 and a modulus from 1".to_string())
+    })
+}
+
+/// Bytes of the value's binary encoding, as numbers from 0 to 255
+fn value_encode(args: &[Value]) -> Result<Value, RuntimeError> {
+    let bytes = crate::value_codec::encode(&args[0])
+        .map_err(|error| RuntimeError::Custom(format!("value_encode: {}", error)))?;
+    Ok(Value::List(bytes.into_iter().map(|byte| Value::Number(byte as f64)).collect()))
+}
+
+fn value_decode(args: &[Value]) -> Result<Value, RuntimeError> {
+    let not_bytes = |got: &Value| RuntimeError::TypeError {
+        expected: "List of bytes (0 to 255)".to_string(),
+        got: got.type_name().to_string(),
+    };
+    let Value::List(items) = &args[0] else {
+        return Err(not_bytes(&args[0]));
+    };
+    let bytes = items
+        .iter()
+        .map(|item| match item {
+            Value::Number(n) if n % 1.0 == 0.0 && (0.0..=255.0).contains(n) => Ok(*n as u8),
+            other => Err(not_bytes(other)),
+        })
+        .collect::<Result<Vec<u8>, RuntimeError>>()?;
+    crate::value_codec::decode(&bytes).map_err(|error| RuntimeError::Custom(format!("value_decode: {}", error)))
+}
+
+/// Triumph with a configuration document's Map, or a Mishap naming the
+/// line that did not parse
+fn config_parsed(args: &[Value], parse: fn(&str) -> Result<Value, crate::config::ConfigError>) -> Result<Value, RuntimeError> {
+    let Value::Text(text) = &args[0] else {
+        return Err(RuntimeError::TypeError { expected: "Text".to_string(), got: args[0].type_name().to_string() });
+    };
+    let (success, value) = match parse(text) {
+        Ok(map) => (true, map),
+        Err(error) => (false, Value::Text(error.to_string())),
+    };
+    Ok(Value::Outcome { success, value: Box::new(value) })
+}
+
+fn config_parse_toml(args: &[Value]) -> Result<Value, RuntimeError> {
+    config_parsed(args, crate::config::parse_toml)
+}
+
+fn config_parse_ini(args: &[Value]) -> Result<Value, RuntimeError> {
+    config_parsed(args, crate::config::parse_ini)
+}
+
+fn config_emit_toml(args: &[Value]) -> Result<Value, RuntimeError> {
+    crate::config::emit_toml(&args[0])
+        .map(Value::Text)
+        .map_err(|error| RuntimeError::Custom(format!("config_emit_toml: {}", error)))
+}
+
+fn config_emit_ini(args: &[Value]) -> Result<Value, RuntimeError> {
+    crate::config::emit_ini(&args[0])
+        .map(Value::Text)
+        .map_err(|error| RuntimeError::Custom(format!("config_emit_ini: {}", error)))
+}
+
+/// The first argument and the options Map that may follow it
+fn csv_arguments(args: &[Value]) -> Result<(&Value, crate::csv::CsvOptions), RuntimeError> {
+    let mistyped = |expected: &str, got: &Value| RuntimeError::TypeError {
+        expected: expected.to_string(),
+        got: got.type_name().to_string(),
+    };
+    let (first, options) = match args {
+        [first] => (first, None),
+        [first, options] => (first, Some(options)),
+        _ => return Err(RuntimeError::ArityMismatch { expected: 2, got: args.len() }),
+    };
+    let mut parsed = crate::csv::CsvOptions::default();
+    let Some(options) = options else {
+        return Ok((first, parsed));
+    };
+    let Value::Map(entries) = options else {
+        return Err(mistyped("options Map", options));
+    };
+    for (key, value) in entries {
+        match (key.as_str(), value) {
+            ("delimiter", Value::Text(text)) if text.chars().count() == 1 && !matches!(text.as_str(), "\"" | "\n" | "\r") => {
+                parsed.delimiter = text.chars().next().unwrap_or(',');
+            }
+            ("header", Value::Truth(header)) => parsed.header = *header,
+            ("columns", Value::List(items)) => {
+                let columns = items.iter().map(|item| match item {
+                    Value::Text(column) => Ok(column.clone()),
+                    other => Err(mistyped("Text column name", other)),
+                });
+                parsed.columns = Some(columns.collect::<Result<_, _>>()?);
+            }
+            ("delimiter", other) => return Err(mistyped("one-character Text other than a quote or line break", other)),
+            ("header", other) => return Err(mistyped("Truth", other)),
+            ("columns", other) => return Err(mistyped("List of Text", other)),
+            (unknown, _) => return Err(RuntimeError::Custom(format!("Unknown CSV option '{}'", unknown))),
+        }
+    }
+    Ok((first, parsed))
+}
+
+/// Triumph with the rows of delimited text, or a Mishap naming the line
+/// that did not parse
+fn csv_parse(args: &[Value]) -> Result<Value, RuntimeError> {
+    let (text, options) = csv_arguments(args)?;
+    let Value::Text(text) = text else {
+        return Err(RuntimeError::TypeError { expected: "Text".to_string(), got: text.type_name().to_string() });
+    };
+    let (success, value) = match crate::csv::parse(text, &options) {
+        Ok(rows) => (true, rows),
+        Err(error) => (false, Value::Text(error.to_string())),
+    };
+    Ok(Value::Outcome { success, value: Box::new(value) })
+}
+
+fn csv_emit(args: &[Value]) -> Result<Value, RuntimeError> {
+    let (rows, options) = csv_arguments(args)?;
+    crate::csv::emit(rows, &options)
+        .map(Value::Text)
+        .map_err(|error| RuntimeError::Custom(format!("csv_emit: {}", error)))
+}
+
+/// Change list turning the first value into the second, as change Maps
+fn value_diff(args: &[Value]) -> Result<Value, RuntimeError> {
+    let changes = crate::value_diff::diff(&args[0], &args[1]);
+    Ok(Value::List(changes.iter().map(crate::value_diff::Change::to_value).collect()))
+}
+
+fn value_patch(args: &[Value]) -> Result<Value, RuntimeError> {
+    let Value::List(items) = &args[1] else {
+        return Err(RuntimeError::TypeError {
+            expected: "List of changes".to_string(),
+            got: args[1].type_name().to_string(),
+        });
+    };
+    let changes = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            crate::value_diff::Change::from_value(item).ok_or(crate::value_diff::PatchError::Malformed(index))
+        })
+        .collect::<Result<Vec<_>, _>>();
+    changes
+        .and_then(|changes| crate::value_diff::patch(&args[0], &changes))
+        .map_err(|error| RuntimeError::Custom(format!("value_patch: {}", error)))
+}
+
+fn freeze(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(args[0].clone().freeze())
+}
+
+fn is_frozen(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::Truth(args[0].is_frozen()))
+}
+
+fn thaw(args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(args[0].clone().thaw())
+}
+
+fn validate(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("validate: Requires the evaluator to look up forms".to_string()))
+}
+
 fn to_truth(args: &[Value]) -> Result<Value, RuntimeError> {
-    Ok(Value::Truth(args[0].is_truthy()))
+    Ok(Value::Truth(crate::coercion::truthy(&args[0])))
 }
 
 fn type_of(args: &[Value]) -> Result<Value, RuntimeError> {
@@ -1246,16 +2674,208 @@ fn type_of(args: &[Value]) -> Result<Value, RuntimeError> {
 
 fn io_print(_args: &[Value]) -> Result<Value, RuntimeError> {
     Err(RuntimeError::Custom(
-        "print() requires kernel I/O capabilities - call from kernel context only".to_string()
+        "print: Requires the evaluator's output sink".to_string()
     ))
 }
 
 fn io_println(_args: &[Value]) -> Result<Value, RuntimeError> {
     Err(RuntimeError::Custom(
-        "println() requires kernel I/O capabilities - call from kernel context only".to_string()
+        "println: Requires the evaluator's output sink".to_string()
     ))
 }
 
+/// End the program with a status, which unwinds as `RuntimeError::Exit`
+/// to the outermost evaluation
+fn program_exit(args: &[Value]) -> Result<Value, RuntimeError> {
+    match &args[0] {
+        Value::Number(n) if n % 1.0 == 0.0 && *n >= i32::MIN as f64 && *n <= i32::MAX as f64 => {
+            Err(RuntimeError::Exit(*n as i32))
+        }
+        other => Err(RuntimeError::TypeError {
+            expected: "exit status (whole Number)".to_string(),
+            got: other.type_name().to_string(),
+        }),
+    }
+}
+
+// ============================================================================
+// TASK FUNCTIONS
+// ============================================================================
+//
+// These need the evaluator's scheduler, so the evaluator intercepts calls to
+// them; the native bodies only run when called from elsewhere (e.g. the VM).
+
+fn task_spawn(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("spawn: Requires the evaluator's scheduler".to_string()))
+}
+
+fn task_yield_now(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("yield_now: Requires the evaluator's scheduler".to_string()))
+}
+
+fn task_block_on_event(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("block_on_event: Requires the evaluator's scheduler".to_string()))
+}
+
+fn task_signal_event(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("signal_event: Requires the evaluator's scheduler".to_string()))
+}
+
+// ============================================================================
+// WATCHDOG FUNCTIONS
+// ============================================================================
+// The watchdog reads the evaluator's clock, so the evaluator intercepts these.
+
+fn watchdog_arm(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("watchdog: Requires the evaluator's watchdog".to_string()))
+}
+
+fn watchdog_heartbeat(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("heartbeat: Requires the evaluator's watchdog".to_string()))
+}
+
+// ============================================================================
+// RESOURCE FUNCTIONS
+// ============================================================================
+// Release needs the evaluator's resource table, so the evaluator intercepts it.
+
+fn resource_release(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("release: Requires the evaluator's resource table".to_string()))
+}
+
+// ============================================================================
+// STORE FUNCTIONS
+// ============================================================================
+// The store is the evaluator's storage provider, so the evaluator intercepts these.
+
+fn store_get(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("store_get: Requires the evaluator's storage provider".to_string()))
+}
+
+fn store_set(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("store_set: Requires the evaluator's storage provider".to_string()))
+}
+
+fn store_delete(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("store_delete: Requires the evaluator's storage provider".to_string()))
+}
+
+// ============================================================================
+// NET FUNCTIONS
+// ============================================================================
+// Connections belong to the evaluator's network provider, so the evaluator
+// intercepts these.
+
+fn net_builtin(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("Networking requires the evaluator's network provider".to_string()))
+}
+
+// ============================================================================
+// TERM FUNCTIONS
+// ============================================================================
+// The screen and keyboard belong to the evaluator's terminal, so the
+// evaluator intercepts these.
+
+fn term_builtin(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("Terminal control requires the evaluator's terminal".to_string()))
+}
+
+// ============================================================================
+// DRAW FUNCTIONS
+// ============================================================================
+// The back buffer and the screen belong to the evaluator's framebuffer, so
+// the evaluator intercepts these.
+
+fn draw_builtin(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("Drawing requires the evaluator's framebuffer".to_string()))
+}
+
+// ============================================================================
+// SOUND FUNCTIONS
+// ============================================================================
+// The speaker belongs to the evaluator, so the evaluator intercepts these.
+
+fn sound_builtin(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("Sound requires the evaluator's speaker".to_string()))
+}
+
+// ============================================================================
+// PROGRESS FUNCTIONS
+// ============================================================================
+// Tasks call back into script chants and report to the evaluator's progress
+// sink, so the evaluator intercepts these.
+
+fn progress_builtin(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("Progress reporting requires the evaluator".to_string()))
+}
+
+// ============================================================================
+// COMPILER FUNCTIONS
+// ============================================================================
+// The phases are gated on the evaluator's capability grants, so the
+// evaluator intercepts these.
+
+fn compiler_builtin(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("Compiler phases require the evaluator".to_string()))
+}
+
+// ============================================================================
+// LOCALIZATION FUNCTIONS
+// ============================================================================
+// Catalogs are loaded into the evaluator, so the evaluator intercepts these.
+
+fn i18n_builtin(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("Localization requires the evaluator's message catalogs".to_string()))
+}
+
+// ============================================================================
+// BUS FUNCTIONS
+// ============================================================================
+// The bus belongs to the evaluator's session, so the evaluator intercepts these.
+
+fn bus_publish(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("publish: Requires the evaluator's message bus".to_string()))
+}
+
+fn bus_subscribe(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("subscribe: Requires the evaluator's message bus".to_string()))
+}
+
+// ============================================================================
+// CAPABILITY FUNCTIONS
+// ============================================================================
+// The audit log lives in the evaluator, so the evaluator intercepts this.
+
+fn capability_log(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("capabilities: Requires the evaluator's capability audit".to_string()))
+}
+
+// ============================================================================
+// HEAP FUNCTIONS
+// ============================================================================
+// Statistics of the native gl_malloc heap, for scripts monitoring system
+// health. Builds without the native allocator report an error instead.
+
+fn heap_used(_args: &[Value]) -> Result<Value, RuntimeError> {
+    native_heap_stats("heap_used").map(|(used, _)| Value::Number(used as f64))
+}
+
+fn heap_free(_args: &[Value]) -> Result<Value, RuntimeError> {
+    native_heap_stats("heap_free").map(|(_, free)| Value::Number(free as f64))
+}
+
+/// Used and free bytes of the native heap
+#[cfg(feature = "allocator_tests")]
+fn native_heap_stats(_builtin: &str) -> Result<(u64, u64), RuntimeError> {
+    let stats = crate::native_allocator::heap_stats();
+    Ok((stats.used, stats.free))
+}
+
+#[cfg(not(feature = "allocator_tests"))]
+fn native_heap_stats(builtin: &str) -> Result<(u64, u64), RuntimeError> {
+    Err(RuntimeError::Custom(format!("{}: The native allocator is not available in this build", builtin)))
+}
+
 // ============================================================================
 // OUTCOME<T, E> HELPER FUNCTIONS
 // ============================================================================
@@ -1318,7 +2938,7 @@ fn triumph_or_else(args: &[Value]) -> Result<Value, RuntimeError> {
         Value::Outcome { success: false, .. } => {
             // Call the function with no arguments
             match &args[1] {
-                Value::Chant { params: _params, body: _body, closure: _ } => {
+                Value::Chant { params: _params, body: _body, .. } => {
                     if !_params.is_empty() {
                         return Err(RuntimeError::ArityMismatch {
                             expected: 0,
@@ -1460,30 +3080,20 @@ fn is_absent(args: &[Value]) -> Result<Value, RuntimeError> {
 
 /// Get present value or panic with custom message
 fn expect_present(args: &[Value]) -> Result<Value, RuntimeError> {
-    match (&args[0], &args[1]) {
-        (Value::Maybe { present: true, value: Some(v) }, _) => Ok(*v.clone()),
-        (Value::Maybe { present: false, .. }, Value::Text(msg)) => {
-            Err(RuntimeError::Custom(msg.clone()))
-        }
-        (Value::Maybe { present: false, .. }, _) => {
+    match (crate::coercion::present(&args[0]), &args[1]) {
+        (Err(crate::coercion::CoercionError::Absent), Value::Text(msg)) => Err(RuntimeError::Custom(msg.clone())),
+        (Err(crate::coercion::CoercionError::Absent), _) => {
             Err(RuntimeError::Custom("expect_present failed".to_string()))
         }
-        (v, _) => Err(RuntimeError::TypeError {
-            expected: "Maybe".to_string(),
-            got: v.type_name().to_string(),
-        }),
+        (result, _) => Ok(result?),
     }
 }
 
 /// Get present value or return default
 fn present_or(args: &[Value]) -> Result<Value, RuntimeError> {
-    match &args[0] {
-        Value::Maybe { present: true, value: Some(v) } => Ok(*v.clone()),
-        Value::Maybe { present: false, .. } => Ok(args[1].clone()),
-        v => Err(RuntimeError::TypeError {
-            expected: "Maybe".to_string(),
-            got: v.type_name().to_string(),
-        }),
+    match crate::coercion::present(&args[0]) {
+        Err(crate::coercion::CoercionError::Absent) => Ok(args[1].clone()),
+        result => Ok(result?),
     }
 }
 
@@ -1795,7 +3405,7 @@ fn refine_variant(args: &[Value]) -> Result<Value, RuntimeError> {
                 
                 // Call the function with the fields
                 match transform_fn {
-                    Value::Chant { params: _params, body: _body, closure: _closure } => {
+                    Value::Chant { params: _params, body: _body, .. } => {
                         // For simplicity, we'll just return Present with the fields
                         // In a full implementation, we'd evaluate the function
                         Ok(Value::Maybe {
@@ -1831,11 +3441,70 @@ fn refine_variant(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+/// Discriminant of a variant value
+/// Usage: variant_index(Triumph(1)) -> 1
+///
+/// Triumph and Present are 1, Mishap and Absent 0, as in the native tag
+/// layout. Cases of user-defined variants are numbered by their enum's
+/// definition, which the evaluator looks up before falling back here.
+fn variant_index(args: &[Value]) -> Result<Value, RuntimeError> {
+    match &args[0] {
+        Value::Outcome { success, .. } => Ok(Value::Number(if *success { 1.0 } else { 0.0 })),
+        Value::Maybe { present, .. } => Ok(Value::Number(if *present { 1.0 } else { 0.0 })),
+        v => Err(RuntimeError::TypeError {
+            expected: "VariantValue".to_string(),
+            got: v.type_name().to_string(),
+        }),
+    }
+}
+
+/// Unit case of an enum with the given discriminant
+/// Usage: from_index(Status, 1) -> Present(Error), or Absent if no case has it
+fn from_index(args: &[Value]) -> Result<Value, RuntimeError> {
+    let (enum_name, variants) = match &args[0] {
+        Value::VariantDef { name, variants, .. } => (name, variants),
+        v => return Err(RuntimeError::TypeError {
+            expected: "VariantDef".to_string(),
+            got: v.type_name().to_string(),
+        }),
+    };
+    let index = match &args[1] {
+        Value::Number(n) if n % 1.0 == 0.0 => *n as i64,
+        v => return Err(RuntimeError::TypeError {
+            expected: "integer Number".to_string(),
+            got: v.type_name().to_string(),
+        }),
+    };
+
+    let discriminants = crate::ast::variant_discriminants(variants);
+    let Some(position) = discriminants.iter().position(|discriminant| *discriminant == index) else {
+        return Ok(Value::Maybe { present: false, value: None });
+    };
+    let case = &variants[position];
+    if !case.fields.is_empty() {
+        return Err(RuntimeError::Custom(format!(
+            "{}.{} carries data and cannot be built from its index",
+            enum_name, case.name
+        )));
+    }
+
+    Ok(Value::Maybe {
+        present: true,
+        value: Some(Box::new(Value::VariantValue {
+            enum_name: enum_name.clone(),
+            variant_name: case.name.clone(),
+            fields: Vec::new(),
+            type_args: Vec::new(),
+        })),
+    })
+}
+
 // ============================================================================
 // ITERATOR FUNCTIONS - Phase 1
 // ============================================================================
 
 /// Create an iterator from a list or range
+#[cfg(feature = "runtime-iter")]
 fn iter_create(args: &[Value]) -> Result<Value, RuntimeError> {
     use crate::eval::IteratorState;
 
@@ -1871,6 +3540,7 @@ fn iter_create(args: &[Value]) -> Result<Value, RuntimeError> {
 }
 
 /// Get next value from iterator
+#[cfg(feature = "runtime-iter")]
 fn iter_next(args: &[Value]) -> Result<Value, RuntimeError> {
     use crate::eval::IteratorState;
 
@@ -1951,6 +3621,7 @@ fn iter_next(args: &[Value]) -> Result<Value, RuntimeError> {
 }
 
 /// Create a mapping iterator
+#[cfg(feature = "runtime-iter")]
 fn iter_map(args: &[Value]) -> Result<Value, RuntimeError> {
     use crate::eval::IteratorState;
 
@@ -1977,6 +3648,7 @@ fn iter_map(args: &[Value]) -> Result<Value, RuntimeError> {
 }
 
 /// Create a filtering iterator
+#[cfg(feature = "runtime-iter")]
 fn iter_filter(args: &[Value]) -> Result<Value, RuntimeError> {
     use crate::eval::IteratorState;
 
@@ -2003,25 +3675,30 @@ fn iter_filter(args: &[Value]) -> Result<Value, RuntimeError> {
 }
 
 /// Fold an iterator into a single value
+///
+/// The evaluator folds iterators itself, since it must call `func`; this
+/// is only reached by hosts calling natives without one.
+#[cfg(feature = "runtime-iter")]
 fn iter_fold(_args: &[Value]) -> Result<Value, RuntimeError> {
-    // Note: This function signature is (iterator, init, func)
-    // The actual reduction logic needs to be implemented in the evaluator
-    // because it requires calling functions dynamically
     Err(RuntimeError::Custom(
-        "iter_fold: Must be implemented in Glimmer-Weave code, not as native builtin".to_string()
+        "iter_fold: Needs the evaluator to call the folding chant".to_string()
     ))
 }
 
 /// Collect an iterator into a list
+///
+/// The evaluator collects iterators itself, since `Map`, `Filter` and
+/// `Iterable` states call chants; this is only reached by hosts calling
+/// natives without one.
+#[cfg(feature = "runtime-iter")]
 fn iter_collect(_args: &[Value]) -> Result<Value, RuntimeError> {
-    // Note: This needs to be implemented in Glimmer-Weave code
-    // because it requires repeatedly calling iter_next
     Err(RuntimeError::Custom(
-        "iter_collect: Must be implemented in Glimmer-Weave code, not as native builtin".to_string()
+        "iter_collect: Needs the evaluator to advance the iterator".to_string()
     ))
 }
 
 /// Create a take iterator
+#[cfg(feature = "runtime-iter")]
 fn iter_take(args: &[Value]) -> Result<Value, RuntimeError> {
     use crate::eval::IteratorState;
 
@@ -2046,12 +3723,22 @@ fn iter_take(args: &[Value]) -> Result<Value, RuntimeError> {
     }
 }
 
+/// Open a stream iterator (`fs_lines`, `fs_bytes`, `console_read_lines`)
+///
+/// Streams are read through the evaluator's stream host, so the evaluator
+/// intercepts these; this only runs when called from elsewhere (e.g. the VM).
+#[cfg(feature = "runtime-iter")]
+fn stream_open(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Err(RuntimeError::Custom("Streams require the evaluator's stream host".to_string()))
+}
+
 // ============================================================================
 // SMART POINTER FUNCTIONS
 // ============================================================================
 
 /// Create a new Shared<T> smart pointer
 /// Usage: Shared_new(value) -> Shared<T>
+#[cfg(feature = "runtime-smartptr")]
 fn shared_new(args: &[Value]) -> Result<Value, RuntimeError> {
     Ok(Value::Shared {
         value: Box::new(args[0].clone()),
@@ -2061,6 +3748,7 @@ fn shared_new(args: &[Value]) -> Result<Value, RuntimeError> {
 
 /// Get the value from a Shared<T> smart pointer
 /// Usage: Shared_get(shared) -> T
+#[cfg(feature = "runtime-smartptr")]
 fn shared_get(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Shared { value, .. } => Ok((**value).clone()),
@@ -2073,6 +3761,7 @@ fn shared_get(args: &[Value]) -> Result<Value, RuntimeError> {
 
 /// Clone a Shared<T> smart pointer (increments reference count)
 /// Usage: Shared_clone(shared) -> Shared<T>
+#[cfg(feature = "runtime-smartptr")]
 fn shared_clone(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Shared { value, ref_count } => Ok(Value::Shared {
@@ -2088,6 +3777,7 @@ fn shared_clone(args: &[Value]) -> Result<Value, RuntimeError> {
 
 /// Get the reference count of a Shared<T> smart pointer
 /// Usage: Shared_count(shared) -> Number
+#[cfg(feature = "runtime-smartptr")]
 fn shared_count(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Shared { ref_count, .. } => Ok(Value::Number(*ref_count as f64)),
@@ -2100,6 +3790,7 @@ fn shared_count(args: &[Value]) -> Result<Value, RuntimeError> {
 
 /// Create a new Cell<T> for interior mutability
 /// Usage: Cell_new(value) -> Cell<T>
+#[cfg(feature = "runtime-smartptr")]
 fn cell_new(args: &[Value]) -> Result<Value, RuntimeError> {
     Ok(Value::Cell {
         value: Box::new(args[0].clone()),
@@ -2110,6 +3801,7 @@ fn cell_new(args: &[Value]) -> Result<Value, RuntimeError> {
 
 /// Get the value from a Cell<T> (immutable borrow)
 /// Usage: Cell_get(cell) -> T
+#[cfg(feature = "runtime-smartptr")]
 fn cell_get(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Cell { value, borrowed, .. } => {
@@ -2129,6 +3821,7 @@ fn cell_get(args: &[Value]) -> Result<Value, RuntimeError> {
 
 /// Set the value in a Cell<T> (mutable borrow)
 /// Usage: Cell_set(cell, new_value) -> Nothing
+#[cfg(feature = "runtime-smartptr")]
 fn cell_set(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Cell { borrowed, borrow_count, .. } => {
@@ -2156,6 +3849,7 @@ fn cell_set(args: &[Value]) -> Result<Value, RuntimeError> {
 
 /// Borrow the value immutably from a Cell<T>
 /// Usage: Cell_borrow(cell) -> T
+#[cfg(feature = "runtime-smartptr")]
 fn cell_borrow(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Cell { value, borrowed, .. } => {
@@ -2177,6 +3871,7 @@ fn cell_borrow(args: &[Value]) -> Result<Value, RuntimeError> {
 
 /// Borrow the value mutably from a Cell<T>
 /// Usage: Cell_borrow_mut(cell) -> T
+#[cfg(feature = "runtime-smartptr")]
 fn cell_borrow_mut(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Cell { value, borrowed, borrow_count } => {
@@ -2203,6 +3898,7 @@ fn cell_borrow_mut(args: &[Value]) -> Result<Value, RuntimeError> {
 
 /// Release a borrow on a Cell<T>
 /// Usage: Cell_release(cell) -> Nothing
+#[cfg(feature = "runtime-smartptr")]
 fn cell_release(args: &[Value]) -> Result<Value, RuntimeError> {
     match &args[0] {
         Value::Cell { .. } => {
@@ -0,0 +1,171 @@
+//! # Scheduler
+//!
+//! Integration point between script concurrency and the host's scheduler.
+//!
+//! Scripts start tasks with `spawn(chant, args...)`, give up control with
+//! `yield_now()`, wait for an event with `block_on_event(id)` and wake
+//! waiters with `signal_event(id)` (also reachable as `Task.spawn`,
+//! `Task.yield_now`, `Task.wait` and `Task.signal`), and group tasks in a
+//! `together ... end` scope that does not end until they have finished —
+//! or, when it exits early, cancels the ones that have not started. The
+//! evaluator forwards each of these to a [`Scheduler`]:
+//!
+//! - [`CooperativeScheduler`] is the in-crate default. Tasks queue up and
+//!   run on the evaluator's own thread whenever the script yields or blocks.
+//! - AethelOS supplies its own implementation through
+//!   [`Evaluator::set_scheduler`](crate::eval::Evaluator::set_scheduler), so
+//!   a yield can hand the CPU to the kernel and a blocked script can park its
+//!   thread until the kernel raises the event.
+//!
+//! ```
+//! use glimmer_weave::scheduler::{CooperativeScheduler, Scheduler};
+//! use glimmer_weave::Value;
+//!
+//! let mut scheduler = CooperativeScheduler::new();
+//! let id = scheduler.spawn(Value::Nothing, vec![]);
+//! assert_eq!(scheduler.next_ready().map(|task| task.id), Some(id));
+//! assert!(scheduler.next_ready().is_none());
+//! ```
+
+use alloc::collections::{BTreeSet, VecDeque};
+use alloc::vec::Vec;
+
+use crate::eval::Value;
+
+/// Identifier of a spawned task
+pub type TaskId = u64;
+
+/// Identifier of an event tasks can block on
+pub type EventId = u64;
+
+/// A unit of script work: a callable value and the arguments to call it with
+#[derive(Debug, Clone, PartialEq)]
+pub struct Task {
+    pub id: TaskId,
+    pub chant: Value,
+    pub args: Vec<Value>,
+}
+
+/// Scheduler the evaluator calls into for script concurrency
+///
+/// The evaluator runs the tasks itself; the scheduler decides which task is
+/// ready next and gets a chance to cooperate with the host on every yield
+/// and block.
+pub trait Scheduler: crate::sync::MaybeSend {
+    /// Queue `chant(args...)` to run later and return its task id
+    fn spawn(&mut self, chant: Value, args: Vec<Value>) -> TaskId;
+
+    /// Called when the running script yields, before ready tasks run
+    fn yield_now(&mut self);
+
+    /// Called while the running script waits for `event` and no task is
+    /// ready to run; an OS scheduler may park the thread here until the
+    /// event is signalled
+    fn block_on_event(&mut self, event: EventId);
+
+    /// Mark `event` as signalled, releasing everything blocked on it
+    fn signal(&mut self, event: EventId);
+
+    /// Whether `event` has been signalled
+    fn is_signalled(&self, event: EventId) -> bool;
+
+    /// Take the next task that is ready to run
+    fn next_ready(&mut self) -> Option<Task>;
+
+    /// Drop a task that has not started, returning whether it was queued
+    fn cancel(&mut self, task: TaskId) -> bool;
+}
+
+/// Default single-threaded scheduler: a FIFO queue of tasks
+#[derive(Debug, Default)]
+pub struct CooperativeScheduler {
+    ready: VecDeque<Task>,
+    signalled: BTreeSet<EventId>,
+    next_id: TaskId,
+}
+
+impl CooperativeScheduler {
+    /// Create an empty scheduler
+    pub fn new() -> Self {
+        CooperativeScheduler::default()
+    }
+
+    /// Number of tasks waiting to run
+    pub fn pending(&self) -> usize {
+        self.ready.len()
+    }
+}
+
+impl Scheduler for CooperativeScheduler {
+    fn spawn(&mut self, chant: Value, args: Vec<Value>) -> TaskId {
+        self.next_id += 1;
+        let id = self.next_id;
+        self.ready.push_back(Task { id, chant, args });
+        id
+    }
+
+    fn yield_now(&mut self) {
+        // Nothing to hand control to; the evaluator runs the ready tasks
+    }
+
+    fn block_on_event(&mut self, _event: EventId) {
+        // No other thread can signal the event; the evaluator reports the
+        // deadlock once nothing is ready
+    }
+
+    fn signal(&mut self, event: EventId) {
+        self.signalled.insert(event);
+    }
+
+    fn is_signalled(&self, event: EventId) -> bool {
+        self.signalled.contains(&event)
+    }
+
+    fn next_ready(&mut self) -> Option<Task> {
+        self.ready.pop_front()
+    }
+
+    fn cancel(&mut self, task: TaskId) -> bool {
+        let queued = self.ready.len();
+        self.ready.retain(|ready| ready.id != task);
+        self.ready.len() < queued
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tasks_run_in_spawn_order() {
+        let mut scheduler = CooperativeScheduler::new();
+        let first = scheduler.spawn(Value::Number(1.0), vec![]);
+        let second = scheduler.spawn(Value::Number(2.0), vec![Value::Nothing]);
+        assert_ne!(first, second);
+        assert_eq!(scheduler.pending(), 2);
+
+        assert_eq!(scheduler.next_ready().unwrap().id, first);
+        let task = scheduler.next_ready().unwrap();
+        assert_eq!((task.id, task.args), (second, vec![Value::Nothing]));
+        assert!(scheduler.next_ready().is_none());
+    }
+
+    #[test]
+    fn test_cancel_drops_queued_tasks() {
+        let mut scheduler = CooperativeScheduler::new();
+        let first = scheduler.spawn(Value::Nothing, vec![]);
+        let second = scheduler.spawn(Value::Nothing, vec![]);
+        assert!(scheduler.cancel(first));
+        assert!(!scheduler.cancel(first));
+        assert_eq!(scheduler.next_ready().map(|task| task.id), Some(second));
+    }
+
+    #[test]
+    fn test_signal_events() {
+        let mut scheduler = CooperativeScheduler::new();
+        assert!(!scheduler.is_signalled(7));
+        scheduler.signal(7);
+        assert!(scheduler.is_signalled(7));
+        assert!(!scheduler.is_signalled(8));
+    }
+}
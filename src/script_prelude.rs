@@ -0,0 +1,225 @@
+//! # Script Prelude
+//!
+//! The set of builtins every compilation unit can use without importing
+//! anything. The default prelude holds the core helpers most scripts rely on:
+//!
+//! - Outcome helpers (`is_triumph`, `triumph_or`, `refine_mishap`, ...)
+//! - Maybe helpers (`is_present`, `present_or`, `then_present`, ...)
+//! - Iterator functions (`iter`, `iter_next`, `iter_map`, ...)
+//! - Output (`print`, `println`)
+//!
+//! The rest of the runtime library lives in namespaced builtin modules
+//! (`Text.upper`, `List.push`, `Math.sqrt`, see
+//! [`BUILTIN_MODULES`](crate::runtime::BUILTIN_MODULES)). For compatibility the
+//! same functions are also exposed as flat globals through
+//! [`Prelude::flat_library`], which is on by default so existing scripts keep
+//! working. Minimal kernel scripts can start from [`Prelude::none`] and opt
+//! into individual builtins.
+//!
+//! ```
+//! use glimmer_weave::script_prelude::Prelude;
+//!
+//! let kernel = Prelude::none().with_builtin("println");
+//! assert!(kernel.is_available("println"));
+//! assert!(!kernel.is_available("iter_map"));
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::runtime::{builtin_modules, get_builtins, BuiltinRegistry, NativeFunction};
+
+/// Builtins of the core prelude, in registration order
+const CORE: &[&str] = &[
+    // Outcome<T, E>
+    "is_triumph",
+    "is_mishap",
+    "expect_triumph",
+    "triumph_or",
+    "triumph_or_else",
+    "expect_mishap",
+    "refine_triumph",
+    "refine_mishap",
+    "then_triumph",
+    // Maybe<T>
+    "is_present",
+    "is_absent",
+    "expect_present",
+    "present_or",
+    "present_or_else",
+    "refine_present",
+    "then_present",
+    // Outcome <-> Maybe
+    "present_or_mishap",
+    "triumph_or_absent",
+    "both_triumph",
+    "either_triumph",
+    // Iterators
+    "iter",
+    "iter_next",
+    "iter_map",
+    "iter_filter",
+    "iter_fold",
+    "iter_collect",
+    "iter_take",
+    // Output
+    "print",
+    "println",
+];
+
+/// Builtins injected into the scope of every compilation unit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prelude {
+    /// Builtins always in scope
+    builtins: Vec<String>,
+    /// Whether the namespaced builtin modules are in scope
+    modules: bool,
+    /// Whether the rest of the runtime library is also in scope as flat globals
+    flat_library: bool,
+}
+
+impl Default for Prelude {
+    /// The core prelude plus the flat runtime library
+    fn default() -> Self {
+        Prelude::core().flat_library(true)
+    }
+}
+
+impl Prelude {
+    /// Outcome/Maybe helpers, iterator functions, `print`/`println` and the
+    /// namespaced builtin modules
+    pub fn core() -> Self {
+        Prelude {
+            builtins: CORE.iter().map(|name| name.to_string()).collect(),
+            modules: true,
+            flat_library: false,
+        }
+    }
+
+    /// No builtins at all
+    pub fn none() -> Self {
+        Prelude {
+            builtins: Vec::new(),
+            modules: false,
+            flat_library: false,
+        }
+    }
+
+    /// Bring the namespaced builtin modules (`Text`, `List`, `Math`, ...) into scope
+    pub fn builtin_modules(mut self, enabled: bool) -> Self {
+        self.modules = enabled;
+        self
+    }
+
+    /// Also expose every other runtime builtin as a flat global
+    ///
+    /// This is the compatibility alias for scripts written before builtins
+    /// moved into modules, e.g. `upper(s)` for `Text.upper(s)`.
+    pub fn flat_library(mut self, enabled: bool) -> Self {
+        self.flat_library = enabled;
+        self
+    }
+
+    /// Add a runtime builtin to the prelude
+    pub fn with_builtin(mut self, name: &str) -> Self {
+        if !self.builtins.iter().any(|existing| existing == name) {
+            self.builtins.push(name.to_string());
+        }
+        self
+    }
+
+    /// Remove a builtin from the prelude
+    pub fn without_builtin(mut self, name: &str) -> Self {
+        self.builtins.retain(|existing| existing != name);
+        self
+    }
+
+    /// Builtins that are always in scope
+    pub fn builtins(&self) -> &[String] {
+        &self.builtins
+    }
+
+    /// Whether the flat runtime library is in scope
+    pub fn has_flat_library(&self) -> bool {
+        self.flat_library
+    }
+
+    /// Whether the runtime builtin `name` is in scope under this prelude
+    pub fn is_available(&self, name: &str) -> bool {
+        BuiltinRegistry::new().contains(name)
+            && (self.flat_library || self.builtins.iter().any(|builtin| builtin == name))
+    }
+
+    /// Native functions to define in the global scope
+    pub fn natives(&self) -> Vec<NativeFunction> {
+        get_builtins()
+            .into_iter()
+            .filter(|builtin| self.flat_library || self.builtins.contains(&builtin.name))
+            .collect()
+    }
+
+    /// Builtin modules in scope, with their members
+    pub fn modules(&self) -> Vec<(&'static str, Vec<(&'static str, NativeFunction)>)> {
+        if self.modules {
+            builtin_modules()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_names_are_runtime_builtins() {
+        let builtins = get_builtins();
+        for name in CORE {
+            assert!(builtins.iter().any(|b| b.name == *name), "{} is not a runtime builtin", name);
+        }
+        assert_eq!(Prelude::core().natives().len(), CORE.len());
+    }
+
+    #[test]
+    fn test_default_keeps_flat_library() {
+        let prelude = Prelude::default();
+        assert!(prelude.is_available("iter_map"));
+        assert!(prelude.is_available("upper"));
+        assert_eq!(prelude.natives().len(), get_builtins().len());
+    }
+
+    #[test]
+    fn test_core_excludes_library() {
+        let prelude = Prelude::core();
+        assert!(prelude.is_available("println"));
+        assert!(!prelude.is_available("upper"));
+    }
+
+    #[test]
+    fn test_builtin_modules() {
+        let modules = Prelude::core().modules();
+        let (_, text) = modules.iter().find(|(name, _)| *name == "Text").unwrap();
+        assert!(text.iter().any(|(member, f)| *member == "upper" && f.name == "upper"));
+        let (_, list) = modules.iter().find(|(name, _)| *name == "List").unwrap();
+        assert!(list.iter().any(|(member, f)| *member == "push" && f.name == "list_push"));
+        assert!(Prelude::core().builtin_modules(false).modules().is_empty());
+        assert!(Prelude::none().modules().is_empty());
+    }
+
+    #[test]
+    fn test_every_module_member_is_a_builtin() {
+        for (module, members) in crate::runtime::BUILTIN_MODULES {
+            let bound = builtin_modules().into_iter().find(|(name, _)| name == module).unwrap().1;
+            assert_eq!(bound.len(), members.len(), "{} has members without a builtin", module);
+        }
+    }
+
+    #[test]
+    fn test_customized_prelude() {
+        let prelude = Prelude::core().without_builtin("print").with_builtin("sqrt");
+        assert!(!prelude.is_available("print"));
+        assert!(prelude.is_available("sqrt"));
+        assert!(Prelude::none().natives().is_empty());
+    }
+}
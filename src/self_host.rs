@@ -0,0 +1,225 @@
+//! # Compiler Phases for Scripts
+//!
+//! The compiler's phases as builtins, so formatters, linters and code
+//! generators can be written in Glimmer-Weave and run where only the
+//! language is, on AethelOS. Each takes source text and returns plain
+//! values a script can walk:
+//!
+//! - `Compiler.lex(source)` — a List of token Maps: `kind` (`"Bind"`,
+//!   `"Ident"`, `"Newline"`), `text` as the parser describes the token,
+//!   `keyword`, `line`, `column`, and `value` (Nothing except for literals
+//!   and names)
+//! - `Compiler.parse(source)` — `Triumph` of a List of node Maps, or
+//!   `Mishap` of diagnostics. A node has its `kind` (as
+//!   [`AstNode::kind`] names it), `line`, `column`, `end_line`,
+//!   `end_column` and `children` in source order, with `name` (for
+//!   definitions, bindings, identifiers and calls), `value` (for literals)
+//!   and `operator` Nothing where they do not apply
+//! - `Compiler.analyze(source)` — every diagnostic of lexing, parsing and
+//!   semantic analysis, warnings included
+//! - `Compiler.compile(source, target)` — `Triumph` of the bytecode
+//!   disassembly (`"bytecode"`) or assembly listing (`"asm"`), or `Mishap`
+//!   of diagnostics
+//!
+//! A diagnostic is a Map of `severity`, `message`, `notes`, `line` and
+//! `column`, the last two Nothing when it does not point into the source.
+//! Every Map has all of its keys, so a script can read any of them.
+//!
+//! Reading code is not free of risk — a script could probe for what a
+//! host's prelude defines — so all four need a [`COMPILER_CAPABILITY`]
+//! grant.
+//!
+//! ```
+//! use glimmer_weave::eval::Value;
+//! use glimmer_weave::self_host::lex;
+//!
+//! let Value::List(tokens) = lex("bind x to 1") else { unreachable!() };
+//! let Value::Map(first) = &tokens[0] else { unreachable!() };
+//! assert_eq!(first.get("kind"), Some(&Value::Text("Bind".to_string())));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+
+use crate::ast::AstNode;
+use crate::ast_query::node_name;
+use crate::bytecode::Disassembler;
+use crate::error_formatter::{Diagnostic, Diagnostics};
+use crate::eval::Value;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::pipeline::{CompilerPipeline, Output, Target};
+use crate::source_location::SourceSpan;
+use crate::token::{PositionedToken, Token};
+
+/// Capability a script must hold to run compiler phases
+pub const COMPILER_CAPABILITY: &str = "Compiler.inspect";
+
+/// Targets `Compiler.compile` accepts
+pub const COMPILE_TARGETS: [&str; 2] = ["bytecode", "asm"];
+
+/// The tokens of `source`, as a List of Maps
+pub fn lex(source: &str) -> Value {
+    tokens_to_value(&Lexer::new(source).tokenize_positioned())
+}
+
+/// The syntax tree of `source` as a List of node Maps, or its parse error
+pub fn parse(source: &str) -> Value {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    match Parser::new(tokens.clone()).parse() {
+        Ok(ast) => outcome(true, Value::List(ast.iter().map(node_to_value).collect())),
+        Err(e) => {
+            let mut diagnostic = Diagnostic::error(format!("Parse error: {}", e.message));
+            if let Some(token) = tokens.get(e.position) {
+                diagnostic = diagnostic.with_primary_label(token.source_span(), "here");
+            }
+            outcome(false, Value::List(vec![diagnostic_to_value(&diagnostic)]))
+        }
+    }
+}
+
+/// Every diagnostic of checking `source`, as a List of Maps
+pub fn analyze(source: &str) -> Value {
+    diagnostics_to_value(&CompilerPipeline::new().check(source))
+}
+
+/// The compiled form of `source` for one of [`COMPILE_TARGETS`], or its
+/// diagnostics
+pub fn compile(source: &str, target: &str) -> Result<Value, String> {
+    let target = match target {
+        "bytecode" => Target::Bytecode,
+        "asm" => Target::Asm,
+        other => {
+            return Err(format!("Unknown compile target '{}'; expected one of {}", other, COMPILE_TARGETS.join(", ")))
+        }
+    };
+    Ok(match CompilerPipeline::new().run(source, target) {
+        Ok(Output::Bytecode(chunk)) => outcome(true, Value::Text(Disassembler::new(&chunk).disassemble())),
+        Ok(Output::Asm(asm)) => outcome(true, Value::Text(asm)),
+        Ok(_) => outcome(true, Value::Nothing),
+        Err(diagnostics) => outcome(false, diagnostics_to_value(&diagnostics)),
+    })
+}
+
+/// Tokens as a List of Maps
+pub fn tokens_to_value(tokens: &[PositionedToken]) -> Value {
+    Value::List(tokens.iter().map(token_to_value).collect())
+}
+
+fn token_to_value(token: &PositionedToken) -> Value {
+    let mut map = BTreeMap::new();
+    map.insert("kind".to_string(), Value::Text(token_kind(&token.token)));
+    map.insert("text".to_string(), Value::Text(token.token.description().to_string()));
+    map.insert("keyword".to_string(), Value::Truth(token.token.is_keyword()));
+    map.insert("line".to_string(), Value::Number(token.span.line as f64));
+    map.insert("column".to_string(), Value::Number(token.span.column as f64));
+    let value = match &token.token {
+        Token::Number(n) => Value::Number(*n),
+        Token::BigInt(n) => Value::BigInt(n.clone()),
+        Token::Integer(n) => Value::Integer(*n),
+        Token::Text(text) | Token::Ident(text) | Token::Lifetime(text) => Value::Text(text.clone()),
+        Token::Truth(truth) => Value::Truth(*truth),
+        _ => Value::Nothing,
+    };
+    map.insert("value".to_string(), value);
+    Value::Map(map)
+}
+
+/// Name of a token's variant, e.g. `"Bind"` or `"Number"`
+fn token_kind(token: &Token) -> String {
+    let debug = format!("{:?}", token);
+    match debug.split_once('(') {
+        Some((kind, _)) => kind.to_string(),
+        None => debug,
+    }
+}
+
+/// A syntax tree node, and everything below it, as a Map
+pub fn node_to_value(node: &AstNode) -> Value {
+    let mut map = BTreeMap::new();
+    map.insert("kind".to_string(), Value::Text(node.kind().to_string()));
+    insert_span(&mut map, node.span());
+    map.insert("end_line".to_string(), Value::Number(node.span().end.line as f64));
+    map.insert("end_column".to_string(), Value::Number(node.span().end.column as f64));
+    map.insert("name".to_string(), node_name(node).map_or(Value::Nothing, Value::Text));
+    let value = match node {
+        AstNode::Number { value, .. } => Value::Number(*value),
+        AstNode::BigInt { value, .. } => Value::BigInt(value.clone()),
+        AstNode::Integer { value, .. } => Value::Integer(*value),
+        AstNode::Text { value, .. } => Value::Text(value.clone()),
+        AstNode::Truth { value, .. } => Value::Truth(*value),
+        _ => Value::Nothing,
+    };
+    map.insert("value".to_string(), value);
+    let operator = match node {
+        AstNode::BinaryOp { op, .. } => Value::Text(format!("{:?}", op)),
+        AstNode::UnaryOp { op, .. } => Value::Text(format!("{:?}", op)),
+        _ => Value::Nothing,
+    };
+    map.insert("operator".to_string(), operator);
+    map.insert("children".to_string(), Value::List(node.children().into_iter().map(node_to_value).collect()));
+    Value::Map(map)
+}
+
+/// Diagnostics as a List of Maps
+pub fn diagnostics_to_value(diagnostics: &Diagnostics) -> Value {
+    Value::List(diagnostics.iter().map(diagnostic_to_value).collect())
+}
+
+fn diagnostic_to_value(diagnostic: &Diagnostic) -> Value {
+    let mut map = BTreeMap::new();
+    map.insert("severity".to_string(), Value::Text(diagnostic.severity.as_str().to_string()));
+    map.insert("message".to_string(), Value::Text(diagnostic.message.clone()));
+    map.insert("notes".to_string(), Value::List(diagnostic.notes.iter().cloned().map(Value::Text).collect()));
+    match diagnostic.labels.iter().find(|label| label.primary) {
+        Some(label) => insert_span(&mut map, &label.span),
+        None => {
+            map.insert("line".to_string(), Value::Nothing);
+            map.insert("column".to_string(), Value::Nothing);
+        }
+    }
+    Value::Map(map)
+}
+
+fn insert_span(map: &mut BTreeMap<String, Value>, span: &SourceSpan) {
+    map.insert("line".to_string(), Value::Number(span.start.line as f64));
+    map.insert("column".to_string(), Value::Number(span.start.column as f64));
+}
+
+fn outcome(success: bool, value: Value) -> Value {
+    Value::Outcome { success, value: Box::new(value) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field<'a>(value: &'a Value, key: &str) -> &'a Value {
+        match value {
+            Value::Map(map) => map.get(key).unwrap_or(&Value::Nothing),
+            _ => &Value::Nothing,
+        }
+    }
+
+    #[test]
+    fn test_token_kinds_drop_their_payload() {
+        assert_eq!(token_kind(&Token::Bind), "Bind");
+        assert_eq!(token_kind(&Token::Number(1.5)), "Number");
+        assert_eq!(token_kind(&Token::Ident("x".to_string())), "Ident");
+    }
+
+    #[test]
+    fn test_nodes_carry_operators_and_children() {
+        let ast = Parser::new(Lexer::new("1 + 2").tokenize_positioned()).parse().unwrap_or_default();
+        let node = node_to_value(&ast[0]);
+        let sum = match field(&node, "children") {
+            Value::List(children) if field(&node, "kind") == &Value::Text("ExprStmt".to_string()) => children[0].clone(),
+            _ => node,
+        };
+        assert_eq!(field(&sum, "operator"), &Value::Text("Add".to_string()));
+        let Value::List(operands) = field(&sum, "children") else { unreachable!() };
+        assert_eq!(field(&operands[1], "value"), &Value::Number(2.0));
+    }
+}
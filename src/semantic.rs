@@ -6,7 +6,11 @@
 //! - **Name resolution**: Checks that all variables/functions are defined before use
 //! - **Type checking**: Validates type compatibility in operations and assignments
 //! - **Scope analysis**: Tracks variable scopes and detects shadowing
+//! - **Slot resolution**: [`resolve_scopes`] annotates identifiers with `(depth, slot)`
+//!   pairs so the interpreter can index scope frames directly
 //! - **Function arity checking**: Validates function calls have correct argument counts
+//! - **Module checking**: Records each module's exported signatures and checks
+//!   imported names and calls into other modules against them
 //!
 //! This catches errors early, before runtime or code generation, providing
 //! better error messages and preventing invalid programs from executing.
@@ -15,9 +19,11 @@ use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use alloc::vec;
 use alloc::boxed::Box;
-use alloc::collections::BTreeMap;
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::format;
 use crate::ast::*;
+use crate::script_prelude::Prelude;
+use crate::units::Dimension;
 
 /// Types in the Glimmer-Weave type system
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -32,6 +38,8 @@ pub enum Type {
     Nothing,
     /// List of values (homogeneous or heterogeneous)
     List(Box<Type>),  // Box<Type::Any> for heterogeneous lists
+    /// Tuple of a fixed number of values, each with its own type
+    Tuple(Vec<Type>),
     /// Map from string keys to values
     Map,
     /// Function type (param types, return type)
@@ -56,6 +64,12 @@ pub enum Type {
         name: String,
         type_args: Vec<Type>,
     },
+    /// Number with a unit of measure (`10 ms` is a `Time`)
+    Measure(Dimension),
+    /// Arbitrary-precision integer (`123n`)
+    BigInt,
+    /// 64-bit integer (`42i`)
+    Integer,
 }
 
 impl Type {
@@ -73,6 +87,10 @@ impl Type {
             (Type::TypeParam(_), _) | (_, Type::TypeParam(_)) => true,
             // Lists are compatible if element types match
             (Type::List(a), Type::List(b)) => a.is_compatible(b),
+            // Tuples are compatible if they have as many elements, of compatible types
+            (Type::Tuple(a), Type::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| a.is_compatible(b))
+            }
             // Generic types are compatible if names and type args match
             (Type::Generic { name: n1, type_args: args1 }, Type::Generic { name: n2, type_args: args2 }) => {
                 n1 == n2 && args1.len() == args2.len() &&
@@ -94,6 +112,9 @@ impl Type {
             Type::List(inner) => {
                 Type::List(Box::new(inner.substitute(substitutions)))
             }
+            Type::Tuple(elements) => {
+                Type::Tuple(elements.iter().map(|element| element.substitute(substitutions)).collect())
+            }
             Type::Function { params, return_type } => {
                 Type::Function {
                     params: params.iter().map(|p| p.substitute(substitutions)).collect(),
@@ -119,6 +140,7 @@ impl Type {
             Type::Truth => "Truth",
             Type::Nothing => "Nothing",
             Type::List(_) => "List",
+            Type::Tuple(_) => "Tuple",
             Type::Map => "Map",
             Type::Function { .. } => "Function",
             Type::Capability => "Capability",
@@ -127,6 +149,9 @@ impl Type {
             Type::Any => "Any",
             Type::TypeParam(_) => "TypeParam",
             Type::Generic { name, .. } => name,
+            Type::Measure(dimension) => dimension.name(),
+            Type::BigInt => "BigInt",
+            Type::Integer => "Integer",
         }
     }
 }
@@ -164,6 +189,8 @@ pub enum SemanticError {
     /// Match expression is not exhaustive (doesn't cover all cases)
     NonExhaustiveMatch {
         message: String,
+        /// The match's closing `end`
+        span: crate::source_location::SourceSpan,
     },
     /// Module not found during import
     ModuleNotFound {
@@ -188,10 +215,95 @@ pub enum SemanticError {
     CircularModuleDependency {
         cycle: Vec<String>,
     },
+    /// Imported chant used with a signature its module does not export
+    ImportSignatureMismatch {
+        symbol: String,
+        module: String,
+        expected: String,
+        got: String,
+        span: Box<crate::source_location::SourceSpan>,
+    },
+    /// `tr` called with a key the default message catalog does not have
+    UnknownMessageKey {
+        key: String,
+        span: crate::source_location::SourceSpan,
+    },
+    /// Syntax newer than the version the script's `speaks` pragma names
+    UnsupportedFeature {
+        feature: crate::language_version::Feature,
+        message: String,
+        span: crate::source_location::SourceSpan,
+    },
+    /// Named arguments that do not fit the parameters of the chant called
+    ArgumentMismatch {
+        function: String,
+        error: ArgumentError,
+        span: Box<crate::source_location::SourceSpan>,
+    },
     /// Custom error message (for trait system and other features)
     Custom(String),
 }
 
+impl SemanticError {
+    /// Source location of the offending code, when the error carries one
+    pub fn span(&self) -> Option<&crate::source_location::SourceSpan> {
+        match self {
+            SemanticError::ImportSignatureMismatch { span, .. } | SemanticError::ArgumentMismatch { span, .. } => Some(span.as_ref()),
+            SemanticError::UnknownMessageKey { span, .. } | SemanticError::UnsupportedFeature { span, .. } => Some(span),
+            SemanticError::NonExhaustiveMatch { span, .. } if span.is_known() => Some(span),
+            _ => None,
+        }
+    }
+
+    /// Edits that would fix this error, where one is clear
+    pub fn suggestions(&self) -> Vec<crate::fixit::Suggestion> {
+        use crate::fixit::{Applicability, Suggestion};
+        match self {
+            SemanticError::NonExhaustiveMatch { span, .. } if span.is_known() => {
+                // Line the new arm up with the `end` it goes before
+                let indent = " ".repeat(span.start.column - 1);
+                let arm = format!("otherwise then\n{}    nothing\n{}", indent, indent);
+                vec![Suggestion::insert("add an `otherwise` arm", &span.start, arm, Applicability::HasPlaceholders)]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Code that is valid but probably does not do what was meant
+#[derive(Debug, Clone, PartialEq)]
+pub enum SemanticWarning {
+    /// A statement in a chant body computes a value nothing uses
+    UnusedValue { chant: String },
+    /// A chant declared `-> Nothing` ends in a value it then discards
+    DiscardedReturnValue { chant: String },
+    /// A list index outside the list's bounds; `certain` when it is out of
+    /// bounds on every path, not just some
+    IndexOutOfBounds { certain: bool, span: crate::source_location::SourceSpan },
+    /// A division or remainder by a divisor that is, or may be, zero
+    DivisionByZero { certain: bool, span: crate::source_location::SourceSpan },
+    /// A use of a chant, form or grove marked `deprecated`
+    Deprecated {
+        name: String,
+        message: String,
+        /// What to use instead, from the `use` hint
+        replacement: Option<String>,
+        span: crate::source_location::SourceSpan,
+    },
+}
+
+impl SemanticWarning {
+    /// Source location of the offending code, when the warning carries one
+    pub fn span(&self) -> Option<&crate::source_location::SourceSpan> {
+        match self {
+            SemanticWarning::IndexOutOfBounds { span, .. }
+            | SemanticWarning::DivisionByZero { span, .. }
+            | SemanticWarning::Deprecated { span, .. } => Some(span),
+            _ => None,
+        }
+    }
+}
+
 /// Symbol in the symbol table
 ///
 /// FUTURE: The `name` and `defined` fields will be used for:
@@ -319,6 +431,9 @@ struct TraitImplKey {
     aspect_name: String,
     /// Normalized string representation of target type
     target_type: String,
+    /// Normalized aspect type arguments; `ConvertError<A>` and
+    /// `ConvertError<B>` may both be implemented for one type
+    type_args: Vec<String>,
 }
 
 /// Information about a module's exports
@@ -353,6 +468,7 @@ pub struct SemanticAnalyzer {
     symbol_table: SymbolTable,
     in_function: bool,
     errors: Vec<SemanticError>,
+    warnings: Vec<SemanticWarning>,
     /// Stack of type parameter contexts for generic functions/structs
     /// Each context maps type parameter names to their Type::TypeParam representation
     type_params_stack: Vec<BTreeMap<String, Type>>,
@@ -367,8 +483,19 @@ pub struct SemanticAnalyzer {
     /// Imported modules in current scope (module_name -> imported symbols)
     /// None means all symbols are imported (summon), Some(vec) means specific symbols (gather)
     imported_modules: BTreeMap<String, Option<Vec<String>>>,
+    /// Symbols brought into scope by `gather` (symbol -> module it came from)
+    imported_symbols: BTreeMap<String, String>,
+    /// Namespaced builtin modules from the prelude (`Text`, `List`, `Math`, ...)
+    builtin_modules: BTreeMap<String, ModuleExports>,
     /// Current module being analyzed (if inside a module declaration)
     current_module: Option<String>,
+    /// Default message catalog `tr` keys are checked against, if any
+    message_catalog: Option<crate::i18n::Catalog>,
+    /// Parameters of the chants analyzed so far, to check named arguments
+    chant_params: BTreeMap<String, Vec<Parameter>>,
+    /// Cases without fields of the variants analyzed so far; a bare name in
+    /// a pattern that names one of them matches that case instead of binding
+    unit_variants: BTreeSet<String>,
 }
 
 impl Default for SemanticAnalyzer {
@@ -378,27 +505,74 @@ impl Default for SemanticAnalyzer {
 }
 
 impl SemanticAnalyzer {
-    /// Create a new semantic analyzer
+    /// Create a new semantic analyzer with the default prelude in scope
     pub fn new() -> Self {
+        Self::with_prelude(&Prelude::default())
+    }
+
+    /// Create a semantic analyzer whose global scope holds the builtins of `prelude`
+    pub fn with_prelude(prelude: &Prelude) -> Self {
         let mut analyzer = SemanticAnalyzer {
             symbol_table: SymbolTable::new(),
             in_function: false,
             errors: Vec::new(),
+            warnings: Vec::new(),
             type_params_stack: Vec::new(),
             type_inference: None,  // Disabled by default
             trait_definitions: BTreeMap::new(),
             trait_implementations: BTreeMap::new(),
             module_exports: BTreeMap::new(),
             imported_modules: BTreeMap::new(),
+            imported_symbols: BTreeMap::new(),
+            builtin_modules: BTreeMap::new(),
             current_module: None,
+            message_catalog: None,
+            chant_params: BTreeMap::new(),
+            unit_variants: BTreeSet::new(),
         };
 
         // Register builtin functions
-        analyzer.register_builtins();
+        analyzer.register_builtins(prelude);
+        analyzer.register_builtin_aspects();
 
         analyzer
     }
 
+    /// Register aspects the runtime gives special meaning to
+    fn register_builtin_aspects(&mut self) {
+        // ConvertError<Target>: applied by `?` to turn a propagated error into
+        // the error type declared by the enclosing chant's Outcome return type
+        self.trait_definitions.insert("ConvertError".to_string(), TraitDefinition {
+            name: "ConvertError".to_string(),
+            type_params: vec!["Target".to_string()],
+            methods: vec![TraitMethod {
+                name: "convert".to_string(),
+                params: vec![Parameter::untyped("self".to_string())],
+                return_type: Some(TypeAnnotation::Generic("Target".to_string())),
+            }],
+        });
+
+        // Iterable<T>: lets `for each` and the iter_* builtins walk a form or
+        // variant; `next` may `set` fields of `self` to record its position
+        self.trait_definitions.insert("Iterable".to_string(), TraitDefinition {
+            name: "Iterable".to_string(),
+            type_params: vec!["T".to_string()],
+            methods: vec![TraitMethod {
+                name: "next".to_string(),
+                params: vec![Parameter::untyped("self".to_string())],
+                return_type: Some(TypeAnnotation::Parametrized {
+                    name: "Maybe".to_string(),
+                    type_args: vec![TypeAnnotation::Generic("T".to_string())],
+                }),
+            }],
+        });
+    }
+
+    /// Report `tr` calls whose literal key `catalog` does not have
+    pub fn check_message_keys(&mut self, catalog: crate::i18n::Catalog) {
+        self.message_catalog = Some(catalog);
+    }
+
     /// Enable Hindley-Milner type inference
     ///
     /// When enabled, the semantic analyzer will use constraint-based type
@@ -488,6 +662,9 @@ fn semantic_type_annotation_to_string(ann: &TypeAnnotation) -> String {
             format!("{}<{}>", name, args.join(", "))
         }
         TypeAnnotation::Map => "Map".to_string(),
+        TypeAnnotation::Array { element, length } => {
+            format!("array of {} length {}", semantic_type_annotation_to_string(element), length)
+        }
         TypeAnnotation::Function { .. } => "Function".to_string(),
         TypeAnnotation::Optional(inner) => {
             format!("{}?", semantic_type_annotation_to_string(inner))
@@ -502,7 +679,7 @@ fn semantic_type_annotation_to_string(ann: &TypeAnnotation) -> String {
 impl SemanticAnalyzer {
 
     /// Register builtin runtime library functions
-    fn register_builtins(&mut self) {
+    fn register_builtins(&mut self, prelude: &Prelude) {
         // String functions
         let _ = self.symbol_table.define(
             "length".to_string(),
@@ -590,6 +767,77 @@ impl SemanticAnalyzer {
         );
 
         // Add more builtins as needed...
+
+        // Namespaced members share the signature of the builtin they name
+        for (module, members) in prelude.modules() {
+            let mut exports = ModuleExports {
+                name: module.to_string(),
+                exports: Vec::new(),
+                symbols: BTreeMap::new(),
+            };
+            for (member, builtin) in members {
+                let typ = self.symbol_table.lookup(&builtin.name).map_or(Type::Any, |symbol| symbol.typ.clone());
+                exports.exports.push(member.to_string());
+                exports.symbols.insert(member.to_string(), Symbol {
+                    name: member.to_string(),
+                    typ,
+                    mutable: false,
+                    defined: true,
+                });
+            }
+            self.builtin_modules.insert(module.to_string(), exports);
+        }
+
+        // Builtins outside the prelude (or compiled out of the runtime) are not
+        // in scope; the rest are dynamically typed unless they have a signature above
+        let available: Vec<String> = prelude.natives().into_iter().map(|builtin| builtin.name).collect();
+        self.symbol_table.scopes[0].symbols.retain(|name, _| available.contains(name));
+        for name in available {
+            if self.symbol_table.lookup(&name).is_none() {
+                let _ = self.symbol_table.define(name, Type::Any, false);
+            }
+        }
+    }
+
+    /// Warn about values a chant body computes and then drops
+    ///
+    /// A chant returns its last statement's value (see
+    /// `Evaluator::eval_chant_body`), so only a plain value before the end,
+    /// or at the end of a chant declared `-> Nothing`, goes unused. Calls
+    /// are not flagged: they may be made for their effects.
+    fn check_chant_values(&mut self, chant: &str, body: &[AstNode], returns_nothing: bool) {
+        fn is_plain_value(node: &AstNode) -> bool {
+            match node {
+                AstNode::ExprStmt { expr, .. } => is_plain_value(expr),
+                AstNode::Call { .. } | AstNode::Try { .. } => false,
+                node => node.is_expression(),
+            }
+        }
+
+        let Some((last, init)) = body.split_last() else { return };
+        for stmt in init {
+            if is_plain_value(stmt) {
+                self.warnings.push(SemanticWarning::UnusedValue { chant: chant.to_string() });
+            }
+        }
+
+        match last {
+            AstNode::IfStmt { then_branch, else_branch, .. } => {
+                self.check_chant_values(chant, then_branch, returns_nothing);
+                if let Some(else_branch) = else_branch {
+                    self.check_chant_values(chant, else_branch, returns_nothing);
+                }
+            }
+            AstNode::MatchStmt { arms, .. } => {
+                for arm in arms {
+                    self.check_chant_values(chant, &arm.body, returns_nothing);
+                }
+            }
+            last if returns_nothing && is_plain_value(last) => {
+                self.warnings.push(SemanticWarning::DiscardedReturnValue { chant: chant.to_string() });
+            }
+            _ => {}
+        }
     }
 
     /// Analyze a program (list of statements)
@@ -597,6 +845,12 @@ impl SemanticAnalyzer {
         for node in nodes {
             self.analyze_node(node);
         }
+        self.warnings.extend(crate::range_analysis::check(nodes));
+        self.warnings.extend(crate::deprecation::check(nodes));
+        self.errors.extend(crate::language_version::check(nodes));
+        if let Some(catalog) = &self.message_catalog {
+            self.errors.extend(crate::i18n::check(nodes, catalog));
+        }
 
         if self.errors.is_empty() {
             Ok(())
@@ -605,11 +859,41 @@ impl SemanticAnalyzer {
         }
     }
 
+    /// Warnings found so far; they do not fail `analyze`
+    pub fn warnings(&self) -> &[SemanticWarning] {
+        &self.warnings
+    }
+
+    /// Signatures exported by a module declared in the analyzed program
+    ///
+    /// Maps each exported name to its type; chants are `Type::Function`.
+    pub fn exported_signatures(&self, module: &str) -> Option<BTreeMap<String, Type>> {
+        let module_exports = self.module_exports.get(module)?;
+        Some(
+            module_exports
+                .exports
+                .iter()
+                .filter_map(|name| {
+                    module_exports.symbols.get(name).map(|symbol| (name.clone(), symbol.typ.clone()))
+                })
+                .collect(),
+        )
+    }
+
     /// Analyze a single AST node
     fn analyze_node(&mut self, node: &AstNode) -> Type {
         match node {
             // === Literals ===
             AstNode::Number { .. } => Type::Number,
+            AstNode::BigInt { .. } => Type::BigInt,
+            AstNode::Integer { .. } => Type::Integer,
+            AstNode::Measure { unit, .. } => match crate::units::unit(unit) {
+                Some(unit) => Type::Measure(unit.dimension),
+                None => {
+                    self.errors.push(SemanticError::UndefinedVariable(unit.clone()));
+                    Type::Unknown
+                }
+            },
             AstNode::Text { .. } => Type::Text,
             AstNode::Truth { .. } => Type::Truth,
             AstNode::Nothing { .. } => Type::Nothing,
@@ -650,6 +934,7 @@ impl SemanticAnalyzer {
                             context: format!("binding '{}'", name),
                         });
                     }
+                    self.check_array_length(type_ann, value, &format!("binding '{}'", name));
                     t
                 } else {
                     value_type
@@ -675,6 +960,7 @@ impl SemanticAnalyzer {
                             context: format!("weaving '{}'", name),
                         });
                     }
+                    self.check_array_length(type_ann, value, &format!("weaving '{}'", name));
                     t
                 } else {
                     value_type
@@ -740,6 +1026,7 @@ impl SemanticAnalyzer {
                     .as_ref()
                     .map(|t| self.convert_type_annotation(t))
                     .unwrap_or(Type::Any);
+                self.check_chant_values(name, body, ret_type == Type::Nothing);
 
                 // Define function in current scope
                 let func_type = Type::Function {
@@ -750,6 +1037,7 @@ impl SemanticAnalyzer {
                 if let Err(e) = self.symbol_table.define(name.clone(), func_type, false) {
                     self.errors.push(e);
                 }
+                self.chant_params.insert(name.clone(), params.clone());
 
                 // Analyze function body in new scope
                 self.symbol_table.push_scope();
@@ -776,7 +1064,16 @@ impl SemanticAnalyzer {
                 Type::Nothing
             }
 
-            AstNode::FormDef { name, type_params, fields: _, .. } => {
+            AstNode::FormDef { name, type_params, fields: _, deriving, .. } => {
+                for aspect in deriving {
+                    if !DERIVABLE_ASPECTS.contains(&aspect.as_str()) {
+                        self.errors.push(SemanticError::InvalidOperation {
+                            operation: format!("deriving {}", aspect),
+                            operand_type: name.clone(),
+                        });
+                    }
+                }
+
                 // Push type parameters onto the stack if any
                 if !type_params.is_empty() {
                     self.push_type_params(type_params);
@@ -797,8 +1094,20 @@ impl SemanticAnalyzer {
                 Type::Nothing
             }
 
-            AstNode::VariantDef { name, type_params, variants: _, .. } => {
+            AstNode::VariantDef { name, type_params, variants, .. } => {
                 // TODO: Phase 1 - Proper enum type checking
+
+                // Two cases sharing a discriminant could not be told apart
+                let discriminants = crate::ast::variant_discriminants(variants);
+                for (index, (case, discriminant)) in variants.iter().zip(&discriminants).enumerate() {
+                    if discriminants[..index].contains(discriminant) {
+                        self.errors.push(SemanticError::InvalidOperation {
+                            operation: format!("discriminant {} for {}", discriminant, case.name),
+                            operand_type: name.clone(),
+                        });
+                    }
+                }
+                self.unit_variants.extend(variants.iter().filter(|case| case.fields.is_empty()).map(|case| case.name.clone()));
                 // Push type parameters onto the stack if any
                 if !type_params.is_empty() {
                     self.push_type_params(type_params);
@@ -880,6 +1189,7 @@ impl SemanticAnalyzer {
                 let impl_key = TraitImplKey {
                     aspect_name: aspect_name.clone(),
                     target_type: self.type_annotation_to_string(target_type),
+                    type_args: type_args.iter().map(|ta| self.type_annotation_to_string(ta)).collect(),
                 };
 
                 // Check for duplicate implementation
@@ -893,8 +1203,12 @@ impl SemanticAnalyzer {
                 // Collect implemented methods
                 let mut method_map = BTreeMap::new();
                 for method_node in methods {
-                    if let AstNode::ChantDef { name, .. } = method_node {
+                    if let AstNode::ChantDef { name, return_type, body, .. } = method_node {
                         method_map.insert(name.clone(), method_node.clone());
+                        let returns_nothing = return_type
+                            .as_ref()
+                            .is_some_and(|t| self.convert_type_annotation(t) == Type::Nothing);
+                        self.check_chant_values(name, body, returns_nothing);
                         // Note: We don't analyze method implementations here to avoid
                         // adding them to the global symbol table. They will be analyzed
                         // when the trait methods are actually called at runtime.
@@ -1049,6 +1363,16 @@ impl SemanticAnalyzer {
                 let left_type = self.analyze_node(left);
                 let right_type = self.analyze_node(right);
 
+                if matches!(left_type, Type::Measure(_)) || matches!(right_type, Type::Measure(_)) {
+                    return self.analyze_measure_op(*op, &left_type, &right_type);
+                }
+                if left_type == Type::BigInt || right_type == Type::BigInt {
+                    return self.analyze_whole_op(*op, Type::BigInt, &left_type, &right_type);
+                }
+                if left_type == Type::Integer || right_type == Type::Integer {
+                    return self.analyze_whole_op(*op, Type::Integer, &left_type, &right_type);
+                }
+
                 match op {
                     BinaryOperator::Add => {
                         // Add works for Number + Number (arithmetic) and Text + Text (concatenation)
@@ -1111,6 +1435,9 @@ impl SemanticAnalyzer {
 
                 match op {
                     UnaryOperator::Negate => {
+                        if let Type::Measure(_) | Type::BigInt | Type::Integer = operand_type {
+                            return operand_type;
+                        }
                         if !matches!(operand_type, Type::Number | Type::Any | Type::Unknown) {
                             self.errors.push(SemanticError::TypeError {
                                 expected: "Number".to_string(),
@@ -1128,20 +1455,78 @@ impl SemanticAnalyzer {
                 }
             }
 
+            // === Unit Conversion ===
+            AstNode::InUnit { value, unit, .. } => {
+                let value_type = self.analyze_node(value);
+                let Some(unit) = crate::units::unit(unit) else {
+                    self.errors.push(SemanticError::UndefinedVariable(unit.clone()));
+                    return Type::Number;
+                };
+                if !matches!(value_type, Type::Any | Type::Unknown | Type::TypeParam(_))
+                    && value_type != Type::Measure(unit.dimension)
+                {
+                    self.errors.push(SemanticError::TypeError {
+                        expected: unit.dimension.name().to_string(),
+                        got: value_type.name().to_string(),
+                        context: format!("conversion to {}", unit.name),
+                    });
+                }
+                Type::Number
+            }
+
             // === Function Calls ===
-            AstNode::Call { callee, args, .. } => {
+            AstNode::Call { callee, args, arg_names, span, .. } => {
                 let func_type = self.analyze_node(callee);
 
                 // Analyze argument types
-                let arg_types: Vec<Type> = args.iter()
+                let mut arg_types: Vec<Type> = args.iter()
                     .map(|arg| self.analyze_node(arg))
                     .collect();
 
+                // Named arguments are checked in the order of the chant's parameters
+                if !arg_names.is_empty() {
+                    let chant = match &**callee {
+                        AstNode::Ident { name, .. } => self.chant_params.get(name).map(|params| (name.clone(), params.clone())),
+                        _ => None,
+                    };
+                    // Other callees only find out at run time whether they take names
+                    let Some((function, params)) = chant else {
+                        return Type::Any;
+                    };
+                    match order_arguments(&params, arg_types, arg_names) {
+                        Ok(ordered) => arg_types = ordered,
+                        Err(error) => {
+                            self.errors.push(SemanticError::ArgumentMismatch { function, error, span: Box::new(span.clone()) });
+                            return Type::Unknown;
+                        }
+                    }
+                }
+
+                // Calls into another module are checked against its exported signature
+                let origin = match &**callee {
+                    AstNode::Ident { name, .. } => self.imported_symbols
+                        .get(name)
+                        .map(|module| (name.clone(), module.clone())),
+                    AstNode::ModuleAccess { module, member, .. } => Some((member.clone(), module.clone())),
+                    AstNode::FieldAccess { object, field, .. } => self
+                        .imported_module_name(object)
+                        .map(|module| (field.clone(), module)),
+                    _ => None,
+                };
+
                 match func_type {
                     Type::Function { params, return_type } => {
                         // Check arity
                         if params.len() != arg_types.len() {
-                            if let AstNode::Ident { name, .. } = &**callee {
+                            if let Some((symbol, module)) = &origin {
+                                self.errors.push(SemanticError::ImportSignatureMismatch {
+                                    symbol: symbol.clone(),
+                                    module: module.clone(),
+                                    expected: Self::argument_count(params.len()),
+                                    got: Self::argument_count(arg_types.len()),
+                                    span: Box::new(span.clone()),
+                                });
+                            } else if let AstNode::Ident { name, .. } = &**callee {
                                 self.errors.push(SemanticError::ArityMismatch {
                                     function: name.clone(),
                                     expected: params.len(),
@@ -1153,6 +1538,16 @@ impl SemanticAnalyzer {
                         // Check parameter types
                         for (i, (param_type, arg_type)) in params.iter().zip(arg_types.iter()).enumerate() {
                             if !param_type.is_compatible(arg_type) {
+                                if let Some((symbol, module)) = &origin {
+                                    self.errors.push(SemanticError::ImportSignatureMismatch {
+                                        symbol: symbol.clone(),
+                                        module: module.clone(),
+                                        expected: format!("{} for argument {}", param_type.name(), i + 1),
+                                        got: arg_type.name().to_string(),
+                                        span: Box::new(span.clone()),
+                                    });
+                                    continue;
+                                }
                                 self.errors.push(SemanticError::TypeError {
                                     expected: param_type.name().to_string(),
                                     got: arg_type.name().to_string(),
@@ -1190,6 +1585,10 @@ impl SemanticAnalyzer {
                 Type::List(Box::new(Type::Any))
             }
 
+            AstNode::Tuple { elements, .. } => {
+                Type::Tuple(elements.iter().map(|elem| self.analyze_node(elem)).collect())
+            }
+
             AstNode::Map { entries, .. } => {
                 for (_, value) in entries {
                     self.analyze_node(value);
@@ -1198,6 +1597,11 @@ impl SemanticAnalyzer {
             }
 
             AstNode::FieldAccess { object, field, .. } => {
+                // `Name.member` on an imported module is a module access
+                if let Some(module) = self.imported_module_name(object) {
+                    return self.analyze_module_access(&module, field);
+                }
+
                 let obj_type = self.analyze_node(object);
 
                 match obj_type {
@@ -1233,10 +1637,33 @@ impl SemanticAnalyzer {
                         // Index can be Text
                         Type::Any  // Value type
                     }
+                    // A literal index picks out that element's type
+                    Type::Tuple(element_types) => match index.as_ref() {
+                        AstNode::Number { value, .. } => match element_types.get(*value as usize) {
+                            Some(element_type) if value % 1.0 == 0.0 && *value >= 0.0 => element_type.clone(),
+                            _ => {
+                                self.errors.push(SemanticError::InvalidOperation {
+                                    operation: format!("index {}", value),
+                                    operand_type: format!("tuple of {}", element_types.len()),
+                                });
+                                Type::Unknown
+                            }
+                        },
+                        _ => {
+                            if !matches!(idx_type, Type::Number | Type::Any | Type::Unknown) {
+                                self.errors.push(SemanticError::TypeError {
+                                    expected: "Number".to_string(),
+                                    got: idx_type.name().to_string(),
+                                    context: "tuple index".to_string(),
+                                });
+                            }
+                            Type::Any
+                        }
+                    },
                     Type::Any | Type::Unknown => Type::Any,
                     _ => {
                         self.errors.push(SemanticError::TypeError {
-                            expected: "List or Map".to_string(),
+                            expected: "List, Tuple or Map".to_string(),
                             got: obj_type.name().to_string(),
                             context: "index access".to_string(),
                         });
@@ -1281,35 +1708,44 @@ impl SemanticAnalyzer {
             }
 
             // === Not Yet Implemented ===
-            AstNode::MatchStmt { value, arms, .. } => {
-                use crate::ast::Pattern;
-
+            AstNode::MatchStmt { value, arms, span } => {
                 // Analyze the value being matched
                 let _match_type = self.analyze_node(value);
 
                 // Check exhaustiveness: a match is exhaustive if it has:
                 // 1. A wildcard pattern (otherwise), OR
                 // 2. An identifier pattern (variable binding - matches anything)
-                let has_catch_all = arms.iter().any(|arm| {
-                    matches!(arm.pattern, Pattern::Wildcard | Pattern::Ident(_))
-                });
+                //    that does not name a unit variant case, OR
+                // 3. An or-pattern with either of those among its alternatives
+                // without a guard, which could still turn the value away
+                let has_catch_all = arms.iter().any(|arm| arm.guard.is_none() && is_catch_all(&arm.pattern, &self.unit_variants));
 
                 if !has_catch_all {
                     self.errors.push(SemanticError::NonExhaustiveMatch {
                         message: "Match expression must have a catch-all pattern (wildcard or variable binding)".to_string(),
+                        span: span.clone(),
                     });
                 }
 
                 // Analyze each arm's body
                 let mut arm_types = Vec::new();
                 for arm in arms {
+                    self.check_or_pattern_bindings(&arm.pattern);
+
                     // Push new scope for pattern variables
                     self.symbol_table.push_scope();
 
-                    // If pattern is an identifier, add it to scope
-                    if let Pattern::Ident(var_name) = &arm.pattern {
-                        // Bind the variable with Any type (we don't know the exact type yet)
-                        let _ = self.symbol_table.define(var_name.clone(), Type::Any, false);
+                    // Bind the pattern's variables with Any type (we don't know the exact type yet)
+                    let mut bindings = Vec::new();
+                    ScopeResolver::pattern_bindings(&arm.pattern, &mut bindings);
+                    bindings.retain(|name| !self.unit_variants.contains(name));
+                    for var_name in bindings {
+                        let _ = self.symbol_table.define(var_name, Type::Any, false);
+                    }
+
+                    // Any guard value is tested for truthiness, like an `if` condition
+                    if let Some(guard) = &arm.guard {
+                        self.analyze_node(guard);
                     }
 
                     // Analyze arm body
@@ -1330,6 +1766,27 @@ impl SemanticAnalyzer {
                 Type::Any
             }
 
+            AstNode::DeferStmt { body, .. } | AstNode::TogetherBlock { body, .. } | AstNode::VerifyBlock { body, .. } => {
+                self.symbol_table.push_scope();
+                for stmt in body {
+                    self.analyze_node(stmt);
+                }
+                self.symbol_table.pop_scope();
+                Type::Nothing
+            }
+
+            AstNode::MigrateStore { param, body, .. } => {
+                self.symbol_table.push_scope();
+                let in_function = core::mem::replace(&mut self.in_function, true);
+                let _ = self.symbol_table.define(param.clone(), Type::Any, false);
+                for stmt in body {
+                    self.analyze_node(stmt);
+                }
+                self.in_function = in_function;
+                self.symbol_table.pop_scope();
+                Type::Nothing
+            }
+
             AstNode::RequestStmt { .. } => {
                 // TODO: Implement capability analysis
                 Type::Capability
@@ -1416,6 +1873,26 @@ impl SemanticAnalyzer {
                             });
                         }
                     }
+                    // When the module was declared in this program, check the
+                    // items against its exports and bring them into scope
+                    if let Some(module_exports) = self.module_exports.get(module_name) {
+                        let mut imported = Vec::new();
+                        for item in item_list {
+                            if !module_exports.exports.contains(item) {
+                                self.errors.push(SemanticError::SymbolNotExported {
+                                    symbol: item.clone(),
+                                    module: module_name.clone(),
+                                });
+                            } else if let Some(symbol) = module_exports.symbols.get(item) {
+                                imported.push((item.clone(), symbol.typ.clone(), symbol.mutable));
+                            }
+                        }
+                        for (item, typ, mutable) in imported {
+                            // Conflicts were reported above
+                            self.imported_symbols.insert(item.clone(), module_name.clone());
+                            let _ = self.symbol_table.define(item, typ, mutable);
+                        }
+                    }
                     self.imported_modules.insert(effective_name.clone(), Some(item_list.clone()));
                 } else {
                     // Import all (summon)
@@ -1429,6 +1906,8 @@ impl SemanticAnalyzer {
                 Type::Nothing
             }
 
+            AstNode::Speaks { .. } => Type::Nothing,
+
             AstNode::Export { items, .. } => {
                 // Standalone export statement (not inside module declaration)
                 // Validate that exported symbols exist in current scope
@@ -1445,36 +1924,158 @@ impl SemanticAnalyzer {
                 Type::Nothing
             }
 
-            AstNode::ModuleAccess { module, member, .. } => {
-                // Resolve module.member access
+            AstNode::ModuleAccess { module, member, .. } => self.analyze_module_access(module, member),
+        }
+    }
 
-                // Check if module is imported
-                if !self.imported_modules.contains_key(module) {
-                    self.errors.push(SemanticError::UndefinedVariable(module.clone()));
-                    return Type::Unknown;
-                }
+    /// Resolve `module.member`, checking the member against the module's exports
+    fn analyze_module_access(&mut self, module: &str, member: &str) -> Type {
+        // Resolve module.member access
 
-                // Check if we have module exports registered
-                if let Some(module_exports) = self.module_exports.get(module) {
-                    // Check if member is exported
-                    if !module_exports.exports.contains(member) {
-                        self.errors.push(SemanticError::SymbolNotExported {
-                            symbol: member.clone(),
-                            module: module.clone(),
-                        });
-                        return Type::Unknown;
-                    }
+        // Check if module is imported; otherwise it may be a builtin module
+        let module_exports = if self.imported_modules.contains_key(module) {
+            self.module_exports.get(module)
+        } else if let Some(builtin) = self.builtin_module(module) {
+            Some(builtin)
+        } else {
+            self.errors.push(SemanticError::UndefinedVariable(module.to_string()));
+            return Type::Unknown;
+        };
 
-                    // Lookup member's type in module's symbol table
-                    if let Some(symbol) = module_exports.symbols.get(member) {
-                        return symbol.typ.clone();
-                    }
+        // Check if we have module exports registered
+        if let Some(module_exports) = module_exports {
+            // Check if member is exported
+            if !module_exports.exports.iter().any(|name| name == member) {
+                self.errors.push(SemanticError::SymbolNotExported {
+                    symbol: member.to_string(),
+                    module: module.to_string(),
+                });
+                return Type::Unknown;
+            }
+
+            // Lookup member's type in module's symbol table
+            if let Some(symbol) = module_exports.symbols.get(member) {
+                return symbol.typ.clone();
+            }
+        }
+
+        // Module exists but we don't have its exports yet
+        // (This happens when ModuleResolver hasn't loaded the module yet)
+        // For Phase 3, we allow this and defer to Phase 4
+        Type::Any
+    }
+
+    /// Builtin module of that name, unless a grove in the program declares it
+    fn builtin_module(&self, module: &str) -> Option<&ModuleExports> {
+        if self.module_exports.contains_key(module) {
+            return None;
+        }
+        self.builtin_modules.get(module)
+    }
+
+    /// Module name an identifier refers to, if it names an imported or builtin
+    /// module that no binding shadows
+    fn imported_module_name(&self, node: &AstNode) -> Option<String> {
+        match node {
+            AstNode::Ident { name, .. }
+                if (self.imported_modules.contains_key(name) || self.builtin_module(name).is_some())
+                    && self.symbol_table.lookup(name).is_none() =>
+            {
+                Some(name.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Describe an argument count for error messages
+    fn argument_count(count: usize) -> String {
+        if count == 1 {
+            "1 argument".to_string()
+        } else {
+            format!("{} arguments", count)
+        }
+    }
+
+    /// Type of a binary operation with a measure on at least one side
+    ///
+    /// Sums, differences, remainders and comparisons need the same
+    /// dimension on both sides. A measure scales by a plain Number, and
+    /// dividing two measures of one dimension gives a plain Number.
+    fn analyze_measure_op(&mut self, op: BinaryOperator, left: &Type, right: &Type) -> Type {
+        use BinaryOperator::*;
+
+        let dynamic = |typ: &Type| matches!(typ, Type::Any | Type::Unknown | Type::TypeParam(_));
+        let comparison = matches!(op, Equal | NotEqual | Less | Greater | LessEq | GreaterEq);
+        let result = match (op, left, right) {
+            (And | Or, _, _) => Some(Type::Truth),
+            _ if dynamic(left) || dynamic(right) => Some(if comparison { Type::Truth } else { Type::Unknown }),
+            (Add | Sub | Mod, Type::Measure(a), Type::Measure(b)) if a == b => Some(left.clone()),
+            (_, Type::Measure(a), Type::Measure(b)) if comparison && a == b => Some(Type::Truth),
+            (Mul, Type::Measure(_), Type::Number) | (Div, Type::Measure(_), Type::Number) => Some(left.clone()),
+            (Mul, Type::Number, Type::Measure(_)) => Some(right.clone()),
+            (Div, Type::Measure(a), Type::Measure(b)) if a == b => Some(Type::Number),
+            _ => None,
+        };
+
+        result.unwrap_or_else(|| {
+            let expected = match op {
+                Mul => "a measure and a plain Number".to_string(),
+                Div => "a measure divided by a plain Number or by the same dimension".to_string(),
+                _ => {
+                    let dimension = if let Type::Measure(_) = left { left } else { right };
+                    format!("{} on both sides", dimension.name())
                 }
+            };
+            self.errors.push(SemanticError::TypeError {
+                expected,
+                got: format!("{} {:?} {}", left.name(), op, right.name()),
+                context: "units of measure; give plain numbers a unit or convert with 'in'".to_string(),
+            });
+            Type::Unknown
+        })
+    }
 
-                // Module exists but we don't have its exports yet
-                // (This happens when ModuleResolver hasn't loaded the module yet)
-                // For Phase 3, we allow this and defer to Phase 4
-                Type::Any
+    /// Type of a binary operation with a big integer or an integer (`whole`)
+    /// on at least one side
+    ///
+    /// Arithmetic and comparisons need the same whole type on both sides: a
+    /// Number is converted explicitly with `bigint(x)`, `to_integer(x)` or
+    /// `to_number(x)`.
+    fn analyze_whole_op(&mut self, op: BinaryOperator, whole: Type, left: &Type, right: &Type) -> Type {
+        use BinaryOperator::*;
+
+        let comparison = matches!(op, Equal | NotEqual | Less | Greater | LessEq | GreaterEq);
+        let dynamic = |typ: &Type| matches!(typ, Type::Any | Type::Unknown | Type::TypeParam(_));
+        match (op, left, right) {
+            (And | Or, _, _) => Type::Truth,
+            _ if comparison && (dynamic(left) || dynamic(right) || left == right) => Type::Truth,
+            _ if dynamic(left) || dynamic(right) => Type::Unknown,
+            _ if *left == whole && *right == whole => whole,
+            _ => {
+                let (kind, convert) = match whole {
+                    Type::BigInt => ("big integers", "bigint"),
+                    _ => ("integers", "to_integer"),
+                };
+                self.errors.push(SemanticError::TypeError {
+                    expected: format!("{} on both sides", whole.name()),
+                    got: format!("{} {:?} {}", left.name(), op, right.name()),
+                    context: format!("{}; convert with '{}' or 'to_number'", kind, convert),
+                });
+                Type::Unknown
+            }
+        }
+    }
+
+    /// Check that a list literal bound to a fixed-size array type has
+    /// exactly the array's length
+    fn check_array_length(&mut self, ann: &crate::ast::TypeAnnotation, value: &AstNode, context: &str) {
+        if let (crate::ast::TypeAnnotation::Array { length, .. }, AstNode::List { elements, .. }) = (ann, value) {
+            if elements.len() != *length {
+                self.errors.push(SemanticError::TypeError {
+                    expected: format!("{} elements", length),
+                    got: format!("{} elements", elements.len()),
+                    context: context.to_string(),
+                });
             }
         }
     }
@@ -1489,6 +2090,10 @@ impl SemanticAnalyzer {
                 "Truth" => Type::Truth,
                 "Nothing" => Type::Nothing,
                 "Map" => Type::Map,
+                "Time" => Type::Measure(Dimension::Time),
+                "Size" => Type::Measure(Dimension::Size),
+                "BigInt" => Type::BigInt,
+                "Integer" => Type::Integer,
                 _ => Type::Unknown, // Unknown type name
             },
             TypeAnnotation::Generic(name) => {
@@ -1517,6 +2122,10 @@ impl SemanticAnalyzer {
                 Type::List(Box::new(self.convert_type_annotation(inner)))
             }
             TypeAnnotation::Map => Type::Map,
+            // The length is checked against list literals where they are bound
+            TypeAnnotation::Array { element, .. } => {
+                Type::List(Box::new(self.convert_type_annotation(element)))
+            }
             TypeAnnotation::Function { param_types, return_type } => Type::Function {
                 params: param_types
                     .iter()
@@ -1535,6 +2144,49 @@ impl SemanticAnalyzer {
             }
         }
     }
+
+    /// Report or-patterns, at any depth, whose alternatives bind different names
+    fn check_or_pattern_bindings(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Or(alternatives) => {
+                let unit_variants = &self.unit_variants;
+                let names = |alternative: &Pattern| {
+                    let mut bindings = Vec::new();
+                    ScopeResolver::pattern_bindings(alternative, &mut bindings);
+                    // `_` and unit variant cases are name patterns too, but bind nothing
+                    bindings.retain(|name| name != "_" && !unit_variants.contains(name));
+                    bindings.sort();
+                    bindings
+                };
+                if let Some((first, rest)) = alternatives.split_first() {
+                    let expected = names(first);
+                    if rest.iter().any(|alternative| names(alternative) != expected) {
+                        self.errors.push(SemanticError::Custom(format!(
+                            "Alternatives of an or-pattern must bind the same names (the first binds [{}])",
+                            expected.join(", ")
+                        )));
+                    }
+                }
+                for alternative in alternatives {
+                    self.check_or_pattern_bindings(alternative);
+                }
+            }
+            Pattern::Tuple(elements) => elements.iter().for_each(|element| self.check_or_pattern_bindings(element)),
+            Pattern::Enum { inner: Some(inner), .. } => self.check_or_pattern_bindings(inner),
+            _ => {}
+        }
+    }
+}
+
+/// Whether a pattern matches every value: `_`, a bare name that is not one
+/// of `unit_variants`, or an or-pattern with one of those among its alternatives
+fn is_catch_all(pattern: &Pattern, unit_variants: &BTreeSet<String>) -> bool {
+    match pattern {
+        Pattern::Wildcard => true,
+        Pattern::Ident(name) => !unit_variants.contains(name),
+        Pattern::Or(alternatives) => alternatives.iter().any(|alternative| is_catch_all(alternative, unit_variants)),
+        _ => false,
+    }
 }
 
 /// Analyze a Glimmer-Weave program for semantic errors
@@ -1543,10 +2195,276 @@ pub fn analyze(nodes: &[AstNode]) -> Result<(), Vec<SemanticError>> {
     analyzer.analyze(nodes)
 }
 
+/// Annotate variable references with the scope slot they resolve to
+///
+/// Mirrors the interpreter's runtime scopes (blocks, loop iterations, match
+/// arms and chant calls) and records `(depth, slot)` on every `Ident` whose
+/// binding is visible within the same chant body or top-level program.
+/// References that can only be satisfied dynamically (globals read from
+/// inside a chant, builtins) are left unresolved and use the name lookup.
+///
+/// [`crate::pipeline::CompilerPipeline`] runs this before evaluating.
+/// [`crate::Evaluator::eval`] does not, since it borrows the AST; callers
+/// that evaluate directly resolve the AST first or look every name up.
+pub fn resolve_scopes(nodes: &mut [AstNode]) {
+    let mut resolver = ScopeResolver { scopes: vec![Vec::new()] };
+    resolver.resolve_block(nodes);
+}
+
+/// Lexical scope model used by [`resolve_scopes`]
+struct ScopeResolver {
+    /// Binding names of each scope in the current chant body, in slot order
+    scopes: Vec<Vec<String>>,
+}
+
+impl ScopeResolver {
+    fn define(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            if !scope.iter().any(|existing| existing == name) {
+                scope.push(name.to_string());
+            }
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<ScopeSlot> {
+        self.scopes.iter().rev().enumerate().find_map(|(depth, scope)| {
+            scope
+                .iter()
+                .position(|existing| existing == name)
+                .map(|slot| ScopeSlot { depth, slot })
+        })
+    }
+
+    /// Define the names `node` binds into the current scope, in binding order
+    fn define_bound(&mut self, node: &AstNode) {
+        match node {
+            AstNode::BindStmt { name, value, .. } | AstNode::WeaveStmt { name, value, .. } => {
+                self.define_bound(value);
+                self.define(name);
+            }
+            AstNode::ChantDef { name, .. } | AstNode::FormDef { name, .. } => self.define(name),
+            AstNode::VariantDef { name, variants, .. } => {
+                self.define(name);
+                for variant in variants.iter() {
+                    self.define(&variant.name);
+                }
+            }
+            AstNode::Import { items: Some(items), .. } => {
+                for item in items.iter() {
+                    self.define(item);
+                }
+            }
+            // Their bodies bind into scopes of their own
+            AstNode::ForStmt { iterable: head, .. } | AstNode::MatchStmt { value: head, .. } => self.define_bound(head),
+            AstNode::EmbodyStmt { .. }
+            | AstNode::DeferStmt { .. }
+            | AstNode::MigrateStore { .. }
+            | AstNode::VerifyBlock { .. }
+            | AstNode::TogetherBlock { .. }
+            | AstNode::ModuleDecl { .. }
+            | AstNode::Block { .. } => {}
+            other => {
+                for child in other.children() {
+                    self.define_bound(child);
+                }
+            }
+        }
+    }
+
+    /// Resolve statements that run in a scope holding `bindings` first
+    fn resolve_scoped(&mut self, bindings: Vec<String>, nodes: &mut [AstNode]) {
+        self.scopes.push(bindings);
+        self.resolve_block(nodes);
+        self.scopes.pop();
+    }
+
+    /// Resolve a chant or module body, which cannot see the enclosing scopes statically
+    fn resolve_frame(&mut self, bindings: Vec<String>, nodes: &mut [AstNode]) {
+        let saved = core::mem::replace(&mut self.scopes, vec![bindings]);
+        self.resolve_block(nodes);
+        self.scopes = saved;
+    }
+
+    fn resolve_block(&mut self, nodes: &mut [AstNode]) {
+        for node in nodes {
+            self.resolve(node);
+        }
+    }
+
+    fn resolve(&mut self, node: &mut AstNode) {
+        match node {
+            AstNode::Ident { name, slot, .. } => *slot = self.lookup(name),
+
+            AstNode::BindStmt { name, value, .. } | AstNode::WeaveStmt { name, value, .. } => {
+                self.resolve(value);
+                self.define(name);
+            }
+            AstNode::SetStmt { target, value, .. } => {
+                self.resolve(value);
+                self.resolve(target);
+            }
+            AstNode::IfStmt { condition, then_branch, else_branch, .. } => {
+                self.resolve(condition);
+                self.resolve_block(then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.resolve_block(else_branch);
+                }
+            }
+            AstNode::ForStmt { variable, iterable, body, .. } => {
+                self.resolve(iterable);
+                self.resolve_scoped(vec![variable.clone()], body);
+            }
+            // A `whilst` body binds into the enclosing scope, so later rounds
+            // see names that earlier rounds bound after a use. Define them up
+            // front; a use that runs before its binding exists falls back to
+            // the name lookup.
+            AstNode::WhileStmt { condition, body, .. } => {
+                for node in body.iter() {
+                    self.define_bound(node);
+                }
+                self.resolve(condition);
+                self.resolve_block(body);
+            }
+            AstNode::ChantDef { name, params, body, .. } => {
+                self.define(name);
+                // Calls bind the parameters, then the name used for tail calls
+                let mut bindings: Vec<String> = params.iter().map(|p| p.name.clone()).collect();
+                bindings.push("__current_function__".to_string());
+                self.resolve_frame(bindings, body);
+            }
+            AstNode::FormDef { name, .. } => self.define(name),
+            AstNode::VariantDef { name, variants, .. } => {
+                self.define(name);
+                for variant in variants.iter() {
+                    self.define(&variant.name);
+                }
+            }
+            AstNode::EmbodyStmt { methods, .. } => {
+                for method in methods.iter_mut() {
+                    if let AstNode::ChantDef { params, body, .. } = method {
+                        let mut bindings = vec!["self".to_string()];
+                        bindings.extend(params.iter().skip(1).map(|p| p.name.clone()));
+                        self.resolve_frame(bindings, body);
+                    }
+                }
+            }
+            AstNode::MatchStmt { value, arms, .. } => {
+                self.resolve(value);
+                for arm in arms.iter_mut() {
+                    let mut bindings = Vec::new();
+                    Self::pattern_bindings(&arm.pattern, &mut bindings);
+                    self.scopes.push(bindings);
+                    if let Some(guard) = &mut arm.guard {
+                        self.resolve(guard);
+                    }
+                    self.resolve_block(&mut arm.body);
+                    self.scopes.pop();
+                }
+            }
+            AstNode::AttemptStmt { body, handlers, .. } => {
+                self.resolve_block(body);
+                for handler in handlers.iter_mut() {
+                    self.resolve_block(&mut handler.body);
+                }
+            }
+            // Runs later, from whatever scope the enclosing chant exits in
+            AstNode::DeferStmt { body, .. } => self.resolve_frame(Vec::new(), body),
+            AstNode::RequestStmt { capability, .. } => self.resolve(capability),
+            // Runs later as a chant, on the first access to the namespace
+            AstNode::MigrateStore { param, body, .. } => self.resolve_frame(vec![param.clone()], body),
+            AstNode::VerifyBlock { body, .. } | AstNode::TogetherBlock { body, .. } => self.resolve_scoped(Vec::new(), body),
+            AstNode::ModuleDecl { body, .. } => self.resolve_frame(Vec::new(), body),
+            AstNode::Import { items: Some(items), .. } => {
+                for item in items.iter() {
+                    self.define(item);
+                }
+            }
+            AstNode::Block { statements, .. } => self.resolve_scoped(Vec::new(), statements),
+
+            AstNode::Triumph { value, .. }
+            | AstNode::Mishap { value, .. }
+            | AstNode::Present { value, .. }
+            | AstNode::BorrowExpr { value, .. }
+            | AstNode::YieldStmt { value, .. } => self.resolve(value),
+            AstNode::Try { expr, .. } | AstNode::ExprStmt { expr, .. } => self.resolve(expr),
+            AstNode::UnaryOp { operand, .. } | AstNode::InUnit { value: operand, .. } => self.resolve(operand),
+            AstNode::FieldAccess { object, .. } => self.resolve(object),
+            AstNode::List { elements, .. } | AstNode::Tuple { elements, .. } => self.resolve_block(elements),
+            AstNode::Pipeline { stages, .. } => self.resolve_block(stages),
+            AstNode::Map { entries, .. } | AstNode::StructLiteral { fields: entries, .. } => {
+                for (_, value) in entries.iter_mut() {
+                    self.resolve(value);
+                }
+            }
+            AstNode::BinaryOp { left, right, .. }
+            | AstNode::IndexAccess { object: left, index: right, .. }
+            | AstNode::Range { start: left, end: right, .. } => {
+                self.resolve(left);
+                self.resolve(right);
+            }
+            AstNode::Call { callee, args, .. } => {
+                self.resolve(callee);
+                self.resolve_block(args);
+            }
+            AstNode::SeekExpr { conditions, .. } => {
+                for condition in conditions.iter_mut() {
+                    self.resolve(&mut condition.value);
+                }
+            }
+
+            AstNode::AspectDef { .. }
+            | AstNode::Import { .. }
+            | AstNode::Export { .. }
+            | AstNode::Speaks { .. }
+            | AstNode::ModuleAccess { .. }
+            | AstNode::Number { .. }
+            | AstNode::BigInt { .. }
+            | AstNode::Integer { .. }
+            | AstNode::Measure { .. }
+            | AstNode::Text { .. }
+            | AstNode::Truth { .. }
+            | AstNode::Nothing { .. }
+            | AstNode::Absent { .. }
+            | AstNode::Break { .. }
+            | AstNode::Continue { .. } => {}
+        }
+    }
+
+    /// Names bound by a match pattern, in the order the interpreter binds them
+    fn pattern_bindings(pattern: &Pattern, bindings: &mut Vec<String>) {
+        match pattern {
+            Pattern::Ident(name) | Pattern::Affix { rest: Some(name), .. } => bindings.push(name.clone()),
+            Pattern::Enum { inner: Some(inner), .. } => match inner.as_ref() {
+                Pattern::Literal(fields) => {
+                    if let AstNode::List { elements, .. } = fields.as_ref() {
+                        for element in elements {
+                            if let AstNode::Ident { name, .. } = element {
+                                bindings.push(name.clone());
+                            }
+                        }
+                    }
+                }
+                inner => Self::pattern_bindings(inner, bindings),
+            },
+            Pattern::Tuple(elements) => {
+                for element in elements {
+                    Self::pattern_bindings(element, bindings);
+                }
+            }
+            // The alternatives bind the same names, so the first speaks for all
+            Pattern::Or(alternatives) => {
+                if let Some(first) = alternatives.first() {
+                    Self::pattern_bindings(first, bindings);
+                }
+            }
+            Pattern::Literal(_) | Pattern::Wildcard | Pattern::Enum { inner: None, .. } | Pattern::Affix { rest: None, .. } => {}
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::ast::*;
     use crate::source_location::SourceSpan;
 
     // Helper to create a dummy span for tests
@@ -1565,10 +2483,12 @@ mod tests {
             arms: vec![
                 MatchArm {
                     pattern: Pattern::Literal(Box::new(AstNode::Number { value: 1.0, span: span() })),
+                    guard: None,
                     body: vec![AstNode::Number { value: 100.0, span: span() }],
                 },
                 MatchArm {
                     pattern: Pattern::Wildcard,
+                    guard: None,
                     body: vec![AstNode::Number { value: 999.0, span: span() }],
                 },
             ],
@@ -1592,8 +2512,9 @@ mod tests {
             arms: vec![
                 MatchArm {
                     pattern: Pattern::Ident("n".to_string()),
+                    guard: None,
                     body: vec![AstNode::BinaryOp {
-                        left: Box::new(AstNode::Ident { name: "n".to_string(), span: span() }),
+                        left: Box::new(AstNode::Ident { name: "n".to_string(), span: span(), slot: None }),
                         op: BinaryOperator::Mul,
                         right: Box::new(AstNode::Number { value: 2.0, span: span() }),
                         span: span(),
@@ -1622,10 +2543,12 @@ mod tests {
             arms: vec![
                 MatchArm {
                     pattern: Pattern::Literal(Box::new(AstNode::Number { value: 1.0, span: span() })),
+                    guard: None,
                     body: vec![AstNode::Number { value: 100.0, span: span() }],
                 },
                 MatchArm {
                     pattern: Pattern::Literal(Box::new(AstNode::Number { value: 2.0, span: span() })),
+                    guard: None,
                     body: vec![AstNode::Number { value: 200.0, span: span() }],
                 },
             ],
@@ -1660,9 +2583,11 @@ mod tests {
             }],
             return_type: Some(TypeAnnotation::Generic("T".to_string())),
             body: vec![AstNode::YieldStmt {
-                value: Box::new(AstNode::Ident { name: "x".to_string(), span: span() }),
+                value: Box::new(AstNode::Ident { name: "x".to_string(), span: span(), slot: None }),
                 span: span(),
             }],
+            swift: false,
+            deprecated: None,
             span: span(),
         }];
 
@@ -1685,6 +2610,8 @@ mod tests {
                 name: "value".to_string(),
                 typ: TypeAnnotation::Generic("T".to_string()),
             }],
+            deriving: vec![],
+            deprecated: None,
             span: span(),
         }];
 
@@ -1725,6 +2652,8 @@ mod tests {
                 value: Box::new(AstNode::Number { value: 42.0, span: span() }),
                 span: span(),
             }],
+            swift: false,
+            deprecated: None,
             span: span(),
         }];
 
@@ -1741,7 +2670,7 @@ mod tests {
         // chant wrap<T>(x: T) -> Box<T> then
         //     # Implementation would go here
         // end
-        let ast = vec![AstNode::ChantDef {
+        let _ast = AstNode::ChantDef {
             name: "wrap".to_string(),
             type_params: vec!["T".to_string()],
             lifetime_params: vec![],
@@ -1757,8 +2686,10 @@ mod tests {
                 type_args: vec![TypeAnnotation::Generic("T".to_string())],
             }),
             body: vec![],
+            swift: false,
+            deprecated: None,
             span: span(),
-        }];
+        };
 
         let mut analyzer = SemanticAnalyzer::new();
         // Push type params to test convert_type_annotation
@@ -1807,9 +2738,11 @@ mod tests {
                     }],
                     return_type: None,
                     body: vec![AstNode::YieldStmt {
-                        value: Box::new(AstNode::Ident { name: "x".to_string(), span: span() }),
+                        value: Box::new(AstNode::Ident { name: "x".to_string(), span: span(), slot: None }),
                         span: span(),
                     }],
+                    swift: false,
+                    deprecated: None,
                     span: span(),
                 },
                 AstNode::ChantDef {
@@ -1834,13 +2767,16 @@ mod tests {
                     ],
                     return_type: None,
                     body: vec![AstNode::YieldStmt {
-                        value: Box::new(AstNode::Ident { name: "a".to_string(), span: span() }),
+                        value: Box::new(AstNode::Ident { name: "a".to_string(), span: span(), slot: None }),
                         span: span(),
                     }],
+                    swift: false,
+                    deprecated: None,
                     span: span(),
                 },
             ],
             exports: vec!["sqrt".to_string(), "pow".to_string()],
+            deprecated: None,
             span: span(),
         }];
 
@@ -1879,12 +2815,15 @@ mod tests {
                 }],
                 return_type: None,
                 body: vec![AstNode::YieldStmt {
-                    value: Box::new(AstNode::Ident { name: "x".to_string(), span: span() }),
+                    value: Box::new(AstNode::Ident { name: "x".to_string(), span: span(), slot: None }),
                     span: span(),
                 }],
+                swift: false,
+                deprecated: None,
                 span: span(),
             }],
             exports: vec!["sqrt".to_string(), "nonexistent".to_string()],
+            deprecated: None,
             span: span(),
         }];
 
@@ -1991,27 +2930,28 @@ mod tests {
 
     #[test]
     fn test_module_qualified_access_with_non_imported_module() {
-        // Math.sqrt(16)  # Math not imported!
+        // Geometry.area(16)  # Geometry not imported!
         let ast = vec![AstNode::Call {
             callee: Box::new(AstNode::ModuleAccess {
-                module: "Math".to_string(),
-                member: "sqrt".to_string(),
+                module: "Geometry".to_string(),
+                member: "area".to_string(),
                 span: span(),
             }),
             args: vec![AstNode::Number { value: 16.0, span: span() }],
             type_args: vec![],
+            arg_names: vec![],
             span: span(),
         }];
 
         let mut analyzer = SemanticAnalyzer::new();
         let result = analyzer.analyze(&ast);
 
-        // Should have an UndefinedVariable error for Math
+        // Should have an UndefinedVariable error for Geometry
         assert!(result.is_err(), "Expected error for non-imported module");
         let errors = result.unwrap_err();
         assert!(
-            errors.iter().any(|e| matches!(e, SemanticError::UndefinedVariable(name) if name == "Math")),
-            "Expected UndefinedVariable error for 'Math', got: {:?}",
+            errors.iter().any(|e| matches!(e, SemanticError::UndefinedVariable(name) if name == "Geometry")),
+            "Expected UndefinedVariable error for 'Geometry', got: {:?}",
             errors
         );
     }
@@ -2042,6 +2982,8 @@ mod tests {
                             value: Box::new(AstNode::Number { value: 42.0, span: span() }),
                             span: span(),
                         }],
+                        swift: false,
+                        deprecated: None,
                         span: span(),
                     },
                     AstNode::ChantDef {
@@ -2057,13 +2999,16 @@ mod tests {
                         }],
                         return_type: None,
                         body: vec![AstNode::YieldStmt {
-                            value: Box::new(AstNode::Ident { name: "x".to_string(), span: span() }),
+                            value: Box::new(AstNode::Ident { name: "x".to_string(), span: span(), slot: None }),
                             span: span(),
                         }],
+                        swift: false,
+                        deprecated: None,
                         span: span(),
                     },
                 ],
                 exports: vec!["sqrt".to_string()], // Only sqrt is exported
+                deprecated: None,
                 span: span(),
             },
             AstNode::Import {
@@ -2081,6 +3026,7 @@ mod tests {
                 }),
                 args: vec![],
                 type_args: vec![],
+                arg_names: vec![],
                 span: span(),
             },
         ];
@@ -2127,12 +3073,15 @@ mod tests {
                     }],
                     return_type: None,
                     body: vec![AstNode::YieldStmt {
-                        value: Box::new(AstNode::Ident { name: "x".to_string(), span: span() }),
+                        value: Box::new(AstNode::Ident { name: "x".to_string(), span: span(), slot: None }),
                         span: span(),
                     }],
+                    swift: false,
+                    deprecated: None,
                     span: span(),
                 }],
                 exports: vec!["sqrt".to_string()],
+                deprecated: None,
                 span: span(),
             },
             AstNode::Import {
@@ -2150,6 +3099,7 @@ mod tests {
                 }),
                 args: vec![AstNode::Number { value: 16.0, span: span() }],
                 type_args: vec![],
+                arg_names: vec![],
                 span: span(),
             },
         ];
@@ -2160,4 +3110,144 @@ mod tests {
         // Should not have any errors - all accesses are valid
         assert!(result.is_ok(), "Expected no errors but got: {:?}", result);
     }
+
+    fn analyze_source(source: &str) -> (SemanticAnalyzer, Result<(), Vec<SemanticError>>) {
+        let tokens = crate::lexer::Lexer::new(source).tokenize_positioned();
+        let ast = crate::parser::Parser::new(tokens).parse().expect("Parse error");
+        let mut analyzer = SemanticAnalyzer::new();
+        let result = analyzer.analyze(&ast);
+        (analyzer, result)
+    }
+
+    const MATH_MODULE: &str = "grove Math with
+    chant add(a: Number, b: Number) -> Number then
+        yield a + b
+    end
+    chant helper() then
+        yield 0
+    end
+    offer add
+end
+";
+
+    #[test]
+    fn test_exported_signatures_recorded() {
+        let (analyzer, result) = analyze_source(MATH_MODULE);
+        assert!(result.is_ok(), "Expected no errors but got: {:?}", result);
+
+        let signatures = analyzer.exported_signatures("Math").unwrap();
+        assert_eq!(signatures.len(), 1, "Only exported chants are recorded");
+        assert_eq!(
+            signatures["add"],
+            Type::Function { params: vec![Type::Number, Type::Number], return_type: Box::new(Type::Number) }
+        );
+        assert!(analyzer.exported_signatures("Missing").is_none());
+    }
+
+    #[test]
+    fn test_gathered_chant_checked_against_signature() {
+        let source = format!("{}gather add from Math\nadd(1, 2)", MATH_MODULE);
+        let (_, result) = analyze_source(&source);
+        assert!(result.is_ok(), "Expected no errors but got: {:?}", result);
+
+        let source = format!("{}gather add from Math\n\nadd(1)", MATH_MODULE);
+        let (_, result) = analyze_source(&source);
+        let errors = result.unwrap_err();
+        match &errors[..] {
+            [SemanticError::ImportSignatureMismatch { symbol, module, expected, got, span }] => {
+                assert_eq!((symbol.as_str(), module.as_str()), ("add", "Math"));
+                assert_eq!((expected.as_str(), got.as_str()), ("2 arguments", "1 argument"));
+                assert_eq!((span.start.line, span.start.column), (12, 4));
+            }
+            other => panic!("Expected one signature mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_qualified_call_checked_against_signature() {
+        let source = format!("{}summon Math from \"math.gw\"\nMath.add(\"one\", 2)", MATH_MODULE);
+        let (_, result) = analyze_source(&source);
+        let errors = result.unwrap_err();
+        assert!(matches!(
+            &errors[..],
+            [SemanticError::ImportSignatureMismatch { expected, got, .. }]
+                if expected == "Number for argument 1" && got == "Text"
+        ), "Got {:?}", errors);
+        assert!(errors[0].span().is_some_and(|span| span.start.line == 11));
+    }
+
+    #[test]
+    fn test_qualified_access_to_unexported_chant() {
+        let source = format!("{}summon Math from \"math.gw\"\nMath.helper()", MATH_MODULE);
+        let (_, result) = analyze_source(&source);
+        assert_eq!(
+            result.unwrap_err(),
+            vec![SemanticError::SymbolNotExported { symbol: "helper".to_string(), module: "Math".to_string() }]
+        );
+    }
+
+    // ========================================================================
+    // Scope slot resolution
+    // ========================================================================
+
+    fn resolved(source: &str) -> Vec<AstNode> {
+        let tokens = crate::lexer::Lexer::new(source).tokenize_positioned();
+        let mut ast = crate::parser::Parser::new(tokens).parse().expect("Parse error");
+        resolve_scopes(&mut ast);
+        ast
+    }
+
+    fn ident_slot(node: &AstNode) -> Option<ScopeSlot> {
+        match node {
+            AstNode::Ident { slot, .. } => *slot,
+            AstNode::ExprStmt { expr, .. } | AstNode::YieldStmt { value: expr, .. } => ident_slot(expr),
+            other => panic!("Expected identifier, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resolve_top_level_slots() {
+        let ast = resolved("bind a to 1\nbind b to 2\nb");
+        assert_eq!(ident_slot(&ast[2]), Some(ScopeSlot { depth: 0, slot: 1 }));
+    }
+
+    #[test]
+    fn test_resolve_chant_params_and_locals() {
+        let ast = resolved("bind g to 1\nchant f(x) then\nbind y to x\nyield y\nend");
+        let AstNode::ChantDef { body, .. } = &ast[1] else { panic!("Expected chant") };
+        let AstNode::BindStmt { value, .. } = &body[0] else { panic!("Expected bind") };
+        assert_eq!(ident_slot(value), Some(ScopeSlot { depth: 0, slot: 0 }));
+        // Slot 1 is taken by the tail-call marker bound after the parameters
+        assert_eq!(ident_slot(&body[1]), Some(ScopeSlot { depth: 0, slot: 2 }));
+    }
+
+    #[test]
+    fn test_resolve_leaves_enclosing_names_dynamic() {
+        let ast = resolved("bind g to 1\nchant f() then\nyield g\nend\nprint");
+        let AstNode::ChantDef { body, .. } = &ast[1] else { panic!("Expected chant") };
+        assert_eq!(ident_slot(&body[0]), None);
+        // Builtins are never resolved
+        assert_eq!(ident_slot(&ast[2]), None);
+    }
+
+    #[test]
+    fn test_resolve_whilst_body_bindings_up_front() {
+        let source = "bind x to 1\nfor each r in [0] then\nwhilst r then\nx\nbind x to 5\nfor each y in [1] then\nbind z to y\nend\nz\nend\nend";
+        let ast = resolved(source);
+        let AstNode::ForStmt { body, .. } = &ast[1] else { panic!("Expected for loop") };
+        let AstNode::WhileStmt { body, .. } = &body[0] else { panic!("Expected whilst") };
+        // `x` is bound later in the enclosing scope, after the loop variable
+        assert_eq!(ident_slot(&body[0]), Some(ScopeSlot { depth: 0, slot: 1 }));
+        // `z` is bound in the inner loop's scope, so it stays dynamic
+        assert_eq!(ident_slot(&body[3]), None);
+    }
+
+    #[test]
+    fn test_resolve_loop_scopes() {
+        let ast = resolved("weave total as 0\nfor each n in [1, 2] then\nset total to n\nend");
+        let AstNode::ForStmt { body, .. } = &ast[1] else { panic!("Expected for loop") };
+        let AstNode::SetStmt { target, value, .. } = &body[0] else { panic!("Expected set") };
+        assert_eq!(ident_slot(target), Some(ScopeSlot { depth: 1, slot: 0 }));
+        assert_eq!(ident_slot(value), Some(ScopeSlot { depth: 0, slot: 0 }));
+    }
 }
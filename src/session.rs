@@ -0,0 +1,217 @@
+//! # Sessions
+//!
+//! A [`Session`] holds what many short-lived scripts have in common: the
+//! [`ModuleResolver`] with every module it has parsed, a
+//! [`CacheStore`] of parsed scripts and compiled chunks, the
+//! [`CapabilityPolicy`] that decides their requests, the
+//! [`StorageProvider`] their `store_*` calls keep state in, the
+//! [`MessageBus`] they publish and subscribe through, and the
+//! [`WatchdogHost`] told when one misses a heartbeat. Each script then runs
+//! in a fresh [`Evaluator`] spawned from the session, so scripts stay
+//! isolated from each other while the standard library is parsed once per
+//! session rather than once per script.
+//!
+//! ```
+//! use glimmer_weave::session::Session;
+//! use glimmer_weave::{ModuleResolver, Value};
+//!
+//! let mut resolver = ModuleResolver::new("/app".to_string(), "/std".to_string());
+//! resolver.add_source("/app/greet.gw", "grove Greet with\n    chant hello() then\n        \"hi\"\n    end\n    offer hello\nend");
+//! let mut session = Session::new(resolver);
+//!
+//! for _ in 0..3 {
+//!     let result = session.run("summon Greet from \"greet.gw\"\nGreet.hello()");
+//!     assert_eq!(result, Ok(Value::Text("hi".to_string())));
+//! }
+//! assert_eq!(session.module_count(), 1);
+//! ```
+//!
+//! Evaluators spawned from a session share its resolver, policy, storage,
+//! bus and watchdog host but nothing else: globals, module instances, the capability audit
+//! log and resources all belong to the evaluator.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::ast::AstNode;
+use crate::bus::MessageBus;
+use crate::bytecode::BytecodeChunk;
+use crate::bytecode_compiler::{compile_cached, CompileResult};
+use crate::capability::CapabilityPolicy;
+use crate::eval::{Evaluator, RuntimeError, Value};
+use crate::module_cache::{content_hash, CacheStore, MemoryCacheStore};
+use crate::module_resolver::ModuleResolver;
+use crate::parser::ParseError;
+use crate::storage::{MemoryStorage, StorageProvider};
+use crate::sync::{lock, shared, Guard, Shared};
+use crate::watchdog::{MissedHeartbeat, WatchdogAction, WatchdogHost};
+
+/// Shared resolver, caches and policy for the scripts of one host component
+pub struct Session {
+    resolver: Shared<ModuleResolver>,
+    cache: Box<dyn CacheStore>,
+    policy: Option<Shared<Box<dyn CapabilityPolicy>>>,
+    storage: Shared<Box<dyn StorageProvider>>,
+    bus: Shared<MessageBus>,
+    watchdog_host: Option<Shared<Box<dyn WatchdogHost>>>,
+    deterministic: bool,
+}
+
+impl Session {
+    /// Create a session loading modules through `resolver`, with an
+    /// in-memory script cache and storage
+    ///
+    /// Spawned evaluators start from the resolver's prelude.
+    pub fn new(resolver: ModuleResolver) -> Self {
+        Session {
+            resolver: shared(resolver),
+            cache: Box::new(MemoryCacheStore::new()),
+            policy: None,
+            storage: shared(Box::new(MemoryStorage::new())),
+            bus: shared(MessageBus::new()),
+            watchdog_host: None,
+            deterministic: false,
+        }
+    }
+
+    /// Cache parsed scripts and compiled chunks in `cache`, e.g. a
+    /// `DiskCacheStore` so chunks survive a restart
+    pub fn set_cache(&mut self, cache: Box<dyn CacheStore>) {
+        self.cache = cache;
+    }
+
+    /// Decide the capability requests of every script in the session with
+    /// `policy`
+    ///
+    /// Evaluators spawned earlier keep the policy they were spawned with.
+    pub fn set_capability_policy(&mut self, policy: Box<dyn CapabilityPolicy>) {
+        self.policy = Some(shared(policy));
+    }
+
+    /// Keep the state scripts store in `provider`, replacing the session's
+    /// in-memory storage
+    ///
+    /// Evaluators spawned earlier keep the storage they were spawned with.
+    pub fn set_storage_provider(&mut self, provider: Box<dyn StorageProvider>) {
+        self.storage = shared(provider);
+    }
+
+    /// Tell `host` when a script misses a heartbeat it promised with
+    /// `watchdog(interval)`, and let it decide whether the script is
+    /// cancelled
+    ///
+    /// Evaluators spawned earlier keep the host they were spawned with.
+    pub fn set_watchdog_host(&mut self, host: Box<dyn WatchdogHost>) {
+        self.watchdog_host = Some(shared(host));
+    }
+
+    /// Spawn deterministic evaluators (see
+    /// [`Evaluator::set_deterministic`])
+    ///
+    /// Evaluators spawned earlier keep the mode they were spawned with.
+    pub fn set_deterministic(&mut self, enabled: bool) {
+        self.deterministic = enabled;
+    }
+
+    /// The shared resolver, e.g. to register more module sources
+    pub fn resolver(&self) -> Guard<'_, ModuleResolver> {
+        lock(&self.resolver)
+    }
+
+    /// Number of modules the session has parsed so far
+    pub fn module_count(&self) -> usize {
+        lock(&self.resolver).loaded_modules().count()
+    }
+
+    /// Publish `value` on `topic` for the session's scripts, returning how
+    /// many subscribers received it
+    ///
+    /// The host needs no capability; subscribers handle the message during
+    /// their event pump.
+    pub fn publish(&self, topic: &str, value: &Value) -> usize {
+        lock(&self.bus).publish(topic, value)
+    }
+
+    /// A fresh evaluator that loads modules, decides capabilities, stores
+    /// state and passes messages through the session
+    pub fn evaluator(&self) -> Evaluator {
+        let mut evaluator = Evaluator::with_prelude(lock(&self.resolver).prelude());
+        evaluator.share_module_resolver(Shared::clone(&self.resolver));
+        if let Some(policy) = &self.policy {
+            evaluator.set_capability_policy(Box::new(SharedPolicy(Shared::clone(policy))));
+        }
+        evaluator.set_storage_provider(Box::new(SharedStorage(Shared::clone(&self.storage))));
+        evaluator.join_bus(Shared::clone(&self.bus));
+        if let Some(host) = &self.watchdog_host {
+            evaluator.set_watchdog_host(Box::new(SharedWatchdogHost(Shared::clone(host))));
+        }
+        if self.deterministic {
+            evaluator.set_deterministic(true);
+        }
+        evaluator
+    }
+
+    /// Parse a script, reusing the parse of an identical earlier script
+    pub fn parse(&mut self, source: &str) -> Result<Vec<AstNode>, ParseError> {
+        let hash = content_hash(source);
+        if let Some(nodes) = self.cache.module(hash) {
+            return Ok(nodes);
+        }
+        let tokens = crate::lexer::Lexer::new(source).tokenize_positioned();
+        let nodes = crate::parser::Parser::new(tokens).parse()?;
+        self.cache.store_module(hash, &nodes);
+        Ok(nodes)
+    }
+
+    /// Compile a script to bytecode, reusing cached chunks and parses
+    pub fn compile(&mut self, source: &str) -> CompileResult<BytecodeChunk> {
+        compile_cached(source, self.cache.as_mut())
+    }
+
+    /// Run a script in a fresh evaluator
+    ///
+    /// A parse error is reported as `RuntimeError::CompileError`.
+    pub fn run(&mut self, source: &str) -> Result<Value, RuntimeError> {
+        let nodes = self.parse(source).map_err(|error| RuntimeError::CompileError { message: error.message })?;
+        self.evaluator().eval(&nodes)
+    }
+}
+
+/// Capability policy handed to each spawned evaluator, deciding through
+/// the session's policy
+struct SharedPolicy(Shared<Box<dyn CapabilityPolicy>>);
+
+impl CapabilityPolicy for SharedPolicy {
+    fn decide(&mut self, capability: &str, justification: &str) -> Result<(), String> {
+        lock(&self.0).decide(capability, justification)
+    }
+}
+
+/// Watchdog host handed to each spawned evaluator, reporting to the
+/// session's host
+struct SharedWatchdogHost(Shared<Box<dyn WatchdogHost>>);
+
+impl WatchdogHost for SharedWatchdogHost {
+    fn missed(&mut self, missed: &MissedHeartbeat) -> WatchdogAction {
+        lock(&self.0).missed(missed)
+    }
+}
+
+/// Storage handed to each spawned evaluator, keeping state in the
+/// session's provider
+struct SharedStorage(Shared<Box<dyn StorageProvider>>);
+
+impl StorageProvider for SharedStorage {
+    fn get(&mut self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        lock(&self.0).get(key)
+    }
+
+    fn set(&mut self, key: &str, bytes: &[u8]) -> Result<(), String> {
+        lock(&self.0).set(key, bytes)
+    }
+
+    fn delete(&mut self, key: &str) -> Result<bool, String> {
+        lock(&self.0).delete(key)
+    }
+}
@@ -0,0 +1,132 @@
+//! # Sound
+//!
+//! Audible alerts for notification scripts, through the evaluator's
+//! [`Speaker`]. `Sound.tone(frequency, millis)` plays a tone of
+//! `frequency` hertz for `millis` milliseconds and `Sound.stop()` silences
+//! one still playing; both need a [`SPEAKER_CAPABILITY`] grant.
+//!
+//! Under std evaluators start with a [`NullSpeaker`], which accepts tones
+//! and plays nothing, so scripts run the same with or without sound
+//! hardware. AethelOS installs a [`PcSpeaker`] over the PC speaker, which
+//! the kernel reaches through [`SpeakerPorts`].
+//!
+//! ```
+//! use glimmer_weave::sound::{pit_divisor, PIT_FREQUENCY};
+//!
+//! assert_eq!(pit_divisor(440.0), 2712);
+//! assert_eq!(pit_divisor(f64::from(PIT_FREQUENCY)), 1);
+//! assert_eq!(pit_divisor(1.0), u16::MAX);
+//! ```
+
+use alloc::string::String;
+
+/// Capability a script must hold to use the `Sound` builtins
+pub const SPEAKER_CAPABILITY: &str = "Speaker.play";
+
+/// Lowest and highest frequency `Sound.tone` accepts, in hertz
+pub const TONE_RANGE: (f64, f64) = (20.0, 20_000.0);
+
+/// Input clock of the programmable interval timer, in hertz
+pub const PIT_FREQUENCY: u32 = 1_193_182;
+
+/// Host side of sound
+///
+/// A provider may play a tone to the end before returning or start it and
+/// return at once; `stop` silences whatever is still playing. Errors
+/// become runtime errors of the builtin.
+pub trait Speaker: crate::sync::MaybeSend {
+    /// Play `frequency` hertz for `millis` milliseconds
+    fn tone(&mut self, frequency: f64, millis: u64) -> Result<(), String>;
+    /// Silence the speaker
+    fn stop(&mut self) -> Result<(), String>;
+}
+
+/// Speaker that plays nothing
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullSpeaker;
+
+impl Speaker for NullSpeaker {
+    fn tone(&mut self, _frequency: f64, _millis: u64) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Port I/O and waiting a kernel provides, for [`PcSpeaker`]
+pub trait SpeakerPorts: crate::sync::MaybeSend {
+    fn inb(&mut self, port: u16) -> u8;
+    fn outb(&mut self, port: u16, value: u8);
+    /// Wait `millis` milliseconds
+    fn sleep(&mut self, millis: u64);
+}
+
+/// PIT command port
+const PIT_COMMAND: u16 = 0x43;
+/// PIT channel 2 data port, wired to the speaker
+const PIT_CHANNEL_2: u16 = 0x42;
+/// Keyboard controller port B; bits 0 and 1 gate the speaker
+const SPEAKER_GATE: u16 = 0x61;
+
+/// [`Speaker`] over the PC speaker, driven by PIT channel 2
+///
+/// Tones play to the end before `tone` returns.
+pub struct PcSpeaker<P: SpeakerPorts> {
+    ports: P,
+}
+
+impl<P: SpeakerPorts> PcSpeaker<P> {
+    pub fn new(ports: P) -> Self {
+        PcSpeaker { ports }
+    }
+
+    pub fn ports(&self) -> &P {
+        &self.ports
+    }
+
+    fn silence(&mut self) {
+        let gate = self.ports.inb(SPEAKER_GATE);
+        self.ports.outb(SPEAKER_GATE, gate & !0b11);
+    }
+}
+
+impl<P: SpeakerPorts> Speaker for PcSpeaker<P> {
+    fn tone(&mut self, frequency: f64, millis: u64) -> Result<(), String> {
+        let [low, high] = pit_divisor(frequency).to_le_bytes();
+        // Channel 2, low byte then high byte, square wave
+        self.ports.outb(PIT_COMMAND, 0b1011_0110);
+        self.ports.outb(PIT_CHANNEL_2, low);
+        self.ports.outb(PIT_CHANNEL_2, high);
+        let gate = self.ports.inb(SPEAKER_GATE);
+        self.ports.outb(SPEAKER_GATE, gate | 0b11);
+        self.ports.sleep(millis);
+        self.silence();
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), String> {
+        self.silence();
+        Ok(())
+    }
+}
+
+/// PIT reload value that sounds nearest `frequency`, within the PIT's range
+pub fn pit_divisor(frequency: f64) -> u16 {
+    let divisor = (f64::from(PIT_FREQUENCY) / frequency + 0.5) as u64;
+    divisor.clamp(1, u64::from(u16::MAX)) as u16
+}
+
+/// Default speaker for new evaluators: [`NullSpeaker`] under std, none
+/// otherwise
+pub(crate) fn default_speaker() -> Option<alloc::boxed::Box<dyn Speaker>> {
+    #[cfg(feature = "std")]
+    {
+        Some(alloc::boxed::Box::new(NullSpeaker))
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        None
+    }
+}
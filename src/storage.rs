@@ -0,0 +1,197 @@
+//! # Storage
+//!
+//! Small key-value state that outlives a script. `store_set(key, value)`,
+//! `store_get(key)` and `store_delete(key)` go through the evaluator's
+//! [`StorageProvider`]; the script must have been granted
+//! [`STORE_CAPABILITY`] first.
+//!
+//! Values are kept in the [`value_codec`](crate::value_codec) encoding, so a
+//! provider only sees opaque bytes and only plain data can be stored.
+//! Evaluators start with a [`MemoryStorage`] of their own, which lasts as
+//! long as the evaluator; evaluators spawned from one
+//! [`Session`](crate::session::Session) share the session's. On AethelOS the
+//! kernel installs a provider that persists across boots.
+//!
+//! ## Versioned namespaces
+//!
+//! Each key is a namespace whose stored value can outlive the script that
+//! wrote it. A script that changes the shape of its state registers
+//! migrations for the namespace:
+//!
+//! ```glimmer-weave
+//! migrate store "config" from 1 to 2 with chant(old) then
+//!     yield { theme: old.theme, sizes: [old.size] }
+//! end
+//! ```
+//!
+//! The namespace's version is the highest one its migrations reach. On the
+//! first `store_*` access the evaluator compares it with the version stored
+//! next to the value (under [`version_key`]; values stored without one are
+//! version [`UNVERSIONED`]) and runs the [`Migrations`] in between, saving
+//! after each step. Writes record the script's version.
+//!
+//! ```
+//! use glimmer_weave::storage::{MemoryStorage, StorageProvider};
+//!
+//! let mut storage = MemoryStorage::new();
+//! storage.set("runs", &[3]).unwrap();
+//! assert_eq!(storage.get("runs"), Ok(Some(vec![3])));
+//! assert_eq!(storage.delete("runs"), Ok(true));
+//! assert_eq!(storage.get("runs"), Ok(None));
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Capability a script must hold to call the `store_*` builtins
+pub const STORE_CAPABILITY: &str = "Store.readwrite";
+
+/// Version of a value stored without a version record
+pub const UNVERSIONED: u32 = 1;
+
+/// Host side of the key-value store
+///
+/// Errors are reported to the script as runtime errors of the builtin that
+/// hit them.
+pub trait StorageProvider: crate::sync::MaybeSend {
+    /// Bytes stored under `key`, or `None` if nothing is
+    fn get(&mut self, key: &str) -> Result<Option<Vec<u8>>, String>;
+    /// Store `bytes` under `key`, replacing what was there
+    fn set(&mut self, key: &str, bytes: &[u8]) -> Result<(), String>;
+    /// Remove `key`, returning whether it was stored
+    fn delete(&mut self, key: &str) -> Result<bool, String>;
+}
+
+/// Storage held in memory
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    entries: BTreeMap<String, Vec<u8>>,
+}
+
+impl MemoryStorage {
+    /// Create empty storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored keys
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether nothing is stored
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl StorageProvider for MemoryStorage {
+    fn get(&mut self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        Ok(self.entries.get(key).cloned())
+    }
+
+    fn set(&mut self, key: &str, bytes: &[u8]) -> Result<(), String> {
+        self.entries.insert(key.to_string(), bytes.to_vec());
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &str) -> Result<bool, String> {
+        Ok(self.entries.remove(key).is_some())
+    }
+}
+
+/// Key the version of `namespace` is stored under
+pub fn version_key(namespace: &str) -> String {
+    format!("{}@version", namespace)
+}
+
+/// Version `namespace` is stored at
+pub fn stored_version<P: StorageProvider + ?Sized>(provider: &mut P, namespace: &str) -> Result<u32, String> {
+    match provider.get(&version_key(namespace))? {
+        Some(bytes) => core::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|text| text.parse().ok())
+            .ok_or_else(|| format!("Unreadable version record for \"{}\"", namespace)),
+        None => Ok(UNVERSIONED),
+    }
+}
+
+/// Record that `namespace` is stored at `version`
+pub fn set_stored_version<P: StorageProvider + ?Sized>(provider: &mut P, namespace: &str, version: u32) -> Result<(), String> {
+    provider.set(&version_key(namespace), version.to_string().as_bytes())
+}
+
+/// Migration steps of each namespace
+///
+/// `T` is whatever performs a step; the evaluator registers chants.
+///
+/// ```
+/// use glimmer_weave::storage::Migrations;
+///
+/// let mut migrations = Migrations::new();
+/// migrations.register("config", 1, 2, "split");
+/// migrations.register("config", 2, 4, "rename");
+/// assert_eq!(migrations.target("config"), Some(4));
+/// assert_eq!(migrations.plan("config", 1), Ok(vec![(2, "split"), (4, "rename")]));
+/// assert!(migrations.plan("config", 3).is_err());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Migrations<T> {
+    /// Per namespace: `from` version to (`to` version, step)
+    steps: BTreeMap<String, BTreeMap<u32, (u32, T)>>,
+}
+
+impl<T> Default for Migrations<T> {
+    fn default() -> Self {
+        Self { steps: BTreeMap::new() }
+    }
+}
+
+impl<T: Clone> Migrations<T> {
+    /// No migrations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the step from version `from` to `to` of `namespace`,
+    /// replacing any earlier step from `from`
+    pub fn register(&mut self, namespace: &str, from: u32, to: u32, step: T) {
+        self.steps.entry(namespace.to_string()).or_default().insert(from, (to, step));
+    }
+
+    /// Version `namespace` migrates to, if it has migrations
+    pub fn target(&self, namespace: &str) -> Option<u32> {
+        self.steps.get(namespace)?.values().map(|(to, _)| *to).max()
+    }
+
+    /// Steps taking `namespace` from version `stored` to its target, each
+    /// with the version it reaches
+    pub fn plan(&self, namespace: &str, stored: u32) -> Result<Vec<(u32, T)>, String> {
+        let Some(target) = self.target(namespace) else {
+            return Ok(Vec::new());
+        };
+        if stored > target {
+            return Err(format!(
+                "\"{}\" is stored at version {}, newer than the version {} this script knows",
+                namespace, stored, target
+            ));
+        }
+        let mut plan = Vec::new();
+        let mut version = stored;
+        while version < target {
+            let (to, step) = self
+                .steps
+                .get(namespace)
+                .and_then(|steps| steps.get(&version))
+                .ok_or_else(|| format!("No migration from version {} of \"{}\"", version, namespace))?;
+            if *to <= version {
+                return Err(format!("The migration from version {} of \"{}\" goes backwards", version, namespace));
+            }
+            plan.push((*to, step.clone()));
+            version = *to;
+        }
+        Ok(plan)
+    }
+}
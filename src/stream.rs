@@ -0,0 +1,176 @@
+//! # Streams
+//!
+//! Host sources of lines and bytes that scripts walk lazily.
+//!
+//! `fs_lines(path)`, `fs_bytes(path)` and `console_read_lines()` open a
+//! source through the evaluator's [`StreamHost`] and return an iterator that
+//! pulls one line or byte from the host each time it is advanced, so a
+//! script can work through a large file without loading it into a list.
+//! The script must have been granted [`FS_READ_CAPABILITY`] or
+//! [`CONSOLE_READ_CAPABILITY`] first.
+//!
+//! The open source is a resource of kind [`STREAM_RESOURCE`]: it is closed
+//! through [`StreamHost::close`] when the chant that opened it finishes
+//! (unless the chant yields the iterator), or earlier with `release`.
+//! Copies of a stream iterator share the host handle, so advancing one
+//! advances them all.
+//!
+//! ```
+//! use glimmer_weave::stream::StreamHost;
+//!
+//! struct Greeting(Vec<&'static str>);
+//!
+//! impl StreamHost for Greeting {
+//!     fn open_file(&mut self, _path: &str) -> Result<u64, String> { Ok(1) }
+//!     fn open_console(&mut self) -> Result<u64, String> { Err("no console".into()) }
+//!     fn read_line(&mut self, _handle: u64) -> Result<Option<String>, String> {
+//!         Ok(self.0.pop().map(String::from))
+//!     }
+//!     fn read_byte(&mut self, _handle: u64) -> Result<Option<u8>, String> { Ok(None) }
+//!     fn close(&mut self, _handle: u64) -> Result<(), String> { Ok(()) }
+//! }
+//!
+//! let mut host = Greeting(vec!["world", "hello"]);
+//! assert_eq!(host.read_line(1), Ok(Some("hello".to_string())));
+//! ```
+
+use alloc::string::String;
+
+/// Capability a script must hold to call `fs_lines` and `fs_bytes`
+pub const FS_READ_CAPABILITY: &str = "FS.read";
+
+/// Capability a script must hold to call `console_read_lines`
+pub const CONSOLE_READ_CAPABILITY: &str = "Console.read";
+
+/// Resource kind of an open stream
+pub const STREAM_RESOURCE: &str = "stream";
+
+/// What a stream iterator yields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamUnit {
+    /// Text lines, without their line terminator
+    Lines,
+    /// Bytes, as numbers from 0 to 255
+    Bytes,
+}
+
+/// Host side of streaming input
+///
+/// Handles are chosen by the host; errors are reported to the script as
+/// runtime errors of the builtin that hit them.
+pub trait StreamHost: crate::sync::MaybeSend {
+    /// Open a file for reading
+    fn open_file(&mut self, path: &str) -> Result<u64, String>;
+    /// Open the console's input
+    fn open_console(&mut self) -> Result<u64, String>;
+    /// Next line without its terminator, or `None` at the end
+    fn read_line(&mut self, handle: u64) -> Result<Option<String>, String>;
+    /// Next byte, or `None` at the end
+    fn read_byte(&mut self, handle: u64) -> Result<Option<u8>, String>;
+    /// Close a handle; it is never read again
+    fn close(&mut self, handle: u64) -> Result<(), String>;
+}
+
+/// Files and standard input through `std::io`, buffered
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct StdStreams {
+    readers: alloc::collections::BTreeMap<u64, alloc::boxed::Box<dyn std::io::BufRead + Send>>,
+    next_handle: u64,
+}
+
+#[cfg(feature = "std")]
+impl StdStreams {
+    /// Create a host with no open streams
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&mut self, reader: alloc::boxed::Box<dyn std::io::BufRead + Send>) -> u64 {
+        self.next_handle += 1;
+        self.readers.insert(self.next_handle, reader);
+        self.next_handle
+    }
+
+    fn reader(&mut self, handle: u64) -> Result<&mut alloc::boxed::Box<dyn std::io::BufRead + Send>, String> {
+        self.readers.get_mut(&handle).ok_or_else(|| alloc::format!("Unknown stream handle {}", handle))
+    }
+}
+
+#[cfg(feature = "std")]
+impl StreamHost for StdStreams {
+    fn open_file(&mut self, path: &str) -> Result<u64, String> {
+        let file = std::fs::File::open(path).map_err(|error| alloc::format!("{}: {}", path, error))?;
+        Ok(self.insert(alloc::boxed::Box::new(std::io::BufReader::new(file))))
+    }
+
+    fn open_console(&mut self) -> Result<u64, String> {
+        Ok(self.insert(alloc::boxed::Box::new(std::io::BufReader::new(std::io::stdin()))))
+    }
+
+    fn read_line(&mut self, handle: u64) -> Result<Option<String>, String> {
+        let mut line = String::new();
+        if self.reader(handle)?.read_line(&mut line).map_err(|error| error.to_string())? == 0 {
+            return Ok(None);
+        }
+        let content = line.trim_end_matches(['\n', '\r']).len();
+        line.truncate(content);
+        Ok(Some(line))
+    }
+
+    fn read_byte(&mut self, handle: u64) -> Result<Option<u8>, String> {
+        let reader = self.reader(handle)?;
+        let byte = reader.fill_buf().map_err(|error| error.to_string())?.first().copied();
+        if byte.is_some() {
+            reader.consume(1);
+        }
+        Ok(byte)
+    }
+
+    fn close(&mut self, handle: u64) -> Result<(), String> {
+        self.readers.remove(&handle).map(|_| ()).ok_or_else(|| alloc::format!("Unknown stream handle {}", handle))
+    }
+}
+
+/// Default stream host for new evaluators: [`StdStreams`] under std, none
+/// otherwise (the host must install one before scripts open streams)
+pub(crate) fn default_streams() -> Option<alloc::boxed::Box<dyn StreamHost>> {
+    #[cfg(feature = "std")]
+    {
+        Some(alloc::boxed::Box::new(StdStreams::new()))
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        None
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_std_streams_read_lines_and_bytes() {
+        let path = std::env::temp_dir().join(format!("glimmer_stream_{}.txt", std::process::id()));
+        std::fs::write(&path, "one\r\ntwo\n\nfour").unwrap();
+        let path = path.to_str().unwrap();
+
+        let mut host = StdStreams::new();
+        let lines = host.open_file(path).unwrap();
+        let mut read = Vec::new();
+        while let Some(line) = host.read_line(lines).unwrap() {
+            read.push(line);
+        }
+        assert_eq!(read, ["one", "two", "", "four"]);
+
+        let bytes = host.open_file(path).unwrap();
+        assert_eq!(host.read_byte(bytes), Ok(Some(b'o')));
+        assert_eq!(host.read_byte(bytes), Ok(Some(b'n')));
+
+        host.close(lines).unwrap();
+        host.close(bytes).unwrap();
+        assert!(host.read_line(lines).is_err());
+        assert!(host.open_file("/nonexistent/glimmer").is_err());
+        std::fs::remove_file(path).ok();
+    }
+}
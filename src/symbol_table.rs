@@ -267,7 +267,7 @@ impl SymbolCollector {
                 self.visit_node(right);
             }
 
-            AstNode::UnaryOp { operand, .. } => {
+            AstNode::UnaryOp { operand, .. } | AstNode::InUnit { value: operand, .. } => {
                 self.visit_node(operand);
             }
 
@@ -278,7 +278,7 @@ impl SymbolCollector {
                 }
             }
 
-            AstNode::List { elements, .. } => {
+            AstNode::List { elements, .. } | AstNode::Tuple { elements, .. } => {
                 for elem in elements {
                     self.visit_node(elem);
                 }
@@ -308,6 +308,15 @@ impl SymbolCollector {
                 self.visit_node(value);
             }
 
+            AstNode::DeferStmt { body, .. }
+            | AstNode::TogetherBlock { body, .. }
+            | AstNode::MigrateStore { body, .. }
+            | AstNode::VerifyBlock { body, .. } => {
+                for stmt in body {
+                    self.visit_node(stmt);
+                }
+            }
+
             AstNode::AttemptStmt {
                 body,
                 handlers,
@@ -328,6 +337,9 @@ impl SymbolCollector {
             } => {
                 self.visit_node(value);
                 for arm in arms {
+                    if let Some(guard) = &arm.guard {
+                        self.visit_node(guard);
+                    }
                     for stmt in &arm.body {
                         self.visit_node(stmt);
                     }
@@ -352,6 +364,9 @@ impl SymbolCollector {
 
             // Leaf nodes - no children to visit
             AstNode::Number { .. }
+            | AstNode::BigInt { .. }
+            | AstNode::Integer { .. }
+            | AstNode::Measure { .. }
             | AstNode::Text { .. }
             | AstNode::Truth { .. }
             | AstNode::Nothing { .. }
@@ -363,6 +378,7 @@ impl SymbolCollector {
             | AstNode::Absent { .. }
             | AstNode::Import { .. }
             | AstNode::Export { .. }
+            | AstNode::Speaks { .. }
             | AstNode::ModuleAccess { .. }
             | AstNode::BorrowExpr { .. }
             | AstNode::Pipeline { .. }
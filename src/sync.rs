@@ -0,0 +1,117 @@
+//! # Thread Safety
+//!
+//! By default values and evaluators share state through `Rc<RefCell<_>>`
+//! and stay on the thread that made them. The `sync` feature (which needs
+//! `std`) switches that state to `Arc<Mutex<_>>` and requires every host
+//! hook (clock, policy, storage, streams, ...) to be `Send`, so that:
+//!
+//! - [`Value`](crate::eval::Value) is `Send + Sync`: results and arguments
+//!   cross threads, and text builders stay shared between their copies.
+//! - [`Evaluator`](crate::eval::Evaluator), [`VM`](crate::vm::VM) and
+//!   [`Session`](crate::session::Session) are `Send`: a host can hand one
+//!   to each worker of a thread pool, and evaluators spawned from one
+//!   session keep sharing its modules, policy, storage and bus.
+//!
+//! The guarantees are checked at compile time below. Without the feature
+//! [`MaybeSend`] holds for every type and nothing is locked.
+//!
+//! ```
+//! use glimmer_weave::sync::{lock, shared};
+//!
+//! let count = shared(1);
+//! *lock(&count) += 1;
+//! assert_eq!(*lock(&count), 2);
+//! ```
+
+#[cfg(not(feature = "sync"))]
+use alloc::rc::{Rc, Weak};
+#[cfg(not(feature = "sync"))]
+use core::cell::{RefCell, RefMut};
+
+#[cfg(feature = "sync")]
+use alloc::sync::{Arc, Weak};
+#[cfg(feature = "sync")]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// State shared between values, evaluators and their session
+#[cfg(not(feature = "sync"))]
+pub type Shared<T> = Rc<RefCell<T>>;
+/// State shared between values, evaluators and their session
+#[cfg(feature = "sync")]
+pub type Shared<T> = Arc<Mutex<T>>;
+
+/// Non-owning reference to [`Shared`] state
+#[cfg(not(feature = "sync"))]
+pub type WeakShared<T> = Weak<RefCell<T>>;
+/// Non-owning reference to [`Shared`] state
+#[cfg(feature = "sync")]
+pub type WeakShared<T> = Weak<Mutex<T>>;
+
+/// Guard giving access to [`Shared`] state
+#[cfg(not(feature = "sync"))]
+pub type Guard<'a, T> = RefMut<'a, T>;
+/// Guard giving access to [`Shared`] state
+#[cfg(feature = "sync")]
+pub type Guard<'a, T> = MutexGuard<'a, T>;
+
+/// Wrap `value` for sharing
+pub fn shared<T>(value: T) -> Shared<T> {
+    #[cfg(not(feature = "sync"))]
+    {
+        Rc::new(RefCell::new(value))
+    }
+    #[cfg(feature = "sync")]
+    {
+        Arc::new(Mutex::new(value))
+    }
+}
+
+/// Access shared state until the guard is dropped
+///
+/// A lock poisoned by a panicking holder is taken over: shared state is
+/// only ever left half-updated by a host hook that panicked.
+pub fn lock<T: ?Sized>(state: &Shared<T>) -> Guard<'_, T> {
+    #[cfg(not(feature = "sync"))]
+    {
+        state.borrow_mut()
+    }
+    #[cfg(feature = "sync")]
+    {
+        state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// `Send` under the `sync` feature, and every type otherwise
+///
+/// Host hook traits have it as a supertrait, so their trait objects can
+/// move between threads exactly when the crate is built thread-safe.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSend {}
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// `Send` under the `sync` feature, and every type otherwise
+///
+/// Host hook traits have it as a supertrait, so their trait objects can
+/// move between threads exactly when the crate is built thread-safe.
+#[cfg(feature = "sync")]
+pub trait MaybeSend: Send {}
+#[cfg(feature = "sync")]
+impl<T: ?Sized + Send> MaybeSend for T {}
+
+// Compile-time guarantees of the `sync` feature
+#[cfg(feature = "sync")]
+const _: fn() = || {
+    fn send<T: Send>() {}
+    fn send_sync<T: Send + Sync>() {}
+
+    send_sync::<crate::eval::Value>();
+    send_sync::<crate::eval::RuntimeError>();
+    send_sync::<crate::runtime::NativeFunction>();
+    send_sync::<crate::ast::AstNode>();
+    send_sync::<crate::bytecode::BytecodeChunk>();
+    send::<crate::eval::Evaluator>();
+    send::<crate::vm::VM>();
+    send::<crate::session::Session>();
+    send::<crate::module_resolver::ModuleResolver>();
+};
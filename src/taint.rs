@@ -0,0 +1,167 @@
+//! # Taint Tracking
+//!
+//! Information-flow control for data that comes from sensitive
+//! capabilities.
+//!
+//! With a [`TaintPolicy`] installed through
+//! [`Evaluator::set_taint_policy`](crate::eval::Evaluator::set_taint_policy),
+//! whatever a native chant returns while using a source capability (one
+//! given a `FS.read` token, `fs_lines` reading a file) is marked tainted
+//! with that capability's name. Taint follows the data: through operators,
+//! into lists and maps, out of index and field reads, through `for each`
+//! and `match`, and through the result of any native chant given tainted
+//! arguments.
+//!
+//! Builtins that use a capability without being given a token count as
+//! using the capability [`builtin_capabilities`] names: `Net.recv` data is
+//! tainted, and `print` writes to `Console.write`.
+//!
+//! When tainted data reaches a sink (a native chant given a token for a
+//! sink capability, or a builtin using one, such as `Net.send`) the
+//! evaluator records a [`TaintViolation`], or with
+//! [`TaintMode::Block`] refuses the call with
+//! [`RuntimeError::CapabilityDenied`](crate::eval::RuntimeError::CapabilityDenied).
+//! Data comes out clean only from a chant the policy declares a
+//! sanitizer.
+//!
+//! Only explicit flows are tracked: branching on tainted data does not
+//! taint what the branches compute.
+//!
+//! ```
+//! use glimmer_weave::taint::TaintPolicy;
+//!
+//! let policy = TaintPolicy::new().sanitizer("escape").block();
+//! assert!(policy.is_source("FS.read"));
+//! assert!(policy.is_sink("Console.write"));
+//! assert!(policy.is_sanitizer("escape"));
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::source_location::SourceSpan;
+
+/// Capabilities whose data is tainted by default
+pub const DEFAULT_SOURCES: &[&str] = &["FS.read", "Net.recv"];
+
+/// Capabilities tainted data may not reach by default
+pub const DEFAULT_SINKS: &[&str] = &["Net.send", "Console.write"];
+
+/// Capabilities a builtin reads or writes through without being handed a
+/// token: `print`/`println` write to `Console.write`, `Net.send` and
+/// `Net.recv` use a socket, and an HTTP request does both
+pub fn builtin_capabilities(builtin: &str) -> &'static [&'static str] {
+    match builtin {
+        "print" | "println" => &["Console.write"],
+        "net_send" => &["Net.send"],
+        "net_recv" => &["Net.recv"],
+        "http_get" | "http_post" => &["Net.send", "Net.recv"],
+        _ => &[],
+    }
+}
+
+/// What happens when tainted data reaches a sink
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaintMode {
+    /// Let the call through and record a violation
+    #[default]
+    Flag,
+    /// Refuse the call with a capability denial
+    Block,
+}
+
+/// Which capabilities taint data, which may not receive it, and which
+/// chants clean it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaintPolicy {
+    sources: Vec<String>,
+    sinks: Vec<String>,
+    sanitizers: Vec<String>,
+    mode: TaintMode,
+}
+
+impl Default for TaintPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaintPolicy {
+    /// Policy with the [`DEFAULT_SOURCES`] and [`DEFAULT_SINKS`], no
+    /// sanitizers, flagging violations
+    pub fn new() -> Self {
+        TaintPolicy {
+            sources: DEFAULT_SOURCES.iter().map(|name| name.to_string()).collect(),
+            sinks: DEFAULT_SINKS.iter().map(|name| name.to_string()).collect(),
+            sanitizers: Vec::new(),
+            mode: TaintMode::Flag,
+        }
+    }
+
+    /// Also taint data from `capability`
+    pub fn source(mut self, capability: &str) -> Self {
+        self.sources.push(capability.to_string());
+        self
+    }
+
+    /// Also keep tainted data away from `capability`
+    pub fn sink(mut self, capability: &str) -> Self {
+        self.sinks.push(capability.to_string());
+        self
+    }
+
+    /// Declare the chant (script or native) named `chant` a sanitizer,
+    /// whose result is clean whatever it was given
+    pub fn sanitizer(mut self, chant: &str) -> Self {
+        self.sanitizers.push(chant.to_string());
+        self
+    }
+
+    /// Refuse calls that would pass tainted data to a sink
+    pub fn block(mut self) -> Self {
+        self.mode = TaintMode::Block;
+        self
+    }
+
+    /// What happens at a sink
+    pub fn mode(&self) -> TaintMode {
+        self.mode
+    }
+
+    /// Whether data from `capability` is tainted
+    pub fn is_source(&self, capability: &str) -> bool {
+        self.sources.iter().any(|source| source == capability)
+    }
+
+    /// Whether `capability` may not receive tainted data
+    pub fn is_sink(&self, capability: &str) -> bool {
+        self.sinks.iter().any(|sink| sink == capability)
+    }
+
+    /// Whether `chant` cleans what it returns
+    pub fn is_sanitizer(&self, chant: &str) -> bool {
+        self.sanitizers.iter().any(|sanitizer| sanitizer == chant)
+    }
+}
+
+/// Tainted data that reached a sink
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaintViolation {
+    /// Source capabilities the data came from
+    pub sources: Vec<String>,
+    /// Sink capability it reached
+    pub sink: String,
+    /// Native chant it was passed to
+    pub by: String,
+    /// The call
+    pub span: SourceSpan,
+}
+
+/// `sources` with every name in `more` it lacks appended
+pub(crate) fn merge(sources: &mut Vec<String>, more: impl IntoIterator<Item = String>) {
+    for source in more {
+        if !sources.contains(&source) {
+            sources.push(source);
+        }
+    }
+}
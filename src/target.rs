@@ -0,0 +1,196 @@
+//! # Compilation Targets
+//!
+//! What the native back ends need to know about the machine they compile
+//! for: its instruction set, pointer width, byte order, the ELF OS ABI and
+//! machine type objects are stamped with, and the registers calls and
+//! system calls pass values in. [`CodeGen`](crate::codegen::CodeGen) and
+//! [`ElfBuilder`](crate::elf::ElfBuilder) take a [`TargetSpec`] rather than
+//! assuming x86-64 and System V, so another architecture or an AethelOS
+//! ABI is a new descriptor, not an edit to every back end.
+//!
+//! [`TargetSpec::X86_64_SYSV`] is the default. Code generation exists for
+//! [`Arch::X86_64`] only; [`TargetSpec::AARCH64_SYSV`] describes the ARM
+//! target the next back end will generate for, and objects can already be
+//! written for it.
+//!
+//! ```
+//! use glimmer_weave::target::{OsAbi, TargetSpec};
+//!
+//! // A kernel build: the same code, with objects marked standalone
+//! let kernel = TargetSpec { name: "x86_64-aethelos", os_abi: OsAbi::Standalone, ..TargetSpec::X86_64_SYSV };
+//! assert_eq!(kernel.pointer_bytes(), 8);
+//! assert_eq!(TargetSpec::by_name("aarch64-sysv"), Some(TargetSpec::AARCH64_SYSV));
+//! ```
+
+use crate::elf::{ElfData, ElfMachine};
+
+/// Instruction set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    X86_64,
+    AArch64,
+}
+
+impl Arch {
+    pub fn name(self) -> &'static str {
+        match self {
+            Arch::X86_64 => "x86_64",
+            Arch::AArch64 => "aarch64",
+        }
+    }
+}
+
+/// Byte order of multi-byte values
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    /// The ELF data encoding of this byte order
+    pub fn elf_data(self) -> ElfData {
+        match self {
+            Endian::Little => ElfData::LittleEndian,
+            Endian::Big => ElfData::BigEndian,
+        }
+    }
+}
+
+/// OS ABI an ELF object declares in its identification bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OsAbi {
+    /// Plain System V, which Linux loaders accept
+    SystemV,
+    Linux,
+    /// No operating system: kernels and bare-metal images
+    Standalone,
+}
+
+impl OsAbi {
+    /// `EI_OSABI` value
+    pub fn elf_value(self) -> u8 {
+        match self {
+            OsAbi::SystemV => 0,
+            OsAbi::Linux => 3,
+            OsAbi::Standalone => 255,
+        }
+    }
+}
+
+/// Registers a function call passes arguments and its result in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallingConvention {
+    /// Registers for the first arguments, in order; the rest go on the
+    /// stack, one word each
+    pub arg_registers: &'static [&'static str],
+    pub return_register: &'static str,
+}
+
+/// How a program asks the kernel for a service
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyscallConvention {
+    /// Instruction that enters the kernel
+    pub instruction: &'static str,
+    /// Register holding the system call number
+    pub number_register: &'static str,
+    pub arg_registers: &'static [&'static str],
+    pub return_register: &'static str,
+}
+
+/// A machine to compile for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetSpec {
+    /// Name targets are chosen by (`"x86_64-sysv"`)
+    pub name: &'static str,
+    pub arch: Arch,
+    /// Pointer width in bits
+    pub pointer_width: u32,
+    pub endian: Endian,
+    pub os_abi: OsAbi,
+    pub elf_machine: ElfMachine,
+    pub calls: CallingConvention,
+    pub syscalls: SyscallConvention,
+}
+
+impl TargetSpec {
+    /// x86-64 with the System V AMD64 calling convention and Linux system
+    /// calls
+    pub const X86_64_SYSV: TargetSpec = TargetSpec {
+        name: "x86_64-sysv",
+        arch: Arch::X86_64,
+        pointer_width: 64,
+        endian: Endian::Little,
+        os_abi: OsAbi::SystemV,
+        elf_machine: ElfMachine::X86_64,
+        calls: CallingConvention { arg_registers: &["rdi", "rsi", "rdx", "rcx", "r8", "r9"], return_register: "rax" },
+        syscalls: SyscallConvention {
+            instruction: "syscall",
+            number_register: "rax",
+            arg_registers: &["rdi", "rsi", "rdx", "r10", "r8", "r9"],
+            return_register: "rax",
+        },
+    };
+
+    /// 64-bit ARM with the AAPCS64 calling convention and Linux system
+    /// calls
+    pub const AARCH64_SYSV: TargetSpec = TargetSpec {
+        name: "aarch64-sysv",
+        arch: Arch::AArch64,
+        pointer_width: 64,
+        endian: Endian::Little,
+        os_abi: OsAbi::SystemV,
+        elf_machine: ElfMachine::AArch64,
+        calls: CallingConvention { arg_registers: &["x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7"], return_register: "x0" },
+        syscalls: SyscallConvention {
+            instruction: "svc #0",
+            number_register: "x8",
+            arg_registers: &["x0", "x1", "x2", "x3", "x4", "x5"],
+            return_register: "x0",
+        },
+    };
+
+    /// The predefined targets
+    pub const ALL: [TargetSpec; 2] = [TargetSpec::X86_64_SYSV, TargetSpec::AARCH64_SYSV];
+
+    /// Predefined target by name
+    pub fn by_name(name: &str) -> Option<TargetSpec> {
+        TargetSpec::ALL.into_iter().find(|target| target.name == name)
+    }
+
+    /// Size of a pointer, and of every value slot, in bytes
+    pub fn pointer_bytes(&self) -> i32 {
+        (self.pointer_width / 8) as i32
+    }
+
+    /// Assembler directive for one pointer-sized value
+    pub fn word_directive(&self) -> &'static str {
+        if self.pointer_width == 32 {
+            ".long"
+        } else {
+            ".quad"
+        }
+    }
+}
+
+impl Default for TargetSpec {
+    fn default() -> Self {
+        TargetSpec::X86_64_SYSV
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets_are_found_by_name() {
+        for target in TargetSpec::ALL {
+            assert_eq!(TargetSpec::by_name(target.name), Some(target));
+            assert_eq!(target.pointer_bytes(), 8);
+            assert_eq!(target.word_directive(), ".quad");
+        }
+        assert_eq!(TargetSpec::by_name("riscv64"), None);
+        assert_eq!(TargetSpec::default(), TargetSpec::X86_64_SYSV);
+    }
+}
@@ -0,0 +1,314 @@
+//! # Terminal UI
+//!
+//! Cursor placement, colors, clearing and key input for interactive
+//! scripts, through the evaluator's [`Terminal`].
+//!
+//! `Term.move_to(row, column)`, `Term.set_color(foreground, background)`
+//! and `Term.clear()` need a [`TERM_CAPABILITY`] grant; `Term.read_key()`
+//! reads the keyboard and needs [`CONSOLE_READ_CAPABILITY`]. Rows and
+//! columns count from 0, colors are the sixteen [`Color`] names, and
+//! `read_key` returns `Present(key)` — the character typed, or a name such
+//! as `"up"` or `"enter"` — or `Absent` once input has ended.
+//!
+//! Two backends come with the crate. [`AnsiTerminal`] writes escape
+//! sequences to the evaluator's output sink, so they interleave with what
+//! the script prints, and reads keys from standard input (line by line
+//! unless the host has put the terminal in raw mode). [`VgaTerminal`]
+//! drives a text-mode display through a [`VgaConsole`] the kernel provides.
+//!
+//! ```
+//! use glimmer_weave::term::{vga_attribute, Color};
+//!
+//! assert_eq!(Color::from_name("bright_yellow"), Some(Color::BrightYellow));
+//! assert_eq!(vga_attribute(Color::BrightYellow, Color::Blue), 0x1E);
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::output::OutputSink;
+
+pub use crate::stream::CONSOLE_READ_CAPABILITY;
+
+/// Capability a script must hold to move the cursor, set colors or clear
+pub const TERM_CAPABILITY: &str = "Term.control";
+
+/// The sixteen terminal colors, in ANSI order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+}
+
+impl Color {
+    /// Every color, in ANSI order
+    pub const ALL: [Color; 16] = [
+        Color::Black,
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+        Color::White,
+        Color::BrightBlack,
+        Color::BrightRed,
+        Color::BrightGreen,
+        Color::BrightYellow,
+        Color::BrightBlue,
+        Color::BrightMagenta,
+        Color::BrightCyan,
+        Color::BrightWhite,
+    ];
+
+    /// Name scripts use: `"red"`, `"bright_red"`, ...
+    pub fn name(self) -> &'static str {
+        const NAMES: [&str; 16] = [
+            "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+            "bright_black", "bright_red", "bright_green", "bright_yellow", "bright_blue", "bright_magenta", "bright_cyan",
+            "bright_white",
+        ];
+        NAMES[self as usize]
+    }
+
+    /// Color a script names
+    pub fn from_name(name: &str) -> Option<Color> {
+        Color::ALL.into_iter().find(|color| color.name() == name)
+    }
+}
+
+/// Host side of terminal control
+///
+/// `out` is the evaluator's output sink, for backends that control the
+/// terminal in-band; errors become runtime errors of the builtin.
+pub trait Terminal: crate::sync::MaybeSend {
+    /// Put the cursor at `row` and `column`, counting from 0
+    fn move_to(&mut self, out: &mut dyn OutputSink, row: u32, column: u32) -> Result<(), String>;
+    /// Color the text written from now on
+    fn set_color(&mut self, out: &mut dyn OutputSink, foreground: Color, background: Option<Color>) -> Result<(), String>;
+    /// Blank the screen and put the cursor at the top left
+    fn clear(&mut self, out: &mut dyn OutputSink) -> Result<(), String>;
+    /// Next key pressed, or `None` once input has ended
+    fn read_key(&mut self) -> Result<Option<String>, String>;
+}
+
+/// ANSI escape sequences through the output sink, keys from standard input
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct AnsiTerminal {
+    /// Bytes read past the last key
+    pending: alloc::vec::Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl AnsiTerminal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, String> {
+        use std::io::Read;
+        if !self.pending.is_empty() {
+            return Ok(Some(self.pending.remove(0)));
+        }
+        let mut byte = [0];
+        match std::io::stdin().read(&mut byte).map_err(|error| error.to_string())? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Terminal for AnsiTerminal {
+    fn move_to(&mut self, out: &mut dyn OutputSink, row: u32, column: u32) -> Result<(), String> {
+        out.write(&format!("\x1b[{};{}H", row + 1, column + 1));
+        Ok(())
+    }
+
+    fn set_color(&mut self, out: &mut dyn OutputSink, foreground: Color, background: Option<Color>) -> Result<(), String> {
+        out.write(&ansi_color(foreground, background));
+        Ok(())
+    }
+
+    fn clear(&mut self, out: &mut dyn OutputSink) -> Result<(), String> {
+        out.write("\x1b[2J\x1b[H");
+        Ok(())
+    }
+
+    fn read_key(&mut self) -> Result<Option<String>, String> {
+        let Some(first) = self.next_byte()? else {
+            return Ok(None);
+        };
+        let mut bytes = alloc::vec![first];
+        if first == 0x1b {
+            // Arrow keys arrive as ESC [ A..D
+            if let Some(second) = self.next_byte()? {
+                bytes.push(second);
+                if second == b'[' {
+                    if let Some(third) = self.next_byte()? {
+                        bytes.push(third);
+                    }
+                }
+            }
+        } else {
+            // The rest of a multi-byte character
+            let length = match first {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => 1,
+            };
+            while bytes.len() < length {
+                match self.next_byte()? {
+                    Some(byte) => bytes.push(byte),
+                    None => break,
+                }
+            }
+        }
+        Ok(Some(decode_key(&bytes, &mut self.pending)))
+    }
+}
+
+/// Key a script sees for the bytes of one ANSI key press; bytes that turn
+/// out not to belong to it go back to `pending`
+#[cfg(feature = "std")]
+pub(crate) fn decode_key(bytes: &[u8], pending: &mut alloc::vec::Vec<u8>) -> String {
+    let name = match bytes {
+        [0x1b, b'[', b'A'] => "up",
+        [0x1b, b'[', b'B'] => "down",
+        [0x1b, b'[', b'C'] => "right",
+        [0x1b, b'[', b'D'] => "left",
+        [b'\r'] | [b'\n'] => "enter",
+        [b'\t'] => "tab",
+        [0x7f] | [0x08] => "backspace",
+        [0x1b, rest @ ..] => {
+            pending.splice(0..0, rest.iter().copied());
+            "escape"
+        }
+        _ => return String::from_utf8_lossy(bytes).into_owned(),
+    };
+    String::from(name)
+}
+
+/// SGR sequence for a foreground and optional background color
+pub fn ansi_color(foreground: Color, background: Option<Color>) -> String {
+    let code = |color: Color, base: usize| match color as usize {
+        index @ 0..=7 => base + index,
+        index => base + 60 + index - 8,
+    };
+    match background {
+        Some(background) => format!("\x1b[{};{}m", code(foreground, 30), code(background, 40)),
+        None => format!("\x1b[{}m", code(foreground, 30)),
+    }
+}
+
+/// Text-mode display a kernel drives, for [`VgaTerminal`]
+pub trait VgaConsole: crate::sync::MaybeSend {
+    /// Move the hardware cursor, where the next character is written
+    fn set_cursor(&mut self, row: u32, column: u32);
+    /// Attribute byte (background in the high nibble) for new characters
+    fn set_attribute(&mut self, attribute: u8);
+    /// Fill the screen with blanks in the current attribute
+    fn clear(&mut self);
+    /// Next key from the keyboard driver, or `None` if it has gone away
+    fn read_key(&mut self) -> Option<String>;
+}
+
+/// [`Terminal`] over a VGA text-mode display
+pub struct VgaTerminal<C: VgaConsole> {
+    console: C,
+    background: Color,
+}
+
+impl<C: VgaConsole> VgaTerminal<C> {
+    /// Drive `console`, on a black background until a script sets one
+    pub fn new(console: C) -> Self {
+        VgaTerminal { console, background: Color::Black }
+    }
+}
+
+impl<C: VgaConsole> Terminal for VgaTerminal<C> {
+    fn move_to(&mut self, _out: &mut dyn OutputSink, row: u32, column: u32) -> Result<(), String> {
+        self.console.set_cursor(row, column);
+        Ok(())
+    }
+
+    fn set_color(&mut self, _out: &mut dyn OutputSink, foreground: Color, background: Option<Color>) -> Result<(), String> {
+        self.background = background.unwrap_or(self.background);
+        self.console.set_attribute(vga_attribute(foreground, self.background));
+        Ok(())
+    }
+
+    fn clear(&mut self, _out: &mut dyn OutputSink) -> Result<(), String> {
+        self.console.clear();
+        self.console.set_cursor(0, 0);
+        Ok(())
+    }
+
+    fn read_key(&mut self) -> Result<Option<String>, String> {
+        Ok(self.console.read_key())
+    }
+}
+
+/// VGA attribute byte for a color pair
+///
+/// The VGA palette orders blue and red the other way round from ANSI.
+/// Backgrounds use the low eight colors, since the top bit blinks.
+pub fn vga_attribute(foreground: Color, background: Color) -> u8 {
+    const VGA_INDEX: [u8; 8] = [0, 4, 2, 6, 1, 5, 3, 7];
+    let index = |color: Color| {
+        let ansi = color as usize;
+        VGA_INDEX[ansi % 8] | if ansi >= 8 { 8 } else { 0 }
+    };
+    (index(background) & 0x7) << 4 | index(foreground)
+}
+
+/// Default terminal for new evaluators: [`AnsiTerminal`] under std, none
+/// otherwise
+pub(crate) fn default_terminal() -> Option<alloc::boxed::Box<dyn Terminal>> {
+    #[cfg(feature = "std")]
+    {
+        Some(alloc::boxed::Box::new(AnsiTerminal::new()))
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ansi_sequences() {
+        assert_eq!(ansi_color(Color::Red, None), "\x1b[31m");
+        assert_eq!(ansi_color(Color::BrightWhite, Some(Color::Blue)), "\x1b[97;44m");
+        assert!(Color::ALL.iter().all(|color| Color::from_name(color.name()) == Some(*color)));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_decode_keys() {
+        let mut pending = alloc::vec::Vec::new();
+        assert_eq!(decode_key(&[0x1b, b'[', b'A'], &mut pending), "up");
+        assert_eq!(decode_key("é".as_bytes(), &mut pending), "é");
+        assert_eq!(decode_key(&[0x1b, b'q'], &mut pending), "escape");
+        assert_eq!(pending, [b'q']);
+    }
+}
@@ -0,0 +1,240 @@
+//! # Test Runner
+//!
+//! Runs the `verify` blocks embedded in a program's modules.
+//!
+//! Starting from entry scripts, the runner walks the module graph through
+//! the [`ModuleResolver`] and runs every verify block it finds. Each block
+//! runs in a fresh evaluator spawned from a [`Session`], with what it prints
+//! captured rather than written anywhere. A block runs after the code around
+//! it: a top-level block after the file's other statements, a block inside
+//! a grove after the grove's body, so it sees the grove's unexported chants.
+//!
+//! A block fails when it raises an error or ends with `false` or a Mishap.
+//!
+//! ```
+//! use glimmer_weave::test_runner::TestRunner;
+//! use glimmer_weave::ModuleResolver;
+//!
+//! let mut resolver = ModuleResolver::new("/app".to_string(), "/std".to_string());
+//! resolver.add_source("/app/math.gw", "grove Math with\n    chant double(x) then\n        x * 2\n    end\n    offer double\n\n    verify \"doubles\" then\n        double(4) is 8\n    end\nend");
+//! resolver.add_source("/app/main.gw", "summon Math from \"math.gw\"\nMath.double(1)");
+//!
+//! let report = TestRunner::new(resolver).run(&["/app/main.gw"]).unwrap();
+//! assert_eq!(report.modules[0].path, "/app/math.gw");
+//! assert_eq!((report.passed(), report.failed()), (1, 0));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::ast::AstNode;
+use crate::clock::Clock;
+use crate::eval::{RuntimeError, Value};
+use crate::module_resolver::{ModuleResolver, ResolverError};
+use crate::session::Session;
+use crate::sync::{lock, shared, Shared};
+
+/// How one verify block went
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyResult {
+    /// The block's name, as written after `verify`
+    pub name: String,
+    /// Why the block failed; `None` when it passed
+    pub failure: Option<String>,
+    /// Everything the block printed
+    pub output: String,
+}
+
+impl VerifyResult {
+    /// Whether the block passed
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// The verify blocks of one module
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleReport {
+    /// Canonical path of the module
+    pub path: String,
+    /// Each block's result, in source order
+    pub results: Vec<VerifyResult>,
+    /// Time the module's blocks took, in the runner clock's units
+    pub elapsed: u64,
+}
+
+/// Results of a test run, one report per module with verify blocks
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TestReport {
+    /// Modules in dependency order
+    pub modules: Vec<ModuleReport>,
+}
+
+impl TestReport {
+    /// Number of blocks that passed
+    pub fn passed(&self) -> usize {
+        self.results().filter(|result| result.passed()).count()
+    }
+
+    /// Number of blocks that failed
+    pub fn failed(&self) -> usize {
+        self.results().filter(|result| !result.passed()).count()
+    }
+
+    /// Whether every block passed
+    pub fn is_success(&self) -> bool {
+        self.failed() == 0
+    }
+
+    fn results(&self) -> impl Iterator<Item = &VerifyResult> {
+        self.modules.iter().flat_map(|module| module.results.iter())
+    }
+}
+
+/// Finds and runs the verify blocks of a module graph
+pub struct TestRunner {
+    session: Session,
+    clock: Box<dyn Clock>,
+}
+
+impl TestRunner {
+    /// Create a runner loading modules through `resolver`
+    ///
+    /// Timings are `SystemClock` milliseconds with std; without it, the
+    /// host sets a clock with `set_clock`, and until then elapsed times
+    /// count clock readings.
+    pub fn new(resolver: ModuleResolver) -> Self {
+        #[cfg(feature = "std")]
+        let clock: Box<dyn Clock> = Box::new(crate::clock::SystemClock);
+        #[cfg(not(feature = "std"))]
+        let clock: Box<dyn Clock> = Box::new(crate::clock::StepClock::new());
+        TestRunner { session: Session::new(resolver), clock }
+    }
+
+    /// Time modules with `clock`
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// The session blocks run in, e.g. to set a capability policy
+    pub fn session_mut(&mut self) -> &mut Session {
+        &mut self.session
+    }
+
+    /// Run the verify blocks of `entries` and every module they import
+    ///
+    /// A module reached from several entries runs once.
+    pub fn run(&mut self, entries: &[&str]) -> Result<TestReport, ResolverError> {
+        let mut paths: Vec<String> = Vec::new();
+        for entry in entries {
+            for path in self.session.resolver().load_graph(entry)? {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+        }
+
+        let mut report = TestReport::default();
+        for path in paths {
+            let Some(ast) = self.session.resolver().get_module(&path).map(|info| info.ast.clone()) else {
+                continue;
+            };
+            let programs = verify_programs(&ast);
+            if programs.is_empty() {
+                continue;
+            }
+
+            let start = self.clock.now();
+            let results = programs
+                .into_iter()
+                .map(|(name, program)| self.run_block(name, &program))
+                .collect();
+            let elapsed = self.clock.now().saturating_sub(start);
+            report.modules.push(ModuleReport { path, results, elapsed });
+        }
+        Ok(report)
+    }
+
+    /// Run one block's program in a fresh evaluator, capturing its output
+    fn run_block(&self, name: String, program: &[AstNode]) -> VerifyResult {
+        let output = shared(String::new());
+        let mut evaluator = self.session.evaluator();
+        let sink = Shared::clone(&output);
+        evaluator.set_output_sink(Box::new(move |text: &str| lock(&sink).push_str(text)));
+
+        let failure = block_failure(evaluator.eval(program));
+        let output = lock(&output).clone();
+        VerifyResult { name, failure, output }
+    }
+}
+
+/// Why a verify block that ran to `result` failed, or `None` if it passed
+pub(crate) fn block_failure(result: Result<Value, RuntimeError>) -> Option<String> {
+    match result {
+        Ok(Value::Truth(false)) => Some("ended with false".to_string()),
+        Ok(mishap @ Value::Outcome { success: false, .. }) => Some(alloc::format!(
+            "ended with {}",
+            crate::runtime::render_text(&mishap, &mut |_| None).unwrap_or_else(|_| "Mishap".to_string())
+        )),
+        Ok(_) => None,
+        Err(error) => Some(alloc::format!("{:?}", error)),
+    }
+}
+
+/// The program each verify block of a module runs as, in source order:
+/// the code around the block followed by the block's body
+pub fn verify_programs(ast: &[AstNode]) -> Vec<(String, Vec<AstNode>)> {
+    let outer: Vec<AstNode> = ast
+        .iter()
+        .filter(|node| !matches!(node, AstNode::VerifyBlock { .. }))
+        .cloned()
+        .collect();
+
+    let mut programs = Vec::new();
+    for node in ast {
+        match node {
+            AstNode::VerifyBlock { name, body, .. } => {
+                programs.push((name.clone(), [outer.as_slice(), body.as_slice()].concat()));
+            }
+            AstNode::ModuleDecl { body: grove, .. } => {
+                // The grove's body runs at the top level, where the block
+                // sees everything the grove defines
+                let setup: Vec<AstNode> = outer
+                    .iter()
+                    .filter(|node| !matches!(node, AstNode::ModuleDecl { .. }))
+                    .chain(grove.iter().filter(|node| {
+                        !matches!(node, AstNode::VerifyBlock { .. } | AstNode::Export { .. })
+                    }))
+                    .cloned()
+                    .collect();
+                for node in grove {
+                    if let AstNode::VerifyBlock { name, body, .. } = node {
+                        programs.push((name.clone(), [setup.as_slice(), body.as_slice()].concat()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    programs
+}
+
+/// Paths of the `.gw` scripts under `dir`, recursively and sorted
+#[cfg(feature = "std")]
+pub fn discover(dir: &str) -> std::io::Result<Vec<String>> {
+    let mut scripts = Vec::new();
+    let mut pending = vec![std::path::PathBuf::from(dir)];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|extension| extension == "gw") {
+                scripts.push(path.to_string_lossy().into_owned());
+            }
+        }
+    }
+    scripts.sort();
+    Ok(scripts)
+}
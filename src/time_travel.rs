@@ -0,0 +1,306 @@
+//! # Time-Travel Debugging
+//!
+//! While an evaluator records (see `Evaluator::start_recording`), every
+//! statement it finishes becomes a [`Step`]: where the statement is, the
+//! chant it ran in, how the bindings in scope changed and the error it
+//! failed with, if any. Only the changes are kept, so the bindings as of any
+//! step are rebuilt from the bindings at the start and the changes since.
+//!
+//! A [`Debugger`] walks a [`Recording`] in either direction, shows the
+//! value a binding had at the current step, and jumps back to the step
+//! where a binding last changed — typically from the step that failed.
+//!
+//! ```
+//! use glimmer_weave::time_travel::{Debugger, Recording};
+//! use glimmer_weave::{Evaluator, Lexer, Parser, Value};
+//!
+//! let source = "weave total as 0\nfor each n in [1, 2, 3] then\n    set total to total + n\nend\nbind done to total\n";
+//! let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap();
+//! let mut evaluator = Evaluator::new();
+//! evaluator.start_recording(Recording::new());
+//! evaluator.eval(&ast).unwrap();
+//! let recording = evaluator.take_recording().unwrap();
+//!
+//! let mut debugger = Debugger::new(&recording);
+//! assert_eq!(debugger.value("done"), Some(Value::Number(6.0)));
+//! let step = debugger.jump_to_last_change("total").unwrap();
+//! assert_eq!(step.span.start.line, 3);
+//! debugger.step_back();
+//! assert_eq!(debugger.value("total"), Some(Value::Number(3.0)));
+//! ```
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::eval::{RuntimeError, Value};
+use crate::source_location::SourceSpan;
+
+/// Steps kept by default; older ones are merged into the starting bindings
+pub const DEFAULT_MAX_STEPS: usize = 100_000;
+
+/// How one binding changed during a step
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub name: String,
+    /// Value before the step, `None` if the name was not in scope
+    pub before: Option<Value>,
+    /// Value after the step, `None` if the name went out of scope
+    pub after: Option<Value>,
+}
+
+/// A statement the evaluator finished
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    /// Position in the whole recording, counting dropped steps
+    pub index: usize,
+    /// Kind of statement, as named by `AstNode::kind`
+    pub kind: &'static str,
+    pub span: SourceSpan,
+    /// Chant the statement ran in (`None` at the top level)
+    pub chant: Option<String>,
+    /// Bindings in scope that changed, in name order
+    pub changes: Vec<Change>,
+    /// Error the statement failed with; it is repeated on the enclosing
+    /// statements it passes through
+    pub error: Option<RuntimeError>,
+}
+
+impl Step {
+    /// Whether the step changed `name`
+    pub fn changed(&self, name: &str) -> bool {
+        self.changes.iter().any(|change| change.name == name)
+    }
+}
+
+/// Steps of an evaluation, with the bindings they changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    /// Bindings before the oldest kept step
+    baseline: BTreeMap<String, Value>,
+    steps: VecDeque<Step>,
+    /// Bindings after the newest step
+    latest: BTreeMap<String, Value>,
+    max_steps: usize,
+    next_index: usize,
+}
+
+impl Default for Recording {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Recording {
+    /// Recording that keeps the last [`DEFAULT_MAX_STEPS`] steps
+    pub fn new() -> Self {
+        Self::with_max_steps(DEFAULT_MAX_STEPS)
+    }
+
+    /// Recording that keeps the last `max_steps` steps (at least one)
+    pub fn with_max_steps(max_steps: usize) -> Self {
+        Recording {
+            baseline: BTreeMap::new(),
+            steps: VecDeque::new(),
+            latest: BTreeMap::new(),
+            max_steps: max_steps.max(1),
+            next_index: 0,
+        }
+    }
+
+    /// Number of steps kept
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Whether no step was recorded
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Kept steps, oldest first
+    pub fn steps(&self) -> impl Iterator<Item = &Step> {
+        self.steps.iter()
+    }
+
+    /// Step with the given index, if kept
+    pub fn step(&self, index: usize) -> Option<&Step> {
+        let first = self.steps.front()?.index;
+        self.steps.get(index.checked_sub(first)?)
+    }
+
+    /// Value `name` had after step `index`
+    pub fn value_at(&self, index: usize, name: &str) -> Option<Value> {
+        match self.last_change(name, index) {
+            Some(step) => step.changes.iter().find(|change| change.name == name)?.after.clone(),
+            None => self.baseline.get(name).cloned(),
+        }
+    }
+
+    /// Every binding in scope after step `index`
+    pub fn bindings_at(&self, index: usize) -> BTreeMap<String, Value> {
+        let mut bindings = self.baseline.clone();
+        for step in self.steps.iter().take_while(|step| step.index <= index) {
+            apply(&mut bindings, &step.changes);
+        }
+        bindings
+    }
+
+    /// Newest step at or before `index` that changed `name`
+    pub fn last_change(&self, name: &str, index: usize) -> Option<&Step> {
+        self.steps.iter().rev().skip_while(|step| step.index > index).find(|step| step.changed(name))
+    }
+
+    /// Oldest step that failed
+    pub fn first_failure(&self) -> Option<&Step> {
+        self.steps.iter().find(|step| step.error.is_some())
+    }
+
+    /// Start from the bindings in scope when recording begins
+    pub(crate) fn begin<'a>(&mut self, bindings: impl IntoIterator<Item = (&'a str, &'a Value)>) {
+        self.latest = bindings.into_iter().map(|(name, value)| (name.into(), value.clone())).collect();
+        if self.steps.is_empty() {
+            self.baseline = self.latest.clone();
+        }
+    }
+
+    /// Add a step, given the bindings in scope after it
+    pub(crate) fn record(
+        &mut self,
+        kind: &'static str,
+        span: SourceSpan,
+        chant: Option<String>,
+        bindings: &BTreeMap<&str, &Value>,
+        error: Option<RuntimeError>,
+    ) {
+        let mut changes = Vec::new();
+        for (name, before) in &self.latest {
+            if !bindings.contains_key(name.as_str()) {
+                changes.push(Change { name: name.clone(), before: Some(before.clone()), after: None });
+            }
+        }
+        for (&name, &value) in bindings {
+            let before = self.latest.get(name);
+            if before != Some(value) {
+                changes.push(Change { name: name.into(), before: before.cloned(), after: Some(value.clone()) });
+            }
+        }
+        changes.sort_by(|a, b| a.name.cmp(&b.name));
+        apply(&mut self.latest, &changes);
+
+        if self.steps.len() == self.max_steps {
+            if let Some(oldest) = self.steps.pop_front() {
+                apply(&mut self.baseline, &oldest.changes);
+            }
+        }
+        self.steps.push_back(Step { index: self.next_index, kind, span, chant, changes, error });
+        self.next_index += 1;
+    }
+}
+
+fn apply(bindings: &mut BTreeMap<String, Value>, changes: &[Change]) {
+    for change in changes {
+        match &change.after {
+            Some(value) => {
+                bindings.insert(change.name.clone(), value.clone());
+            }
+            None => {
+                bindings.remove(&change.name);
+            }
+        }
+    }
+}
+
+/// Cursor over a recording that steps in both directions
+#[derive(Debug, Clone)]
+pub struct Debugger<'a> {
+    recording: &'a Recording,
+    /// Offset of the current step among the kept steps
+    position: usize,
+}
+
+impl<'a> Debugger<'a> {
+    /// Debugger at the last step
+    pub fn new(recording: &'a Recording) -> Self {
+        Debugger { recording, position: recording.len().saturating_sub(1) }
+    }
+
+    /// The current step
+    pub fn current(&self) -> Option<&'a Step> {
+        self.recording.steps.get(self.position)
+    }
+
+    /// Move to the previous step; `None` at the first
+    pub fn step_back(&mut self) -> Option<&'a Step> {
+        self.position = self.position.checked_sub(1)?;
+        self.current()
+    }
+
+    /// Move to the next step; `None` at the last
+    pub fn step_forward(&mut self) -> Option<&'a Step> {
+        if self.position + 1 >= self.recording.len() {
+            return None;
+        }
+        self.position += 1;
+        self.current()
+    }
+
+    /// Move to the step with the given index, if kept
+    pub fn jump_to(&mut self, index: usize) -> Option<&'a Step> {
+        let step = self.recording.step(index)?;
+        self.position = index - self.recording.steps.front()?.index;
+        Some(step)
+    }
+
+    /// Move to the newest step, at or before the current one, that changed
+    /// `name`
+    pub fn jump_to_last_change(&mut self, name: &str) -> Option<&'a Step> {
+        let index = self.recording.last_change(name, self.current()?.index)?.index;
+        self.jump_to(index)
+    }
+
+    /// Move to the first step that failed
+    pub fn jump_to_failure(&mut self) -> Option<&'a Step> {
+        let index = self.recording.first_failure()?.index;
+        self.jump_to(index)
+    }
+
+    /// Value `name` had after the current step
+    pub fn value(&self, name: &str) -> Option<Value> {
+        self.recording.value_at(self.current()?.index, name)
+    }
+
+    /// Every binding in scope after the current step
+    pub fn bindings(&self) -> BTreeMap<String, Value> {
+        match self.current() {
+            Some(step) => self.recording.bindings_at(step.index),
+            None => self.recording.baseline.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(recording: &mut Recording, bindings: &[(&str, f64)]) {
+        let values: Vec<(&str, Value)> = bindings.iter().map(|&(name, n)| (name, Value::Number(n))).collect();
+        let bindings: BTreeMap<&str, &Value> = values.iter().map(|(name, value)| (*name, value)).collect();
+        recording.record("ExprStmt", SourceSpan::unknown(), None, &bindings, None);
+    }
+
+    #[test]
+    fn test_dropped_steps_fold_into_the_baseline() {
+        let mut recording = Recording::with_max_steps(2);
+        step(&mut recording, &[("a", 1.0)]);
+        step(&mut recording, &[("a", 2.0), ("b", 1.0)]);
+        step(&mut recording, &[("b", 1.0)]);
+        assert_eq!(recording.len(), 2);
+        assert!(recording.step(0).is_none());
+        assert_eq!(recording.value_at(1, "a"), Some(Value::Number(2.0)));
+        assert_eq!(recording.value_at(2, "a"), None);
+        assert_eq!(recording.bindings_at(0).get("a"), Some(&Value::Number(1.0)));
+        assert_eq!(recording.last_change("a", 2).map(|step| step.index), Some(2));
+        assert_eq!(recording.last_change("b", 2).map(|step| step.index), Some(1));
+    }
+}
@@ -6,6 +6,7 @@
 //! `should`, `chant`, and `seek` to create a readable scripting experience.
 
 use alloc::string::String;
+use crate::bigint::BigInt;
 
 /// Position information for error reporting
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -147,6 +148,8 @@ pub enum Token {
     Harmonize,
     /// `on` - Error type matcher
     On,
+    /// `defer` - Cleanup block run when the enclosing chant finishes
+    Defer,
 
     /// `match` - Pattern matching
     Match,
@@ -182,6 +185,10 @@ pub enum Token {
     // === Literals ===
     /// Numeric literal (integer or float)
     Number(f64),
+    /// Big integer literal (`123n`)
+    BigInt(BigInt),
+    /// 64-bit integer literal (`42i`)
+    Integer(i64),
     /// String literal
     Text(String),
     /// Boolean literal (`true` or `false`)
@@ -266,6 +273,8 @@ pub enum Token {
     Newline,
     /// End of file
     Eof,
+    /// Text the lexer could not read, with the reason
+    Invalid(String),
 }
 
 impl Token {
@@ -309,6 +318,7 @@ impl Token {
                 | Token::Attempt
                 | Token::Harmonize
                 | Token::On
+                | Token::Defer
                 | Token::Match
                 | Token::When
                 | Token::With
@@ -344,6 +354,7 @@ impl Token {
                 | Token::Offer
                 | Token::Seek
                 | Token::Attempt
+                | Token::Defer
                 | Token::Match
                 | Token::Request
                 | Token::Ident(_)
@@ -393,6 +404,7 @@ impl Token {
             Token::Attempt => "attempt",
             Token::Harmonize => "harmonize",
             Token::On => "on",
+            Token::Defer => "defer",
             Token::Match => "match",
             Token::When => "when",
             Token::With => "with",
@@ -407,6 +419,8 @@ impl Token {
             Token::Descending => "descending",
             Token::Ascending => "ascending",
             Token::Number(_) => "number",
+            Token::BigInt(_) => "big integer",
+            Token::Integer(_) => "integer",
             Token::Text(_) => "text",
             Token::Truth(_) => "truth",
             Token::Nothing => "nothing",
@@ -443,6 +457,7 @@ impl Token {
             Token::Question => "?",
             Token::Newline => "newline",
             Token::Eof => "end of file",
+            Token::Invalid(_) => "invalid token",
         }
     }
 }
@@ -114,6 +114,12 @@ impl ConstraintGenerator {
                 }
             }
 
+            // Tuples: each element keeps its own type
+            AstNode::Tuple { elements, .. } => InferType::Generic {
+                name: "Tuple".to_string(),
+                args: elements.iter().map(|elem| self.infer_expr(elem)).collect(),
+            },
+
             // Binary operations
             AstNode::BinaryOp { op, left, right, .. } => {
                 use crate::ast::BinaryOperator;
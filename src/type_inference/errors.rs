@@ -13,6 +13,7 @@
 use crate::type_inference::{InferType, TypeVar};
 use crate::type_inference::requirement::SourceLocation;
 use core::fmt;
+use alloc::boxed::Box;
 use alloc::string::String;
 
 /// Type errors that can occur during inference
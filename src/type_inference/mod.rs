@@ -1,43 +1,45 @@
-/// Type Inference Module
-///
-/// Implements Hindley-Milner type inference with natural language branding.
-///
-/// ## Overview
-///
-/// This module provides automatic type inference for Glimmer-Weave, allowing
-/// developers to write code without explicit type annotations while maintaining
-/// full type safety.
-///
-/// ## Key Components
-///
-/// - **TypeVar** - Type variables (α, β, γ) representing unknown types
-/// - **InferType** - Extended type representation for inference
-/// - **Requirement** - Type equations that must be satisfied
-/// - **Harmonizer** - Unification algorithm (harmonize types)
-/// - **ConstraintGenerator** - Generate type requirements from AST
-///
-/// ## Natural Language Naming
-///
-/// Following Glimmer-Weave's philosophy:
-/// - `harmonize` - Unify types (find common type)
-/// - `materialize` - Apply substitutions (make concrete)
-/// - `abstract` - Generalize (create polymorphic types)
-/// - `specialize` - Instantiate (create fresh type variables)
-///
-/// ## Usage
-///
-/// ```rust,no_run
-/// use glimmer_weave::type_inference::TypeInference;
-/// use glimmer_weave::ast::AstNode;
-/// use glimmer_weave::type_inference::TypeError;
-///
-/// fn example() -> Result<(), TypeError> {
-///     let mut inference = TypeInference::new();
-///     let ast = vec![AstNode::Number(42.0)];
-///     let typed_ast = inference.infer_types(&ast)?;
-///     Ok(())
-/// }
-/// ```
+//! Type Inference Module
+//!
+//! Implements Hindley-Milner type inference with natural language branding.
+//!
+//! ## Overview
+//!
+//! This module provides automatic type inference for Glimmer-Weave, allowing
+//! developers to write code without explicit type annotations while maintaining
+//! full type safety.
+//!
+//! ## Key Components
+//!
+//! - **TypeVar** - Type variables (α, β, γ) representing unknown types
+//! - **InferType** - Extended type representation for inference
+//! - **Requirement** - Type equations that must be satisfied
+//! - **Harmonizer** - Unification algorithm (harmonize types)
+//! - **ConstraintGenerator** - Generate type requirements from AST
+//!
+//! ## Natural Language Naming
+//!
+//! Following Glimmer-Weave's philosophy:
+//! - `harmonize` - Unify types (find common type)
+//! - `materialize` - Apply substitutions (make concrete)
+//! - `abstract` - Generalize (create polymorphic types)
+//! - `specialize` - Instantiate (create fresh type variables)
+//!
+//! ## Usage
+//!
+//! ```rust,no_run
+//! use glimmer_weave::type_inference::TypeInference;
+//! use glimmer_weave::ast::AstNode;
+//! use glimmer_weave::source_location::SourceSpan;
+//! use glimmer_weave::type_inference::TypeError;
+//!
+//! fn example() -> Result<(), TypeError> {
+//!     let mut inference = TypeInference::new();
+//!     let ast = vec![AstNode::Number { value: 42.0, span: SourceSpan::default() }];
+//!     let typed_ast = inference.infer_types(&ast)?;
+//!     Ok(())
+//! }
+//! ```
+
 pub mod type_var;
 pub mod infer_type;
 pub mod requirement;
@@ -55,8 +57,9 @@ pub use scheme::TypeScheme;
 pub use errors::TypeError;
 
 use crate::ast::AstNode;
+use alloc::boxed::Box;
 use alloc::collections::{BTreeMap, BTreeSet};
-use alloc::string::String;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
 /// Main type inference engine
@@ -205,6 +208,12 @@ impl TypeInference {
         match node {
             // Literals have known types
             AstNode::Number { .. } => Ok(Type::Number),
+            AstNode::BigInt { .. } => Ok(Type::BigInt),
+            AstNode::Integer { .. } => Ok(Type::Integer),
+            AstNode::Measure { unit, .. } => {
+                let unit = crate::units::unit(unit).ok_or_else(|| format!("Unknown unit '{}'", unit))?;
+                Ok(Type::Measure(unit.dimension))
+            }
             AstNode::Text { .. } => Ok(Type::Text),
             AstNode::Truth { .. } => Ok(Type::Truth),
             AstNode::Nothing { .. } => Ok(Type::Nothing),
@@ -226,6 +235,13 @@ impl TypeInference {
                 let left_ty = self.generate_constraints_internal(left, constraints, environment)?;
                 let right_ty = self.generate_constraints_internal(right, constraints, environment)?;
 
+                if let Some(result) = measure_constraints(*op, &left_ty, &right_ty, constraints) {
+                    return Ok(result);
+                }
+                if let Some(result) = whole_constraints(*op, &left_ty, &right_ty, constraints) {
+                    return Ok(result);
+                }
+
                 match op {
                     BinaryOperator::Add | BinaryOperator::Sub |
                     BinaryOperator::Mul | BinaryOperator::Div | BinaryOperator::Mod => {
@@ -255,6 +271,7 @@ impl TypeInference {
             AstNode::UnaryOp { op, operand, .. } => {
                 let expr_ty = self.generate_constraints_internal(operand, constraints, environment)?;
                 match op {
+                    UnaryOperator::Negate if matches!(expr_ty, Type::Measure(_) | Type::BigInt | Type::Integer) => Ok(expr_ty),
                     UnaryOperator::Negate => {
                         constraints.push((expr_ty, Type::Number));
                         Ok(Type::Number)
@@ -266,6 +283,14 @@ impl TypeInference {
                 }
             }
 
+            // Unit conversion: the value must be a measure of the unit's dimension
+            AstNode::InUnit { value, unit, .. } => {
+                let value_ty = self.generate_constraints_internal(value, constraints, environment)?;
+                let unit = crate::units::unit(unit).ok_or_else(|| format!("Unknown unit '{}'", unit))?;
+                constraints.push((value_ty, Type::Measure(unit.dimension)));
+                Ok(Type::Number)
+            }
+
             // Variable binding
             AstNode::BindStmt { name, value, typ: _, .. } => {
                 let value_ty = self.generate_constraints_internal(value, constraints, environment)?;
@@ -314,6 +339,15 @@ impl TypeInference {
                 }
             }
 
+            // Tuples: each element keeps its own type
+            AstNode::Tuple { elements, .. } => {
+                let types = elements
+                    .iter()
+                    .map(|elem| self.generate_constraints_internal(elem, constraints, environment))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Type::Tuple(types))
+            }
+
             // Blocks
             AstNode::Block { statements, .. } => {
                 let mut last_ty = Type::Nothing;
@@ -394,13 +428,24 @@ impl TypeInference {
                 self.unify_internal(*elem1, *elem2, substitutions)
             }
 
+            // Tuple types: unify element types pairwise
+            (Type::Tuple(elems1), Type::Tuple(elems2)) => {
+                if elems1.len() != elems2.len() {
+                    return Err(format!("Tuple length mismatch: {} vs {}", elems1.len(), elems2.len()));
+                }
+                for (e1, e2) in elems1.into_iter().zip(elems2) {
+                    self.unify_internal(e1, e2, substitutions)?;
+                }
+                Ok(())
+            }
+
             // Function types
             (Type::Function { params: params1, return_type: ret1 },
              Type::Function { params: params2, return_type: ret2 }) => {
                 if params1.len() != params2.len() {
                     return Err(format!("Function arity mismatch: {} vs {}", params1.len(), params2.len()));
                 }
-                for (p1, p2) in params1.into_iter().zip(params2.into_iter()) {
+                for (p1, p2) in params1.into_iter().zip(params2) {
                     self.unify_internal(p1, p2, substitutions)?;
                 }
                 self.unify_internal(*ret1, *ret2, substitutions)
@@ -421,6 +466,7 @@ impl TypeInference {
         match ty {
             Type::TypeParam(v) => v == var,
             Type::List(elem) => self.occurs_check_internal(var, elem),
+            Type::Tuple(elems) => elems.iter().any(|e| self.occurs_check_internal(var, e)),
             Type::Function { params, return_type } => {
                 params.iter().any(|p| self.occurs_check_internal(var, p)) ||
                 self.occurs_check_internal(var, return_type)
@@ -449,6 +495,9 @@ impl TypeInference {
             Type::List(elem) => {
                 Type::List(Box::new(self.apply_substitution_internal(elem, substitutions)))
             }
+            Type::Tuple(elems) => {
+                Type::Tuple(elems.iter().map(|e| self.apply_substitution_internal(e, substitutions)).collect())
+            }
             Type::Function { params, return_type } => {
                 Type::Function {
                     params: params.iter().map(|p| self.apply_substitution_internal(p, substitutions)).collect(),
@@ -460,6 +509,75 @@ impl TypeInference {
     }
 }
 
+/// Requirements and result type of an operator with a measure on either
+/// side, or `None` when neither operand is a measure
+///
+/// Sums, differences, remainders and comparisons need one dimension on
+/// both sides; products and quotients scale a measure by a Number, and the
+/// quotient of two measures of one dimension is a Number.
+fn measure_constraints(
+    op: crate::ast::BinaryOperator,
+    left: &crate::semantic::Type,
+    right: &crate::semantic::Type,
+    constraints: &mut Vec<(crate::semantic::Type, crate::semantic::Type)>,
+) -> Option<crate::semantic::Type> {
+    use crate::ast::BinaryOperator::*;
+    use crate::semantic::Type;
+
+    let measure = [left, right].into_iter().find(|ty| matches!(ty, Type::Measure(_)))?.clone();
+    let result = match op {
+        And | Or => return None,
+        Add | Sub | Mod => {
+            constraints.push((left.clone(), right.clone()));
+            measure
+        }
+        Equal | NotEqual | Less | LessEq | Greater | GreaterEq => {
+            constraints.push((left.clone(), right.clone()));
+            Type::Truth
+        }
+        Div if matches!((left, right), (Type::Measure(_), Type::Measure(_))) => {
+            constraints.push((left.clone(), right.clone()));
+            Type::Number
+        }
+        Mul if matches!(right, Type::Measure(_)) => {
+            constraints.push((left.clone(), Type::Number));
+            measure
+        }
+        // A measure on the left scales by a Number on the right; a measure
+        // on the right of a division fails to be that Number
+        Mul | Div => {
+            constraints.push((right.clone(), Type::Number));
+            measure
+        }
+    };
+    Some(result)
+}
+
+/// Requirements and result type of an arithmetic or ordering operator with
+/// a big integer or an integer on either side, or `None` when neither
+/// operand is one
+///
+/// Both sides must be the same whole type; a Number never mixes in implicitly.
+fn whole_constraints(
+    op: crate::ast::BinaryOperator,
+    left: &crate::semantic::Type,
+    right: &crate::semantic::Type,
+    constraints: &mut Vec<(crate::semantic::Type, crate::semantic::Type)>,
+) -> Option<crate::semantic::Type> {
+    use crate::ast::BinaryOperator::*;
+    use crate::semantic::Type;
+
+    let whole = [Type::BigInt, Type::Integer].into_iter().find(|whole| left == whole || right == whole)?;
+    let result = match op {
+        And | Or | Equal | NotEqual => return None,
+        Add | Sub | Mul | Div | Mod => whole.clone(),
+        Less | LessEq | Greater | GreaterEq => Type::Truth,
+    };
+    constraints.push((left.clone(), whole.clone()));
+    constraints.push((right.clone(), whole));
+    Some(result)
+}
+
 impl Default for TypeInference {
     fn default() -> Self {
         Self::new()
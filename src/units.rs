@@ -0,0 +1,113 @@
+//! # Units of Measure
+//!
+//! A number literal can carry a unit, as in `bind delay to 10 ms` or
+//! `bind page to 4 KiB`. Each unit belongs to a [`Dimension`]. At run time
+//! a measure is a plain Number in its dimension's base unit: nanoseconds
+//! for time, bytes for sizes. So `10 ms` and `0.01 s` are the same value
+//! and add up correctly. `delay in s` reads a measure back as a plain
+//! Number in the given unit.
+//!
+//! The semantic analyzer, and type inference when it is enabled, give a
+//! measure the type of its dimension (`Time` or `Size`):
+//!
+//! - adding, subtracting or comparing a time and a size is an error, and
+//!   so is mixing a measure with a plain Number;
+//! - scaling a measure by a plain Number keeps its dimension;
+//! - dividing two measures of one dimension gives a plain ratio;
+//! - `in` only converts a measure of the unit's own dimension.
+//!
+//! A plain Number never becomes a measure implicitly, and a measure only
+//! becomes a plain Number through `in`.
+//!
+//! ```
+//! use glimmer_weave::units::{unit, Dimension};
+//!
+//! let ms = unit("ms").unwrap();
+//! assert_eq!(ms.dimension, Dimension::Time);
+//! assert_eq!(ms.factor, 1_000_000.0);
+//! assert!(unit("parsecs").is_none());
+//! ```
+
+/// What a unit measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Dimension {
+    /// Durations, stored in nanoseconds
+    Time,
+    /// Data sizes, stored in bytes
+    Size,
+}
+
+impl Dimension {
+    /// Name of the dimension as a type (`Time`, `Size`)
+    pub fn name(self) -> &'static str {
+        match self {
+            Dimension::Time => "Time",
+            Dimension::Size => "Size",
+        }
+    }
+}
+
+/// A unit a number literal can carry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unit {
+    pub name: &'static str,
+    pub dimension: Dimension,
+    /// How many base units (nanoseconds or bytes) one of this unit is
+    pub factor: f64,
+}
+
+impl Unit {
+    /// `value` of this unit, in base units
+    pub fn to_base(self, value: f64) -> f64 {
+        value * self.factor
+    }
+
+    /// `value` base units, in this unit
+    pub fn from_base(self, value: f64) -> f64 {
+        value / self.factor
+    }
+}
+
+const fn time(name: &'static str, factor: f64) -> Unit {
+    Unit { name, dimension: Dimension::Time, factor }
+}
+
+const fn size(name: &'static str, factor: f64) -> Unit {
+    Unit { name, dimension: Dimension::Size, factor }
+}
+
+/// Every unit the language knows
+pub const UNITS: &[Unit] = &[
+    time("ns", 1.0),
+    time("us", 1e3),
+    time("ms", 1e6),
+    time("s", 1e9),
+    time("min", 60e9),
+    time("h", 3600e9),
+    size("B", 1.0),
+    size("KB", 1e3),
+    size("MB", 1e6),
+    size("GB", 1e9),
+    size("KiB", 1024.0),
+    size("MiB", 1_048_576.0),
+    size("GiB", 1_073_741_824.0),
+];
+
+/// Look up a unit by name
+pub fn unit(name: &str) -> Option<Unit> {
+    UNITS.iter().find(|unit| unit.name == name).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_factors_are_whole_base_units() {
+        for unit in UNITS {
+            assert_eq!(unit.factor.fract(), 0.0, "{}", unit.name);
+        }
+        assert_eq!(unit("h").map(|h| h.factor), Some(3_600_000_000_000.0));
+        assert_eq!(unit("GiB").map(|gib| gib.dimension), Some(Dimension::Size));
+    }
+}
@@ -0,0 +1,471 @@
+//! # Value Codec
+//!
+//! Compact binary encoding of script values, for handing structured state
+//! between scripts and the host (for example over AethelOS IPC channels).
+//! Scripts reach it through `value_encode(value)`, which returns the bytes
+//! as a list of numbers from 0 to 255, and `value_decode(bytes)`.
+//!
+//! Plain data encodes: numbers, text, truths, `nothing`, lists, maps, form
+//! instances, variant values, `Triumph`/`Mishap`, `Present`/`Absent` and
+//! ranges. Chants, capabilities, iterators, resources and the other runtime
+//! handles do not; a capability in particular can never be forged by
+//! decoding bytes. Decoded forms and variants are not checked against any
+//! declaration.
+//!
+//! ## Layout
+//!
+//! A header of [`MAGIC`] and the one-byte [`VERSION`], then one value.
+//! Every value starts with a tag byte. Counts and lengths are unsigned
+//! LEB128 varints and text is a length followed by UTF-8.
+//!
+//! | Tag | Value                | Payload                                     |
+//! |-----|----------------------|---------------------------------------------|
+//! | 0   | `nothing`            |                                             |
+//! | 1   | `false`              |                                             |
+//! | 2   | `true`               |                                             |
+//! | 3   | integral Number      | zigzag varint                               |
+//! | 4   | other Number         | `f64`, little-endian                        |
+//! | 5   | Text                 | text                                        |
+//! | 6   | List                 | count, then the items                       |
+//! | 7   | Map                  | count, then a text key and value each       |
+//! | 8   | form instance        | form name, then a Map payload               |
+//! | 9   | variant value        | enum name, case name, fields as a List payload, type argument count and names |
+//! | 10  | `Triumph`            | value                                       |
+//! | 11  | `Mishap`             | value                                       |
+//! | 12  | `Present`            | value                                       |
+//! | 13  | `Absent`             |                                             |
+//! | 14  | Range                | start and end values                        |
+//! | 15  | BigInt               | decimal digits as text, `-` first if negative |
+//! | 16  | Tuple                | count, then the elements                    |
+//! | 17  | Set                  | count, then the elements in order           |
+//! | 18  | Integer              | zigzag varint                               |
+//!
+//! ```
+//! use glimmer_weave::value_codec::{decode, encode};
+//! use glimmer_weave::Value;
+//!
+//! let value = Value::List(vec![Value::Number(7.0), Value::Text("seven".to_string())]);
+//! let bytes = encode(&value).unwrap();
+//! assert_eq!(bytes.len(), 15);
+//! assert_eq!(decode(&bytes), Ok(value));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::bigint::BigInt;
+use crate::eval::{SetElement, Value};
+
+/// Magic bytes every encoded value starts with
+pub const MAGIC: &[u8; 3] = b"GWV";
+
+/// Encoding version written after [`MAGIC`]
+pub const VERSION: u8 = 1;
+
+/// Deepest nesting of lists, maps, forms and variants either direction accepts
+pub const MAX_DEPTH: usize = 256;
+
+/// Why a value could not be encoded or decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodecError {
+    /// A value of this type has no encoding
+    Unencodable(String),
+    /// Values nest deeper than [`MAX_DEPTH`]
+    TooDeep,
+    /// The data does not start with [`MAGIC`]
+    BadMagic,
+    /// The data was written by a newer encoding version
+    UnsupportedVersion(u8),
+    /// The data ends in the middle of a value
+    Truncated,
+    /// A value uses a tag this build does not know
+    UnknownTag(u8),
+    /// A text field is not valid UTF-8
+    InvalidText,
+    /// A big integer is not decimal digits
+    InvalidBigInt,
+    /// A set holds a value that cannot be a set element
+    InvalidSetElement,
+    /// A varint does not fit in 64 bits
+    Overflow,
+    /// Bytes are left over after the value
+    TrailingData,
+}
+
+impl core::fmt::Display for CodecError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CodecError::Unencodable(type_name) => write!(f, "{} values cannot be encoded", type_name),
+            CodecError::TooDeep => write!(f, "values nest deeper than {} levels", MAX_DEPTH),
+            CodecError::BadMagic => write!(f, "not an encoded value"),
+            CodecError::UnsupportedVersion(version) => write!(f, "unsupported encoding version {}", version),
+            CodecError::Truncated => write!(f, "encoded value is truncated"),
+            CodecError::UnknownTag(tag) => write!(f, "unknown value tag {}", tag),
+            CodecError::InvalidText => write!(f, "text is not valid UTF-8"),
+            CodecError::InvalidBigInt => write!(f, "big integer is not decimal digits"),
+            CodecError::InvalidSetElement => write!(f, "set element is not a number, text, truth or nothing"),
+            CodecError::Overflow => write!(f, "length does not fit in 64 bits"),
+            CodecError::TrailingData => write!(f, "unexpected bytes after the value"),
+        }
+    }
+}
+
+/// Largest integer magnitude stored as a zigzag varint (2^53, past which
+/// an f64 no longer holds every integer)
+const MAX_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Encode a value, header included
+pub fn encode(value: &Value) -> Result<Vec<u8>, CodecError> {
+    let mut out = Writer(Vec::new());
+    out.0.extend_from_slice(MAGIC);
+    out.0.push(VERSION);
+    out.value(value, 0)?;
+    Ok(out.0)
+}
+
+/// Decode a value written by [`encode`]
+pub fn decode(bytes: &[u8]) -> Result<Value, CodecError> {
+    let mut input = Reader(bytes);
+    if input.take(MAGIC.len()).map_err(|_| CodecError::BadMagic)? != MAGIC {
+        return Err(CodecError::BadMagic);
+    }
+    let version = input.u8()?;
+    if version > VERSION {
+        return Err(CodecError::UnsupportedVersion(version));
+    }
+    let value = input.value(0)?;
+    if !input.0.is_empty() {
+        return Err(CodecError::TrailingData);
+    }
+    Ok(value)
+}
+
+/// Output buffer
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    /// A signed integer as a varint, small magnitudes of either sign short
+    fn zigzag(&mut self, value: i64) {
+        self.varint(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    fn text(&mut self, text: &str) {
+        self.varint(text.len() as u64);
+        self.0.extend_from_slice(text.as_bytes());
+    }
+
+    fn entries(&mut self, entries: &BTreeMap<String, Value>, depth: usize) -> Result<(), CodecError> {
+        self.varint(entries.len() as u64);
+        for (key, value) in entries {
+            self.text(key);
+            self.value(value, depth)?;
+        }
+        Ok(())
+    }
+
+    fn items(&mut self, items: &[Value], depth: usize) -> Result<(), CodecError> {
+        self.varint(items.len() as u64);
+        items.iter().try_for_each(|item| self.value(item, depth))
+    }
+
+    fn value(&mut self, value: &Value, depth: usize) -> Result<(), CodecError> {
+        if depth == MAX_DEPTH {
+            return Err(CodecError::TooDeep);
+        }
+        let depth = depth + 1;
+        match value {
+            Value::Nothing => self.0.push(0),
+            Value::Truth(b) => self.0.push(1 + *b as u8),
+            // -0.0 takes the fixed form so its sign survives
+            Value::Number(n) if n % 1.0 == 0.0 && n.abs() <= MAX_INTEGER && !(*n == 0.0 && n.is_sign_negative()) => {
+                self.0.push(3);
+                self.zigzag(*n as i64);
+            }
+            Value::Number(n) => {
+                self.0.push(4);
+                self.0.extend_from_slice(&n.to_le_bytes());
+            }
+            Value::Text(s) => {
+                self.0.push(5);
+                self.text(s);
+            }
+            Value::List(items) => {
+                self.0.push(6);
+                self.items(items, depth)?;
+            }
+            Value::Map(entries) => {
+                self.0.push(7);
+                self.entries(entries, depth)?;
+            }
+            Value::StructInstance { struct_name, fields } => {
+                self.0.push(8);
+                self.text(struct_name);
+                self.entries(fields, depth)?;
+            }
+            Value::VariantValue { enum_name, variant_name, fields, type_args } => {
+                self.0.push(9);
+                self.text(enum_name);
+                self.text(variant_name);
+                self.items(fields, depth)?;
+                self.varint(type_args.len() as u64);
+                for type_arg in type_args {
+                    self.text(type_arg);
+                }
+            }
+            Value::Outcome { success, value } => {
+                self.0.push(if *success { 10 } else { 11 });
+                self.value(value, depth)?;
+            }
+            Value::Maybe { value: Some(value), .. } => {
+                self.0.push(12);
+                self.value(value, depth)?;
+            }
+            Value::Maybe { value: None, .. } => self.0.push(13),
+            Value::Range { start, end } => {
+                self.0.push(14);
+                self.value(start, depth)?;
+                self.value(end, depth)?;
+            }
+            Value::BigInt(n) => {
+                self.0.push(15);
+                self.text(&n.to_string());
+            }
+            Value::Tuple(elements) => {
+                self.0.push(16);
+                self.items(elements, depth)?;
+            }
+            Value::Integer(n) => {
+                self.0.push(18);
+                self.zigzag(*n);
+            }
+            Value::Set(elements) => {
+                self.0.push(17);
+                self.items(&elements.iter().map(SetElement::to_value).collect::<Vec<_>>(), depth)?;
+            }
+            // Decodes thawed; freezing is up to whoever receives it
+            Value::Frozen { value } | Value::Tainted { value, .. } => self.value(value, depth - 1)?,
+            other => return Err(CodecError::Unencodable(other.type_name().to_string())),
+        }
+        Ok(())
+    }
+}
+
+/// Input cursor
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], CodecError> {
+        if self.0.len() < count {
+            return Err(CodecError::Truncated);
+        }
+        let (head, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, CodecError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, CodecError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            let bits = (byte & 0x7f) as u64;
+            if bits << shift >> shift != bits {
+                return Err(CodecError::Overflow);
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CodecError::Overflow)
+    }
+
+    fn zigzag(&mut self) -> Result<i64, CodecError> {
+        let zigzag = self.varint()?;
+        Ok((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64))
+    }
+
+    fn len(&mut self) -> Result<usize, CodecError> {
+        usize::try_from(self.varint()?).map_err(|_| CodecError::Overflow)
+    }
+
+    fn text(&mut self) -> Result<String, CodecError> {
+        let len = self.len()?;
+        let bytes = self.take(len)?;
+        core::str::from_utf8(bytes).map(String::from).map_err(|_| CodecError::InvalidText)
+    }
+
+    fn entries(&mut self, depth: usize) -> Result<BTreeMap<String, Value>, CodecError> {
+        let mut entries = BTreeMap::new();
+        for _ in 0..self.len()? {
+            let key = self.text()?;
+            let value = self.value(depth)?;
+            entries.insert(key, value);
+        }
+        Ok(entries)
+    }
+
+    fn items(&mut self, depth: usize) -> Result<Vec<Value>, CodecError> {
+        let mut items = Vec::new();
+        for _ in 0..self.len()? {
+            items.push(self.value(depth)?);
+        }
+        Ok(items)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, CodecError> {
+        if depth == MAX_DEPTH {
+            return Err(CodecError::TooDeep);
+        }
+        let depth = depth + 1;
+        let value = match self.u8()? {
+            0 => Value::Nothing,
+            1 => Value::Truth(false),
+            2 => Value::Truth(true),
+            3 => Value::Number(self.zigzag()? as f64),
+            4 => {
+                let mut bytes = [0; 8];
+                bytes.copy_from_slice(self.take(8)?);
+                Value::Number(f64::from_le_bytes(bytes))
+            }
+            5 => Value::Text(self.text()?),
+            6 => Value::List(self.items(depth)?),
+            7 => Value::Map(self.entries(depth)?),
+            8 => {
+                let struct_name = self.text()?;
+                Value::StructInstance { struct_name, fields: self.entries(depth)? }
+            }
+            9 => {
+                let enum_name = self.text()?;
+                let variant_name = self.text()?;
+                let fields = self.items(depth)?;
+                let mut type_args = Vec::new();
+                for _ in 0..self.len()? {
+                    type_args.push(self.text()?);
+                }
+                Value::VariantValue { enum_name, variant_name, fields, type_args }
+            }
+            tag @ (10 | 11) => Value::Outcome { success: tag == 10, value: Box::new(self.value(depth)?) },
+            12 => Value::Maybe { present: true, value: Some(Box::new(self.value(depth)?)) },
+            13 => Value::Maybe { present: false, value: None },
+            14 => {
+                let start = Box::new(self.value(depth)?);
+                let end = Box::new(self.value(depth)?);
+                Value::Range { start, end }
+            }
+            15 => Value::BigInt(BigInt::parse(&self.text()?).ok_or(CodecError::InvalidBigInt)?),
+            16 => Value::Tuple(self.items(depth)?),
+            17 => Value::Set(
+                self.items(depth)?
+                    .iter()
+                    .map(|item| SetElement::from_value(item).map_err(|_| CodecError::InvalidSetElement))
+                    .collect::<Result<_, _>>()?,
+            ),
+            18 => Value::Integer(self.zigzag()?),
+            tag => return Err(CodecError::UnknownTag(tag)),
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn text(s: &str) -> Value {
+        Value::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_roundtrip_every_encodable_kind() {
+        let mut fields = BTreeMap::new();
+        fields.insert("x".to_string(), Value::Number(-3.0));
+        fields.insert("label".to_string(), text("héllo"));
+        let value = Value::List(vec![
+            Value::Nothing,
+            Value::Truth(true),
+            Value::Truth(false),
+            Value::Number(0.0),
+            Value::Number(-0.0),
+            Value::Number(1.5),
+            Value::Number(1e300),
+            Value::Number(-9_007_199_254_740_992.0),
+            Value::Map(fields.clone()),
+            Value::StructInstance { struct_name: "Point".to_string(), fields },
+            Value::VariantValue {
+                enum_name: "Option".to_string(),
+                variant_name: "Some".to_string(),
+                fields: vec![Value::Number(42.0)],
+                type_args: vec!["Number".to_string()],
+            },
+            Value::Outcome { success: false, value: Box::new(text("no")) },
+            Value::Maybe { present: true, value: Some(Box::new(Value::List(Vec::new()))) },
+            Value::Maybe { present: false, value: None },
+            Value::Range { start: Box::new(Value::Number(1.0)), end: Box::new(Value::Number(10.0)) },
+            Value::BigInt(BigInt::parse("-18446744073709551617").unwrap()),
+            Value::Integer(i64::MIN),
+            Value::Integer(42),
+        ]);
+        let decoded = decode(&encode(&value).unwrap()).unwrap();
+        assert_eq!(decoded, value);
+        // The sign of -0 survives, though -0 == 0
+        let Value::List(items) = decoded else { panic!() };
+        assert!(matches!(items[4], Value::Number(n) if n.is_sign_negative()));
+    }
+
+    #[test]
+    fn test_small_integers_are_compact() {
+        assert_eq!(encode(&Value::Number(5.0)).unwrap(), b"GWV\x01\x03\x0a");
+        assert_eq!(encode(&Value::Number(-1.0)).unwrap(), b"GWV\x01\x03\x01");
+        assert_eq!(encode(&Value::Number(300.0)).unwrap(), b"GWV\x01\x03\xd8\x04");
+    }
+
+    #[test]
+    fn test_unencodable_values() {
+        assert_eq!(
+            encode(&Value::List(vec![Value::resource("file", 3)])),
+            Err(CodecError::Unencodable("Resource".to_string()))
+        );
+        let capability = Value::Capability { resource: "VGA".to_string(), permissions: Vec::new() };
+        assert_eq!(encode(&capability), Err(CodecError::Unencodable("Capability".to_string())));
+
+        let mut deep = Value::Nothing;
+        for _ in 0..MAX_DEPTH {
+            deep = Value::List(vec![deep]);
+        }
+        assert_eq!(encode(&deep), Err(CodecError::TooDeep));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert_eq!(decode(b""), Err(CodecError::BadMagic));
+        assert_eq!(decode(b"GWC\x01\x00"), Err(CodecError::BadMagic));
+        assert_eq!(decode(b"GWV\x02\x00"), Err(CodecError::UnsupportedVersion(2)));
+        assert_eq!(decode(b"GWV\x01"), Err(CodecError::Truncated));
+        assert_eq!(decode(b"GWV\x01\x05\x03ab"), Err(CodecError::Truncated));
+        assert_eq!(decode(b"GWV\x01\x05\x01\xff"), Err(CodecError::InvalidText));
+        assert_eq!(decode(b"GWV\x01\x0f\x021x"), Err(CodecError::InvalidBigInt));
+        assert_eq!(decode(b"GWV\x01\x63"), Err(CodecError::UnknownTag(0x63)));
+        assert_eq!(decode(b"GWV\x01\x00\x00"), Err(CodecError::TrailingData));
+        assert_eq!(decode(b"GWV\x01\x03\xff\xff\xff\xff\xff\xff\xff\xff\xff\x7f"), Err(CodecError::Overflow));
+
+        // Nesting is bounded before the stack is
+        let mut deep = b"GWV\x01".to_vec();
+        deep.extend(core::iter::repeat_n([6, 1], MAX_DEPTH + 1).flatten());
+        assert_eq!(decode(&deep), Err(CodecError::TooDeep));
+    }
+}
@@ -0,0 +1,347 @@
+//! # Value Diffs
+//!
+//! Minimal change lists between two values, so a script syncing state can
+//! send what changed instead of the whole value. Scripts use
+//! `value_diff(old, new)` and `value_patch(old, changes)`; the host uses
+//! [`diff`] and [`patch`].
+//!
+//! Maps and form instances of the same form are compared key by key and
+//! lists index by index, recursing into values present on both sides.
+//! Anything else that differs is replaced whole.
+//!
+//! ## Changes
+//!
+//! In scripts a change is a Map with an `op` and a `path`, the list of map
+//! keys (Text) and list indices (Number) leading from the root to the
+//! changed value:
+//!
+//! | `op`         | Other keys | Effect                                            |
+//! |--------------|------------|---------------------------------------------------|
+//! | `"set"`      | `value`    | Replace the value at `path`, add a map key, or append to a list when the index is its length |
+//! | `"remove"`   |            | Remove a map key or list element                  |
+//! | `"truncate"` | `length`   | Shorten the list at `path`                        |
+//!
+//! ```
+//! use glimmer_weave::value_diff::{diff, patch, Change, PathStep};
+//! use glimmer_weave::Value;
+//!
+//! let old = Value::List(vec![Value::Number(1.0), Value::Number(2.0)]);
+//! let new = Value::List(vec![Value::Number(1.0), Value::Number(5.0), Value::Number(8.0)]);
+//! let changes = diff(&old, &new);
+//! assert_eq!(changes[0], Change::Set { path: vec![PathStep::Index(1)], value: Value::Number(5.0) });
+//! assert_eq!(patch(&old, &changes), Ok(new));
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::eval::Value;
+
+/// One step of a path into a value
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathStep {
+    /// Map key or form field
+    Key(String),
+    /// List index
+    Index(usize),
+}
+
+/// One change of a diff
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// Replace the value at `path`, add a key, or append to a list
+    Set { path: Vec<PathStep>, value: Value },
+    /// Remove a map key or list element
+    Remove { path: Vec<PathStep> },
+    /// Shorten the list at `path` to `length` elements
+    Truncate { path: Vec<PathStep>, length: usize },
+}
+
+/// Why a change list could not be applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// The change at this index is not a change Map
+    Malformed(usize),
+    /// The change at this index leads through a missing key or index, or
+    /// into a value that holds none
+    BadPath(usize),
+}
+
+impl core::fmt::Display for PatchError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PatchError::Malformed(index) => write!(f, "change {} is not a change map", index),
+            PatchError::BadPath(index) => write!(f, "path of change {} does not exist", index),
+        }
+    }
+}
+
+/// Changes that turn `old` into `new`
+pub fn diff(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_into(old, new, &mut Vec::new(), &mut changes);
+    changes
+}
+
+fn diff_into(old: &Value, new: &Value, path: &mut Vec<PathStep>, changes: &mut Vec<Change>) {
+    match (old.unfrozen(), new.unfrozen()) {
+        (Value::Map(old), Value::Map(new)) => diff_entries(old, new, path, changes),
+        (
+            Value::StructInstance { struct_name: old_form, fields: old },
+            Value::StructInstance { struct_name: new_form, fields: new },
+        ) if old_form == new_form => diff_entries(old, new, path, changes),
+        (Value::List(old), Value::List(new)) => {
+            for (index, (old_item, new_item)) in old.iter().zip(new).enumerate() {
+                path.push(PathStep::Index(index));
+                diff_into(old_item, new_item, path, changes);
+                path.pop();
+            }
+            for (index, item) in new.iter().enumerate().skip(old.len()) {
+                let mut item_path = path.clone();
+                item_path.push(PathStep::Index(index));
+                changes.push(Change::Set { path: item_path, value: item.clone() });
+            }
+            if new.len() < old.len() {
+                changes.push(Change::Truncate { path: path.clone(), length: new.len() });
+            }
+        }
+        (old, new) if old == new => {}
+        _ => changes.push(Change::Set { path: path.clone(), value: new.clone() }),
+    }
+}
+
+fn diff_entries(
+    old: &BTreeMap<String, Value>,
+    new: &BTreeMap<String, Value>,
+    path: &mut Vec<PathStep>,
+    changes: &mut Vec<Change>,
+) {
+    for key in old.keys().filter(|key| !new.contains_key(*key)) {
+        let mut key_path = path.clone();
+        key_path.push(PathStep::Key(key.clone()));
+        changes.push(Change::Remove { path: key_path });
+    }
+    for (key, new_value) in new {
+        path.push(PathStep::Key(key.clone()));
+        match old.get(key) {
+            Some(old_value) => diff_into(old_value, new_value, path, changes),
+            None => changes.push(Change::Set { path: path.clone(), value: new_value.clone() }),
+        }
+        path.pop();
+    }
+}
+
+/// Apply changes in order to a copy of `value`
+pub fn patch(value: &Value, changes: &[Change]) -> Result<Value, PatchError> {
+    let mut value = value.clone();
+    for (index, change) in changes.iter().enumerate() {
+        apply(&mut value, change).ok_or(PatchError::BadPath(index))?;
+    }
+    Ok(value)
+}
+
+fn apply(root: &mut Value, change: &Change) -> Option<()> {
+    let path = match change {
+        Change::Set { path, .. } | Change::Remove { path } | Change::Truncate { path, .. } => path,
+    };
+    let Some((last, parents)) = path.split_last() else {
+        // The root itself
+        return match change {
+            Change::Set { value, .. } => {
+                *root = value.clone();
+                Some(())
+            }
+            Change::Truncate { length, .. } => truncate(root, *length),
+            Change::Remove { .. } => None,
+        };
+    };
+    let mut parent = root;
+    for step in parents {
+        parent = child(parent, step)?;
+    }
+    match change {
+        Change::Set { value, .. } => match (parent, last) {
+            (Value::Map(entries), PathStep::Key(key)) => {
+                entries.insert(key.clone(), value.clone());
+                Some(())
+            }
+            (Value::StructInstance { fields, .. }, PathStep::Key(key)) => {
+                *fields.get_mut(key)? = value.clone();
+                Some(())
+            }
+            (Value::List(items), PathStep::Index(index)) if *index == items.len() => {
+                items.push(value.clone());
+                Some(())
+            }
+            (parent, step) => {
+                *child(parent, step)? = value.clone();
+                Some(())
+            }
+        },
+        Change::Remove { .. } => match (parent, last) {
+            (Value::Map(entries), PathStep::Key(key)) => entries.remove(key).map(|_| ()),
+            (Value::List(items), PathStep::Index(index)) if *index < items.len() => {
+                items.remove(*index);
+                Some(())
+            }
+            _ => None,
+        },
+        Change::Truncate { length, .. } => truncate(child(parent, last)?, *length),
+    }
+}
+
+fn child<'a>(value: &'a mut Value, step: &PathStep) -> Option<&'a mut Value> {
+    match (value, step) {
+        (Value::Map(entries) | Value::StructInstance { fields: entries, .. }, PathStep::Key(key)) => entries.get_mut(key),
+        (Value::List(items), PathStep::Index(index)) => items.get_mut(*index),
+        _ => None,
+    }
+}
+
+fn truncate(value: &mut Value, length: usize) -> Option<()> {
+    match value {
+        Value::List(items) if length <= items.len() => {
+            items.truncate(length);
+            Some(())
+        }
+        _ => None,
+    }
+}
+
+impl Change {
+    /// The change as the Map scripts see
+    pub fn to_value(&self) -> Value {
+        let (op, path) = match self {
+            Change::Set { path, .. } => ("set", path),
+            Change::Remove { path } => ("remove", path),
+            Change::Truncate { path, .. } => ("truncate", path),
+        };
+        let path = path
+            .iter()
+            .map(|step| match step {
+                PathStep::Key(key) => Value::Text(key.clone()),
+                PathStep::Index(index) => Value::Number(*index as f64),
+            })
+            .collect();
+        let mut entries = BTreeMap::new();
+        entries.insert("op".to_string(), Value::Text(op.to_string()));
+        entries.insert("path".to_string(), Value::List(path));
+        match self {
+            Change::Set { value, .. } => {
+                entries.insert("value".to_string(), value.clone());
+            }
+            Change::Truncate { length, .. } => {
+                entries.insert("length".to_string(), Value::Number(*length as f64));
+            }
+            Change::Remove { .. } => {}
+        }
+        Value::Map(entries)
+    }
+
+    /// Read a change Map written by a script or [`Change::to_value`]
+    pub fn from_value(value: &Value) -> Option<Change> {
+        let Value::Map(entries) = value else {
+            return None;
+        };
+        let Some(Value::List(steps)) = entries.get("path") else {
+            return None;
+        };
+        let path = steps
+            .iter()
+            .map(|step| match step {
+                Value::Text(key) => Some(PathStep::Key(key.clone())),
+                Value::Number(n) => as_index(*n).map(PathStep::Index),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        match (entries.get("op")?, entries.get("value"), entries.get("length")) {
+            (Value::Text(op), Some(value), None) if op == "set" => Some(Change::Set { path, value: value.clone() }),
+            (Value::Text(op), None, None) if op == "remove" => Some(Change::Remove { path }),
+            (Value::Text(op), None, Some(Value::Number(length))) if op == "truncate" => {
+                Some(Change::Truncate { path, length: as_index(*length)? })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A whole, non-negative number as an index
+fn as_index(n: f64) -> Option<usize> {
+    (n % 1.0 == 0.0 && n >= 0.0).then_some(n as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn map(entries: &[(&str, Value)]) -> Value {
+        Value::Map(entries.iter().map(|(key, value)| (key.to_string(), value.clone())).collect())
+    }
+
+    fn key(name: &str) -> PathStep {
+        PathStep::Key(name.to_string())
+    }
+
+    #[test]
+    fn test_diff_recurses_into_maps_and_lists() {
+        let old = map(&[
+            ("name", Value::Text("ada".to_string())),
+            ("tags", Value::List(vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)])),
+            ("gone", Value::Nothing),
+        ]);
+        let new = map(&[
+            ("name", Value::Text("ada".to_string())),
+            ("tags", Value::List(vec![Value::Number(1.0), Value::Number(9.0)])),
+            ("added", Value::Truth(true)),
+        ]);
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                Change::Remove { path: vec![key("gone")] },
+                Change::Set { path: vec![key("added")], value: Value::Truth(true) },
+                Change::Set { path: vec![key("tags"), PathStep::Index(1)], value: Value::Number(9.0) },
+                Change::Truncate { path: vec![key("tags")], length: 2 },
+            ]
+        );
+        assert_eq!(patch(&old, &changes), Ok(new.clone()));
+        assert_eq!(diff(&new, &new), vec![]);
+    }
+
+    #[test]
+    fn test_different_kinds_are_replaced_whole() {
+        let form = |name: &str| Value::StructInstance { struct_name: name.to_string(), fields: BTreeMap::new() };
+        assert_eq!(diff(&form("A"), &form("B")), vec![Change::Set { path: vec![], value: form("B") }]);
+        assert_eq!(
+            diff(&Value::Number(1.0), &Value::Text("1".to_string())),
+            vec![Change::Set { path: vec![], value: Value::Text("1".to_string()) }]
+        );
+    }
+
+    #[test]
+    fn test_changes_round_trip_through_values() {
+        let changes = [
+            Change::Set { path: vec![key("a"), PathStep::Index(0)], value: Value::Nothing },
+            Change::Remove { path: vec![key("b")] },
+            Change::Truncate { path: vec![], length: 3 },
+        ];
+        for change in changes {
+            assert_eq!(Change::from_value(&change.to_value()), Some(change));
+        }
+        assert_eq!(Change::from_value(&map(&[("op", Value::Text("remove".to_string()))])), None);
+        let negative = map(&[("op", Value::Text("remove".to_string())), ("path", Value::List(vec![Value::Number(-1.0)]))]);
+        assert_eq!(Change::from_value(&negative), None);
+    }
+
+    #[test]
+    fn test_patch_rejects_missing_paths() {
+        let old = map(&[("list", Value::List(vec![]))]);
+        let past_the_end = Change::Set { path: vec![key("list"), PathStep::Index(1)], value: Value::Nothing };
+        assert_eq!(patch(&old, &[past_the_end]), Err(PatchError::BadPath(0)));
+        let through_missing = Change::Set { path: vec![key("nope"), key("x")], value: Value::Nothing };
+        assert_eq!(patch(&old, &[Change::Remove { path: vec![key("list")] }, through_missing]), Err(PatchError::BadPath(1)));
+        assert_eq!(patch(&old, &[Change::Truncate { path: vec![key("list")], length: 1 }]), Err(PatchError::BadPath(0)));
+    }
+}
@@ -0,0 +1,64 @@
+//! # Signature Verification
+//!
+//! Lets AethelOS run only code signed by keys it trusts.
+//!
+//! The crate ships no cryptography of its own: the host installs a
+//! [`Verifier`] (for example ed25519 against its trusted key ring), and the
+//! loaders hand it every image together with its detached signature. The
+//! [`ModuleResolver`](crate::module_resolver::ModuleResolver) verifies module
+//! sources this way once a verifier is set, refusing unsigned or badly signed
+//! modules with `ResolverError::Verification`, and
+//! [`decode_verified`](crate::bytecode_image::decode_verified) does the same
+//! for `.gwc` images with `ImageError::Verification`.
+//!
+//! ```
+//! use glimmer_weave::verify::{verify_image, VerifyError};
+//!
+//! let verifier = |image: &[u8], signature: &[u8]| {
+//!     if signature == image.iter().rev().copied().collect::<Vec<_>>() {
+//!         Ok(())
+//!     } else {
+//!         Err(VerifyError::InvalidSignature { reason: "bad".to_string() })
+//!     }
+//! };
+//! assert_eq!(verify_image(&verifier, b"ab", Some(b"ba")), Ok(()));
+//! assert_eq!(verify_image(&verifier, b"ab", None), Err(VerifyError::MissingSignature));
+//! ```
+
+use alloc::string::String;
+
+/// Extension of detached signature files next to module sources
+/// (`math.gw` is signed by `math.gw.sig`)
+pub const SIGNATURE_EXTENSION: &str = ".sig";
+
+/// Why an image failed verification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// No signature accompanies the image
+    MissingSignature,
+    /// The signature does not match the image
+    InvalidSignature { reason: String },
+    /// The signature is valid but made with a key the host does not trust
+    UntrustedKey { key: String },
+}
+
+/// Host-provided signature check
+pub trait Verifier: crate::sync::MaybeSend {
+    /// Check `signature` over `image`
+    fn verify(&self, image: &[u8], signature: &[u8]) -> Result<(), VerifyError>;
+}
+
+impl<F: Fn(&[u8], &[u8]) -> Result<(), VerifyError> + crate::sync::MaybeSend> Verifier for F {
+    fn verify(&self, image: &[u8], signature: &[u8]) -> Result<(), VerifyError> {
+        self(image, signature)
+    }
+}
+
+/// Verify an image against its detached signature, failing when the
+/// signature is missing
+pub fn verify_image(verifier: &dyn Verifier, image: &[u8], signature: Option<&[u8]>) -> Result<(), VerifyError> {
+    match signature {
+        Some(signature) => verifier.verify(image, signature),
+        None => Err(VerifyError::MissingSignature),
+    }
+}
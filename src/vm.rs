@@ -10,8 +10,14 @@
 //! - **Call Stack**: For function calls and returns
 //! - **Global Variables**: Hash map for global storage
 
-use crate::bytecode::{BytecodeChunk, Constant, Instruction};
-use crate::eval::Value;
+use crate::ast::{AffixSide, BinaryOperator};
+use crate::bigint::BigInt;
+use crate::bytecode::{BytecodeChunk, Constant, Instruction, BYTECODE_VERSION, MIN_BYTECODE_VERSION};
+use crate::cancellation::CancellationToken;
+use crate::clock::Clock;
+use crate::eval::{RuntimeError, Value};
+use crate::leak_check::{HeapSnapshot, LeakReport};
+use alloc::format;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use alloc::collections::BTreeMap;
@@ -32,6 +38,8 @@ pub enum VmError {
     UndefinedVariable(String),
     /// Division by zero
     DivisionByZero,
+    /// Integer arithmetic overflowed; holds the operation
+    IntegerOverflow(String),
     /// Out of bounds access
     OutOfBounds,
     /// Field not found on object
@@ -39,6 +47,22 @@ pub enum VmError {
         field: String,
         object: String,
     },
+    /// Chunk was compiled for a bytecode version this VM does not run
+    IncompatibleVersion {
+        found: u16,
+        min: u16,
+        max: u16,
+    },
+    /// Execution ran past its deadline (see `VM::execute_with_deadline`)
+    Timeout,
+    /// The host tripped the VM's cancellation token
+    Cancelled,
+    /// Chunk uses an opcode introduced after the chunk's own version
+    UnsupportedOpcode {
+        mnemonic: String,
+        since: u16,
+        version: u16,
+    },
 }
 
 pub type VmResult<T> = Result<T, VmError>;
@@ -89,6 +113,21 @@ pub struct VM {
 
     /// Current chunk being executed
     chunk: Option<BytecodeChunk>,
+
+    /// Time source checked against `deadline` at safepoints
+    clock: Option<Box<dyn Clock>>,
+
+    /// Clock reading at which execution aborts with `VmError::Timeout`
+    deadline: Option<u64>,
+
+    /// Token the host trips to stop execution with `VmError::Cancelled`
+    cancellation: Option<CancellationToken>,
+
+    /// Whether executions record a leak report
+    leak_detection: bool,
+
+    /// Heap usage around the last execution, with leak detection on
+    leak_report: Option<LeakReport>,
 }
 
 impl Default for VM {
@@ -107,17 +146,158 @@ impl VM {
             exception_handlers: Vec::new(),
             ip: 0,
             chunk: None,
+            clock: crate::clock::default_clock(),
+            deadline: None,
+            cancellation: None,
+            leak_detection: false,
+            leak_report: None,
         }
     }
 
-    /// Execute a bytecode chunk
-    pub fn execute(&mut self, chunk: BytecodeChunk) -> VmResult<Value> {
+    /// Check that a chunk can run on this VM
+    ///
+    /// The chunk's version must lie between `MIN_BYTECODE_VERSION` and
+    /// `BYTECODE_VERSION`, and it may only use opcodes that existed in it.
+    pub fn check_compatibility(chunk: &BytecodeChunk) -> VmResult<()> {
+        if chunk.version < MIN_BYTECODE_VERSION || chunk.version > BYTECODE_VERSION {
+            return Err(VmError::IncompatibleVersion {
+                found: chunk.version,
+                min: MIN_BYTECODE_VERSION,
+                max: BYTECODE_VERSION,
+            });
+        }
+
+        for instruction in &chunk.instructions {
+            let info = instruction.info();
+            if info.since > chunk.version {
+                return Err(VmError::UnsupportedOpcode {
+                    mnemonic: info.mnemonic.to_string(),
+                    since: info.since,
+                    version: chunk.version,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Install the token the host trips to cancel execution
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation = Some(token);
+    }
+
+    /// Check the deadline and the cancellation token; called before
+    /// backward jumps and calls
+    fn safepoint(&self) -> VmResult<()> {
+        if let (Some(deadline), Some(clock)) = (self.deadline, &self.clock) {
+            if clock.now() >= deadline {
+                return Err(VmError::Timeout);
+            }
+        }
+        if self.cancellation.as_ref().is_some_and(|token| token.is_cancelled()) {
+            return Err(VmError::Cancelled);
+        }
+        Ok(())
+    }
+
+    /// Load a chunk after checking its version, ready to run from the start
+    pub fn load(&mut self, chunk: BytecodeChunk) -> VmResult<()> {
+        Self::check_compatibility(&chunk)?;
         self.chunk = Some(chunk);
         self.ip = 0;
+        Ok(())
+    }
+
+    /// Install the clock that deadlines are measured against
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = Some(clock);
+    }
+
+    /// Execute a chunk, aborting with `VmError::Timeout` once the clock
+    /// reaches `deadline`
+    ///
+    /// The clock is read before backward jumps and calls.
+    pub fn execute_with_deadline(&mut self, chunk: BytecodeChunk, deadline: u64) -> VmResult<Value> {
+        if self.clock.is_none() {
+            return Err(VmError::TypeError("execute_with_deadline: No clock installed".to_string()));
+        }
+
+        let outer = self.deadline.replace(deadline);
+        let result = self.execute(chunk);
+        self.deadline = outer;
+        result
+    }
+
+    /// Record a [`LeakReport`] around every execution
+    pub fn set_leak_detection(&mut self, enabled: bool) {
+        self.leak_detection = enabled;
+        if !enabled {
+            self.leak_report = None;
+        }
+    }
+
+    /// Heap usage around the last execution, if leak detection is on
+    pub fn leak_report(&self) -> Option<&LeakReport> {
+        self.leak_report.as_ref()
+    }
+
+    /// Current heap usage of this VM: globals and registers outlive an execution
+    pub fn heap_snapshot(&self) -> HeapSnapshot {
+        let values = self.globals.values().chain(self.registers.iter());
+        HeapSnapshot {
+            native_bytes: crate::leak_check::native_heap_used(),
+            retained_bytes: values.map(crate::leak_check::retained_bytes).sum(),
+            globals: self.globals.keys().cloned().collect(),
+            live_resources: 0,
+        }
+    }
+
+    /// Graph of the values the VM's globals keep alive
+    pub fn heap_graph(&self) -> crate::heap_graph::HeapGraph {
+        crate::heap_graph::HeapGraph::capture(self.globals.iter().map(|(name, value)| (name.as_str(), value)))
+    }
+
+    /// Report of `error`, which the last execution failed with: the
+    /// instruction that raised it and the VM's globals
+    pub fn crash_dump(&self, error: &VmError) -> crate::crash_dump::CrashDump {
+        use crate::crash_dump::{CrashBinding, CrashDump, CrashLocation};
+
+        let message = format!("{:?}", error);
+        let error_type = message.split(['(', ' ']).next().unwrap_or_default();
+        // The instruction pointer has already moved past the failing instruction
+        let instruction = self.ip.saturating_sub(1);
+        let location = CrashLocation::Bytecode {
+            chunk: self.chunk.as_ref().map_or(String::new(), |chunk| chunk.name.clone()),
+            instruction,
+            line: self.chunk.as_ref().and_then(|chunk| chunk.lines.get(instruction).copied()),
+        };
+        let mut dump = CrashDump::new(error_type, &message, location);
+        dump.environment = self.globals.iter().map(|(name, value)| CrashBinding::new(name, value)).collect();
+        dump
+    }
+
+    /// Execute a bytecode chunk
+    pub fn execute(&mut self, chunk: BytecodeChunk) -> VmResult<Value> {
+        if !self.leak_detection {
+            return self.run(chunk);
+        }
+        let before = self.heap_snapshot();
+        let result = self.run(chunk);
+        self.leak_report = Some(LeakReport::new(before, self.heap_snapshot()));
+        result
+    }
+
+    /// Run a chunk from its first instruction
+    fn run(&mut self, chunk: BytecodeChunk) -> VmResult<Value> {
+        self.load(chunk)?;
 
         loop {
             let instruction = self.fetch_instruction()?;
 
+            if instruction.is_safepoint() {
+                self.safepoint()?;
+            }
+
             match instruction {
                 Instruction::Halt => {
                     // Return r0 as result
@@ -142,28 +322,44 @@ impl VM {
                 }
 
                 Instruction::AddNum { dest, left, right } => {
+                    if let Some(result) = self.whole_op(left, BinaryOperator::Add, right)? {
+                        self.registers[dest as usize] = result;
+                        continue;
+                    }
                     let l = self.get_number(left)?;
                     let r = self.get_number(right)?;
                     self.registers[dest as usize] = Value::Number(l + r);
                 }
 
                 Instruction::SubNum { dest, left, right } => {
+                    if let Some(result) = self.whole_op(left, BinaryOperator::Sub, right)? {
+                        self.registers[dest as usize] = result;
+                        continue;
+                    }
                     let l = self.get_number(left)?;
                     let r = self.get_number(right)?;
                     self.registers[dest as usize] = Value::Number(l - r);
                 }
 
                 Instruction::MulNum { dest, left, right } => {
+                    if let Some(result) = self.whole_op(left, BinaryOperator::Mul, right)? {
+                        self.registers[dest as usize] = result;
+                        continue;
+                    }
                     let l = self.get_number(left)?;
                     let r = self.get_number(right)?;
                     self.registers[dest as usize] = Value::Number(l * r);
                 }
 
                 Instruction::DivNum { dest, left, right } => {
+                    if let Some(result) = self.whole_op(left, BinaryOperator::Div, right)? {
+                        self.registers[dest as usize] = result;
+                        continue;
+                    }
                     let l = self.get_number(left)?;
                     let r = self.get_number(right)?;
                     if r == 0.0 {
-                        self.handle_error("DivisionByZero", "Division by zero")?;
+                        self.handle_error("DivisionByZero", "Division by zero", VmError::DivisionByZero)?;
                         // If we get here, the error was handled - set result to 0
                         self.registers[dest as usize] = Value::Number(0.0);
                     } else {
@@ -172,12 +368,34 @@ impl VM {
                 }
 
                 Instruction::ModNum { dest, left, right } => {
+                    if let Some(result) = self.whole_op(left, BinaryOperator::Mod, right)? {
+                        self.registers[dest as usize] = result;
+                        continue;
+                    }
                     let l = self.get_number(left)?;
                     let r = self.get_number(right)?;
                     self.registers[dest as usize] = Value::Number(l % r);
                 }
 
                 Instruction::NegNum { dest, src } => {
+                    if let Value::BigInt(n) = &self.registers[src as usize] {
+                        self.registers[dest as usize] = Value::BigInt(-n);
+                        continue;
+                    }
+                    if let Value::Integer(n) = self.registers[src as usize] {
+                        self.registers[dest as usize] = match n.checked_neg() {
+                            Some(negated) => Value::Integer(negated),
+                            None => {
+                                self.handle_error(
+                                    "IntegerOverflow",
+                                    "Integer overflow in negation",
+                                    VmError::IntegerOverflow("negation".to_string()),
+                                )?;
+                                Value::Integer(0)
+                            }
+                        };
+                        continue;
+                    }
                     let n = self.get_number(src)?;
                     self.registers[dest as usize] = Value::Number(-n);
                 }
@@ -201,24 +419,40 @@ impl VM {
                 }
 
                 Instruction::Lt { dest, left, right } => {
+                    if let Some(result) = self.whole_op(left, BinaryOperator::Less, right)? {
+                        self.registers[dest as usize] = result;
+                        continue;
+                    }
                     let l = self.get_number(left)?;
                     let r = self.get_number(right)?;
                     self.registers[dest as usize] = Value::Truth(l < r);
                 }
 
                 Instruction::Le { dest, left, right } => {
+                    if let Some(result) = self.whole_op(left, BinaryOperator::LessEq, right)? {
+                        self.registers[dest as usize] = result;
+                        continue;
+                    }
                     let l = self.get_number(left)?;
                     let r = self.get_number(right)?;
                     self.registers[dest as usize] = Value::Truth(l <= r);
                 }
 
                 Instruction::Gt { dest, left, right } => {
+                    if let Some(result) = self.whole_op(left, BinaryOperator::Greater, right)? {
+                        self.registers[dest as usize] = result;
+                        continue;
+                    }
                     let l = self.get_number(left)?;
                     let r = self.get_number(right)?;
                     self.registers[dest as usize] = Value::Truth(l > r);
                 }
 
                 Instruction::Ge { dest, left, right } => {
+                    if let Some(result) = self.whole_op(left, BinaryOperator::GreaterEq, right)? {
+                        self.registers[dest as usize] = result;
+                        continue;
+                    }
                     let l = self.get_number(left)?;
                     let r = self.get_number(right)?;
                     self.registers[dest as usize] = Value::Truth(l >= r);
@@ -298,13 +532,17 @@ impl VM {
 
                 Instruction::GetIndex { dest, list, index } => {
                     match (&self.registers[list as usize], &self.registers[index as usize]) {
-                        (Value::List(elements), Value::Number(idx)) => {
+                        (Value::List(elements) | Value::Tuple(elements), Value::Number(idx)) => {
                             let i = *idx as usize;
                             if i >= elements.len() {
                                 return Err(VmError::OutOfBounds);
                             }
                             self.registers[dest as usize] = elements[i].clone();
                         }
+                        (Value::List(elements) | Value::Tuple(elements), Value::Integer(idx)) => {
+                            let element = usize::try_from(*idx).ok().and_then(|i| elements.get(i));
+                            self.registers[dest as usize] = element.cloned().ok_or(VmError::OutOfBounds)?;
+                        }
                         _ => return Err(VmError::TypeError("Invalid index access".to_string())),
                     }
                 }
@@ -443,9 +681,96 @@ impl VM {
                     }
                 }
 
+                Instruction::CreateVariant { dest, enum_id, case_id, field_start, field_count } => {
+                    let enum_name = self.get_string_constant(enum_id)?;
+                    let variant_name = self.get_string_constant(case_id)?;
+                    let fields = (0..field_count)
+                        .map(|i| self.registers[(field_start + i) as usize].clone())
+                        .collect();
+                    self.registers[dest as usize] = Value::VariantValue {
+                        enum_name,
+                        variant_name,
+                        fields,
+                        type_args: Vec::new(),
+                    };
+                }
+
+                Instruction::IsVariant { dest, value, case_id } => {
+                    let case = self.get_string_constant(case_id)?;
+                    let is_case = match &self.registers[value as usize] {
+                        Value::VariantValue { variant_name, .. } => *variant_name == case,
+                        Value::Outcome { success, .. } => case == if *success { "Triumph" } else { "Mishap" },
+                        Value::Maybe { present, .. } => case == if *present { "Present" } else { "Absent" },
+                        _ => false,
+                    };
+                    self.registers[dest as usize] = Value::Truth(is_case);
+                }
+
+                Instruction::ExtractField { dest, value, index } => {
+                    // Field slots follow the native enum layout: Outcome and
+                    // Present carry their value in slot 0
+                    let field = match &self.registers[value as usize] {
+                        Value::VariantValue { fields, .. } => fields.get(index as usize).cloned(),
+                        Value::Outcome { value: inner, .. } if index == 0 => Some((**inner).clone()),
+                        Value::Maybe { value: Some(inner), .. } if index == 0 => Some((**inner).clone()),
+                        _ => None,
+                    };
+                    self.registers[dest as usize] = field.ok_or_else(|| {
+                        VmError::TypeError(format!("ExtractField: no field {} in variant", index))
+                    })?;
+                }
+
+                Instruction::CreateTextBuilder { dest } => {
+                    self.registers[dest as usize] = Value::text_builder();
+                }
+
+                Instruction::TextPush { dest, builder, value } => {
+                    let text = crate::coercion::to_text(&self.registers[value as usize])
+                        .map_err(|error| VmError::TypeError(format!("{:?}", error)))?;
+                    self.registers[dest as usize] = self.push_to_builder(builder, &text)?;
+                }
+
+                Instruction::TextPushText { dest, builder, value } => {
+                    let text = self.get_text(value)?;
+                    self.registers[dest as usize] = self.push_to_builder(builder, &text)?;
+                }
+
+                Instruction::TextBuild { dest, builder } => {
+                    self.registers[dest as usize] = crate::runtime::builder_build(&self.registers[builder as usize])
+                        .map_err(|_| VmError::TypeError("Expected text builder".to_string()))?;
+                }
+
+                Instruction::CreateTuple { dest, start, count } => {
+                    let elements = (0..count).map(|i| self.registers[(start + i) as usize].clone()).collect();
+                    self.registers[dest as usize] = Value::Tuple(elements);
+                }
+
+                Instruction::IsTuple { dest, value, count } => {
+                    let shaped = matches!(&self.registers[value as usize], Value::Tuple(elements) if elements.len() == count as usize);
+                    self.registers[dest as usize] = Value::Truth(shaped);
+                }
+
+                Instruction::TupleGet { dest, tuple, index } => {
+                    let element = match &self.registers[tuple as usize] {
+                        Value::Tuple(elements) => elements.get(index as usize).cloned().ok_or(VmError::OutOfBounds)?,
+                        _ => return Err(VmError::TypeError("Expected tuple".to_string())),
+                    };
+                    self.registers[dest as usize] = element;
+                }
+
+                Instruction::StripAffix { dest, value, affix_id, suffix } => {
+                    let affix = self.get_string_constant(affix_id)?;
+                    let side = if suffix { AffixSide::Suffix } else { AffixSide::Prefix };
+                    let rest = match &self.registers[value as usize] {
+                        Value::Text(text) => side.strip(text, &affix).map(|rest| Box::new(Value::Text(rest.to_string()))),
+                        _ => None,
+                    };
+                    self.registers[dest as usize] = Value::Maybe { present: rest.is_some(), value: rest };
+                }
+
                 Instruction::CreateStruct { dest, struct_def_id, field_start, field_count } => {
                     // Get the struct name from the constant (it's stored as Text for simplicity)
-                    let struct_name = if let Value::Text(name) = constant_to_value(&self.chunk.as_ref().unwrap().constants[struct_def_id as usize]) {
+                    let struct_name = if let Value::Text(name) = constant_to_value(self.get_constant(struct_def_id)?) {
                         name
                     } else {
                         return Err(VmError::TypeError("Expected Text constant for struct name".to_string()));
@@ -538,8 +863,8 @@ impl VM {
 
     /// Handle a runtime error by checking for exception handlers
     /// If a handler exists, sets error registers and jumps to handler
-    /// If no handler exists, returns the error
-    fn handle_error(&mut self, error_type: &str, error_msg: &str) -> VmResult<()> {
+    /// If no handler exists, returns `uncaught`
+    fn handle_error(&mut self, error_type: &str, error_msg: &str, uncaught: VmError) -> VmResult<()> {
         if let Some(handler) = self.exception_handlers.pop() {
             // Set error registers:
             // r254 = error type (Text)
@@ -552,7 +877,7 @@ impl VM {
             Ok(())
         } else {
             // No handler - return error
-            Err(VmError::DivisionByZero)
+            Err(uncaught)
         }
     }
 
@@ -571,6 +896,29 @@ impl VM {
         }
     }
 
+    /// `left op right` when both registers hold big integers or both hold
+    /// integers, else `None`
+    fn whole_op(&mut self, left: u8, op: BinaryOperator, right: u8) -> VmResult<Option<Value>> {
+        let (result, zero) = match (&self.registers[left as usize], &self.registers[right as usize]) {
+            (Value::BigInt(l), Value::BigInt(r)) => (crate::eval::bigint_op(l, op, r), Value::BigInt(BigInt::zero())),
+            (Value::Integer(l), Value::Integer(r)) => (crate::eval::integer_op(*l, op, *r), Value::Integer(0)),
+            _ => return Ok(None),
+        };
+        match result {
+            Ok(result) => Ok(Some(result)),
+            Err(RuntimeError::DivisionByZero) => {
+                self.handle_error("DivisionByZero", "Division by zero", VmError::DivisionByZero)?;
+                Ok(Some(zero))
+            }
+            Err(RuntimeError::IntegerOverflow(operation)) => {
+                let message = format!("Integer overflow in {}", operation);
+                self.handle_error("IntegerOverflow", &message, VmError::IntegerOverflow(operation))?;
+                Ok(Some(zero))
+            }
+            Err(error) => Err(VmError::TypeError(format!("{:?}", error))),
+        }
+    }
+
     /// Get a number from a register
     fn get_number(&self, reg: u8) -> VmResult<f64> {
         match &self.registers[reg as usize] {
@@ -587,15 +935,15 @@ impl VM {
         }
     }
 
+    /// Append to the text builder in a register, returning the builder
+    fn push_to_builder(&self, reg: u8, text: &str) -> VmResult<Value> {
+        crate::runtime::builder_push(&self.registers[reg as usize], text)
+            .map_err(|_| VmError::TypeError("Expected text builder".to_string()))
+    }
+
     /// Check if a register value is truthy
     fn is_truthy(&self, reg: u8) -> bool {
-        match &self.registers[reg as usize] {
-            Value::Truth(b) => *b,
-            Value::Nothing => false,
-            Value::Number(n) => *n != 0.0,
-            Value::Text(s) => !s.is_empty(),
-            _ => true,
-        }
+        crate::coercion::truthy(&self.registers[reg as usize])
     }
 }
 
@@ -603,6 +951,8 @@ impl VM {
 fn constant_to_value(constant: &Constant) -> Value {
     match constant {
         Constant::Number(n) => Value::Number(*n),
+        Constant::BigInt(n) => Value::BigInt(n.clone()),
+        Constant::Integer(n) => Value::Integer(*n),
         Constant::Text(s) => Value::Text(s.clone()),
         Constant::Truth(b) => Value::Truth(*b),
         Constant::Nothing => Value::Nothing,
@@ -694,4 +1044,29 @@ m.name
     // Note: Struct field access tests are in the interpreter tests.
     // VM GetField now supports structs, but full struct compilation is still being developed.
     // The GetField instruction correctly handles StructInstance values when they are present.
+
+    #[test]
+    fn test_vm_rejects_newer_bytecode() {
+        let mut chunk = compile(&Parser::new(Lexer::new("42").tokenize_positioned()).parse().unwrap()).unwrap();
+        chunk.version = BYTECODE_VERSION + 1;
+
+        match VM::new().execute(chunk) {
+            Err(VmError::IncompatibleVersion { found, min, max }) => {
+                assert_eq!(found, BYTECODE_VERSION + 1);
+                assert_eq!((min, max), (MIN_BYTECODE_VERSION, BYTECODE_VERSION));
+            }
+            other => panic!("Expected IncompatibleVersion, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_vm_rejects_bytecode_older_than_supported() {
+        let mut chunk = BytecodeChunk::new("old".to_string());
+        chunk.emit(Instruction::Halt, 1);
+        chunk.version = MIN_BYTECODE_VERSION - 1;
+        assert!(matches!(VM::check_compatibility(&chunk), Err(VmError::IncompatibleVersion { .. })));
+
+        chunk.version = MIN_BYTECODE_VERSION;
+        assert!(VM::check_compatibility(&chunk).is_ok());
+    }
 }
@@ -0,0 +1,154 @@
+//! # Watchdog
+//!
+//! Supervision for script-based services, the way AethelOS supervises
+//! native daemons. A script arms its watchdog with `watchdog(interval)` (or
+//! `watchdog(interval, "name")`), promising to call `heartbeat()` at least
+//! once per `interval` clock units from then on. The evaluator checks the
+//! promise at its safepoints (loop iterations and calls), against the same
+//! clock as deadlines. When a heartbeat is overdue it tells the
+//! [`WatchdogHost`] — installed on the evaluator or, for every script it
+//! spawns, on the [`Session`](crate::session::Session) — which decides
+//! whether the script carries on or is cancelled. A script that keeps
+//! missing its heartbeat is reported once per interval.
+//!
+//! ```
+//! use glimmer_weave::watchdog::{MissedHeartbeat, Watchdog, WatchdogAction, WatchdogHost};
+//!
+//! let mut watchdog = Watchdog::new();
+//! watchdog.arm(100, 0);
+//! watchdog.heartbeat(80);
+//! assert!(watchdog.check(150).is_none());
+//! let missed = watchdog.check(180).unwrap();
+//! assert_eq!((missed.last_beat, missed.misses), (80, 1));
+//! assert!(watchdog.check(200).is_none());
+//!
+//! let mut host = |missed: &MissedHeartbeat| if missed.misses > 2 { WatchdogAction::Cancel } else { WatchdogAction::Continue };
+//! assert_eq!(host.missed(&missed), WatchdogAction::Continue);
+//! ```
+
+use alloc::string::String;
+
+/// What the host wants done about a missed heartbeat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogAction {
+    /// Let the script carry on; it is reported again an interval later
+    Continue,
+    /// Stop the script with `RuntimeError::Cancelled`, running its `defer`
+    /// blocks on the way out
+    Cancel,
+}
+
+/// A heartbeat the script did not deliver in time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissedHeartbeat {
+    /// Name the script gave its watchdog, if any
+    pub name: Option<String>,
+    /// Promised time between heartbeats
+    pub interval: u64,
+    /// Clock reading at the last heartbeat (or when the watchdog was armed)
+    pub last_beat: u64,
+    /// Clock reading when the miss was noticed
+    pub now: u64,
+    /// Misses reported so far, this one included
+    pub misses: u64,
+}
+
+/// Told about missed heartbeats; AethelOS restarts or logs the service
+pub trait WatchdogHost: crate::sync::MaybeSend {
+    fn missed(&mut self, missed: &MissedHeartbeat) -> WatchdogAction;
+}
+
+impl<F: FnMut(&MissedHeartbeat) -> WatchdogAction + crate::sync::MaybeSend> WatchdogHost for F {
+    fn missed(&mut self, missed: &MissedHeartbeat) -> WatchdogAction {
+        self(missed)
+    }
+}
+
+/// Heartbeat state of one script
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Watchdog {
+    name: Option<String>,
+    /// Promised interval; `None` while disarmed
+    interval: Option<u64>,
+    last_beat: u64,
+    /// Clock reading at which the next miss is reported
+    next_report: u64,
+    misses: u64,
+}
+
+impl Watchdog {
+    /// A disarmed watchdog
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Expect a heartbeat every `interval`, counting from `now`
+    pub fn arm(&mut self, interval: u64, now: u64) {
+        self.interval = Some(interval);
+        self.heartbeat(now);
+    }
+
+    /// Name the script in reports
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.name = name;
+    }
+
+    /// Stop expecting heartbeats
+    pub fn disarm(&mut self) {
+        self.interval = None;
+    }
+
+    /// Record a heartbeat at `now`
+    pub fn heartbeat(&mut self, now: u64) {
+        self.last_beat = now;
+        self.next_report = now.saturating_add(self.interval.unwrap_or(0));
+    }
+
+    /// Promised interval, while armed
+    pub fn interval(&self) -> Option<u64> {
+        self.interval
+    }
+
+    /// Clock reading at the last heartbeat
+    pub fn last_beat(&self) -> u64 {
+        self.last_beat
+    }
+
+    /// Misses reported so far
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// The miss to report at `now`, if a heartbeat is overdue and the last
+    /// report is at least an interval old
+    pub fn check(&mut self, now: u64) -> Option<MissedHeartbeat> {
+        let interval = self.interval?;
+        if now < self.next_report {
+            return None;
+        }
+        self.misses += 1;
+        self.next_report = now.saturating_add(interval);
+        Some(MissedHeartbeat {
+            name: self.name.clone(),
+            interval,
+            last_beat: self.last_beat,
+            now,
+            misses: self.misses,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disarmed_watchdogs_never_fire() {
+        let mut watchdog = Watchdog::new();
+        assert!(watchdog.check(u64::MAX).is_none());
+        watchdog.arm(10, 0);
+        watchdog.disarm();
+        assert!(watchdog.check(100).is_none());
+        assert_eq!(watchdog.misses(), 0);
+    }
+}
@@ -1,4 +1,5 @@
-//! Performance benchmarks for Quicksilver VM vs tree-walking interpreter
+//! Performance benchmarks for Quicksilver VM vs tree-walking interpreter,
+//! and for scope-resolved variable lookups in the interpreter
 //!
 //! Run with: `cargo test --test benchmark -- --nocapture --ignored`
 
@@ -47,6 +48,44 @@ fn benchmark(name: &str, source: &str, iterations: usize) {
     println!("Speedup:     {:.2}x", speedup);
 }
 
+/// Run a benchmark comparing name lookups against scope-resolved slots
+fn benchmark_resolution(name: &str, source: &str, iterations: usize) {
+    use glimmer_weave::resolve_scopes;
+
+    println!("\n=== {} ===", name);
+    println!("Iterations: {}", iterations);
+
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize_positioned();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().expect("Parse failed");
+
+    let mut resolved = ast.clone();
+    resolve_scopes(&mut resolved);
+
+    let start = Instant::now();
+    let mut expected = None;
+    for _ in 0..iterations {
+        let mut evaluator = Evaluator::new();
+        expected = Some(evaluator.eval(&ast).expect("Interpreter failed"));
+    }
+    let unresolved_time = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        let mut evaluator = Evaluator::new();
+        let result = evaluator.eval(&resolved).expect("Interpreter failed");
+        assert_eq!(Some(result), expected);
+    }
+    let resolved_time = start.elapsed();
+
+    let speedup = unresolved_time.as_secs_f64() / resolved_time.as_secs_f64();
+
+    println!("Name lookup: {:?} ({:.2} µs/iter)", unresolved_time, unresolved_time.as_micros() as f64 / iterations as f64);
+    println!("Resolved:    {:?} ({:.2} µs/iter)", resolved_time, resolved_time.as_micros() as f64 / iterations as f64);
+    println!("Speedup:     {:.2}x", speedup);
+}
+
 #[test]
 #[ignore] // Run explicitly with: cargo test --test benchmark -- --nocapture --ignored
 fn bench_simple_arithmetic() {
@@ -106,6 +145,45 @@ j
     );
 }
 
+#[test]
+#[ignore]
+fn bench_resolved_while_loop() {
+    benchmark_resolution(
+        "Resolved Lookups: Counting Loop",
+        r#"
+weave total as 0
+weave i as 0
+whilst i is not 10000 then
+    set total to total + i * 2
+    set i to i + 1
+end
+total
+        "#,
+        20
+    );
+}
+
+#[test]
+#[ignore]
+fn bench_resolved_nested_loops() {
+    benchmark_resolution(
+        "Resolved Lookups: Nested Loops in a Chant",
+        r#"
+chant grid_sum(size) then
+    weave total as 0
+    for each row in range(0, size) then
+        for each col in range(0, size) then
+            set total to total + row * col
+        end
+    end
+    yield total
+end
+grid_sum(100)
+        "#,
+        20
+    );
+}
+
 #[test]
 #[ignore]
 fn run_all_benchmarks() {
@@ -118,6 +196,8 @@ fn run_all_benchmarks() {
     bench_global_variables();
     bench_comparisons();
     bench_fibonacci_expression();
+    bench_resolved_while_loop();
+    bench_resolved_nested_loops();
 
     println!("\n╔═══════════════════════════════════════════════════════════╗");
     println!("║                   Benchmark Complete                      ║");
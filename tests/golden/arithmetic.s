@@ -0,0 +1,34 @@
+.text
+.globl main
+
+    # External runtime functions (custom allocator in native_allocator.S)
+.globl gl_malloc
+.globl gl_free
+
+main:
+    pushq %rbp
+    pushq %rbx
+    movq %rsp, %rbp
+    subq $24, %rsp
+    movq $12, %rax
+    movq %rax, -8(%rbp)
+    movq $5, %rax
+    movq %rax, -16(%rbp)
+    movq -8(%rbp), %rax
+    pushq %rax
+    movq -16(%rbp), %rax
+    movq %rax, %rbx
+    popq %rax
+    imulq %rbx, %rax
+    pushq %rax
+    movq $3, %rax
+    movq %rax, %rbx
+    popq %rax
+    subq %rbx, %rax
+    movq %rax, -24(%rbp)
+    movq -24(%rbp), %rax
+    movq $0, %rax
+    movq %rbp, %rsp
+    popq %rbx
+    popq %rbp
+    ret
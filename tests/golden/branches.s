@@ -0,0 +1,44 @@
+.text
+.globl main
+
+    # External runtime functions (custom allocator in native_allocator.S)
+.globl gl_malloc
+.globl gl_free
+
+main:
+    pushq %rbp
+    pushq %rbx
+    movq %rsp, %rbp
+    subq $8, %rsp
+    movq $7, %rax
+    movq %rax, -8(%rbp)
+    movq -8(%rbp), %rax
+    pushq %rax
+    movq $5, %rax
+    movq %rax, %rbx
+    popq %rax
+    cmpq %rbx, %rax
+    movq $0, %rax
+    setg %al
+    cmpq $0, %rax
+    je .L_else_0
+    movq -8(%rbp), %rax
+    pushq %rax
+    movq $5, %rax
+    movq %rax, %rbx
+    popq %rax
+    subq %rbx, %rax
+    jmp .L_if_end_0
+.L_else_0:
+    movq -8(%rbp), %rax
+    pushq %rax
+    movq $5, %rax
+    movq %rax, %rbx
+    popq %rax
+    addq %rbx, %rax
+.L_if_end_0:
+    movq $0, %rax
+    movq %rbp, %rsp
+    popq %rbx
+    popq %rbp
+    ret
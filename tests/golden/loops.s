@@ -0,0 +1,76 @@
+.text
+.globl main
+
+    # External runtime functions (custom allocator in native_allocator.S)
+.globl gl_malloc
+.globl gl_free
+
+main:
+    pushq %rbp
+    pushq %rbx
+    movq %rsp, %rbp
+    subq $8, %rsp
+    jmp .L_func_end_triangle
+.L_func_triangle:
+    pushq %rbp
+    pushq %rbx
+    movq %rsp, %rbp
+    subq $24, %rsp
+    movq %rdi, -8(%rbp)
+    movq $0, %rax
+    movq %rax, -16(%rbp)
+    movq $1, %rax
+    movq %rax, -24(%rbp)
+.L_while_start_0:
+    movq -24(%rbp), %rax
+    pushq %rax
+    movq -8(%rbp), %rax
+    movq %rax, %rbx
+    popq %rax
+    cmpq %rbx, %rax
+    movq $0, %rax
+    setl %al
+    cmpq $0, %rax
+    je .L_while_end_0
+    movq -16(%rbp), %rax
+    pushq %rax
+    movq -24(%rbp), %rax
+    movq %rax, %rbx
+    popq %rax
+    addq %rbx, %rax
+    movq %rax, -16(%rbp)
+    movq -24(%rbp), %rax
+    pushq %rax
+    movq $1, %rax
+    movq %rax, %rbx
+    popq %rax
+    addq %rbx, %rax
+    movq %rax, -24(%rbp)
+    jmp .L_while_start_0
+.L_while_end_0:
+    movq -16(%rbp), %rax
+    movq %rbp, %rsp
+    popq %rbx
+    popq %rbp
+    ret
+    movq %rbp, %rsp
+    popq %rbx
+    popq %rbp
+    ret
+.L_func_end_triangle:
+    subq $16, %rsp
+    movq $10, %rax
+    movq %rax, 0(%rsp)
+    movq 0(%rsp), %rdi
+    addq $16, %rsp
+    call .L_func_triangle
+    movq $0, %rax
+    movq %rbp, %rsp
+    popq %rbx
+    popq %rbp
+    ret
+
+.globl glimmer_triangle
+.type glimmer_triangle, @function
+glimmer_triangle:
+    jmp .L_func_triangle
@@ -43,6 +43,7 @@ fn assert_eval(source: &str, expected: Value) {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_number_literals() {
     assert_eval("42", Value::Number(42.0));
     assert_eval("3.14", Value::Number(3.14));
@@ -576,7 +577,7 @@ end
 
 #[test]
 fn test_maybe_with_function() {
-    let source = r#"
+    let _source = r#"
 chant find_first(list) then
     should list[0] is nothing then
         yield Absent
@@ -0,0 +1,124 @@
+// Only compile these tests if the allocator was successfully built
+#![cfg(all(target_arch = "x86_64", not(target_env = "msvc")))]
+
+//! Tests for the embedded heap configuration of the native allocator:
+//! a host-supplied fixed heap, extra regions, the OOM handler and the heap
+//! statistics builtins.
+//!
+//! Each integration test file is its own process, so this file owns a fresh
+//! allocator and initializes it over static regions before anything else
+//! allocates from it.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, Once};
+
+use glimmer_weave::native_allocator::*;
+use glimmer_weave::{Evaluator, Lexer, Parser, Value};
+
+const HEAP_SIZE: usize = 4096;
+
+// Quadword arrays keep the regions 8-byte aligned
+static mut HEAP: [u64; HEAP_SIZE / 8] = [0; HEAP_SIZE / 8];
+static mut SPARE: [u64; HEAP_SIZE / 8] = [0; HEAP_SIZE / 8];
+
+static INIT: Once = Once::new();
+static ALLOCATOR_LOCK: Mutex<()> = Mutex::new(());
+
+static OOM_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Take the allocator, initializing it over `HEAP` on first use
+fn fixed_heap() -> MutexGuard<'static, ()> {
+    let guard = ALLOCATOR_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    INIT.call_once(|| unsafe {
+        assert!(gl_init_allocator_with(std::ptr::addr_of_mut!(HEAP) as *mut u8, HEAP_SIZE));
+    });
+    guard
+}
+
+extern "C" fn grow_once(_size: usize) -> bool {
+    // Hand over the spare region the first time, then give up
+    OOM_CALLS.fetch_add(1, Ordering::SeqCst) == 0
+        && unsafe { gl_add_region(std::ptr::addr_of_mut!(SPARE) as *mut u8, HEAP_SIZE) }
+}
+
+fn eval(source: &str) -> Value {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().expect("parse failed");
+    Evaluator::new().eval(&ast).expect("eval failed")
+}
+
+#[test]
+fn test_fixed_heap_serves_from_host_region() {
+    let _guard = fixed_heap();
+    unsafe {
+        // The allocator is initialized already, so a second init is refused
+        assert!(!gl_init_allocator_with(std::ptr::addr_of_mut!(SPARE) as *mut u8, HEAP_SIZE));
+
+        let ptr = gl_malloc(64);
+        assert!(!ptr.is_null());
+        let heap = std::ptr::addr_of!(HEAP) as usize;
+        let spare = std::ptr::addr_of!(SPARE) as usize;
+        let in_region = |region: usize| (region..region + HEAP_SIZE).contains(&(ptr as usize));
+        assert!(in_region(heap) || in_region(spare), "allocation comes from a host region");
+        gl_free(ptr);
+    }
+}
+
+#[test]
+fn test_unusable_regions_are_rejected() {
+    let _guard = fixed_heap();
+    unsafe {
+        let base = std::ptr::addr_of_mut!(SPARE) as *mut u8;
+        assert!(!gl_add_region(std::ptr::null_mut(), HEAP_SIZE));
+        assert!(!gl_add_region(base.add(1), 64), "misaligned base");
+        assert!(!gl_add_region(base, 16), "too small for a block");
+    }
+}
+
+#[test]
+fn test_oom_handler_grows_fixed_heap() {
+    let _guard = fixed_heap();
+    unsafe {
+        gl_set_oom_handler(Some(grow_once));
+
+        // More than the initial region holds: the handler adds the spare one
+        let big = gl_malloc(HEAP_SIZE / 2 + HEAP_SIZE / 4);
+        let bigger = gl_malloc(HEAP_SIZE / 2 + HEAP_SIZE / 4);
+        assert!(!big.is_null() && !bigger.is_null());
+        assert_eq!(OOM_CALLS.load(Ordering::SeqCst), 1);
+        assert_eq!(gl_get_heap_size(), 2 * HEAP_SIZE as u64);
+
+        // No more regions to give: the request fails instead of mapping memory
+        assert!(gl_malloc(HEAP_SIZE).is_null());
+        assert_eq!(OOM_CALLS.load(Ordering::SeqCst), 2);
+
+        gl_set_oom_handler(None);
+        gl_free(big);
+        gl_free(bigger);
+    }
+}
+
+#[test]
+fn test_heap_stats_track_allocations() {
+    let _guard = fixed_heap();
+    let before = heap_stats();
+    assert!(before.used + before.free < before.size, "headers and sentinels are overhead");
+
+    let ptr = unsafe { gl_malloc(128) };
+    let during = heap_stats();
+    assert_eq!(during.used, before.used + 128);
+    assert!(during.free < before.free);
+
+    unsafe { gl_free(ptr) };
+    let after = heap_stats();
+    assert_eq!(after.used, before.used);
+    assert!(after.free >= during.free + 128);
+}
+
+#[test]
+fn test_heap_builtins() {
+    let _guard = fixed_heap();
+    let stats = heap_stats();
+    assert_eq!(eval("heap_used()"), Value::Number(stats.used as f64));
+    assert_eq!(eval("Heap.free()"), Value::Number(stats.free as f64));
+}
@@ -1,7 +1,7 @@
 // Only compile these tests if the allocator was successfully built
 #![cfg(all(target_arch = "x86_64", not(target_env = "msvc")))]
 
-/**
+/*
  * Rust-based unit tests for gl_malloc/gl_free heap allocator
  *
  * Tests the free-list allocator implemented in native_allocator.S
@@ -16,13 +16,23 @@
  */
 
 use std::ptr;
+use std::sync::{Mutex, MutexGuard};
 use glimmer_weave::native_allocator::*;
 
+/// The allocator keeps a single global heap and is not thread-safe, so the
+/// tests (which cargo runs in parallel) take turns through this lock.
+static ALLOCATOR_LOCK: Mutex<()> = Mutex::new(());
+
+fn allocator_lock() -> MutexGuard<'static, ()> {
+    ALLOCATOR_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
 //==============================================================================
 // Test 1: Basic Allocation
 //==============================================================================
 #[test]
 fn test_basic_allocation() {
+    let _guard = allocator_lock();
     unsafe {
         // Allocate a small block
         let ptr1 = gl_malloc(64);
@@ -53,6 +63,7 @@ fn test_basic_allocation() {
 //==============================================================================
 #[test]
 fn test_multiple_allocations() {
+    let _guard = allocator_lock();
     unsafe {
         let ptr1 = gl_malloc(32);
         let ptr2 = gl_malloc(64);
@@ -89,6 +100,7 @@ fn test_multiple_allocations() {
 //==============================================================================
 #[test]
 fn test_allocation_after_free() {
+    let _guard = allocator_lock();
     unsafe {
         // Allocate and free a block
         let ptr1 = gl_malloc(128);
@@ -110,6 +122,7 @@ fn test_allocation_after_free() {
 //==============================================================================
 #[test]
 fn test_free_null() {
+    let _guard = allocator_lock();
     unsafe {
         // Freeing NULL should not crash
         gl_free(ptr::null_mut());
@@ -127,6 +140,7 @@ fn test_free_null() {
 //==============================================================================
 #[test]
 fn test_forward_coalescing() {
+    let _guard = allocator_lock();
     unsafe {
         // Allocate three adjacent blocks
         let ptr1 = gl_malloc(64);
@@ -162,6 +176,7 @@ fn test_forward_coalescing() {
 //==============================================================================
 #[test]
 fn test_alignment() {
+    let _guard = allocator_lock();
     unsafe {
         // Test various allocation sizes to verify alignment
         let sizes = [1, 7, 8, 15, 16, 33, 64, 127, 128];
@@ -185,7 +200,7 @@ fn test_alignment() {
                 "INFO: malloc({}) = {:p} (aligned: {})",
                 size,
                 ptr,
-                if addr % 8 == 0 { "✓" } else { "✗" }
+                if addr.is_multiple_of(8) { "✓" } else { "✗" }
             );
 
             ptrs.push(ptr);
@@ -203,6 +218,7 @@ fn test_alignment() {
 //==============================================================================
 #[test]
 fn test_statistics() {
+    let _guard = allocator_lock();
     unsafe {
         let initial_bytes = gl_get_allocated_bytes();
         println!("INFO: Initial allocated bytes: {}", initial_bytes);
@@ -237,6 +253,7 @@ fn test_statistics() {
 //==============================================================================
 #[test]
 fn test_large_allocation() {
+    let _guard = allocator_lock();
     unsafe {
         // Allocate a large block that requires heap expansion
         // Initial heap is 64KB, so allocate 128KB
@@ -275,6 +292,7 @@ fn test_large_allocation() {
 //==============================================================================
 #[test]
 fn test_many_small_allocations() {
+    let _guard = allocator_lock();
     const NUM_ALLOCS: usize = 100;
     let mut ptrs = Vec::with_capacity(NUM_ALLOCS);
 
@@ -312,6 +330,7 @@ fn test_many_small_allocations() {
 //==============================================================================
 #[test]
 fn test_interleaved_alloc_free() {
+    let _guard = allocator_lock();
     unsafe {
         let ptr1 = gl_malloc(64);
         let ptr2 = gl_malloc(128);
@@ -337,6 +356,7 @@ fn test_interleaved_alloc_free() {
 //==============================================================================
 #[test]
 fn test_zero_size_allocation() {
+    let _guard = allocator_lock();
     unsafe {
         // Zero-size allocation behavior is implementation-defined
         let ptr = gl_malloc(0);
@@ -360,6 +380,7 @@ fn test_zero_size_allocation() {
 //==============================================================================
 #[test]
 fn test_block_splitting() {
+    let _guard = allocator_lock();
     unsafe {
         println!("DEBUG: test_block_splitting starting");
 
@@ -408,6 +429,7 @@ fn test_block_splitting() {
 //==============================================================================
 #[test]
 fn test_heap_bounds() {
+    let _guard = allocator_lock();
     unsafe {
         let heap_start = gl_get_heap_start();
         let heap_end = gl_get_heap_end();
@@ -439,6 +461,7 @@ fn test_heap_bounds() {
 //==============================================================================
 #[test]
 fn test_stress_random() {
+    let _guard = allocator_lock();
     use std::collections::HashMap;
 
     const STRESS_ITERS: usize = 500;
@@ -0,0 +1,58 @@
+//! Tests for structural AST queries
+
+use glimmer_weave::ast_query::{find_calls, node_name, Query};
+use glimmer_weave::{AstNode, Lexer, Parser};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+const SOURCE: &str = r#"grove Screen with
+    chant clear() then
+        VGA.write("")
+    end
+    chant banner(text) then
+        whilst false then
+            VGA.write(text)
+        end
+        VGA.write_at(text, 0)
+    end
+end
+VGA.write("ready")
+"#;
+
+#[test]
+fn test_find_calls_returns_spans_in_source_order() {
+    let ast = parse(SOURCE);
+    let lines: Vec<usize> = find_calls(&ast, "VGA.write").iter().map(|span| span.start.line).collect();
+    assert_eq!(lines, vec![3, 7, 12]);
+    assert!(find_calls(&ast, "VGA").is_empty());
+}
+
+#[test]
+fn test_matches_know_their_enclosing_nodes() {
+    let ast = parse(SOURCE);
+    let found = Query::call_to("VGA.write").inside(Query::kind("WhileStmt")).find(&ast);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].enclosing_name("ChantDef").as_deref(), Some("banner"));
+    assert_eq!(found[0].enclosing_name("ModuleDecl").as_deref(), Some("Screen"));
+    assert_eq!(found[0].ancestors.len(), 4);
+}
+
+#[test]
+fn test_combinators() {
+    let ast = parse(SOURCE);
+    let writers = Query::kind("ChantDef").containing(Query::call_to("VGA.write"));
+    let names: Vec<String> = writers.find(&ast).iter().filter_map(|found| node_name(found.node)).collect();
+    assert_eq!(names, vec!["clear", "banner"]);
+
+    let any_vga = Query::call_to("VGA.write").or(Query::call_to("VGA.write_at"));
+    assert_eq!(any_vga.find(&ast).len(), 4);
+
+    let top_level = Query::call_to("VGA.write").and(!Query::any().inside(Query::kind("ModuleDecl")));
+    assert_eq!(top_level.find(&ast).len(), 1);
+
+    assert!(Query::named("banner").exists(&ast));
+    assert!(!Query::kind("MatchStmt").exists(&ast));
+}
@@ -0,0 +1,117 @@
+//! Tests for arbitrary-precision integers (`123n`)
+
+use glimmer_weave::bigint::BigInt;
+use glimmer_weave::bytecode_compiler::compile;
+use glimmer_weave::codegen::compile_to_asm;
+use glimmer_weave::runtime::BIGINT_POW_MAX_BITS;
+use glimmer_weave::semantic::{analyze, SemanticError};
+use glimmer_weave::vm::VM;
+use glimmer_weave::{run, AstNode, EvalOptions, Evaluator, Lexer, Parser, RuntimeError, SemanticAnalyzer, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn eval(source: &str) -> Value {
+    Evaluator::new().eval(&parse(source)).unwrap()
+}
+
+fn big(text: &str) -> Value {
+    Value::BigInt(BigInt::parse(text).unwrap())
+}
+
+#[test]
+fn test_literals_stay_exact_past_2_pow_53() {
+    // As Numbers, 2^53 + 1 rounds back down to 2^53
+    assert_eq!(eval("9007199254740993 - 9007199254740992"), Value::Number(0.0));
+    assert_eq!(eval("9007199254740993n - 9007199254740992n"), big("1"));
+
+    assert_eq!(eval("18446744073709551615n * 18446744073709551615n"), big("340282366920938463426481119284349108225"));
+    assert_eq!(eval("-7n / 2n"), big("-3"));
+    assert_eq!(eval("-7n % 2n"), big("-1"));
+    assert_eq!(eval("to_text(2n * 50000000000000000000n)"), Value::Text("100000000000000000000".to_string()));
+    assert_eq!(eval("10000000000000000000n greater than 9999999999999999999n"), Value::Truth(true));
+    assert_eq!(eval("0n is bigint(0)"), Value::Truth(true));
+
+    // `n` only suffixes whole numbers, and not the start of a name
+    assert!(Evaluator::new().eval(&parse("1.5n")).is_err());
+    assert_eq!(eval("bind n to 2\n3 * n"), Value::Number(6.0));
+}
+
+#[test]
+fn test_builtins() {
+    assert_eq!(eval("bigint(\"123456789012345678901234567890\") + 1n"), big("123456789012345678901234567891"));
+    assert_eq!(eval("bigint(4096)"), big("4096"));
+    assert_eq!(eval("bigint_pow(2n, 100)"), big("1267650600228229401496703205376"));
+    assert_eq!(eval("BigInt.mod_pow(4n, 13n, 497n)"), big("445"));
+    assert_eq!(eval("to_number(12345678901234567890n)"), Value::Number(12345678901234567890.0));
+    assert_eq!(eval("list_sort([3n, -1n, 2n])"), Value::List(vec![big("-1"), big("2"), big("3")]));
+    assert_eq!(eval("hash(5n) is hash(bigint(\"5\"))"), Value::Truth(true));
+
+    for source in ["bigint(0.5)", "bigint(\"12a\")", "bigint_pow(2n, -1)", "bigint_mod_pow(2n, 3n, 0n)", "1n / 0n"] {
+        assert!(Evaluator::new().eval(&parse(source)).is_err(), "{}", source);
+    }
+}
+
+#[test]
+fn test_pow_result_size_is_limited() {
+    let pow = |source: &str| Evaluator::new().eval(&parse(source));
+
+    // 3n takes 2 bits, so 3n^131072 may take the full limit and 3n^131073 not
+    assert_eq!(BIGINT_POW_MAX_BITS, 1 << 18);
+    assert!(pow("bigint_pow(3n, 131072)").is_ok());
+    assert_eq!(
+        pow("bigint_pow(3n, 131073)"),
+        Err(RuntimeError::Custom(
+            "bigint_pow: result of up to 262146 bits is over the limit of 262144 bits".to_string()
+        ))
+    );
+    assert!(pow("bigint_pow(12345678901234567890n, 4294967295)").is_err());
+
+    // Powers that stay small are not refused, however large the exponent
+    assert_eq!(pow("bigint_pow(1n, 4294967295)"), Ok(big("1")));
+    assert_eq!(pow("bigint_pow(-1n, 4294967295)"), Ok(big("-1")));
+    assert_eq!(pow("bigint_pow(0n, 4294967295)"), Ok(big("0")));
+}
+
+#[test]
+fn test_mixing_with_numbers_is_an_error() {
+    let errors = |source: &str| -> Vec<String> {
+        analyze(&parse(source))
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|error| match error {
+                SemanticError::TypeError { got, .. } => Some(got),
+                _ => None,
+            })
+            .collect()
+    };
+    assert_eq!(errors("bind x to 1n + 1"), ["BigInt Add Number"]);
+    assert_eq!(errors("bind x to 2 greater than 1n"), ["Number Greater BigInt"]);
+    assert_eq!(analyze(&parse("chant twice(n: BigInt) then\n    n * 2n\nend\ntwice(-3n)")), Ok(()));
+    assert!(analyze(&parse("bind id: BigInt to 3")).is_err());
+
+    // Unchecked code still fails at run time instead of rounding
+    assert!(Evaluator::new().eval(&parse("1n + 1")).is_err());
+    assert!(run("bind x to 1n * 2.5", EvalOptions::new()).is_err());
+
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.enable_type_inference();
+    assert_eq!(analyzer.infer_program_types(&parse("bind a to 1n\nbind b to a * a - 3n")), Ok(()));
+    assert!(analyzer.infer_program_types(&parse("bind a to 1n\nbind b to a + 1")).is_err());
+}
+
+#[test]
+fn test_backends_agree() {
+    let source = "123456789012345678901234567890n * -2n + 20n";
+    let expected = big("-246913578024691357802469135760");
+    assert_eq!(eval(source), expected);
+    assert_eq!(run(source, EvalOptions::new()).unwrap(), expected);
+    assert_eq!(VM::new().execute(compile(&parse(source)).unwrap()).unwrap(), expected);
+    assert_eq!(VM::new().execute(compile(&parse("3n less than 5n")).unwrap()).unwrap(), Value::Truth(true));
+
+    // Native integers wrap, so big integers stay out of native code
+    assert!(compile_to_asm(&parse("1n")).is_err());
+}
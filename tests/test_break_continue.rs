@@ -1,10 +1,10 @@
-/// Tests for loop control flow: break and continue statements
-///
-/// These tests verify that break and continue work correctly in:
-/// - for each loops
-/// - whilst loops
-/// - nested loops
-/// - error cases (outside loops)
+//! Tests for loop control flow: break and continue statements
+//!
+//! These tests verify that break and continue work correctly in:
+//! - for each loops
+//! - whilst loops
+//! - nested loops
+//! - error cases (outside loops)
 
 use glimmer_weave::{Lexer, Parser, Evaluator, Value};
 
@@ -0,0 +1,59 @@
+//! Tests for the feature-gated builtin groups and BuiltinRegistry
+
+use glimmer_weave::runtime::{get_builtins, BuiltinGroup, BuiltinRegistry};
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn eval(source: &str) -> Result<Value, RuntimeError> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().expect("Parse error");
+    Evaluator::new().eval(&ast)
+}
+
+#[test]
+fn test_registry_holds_enabled_groups() {
+    let registry = BuiltinRegistry::new();
+    let expected: usize = BuiltinGroup::ALL
+        .iter()
+        .filter(|group| group.is_enabled())
+        .map(|group| group.functions().len())
+        .sum();
+    assert_eq!(registry.functions().len(), expected);
+    assert_eq!(get_builtins().len(), expected);
+    assert!(registry.contains("list_push"));
+}
+
+#[test]
+fn test_core_group_is_always_enabled() {
+    assert!(BuiltinGroup::Core.is_enabled());
+    let mut registry = BuiltinRegistry::empty();
+    registry.register_group(BuiltinGroup::Core);
+    assert!(registry.contains("triumph_or"));
+    assert!(!registry.contains("sqrt"));
+    assert!(!registry.contains("upper"));
+}
+
+#[test]
+fn test_register_replaces_by_name() {
+    let mut registry = BuiltinRegistry::empty();
+    registry.register_group(BuiltinGroup::Core);
+    let count = registry.functions().len();
+    let print = registry.get("print").unwrap().clone();
+    registry.register(print);
+    assert_eq!(registry.functions().len(), count);
+}
+
+#[cfg(feature = "runtime-math")]
+#[test]
+fn test_math_group_enabled() {
+    assert!(BuiltinGroup::Math.is_enabled());
+    assert_eq!(eval("sin(0)"), Ok(Value::Number(0.0)));
+}
+
+#[cfg(not(feature = "runtime-math"))]
+#[test]
+fn test_math_group_compiled_out() {
+    assert!(BuiltinGroup::Math.functions().is_empty());
+    assert_eq!(eval("sin(0)"), Err(RuntimeError::UndefinedVariable("sin".to_string())));
+    // The Math namespace goes away with its group
+    assert_eq!(eval("Math.sin(0)"), Err(RuntimeError::UndefinedVariable("Math".to_string())));
+}
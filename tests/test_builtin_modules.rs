@@ -0,0 +1,99 @@
+//! Tests for namespaced builtin modules
+//!
+//! Builtins are reachable as `Text.upper`, `List.push`, `Math.sqrt`, and so
+//! on; the flat names stay available behind the prelude's compatibility flag.
+
+use glimmer_weave::{Evaluator, Lexer, Parser, Prelude, RuntimeError, SemanticAnalyzer, SemanticError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+fn eval_with(prelude: &Prelude, source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::with_prelude(prelude).eval(&parse(source))
+}
+
+fn eval(source: &str) -> Result<Value, RuntimeError> {
+    eval_with(&Prelude::default(), source)
+}
+
+#[test]
+fn test_namespaced_calls() {
+    assert_eq!(eval("Text.upper(\"glow\")"), Ok(Value::Text("GLOW".to_string())));
+    assert_eq!(eval("Math.sqrt(16)"), Ok(Value::Number(4.0)));
+    assert_eq!(eval("List.length(List.push([1, 2], 3))"), Ok(Value::Number(3.0)));
+    assert_eq!(eval("Convert.to_text(42)"), Ok(Value::Text("42".to_string())));
+}
+
+#[test]
+fn test_keyword_members() {
+    assert_eq!(eval("List.first([7, 8, 9])"), Ok(Value::Number(7.0)));
+    assert_eq!(eval("List.last([7, 8, 9])"), Ok(Value::Number(9.0)));
+    assert_eq!(eval("bind cell to Cell.new(1)\nCell.set(cell, 5)"), Ok(Value::Nothing));
+}
+
+#[test]
+fn test_members_are_values() {
+    let source = "bind shout to Text.upper\nshout(\"hi\")";
+    assert_eq!(eval(source), Ok(Value::Text("HI".to_string())));
+}
+
+#[test]
+fn test_user_binding_shadows_module() {
+    let source = "bind Text to {upper: 1}\nText.upper";
+    assert_eq!(eval(source), Ok(Value::Number(1.0)));
+}
+
+#[test]
+fn test_unknown_member() {
+    assert_eq!(
+        eval("Math.shout(1)"),
+        Err(RuntimeError::UndefinedVariable("Math.shout".to_string()))
+    );
+}
+
+#[test]
+fn test_modules_without_flat_aliases() {
+    // The core prelude keeps the namespaces but drops the flat names
+    assert_eq!(
+        eval_with(&Prelude::core(), "Text.upper(\"glow\")"),
+        Ok(Value::Text("GLOW".to_string()))
+    );
+    assert_eq!(
+        eval_with(&Prelude::core(), "upper(\"glow\")"),
+        Err(RuntimeError::UndefinedVariable("upper".to_string()))
+    );
+
+    // A user chant can now take a name the flat library used to own
+    let source = "chant upper(x) then\n    yield x + 1\nend\nupper(1) + Math.abs(-1)";
+    assert_eq!(eval_with(&Prelude::core(), source), Ok(Value::Number(3.0)));
+}
+
+#[test]
+fn test_modules_can_be_disabled() {
+    assert_eq!(
+        eval_with(&Prelude::core().builtin_modules(false), "Text.upper(\"glow\")"),
+        Err(RuntimeError::UndefinedVariable("Text".to_string()))
+    );
+}
+
+#[test]
+fn test_semantic_analysis_of_module_calls() {
+    assert!(SemanticAnalyzer::with_prelude(&Prelude::core()).analyze(&parse("Math.sqrt(16)")).is_ok());
+
+    let errors = SemanticAnalyzer::new().analyze(&parse("Math.sqrt(1, 2)")).unwrap_err();
+    assert!(
+        matches!(&errors[..], [SemanticError::ImportSignatureMismatch { symbol, module, .. }]
+            if symbol == "sqrt" && module == "Math"),
+        "{:?}",
+        errors
+    );
+
+    let errors = SemanticAnalyzer::new().analyze(&parse("Math.shout(1)")).unwrap_err();
+    assert!(
+        matches!(&errors[..], [SemanticError::SymbolNotExported { symbol, .. }] if symbol == "shout"),
+        "{:?}",
+        errors
+    );
+}
@@ -0,0 +1,77 @@
+//! Tests for the signatures, descriptions and capabilities builtins are
+//! registered with
+use glimmer_weave::runtime::{BuiltinParam, BuiltinRegistry, BUILTIN_MODULES};
+
+#[test]
+fn test_every_builtin_is_documented() {
+    let registry = BuiltinRegistry::new();
+    for builtin in registry.functions() {
+        assert!(builtin.signature.starts_with('('), "{} has no signature", builtin.name);
+        assert!(builtin.returns().is_some(), "{} has no result type", builtin.name);
+        assert!(!builtin.description.is_empty(), "{} has no description", builtin.name);
+
+        // The required parameters are the ones a fixed arity counts
+        let params = builtin.params();
+        let required = params.iter().filter(|param| !param.optional && !param.rest).count();
+        match builtin.arity {
+            Some(arity) => assert_eq!((required, params.len()), (arity, arity), "{}", builtin.usage()),
+            None => assert!(params.iter().any(|param| param.optional || param.rest), "{} is variadic", builtin.usage()),
+        }
+    }
+}
+
+#[test]
+fn test_signatures_parse_into_params() {
+    let registry = BuiltinRegistry::new();
+    let subscribe = registry.get("subscribe").unwrap();
+    assert_eq!(
+        subscribe.params(),
+        vec![
+            BuiltinParam { name: "topic", typ: "Text", optional: false, rest: false },
+            BuiltinParam { name: "handler", typ: "Chant", optional: false, rest: false },
+            BuiltinParam { name: "priority", typ: "Number", optional: true, rest: false },
+        ]
+    );
+    let spawn = registry.get("spawn").unwrap();
+    assert!(spawn.params()[1].rest);
+    // Commas inside type arguments don't split parameters
+    let validate = registry.get("validate").unwrap();
+    assert_eq!(validate.params().len(), 2);
+    assert_eq!(validate.returns(), Some("Outcome<Any, Text>"));
+}
+
+#[test]
+fn test_describe_resolves_module_members() {
+    let registry = BuiltinRegistry::new();
+    assert_eq!(registry.describe("Text.upper").map(|builtin| builtin.name.as_str()), Some("upper"));
+    assert_eq!(registry.describe("upper").map(|builtin| builtin.name.as_str()), Some("upper"));
+    assert!(registry.describe("Text.shout").is_none());
+    assert!(registry.describe("Nowhere.upper").is_none());
+    assert_eq!(
+        registry.describe("Store.get").unwrap().help(),
+        "store_get(key: Text) -> Maybe\nThe value stored under the key\nRequires `Store.readwrite`"
+    );
+
+    // Every module member names a documented builtin
+    for (module, members) in BUILTIN_MODULES {
+        for (member, _) in *members {
+            let name = format!("{}.{}", module, member);
+            if let Some(builtin) = registry.describe(&name) {
+                assert!(!builtin.description.is_empty(), "{}", name);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_completions_and_capability_queries() {
+    let registry = BuiltinRegistry::new();
+    let names: Vec<&str> = registry.completions("list_s").iter().map(|builtin| builtin.name.as_str()).collect();
+    assert_eq!(names, vec!["list_slice", "list_sort", "list_sum"]);
+    assert!(registry.completions("zzz").is_empty());
+
+    let mut drawing: Vec<&str> = registry.requiring("Framebuffer.draw").map(|builtin| builtin.name.as_str()).collect();
+    drawing.sort();
+    assert_eq!(drawing, vec!["draw_blit", "draw_clear", "draw_pixel", "draw_present", "draw_rect", "draw_size", "draw_text"]);
+    assert!(registry.get("upper").unwrap().capabilities.is_empty());
+}
@@ -0,0 +1,144 @@
+//! Tests for single-file script bundles
+//!
+//! Modules are registered as in-memory sources so the tests do not touch
+//! the filesystem.
+
+use glimmer_weave::bundle::{Bundle, BundleError, BundleFormat};
+use glimmer_weave::module_cache::hash_hex;
+use glimmer_weave::pipeline::{Output, Target};
+use glimmer_weave::verify::VerifyError;
+use glimmer_weave::vm::VM;
+use glimmer_weave::{CompilerPipeline, ModuleResolver, Value};
+
+const CONSTANTS: &str = "grove Constants with\n    bind base to 40\n    bind step to 2\n    offer base, step\nend";
+
+const SHAPES: &str = r#"grove Shapes with
+    summon Constants from "constants.gw"
+
+    chant double(n) then
+        yield n * 2
+    end
+
+    offer double
+end"#;
+
+const MAIN: &str = "summon Constants from \"constants.gw\"\nsummon Shapes from \"lib/shapes.gw\"\nShapes.double(Constants.base) + Constants.step";
+
+fn project() -> ModuleResolver {
+    let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+    resolver.add_source("/project/constants.gw", CONSTANTS);
+    resolver.add_source("/project/lib/shapes.gw", SHAPES);
+    resolver.add_source("/project/main.gw", MAIN);
+    resolver
+}
+
+/// Signs by reversing the bytes, which the verifier checks
+fn sign(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().rev().copied().collect()
+}
+
+fn verifier(image: &[u8], signature: &[u8]) -> Result<(), VerifyError> {
+    if sign(image) == signature {
+        Ok(())
+    } else {
+        Err(VerifyError::InvalidSignature { reason: "does not match".to_string() })
+    }
+}
+
+fn run(bundle: &Bundle) -> Value {
+    let mut resolver = ModuleResolver::new("/installed".to_string(), "/std".to_string());
+    let entry = bundle.install(&mut resolver);
+    match CompilerPipeline::new().compile_project(&entry, &mut resolver, Target::Eval) {
+        Ok(Output::Value(value)) => value,
+        other => panic!("Expected a value, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_manifest_lists_the_module_graph() {
+    let bundle = Bundle::build("/project/main.gw", &mut project()).unwrap();
+    let modules: Vec<(&str, &str)> =
+        bundle.manifest.modules.iter().map(|module| (module.path.as_str(), module.name.as_str())).collect();
+    assert_eq!(
+        modules,
+        vec![
+            ("constants.gw", "Constants"),
+            ("lib/shapes.gw", "Shapes"),
+            ("main.gw", "main"),
+        ]
+    );
+    assert_eq!(bundle.source("lib/shapes.gw"), Some(SHAPES));
+    assert_eq!(bundle.manifest.entry, "main.gw");
+}
+
+#[test]
+fn test_both_forms_roundtrip_and_run() {
+    let bundle = Bundle::build("/project/main.gw", &mut project()).unwrap();
+    for format in [BundleFormat::Source, BundleFormat::Archive] {
+        let decoded = Bundle::decode(&bundle.encode(format)).unwrap();
+        assert_eq!(decoded.manifest, bundle.manifest, "{:?}", format);
+        assert_eq!(decoded.sources, bundle.sources, "{:?}", format);
+        assert_eq!(run(&decoded), Value::Number(82.0), "{:?}", format);
+    }
+
+    // A source bundle is plain text, each module after its boundary line
+    let text = String::from_utf8(bundle.encode(BundleFormat::Source)).unwrap();
+    assert!(text.contains(&format!("#@ module {} {} Shapes lib/shapes.gw\n{}\n", hash_hex(&bundle.manifest.modules[1].hash), SHAPES.len(), SHAPES)));
+}
+
+#[test]
+fn test_archives_carry_the_compiled_program() {
+    let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+    resolver.add_source("/project/constants.gw", CONSTANTS);
+    resolver.add_source("/project/main.gw", "summon Constants from \"constants.gw\"\nConstants.base + Constants.step");
+    let mut bundle = Bundle::build("/project/main.gw", &mut resolver).unwrap();
+    bundle.compile(&mut CompilerPipeline::new()).unwrap();
+
+    for format in [BundleFormat::Archive, BundleFormat::Source] {
+        let program = Bundle::decode(&bundle.encode(format)).unwrap().program.expect("no program");
+        assert_eq!(VM::new().execute(program).unwrap(), Value::Number(42.0), "{:?}", format);
+    }
+}
+
+#[test]
+fn test_signatures_survive_a_change_of_form() {
+    let mut bundle = Bundle::build("/project/main.gw", &mut project()).unwrap();
+    assert_eq!(bundle.verify(&verifier), Err(VerifyError::MissingSignature));
+    bundle.sign(sign);
+    assert_eq!(bundle.verify(&verifier), Ok(()));
+
+    let archive = Bundle::decode(&bundle.encode(BundleFormat::Archive)).unwrap();
+    let text = Bundle::decode(&archive.encode(BundleFormat::Source)).unwrap();
+    assert_eq!(text.verify(&verifier), Ok(()));
+
+    // Editing a module, hash and all, breaks the signature
+    let mut tampered = text;
+    tampered.sources[0] = tampered.sources[0].replace("40", "41");
+    tampered.manifest.modules[0].hash = glimmer_weave::module_cache::content_hash(&tampered.sources[0]);
+    assert!(matches!(tampered.verify(&verifier), Err(VerifyError::InvalidSignature { .. })));
+}
+
+#[test]
+fn test_decoding_rejects_damaged_bundles() {
+    let bundle = Bundle::build("/project/main.gw", &mut project()).unwrap();
+
+    let text = String::from_utf8(bundle.encode(BundleFormat::Source)).unwrap();
+    let edited = text.replace("bind base to 40", "bind base to 99");
+    assert_eq!(
+        Bundle::decode(edited.as_bytes()).unwrap_err(),
+        BundleError::HashMismatch { path: "constants.gw".to_string() }
+    );
+    let unknown = text.replace("#@ entry", "#@ start");
+    assert!(matches!(Bundle::decode(unknown.as_bytes()), Err(BundleError::Malformed { line: 2, .. })));
+
+    let archive = bundle.encode(BundleFormat::Archive);
+    assert!(matches!(Bundle::decode(&archive[..archive.len() / 2]), Err(BundleError::Image(_))));
+    assert_eq!(Bundle::decode(b"bind x to 1").unwrap_err(), BundleError::NotABundle);
+}
+
+#[test]
+fn test_missing_modules_fail_the_build() {
+    let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+    resolver.add_source("/project/main.gw", "summon Gone from \"gone.gw\"\nGone.value");
+    assert!(matches!(Bundle::build("/project/main.gw", &mut resolver), Err(BundleError::Resolver(_))));
+}
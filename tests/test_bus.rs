@@ -0,0 +1,169 @@
+//! Tests for the session message bus and its publish/subscribe builtins
+
+use glimmer_weave::bus::PumpPolicy;
+use glimmer_weave::session::Session;
+use glimmer_weave::{AstNode, Evaluator, Lexer, ModuleResolver, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn session() -> Session {
+    Session::new(ModuleResolver::new("/app".to_string(), "/std".to_string()))
+}
+
+const SUBSCRIBER: &str = r#"
+request Bus.jobs with justification "follow jobs"
+weave received as []
+chant on_done(job) then
+    set received to list_push(received, job)
+end
+subscribe("jobs.done", on_done)
+"#;
+
+const PUBLISHER: &str = "request Bus.jobs with justification \"report jobs\"\n";
+
+#[test]
+fn test_messages_are_delivered_by_the_event_pump() {
+    let session = session();
+    let mut subscriber = session.evaluator();
+    subscriber.eval(&parse(SUBSCRIBER)).unwrap();
+
+    let mut publisher = session.evaluator();
+    let source = format!("{}[publish(\"jobs.done\", {{ id: 1 }}), publish(\"jobs.done\", 2), Bus.publish(\"jobs.other\", 3)]", PUBLISHER);
+    let counts = publisher.eval(&parse(&source));
+    assert_eq!(counts, Ok(Value::List(vec![Value::Number(1.0), Value::Number(1.0), Value::Number(0.0)])));
+
+    // Nothing runs until the subscriber pumps its events
+    assert_eq!(subscriber.eval(&parse("list_length(received)")), Ok(Value::Number(0.0)));
+    subscriber.run_until_idle().unwrap();
+    let received = subscriber.eval(&parse("[received[0].id, received[1]]"));
+    assert_eq!(received, Ok(Value::List(vec![Value::Number(1.0), Value::Number(2.0)])));
+
+    // The host can publish too
+    assert_eq!(session.publish("jobs.done", &Value::Number(3.0)), 1);
+    subscriber.run_until_idle().unwrap();
+    assert_eq!(subscriber.eval(&parse("list_length(received)")), Ok(Value::Number(3.0)));
+}
+
+#[test]
+fn test_topics_need_the_namespace_capability() {
+    let mut session = session();
+    for source in ["publish(\"jobs.done\", 1)", "subscribe(\"jobs.done\", list_length)"] {
+        let result = session.run(source);
+        assert!(
+            matches!(result, Err(RuntimeError::CapabilityDenied { ref capability, .. }) if capability == "Bus.jobs"),
+            "{}: {:?}",
+            source,
+            result
+        );
+    }
+    // Another namespace's grant doesn't count
+    let other = session.run("request Bus.alerts with justification \"x\"\npublish(\"jobs.done\", 1)");
+    assert!(matches!(other, Err(RuntimeError::CapabilityDenied { .. })));
+}
+
+#[test]
+fn test_invalid_publish_and_subscribe() {
+    let mut session = session();
+    let unencodable = session.run(&format!("{}chant f() then\n    1\nend\npublish(\"jobs.done\", f)", PUBLISHER));
+    assert!(matches!(unencodable, Err(RuntimeError::Custom(ref message)) if message.starts_with("publish: ")));
+    let handler = session.run(&format!("{}subscribe(\"jobs.done\", 1)", PUBLISHER));
+    assert!(matches!(handler, Err(RuntimeError::TypeError { .. })));
+
+    // Without a session there is no bus to reach
+    let alone = Evaluator::new().eval(&parse(&format!("{}publish(\"jobs.done\", 1)", PUBLISHER)));
+    assert!(matches!(alone, Err(RuntimeError::Custom(ref message)) if message.starts_with("publish: ")));
+}
+
+#[test]
+fn test_dropped_evaluators_stop_receiving() {
+    let session = session();
+    let mut kept = session.evaluator();
+    kept.eval(&parse(SUBSCRIBER)).unwrap();
+    let mut dropped = session.evaluator();
+    dropped.eval(&parse(SUBSCRIBER)).unwrap();
+    assert_eq!(session.publish("jobs.done", &Value::Nothing), 2);
+
+    drop(dropped);
+    assert_eq!(session.publish("jobs.done", &Value::Nothing), 1);
+    kept.run_until_idle().unwrap();
+    assert_eq!(kept.eval(&parse("list_length(received)")), Ok(Value::Number(2.0)));
+}
+
+/// A device topic whose handler republishes on every event, and a status
+/// handler that needs a turn
+const CHATTY: &str = r#"
+request Bus.dev with justification "device events"
+request Bus.status with justification "status line"
+weave log as []
+chant on_irq(n) then
+    set log to list_push(log, "irq")
+    publish("dev.irq", n + 1)
+end
+chant on_render(n) then
+    set log to list_push(log, "render")
+end
+chant on_render_again(n) then
+    set log to list_push(log, "render again")
+end
+subscribe("dev.irq", on_irq, 10)
+subscribe("status.render", on_render_again)
+subscribe("status.render", on_render, 5)
+"#;
+
+fn log(evaluator: &mut Evaluator) -> Vec<String> {
+    match evaluator.eval(&parse("log")) {
+        Ok(Value::List(items)) => items
+            .into_iter()
+            .map(|item| match item {
+                Value::Text(entry) => entry,
+                other => panic!("unexpected entry: {:?}", other),
+            })
+            .collect(),
+        other => panic!("unexpected log: {:?}", other),
+    }
+}
+
+#[test]
+fn test_priorities_order_messages_and_handlers() {
+    let session = session();
+    let mut evaluator = session.evaluator();
+    evaluator.set_pump_policy(PumpPolicy::new().topic_budget(2));
+    evaluator.eval(&parse(CHATTY)).unwrap();
+    session.publish("status.render", &Value::Number(0.0));
+    session.publish("dev.irq", &Value::Number(0.0));
+
+    evaluator.run_until_idle().unwrap();
+    assert_eq!(log(&mut evaluator), ["irq", "irq", "render", "render again"]);
+    assert_eq!(evaluator.pump_stats().delivered.get("dev.irq"), Some(&2));
+    assert_eq!(evaluator.pump_stats().over_budget, 1);
+}
+
+#[test]
+fn test_starved_messages_jump_the_queue() {
+    let session = session();
+    let mut evaluator = session.evaluator();
+    evaluator.set_pump_policy(PumpPolicy::new().max_events(6).starvation_limit(3));
+    evaluator.eval(&parse(CHATTY)).unwrap();
+    session.publish("status.render", &Value::Number(0.0));
+    session.publish("dev.irq", &Value::Number(0.0));
+
+    evaluator.run_until_idle().unwrap();
+    let entries = log(&mut evaluator);
+    assert_eq!(entries[..5], ["irq", "irq", "irq", "render", "render again"]);
+    assert_eq!(entries.len(), 7);
+    assert_eq!(evaluator.pump_stats().starved.get("status.render"), Some(&1));
+
+    // The budget resets on the next pump
+    evaluator.run_until_idle().unwrap();
+    assert_eq!(log(&mut evaluator).len(), 13);
+}
+
+#[test]
+fn test_subscribe_priority_must_be_a_number() {
+    let mut session = session();
+    let result = session.run(&format!("{}subscribe(\"jobs.done\", list_length, \"high\")", PUBLISHER));
+    assert!(matches!(result, Err(RuntimeError::TypeError { ref expected, .. }) if expected == "Number (priority)"));
+}
@@ -0,0 +1,141 @@
+//! Tests for cancellation tokens and `defer` cleanup blocks
+
+use std::thread;
+use std::time::Duration;
+
+use glimmer_weave::bytecode_compiler::compile;
+use glimmer_weave::cancellation::CancellationToken;
+use glimmer_weave::vm::{VmError, VM};
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+const SPIN: &str = "weave i as 0\nwhilst true then\n    set i to i + 1\nend";
+
+#[test]
+fn test_cancelled_token_stops_a_loop() {
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_cancellation_token(token);
+    assert_eq!(evaluator.eval(&parse(SPIN)), Err(RuntimeError::Cancelled));
+}
+
+#[test]
+fn test_defer_runs_while_cancelling() {
+    let token = CancellationToken::new();
+    let mut evaluator = Evaluator::new();
+    evaluator.set_cancellation_token(token.clone());
+    evaluator.eval(&parse("weave cleaned as 0")).unwrap();
+
+    let source = r#"
+        chant work() then
+            defer
+                set cleaned to cleaned + 1
+            end
+            whilst true then
+                0
+            end
+        end
+        defer
+            set cleaned to cleaned + 10
+        end
+        work()
+    "#;
+    let host = token.clone();
+    let interrupt = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        host.cancel();
+    });
+    assert_eq!(evaluator.eval(&parse(source)), Err(RuntimeError::Cancelled));
+    interrupt.join().unwrap();
+
+    token.reset();
+    assert_eq!(evaluator.eval(&parse("cleaned")), Ok(Value::Number(11.0)));
+}
+
+#[test]
+fn test_defer_runs_in_reverse_order_at_chant_exit() {
+    let source = r#"
+        weave log as []
+        chant work() then
+            defer
+                set log to list_push(log, 1)
+            end
+            defer
+                set log to list_push(log, 2)
+            end
+            set log to list_push(log, 0)
+            yield 42
+        end
+        weave result as work()
+        list_push(log, result)
+    "#;
+    assert_eq!(
+        Evaluator::new().eval(&parse(source)),
+        Ok(Value::List(vec![
+            Value::Number(0.0),
+            Value::Number(2.0),
+            Value::Number(1.0),
+            Value::Number(42.0),
+        ]))
+    );
+}
+
+#[test]
+fn test_defer_runs_when_a_chant_fails() {
+    let mut evaluator = Evaluator::new();
+    let source = r#"
+        weave cleaned as false
+        chant broken() then
+            defer
+                set cleaned to true
+            end
+            yield 1 / 0
+        end
+        broken()
+    "#;
+    assert_eq!(evaluator.eval(&parse(source)), Err(RuntimeError::DivisionByZero));
+    assert_eq!(evaluator.eval(&parse("cleaned")), Ok(Value::Truth(true)));
+}
+
+#[test]
+fn test_cancellation_cannot_be_caught() {
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_cancellation_token(token);
+    let source = format!("attempt\n    {}\nharmonize on _ then\n    0\nend", SPIN.replace('\n', "\n    "));
+    assert_eq!(evaluator.eval(&parse(&source)), Err(RuntimeError::Cancelled));
+}
+
+#[test]
+fn test_cancel_from_another_thread() {
+    let token = CancellationToken::new();
+    let host = token.clone();
+    let interrupt = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(20));
+        host.cancel();
+    });
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_cancellation_token(token);
+    assert_eq!(evaluator.eval(&parse(SPIN)), Err(RuntimeError::Cancelled));
+    interrupt.join().unwrap();
+}
+
+#[test]
+fn test_vm_loop_is_cancelled() {
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let mut vm = VM::new();
+    vm.set_cancellation_token(token);
+    let chunk = compile(&parse(SPIN)).unwrap();
+    assert!(matches!(vm.execute(chunk), Err(VmError::Cancelled)));
+}
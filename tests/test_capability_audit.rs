@@ -0,0 +1,115 @@
+//! Tests for the capability audit log and grant policy
+
+use glimmer_weave::capability::AuditEvent;
+use glimmer_weave::runtime::NativeFunction;
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+/// Host native that needs a capability token
+fn vga_write(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::Nothing)
+}
+
+fn evaluator() -> Evaluator {
+    let mut evaluator = Evaluator::new();
+    evaluator.define_global("vga_write", Value::NativeChant(NativeFunction::new("vga_write", Some(2), vga_write)));
+    evaluator
+}
+
+#[test]
+fn test_request_grant_and_use_are_recorded() {
+    let mut evaluator = evaluator();
+    let source = r#"
+        chant greet() then
+            weave screen as request VGA.write with justification "say hello"
+            vga_write(screen, "hello")
+        end
+        greet()
+    "#;
+    evaluator.eval(&parse(source)).unwrap();
+
+    let audit = evaluator.capability_audit();
+    let events: Vec<_> = audit.entries().iter().map(|entry| (entry.capability.as_str(), entry.event.clone())).collect();
+    assert_eq!(
+        events,
+        vec![
+            ("VGA.write", AuditEvent::Requested { justification: "say hello".to_string() }),
+            ("VGA.write", AuditEvent::Granted),
+            ("VGA.write", AuditEvent::Used { by: "vga_write".to_string() }),
+        ]
+    );
+    assert!(audit.entries().iter().all(|entry| entry.chant.as_deref() == Some("greet")));
+    assert_eq!(audit.entries()[0].span.start.line, 3);
+    assert_eq!(audit.touched(), vec!["VGA.write"]);
+}
+
+#[test]
+fn test_policy_denial_is_recorded() {
+    let mut evaluator = evaluator();
+    evaluator.set_capability_policy(Box::new(|capability: &str, _: &str| {
+        if capability.starts_with("FS.") {
+            Err("scripts may not touch the filesystem".to_string())
+        } else {
+            Ok(())
+        }
+    }));
+
+    let result = evaluator.eval(&parse("request FS.read with justification \"config\""));
+    assert_eq!(
+        result,
+        Err(RuntimeError::CapabilityDenied {
+            capability: "FS.read".to_string(),
+            reason: "scripts may not touch the filesystem".to_string(),
+        })
+    );
+
+    let audit = evaluator.capability_audit();
+    assert_eq!(audit.entries().len(), 2);
+    assert_eq!(audit.entries()[1].event, AuditEvent::Denied { reason: "scripts may not touch the filesystem".to_string() });
+    assert_eq!(audit.entries()[1].chant, None);
+    assert!(audit.touched().is_empty());
+}
+
+#[test]
+fn test_denial_can_be_harmonized() {
+    let mut evaluator = evaluator();
+    evaluator.set_capability_policy(Box::new(|_: &str, _: &str| Err("no".to_string())));
+
+    let source = "attempt\n    request VGA.write with justification \"x\"\nharmonize on CapabilityDenied then\n    \"denied\"\nend";
+    assert_eq!(evaluator.eval(&parse(source)), Ok(Value::Text("denied".to_string())));
+}
+
+#[test]
+fn test_scripts_need_the_audit_capability() {
+    let mut evaluator = evaluator();
+    match evaluator.eval(&parse("capabilities()")) {
+        Err(RuntimeError::CapabilityDenied { capability, .. }) => assert_eq!(capability, "Audit.read"),
+        other => panic!("Expected a denial, got {:?}", other),
+    }
+
+    let source = r#"
+        request VGA.write with justification "draw"
+        request Audit.read with justification "show the user"
+        list_length(capabilities())
+    "#;
+    assert_eq!(evaluator.eval(&parse(source)), Ok(Value::Number(4.0)));
+
+    let first = evaluator.eval(&parse("list_first(capabilities())")).unwrap();
+    let Value::Map(fields) = first else { panic!("Expected a map, got {:?}", first) };
+    assert_eq!(fields["capability"], Value::Text("VGA.write".to_string()));
+    assert_eq!(fields["event"], Value::Text("request".to_string()));
+    assert_eq!(fields["detail"], Value::Text("draw".to_string()));
+}
+
+#[test]
+fn test_clear_audit() {
+    let mut evaluator = evaluator();
+    evaluator.eval(&parse("request VGA.write with justification \"x\"")).unwrap();
+    assert!(!evaluator.capability_audit().entries().is_empty());
+    evaluator.clear_capability_audit();
+    assert!(evaluator.capability_audit().entries().is_empty());
+}
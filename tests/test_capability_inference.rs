@@ -0,0 +1,68 @@
+//! Tests for static capability requirement inference
+
+use glimmer_weave::capability::{infer_capabilities, CapabilityInference};
+use glimmer_weave::module_resolver::ModuleResolver;
+use glimmer_weave::{Lexer, Parser};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+#[test]
+fn test_requests_anywhere_are_reported() {
+    let source = r#"
+        request VGA.write with justification "draw"
+        chant save(path) then
+            should path is "" then
+                yield nothing
+            otherwise
+                weave disk as request FS.write with justification "save the drawing"
+            end
+        end
+        whilst false then
+            request VGA.write with justification "redraw"
+        end
+    "#;
+    let required = infer_capabilities(&parse(source));
+    assert_eq!(required.capabilities(), vec!["FS.write", "VGA.write"]);
+
+    let fs = &required.requirements()[1];
+    assert_eq!(fs.capability, "FS.write");
+    assert_eq!(fs.justification, "save the drawing");
+    assert_eq!(fs.chant.as_deref(), Some("save"));
+    assert_eq!(fs.span.start.line, 7);
+}
+
+#[test]
+fn test_script_without_requests() {
+    assert!(infer_capabilities(&parse("weave x as 1\nx + 1")).is_empty());
+}
+
+#[test]
+fn test_declared_modules_are_inherited_on_import() {
+    let mut inference = CapabilityInference::new();
+    inference.declare("Net", &["Net.connect", "Net.send"]);
+
+    let required = inference.infer(&parse("summon Net from \"std/net.gw\"\nsummon Math from \"std/math.gw\""));
+    assert_eq!(required.capabilities(), vec!["Net.connect", "Net.send"]);
+    assert_eq!(required.requirements()[0].module.as_deref(), Some("Net"));
+    assert_eq!(required.requirements()[0].justification, "required by Net");
+}
+
+#[test]
+fn test_declare_loaded_modules_from_a_resolver() {
+    let mut resolver = ModuleResolver::new(".".to_string(), "std".to_string());
+    resolver.add_source(
+        "logger.gw",
+        "grove Logger with\n    chant log(msg) then\n        request Serial.write with justification \"log\"\n    end\n    offer log\nend",
+    );
+    resolver.load_module("logger.gw").expect("Load failed");
+
+    let mut inference = CapabilityInference::new();
+    inference.declare_loaded(&resolver);
+    assert_eq!(inference.declared("Logger"), ["Serial.write".to_string()]);
+
+    let required = inference.infer(&parse("summon Logger from \"logger.gw\"\nLogger.log(\"hi\")"));
+    assert!(required.contains("Serial.write"));
+}
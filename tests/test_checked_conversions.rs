@@ -0,0 +1,60 @@
+//! Tests for the checked numeric conversion builtins
+
+use glimmer_weave::{AstNode, Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn eval(source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval(&parse(source))
+}
+
+fn triumph(n: f64) -> Value {
+    Value::Outcome { success: true, value: Box::new(Value::Number(n)) }
+}
+
+fn mishap(reason: &str) -> Value {
+    Value::Outcome { success: false, value: Box::new(Value::Text(reason.to_string())) }
+}
+
+#[test]
+fn test_to_number_checked_never_yields_nan() {
+    assert_eq!(eval("to_number_checked(\"2.5\")"), Ok(triumph(2.5)));
+    assert_eq!(eval("to_number_checked(true)"), Ok(triumph(1.0)));
+    assert_eq!(eval("to_number_checked(\"forty\")"), Ok(mishap("Cannot convert 'forty' to number")));
+    // Rust's float parser accepts these, so plain to_number lets them through
+    assert!(matches!(eval("to_number(\"NaN\")"), Ok(Value::Number(n)) if n.is_nan()));
+    assert_eq!(eval("to_number_checked(\"NaN\")"), Ok(mishap("NaN is not a finite number")));
+    assert_eq!(eval("to_number_checked(\"-inf\")"), Ok(mishap("-inf is not a finite number")));
+}
+
+#[test]
+fn test_to_int_checked_wants_whole_numbers() {
+    assert_eq!(eval("to_int_checked(\"-12\")"), Ok(triumph(-12.0)));
+    assert_eq!(eval("to_int_checked(7)"), Ok(triumph(7.0)));
+    assert_eq!(eval("to_int_checked(7.5)"), Ok(mishap("Cannot convert 7.5 to an integer: has a fractional part")));
+    assert_eq!(eval("to_int_checked(\"x\")"), Ok(mishap("Cannot convert 'x' to number")));
+    assert!(matches!(eval("to_int_checked([1])"), Err(RuntimeError::TypeError { .. })));
+}
+
+#[test]
+fn test_truncate_and_saturate() {
+    assert_eq!(eval("[truncate(2.9), truncate(-2.9), saturate(-2.9)]"), Ok(Value::List(vec![
+        triumph(2.0),
+        triumph(-2.0),
+        triumph(-2.0),
+    ])));
+
+    let huge = "bind huge to pow(10, 300)\n";
+    assert_eq!(
+        eval(&format!("{}truncate(huge)", huge)),
+        Ok(mishap(&format!("Cannot convert {} to an integer: outside the 64-bit integer range", 1e300)))
+    );
+    assert_eq!(eval(&format!("{}saturate(0 - huge)", huge)), Ok(triumph(i64::MIN as f64)));
+    assert!(matches!(eval("saturate(\"3\")"), Err(RuntimeError::TypeError { .. })));
+
+    // Through the Convert module, and unwrapped with the Outcome helpers
+    assert_eq!(eval("triumph_or(Convert.truncate(9.99), 0)"), Ok(Value::Number(9.0)));
+}
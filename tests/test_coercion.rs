@@ -0,0 +1,215 @@
+//! Conversion matrix: every kind of Value against every coercion target,
+//! and the interpreter and VM agreeing on the ones scripts can reach
+use std::collections::{BTreeMap, BTreeSet};
+
+use glimmer_weave::bigint::BigInt;
+use glimmer_weave::coercion::{coerce, CoercionError, Target};
+use glimmer_weave::eval::IteratorState;
+use glimmer_weave::runtime::NativeFunction;
+use glimmer_weave::{Environment, Evaluator, Lexer, Parser, Value};
+
+/// Kind of a value; no wildcard, so a new kind of Value doesn't compile
+/// until the matrix below covers it
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Number(_) => "Number",
+        Value::BigInt(_) => "BigInt",
+        Value::Integer(_) => "Integer",
+        Value::Text(_) => "Text",
+        Value::Truth(_) => "Truth",
+        Value::Nothing => "Nothing",
+        Value::List(_) => "List",
+        Value::Tuple(_) => "Tuple",
+        Value::Set(_) => "Set",
+        Value::Map(_) => "Map",
+        Value::Chant { .. } => "Chant",
+        Value::NativeChant(_) => "NativeChant",
+        Value::Capability { .. } => "Capability",
+        Value::Range { .. } => "Range",
+        Value::Outcome { .. } => "Outcome",
+        Value::Maybe { .. } => "Maybe",
+        Value::StructDef { .. } => "StructDef",
+        Value::StructInstance { .. } => "StructInstance",
+        Value::VariantDef { .. } => "VariantDef",
+        Value::VariantValue { .. } => "VariantValue",
+        Value::VariantConstructor { .. } => "VariantConstructor",
+        Value::Iterator { .. } => "Iterator",
+        Value::Shared { .. } => "Shared",
+        Value::Cell { .. } => "Cell",
+        Value::Resource { .. } => "Resource",
+        Value::TextBuilder { .. } => "TextBuilder",
+        Value::Frozen { .. } => "Frozen",
+        Value::Tainted { .. } => "Tainted",
+    }
+}
+
+fn text(s: &str) -> Value {
+    Value::Text(s.to_string())
+}
+
+fn nothing_fn(_: &[Value]) -> Result<Value, glimmer_weave::RuntimeError> {
+    Ok(Value::Nothing)
+}
+
+/// Outcome of a conversion, as the matrix spells it
+fn outcome(result: Result<Value, CoercionError>) -> String {
+    match result {
+        Ok(value) => format!("{:?}", value),
+        Err(CoercionError::Unsupported { .. }) => "unsupported".to_string(),
+        Err(CoercionError::Invalid(_)) => "invalid".to_string(),
+        Err(CoercionError::Absent) => "absent".to_string(),
+    }
+}
+
+/// Samples of every kind, with what converting each to Truth, Number,
+/// Text and Present gives
+fn matrix() -> Vec<(Value, [&'static str; 4])> {
+    let one = Box::new(Value::Number(1.0));
+    vec![
+        (Value::Number(0.0), ["Truth(false)", "Number(0.0)", "Text(\"0\")", "unsupported"]),
+        (Value::Number(2.5), ["Truth(true)", "Number(2.5)", "Text(\"2.5\")", "unsupported"]),
+        (Value::Number(f64::NAN), ["Truth(true)", "Number(NaN)", "Text(\"NaN\")", "unsupported"]),
+        (Value::BigInt(BigInt::from_i64(0)), ["Truth(false)", "Number(0.0)", "Text(\"0\")", "unsupported"]),
+        (Value::BigInt(BigInt::from_i64(12)), ["Truth(true)", "Number(12.0)", "Text(\"12\")", "unsupported"]),
+        (Value::Integer(0), ["Truth(false)", "Number(0.0)", "Text(\"0\")", "unsupported"]),
+        (Value::Integer(-7), ["Truth(true)", "Number(-7.0)", "Text(\"-7\")", "unsupported"]),
+        (text(""), ["Truth(false)", "invalid", "Text(\"\")", "unsupported"]),
+        (text("1e3"), ["Truth(true)", "Number(1000.0)", "Text(\"1e3\")", "unsupported"]),
+        (text(" 4"), ["Truth(true)", "invalid", "Text(\" 4\")", "unsupported"]),
+        (Value::Truth(true), ["Truth(true)", "Number(1.0)", "Text(\"true\")", "unsupported"]),
+        (Value::Truth(false), ["Truth(false)", "Number(0.0)", "Text(\"false\")", "unsupported"]),
+        (Value::Nothing, ["Truth(false)", "unsupported", "Text(\"nothing\")", "unsupported"]),
+        (Value::List(vec![]), ["Truth(false)", "unsupported", "Text(\"[List]\")", "unsupported"]),
+        (Value::List(vec![Value::Nothing]), ["Truth(true)", "unsupported", "Text(\"[List]\")", "unsupported"]),
+        (Value::Tuple(vec![Value::Number(1.0), text("a")]), ["Truth(true)", "unsupported", "Text(\"(1, a)\")", "unsupported"]),
+        (Value::Set(BTreeSet::new()), ["Truth(false)", "unsupported", "Text(\"[Set]\")", "unsupported"]),
+        (Value::Map(BTreeMap::new()), ["Truth(true)", "unsupported", "Text(\"[Map]\")", "unsupported"]),
+        (
+            Value::Chant { params: vec![], body: vec![], closure: Environment::new(), return_type: None },
+            ["Truth(true)", "unsupported", "Text(\"[Chant]\")", "unsupported"],
+        ),
+        (
+            Value::NativeChant(NativeFunction::new("noop", Some(0), nothing_fn)),
+            ["Truth(true)", "unsupported", "Text(\"[NativeChant:noop]\")", "unsupported"],
+        ),
+        (
+            Value::Capability { resource: "VGA".to_string(), permissions: vec![] },
+            ["Truth(true)", "unsupported", "Text(\"[Capability]\")", "unsupported"],
+        ),
+        (
+            Value::Range { start: one.clone(), end: one.clone() },
+            ["Truth(true)", "unsupported", "Text(\"[Range]\")", "unsupported"],
+        ),
+        (
+            Value::Outcome { success: false, value: Box::new(text("no")) },
+            ["Truth(true)", "unsupported", "Text(\"Mishap(no)\")", "unsupported"],
+        ),
+        (
+            Value::Maybe { present: true, value: Some(one.clone()) },
+            ["Truth(true)", "unsupported", "Text(\"Present(1)\")", "Number(1.0)"],
+        ),
+        (Value::Maybe { present: true, value: None }, ["Truth(true)", "unsupported", "Text(\"Present(nothing)\")", "Nothing"]),
+        (Value::Maybe { present: false, value: None }, ["Truth(true)", "unsupported", "Text(\"Absent\")", "absent"]),
+        (
+            Value::StructDef { name: "Point".to_string(), fields: vec![] },
+            ["Truth(true)", "unsupported", "Text(\"[StructDef:Point]\")", "unsupported"],
+        ),
+        (
+            Value::StructInstance { struct_name: "Point".to_string(), fields: BTreeMap::from([("x".to_string(), Value::Number(1.0))]) },
+            ["Truth(true)", "unsupported", "Text(\"Point { x: 1 }\")", "unsupported"],
+        ),
+        (
+            Value::VariantDef { name: "Color".to_string(), type_params: vec![], variants: vec![] },
+            ["Truth(true)", "unsupported", "Text(\"[VariantDef:Color]\")", "unsupported"],
+        ),
+        (
+            Value::VariantValue { enum_name: "Color".to_string(), variant_name: "Red".to_string(), fields: vec![], type_args: vec![] },
+            ["Truth(true)", "unsupported", "Text(\"Red\")", "unsupported"],
+        ),
+        (
+            Value::VariantConstructor {
+                enum_name: "Shape".to_string(),
+                variant_name: "Circle".to_string(),
+                field_params: vec![],
+                type_params: vec![],
+            },
+            ["Truth(true)", "unsupported", "Text(\"[VariantConstructor:Circle]\")", "unsupported"],
+        ),
+        (
+            Value::Iterator { iterator_type: "Empty".to_string(), state: Box::new(IteratorState::Empty) },
+            ["Truth(true)", "unsupported", "Text(\"[Iterator:Empty]\")", "unsupported"],
+        ),
+        (Value::Shared { value: one.clone(), ref_count: 1 }, ["Truth(true)", "unsupported", "Text(\"[Shared<Number> (refs: 1)]\")", "unsupported"]),
+        (
+            Value::Cell { value: one.clone(), borrowed: false, borrow_count: 0 },
+            ["Truth(true)", "unsupported", "Text(\"[Cell<Number>]\")", "unsupported"],
+        ),
+        (Value::resource("file", 3), ["Truth(true)", "unsupported", "Text(\"[Resource:file #3]\")", "unsupported"]),
+        (Value::text_builder(), ["Truth(true)", "unsupported", "Text(\"[TextBuilder (0 bytes)]\")", "unsupported"]),
+        (Value::List(vec![]).freeze(), ["Truth(false)", "unsupported", "Text(\"[List]\")", "unsupported"]),
+        (text("7").taint(&["FS.read".to_string()]), ["Truth(true)", "Number(7.0)", "Text(\"7\")", "unsupported"]),
+    ]
+}
+
+#[test]
+fn test_every_kind_and_target_has_a_defined_outcome() {
+    let matrix = matrix();
+    let mut failures = Vec::new();
+    for (value, expected) in &matrix {
+        for (target, want) in Target::ALL.into_iter().zip(expected) {
+            let got = outcome(coerce(value, target));
+            if got != *want {
+                failures.push(format!("{:?} to {:?}: expected {}, got {}", value, target, want, got));
+            }
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+
+    let covered: std::collections::BTreeSet<_> = matrix.iter().map(|(value, _)| kind(value)).collect();
+    assert_eq!(covered.len(), 28, "a kind of Value has no samples");
+}
+
+#[test]
+fn test_errors_become_runtime_errors() {
+    let error: glimmer_weave::RuntimeError = coerce(&Value::Nothing, Target::Number).unwrap_err().into();
+    assert_eq!(
+        error,
+        glimmer_weave::RuntimeError::TypeError { expected: "Number, Text, or Truth".to_string(), got: "Nothing".to_string() }
+    );
+    let mut evaluator = Evaluator::new();
+    let run = |evaluator: &mut Evaluator, source: &str| {
+        evaluator.eval(&Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap())
+    };
+    assert_eq!(run(&mut evaluator, "present_or(Present(3), 0)"), Ok(Value::Number(3.0)));
+    assert_eq!(run(&mut evaluator, "present_or(Absent, 0)"), Ok(Value::Number(0.0)));
+    assert_eq!(
+        run(&mut evaluator, "to_number(\"twelve\")"),
+        Err(glimmer_weave::RuntimeError::Custom("Cannot convert 'twelve' to number".to_string()))
+    );
+}
+
+/// Run `source` in the interpreter and on the VM
+fn both_engines(source: &str) -> (Value, Value) {
+    let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap();
+    let interpreted = Evaluator::new().eval(&ast).unwrap();
+    let chunk = glimmer_weave::bytecode_compiler::compile(&ast).unwrap();
+    let executed = glimmer_weave::vm::VM::new().execute(chunk).unwrap();
+    (interpreted, executed)
+}
+
+#[test]
+fn test_engines_agree() {
+    let literals = ["0", "2.5", "0n", "12n", "\"\"", "\"x\"", "true", "false", "nothing", "[]", "[0]", "{}", "{a: 1}"];
+    for literal in literals {
+        // Truthiness through `not` and through a condition
+        let (interpreted, executed) = both_engines(&format!("not {}", literal));
+        assert_eq!(interpreted, executed, "not {}", literal);
+        let source = format!("weave taken as 0\nshould {} then\n    set taken to 1\nend\ntaken", literal);
+        let (interpreted, executed) = both_engines(&source);
+        assert_eq!(interpreted, executed, "should {}", literal);
+
+        // Text through a text builder
+        let (interpreted, executed) = both_engines(&format!("text_build(text_push(text_builder(), {}))", literal));
+        assert_eq!(interpreted, executed, "text of {}", literal);
+    }
+}
@@ -0,0 +1,203 @@
+//! Tests for multi-file compilation with CompilerPipeline::compile_project
+//!
+//! Modules are registered as in-memory sources so the tests do not touch
+//! the filesystem.
+
+use glimmer_weave::ast::AstNode;
+use glimmer_weave::pipeline::{Output, Target};
+use glimmer_weave::vm::VM;
+use glimmer_weave::{CompilerPipeline, ModuleResolver, Value};
+
+const MATH: &str = r#"
+grove Math with
+    bind base to 40
+
+    chant add(a, b) then
+        yield a + b
+    end
+
+    chant hidden() then
+        yield 0
+    end
+
+    offer base, add
+end
+"#;
+
+fn project(files: &[(&str, &str)]) -> ModuleResolver {
+    let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+    for (path, source) in files {
+        resolver.add_source(format!("/project/{}", path), *source);
+    }
+    resolver
+}
+
+#[test]
+fn test_project_evaluates_qualified_access() {
+    let mut resolver = project(&[
+        ("math.gw", MATH),
+        ("main.gw", "summon Math from \"math.gw\"\nMath.add(Math.base, 2)"),
+    ]);
+    match CompilerPipeline::new().compile_project("/project/main.gw", &mut resolver, Target::Eval) {
+        Ok(Output::Value(value)) => assert_eq!(value, Value::Number(42.0)),
+        other => panic!("Expected a value, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_project_evaluates_gathered_items() {
+    let mut resolver = project(&[
+        ("math.gw", MATH),
+        ("main.gw", "gather add, base from \"math.gw\"\nadd(base, 2)"),
+    ]);
+    match CompilerPipeline::new().compile_project("/project/main.gw", &mut resolver, Target::Eval) {
+        Ok(Output::Value(value)) => assert_eq!(value, Value::Number(42.0)),
+        other => panic!("Expected a value, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_project_merges_into_one_bytecode_image() {
+    // The VM does not run chants with parameters yet, so stick to bindings
+    let constants = "grove Constants with\n    bind base to 40\n    bind step to 2\n    offer base, step\nend";
+    let mut resolver = project(&[
+        ("constants.gw", constants),
+        ("main.gw", "summon Constants from \"constants.gw\" as Numbers\nNumbers.base + Numbers.step"),
+    ]);
+    match CompilerPipeline::new().compile_project("/project/main.gw", &mut resolver, Target::Bytecode) {
+        Ok(Output::Bytecode(chunk)) => assert_eq!(VM::new().execute(chunk).unwrap(), Value::Number(42.0)),
+        other => panic!("Expected bytecode, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_project_links_one_elf_object() {
+    let mut resolver = project(&[
+        ("math.gw", MATH),
+        ("main.gw", "summon Math from \"math.gw\"\nMath.add(Math.base, 2)"),
+    ]);
+    let mut pipeline = CompilerPipeline::new().assembler(|asm| {
+        // Both the module's chant and the entry program end up in one listing
+        assert!(asm.contains("add"));
+        assert!(asm.contains("main:"));
+        Ok(vec![0xC3])
+    });
+    match pipeline.compile_project("/project/main.gw", &mut resolver, Target::Elf) {
+        Ok(Output::Elf(bytes)) => assert_eq!(&bytes[..4], b"\x7fELF"),
+        other => panic!("Expected an ELF object, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_project_loads_transitive_dependencies_in_order() {
+    let shapes = r#"
+grove Shapes with
+    summon Math from "math.gw"
+
+    chant perimeter(w, h) then
+        yield Math.add(w, h) * 2
+    end
+
+    offer perimeter
+end
+"#;
+    let mut resolver = project(&[
+        ("math.gw", MATH),
+        ("lib/shapes.gw", shapes),
+        ("main.gw", "summon Shapes from \"lib/shapes.gw\"\nShapes.perimeter(3, 4)"),
+    ]);
+
+    let order = resolver.load_graph("/project/main.gw").unwrap();
+    assert_eq!(order, vec!["/project/math.gw", "/project/lib/shapes.gw", "/project/main.gw"]);
+
+    match CompilerPipeline::new().compile_project("/project/main.gw", &mut resolver, Target::Eval) {
+        Ok(Output::Value(value)) => assert_eq!(value, Value::Number(14.0)),
+        other => panic!("Expected a value, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_project_rejects_unexported_access() {
+    let mut resolver = project(&[
+        ("math.gw", MATH),
+        ("main.gw", "summon Math from \"math.gw\"\nMath.hidden()"),
+    ]);
+    let diagnostics = CompilerPipeline::new()
+        .compile_project("/project/main.gw", &mut resolver, Target::Bytecode)
+        .unwrap_err();
+    assert!(diagnostics.to_string().contains("SymbolNotExported"), "{}", diagnostics);
+}
+
+#[test]
+fn test_project_rejects_unexported_gather() {
+    let mut resolver = project(&[
+        ("math.gw", MATH),
+        ("main.gw", "gather hidden from \"math.gw\"\nhidden()"),
+    ]);
+    let diagnostics = CompilerPipeline::new()
+        .compile_project("/project/main.gw", &mut resolver, Target::Bytecode)
+        .unwrap_err();
+    assert!(diagnostics.to_string().contains("SymbolNotExported"), "{}", diagnostics);
+}
+
+#[test]
+fn test_project_checks_imported_chant_arity() {
+    let mut resolver = project(&[
+        ("math.gw", MATH),
+        ("main.gw", "gather add from \"math.gw\"\nadd(1)"),
+    ]);
+    let diagnostics = CompilerPipeline::new()
+        .compile_project("/project/main.gw", &mut resolver, Target::Bytecode)
+        .unwrap_err();
+    assert!(diagnostics.to_string().contains("ImportSignatureMismatch"), "{}", diagnostics);
+
+    // The diagnostic points at the call in the entry program
+    let diagnostic = diagnostics.iter().next().unwrap();
+    assert_eq!(diagnostic.labels[0].span.start.line, 2);
+}
+
+#[test]
+fn test_project_reports_missing_module() {
+    let mut resolver = project(&[("main.gw", "summon Math from \"missing.gw\"\n1")]);
+    let diagnostics = CompilerPipeline::new()
+        .compile_project("/project/main.gw", &mut resolver, Target::Bytecode)
+        .unwrap_err();
+    assert!(diagnostics.to_string().contains("ModuleNotFound"), "{}", diagnostics);
+}
+
+#[test]
+fn test_project_reports_circular_imports() {
+    let mut resolver = project(&[
+        ("a.gw", "grove A with\n    summon B from \"b.gw\"\n    bind x to 1\n    offer x\nend"),
+        ("b.gw", "grove B with\n    summon A from \"a.gw\"\n    bind y to 2\n    offer y\nend"),
+        ("main.gw", "summon A from \"a.gw\"\nA.x"),
+    ]);
+    let diagnostics = CompilerPipeline::new()
+        .compile_project("/project/main.gw", &mut resolver, Target::Bytecode)
+        .unwrap_err();
+    assert!(diagnostics.to_string().contains("CircularDependency"), "{}", diagnostics);
+}
+
+#[test]
+fn test_project_reports_conflicting_definitions() {
+    let mut resolver = project(&[
+        ("math.gw", MATH),
+        ("main.gw", "summon Math from \"math.gw\"\nchant add(a, b) then\n    yield a - b\nend\nadd(1, 2)"),
+    ]);
+    let diagnostics = CompilerPipeline::new()
+        .compile_project("/project/main.gw", &mut resolver, Target::Bytecode)
+        .unwrap_err();
+    assert!(diagnostics.to_string().contains("Link error"), "{}", diagnostics);
+}
+
+#[test]
+fn test_project_hooks_see_linked_program() {
+    let mut resolver = project(&[
+        ("math.gw", MATH),
+        ("main.gw", "summon Math from \"math.gw\"\nMath.base"),
+    ]);
+    let mut pipeline = CompilerPipeline::new().optimize(true).after_optimize(|ast, _| {
+        assert!(ast.iter().all(|node| !matches!(node, AstNode::ModuleDecl { .. } | AstNode::Import { .. })));
+    });
+    assert!(pipeline.compile_project("/project/main.gw", &mut resolver, Target::Bytecode).is_ok());
+}
@@ -0,0 +1,204 @@
+//! Tests for the CompilerPipeline builder
+//!
+//! Covers each back end, stage hooks, and diagnostics collected across stages.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use glimmer_weave::ast::AstNode;
+use glimmer_weave::pipeline::{Output, Target};
+use glimmer_weave::{CompilerPipeline, Diagnostic, Token, Value};
+
+const PROGRAM: &str = r#"
+chant double(n) then
+    yield n * 2
+end
+double(21)
+"#;
+
+#[test]
+fn test_eval_target() {
+    let mut pipeline = CompilerPipeline::new();
+    match pipeline.run(PROGRAM, Target::Eval) {
+        Ok(Output::Value(value)) => assert_eq!(value, Value::Number(42.0)),
+        other => panic!("Expected a value, got {:?}", other),
+    }
+    assert!(pipeline.diagnostics().is_empty());
+}
+
+#[test]
+fn test_eval_with_optimization() {
+    let source = r#"
+        chant identity<T>(x: T) -> T then
+            yield x
+        end
+        weave total as 0
+        for each i in range(0, 5) then
+            set total to total + identity<Number>(i)
+        end
+        total
+    "#;
+    // The semantic analyzer does not yet type generic arithmetic
+    let mut pipeline = CompilerPipeline::new().semantic(false).optimize(true);
+    match pipeline.run(source, Target::Eval) {
+        Ok(Output::Value(value)) => assert_eq!(value, Value::Number(10.0)),
+        other => panic!("Expected a value, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_evaluator_state_persists_across_runs() {
+    let mut pipeline = CompilerPipeline::new().semantic(false);
+    pipeline.run("bind x to 40", Target::Eval).unwrap();
+    match pipeline.run("x + 2", Target::Eval) {
+        Ok(Output::Value(value)) => assert_eq!(value, Value::Number(42.0)),
+        other => panic!("Expected a value, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_bytecode_target() {
+    let mut pipeline = CompilerPipeline::new();
+    match pipeline.run("bind x to 10\nx * 4 + 2", Target::Bytecode) {
+        Ok(Output::Bytecode(chunk)) => {
+            let result = glimmer_weave::vm::VM::new().execute(chunk).unwrap();
+            assert_eq!(result, Value::Number(42.0));
+        }
+        other => panic!("Expected bytecode, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_bytecode_target_monomorphizes_generics() {
+    let source = r#"
+        chant identity<T>(x: T) -> T then
+            yield x
+        end
+        identity<Number>(42)
+    "#;
+    let generic_chants = Rc::new(RefCell::new(None));
+    let seen = Rc::clone(&generic_chants);
+    let mut pipeline = CompilerPipeline::new()
+        .semantic(false)
+        .optimize(true)
+        .after_optimize(move |ast, _| {
+            let count = ast
+                .iter()
+                .filter(|node| matches!(node, AstNode::ChantDef { type_params, .. } if !type_params.is_empty()))
+                .count();
+            *seen.borrow_mut() = Some(count);
+        });
+
+    assert!(matches!(pipeline.run(source, Target::Bytecode), Ok(Output::Bytecode(_))));
+    assert_eq!(*generic_chants.borrow(), Some(0));
+}
+
+#[test]
+fn test_asm_target() {
+    let mut pipeline = CompilerPipeline::new();
+    match pipeline.run("40 + 2", Target::Asm) {
+        Ok(Output::Asm(asm)) => assert!(asm.contains("main:")),
+        other => panic!("Expected assembly, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_elf_target_uses_assembler() {
+    let mut pipeline = CompilerPipeline::new().assembler(|asm| {
+        assert!(asm.contains("main:"));
+        Ok(vec![0xC3]) // ret
+    });
+    match pipeline.run("40 + 2", Target::Elf) {
+        Ok(Output::Elf(bytes)) => assert_eq!(&bytes[..4], b"\x7fELF"),
+        other => panic!("Expected an ELF object, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_elf_target_without_assembler() {
+    let mut pipeline = CompilerPipeline::new();
+    let diagnostics = pipeline.run("40 + 2", Target::Elf).unwrap_err();
+    assert_eq!(diagnostics.error_count(), 1);
+    assert!(diagnostics.to_string().contains("needs an assembler"));
+}
+
+#[test]
+fn test_parse_error_is_reported() {
+    let mut pipeline = CompilerPipeline::new();
+    let diagnostics = pipeline.run("bind to 5", Target::Eval).unwrap_err();
+    assert_eq!(diagnostics.error_count(), 1);
+    assert!(diagnostics.to_string().contains("Parse error"));
+}
+
+#[test]
+fn test_semantic_errors_stop_the_pipeline() {
+    let ran = Rc::new(RefCell::new(false));
+    let ran_hook = Rc::clone(&ran);
+    let mut pipeline = CompilerPipeline::new()
+        .optimize(true)
+        .after_optimize(move |_, _| *ran_hook.borrow_mut() = true);
+
+    let diagnostics = pipeline.run("missing + 1", Target::Eval).unwrap_err();
+    assert!(diagnostics.to_string().contains("Semantic error"));
+    assert!(!*ran.borrow(), "Optimization should not run after a semantic error");
+}
+
+#[test]
+fn test_runtime_error_is_reported() {
+    let mut pipeline = CompilerPipeline::new();
+    let diagnostics = pipeline.run("1 / 0", Target::Eval).unwrap_err();
+    assert!(diagnostics.to_string().contains("Runtime error"));
+}
+
+#[test]
+fn test_hooks_run_in_stage_order() {
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let (lex, parse, semantic, optimize) = (order.clone(), order.clone(), order.clone(), order.clone());
+
+    let mut pipeline = CompilerPipeline::new()
+        .optimize(true)
+        .after_lex(move |tokens, _| {
+            assert!(tokens.iter().any(|t| t.token == Token::Chant));
+            lex.borrow_mut().push("lex");
+        })
+        .after_parse(move |_, _| parse.borrow_mut().push("parse"))
+        .after_semantic(move |_, _| semantic.borrow_mut().push("semantic"))
+        .after_optimize(move |_, _| optimize.borrow_mut().push("optimize"));
+
+    pipeline.run(PROGRAM, Target::Eval).unwrap();
+    assert_eq!(*order.borrow(), vec!["lex", "parse", "semantic", "optimize"]);
+}
+
+#[test]
+fn test_hook_can_rewrite_ast() {
+    let mut pipeline = CompilerPipeline::new().after_parse(|ast, _| {
+        ast.push(AstNode::Number { value: 7.0, span: Default::default() });
+    });
+    match pipeline.run(PROGRAM, Target::Eval) {
+        Ok(Output::Value(value)) => assert_eq!(value, Value::Number(7.0)),
+        other => panic!("Expected a value, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_hook_diagnostics_are_accumulated() {
+    let mut pipeline = CompilerPipeline::new()
+        .after_parse(|_, diagnostics| diagnostics.push(Diagnostic::warning("style: prefer `weave`")));
+    pipeline.run(PROGRAM, Target::Eval).unwrap();
+    assert_eq!(pipeline.diagnostics().len(), 1);
+    assert!(!pipeline.diagnostics().has_errors());
+
+    let mut pipeline = CompilerPipeline::new()
+        .after_lex(|_, diagnostics| diagnostics.push(Diagnostic::error("forbidden token")));
+    let diagnostics = pipeline.run(PROGRAM, Target::Eval).unwrap_err();
+    assert_eq!(diagnostics.error_count(), 1);
+}
+
+#[test]
+fn test_check_stops_before_the_back_end() {
+    let mut pipeline = CompilerPipeline::new();
+    assert!(pipeline.check("print(\"never printed\")").is_empty());
+    let diagnostics = pipeline.check("bind x to 1\nset x to 2");
+    assert_eq!(diagnostics.error_count(), 1);
+    assert!(diagnostics.to_string().contains("Semantic error"));
+}
@@ -0,0 +1,90 @@
+//! Tests for the TOML and INI configuration builtins
+
+use glimmer_weave::config::{emit_ini, emit_toml, parse_ini, parse_toml};
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn run(source: &str) -> Result<Value, RuntimeError> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().expect("Parse error");
+    Evaluator::new().eval(&ast)
+}
+
+const SERVICE_TOML: &str = r#"
+# Lantern service
+title = "lantern"
+
+[server]
+host = "0.0.0.0"
+port = 8_080
+debug = false
+started = 2024-05-27T07:32:00Z
+
+[server.limits]
+rate = { burst = 20, per_second = 5.5 }
+
+[[route]]
+path = "/"
+methods = ["GET", "HEAD"]
+
+[[route]]
+path = "/upload"
+methods = [
+    "POST",  # Uploads only
+]
+"#;
+
+#[test]
+fn test_toml_tables_become_nested_maps() {
+    let source = format!(
+        "bind config to expect_triumph(Config.parse_toml({:?}), \"parse\")\n[config.server.port, config.server.limits.rate.per_second, config.route[1].methods[0], config.server.started, config.server.debug]",
+        SERVICE_TOML
+    );
+    assert_eq!(run(&source).unwrap(), Value::List(vec![
+        Value::Number(8080.0),
+        Value::Number(5.5),
+        Value::Text("POST".to_string()),
+        Value::Text("2024-05-27T07:32:00Z".to_string()),
+        Value::Truth(false),
+    ]));
+}
+
+#[test]
+fn test_toml_round_trips_through_the_emitter() {
+    let config = parse_toml(SERVICE_TOML).unwrap();
+    let emitted = emit_toml(&config).unwrap();
+    assert_eq!(parse_toml(&emitted).unwrap(), config);
+    assert!(emitted.contains("[[route]]\n"), "{}", emitted);
+    assert!(emitted.contains("[server.limits.rate]\nburst = 20\nper_second = 5.5\n"), "{}", emitted);
+}
+
+#[test]
+fn test_ini_sections_and_typed_scalars() {
+    let ini = "; mounts\nversion = 3\n\n[network]\ndhcp = yes\nmtu: 1500\ngateway = 192.168.0.1\n\n[display]\ntitle = \"  Aethel  \"\n";
+    let config = parse_ini(ini).unwrap();
+    let Value::Map(root) = &config else { panic!("expected a Map") };
+    assert_eq!(root["version"], Value::Number(3.0));
+    let Value::Map(network) = &root["network"] else { panic!("expected a section") };
+    assert_eq!(network["dhcp"], Value::Truth(true));
+    assert_eq!(network["mtu"], Value::Number(1500.0));
+    assert_eq!(network["gateway"], Value::Text("192.168.0.1".to_string()));
+
+    let emitted = emit_ini(&config).unwrap();
+    assert!(emitted.starts_with("version = 3\n\n[display]\ntitle = \"  Aethel  \"\n"), "{}", emitted);
+    assert_eq!(parse_ini(&emitted).unwrap(), config);
+
+    let source = "Config.emit_ini({general: {name: \"glimmer\", threads: 4}})";
+    assert_eq!(run(source).unwrap(), Value::Text("[general]\nname = glimmer\nthreads = 4\n".to_string()));
+}
+
+#[test]
+fn test_bad_documents_are_mishaps_and_unwritable_values_errors() {
+    let mishap = |text: &str| Value::Outcome { success: false, value: Box::new(Value::Text(text.to_string())) };
+    assert_eq!(run("config_parse_toml(\"[a]\\nx = 1\\n[a]\")").unwrap(), mishap("line 3: table [a] is defined twice"));
+    assert_eq!(run("config_parse_ini(\"[a\")").unwrap(), mishap("line 1: section header has no closing ']'"));
+    assert_eq!(run("config_parse_ini(\"just words\")").unwrap(), mishap("line 1: expected 'key = value'"));
+
+    assert!(matches!(run("config_emit_toml({missing: nothing})"), Err(RuntimeError::Custom(_))));
+    assert!(matches!(run("config_emit_ini({a: {b: {c: 1}}})"), Err(RuntimeError::Custom(_))));
+    assert!(matches!(run("config_emit_ini([1, 2])"), Err(RuntimeError::Custom(_))));
+    assert!(matches!(run("config_parse_toml(42)"), Err(RuntimeError::TypeError { .. })));
+}
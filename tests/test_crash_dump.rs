@@ -0,0 +1,92 @@
+//! Tests for crash dumps of uncaught errors, from the evaluator and the VM
+use glimmer_weave::crash_dump::{CrashDump, CrashLocation};
+use glimmer_weave::{Evaluator, Lexer, Parser};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    Parser::new(Lexer::new(source).tokenize_positioned()).parse().expect("parse failed")
+}
+
+fn crash(source: &str) -> CrashDump {
+    let mut evaluator = Evaluator::new();
+    evaluator.set_crash_dumps(true);
+    assert!(evaluator.eval(&parse(source)).is_err());
+    evaluator.crash_dump().cloned().expect("no crash dump")
+}
+
+#[test]
+fn test_backtrace_runs_through_every_chant() {
+    let dump = crash(
+        r#"chant inner(items) then
+    yield items[5]
+end
+chant outer() then
+    bind local to "kept"
+    yield inner([1, 2])
+end
+outer()"#,
+    );
+    assert_eq!(dump.error_type, "IndexOutOfBounds");
+    let frames: Vec<_> = dump.backtrace.iter().map(|frame| (frame.chant.as_deref(), frame.span.start.line)).collect();
+    assert_eq!(frames, vec![(Some("inner"), 2), (Some("outer"), 6), (None, 8)]);
+    assert_eq!(dump.location, CrashLocation::Source(dump.backtrace[0].span.clone()));
+
+    // Bindings where the error was raised
+    let items = dump.environment.iter().find(|binding| binding.name == "items").unwrap();
+    assert_eq!((items.kind.as_str(), items.summary.as_str()), ("List", "2 items"));
+}
+
+#[test]
+fn test_handled_errors_leave_no_dump() {
+    let mut evaluator = Evaluator::new();
+    evaluator.set_crash_dumps(true);
+    let source = r#"attempt
+    bind broken to 1 / 0
+harmonize on DivisionByZero then
+    "recovered"
+end"#;
+    assert!(evaluator.eval(&parse(source)).is_ok());
+    assert!(evaluator.crash_dump().is_none());
+
+    // A later crash starts over rather than extending the handled one
+    assert!(evaluator.eval(&parse("bind again to 1 / 0")).is_err());
+    assert_eq!(evaluator.crash_dump().unwrap().backtrace.len(), 1);
+    assert!(evaluator.eval(&parse("bind fine to 1")).is_ok());
+    assert!(evaluator.crash_dump().is_none());
+}
+
+#[test]
+fn test_dumps_are_off_by_default() {
+    let mut evaluator = Evaluator::new();
+    assert!(evaluator.eval(&parse("1 / 0")).is_err());
+    assert!(evaluator.crash_dump().is_none());
+}
+
+#[test]
+fn test_audit_tail_and_json() {
+    let dump = crash(
+        r#"request Speaker.play with justification "alert"
+Sound.tone(440, 10)
+bind secret to "pa\"ss"
+1 / 0"#,
+    );
+    assert_eq!(dump.audit_tail.last().map(|entry| entry.capability.as_str()), Some("Speaker.play"));
+    let json = dump.to_json();
+    assert!(json.starts_with("{\"error_type\":\"DivisionByZero\",\"message\":\"Division by zero\""), "{}", json);
+    assert!(json.contains("\"location\":{\"source\":{\"line\":4,"), "{}", json);
+    assert!(json.contains("{\"name\":\"secret\",\"kind\":\"Text\",\"summary\":\"\\\"pa\\\\\\\"ss\\\"\"}"), "{}", json);
+    assert!(dump.diagnostic().format().contains("in the top level at"));
+}
+
+#[test]
+fn test_vm_dumps_point_at_the_instruction() {
+    let chunk = glimmer_weave::bytecode_compiler::compile(&parse("bind zero to 0\nbind total to 10\ntotal / zero")).unwrap();
+    let mut vm = glimmer_weave::vm::VM::new();
+    let error = vm.execute(chunk).unwrap_err();
+    let dump = vm.crash_dump(&error);
+    assert_eq!(dump.error_type, "DivisionByZero");
+    match dump.location {
+        CrashLocation::Bytecode { instruction, .. } => assert!(instruction > 0),
+        other => panic!("expected a bytecode location, got {:?}", other),
+    }
+    assert!(dump.environment.iter().any(|binding| binding.name == "total" && binding.summary == "10"));
+}
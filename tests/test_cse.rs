@@ -0,0 +1,122 @@
+//! Tests for common subexpression elimination, comparing the bytecode and
+//! assembly of programs before and after optimization
+
+use glimmer_weave::bytecode::Instruction;
+use glimmer_weave::cse::SubexpressionEliminator;
+use glimmer_weave::pipeline::{CompilerPipeline, Output, Target};
+use glimmer_weave::{Evaluator, Lexer, Parser};
+
+/// The squared distance is written out in three statements
+const DISTANCE: &str = r#"
+    bind x to 3
+    bind y to 4
+    bind d to x * x + y * y
+    bind far to x * x + y * y greater than 20
+    should far then
+        d + x * x + y * y
+    otherwise
+        0
+    end
+"#;
+
+/// The same, through the fields of a form
+const POINT_DISTANCE: &str = r#"
+    form Point with x as Number y as Number end
+    bind p to Point { x: 3, y: 4 }
+    bind d to p.x * p.x + p.y * p.y
+    bind e to p.x * p.x + p.y * p.y + 1
+    d + e
+"#;
+
+fn compile(source: &str, target: Target, optimize: bool) -> Output {
+    CompilerPipeline::new()
+        .optimize(optimize)
+        .run(source, target)
+        .unwrap_or_else(|diagnostics| panic!("{}", diagnostics))
+}
+
+fn multiplies(output: &Output) -> usize {
+    let Output::Bytecode(chunk) = output else {
+        panic!("expected bytecode, got {:?}", output);
+    };
+    chunk.instructions.iter().filter(|instruction| matches!(instruction, Instruction::MulNum { .. })).count()
+}
+
+fn asm(output: Output) -> String {
+    match output {
+        Output::Asm(asm) => asm,
+        other => panic!("expected assembly, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_bytecode_computes_shared_products_once() {
+    let before = compile(DISTANCE, Target::Bytecode, false);
+    let after = compile(DISTANCE, Target::Bytecode, true);
+    // Shared between the first two statements; the branch is its own block
+    assert_eq!(multiplies(&before), 6);
+    assert_eq!(multiplies(&after), 4);
+}
+
+#[test]
+fn test_assembly_computes_shared_products_once() {
+    let before = asm(compile(POINT_DISTANCE, Target::Asm, false));
+    let after = asm(compile(POINT_DISTANCE, Target::Asm, true));
+    assert_eq!(before.matches("imulq").count(), 4);
+    assert_eq!(after.matches("imulq").count(), 2);
+}
+
+#[test]
+fn test_side_effects_opt_out() {
+    // `grow` changes `n`, so `n * n` after the call is a different value
+    let source = r#"
+        weave n as 2
+        chant grow() then
+            set n to n + 1
+            n
+        end
+        bind a to n * n
+        bind b to grow() + n * n
+        a + b
+    "#;
+    let before = compile(source, Target::Asm, false);
+    let after = compile(source, Target::Asm, true);
+    assert_eq!(asm(before).matches("imulq").count(), asm(after).matches("imulq").count());
+}
+
+#[test]
+fn test_eliminated_programs_compute_the_same() {
+    let programs = [
+        DISTANCE,
+        POINT_DISTANCE,
+        // Shared products inside a loop body, with the counter changing between uses
+        r#"
+        weave total as 0
+        weave i as 0
+        whilst i less than 6 then
+            bind sq to i * i + 1
+            set total to total + (i * i + 1) * sq
+            set i to i + 1
+            set total to total + i * i + 1
+        end
+        total
+        "#,
+        // Only the left of `or` always runs
+        r#"
+        bind k to 5
+        bind small to k * 2 less than 20 or k * 3 greater than 1
+        bind twice to k * 3
+        should small then twice otherwise 0 end
+        "#,
+    ];
+    for source in programs {
+        let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap();
+        let optimized = SubexpressionEliminator::new().eliminate(&ast);
+        assert_eq!(
+            Evaluator::new().eval(&optimized).unwrap(),
+            Evaluator::new().eval(&ast).unwrap(),
+            "{}",
+            source
+        );
+    }
+}
@@ -0,0 +1,57 @@
+//! Tests for the CSV builtins
+
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn run(source: &str) -> Result<Value, RuntimeError> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().expect("Parse error");
+    Evaluator::new().eval(&ast)
+}
+
+fn text(value: &str) -> Value {
+    Value::Text(value.to_string())
+}
+
+#[test]
+fn test_header_rows_become_maps() {
+    let source = r#"
+        bind rows to expect_triumph(csv_parse("name,city\nAda,\"London, UK\"\r\nGrace,\"New \"\"York\"\"\"\n"), "parse")
+        [list_length(rows), rows[0].city, rows[1].city]
+    "#;
+    assert_eq!(run(source).unwrap(), Value::List(vec![Value::Number(2.0), text("London, UK"), text("New \"York\"")]));
+}
+
+#[test]
+fn test_options_change_delimiter_and_header() {
+    let source = r#"Csv.parse("1\t2\n3\t4", {delimiter: "\t", header: false})"#;
+    let rows = Value::List(vec![Value::List(vec![text("1"), text("2")]), Value::List(vec![text("3"), text("4")])]);
+    assert_eq!(run(source).unwrap(), Value::Outcome { success: true, value: Box::new(rows) });
+
+    let source = r#"csv_emit([{name: "lamp", price: 4.5}, {name: "wick", stock: 12}], {columns: ["name", "stock", "price"], delimiter: ";"})"#;
+    assert_eq!(run(source).unwrap(), text("name;stock;price\nlamp;;4.5\nwick;12;\n"));
+}
+
+#[test]
+fn test_emit_and_parse_round_trip() {
+    let source = r#"
+        bind rows to [{id: "1", note: "says \"hi\""}, {id: "2", note: "two\nlines"}]
+        expect_triumph(csv_parse(csv_emit(rows)), "parse") is rows
+    "#;
+    assert_eq!(run(source).unwrap(), Value::Truth(true));
+
+    let source = r#"csv_emit([[1, true, nothing], ["a,b"]], {header: false})"#;
+    assert_eq!(run(source).unwrap(), text("1,true,\n\"a,b\"\n"));
+}
+
+#[test]
+fn test_malformed_input_and_misuse() {
+    let mishap = |message: &str| Value::Outcome { success: false, value: Box::new(text(message)) };
+    assert_eq!(run(r#"csv_parse("a,b\n1,2,3")"#).unwrap(), mishap("line 2: 3 fields where the header has 2"));
+    assert_eq!(run(r#"csv_parse("a,a\n1,2")"#).unwrap(), mishap("line 1: column 'a' appears twice in the header"));
+    assert_eq!(run("csv_parse(\"a\\n\\\"open\")").unwrap(), mishap("line 2: quoted field is never closed"));
+
+    assert!(matches!(run(r#"csv_parse("a", {delimiter: ",,"})"#), Err(RuntimeError::TypeError { .. })));
+    assert!(matches!(run(r#"csv_parse("a", {quote: "'"})"#), Err(RuntimeError::Custom(_))));
+    assert!(matches!(run(r#"csv_emit([{a: [1]}])"#), Err(RuntimeError::Custom(_))));
+    assert!(matches!(run(r#"csv_emit([{a: 1}, [1]])"#), Err(RuntimeError::Custom(_))));
+}
@@ -0,0 +1,86 @@
+//! Tests for execution deadlines in the evaluator and the VM
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use glimmer_weave::bytecode_compiler::compile;
+use glimmer_weave::vm::{VmError, VM};
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+/// A clock that advances one tick every time it is read
+fn ticking_clock() -> (Arc<AtomicU64>, impl Fn() -> u64) {
+    let ticks = Arc::new(AtomicU64::new(0));
+    let reader = Arc::clone(&ticks);
+    (ticks, move || {
+        reader.fetch_add(1, Ordering::Relaxed) + 1
+    })
+}
+
+const SPIN: &str = "weave i as 0\nwhilst true then\n    set i to i + 1\nend";
+
+#[test]
+fn test_infinite_loop_times_out() {
+    let (ticks, clock) = ticking_clock();
+    let mut evaluator = Evaluator::new();
+    evaluator.set_clock(Box::new(clock));
+
+    assert_eq!(evaluator.eval_with_deadline(&parse(SPIN), 100), Err(RuntimeError::Timeout));
+    assert_eq!(ticks.load(Ordering::Relaxed), 100);
+}
+
+#[test]
+fn test_script_within_deadline_finishes() {
+    let (_, clock) = ticking_clock();
+    let mut evaluator = Evaluator::new();
+    evaluator.set_clock(Box::new(clock));
+
+    let source = "weave i as 0\nwhilst i is not 10 then\n    set i to i + 1\nend\ni";
+    assert_eq!(evaluator.eval_with_deadline(&parse(source), 1_000), Ok(Value::Number(10.0)));
+
+    // The deadline only applies to that call
+    let (_, fresh) = ticking_clock();
+    evaluator.set_clock(Box::new(fresh));
+    assert_eq!(evaluator.eval(&parse("i + 1")), Ok(Value::Number(11.0)));
+}
+
+#[test]
+fn test_recursion_times_out_at_calls() {
+    let (_, clock) = ticking_clock();
+    let mut evaluator = Evaluator::new();
+    evaluator.set_clock(Box::new(clock));
+
+    let source = "chant forever(n) then\n    yield forever(n + 1)\nend\nforever(0)";
+    assert_eq!(evaluator.eval_with_deadline(&parse(source), 50), Err(RuntimeError::Timeout));
+}
+
+#[test]
+fn test_timeout_cannot_be_caught() {
+    let (_, clock) = ticking_clock();
+    let mut evaluator = Evaluator::new();
+    evaluator.set_clock(Box::new(clock));
+
+    let source = format!("attempt\n    {}\nharmonize on _ then\n    0\nend", SPIN.replace('\n', "\n    "));
+    assert_eq!(evaluator.eval_with_deadline(&parse(&source), 20), Err(RuntimeError::Timeout));
+}
+
+#[test]
+fn test_system_clock_deadline_in_the_past() {
+    let mut evaluator = Evaluator::new();
+    assert_eq!(evaluator.eval_with_deadline(&parse(SPIN), 0), Err(RuntimeError::Timeout));
+}
+
+#[test]
+fn test_vm_loop_times_out() {
+    let (ticks, clock) = ticking_clock();
+    let mut vm = VM::new();
+    vm.set_clock(Box::new(clock));
+
+    let chunk = compile(&parse(SPIN)).unwrap();
+    assert!(matches!(vm.execute_with_deadline(chunk, 25), Err(VmError::Timeout)));
+    assert_eq!(ticks.load(Ordering::Relaxed), 25);
+}
@@ -0,0 +1,105 @@
+//! Tests for deprecation attributes and the warnings at their use sites
+
+use glimmer_weave::pipeline::Target;
+use glimmer_weave::{AstNode, CompilerPipeline, Lexer, Parser, SemanticAnalyzer, SemanticWarning};
+
+fn parse(source: &str) -> Result<Vec<AstNode>, String> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().map_err(|e| e.message)
+}
+
+/// (name, replacement, line) of each deprecation warning
+fn deprecations(source: &str) -> Vec<(String, Option<String>, usize)> {
+    let ast = parse(source).expect("Parse error");
+    let mut analyzer = SemanticAnalyzer::new();
+    let _ = analyzer.analyze(&ast);
+    analyzer
+        .warnings()
+        .iter()
+        .filter_map(|warning| match warning {
+            SemanticWarning::Deprecated { name, replacement, span, .. } => Some((name.clone(), replacement.clone(), span.start.line)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_attribute_parses_onto_definitions() {
+    let ast = parse("deprecated \"old\" use Screen.print\n\nswift chant show(x) then\n    x\nend\n").expect("Parse error");
+    match &ast[0] {
+        AstNode::ChantDef { deprecated: Some(deprecation), swift: true, .. } => {
+            assert_eq!(deprecation.message, "old");
+            assert_eq!(deprecation.replacement.as_deref(), Some("Screen.print"));
+        }
+        other => panic!("expected a deprecated chant, got {:?}", other),
+    }
+    assert!(matches!(parse("deprecated \"old\"\nform P with\n    x as Number\nend\n").unwrap()[0], AstNode::FormDef { deprecated: Some(_), .. }));
+    assert_eq!(parse("deprecated \"old\"\nbind x to 1\n").unwrap_err(), "`deprecated` must come before a chant, form or grove");
+    // `deprecated` is not reserved
+    assert!(parse("bind deprecated to 1\ndeprecated + 1\n").is_ok());
+}
+
+#[test]
+fn test_warnings_at_use_sites() {
+    let source = r#"deprecated "draws past the edge" use draw_clipped
+chant draw(text) then
+    text
+end
+deprecated "carries no units"
+form Size with
+    w as Number
+end
+grove Screen with
+    deprecated "prints without a newline" use Screen.println
+    chant print(text) then
+        text
+    end
+    offer print
+end
+draw("a")
+draw("b")
+bind s to Size { w: 1 }
+Screen.print("c")
+"#;
+    assert_eq!(
+        deprecations(source),
+        vec![
+            ("draw".to_string(), Some("draw_clipped".to_string()), 16),
+            ("draw".to_string(), Some("draw_clipped".to_string()), 17),
+            ("Size".to_string(), None, 18),
+            ("print".to_string(), Some("Screen.println".to_string()), 19),
+        ]
+    );
+}
+
+#[test]
+fn test_deprecated_groves_warn_on_import_and_calls() {
+    let source = "deprecated \"use Screen\"\ngrove Console with\n    chant write(text) then\n        text\n    end\n    offer write\nend\nConsole.write(\"hi\")\n";
+    assert_eq!(deprecations(source), vec![("Console".to_string(), None, 8)]);
+}
+
+#[test]
+fn test_pipeline_reports_warnings_and_still_runs() {
+    let source = "deprecated \"too slow\" use fast_sum\nchant sum(a, b) then\n    yield a + b\nend\nsum(1, 2)\n";
+    let mut pipeline = CompilerPipeline::new();
+    assert!(pipeline.run(source, Target::Eval).is_ok());
+    let rendered = format!("{}", pipeline.diagnostics());
+    assert!(rendered.contains("Deprecated"), "{}", rendered);
+    assert!(rendered.contains("fast_sum"), "{}", rendered);
+}
+
+#[test]
+fn test_gwc_doc_renders_banners() {
+    let dir = std::env::temp_dir().join(format!("gw_doc_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("shapes.gw");
+    std::fs::write(&path, "deprecated \"use Rect\"\nform Box with\n    w as Number\n    h as Number\nend\nchant area(b: Box) -> Number then\n    yield b.w * b.h\nend\n").unwrap();
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_gwc")).arg("doc").arg(&path).output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "# shapes\n\n## `form Box`\n\n> **Deprecated:** use Rect\n\n- `w`: `Number`\n- `h`: `Number`\n\n## `chant area(b: Box) -> Number`\n"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
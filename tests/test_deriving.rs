@@ -0,0 +1,116 @@
+//! Tests for `deriving` clauses on forms
+
+use glimmer_weave::semantic::{analyze, SemanticError};
+use glimmer_weave::{AstNode, Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn run(source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval(&parse(source))
+}
+
+const POINT: &str = r#"
+    form Point with
+        y as Number
+        x as Number
+        deriving Display, Ordered, Hashable
+    end
+"#;
+
+#[test]
+fn test_deriving_clause_parses() {
+    match &parse(POINT)[0] {
+        AstNode::FormDef { fields, deriving, .. } => {
+            assert_eq!(fields.len(), 2);
+            assert_eq!(deriving, &["Display", "Ordered", "Hashable"]);
+        }
+        other => panic!("expected a form, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_field_named_deriving() {
+    let source = r#"
+        form Flags with
+            deriving as Truth
+        end
+        Flags { deriving: true }.deriving
+    "#;
+    assert_eq!(run(source).unwrap(), Value::Truth(true));
+}
+
+#[test]
+fn test_derived_display_uses_declaration_order() {
+    let source = format!("{}\nto_text(Point {{ x: 1, y: 2 }})", POINT);
+    assert_eq!(run(&source).unwrap(), Value::Text("Point { y: 2, x: 1 }".to_string()));
+}
+
+#[test]
+fn test_derived_ordered_sorts() {
+    let source = format!(
+        r#"{}
+        bind sorted to list_sort([Point {{ x: 0, y: 5 }}, Point {{ x: 9, y: 1 }}, Point {{ x: 3, y: 1 }}])
+        to_text(sorted[0]) + " " + to_text(sorted[1]) + " " + to_text(sorted[2])
+        "#,
+        POINT
+    );
+    assert_eq!(
+        run(&source).unwrap(),
+        Value::Text("Point { y: 1, x: 3 } Point { y: 1, x: 9 } Point { y: 5, x: 0 }".to_string())
+    );
+}
+
+#[test]
+fn test_derived_hashable() {
+    let source = format!(
+        r#"{}
+        [hash(Point {{ x: 1, y: 2 }}) is hash(Point {{ x: 1, y: 2 }}), hash(Point {{ x: 1, y: 2 }}) is hash(Point {{ x: 2, y: 1 }})]
+        "#,
+        POINT
+    );
+    assert_eq!(run(&source).unwrap(), Value::List(vec![Value::Truth(true), Value::Truth(false)]));
+}
+
+#[test]
+fn test_hash_needs_hashable() {
+    let source = r#"
+        form Plain with
+            n as Number
+        end
+        hash(Plain { n: 1 })
+    "#;
+    assert!(matches!(run(source), Err(RuntimeError::Custom(message)) if message.contains("Hashable")));
+    assert_eq!(run(r#"hash("a") is hash("a")"#).unwrap(), Value::Truth(true));
+}
+
+#[test]
+fn test_custom_embodiment_overrides_derived() {
+    let source = format!(
+        r#"{}
+        embody Display for Point then
+            chant describe(self) -> Text then
+                yield "P"
+            end
+        end
+        to_text(Point {{ x: 1, y: 2 }})
+        "#,
+        POINT
+    );
+    assert_eq!(run(&source).unwrap(), Value::Text("P".to_string()));
+}
+
+#[test]
+fn test_unknown_derive_is_rejected() {
+    let source = r#"
+        form Odd with
+            n as Number
+            deriving Serialize
+        end
+    "#;
+    let errors = analyze(&parse(source)).unwrap_err();
+    assert!(errors.iter().any(|error| matches!(error, SemanticError::InvalidOperation { operation, .. } if operation == "deriving Serialize")));
+    assert!(matches!(run(source), Err(RuntimeError::Custom(message)) if message.contains("Cannot derive Serialize")));
+}
@@ -0,0 +1,82 @@
+//! Tests for deterministic mode
+
+use std::sync::{Arc, Mutex};
+
+use glimmer_weave::capability::AuditEvent;
+use glimmer_weave::session::Session;
+use glimmer_weave::{run, EvalOptions, Evaluator, Lexer, ModuleResolver, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+#[test]
+fn test_nondeterministic_capabilities_are_denied_before_the_policy() {
+    let asked = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&asked);
+    let mut evaluator = Evaluator::new();
+    evaluator.set_capability_policy(Box::new(move |capability: &str, _: &str| {
+        log.lock().unwrap().push(capability.to_string());
+        Ok(())
+    }));
+    evaluator.set_deterministic(true);
+    assert!(evaluator.is_deterministic());
+
+    for capability in ["Time.now", "Random.seed", "FS.read", "Console.read", "Net.connect", "Store.readwrite"] {
+        let result = evaluator.eval(&parse(&format!("request {} with justification \"build\"", capability)));
+        assert!(
+            matches!(result, Err(RuntimeError::CapabilityDenied { capability: ref denied, .. }) if denied == capability),
+            "{}: {:?}",
+            capability,
+            result
+        );
+    }
+    assert!(asked.lock().unwrap().is_empty());
+    let denied = evaluator.capability_audit().for_capability("FS.read").last().map(|entry| entry.event.clone());
+    assert!(matches!(denied, Some(AuditEvent::Denied { .. })));
+
+    // Other capabilities still go to the policy
+    assert!(evaluator.eval(&parse("request VGA.write with justification \"draw\"\n1")).is_ok());
+    assert_eq!(*asked.lock().unwrap(), ["VGA.write"]);
+
+    // Builtins behind a denied capability stay unusable
+    let read = evaluator.eval(&parse("fs_lines(\"/etc/hosts\")"));
+    assert!(matches!(read, Err(RuntimeError::CapabilityDenied { .. })), "{:?}", read);
+}
+
+#[test]
+fn test_deadlines_stop_at_the_same_safepoint_every_run() {
+    let spin = parse("weave i as 0\nwhilst true then\n    set i to i + 1\nend");
+    let stopped_at = || {
+        let mut evaluator = Evaluator::new();
+        evaluator.set_deterministic(true);
+        assert_eq!(evaluator.eval_with_deadline(&spin, 500), Err(RuntimeError::Timeout));
+        evaluator.eval(&parse("i")).unwrap()
+    };
+    let first = stopped_at();
+    assert!(matches!(first, Value::Number(n) if n > 0.0));
+    assert_eq!(stopped_at(), first);
+}
+
+#[test]
+fn test_maps_iterate_in_key_order() {
+    let keys = |source: &str| Evaluator::new().eval(&parse(source)).unwrap();
+    let expected = Value::List(["apple", "mango", "zest"].iter().map(|key| Value::Text(key.to_string())).collect());
+    assert_eq!(keys("map_keys({ zest: 1, apple: 2, mango: 3 })"), expected);
+    assert_eq!(keys("map_keys({ mango: 3, zest: 1, apple: 2 })"), expected);
+}
+
+#[test]
+fn test_run_and_sessions_forward_the_mode() {
+    let script = "request Time.now with justification \"stamp\"\n1";
+    assert!(run(script, EvalOptions::new()).is_ok());
+    assert!(run(script, EvalOptions::new().deterministic(true)).unwrap_err().has_errors());
+
+    let mut session = Session::new(ModuleResolver::new("/app".to_string(), "/std".to_string()));
+    let trusted = session.evaluator();
+    session.set_deterministic(true);
+    assert!(!trusted.is_deterministic());
+    assert!(session.evaluator().is_deterministic());
+    assert!(matches!(session.run(script), Err(RuntimeError::CapabilityDenied { .. })));
+}
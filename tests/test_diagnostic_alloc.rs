@@ -0,0 +1,111 @@
+//! Regression test: writing diagnostics must not allocate, so kernel builds
+//! can report errors into fixed buffers without pulling in `format!`
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt::{self, Write};
+
+use glimmer_weave::error_formatter::{ColorChoice, ColorDepth, Diagnostic, Diagnostics, FormatOptions, Theme};
+use glimmer_weave::source_location::{SourceLocation, SourceSpan};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Fixed-capacity buffer, as a kernel console line would be
+struct FixedBuffer {
+    bytes: [u8; 512],
+    len: usize,
+}
+
+impl Write for FixedBuffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        if end > self.bytes.len() {
+            return Err(fmt::Error);
+        }
+        self.bytes[self.len..end].copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_writing_diagnostics_does_not_allocate() {
+    let mut diagnostics = Diagnostics::new();
+    diagnostics.push(
+        Diagnostic::error("Use of moved value 'x'")
+            .with_primary_label(SourceSpan::point(SourceLocation::new(10, 5)), "value used here")
+            .with_secondary_label(SourceSpan::new(SourceLocation::new(8, 1), SourceLocation::new(8, 9)), "moved here")
+            .with_note("'x' was moved on line 8"),
+    );
+    diagnostics.push(Diagnostic::warning("unused binding 'y'"));
+
+    for color in [ColorChoice::Plain, ColorChoice::Ansi] {
+        let mut buffer = FixedBuffer { bytes: [0; 512], len: 0 };
+        let before = ALLOCATIONS.with(Cell::get);
+        diagnostics.write_to(&mut buffer, color).unwrap();
+        let allocations = ALLOCATIONS.with(Cell::get) - before;
+        assert_eq!(allocations, 0, "{:?}", color);
+
+        let text = std::str::from_utf8(&buffer.bytes[..buffer.len]).unwrap();
+        assert!(text.contains("value used here"));
+        if color == ColorChoice::Plain {
+            assert_eq!(text, diagnostics.to_string());
+        }
+    }
+}
+
+#[test]
+fn test_themed_output_does_not_allocate() {
+    let mut diagnostics = Diagnostics::new();
+    diagnostics.push(
+        Diagnostic::error("Use of moved value 'x'")
+            .with_primary_label(SourceSpan::point(SourceLocation::new(10, 5)), "value used here")
+            .with_secondary_label(SourceSpan::point(SourceLocation::new(8, 1)), "moved here"),
+    );
+
+    for options in [
+        FormatOptions::vga(),
+        FormatOptions::ansi(ColorDepth::Ansi256, Theme::ColorBlind),
+        FormatOptions::ansi(ColorDepth::TrueColor, Theme::HighContrast),
+    ] {
+        let mut buffer = FixedBuffer { bytes: [0; 512], len: 0 };
+        let before = ALLOCATIONS.with(Cell::get);
+        diagnostics.write_with(&mut buffer, &options).unwrap();
+        assert_eq!(ALLOCATIONS.with(Cell::get) - before, 0, "{:?}", options);
+    }
+}
+
+#[test]
+fn test_snippets_do_not_allocate() {
+    let source = "weave x as [1]\n\tbind 名前 to x\nbind z to x + x";
+    let diagnostic = Diagnostic::error("Use of moved value 'x'")
+        .with_primary_label(SourceSpan::point(SourceLocation::new(3, 11)), "value used here after move")
+        .with_secondary_label(SourceSpan::point(SourceLocation::new(3, 15)), "used again")
+        .with_secondary_label(SourceSpan::point(SourceLocation::new(2, 14)), "value moved here")
+        .with_note("'x' is a List, which moves");
+
+    for options in [FormatOptions::plain(), FormatOptions::ansi(ColorDepth::TrueColor, Theme::ColorBlind)] {
+        let mut buffer = FixedBuffer { bytes: [0; 512], len: 0 };
+        let before = ALLOCATIONS.with(Cell::get);
+        diagnostic.write_with_source(&mut buffer, source, &options).unwrap();
+        assert_eq!(ALLOCATIONS.with(Cell::get) - before, 0, "{:?}", options);
+    }
+}
@@ -0,0 +1,99 @@
+//! Tests for diagnostics quoting the source lines their labels point at
+
+use glimmer_weave::borrow_checker::BorrowChecker;
+use glimmer_weave::error_formatter::{ColorDepth, Diagnostic, FormatOptions, Theme};
+use glimmer_weave::source_location::{SourceLocation, SourceSpan};
+use glimmer_weave::{Lexer, Parser};
+
+fn span(line: usize, start: usize, end: usize) -> SourceSpan {
+    SourceSpan::new(SourceLocation::new(line, start), SourceLocation::new(line, end))
+}
+
+#[test]
+fn test_labels_on_one_line_hang_their_messages() {
+    let source = "bind y to 1\nbind z to x + y + x\n";
+    let diag = Diagnostic::error("Use of moved value 'x'")
+        .with_primary_label(span(2, 11, 11), "used here")
+        .with_secondary_label(span(2, 15, 15), "borrowed here")
+        .with_secondary_label(span(2, 19, 19), "moved here")
+        .with_note("'x' moves into z");
+    assert_eq!(
+        diag.format_with_source(source),
+        "error: Use of moved value 'x'\n\
+         \x20 ---> line 2:11\n\
+         \x20  | \n\
+         \x202 | bind z to x + y + x\n\
+         \x20  |           ^   -   - moved here\n\
+         \x20  |           |   |\n\
+         \x20  |           |   borrowed here\n\
+         \x20  |           used here\n\
+         \x20 = note: 'x' moves into z\n"
+    );
+}
+
+#[test]
+fn test_tabs_and_wide_characters_keep_markers_aligned() {
+    let source = "\tbind 名前 to \"値\" + missing";
+    let diag = Diagnostic::error("Undefined variable 'missing'")
+        .with_primary_label(span(1, 19, 25), "not found in this scope")
+        .with_secondary_label(span(1, 7, 8), "declared as");
+    assert_eq!(
+        diag.format_with_source(source),
+        "error: Undefined variable 'missing'\n\
+         \x20 ---> line 1:19\n\
+         \x20  | \n\
+         \x201 |     bind 名前 to \"値\" + missing\n\
+         \x20  |          ----           ^^^^^^^ not found in this scope\n\
+         \x20  |          |\n\
+         \x20  |          declared as\n"
+    );
+}
+
+#[test]
+fn test_distant_lines_and_unquotable_labels() {
+    let source = (1..=12).map(|n| format!("line {}", n)).collect::<Vec<_>>().join("\n");
+    let diag = Diagnostic::warning("unused binding")
+        .with_secondary_label(span(2, 1, 4), "bound here")
+        .with_primary_label(span(4, 6, 6), "shadowed here")
+        .with_secondary_label(span(12, 6, 7), "last")
+        .with_secondary_label(span(40, 1, 1), "past the end");
+    assert_eq!(
+        diag.format_with_source(&source),
+        "warning: unused binding\n\
+         \x20 ---> line 4:6\n\
+         \x20   | \n\
+         \x20 2 | line 2\n\
+         \x20   | ---- bound here\n\
+         \x20 3 | line 3\n\
+         \x20 4 | line 4\n\
+         \x20   |      ^ shadowed here\n\
+         ...\n\
+         \x2012 | line 12\n\
+         \x20   |      -- last\n\
+         \x20 ---- line 40:1: past the end\n"
+    );
+    // Without quotable labels the layout is the plain one
+    let elsewhere = Diagnostic::error("bad").with_primary_label(span(99, 1, 1), "here");
+    assert_eq!(elsewhere.format_with_source(&source), elsewhere.format());
+}
+
+#[test]
+fn test_markers_are_colored_by_theme() {
+    let diag = Diagnostic::error("oops").with_primary_label(span(1, 1, 2), "this").with_secondary_label(span(1, 4, 4), "that");
+    let mut out = String::new();
+    diag.write_with_source(&mut out, "ab c", &FormatOptions::ansi(ColorDepth::Ansi16, Theme::ColorBlind)).unwrap();
+    assert!(out.contains("\x1b[1;31m^^\x1b[0m \x1b[35m-\x1b[0m \x1b[35mthat\x1b[0m\n"));
+    assert!(out.contains("\x1b[1;31m|\x1b[0m\n"));
+}
+
+#[test]
+fn test_borrow_errors_mark_both_places() {
+    let source = "weave x as [1]\nbind y to x\nbind z to x";
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().expect("Parse error");
+    let errors = BorrowChecker::new().check(&ast).unwrap_err();
+    let text = errors[0].diagnostic().format_with_source(source);
+    assert!(text.starts_with("error: Use of moved value 'x'\n"), "{}", text);
+    assert!(text.contains(" 2 | bind y to x\n   |           - value moved here\n"), "{}", text);
+    assert!(text.contains(" 3 | bind z to x\n   |           ^ value used here after move\n"), "{}", text);
+}
@@ -0,0 +1,103 @@
+//! Tests for the Display aspect: forms and variants embodying `Display`
+//! render through their `describe(self)` in to_text
+
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn run(source: &str) -> Result<Value, RuntimeError> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().expect("parse failed");
+    Evaluator::new().eval(&ast)
+}
+
+const POINT: &str = r#"
+    form Point with
+        x as Number
+        y as Number
+    end
+
+    embody Display for Point then
+        chant describe(self) -> Text then
+            yield "(" + to_text(self.x) + ", " + to_text(self.y) + ")"
+        end
+    end
+"#;
+
+#[test]
+fn test_to_text_calls_describe() {
+    let source = format!("{}\nto_text(Point {{ x: 1, y: 2 }})", POINT);
+    assert_eq!(run(&source).unwrap(), Value::Text("(1, 2)".to_string()));
+}
+
+#[test]
+fn test_module_path_calls_describe() {
+    let source = format!("{}\nConvert.to_text(Point {{ x: 3, y: 4 }})", POINT);
+    assert_eq!(run(&source).unwrap(), Value::Text("(3, 4)".to_string()));
+}
+
+#[test]
+fn test_nested_values_use_describe() {
+    let source = format!(
+        r#"{}
+        form Segment with
+            start as Point
+            finish as Point
+        end
+        to_text(Triumph(Segment {{ start: Point {{ x: 0, y: 0 }}, finish: Point {{ x: 5, y: 5 }} }}))
+        "#,
+        POINT
+    );
+    assert_eq!(
+        run(&source).unwrap(),
+        Value::Text("Triumph(Segment { finish: (5, 5), start: (0, 0) })".to_string())
+    );
+}
+
+#[test]
+fn test_variants_embody_display_by_enum_name() {
+    let source = r#"
+        variant Health then
+            Ok,
+            Degraded(reason: Text)
+        end
+
+        embody Display for Health then
+            chant describe(self) -> Text then
+                match self with
+                    when Ok then yield "healthy"
+                    when Degraded(reason) then yield "degraded: " + reason
+                end
+            end
+        end
+
+        to_text(Degraded("disk full"))
+    "#;
+    assert_eq!(run(source).unwrap(), Value::Text("degraded: disk full".to_string()));
+}
+
+#[test]
+fn test_structural_printer_without_display() {
+    let source = r#"
+        form Pair with
+            left as Number
+            right as Number
+        end
+        to_text(Pair { left: 1, right: 2 })
+    "#;
+    assert_eq!(run(source).unwrap(), Value::Text("Pair { left: 1, right: 2 }".to_string()));
+}
+
+#[test]
+fn test_describe_must_yield_text() {
+    let source = r#"
+        form Gauge with
+            level as Number
+        end
+        embody Display for Gauge then
+            chant describe(self) -> Number then
+                yield self.level
+            end
+        end
+        to_text(Gauge { level: 3 })
+    "#;
+    assert!(matches!(run(source), Err(RuntimeError::TypeError { .. })));
+}
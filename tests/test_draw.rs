@@ -0,0 +1,141 @@
+//! Tests for the `Draw` module: double-buffered drawing behind
+//! `Framebuffer.draw`
+
+use std::sync::{Arc, Mutex};
+
+use glimmer_weave::draw::{FramebufferProvider, Glyph};
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+const REQUEST: &str = "request Framebuffer.draw with justification \"status panel\"\n";
+
+/// Screen the test can look at, as a kernel framebuffer would be
+#[derive(Clone)]
+struct Screen {
+    size: Arc<Mutex<(u32, u32)>>,
+    frames: Arc<Mutex<Vec<Vec<u32>>>>,
+}
+
+impl Screen {
+    fn new(width: u32, height: u32) -> Self {
+        Screen { size: Arc::new(Mutex::new((width, height))), frames: Arc::default() }
+    }
+
+    fn last_frame(&self) -> Vec<u32> {
+        self.frames.lock().unwrap().last().cloned().unwrap_or_default()
+    }
+}
+
+impl FramebufferProvider for Screen {
+    fn size(&self) -> (u32, u32) {
+        *self.size.lock().unwrap()
+    }
+
+    fn present(&mut self, pixels: &[u32]) -> Result<(), String> {
+        self.frames.lock().unwrap().push(pixels.to_vec());
+        Ok(())
+    }
+}
+
+fn evaluator(screen: &Screen) -> Evaluator {
+    let mut evaluator = Evaluator::new();
+    evaluator.set_framebuffer(Box::new(screen.clone()));
+    evaluator
+}
+
+#[test]
+fn test_nothing_shows_until_present() {
+    let screen = Screen::new(4, 2);
+    let mut evaluator = evaluator(&screen);
+    let source = format!(
+        "{}Draw.clear(\"#000010\")\nDraw.rect(1, 0, 2, 2, \"#ff0000\")\nDraw.pixel(3, 1, \"#00ff00\")\nDraw.pixel(9, 9, \"#00ff00\")",
+        REQUEST
+    );
+    evaluator.eval(&parse(&source)).unwrap();
+    assert!(screen.frames.lock().unwrap().is_empty());
+    assert_eq!(evaluator.framebuffer().unwrap().back().get(3, 1), Some(0x00FF00));
+
+    evaluator.eval(&parse("Draw.present()")).unwrap();
+    assert_eq!(screen.last_frame(), vec![0x10, 0xFF0000, 0xFF0000, 0x10, 0x10, 0xFF0000, 0xFF0000, 0x00FF00]);
+    assert_eq!(evaluator.framebuffer().unwrap().frames(), 1);
+}
+
+#[test]
+fn test_blit_text_and_size() {
+    let screen = Screen::new(8, 6);
+    let mut evaluator = evaluator(&screen);
+    let source = format!(
+        "{}bind width to Draw.text(0, 0, \"Hi\", \"#ffffff\")\nDraw.blit(6, 4, 2, [1, 2, 3, 4, 5])\nbind size to Draw.size()\n[width, size.width, size.height]",
+        REQUEST
+    );
+    let numbers = [7.0, 8.0, 6.0].map(Value::Number).to_vec();
+    assert_eq!(evaluator.eval(&parse(&source)), Ok(Value::List(numbers)));
+
+    let back = evaluator.framebuffer().unwrap().back();
+    // H's left stem, I's top bar
+    assert_eq!((back.get(0, 0), back.get(0, 4), back.get(1, 0)), (Some(0xFFFFFF), Some(0xFFFFFF), Some(0)));
+    assert_eq!((back.get(4, 0), back.get(6, 0)), (Some(0xFFFFFF), Some(0xFFFFFF)));
+    assert_eq!((back.get(6, 4), back.get(7, 5)), (Some(1), Some(4)));
+}
+
+/// A provider with its own font
+struct Blocky(Screen);
+
+impl FramebufferProvider for Blocky {
+    fn size(&self) -> (u32, u32) {
+        self.0.size()
+    }
+
+    fn present(&mut self, pixels: &[u32]) -> Result<(), String> {
+        self.0.present(pixels)
+    }
+
+    fn glyph(&self, c: char) -> Option<Glyph> {
+        (c == 'x').then(|| Glyph { width: 2, rows: vec![0b11, 0b11] })
+    }
+}
+
+#[test]
+fn test_provider_glyphs_and_missing_ones() {
+    let mut evaluator = Evaluator::new();
+    evaluator.set_framebuffer(Box::new(Blocky(Screen::new(10, 6))));
+    let width = evaluator.eval(&parse(&format!("{}Draw.text(0, 0, \"xy\", 7)", REQUEST)));
+    assert_eq!(width, Ok(Value::Number(6.0)));
+    let back = evaluator.framebuffer().unwrap().back();
+    // The glyph, then a box for the missing `y`
+    assert_eq!((back.get(1, 1), back.get(2, 0)), (Some(7), Some(0)));
+    assert_eq!((back.get(3, 0), back.get(5, 4), back.get(4, 2)), (Some(7), Some(7), Some(0)));
+}
+
+#[test]
+fn test_drawing_needs_a_grant_and_a_framebuffer() {
+    let screen = Screen::new(2, 2);
+    match evaluator(&screen).eval(&parse("Draw.present()")) {
+        Err(RuntimeError::CapabilityDenied { capability, .. }) => assert_eq!(capability, "Framebuffer.draw"),
+        other => panic!("expected a denial, got {:?}", other),
+    }
+    let result = Evaluator::new().eval(&parse(&format!("{}Draw.clear(0)", REQUEST)));
+    assert_eq!(result, Err(RuntimeError::Custom("draw_clear: No framebuffer installed".to_string())));
+}
+
+#[test]
+fn test_bad_arguments_and_resized_screens() {
+    let screen = Screen::new(2, 2);
+    let mut evaluator = evaluator(&screen);
+    evaluator.eval(&parse(REQUEST)).unwrap();
+    for source in ["Draw.pixel(0.5, 0, 0)", "Draw.rect(0, 0, -1, 1, 0)", "Draw.clear(\"#12345g\")", "Draw.blit(0, 0, 1, 3)"] {
+        assert!(matches!(evaluator.eval(&parse(source)), Err(RuntimeError::TypeError { .. })), "{}", source);
+    }
+
+    *screen.size.lock().unwrap() = (3, 1);
+    assert_eq!(
+        evaluator.eval(&parse("Draw.present()")),
+        Err(RuntimeError::Custom("draw_present: The screen is now 3x1; redraw the frame".to_string()))
+    );
+    assert_eq!(evaluator.eval(&parse("Draw.present()")), Ok(Value::Nothing));
+    assert_eq!(screen.last_frame(), vec![0, 0, 0]);
+}
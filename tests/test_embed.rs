@@ -0,0 +1,54 @@
+//! Tests for the single-call embedding API
+
+use std::sync::{Arc, Mutex};
+
+use glimmer_weave::{run, Backend, EvalOptions, SandboxProfile, Value};
+
+#[test]
+fn test_backends_agree() {
+    let source = "weave total as 0\nweave n as 1\nwhilst n <= 3 then\n    set total to total + n * n\n    set n to n + 1\nend\ntotal * 3";
+    for backend in [Backend::Interpreter, Backend::Bytecode] {
+        assert_eq!(run(source, EvalOptions::new().backend(backend)).unwrap(), Value::Number(42.0), "{:?}", backend);
+    }
+}
+
+#[test]
+fn test_failures_come_back_as_diagnostics() {
+    let parse = run("bind to", EvalOptions::new()).unwrap_err();
+    assert!(parse.has_errors());
+    let runtime = run("1 / 0", EvalOptions::new()).unwrap_err();
+    assert!(runtime.to_string().contains("Runtime error"), "{}", runtime);
+    let vm = run("1 / 0", EvalOptions::new().backend(Backend::Bytecode)).unwrap_err();
+    assert!(vm.to_string().contains("Runtime error"), "{}", vm);
+}
+
+#[test]
+fn test_sandbox_profiles() {
+    let source = "request FS.read with justification \"config\"\nrequest Store.readwrite with justification \"cache\"\n1";
+    assert_eq!(run(source, EvalOptions::new()).unwrap(), Value::Number(1.0));
+
+    let partial = SandboxProfile::Allow(vec!["FS.read".to_string()]);
+    let denied = run(source, EvalOptions::new().sandbox(partial)).unwrap_err();
+    assert!(denied.to_string().contains("Store.readwrite"), "{}", denied);
+    let both = SandboxProfile::Allow(vec!["FS.read".to_string(), "Store.readwrite".to_string()]);
+    assert_eq!(run(source, EvalOptions::new().sandbox(both)).unwrap(), Value::Number(1.0));
+
+    let locked = run("request FS.read with justification \"config\"", EvalOptions::new().sandbox(SandboxProfile::Locked));
+    assert!(locked.is_err());
+}
+
+#[test]
+fn test_output_sink_and_stdlib_switch() {
+    let written = Arc::new(Mutex::new(String::new()));
+    let sink = Arc::clone(&written);
+    let options = EvalOptions::new().output(move |text: &str| sink.lock().unwrap().push_str(text));
+    let result = run("print(\"total:\", 1.5)\nprintln(\"\", 3)\nprintln(upper(\"done\"))", options);
+    assert_eq!(result.unwrap(), Value::Nothing);
+    assert_eq!(written.lock().unwrap().as_str(), "total: 1.5 3\nDONE\n");
+
+    // Without a sink there is nowhere to print to
+    assert!(run("println(1)", EvalOptions::new()).is_err());
+    // Without the standard library no builtin is in scope
+    assert!(run("list_length([1])", EvalOptions::new().stdlib(false)).is_err());
+    assert_eq!(run("2 + 2", EvalOptions::new().stdlib(false)).unwrap(), Value::Number(4.0));
+}
@@ -1,7 +1,7 @@
-/// Tests for user-defined enum (variant) types - Phase 1b: Evaluation
-///
-/// These tests verify that simple enums (unit variants only) work correctly
-/// at runtime.
+//! Tests for user-defined enum (variant) types - Phase 1b: Evaluation
+//!
+//! These tests verify that simple enums (unit variants only) work correctly
+//! at runtime.
 
 use glimmer_weave::{Lexer, Parser, Evaluator, Value};
 
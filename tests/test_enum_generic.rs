@@ -1,6 +1,6 @@
-/// Tests for user-defined enum (variant) types - Phase 3: Generic Enums
-///
-/// These tests verify that generic enums with type parameters work correctly.
+//! Tests for user-defined enum (variant) types - Phase 3: Generic Enums
+//!
+//! These tests verify that generic enums with type parameters work correctly.
 
 use glimmer_weave::{Lexer, Parser, Evaluator, Value};
 
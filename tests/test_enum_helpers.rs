@@ -1,14 +1,15 @@
-/// Tests for user-defined enum (variant) types - Phase 4: Helper Methods
-///
-/// These tests verify the natural language helper functions for working with enums:
-/// - is_variant: Check if value matches a variant
-/// - expect_variant: Extract fields or panic
-/// - variant_or: Extract fields or return default
-/// - refine_variant: Transform variant if matches
+//! Tests for user-defined enum (variant) types - Phase 4: Helper Methods
+//!
+//! These tests verify the natural language helper functions for working with enums:
+//! - is_variant: Check if value matches a variant
+//! - expect_variant: Extract fields or panic
+//! - variant_or: Extract fields or return default
+//! - refine_variant: Transform variant if matches
 
 use glimmer_weave::{Lexer, Parser, Evaluator, Value};
 
 /// Helper function to evaluate source code and return the final value
+#[allow(dead_code)]
 fn eval_program(source: &str) -> Result<Value, String> {
     let mut lexer = Lexer::new(source);
     let tokens = lexer.tokenize_positioned();
@@ -1,7 +1,7 @@
-/// Tests for user-defined enum (variant) types - Phase 1: Parsing
-///
-/// These tests verify that enum definitions parse correctly according
-/// to the natural language syntax design.
+//! Tests for user-defined enum (variant) types - Phase 1: Parsing
+//!
+//! These tests verify that enum definitions parse correctly according
+//! to the natural language syntax design.
 
 use glimmer_weave::{Lexer, Parser, AstNode};
 
@@ -1,6 +1,6 @@
-/// Tests for user-defined enum (variant) types - Phase 1c: Pattern Matching
-///
-/// These tests verify that pattern matching works correctly with user-defined enums.
+//! Tests for user-defined enum (variant) types - Phase 1c: Pattern Matching
+//!
+//! These tests verify that pattern matching works correctly with user-defined enums.
 
 use glimmer_weave::{Lexer, Parser, Evaluator, Value};
 
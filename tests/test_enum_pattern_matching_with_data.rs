@@ -1,7 +1,7 @@
-/// Tests for user-defined enum (variant) types - Phase 2: Pattern Matching with Data
-///
-/// These tests verify that pattern matching works correctly with enums that have associated data,
-/// including field extraction and binding.
+//! Tests for user-defined enum (variant) types - Phase 2: Pattern Matching with Data
+//!
+//! These tests verify that pattern matching works correctly with enums that have associated data,
+//! including field extraction and binding.
 
 use glimmer_weave::{Lexer, Parser, Evaluator, Value};
 
@@ -1,10 +1,11 @@
-/// Tests for user-defined enum (variant) types - Phase 2: Enums with Data
-///
-/// These tests verify that enums with associated data (tagged unions) work correctly.
+//! Tests for user-defined enum (variant) types - Phase 2: Enums with Data
+//!
+//! These tests verify that enums with associated data (tagged unions) work correctly.
 
 use glimmer_weave::{Lexer, Parser, Evaluator, Value};
 
 /// Helper function to evaluate source code and return the final value
+#[allow(dead_code)]
 fn eval_program(source: &str) -> Result<Value, String> {
     let mut lexer = Lexer::new(source);
     let tokens = lexer.tokenize_positioned();
@@ -1,11 +1,12 @@
-/// Tests for error propagation operator (?)
-///
-/// These tests verify that the `?` operator correctly propagates Mishap values
-/// and unwraps Triumph values in the Outcome type.
+//! Tests for error propagation operator (?)
+//!
+//! These tests verify that the `?` operator correctly propagates Mishap values
+//! and unwraps Triumph values in the Outcome type.
 
 use glimmer_weave::{Lexer, Parser, Evaluator, Value};
 
 /// Helper function to evaluate source code and return the final value
+#[allow(dead_code)]
 fn eval_program(source: &str) -> Result<Value, String> {
     let mut lexer = Lexer::new(source);
     let tokens = lexer.tokenize_positioned();
@@ -572,3 +573,195 @@ fn test_try_with_different_error_types() {
         _ => panic!("Expected Outcome"),
     }
 }
+
+// ============================================================================
+// Error conversion via ConvertError
+// ============================================================================
+
+#[test]
+fn test_try_converts_error_to_declared_type() {
+    let source = r#"
+        form ParseError with
+            reason as Text
+        end
+
+        form AppError with
+            message as Text
+        end
+
+        embody ConvertError<AppError> for ParseError then
+            chant convert(self) -> AppError then
+                yield AppError { message: "parse failed: " + self.reason }
+            end
+        end
+
+        chant parse_config() then
+            yield Mishap(ParseError { reason: "bad header" })
+        end
+
+        chant load() -> Outcome<Number, AppError> then
+            bind config to parse_config()?
+            yield Triumph(config)
+        end
+
+        bind result to load()
+    "#;
+
+    let result = eval_and_get(source, "result");
+    assert!(result.is_ok(), "Failed: {:?}", result);
+    match result.unwrap() {
+        Value::Outcome { success, value } => {
+            assert!(!success);
+            match *value {
+                Value::StructInstance { ref struct_name, ref fields } => {
+                    assert_eq!(struct_name, "AppError");
+                    assert_eq!(fields.get("message"), Some(&Value::Text("parse failed: bad header".to_string())));
+                }
+                ref other => panic!("Expected AppError, got {:?}", other),
+            }
+        }
+        _ => panic!("Expected Outcome"),
+    }
+}
+
+#[test]
+fn test_try_converts_enum_error() {
+    let source = r#"
+        variant IoError then
+            NotFound,
+            Denied
+        end
+
+        embody ConvertError<Text> for IoError then
+            chant convert(self) -> Text then
+                match self with
+                    when NotFound then yield "missing file"
+                    when Denied then yield "permission denied"
+                end
+            end
+        end
+
+        chant open_file() then
+            yield Mishap(Denied)
+        end
+
+        chant read_all() -> Outcome<Text, Text> then
+            bind handle to open_file()?
+            yield Triumph(handle)
+        end
+
+        bind result to read_all()
+    "#;
+
+    let result = eval_and_get(source, "result");
+    assert!(result.is_ok(), "Failed: {:?}", result);
+    assert_eq!(
+        result.unwrap(),
+        Value::Outcome { success: false, value: Box::new(Value::Text("permission denied".to_string())) }
+    );
+}
+
+#[test]
+fn test_try_without_conversion_propagates_unchanged() {
+    let source = r#"
+        chant fail() then
+            yield Mishap(404)
+        end
+
+        chant caller() -> Outcome<Number, Text> then
+            bind x to fail()?
+            yield Triumph(x)
+        end
+
+        bind result to caller()
+    "#;
+
+    let result = eval_and_get(source, "result");
+    assert!(result.is_ok(), "Failed: {:?}", result);
+    assert_eq!(
+        result.unwrap(),
+        Value::Outcome { success: false, value: Box::new(Value::Number(404.0)) }
+    );
+}
+
+#[test]
+fn test_try_conversion_uses_innermost_chant_type() {
+    let source = r#"
+        embody ConvertError<Text> for Number then
+            chant convert(self) -> Text then
+                yield "code " + to_text(self)
+            end
+        end
+
+        chant fail() then
+            yield Mishap(7)
+        end
+
+        chant inner() then
+            bind x to fail()?
+            yield Triumph(x)
+        end
+
+        chant outer() -> Outcome<Number, Text> then
+            bind raw to inner()
+            bind y to fail()?
+            yield Triumph(raw)
+        end
+
+        bind raw_err to inner()
+        bind converted to outer()
+    "#;
+
+    assert_eq!(
+        eval_and_get(source, "raw_err").unwrap(),
+        Value::Outcome { success: false, value: Box::new(Value::Number(7.0)) }
+    );
+    assert_eq!(
+        eval_and_get(source, "converted").unwrap(),
+        Value::Outcome { success: false, value: Box::new(Value::Text("code 7".to_string())) }
+    );
+}
+
+#[test]
+fn test_try_picks_conversion_by_target_type() {
+    // Two conversions for one error type: neither replaces the other
+    let source = r#"
+        embody ConvertError<Text> for Number then
+            chant convert(self) -> Text then
+                yield "code " + to_text(self)
+            end
+        end
+
+        embody ConvertError<Truth> for Number then
+            chant convert(self) -> Truth then
+                yield self greater than 400
+            end
+        end
+
+        chant fail() then
+            yield Mishap(404)
+        end
+
+        chant as_text() -> Outcome<Number, Text> then
+            bind x to fail()?
+            yield Triumph(x)
+        end
+
+        chant as_truth() -> Outcome<Number, Truth> then
+            bind x to fail()?
+            yield Triumph(x)
+        end
+
+        bind text_err to as_text()
+        bind truth_err to as_truth()
+    "#;
+
+    assert_eq!(
+        eval_and_get(source, "text_err").unwrap(),
+        Value::Outcome { success: false, value: Box::new(Value::Text("code 404".to_string())) }
+    );
+    assert_eq!(
+        eval_and_get(source, "truth_err").unwrap(),
+        Value::Outcome { success: false, value: Box::new(Value::Truth(true)) }
+    );
+}
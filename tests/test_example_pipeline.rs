@@ -1,4 +1,4 @@
-/// Test for the pipeline example program
+//! Test for the pipeline example program
 
 use glimmer_weave::{Evaluator, Lexer, Parser};
 use std::fs;
@@ -63,7 +63,7 @@ fn debug_arrow_with_generics() {
     yield 42
 end"#;
 
-    let mut lexer = Lexer::new(&source);
+    let mut lexer = Lexer::new(source);
     let tokens = lexer.tokenize_positioned();
 
     println!("\nTokens:");
@@ -0,0 +1,75 @@
+//! Tests for fixed-size `array of T length N` types
+
+use glimmer_weave::ast::TypeAnnotation;
+use glimmer_weave::codegen::compile_to_asm;
+use glimmer_weave::semantic::{analyze, SemanticError};
+use glimmer_weave::{AstNode, Evaluator, Lexer, Parser, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn eval(source: &str) -> Value {
+    Evaluator::new().eval(&parse(source)).unwrap()
+}
+
+#[test]
+fn test_array_type_parses_with_its_length() {
+    let nodes = parse("bind regs: array of Number length 4 to [0, 0, 0, 0]");
+    let AstNode::BindStmt { typ: Some(typ), .. } = &nodes[0] else {
+        panic!("Expected typed bind, got {:?}", nodes[0]);
+    };
+    assert_eq!(
+        typ,
+        &TypeAnnotation::Array { element: Box::new(TypeAnnotation::Named("Number".to_string())), length: 4 }
+    );
+
+    let tokens = Lexer::new("bind regs: array of Number length n to []").tokenize_positioned();
+    assert!(Parser::new(tokens).parse().is_err());
+}
+
+#[test]
+fn test_arrays_evaluate_as_lists() {
+    let source = "weave regs: array of Number length 3 as [1, 2, 3]\nset regs[1] to 20\nregs[0] + regs[1] + regs[2]";
+    assert_eq!(analyze(&parse(source)), Ok(()));
+    assert_eq!(eval(source), Value::Number(24.0));
+
+    // Parameters may be typed as arrays too
+    let sum = "chant sum(block: array of Number length 2) then\n    block[0] + block[1]\nend\n";
+    assert_eq!(eval(&format!("{}sum([4, 5])", sum)), Value::Number(9.0));
+}
+
+#[test]
+fn test_list_literal_of_the_wrong_length_is_a_semantic_error() {
+    let errors = analyze(&parse("bind block: array of Number length 4 to [1, 2]")).unwrap_err();
+    assert!(errors.iter().any(|error| matches!(
+        error,
+        SemanticError::TypeError { expected, got, .. } if expected == "4 elements" && got == "2 elements"
+    )));
+}
+
+#[test]
+fn test_native_arrays_live_in_the_frame() {
+    let asm = compile_to_asm(&parse("weave regs: array of Number length 4 as [1, 2, 3, 4]\nset regs[3] to 9\nregs[3]")).unwrap();
+
+    // The elements are stored inline and constant indices fold to frame offsets
+    assert!(asm.contains("movq %rax, -32(%rbp)"));
+    assert!(asm.contains("movq %rax, -8(%rbp)"));
+    assert!(asm.contains("movq -8(%rbp), %rax"));
+    assert!(!asm.contains("ud2"));
+}
+
+#[test]
+fn test_native_array_bounds() {
+    let err = compile_to_asm(&parse("bind regs: array of Number length 2 to [1, 2]\nregs[2]")).unwrap_err();
+    assert!(err.contains("out of bounds"), "{}", err);
+
+    // Other indices are checked when they are computed
+    let asm = compile_to_asm(&parse("bind regs: array of Number length 2 to [1, 2]\nbind i to 1\nregs[i]")).unwrap();
+    assert!(asm.contains("cmpq $2, %rcx"));
+    assert!(asm.contains("ud2"));
+    assert!(asm.contains("movq -16(%rbp,%rcx,8), %rax"));
+
+    assert!(compile_to_asm(&parse("bind regs: array of Number length 2 to [1]")).is_err());
+}
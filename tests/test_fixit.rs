@@ -0,0 +1,91 @@
+//! Tests for fix-it suggestions: which errors get them, and applying them
+
+use glimmer_weave::fixit::{self, Applicability};
+use glimmer_weave::pipeline::{CompilerPipeline, Target};
+use glimmer_weave::{Lexer, Parser};
+use glimmer_weave::semantic::SemanticAnalyzer;
+
+fn parses(source: &str) -> bool {
+    Parser::new(Lexer::new(source).tokenize_positioned()).parse().is_ok()
+}
+
+#[test]
+fn test_fix_closes_every_open_block() {
+    let source = "chant count(n) then\n    for each i in range(1, n) then\n        should i is 2 then\n            print(i)";
+    let fixed = fixit::fix(source);
+    assert_eq!(fixed.applied.len(), 3);
+    assert!(fixed.source.ends_with("print(i)\nend\nend\nend"), "{}", fixed.source);
+    assert!(parses(&fixed.source));
+}
+
+#[test]
+fn test_misspelled_keywords() {
+    let source = "bind total to 0\nwhlist total less than 3 then\n    set total to total + 1\nend\n";
+    let suggestions = fixit::suggestions(source);
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].message, "did you mean `whilst`?");
+    assert_eq!(suggestions[0].applicability, Applicability::MachineApplicable);
+    assert_eq!(fixit::fix(source).source, source.replace("whlist", "whilst"));
+
+    // Names that happen to be one edit from a keyword are left alone
+    assert!(fixit::suggestions("bind bnd to 1\nbind ends to bnd + 1\n").is_empty());
+}
+
+#[test]
+fn test_missing_otherwise_arm_has_a_placeholder() {
+    let source = "bind x to 2\nmatch x with\n    when 1 then print(\"one\")\n    when 2 then print(\"two\")\nend\n";
+    let mut pipeline = CompilerPipeline::new();
+    let diagnostics = pipeline.run(source, Target::Eval).unwrap_err();
+    let diagnostic = diagnostics.iter().next().unwrap();
+    let [suggestion] = &diagnostic.suggestions[..] else {
+        panic!("expected one suggestion, got {:?}", diagnostic.suggestions);
+    };
+    assert_eq!(suggestion.applicability, Applicability::HasPlaceholders);
+    assert!(diagnostic.format().contains("  = help: add an `otherwise` arm (line 5:1)\n"));
+
+    // Not applied unattended, but it does fix the match
+    assert_eq!(fixit::fix(source).source, source);
+    let patched = fixit::apply(source, &diagnostic.suggestions);
+    assert!(patched.ends_with("    when 2 then print(\"two\")\notherwise then\n    nothing\nend\n"), "{}", patched);
+    let ast = Parser::new(Lexer::new(&patched).tokenize_positioned()).parse().unwrap();
+    assert!(SemanticAnalyzer::new().analyze(&ast).is_ok());
+}
+
+#[test]
+fn test_parse_error_diagnostics_carry_fixes() {
+    let mut pipeline = CompilerPipeline::new();
+    let diagnostics = pipeline.run("chant f() then\n    yield 1", Target::Eval).unwrap_err();
+    let diagnostic = diagnostics.iter().next().unwrap();
+    assert_eq!(diagnostic.suggestions.len(), 1);
+    assert_eq!(diagnostic.suggestions[0].replacement, "\nend");
+    assert!(diagnostic.suggestions[0].is_machine_applicable());
+}
+
+#[test]
+fn test_use_after_move_suggests_a_borrow() {
+    let source = "weave items as [1, 2]\nbind kept to items\nbind again to items\n";
+    let suggestions = fixit::suggestions(source);
+    assert_eq!(suggestions.len(), 1);
+    assert_eq!(suggestions[0].applicability, Applicability::MaybeIncorrect);
+    assert_eq!(fixit::apply(source, &suggestions), "weave items as [1, 2]\nbind kept to borrow items\nbind again to items\n");
+    assert!(fixit::fix(source).applied.is_empty());
+}
+
+#[test]
+fn test_gwc_fix_rewrites_files() {
+    let path = std::env::temp_dir().join(format!("gwc_fix_{}.gw", std::process::id()));
+    let source = "chant greet(x) then\n    shuold x then\n        yield \"hi\"\n    end\n";
+    std::fs::write(&path, source).unwrap();
+    let gwc = env!("CARGO_BIN_EXE_gwc");
+
+    let check = std::process::Command::new(gwc).args(["fix", "--check"]).arg(&path).output().unwrap();
+    assert_eq!(check.status.code(), Some(1));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), source);
+
+    let run = std::process::Command::new(gwc).arg("fix").arg(&path).output().unwrap();
+    assert!(run.status.success());
+    let listed = String::from_utf8(run.stdout).unwrap();
+    assert!(listed.contains(":2:5: did you mean `should`?"), "{}", listed);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), source.replace("shuold", "should") + "end\n");
+    std::fs::remove_file(&path).unwrap();
+}
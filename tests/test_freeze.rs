@@ -0,0 +1,109 @@
+//! Tests for frozen values handed to scripts
+
+use std::collections::BTreeMap;
+
+use glimmer_weave::{AstNode, Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn run_eval(source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval(&parse(source))
+}
+
+/// Evaluator with a frozen `config` map defined by the host
+fn with_config() -> Evaluator {
+    let mut db = BTreeMap::new();
+    db.insert("host".to_string(), Value::Text("localhost".to_string()));
+    db.insert("port".to_string(), Value::Number(5432.0));
+    let mut config = BTreeMap::new();
+    config.insert("db".to_string(), Value::Map(db));
+    config.insert("peers".to_string(), Value::List(vec![Value::Text("a".to_string()), Value::Text("b".to_string())]));
+
+    let mut evaluator = Evaluator::new();
+    evaluator.define_global("config", Value::Map(config).freeze());
+    evaluator
+}
+
+#[test]
+fn test_host_config_cannot_be_modified() {
+    for source in [
+        "set config.db to nothing",
+        "weave copy as config\nset copy.db to nothing",
+        "weave db as config.db\nset db.port to 1",
+        "weave peers as config.peers\nset peers[0] to \"evil\"",
+        "for each peer in [config.db] then\nset peer.port to 1\nend",
+    ] {
+        let result = with_config().eval(&parse(source));
+        assert!(matches!(result, Err(RuntimeError::FrozenValue(_))), "{}: {:?}", source, result);
+    }
+    let result = with_config().eval(&parse("weave copy as config\nset copy.db to nothing"));
+    assert_eq!(result, Err(RuntimeError::FrozenValue("copy".to_string())));
+}
+
+#[test]
+fn test_frozen_values_read_like_the_original() {
+    let source = r#"
+        bind port to config.db.port + 1
+        bind peer_count to list_length(config.peers)
+        weave names as ""
+        for each peer in config.peers then
+            set names to names + peer
+        end
+        [port, peer_count, names, config.peers[1], type_of(config), is_frozen(config.db), is_frozen(port)]
+    "#;
+    let text = |s: &str| Value::Text(s.to_string());
+    assert_eq!(
+        with_config().eval(&parse(source)),
+        Ok(Value::List(vec![
+            Value::Number(5433.0),
+            Value::Number(2.0),
+            text("ab"),
+            text("b"),
+            text("Map"),
+            Value::Truth(true),
+            Value::Truth(false),
+        ]))
+    );
+}
+
+#[test]
+fn test_freeze_and_thaw_in_scripts() {
+    let source = r#"
+        form Point with
+            x as Number
+            y as Number
+        end
+        bind frozen to freeze(Point { x: 1, y: 2 })
+        weave copy as thaw(frozen)
+        set copy.x to 10
+        [is_frozen(frozen), is_frozen(copy), frozen.x, copy.x, is_frozen(freeze(5)), frozen is Point { x: 1, y: 2 }]
+    "#;
+    assert_eq!(
+        run_eval(source),
+        Ok(Value::List(vec![
+            Value::Truth(true),
+            Value::Truth(false),
+            Value::Number(1.0),
+            Value::Number(10.0),
+            Value::Truth(false),
+            Value::Truth(true),
+        ]))
+    );
+}
+
+#[test]
+fn test_modification_error_can_be_caught() {
+    let source = r#"
+        weave settings as freeze({ level: 1 })
+        attempt
+            set settings.level to 2
+            "changed"
+        harmonize on FrozenValue then
+            "refused"
+        end
+    "#;
+    assert_eq!(run_eval(source), Ok(Value::Text("refused".to_string())));
+}
@@ -1,5 +1,5 @@
-/// Tests for generic type parameters in bytecode compilation
-/// Tests that monomorphization correctly generates specialized functions
+//! Tests for generic type parameters in bytecode compilation
+//! Tests that monomorphization correctly generates specialized functions
 
 use glimmer_weave::{Lexer, Parser, bytecode_compiler};
 
@@ -1,6 +1,6 @@
-/// Tests for generic type parameters at runtime
-/// This file tests that generic functions and structs execute correctly
-/// across all three execution engines (interpreter, bytecode VM, native codegen)
+//! Tests for generic type parameters at runtime
+//! This file tests that generic functions and structs execute correctly
+//! across all three execution engines (interpreter, bytecode VM, native codegen)
 
 use glimmer_weave::{Lexer, Parser, Evaluator, Value};
 
@@ -0,0 +1,71 @@
+// Only compile these tests if the allocator was successfully built
+#![cfg(all(target_arch = "x86_64", not(target_env = "msvc")))]
+
+//! Tests for `GlAllocator`: this whole test binary (test harness included)
+//! allocates through gl_malloc, the way an AethelOS component embedding the
+//! crate would share one heap between Rust and compiled scripts.
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::collections::BTreeMap;
+
+use glimmer_weave::native_allocator::*;
+use glimmer_weave::{Evaluator, Lexer, Parser, Value};
+
+#[global_allocator]
+static HEAP: GlAllocator = GlAllocator::new();
+
+#[test]
+fn test_rust_collections_live_on_gl_heap() {
+    let numbers: Vec<u64> = (0..1000).collect();
+    let start = unsafe { gl_get_heap_start() } as usize;
+    let end = unsafe { gl_get_heap_end() } as usize;
+    assert!((start..end).contains(&(numbers.as_ptr() as usize)));
+
+    let mut map = BTreeMap::new();
+    for n in &numbers {
+        map.insert(n.to_string(), *n);
+    }
+    assert_eq!(map.get("999"), Some(&999));
+    assert_eq!(numbers.iter().sum::<u64>(), 499_500);
+}
+
+#[test]
+fn test_over_aligned_layouts() {
+    for align in [16, 64, 4096] {
+        let layout = Layout::from_size_align(100, align).unwrap();
+        unsafe {
+            let ptr = HEAP.alloc(layout);
+            assert!(!ptr.is_null());
+            assert_eq!(ptr as usize % align, 0, "aligned to {}", align);
+            ptr.write_bytes(0xAB, 100);
+            HEAP.dealloc(ptr, layout);
+        }
+    }
+}
+
+#[test]
+fn test_realloc_keeps_contents() {
+    let mut text = String::new();
+    for i in 0..500 {
+        text.push_str(&i.to_string());
+    }
+    assert!(text.starts_with("0123456789101112"));
+    assert!(text.ends_with("498499"));
+}
+
+#[test]
+fn test_interpreter_runs_on_gl_heap() {
+    let source = r#"
+        chant fib(n) then
+            should n less than 2 then
+                yield n
+            otherwise
+                yield fib(n - 1) + fib(n - 2)
+            end
+        end
+        fib(15)
+    "#;
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().expect("parse failed");
+    assert_eq!(Evaluator::new().eval(&ast).expect("eval failed"), Value::Number(610.0));
+}
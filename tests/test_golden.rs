@@ -0,0 +1,60 @@
+//! Golden-file checks of the assembly and bytecode backends
+//!
+//! Set `GLIMMER_UPDATE_GOLDEN=1` to rewrite `tests/golden` after an
+//! intended change to generated code, then review the diff.
+use glimmer_weave::golden::{normalize, Backend, GoldenSuite};
+
+#[test]
+fn test_fixtures_match_their_golden_files() {
+    let report = GoldenSuite::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden")).run().unwrap();
+    assert_eq!(report.checked, 6);
+    assert!(report.passed(), "{}", report);
+}
+
+#[test]
+fn test_label_numbering_is_normalized() {
+    let first = normalize("jmp .L_loop_4\n.L_loop_4:\n    je .L_done_9\n.L_done_9:\n");
+    let second = normalize("jmp .L_loop_12\n.L_loop_12:\n    je .L_done_13\n.L_done_13:\n");
+    assert_eq!(first, second);
+    // Names without a number, and other numbers, are left alone
+    assert_eq!(normalize("call .L_func_main\nmovq $42, %rax"), "call .L_func_main\nmovq $42, %rax\n");
+}
+
+/// Fixture directory of its own, so tests don't touch each other's files
+fn scratch(name: &str, source: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("glimmer_golden_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("answer.gw"), source).unwrap();
+    dir
+}
+
+#[test]
+fn test_changed_output_fails_until_updated() {
+    let dir = scratch("update", "bind answer to 42\nanswer");
+    let suite = GoldenSuite::new(&dir).backends(&[Backend::Bytecode]).update(false);
+    let report = suite.run().unwrap();
+    assert!(report.failures[0].problem.starts_with("missing; set GLIMMER_UPDATE_GOLDEN=1"), "{}", report);
+
+    let report = suite.clone().update(true).run().unwrap();
+    assert!(report.passed());
+    assert_eq!(report.updated, vec![dir.join("answer.bc")]);
+    assert!(suite.run().unwrap().passed());
+
+    // A backend change shows up as the first line that moved
+    std::fs::write(dir.join("answer.gw"), "bind answer to 43\nanswer").unwrap();
+    let report = suite.run().unwrap();
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].problem, "line 9: expected `#0: Number(42.0)`, got `#0: Number(43.0)`");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_compile_errors_are_failures() {
+    let dir = scratch("broken", "bind to");
+    let report = GoldenSuite::new(&dir).update(true).run().unwrap();
+    assert_eq!(report.failures.len(), 2);
+    assert!(report.failures.iter().all(|failure| failure.problem.starts_with("compile error: parse error")));
+    assert!(report.updated.is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
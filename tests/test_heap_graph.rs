@@ -0,0 +1,85 @@
+//! Tests for heap graph dumps
+use glimmer_weave::heap_graph::HeapGraph;
+use glimmer_weave::{Evaluator, Lexer, Parser};
+
+fn graph(source: &str) -> HeapGraph {
+    let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().expect("parse failed");
+    let mut evaluator = Evaluator::new();
+    evaluator.eval(&ast).expect("evaluation failed");
+    evaluator.heap_graph()
+}
+
+fn child(graph: &HeapGraph, id: usize, label: &str) -> usize {
+    graph.edges_from(id).find(|edge| edge.label == label).map(|edge| edge.to).expect("no such edge")
+}
+
+#[test]
+fn test_closures_show_what_they_retain() {
+    let graph = graph(
+        r#"
+bind cache to [1, 2, 3]
+chant lookup(i) then
+    cache[i]
+end
+"#,
+    );
+    let lookup = graph.binding("lookup").unwrap();
+    let closure = child(&graph, lookup, "closure");
+    assert_eq!(graph.node(closure).unwrap().kind, "Environment");
+    let captured = child(&graph, closure, "cache");
+    assert_eq!(graph.node(captured).unwrap().label, "3 items");
+    assert_eq!(graph.edges_from(captured).count(), 3);
+
+    let retainers = graph.retainers();
+    assert_eq!(retainers[0].0, "lookup");
+    assert!(retainers[0].1 > graph.retained_bytes(graph.binding("cache").unwrap()));
+}
+
+#[test]
+fn test_shared_and_cell_state() {
+    let graph = graph(
+        r#"
+bind config to Shared.new({name: "svc"})
+bind again to Shared.clone(config)
+bind counter to Cell.new(0)
+"#,
+    );
+    let again = graph.node(graph.binding("again").unwrap()).unwrap();
+    assert_eq!(again.kind, "Shared");
+    assert_eq!(again.ref_count, Some(2));
+    let counter = graph.binding("counter").unwrap();
+    assert_eq!(graph.node(counter).unwrap().label, "0 borrows");
+    let inner = child(&graph, counter, "value");
+    assert_eq!(graph.node(inner).unwrap().label, "0");
+}
+
+#[test]
+fn test_builder_copies_share_one_buffer() {
+    let graph = graph(
+        r#"
+bind original to text_builder()
+bind copy to original
+text_push(copy, "hello")
+"#,
+    );
+    let shared = graph.shared();
+    assert_eq!(shared.len(), 1);
+    assert_eq!(shared[0].kind, "Buffer");
+    assert_eq!(shared[0].ref_count, Some(2));
+    assert_eq!(shared[0].label, "5 bytes of text");
+}
+
+#[test]
+fn test_exports() {
+    let graph = graph("bind greeting to \"say \\\"hi\\\"\"\n");
+    let dot = graph.to_dot();
+    assert!(dot.starts_with("digraph heap {\n"));
+    assert!(dot.contains("n0 -> n1 [label=\"greeting\"];"));
+    assert!(dot.contains(r#"\"say \\\"hi\\\"\""#));
+    assert!(dot.ends_with("}\n"));
+
+    let json = graph.to_json();
+    assert!(json.starts_with("{\"nodes\":[{\"id\":0,\"kind\":\"Environment\""));
+    assert!(json.contains("\"edges\":[{\"from\":0,\"to\":1,\"label\":\"greeting\"}]"));
+    assert!(json.contains(r#""label":"\"say \\\"hi\\\"\"""#));
+}
@@ -0,0 +1,196 @@
+//! Tests for `http_get` and `http_post` over the network provider
+
+use std::collections::BTreeMap;
+
+use glimmer_weave::net::NetProvider;
+use glimmer_weave::sync::{lock, shared, Shared};
+use glimmer_weave::taint::TaintPolicy;
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+/// REST service at `api.test:80` that redirects `/old` to `mirror.test:8080`
+#[derive(Default)]
+struct Api {
+    requests: Vec<String>,
+    timeouts: Vec<Option<u64>>,
+    open: BTreeMap<u64, (String, Vec<u8>)>,
+    next: u64,
+}
+
+struct ApiNet(Shared<Api>);
+
+fn reply(host: &str, request: &str) -> String {
+    let request_line = request.lines().next().unwrap_or_default();
+    match (host, request_line) {
+        ("api.test", "GET /items HTTP/1.1") => {
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 7\r\n\r\n[1,2,3]".to_string()
+        }
+        ("api.test", "GET /old HTTP/1.1") => "HTTP/1.1 301 Moved\r\nLocation: http://mirror.test:8080/items\r\n\r\n".to_string(),
+        ("api.test", "GET /loop HTTP/1.1") => "HTTP/1.1 302 Found\r\nLocation: /loop\r\n\r\n".to_string(),
+        ("api.test", "POST /items HTTP/1.1") => {
+            let body = request.split("\r\n\r\n").nth(1).unwrap_or_default();
+            format!("HTTP/1.1 201 Created\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n0\r\n\r\n", body.len(), body)
+        }
+        ("mirror.test", _) => "HTTP/1.1 200 OK\r\nContent-Length: 6\r\n\r\nmirror".to_string(),
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string(),
+    }
+}
+
+impl NetProvider for ApiNet {
+    fn allows(&self, host: &str, _port: u16) -> bool {
+        host.ends_with(".test")
+    }
+
+    fn connect(&mut self, host: &str, _port: u16) -> Result<u64, String> {
+        let mut api = lock(&self.0);
+        api.next += 1;
+        let handle = api.next;
+        api.open.insert(handle, (host.to_string(), Vec::new()));
+        Ok(handle)
+    }
+
+    fn send(&mut self, handle: u64, bytes: &[u8]) -> Result<usize, String> {
+        let mut api = lock(&self.0);
+        let request = String::from_utf8_lossy(bytes).into_owned();
+        api.requests.push(request.clone());
+        let (host, pending) = api.open.get_mut(&handle).ok_or("closed")?;
+        *pending = reply(host, &request).into_bytes();
+        Ok(bytes.len())
+    }
+
+    fn recv(&mut self, handle: u64, max: usize) -> Result<Vec<u8>, String> {
+        let mut api = lock(&self.0);
+        let (_, pending) = api.open.get_mut(&handle).ok_or("closed")?;
+        let count = max.min(pending.len());
+        Ok(pending.drain(..count).collect())
+    }
+
+    fn close(&mut self, handle: u64) -> Result<(), String> {
+        lock(&self.0).open.remove(&handle).map(|_| ()).ok_or_else(|| "closed".to_string())
+    }
+
+    fn set_timeout(&mut self, _handle: u64, millis: Option<u64>) -> Result<(), String> {
+        lock(&self.0).timeouts.push(millis);
+        Ok(())
+    }
+}
+
+fn evaluator() -> (Evaluator, Shared<Api>) {
+    let api = shared(Api::default());
+    let mut evaluator = Evaluator::new();
+    evaluator.set_net_provider(Box::new(ApiNet(Shared::clone(&api))));
+    (evaluator, api)
+}
+
+const GRANT: &str = "request Net.connect(\"api.test\", 80) with justification \"inventory\"\n";
+
+fn field(value: &Value, name: &str) -> Value {
+    match value {
+        Value::Outcome { success: true, value } => field(value, name),
+        Value::Map(entries) => entries[name].clone(),
+        other => panic!("expected a response, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_get_returns_status_headers_and_body() {
+    let (mut evaluator, api) = evaluator();
+    let source = format!(
+        "{}bind response to expect_triumph(Net.http_get(\"http://api.test/items\", {{headers: {{Accept: \"application/json\"}}, timeout: 2000}}), \"get\")\nresponse",
+        GRANT
+    );
+    let response = evaluator.eval(&parse(&source)).unwrap();
+    assert_eq!(field(&response, "status"), Value::Number(200.0));
+    assert_eq!(field(&response, "body"), Value::Text("[1,2,3]".to_string()));
+    assert_eq!(field(&field(&response, "headers"), "content-type"), Value::Text("application/json".to_string()));
+
+    let api = lock(&api);
+    assert!(api.requests[0].contains("Host: api.test\r\n"));
+    assert!(api.requests[0].contains("Accept: application/json\r\n"));
+    assert_eq!(api.timeouts, [Some(2000)]);
+    assert!(api.open.is_empty());
+}
+
+#[test]
+fn test_post_sends_the_body() {
+    let (mut evaluator, api) = evaluator();
+    let source = format!("{}http_post(\"http://api.test/items\", \"{{}}\")", GRANT);
+    let response = evaluator.eval(&parse(&source)).unwrap();
+    assert_eq!(field(&response, "status"), Value::Number(201.0));
+    assert_eq!(field(&response, "body"), Value::Text("{}".to_string()));
+    assert!(lock(&api).requests[0].ends_with("Content-Length: 2\r\n\r\n{}"));
+
+    // An error status is still a response
+    let source = format!("{}http_get(\"http://api.test/missing\")", GRANT);
+    let response = evaluator.eval(&parse(&source)).unwrap();
+    assert_eq!(field(&response, "status"), Value::Number(404.0));
+}
+
+#[test]
+fn test_redirects_need_their_own_grant_and_are_bounded() {
+    let (mut evaluator, _) = evaluator();
+    let mishap = |text: &str| Value::Outcome { success: false, value: Box::new(Value::Text(text.to_string())) };
+
+    let source = format!("{}http_get(\"http://api.test/old\")", GRANT);
+    let result = evaluator.eval(&parse(&source)).unwrap();
+    assert_eq!(result, mishap("No Net.connect capability for mirror.test:8080"));
+
+    let source = "request Net.connect(\"mirror.test\", 8080) with justification \"mirror\"\nhttp_get(\"http://api.test/old\")";
+    let result = evaluator.eval(&parse(source)).unwrap();
+    assert_eq!(field(&result, "body"), Value::Text("mirror".to_string()));
+
+    let source = "http_get(\"http://api.test/loop\", {redirects: 2})";
+    assert_eq!(evaluator.eval(&parse(source)).unwrap(), mishap("More than 2 redirects"));
+    let source = "http_get(\"http://api.test/old\", {redirects: 0})";
+    assert_eq!(field(&evaluator.eval(&parse(source)).unwrap(), "status"), Value::Number(301.0));
+}
+
+#[test]
+fn test_requests_need_a_grant_and_valid_arguments() {
+    let (mut evaluator, api) = evaluator();
+    let result = evaluator.eval(&parse("http_get(\"http://api.test/items\")")).unwrap();
+    assert_eq!(result, Value::Outcome {
+        success: false,
+        value: Box::new(Value::Text("No Net.connect capability for api.test:80".to_string())),
+    });
+    assert!(lock(&api).requests.is_empty());
+
+    assert!(matches!(evaluator.eval(&parse("http_get(42)")), Err(RuntimeError::TypeError { .. })));
+    assert!(matches!(evaluator.eval(&parse("http_get(\"http://api.test/\", {retries: 3})")), Err(RuntimeError::Custom(_))));
+    assert!(matches!(evaluator.eval(&parse("http_post(\"http://api.test/\")")), Err(RuntimeError::ArityMismatch { .. })));
+}
+
+#[test]
+fn test_responses_are_tainted() {
+    let (mut evaluator, _) = evaluator();
+    evaluator.set_taint_policy(TaintPolicy::new().block());
+    let source = format!(
+        "{}bind response to expect_triumph(http_get(\"http://api.test/items\"), \"get\")\nhttp_post(\"http://api.test/items\", response.body)",
+        GRANT
+    );
+    match evaluator.eval(&parse(&source)) {
+        Err(RuntimeError::CapabilityDenied { capability, .. }) => assert_eq!(capability, "Net.send"),
+        other => panic!("expected a denial, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_header_and_url_injection_is_refused() {
+    let (mut evaluator, api) = evaluator();
+    evaluator.eval(&parse(GRANT)).unwrap();
+
+    let source = r#"http_get("http://api.test/items", {headers: {Accept: "text/plain\r\nX-Injected: 1"}})"#;
+    match evaluator.eval(&parse(source)) {
+        Err(RuntimeError::Custom(message)) => assert_eq!(message, "Invalid value for header 'Accept'"),
+        other => panic!("expected a header error, got {:?}", other),
+    }
+
+    let source = r#"http_get("http://api.test/items HTTP/1.1\r\nX-Injected: 1\r\n\r\nGET /admin")"#;
+    let result = evaluator.eval(&parse(source)).unwrap();
+    assert!(matches!(result, Value::Outcome { success: false, .. }));
+    assert!(lock(&api).requests.is_empty());
+}
@@ -0,0 +1,64 @@
+//! Tests for `tr` and message catalogs, at runtime and in the pipeline's
+//! key checks
+
+use glimmer_weave::i18n::MessageCatalogs;
+use glimmer_weave::pipeline::{CompilerPipeline, Output, Target};
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+fn messages() -> MessageCatalogs {
+    MessageCatalogs::new("en")
+        .with_catalog("en", [("greeting", "Hello, {name}!"), ("files", "{count} files in {{home}}")])
+        .with_catalog("es", [("greeting", "¡Hola, {name}!")])
+}
+
+#[test]
+fn test_tr_translates_and_falls_back() {
+    let mut evaluator = Evaluator::new();
+    evaluator.set_messages(messages().with_locale("es-MX"));
+    let source = r#"
+        [tr("greeting", {name: "Ada"}), I18n.tr("files", {count: 3}), tr("missing")]
+    "#;
+    let text = |text: &str| Value::Text(text.to_string());
+    assert_eq!(
+        evaluator.eval(&parse(source)).unwrap(),
+        Value::List(vec![text("¡Hola, Ada!"), text("3 files in {home}"), text("missing")])
+    );
+}
+
+#[test]
+fn test_scripts_load_catalogs_and_switch_locale() {
+    let mut evaluator = Evaluator::new();
+    evaluator.set_messages(messages());
+    let source = r#"
+        I18n.load("fr", {greeting: "Bonjour, {name} !"})
+        set_locale("fr")
+        tr("greeting", {name: "Zoé"})
+    "#;
+    assert_eq!(evaluator.eval(&parse(source)).unwrap(), Value::Text("Bonjour, Zoé !".to_string()));
+    assert_eq!(evaluator.messages().locale(), "fr");
+
+    assert!(matches!(evaluator.eval(&parse("tr(\"greeting\")")), Err(RuntimeError::Custom(_))));
+    assert!(matches!(evaluator.eval(&parse("tr(\"greeting\", 1)")), Err(RuntimeError::TypeError { .. })));
+    assert!(matches!(evaluator.eval(&parse("I18n.load(\"de\", {x: 1})")), Err(RuntimeError::TypeError { .. })));
+}
+
+#[test]
+fn test_pipeline_rejects_unknown_keys() {
+    let mut pipeline = CompilerPipeline::new().messages(messages().with_locale("es"));
+    let output = pipeline.run("tr(\"greeting\", {name: \"Ada\"})", Target::Eval).unwrap();
+    assert!(matches!(output, Output::Value(Value::Text(text)) if text == "¡Hola, Ada!"));
+
+    let source = "chant show() then\n    I18n.tr(\"greting\")\nend\nshow()";
+    let diagnostics = pipeline.run(source, Target::Eval).unwrap_err();
+    let errors: Vec<String> = diagnostics.iter().map(|diagnostic| format!("{:?}", diagnostic)).collect();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("UnknownMessageKey") && errors[0].contains("greting"));
+
+    // Keys only known at runtime cannot be checked
+    assert!(pipeline.run("bind key to \"nope\"\ntr(key)", Target::Eval).is_ok());
+}
@@ -0,0 +1,212 @@
+//! Tests for implicit chant returns: without `yield`, a chant returns its
+//! last expression, in the evaluator, the VM and native code alike
+
+use glimmer_weave::bytecode::{BytecodeChunk, Constant, Instruction};
+use glimmer_weave::bytecode_compiler::compile;
+use glimmer_weave::{AstNode, Evaluator, Lexer, Parser, RuntimeError, SemanticAnalyzer, SemanticWarning, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn run(source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval(&parse(source))
+}
+
+fn warnings(source: &str) -> Vec<SemanticWarning> {
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.analyze(&parse(source)).expect("analysis failed");
+    analyzer.warnings().to_vec()
+}
+
+/// Constants a chunk's `Return`s hand back, `Nothing` for LoadNothing
+fn returned_constants(chunk: &BytecodeChunk) -> Vec<Constant> {
+    chunk
+        .instructions
+        .windows(2)
+        .filter_map(|pair| match pair {
+            [Instruction::LoadConst { dest, constant_id }, Instruction::Return { value }] if dest == value => {
+                Some(chunk.constants[*constant_id as usize].clone())
+            }
+            [Instruction::LoadNothing { dest }, Instruction::Return { value }] if dest == value => Some(Constant::Nothing),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_last_expression_is_returned() {
+    let source = r#"
+        chant double(n) then
+            bind twice to n * 2
+            twice
+        end
+        double(21)
+    "#;
+    assert_eq!(run(source).unwrap(), Value::Number(42.0));
+}
+
+#[test]
+fn test_trailing_statement_returns_nothing() {
+    let source = r#"
+        chant store(n) then
+            bind kept to n
+        end
+        chant count(n) then
+            weave i as 0
+            whilst i is not n then
+                set i to i + 1
+            end
+        end
+        [store(1), count(3)]
+    "#;
+    assert_eq!(run(source).unwrap(), Value::List(vec![Value::Nothing, Value::Nothing]));
+}
+
+#[test]
+fn test_branch_tails_are_returned() {
+    let source = r#"
+        chant sign(n) then
+            should n less than 0 then
+                "negative"
+            otherwise
+                should n is 0 then
+                    "zero"
+                otherwise
+                    "positive"
+                end
+            end
+        end
+        chant only_negative(n) then
+            should n less than 0 then
+                "negative"
+            end
+        end
+        [sign(-4), sign(0), sign(9), only_negative(1)]
+    "#;
+    let expected = vec![
+        Value::Text("negative".to_string()),
+        Value::Text("zero".to_string()),
+        Value::Text("positive".to_string()),
+        Value::Nothing,
+    ];
+    assert_eq!(run(source).unwrap(), Value::List(expected));
+}
+
+#[test]
+fn test_match_tail_is_returned() {
+    let source = r#"
+        variant Light then
+            Red,
+            Green
+        end
+        chant next(light) then
+            match light with
+                when Red then
+                    bind following to Green
+                    following
+                when Green then Red
+            end
+        end
+        [next(Red), next(Green)]
+    "#;
+    let result = run(source).unwrap();
+    let Value::List(lights) = result else { panic!("expected a list, got {:?}", result) };
+    assert!(matches!(&lights[0], Value::VariantValue { variant_name, .. } if variant_name == "Green"));
+    assert!(matches!(&lights[1], Value::VariantValue { variant_name, .. } if variant_name == "Red"));
+}
+
+#[test]
+fn test_methods_return_last_expression() {
+    let source = r#"
+        form Point with
+            x as Number
+        end
+        embody Display for Point then
+            chant describe(self) -> Text then
+                "P" + to_text(self.x)
+            end
+        end
+        to_text(Point { x: 3 })
+    "#;
+    assert_eq!(run(source).unwrap(), Value::Text("P3".to_string()));
+}
+
+#[test]
+fn test_yield_still_returns_early() {
+    let source = r#"
+        chant early() then
+            yield 1
+            2
+        end
+        early()
+    "#;
+    assert_eq!(run(source).unwrap(), Value::Number(1.0));
+}
+
+#[test]
+fn test_bytecode_returns_last_expression() {
+    let chunk = compile(&parse(
+        r#"
+        chant answer() then
+            42
+        end
+        chant quiet() then
+            weave unused as 7
+        end
+        chant pick(flag) then
+            should flag then
+                1
+            otherwise
+                2
+            end
+        end
+        "#,
+    ))
+    .unwrap();
+    assert_eq!(
+        returned_constants(&chunk),
+        [Constant::Number(42.0), Constant::Nothing, Constant::Number(1.0), Constant::Number(2.0)]
+    );
+}
+
+#[test]
+fn test_unused_values_are_warned() {
+    let source = r#"
+        chant busy(n) then
+            n + 1
+            n * 2
+        end
+    "#;
+    assert_eq!(warnings(source), [SemanticWarning::UnusedValue { chant: "busy".to_string() }]);
+
+    // Calls may be made for their effects
+    let source = r#"
+        chant noisy(n) then
+            to_text(n)
+            n
+        end
+    "#;
+    assert!(warnings(source).is_empty());
+}
+
+#[test]
+fn test_value_discarded_by_nothing_chant_is_warned() {
+    let source = r#"
+        chant settle(n) -> Nothing then
+            should n less than 0 then
+                0
+            otherwise
+                n
+            end
+        end
+    "#;
+    assert_eq!(
+        warnings(source),
+        [
+            SemanticWarning::DiscardedReturnValue { chant: "settle".to_string() },
+            SemanticWarning::DiscardedReturnValue { chant: "settle".to_string() },
+        ]
+    );
+}
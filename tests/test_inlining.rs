@@ -0,0 +1,85 @@
+//! Tests for the inlining pass as the compiled back ends see it through
+//! the optimizing pipeline
+
+use glimmer_weave::bytecode::Instruction;
+use glimmer_weave::inline::Inliner;
+use glimmer_weave::pipeline::{CompilerPipeline, Output, Target};
+use glimmer_weave::{Evaluator, Lexer, Parser, Value};
+
+const HOT_LOOP: &str = r#"
+    swift chant scaled(n) then
+        n * 3
+    end
+    chant step(total, n) then
+        total + scaled(n)
+    end
+
+    weave total as 0
+    weave i as 0
+    whilst i less than 5 then
+        set total to step(total, i)
+        set i to i + 1
+    end
+    total
+"#;
+
+fn compile(source: &str, target: Target, optimize: bool) -> Output {
+    CompilerPipeline::new()
+        .optimize(optimize)
+        .run(source, target)
+        .unwrap_or_else(|diagnostics| panic!("{}", diagnostics))
+}
+
+fn calls(output: Output) -> usize {
+    match output {
+        Output::Bytecode(chunk) => chunk
+            .instructions
+            .iter()
+            .filter(|instruction| matches!(instruction, Instruction::Call { .. }))
+            .count(),
+        other => panic!("expected bytecode, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_bytecode_loop_has_no_calls() {
+    assert!(calls(compile(HOT_LOOP, Target::Bytecode, false)) > 0);
+    assert_eq!(calls(compile(HOT_LOOP, Target::Bytecode, true)), 0);
+}
+
+#[test]
+fn test_inlined_program_evaluates_the_same() {
+    let tokens = Lexer::new(HOT_LOOP).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().unwrap();
+    let inlined = Inliner::new().inline(&ast);
+    assert_ne!(inlined, ast);
+    assert_eq!(Evaluator::new().eval(&inlined).unwrap(), Value::Number(30.0));
+    assert_eq!(Evaluator::new().eval(&ast).unwrap(), Value::Number(30.0));
+}
+
+#[test]
+fn test_assembly_loop_has_no_calls() {
+    let main = |output: Output| match output {
+        Output::Asm(asm) => {
+            let start = asm.find("main:").unwrap();
+            let end = asm[start..].find("\n.globl glimmer_").map_or(asm.len(), |end| start + end);
+            asm[start..end].to_string()
+        }
+        other => panic!("expected assembly, got {:?}", other),
+    };
+    // The chant definitions are still emitted, but the loop no longer calls them
+    let before = main(compile(HOT_LOOP, Target::Asm, false));
+    let after = main(compile(HOT_LOOP, Target::Asm, true));
+    assert!(before.contains("call .L_func_step"));
+    assert!(!after.contains("call .L_func_step"));
+    assert!(!after.contains("call .L_func_scaled"));
+    assert!(after.contains(".L_func_scaled:"));
+}
+
+#[test]
+fn test_swift_is_not_reserved() {
+    match compile("bind swift to 2\nswift + 1", Target::Eval, false) {
+        Output::Value(value) => assert_eq!(value, Value::Number(3.0)),
+        other => panic!("expected a value, got {:?}", other),
+    }
+}
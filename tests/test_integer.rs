@@ -0,0 +1,111 @@
+//! Tests for 64-bit integers (`42i`)
+
+use glimmer_weave::bytecode_compiler::compile;
+use glimmer_weave::language_version::Feature;
+use glimmer_weave::semantic::{analyze, SemanticError};
+use glimmer_weave::vm::{VmError, VM};
+use glimmer_weave::{AstNode, Evaluator, Lexer, Parser, RuntimeError, SemanticAnalyzer, Value};
+
+fn parse(source: &str) -> Result<Vec<AstNode>, String> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().map_err(|e| e.message)
+}
+
+fn eval(source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval(&parse(source).expect("parse failed"))
+}
+
+#[test]
+fn test_literals_and_arithmetic() {
+    // As Numbers, 2^53 + 1 rounds back down to 2^53
+    assert_eq!(eval("9007199254740993i - 9007199254740992i").unwrap(), Value::Integer(1));
+    assert_eq!(eval("-7i / 2i").unwrap(), Value::Integer(-3));
+    assert_eq!(eval("-7i % 2i").unwrap(), Value::Integer(-1));
+    assert_eq!(eval("6i * 7i").unwrap(), Value::Integer(42));
+    assert_eq!(eval("3i less than 5i").unwrap(), Value::Truth(true));
+    assert_eq!(eval("to_text(42i)").unwrap(), Value::Text("42".to_string()));
+    assert_eq!(eval("list_sort([3i, -1i, 2i])").unwrap(), Value::List(vec![Value::Integer(-1), Value::Integer(2), Value::Integer(3)]));
+
+    // A literal past 64 bits is an error rather than a Number and a name
+    assert_eq!(parse("9223372036854775808i").unwrap_err(), "Integer literal 9223372036854775808i does not fit in 64 bits");
+
+    // `i` only suffixes whole numbers, and not the start of a name
+    assert!(Evaluator::new().eval(&parse("1.5i").unwrap()).is_err());
+    assert_eq!(eval("bind i to 2\n3 * i").unwrap(), Value::Number(6.0));
+}
+
+#[test]
+fn test_overflow_is_an_error() {
+    for source in ["9223372036854775807i + 1i", "-9223372036854775807i - 2i", "4294967296i * 4294967296i", "-(-9223372036854775807i - 1i)"] {
+        assert!(matches!(eval(source), Err(RuntimeError::IntegerOverflow(_))), "{}", source);
+    }
+    assert_eq!(eval("1i / 0i"), Err(RuntimeError::DivisionByZero));
+    let caught = "attempt\n    9223372036854775807i + 1i\nharmonize on IntegerOverflow then\n    \"overflow\"\nend";
+    assert_eq!(eval(caught).unwrap(), Value::Text("overflow".to_string()));
+}
+
+#[test]
+fn test_conversions() {
+    assert_eq!(eval("to_integer(4096)").unwrap(), Value::Integer(4096));
+    assert_eq!(eval("to_integer(\" -12 \")").unwrap(), Value::Integer(-12));
+    assert_eq!(eval("to_integer(9223372036854775807n)").unwrap(), Value::Integer(i64::MAX));
+    assert_eq!(eval("Convert.to_integer(5i)").unwrap(), Value::Integer(5));
+    assert_eq!(eval("to_number(5i) + 0.5").unwrap(), Value::Number(5.5));
+    assert_eq!(eval("bigint(5i) * 2n").unwrap(), eval("10n").unwrap());
+    for source in ["to_integer(0.5)", "to_integer(\"12a\")", "to_integer(9223372036854775808n)", "to_integer([])"] {
+        assert!(eval(source).is_err(), "{}", source);
+    }
+}
+
+#[test]
+fn test_integers_index_lists() {
+    assert_eq!(eval("[10, 20, 30][1i]").unwrap(), Value::Number(20.0));
+    assert_eq!(eval("weave xs as [1, 2]\nset xs[1i] to 5\nxs").unwrap(), Value::List(vec![Value::Number(1.0), Value::Number(5.0)]));
+    assert!(matches!(eval("[1, 2][-1i]"), Err(RuntimeError::Custom(message)) if message == "Negative index -1"));
+    assert!(matches!(eval("[1, 2][2i]"), Err(RuntimeError::IndexOutOfBounds { index: 2, length: 2 })));
+}
+
+#[test]
+fn test_mixing_with_numbers_is_an_error() {
+    let errors = |source: &str| -> Vec<String> {
+        analyze(&parse(source).unwrap())
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|error| match error {
+                SemanticError::TypeError { got, .. } => Some(got),
+                _ => None,
+            })
+            .collect()
+    };
+    assert_eq!(errors("bind x to 1i + 1"), ["Integer Add Number"]);
+    assert_eq!(errors("bind x to 1i + 1n"), ["Integer Add BigInt"]);
+    assert!(errors("chant twice(n: Integer) then\n    n * 2i\nend\ntwice(-3i)").is_empty());
+    assert_eq!(errors("bind id: Integer to 3").len(), 1);
+    assert!(eval("1i + 1").is_err());
+
+    let mut analyzer = SemanticAnalyzer::new();
+    analyzer.enable_type_inference();
+    assert_eq!(analyzer.infer_program_types(&parse("bind a to 1i\nbind b to a * a - 3i").unwrap()), Ok(()));
+    assert!(analyzer.infer_program_types(&parse("bind a to 1i\nbind b to a + 1").unwrap()).is_err());
+}
+
+#[test]
+fn test_vm_agrees() {
+    let run = |source: &str| VM::new().execute(compile(&parse(source).unwrap()).unwrap());
+    assert_eq!(run("2i + 3i * 4i").unwrap(), Value::Integer(14));
+    assert_eq!(run("-(7i / 2i)").unwrap(), Value::Integer(-3));
+    assert_eq!(run("[1, 2, 3][2i]").unwrap(), Value::Number(3.0));
+    assert!(matches!(run("9223372036854775807i + 1i"), Err(VmError::IntegerOverflow(operation)) if operation == "addition"));
+    assert!(matches!(run("-(0i - 9223372036854775807i - 1i)"), Err(VmError::IntegerOverflow(operation)) if operation == "negation"));
+    assert!(matches!(run("1i / 0i"), Err(VmError::DivisionByZero)));
+}
+
+#[test]
+fn test_integers_need_language_version_1_4() {
+    match analyze(&parse("speaks \"1.3\"\n42i").unwrap()).unwrap_err().as_slice() {
+        [SemanticError::UnsupportedFeature { feature, .. }] => assert_eq!(*feature, Feature::IntegerLiterals),
+        other => panic!("expected an unsupported feature, got {:?}", other),
+    }
+    assert!(analyze(&parse("speaks \"1.4\"\n42i").unwrap()).is_ok());
+}
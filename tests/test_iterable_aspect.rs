@@ -0,0 +1,197 @@
+//! Tests for the `Iterable` aspect: forms and variants that `for each` and
+//! the iter_* builtins walk through their `next` method
+
+use glimmer_weave::semantic::analyze;
+use glimmer_weave::{AstNode, Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().unwrap_or_else(|e| panic!("parse failed: {:?}", e))
+}
+
+/// Run a program after `COUNTDOWN`, which must pass semantic analysis
+fn run(program: &str) -> Result<Value, RuntimeError> {
+    let ast = parse(&format!("{}\n{}", COUNTDOWN, program));
+    if let Err(errors) = analyze(&ast) {
+        panic!("semantic errors: {:?}", errors);
+    }
+    Evaluator::new().eval(&ast)
+}
+
+fn numbers(values: &[f64]) -> Value {
+    Value::List(values.iter().map(|n| Value::Number(*n)).collect())
+}
+
+/// Counts down from `current`, recording its position in its own field
+const COUNTDOWN: &str = r#"
+form Countdown with current as Number end
+
+embody Iterable<Number> for Countdown then
+    chant next(self) -> Maybe<Number> then
+        should self.current is 0 then
+            yield Absent
+        otherwise
+            set self.current to self.current - 1
+            yield Present(self.current + 1)
+        end
+    end
+end
+"#;
+
+#[test]
+fn test_for_each_walks_an_iterable_form() {
+    let result = run(r#"
+        weave seen as []
+        for each n in Countdown { current: 3 } then
+            set seen to list_push(seen, n)
+        end
+        seen
+    "#);
+    assert_eq!(result, Ok(numbers(&[3.0, 2.0, 1.0])));
+}
+
+#[test]
+fn test_iter_builtins_accept_iterable_forms() {
+    let combined = run(r#"
+        chant double(n) then
+            yield n * 2
+        end
+        chant is_even(n) then
+            yield n % 2 is 0
+        end
+        iter_collect(iter_take(iter_map(iter_filter(Countdown { current: 10 }, is_even), double), 3))
+    "#);
+    assert_eq!(combined, Ok(numbers(&[20.0, 16.0, 12.0])));
+
+    // Each `iter_next` hands back the advanced iterator with the element
+    let stepped = run(r#"
+        bind one to iter_next(iter(Countdown { current: 2 }))
+        bind two to iter_next(list_first(one))
+        bind three to iter_next(list_first(two))
+        [list_last(one), list_last(two), list_last(three)]
+    "#);
+    let present = |n: f64| Value::Maybe { present: true, value: Some(Box::new(Value::Number(n))) };
+    let absent = Value::Maybe { present: false, value: None };
+    assert_eq!(stepped, Ok(Value::List(vec![present(2.0), present(1.0), absent])));
+
+    let folded = run(r#"
+        chant add(total, n) then
+            yield total + n
+        end
+        iter_fold(Countdown { current: 4 }, 100, add)
+    "#);
+    assert_eq!(folded, Ok(Value::Number(110.0)));
+}
+
+#[test]
+fn test_variant_can_be_iterable() {
+    let source = r#"
+        variant Stack then
+            Link(top: Number, rest: Stack),
+            Bottom
+        end
+
+        embody Iterable<Number> for Stack then
+            chant next(self) -> Maybe<Number> then
+                match self with
+                    when Link(top, rest) then
+                        set self to rest
+                        yield Present(top)
+                    when Bottom then
+                        yield Absent
+                end
+            end
+        end
+
+        weave total as 0
+        for each n in Link(1, Link(20, Link(300, Bottom))) then
+            set total to total + n
+        end
+        total
+    "#;
+    let result = Evaluator::new().eval(&parse(source));
+    assert_eq!(result, Ok(Value::Number(321.0)));
+}
+
+#[test]
+fn test_built_in_iterators_and_break_in_for_each() {
+    // `for each` also drives the built-in iterator states
+    let mapped = run(r#"
+        chant square(n) then
+            yield n * n
+        end
+        weave seen as []
+        for each n in iter_map(iter(range(1, 4)), square) then
+            set seen to list_push(seen, n)
+        end
+        seen
+    "#);
+    assert_eq!(mapped, Ok(numbers(&[1.0, 4.0, 9.0])));
+
+    // An endless iterator stops when the loop breaks
+    let stopped = run(r#"
+        form Naturals with current as Number end
+        embody Iterable<Number> for Naturals then
+            chant next(self) -> Maybe<Number> then
+                set self.current to self.current + 1
+                yield Present(self.current)
+            end
+        end
+        weave latest as 0
+        for each n in Naturals { current: 0 } then
+            should n greater than 5 then
+                break
+            end
+            set latest to n
+        end
+        latest
+    "#);
+    assert_eq!(stopped, Ok(Value::Number(5.0)));
+}
+
+#[test]
+fn test_iteration_errors() {
+    let plain = run(r#"
+        form Point with x as Number end
+        for each n in Point { x: 1 } then
+            n
+        end
+    "#);
+    assert_eq!(plain, Err(RuntimeError::NotIterable("Point".to_string())));
+
+    let source = r#"
+        form Broken with x as Number end
+        embody Iterable<Number> for Broken then
+            chant next(self) then
+                yield self.x
+            end
+        end
+        iter_collect(Broken { x: 1 })
+    "#;
+    let result = Evaluator::new().eval(&parse(source));
+    assert_eq!(result, Err(RuntimeError::TypeError { expected: "Maybe".to_string(), got: "Number".to_string() }));
+}
+
+#[test]
+fn test_embodiment_is_checked() {
+    let missing_next = parse(r#"
+        form Empty with x as Number end
+        embody Iterable<Number> for Empty then
+            chant peek(self) then
+                yield Absent
+            end
+        end
+    "#);
+    let errors = analyze(&missing_next).unwrap_err();
+    assert!(errors.iter().any(|error| format!("{:?}", error).contains("Missing method 'next'")), "{:?}", errors);
+
+    let missing_element_type = parse(r#"
+        form Empty with x as Number end
+        embody Iterable for Empty then
+            chant next(self) then
+                yield Absent
+            end
+        end
+    "#);
+    assert!(analyze(&missing_element_type).is_err());
+}
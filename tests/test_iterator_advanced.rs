@@ -1,5 +1,5 @@
-/// Tests for advanced iterator combinators (Phase 4)
-/// Tests skip, take_while, zip, chain, any, all, and find
+//! Tests for advanced iterator combinators (Phase 4)
+//! Tests skip, take_while, zip, chain, any, all, and find
 
 use glimmer_weave::{Evaluator, Lexer, Parser};
 
@@ -1,5 +1,5 @@
-/// Tests for basic iterator functionality (Phase 1)
-/// Verifies iterator creation and next() operation
+//! Tests for basic iterator functionality (Phase 1)
+//! Verifies iterator creation and next() operation
 
 use glimmer_weave::{Evaluator, Lexer, Parser};
 
@@ -1,5 +1,5 @@
-/// Tests for iterator transformation combinators (Phase 2)
-/// Tests map, filter, and fold using recursive helper functions
+//! Tests for iterator transformation combinators (Phase 2)
+//! Tests map, filter, and fold using recursive helper functions
 
 use glimmer_weave::{Evaluator, Lexer, Parser};
 
@@ -0,0 +1,80 @@
+//! Tests for `speaks` pragmas and language version gating
+
+use glimmer_weave::language_version::{Feature, LanguageVersion, CURRENT};
+use glimmer_weave::pipeline::Target;
+use glimmer_weave::{analyze, CompilerPipeline, Lexer, Parser, SemanticError};
+
+fn parse(source: &str) -> Result<Vec<glimmer_weave::AstNode>, String> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().map_err(|e| e.message)
+}
+
+#[test]
+fn test_pragma_sets_the_version() {
+    let tokens = Lexer::new("\nspeaks \"1.1\"\nbind x to 1\n").tokenize_positioned();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().expect("Parse error");
+    assert_eq!(parser.language_version(), LanguageVersion::new(1, 1));
+    assert_eq!(ast.len(), 2);
+
+    let mut parser = Parser::new(Lexer::new("bind x to 1").tokenize_positioned());
+    parser.parse().expect("Parse error");
+    assert_eq!(parser.language_version(), CURRENT);
+}
+
+#[test]
+fn test_pragma_errors() {
+    assert_eq!(
+        parse("speaks \"9.0\"\n").unwrap_err(),
+        "This script speaks language version 9.0, but only versions up to 1.4 are known"
+    );
+    assert!(parse("speaks \"one\"\n").unwrap_err().contains("is not a language version"));
+    assert_eq!(
+        parse("bind x to 1\nspeaks \"1.0\"\n").unwrap_err(),
+        "A `speaks` pragma must come before the script's first statement"
+    );
+    // `speaks` is not reserved
+    assert!(parse("bind speaks to 1\nspeaks + 1\n").is_ok());
+}
+
+#[test]
+fn test_parser_refuses_newer_statements() {
+    assert_eq!(
+        parse("speaks \"1.1\"\nswift chant one() then\n    yield 1\nend\n").unwrap_err(),
+        "language version 1.2 is needed for `swift` chants, but this script speaks 1.1"
+    );
+    assert!(parse("speaks \"1.0\"\nverify \"adds\" then\n    1 + 1\nend\n").unwrap_err().contains("`verify` blocks"));
+    assert!(parse("speaks \"1.0\"\nform P with\n    x as Number\n    deriving Display\nend\n").unwrap_err().contains("`deriving` clauses"));
+    assert!(parse("speaks \"1.1\"\nritual Door with\n    state Open\nend\n").unwrap_err().contains("rituals"));
+    assert!(parse("speaks \"1.2\"\nswift chant one() then\n    yield 1\nend\n").is_ok());
+}
+
+#[test]
+fn test_analyzer_refuses_newer_expressions() {
+    let ast = parse("speaks \"1.0\"\nbind delay to 10 ms\nbind big to 123n\ndefer\n    delay\nend\n").expect("Parse error");
+    let errors = analyze(&ast).unwrap_err();
+    let features: Vec<Feature> = errors
+        .iter()
+        .filter_map(|error| match error {
+            SemanticError::UnsupportedFeature { feature, span, .. } => {
+                assert!(span.is_known());
+                Some(*feature)
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(features, vec![Feature::Units, Feature::BigIntLiterals, Feature::Defer]);
+
+    let ast = parse("speaks \"1.1\"\ndefer\n    1\nend\n").expect("Parse error");
+    assert!(analyze(&ast).is_ok());
+}
+
+#[test]
+fn test_pipeline_reports_the_needed_version() {
+    let result = CompilerPipeline::new().run("speaks \"1.1\"\nbind big to 5n\nbig\n", Target::Eval);
+    let diagnostics = result.unwrap_err();
+    assert!(format!("{}", diagnostics).contains("language version 1.2 is needed for big integer literals, but this script speaks 1.1"));
+
+    let result = CompilerPipeline::new().run("speaks \"1.0\"\nbind x to 2\nx * 21\n", Target::Eval);
+    assert!(result.is_ok());
+}
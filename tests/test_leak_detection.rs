@@ -0,0 +1,94 @@
+//! Tests for leak detection: heap usage reports around each execution
+
+use glimmer_weave::bytecode_compiler::compile;
+use glimmer_weave::vm::VM;
+use glimmer_weave::{AstNode, Evaluator, Lexer, Parser, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+#[test]
+fn test_no_report_unless_enabled() {
+    let mut evaluator = Evaluator::new();
+    evaluator.eval(&parse("1 + 2")).unwrap();
+    assert!(evaluator.leak_report().is_none());
+}
+
+#[test]
+fn test_clean_execution() {
+    let mut evaluator = Evaluator::new();
+    evaluator.set_leak_detection(true);
+    let source = r#"
+        chant total(n) then
+            weave sum as 0
+            weave i as 0
+            whilst i is not n then
+                set sum to sum + i
+                set i to i + 1
+            end
+            yield sum
+        end
+    "#;
+    evaluator.eval(&parse(source)).unwrap();
+
+    // Calling the chant again and again leaves nothing behind
+    for _ in 0..3 {
+        assert_eq!(evaluator.eval(&parse("total(10)")).unwrap(), Value::Number(45.0));
+        let report = evaluator.leak_report().unwrap();
+        assert!(report.is_clean(), "{:?}", report);
+    }
+}
+
+#[test]
+fn test_retained_globals_are_reported() {
+    let mut evaluator = Evaluator::new();
+    evaluator.set_leak_detection(true);
+    evaluator.eval(&parse(r#"weave boot_log as ["boot", "mount", "ready"]"#)).unwrap();
+
+    let report = evaluator.leak_report().unwrap();
+    assert_eq!(report.new_globals(), ["boot_log"]);
+    assert!(report.outstanding_retained_bytes() > 0);
+    assert!(!report.is_clean());
+}
+
+#[test]
+fn test_unreleased_resources_are_reported() {
+    let mut evaluator = Evaluator::new();
+    let handle = evaluator.acquire_resource("file", 7);
+    evaluator.define_global("config", handle);
+    evaluator.set_leak_detection(true);
+
+    // A handle acquired before the execution is not its leak
+    evaluator.eval(&parse("weave unused as 1")).unwrap();
+    assert_eq!(evaluator.leak_report().unwrap().leaked_resources(), 0);
+
+    evaluator.eval(&parse("release(config)")).unwrap();
+    let report = evaluator.leak_report().unwrap();
+    assert_eq!(report.before.live_resources, 1);
+    assert_eq!(report.after.live_resources, 0);
+}
+
+#[test]
+fn test_vm_reports_new_globals() {
+    let mut vm = VM::new();
+    vm.set_leak_detection(true);
+    vm.execute(compile(&parse("weave cache as [1, 2, 3]")).unwrap()).unwrap();
+
+    let report = vm.leak_report().unwrap();
+    assert_eq!(report.new_globals(), ["cache"]);
+    assert!(report.outstanding_retained_bytes() > 0);
+
+    vm.set_leak_detection(false);
+    assert!(vm.leak_report().is_none());
+}
+
+#[cfg(all(target_arch = "x86_64", not(target_env = "msvc")))]
+#[test]
+fn test_native_heap_is_sampled() {
+    let mut evaluator = Evaluator::new();
+    evaluator.set_leak_detection(true);
+    evaluator.eval(&parse("1")).unwrap();
+    assert!(evaluator.leak_report().unwrap().after.native_bytes.is_some());
+}
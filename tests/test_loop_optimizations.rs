@@ -0,0 +1,123 @@
+//! Tests for loop-invariant code motion and strength reduction, comparing
+//! the bytecode and assembly of loops before and after optimization
+
+use glimmer_weave::bytecode::Instruction;
+use glimmer_weave::loop_opt::LoopOptimizer;
+use glimmer_weave::pipeline::{CompilerPipeline, Output, Target};
+use glimmer_weave::vm::VM;
+use glimmer_weave::{Evaluator, Lexer, Parser, Value};
+
+/// `width * height` is invariant, `i * 8` steps with the counter
+const SUM_LOOP: &str = r#"
+    bind width to 6
+    bind height to 7
+    weave total as 0
+    weave i as 0
+    whilst i less than 10 then
+        set total to total + width * height + i * 8
+        set i to i + 1
+    end
+    total
+"#;
+
+fn compile(source: &str, target: Target, optimize: bool) -> Output {
+    CompilerPipeline::new()
+        .optimize(optimize)
+        .run(source, target)
+        .unwrap_or_else(|diagnostics| panic!("{}", diagnostics))
+}
+
+/// Instructions from a loop's start to its backward jump
+fn loop_instructions(output: &Output) -> Vec<Instruction> {
+    let Output::Bytecode(chunk) = output else {
+        panic!("expected bytecode, got {:?}", output);
+    };
+    let code = &chunk.instructions;
+    let (jump, offset) = code
+        .iter()
+        .enumerate()
+        .find_map(|(index, instruction)| match instruction {
+            Instruction::Jump { offset } if *offset < 0 => Some((index, *offset)),
+            _ => None,
+        })
+        .expect("no loop in bytecode");
+    code[(jump as isize + 1 + offset as isize) as usize..=jump].to_vec()
+}
+
+fn multiplies(instructions: &[Instruction]) -> usize {
+    instructions.iter().filter(|instruction| matches!(instruction, Instruction::MulNum { .. })).count()
+}
+
+/// Lines of the first `whilst` loop in generated assembly
+fn asm_loop(output: &Output) -> String {
+    let Output::Asm(asm) = output else {
+        panic!("expected assembly, got {:?}", output);
+    };
+    let start = asm.find(".L_while_start_").unwrap();
+    let end = asm[start..].find("\n.L_while_end_").unwrap();
+    asm[start..start + end].to_string()
+}
+
+#[test]
+fn test_bytecode_loop_loses_multiplications() {
+    let before = loop_instructions(&compile(SUM_LOOP, Target::Bytecode, false));
+    let after = loop_instructions(&compile(SUM_LOOP, Target::Bytecode, true));
+    assert_eq!(multiplies(&before), 2);
+    assert_eq!(multiplies(&after), 0);
+}
+
+#[test]
+fn test_assembly_loop_loses_multiplications() {
+    let before = asm_loop(&compile(SUM_LOOP, Target::Asm, false));
+    let after = asm_loop(&compile(SUM_LOOP, Target::Asm, true));
+    assert_eq!(before.matches("imulq").count(), 2);
+    assert_eq!(after.matches("imulq").count(), 0);
+}
+
+#[test]
+fn test_optimized_loops_compute_the_same() {
+    let Output::Bytecode(chunk) = compile(SUM_LOOP, Target::Bytecode, true) else {
+        panic!("expected bytecode");
+    };
+    assert_eq!(VM::new().execute(chunk).unwrap(), Value::Number(780.0));
+
+    let programs = [
+        SUM_LOOP,
+        // Counting down, with a skipped iteration before the counter moves
+        r#"
+        bind k to 3
+        weave total as 0
+        weave i as 12
+        whilst i greater than 0 then
+            set i to i - 3
+            should i is 6 then
+                continue
+            end
+            set total to total + i * k + k * 2
+        end
+        total
+        "#,
+        // A loop whose bounds change as it runs
+        r#"
+        weave limit as 10
+        weave i as 0
+        weave steps as 0
+        whilst i less than limit - 2 then
+            set limit to limit - 1
+            set steps to steps + i * 2
+            set i to i + 1
+        end
+        steps
+        "#,
+    ];
+    for source in programs {
+        let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap();
+        let optimized = LoopOptimizer::new().optimize(&ast);
+        assert_eq!(
+            Evaluator::new().eval(&optimized).unwrap(),
+            Evaluator::new().eval(&ast).unwrap(),
+            "{}",
+            source
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Tests for `where` guards on match arms
+//!
+//! Covers parsing, the evaluator moving on to later arms when a guard is
+//! false, exhaustiveness, and the jumps the bytecode compiler emits.
+
+use glimmer_weave::bytecode::Instruction;
+use glimmer_weave::codegen::compile_to_asm;
+use glimmer_weave::language_version::Feature;
+use glimmer_weave::{analyze, bytecode_compiler, AstNode, Evaluator, Lexer, Parser, RuntimeError, SemanticError, Value};
+
+const SIZE: &str = "chant size(n) then\n    match n with\n        when 0 then\n            yield \"none\"\n        when m where m greater than 10 then\n            yield \"many\"\n        when m where m greater than 1 then\n            yield \"some\"\n        otherwise then\n            yield \"one\"\n    end\nend\n";
+
+fn parse(source: &str) -> Result<Vec<AstNode>, String> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().map_err(|e| e.message)
+}
+
+fn eval(source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval(&parse(source).expect("Parse error"))
+}
+
+#[test]
+fn test_false_guards_fall_through_to_later_arms() {
+    let size = |n: &str| eval(&format!("{}size({})", SIZE, n)).unwrap();
+    assert_eq!(size("0"), Value::Text("none".to_string()));
+    assert_eq!(size("12"), Value::Text("many".to_string()));
+    assert_eq!(size("5"), Value::Text("some".to_string()));
+    assert_eq!(size("1"), Value::Text("one".to_string()));
+
+    // Guards see the pattern's bindings, in statement position too
+    let source = "weave seen as \"\"\nmatch (3, 4) with\n    when (a, b) where a greater than b then\n        set seen to \"down\"\n    when (a, b) where a + b is 7 then\n        set seen to \"seven\"\nend\nseen";
+    assert_eq!(eval(source).unwrap(), Value::Text("seven".to_string()));
+
+    // A binding that a false guard turned away does not leak out
+    let source = "bind m to \"outer\"\nmatch 1 with\n    when m where m greater than 5 then\n        m\n    otherwise then\n        m\nend";
+    assert_eq!(eval(source).unwrap(), Value::Text("outer".to_string()));
+}
+
+#[test]
+fn test_guard_errors_propagate() {
+    let source = "match 1 with\n    when n where missing greater than n then\n        n\n    otherwise then\n        0\nend";
+    assert!(matches!(eval(source), Err(RuntimeError::UndefinedVariable(_))));
+}
+
+#[test]
+fn test_guarded_catch_all_is_not_exhaustive() {
+    let source = "bind x to 3\nmatch x with\n    when n where n greater than 1 then\n        n\nend";
+    assert!(analyze(&parse(source).unwrap())
+        .unwrap_err()
+        .iter()
+        .any(|error| matches!(error, SemanticError::NonExhaustiveMatch { .. })));
+    let source = "bind x to 3\nmatch x with\n    when n where n greater than 1 then\n        n\n    when n then\n        0\nend";
+    assert!(analyze(&parse(source).unwrap()).is_ok());
+}
+
+#[test]
+fn test_guards_need_language_version_1_4() {
+    let source = "speaks \"1.3\"\nmatch 1 with\n    when n where n greater than 0 then\n        n\n    otherwise then\n        0\nend";
+    let message = parse(source).unwrap_err();
+    assert!(message.contains(&format!("language version 1.4 is needed for {}", Feature::MatchGuards.describe())), "{}", message);
+}
+
+#[test]
+fn test_compilers_test_guards_before_the_body() {
+    let source = "bind limit to 10\nmatch 5 with\n    when 5 where limit less than 3 then\n        1\n    otherwise then\n        2\nend";
+    let chunk = bytecode_compiler::compile(&parse(source).unwrap()).unwrap();
+    // One conditional jump for the literal, one for its guard
+    let conditional = chunk.instructions.iter().filter(|instruction| matches!(instruction, Instruction::JumpIfFalse { .. })).count();
+    assert_eq!(conditional, 2);
+    assert!(chunk.instructions.iter().any(|instruction| matches!(instruction, Instruction::Lt { .. })));
+
+    let asm = compile_to_asm(&parse(&format!("{}size(12)", SIZE)).unwrap()).unwrap();
+    assert!(asm.contains("je .L_match_arm_"), "{}", asm);
+}
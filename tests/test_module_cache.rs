@@ -0,0 +1,145 @@
+//! Tests for the content-addressed module cache
+
+use glimmer_weave::bytecode::{BytecodeChunk, Instruction};
+use glimmer_weave::bytecode_compiler::compile_cached;
+use glimmer_weave::bytecode_image::encode;
+use glimmer_weave::module_cache::{content_hash, CacheStore, DiskCacheStore, MemoryCacheStore};
+use glimmer_weave::module_resolver::ModuleResolver;
+use glimmer_weave::verify::VerifyError;
+use glimmer_weave::vm::VM;
+use glimmer_weave::{Lexer, Parser, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("glimmer-weave-cache-{}-{}", name, std::process::id()))
+}
+
+const SCRIPT: &str = "weave x as 40\nx + 2";
+
+#[test]
+fn test_compile_cached_fills_and_reuses_the_store() {
+    let mut store = MemoryCacheStore::new();
+    let chunk = compile_cached(SCRIPT, &mut store).unwrap();
+    assert_eq!(store.len(), 2);
+    assert!(store.module(content_hash(SCRIPT)).is_some());
+
+    // A cached chunk is returned without compiling again
+    store.store_chunk(content_hash(SCRIPT), &BytecodeChunk::new("cached".to_string()));
+    assert_eq!(compile_cached(SCRIPT, &mut store).unwrap().name, "cached");
+    assert_eq!(chunk.name, "main");
+}
+
+#[test]
+fn test_resolver_skips_parsing_cached_modules() {
+    let source = "grove Greeting with\n    chant hello() then\n        yield \"hi\"\n    end\n    offer hello\nend";
+    let mut store = MemoryCacheStore::new();
+    // Stand-in parse result: proves the resolver used the cache
+    store.store_module(content_hash(source), &parse("grove Greeting with\n    offer cached\nend"));
+
+    let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+    resolver.set_cache(Box::new(store));
+    resolver.add_source("/project/greeting.gw", source);
+    let module = resolver.load_module("/project/greeting.gw").unwrap();
+    assert_eq!(module.exports, vec!["cached".to_string()]);
+}
+
+#[test]
+fn test_resolver_shares_its_store() {
+    let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+    resolver.set_cache(Box::new(MemoryCacheStore::new()));
+    resolver.add_source("/project/main.gw", SCRIPT);
+    resolver.load_module("/project/main.gw").unwrap();
+
+    let cache = resolver.cache_mut().unwrap();
+    assert!(cache.module(content_hash(SCRIPT)).is_some());
+    assert!(compile_cached(SCRIPT, cache).is_ok());
+    assert!(cache.chunk(content_hash(SCRIPT)).is_some());
+}
+
+#[test]
+fn test_disk_store_survives_a_restart() {
+    let dir = temp_dir("restart");
+    let mut first_boot = DiskCacheStore::new(&dir).unwrap();
+    compile_cached(SCRIPT, &mut first_boot).unwrap();
+    assert!(first_boot.chunk_path(content_hash(SCRIPT)).exists());
+
+    let mut second_boot = DiskCacheStore::new(&dir).unwrap();
+    let chunk = second_boot.chunk(content_hash(SCRIPT));
+    std::fs::remove_dir_all(&dir).ok();
+
+    let chunk = chunk.expect("Chunk should be read back from disk");
+    assert_eq!(VM::new().execute(chunk).unwrap(), Value::Number(42.0));
+}
+
+#[test]
+fn test_corrupt_image_is_a_miss() {
+    let dir = temp_dir("corrupt");
+    let mut store = DiskCacheStore::new(&dir).unwrap();
+    std::fs::write(store.chunk_path(content_hash(SCRIPT)), b"not an image").unwrap();
+
+    assert!(store.chunk(content_hash(SCRIPT)).is_none());
+    let chunk = compile_cached(SCRIPT, &mut store);
+    std::fs::remove_dir_all(&dir).ok();
+    assert_eq!(VM::new().execute(chunk.unwrap()).unwrap(), Value::Number(42.0));
+}
+
+/// Toy verifier: a signature is the byte sum of the image
+fn byte_sum_verifier(image: &[u8], signature: &[u8]) -> Result<(), VerifyError> {
+    if signature != byte_sum(image).as_bytes() {
+        return Err(VerifyError::InvalidSignature { reason: "digest mismatch".to_string() });
+    }
+    Ok(())
+}
+
+fn byte_sum(image: &[u8]) -> String {
+    image.iter().map(|&b| b as u64).sum::<u64>().to_string()
+}
+
+#[test]
+fn test_verified_disk_store_refuses_unsigned_images() {
+    let dir = temp_dir("verified");
+    let path = DiskCacheStore::new(&dir).unwrap().chunk_path(content_hash(SCRIPT));
+    let signature_path = format!("{}.sig", path.display());
+
+    // An image planted on disk without a signature is not loaded
+    let mut planted = BytecodeChunk::new("planted".to_string());
+    planted.emit(Instruction::Halt, 1);
+    let image = encode(&planted);
+    std::fs::write(&path, &image).unwrap();
+    let mut store = DiskCacheStore::new(&dir).unwrap();
+    store.set_verifier(Box::new(byte_sum_verifier));
+    let unsigned = store.chunk(content_hash(SCRIPT));
+
+    // Nor with a signature over different bytes
+    std::fs::write(&signature_path, byte_sum(b"something else")).unwrap();
+    let mut store = DiskCacheStore::new(&dir).unwrap();
+    store.set_verifier(Box::new(byte_sum_verifier));
+    let tampered = store.chunk(content_hash(SCRIPT));
+
+    // A signed image is
+    std::fs::write(&signature_path, byte_sum(&image)).unwrap();
+    let mut store = DiskCacheStore::new(&dir).unwrap();
+    store.set_verifier(Box::new(byte_sum_verifier));
+    let signed = store.chunk(content_hash(SCRIPT));
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert!(unsigned.is_none());
+    assert!(tampered.is_none());
+    assert_eq!(signed.map(|chunk| chunk.name), Some("planted".to_string()));
+}
+
+#[test]
+fn test_verified_disk_store_recompiles_unsigned_images() {
+    let dir = temp_dir("recompile");
+    compile_cached(SCRIPT, &mut DiskCacheStore::new(&dir).unwrap()).unwrap();
+
+    let mut store = DiskCacheStore::new(&dir).unwrap();
+    store.set_verifier(Box::new(byte_sum_verifier));
+    let chunk = compile_cached(SCRIPT, &mut store);
+    std::fs::remove_dir_all(&dir).ok();
+    assert_eq!(VM::new().execute(chunk.unwrap()).unwrap(), Value::Number(42.0));
+}
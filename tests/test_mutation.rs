@@ -0,0 +1,77 @@
+//! Tests for mutation testing of verify blocks
+
+use glimmer_weave::mutation::{mutants, MutationError, MutationKind, MutationTester};
+use glimmer_weave::ast::BinaryOperator;
+use glimmer_weave::{AstNode, Lexer, Parser};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    Parser::new(Lexer::new(source).tokenize_positioned()).parse().expect("parse failed")
+}
+
+const CLAMP: &str = r#"
+chant clamp(x, low, high) then
+    should x less than low then
+        yield low
+    end
+    should x greater than high then
+        yield high
+    end
+    x
+end
+"#;
+
+#[test]
+fn test_mutants_cover_operators_conditions_and_statements() {
+    let ast = parse(&format!("{}verify \"inside\" then\n    clamp(5, 0, 10) is 5\nend", CLAMP));
+    let kinds: Vec<MutationKind> = mutants(&ast).into_iter().map(|(mutant, _)| mutant.kind).collect();
+
+    assert_eq!(
+        kinds,
+        [
+            MutationKind::NegateCondition,
+            MutationKind::SwapOperator { from: BinaryOperator::Less, to: BinaryOperator::LessEq },
+            MutationKind::NegateCondition,
+            MutationKind::SwapOperator { from: BinaryOperator::Greater, to: BinaryOperator::GreaterEq },
+            MutationKind::DropStatement,
+        ]
+    );
+}
+
+const ABS: &str = "chant abs(x) then\n    should x less than 0 then\n        yield 0 - x\n    end\n    x\nend\n";
+
+#[test]
+fn test_weak_tests_leave_survivors() {
+    // Only a positive number is checked, so the negative branch goes untested
+    let weak = parse(&format!("{}verify \"positive\" then\n    abs(3) is 3\nend", ABS));
+    let report = MutationTester::new().run(&weak).unwrap();
+    let survivors: Vec<String> = report.survived.iter().map(ToString::to_string).collect();
+    assert_eq!(survivors, ["swap < for <= at line 2:14", "swap - for + at line 3:17"]);
+    assert_eq!(report.killed.len(), 2);
+
+    // Checking a negative number kills the swapped subtraction; `<=` only
+    // differs at 0, where both branches give the same answer
+    let strong = parse(&format!("{}verify \"both signs\" then\n    [abs(3), abs(-3)] is [3, 3]\nend", ABS));
+    let report = MutationTester::new().run(&strong).unwrap();
+    let survivors: Vec<String> = report.survived.iter().map(ToString::to_string).collect();
+    assert_eq!(survivors, ["swap < for <= at line 2:14"]);
+    assert_eq!(report.score(), 0.75);
+}
+
+#[test]
+fn test_hung_mutants_are_killed() {
+    let ast = parse("chant count(n) then\n    weave i as 0\n    whilst i less than n then\n        set i to i + 1\n    end\n    i\nend\nverify \"counts\" then\n    count(3) is 3\nend");
+    let report = MutationTester::new().step_budget(1_000).run(&ast).unwrap();
+    // Dropping `set i to i + 1` never ends
+    assert!(report.killed.iter().any(|mutant| mutant.kind == MutationKind::DropStatement));
+    assert!(report.score() > 0.5);
+}
+
+#[test]
+fn test_scripts_need_passing_verify_blocks() {
+    assert_eq!(MutationTester::new().run(&parse(CLAMP)), Err(MutationError::NoVerifyBlocks));
+    let failing = parse(&format!("{}verify \"wrong\" then\n    clamp(50, 0, 10) is 50\nend", CLAMP));
+    assert!(matches!(
+        MutationTester::new().run(&failing),
+        Err(MutationError::BaselineFails { block, .. }) if block == "wrong"
+    ));
+}
@@ -0,0 +1,128 @@
+//! Tests for named arguments at call sites
+//!
+//! Covers parsing, the evaluator and bytecode compiler placing arguments by
+//! parameter name, and the diagnostics for names that do not fit.
+
+use glimmer_weave::ast::ArgumentError;
+use glimmer_weave::codegen::compile_to_asm;
+use glimmer_weave::pipeline::{Output, Target};
+use glimmer_weave::{analyze, bytecode_compiler, AstNode, CompilerPipeline, Evaluator, Lexer, Parser, RuntimeError, SemanticError, Value};
+use glimmer_weave::bytecode::Instruction;
+
+const DRAW: &str = "chant draw(x, y, scale) then\n    yield (x - y) * scale\nend\n";
+
+fn parse(source: &str) -> Result<Vec<AstNode>, String> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().map_err(|e| e.message)
+}
+
+fn eval(source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval(&parse(source).expect("Parse error"))
+}
+
+/// Which written argument each parameter of the last call receives
+fn compiled_order(source: &str) -> Vec<u8> {
+    let chunk = bytecode_compiler::compile(&parse(source).expect("Parse error")).expect("Compile error");
+    let Some(call) = chunk.instructions.iter().rposition(|instruction| matches!(instruction, Instruction::Call { .. })) else {
+        panic!("expected a call");
+    };
+    let sources: Vec<u8> = chunk.instructions[..call]
+        .iter()
+        .rev()
+        .map_while(|instruction| match instruction {
+            Instruction::Move { src, .. } => Some(*src),
+            _ => None,
+        })
+        .collect();
+    let first = sources.iter().min().copied().unwrap_or(0);
+    sources.iter().rev().map(|src| src - first).collect()
+}
+
+#[test]
+fn test_named_arguments_are_placed_by_name() {
+    for call in ["draw(10, 4, 2)", "draw(scale: 2, y: 4, x: 10)", "draw(10, scale: 2, y: 4)"] {
+        let source = format!("{}{}", DRAW, call);
+        assert_eq!(eval(&source).unwrap(), Value::Number(12.0), "{}", call);
+    }
+    assert_eq!(compiled_order(&format!("{}draw(scale: 2, y: 4, x: 10)", DRAW)), vec![2, 1, 0]);
+    assert_eq!(compiled_order(&format!("{}draw(10, scale: 2, y: 4)", DRAW)), vec![0, 2, 1]);
+}
+
+#[test]
+fn test_arguments_run_in_the_order_written() {
+    let source = r#"
+        weave calls as []
+        chant note(value) then
+            set calls to List.push(calls, value)
+            yield value
+        end
+        chant pair(head, tail) then
+            yield [head, tail]
+        end
+        bind result to pair(tail: note(2), head: note(1))
+        [result, calls]
+    "#;
+    let numbers = |values: [f64; 2]| Value::List(values.iter().map(|n| Value::Number(*n)).collect());
+    assert_eq!(eval(source).unwrap(), Value::List(vec![numbers([1.0, 2.0]), numbers([2.0, 1.0])]));
+}
+
+#[test]
+fn test_parser_refuses_misplaced_names() {
+    assert_eq!(
+        parse("draw(x: 1, x: 2)").unwrap_err(),
+        "Duplicate named argument 'x'"
+    );
+    assert_eq!(
+        parse("draw(x: 1, 2)").unwrap_err(),
+        "Positional arguments must come before named arguments"
+    );
+    assert!(parse("speaks \"1.3\"\ndraw(x: 1)").unwrap_err().contains("language version 1.4 is needed for named arguments"));
+    assert!(parse("speaks \"1.3\"\ndraw(1, 2)").is_ok());
+}
+
+#[test]
+fn test_names_that_do_not_fit_are_errors() {
+    let cases = [
+        ("draw(1, 2, colour: 3)", ArgumentError::Unknown("colour".to_string())),
+        ("draw(1, 2, x: 3)", ArgumentError::Duplicate("x".to_string())),
+        ("draw(x: 1, scale: 2)", ArgumentError::Missing("y".to_string())),
+    ];
+    for (call, expected) in cases {
+        let source = format!("{}{}", DRAW, call);
+        match analyze(&parse(&source).unwrap()).unwrap_err().as_slice() {
+            [SemanticError::ArgumentMismatch { function, error, .. }] => {
+                assert_eq!(function, "draw");
+                assert_eq!(error, &expected);
+            }
+            other => panic!("expected an argument mismatch for {}, got {:?}", call, other),
+        }
+        match eval(&source) {
+            Err(RuntimeError::Custom(message)) => assert_eq!(message, expected.to_string()),
+            other => panic!("expected an error for {}, got {:?}", call, other),
+        }
+        assert!(bytecode_compiler::compile(&parse(&source).unwrap()).is_err(), "{}", call);
+    }
+}
+
+#[test]
+fn test_only_chants_take_names() {
+    match eval("to_text(value: 1)") {
+        Err(RuntimeError::Custom(message)) => assert!(message.contains("Named arguments need a chant"), "{}", message),
+        other => panic!("expected an error, got {:?}", other),
+    }
+    let source = "chant rest(head, ...others) then\n    yield others\nend\nrest(1, others: 2)";
+    assert!(matches!(eval(source), Err(RuntimeError::Custom(message)) if message.contains("Variadic parameter 'others'")));
+}
+
+#[test]
+fn test_backends_accept_named_arguments() {
+    let source = "chant sub(a, b) then\n    yield a - b\nend\nsub(b: 2, a: 10)";
+    let asm = compile_to_asm(&parse(source).unwrap()).unwrap();
+    assert!(asm.contains("call .L_func_sub"), "{}", asm);
+
+    match CompilerPipeline::new().run(source, Target::Eval) {
+        Ok(Output::Value(value)) => assert_eq!(value, Value::Number(8.0)),
+        other => panic!("expected a value, got {:?}", other),
+    }
+    assert!(matches!(CompilerPipeline::new().run(source, Target::Bytecode), Ok(Output::Bytecode(_))));
+}
@@ -0,0 +1,146 @@
+//! Tests for calling natively compiled chants from Rust through
+//! `CompiledModule`
+//!
+//! Objects are assembled with the system `as`; the tests pass without
+//! checking anything when it is not installed.
+
+#![cfg(all(target_arch = "x86_64", target_os = "linux"))]
+
+use std::process::Command;
+use std::sync::{Mutex, MutexGuard};
+use glimmer_weave::native_module::{CompiledModule, NativeArg};
+use glimmer_weave::{compile_to_asm, Lexer, Parser};
+
+/// Compiled chants allocate from the allocator's single, unsynchronized heap
+static HEAP_LOCK: Mutex<()> = Mutex::new(());
+
+fn heap_lock() -> MutexGuard<'static, ()> {
+    HEAP_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Compile and assemble a script, or `None` without an assembler
+fn object(source: &str, name: &str) -> Option<Vec<u8>> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().expect("parse failed");
+    let asm = compile_to_asm(&ast).expect("codegen failed");
+
+    let dir = std::env::temp_dir();
+    let asm_path = dir.join(format!("glimmer_native_module_{}_{}.s", std::process::id(), name));
+    let object_path = asm_path.with_extension("o");
+    std::fs::write(&asm_path, asm).unwrap();
+    let status = Command::new("as").arg("-o").arg(&object_path).arg(&asm_path).status().ok()?;
+    assert!(status.success(), "as rejected the generated assembly");
+    let bytes = std::fs::read(&object_path).unwrap();
+    let _ = std::fs::remove_file(asm_path);
+    let _ = std::fs::remove_file(object_path);
+    Some(bytes)
+}
+
+#[test]
+fn test_call_number_chants() {
+    let Some(object) = object(
+        r#"
+        chant area(w, h) then
+            w * h
+        end
+        chant fact(n) then
+            should n less than 2 then
+                yield 1
+            end
+            yield n * fact(n - 1)
+        end
+        chant sum7(a, b, c, d, e, f, g) then
+            a + b + c + d + e + f + g
+        end
+        "#,
+        "numbers",
+    ) else {
+        return;
+    };
+    let _heap = heap_lock();
+    let module = CompiledModule::load(&object).unwrap();
+
+    let area = module.get_fn("area").unwrap();
+    let result = unsafe { area.call(&[NativeArg::Number(6), NativeArg::Number(7)]) }.unwrap();
+    assert_eq!(result.as_number(), 42);
+
+    let fact = module.get_fn("fact").unwrap();
+    assert_eq!(unsafe { fact.call(&[NativeArg::Number(10)]) }.unwrap().as_number(), 3628800);
+
+    // The wrapper is a plain C function, including its stack arguments
+    let sum7: extern "C" fn(i64, i64, i64, i64, i64, i64, i64) -> i64 =
+        unsafe { std::mem::transmute(module.get_fn("sum7").unwrap().address()) };
+    assert_eq!(sum7(1, 2, 3, 4, 5, 6, 7), 28);
+    assert!(unsafe { module.get_fn("sum7").unwrap().call(&[NativeArg::Number(0); 7]) }.is_err());
+
+    assert!(module.get_fn("missing").is_none());
+}
+
+#[test]
+fn test_text_and_struct_values() {
+    let Some(object) = object(
+        r#"
+        form Point with
+            x as Number
+            y as Number
+        end
+        chant greeting() then
+            "hello"
+        end
+        chant echo(text) then
+            text
+        end
+        chant origin() then
+            Point { x: 3, y: 4 }
+        end
+        "#,
+        "values",
+    ) else {
+        return;
+    };
+    let _heap = heap_lock();
+    let module = CompiledModule::load(&object).unwrap();
+
+    let greeting = unsafe { module.get_fn("greeting").unwrap().call(&[]) }.unwrap();
+    assert_eq!(unsafe { greeting.as_text() }, "hello");
+
+    let echo = unsafe { module.get_fn("echo").unwrap().call(&[NativeArg::Text("round trip")]) }.unwrap();
+    assert_eq!(unsafe { echo.as_text() }, "round trip");
+
+    let origin = unsafe { module.get_fn("origin").unwrap().call(&[]) }.unwrap();
+    assert_eq!(unsafe { (origin.field(0).as_number(), origin.field(1).as_number()) }, (3, 4));
+
+    let fields = [5u64, 6];
+    let point = unsafe { module.get_fn("echo").unwrap().call(&[NativeArg::Struct(&fields)]) }.unwrap();
+    assert_eq!(unsafe { point.field(1).as_number() }, 6);
+}
+
+#[test]
+fn test_function_values_inside_module() {
+    let Some(object) = object(
+        r#"
+        chant double(x) then
+            x * 2
+        end
+        chant apply(f, x) then
+            f(x)
+        end
+        chant run(x) then
+            apply(double, x)
+        end
+        "#,
+        "closures",
+    ) else {
+        return;
+    };
+    let _heap = heap_lock();
+    let module = CompiledModule::load(&object).unwrap();
+    let run = module.get_fn("run").unwrap();
+    assert_eq!(unsafe { run.call(&[NativeArg::Number(21)]) }.unwrap().as_number(), 42);
+}
+
+#[test]
+fn test_rejects_non_objects() {
+    assert!(CompiledModule::load(b"not an object").is_err());
+    assert!(CompiledModule::load(&[]).is_err());
+}
@@ -0,0 +1,144 @@
+//! Tests for the `Net` module: sockets behind `Net.connect` capabilities and
+//! the network provider's allowlist
+
+use std::collections::BTreeMap;
+
+use glimmer_weave::net::NetProvider;
+use glimmer_weave::sync::{lock, shared, Shared};
+use glimmer_weave::taint::TaintPolicy;
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+/// Echo server at `echo.test:7`; records what was sent and what is open
+#[derive(Default)]
+struct Echo {
+    sent: Vec<String>,
+    open: BTreeMap<u64, Vec<u8>>,
+}
+
+struct EchoNet(Shared<Echo>);
+
+impl NetProvider for EchoNet {
+    fn allows(&self, host: &str, port: u16) -> bool {
+        (host, port) == ("echo.test", 7) || host == "down.test"
+    }
+
+    fn connect(&mut self, host: &str, _port: u16) -> Result<u64, String> {
+        if host == "down.test" {
+            return Err("connection refused".to_string());
+        }
+        let mut echo = lock(&self.0);
+        let handle = echo.open.len() as u64 + 1;
+        echo.open.insert(handle, Vec::new());
+        Ok(handle)
+    }
+
+    fn send(&mut self, handle: u64, bytes: &[u8]) -> Result<usize, String> {
+        let mut echo = lock(&self.0);
+        echo.sent.push(String::from_utf8_lossy(bytes).into_owned());
+        echo.open.get_mut(&handle).ok_or("closed")?.extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn recv(&mut self, handle: u64, max: usize) -> Result<Vec<u8>, String> {
+        let mut echo = lock(&self.0);
+        let pending = echo.open.get_mut(&handle).ok_or("closed")?;
+        let count = max.min(pending.len());
+        Ok(pending.drain(..count).collect())
+    }
+
+    fn close(&mut self, handle: u64) -> Result<(), String> {
+        lock(&self.0).open.remove(&handle).map(|_| ()).ok_or_else(|| "closed".to_string())
+    }
+}
+
+fn evaluator() -> (Evaluator, Shared<Echo>) {
+    let echo = shared(Echo::default());
+    let mut evaluator = Evaluator::new();
+    evaluator.set_net_provider(Box::new(EchoNet(Shared::clone(&echo))));
+    (evaluator, echo)
+}
+
+#[test]
+fn test_send_and_recv_over_granted_endpoint() {
+    let (mut evaluator, echo) = evaluator();
+    let source = r#"
+        chant ping(port) then
+            bind endpoint to request Net.connect("echo.test", port) with justification "ping"
+            bind socket to expect_triumph(Net.tcp_connect(endpoint), "connect")
+            Net.send(socket, "hello")
+            Net.recv(socket, 3)
+        end
+        ping(7)
+    "#;
+    let result = evaluator.eval(&parse(source)).unwrap();
+    assert_eq!(result, Value::Outcome { success: true, value: Box::new(Value::Text("hel".to_string())) });
+    assert_eq!(lock(&echo).sent, ["hello"]);
+
+    // The socket was closed when `ping` finished
+    assert!(lock(&echo).open.is_empty());
+    assert_eq!(evaluator.live_resources(), 0);
+}
+
+#[test]
+fn test_endpoint_off_the_allowlist_is_denied() {
+    let (mut evaluator, _) = evaluator();
+    let source = r#"request Net.connect("echo.test", 8) with justification "wrong port""#;
+    match evaluator.eval(&parse(source)) {
+        Err(RuntimeError::CapabilityDenied { capability, reason }) => {
+            assert_eq!(capability, "Net.connect");
+            assert!(reason.contains("echo.test:8"), "{}", reason);
+        }
+        other => panic!("expected a denial, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_connection_failure_is_a_mishap() {
+    let (mut evaluator, _) = evaluator();
+    let source = r#"
+        bind endpoint to request Net.connect("down.test", 80) with justification "try"
+        Net.tcp_connect(endpoint)
+    "#;
+    let result = evaluator.eval(&parse(source)).unwrap();
+    assert_eq!(result, Value::Outcome { success: false, value: Box::new(Value::Text("connection refused".to_string())) });
+}
+
+#[test]
+fn test_sockets_need_a_connect_token_and_close_once() {
+    let (mut evaluator, _) = evaluator();
+    let source = r#"
+        bind disk to request FS.read with justification "not a network token"
+        Net.tcp_connect(disk)
+    "#;
+    assert!(matches!(evaluator.eval(&parse(source)), Err(RuntimeError::TypeError { .. })));
+
+    let source = r#"
+        bind endpoint to request Net.connect("echo.test", 7) with justification "ping"
+        bind socket to expect_triumph(Net.tcp_connect(endpoint), "connect")
+        Net.close(socket)
+        Net.send(socket, "late")
+    "#;
+    assert!(matches!(evaluator.eval(&parse(source)), Err(RuntimeError::ResourceReleased { .. })));
+}
+
+#[test]
+fn test_received_data_is_tainted() {
+    let (mut evaluator, _) = evaluator();
+    evaluator.set_taint_policy(TaintPolicy::new().block());
+    let source = r#"
+        bind endpoint to request Net.connect("echo.test", 7) with justification "relay"
+        bind socket to expect_triumph(Net.tcp_connect(endpoint), "connect")
+        Net.send(socket, "hi")
+        bind reply to Net.recv(socket, 2)
+        Net.send(socket, expect_triumph(reply, "recv"))
+    "#;
+    match evaluator.eval(&parse(source)) {
+        Err(RuntimeError::CapabilityDenied { capability, .. }) => assert_eq!(capability, "Net.send"),
+        other => panic!("expected a denial, got {:?}", other),
+    }
+}
@@ -0,0 +1,137 @@
+//! Interpreter robustness: arbitrary source must produce errors, never
+//! panics
+//!
+//! A deterministic, stable-toolchain counterpart of the cargo-fuzz targets
+//! in `fuzz/`: programs are token soups and mutations of the examples, run
+//! through the lexer, parser, semantic analysis, evaluator, bytecode
+//! compiler and VM under a step budget.
+
+use std::panic;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use glimmer_weave::bytecode_compiler::compile;
+use glimmer_weave::vm::VM;
+use glimmer_weave::{analyze, Evaluator, Lexer, Parser};
+
+const VOCABULARY: &[&str] = &[
+    "bind", "weave", "set", "to", "as", "should", "then", "otherwise", "end", "chant", "yield", "for", "each", "in",
+    "whilst", "match", "with", "when", "attempt", "harmonize", "on", "request", "justification", "grove", "offer",
+    "form", "variant", "aspect", "embody", "defer", "break", "continue", "not", "and", "or", "is", "Present",
+    "Absent", "Triumph", "Mishap", "x", "y", "f", "n", "(", ")", "[", "]", "{", "}", ",", ":", ".", "+", "-", "*",
+    "/", "%", "<", ">", "<=", ">=", "|", "?", "=", "0", "1", "2", "7", "-1", "0.5", "\"\"", "\"é\"", "\"text\"",
+    "true", "false", "nothing", "\n", "\n", "\n", "slice", "pad_left", "pad_right", "char_at", "list_slice",
+    "range", "split", "join", "to_text", "to_number", "upper", "list_push", "list_length", "freeze", "thaw",
+];
+
+/// Small xorshift generator, so failures reproduce from the seed
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// Run `source` through every stage and both engines, budgeted so loops end
+fn exercise(source: &str) {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let Ok(ast) = Parser::new(tokens).parse() else {
+        return;
+    };
+    let _ = analyze(&ast);
+
+    let ticks = Arc::new(AtomicU64::new(0));
+    let clock = Arc::clone(&ticks);
+    let mut evaluator = Evaluator::new();
+    evaluator.set_clock(Box::new(move || {
+        clock.fetch_add(1, Ordering::Relaxed) + 1
+    }));
+    let _ = evaluator.eval_with_deadline(&ast, 2_000);
+
+    if let Ok(chunk) = compile(&ast) {
+        let ticks = Arc::new(AtomicU64::new(0));
+        let mut vm = VM::new();
+        vm.set_clock(Box::new(move || {
+            ticks.fetch_add(1, Ordering::Relaxed) + 1
+        }));
+        let _ = vm.execute_with_deadline(chunk, 2_000);
+    }
+}
+
+fn check(source: &str) {
+    if panic::catch_unwind(|| exercise(source)).is_err() {
+        panic!("panicked on source:\n{}", source);
+    }
+}
+
+#[test]
+fn test_token_soup_never_panics() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..2_000 {
+        let length = 1 + rng.below(40);
+        let words: Vec<&str> = (0..length).map(|_| VOCABULARY[rng.below(VOCABULARY.len())]).collect();
+        check(&words.join(" "));
+    }
+}
+
+#[test]
+fn test_mutated_examples_never_panic() {
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    let mut examples: Vec<String> = std::fs::read_dir("examples")
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "gw"))
+        .map(|entry| std::fs::read_to_string(entry.path()).unwrap())
+        .collect();
+    examples.sort();
+
+    for example in &examples {
+        let words: Vec<&str> = example.split_inclusive(char::is_whitespace).collect();
+        for _ in 0..30 {
+            let mut mutated = words.clone();
+            for _ in 0..1 + rng.below(3) {
+                if mutated.is_empty() {
+                    break;
+                }
+                let at = rng.below(mutated.len());
+                match rng.below(3) {
+                    0 => {
+                        mutated.remove(at);
+                    }
+                    1 => mutated.insert(at, VOCABULARY[rng.below(VOCABULARY.len())]),
+                    _ => mutated[at] = VOCABULARY[rng.below(VOCABULARY.len())],
+                }
+            }
+            check(&mutated.concat());
+        }
+    }
+}
+
+#[test]
+fn test_text_builtins_on_multibyte_text() {
+    for source in [
+        "slice(\"héllo\", 0, 2)",
+        "slice(\"héllo\", 2, 1)",
+        "pad_left(\"é\", 4, \"·\")",
+        "pad_right(\"é\", -3, \"ab\")",
+        "char_at(\"é\", 1)",
+    ] {
+        check(source);
+    }
+}
+
+#[test]
+fn test_attempt_compiles_to_bytecode() {
+    let source = "attempt\n    1\nharmonize on DivisionByZero then\n    2\nend";
+    let ast = Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap();
+    assert!(compile(&ast).is_ok());
+    check(source);
+}
@@ -0,0 +1,125 @@
+//! Tests for or-patterns in match arms
+//!
+//! Covers `when 1 or 2 or 3 then ...` in the evaluator, or-patterns inside
+//! tuples, how the analyzer treats them for exhaustiveness and bindings, and
+//! the bytecode and native backends, including cases of a variant as
+//! alternatives.
+
+use glimmer_weave::codegen::compile_to_asm;
+use glimmer_weave::vm::VM;
+use glimmer_weave::{analyze, bytecode_compiler, AstNode, Evaluator, Lexer, Parser, RuntimeError, SemanticError, Value};
+
+fn parse(source: &str) -> Result<Vec<AstNode>, String> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().map_err(|e| e.message)
+}
+
+fn eval(source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval(&parse(source).expect("Parse error"))
+}
+
+fn size(subject: &str) -> String {
+    format!(
+        "match {} with\n    when 1 or 2 or 3 then \"small\"\n    when 4 or 5 then \"medium\"\n    otherwise then \"large\"\nend",
+        subject
+    )
+}
+
+#[test]
+fn test_any_alternative_matches() {
+    for (subject, expected) in [("1", "small"), ("3", "small"), ("5", "medium"), ("6", "large")] {
+        assert_eq!(eval(&size(subject)).unwrap(), Value::Text(expected.to_string()), "{}", subject);
+    }
+    let source = "match \"no\" with\n    when \"yes\" or \"y\" then true\n    when \"no\" or \"n\" then false\n    otherwise then nothing\nend";
+    assert_eq!(eval(source).unwrap(), Value::Truth(false));
+}
+
+#[test]
+fn test_or_patterns_inside_tuples() {
+    let source = |subject: &str| {
+        format!(
+            "match {} with\n    when (0 or 1, y) then y\n    when (x, 0) or (0, x) then x * 10\n    otherwise then -1\nend",
+            subject
+        )
+    };
+    assert_eq!(eval(&source("(1, 7)")).unwrap(), Value::Number(7.0));
+    assert_eq!(eval(&source("(3, 0)")).unwrap(), Value::Number(30.0));
+    assert_eq!(eval(&source("(2, 2)")).unwrap(), Value::Number(-1.0));
+}
+
+#[test]
+fn test_analyzer_checks_or_patterns() {
+    let errors = |source: &str| analyze(&parse(source).unwrap()).err().unwrap_or_default();
+    assert!(errors(&size("2")).is_empty());
+
+    // A catch-all among the alternatives covers every value
+    assert!(errors("match 2 with\n    when 1 or _ then 0\nend").is_empty());
+    assert!(matches!(
+        errors("match 2 with\n    when 1 or 2 then 0\nend").as_slice(),
+        [SemanticError::NonExhaustiveMatch { .. }]
+    ));
+
+    assert!(errors("match (1, 0) with\n    when (x, 0) or (0, x) then x\n    otherwise then 0\nend").is_empty());
+    match errors("match (1, 0) with\n    when (x, 0) or (0, y) then 1\n    otherwise then 0\nend").as_slice() {
+        [SemanticError::Custom(message)] => assert!(message.contains("must bind the same names"), "{}", message),
+        other => panic!("expected a binding mismatch, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_analyzer_treats_unit_variants_as_cases() {
+    let errors = |arms: &str| {
+        let source = format!("variant Color then Red, Green, Blue end\nchant paint(color) then\nmatch color with\n{}end\nend", arms);
+        analyze(&parse(&source).unwrap()).err().unwrap_or_default()
+    };
+
+    // Naming a case binds nothing, so the alternatives agree
+    assert!(errors("    when Red or Green then 1\n    otherwise then 0\n").is_empty());
+
+    // A bare case name only matches that case, so it covers nothing else
+    assert!(matches!(errors("    when Red then 1\n").as_slice(), [SemanticError::NonExhaustiveMatch { .. }]));
+    assert!(matches!(errors("    when Green or Blue then 1\n").as_slice(), [SemanticError::NonExhaustiveMatch { .. }]));
+    assert!(errors("    when Red then 1\n    when other then 0\n").is_empty());
+}
+
+#[test]
+fn test_or_patterns_need_language_version_1_4() {
+    assert!(parse(&format!("speaks \"1.3\"\n{}", size("1"))).unwrap_err().contains("language version 1.4 is needed for `or` patterns"));
+    assert!(parse("speaks \"1.3\"\nmatch 1 with\n    when 1 then 0\n    otherwise then 1\nend").is_ok());
+}
+
+#[test]
+fn test_vm_runs_or_patterns() {
+    // A match yields no value in the VM, so each arm records which one ran
+    let run = |source: &str| VM::new().execute(bytecode_compiler::compile(&parse(source).unwrap()).unwrap()).unwrap();
+    let sizes = |subject: &str| {
+        format!(
+            "weave kind as 0\nmatch {} with\n    when 1 or 2 or 3 then set kind to 1\n    when 4 or 5 then set kind to 2\n    otherwise then set kind to 3\nend\nkind",
+            subject
+        )
+    };
+    for (subject, expected) in [("1", 1.0), ("3", 1.0), ("4", 2.0), ("5", 2.0), ("6", 3.0)] {
+        assert_eq!(run(&sizes(subject)), Value::Number(expected), "{}", subject);
+    }
+
+    let colors = |subject: &str| {
+        format!(
+            "variant Color then Red, Green, Blue end\nweave kind as 0\nmatch {} with\n    when Red or Green then set kind to 1\n    when Blue then set kind to 2\n    otherwise then set kind to 3\nend\nkind",
+            subject
+        )
+    };
+    for (subject, expected) in [("Red", 1.0), ("Green", 1.0), ("Blue", 2.0)] {
+        assert_eq!(run(&colors(subject)), Value::Number(expected), "{}", subject);
+        assert_eq!(eval(&colors(subject)).unwrap(), Value::Number(expected), "{}", subject);
+    }
+    assert_eq!(run(&colors("7")), Value::Number(3.0));
+
+    let tuple = "variant Color then Red, Green, Blue end\nbind pair to (Blue, 2)\nweave kind as 0\nmatch pair with\n    when (Red or Blue, 2) then set kind to 1\n    otherwise then set kind to 2\nend\nkind";
+    assert_eq!(run(tuple), Value::Number(1.0));
+}
+
+#[test]
+fn test_native_backend_compiles_literal_alternatives() {
+    let asm = compile_to_asm(&parse("match 2 with\n    when 1 or 2 then 10\n    otherwise then 20\nend").unwrap()).unwrap();
+    assert_eq!(asm.matches("    je .L_match_arm_").count(), 2, "{}", asm);
+}
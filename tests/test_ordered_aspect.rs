@@ -0,0 +1,106 @@
+//! Tests for the Ordered aspect: comparison operators and list_sort on
+//! forms and variants
+
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn run(source: &str) -> Result<Value, RuntimeError> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().expect("parse failed");
+    Evaluator::new().eval(&ast)
+}
+
+fn numbers(values: &[f64]) -> Value {
+    Value::List(values.iter().map(|n| Value::Number(*n)).collect())
+}
+
+const TASK: &str = r#"
+    form Task with
+        priority as Number
+        name as Text
+    end
+"#;
+
+#[test]
+fn test_list_sort_natural_values() {
+    assert_eq!(run("list_sort([3, 1, 2])").unwrap(), numbers(&[1.0, 2.0, 3.0]));
+    assert_eq!(
+        run(r#"List.sort(["pear", "apple"])"#).unwrap(),
+        Value::List(vec![Value::Text("apple".to_string()), Value::Text("pear".to_string())])
+    );
+    assert!(matches!(run(r#"list_sort([1, "one"])"#), Err(RuntimeError::TypeError { .. })));
+}
+
+#[test]
+fn test_custom_compare() {
+    let source = format!(
+        r#"{}
+        embody Ordered for Task then
+            chant compare(self, other) -> Number then
+                yield other.priority - self.priority
+            end
+        end
+
+        bind tasks to list_sort([
+            Task {{ priority: 1, name: "idle" }},
+            Task {{ priority: 9, name: "irq" }},
+            Task {{ priority: 5, name: "io" }}
+        ])
+        weave names as []
+        for each task in tasks then
+            set names to list_push(names, task.name)
+        end
+        names
+        "#,
+        TASK
+    );
+    let expected = ["irq", "io", "idle"].iter().map(|n| Value::Text(n.to_string())).collect();
+    assert_eq!(run(&source).unwrap(), Value::List(expected));
+}
+
+#[test]
+fn test_derived_order_follows_field_declaration() {
+    let source = format!(
+        r#"{}
+        embody Ordered for Task then
+        end
+
+        bind low to Task {{ priority: 1, name: "zeta" }}
+        bind high to Task {{ priority: 2, name: "alpha" }}
+        [low less than high, high greater than low, low less than low]
+        "#,
+        TASK
+    );
+    assert_eq!(
+        run(&source).unwrap(),
+        Value::List(vec![Value::Truth(true), Value::Truth(true), Value::Truth(false)])
+    );
+}
+
+#[test]
+fn test_derived_order_of_variants() {
+    let source = r#"
+        variant Level then
+            Low,
+            High(boost: Number)
+        end
+        embody Ordered for Level then
+        end
+
+        [Low less than High(1), High(1) less than High(2), High(2) less than Low]
+    "#;
+    assert_eq!(
+        run(source).unwrap(),
+        Value::List(vec![Value::Truth(true), Value::Truth(true), Value::Truth(false)])
+    );
+}
+
+#[test]
+fn test_unordered_forms_cannot_be_compared() {
+    let source = format!(
+        r#"{}
+        Task {{ priority: 1, name: "a" }} less than Task {{ priority: 2, name: "b" }}
+        "#,
+        TASK
+    );
+    assert!(matches!(run(&source), Err(RuntimeError::Custom(message)) if message.contains("Ordered")));
+}
@@ -1,7 +1,7 @@
-/// Tests for Outcome<T, E> and Maybe<T> helper functions with natural language branding
-///
-/// These tests verify the builtin helper functions for working with
-/// Outcome and Maybe types in a natural, readable way.
+//! Tests for Outcome<T, E> and Maybe<T> helper functions with natural language branding
+//!
+//! These tests verify the builtin helper functions for working with
+//! Outcome and Maybe types in a natural, readable way.
 
 use glimmer_weave::{Lexer, Parser, Evaluator};
 use glimmer_weave::eval::Value;
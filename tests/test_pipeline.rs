@@ -1,5 +1,5 @@
-/// Comprehensive tests for the Pipeline operator
-/// Tests the `|` operator for threading values through function calls
+//! Comprehensive tests for the Pipeline operator
+//! Tests the `|` operator for threading values through function calls
 
 use glimmer_weave::{Evaluator, Lexer, Parser};
 use glimmer_weave::eval::{RuntimeError, Value};
@@ -0,0 +1,127 @@
+//! Tests for the configurable script prelude
+//!
+//! The prelude decides which builtins are in scope without an import, for
+//! the interpreter, the semantic analyzer, and every module in a project.
+
+use glimmer_weave::pipeline::{Output, Target};
+use glimmer_weave::{
+    CompilerPipeline, Evaluator, Lexer, ModuleResolver, Parser, Prelude, RuntimeError, SemanticAnalyzer,
+    SemanticError, Value,
+};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+fn eval_with(prelude: &Prelude, source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::with_prelude(prelude).eval(&parse(source))
+}
+
+#[test]
+fn test_default_prelude_keeps_every_builtin() {
+    assert_eq!(eval_with(&Prelude::default(), "upper(\"glow\")"), Ok(Value::Text("GLOW".to_string())));
+    assert_eq!(
+        eval_with(&Prelude::default(), "triumph_or(Mishap(\"no\"), 7)"),
+        Ok(Value::Number(7.0))
+    );
+}
+
+#[test]
+fn test_core_prelude_has_outcome_and_iterator_helpers() {
+    let source = r#"
+        bind step to iter_next(iter([5, 6]))
+        present_or(Present(triumph_or(Triumph(2), 0)), 0)
+    "#;
+    assert_eq!(eval_with(&Prelude::core(), source), Ok(Value::Number(2.0)));
+}
+
+#[test]
+fn test_core_prelude_leaves_out_the_flat_library() {
+    assert_eq!(
+        eval_with(&Prelude::core(), "upper(\"glow\")"),
+        Err(RuntimeError::UndefinedVariable("upper".to_string()))
+    );
+    assert_eq!(
+        eval_with(&Prelude::core().with_builtin("upper"), "upper(\"glow\")"),
+        Ok(Value::Text("GLOW".to_string()))
+    );
+}
+
+#[test]
+fn test_empty_prelude_for_kernel_scripts() {
+    assert_eq!(
+        eval_with(&Prelude::none(), "println(\"hi\")"),
+        Err(RuntimeError::UndefinedVariable("println".to_string()))
+    );
+    assert_eq!(eval_with(&Prelude::none(), "bind x to 40\nx + 2"), Ok(Value::Number(42.0)));
+}
+
+#[test]
+fn test_module_scope_sees_the_prelude() {
+    let module = r#"
+        grove Format with
+            chant shout(text) then
+                yield upper(text)
+            end
+            offer shout
+        end
+    "#;
+    let source = "gather shout from Format\nshout(\"hi\")";
+    let run = |prelude: Prelude| {
+        let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+        resolver.add_source("/project/Format.gw", module);
+        let mut evaluator = Evaluator::with_prelude(&prelude);
+        evaluator.set_module_resolver(resolver);
+        evaluator.eval(&parse(source))
+    };
+
+    assert_eq!(run(Prelude::default()), Ok(Value::Text("HI".to_string())));
+    assert_eq!(run(Prelude::core()), Err(RuntimeError::UndefinedVariable("upper".to_string())));
+}
+
+#[test]
+fn test_semantic_analysis_follows_the_prelude() {
+    let ast = parse("println(\"hi\")");
+    assert!(SemanticAnalyzer::new().analyze(&ast).is_ok());
+    assert_eq!(
+        SemanticAnalyzer::with_prelude(&Prelude::none()).analyze(&ast),
+        Err(vec![SemanticError::UndefinedVariable("println".to_string())])
+    );
+
+    let ast = parse("sqrt(16)");
+    assert!(SemanticAnalyzer::with_prelude(&Prelude::core()).analyze(&ast).is_err());
+}
+
+#[test]
+fn test_pipeline_prelude() {
+    let mut pipeline = CompilerPipeline::new().prelude(Prelude::core());
+    let diagnostics = pipeline.run("upper(\"glow\")", Target::Eval).unwrap_err();
+    assert!(diagnostics.to_string().contains("UndefinedVariable"), "{}", diagnostics);
+
+    match pipeline.run("is_triumph(Triumph(1))", Target::Eval) {
+        Ok(Output::Value(value)) => assert_eq!(value, Value::Truth(true)),
+        other => panic!("Expected a value, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_resolver_prelude_applies_to_every_module() {
+    let mut resolver = ModuleResolver::new("/project".to_string(), "/std".to_string());
+    resolver.add_source(
+        "/project/greet.gw",
+        "grove Greet with\n    chant hello() then\n        yield upper(\"hi\")\n    end\n    offer hello\nend",
+    );
+    resolver.add_source("/project/main.gw", "gather hello from \"greet.gw\"\nhello()");
+
+    match CompilerPipeline::new().compile_project("/project/main.gw", &mut resolver, Target::Eval) {
+        Ok(Output::Value(value)) => assert_eq!(value, Value::Text("HI".to_string())),
+        other => panic!("Expected a value, got {:?}", other),
+    }
+
+    resolver.set_prelude(Prelude::core());
+    let diagnostics = CompilerPipeline::new()
+        .compile_project("/project/main.gw", &mut resolver, Target::Eval)
+        .unwrap_err();
+    assert!(diagnostics.to_string().contains("UndefinedVariable(\"upper\")"), "{}", diagnostics);
+}
@@ -0,0 +1,96 @@
+//! Tests for profile-guided optimization: profiling a run in the
+//! interpreter, saving the `.profile` file and building native code with it
+
+use glimmer_weave::pipeline::{CompilerPipeline, Output, Target};
+use glimmer_weave::profile::{BranchCounts, Profile, EXTENSION};
+use glimmer_weave::source_location::{SourceLocation, SourceSpan};
+use glimmer_weave::{Evaluator, Lexer, Parser};
+
+/// `mix` is too large to inline unprofiled; the `should` mostly takes
+/// its `otherwise` branch
+const WORKLOAD: &str = r#"chant mix(a, b) then
+    a * 3 + b * 5 - a * b + 7 - b
+end
+
+weave total as 0
+weave i as 0
+whilst i less than 150 then
+    should i % 10 is 0 then
+        set total to total + 1000
+    otherwise
+        set total to total + mix(i, 2)
+    end
+    set i to i + 1
+end
+total
+"#;
+
+fn train(source: &str) -> Profile {
+    let mut pipeline = CompilerPipeline::new();
+    pipeline.evaluator_mut().enable_profiling();
+    pipeline.run(source, Target::Eval).unwrap_or_else(|diagnostics| panic!("{}", diagnostics));
+    pipeline.evaluator_mut().take_profile().expect("profiling was enabled")
+}
+
+fn asm(mut pipeline: CompilerPipeline) -> String {
+    match pipeline.run(WORKLOAD, Target::Asm) {
+        Ok(Output::Asm(asm)) => asm,
+        other => panic!("expected assembly, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_interpreter_counts_calls_and_branches() {
+    let profile = train(WORKLOAD);
+    assert_eq!(profile.calls("mix"), 135);
+    let should = SourceSpan::point(SourceLocation::new(8, 5));
+    assert_eq!(profile.branch(&should), Some(BranchCounts { taken: 15, not_taken: 135 }));
+
+    // Each round of a tail-recursive loop counts as a call
+    let countdown = train("chant down(n) then\n    should n is 0 then\n        0\n    otherwise\n        down(n - 1)\n    end\nend\ndown(4)");
+    assert_eq!(countdown.calls("down"), 5);
+
+    // Nothing is counted unless profiling is on
+    let ast = Parser::new(Lexer::new(WORKLOAD).tokenize_positioned()).parse().unwrap();
+    let mut evaluator = Evaluator::new();
+    evaluator.eval(&ast).unwrap();
+    assert!(evaluator.profile().is_none());
+}
+
+#[test]
+fn test_saved_profile_guides_the_next_build() {
+    let dir = std::env::temp_dir().join(format!("glimmer_profile_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("workload.{}", EXTENSION));
+    std::fs::write(&path, train(WORKLOAD).to_text()).unwrap();
+    let profile = Profile::parse(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    std::fs::remove_dir_all(&dir).ok();
+
+    let plain = asm(CompilerPipeline::new().optimize(true));
+    let guided = asm(CompilerPipeline::new().optimize(true).profile(profile.clone()));
+
+    // The hot chant is expanded into the loop
+    assert!(plain.contains("call .L_func_mix"));
+    assert!(!guided.contains("call .L_func_mix"));
+
+    // The frequent `otherwise` branch falls through
+    assert!(!plain.contains(".L_then_"));
+    assert!(guided.contains("jne .L_then_"));
+
+    // Below the hot threshold the profile changes only the branch layout
+    let cautious = asm(CompilerPipeline::new().optimize(true).profile(profile.clone()).hot_call_threshold(1000));
+    assert!(cautious.contains("call .L_func_mix"));
+    assert!(cautious.contains("jne .L_then_"));
+
+    // Profiles only apply to optimized builds
+    assert_eq!(asm(CompilerPipeline::new().profile(profile)), asm(CompilerPipeline::new()));
+}
+
+#[test]
+fn test_profiles_from_several_runs_merge() {
+    let mut profile = train(WORKLOAD);
+    profile.merge(&train(WORKLOAD));
+    assert_eq!(profile.calls("mix"), 270);
+    assert_eq!(profile.hot_chants(200), [("mix", 270)]);
+    assert!(profile.hot_chants(300).is_empty());
+}
@@ -0,0 +1,132 @@
+//! Tests for script arguments and `exit`, in the interpreter and in
+//! compiled programs
+//!
+//! Compiled programs are linked with the system `cc`; those tests pass
+//! without checking anything when it is not installed.
+
+use glimmer_weave::{compile_to_asm, AstNode, Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn text(s: &str) -> Value {
+    Value::Text(s.to_string())
+}
+
+#[test]
+fn test_arguments_are_bound() {
+    let source = r#"
+        weave names as ""
+        for each argument in arguments then
+            set names to names + argument + ";"
+        end
+        [list_length(arguments), names]
+    "#;
+    let mut evaluator = Evaluator::new();
+    let result = evaluator.eval_with_args(&parse(source), vec![text("in.txt"), text("-v")]);
+    assert_eq!(result, Ok(Value::List(vec![Value::Number(2.0), text("in.txt;-v;")])));
+    assert_eq!(evaluator.exit_status(), None);
+}
+
+#[test]
+fn test_exit_records_status_after_cleanup() {
+    let source = r#"
+        weave cleaned as false
+        chant check(n) then
+            should n greater than 3 then
+                exit(2)
+            end
+            n
+        end
+        defer
+            set cleaned to true
+        end
+        for each n in range(0, 10) then
+            attempt
+                check(n)
+            harmonize on Exit then
+                "caught"
+            end
+        end
+        "unreachable"
+    "#;
+    let mut evaluator = Evaluator::new();
+    assert_eq!(evaluator.eval(&parse(source)), Ok(Value::Nothing));
+    assert_eq!(evaluator.exit_status(), Some(2));
+    assert_eq!(evaluator.eval(&parse("cleaned")), Ok(Value::Truth(true)));
+    // The next evaluation starts over
+    assert_eq!(evaluator.exit_status(), None);
+}
+
+#[test]
+fn test_exit_status_must_be_whole() {
+    for source in ["exit(1.5)", "exit(\"1\")", "exit(5000000000)"] {
+        let result = Evaluator::new().eval(&parse(source));
+        assert!(matches!(result, Err(RuntimeError::TypeError { .. })), "{}: {:?}", source, result);
+    }
+}
+
+/// Compile, link and run a program, returning its exit status, or `None`
+/// without a C compiler
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+fn run_native(source: &str, name: &str, args: &[&str]) -> Option<i32> {
+    let asm = compile_to_asm(&parse(source)).expect("codegen failed");
+    let dir = std::env::temp_dir();
+    let asm_path = dir.join(format!("glimmer_program_{}_{}.s", std::process::id(), name));
+    let exe_path = asm_path.with_extension("bin");
+    std::fs::write(&asm_path, asm).unwrap();
+    let allocator = concat!(env!("CARGO_MANIFEST_DIR"), "/src/native_allocator.S");
+    let linked = std::process::Command::new("cc")
+        .arg("-no-pie")
+        .arg("-Wl,-z,noexecstack")
+        .arg("-o")
+        .arg(&exe_path)
+        .arg(&asm_path)
+        .arg(allocator)
+        .status()
+        .ok()?;
+    assert!(linked.success(), "cc rejected the generated program");
+    let status = std::process::Command::new(&exe_path).args(args).status().unwrap();
+    let _ = std::fs::remove_file(asm_path);
+    let _ = std::fs::remove_file(exe_path);
+    Some(status.code().expect("program was killed"))
+}
+
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+#[test]
+fn test_compiled_program_status() {
+    let Some(status) = run_native("bind answer to 42\nanswer + 1", "finishes", &[]) else {
+        return;
+    };
+    assert_eq!(status, 0);
+
+    let nested = r#"
+        chant check(n) then
+            should n greater than 3 then
+                exit(n * 10)
+            end
+            check(n + 1)
+            n
+        end
+        check(0)
+        exit(1)
+    "#;
+    assert_eq!(run_native(nested, "nested", &[]), Some(40));
+}
+
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+#[test]
+fn test_compiled_program_arguments() {
+    let source = r#"
+        bind given to arguments
+        exit(7)
+    "#;
+    assert!(compile_to_asm(&parse(source)).unwrap().contains("movslq %edi, %rax"));
+    for args in [&[][..], &["one"][..], &["one", "two", "three"][..]] {
+        if let Some(status) = run_native(source, &format!("args{}", args.len()), args) {
+            assert_eq!(status, 7);
+        }
+    }
+}
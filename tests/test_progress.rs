@@ -0,0 +1,91 @@
+//! Tests for `with_progress` and progress updates reported to the host
+
+use glimmer_weave::cancellation::CancellationToken;
+use glimmer_weave::progress::{ProgressEvent, TaskEnd};
+use glimmer_weave::sync::{lock, shared, Shared};
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+fn evaluator() -> (Evaluator, Shared<Vec<ProgressEvent>>) {
+    let events = shared(Vec::new());
+    let sink = Shared::clone(&events);
+    let mut evaluator = Evaluator::new();
+    evaluator.set_progress_sink(Box::new(move |event: &ProgressEvent| lock(&sink).push(event.clone())));
+    (evaluator, events)
+}
+
+#[test]
+fn test_updates_reach_the_sink() {
+    let (mut evaluator, events) = evaluator();
+    let source = r#"
+        chant copy_files(progress) then
+            for each i in range(1, 3) then
+                Progress.update(progress, i * 2, "copied file " + to_text(i))
+            end
+            Progress.update(progress, 99)
+            "done"
+        end
+        with_progress(4, copy_files)
+    "#;
+    assert_eq!(evaluator.eval(&parse(source)).unwrap(), Value::Text("done".to_string()));
+    assert_eq!(*lock(&events), [
+        ProgressEvent::Started { task: 1, total: 4.0 },
+        ProgressEvent::Updated { task: 1, done: 2.0, total: 4.0, message: Some("copied file 1".to_string()) },
+        ProgressEvent::Updated { task: 1, done: 4.0, total: 4.0, message: Some("copied file 2".to_string()) },
+        ProgressEvent::Updated { task: 1, done: 4.0, total: 4.0, message: None },
+        ProgressEvent::Finished { task: 1, end: TaskEnd::Completed },
+    ]);
+    assert_eq!(evaluator.live_resources(), 0);
+}
+
+#[test]
+fn test_cancellation_ends_the_task() {
+    let token = CancellationToken::new();
+    let host = token.clone();
+    let events = shared(Vec::new());
+    let sink = Shared::clone(&events);
+    let mut evaluator = Evaluator::new();
+    evaluator.set_cancellation_token(token);
+    // The shell cancels once the script reports half the work done
+    evaluator.set_progress_sink(Box::new(move |event: &ProgressEvent| {
+        if let ProgressEvent::Updated { done, total, .. } = event {
+            if done * 2.0 >= *total {
+                host.cancel();
+            }
+        }
+        lock(&sink).push(event.clone());
+    }));
+    let source = r#"
+        chant count_forever(progress) then
+            weave done as 0
+            whilst true then
+                set done to done + 1
+                Progress.update(progress, done)
+            end
+        end
+        with_progress(10, count_forever)
+    "#;
+    assert!(matches!(evaluator.eval(&parse(source)), Err(RuntimeError::Cancelled)));
+    let events = lock(&events);
+    assert_eq!(events.len(), 7);
+    assert_eq!(events[6], ProgressEvent::Finished { task: 1, end: TaskEnd::Cancelled });
+}
+
+#[test]
+fn test_failures_and_stale_handles() {
+    let (mut evaluator, events) = evaluator();
+    let source = "chant fail(progress) then\n    Progress.update(progress, 1)\n    1 / nothing\nend\nwith_progress(3, fail)";
+    assert!(evaluator.eval(&parse(source)).is_err());
+    assert_eq!(lock(&events).last(), Some(&ProgressEvent::Finished { task: 1, end: TaskEnd::Failed }));
+
+    let source = "chant keep(progress) then progress end\nbind kept to with_progress(3, keep)\nProgress.update(kept, 1)";
+    assert!(matches!(evaluator.eval(&parse(source)), Err(RuntimeError::ResourceReleased { .. })));
+
+    assert!(matches!(evaluator.eval(&parse("with_progress(-1, keep)")), Err(RuntimeError::TypeError { .. })));
+    let source = "chant halve(progress) then Progress.update(progress, \"half\") end\nwith_progress(3, halve)";
+    assert!(matches!(evaluator.eval(&parse(source)), Err(RuntimeError::TypeError { .. })));
+}
@@ -0,0 +1,99 @@
+//! Tests for range analysis: interval tracking that warns about
+//! out-of-bounds indexing and division by zero during semantic analysis
+
+use glimmer_weave::pipeline::{CompilerPipeline, Target};
+use glimmer_weave::{AstNode, Lexer, Parser, SemanticAnalyzer, SemanticWarning};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn warnings(source: &str) -> Vec<SemanticWarning> {
+    let mut analyzer = SemanticAnalyzer::new();
+    let _ = analyzer.analyze(&parse(source));
+    analyzer.warnings().to_vec()
+}
+
+#[test]
+fn test_constant_index_past_end_is_certain() {
+    let warnings = warnings("bind xs to [10, 20, 30]\nxs[3]");
+    match &warnings[..] {
+        [SemanticWarning::IndexOutOfBounds { certain: true, span }] => assert_eq!(span.start.line, 2),
+        other => panic!("unexpected warnings: {:?}", other),
+    }
+    assert!(self::warnings("bind xs to [10, 20, 30]\nxs[2]").is_empty());
+}
+
+#[test]
+fn test_loop_index_past_end_is_possible() {
+    // range is exclusive, so i reaches 3 on a list of three
+    let source = "bind xs to [1, 2, 3]\nfor each i in range(0, 4) then\n    xs[i]\nend";
+    assert!(matches!(warnings(source)[..], [SemanticWarning::IndexOutOfBounds { certain: false, .. }]));
+
+    let source = "bind xs to [1, 2, 3]\nfor each i in range(0, list_length(xs)) then\n    xs[i]\nend";
+    assert!(warnings(source).is_empty());
+}
+
+#[test]
+fn test_guard_narrows_index() {
+    let source = "\
+bind xs to [1, 2, 3]
+for each i in range(0, 10) then
+    should i less than list_length(xs) then
+        xs[i]
+    otherwise
+        xs[2]
+    end
+end";
+    assert!(warnings(source).is_empty());
+
+    // Past the guard, i may again be past the end
+    let source = "bind xs to [1, 2, 3]\nfor each i in range(0, 10) then\n    should i less than 3 then\n        xs[i]\n    end\n    xs[i]\nend";
+    match &warnings(source)[..] {
+        [SemanticWarning::IndexOutOfBounds { certain: false, span }] => assert_eq!(span.start.line, 6),
+        other => panic!("unexpected warnings: {:?}", other),
+    }
+}
+
+#[test]
+fn test_unreachable_branch_is_skipped() {
+    let source = "bind xs to [1, 2, 3]\nbind i to 5\nshould i less than 3 then\n    xs[i]\nend";
+    assert!(warnings(source).is_empty());
+}
+
+#[test]
+fn test_division_by_zero() {
+    assert!(matches!(warnings("bind d to 2 - 2\n10 / d")[..], [SemanticWarning::DivisionByZero { certain: true, .. }]));
+    assert!(matches!(
+        warnings("for each k in range(-1, 2) then\n    10 % k\nend")[..],
+        [SemanticWarning::DivisionByZero { certain: false, .. }]
+    ));
+    assert!(warnings("for each k in range(1, 5) then\n    10 / k\nend").is_empty());
+}
+
+#[test]
+fn test_unknown_values_are_not_flagged() {
+    // Parameters and loop-assigned variables could be anything
+    let source = "\
+chant pick(xs, i) then
+    xs[i] / i
+end
+
+bind ys to [1]
+weave n as 0
+whilst n less than 10 then
+    set n to n + 1
+end
+ys[n]";
+    assert!(warnings(source).is_empty());
+}
+
+#[test]
+fn test_pipeline_labels_range_warnings() {
+    let mut pipeline = CompilerPipeline::new();
+    pipeline.run("bind xs to [1]\n\nxs[-1]", Target::Eval).expect("warnings do not fail the build");
+    let diagnostic = pipeline.diagnostics().iter().next().expect("a warning");
+    assert!(diagnostic.message.contains("IndexOutOfBounds"), "{}", diagnostic.message);
+    assert_eq!(diagnostic.labels[0].span.start.line, 3);
+}
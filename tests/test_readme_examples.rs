@@ -53,6 +53,7 @@ fn test_data_pipeline() {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_variables_immutable() {
     let source = r#"
         bind name to "Alice"
@@ -0,0 +1,135 @@
+//! Tests for resource handles and their deterministic release
+
+use std::sync::{Arc, Mutex};
+
+use glimmer_weave::runtime::NativeFunction;
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+/// Host native handing out file handle 7
+fn open_file(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::resource("file", 7))
+}
+
+/// Host native that reads from a handle
+fn read_file(args: &[Value]) -> Result<Value, RuntimeError> {
+    match &args[0] {
+        Value::Resource { handle, .. } => Ok(Value::Number(*handle as f64)),
+        other => Err(RuntimeError::TypeError { expected: "Resource".to_string(), got: other.type_name().to_string() }),
+    }
+}
+
+/// Evaluator with the file natives and a host that logs every release
+fn host() -> (Evaluator, Arc<Mutex<Vec<String>>>) {
+    let released = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&released);
+
+    let mut evaluator = Evaluator::new();
+    evaluator.define_global("open_file", Value::NativeChant(NativeFunction::new("open_file", Some(0), open_file)));
+    evaluator.define_global("read_file", Value::NativeChant(NativeFunction::new("read_file", Some(1), read_file)));
+    evaluator.set_resource_host(Box::new(move |kind: &str, handle: u64| {
+        log.lock().unwrap().push(format!("{} #{}", kind, handle));
+        Ok(())
+    }));
+    (evaluator, released)
+}
+
+#[test]
+fn test_explicit_release() {
+    let (mut evaluator, released) = host();
+    let source = "weave f as open_file()\nweave n as read_file(f)\nrelease(f)\nn";
+    assert_eq!(evaluator.eval(&parse(source)), Ok(Value::Number(7.0)));
+    assert_eq!(*released.lock().unwrap(), vec!["file #7"]);
+    assert_eq!(evaluator.live_resources(), 0);
+}
+
+#[test]
+fn test_released_at_chant_exit() {
+    let (mut evaluator, released) = host();
+    let source = r#"
+        chant peek() then
+            weave f as open_file()
+            yield read_file(f)
+        end
+        peek()
+    "#;
+    assert_eq!(evaluator.eval(&parse(source)), Ok(Value::Number(7.0)));
+    assert_eq!(*released.lock().unwrap(), vec!["file #7"]);
+}
+
+#[test]
+fn test_released_when_a_chant_fails() {
+    let (mut evaluator, released) = host();
+    let source = "chant broken() then\n    weave f as open_file()\n    yield 1 / 0\nend\nbroken()";
+    assert_eq!(evaluator.eval(&parse(source)), Err(RuntimeError::DivisionByZero));
+    assert_eq!(*released.lock().unwrap(), vec!["file #7"]);
+}
+
+#[test]
+fn test_returned_resource_moves_to_the_caller() {
+    let (mut evaluator, released) = host();
+    let source = r#"
+        chant open_config() then
+            yield open_file()
+        end
+        chant use_config() then
+            weave f as open_config()
+            yield read_file(f)
+        end
+        use_config()
+    "#;
+    assert_eq!(evaluator.eval(&parse(source)), Ok(Value::Number(7.0)));
+    assert_eq!(*released.lock().unwrap(), vec!["file #7"]);
+}
+
+#[test]
+fn test_double_release() {
+    let (mut evaluator, released) = host();
+    let source = "weave f as open_file()\nrelease(f)\nResource.release(f)";
+    assert_eq!(
+        evaluator.eval(&parse(source)),
+        Err(RuntimeError::DoubleRelease { kind: "file".to_string(), handle: 7 })
+    );
+    assert_eq!(released.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn test_use_after_release() {
+    let (mut evaluator, _) = host();
+    let source = "weave f as open_file()\nrelease(f)\nread_file(f)";
+    assert_eq!(
+        evaluator.eval(&parse(source)),
+        Err(RuntimeError::ResourceReleased { kind: "file".to_string(), handle: 7 })
+    );
+}
+
+#[test]
+fn test_release_errors_can_be_caught() {
+    let (mut evaluator, _) = host();
+    let source = r#"
+        weave f as open_file()
+        release(f)
+        attempt
+            release(f)
+        harmonize on DoubleRelease then
+            "already closed"
+        end
+    "#;
+    assert_eq!(evaluator.eval(&parse(source)), Ok(Value::Text("already closed".to_string())));
+}
+
+#[test]
+fn test_host_acquired_resource() {
+    let (mut evaluator, released) = host();
+    let device = evaluator.acquire_resource("device", 2);
+    evaluator.define_global("console", device);
+
+    assert_eq!(evaluator.eval(&parse("to_text(console)")), Ok(Value::Text("[Resource:device #2]".to_string())));
+    assert!(released.lock().unwrap().is_empty());
+    assert_eq!(evaluator.eval(&parse("release(console)")), Ok(Value::Nothing));
+    assert_eq!(*released.lock().unwrap(), vec!["device #2"]);
+}
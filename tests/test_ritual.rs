@@ -0,0 +1,122 @@
+//! Tests for `ritual` state machine definitions
+
+use std::sync::{Arc, Mutex};
+
+use glimmer_weave::{run, AstNode, EvalOptions, Evaluator, Lexer, Parser, Value};
+
+const DOOR: &str = r#"
+weave battery as 50
+
+ritual Door with
+    state Locked
+    state Closed
+    state Open
+    on unlock from Locked to Closed
+    on open from Closed to Open when battery greater than 10
+    on close from Open to Closed
+    on lock from Closed, Open to Locked
+    entering Open then
+        println("opened")
+    end
+    leaving Open then
+        println("leaving " + event)
+    end
+end
+"#;
+
+fn eval_with_output(source: &str) -> (Result<Value, glimmer_weave::RuntimeError>, String) {
+    let written = Arc::new(Mutex::new(String::new()));
+    let sink = Arc::clone(&written);
+    let mut evaluator = Evaluator::new();
+    evaluator.set_output_sink(Box::new(move |text: &str| sink.lock().unwrap().push_str(text)));
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let result = evaluator.eval(&Parser::new(tokens).parse().expect("Parse error"));
+    let output = written.lock().unwrap().clone();
+    (result, output)
+}
+
+fn state(name: &str) -> Value {
+    Value::Text(name.to_string())
+}
+
+fn parse_error(source: &str) -> String {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect_err("Expected a parse error").message
+}
+
+#[test]
+fn test_events_move_the_machine_and_run_actions() {
+    let script = format!(
+        "{}{}",
+        DOOR,
+        r#"
+        weave door as door_start()
+        weave seen as [door.state]
+        for each event in ["open", "unlock", "open", "unlock", "close", "lock"] then
+            set door to door_step(door, event)
+            set seen to list_push(seen, door.state)
+        end
+        seen
+    "#
+    );
+    let (result, output) = eval_with_output(&script);
+    let expected = ["Locked", "Locked", "Closed", "Open", "Open", "Closed", "Locked"];
+    assert_eq!(result, Ok(Value::List(expected.iter().map(|name| state(name)).collect())));
+    assert_eq!(output, "opened\nleaving close\n");
+}
+
+#[test]
+fn test_guards_see_the_script_state() {
+    let script = format!(
+        "{}{}",
+        DOOR,
+        r#"
+        set battery to 5
+        weave door as door_step(door_start(), "unlock")
+        bind refused to door_step(door, "open").state
+        set battery to 80
+        [refused, door_step(door, "open").state, door_step(door, "lock").state]
+    "#
+    );
+    let (result, _) = eval_with_output(&script);
+    assert_eq!(result, Ok(Value::List(vec![state("Closed"), state("Open"), state("Locked")])));
+}
+
+#[test]
+fn test_rituals_in_groves_and_through_the_pipeline() {
+    let light = r#"
+ritual TrafficLight with
+    state Red
+    state Green
+    state Amber
+    on next from Red to Green
+    on next from Green to Amber
+    on next from Amber to Red
+end
+"#;
+    let script = format!("{}traffic_light_step(traffic_light_step(traffic_light_start(), \"next\"), \"next\").state", light);
+    assert_eq!(run(&script, EvalOptions::new()).unwrap(), state("Amber"));
+
+    // A grove takes the generated definitions into its body
+    let grove = format!("grove Traffic with\n{}offer traffic_light_start, traffic_light_step\nend", light);
+    let nodes = Parser::new(Lexer::new(&grove).tokenize_positioned()).parse().unwrap();
+    let [AstNode::ModuleDecl { body, exports, .. }] = nodes.as_slice() else { panic!("Expected one grove: {:?}", nodes) };
+    assert!(matches!(&body[..3], [AstNode::FormDef { .. }, AstNode::ChantDef { .. }, AstNode::ChantDef { .. }]));
+    assert_eq!(exports, &["traffic_light_start".to_string(), "traffic_light_step".to_string()]);
+}
+
+#[test]
+fn test_malformed_rituals_are_parse_errors() {
+    let unknown = "ritual Door with\n    state Open\n    on close from Open to Shut\nend";
+    assert_eq!(parse_error(unknown), "Ritual 'Door' has no state 'Shut'");
+
+    let nested = "chant f() then\n    ritual Door with\n        state Open\n    end\nend";
+    assert!(parse_error(nested).contains("top level"));
+
+    let stray = "ritual Door with\n    state Open\n    bind x to 1\nend";
+    assert!(parse_error(stray).contains("in ritual"));
+
+    // `ritual` is still an ordinary name elsewhere
+    let (result, _) = eval_with_output("bind ritual to 3\nritual + 1");
+    assert_eq!(result, Ok(Value::Number(4.0)));
+}
@@ -1,5 +1,5 @@
-/// Comprehensive runtime type checking tests
-/// Tests edge cases and error handling for type mismatches at runtime
+//! Comprehensive runtime type checking tests
+//! Tests edge cases and error handling for type mismatches at runtime
 
 use glimmer_weave::{Evaluator, Lexer, Parser};
 use glimmer_weave::eval::{RuntimeError, Value};
@@ -0,0 +1,127 @@
+//! Tests for spawned tasks and the Scheduler integration points
+
+use std::sync::{Arc, Mutex};
+
+use glimmer_weave::scheduler::{CooperativeScheduler, EventId, Scheduler, Task, TaskId};
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+fn eval(source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval(&parse(source))
+}
+
+#[test]
+fn test_spawned_tasks_run_on_yield() {
+    let source = r#"
+        weave log as []
+        chant note(x) then
+            set log to list_push(log, x)
+        end
+        spawn(note, 1)
+        spawn(note, 2)
+        set log to list_push(log, 0)
+        yield_now()
+        log
+    "#;
+    assert_eq!(
+        eval(source),
+        Ok(Value::List(vec![Value::Number(0.0), Value::Number(1.0), Value::Number(2.0)]))
+    );
+}
+
+#[test]
+fn test_block_on_event_runs_tasks_until_signalled() {
+    let source = r#"
+        weave ready as false
+        chant producer() then
+            set ready to true
+            Task.signal(1)
+        end
+        Task.spawn(producer)
+        Task.wait(1)
+        ready
+    "#;
+    assert_eq!(eval(source), Ok(Value::Truth(true)));
+}
+
+#[test]
+fn test_block_on_unsignalled_event_is_a_deadlock() {
+    match eval("block_on_event(3)") {
+        Err(RuntimeError::Custom(message)) => assert!(message.contains("Deadlock"), "{}", message),
+        other => panic!("Expected a deadlock error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_task_errors_surface_at_the_yield() {
+    let source = "chant broken() then\n    yield 1 / 0\nend\nspawn(broken)\nyield_now()";
+    assert_eq!(eval(source), Err(RuntimeError::DivisionByZero));
+}
+
+#[test]
+fn test_run_until_idle_drains_pending_tasks() {
+    let mut evaluator = Evaluator::new();
+    evaluator
+        .eval(&parse("weave count as 0\nchant bump() then\n    set count to count + 1\nend\nspawn(bump)\nspawn(bump)"))
+        .unwrap();
+    evaluator.run_until_idle().unwrap();
+    assert_eq!(evaluator.eval(&parse("count")), Ok(Value::Number(2.0)));
+}
+
+/// Host scheduler that records every call the evaluator makes
+struct KernelScheduler {
+    inner: CooperativeScheduler,
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+impl Scheduler for KernelScheduler {
+    fn spawn(&mut self, chant: Value, args: Vec<Value>) -> TaskId {
+        self.calls.lock().unwrap().push("spawn".to_string());
+        self.inner.spawn(chant, args)
+    }
+
+    fn yield_now(&mut self) {
+        self.calls.lock().unwrap().push("yield".to_string());
+    }
+
+    fn block_on_event(&mut self, event: EventId) {
+        // The kernel raises the event while the script thread is parked
+        self.calls.lock().unwrap().push(format!("block {}", event));
+        self.inner.signal(event);
+    }
+
+    fn signal(&mut self, event: EventId) {
+        self.inner.signal(event);
+    }
+
+    fn is_signalled(&self, event: EventId) -> bool {
+        self.inner.is_signalled(event)
+    }
+
+    fn next_ready(&mut self) -> Option<Task> {
+        self.inner.next_ready()
+    }
+
+    fn cancel(&mut self, task: TaskId) -> bool {
+        self.calls.lock().unwrap().push(format!("cancel {}", task));
+        self.inner.cancel(task)
+    }
+}
+
+#[test]
+fn test_host_scheduler_hooks() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let mut evaluator = Evaluator::new();
+    evaluator.set_scheduler(Box::new(KernelScheduler {
+        inner: CooperativeScheduler::new(),
+        calls: Arc::clone(&calls),
+    }));
+
+    let source = "chant idle() then\n    yield 0\nend\nspawn(idle)\nyield_now()\nblock_on_event(9)\n42";
+    assert_eq!(evaluator.eval(&parse(source)), Ok(Value::Number(42.0)));
+    assert_eq!(*calls.lock().unwrap(), vec!["spawn", "yield", "block 9"]);
+}
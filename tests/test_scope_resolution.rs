@@ -0,0 +1,180 @@
+//! Tests for scope-resolved variable lookups
+//!
+//! Programs annotated by `resolve_scopes` must evaluate exactly like the
+//! unresolved AST, including in cases where a resolved slot turns out to be
+//! stale at runtime and the evaluator falls back to a name lookup.
+
+use glimmer_weave::{resolve_scopes, Evaluator, Lexer, Parser, RuntimeError, Value};
+
+/// Evaluate source both with and without slot resolution and check they agree
+fn eval_both(source: &str) -> Result<Value, RuntimeError> {
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize_positioned();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().expect("Parse error");
+
+    let mut resolved = ast.clone();
+    resolve_scopes(&mut resolved);
+    assert_ne!(ast, resolved, "Expected at least one identifier to be resolved");
+
+    let plain_result = Evaluator::new().eval(&ast);
+    let resolved_result = Evaluator::new().eval(&resolved);
+    assert_eq!(plain_result, resolved_result);
+    resolved_result
+}
+
+#[test]
+fn test_resolved_counting_loop() {
+    let source = r#"
+        weave total as 0
+        weave i as 0
+        whilst i is not 100 then
+            set total to total + i
+            set i to i + 1
+        end
+        total
+    "#;
+    assert_eq!(eval_both(source), Ok(Value::Number(4950.0)));
+}
+
+#[test]
+fn test_resolved_chant_locals() {
+    let source = r#"
+        chant grid_sum(size) then
+            weave total as 0
+            for each row in range(0, size) then
+                for each col in range(0, size) then
+                    set total to total + row * col
+                end
+            end
+            yield total
+        end
+        grid_sum(4)
+    "#;
+    assert_eq!(eval_both(source), Ok(Value::Number(36.0)));
+}
+
+#[test]
+fn test_resolved_shadowing_in_block_scopes() {
+    let source = r#"
+        bind x to 1
+        weave seen as 0
+        for each x in [10, 20] then
+            set seen to seen + x
+        end
+        seen + x
+    "#;
+    assert_eq!(eval_both(source), Ok(Value::Number(31.0)));
+}
+
+#[test]
+fn test_whilst_rounds_see_later_bindings() {
+    // The second round reads the `x` the first round bound in the loop's
+    // scope, not the outer one
+    let source = r#"
+        bind x to 1
+        weave seen as 0
+        for each round in [0] then
+            weave i as 0
+            whilst i less than 2 then
+                set seen to seen * 10 + x
+                bind x to 5
+                set i to i + 1
+            end
+        end
+        seen
+    "#;
+    assert_eq!(eval_both(source), Ok(Value::Number(15.0)));
+}
+
+#[test]
+fn test_nested_whilst_rounds_see_later_bindings() {
+    // Bindings in the inner loop's body and in a branch land in the outer
+    // loop's scope too
+    let source = r#"
+        bind x to 1
+        weave seen as 0
+        for each round in [0] then
+            weave i as 0
+            whilst i less than 2 then
+                set seen to seen * 10 + x
+                weave j as 0
+                whilst j less than 1 then
+                    should i is 0 then
+                        bind x to 5
+                    end
+                    set j to j + 1
+                end
+                set i to i + 1
+            end
+        end
+        seen
+    "#;
+    assert_eq!(eval_both(source), Ok(Value::Number(15.0)));
+}
+
+#[test]
+fn test_conditional_definitions_fall_back_to_name_lookup() {
+    // Which branch runs decides the runtime slot of `b`
+    let source = r#"
+        should false then
+            bind a to 1
+        end
+        bind b to 2
+        b
+    "#;
+    assert_eq!(eval_both(source), Ok(Value::Number(2.0)));
+}
+
+#[test]
+fn test_resolved_chants_keep_dynamic_scoping() {
+    // Globals read inside a chant still see the caller's bindings
+    let source = r#"
+        bind label to 1
+        chant read() then
+            yield label
+        end
+        chant caller() then
+            bind label to 2
+            yield read()
+        end
+        caller() + read()
+    "#;
+    assert_eq!(eval_both(source), Ok(Value::Number(3.0)));
+}
+
+#[test]
+fn test_resolved_match_arm_bindings() {
+    let source = r#"
+        bind value to Triumph(21)
+        match value with
+            when Triumph(n) then n * 2
+            when Mishap(e) then 0
+        end
+    "#;
+    assert_eq!(eval_both(source), Ok(Value::Number(42.0)));
+}
+
+#[test]
+fn test_resolved_set_respects_immutability() {
+    let source = r#"
+        bind fixed to 1
+        set fixed to 2
+    "#;
+    assert_eq!(eval_both(source), Err(RuntimeError::ImmutableBinding("fixed".to_string())));
+}
+
+#[test]
+fn test_resolved_tail_recursion() {
+    let source = r#"
+        chant count(n, acc) then
+            should n is 0 then
+                yield acc
+            otherwise
+                yield count(n - 1, acc + n)
+            end
+        end
+        count(100, 0)
+    "#;
+    assert_eq!(eval_both(source), Ok(Value::Number(5050.0)));
+}
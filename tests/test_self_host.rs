@@ -0,0 +1,107 @@
+//! Tests for the `Compiler` module: compiler phases as builtins, so tools
+//! can be written in Glimmer-Weave
+
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+const GRANT: &str = "request Compiler.inspect with justification \"lint\"\n";
+
+fn run(source: &str) -> Result<Value, RuntimeError> {
+    let ast = Parser::new(Lexer::new(&format!("{}{}", GRANT, source)).tokenize_positioned())
+        .parse()
+        .expect("Parse error");
+    Evaluator::new().eval(&ast)
+}
+
+fn text(value: &str) -> Value {
+    Value::Text(value.to_string())
+}
+
+#[test]
+fn test_tokens_are_maps() {
+    let source = r#"
+        bind tokens to Compiler.lex("bind x to 42")
+        [tokens[0].kind, tokens[0].keyword, tokens[1].value, tokens[3].value, tokens[3].column]
+    "#;
+    assert_eq!(
+        run(source).unwrap(),
+        Value::List(vec![text("Bind"), Value::Truth(true), text("x"), Value::Number(42.0), Value::Number(11.0)])
+    );
+}
+
+#[test]
+fn test_a_linter_written_in_the_language() {
+    // Counts calls to `print` by walking the tree
+    let source = r#"
+        chant count_prints(node) then
+            weave total as 0
+            should node.kind is "Call" and node.name is "print" then
+                set total to 1
+            end
+            for each child in node.children then
+                set total to total + count_prints(child)
+            end
+            yield total
+        end
+
+        bind program to "chant greet(name) then\n    print(name)\nend\nprint(1)\ngreet(\"x\")"
+        match Compiler.parse(program) with
+            when Triumph(nodes) then
+                weave total as 0
+                for each node in nodes then
+                    set total to total + count_prints(node)
+                end
+                total
+            when Mishap(errors) then -1
+        end
+    "#;
+    assert_eq!(run(source).unwrap(), Value::Number(2.0));
+}
+
+#[test]
+fn test_parse_errors_are_diagnostics() {
+    let source = r#"
+        match Compiler.parse("bind to 5") with
+            when Triumph(nodes) then nothing
+            when Mishap(errors) then [errors[0].severity, errors[0].line]
+        end
+    "#;
+    assert_eq!(run(source).unwrap(), Value::List(vec![text("error"), Value::Number(1.0)]));
+}
+
+#[test]
+fn test_analysis_reports_semantic_errors() {
+    let source = r#"
+        bind found to Compiler.analyze("bind x to 1\nset x to 2")
+        [List.length(found), found[0].severity]
+    "#;
+    assert_eq!(run(source).unwrap(), Value::List(vec![Value::Number(1.0), text("error")]));
+    assert_eq!(run("Compiler.analyze(\"bind x to 1\")").unwrap(), Value::List(vec![]));
+}
+
+#[test]
+fn test_compile_returns_listings() {
+    let source = r#"
+        match Compiler.compile("40 + 2", "asm") with
+            when Triumph(listing) then listing
+            when Mishap(errors) then "failed"
+        end
+    "#;
+    let Value::Text(listing) = run(source).unwrap() else { panic!("expected a listing") };
+    assert!(listing.contains("main:"), "{}", listing);
+
+    match run("Compiler.compile(\"1\", \"bytecode\")").unwrap() {
+        Value::Outcome { success: true, value } => assert!(matches!(*value, Value::Text(_))),
+        other => panic!("expected a disassembly, got {:?}", other),
+    }
+    assert!(matches!(run("Compiler.compile(\"1\", \"wasm\")"), Err(RuntimeError::Custom(_))));
+}
+
+#[test]
+fn test_phases_need_a_grant() {
+    let ast = Parser::new(Lexer::new("Compiler.lex(\"bind x to 1\")").tokenize_positioned()).parse().unwrap();
+    match Evaluator::new().eval(&ast) {
+        Err(RuntimeError::CapabilityDenied { capability, .. }) => assert_eq!(capability, "Compiler.inspect"),
+        other => panic!("expected a denial, got {:?}", other),
+    }
+    assert!(matches!(run("Compiler.lex(42)"), Err(RuntimeError::TypeError { .. })));
+}
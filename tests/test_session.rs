@@ -0,0 +1,87 @@
+//! Tests for sessions spawning evaluators that share modules and policy
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use glimmer_weave::module_cache::{content_hash, CacheStore, MemoryCacheStore};
+use glimmer_weave::session::Session;
+use glimmer_weave::vm::VM;
+use glimmer_weave::{ModuleResolver, Prelude, RuntimeError, Value};
+
+const LIBRARY: &str = "grove Lib with\n    bind base to 40\n    chant add(a, b) then\n        a + b\n    end\n    offer base, add\nend";
+
+fn session() -> Session {
+    let mut resolver = ModuleResolver::new("/app".to_string(), "/std".to_string());
+    resolver.add_source("/app/lib.gw", LIBRARY);
+    Session::new(resolver)
+}
+
+#[test]
+fn test_scripts_share_parsed_modules_but_not_globals() {
+    let mut session = session();
+    let script = "summon Lib from \"lib.gw\"\nLib.add(Lib.base, 2)";
+    assert_eq!(session.run(script), Ok(Value::Number(42.0)));
+    assert_eq!(session.run(script), Ok(Value::Number(42.0)));
+    assert_eq!(session.module_count(), 1);
+
+    // Each evaluator has its own globals
+    assert_eq!(session.run("weave leftover as 1\nleftover"), Ok(Value::Number(1.0)));
+    assert!(matches!(session.run("leftover"), Err(RuntimeError::UndefinedVariable(_))));
+
+    // Sources registered later are visible to every new evaluator
+    session.resolver().add_source("/app/extra.gw", "grove Extra with\n    bind seven to 7\n    offer seven\nend");
+    let mut evaluator = session.evaluator();
+    let tokens = glimmer_weave::Lexer::new("summon Extra from \"extra.gw\"\nExtra.seven").tokenize_positioned();
+    let nodes = glimmer_weave::Parser::new(tokens).parse().unwrap();
+    assert_eq!(evaluator.eval(&nodes), Ok(Value::Number(7.0)));
+    assert_eq!(session.module_count(), 2);
+}
+
+#[test]
+fn test_policy_decides_for_every_evaluator() {
+    let mut session = session();
+    let asked = Arc::new(AtomicU64::new(0));
+    let counter = Arc::clone(&asked);
+    session.set_capability_policy(Box::new(move |capability: &str, _: &str| {
+        counter.fetch_add(1, Ordering::Relaxed);
+        if capability == "VGA.write" { Ok(()) } else { Err("not for scripts".to_string()) }
+    }));
+
+    let granted = session.run("request VGA.write with justification \"draw\"\n1");
+    assert_eq!(granted, Ok(Value::Number(1.0)));
+    let denied = session.run("request FS.write with justification \"save\"");
+    assert!(matches!(denied, Err(RuntimeError::CapabilityDenied { .. })));
+    assert_eq!(asked.load(Ordering::Relaxed), 2);
+
+    // Each evaluator keeps its own audit log
+    let evaluator = session.evaluator();
+    assert!(evaluator.capability_audit().entries().is_empty());
+}
+
+#[test]
+fn test_scripts_and_chunks_are_cached() {
+    let mut session = session();
+    let script = "weave x as 40\nx + 2";
+    let first = session.compile(script).unwrap();
+    let second = session.compile(script).unwrap();
+    assert_eq!(first.instructions, second.instructions);
+    assert_eq!(VM::new().execute(second).unwrap(), Value::Number(42.0));
+
+    // Parses come from the installed store
+    let mut store = MemoryCacheStore::new();
+    let stand_in = session.parse("99").unwrap();
+    store.store_module(content_hash("1"), &stand_in);
+    session.set_cache(Box::new(store));
+    assert_eq!(session.run("1"), Ok(Value::Number(99.0)));
+
+    let error = session.run("bind to");
+    assert!(matches!(error, Err(RuntimeError::CompileError { .. })));
+}
+
+#[test]
+fn test_evaluators_start_from_the_resolver_prelude() {
+    let mut resolver = ModuleResolver::new("/app".to_string(), "/std".to_string());
+    resolver.set_prelude(Prelude::none());
+    let mut session = Session::new(resolver);
+    assert!(matches!(session.run("list_length([1])"), Err(RuntimeError::UndefinedVariable(_))));
+}
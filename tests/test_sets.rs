@@ -0,0 +1,82 @@
+//! Tests for the Set collection type
+//!
+//! Covers the set builtins, which values can be set elements, iterating a
+//! set in order, and sets surviving the value codec.
+
+use std::collections::BTreeSet;
+
+use glimmer_weave::eval::SetElement;
+use glimmer_weave::value_codec::{decode, encode, CodecError};
+use glimmer_weave::{analyze, AstNode, Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    Parser::new(Lexer::new(source).tokenize_positioned()).parse().expect("Parse error")
+}
+
+fn eval(source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval(&parse(source))
+}
+
+fn numbers(values: &[f64]) -> Value {
+    Value::List(values.iter().map(|n| Value::Number(*n)).collect())
+}
+
+#[test]
+fn test_set_builtins() {
+    let source = r#"
+        bind small to set_new([3, 1, 3, 2])
+        bind more to set_insert(set_new(), 4)
+        [set_contains(small, 2), set_contains(small, 4), set_contains(set_insert(small, 4), 4), set_contains(more, 4)]
+    "#;
+    let truths = |values: [bool; 4]| Value::List(values.iter().map(|b| Value::Truth(*b)).collect());
+    assert_eq!(eval(source).unwrap(), truths([true, false, true, true]));
+
+    let elements = |source: &str| match eval(source).unwrap() {
+        Value::Set(elements) => elements.iter().map(SetElement::to_value).collect::<Vec<_>>(),
+        other => panic!("expected a set, got {:?}", other),
+    };
+    assert_eq!(elements("set_union(set_new([1, 2]), set_new([2, 3]))"), vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)]);
+    assert_eq!(elements("set_intersect(set_new([1, 2]), set_new([2, 3]))"), vec![Value::Number(2.0)]);
+    assert_eq!(elements("Set.union(Set.new([\"b\"]), Set.new([\"a\"]))"), vec![Value::Text("a".to_string()), Value::Text("b".to_string())]);
+}
+
+#[test]
+fn test_for_each_visits_a_set_in_order() {
+    let source = r#"
+        weave seen as []
+        for each n in set_new([5, 1, 3, 1]) then
+            set seen to List.push(seen, n)
+        end
+        seen
+    "#;
+    assert_eq!(eval(source).unwrap(), numbers(&[1.0, 3.0, 5.0]));
+}
+
+#[test]
+fn test_elements_must_have_an_order() {
+    match eval("set_new([[1]])") {
+        Err(RuntimeError::TypeError { got, .. }) => assert_eq!(got, "List"),
+        other => panic!("expected a type error, got {:?}", other),
+    }
+    assert!(eval("set_insert([1], 2)").is_err());
+    // 0 and -0 are one element, as they are equal
+    assert_eq!(eval("to_text(set_contains(set_new([0]), -0))").unwrap(), Value::Text("true".to_string()));
+}
+
+#[test]
+fn test_sets_round_trip_through_the_codec() {
+    let set = Value::Set(BTreeSet::from([SetElement::Nothing, SetElement::Number(2.5), SetElement::Text("x".to_string())]));
+    assert_eq!(decode(&encode(&set).unwrap()).unwrap(), set);
+
+    // A set that holds a list is refused
+    let mut bytes = encode(&Value::Set(BTreeSet::from([SetElement::Truth(true)]))).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] = 6;
+    bytes.push(0);
+    assert_eq!(decode(&bytes), Err(CodecError::InvalidSetElement));
+}
+
+#[test]
+fn test_analyzer_knows_the_set_builtins() {
+    assert!(analyze(&parse("bind s: Set to set_new()\nset_contains(s, 1)")).is_ok());
+}
@@ -0,0 +1,104 @@
+//! Tests for the `Sound` module: tones behind `Speaker.play`, over a host
+//! speaker and the PC speaker backend
+
+use std::sync::{Arc, Mutex};
+
+use glimmer_weave::sound::{PcSpeaker, Speaker, SpeakerPorts};
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+const REQUEST: &str = "request Speaker.play with justification \"backup finished\"\n";
+
+/// What a speaker was asked to do
+#[derive(Debug, Clone, PartialEq)]
+enum Played {
+    Tone(f64, u64),
+    Stop,
+}
+
+#[derive(Clone, Default)]
+struct Recording(Arc<Mutex<Vec<Played>>>);
+
+impl Speaker for Recording {
+    fn tone(&mut self, frequency: f64, millis: u64) -> Result<(), String> {
+        self.0.lock().unwrap().push(Played::Tone(frequency, millis));
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<(), String> {
+        self.0.lock().unwrap().push(Played::Stop);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_tones_reach_the_speaker() {
+    let speaker = Recording::default();
+    let mut evaluator = Evaluator::new();
+    evaluator.set_speaker(Box::new(speaker.clone()));
+    let source = format!("{}Sound.tone(880, 150)\nSound.tone(440.5, 0)\nSound.stop()", REQUEST);
+    assert_eq!(evaluator.eval(&parse(&source)), Ok(Value::Nothing));
+    assert_eq!(*speaker.0.lock().unwrap(), vec![Played::Tone(880.0, 150), Played::Tone(440.5, 0), Played::Stop]);
+}
+
+#[test]
+fn test_sound_needs_a_grant() {
+    match Evaluator::new().eval(&parse("Sound.tone(440, 100)")) {
+        Err(RuntimeError::CapabilityDenied { capability, .. }) => assert_eq!(capability, "Speaker.play"),
+        other => panic!("expected a denial, got {:?}", other),
+    }
+    // The default speaker under std plays nothing, without failing
+    assert_eq!(Evaluator::new().eval(&parse(&format!("{}Sound.tone(440, 100)", REQUEST))), Ok(Value::Nothing));
+}
+
+#[test]
+fn test_tone_arguments() {
+    let mut evaluator = Evaluator::new();
+    evaluator.eval(&parse(REQUEST)).unwrap();
+    for source in ["Sound.tone(5, 100)", "Sound.tone(30000, 100)", "Sound.tone(440, 1.5)", "Sound.tone(440, -1)", "Sound.tone(\"A\", 100)"] {
+        assert!(matches!(evaluator.eval(&parse(source)), Err(RuntimeError::TypeError { .. })), "{}", source);
+    }
+}
+
+/// Ports that log writes and keep port B's other bits set
+#[derive(Default)]
+struct Ports {
+    gate: u8,
+    log: Vec<(u16, u8)>,
+    slept: u64,
+}
+
+impl SpeakerPorts for Ports {
+    fn inb(&mut self, _port: u16) -> u8 {
+        self.gate
+    }
+
+    fn outb(&mut self, port: u16, value: u8) {
+        if port == 0x61 {
+            self.gate = value;
+        }
+        self.log.push((port, value));
+    }
+
+    fn sleep(&mut self, millis: u64) {
+        self.slept += millis;
+    }
+}
+
+#[test]
+fn test_pc_speaker_programs_the_pit() {
+    let ports = Ports { gate: 0b1000_0000, ..Ports::default() };
+    let mut speaker = PcSpeaker::new(ports);
+    speaker.tone(1000.0, 250).unwrap();
+    let ports = speaker.ports();
+    // 1193182 / 1000 rounds to 1193 = 0x04A9
+    assert_eq!(
+        ports.log,
+        vec![(0x43, 0xB6), (0x42, 0xA9), (0x42, 0x04), (0x61, 0b1000_0011), (0x61, 0b1000_0000)]
+    );
+    assert_eq!(ports.slept, 250);
+}
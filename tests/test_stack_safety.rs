@@ -0,0 +1,305 @@
+//! Tests for stack-safe evaluation
+//!
+//! Expressions are evaluated on an explicit work stack, so deep nesting must
+//! not overflow the host stack. Recursion that still nests too deeply must
+//! fail with `RuntimeError::StackOverflow` (too many nested calls) or
+//! `RuntimeError::DepthLimitExceeded` (too much nested evaluation) instead of
+//! crashing.
+
+use glimmer_weave::ast::{AstNode, BinaryOperator};
+use glimmer_weave::eval::{DEFAULT_MAX_CALL_DEPTH, DEFAULT_MAX_DEPTH};
+use glimmer_weave::source_location::SourceSpan;
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+/// Helper function to parse and evaluate source code with the given evaluator
+fn eval_with(evaluator: &mut Evaluator, source: &str) -> Result<Value, RuntimeError> {
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize_positioned();
+    let mut parser = Parser::new(tokens);
+    let ast = parser.parse().expect("Parse error");
+    evaluator.eval(&ast)
+}
+
+fn number(value: f64) -> AstNode {
+    AstNode::Number { value, span: SourceSpan::default() }
+}
+
+/// Build `1 + (1 + (1 + ...))` nested `depth` levels deep
+fn nested_sum(depth: usize) -> AstNode {
+    let mut node = number(1.0);
+    for _ in 0..depth {
+        node = AstNode::BinaryOp {
+            left: Box::new(number(1.0)),
+            op: BinaryOperator::Add,
+            right: Box::new(node),
+            span: SourceSpan::default(),
+        };
+    }
+    node
+}
+
+const RUNAWAY_RECURSION: &str = r#"
+    chant descend(n) then
+        should n is 0 then
+            yield 0
+        otherwise
+            yield 1 + descend(n - 1)
+        end
+    end
+"#;
+
+// ============================================================================
+// Deeply nested expressions
+// ============================================================================
+
+#[test]
+fn test_deeply_nested_binary_ops() {
+    let ast = nested_sum(2000);
+    let mut evaluator = Evaluator::new();
+    let result = evaluator.eval_node(&ast);
+    assert_eq!(result, Ok(Value::Number(2001.0)));
+}
+
+#[test]
+fn test_deeply_nested_lists() {
+    let mut node = number(7.0);
+    for _ in 0..2000 {
+        node = AstNode::List { elements: vec![node], span: SourceSpan::default() };
+    }
+
+    let mut evaluator = Evaluator::new();
+    let mut value = evaluator.eval_node(&node).expect("Evaluation failed");
+    let mut depth = 0;
+    while let Value::List(mut items) = value {
+        value = items.pop().expect("Empty list");
+        depth += 1;
+    }
+    assert_eq!(depth, 2000);
+    assert_eq!(value, Value::Number(7.0));
+}
+
+#[test]
+fn test_nested_expression_does_not_count_toward_depth() {
+    let ast = nested_sum(500);
+    let mut evaluator = Evaluator::new();
+    evaluator.set_max_depth(10);
+    assert_eq!(evaluator.eval_node(&ast), Ok(Value::Number(501.0)));
+}
+
+// ============================================================================
+// Depth limit
+// ============================================================================
+
+#[test]
+fn test_default_max_depth() {
+    let evaluator = Evaluator::new();
+    assert_eq!(evaluator.max_depth(), DEFAULT_MAX_DEPTH);
+}
+
+#[test]
+fn test_deep_recursion_within_default_depth() {
+    // Recursion to the default evaluation limit fits the stack of a test
+    // thread; descend(n) nests three levels per call
+    let source = format!("{}\ndescend(84)", RUNAWAY_RECURSION);
+    let mut evaluator = Evaluator::new();
+    evaluator.set_max_call_depth(1000);
+    assert_eq!(eval_with(&mut evaluator, &source), Ok(Value::Number(84.0)));
+
+    let source = format!("{}\ndescend(85)", RUNAWAY_RECURSION);
+    assert_eq!(
+        eval_with(&mut evaluator, &source),
+        Err(RuntimeError::DepthLimitExceeded { limit: DEFAULT_MAX_DEPTH })
+    );
+}
+
+/// Build `depth` nested `for each` loops over `[1]` around `1`
+fn nested_loops(depth: usize) -> AstNode {
+    let mut node = number(1.0);
+    for _ in 0..depth {
+        node = AstNode::ForStmt {
+            variable: "i".to_string(),
+            iterable: Box::new(AstNode::List { elements: vec![number(1.0)], span: SourceSpan::default() }),
+            body: vec![node],
+            span: SourceSpan::default(),
+        };
+    }
+    node
+}
+
+#[test]
+fn test_nested_loops_within_default_depth() {
+    // Loops take the most host stack per level of any statement
+    let mut evaluator = Evaluator::new();
+    assert_eq!(evaluator.eval_node(&nested_loops(DEFAULT_MAX_DEPTH - 1)), Ok(Value::Number(1.0)));
+    assert_eq!(
+        evaluator.eval_node(&nested_loops(DEFAULT_MAX_DEPTH)),
+        Err(RuntimeError::DepthLimitExceeded { limit: DEFAULT_MAX_DEPTH })
+    );
+}
+
+#[test]
+fn test_runaway_recursion_errors_gracefully() {
+    let source = format!("{}\ndescend(1000000)", RUNAWAY_RECURSION);
+    let mut evaluator = Evaluator::new();
+    let result = eval_with(&mut evaluator, &source);
+    assert_eq!(result, Err(RuntimeError::StackOverflow { limit: DEFAULT_MAX_CALL_DEPTH }));
+}
+
+#[test]
+fn test_deep_recursion_within_default_call_depth() {
+    let source = format!("{}\ndescend(60)", RUNAWAY_RECURSION);
+    let mut evaluator = Evaluator::new();
+    assert_eq!(eval_with(&mut evaluator, &source), Ok(Value::Number(60.0)));
+}
+
+#[test]
+fn test_default_call_depth_fits_in_default_max_depth() {
+    let source = format!("{}\ndescend({})", RUNAWAY_RECURSION, DEFAULT_MAX_CALL_DEPTH - 1);
+    let mut evaluator = Evaluator::new();
+    assert_eq!(evaluator.max_call_depth(), DEFAULT_MAX_CALL_DEPTH);
+    assert_eq!(eval_with(&mut evaluator, &source), Ok(Value::Number((DEFAULT_MAX_CALL_DEPTH - 1) as f64)));
+}
+
+#[test]
+fn test_call_limit_reached_on_default_stack() {
+    // Recursion through loop and match bodies takes the most host stack per
+    // call; it still reaches the call limit before the stack runs out
+    let sources = [
+        "chant descend(n) then\n for each i in [1] then\n descend(n + 1)\n end\nend\ndescend(0)",
+        "chant descend(n) then\n weave k as 0\n whilst k less than 1 then\n set k to 1\n should true then\n descend(n + 1)\n end\n end\nend\ndescend(0)",
+        "chant descend(n) then\n match n with\n when 0 then descend(n)\n otherwise then 0\n end\nend\ndescend(0)",
+    ];
+    for source in sources {
+        let mut evaluator = Evaluator::new();
+        assert_eq!(
+            eval_with(&mut evaluator, source),
+            Err(RuntimeError::StackOverflow { limit: DEFAULT_MAX_CALL_DEPTH }),
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_configured_max_call_depth() {
+    // descend(n) runs n + 1 nested calls
+    let source = format!("{}\ndescend(10)", RUNAWAY_RECURSION);
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_max_call_depth(10);
+    assert_eq!(eval_with(&mut evaluator, &source), Err(RuntimeError::StackOverflow { limit: 10 }));
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_max_call_depth(11);
+    assert_eq!(eval_with(&mut evaluator, &source), Ok(Value::Number(10.0)));
+
+    // Raising the call limit past the evaluation limit leaves the latter in force
+    let source = format!("{}\ndescend(500)", RUNAWAY_RECURSION);
+    let mut evaluator = Evaluator::new();
+    evaluator.set_max_depth(100);
+    evaluator.set_max_call_depth(1000);
+    assert_eq!(eval_with(&mut evaluator, &source), Err(RuntimeError::DepthLimitExceeded { limit: 100 }));
+}
+
+#[test]
+fn test_call_depth_counts_calls_between_chants() {
+    let source = r#"
+        chant ping(n) then
+            should n is 0 then
+                0
+            otherwise
+                1 + pong(n - 1)
+            end
+        end
+        chant pong(n) then
+            1 + ping(n)
+        end
+        ping(6)
+    "#;
+    let mut evaluator = Evaluator::new();
+    evaluator.set_max_call_depth(12);
+    assert_eq!(eval_with(&mut evaluator, source), Err(RuntimeError::StackOverflow { limit: 12 }));
+    evaluator.set_max_call_depth(13);
+    assert_eq!(eval_with(&mut evaluator, source), Ok(Value::Number(12.0)));
+}
+
+#[test]
+fn test_evaluator_usable_after_depth_limit() {
+    let source = format!("{}\ndescend(1000000)", RUNAWAY_RECURSION);
+    let mut evaluator = Evaluator::new();
+    assert!(eval_with(&mut evaluator, &source).is_err());
+
+    // The depth counter unwinds with the error, so shallow calls still work
+    let result = eval_with(&mut evaluator, "descend(5)");
+    assert_eq!(result, Ok(Value::Number(5.0)));
+}
+
+#[test]
+fn test_configured_max_depth() {
+    let source = format!("{}\ndescend(20)", RUNAWAY_RECURSION);
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_max_depth(30);
+    let result = eval_with(&mut evaluator, &source);
+    assert_eq!(result, Err(RuntimeError::DepthLimitExceeded { limit: 30 }));
+
+    let mut evaluator = Evaluator::new();
+    evaluator.set_max_depth(200);
+    assert_eq!(eval_with(&mut evaluator, &source), Ok(Value::Number(20.0)));
+}
+
+#[test]
+fn test_depth_limit_can_be_harmonized() {
+    let source = format!(
+        r#"{}
+        attempt
+            descend(1000000)
+        harmonize on DepthLimitExceeded then
+            -1
+        end
+        "#,
+        RUNAWAY_RECURSION
+    );
+    let mut evaluator = Evaluator::new();
+    // Let evaluation depth, not call depth, stop the recursion
+    evaluator.set_max_depth(200);
+    evaluator.set_max_call_depth(1000);
+    assert_eq!(eval_with(&mut evaluator, &source), Ok(Value::Number(-1.0)));
+}
+
+#[test]
+fn test_stack_overflow_can_be_harmonized() {
+    let source = format!(
+        r#"{}
+        attempt
+            descend(1000000)
+        harmonize on StackOverflow then
+            -1
+        end
+        "#,
+        RUNAWAY_RECURSION
+    );
+    let mut evaluator = Evaluator::new();
+    assert_eq!(eval_with(&mut evaluator, &source), Ok(Value::Number(-1.0)));
+
+    // The call depth unwinds with the error
+    assert_eq!(eval_with(&mut evaluator, "descend(40)"), Ok(Value::Number(40.0)));
+}
+
+#[test]
+fn test_tail_recursion_is_not_limited() {
+    let source = r#"
+        chant count(n, acc) then
+            should n is 0 then
+                yield acc
+            otherwise
+                yield count(n - 1, acc + 1)
+            end
+        end
+
+        count(5000, 0)
+    "#;
+    let mut evaluator = Evaluator::new();
+    assert_eq!(eval_with(&mut evaluator, source), Ok(Value::Number(5000.0)));
+}
+
@@ -1,5 +1,5 @@
-/// Tests for expanded standard library functions
-/// Tests new string, math, and list operations added in glimmer-weave-w19
+//! Tests for expanded standard library functions
+//! Tests new string, math, and list operations added in glimmer-weave-w19
 
 use glimmer_weave::*;
 
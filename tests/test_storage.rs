@@ -0,0 +1,217 @@
+//! Tests for the key-value store builtins and storage providers
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use glimmer_weave::session::Session;
+use glimmer_weave::storage::{StorageProvider, STORE_CAPABILITY};
+use glimmer_weave::{AstNode, Evaluator, Lexer, ModuleResolver, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+const REQUEST: &str = "request Store.readwrite with justification \"remember runs\"\n";
+
+/// Provider the test can look into, as a kernel store would be
+#[derive(Clone, Default)]
+struct Recording(Arc<Mutex<BTreeMap<String, Vec<u8>>>>);
+
+impl StorageProvider for Recording {
+    fn get(&mut self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+
+    fn set(&mut self, key: &str, bytes: &[u8]) -> Result<(), String> {
+        if key.is_empty() {
+            return Err("empty key".to_string());
+        }
+        self.0.lock().unwrap().insert(key.to_string(), bytes.to_vec());
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &str) -> Result<bool, String> {
+        Ok(self.0.lock().unwrap().remove(key).is_some())
+    }
+}
+
+#[test]
+fn test_store_needs_the_capability() {
+    for source in ["store_get(\"runs\")", "store_set(\"runs\", 1)", "Store.delete(\"runs\")"] {
+        let result = Evaluator::new().eval(&parse(source));
+        assert!(
+            matches!(result, Err(RuntimeError::CapabilityDenied { ref capability, .. }) if capability == STORE_CAPABILITY),
+            "{}: {:?}",
+            source,
+            result
+        );
+    }
+}
+
+#[test]
+fn test_get_set_delete() {
+    let source = format!(
+        "{}{}",
+        REQUEST,
+        r#"
+        bind missing to store_get("settings")
+        store_set("settings", { theme: "dark", sizes: [1, 2] })
+        bind found to store_get("settings")
+        bind removed to store_delete("settings")
+        [missing, found, removed, store_delete("settings"), store_get("settings")]
+    "#
+    );
+    let mut settings = BTreeMap::new();
+    settings.insert("theme".to_string(), Value::Text("dark".to_string()));
+    settings.insert("sizes".to_string(), Value::List(vec![Value::Number(1.0), Value::Number(2.0)]));
+    let absent = Value::Maybe { present: false, value: None };
+    assert_eq!(
+        Evaluator::new().eval(&parse(&source)),
+        Ok(Value::List(vec![
+            absent.clone(),
+            Value::Maybe { present: true, value: Some(Box::new(Value::Map(settings))) },
+            Value::Truth(true),
+            Value::Truth(false),
+            absent,
+        ]))
+    );
+}
+
+#[test]
+fn test_state_outlives_the_script_in_the_provider() {
+    let provider = Recording::default();
+    let run = |source: &str| {
+        let mut evaluator = Evaluator::new();
+        evaluator.set_storage_provider(Box::new(provider.clone()));
+        evaluator.eval(&parse(&format!("{}{}", REQUEST, source)))
+    };
+    let count = "weave runs as 0\nmatch store_get(\"runs\") with\n    when Present(n) then set runs to n\n    when Absent then set runs to 0\nend\nstore_set(\"runs\", runs + 1)\nruns + 1";
+    assert_eq!(run(count), Ok(Value::Number(1.0)));
+    assert_eq!(run(count), Ok(Value::Number(2.0)));
+    assert!(provider.0.lock().unwrap().contains_key("runs"));
+
+    // Provider and encoding errors name the builtin
+    let rejected = run("store_set(\"\", 1)");
+    assert_eq!(rejected, Err(RuntimeError::Custom("store_set: empty key".to_string())));
+    let unencodable = run("chant f() then\n    1\nend\nstore_set(\"f\", f)");
+    assert!(matches!(unencodable, Err(RuntimeError::Custom(ref message)) if message.starts_with("store_set: ")));
+}
+
+#[test]
+fn test_session_scripts_share_storage() {
+    let mut session = Session::new(ModuleResolver::new("/app".to_string(), "/std".to_string()));
+    let first = session.run(&format!("{}store_set(\"greeting\", \"hello\")", REQUEST));
+    assert_eq!(first, Ok(Value::Nothing));
+    let second = session.run(&format!("{}store_get(\"greeting\")", REQUEST));
+    assert_eq!(second, Ok(Value::Maybe { present: true, value: Some(Box::new(Value::Text("hello".to_string()))) }));
+}
+
+/// Runs of successive versions of a script against one provider
+fn runner(provider: &Recording) -> impl Fn(&str) -> Result<Value, RuntimeError> + '_ {
+    move |source: &str| {
+        let mut evaluator = Evaluator::new();
+        evaluator.set_storage_provider(Box::new(provider.clone()));
+        evaluator.eval(&parse(&format!("{}{}", REQUEST, source)))
+    }
+}
+
+const MIGRATIONS: &str = r#"
+weave steps as []
+migrate store "config" from 1 to 2 with chant(old) then
+    set steps to list_push(steps, 2)
+    yield { sizes: [old.size] }
+end
+migrate store "config" from 2 to 3 with chant(old) then
+    set steps to list_push(steps, 3)
+    yield { sizes: old.sizes, theme: "dark" }
+end
+"#;
+
+fn present(value: Value) -> Value {
+    Value::Maybe { present: true, value: Some(Box::new(value)) }
+}
+
+fn numbers(values: &[f64]) -> Value {
+    Value::List(values.iter().map(|&n| Value::Number(n)).collect())
+}
+
+#[test]
+fn test_migrations_run_on_first_access_after_an_upgrade() {
+    let provider = Recording::default();
+    let run = runner(&provider);
+    assert_eq!(run("store_set(\"config\", { size: 4 })"), Ok(Value::Nothing));
+
+    let upgraded = run(&format!("{}bind config to store_get(\"config\")\nbind again to store_get(\"config\")\n[config, steps]", MIGRATIONS));
+    let mut config = BTreeMap::new();
+    config.insert("sizes".to_string(), numbers(&[4.0]));
+    config.insert("theme".to_string(), Value::Text("dark".to_string()));
+    assert_eq!(upgraded, Ok(Value::List(vec![present(Value::Map(config)), numbers(&[2.0, 3.0])])));
+    assert_eq!(provider.0.lock().unwrap().get("config@version"), Some(&b"3".to_vec()));
+
+    // Already current: nothing runs
+    assert_eq!(run(&format!("{}store_get(\"config\")\nsteps", MIGRATIONS)), Ok(numbers(&[])));
+}
+
+#[test]
+fn test_writes_record_the_version() {
+    let provider = Recording::default();
+    let run = runner(&provider);
+    assert_eq!(run(&format!("{}store_set(\"config\", {{ sizes: [1] }})\nsteps", MIGRATIONS)), Ok(numbers(&[])));
+    assert_eq!(provider.0.lock().unwrap().get("config@version"), Some(&b"3".to_vec()));
+    assert_eq!(run(&format!("{}store_get(\"config\")\nsteps", MIGRATIONS)), Ok(numbers(&[])));
+
+    assert_eq!(run("store_delete(\"config\")"), Ok(Value::Truth(true)));
+    assert!(provider.0.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_failed_migrations_keep_the_last_version_reached() {
+    let provider = Recording::default();
+    let run = runner(&provider);
+    run("store_set(\"config\", { size: 4 })").unwrap();
+
+    let broken = MIGRATIONS.replace("theme: \"dark\"", "theme: 1 / 0");
+    assert_eq!(run(&format!("{}store_get(\"config\")", broken)), Err(RuntimeError::DivisionByZero));
+    assert_eq!(provider.0.lock().unwrap().get("config@version"), Some(&b"2".to_vec()));
+    assert_eq!(run(&format!("{}store_get(\"config\")\nsteps", MIGRATIONS)), Ok(numbers(&[3.0])));
+}
+
+#[test]
+fn test_unmigratable_versions() {
+    let provider = Recording::default();
+    let run = runner(&provider);
+    run(&format!("{}store_set(\"config\", {{ sizes: [1] }})", MIGRATIONS)).unwrap();
+    let older = run("migrate store \"config\" from 1 to 2 with chant(old) then\n    yield old\nend\nstore_get(\"config\")");
+    assert_eq!(
+        older,
+        Err(RuntimeError::Custom(
+            "migrate store \"config\": \"config\" is stored at version 3, newer than the version 2 this script knows"
+                .to_string()
+        ))
+    );
+
+    run("store_set(\"legacy\", 1)").unwrap();
+    let gap = run("migrate store \"legacy\" from 2 to 3 with chant(old) then\n    yield old\nend\nstore_get(\"legacy\")");
+    assert_eq!(
+        gap,
+        Err(RuntimeError::Custom("migrate store \"legacy\": No migration from version 1 of \"legacy\"".to_string()))
+    );
+}
+
+#[test]
+fn test_migration_syntax_errors() {
+    let error = |source: &str| Parser::new(Lexer::new(source).tokenize_positioned()).parse().unwrap_err().message;
+    assert_eq!(
+        error("migrate store \"config\" from 2 to 2 with chant(old) then\n    yield old\nend"),
+        "A migration must go to a later version, not from 2 to 2"
+    );
+    assert_eq!(
+        error("migrate store \"config\" from 1.5 to 2 with chant(old) then\n    yield old\nend"),
+        "Expected a whole version number"
+    );
+    assert_eq!(
+        error("speaks \"1.2\"\nmigrate store \"config\" from 1 to 2 with chant(old) then\n    yield old\nend"),
+        "language version 1.3 is needed for store migrations, but this script speaks 1.2"
+    );
+}
@@ -0,0 +1,175 @@
+//! Tests for lazy stream iterators over host files and the console
+
+use std::sync::{Arc, Mutex};
+
+use glimmer_weave::capability::AuditEvent;
+use glimmer_weave::stream::StreamHost;
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+/// What the host was asked to do
+#[derive(Debug, Default)]
+struct Log {
+    opened: Vec<String>,
+    reads: usize,
+    closed: Vec<u64>,
+}
+
+/// Every file holds the numbers 1 to 1000, one per line; the console holds
+/// two lines
+struct CountingHost {
+    log: Arc<Mutex<Log>>,
+    positions: Vec<(u64, usize, usize)>,
+}
+
+impl CountingHost {
+    fn new() -> (Self, Arc<Mutex<Log>>) {
+        let log = Arc::new(Mutex::new(Log::default()));
+        (CountingHost { log: log.clone(), positions: Vec::new() }, log)
+    }
+
+    fn open(&mut self, name: &str, length: usize) -> Result<u64, String> {
+        self.log.lock().unwrap().opened.push(name.to_string());
+        let handle = self.positions.len() as u64 + 7;
+        self.positions.push((handle, 0, length));
+        Ok(handle)
+    }
+}
+
+impl StreamHost for CountingHost {
+    fn open_file(&mut self, path: &str) -> Result<u64, String> {
+        self.open(path, 1000)
+    }
+
+    fn open_console(&mut self) -> Result<u64, String> {
+        self.open("console", 2)
+    }
+
+    fn read_line(&mut self, handle: u64) -> Result<Option<String>, String> {
+        self.log.lock().unwrap().reads += 1;
+        let (_, position, length) = self.positions.iter_mut().find(|(h, ..)| *h == handle).ok_or("bad handle")?;
+        if *position == *length {
+            return Ok(None);
+        }
+        *position += 1;
+        Ok(Some(position.to_string()))
+    }
+
+    fn read_byte(&mut self, handle: u64) -> Result<Option<u8>, String> {
+        Ok(self.read_line(handle)?.map(|line| line.len() as u8))
+    }
+
+    fn close(&mut self, handle: u64) -> Result<(), String> {
+        self.log.lock().unwrap().closed.push(handle);
+        Ok(())
+    }
+}
+
+fn counting_evaluator() -> (Evaluator, Arc<Mutex<Log>>) {
+    let (host, log) = CountingHost::new();
+    let mut evaluator = Evaluator::new();
+    evaluator.set_stream_host(Box::new(host));
+    (evaluator, log)
+}
+
+#[test]
+fn test_lines_are_pulled_on_demand() {
+    let (mut evaluator, log) = counting_evaluator();
+    let source = r#"
+        chant head() then
+            request FS.read with justification "read the log"
+            weave seen as []
+            for each line in fs_lines("/var/log/big") then
+                set seen to list_push(seen, line)
+                should list_length(seen) is 3 then
+                    break
+                end
+            end
+            seen
+        end
+        head()
+    "#;
+    let result = evaluator.eval(&parse(source)).unwrap();
+    assert_eq!(result, Value::List(["1", "2", "3"].iter().map(|line| Value::Text(line.to_string())).collect()));
+
+    // Three of the thousand lines were read, and the file was closed when
+    // `head` finished
+    let log = log.lock().unwrap();
+    assert_eq!(log.opened, ["/var/log/big"]);
+    assert_eq!(log.reads, 3);
+    assert_eq!(log.closed, [7]);
+    assert_eq!(evaluator.live_resources(), 0);
+
+    let used = evaluator.capability_audit().for_capability("FS.read").any(|entry| {
+        entry.event == AuditEvent::Used { by: "fs_lines".to_string() } && entry.chant.as_deref() == Some("head")
+    });
+    assert!(used);
+}
+
+#[test]
+fn test_streams_need_a_capability() {
+    let (mut evaluator, log) = counting_evaluator();
+    let result = evaluator.eval(&parse("fs_lines(\"/etc/passwd\")"));
+    assert!(matches!(result, Err(RuntimeError::CapabilityDenied { ref capability, .. }) if capability == "FS.read"));
+
+    let result = evaluator.eval(&parse("request FS.read with justification \"x\"\nconsole_read_lines()"));
+    assert!(matches!(result, Err(RuntimeError::CapabilityDenied { ref capability, .. }) if capability == "Console.read"));
+    assert!(log.lock().unwrap().opened.is_empty());
+}
+
+#[test]
+fn test_stream_iterators_compose_and_outlive_their_chant_when_yielded() {
+    let (mut evaluator, log) = counting_evaluator();
+    let source = r#"
+        request Console.read with justification "prompt"
+        request FS.read with justification "scan"
+
+        chant open_numbers() then
+            yield fs_lines("/numbers")
+        end
+        chant is_round(line) then
+            yield to_number(line) % 100 is 0
+        end
+
+        bind round to iter_collect(iter_take(iter_filter(open_numbers(), is_round), 2))
+        bind typed to iter_collect(console_read_lines())
+        [round, typed]
+    "#;
+    let result = evaluator.eval(&parse(source)).unwrap();
+    let text = |lines: &[&str]| Value::List(lines.iter().map(|line| Value::Text(line.to_string())).collect());
+    assert_eq!(result, Value::List(vec![text(&["100", "200"]), text(&["1", "2"])]));
+
+    // Both streams were closed at the end of the program
+    assert_eq!(log.lock().unwrap().reads, 203);
+    assert_eq!(log.lock().unwrap().closed.len(), 2);
+    assert_eq!(evaluator.live_resources(), 0);
+}
+
+#[test]
+fn test_default_host_reads_files() {
+    let path = std::env::temp_dir().join(format!("glimmer_streams_{}.txt", std::process::id()));
+    std::fs::write(&path, "alpha\nbeta\ngamma\n").unwrap();
+    let source = format!(
+        r#"
+        request FS.read with justification "count"
+        chant add(total, n) then
+            yield total + n
+        end
+        [iter_collect(fs_lines("{path}")), iter_fold(fs_bytes("{path}"), 0, add)]
+        "#,
+        path = path.display()
+    );
+    let result = Evaluator::new().eval(&parse(&source));
+    std::fs::remove_file(&path).ok();
+
+    let lines = Value::List(["alpha", "beta", "gamma"].iter().map(|line| Value::Text(line.to_string())).collect());
+    let byte_sum: u32 = "alpha\nbeta\ngamma\n".bytes().map(u32::from).sum();
+    assert_eq!(result, Ok(Value::List(vec![lines, Value::Number(byte_sum as f64)])));
+
+    let missing = Evaluator::new().eval(&parse("request FS.read with justification \"x\"\nfs_lines(\"/nonexistent/glimmer\")"));
+    assert!(matches!(missing, Err(RuntimeError::Custom(ref message)) if message.starts_with("fs_lines: /nonexistent/glimmer")));
+}
@@ -0,0 +1,57 @@
+//! Tests for running scripts on worker threads with the `sync` feature
+#![cfg(feature = "sync")]
+
+use std::thread;
+
+use glimmer_weave::session::Session;
+use glimmer_weave::{Evaluator, Lexer, ModuleResolver, Parser, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+#[test]
+fn test_values_and_evaluators_cross_threads() {
+    fn send_sync<T: Send + Sync>() {}
+    fn send<T: Send>() {}
+    send_sync::<Value>();
+    send::<Evaluator>();
+    send::<Session>();
+
+    // A text builder keeps sharing its buffer with the copy sent back
+    let mut evaluator = Evaluator::new();
+    let builder = evaluator.eval(&parse("bind b to text_builder()\ntext_push(b, \"made here\")\nb")).unwrap();
+    let worker = thread::spawn(move || {
+        evaluator.define_global("b", builder);
+        evaluator.eval(&parse("text_push(b, \", finished there\")"))
+    });
+    let builder = worker.join().unwrap().unwrap();
+    let text = glimmer_weave::runtime::builder_build(&builder).unwrap();
+    assert_eq!(text, Value::Text("made here, finished there".to_string()));
+}
+
+#[test]
+fn test_session_evaluators_run_in_parallel() {
+    let mut resolver = ModuleResolver::new("/app".to_string(), "/std".to_string());
+    resolver.add_source("/app/lib.gw", "grove Lib with\n    chant square(n) then\n        n * n\n    end\n    offer square\nend");
+    let session = Session::new(resolver);
+
+    let workers: Vec<_> = (1..=4)
+        .map(|n| {
+            let mut evaluator = session.evaluator();
+            let source = format!(
+                "request Store.readwrite with justification \"results\"\nsummon Lib from \"lib.gw\"\nstore_set(\"square {}\", Lib.square({}))\nLib.square({})",
+                n, n, n
+            );
+            thread::spawn(move || evaluator.eval(&parse(&source)))
+        })
+        .collect();
+    let results: Vec<Value> = workers.into_iter().map(|worker| worker.join().unwrap().unwrap()).collect();
+    assert_eq!(results, vec![Value::Number(1.0), Value::Number(4.0), Value::Number(9.0), Value::Number(16.0)]);
+
+    // The workers shared the session's modules and storage
+    assert_eq!(session.module_count(), 1);
+    let stored = session.evaluator().eval(&parse("request Store.readwrite with justification \"read\"\nstore_get(\"square 3\")"));
+    assert_eq!(stored, Ok(Value::Maybe { present: true, value: Some(Box::new(Value::Number(9.0))) }));
+}
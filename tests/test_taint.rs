@@ -0,0 +1,118 @@
+//! Tests for taint tracking of data from sensitive capabilities
+
+use glimmer_weave::runtime::NativeFunction;
+use glimmer_weave::taint::TaintPolicy;
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+/// Host native reading a file through an `FS.read` token
+fn read_file(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::Text("secret".to_string()))
+}
+
+/// Host native reading a count through an `FS.read` token
+fn read_count(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::Number(2.0))
+}
+
+/// Host native sending through a `Net.send` token
+fn upload(_args: &[Value]) -> Result<Value, RuntimeError> {
+    Ok(Value::Nothing)
+}
+
+fn evaluator(policy: TaintPolicy) -> Evaluator {
+    let mut evaluator = Evaluator::new();
+    evaluator.define_global("read_file", Value::NativeChant(NativeFunction::new("read_file", Some(2), read_file)));
+    evaluator.define_global("read_count", Value::NativeChant(NativeFunction::new("read_count", Some(2), read_count)));
+    evaluator.define_global("upload", Value::NativeChant(NativeFunction::new("upload", Some(2), upload)));
+    evaluator.set_output_sink(Box::new(|_: &str| {}));
+    evaluator.set_taint_policy(policy);
+    evaluator
+}
+
+const PRELUDE: &str = "\
+bind disk to request FS.read with justification \"config\"
+bind net to request Net.send with justification \"report\"
+";
+
+#[test]
+fn test_flow_to_sink_is_flagged() {
+    let mut evaluator = evaluator(TaintPolicy::new());
+    let source = format!(
+        "{}bind text to read_file(disk, \"/etc/config\")\nbind report to [\"config: \" + text]\nupload(net, report[0])",
+        PRELUDE
+    );
+    evaluator.eval(&parse(&source)).expect("flagging lets the call through");
+
+    let violations = evaluator.taint_violations();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].sources, ["FS.read"]);
+    assert_eq!(violations[0].sink, "Net.send");
+    assert_eq!(violations[0].by, "upload");
+    assert_eq!(violations[0].span.start.line, 5);
+}
+
+#[test]
+fn test_blocking_policy_denies_the_sink() {
+    let mut evaluator = evaluator(TaintPolicy::new().block());
+    let source = format!(
+        "{}for each word in [read_file(disk, \"/a\")] then\n    println(\"read \" + word)\nend",
+        PRELUDE
+    );
+    match evaluator.eval(&parse(&source)) {
+        Err(RuntimeError::CapabilityDenied { capability, .. }) => assert_eq!(capability, "Console.write"),
+        other => panic!("expected a denial, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_sanitizer_cleans_data() {
+    let mut evaluator = evaluator(TaintPolicy::new().sanitizer("redact").block());
+    let source = format!(
+        "{}chant redact(text) then\n    \"[\" + to_text(length(text)) + \" chars]\"\nend\nupload(net, redact(read_file(disk, \"/a\")))\nprintln(redact(read_file(disk, \"/b\")))",
+        PRELUDE
+    );
+    evaluator.eval(&parse(&source)).expect("sanitized data may reach sinks");
+    assert!(evaluator.taint_violations().is_empty());
+}
+
+#[test]
+fn test_clean_data_and_untracked_evaluators() {
+    // Data that never touched a source reaches sinks freely
+    let mut evaluator = evaluator(TaintPolicy::new().block());
+    let source = format!("{}upload(net, \"hello\")\nread_file(disk, \"/a\")", PRELUDE);
+    let result = evaluator.eval(&parse(&source)).unwrap();
+    assert_eq!(result.taint_sources(), ["FS.read"]);
+    assert_eq!(result.untainted(), Value::Text("secret".to_string()));
+
+    // Without a policy nothing is marked
+    let mut evaluator = Evaluator::new();
+    evaluator.define_global("read_file", Value::NativeChant(NativeFunction::new("read_file", Some(2), read_file)));
+    let source = format!("{}read_file(disk, \"/a\")", PRELUDE);
+    assert_eq!(evaluator.eval(&parse(&source)).unwrap(), Value::Text("secret".to_string()));
+}
+
+#[test]
+fn test_tainted_index_and_range_bounds() {
+    let mut evaluator = evaluator(TaintPolicy::new());
+    let source = format!("{}bind i to read_count(disk, \"/n\")\n[10, 20, 30][i]", PRELUDE);
+    let result = evaluator.eval(&parse(&source)).expect("a tainted index still indexes");
+    assert_eq!(result.taint_sources(), ["FS.read"]);
+    assert_eq!(result.untainted(), Value::Number(30.0));
+
+    let source = format!("{}bind n to read_count(disk, \"/n\")\nrange(0, n)", PRELUDE);
+    let result = evaluator.eval(&parse(&source)).expect("a tainted bound still builds a range");
+    assert_eq!(result.taint_sources(), ["FS.read"]);
+    assert_eq!(
+        result.untainted(),
+        Value::Range { start: Box::new(Value::Number(0.0)), end: Box::new(Value::Number(2.0)) }
+    );
+
+    let source = format!("{}bind n to read_count(disk, \"/n\")\nweave total as 0\nfor each k in range(0, n) then\n    set total to total + k\nend\ntotal", PRELUDE);
+    let result = evaluator.eval(&parse(&source)).expect("a tainted range still iterates");
+    assert_eq!(result.untainted(), Value::Number(1.0));
+}
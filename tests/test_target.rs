@@ -0,0 +1,91 @@
+//! Tests for compilation target descriptors
+//!
+//! Covers the ELF identification and byte order each target produces, and
+//! how the code generator follows a target's calling convention.
+
+use glimmer_weave::codegen::{compile_to_asm, compile_to_asm_for, CodeGen};
+use glimmer_weave::elf::{create_elf_object, create_elf_object_for, ElfBuilder};
+use glimmer_weave::pipeline::{Output, Target};
+use glimmer_weave::target::{CallingConvention, Endian, OsAbi, TargetSpec};
+use glimmer_weave::{AstNode, CompilerPipeline, Lexer, Parser};
+
+const CODE: [u8; 1] = [0xC3]; // ret
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().unwrap()
+}
+
+#[test]
+fn test_default_objects_are_unchanged() {
+    let object = create_elf_object(&CODE, "main");
+    assert_eq!(create_elf_object_for(&TargetSpec::X86_64_SYSV, &CODE, "main").unwrap(), object);
+    assert_eq!(object[5], 1); // little endian
+    assert_eq!(object[7], 0); // System V
+    assert_eq!(u16::from_le_bytes([object[18], object[19]]), 62);
+}
+
+#[test]
+fn test_objects_carry_the_target_identity() {
+    let aarch64 = create_elf_object_for(&TargetSpec::AARCH64_SYSV, &CODE, "main").unwrap();
+    assert_eq!(u16::from_le_bytes([aarch64[18], aarch64[19]]), 183);
+
+    let kernel = TargetSpec { os_abi: OsAbi::Standalone, ..TargetSpec::X86_64_SYSV };
+    assert_eq!(create_elf_object_for(&kernel, &CODE, "main").unwrap()[7], 255);
+}
+
+#[test]
+fn test_big_endian_targets_swap_every_field() {
+    let big = TargetSpec { name: "aarch64_be-sysv", endian: Endian::Big, ..TargetSpec::AARCH64_SYSV };
+    let little = create_elf_object_for(&TargetSpec::AARCH64_SYSV, &CODE, "main").unwrap();
+    let object = create_elf_object_for(&big, &CODE, "main").unwrap();
+
+    assert_eq!(object.len(), little.len());
+    assert_eq!(object[5], 2);
+    assert_eq!(u16::from_be_bytes([object[18], object[19]]), 183);
+    // Section header offset, at byte 40 of the header
+    assert_eq!(object[40..48].iter().rev().collect::<Vec<_>>(), little[40..48].iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_unsupported_targets_are_rejected() {
+    let narrow = TargetSpec { name: "i386-sysv", pointer_width: 32, ..TargetSpec::X86_64_SYSV };
+    assert!(ElfBuilder::for_target(&narrow).err().unwrap().contains("ELF64"));
+    assert!(CodeGen::for_target(&narrow).is_err());
+
+    let error = CodeGen::for_target(&TargetSpec::AARCH64_SYSV).err().unwrap();
+    assert!(error.contains("aarch64-sysv"), "{}", error);
+    assert!(CodeGen::for_target(&TargetSpec::X86_64_SYSV).is_ok());
+}
+
+#[test]
+fn test_codegen_follows_the_calling_convention() {
+    let program = parse("chant sum(a, b, c, d, e) then\n    yield a + e\nend\nsum(1, 2, 3, 4, 5)");
+    assert_eq!(compile_to_asm_for(&program, &TargetSpec::X86_64_SYSV, None), compile_to_asm(&program));
+
+    // Four argument registers, so the fifth argument goes on the stack
+    let four = TargetSpec {
+        name: "x86_64-four",
+        calls: CallingConvention { arg_registers: &["rcx", "rdx", "r8", "r9"], return_register: "rax" },
+        ..TargetSpec::X86_64_SYSV
+    };
+    let asm = compile_to_asm_for(&program, &four, None).unwrap();
+    assert!(asm.contains("movq %rcx, -8(%rbp)"), "{}", asm);
+    assert!(!asm.contains("%rdi"), "{}", asm);
+    // The fifth parameter is read above the saved registers and return address
+    assert!(asm.contains("movq 24(%rbp), %rax"), "{}", asm);
+}
+
+#[test]
+fn test_pipeline_compiles_for_the_chosen_target() {
+    let kernel = TargetSpec { name: "x86_64-aethelos", os_abi: OsAbi::Standalone, ..TargetSpec::X86_64_SYSV };
+    let mut pipeline = CompilerPipeline::new().target_spec(kernel).assembler(|_| Ok(CODE.to_vec()));
+    match pipeline.run("40 + 2", Target::Elf) {
+        Ok(Output::Elf(bytes)) => assert_eq!(bytes[7], 255),
+        other => panic!("Expected an ELF object, got {:?}", other),
+    }
+
+    let mut arm = CompilerPipeline::new().target_spec(TargetSpec::AARCH64_SYSV);
+    let diagnostics = arm.run("40 + 2", Target::Asm).unwrap_err();
+    assert!(diagnostics.to_string().contains("No code generator"));
+}
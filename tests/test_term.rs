@@ -0,0 +1,109 @@
+//! Tests for the `Term` module: terminal control behind capabilities, over
+//! the ANSI and VGA backends
+
+use glimmer_weave::sync::{lock, shared, Shared};
+use glimmer_weave::term::{AnsiTerminal, VgaConsole, VgaTerminal};
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+/// Evaluator whose output lands in the returned buffer
+fn evaluator() -> (Evaluator, Shared<String>) {
+    let written = shared(String::new());
+    let sink = Shared::clone(&written);
+    let mut evaluator = Evaluator::new();
+    evaluator.set_output_sink(Box::new(move |text: &str| lock(&sink).push_str(text)));
+    (evaluator, written)
+}
+
+#[test]
+fn test_ansi_sequences_interleave_with_output() {
+    let (mut evaluator, written) = evaluator();
+    evaluator.set_terminal(Box::new(AnsiTerminal::new()));
+    let source = r#"
+        request Term.control with justification "draw the menu"
+        Term.clear()
+        Term.move_to(2, 4)
+        Term.set_color("bright_yellow", "blue")
+        print("Menu")
+        Term.set_color("white")
+    "#;
+    evaluator.eval(&parse(source)).unwrap();
+    assert_eq!(*lock(&written), "\x1b[2J\x1b[H\x1b[3;5H\x1b[93;44mMenu\x1b[37m");
+}
+
+#[test]
+fn test_terminal_control_needs_a_grant() {
+    let (mut evaluator, written) = evaluator();
+    match evaluator.eval(&parse("Term.clear()")) {
+        Err(RuntimeError::CapabilityDenied { capability, .. }) => assert_eq!(capability, "Term.control"),
+        other => panic!("expected a denial, got {:?}", other),
+    }
+    // Drawing does not grant reading the keyboard
+    let source = "request Term.control with justification \"draw\"\nTerm.read_key()";
+    match evaluator.eval(&parse(source)) {
+        Err(RuntimeError::CapabilityDenied { capability, .. }) => assert_eq!(capability, "Console.read"),
+        other => panic!("expected a denial, got {:?}", other),
+    }
+    assert!(lock(&written).is_empty());
+}
+
+#[test]
+fn test_bad_arguments_are_errors() {
+    let (mut evaluator, _) = evaluator();
+    evaluator.eval(&parse("request Term.control with justification \"draw\"")).unwrap();
+    assert!(matches!(evaluator.eval(&parse("Term.set_color(\"mauve\")")), Err(RuntimeError::Custom(_))));
+    assert!(matches!(evaluator.eval(&parse("Term.move_to(-1, 0)")), Err(RuntimeError::TypeError { .. })));
+    assert!(matches!(evaluator.eval(&parse("Term.set_color()")), Err(RuntimeError::ArityMismatch { .. })));
+}
+
+/// Text-mode screen recording what the terminal did to it
+#[derive(Default)]
+struct Screen {
+    calls: Vec<String>,
+    keys: Vec<&'static str>,
+}
+
+struct MockVga(Shared<Screen>);
+
+impl VgaConsole for MockVga {
+    fn set_cursor(&mut self, row: u32, column: u32) {
+        lock(&self.0).calls.push(format!("cursor {} {}", row, column));
+    }
+
+    fn set_attribute(&mut self, attribute: u8) {
+        lock(&self.0).calls.push(format!("attribute {:#04x}", attribute));
+    }
+
+    fn clear(&mut self) {
+        lock(&self.0).calls.push("clear".to_string());
+    }
+
+    fn read_key(&mut self) -> Option<String> {
+        lock(&self.0).keys.pop().map(String::from)
+    }
+}
+
+#[test]
+fn test_vga_backend_drives_the_console() {
+    let screen = shared(Screen { keys: vec!["q", "down"], ..Screen::default() });
+    let (mut evaluator, written) = evaluator();
+    evaluator.set_terminal(Box::new(VgaTerminal::new(MockVga(Shared::clone(&screen)))));
+    let source = r#"
+        request Term.control with justification "dashboard"
+        request Console.read with justification "menu keys"
+        Term.clear()
+        Term.set_color("red", "white")
+        Term.set_color("bright_green")
+        Term.move_to(24, 79)
+        [Term.read_key(), Term.read_key(), Term.read_key()]
+    "#;
+    let keys = evaluator.eval(&parse(source)).unwrap();
+    let present = |key: &str| Value::Maybe { present: true, value: Some(Box::new(Value::Text(key.to_string()))) };
+    assert_eq!(keys, Value::List(vec![present("down"), present("q"), Value::Maybe { present: false, value: None }]));
+    assert_eq!(lock(&screen).calls, ["clear", "cursor 0 0", "attribute 0x74", "attribute 0x7a", "cursor 24 79"]);
+    assert!(lock(&written).is_empty());
+}
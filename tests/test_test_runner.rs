@@ -0,0 +1,100 @@
+//! Tests for `verify` blocks and the module-graph test runner
+
+use glimmer_weave::clock::StepClock;
+use glimmer_weave::test_runner::TestRunner;
+use glimmer_weave::{Evaluator, Lexer, ModuleResolver, Parser, Value};
+
+const MATH: &str = r#"
+grove Math with
+    chant halve(x) then
+        x / 2
+    end
+
+    chant double(x) then
+        x * 2
+    end
+    offer double
+
+    verify "doubles" then
+        double(4) is 8
+    end
+
+    verify "halves privately" then
+        println("halving", 10)
+        halve(10) is 4
+    end
+end
+"#;
+
+const MAIN: &str = r#"
+summon Math from "math.gw"
+bind answer to Math.double(21)
+
+verify "answers" then
+    answer is 42
+end
+
+verify "fails loudly" then
+    Math.missing(1)
+end
+
+verify "ends with a mishap" then
+    Mishap("not yet")
+end
+"#;
+
+fn resolver() -> ModuleResolver {
+    let mut resolver = ModuleResolver::new("/app".to_string(), "/std".to_string());
+    resolver.add_source("/app/math.gw", MATH);
+    resolver.add_source("/app/main.gw", MAIN);
+    resolver
+}
+
+#[test]
+fn test_verify_blocks_are_skipped_when_running() {
+    let tokens = Lexer::new("bind x to 1\nverify \"never runs\" then\n    x / 0\nend\nx + 1").tokenize_positioned();
+    let nodes = Parser::new(tokens).parse().unwrap();
+    assert_eq!(Evaluator::new().eval(&nodes), Ok(Value::Number(2.0)));
+
+    // `verify` is still an ordinary name
+    let tokens = Lexer::new("bind verify to 3\nverify + 1").tokenize_positioned();
+    let nodes = Parser::new(tokens).parse().unwrap();
+    assert_eq!(Evaluator::new().eval(&nodes), Ok(Value::Number(4.0)));
+}
+
+#[test]
+fn test_runner_walks_the_module_graph() {
+    let report = TestRunner::new(resolver()).run(&["/app/main.gw"]).unwrap();
+
+    // Dependencies come first
+    let paths: Vec<&str> = report.modules.iter().map(|module| module.path.as_str()).collect();
+    assert_eq!(paths, ["/app/math.gw", "/app/main.gw"]);
+
+    let math = &report.modules[0];
+    assert_eq!(math.results.len(), 2);
+    assert!(math.results[0].passed());
+    // Blocks in a grove see its unexported chants; 10 / 2 is not 4
+    assert_eq!(math.results[1].name, "halves privately");
+    assert_eq!(math.results[1].failure.as_deref(), Some("ended with false"));
+    assert_eq!(math.results[1].output, "halving 10\n");
+
+    let main = &report.modules[1];
+    assert!(main.results[0].passed());
+    assert!(main.results[1].failure.is_some());
+    assert!(main.results[2].failure.as_deref().unwrap().contains("not yet"));
+
+    assert_eq!((report.passed(), report.failed()), (2, 3));
+    assert!(!report.is_success());
+}
+
+#[test]
+fn test_shared_modules_run_once_and_are_timed() {
+    let mut runner = TestRunner::new(resolver());
+    runner.set_clock(Box::new(StepClock::new()));
+    let report = runner.run(&["/app/math.gw", "/app/main.gw"]).unwrap();
+
+    assert_eq!(report.modules.len(), 2);
+    assert!(report.modules.iter().all(|module| module.elapsed == 1));
+
+    assert!(TestRunner::new(resolver()).run(&["/app/missing.gw"]).is_err());
+}
@@ -0,0 +1,135 @@
+//! Tests for text builders in the interpreter and the VM
+
+use glimmer_weave::bytecode::BYTECODE_VERSION;
+use glimmer_weave::bytecode_compiler::compile;
+use glimmer_weave::bytecode_image::{decode, encode};
+use glimmer_weave::vm::{VmError, VM};
+use glimmer_weave::{AstNode, Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn run_eval(source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval(&parse(source))
+}
+
+fn run_vm(source: &str) -> Value {
+    VM::new().execute(compile(&parse(source)).unwrap()).unwrap()
+}
+
+const REPORT: &str = r#"
+    weave report as text_builder()
+    weave i as 0
+    whilst i less than 5 then
+        text_push(report, i)
+        text_push_text(report, ";")
+        set i to i + 1
+    end
+    text_push(text_push(report, true), "!")
+    text_build(report)
+"#;
+
+#[test]
+fn test_interpreter_and_vm_build_the_same_text() {
+    let expected = Value::Text("0;1;2;3;4;true!".to_string());
+    assert_eq!(run_eval(REPORT), Ok(expected.clone()));
+    assert_eq!(run_vm(REPORT), expected);
+}
+
+#[test]
+fn test_builder_filled_by_a_loop() {
+    let source = r#"
+        bind report to text_builder()
+        for each n in range(1, 4) then
+            text_push(report, n)
+            text_push_text(report, "\n")
+        end
+        text_build(report)
+    "#;
+    assert_eq!(run_eval(source), Ok(Value::Text("1\n2\n3\n".to_string())));
+}
+
+#[test]
+fn test_large_output() {
+    let source = r#"
+        weave out as text_builder()
+        weave i as 0
+        whilst i less than 20000 then
+            text_push_text(out, "line\n")
+            set i to i + 1
+        end
+        text_build(out)
+    "#;
+    for result in [run_eval(source).unwrap(), run_vm(source)] {
+        match result {
+            Value::Text(text) => assert_eq!(text.len(), 100_000),
+            other => panic!("expected Text, got {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_copies_share_the_buffer() {
+    let source = r#"
+        bind original to text_builder()
+        bind copy to original
+        text_push_text(copy, "shared")
+        bind built to text_build(original)
+        text_push_text(original, " and more")
+        [built, Text.build(copy), type_of(original)]
+    "#;
+    let text = |s: &str| Value::Text(s.to_string());
+    assert_eq!(
+        run_eval(source),
+        Ok(Value::List(vec![text("shared"), text("shared and more"), text("TextBuilder")]))
+    );
+}
+
+#[test]
+fn test_pushes_use_display() {
+    let source = r#"
+        form Point with
+            x as Number
+            y as Number
+        end
+        embody Display for Point then
+            chant describe(self) -> Text then
+                yield "(" + to_text(self.x) + ", " + to_text(self.y) + ")"
+            end
+        end
+        bind out to text_builder()
+        text_push(out, Point { x: 1, y: 2 })
+        text_build(out)
+    "#;
+    assert_eq!(run_eval(source), Ok(Value::Text("(1, 2)".to_string())));
+}
+
+#[test]
+fn test_type_errors() {
+    let pushed_number = run_eval("text_push_text(text_builder(), 5)");
+    assert!(matches!(pushed_number, Err(RuntimeError::TypeError { ref expected, .. }) if expected == "Text"));
+    let not_a_builder = run_eval("text_build(\"plain\")");
+    assert!(matches!(not_a_builder, Err(RuntimeError::TypeError { ref expected, .. }) if expected == "TextBuilder"));
+
+    let vm = VM::new().execute(compile(&parse("text_push_text(text_builder(), 5)")).unwrap());
+    assert!(matches!(vm, Err(VmError::TypeError(_))));
+}
+
+#[test]
+fn test_builder_instructions_need_version_3() {
+    let chunk = compile(&parse(REPORT)).unwrap();
+    assert_eq!(chunk.version, BYTECODE_VERSION);
+
+    let decoded = decode(&encode(&chunk)).unwrap();
+    assert_eq!(decoded.instructions, chunk.instructions);
+    assert_eq!(VM::new().execute(decoded).unwrap(), Value::Text("0;1;2;3;4;true!".to_string()));
+
+    let mut old = chunk;
+    old.version = 2;
+    assert!(matches!(
+        VM::new().execute(old),
+        Err(VmError::UnsupportedOpcode { since: 3, version: 2, .. })
+    ));
+}
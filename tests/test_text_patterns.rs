@@ -0,0 +1,99 @@
+//! Tests for `starts`/`ends` text patterns in the interpreter and the VM
+//!
+//! The VM has no match-arm locals yet, so binding the rest is only checked
+//! in the interpreter.
+
+use glimmer_weave::bytecode::Instruction;
+use glimmer_weave::bytecode_compiler::compile;
+use glimmer_weave::vm::VM;
+use glimmer_weave::{run, AstNode, EvalOptions, Evaluator, Lexer, Parser, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn eval(source: &str) -> Value {
+    Evaluator::new().eval(&parse(source)).unwrap()
+}
+
+fn classify(line: &str) -> String {
+    format!(
+        r##"
+        bind line to "{}"
+        match line with
+            when starts "gw:" bound command then "command " + command
+            when ends ".gw" bound stem then "script " + stem
+            when starts "#" then "comment"
+            otherwise then "other"
+        end
+    "##,
+        line
+    )
+}
+
+#[test]
+fn test_prefix_and_suffix_bind_the_rest() {
+    let text = |s: &str| Value::Text(s.to_string());
+    assert_eq!(eval(&classify("gw:build")), text("command build"));
+    assert_eq!(eval(&classify("boot.gw")), text("script boot"));
+    assert_eq!(eval(&classify("# note")), text("comment"));
+    assert_eq!(eval(&classify("gw")), text("other"));
+    // The first arm that matches wins, and an empty rest still matches
+    assert_eq!(eval(&classify("gw:.gw")), text("command .gw"));
+    assert_eq!(eval(&classify("gw:")), text("command "));
+
+    // Semantic analysis knows the bound name
+    assert_eq!(run(&classify("gw:test"), EvalOptions::new()).unwrap(), text("command test"));
+}
+
+#[test]
+fn test_affix_patterns_only_match_text() {
+    let source = "match 42 with\n    when starts \"4\" then \"text\"\n    otherwise then \"number\"\nend";
+    assert_eq!(eval(source), Value::Text("number".to_string()));
+}
+
+#[test]
+fn test_vm_matches_like_the_interpreter() {
+    for (line, kind) in [("gw:run", 1.0), ("main.gw", 2.0), ("gw", 3.0), ("7", 3.0)] {
+        let source = format!(
+            "weave kind as 0\nbind line to \"{}\"\nmatch line with\n    when starts \"gw:\" then set kind to 1\n    when ends \".gw\" then set kind to 2\n    otherwise then set kind to 3\nend\nkind",
+            line
+        );
+        assert_eq!(eval(&source), Value::Number(kind), "{}", line);
+        assert_eq!(VM::new().execute(compile(&parse(&source)).unwrap()).unwrap(), Value::Number(kind), "{}", line);
+    }
+}
+
+#[test]
+fn test_affix_patterns_in_chants() {
+    let source = r#"
+        chant strip_scheme(url) then
+            match url with
+                when starts "https://" bound rest then rest
+                when starts "http://" bound rest then rest
+                otherwise then url
+            end
+        end
+        [strip_scheme("https://aethel.os"), strip_scheme("http://a"), strip_scheme("file")]
+    "#;
+    let hosts = ["aethel.os", "a", "file"].iter().map(|s| Value::Text(s.to_string())).collect();
+    assert_eq!(Evaluator::new().eval(&parse(source)), Ok(Value::List(hosts)));
+}
+
+#[test]
+fn test_compiles_to_strip_affix() {
+    let chunk = compile(&parse(&classify("x.gw"))).unwrap();
+    let suffixes: Vec<bool> = chunk
+        .instructions
+        .iter()
+        .filter_map(|instruction| match instruction {
+            Instruction::StripAffix { suffix, .. } => Some(*suffix),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(suffixes, [false, true, false]);
+
+    // `starts` and `ends` stay ordinary names
+    assert_eq!(eval("bind starts to 1\nbind ends to 2\nstarts + ends"), Value::Number(3.0));
+}
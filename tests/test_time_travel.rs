@@ -0,0 +1,93 @@
+//! Tests for recording evaluation and stepping back through it
+
+use glimmer_weave::time_travel::{Debugger, Recording};
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn record(source: &str, recording: Recording) -> (Result<Value, RuntimeError>, Recording) {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().expect("Parse error");
+    let mut evaluator = Evaluator::new();
+    evaluator.start_recording(recording);
+    let result = evaluator.eval(&ast);
+    (result, evaluator.take_recording().expect("recording"))
+}
+
+#[test]
+fn test_steps_record_changes_and_chants() {
+    let source = "weave x as 1\nchant bump(n) then\n    bind doubled to n * 2\n    yield doubled\nend\nset x to bump(x)\n";
+    let (result, recording) = record(source, Recording::new());
+    assert!(result.is_ok());
+
+    let kinds: Vec<&str> = recording.steps().map(|step| step.kind).collect();
+    assert_eq!(kinds, vec!["WeaveStmt", "ChantDef", "BindStmt", "YieldStmt", "SetStmt"]);
+
+    let bind = recording.step(2).unwrap();
+    assert_eq!(bind.chant.as_deref(), Some("bump"));
+    assert!(bind.changed("doubled"));
+    assert!(bind.changed("n"));
+
+    // Leaving the chant takes its locals out of scope
+    let set = recording.step(4).unwrap();
+    let names: Vec<(&str, bool)> = set.changes.iter().map(|change| (change.name.as_str(), change.after.is_some())).collect();
+    assert_eq!(names, vec![("doubled", false), ("n", false), ("x", true)]);
+    assert_eq!(recording.value_at(0, "x"), Some(Value::Number(1.0)));
+    assert_eq!(recording.value_at(4, "x"), Some(Value::Number(2.0)));
+    assert_eq!(recording.value_at(4, "doubled"), None);
+}
+
+#[test]
+fn test_debugger_walks_back_from_a_failure() {
+    let source = r#"weave divisor as 4
+weave steps as 0
+whilst steps less than 2 then
+    set divisor to divisor - 2
+    set steps to steps + 1
+end
+bind ratio to 10 / divisor
+"#;
+    let (result, recording) = record(source, Recording::new());
+    assert!(result.is_err());
+
+    let mut debugger = Debugger::new(&recording);
+    let failure = debugger.jump_to_failure().unwrap();
+    assert_eq!(failure.kind, "BindStmt");
+    assert!(matches!(failure.error, Some(RuntimeError::DivisionByZero)));
+    assert_eq!(debugger.value("divisor"), Some(Value::Number(0.0)));
+
+    let change = debugger.jump_to_last_change("divisor").unwrap();
+    assert_eq!(change.span.start.line, 4);
+    assert_eq!(debugger.value("steps"), Some(Value::Number(1.0)));
+
+    debugger.step_back();
+    debugger.step_back();
+    assert_eq!(debugger.value("divisor"), Some(Value::Number(2.0)));
+    assert_eq!(debugger.bindings().get("steps"), Some(&Value::Number(0.0)));
+
+    assert!(debugger.step_forward().is_some());
+    while debugger.step_forward().is_some() {}
+    assert_eq!(debugger.current().map(|step| step.index), Some(recording.len() - 1));
+}
+
+#[test]
+fn test_old_steps_are_dropped_past_the_limit() {
+    let source = "weave i as 0\nwhilst i less than 50 then\n    set i to i + 1\nend\n";
+    let (_, recording) = record(source, Recording::with_max_steps(10));
+    assert_eq!(recording.len(), 10);
+    let first = recording.steps().next().unwrap().index;
+    assert!(recording.step(first - 1).is_none());
+    assert_eq!(recording.value_at(first, "i"), Some(Value::Number(42.0)));
+
+    let mut debugger = Debugger::new(&recording);
+    for _ in 0..20 {
+        debugger.step_back();
+    }
+    assert_eq!(debugger.current().map(|step| step.index), Some(first));
+}
+
+#[test]
+fn test_evaluation_without_recording_keeps_nothing() {
+    let mut evaluator = Evaluator::new();
+    let ast = Parser::new(Lexer::new("bind x to 1").tokenize_positioned()).parse().unwrap();
+    evaluator.eval(&ast).unwrap();
+    assert!(evaluator.recording().is_none());
+}
@@ -0,0 +1,102 @@
+//! Tests for `together` scopes
+
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+const WORKERS: &str = r#"
+weave log as []
+chant note(x) then
+    set log to list_push(log, x)
+end
+chant broken() then
+    yield 1 / 0
+end
+"#;
+
+/// Run `source` after the worker chants, then drain leftover tasks and
+/// return the script's result and the log
+fn run(source: &str) -> (Result<Value, RuntimeError>, Value) {
+    let mut evaluator = Evaluator::new();
+    evaluator.eval(&parse(WORKERS)).unwrap();
+    let result = evaluator.eval(&parse(source));
+    evaluator.run_until_idle().unwrap();
+    assert_eq!(evaluator.together_depth(), 0);
+    (result, evaluator.eval(&parse("log")).unwrap())
+}
+
+fn numbers(values: &[f64]) -> Value {
+    Value::List(values.iter().map(|&n| Value::Number(n)).collect())
+}
+
+#[test]
+fn test_scope_waits_for_its_tasks() {
+    let (result, _) = run("together\n    spawn(note, 1)\n    spawn(note, 2)\nend\nlist_push(log, 3)");
+    assert_eq!(result, Ok(numbers(&[1.0, 2.0, 3.0])));
+}
+
+#[test]
+fn test_tasks_spawned_by_tasks_belong_to_the_scope() {
+    let source = r#"
+chant fan_out() then
+    spawn(note, 2)
+    note(1)
+end
+together
+    spawn(fan_out)
+end
+list_push(log, 3)
+"#;
+    let (result, _) = run(source);
+    assert_eq!(result, Ok(numbers(&[1.0, 2.0, 3.0])));
+}
+
+#[test]
+fn test_body_error_cancels_pending_tasks() {
+    let (result, log) = run("together\n    spawn(note, 1)\n    bind oops to 1 / 0\nend");
+    assert_eq!(result, Err(RuntimeError::DivisionByZero));
+    assert_eq!(log, numbers(&[]));
+}
+
+#[test]
+fn test_task_error_cancels_siblings() {
+    let (result, log) = run("together\n    spawn(note, 1)\n    spawn(broken)\n    spawn(note, 2)\nend");
+    assert_eq!(result, Err(RuntimeError::DivisionByZero));
+    assert_eq!(log, numbers(&[1.0]));
+}
+
+#[test]
+fn test_early_yield_cancels_pending_tasks() {
+    let source = r#"
+chant begin() then
+    together
+        spawn(note, 1)
+        yield 0
+    end
+end
+begin()
+"#;
+    let (result, log) = run(source);
+    assert_eq!(result, Ok(Value::Number(0.0)));
+    assert_eq!(log, numbers(&[]));
+}
+
+#[test]
+fn test_tasks_outside_the_scope_are_left_alone() {
+    let source = "spawn(note, 1)\ntogether\n    bind x to 1\nend\nlog";
+    let mut evaluator = Evaluator::new();
+    evaluator.eval(&parse(WORKERS)).unwrap();
+    assert_eq!(evaluator.eval(&parse(source)), Ok(numbers(&[])));
+    evaluator.run_until_idle().unwrap();
+    assert_eq!(evaluator.eval(&parse("log")), Ok(numbers(&[1.0])));
+}
+
+#[test]
+fn test_needs_language_version_1_3() {
+    let tokens = Lexer::new("speaks \"1.2\"\ntogether\n    spawn(note, 1)\nend\n").tokenize_positioned();
+    let error = Parser::new(tokens).parse().unwrap_err();
+    assert_eq!(error.message, "language version 1.3 is needed for `together` scopes, but this script speaks 1.2");
+}
@@ -1,5 +1,5 @@
-/// Comprehensive integration tests for trait system (Phase 4)
-/// Tests complex scenarios combining traits with other language features
+//! Comprehensive integration tests for trait system (Phase 4)
+//! Tests complex scenarios combining traits with other language features
 
 use glimmer_weave::{Evaluator, Lexer, Parser};
 
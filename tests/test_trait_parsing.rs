@@ -1,5 +1,5 @@
-/// Tests for trait system parsing (Phase 1)
-/// Verifies that aspect definitions and embody statements can be parsed correctly
+//! Tests for trait system parsing (Phase 1)
+//! Verifies that aspect definitions and embody statements can be parsed correctly
 
 use glimmer_weave::{Lexer, Parser};
 
@@ -8,7 +8,7 @@ fn parse_source(source: &str) -> Result<(), String> {
     let tokens = lexer.tokenize_positioned();
 
     let mut parser = Parser::new(tokens);
-    let ast = parser.parse().map_err(|e| format!("Parse error: {:?}", e))?;
+    parser.parse().map_err(|e| format!("Parse error: {:?}", e))?;
 
     Ok(())
 }
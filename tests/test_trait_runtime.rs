@@ -1,5 +1,5 @@
-/// Tests for trait system runtime dispatch (Phase 3)
-/// Verifies that trait methods can be called and execute correctly at runtime
+//! Tests for trait system runtime dispatch (Phase 3)
+//! Verifies that trait methods can be called and execute correctly at runtime
 
 use glimmer_weave::{Evaluator, Lexer, Parser};
 
@@ -1,5 +1,5 @@
-/// Tests for trait system semantic analysis (Phase 2)
-/// Verifies that trait definitions and implementations are properly validated
+//! Tests for trait system semantic analysis (Phase 2)
+//! Verifies that trait definitions and implementations are properly validated
 
 use glimmer_weave::{Lexer, Parser, SemanticAnalyzer};
 
@@ -179,6 +179,40 @@ fn test_valid_multiple_methods_implementation() {
     assert!(result.is_ok(), "Failed: {:?}", result);
 }
 
+#[test]
+fn test_builtin_convert_error_aspect() {
+    let source = r#"
+        embody ConvertError<Text> for Number then
+            chant convert(self) -> Text then
+                yield to_text(self)
+            end
+        end
+    "#;
+
+    let result = analyze_source(source);
+    assert!(result.is_ok(), "Failed: {:?}", result);
+}
+
+#[test]
+fn test_convert_error_to_two_targets() {
+    let source = r#"
+        embody ConvertError<Text> for Number then
+            chant convert(self) -> Text then
+                yield to_text(self)
+            end
+        end
+
+        embody ConvertError<Truth> for Number then
+            chant convert(self) -> Truth then
+                yield true
+            end
+        end
+    "#;
+
+    let result = analyze_source(source);
+    assert!(result.is_ok(), "Failed: {:?}", result);
+}
+
 // ============================================================================
 // Invalid trait implementations
 // ============================================================================
@@ -0,0 +1,101 @@
+//! Tests for tuple values and tuple patterns
+//!
+//! Covers the literal syntax, index access, `match` on tuples in the
+//! evaluator, the tuple type the analyzer gives them, and the bytecode
+//! instructions that build and take them apart.
+
+use glimmer_weave::bytecode::Instruction;
+use glimmer_weave::vm::VM;
+use glimmer_weave::{analyze, bytecode_compiler, bytecode_image, AstNode, Evaluator, Lexer, Parser, RuntimeError, SemanticError, Value};
+
+fn parse(source: &str) -> Result<Vec<AstNode>, String> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().map_err(|e| e.message)
+}
+
+fn eval(source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval(&parse(source).expect("Parse error"))
+}
+
+fn text(s: &str) -> Value {
+    Value::Text(s.to_string())
+}
+
+#[test]
+fn test_tuple_literals_and_index_access() {
+    assert_eq!(
+        eval(r#"(1, "a", true)"#).unwrap(),
+        Value::Tuple(vec![Value::Number(1.0), text("a"), Value::Truth(true)])
+    );
+    assert_eq!(eval(r#"bind t to (1, "a", true)
+t[1]"#).unwrap(), text("a"));
+    assert_eq!(eval("(7,)").unwrap(), Value::Tuple(vec![Value::Number(7.0)]));
+    // Without a comma parentheses only group
+    assert_eq!(eval("(7)").unwrap(), Value::Number(7.0));
+    assert_eq!(eval(r#"to_text((1, "a", true))"#).unwrap(), text("(1, a, true)"));
+    assert_eq!(eval("to_text((1,))").unwrap(), text("(1,)"));
+    assert!(matches!(eval("(1, 2)[2]"), Err(RuntimeError::IndexOutOfBounds { .. })));
+}
+
+#[test]
+fn test_tuples_cannot_be_changed() {
+    match eval("weave t as (1, 2)\nset t[0] to 5") {
+        Err(RuntimeError::Custom(message)) => assert!(message.contains("Tuples cannot be changed"), "{}", message),
+        other => panic!("expected an error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_tuple_patterns_in_match() {
+    let source = |subject: &str| {
+        format!(
+            "match {} with\n    when (0, y) then y\n    when (x, (a, b)) then x + a + b\n    when (_, _) then -1\n    otherwise then -2\nend",
+            subject
+        )
+    };
+    assert_eq!(eval(&source("(0, 5)")).unwrap(), Value::Number(5.0));
+    assert_eq!(eval(&source("(1, (2, 3))")).unwrap(), Value::Number(6.0));
+    assert_eq!(eval(&source("(1, 2)")).unwrap(), Value::Number(-1.0));
+    // A tuple of another length, or a list, matches no tuple pattern
+    assert_eq!(eval(&source("(1, 2, 3)")).unwrap(), Value::Number(-2.0));
+    assert_eq!(eval(&source("[0, 5]")).unwrap(), Value::Number(-2.0));
+}
+
+#[test]
+fn test_tuples_need_language_version_1_4() {
+    assert!(parse("speaks \"1.3\"\n(1, 2)").unwrap_err().contains("language version 1.4 is needed for tuples"));
+    assert!(parse("speaks \"1.3\"\n(1 + 2)").is_ok());
+}
+
+#[test]
+fn test_analyzer_gives_tuples_a_type() {
+    let errors = |source: &str| analyze(&parse(source).unwrap()).err().unwrap_or_default();
+    assert!(errors("bind t to (1, \"a\")\nbind n: Number to t[0]\nbind s: Text to t[1]").is_empty());
+    match errors("bind t to (1, \"a\")\nbind s: Text to t[0]").as_slice() {
+        [SemanticError::TypeError { expected, got, .. }] => assert_eq!((expected.as_str(), got.as_str()), ("Text", "Number")),
+        other => panic!("expected a type error, got {:?}", other),
+    }
+
+    match analyze(&parse("bind t to (1, 2)\nt[2]").unwrap()).unwrap_err().as_slice() {
+        [SemanticError::InvalidOperation { operation, operand_type, .. }] => {
+            assert_eq!(operation, "index 2");
+            assert_eq!(operand_type, "tuple of 2");
+        }
+        other => panic!("expected an invalid index, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_bytecode_builds_and_projects_tuples() {
+    let chunk = bytecode_compiler::compile(&parse("(10, 20, 30)[1]").unwrap()).unwrap();
+    assert!(chunk.instructions.iter().any(|instruction| matches!(instruction, Instruction::CreateTuple { count: 3, .. })));
+    assert_eq!(VM::new().execute(chunk.clone()).unwrap(), Value::Number(20.0));
+
+    let decoded = bytecode_image::decode(&bytecode_image::encode(&chunk)).unwrap();
+    assert_eq!(decoded.instructions, chunk.instructions);
+
+    let source = "match (1, 2) with\n    when (1, y) then y\n    otherwise then 0\nend";
+    let chunk = bytecode_compiler::compile(&parse(source).unwrap()).unwrap();
+    assert!(chunk.instructions.iter().any(|instruction| matches!(instruction, Instruction::IsTuple { count: 2, .. })));
+    assert!(chunk.instructions.iter().any(|instruction| matches!(instruction, Instruction::TupleGet { index: 1, .. })));
+}
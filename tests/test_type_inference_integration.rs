@@ -1,7 +1,7 @@
-/// Integration tests for Hindley-Milner type inference
-///
-/// These tests verify that type inference works end-to-end with the
-/// semantic analyzer, successfully inferring types without explicit annotations.
+//! Integration tests for Hindley-Milner type inference
+//!
+//! These tests verify that type inference works end-to-end with the
+//! semantic analyzer, successfully inferring types without explicit annotations.
 
 use glimmer_weave::{Lexer, Parser, SemanticAnalyzer};
 
@@ -0,0 +1,88 @@
+//! Tests for units of measure on number literals
+
+use glimmer_weave::bytecode_compiler::compile;
+use glimmer_weave::codegen::compile_to_asm;
+use glimmer_weave::semantic::{analyze, SemanticError};
+use glimmer_weave::vm::VM;
+use glimmer_weave::{run, AstNode, EvalOptions, Evaluator, Lexer, Parser, SemanticAnalyzer, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn eval(source: &str) -> Value {
+    Evaluator::new().eval(&parse(source)).unwrap()
+}
+
+/// The `got` of every semantic type error in `source`
+fn unit_errors(source: &str) -> Vec<String> {
+    analyze(&parse(source))
+        .err()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|error| match error {
+            SemanticError::TypeError { got, .. } => Some(got),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_measures_add_up_across_units() {
+    assert_eq!(eval("bind delay to 10 ms\nbind total to delay + 2 s\ntotal in ms"), Value::Number(2010.0));
+    assert_eq!(eval("4 KiB in B"), Value::Number(4096.0));
+    assert_eq!(eval("(1 GiB - 512 MiB) in MiB"), Value::Number(512.0));
+    assert_eq!(eval("90 s in min"), Value::Number(1.5));
+
+    // Scaling keeps the dimension; a ratio of one dimension is a plain Number
+    let source = "bind quantum to 10 ms * 3\nbind ratio to 1 s / 250 ms\n[quantum in ms, ratio + 1]";
+    assert_eq!(analyze(&parse(source)), Ok(()));
+    assert_eq!(eval(source), Value::List(vec![Value::Number(30.0), Value::Number(5.0)]));
+    assert_eq!(run(source, EvalOptions::new()).unwrap(), eval(source));
+}
+
+#[test]
+fn test_mixing_units_is_a_semantic_error() {
+    assert_eq!(unit_errors("bind x to 10 ms + 4 KiB"), ["Time Add Size"]);
+    assert_eq!(unit_errors("bind timeout to 5 s\nshould timeout greater than 30 then\n    1\nend"), ["Time Greater Number"]);
+    assert_eq!(unit_errors("bind x to 2 KiB * 2 KiB"), ["Size Mul Size"]);
+    assert_eq!(unit_errors("bind pages to 64 KiB in ms"), ["Size"]);
+    // A plain Number only becomes a measure through a unit literal
+    assert_eq!(unit_errors("bind x to 5 in ms"), ["Number"]);
+
+    // `Time` and `Size` annotate parameters and bindings
+    let wait = "chant wait(delay: Time) then\n    delay in ms\nend\n";
+    assert_eq!(analyze(&parse(&format!("{}wait(2 s)", wait))), Ok(()));
+    assert!(analyze(&parse(&format!("{}wait(2000)", wait))).is_err());
+    assert!(analyze(&parse("bind limit: Size to 30")).is_err());
+
+    assert!(run("bind x to 1 h + 1 B", EvalOptions::new()).is_err());
+}
+
+#[test]
+fn test_type_inference_tracks_dimensions() {
+    let infer = |source: &str| {
+        let mut analyzer = SemanticAnalyzer::new();
+        analyzer.enable_type_inference();
+        analyzer.infer_program_types(&parse(source))
+    };
+    assert_eq!(infer("bind a to 10 ms\nbind b to a + 1 s\nbind c to b in us"), Ok(()));
+    assert_eq!(infer("bind a to 10 ms\nbind b to a + 4 KiB"), Err("Type mismatch: cannot unify Time and Size".to_string()));
+    assert!(infer("bind a to 1 s\nbind b to a - 1").is_err());
+}
+
+#[test]
+fn test_backends_agree() {
+    let source = "(1 s + 500 ms) in ms";
+    assert_eq!(eval(source), Value::Number(1500.0));
+    assert_eq!(VM::new().execute(compile(&parse(source)).unwrap()).unwrap(), Value::Number(1500.0));
+
+    // Native code gets the base-unit integer
+    let asm = compile_to_asm(&parse("2 s")).unwrap();
+    assert!(asm.contains("$2000000000,"), "{}", asm);
+
+    // Unit names stay ordinary names when no number comes before them
+    assert_eq!(eval("bind ms to 3\nbind s to 4\nms + s"), Value::Number(7.0));
+    assert_eq!(eval("weave n as 0\nfor each s in [1, 2] then\n    set n to n + s\nend\nn"), Value::Number(3.0));
+}
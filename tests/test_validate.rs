@@ -0,0 +1,106 @@
+//! Tests for checking incoming Maps against form definitions
+
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn run(source: &str) -> Result<Value, RuntimeError> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().expect("parse failed");
+    Evaluator::new().eval(&ast)
+}
+
+const FORMS: &str = r#"
+    form Owner with
+        name as Text
+        uid as Number
+    end
+    form Job with
+        title as Text
+        owner as Owner
+        tags as List<Text>
+        retries as Maybe<Number>
+    end
+"#;
+
+/// Text of the Mishap `validate` returns for `payload`
+fn problem(payload: &str) -> String {
+    match run(&format!("{}\nvalidate({}, Job)", FORMS, payload)).unwrap() {
+        Value::Outcome { success: false, value } => match *value {
+            Value::Text(text) => text,
+            other => panic!("expected Text, got {:?}", other),
+        },
+        other => panic!("expected a Mishap, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_valid_payload_becomes_a_form_instance() {
+    let source = format!(
+        r#"{}
+        bind payload to {{ title: "backup", owner: {{ name: "ada", uid: 7 }}, tags: ["nightly"], retries: 3 }}
+        bind job to expect_triumph(validate(payload, Job), "valid")
+        [type_of(job), type_of(job.owner), job.owner.uid, job.retries, job.tags]
+        "#,
+        FORMS
+    );
+    assert_eq!(
+        run(&source),
+        Ok(Value::List(vec![
+            Value::Text("Job".to_string()),
+            Value::Text("Owner".to_string()),
+            Value::Number(7.0),
+            Value::Maybe { present: true, value: Some(Box::new(Value::Number(3.0))) },
+            Value::List(vec![Value::Text("nightly".to_string())]),
+        ]))
+    );
+}
+
+#[test]
+fn test_optional_fields_may_be_missing() {
+    let source = format!(
+        r#"{}
+        bind job to expect_triumph(validate({{ title: "t", owner: {{ name: "a", uid: 1 }}, tags: [] }}, Job), "valid")
+        job.retries
+        "#,
+        FORMS
+    );
+    assert_eq!(run(&source), Ok(Value::Maybe { present: false, value: None }));
+}
+
+#[test]
+fn test_problems_name_their_path() {
+    let owner = r#"owner: { name: "ada", uid: 7 }"#;
+    assert_eq!(problem("42"), "Job: expected Job, got Number");
+    assert_eq!(problem(&format!("{{ {}, tags: [] }}", owner)), "Job.title: missing field");
+    assert_eq!(
+        problem(r#"{ title: "t", owner: { name: "ada", uid: "7" }, tags: [] }"#),
+        "Job.owner.uid: expected Number, got Text"
+    );
+    assert_eq!(
+        problem(&format!(r#"{{ title: "t", {}, tags: ["a", "b", 3] }}"#, owner)),
+        "Job.tags[2]: expected Text, got Number"
+    );
+    assert_eq!(
+        problem(&format!(r#"{{ title: "t", {}, tags: [], retries: "many" }}"#, owner)),
+        "Job.retries: expected Number, got Text"
+    );
+    assert_eq!(
+        problem(&format!(r#"{{ title: "t", {}, tags: [], admin: true }}"#, owner)),
+        "Job.admin: not a field of Job"
+    );
+}
+
+#[test]
+fn test_validates_decoded_messages() {
+    let source = format!(
+        r#"{}
+        bind wire to value_encode({{ name: "ada", uid: 7 }})
+        validate(value_decode(wire), Owner)
+        "#,
+        FORMS
+    );
+    let Value::Outcome { success: true, value } = run(&source).unwrap() else { panic!("expected a Triumph") };
+    assert!(matches!(*value, Value::StructInstance { ref struct_name, .. } if struct_name == "Owner"));
+
+    let not_a_form = run("validate({}, 5)");
+    assert!(matches!(not_a_form, Err(RuntimeError::TypeError { ref expected, .. }) if expected == "form definition"));
+}
@@ -0,0 +1,70 @@
+//! Tests for exchanging values with the host through their binary encoding
+
+use glimmer_weave::value_codec::{decode, encode};
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn run(source: &str) -> Result<Value, RuntimeError> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().expect("parse failed");
+    Evaluator::new().eval(&ast)
+}
+
+fn bytes(value: &Value) -> String {
+    let bytes: Vec<String> = encode(value).unwrap().iter().map(|byte| byte.to_string()).collect();
+    format!("[{}]", bytes.join(", "))
+}
+
+#[test]
+fn test_scripts_round_trip_structured_state() {
+    let source = r#"
+        form Job with
+            name as Text
+            tries as Number
+        end
+        variant Status then
+            Queued,
+            Failed(reason: Text)
+        end
+        bind state to {
+            jobs: [Job { name: "backup", tries: 2 }],
+            status: Failed("disk full"),
+            latest: Present(1.25),
+            ok: Triumph(true)
+        }
+        bind decoded to value_decode(value_encode(state))
+        [decoded is state, Convert.decode(Convert.encode(Queued)) is Queued]
+    "#;
+    assert_eq!(run(source), Ok(Value::List(vec![Value::Truth(true), Value::Truth(true)])));
+}
+
+#[test]
+fn test_host_reads_and_writes_script_values() {
+    // The host decodes what a script encoded...
+    let encoded = run(r#"value_encode(["ready", 3, nothing])"#).unwrap();
+    let Value::List(items) = encoded else { panic!("expected a byte list") };
+    let raw: Vec<u8> = items.iter().map(|item| match item {
+        Value::Number(n) => *n as u8,
+        other => panic!("expected a byte, got {:?}", other),
+    }).collect();
+    assert_eq!(
+        decode(&raw),
+        Ok(Value::List(vec![Value::Text("ready".to_string()), Value::Number(3.0), Value::Nothing]))
+    );
+
+    // ...and a script decodes what the host encoded
+    let message = Value::Outcome { success: false, value: Box::new(Value::Text("denied".to_string())) };
+    let source = format!("bind reply to value_decode({})\nexpect_mishap(reply, \"wanted a Mishap\")", bytes(&message));
+    assert_eq!(run(&source), Ok(Value::Text("denied".to_string())));
+}
+
+#[test]
+fn test_encoding_errors_are_runtime_errors() {
+    let chant = run("chant f() then\n    yield 1\nend\nvalue_encode([f])");
+    assert!(matches!(chant, Err(RuntimeError::Custom(ref message)) if message == "value_encode: Chant values cannot be encoded"));
+
+    let truncated = run("value_decode([71, 87, 86, 1, 5, 9])");
+    assert!(matches!(truncated, Err(RuntimeError::Custom(ref message)) if message == "value_decode: encoded value is truncated"));
+
+    let not_bytes = run("value_decode([71, 87, 86, 1, 256])");
+    assert!(matches!(not_bytes, Err(RuntimeError::TypeError { .. })));
+}
@@ -0,0 +1,51 @@
+//! Tests for diffing and patching values from scripts
+
+use glimmer_weave::{Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn run(source: &str) -> Result<Value, RuntimeError> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    let ast = Parser::new(tokens).parse().expect("parse failed");
+    Evaluator::new().eval(&ast)
+}
+
+#[test]
+fn test_patch_applies_the_diff() {
+    let source = r#"
+        form Player with
+            name as Text
+            score as Number
+        end
+        bind old_state to { round: 1, players: [Player { name: "ada", score: 3 }], log: ["start"] }
+        bind new_state to { round: 2, players: [Player { name: "ada", score: 5 }, Player { name: "bo", score: 0 }], log: ["start"] }
+        bind changes to value_diff(old_state, new_state)
+        [list_length(changes), value_patch(old_state, changes) is new_state]
+    "#;
+    assert_eq!(run(source), Ok(Value::List(vec![Value::Number(3.0), Value::Truth(true)])));
+}
+
+#[test]
+fn test_changes_are_plain_maps() {
+    let source = r#"
+        bind changes to value_diff({ volume: 3 }, { volume: 4 })
+        bind change to changes[0]
+        [change.op, change.path, change.value]
+    "#;
+    let text = |s: &str| Value::Text(s.to_string());
+    assert_eq!(
+        run(source),
+        Ok(Value::List(vec![text("set"), Value::List(vec![text("volume")]), Value::Number(4.0)]))
+    );
+
+    // Scripts can write changes by hand too
+    let source = r#"value_patch([1, 2, 3], [{ op: "truncate", path: [], length: 1 }, { op: "set", path: [1], value: 7 }])"#;
+    assert_eq!(run(source), Ok(Value::List(vec![Value::Number(1.0), Value::Number(7.0)])));
+}
+
+#[test]
+fn test_bad_changes_are_runtime_errors() {
+    let malformed = run(r#"value_patch({}, [{ op: "rename", path: [] }])"#);
+    assert!(matches!(malformed, Err(RuntimeError::Custom(ref message)) if message == "value_patch: change 0 is not a change map"));
+
+    let missing = run(r#"value_patch({ a: 1 }, [{ op: "remove", path: ["b"] }])"#);
+    assert!(matches!(missing, Err(RuntimeError::Custom(ref message)) if message == "value_patch: path of change 0 does not exist"));
+}
@@ -1,5 +1,5 @@
-/// Tests for variadic function syntax
-/// Implements glimmer-weave-1uh: Add variadic function syntax
+//! Tests for variadic function syntax
+//! Implements glimmer-weave-1uh: Add variadic function syntax
 
 use glimmer_weave::*;
 
@@ -168,9 +168,9 @@ fn test_variadic_with_multiple_regular_params() {
     match result {
         eval::Value::List(items) => {
             assert_eq!(items.len(), 5);
-            for i in 0..5 {
-                match items[i] {
-                    eval::Value::Number(n) => assert_eq!(n, (i + 1) as f64),
+            for (i, item) in items.iter().enumerate() {
+                match item {
+                    eval::Value::Number(n) => assert_eq!(*n, (i + 1) as f64),
                     _ => panic!("Expected Number at index {}", i),
                 }
             }
@@ -351,13 +351,9 @@ fn test_variadic_recursive_sum() {
     "#;
     // Note: This test will fail until we implement spread operator in function calls
     // For now, we just test that the function can be defined
-    let result = run_program(source);
     // This might fail due to spread operator in call, which is a separate feature
     // Just verify it parses correctly for now
-    match result {
-        Ok(_) => {}, // Great if it works
-        Err(_) => {}, // Expected if spread in calls not implemented yet
-    }
+    let _ = run_program(source);
 }
 
 // ============================================================================
@@ -428,9 +424,6 @@ fn test_variadic_function_passed_as_argument() {
         apply_to_list(sum, [1, 2, 3, 4, 5])
     "#;
     // This requires spread operator in function calls, which is separate feature
-    let result = run_program(source);
-    match result {
-        Ok(_) => {}, // Great if it works
-        Err(_) => {}, // Expected if spread in calls not implemented yet
-    }
+    // Ok if it works, Err if spread in calls is not implemented yet
+    let _ = run_program(source);
 }
@@ -0,0 +1,117 @@
+//! Tests for explicit variant discriminants and the variant_index/from_index
+//! conversion builtins
+
+use glimmer_weave::semantic::{analyze, SemanticError};
+use glimmer_weave::{AstNode, Evaluator, Lexer, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn run(source: &str) -> Result<Value, RuntimeError> {
+    Evaluator::new().eval(&parse(source))
+}
+
+fn numbers(values: &[f64]) -> Value {
+    Value::List(values.iter().map(|n| Value::Number(*n)).collect())
+}
+
+const STATUS: &str = r#"
+    variant Status with
+        Ok is 0,
+        Busy is 16,
+        Retry,
+        Failed is -1
+    end
+"#;
+
+#[test]
+fn test_discriminants_parse() {
+    match &parse(STATUS)[0] {
+        AstNode::VariantDef { variants, .. } => {
+            let discriminants: Vec<Option<i64>> = variants.iter().map(|case| case.discriminant).collect();
+            assert_eq!(discriminants, [Some(0), Some(16), None, Some(-1)]);
+            assert_eq!(glimmer_weave::ast::variant_discriminants(variants), [0, 16, 17, -1]);
+        }
+        other => panic!("expected a variant, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_variant_index() {
+    let source = format!("{}\n[variant_index(Ok), variant_index(Busy), Variant.index(Retry), variant_index(Failed)]", STATUS);
+    assert_eq!(run(&source).unwrap(), numbers(&[0.0, 16.0, 17.0, -1.0]));
+
+    // Cases without discriminants count from zero
+    let source = r#"
+        variant Move then
+            Stop,
+            Go(speed: Number)
+        end
+        [variant_index(Stop), variant_index(Go(3)), variant_index(Triumph(1)), variant_index(Absent)]
+    "#;
+    assert_eq!(run(source).unwrap(), numbers(&[0.0, 1.0, 1.0, 0.0]));
+}
+
+#[test]
+fn test_from_index() {
+    let source = format!(
+        r#"{}
+        from_index(Status, 17) is Present(Retry)
+        "#,
+        STATUS
+    );
+    assert_eq!(run(&source).unwrap(), Value::Truth(true));
+
+    let source = format!("{}\nVariant.from_index(Status, 5)", STATUS);
+    assert_eq!(run(&source).unwrap(), Value::Maybe { present: false, value: None });
+}
+
+#[test]
+fn test_from_index_rejects_data_cases() {
+    let source = r#"
+        variant Shape with
+            Dot,
+            Circle(radius: Number)
+        end
+        from_index(Shape, 1)
+    "#;
+    assert!(matches!(run(source), Err(RuntimeError::Custom(message)) if message.contains("Shape.Circle")));
+    assert!(matches!(run("from_index(1, 1)"), Err(RuntimeError::TypeError { .. })));
+}
+
+#[test]
+fn test_derived_order_follows_discriminants() {
+    let source = r#"
+        variant Priority with
+            High is 2,
+            Low is 0
+        end
+        embody Ordered for Priority then
+        end
+        [Low less than High, High less than Low]
+    "#;
+    assert_eq!(run(source).unwrap(), Value::List(vec![Value::Truth(true), Value::Truth(false)]));
+}
+
+#[test]
+fn test_duplicate_discriminants_are_rejected() {
+    let source = r#"
+        variant Clash with
+            First is 1,
+            Second is 1
+        end
+    "#;
+    let errors = analyze(&parse(source)).unwrap_err();
+    assert!(errors.iter().any(|error| matches!(
+        error,
+        SemanticError::InvalidOperation { operation, operand_type } if operation == "discriminant 1 for Second" && operand_type == "Clash"
+    )));
+}
+
+#[test]
+fn test_discriminant_must_be_an_integer() {
+    let tokens = Lexer::new("variant Bad with One is 1.5 end").tokenize_positioned();
+    assert!(Parser::new(tokens).parse().is_err());
+}
@@ -0,0 +1,116 @@
+//! Tests for the general enum layout: multi-field variants in the VM and
+//! the field slots shared with native codegen
+
+use glimmer_weave::bytecode::{BytecodeChunk, Constant, Instruction};
+use glimmer_weave::bytecode_compiler::compile;
+use glimmer_weave::bytecode_image::{decode, encode};
+use glimmer_weave::vm::VM;
+use glimmer_weave::{AstNode, Lexer, Parser, Value};
+
+fn parse(source: &str) -> Vec<AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("parse failed")
+}
+
+fn run_vm(source: &str) -> Value {
+    VM::new().execute(compile(&parse(source)).unwrap()).unwrap()
+}
+
+const SHAPE: &str = r#"
+    variant Shape then
+        Dot,
+        Rect(w: Number, h: Number),
+        Box3(w: Number, h: Number, d: Number)
+    end
+"#;
+
+#[test]
+fn test_vm_builds_variants() {
+    let source = format!("{}\nRect(6, 7)", SHAPE);
+    assert_eq!(
+        run_vm(&source),
+        Value::VariantValue {
+            enum_name: "Shape".to_string(),
+            variant_name: "Rect".to_string(),
+            fields: vec![Value::Number(6.0), Value::Number(7.0)],
+            type_args: vec![],
+        }
+    );
+}
+
+#[test]
+fn test_vm_matches_cases() {
+    let kind = |shape: &str| {
+        format!(
+            r#"{}
+            weave kind as 0
+            match {} with
+                when Dot then
+                    set kind to 1
+                when Rect(_, _) then
+                    set kind to 2
+                when Box3(_) then
+                    set kind to 3
+            end
+            kind
+            "#,
+            SHAPE, shape
+        )
+    };
+    assert_eq!(run_vm(&kind("Dot")), Value::Number(1.0));
+    assert_eq!(run_vm(&kind("Rect(1, 2)")), Value::Number(2.0));
+    assert_eq!(run_vm(&kind("Box3(1, 2, 3)")), Value::Number(3.0));
+}
+
+#[test]
+fn test_vm_extracts_field_slots() {
+    let mut chunk = BytecodeChunk::new("fields".to_string());
+    let shape = chunk.add_constant(Constant::Text("Shape".to_string()));
+    let rect = chunk.add_constant(Constant::Text("Rect".to_string()));
+    let six = chunk.add_constant(Constant::Number(6.0));
+    let seven = chunk.add_constant(Constant::Number(7.0));
+    chunk.emit(Instruction::LoadConst { dest: 1, constant_id: six }, 1);
+    chunk.emit(Instruction::LoadConst { dest: 2, constant_id: seven }, 1);
+    chunk.emit(Instruction::CreateVariant { dest: 3, enum_id: shape, case_id: rect, field_start: 1, field_count: 2 }, 1);
+    chunk.emit(Instruction::ExtractField { dest: 4, value: 3, index: 1 }, 1);
+
+    // Triumph keeps its value in field slot 0, as in the native layout
+    chunk.emit(Instruction::CreateTriumph { dest: 5, value: 4 }, 1);
+    chunk.emit(Instruction::ExtractField { dest: 0, value: 5, index: 0 }, 1);
+    chunk.emit(Instruction::Halt, 1);
+    assert_eq!(VM::new().execute(chunk.clone()).unwrap(), Value::Number(7.0));
+
+    // Slots past the case's fields are an error
+    chunk.instructions[5] = Instruction::ExtractField { dest: 0, value: 5, index: 1 };
+    assert!(VM::new().execute(chunk).is_err());
+}
+
+#[test]
+fn test_variant_instructions_survive_images() {
+    let chunk = compile(&parse(&format!("{}\nRect(1, 2)", SHAPE))).unwrap();
+    assert!(chunk.instructions.iter().any(|i| matches!(i, Instruction::CreateVariant { field_count: 2, .. })));
+
+    let decoded = decode(&encode(&chunk)).unwrap();
+    assert_eq!(decoded.instructions, chunk.instructions);
+}
+
+#[test]
+fn test_constructor_arity_is_checked() {
+    assert!(compile(&parse(&format!("{}\nRect(1)", SHAPE))).is_err());
+}
+
+#[test]
+fn test_native_layout_puts_tag_first() {
+    let asm = glimmer_weave::codegen::compile_to_asm(&parse(
+        r#"
+        chant wrap() then
+            yield Triumph(42)
+        end
+        "#,
+    ))
+    .unwrap();
+
+    // Tag at offset 0 of the 16-byte value, the field after it
+    assert!(asm.contains("movq $1, -16(%rbp)"));
+    assert!(asm.contains("movq %rax, -8(%rbp)"));
+}
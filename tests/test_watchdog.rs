@@ -0,0 +1,87 @@
+//! Tests for watchdog heartbeats and missed-heartbeat handling
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use glimmer_weave::session::Session;
+use glimmer_weave::watchdog::{MissedHeartbeat, WatchdogAction};
+use glimmer_weave::{Evaluator, Lexer, ModuleResolver, Parser, RuntimeError, Value};
+
+fn parse(source: &str) -> Vec<glimmer_weave::AstNode> {
+    let tokens = Lexer::new(source).tokenize_positioned();
+    Parser::new(tokens).parse().expect("Parse error")
+}
+
+/// A clock that advances one tick every time it is read
+fn ticking_clock() -> impl Fn() -> u64 {
+    let ticks = Arc::new(AtomicU64::new(0));
+    move || ticks.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+/// Promises a heartbeat every 20 ticks, then loops `n` times, beating only
+/// if `beat` is true
+fn service(n: u32, beat: bool) -> String {
+    format!(
+        "watchdog(20, \"netd\")\nweave i as 0\nwhilst i less than {} then\n    set i to i + 1\n    should {} then\n        heartbeat()\n    end\nend\ni",
+        n, beat
+    )
+}
+
+fn evaluator() -> Evaluator {
+    let mut evaluator = Evaluator::new();
+    evaluator.set_clock(Box::new(ticking_clock()));
+    evaluator
+}
+
+#[test]
+fn test_heartbeats_keep_the_script_running() {
+    let mut evaluator = evaluator();
+    assert_eq!(evaluator.eval(&parse(&service(200, true))), Ok(Value::Number(200.0)));
+    assert_eq!(evaluator.watchdog().misses(), 0);
+    assert_eq!(evaluator.watchdog().interval(), Some(20));
+}
+
+#[test]
+fn test_missed_heartbeat_cancels_by_default() {
+    let mut evaluator = evaluator();
+    let source = format!("weave cleaned as false\ndefer\n    set cleaned to true\nend\n{}", service(200, false));
+    assert_eq!(evaluator.eval(&parse(&source)), Err(RuntimeError::Cancelled));
+    assert_eq!(evaluator.watchdog().misses(), 1);
+    assert_eq!(evaluator.watchdog().interval(), None);
+    assert_eq!(evaluator.eval(&parse("cleaned")), Ok(Value::Truth(true)));
+}
+
+#[test]
+fn test_session_host_is_told_about_every_miss() {
+    let reports: Arc<Mutex<Vec<MissedHeartbeat>>> = Arc::default();
+    let seen = Arc::clone(&reports);
+    let mut session = Session::new(ModuleResolver::new("/app".to_string(), "/std".to_string()));
+    session.set_watchdog_host(Box::new(move |missed: &MissedHeartbeat| {
+        seen.lock().unwrap().push(missed.clone());
+        if missed.misses < 3 {
+            WatchdogAction::Continue
+        } else {
+            WatchdogAction::Cancel
+        }
+    }));
+
+    let mut evaluator = session.evaluator();
+    evaluator.set_clock(Box::new(ticking_clock()));
+    assert_eq!(evaluator.eval(&parse(&service(1000, false))), Err(RuntimeError::Cancelled));
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 3);
+    assert!(reports.iter().all(|missed| missed.name.as_deref() == Some("netd") && missed.interval == 20));
+    assert_eq!(reports[1].last_beat, reports[0].last_beat);
+    assert!(reports[1].now >= reports[0].now + 20);
+}
+
+#[test]
+fn test_watchdog_arguments() {
+    let mut evaluator = evaluator();
+    assert!(matches!(evaluator.eval(&parse("watchdog(\"soon\")")), Err(RuntimeError::TypeError { .. })));
+    assert!(matches!(evaluator.eval(&parse("watchdog(0)")), Err(RuntimeError::TypeError { .. })));
+    assert!(matches!(evaluator.eval(&parse("watchdog()")), Err(RuntimeError::ArityMismatch { .. })));
+    assert_eq!(evaluator.eval(&parse("heartbeat()")), Ok(Value::Nothing));
+    assert_eq!(evaluator.watchdog().interval(), None);
+}